@@ -17,6 +17,7 @@ pub use parser::parse_patch;
 use similar::TextDiff;
 use thiserror::Error;
 use tree_sitter::LanguageError;
+use tree_sitter::Node;
 use tree_sitter::Parser;
 use tree_sitter_bash::LANGUAGE as BASH;
 
@@ -84,6 +85,9 @@ pub enum MaybeApplyPatch {
 pub struct ApplyPatchArgs {
     pub patch: String,
     pub hunks: Vec<Hunk>,
+    /// Directory the patch should be applied in, relative to the cwd of the
+    /// invocation, when `apply_patch` was preceded by `cd <path> &&`.
+    pub workdir: Option<PathBuf>,
 }
 
 pub fn maybe_parse_apply_patch(argv: &[String]) -> MaybeApplyPatch {
@@ -95,15 +99,14 @@ pub fn maybe_parse_apply_patch(argv: &[String]) -> MaybeApplyPatch {
         [bash, flag, script]
             if bash == "bash"
                 && flag == "-lc"
-                && APPLY_PATCH_COMMANDS
-                    .iter()
-                    .any(|cmd| script.trim_start().starts_with(cmd)) =>
+                && APPLY_PATCH_COMMANDS.iter().any(|cmd| script.contains(cmd)) =>
         {
             match extract_heredoc_body_from_apply_patch_command(script) {
-                Ok(body) => match parse_patch(&body) {
-                    Ok(source) => MaybeApplyPatch::Body(source),
+                Ok(Some(ApplyPatchHeredoc { body, workdir })) => match parse_patch(&body) {
+                    Ok(source) => MaybeApplyPatch::Body(ApplyPatchArgs { workdir, ..source }),
                     Err(e) => MaybeApplyPatch::PatchParseError(e),
                 },
+                Ok(None) => MaybeApplyPatch::NotApplyPatch,
                 Err(e) => MaybeApplyPatch::ShellParseError(e),
             }
         }
@@ -165,6 +168,16 @@ impl ApplyPatchAction {
         &self.changes
     }
 
+    /// Drops the changes to files not in `paths`, rewriting `patch` so that
+    /// applying it only touches the remaining files.
+    pub fn retain_paths(&mut self, paths: &[PathBuf]) -> Result<(), ParseError> {
+        let cwd = &self.cwd;
+        self.patch =
+            parser::retain_hunks(&self.patch, |hunk| paths.contains(&hunk.resolve_path(cwd)))?;
+        self.changes.retain(|path, _| paths.contains(path));
+        Ok(())
+    }
+
     /// Should be used exclusively for testing. (Not worth the overhead of
     /// creating a feature flag for this.)
     pub fn new_add_for_test(path: &Path, content: String) -> Self {
@@ -201,7 +214,15 @@ impl ApplyPatchAction {
 /// patch.
 pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApplyPatchVerified {
     match maybe_parse_apply_patch(argv) {
-        MaybeApplyPatch::Body(ApplyPatchArgs { patch, hunks }) => {
+        MaybeApplyPatch::Body(ApplyPatchArgs {
+            patch,
+            hunks,
+            workdir,
+        }) => {
+            let cwd = &match workdir {
+                Some(workdir) => cwd.join(workdir),
+                None => cwd.to_path_buf(),
+            };
             let mut changes = HashMap::new();
             for hunk in hunks {
                 let path = hunk.resolve_path(cwd);
@@ -247,6 +268,20 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
     }
 }
 
+/// Maximum number of enclosing subshells, brace groups and `&&` lists that
+/// will be traversed when resolving a nested `apply_patch` invocation. Scripts
+/// nested more deeply than this are rejected rather than analyzed.
+const MAX_APPLY_PATCH_NESTING_DEPTH: usize = 8;
+
+/// The heredoc passed to an `apply_patch` invocation found in a `bash -lc`
+/// script, along with the directory it would run in relative to the cwd of
+/// the script.
+#[derive(Debug, PartialEq)]
+struct ApplyPatchHeredoc {
+    body: String,
+    workdir: Option<PathBuf>,
+}
+
 /// Attempts to extract a heredoc_body object from a string bash command like:
 /// Optimistically
 ///
@@ -254,6 +289,18 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
 /// bash -lc 'apply_patch <<EOF\n***Begin Patch\n...EOF'
 /// ```
 ///
+/// The invocation may also be nested inside subshells, brace groups, or a
+/// `&&` chain whose preceding commands are all `cd <literal path>`, e.g.:
+///
+/// ```bash
+/// bash -lc '(cd src && apply_patch <<EOF\n***Begin Patch\n...EOF\n)'
+/// ```
+///
+/// Any other construct around the invocation (pipelines, `||`, loops, extra
+/// statements, trailing commands) makes it impossible to tell what the script
+/// would do besides applying the patch, so it is rejected with an error
+/// describing why.
+///
 /// # Arguments
 ///
 /// * `src` - A string slice that holds the full command
@@ -262,19 +309,13 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
 ///
 /// This function returns a `Result` which is:
 ///
-/// * `Ok(String)` - The heredoc body if the extraction is successful.
-/// * `Err(anyhow::Error)` - An error if the extraction fails.
+/// * `Ok(Some(ApplyPatchHeredoc))` - The heredoc body if the extraction is successful.
+/// * `Ok(None)` - The script does not invoke `apply_patch` at all.
+/// * `Err(ExtractHeredocError)` - An error if the extraction fails.
 ///
 fn extract_heredoc_body_from_apply_patch_command(
     src: &str,
-) -> std::result::Result<String, ExtractHeredocError> {
-    if !APPLY_PATCH_COMMANDS
-        .iter()
-        .any(|cmd| src.trim_start().starts_with(cmd))
-    {
-        return Err(ExtractHeredocError::CommandDidNotStartWithApplyPatch);
-    }
-
+) -> std::result::Result<Option<ApplyPatchHeredoc>, ExtractHeredocError> {
     let lang = BASH.into();
     let mut parser = Parser::new();
     parser
@@ -285,15 +326,139 @@ fn extract_heredoc_body_from_apply_patch_command(
         .ok_or(ExtractHeredocError::FailedToParsePatchIntoAst)?;
 
     let bytes = src.as_bytes();
-    let mut c = tree.root_node().walk();
+    let invocations = find_apply_patch_commands(tree.root_node(), bytes);
+    let command = match invocations.as_slice() {
+        [] => return Ok(None),
+        [command] => *command,
+        _ => {
+            return Err(ExtractHeredocError::MultipleApplyPatchInvocations(
+                invocations.len(),
+            ));
+        }
+    };
+
+    let mut heredoc_body: Option<Node> = None;
+    let mut cd_targets: Vec<PathBuf> = Vec::new();
+    let mut depth = 0;
+    let mut child = command;
+    while let Some(parent) = child.parent() {
+        match parent.kind() {
+            "redirected_statement" => {
+                let mut cursor = parent.walk();
+                let redirects: Vec<Node> = parent
+                    .children_by_field_name("redirect", &mut cursor)
+                    .collect();
+                let [redirect] = redirects.as_slice() else {
+                    return Err(ambiguous("apply_patch must be given exactly one heredoc"));
+                };
+                if redirect.kind() != "heredoc_redirect" {
+                    return Err(ambiguous("apply_patch input must come from a heredoc"));
+                }
+                // Anything after the heredoc delimiter on the same line
+                // (`&& ...`, `| ...`) is parsed as part of the redirect.
+                let parts = named_children(*redirect);
+                if parts.iter().any(|node| {
+                    !matches!(
+                        node.kind(),
+                        "heredoc_start" | "heredoc_body" | "heredoc_end"
+                    )
+                }) {
+                    return Err(ambiguous(
+                        "commands chained after the apply_patch heredoc are not supported",
+                    ));
+                }
+                if heredoc_body.is_none() {
+                    heredoc_body = parts.into_iter().find(|node| node.kind() == "heredoc_body");
+                }
+            }
+            "list" => {
+                depth += 1;
+                let operands = named_children(parent);
+                let is_and_list = (0..parent.child_count())
+                    .filter_map(|i| parent.child(i))
+                    .any(|node| node.kind() == "&&");
+                if !is_and_list {
+                    return Err(ambiguous(
+                        "apply_patch may only be chained with `&&` after `cd` commands",
+                    ));
+                }
+                match operands.as_slice() {
+                    [left, right] if right.id() == child.id() => {
+                        // Operands are visited innermost first, so earlier
+                        // `cd` commands are prepended.
+                        let mut targets = collect_cd_targets(*left, bytes)?;
+                        targets.append(&mut cd_targets);
+                        cd_targets = targets;
+                    }
+                    _ => {
+                        return Err(ambiguous(
+                            "apply_patch must be the last command in a `&&` chain",
+                        ));
+                    }
+                }
+            }
+            "subshell" | "compound_statement" | "program" => {
+                if parent.kind() != "program" {
+                    depth += 1;
+                }
+                let statements: Vec<Node> = named_children(parent)
+                    .into_iter()
+                    .filter(|node| node.kind() != "comment")
+                    .collect();
+                if statements.len() != 1 {
+                    return Err(ambiguous(
+                        "apply_patch must be the only statement in the script",
+                    ));
+                }
+            }
+            kind => {
+                return Err(ambiguous(&format!(
+                    "apply_patch cannot be used inside a `{kind}`"
+                )));
+            }
+        }
+
+        if depth > MAX_APPLY_PATCH_NESTING_DEPTH {
+            return Err(ExtractHeredocError::NestingTooDeep(depth));
+        }
+        child = parent;
+    }
+
+    let heredoc_body = heredoc_body.ok_or(ExtractHeredocError::FailedToFindHeredocBody)?;
+    let text = heredoc_body
+        .utf8_text(bytes)
+        .map_err(ExtractHeredocError::HeredocNotUtf8)?;
+    let workdir = cd_targets
+        .into_iter()
+        .reduce(|workdir, target| workdir.join(target));
+    Ok(Some(ApplyPatchHeredoc {
+        body: text.trim_end_matches('\n').to_owned(),
+        workdir,
+    }))
+}
+
+fn ambiguous(reason: &str) -> ExtractHeredocError {
+    ExtractHeredocError::AmbiguousApplyPatchInvocation(reason.to_string())
+}
+
+fn named_children(node: Node) -> Vec<Node> {
+    let mut cursor = node.walk();
+    node.named_children(&mut cursor).collect()
+}
 
+/// Returns every `command` node in the tree whose name is one of
+/// [`APPLY_PATCH_COMMANDS`].
+fn find_apply_patch_commands<'tree>(root: Node<'tree>, bytes: &[u8]) -> Vec<Node<'tree>> {
+    let mut commands = Vec::new();
+    let mut c = root.walk();
     loop {
         let node = c.node();
-        if node.kind() == "heredoc_body" {
-            let text = node
-                .utf8_text(bytes)
-                .map_err(ExtractHeredocError::HeredocNotUtf8)?;
-            return Ok(text.trim_end_matches('\n').to_owned());
+        if node.kind() == "command"
+            && let Some(name) = node.child_by_field_name("name")
+            && let Ok(name) = name.utf8_text(bytes)
+            && APPLY_PATCH_COMMANDS.contains(&name)
+        {
+            commands.push(node);
         }
 
         if c.goto_first_child() {
@@ -301,19 +466,118 @@ fn extract_heredoc_body_from_apply_patch_command(
         }
         while !c.goto_next_sibling() {
             if !c.goto_parent() {
-                return Err(ExtractHeredocError::FailedToFindHeredocBody);
+                return commands;
             }
         }
     }
 }
 
-#[derive(Debug, PartialEq)]
+/// Returns the directories changed into by `node`, which must be a
+/// `cd <literal path>` command or an `&&` list of them.
+fn collect_cd_targets(node: Node, bytes: &[u8]) -> Result<Vec<PathBuf>, ExtractHeredocError> {
+    match node.kind() {
+        "list" => {
+            let is_and_list = (0..node.child_count())
+                .filter_map(|i| node.child(i))
+                .any(|child| child.kind() == "&&");
+            if !is_and_list {
+                return Err(ambiguous(
+                    "apply_patch may only be chained with `&&` after `cd` commands",
+                ));
+            }
+            let mut targets = Vec::new();
+            for operand in named_children(node) {
+                targets.extend(collect_cd_targets(operand, bytes)?);
+            }
+            Ok(targets)
+        }
+        "command" => {
+            let name = node
+                .child_by_field_name("name")
+                .and_then(|name| name.utf8_text(bytes).ok());
+            let mut cursor = node.walk();
+            let arguments: Vec<Node> = node
+                .children_by_field_name("argument", &mut cursor)
+                .collect();
+            match (name, arguments.as_slice()) {
+                // `cd -` and options depend on shell state, and an empty
+                // target leaves the directory unchanged in some shells.
+                (Some("cd"), [argument]) => literal_word(*argument, bytes)
+                    .filter(|target| !target.is_empty() && !target.starts_with('-'))
+                    .map(|target| vec![PathBuf::from(target)])
+                    .ok_or_else(|| ambiguous("`cd` before apply_patch must use a literal path")),
+                _ => Err(ambiguous(
+                    "only `cd <path>` may precede apply_patch in a `&&` chain",
+                )),
+            }
+        }
+        _ => Err(ambiguous(
+            "only `cd <path>` may precede apply_patch in a `&&` chain",
+        )),
+    }
+}
+
+/// Returns the value of a word that contains no expansions.
+fn literal_word(node: Node, bytes: &[u8]) -> Option<String> {
+    let text = node.utf8_text(bytes).ok()?;
+    match node.kind() {
+        "word" if !text.contains(['$', '`', '*', '?', '~', '\\']) => Some(text.to_string()),
+        "raw_string" => text
+            .strip_prefix('\'')
+            .and_then(|text| text.strip_suffix('\''))
+            .map(str::to_string),
+        "string"
+            if named_children(node)
+                .iter()
+                .all(|child| child.kind() == "string_content") =>
+        {
+            text.strip_prefix('"')
+                .and_then(|text| text.strip_suffix('"'))
+                .map(str::to_string)
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, Error, PartialEq)]
 pub enum ExtractHeredocError {
-    CommandDidNotStartWithApplyPatch,
+    #[error("failed to load bash grammar: {0}")]
     FailedToLoadBashGrammar(LanguageError),
+    #[error("apply_patch heredoc is not valid UTF-8: {0}")]
     HeredocNotUtf8(Utf8Error),
+    #[error("failed to parse shell script")]
     FailedToParsePatchIntoAst,
+    #[error("apply_patch was not given a heredoc")]
     FailedToFindHeredocBody,
+    /// The script invokes `apply_patch` more than once.
+    #[error("found {0} apply_patch invocations in one script; apply each patch in a separate call")]
+    MultipleApplyPatchInvocations(usize),
+    /// `apply_patch` is nested more deeply than
+    /// `MAX_APPLY_PATCH_NESTING_DEPTH` subshells, brace groups or lists.
+    #[error(
+        "apply_patch is nested {0} levels deep; invoke it directly instead of inside subshells or lists"
+    )]
+    NestingTooDeep(usize),
+    /// `apply_patch` is invoked in a context where the effect of the rest of
+    /// the script cannot be determined statically.
+    #[error(
+        "ambiguous apply_patch invocation: {0}. Invoke apply_patch on its own (optionally after `cd <path> &&`) and run other commands in a separate call"
+    )]
+    AmbiguousApplyPatchInvocation(String),
+}
+
+impl ExtractHeredocError {
+    /// Returns true if the script clearly invokes `apply_patch`, but in a way
+    /// that was deliberately rejected. Such errors should be reported back to
+    /// the model rather than falling back to running the script as-is.
+    pub fn is_rejected_invocation(&self) -> bool {
+        matches!(
+            self,
+            ExtractHeredocError::MultipleApplyPatchInvocations(_)
+                | ExtractHeredocError::NestingTooDeep(_)
+                | ExtractHeredocError::AmbiguousApplyPatchInvocation(_)
+        )
+    }
 }
 
 /// Applies the patch and prints the result to stdout/stderr.
@@ -717,7 +981,7 @@ mod tests {
         ]);
 
         match maybe_parse_apply_patch(&args) {
-            MaybeApplyPatch::Body(ApplyPatchArgs { hunks, patch: _ }) => {
+            MaybeApplyPatch::Body(ApplyPatchArgs { hunks, .. }) => {
                 assert_eq!(
                     hunks,
                     vec![Hunk::AddFile {
@@ -742,7 +1006,7 @@ mod tests {
         ]);
 
         match maybe_parse_apply_patch(&args) {
-            MaybeApplyPatch::Body(ApplyPatchArgs { hunks, patch: _ }) => {
+            MaybeApplyPatch::Body(ApplyPatchArgs { hunks, .. }) => {
                 assert_eq!(
                     hunks,
                     vec![Hunk::AddFile {
@@ -769,7 +1033,7 @@ PATCH"#,
         ]);
 
         match maybe_parse_apply_patch(&args) {
-            MaybeApplyPatch::Body(ApplyPatchArgs { hunks, patch: _ }) => {
+            MaybeApplyPatch::Body(ApplyPatchArgs { hunks, .. }) => {
                 assert_eq!(
                     hunks,
                     vec![Hunk::AddFile {
@@ -796,7 +1060,7 @@ PATCH"#,
         ]);
 
         match maybe_parse_apply_patch(&args) {
-            MaybeApplyPatch::Body(ApplyPatchArgs { hunks, patch: _ }) => {
+            MaybeApplyPatch::Body(ApplyPatchArgs { hunks, .. }) => {
                 assert_eq!(
                     hunks,
                     vec![Hunk::AddFile {
@@ -809,6 +1073,141 @@ PATCH"#,
         }
     }
 
+    #[test]
+    fn test_heredoc_after_cd_in_subshell() {
+        let args = strs_to_strings(&[
+            "bash",
+            "-lc",
+            r#"(cd foo && cd 'bar baz' && apply_patch <<'PATCH'
+*** Begin Patch
+*** Add File: qux
++hi
+*** End Patch
+PATCH
+)"#,
+        ]);
+
+        match maybe_parse_apply_patch(&args) {
+            MaybeApplyPatch::Body(ApplyPatchArgs { hunks, workdir, .. }) => {
+                assert_eq!(workdir, Some(PathBuf::from("foo/bar baz")));
+                assert_eq!(
+                    hunks,
+                    vec![Hunk::AddFile {
+                        path: PathBuf::from("qux"),
+                        contents: "hi\n".to_string()
+                    }]
+                );
+            }
+            result => panic!("expected MaybeApplyPatch::Body got {result:?}"),
+        }
+    }
+
+    #[test]
+    fn test_heredoc_not_apply_patch() {
+        let args = strs_to_strings(&["bash", "-lc", "echo apply_patch"]);
+        assert_eq!(
+            maybe_parse_apply_patch(&args),
+            MaybeApplyPatch::NotApplyPatch
+        );
+    }
+
+    #[test]
+    fn test_heredoc_rejects_ambiguous_invocations() {
+        let patch = "*** Begin Patch\n*** Add File: foo\n+hi\n*** End Patch";
+        for script in [
+            format!("apply_patch <<'PATCH' && echo done\n{patch}\nPATCH"),
+            format!("echo start; apply_patch <<'PATCH'\n{patch}\nPATCH"),
+            format!("make || apply_patch <<'PATCH'\n{patch}\nPATCH"),
+            format!("cd $HOME && apply_patch <<'PATCH'\n{patch}\nPATCH"),
+            format!("cd - && apply_patch <<'PATCH'\n{patch}\nPATCH"),
+            format!("cd && apply_patch <<'PATCH'\n{patch}\nPATCH"),
+            format!("cd '' && apply_patch <<'PATCH'\n{patch}\nPATCH"),
+            format!("apply_patch <<'PATCH' | cat\n{patch}\nPATCH"),
+        ] {
+            let args = strs_to_strings(&["bash", "-lc", &script]);
+            match maybe_parse_apply_patch(&args) {
+                MaybeApplyPatch::ShellParseError(
+                    ExtractHeredocError::AmbiguousApplyPatchInvocation(_),
+                ) => {}
+                result => panic!("expected ambiguous invocation for {script:?}, got {result:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_heredoc_rejects_multiple_invocations() {
+        let patch = "*** Begin Patch\n*** Add File: foo\n+hi\n*** End Patch";
+        let script = format!("apply_patch <<'A'\n{patch}\nA\napply_patch <<'B'\n{patch}\nB");
+        let args = strs_to_strings(&["bash", "-lc", &script]);
+        assert_eq!(
+            maybe_parse_apply_patch(&args),
+            MaybeApplyPatch::ShellParseError(ExtractHeredocError::MultipleApplyPatchInvocations(2))
+        );
+    }
+
+    #[test]
+    fn test_heredoc_rejects_deep_nesting() {
+        let depth = MAX_APPLY_PATCH_NESTING_DEPTH + 1;
+        let script = format!(
+            "{}apply_patch <<'PATCH'\n*** Begin Patch\n*** Add File: foo\n+hi\n*** End Patch\nPATCH\n{}",
+            "( ".repeat(depth),
+            " )".repeat(depth)
+        );
+        let args = strs_to_strings(&["bash", "-lc", &script]);
+        assert_eq!(
+            maybe_parse_apply_patch(&args),
+            MaybeApplyPatch::ShellParseError(ExtractHeredocError::NestingTooDeep(depth))
+        );
+    }
+
+    #[test]
+    fn test_retain_paths_keeps_only_accepted_hunks() {
+        let dir = tempdir().unwrap();
+        fs::write(dir.path().join("keep.txt"), "old\n").unwrap();
+        let patch = wrap_patch(
+            "*** Add File: drop.txt\n+dropped\n*** Update File: keep.txt\n@@\n-old\n+new\n*** Delete File: gone.txt",
+        );
+        let args = strs_to_strings(&["apply_patch", &patch]);
+        let MaybeApplyPatchVerified::Body(mut action) =
+            maybe_parse_apply_patch_verified(&args, dir.path())
+        else {
+            panic!("expected a verified patch");
+        };
+
+        action.retain_paths(&[dir.path().join("keep.txt")]).unwrap();
+        assert_eq!(
+            action.patch,
+            wrap_patch("*** Update File: keep.txt\n@@\n-old\n+new")
+        );
+        assert_eq!(
+            action.changes().keys().collect::<Vec<_>>(),
+            vec![&dir.path().join("keep.txt")]
+        );
+    }
+
+    #[test]
+    fn test_verified_resolves_paths_against_cd_target() {
+        let dir = tempdir().unwrap();
+        let script = "cd sub && apply_patch <<'PATCH'\n*** Begin Patch\n*** Add File: new.txt\n+hi\n*** End Patch\nPATCH";
+        let args = strs_to_strings(&["bash", "-lc", script]);
+        match maybe_parse_apply_patch_verified(&args, dir.path()) {
+            MaybeApplyPatchVerified::Body(action) => {
+                let expected_cwd = dir.path().join("sub");
+                assert_eq!(action.cwd, expected_cwd);
+                assert_eq!(
+                    action.changes(),
+                    &HashMap::from([(
+                        expected_cwd.join("new.txt"),
+                        ApplyPatchFileChange::Add {
+                            content: "hi\n".to_string()
+                        }
+                    )])
+                );
+            }
+            result => panic!("expected MaybeApplyPatchVerified::Body got {result:?}"),
+        }
+    }
+
     #[test]
     fn test_add_file_hunk_creates_file_with_contents() {
         let dir = tempdir().unwrap();
@@ -175,7 +175,35 @@ fn parse_patch_text(patch: &str, mode: ParseMode) -> Result<ApplyPatchArgs, Pars
         remaining_lines = &remaining_lines[hunk_lines..]
     }
     let patch = lines.join("\n");
-    Ok(ApplyPatchArgs { hunks, patch })
+    Ok(ApplyPatchArgs {
+        hunks,
+        patch,
+        workdir: None,
+    })
+}
+
+/// Returns `patch`, which must already be normalized by [`parse_patch`], with
+/// only the hunks for which `keep` returns true.
+pub(crate) fn retain_hunks(
+    patch: &str,
+    mut keep: impl FnMut(&Hunk) -> bool,
+) -> Result<String, ParseError> {
+    let lines: Vec<&str> = patch.trim().lines().collect();
+    check_patch_boundaries_strict(&lines)?;
+    let last_line_index = lines.len().saturating_sub(1);
+    let mut kept = vec![lines[0]];
+    let mut remaining_lines = &lines[1..last_line_index];
+    let mut line_number = 2;
+    while !remaining_lines.is_empty() {
+        let (hunk, hunk_lines) = parse_one_hunk(remaining_lines, line_number)?;
+        if keep(&hunk) {
+            kept.extend_from_slice(&remaining_lines[..hunk_lines]);
+        }
+        line_number += hunk_lines;
+        remaining_lines = &remaining_lines[hunk_lines..];
+    }
+    kept.push(lines[last_line_index]);
+    Ok(kept.join("\n"))
 }
 
 /// Checks the start and end lines of the patch text for `apply_patch`,
@@ -586,7 +614,8 @@ fn test_parse_patch_lenient() {
         parse_patch_text(&patch_text_in_heredoc, ParseMode::Lenient),
         Ok(ApplyPatchArgs {
             hunks: expected_patch.clone(),
-            patch: patch_text.to_string()
+            patch: patch_text.to_string(),
+            workdir: None,
         })
     );
 
@@ -599,7 +628,8 @@ fn test_parse_patch_lenient() {
         parse_patch_text(&patch_text_in_single_quoted_heredoc, ParseMode::Lenient),
         Ok(ApplyPatchArgs {
             hunks: expected_patch.clone(),
-            patch: patch_text.to_string()
+            patch: patch_text.to_string(),
+            workdir: None,
         })
     );
 
@@ -612,7 +642,8 @@ fn test_parse_patch_lenient() {
         parse_patch_text(&patch_text_in_double_quoted_heredoc, ParseMode::Lenient),
         Ok(ApplyPatchArgs {
             hunks: expected_patch.clone(),
-            patch: patch_text.to_string()
+            patch: patch_text.to_string(),
+            workdir: None,
         })
     );
 
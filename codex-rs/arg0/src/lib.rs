@@ -3,6 +3,7 @@ use std::path::Path;
 use std::path::PathBuf;
 
 use codex_core::CODEX_APPLY_PATCH_ARG1;
+use codex_core::CODEX_WINDOWS_SANDBOX_ARG1;
 #[cfg(unix)]
 use std::os::unix::fs::symlink;
 use tempfile::TempDir;
@@ -19,7 +20,9 @@ const MISSPELLED_APPLY_PATCH_ARG0: &str = "applypatch";
 ///
 /// When the current executable is invoked through the hard-link or alias named
 /// `codex-linux-sandbox` we *directly* execute
-/// [`codex_linux_sandbox::run_main`] (which never returns). Otherwise we:
+/// [`codex_linux_sandbox::run_main`] (which never returns). Likewise, when
+/// `argv[1]` is [`CODEX_WINDOWS_SANDBOX_ARG1`] we execute
+/// [`codex_windows_sandbox::run_main`]. Otherwise we:
 ///
 /// 1.  Use [`dotenvy::from_path`] and [`dotenvy::dotenv`] to modify the
 ///     environment before creating any threads.
@@ -54,6 +57,10 @@ where
     }
 
     let argv1 = args.next().unwrap_or_default();
+    if argv1 == CODEX_WINDOWS_SANDBOX_ARG1 {
+        // Safety: [`run_main`] never returns.
+        codex_windows_sandbox::run_main();
+    }
     if argv1 == CODEX_APPLY_PATCH_ARG1 {
         let patch_arg = args.next().and_then(|s| s.to_str().map(|s| s.to_owned()));
         let exit_code = match patch_arg {
@@ -0,0 +1,189 @@
+use codex_core::audit_log::AuditEvent;
+use codex_core::audit_log::AuditRecord;
+use codex_core::audit_log::PatchFileAction;
+use codex_core::audit_log::find_audit_log;
+use codex_core::audit_log::read_audit_log;
+use codex_core::config::find_codex_home;
+use codex_core::protocol::ReviewDecision;
+
+/// Print the audit log of `session`, a session id or a unique prefix of
+/// one: one line per record, or the records themselves as JSON lines with
+/// `json`.
+pub fn run_audit_show(session: String, json: bool) -> ! {
+    let codex_home = match find_codex_home() {
+        Ok(codex_home) => codex_home,
+        Err(e) => {
+            eprintln!("Error finding codex home: {e}");
+            std::process::exit(1);
+        }
+    };
+    let records = find_audit_log(&codex_home, &session).and_then(|path| read_audit_log(&path));
+    let records = match records {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("Error reading audit log: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    for record in &records {
+        if json {
+            match serde_json::to_string(record) {
+                Ok(line) => println!("{line}"),
+                Err(e) => {
+                    eprintln!("Error serializing audit record: {e}");
+                    std::process::exit(1);
+                }
+            }
+        } else {
+            println!("{}", describe(record));
+        }
+    }
+    std::process::exit(0);
+}
+
+fn describe(record: &AuditRecord) -> String {
+    let AuditRecord { ts, event, .. } = record;
+    let what = match event {
+        AuditEvent::SessionStart {
+            cwd,
+            model,
+            model_provider,
+            approval_policy,
+            sandbox_policy,
+            resumed,
+        } => format!(
+            "{} in {}: model {model} ({model_provider}), approval {approval_policy}, sandbox {sandbox_policy}",
+            if *resumed {
+                "session resumed"
+            } else {
+                "session started"
+            },
+            cwd.display(),
+        ),
+        AuditEvent::CommandApproval {
+            command,
+            cwd,
+            decision,
+            ..
+        } => format!(
+            "{} command: {} (in {})",
+            decision_label(*decision),
+            command.join(" "),
+            cwd.display()
+        ),
+        AuditEvent::PatchApproval {
+            files, decision, ..
+        } => format!(
+            "{} patch to {}",
+            decision_label(*decision),
+            files
+                .iter()
+                .map(|path| path.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        AuditEvent::Command {
+            command,
+            cwd,
+            sandbox,
+            network_access,
+            exit_code,
+            duration_ms,
+            killed_reason,
+            ..
+        } => {
+            let network = if *network_access {
+                "network"
+            } else {
+                "no network"
+            };
+            let killed = killed_reason
+                .map(|reason| format!(", killed: {reason:?}"))
+                .unwrap_or_default();
+            format!(
+                "ran {} (in {}): exit {exit_code} after {duration_ms}ms, sandbox {sandbox}, {network}{killed}",
+                command.join(" "),
+                cwd.display()
+            )
+        }
+        AuditEvent::ExecSession {
+            exec_session_id,
+            command,
+            exit_code,
+        } => format!(
+            "closed exec session {exec_session_id} running {}: {}",
+            command.join(" "),
+            exit_label(*exit_code, "still running")
+        ),
+        AuditEvent::BackgroundTask {
+            task_id,
+            command,
+            cwd,
+            sandbox,
+            exit_code,
+        } => format!(
+            "background task {task_id} stopped: {} (in {}): {}, sandbox {sandbox}",
+            command.join(" "),
+            cwd.display(),
+            exit_label(*exit_code, "killed")
+        ),
+        AuditEvent::Patch {
+            auto_approved,
+            success,
+            files,
+            ..
+        } => {
+            let outcome = if *success {
+                "applied"
+            } else {
+                "failed to apply"
+            };
+            let approval = if *auto_approved {
+                "auto-approved"
+            } else {
+                "approved"
+            };
+            let files = files
+                .iter()
+                .map(|file| {
+                    let action = match file.action {
+                        PatchFileAction::Add => "A",
+                        PatchFileAction::Delete => "D",
+                        PatchFileAction::Update => "M",
+                    };
+                    let path = match &file.move_path {
+                        Some(dest) => format!("{} -> {}", file.path.display(), dest.display()),
+                        None => file.path.display().to_string(),
+                    };
+                    format!(
+                        "{action} {path} (+{} -{})",
+                        file.added_lines, file.removed_lines
+                    )
+                })
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!("{outcome} {approval} patch: {files}")
+        }
+        AuditEvent::NetworkRequest { target, purpose } => {
+            format!("request to {target} ({purpose})")
+        }
+    };
+    format!("{ts}  {what}")
+}
+
+fn decision_label(decision: ReviewDecision) -> &'static str {
+    match decision {
+        ReviewDecision::Approved => "approved",
+        ReviewDecision::ApprovedForSession => "approved for the session",
+        ReviewDecision::Denied => "denied",
+        ReviewDecision::Abort => "denied and aborted",
+    }
+}
+
+fn exit_label(exit_code: Option<i32>, otherwise: &str) -> String {
+    match exit_code {
+        Some(code) => format!("exit {code}"),
+        None => otherwise.to_string(),
+    }
+}
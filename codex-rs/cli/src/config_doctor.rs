@@ -0,0 +1,58 @@
+use codex_core::config::ConfigIssue;
+use codex_core::config::IssueSeverity;
+use codex_core::config::find_codex_home;
+use codex_core::config::validate_config;
+
+/// Print the problems `validate_config` finds in `config.toml` with a fix for
+/// each, and exit non-zero if any of them is an error.
+pub fn run_config_doctor() -> ! {
+    let codex_home = match find_codex_home() {
+        Ok(codex_home) => codex_home,
+        Err(e) => {
+            eprintln!("Error finding codex home: {e}");
+            std::process::exit(1);
+        }
+    };
+    let path = codex_home.join("config.toml");
+
+    let issues = validate_config(&codex_home);
+    if issues.is_empty() {
+        println!("No problems found in {}", path.display());
+        std::process::exit(0);
+    }
+
+    println!("Checked {}\n", path.display());
+    for issue in &issues {
+        print_issue(issue);
+    }
+    let errors = issues
+        .iter()
+        .filter(|i| i.severity == IssueSeverity::Error)
+        .count();
+    let warnings = issues.len() - errors;
+    println!(
+        "{} and {}",
+        plural(errors, "error"),
+        plural(warnings, "warning")
+    );
+    std::process::exit(if errors > 0 { 1 } else { 0 });
+}
+
+fn print_issue(issue: &ConfigIssue) {
+    let label = match issue.severity {
+        IssueSeverity::Error => "error",
+        IssueSeverity::Warning => "warning",
+    };
+    println!("{label}: {issue}");
+    if let Some(fix) = &issue.fix {
+        println!("  fix: {fix}");
+    }
+    println!();
+}
+
+fn plural(count: usize, noun: &str) -> String {
+    match count {
+        1 => format!("1 {noun}"),
+        n => format!("{n} {noun}s"),
+    }
+}
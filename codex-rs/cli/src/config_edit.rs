@@ -0,0 +1,56 @@
+use codex_common::parse_config_value;
+use codex_core::config::find_codex_home;
+use codex_core::config::get_config_value;
+use codex_core::config::set_config_value;
+use codex_core::config::validate_config;
+use std::path::PathBuf;
+
+/// Print the value of the dotted `key` in config.toml. Strings are printed
+/// without quotes and everything else as TOML. Exits with status 1, printing
+/// nothing, if the key is not set.
+pub fn run_config_get(key: String) -> ! {
+    let codex_home = codex_home_or_exit();
+    match get_config_value(&codex_home, &key) {
+        Ok(Some(value)) => {
+            match value.as_str() {
+                Some(s) => println!("{s}"),
+                None => println!("{value}"),
+            }
+            std::process::exit(0);
+        }
+        Ok(None) => std::process::exit(1),
+        Err(e) => {
+            eprintln!("Error reading config.toml: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Set the dotted `key` in config.toml to `value`, parsed like a `-c`
+/// override: as TOML if possible and as a string otherwise.
+pub fn run_config_set(key: String, value: String) -> ! {
+    let codex_home = codex_home_or_exit();
+    if let Err(e) = set_config_value(&codex_home, &key, parse_config_value(&value)) {
+        eprintln!("Error writing config.toml: {e}");
+        std::process::exit(1);
+    }
+
+    // The value loads, but `key` may still be misspelled or refer to
+    // something that does not exist.
+    for issue in validate_config(&codex_home) {
+        if issue.key.as_deref() == Some(key.as_str()) {
+            eprintln!("warning: {issue}");
+        }
+    }
+    std::process::exit(0);
+}
+
+fn codex_home_or_exit() -> PathBuf {
+    match find_codex_home() {
+        Ok(codex_home) => codex_home,
+        Err(e) => {
+            eprintln!("Error finding codex home: {e}");
+            std::process::exit(1);
+        }
+    }
+}
@@ -5,6 +5,7 @@ use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
 use codex_core::exec_env::create_env;
 use codex_core::landlock::spawn_command_under_linux_sandbox;
+use codex_core::seatbelt::create_seatbelt_profile;
 use codex_core::seatbelt::spawn_command_under_seatbelt;
 use codex_core::spawn::StdioPolicy;
 use codex_protocol::config_types::SandboxMode;
@@ -20,8 +21,24 @@ pub async fn run_command_under_seatbelt(
     let SeatbeltCommand {
         full_auto,
         config_overrides,
+        print_profile,
         command,
     } = command;
+    if print_profile {
+        let config = load_config(full_auto, config_overrides, codex_linux_sandbox_exe)?;
+        let (profile, params) = create_seatbelt_profile(
+            &config.sandbox_policy,
+            &config.seatbelt_extensions,
+            &std::env::current_dir()?,
+        );
+        // The parameters follow as comments, so the output is still a
+        // profile that `sandbox-exec -f` accepts.
+        println!("{profile}");
+        for param in params {
+            println!("; {param}");
+        }
+        return Ok(());
+    }
     run_command_under_sandbox(
         full_auto,
         command,
@@ -63,25 +80,23 @@ async fn run_command_under_sandbox(
     codex_linux_sandbox_exe: Option<PathBuf>,
     sandbox_type: SandboxType,
 ) -> anyhow::Result<()> {
-    let sandbox_mode = create_sandbox_mode(full_auto);
     let cwd = std::env::current_dir()?;
-    let config = Config::load_with_cli_overrides(
-        config_overrides
-            .parse_overrides()
-            .map_err(anyhow::Error::msg)?,
-        ConfigOverrides {
-            sandbox_mode: Some(sandbox_mode),
-            codex_linux_sandbox_exe,
-            ..Default::default()
-        },
-    )?;
+    let config = load_config(full_auto, config_overrides, codex_linux_sandbox_exe)?;
     let stdio_policy = StdioPolicy::Inherit;
     let env = create_env(&config.shell_environment_policy);
 
     let mut child = match sandbox_type {
         SandboxType::Seatbelt => {
-            spawn_command_under_seatbelt(command, &config.sandbox_policy, cwd, stdio_policy, env)
-                .await?
+            spawn_command_under_seatbelt(
+                command,
+                &config.sandbox_policy,
+                &config.seatbelt_extensions,
+                cwd,
+                stdio_policy,
+                env,
+                &config.exec_limits,
+            )
+            .await?
         }
         SandboxType::Landlock => {
             #[expect(clippy::expect_used)]
@@ -95,6 +110,8 @@ async fn run_command_under_sandbox(
                 cwd,
                 stdio_policy,
                 env,
+                &config.exec_limits,
+                None,
             )
             .await?
         }
@@ -104,6 +121,24 @@ async fn run_command_under_sandbox(
     handle_exit_status(status);
 }
 
+fn load_config(
+    full_auto: bool,
+    config_overrides: CliConfigOverrides,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+) -> anyhow::Result<Config> {
+    let config = Config::load_with_cli_overrides(
+        config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?,
+        ConfigOverrides {
+            sandbox_mode: Some(create_sandbox_mode(full_auto)),
+            codex_linux_sandbox_exe,
+            ..Default::default()
+        },
+    )?;
+    Ok(config)
+}
+
 pub fn create_sandbox_mode(full_auto: bool) -> SandboxMode {
     if full_auto {
         SandboxMode::WorkspaceWrite
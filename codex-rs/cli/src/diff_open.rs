@@ -55,7 +55,7 @@ pub async fn run_diff_open(cmd: DiffOpenCommand) -> Result<()> {
     let action = match parsed {
         MaybeApplyPatchVerified::Body(a) => a,
         MaybeApplyPatchVerified::ShellParseError(e) => {
-            bail!("failed to parse apply_patch heredoc: {e:?}")
+            bail!("failed to parse apply_patch heredoc: {e}")
         }
         MaybeApplyPatchVerified::CorrectnessError(e) => bail!("invalid patch: {e}"),
         MaybeApplyPatchVerified::NotApplyPatch => {
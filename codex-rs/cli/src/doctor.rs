@@ -0,0 +1,505 @@
+//! `codex doctor`: checks that the installation works end to end and says
+//! how to fix what does not, as a table or, for bug reports, as JSON.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use clap::Parser;
+use codex_common::CliConfigOverrides;
+use codex_core::ModelProviderInfo;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::config::IssueSeverity;
+use codex_core::config::validate_config;
+use codex_core::exec_env::create_env;
+use codex_core::probe_mcp_server;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::spawn::StdioPolicy;
+use codex_login::AuthMode;
+use codex_login::CodexAuth;
+use serde_json::json;
+
+#[derive(Debug, Parser)]
+pub struct DoctorCommand {
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// Print the results as JSON, e.g. to attach to a bug report.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Ok,
+    Warn,
+    Fail,
+    /// Not run, because it does not apply or an earlier check failed.
+    Skip,
+}
+
+impl Status {
+    fn label(self) -> &'static str {
+        match self {
+            Status::Ok => "ok",
+            Status::Warn => "warn",
+            Status::Fail => "fail",
+            Status::Skip => "skip",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Check {
+    name: String,
+    status: Status,
+    detail: String,
+    /// What to do about a warning or failure.
+    fix: Option<String>,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, status: Status, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            status,
+            detail: detail.into(),
+            fix: None,
+        }
+    }
+
+    fn with_fix(mut self, fix: impl Into<String>) -> Self {
+        self.fix = Some(fix.into());
+        self
+    }
+}
+
+/// Run every check, print the results and exit non-zero if any failed.
+pub async fn run_doctor(cmd: DoctorCommand, codex_linux_sandbox_exe: Option<PathBuf>) -> ! {
+    let DoctorCommand {
+        config_overrides,
+        json,
+    } = cmd;
+
+    let mut checks = vec![check_install()];
+    let config = match load_config(config_overrides, codex_linux_sandbox_exe) {
+        Ok(config) => {
+            checks.push(check_config(&config.codex_home));
+            Some(config)
+        }
+        Err(check) => {
+            checks.push(check);
+            None
+        }
+    };
+
+    match &config {
+        Some(config) => {
+            checks.push(check_sandbox(config).await);
+            let auth = check_auth(config).await;
+            let auth_ok = auth.status != Status::Fail;
+            checks.push(auth);
+            checks.push(if auth_ok {
+                check_provider(config).await
+            } else {
+                Check::new("provider", Status::Skip, "needs working credentials")
+            });
+            checks.extend(check_mcp_servers(config).await);
+        }
+        None => {
+            for name in ["sandbox", "auth", "provider", "mcp"] {
+                checks.push(Check::new(name, Status::Skip, "needs a valid config"));
+            }
+        }
+    }
+
+    if json {
+        println!("{}", format_json(&checks));
+    } else {
+        for line in format_table(&checks) {
+            println!("{line}");
+        }
+    }
+    let failed = checks.iter().any(|check| check.status == Status::Fail);
+    std::process::exit(if failed { 1 } else { 0 });
+}
+
+fn load_config(
+    config_overrides: CliConfigOverrides,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+) -> Result<Config, Check> {
+    let fail = |detail: String| {
+        Check::new("config", Status::Fail, detail)
+            .with_fix("run `codex config doctor` for details on each problem")
+    };
+    let cli_overrides = config_overrides
+        .parse_overrides()
+        .map_err(|e| fail(format!("invalid -c override: {e}")))?;
+    Config::load_with_cli_overrides(
+        cli_overrides,
+        ConfigOverrides {
+            codex_linux_sandbox_exe,
+            ..Default::default()
+        },
+    )
+    .map_err(|e| fail(format!("cannot load the configuration: {e}")))
+}
+
+/// The running executable, and on Unix that the `apply_patch` alias that
+/// `codex_arg0` puts on `PATH` leads back to it.
+fn check_install() -> Check {
+    let version = env!("CARGO_PKG_VERSION");
+    let exe = match std::env::current_exe().and_then(|exe| exe.canonicalize()) {
+        Ok(exe) => exe,
+        Err(e) => {
+            return Check::new(
+                "install",
+                Status::Fail,
+                format!("codex {version}: cannot locate the executable: {e}"),
+            )
+            .with_fix("reinstall Codex, e.g. with `npm install -g @openai/codex`");
+        }
+    };
+    let detail = format!("codex {version} at {}", exe.display());
+    if cfg!(windows) {
+        return Check::new("install", Status::Ok, detail);
+    }
+    match find_on_path("apply_patch").map(|alias| alias.canonicalize()) {
+        Some(Ok(target)) if target == exe => Check::new("install", Status::Ok, detail),
+        Some(Ok(target)) => Check::new(
+            "install",
+            Status::Warn,
+            format!("{detail}; `apply_patch` on PATH is {}", target.display()),
+        )
+        .with_fix("remove the other `apply_patch` from PATH so Codex's own is used"),
+        _ => Check::new(
+            "install",
+            Status::Fail,
+            format!("{detail}; the `apply_patch` alias is not on PATH"),
+        )
+        .with_fix("check that the temporary directory is writable and reinstall Codex"),
+    }
+}
+
+fn find_on_path(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| candidate.exists())
+}
+
+fn check_config(codex_home: &Path) -> Check {
+    let issues = validate_config(codex_home);
+    let path = codex_home.join("config.toml");
+    let errors = issues
+        .iter()
+        .filter(|i| i.severity == IssueSeverity::Error)
+        .count();
+    let Some(first) = issues.first() else {
+        return Check::new("config", Status::Ok, format!("{} is valid", path.display()));
+    };
+    let status = if errors > 0 {
+        Status::Fail
+    } else {
+        Status::Warn
+    };
+    let more = match issues.len() {
+        1 => String::new(),
+        n => format!(" (and {} more)", n - 1),
+    };
+    Check::new("config", status, format!("{first}{more}"))
+        .with_fix("run `codex config doctor` for details on each problem")
+}
+
+/// Run `true` under the platform's sandbox with a read-only policy, which
+/// fails when the Seatbelt profile does not compile or the kernel does not
+/// support Landlock.
+async fn check_sandbox(config: &Config) -> Check {
+    let policy = SandboxPolicy::new_read_only_policy();
+    let command = vec!["true".to_string()];
+    let env = create_env(&config.shell_environment_policy);
+    let cwd = config.cwd.clone();
+
+    let (name, child) = if cfg!(target_os = "macos") {
+        let child = codex_core::seatbelt::spawn_command_under_seatbelt(
+            command,
+            &policy,
+            &config.seatbelt_extensions,
+            cwd,
+            StdioPolicy::RedirectForShellTool,
+            env,
+            &config.exec_limits,
+        )
+        .await;
+        ("seatbelt", child)
+    } else if cfg!(target_os = "linux") {
+        let Some(sandbox_exe) = &config.codex_linux_sandbox_exe else {
+            return Check::new("sandbox", Status::Fail, "codex-linux-sandbox was not found")
+                .with_fix("reinstall Codex; the sandbox is built into the `codex` executable");
+        };
+        let child = codex_core::landlock::spawn_command_under_linux_sandbox(
+            sandbox_exe,
+            command,
+            &policy,
+            cwd,
+            StdioPolicy::RedirectForShellTool,
+            env,
+            &config.exec_limits,
+            None,
+        )
+        .await;
+        ("landlock", child)
+    } else {
+        return Check::new("sandbox", Status::Skip, "not checked on this platform");
+    };
+
+    let output = match child {
+        Ok(child) => child.wait_with_output().await,
+        Err(e) => Err(e),
+    };
+    match output {
+        Ok(output) if output.status.success() => {
+            Check::new("sandbox", Status::Ok, format!("{name} works"))
+        }
+        Ok(output) => {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let reason = stderr.lines().next().unwrap_or("no error output").trim();
+            Check::new(
+                "sandbox",
+                Status::Fail,
+                format!("{name} failed ({}): {reason}", output.status),
+            )
+            .with_fix(sandbox_fix())
+        }
+        Err(e) => Check::new("sandbox", Status::Fail, format!("cannot start {name}: {e}"))
+            .with_fix(sandbox_fix()),
+    }
+}
+
+fn sandbox_fix() -> String {
+    if cfg!(target_os = "macos") {
+        "check that /usr/bin/sandbox-exec exists and is not restricted by a device profile"
+            .to_string()
+    } else if landlock_enabled() == Some(false) {
+        "Landlock is not enabled in this kernel: it needs Linux 5.13 or later with `landlock` \
+         in the `lsm=` boot parameter; inside a container, rely on the container and set \
+         `sandbox_mode = \"danger-full-access\"`"
+            .to_string()
+    } else {
+        "run `codex debug landlock true` to see the full error".to_string()
+    }
+}
+
+/// Whether the kernel lists Landlock among its active security modules, or
+/// `None` when that cannot be read.
+fn landlock_enabled() -> Option<bool> {
+    let lsm = std::fs::read_to_string("/sys/kernel/security/lsm").ok()?;
+    Some(lsm.trim().split(',').any(|module| module == "landlock"))
+}
+
+async fn check_auth(config: &Config) -> Check {
+    let provider = &config.model_provider;
+    if !provider.requires_openai_auth {
+        return match provider.api_key() {
+            Ok(Some(_)) => Check::new(
+                "auth",
+                Status::Ok,
+                format!(
+                    "API key from ${}",
+                    provider.env_key.as_deref().unwrap_or_default()
+                ),
+            ),
+            Ok(None) => Check::new(
+                "auth",
+                Status::Ok,
+                format!(
+                    "provider `{}` needs no credentials",
+                    config.model_provider_id
+                ),
+            ),
+            Err(e) => Check::new("auth", Status::Fail, e.to_string()).with_fix(format!(
+                "set ${} in the environment or in $CODEX_HOME/.env",
+                provider.env_key.as_deref().unwrap_or_default()
+            )),
+        };
+    }
+
+    match CodexAuth::from_codex_home(&config.codex_home, config.preferred_auth_method) {
+        Ok(Some(auth)) => {
+            let method = match auth.mode {
+                AuthMode::ApiKey => "an API key",
+                AuthMode::ChatGPT => "ChatGPT",
+            };
+            match auth.get_token().await {
+                Ok(_) => Check::new("auth", Status::Ok, format!("logged in with {method}")),
+                Err(e) => Check::new(
+                    "auth",
+                    Status::Fail,
+                    format!("logged in with {method}, but the token is unusable: {e}"),
+                )
+                .with_fix("run `codex login` again"),
+            }
+        }
+        Ok(None) => Check::new("auth", Status::Fail, "not logged in")
+            .with_fix("run `codex login`, or `codex login --api-key <KEY>`"),
+        Err(e) => Check::new("auth", Status::Fail, format!("cannot read auth.json: {e}"))
+            .with_fix("run `codex logout`, then `codex login`"),
+    }
+}
+
+async fn check_provider(config: &Config) -> Check {
+    let auth = CodexAuth::from_codex_home(&config.codex_home, config.preferred_auth_method)
+        .ok()
+        .flatten();
+    let id = &config.model_provider_id;
+    let client = reqwest::Client::new();
+    match config.model_provider.probe(&client, &auth).await {
+        Ok(status) => provider_check(id, &config.model_provider, status.as_u16()),
+        Err(e) => Check::new("provider", Status::Fail, format!("`{id}` unreachable: {e}"))
+            .with_fix(format!(
+                "check `base_url` of provider `{id}`, the network and any HTTPS_PROXY setting"
+            )),
+    }
+}
+
+/// Any answer but a rejection of the credentials means the provider can be
+/// used; not every provider lists its models.
+fn provider_check(id: &str, provider: &ModelProviderInfo, status: u16) -> Check {
+    let base_url = provider.base_url.as_deref().unwrap_or("the default URL");
+    match status {
+        401 | 403 => Check::new(
+            "provider",
+            Status::Fail,
+            format!("`{id}` at {base_url} rejected the credentials (HTTP {status})"),
+        )
+        .with_fix("run `codex login` again, or check the API key"),
+        500..=599 => Check::new(
+            "provider",
+            Status::Warn,
+            format!("`{id}` at {base_url} answered with HTTP {status}"),
+        )
+        .with_fix("the provider may be having an outage; try again later"),
+        _ => Check::new(
+            "provider",
+            Status::Ok,
+            format!("`{id}` at {base_url} is reachable (HTTP {status})"),
+        ),
+    }
+}
+
+async fn check_mcp_servers(config: &Config) -> Vec<Check> {
+    if config.mcp_servers.is_empty() {
+        return vec![Check::new("mcp", Status::Skip, "no servers configured")];
+    }
+    let mut servers: Vec<_> = config.mcp_servers.iter().collect();
+    servers.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let mut checks = Vec::new();
+    for (name, server) in servers {
+        let check_name = format!("mcp: {name}");
+        checks.push(match probe_mcp_server(server.clone()).await {
+            Ok(tools) => Check::new(check_name, Status::Ok, format!("started, {tools} tools")),
+            Err(e) => Check::new(check_name, Status::Fail, format!("{e:#}")).with_fix(format!(
+                "check `mcp_servers.{name}.command` and `args`, and run the command by hand to \
+                 see its output"
+            )),
+        });
+    }
+    checks
+}
+
+/// One line per check under a header, with the columns aligned and the fix
+/// for a warning or failure on the next line.
+fn format_table(checks: &[Check]) -> Vec<String> {
+    let name_width = checks
+        .iter()
+        .map(|check| check.name.len())
+        .chain(["CHECK".len()])
+        .max()
+        .unwrap_or(0);
+    let status_width = "STATUS".len();
+    let mut lines = vec![format!(
+        "{:<name_width$}  {:<status_width$}  DETAIL",
+        "CHECK", "STATUS"
+    )];
+    for check in checks {
+        lines.push(format!(
+            "{:<name_width$}  {:<status_width$}  {}",
+            check.name,
+            check.status.label(),
+            check.detail
+        ));
+        if let Some(fix) = &check.fix {
+            lines.push(format!(
+                "{:<name_width$}  {:<status_width$}  fix: {fix}",
+                "", ""
+            ));
+        }
+    }
+    lines
+}
+
+fn format_json(checks: &[Check]) -> String {
+    let checks: Vec<_> = checks
+        .iter()
+        .map(|check| {
+            json!({
+                "name": check.name,
+                "status": check.status.label(),
+                "detail": check.detail,
+                "fix": check.fix,
+            })
+        })
+        .collect();
+    let report = json!({
+        "version": env!("CARGO_PKG_VERSION"),
+        "os": std::env::consts::OS,
+        "arch": std::env::consts::ARCH,
+        "checks": checks,
+    });
+    serde_json::to_string_pretty(&report).unwrap_or_else(|_| report.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn table_puts_fixes_under_their_check() {
+        let checks = vec![
+            Check::new("install", Status::Ok, "codex 0.0.0 at /usr/bin/codex"),
+            Check::new("mcp: docs", Status::Fail, "program not found")
+                .with_fix("check `mcp_servers.docs.command`"),
+        ];
+        assert_eq!(
+            format_table(&checks),
+            vec![
+                "CHECK      STATUS  DETAIL".to_string(),
+                "install    ok      codex 0.0.0 at /usr/bin/codex".to_string(),
+                "mcp: docs  fail    program not found".to_string(),
+                "                   fix: check `mcp_servers.docs.command`".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_rejected_credentials_fail_the_provider_check() {
+        let provider = ModelProviderInfo {
+            name: "Local".to_string(),
+            base_url: Some("http://localhost:8080/v1".to_string()),
+            env_key: None,
+            env_key_instructions: None,
+            wire_api: Default::default(),
+            query_params: None,
+            http_headers: None,
+            env_http_headers: None,
+            request_max_retries: None,
+            stream_max_retries: None,
+            stream_idle_timeout_ms: None,
+            requires_openai_auth: false,
+            stream_include_usage: false,
+        };
+        assert_eq!(provider_check("local", &provider, 404).status, Status::Ok);
+        assert_eq!(provider_check("local", &provider, 503).status, Status::Warn);
+        assert_eq!(provider_check("local", &provider, 401).status, Status::Fail);
+    }
+}
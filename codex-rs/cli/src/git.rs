@@ -0,0 +1,120 @@
+use std::io::Write;
+use std::path::Path;
+
+use clap::Parser;
+use codex_common::CliConfigOverrides;
+use codex_core::git_info::commit_log;
+use codex_core::git_info::staged_diff;
+use codex_core::git_message::generate_changelog;
+use codex_core::git_message::generate_commit_message;
+use codex_login::AuthManager;
+
+use crate::login::load_config_or_exit;
+
+#[derive(Debug, Parser)]
+pub struct CommitMsgCommand {
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// Run `git commit` with the message, opening the editor to review it
+    /// first.
+    #[arg(long, conflicts_with = "changelog")]
+    pub commit: bool,
+
+    /// Summarize the commits in RANGE, e.g. `v1.2.0..HEAD`, as a Markdown
+    /// changelog instead.
+    #[arg(long, value_name = "RANGE")]
+    pub changelog: Option<String>,
+}
+
+/// Print a commit message for the staged changes, or a changelog of a
+/// revision range, written by the configured model. With `--commit`, commit
+/// the staged changes with the message instead.
+pub async fn run_commit_msg(cmd: CommitMsgCommand) -> ! {
+    let CommitMsgCommand {
+        config_overrides,
+        commit,
+        changelog,
+    } = cmd;
+    let config = load_config_or_exit(config_overrides);
+    let cwd = config.cwd.clone();
+    let auth_manager = AuthManager::shared(config.codex_home.clone(), config.preferred_auth_method);
+
+    let result = match &changelog {
+        Some(range) => {
+            let log = match commit_log(&cwd, range).await {
+                Some(log) if !log.trim().is_empty() => log,
+                Some(_) => {
+                    eprintln!("No commits in {range}.");
+                    std::process::exit(1);
+                }
+                None => {
+                    eprintln!(
+                        "Error reading the commits in {range}; is it a valid revision range?"
+                    );
+                    std::process::exit(1);
+                }
+            };
+            generate_changelog(&config, auth_manager, &log).await
+        }
+        None => {
+            let diff = match staged_diff(&cwd).await {
+                Some(diff) if !diff.trim().is_empty() => diff,
+                Some(_) => {
+                    eprintln!("No staged changes; stage them with `git add` first.");
+                    std::process::exit(1);
+                }
+                None => {
+                    eprintln!("Error reading the staged changes; is this a git repository?");
+                    std::process::exit(1);
+                }
+            };
+            generate_commit_message(&config, auth_manager, &diff).await
+        }
+    };
+    let text = match result {
+        Ok(text) => text,
+        Err(e) => {
+            eprintln!("Error generating the message: {e}");
+            std::process::exit(1);
+        }
+    };
+
+    if commit {
+        commit_with_message(&cwd, &text);
+    }
+    println!("{text}");
+    std::process::exit(0);
+}
+
+/// Run `git commit -e` with `message` and exit with its status. The message
+/// goes through a file rather than `-F -` so that the editor still reads
+/// from the terminal.
+fn commit_with_message(cwd: &Path, message: &str) -> ! {
+    let file = tempfile::NamedTempFile::new().and_then(|mut file| {
+        file.write_all(message.as_bytes())?;
+        file.write_all(b"\n")?;
+        Ok(file)
+    });
+    let file = match file {
+        Ok(file) => file,
+        Err(e) => {
+            eprintln!("Error writing the commit message: {e}");
+            std::process::exit(1);
+        }
+    };
+    let status = std::process::Command::new("git")
+        .arg("commit")
+        .arg("-e")
+        .arg("-F")
+        .arg(file.path())
+        .current_dir(cwd)
+        .status();
+    match status {
+        Ok(status) => std::process::exit(status.code().unwrap_or(1)),
+        Err(e) => {
+            eprintln!("Error running git commit: {e}");
+            std::process::exit(1);
+        }
+    }
+}
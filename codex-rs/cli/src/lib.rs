@@ -1,7 +1,14 @@
+pub mod audit;
+pub mod config_doctor;
+pub mod config_edit;
 pub mod debug_sandbox;
+pub mod doctor;
 mod exit_status;
+pub mod git;
 pub mod login;
+pub mod oss;
 pub mod proto;
+pub mod sandbox_exec;
 
 use clap::Parser;
 use codex_common::CliConfigOverrides;
@@ -15,6 +22,12 @@ pub struct SeatbeltCommand {
     #[clap(skip)]
     pub config_overrides: CliConfigOverrides,
 
+    /// Print the Seatbelt profile the command would run under, including the
+    /// rules from `[sandbox_seatbelt]`, and its parameters instead of
+    /// running it.
+    #[arg(long = "print-profile", default_value_t = false)]
+    pub print_profile: bool,
+
     /// Full command args to run under seatbelt.
     #[arg(trailing_var_arg = true)]
     pub command: Vec<String>,
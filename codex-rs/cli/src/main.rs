@@ -7,14 +7,28 @@ use codex_chatgpt::apply_command::ApplyCommand;
 use codex_chatgpt::apply_command::run_apply_command;
 use codex_cli::LandlockCommand;
 use codex_cli::SeatbeltCommand;
+use codex_cli::audit::run_audit_show;
+use codex_cli::config_doctor::run_config_doctor;
+use codex_cli::config_edit::run_config_get;
+use codex_cli::config_edit::run_config_set;
+use codex_cli::doctor::DoctorCommand;
+use codex_cli::doctor::run_doctor;
+use codex_cli::git::CommitMsgCommand;
+use codex_cli::git::run_commit_msg;
 mod diff_open;
 use codex_cli::login::run_login_status;
 use codex_cli::login::run_login_with_api_key;
 use codex_cli::login::run_login_with_chatgpt;
 use codex_cli::login::run_logout;
+use codex_cli::oss::ModelsCommand;
+use codex_cli::oss::run_oss_models;
 use codex_cli::proto;
+use codex_cli::sandbox_exec::SandboxExecCommand;
+use codex_cli::sandbox_exec::run_sandbox_exec;
 use codex_common::CliConfigOverrides;
+use codex_exec::AskCli;
 use codex_exec::Cli as ExecCli;
+use codex_mcp_server::Cli as McpCli;
 use codex_tui::Cli as TuiCli;
 use std::path::PathBuf;
 
@@ -51,14 +65,36 @@ enum Subcommand {
     #[clap(visible_alias = "e")]
     Exec(ExecCli),
 
+    /// Answer a question about the code without making changes.
+    Ask(AskCli),
+
     /// Manage login.
     Login(LoginCommand),
 
     /// Remove stored authentication credentials.
     Logout(LogoutCommand),
 
+    /// Inspect the configuration.
+    Config(ConfigCommand),
+
+    /// Review what past sessions did.
+    Audit(AuditCommand),
+
+    /// Run commands under the sandbox the agent uses.
+    Sandbox(SandboxCommand),
+
+    /// Check the installation, sandbox, login, model provider, config and
+    /// MCP servers, and suggest fixes.
+    Doctor(DoctorCommand),
+
+    /// Write commit messages and changelogs with the model.
+    Git(GitCommand),
+
+    /// Manage local open-source models served by Ollama.
+    Oss(OssCommand),
+
     /// Experimental: run Codex as an MCP server.
-    Mcp,
+    Mcp(McpCli),
 
     /// Run the Protocol stream via stdin/stdout
     #[clap(visible_alias = "p")]
@@ -80,6 +116,10 @@ enum Subcommand {
     /// Internal: generate TypeScript protocol bindings.
     #[clap(hide = true)]
     GenerateTs(GenerateTsCommand),
+
+    /// Internal: generate JSON Schemas for the protocol types.
+    #[clap(hide = true)]
+    GenerateJsonSchema(GenerateJsonSchemaCommand),
 }
 
 #[derive(Debug, Parser)]
@@ -104,6 +144,19 @@ enum DebugCommand {
     Landlock(LandlockCommand),
 }
 
+#[derive(Debug, Parser)]
+struct SandboxCommand {
+    #[command(subcommand)]
+    action: SandboxSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum SandboxSubcommand {
+    /// Run a command under Seatbelt, Landlock or the Windows sandbox,
+    /// whichever this platform has, with the agent's sandbox policy.
+    Exec(SandboxExecCommand),
+}
+
 #[derive(Debug, Parser)]
 struct LoginCommand {
     #[clap(skip)]
@@ -128,6 +181,82 @@ struct LogoutCommand {
     config_overrides: CliConfigOverrides,
 }
 
+#[derive(Debug, Parser)]
+struct ConfigCommand {
+    #[command(subcommand)]
+    action: ConfigSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum ConfigSubcommand {
+    /// Check config.toml for unknown keys, wrong types, undefined profiles
+    /// and providers, and MCP servers that cannot start, and suggest fixes.
+    Doctor,
+
+    /// Print the value of a key, e.g. `model` or
+    /// `model_providers.openai.base_url`.
+    Get {
+        /// Dotted path of the key.
+        key: String,
+    },
+
+    /// Set a key, keeping the rest of config.toml and its comments as they
+    /// are.
+    Set {
+        /// Dotted path of the key.
+        key: String,
+
+        /// TOML value, e.g. `true` or `["a", "b"]`. Anything that does not
+        /// parse as TOML is taken as a string.
+        value: String,
+    },
+}
+
+#[derive(Debug, Parser)]
+struct AuditCommand {
+    #[command(subcommand)]
+    action: AuditSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum AuditSubcommand {
+    /// Print a session's approval decisions, commands, patches and network
+    /// requests.
+    Show {
+        /// Session id, or a unique prefix of one.
+        session: String,
+
+        /// Print the records as JSON lines.
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Debug, Parser)]
+struct GitCommand {
+    #[command(subcommand)]
+    action: GitSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum GitSubcommand {
+    /// Print a Conventional Commits message for the staged changes, or a
+    /// changelog of a revision range with `--changelog`.
+    CommitMsg(CommitMsgCommand),
+}
+
+#[derive(Debug, Parser)]
+struct OssCommand {
+    #[command(subcommand)]
+    action: OssSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+enum OssSubcommand {
+    /// List, download, inspect and delete models.
+    Models(ModelsCommand),
+}
+
 #[derive(Debug, Parser)]
 struct GenerateTsCommand {
     /// Output directory where .ts files will be written
@@ -139,6 +268,13 @@ struct GenerateTsCommand {
     prettier: Option<PathBuf>,
 }
 
+#[derive(Debug, Parser)]
+struct GenerateJsonSchemaCommand {
+    /// Output directory where .json files will be written
+    #[arg(short = 'o', long = "out", value_name = "DIR")]
+    out_dir: PathBuf,
+}
+
 fn main() -> anyhow::Result<()> {
     arg0_dispatch_or_else(|codex_linux_sandbox_exe| async move {
         cli_main(codex_linux_sandbox_exe).await?;
@@ -162,8 +298,13 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             prepend_config_flags(&mut exec_cli.config_overrides, cli.config_overrides);
             codex_exec::run_main(exec_cli, codex_linux_sandbox_exe).await?;
         }
-        Some(Subcommand::Mcp) => {
-            codex_mcp_server::run_main(codex_linux_sandbox_exe, cli.config_overrides).await?;
+        Some(Subcommand::Ask(mut ask_cli)) => {
+            prepend_config_flags(&mut ask_cli.config_overrides, cli.config_overrides);
+            codex_exec::run_ask(ask_cli, codex_linux_sandbox_exe).await?;
+        }
+        Some(Subcommand::Mcp(mut mcp_cli)) => {
+            prepend_config_flags(&mut mcp_cli.config_overrides, cli.config_overrides);
+            codex_mcp_server::run_main(codex_linux_sandbox_exe, mcp_cli).await?;
         }
         Some(Subcommand::Login(mut login_cli)) => {
             prepend_config_flags(&mut login_cli.config_overrides, cli.config_overrides);
@@ -184,6 +325,36 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             prepend_config_flags(&mut logout_cli.config_overrides, cli.config_overrides);
             run_logout(logout_cli.config_overrides).await;
         }
+        Some(Subcommand::Config(config_cli)) => match config_cli.action {
+            ConfigSubcommand::Doctor => run_config_doctor(),
+            ConfigSubcommand::Get { key } => run_config_get(key),
+            ConfigSubcommand::Set { key, value } => run_config_set(key, value),
+        },
+        Some(Subcommand::Audit(audit_cli)) => match audit_cli.action {
+            AuditSubcommand::Show { session, json } => run_audit_show(session, json),
+        },
+        Some(Subcommand::Sandbox(sandbox_cli)) => match sandbox_cli.action {
+            SandboxSubcommand::Exec(mut exec_cli) => {
+                prepend_config_flags(&mut exec_cli.config_overrides, cli.config_overrides);
+                run_sandbox_exec(exec_cli, codex_linux_sandbox_exe).await?;
+            }
+        },
+        Some(Subcommand::Doctor(mut doctor_cli)) => {
+            prepend_config_flags(&mut doctor_cli.config_overrides, cli.config_overrides);
+            run_doctor(doctor_cli, codex_linux_sandbox_exe).await;
+        }
+        Some(Subcommand::Git(git_cli)) => match git_cli.action {
+            GitSubcommand::CommitMsg(mut commit_msg_cli) => {
+                prepend_config_flags(&mut commit_msg_cli.config_overrides, cli.config_overrides);
+                run_commit_msg(commit_msg_cli).await;
+            }
+        },
+        Some(Subcommand::Oss(oss_cli)) => match oss_cli.action {
+            OssSubcommand::Models(mut models_cli) => {
+                prepend_config_flags(&mut models_cli.config_overrides, cli.config_overrides);
+                run_oss_models(models_cli).await;
+            }
+        },
         Some(Subcommand::Proto(mut proto_cli)) => {
             prepend_config_flags(&mut proto_cli.config_overrides, cli.config_overrides);
             proto::run_main(proto_cli).await?;
@@ -220,6 +391,9 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
         Some(Subcommand::GenerateTs(gen_cli)) => {
             codex_protocol_ts::generate_ts(&gen_cli.out_dir, gen_cli.prettier.as_deref())?;
         }
+        Some(Subcommand::GenerateJsonSchema(gen_cli)) => {
+            codex_protocol_ts::generate_json_schema(&gen_cli.out_dir)?;
+        }
     }
 
     Ok(())
@@ -0,0 +1,222 @@
+use std::io::BufRead;
+use std::io::Write;
+
+use clap::Parser;
+use codex_common::CliConfigOverrides;
+use codex_ollama::CliProgressReporter;
+use codex_ollama::LocalModel;
+use codex_ollama::OllamaClient;
+
+use crate::login::load_config_or_exit;
+
+#[derive(Debug, Parser)]
+pub struct ModelsCommand {
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub action: ModelsSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum ModelsSubcommand {
+    /// List the models installed in Ollama, with their size and
+    /// quantization.
+    List,
+
+    /// Download one or more models, e.g. `gpt-oss:20b`, in parallel.
+    /// Interrupted downloads are resumed.
+    Pull {
+        #[arg(required = true)]
+        models: Vec<String>,
+    },
+
+    /// Delete a model.
+    Rm {
+        model: String,
+
+        /// Do not ask for confirmation.
+        #[arg(long, short = 'y')]
+        yes: bool,
+    },
+
+    /// Print a model's family, format, size, quantization, context window,
+    /// capabilities and default parameters.
+    Show { model: String },
+}
+
+/// Manage the models of the local Ollama server configured as the `oss`
+/// provider.
+pub async fn run_oss_models(cmd: ModelsCommand) -> ! {
+    let ModelsCommand {
+        config_overrides,
+        action,
+    } = cmd;
+    let config = load_config_or_exit(config_overrides);
+    let client = match OllamaClient::try_from_oss_provider(&config).await {
+        Ok(client) => client,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+
+    let result = match action {
+        ModelsSubcommand::List => list_models(&client).await,
+        ModelsSubcommand::Pull { models } => {
+            let mut reporter = CliProgressReporter::new();
+            client.pull_many_with_reporter(&models, &mut reporter).await
+        }
+        ModelsSubcommand::Rm { model, yes } => {
+            if !yes && !confirm(&format!("Delete model {model}?")) {
+                eprintln!("Aborted.");
+                std::process::exit(1);
+            }
+            client
+                .delete_model(&model)
+                .await
+                .map(|()| println!("Deleted {model}."))
+        }
+        ModelsSubcommand::Show { model } => show_model(&client, &model).await,
+    };
+    match result {
+        Ok(()) => std::process::exit(0),
+        Err(e) => {
+            eprintln!("Error: {e}");
+            std::process::exit(1);
+        }
+    }
+}
+
+async fn list_models(client: &OllamaClient) -> std::io::Result<()> {
+    let models = client.list_local_models().await?;
+    if models.is_empty() {
+        println!("No models installed. Download one with `codex oss models pull <MODEL>`.");
+        return Ok(());
+    }
+    for line in format_model_table(&models) {
+        println!("{line}");
+    }
+    Ok(())
+}
+
+async fn show_model(client: &OllamaClient, model: &str) -> std::io::Result<()> {
+    let details = client.show_model(model).await?;
+    let capabilities = (!details.capabilities.is_empty()).then(|| details.capabilities.join(", "));
+    let fields = [
+        ("family", details.family.clone()),
+        ("format", details.format.clone()),
+        ("parameters", details.parameter_size.clone()),
+        ("quantization", details.quantization_level.clone()),
+        ("context", details.context_window().map(|c| c.to_string())),
+        ("capabilities", capabilities),
+    ];
+    for (label, value) in fields {
+        if let Some(value) = value {
+            println!("{label:<14}{value}");
+        }
+    }
+    if let Some(parameters) = details.parameters {
+        println!("\ndefaults:");
+        for line in parameters.lines() {
+            println!(
+                "  {}",
+                line.split_whitespace().collect::<Vec<_>>().join(" ")
+            );
+        }
+    }
+    if let Some(license) = details.license
+        && let Some(first_line) = license.lines().find(|l| !l.trim().is_empty())
+    {
+        println!("\nlicense: {}", first_line.trim());
+    }
+    Ok(())
+}
+
+/// One line per model under a header, with the columns aligned.
+fn format_model_table(models: &[LocalModel]) -> Vec<String> {
+    let mut rows = vec![[
+        "NAME".to_string(),
+        "SIZE".to_string(),
+        "PARAMS".to_string(),
+        "QUANT".to_string(),
+    ]];
+    for model in models {
+        rows.push([
+            model.name.clone(),
+            model.size.map(format_size).unwrap_or_default(),
+            model.parameter_size.clone().unwrap_or_default(),
+            model.quantization_level.clone().unwrap_or_default(),
+        ]);
+    }
+    let widths: Vec<usize> = (0..4)
+        .map(|col| rows.iter().map(|row| row[col].len()).max().unwrap_or(0))
+        .collect();
+    rows.iter()
+        .map(|row| {
+            row.iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{cell:<width$}"))
+                .collect::<Vec<_>>()
+                .join("  ")
+                .trim_end()
+                .to_string()
+        })
+        .collect()
+}
+
+fn format_size(bytes: u64) -> String {
+    const GB: f64 = 1024.0 * 1024.0 * 1024.0;
+    const MB: f64 = 1024.0 * 1024.0;
+    let bytes = bytes as f64;
+    if bytes >= GB {
+        format!("{:.1} GB", bytes / GB)
+    } else {
+        format!("{:.0} MB", bytes / MB)
+    }
+}
+
+/// Ask `question` on stderr and read a yes/no answer from stdin; anything
+/// but `y` or `yes` is a no.
+fn confirm(question: &str) -> bool {
+    eprint!("{question} [y/N] ");
+    let _ = std::io::stderr().flush();
+    let mut answer = String::new();
+    if std::io::stdin().lock().read_line(&mut answer).is_err() {
+        return false;
+    }
+    matches!(answer.trim().to_ascii_lowercase().as_str(), "y" | "yes")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn model_table_is_aligned() {
+        let models = vec![
+            LocalModel {
+                name: "gpt-oss:20b".to_string(),
+                size: Some(13_780_173_839),
+                parameter_size: Some("20.9B".to_string()),
+                quantization_level: Some("MXFP4".to_string()),
+                modified_at: None,
+            },
+            LocalModel {
+                name: "tiny".to_string(),
+                size: Some(50 * 1024 * 1024),
+                parameter_size: None,
+                quantization_level: None,
+                modified_at: None,
+            },
+        ];
+        assert_eq!(
+            format_model_table(&models),
+            vec![
+                "NAME         SIZE     PARAMS  QUANT".to_string(),
+                "gpt-oss:20b  12.8 GB  20.9B   MXFP4".to_string(),
+                "tiny         50 MB".to_string(),
+            ]
+        );
+    }
+}
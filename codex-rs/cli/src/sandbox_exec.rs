@@ -0,0 +1,248 @@
+//! `codex sandbox exec`: runs a command under the platform's sandbox with
+//! the policy the agent would use, and reports when the sandbox blocked it.
+
+use std::path::PathBuf;
+
+use clap::Parser;
+use codex_common::CliConfigOverrides;
+use codex_common::SandboxModeCliArg;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::exec::SandboxType;
+use codex_core::exec::sandbox_denial_lines;
+use codex_core::exec_env::create_env;
+use codex_core::get_platform_sandbox;
+use codex_core::landlock::spawn_command_under_linux_sandbox;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::seatbelt::spawn_command_under_seatbelt;
+use codex_core::spawn::StdioPolicy;
+use codex_core::windows_sandbox::spawn_command_under_windows_sandbox;
+use serde_json::json;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+
+use crate::exit_status::handle_exit_status;
+
+/// Output kept from each stream to look for denials in; the command's
+/// output is passed through in full.
+const MAX_CAPTURED_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Parser)]
+pub struct SandboxExecCommand {
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// Sandbox policy to run the command with. Defaults to `sandbox_mode`
+    /// from config.toml, as for the agent.
+    #[arg(long = "sandbox", short = 's', value_enum)]
+    pub sandbox_mode: Option<SandboxModeCliArg>,
+
+    /// Another directory the command may write to, in addition to those in
+    /// `sandbox_workspace_write.writable_roots`. Needs `workspace-write`.
+    #[arg(long = "writable-root", value_name = "DIR")]
+    pub writable_roots: Vec<PathBuf>,
+
+    /// Allow network access. Needs `workspace-write`.
+    #[arg(long = "network", default_value_t = false)]
+    pub network: bool,
+
+    /// Directory to run the command in, and the workspace for
+    /// `workspace-write`.
+    #[clap(long = "cd", short = 'C', value_name = "DIR")]
+    pub cwd: Option<PathBuf>,
+
+    /// Command to run, after `--`.
+    #[arg(trailing_var_arg = true, required = true)]
+    pub command: Vec<String>,
+}
+
+/// Run the command as the agent would run it: under the platform's sandbox,
+/// without stdin and with the agent's environment policy. Exits with the
+/// command's exit code. When the command failed and its output shows a
+/// sandbox restriction, a JSON denial report is printed as the last line of
+/// stderr.
+pub async fn run_sandbox_exec(
+    cmd: SandboxExecCommand,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let SandboxExecCommand {
+        config_overrides,
+        sandbox_mode,
+        writable_roots,
+        network,
+        cwd,
+        command,
+    } = cmd;
+    let mut config = Config::load_with_cli_overrides(
+        config_overrides
+            .parse_overrides()
+            .map_err(anyhow::Error::msg)?,
+        ConfigOverrides {
+            sandbox_mode: sandbox_mode.map(Into::into),
+            cwd,
+            codex_linux_sandbox_exe,
+            ..Default::default()
+        },
+    )?;
+    if !writable_roots.is_empty() || network {
+        let SandboxPolicy::WorkspaceWrite {
+            writable_roots: policy_roots,
+            network_access,
+            ..
+        } = &mut config.sandbox_policy
+        else {
+            anyhow::bail!("--writable-root and --network need `--sandbox workspace-write`");
+        };
+        policy_roots.extend(writable_roots.iter().map(|root| config.cwd.join(root)));
+        *network_access |= network;
+    }
+
+    let policy = &config.sandbox_policy;
+    let cwd = config.cwd.clone();
+    let env = create_env(&config.shell_environment_policy);
+    let stdio_policy = StdioPolicy::RedirectForShellTool;
+    let limits = &config.exec_limits;
+    let (backend, child) = match get_platform_sandbox() {
+        Some(SandboxType::MacosSeatbelt) => (
+            "seatbelt",
+            spawn_command_under_seatbelt(
+                command.clone(),
+                policy,
+                &config.seatbelt_extensions,
+                cwd,
+                stdio_policy,
+                env,
+                limits,
+            )
+            .await?,
+        ),
+        Some(SandboxType::LinuxSeccomp) => {
+            let Some(sandbox_exe) = &config.codex_linux_sandbox_exe else {
+                anyhow::bail!("codex-linux-sandbox executable not found");
+            };
+            (
+                "landlock",
+                spawn_command_under_linux_sandbox(
+                    sandbox_exe,
+                    command.clone(),
+                    policy,
+                    cwd,
+                    stdio_policy,
+                    env,
+                    limits,
+                    None,
+                )
+                .await?,
+            )
+        }
+        Some(SandboxType::WindowsRestrictedToken) => (
+            "windows",
+            spawn_command_under_windows_sandbox(
+                command.clone(),
+                policy,
+                cwd,
+                stdio_policy,
+                env,
+                limits,
+            )
+            .await?,
+        ),
+        _ => anyhow::bail!("no sandbox is available on this platform"),
+    };
+
+    let (status, stdout, stderr) = wait_with_tee(child).await?;
+    if !status.success() {
+        let evidence: Vec<&str> = sandbox_denial_lines(&stderr)
+            .into_iter()
+            .chain(sandbox_denial_lines(&stdout))
+            .collect();
+        if !evidence.is_empty() {
+            let report = denial_report(backend, &command, &config, status, &evidence);
+            eprintln!("{report}");
+        }
+    }
+    handle_exit_status(status);
+}
+
+/// Pass the child's output through to ours while keeping the end of each
+/// stream.
+async fn wait_with_tee(
+    mut child: tokio::process::Child,
+) -> std::io::Result<(std::process::ExitStatus, String, String)> {
+    let stdout = child.stdout.take();
+    let stderr = child.stderr.take();
+    let (stdout, stderr) = tokio::try_join!(
+        tee(stdout, tokio::io::stdout()),
+        tee(stderr, tokio::io::stderr())
+    )?;
+    let status = child.wait().await?;
+    Ok((
+        status,
+        String::from_utf8_lossy(&stdout).into_owned(),
+        String::from_utf8_lossy(&stderr).into_owned(),
+    ))
+}
+
+async fn tee<R, W>(reader: Option<R>, mut writer: W) -> std::io::Result<Vec<u8>>
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut captured = Vec::new();
+    let Some(mut reader) = reader else {
+        return Ok(captured);
+    };
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = reader.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        writer.write_all(&buf[..n]).await?;
+        writer.flush().await?;
+        captured.extend_from_slice(&buf[..n]);
+        if captured.len() > MAX_CAPTURED_BYTES {
+            captured.drain(..captured.len() - MAX_CAPTURED_BYTES);
+        }
+    }
+    Ok(captured)
+}
+
+fn denial_report(
+    backend: &str,
+    command: &[String],
+    config: &Config,
+    status: std::process::ExitStatus,
+    evidence: &[&str],
+) -> serde_json::Value {
+    #[cfg(unix)]
+    let signal = std::os::unix::process::ExitStatusExt::signal(&status);
+    #[cfg(not(unix))]
+    let signal: Option<i32> = None;
+    json!({
+        "type": "sandbox_denial",
+        "sandbox": backend,
+        "command": command,
+        "cwd": config.cwd,
+        "exit_code": status.code(),
+        "signal": signal,
+        "policy": config.sandbox_policy,
+        "evidence": evidence,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn tee_passes_everything_through_and_keeps_the_end() {
+        let input = vec![b'x'; MAX_CAPTURED_BYTES + 10];
+        let mut output = Vec::new();
+        let captured = tee(Some(input.as_slice()), &mut output).await.unwrap();
+        assert_eq!(output, input);
+        assert_eq!(captured.len(), MAX_CAPTURED_BYTES);
+    }
+}
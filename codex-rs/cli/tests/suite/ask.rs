@@ -0,0 +1,95 @@
+// e2e tests that spawn the CLI are currently disabled on Windows.
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use assert_cmd::prelude::*;
+use core_test_support::load_sse_fixture_with_id_from_str;
+use serde_json::Value;
+use std::process::Command;
+use tempfile::TempDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+const ANSWER_SSE: &str = r#"[
+  {
+    "type": "response.output_item.done",
+    "item": {
+      "type": "message",
+      "role": "assistant",
+      "content": [{ "type": "output_text", "text": "It is parsed in src/lib.rs:12." }]
+    }
+  },
+  {
+    "type": "response.completed",
+    "response": { "id": "__ID__", "output": [] }
+  }
+]"#;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn ask_prints_only_the_answer_from_a_read_only_session() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id_from_str(ANSWER_SSE, "resp1"),
+                    "text/event-stream",
+                ),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+
+    let cwd = TempDir::new().unwrap();
+    let codex_home = TempDir::new().unwrap();
+    Command::cargo_bin("codex")
+        .unwrap()
+        .current_dir(cwd.path())
+        .env("CODEX_HOME", codex_home.path())
+        .env("OPENAI_API_KEY", "dummy")
+        .env("OPENAI_BASE_URL", format!("{}/v1", server.uri()))
+        .args([
+            "ask",
+            "--skip-git-repo-check",
+            "where is the config parsed?",
+        ])
+        .assert()
+        .success()
+        .stdout("It is parsed in src/lib.rs:12.\n");
+
+    let requests = server.received_requests().await.unwrap();
+    let body = requests[0].body_json::<Value>().unwrap();
+    let tool_names: Vec<&str> = body["tools"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter_map(|tool| tool["name"].as_str())
+        .collect();
+    assert!(!tool_names.contains(&"update_plan"), "{tool_names:?}");
+    assert!(!tool_names.contains(&"apply_patch"), "{tool_names:?}");
+
+    let texts: Vec<&str> = body["input"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .flat_map(|item| item["content"].as_array().into_iter().flatten())
+        .filter_map(|content| content["text"].as_str())
+        .collect();
+    assert!(
+        texts
+            .iter()
+            .any(|text| text.contains("<sandbox_mode>read-only</sandbox_mode>")),
+        "{texts:?}"
+    );
+    assert!(
+        texts
+            .iter()
+            .any(|text| text.ends_with("Question:\nwhere is the config parsed?")),
+        "{texts:?}"
+    );
+}
@@ -59,19 +59,7 @@ impl CliConfigOverrides {
                     return Err(format!("Empty key in override: {s}"));
                 }
 
-                // Attempt to parse as JSON. If that fails, treat it as a raw
-                // string. This allows convenient usage such as
-                // `-c model=o3` without the quotes.
-                let value: Value = match parse_toml_value(value_str) {
-                    Ok(v) => v,
-                    Err(_) => {
-                        // Strip leading/trailing quotes if present
-                        let trimmed = value_str.trim().trim_matches(|c| c == '"' || c == '\'');
-                        Value::String(trimmed.to_string())
-                    }
-                };
-
-                Ok((key.to_string(), value))
+                Ok((key.to_string(), parse_config_value(value_str)))
             })
             .collect()
     }
@@ -132,6 +120,20 @@ fn apply_single_override(root: &mut Value, path: &str, value: Value) {
     }
 }
 
+/// Parse a value given on the command line as TOML. If that fails, treat it
+/// as a raw string. This allows convenient usage such as `-c model=o3`
+/// without the quotes.
+pub fn parse_config_value(raw: &str) -> Value {
+    match parse_toml_value(raw) {
+        Ok(v) => v,
+        Err(_) => {
+            // Strip leading/trailing quotes if present
+            let trimmed = raw.trim().trim_matches(|c| c == '"' || c == '\'');
+            Value::String(trimmed.to_string())
+        }
+    }
+}
+
 fn parse_toml_value(raw: &str) -> Result<Value, toml::de::Error> {
     let wrapped = format!("_x_ = {raw}");
     let table: toml::Table = toml::from_str(&wrapped)?;
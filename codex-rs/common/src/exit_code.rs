@@ -0,0 +1,68 @@
+//! Exit codes shared by the `codex` frontends (`codex exec` and the TUI), so
+//! that scripts wrapping them can tell failures apart without parsing output.
+
+use codex_protocol::protocol::ErrorKind;
+
+/// Process exit codes. The numeric values are a stable contract; see
+/// `docs/exit_codes.md`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExitCode {
+    Success,
+    /// The task failed for a reason not covered below.
+    TaskFailed,
+    /// Invalid configuration or command-line arguments.
+    ConfigError,
+    /// Missing or rejected credentials.
+    AuthFailure,
+    /// The configured sandbox or execution backend cannot be used.
+    SandboxUnavailable,
+    /// A usage limit or budget was reached.
+    BudgetExceeded,
+    /// The model provider returned an error.
+    ModelError,
+    /// A command was denied by the sandbox.
+    SandboxDenied,
+    /// A patch failed to apply.
+    PatchFailed,
+    /// The task completed, but something went wrong along the way.
+    SuccessWithWarnings,
+    /// The run was interrupted with Ctrl-C.
+    Interrupted,
+}
+
+impl ExitCode {
+    pub fn code(self) -> i32 {
+        match self {
+            ExitCode::Success => 0,
+            ExitCode::TaskFailed => 1,
+            ExitCode::ConfigError => 2,
+            ExitCode::AuthFailure => 3,
+            ExitCode::SandboxUnavailable => 4,
+            ExitCode::BudgetExceeded => 5,
+            ExitCode::ModelError => 6,
+            ExitCode::SandboxDenied => 7,
+            ExitCode::PatchFailed => 8,
+            ExitCode::SuccessWithWarnings => 9,
+            // 128 + SIGINT, as a shell reports for a process killed by Ctrl-C.
+            ExitCode::Interrupted => 130,
+        }
+    }
+
+    /// Exit the process with this code.
+    pub fn exit(self) -> ! {
+        std::process::exit(self.code())
+    }
+}
+
+impl From<ErrorKind> for ExitCode {
+    fn from(kind: ErrorKind) -> Self {
+        match kind {
+            ErrorKind::Auth => ExitCode::AuthFailure,
+            ErrorKind::Config => ExitCode::ConfigError,
+            ErrorKind::SandboxUnavailable => ExitCode::SandboxUnavailable,
+            ErrorKind::BudgetExceeded => ExitCode::BudgetExceeded,
+            ErrorKind::Model => ExitCode::ModelError,
+            ErrorKind::Other => ExitCode::TaskFailed,
+        }
+    }
+}
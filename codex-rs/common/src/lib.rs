@@ -19,6 +19,9 @@ mod config_override;
 #[cfg(feature = "cli")]
 pub use config_override::CliConfigOverrides;
 
+#[cfg(feature = "cli")]
+pub use config_override::parse_config_value;
+
 mod sandbox_summary;
 
 #[cfg(feature = "sandbox_summary")]
@@ -26,6 +29,9 @@ pub use sandbox_summary::summarize_sandbox_policy;
 
 mod config_summary;
 
+// Exit codes shared by `codex exec` and the TUI.
+pub mod exit_code;
+
 pub use config_summary::create_config_summary_entries;
 // Shared fuzzy matcher (used by TUI selection popups and other UI filtering)
 pub mod fuzzy_match;
@@ -1,3 +1,4 @@
+use crate::codex::ApprovalResponse;
 use crate::codex::Session;
 use crate::codex::TurnContext;
 use crate::protocol::FileChange;
@@ -31,6 +32,9 @@ pub(crate) enum InternalApplyPatchInvocation {
 pub(crate) struct ApplyPatchExec {
     pub(crate) action: ApplyPatchAction,
     pub(crate) user_explicitly_approved_this_action: bool,
+    /// Files whose changes the user rejected while approving the rest; they
+    /// have been dropped from `action`.
+    pub(crate) rejected_files: Vec<PathBuf>,
 }
 
 impl From<ResponseInputItem> for InternalApplyPatchInvocation {
@@ -56,6 +60,7 @@ pub(crate) async fn apply_patch(
             InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
                 action,
                 user_explicitly_approved_this_action: false,
+                rejected_files: Vec::new(),
             })
         }
         SafetyCheck::AskUser => {
@@ -69,23 +74,24 @@ pub(crate) async fn apply_patch(
             let rx_approve = sess
                 .request_patch_approval(sub_id.to_owned(), call_id.to_owned(), &action, None, None)
                 .await;
-            match rx_approve.await.unwrap_or_default() {
+            let ApprovalResponse {
+                decision,
+                accepted_files,
+            } = rx_approve.await.unwrap_or_default();
+            match decision {
                 ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {
-                    InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
-                        action,
-                        user_explicitly_approved_this_action: true,
-                    })
-                }
-                ReviewDecision::Denied | ReviewDecision::Abort => {
-                    ResponseInputItem::FunctionCallOutput {
-                        call_id: call_id.to_owned(),
-                        output: FunctionCallOutputPayload {
-                            content: "patch rejected by user".to_string(),
-                            success: Some(false),
-                        },
+                    match accepted_files {
+                        Some(accepted_files) => {
+                            apply_accepted_files(call_id, action, &accepted_files)
+                        }
+                        None => InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
+                            action,
+                            user_explicitly_approved_this_action: true,
+                            rejected_files: Vec::new(),
+                        }),
                     }
-                    .into()
                 }
+                ReviewDecision::Denied | ReviewDecision::Abort => rejected_by_user(call_id),
             }
         }
         SafetyCheck::Reject { reason } => ResponseInputItem::FunctionCallOutput {
@@ -99,6 +105,65 @@ pub(crate) async fn apply_patch(
     }
 }
 
+/// Narrows an approved `action` to the files the user accepted.
+fn apply_accepted_files(
+    call_id: &str,
+    mut action: ApplyPatchAction,
+    accepted_files: &[PathBuf],
+) -> InternalApplyPatchInvocation {
+    let mut rejected_files: Vec<PathBuf> = action
+        .changes()
+        .keys()
+        .filter(|path| !accepted_files.contains(path))
+        .cloned()
+        .collect();
+    rejected_files.sort();
+    if let Err(e) = action.retain_paths(accepted_files) {
+        return ResponseInputItem::FunctionCallOutput {
+            call_id: call_id.to_owned(),
+            output: FunctionCallOutputPayload {
+                content: format!("failed to apply the accepted part of the patch: {e}"),
+                success: Some(false),
+            },
+        }
+        .into();
+    }
+    if action.is_empty() {
+        return rejected_by_user(call_id);
+    }
+    InternalApplyPatchInvocation::DelegateToExec(ApplyPatchExec {
+        action,
+        user_explicitly_approved_this_action: true,
+        rejected_files,
+    })
+}
+
+fn rejected_by_user(call_id: &str) -> InternalApplyPatchInvocation {
+    ResponseInputItem::FunctionCallOutput {
+        call_id: call_id.to_owned(),
+        output: FunctionCallOutputPayload {
+            content: "patch rejected by user".to_string(),
+            success: Some(false),
+        },
+    }
+    .into()
+}
+
+/// Tells the model which files were left out of a partially approved patch.
+pub(crate) fn rejected_files_note(rejected_files: &[PathBuf]) -> Option<String> {
+    if rejected_files.is_empty() {
+        return None;
+    }
+    let files = rejected_files
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ");
+    Some(format!(
+        "The user rejected the changes to {files}; they were not applied."
+    ))
+}
+
 pub(crate) fn convert_apply_patch_to_protocol(
     action: &ApplyPatchAction,
 ) -> HashMap<PathBuf, FileChange> {
@@ -123,3 +188,49 @@ pub(crate) fn convert_apply_patch_to_protocol(
     }
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_apply_patch::MaybeApplyPatchVerified;
+    use codex_apply_patch::maybe_parse_apply_patch_verified;
+    use pretty_assertions::assert_eq;
+
+    fn action(cwd: &std::path::Path) -> ApplyPatchAction {
+        let patch =
+            "*** Begin Patch\n*** Add File: a.txt\n+a\n*** Add File: b.txt\n+b\n*** End Patch";
+        let argv = vec!["apply_patch".to_string(), patch.to_string()];
+        match maybe_parse_apply_patch_verified(&argv, cwd) {
+            MaybeApplyPatchVerified::Body(action) => action,
+            other => panic!("expected a patch, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn partial_approval_applies_only_accepted_files() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let accepted = [dir.path().join("b.txt")];
+        let InternalApplyPatchInvocation::DelegateToExec(exec) =
+            apply_accepted_files("call", action(dir.path()), &accepted)
+        else {
+            panic!("expected the accepted file to be applied");
+        };
+        assert_eq!(
+            exec.action.patch,
+            "*** Begin Patch\n*** Add File: b.txt\n+b\n*** End Patch"
+        );
+        assert_eq!(exec.rejected_files, vec![dir.path().join("a.txt")]);
+        assert_eq!(
+            rejected_files_note(&exec.rejected_files),
+            Some(format!(
+                "The user rejected the changes to {}; they were not applied.",
+                dir.path().join("a.txt").display()
+            ))
+        );
+
+        assert!(matches!(
+            apply_accepted_files("call", action(dir.path()), &[]),
+            InternalApplyPatchInvocation::Output(_)
+        ));
+    }
+}
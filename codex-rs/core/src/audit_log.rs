@@ -0,0 +1,445 @@
+//! Append-only audit log of the actions a session took, for compliance
+//! review.
+//!
+//! Where the rollout records the conversation, the audit log records what
+//! the agent did on the user's behalf and who allowed it: approval
+//! decisions, commands with their exit status, applied patches and the
+//! network requests Codex itself makes. Each session appends to
+//! `$CODEX_HOME/audit/<session-id>.jsonl`, one [`AuditRecord`] per line:
+//!
+//! ````text
+//! {"ts":"2025-08-20T12:00:00.123Z","session_id":"<uuid>","type":"command",...}
+//! ````
+//!
+//! The fields of each `type` are those of the matching [`AuditEvent`]
+//! variant and are listed in `config.md`. A record is written before the
+//! session moves on, so a crash cannot lose the record of an action that
+//! already happened. When `[encryption]` is enabled each line is encrypted;
+//! see [`crate::encryption`].
+
+use std::collections::HashMap;
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::codex::MutexExt;
+use crate::encryption::LineCipher;
+use crate::encryption::decode_line;
+use crate::exec::SandboxType;
+use crate::protocol::AskForApproval;
+use crate::protocol::ExecKilledReason;
+use crate::protocol::FileChange;
+use crate::protocol::ReviewDecision;
+use crate::protocol::SandboxPolicy;
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+/// Directory inside `CODEX_HOME` that holds the audit logs.
+const AUDIT_SUBDIR: &str = "audit";
+
+/// One line of an audit log.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditRecord {
+    /// When the record was written, in RFC 3339 format (UTC).
+    pub ts: String,
+    pub session_id: Uuid,
+    #[serde(flatten)]
+    pub event: AuditEvent,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AuditEvent {
+    /// The session started, or was resumed.
+    SessionStart {
+        cwd: PathBuf,
+        model: String,
+        model_provider: String,
+        approval_policy: AskForApproval,
+        sandbox_policy: SandboxPolicy,
+        resumed: bool,
+    },
+
+    /// The user answered a request to run a command.
+    CommandApproval {
+        call_id: String,
+        command: Vec<String>,
+        cwd: PathBuf,
+        decision: ReviewDecision,
+    },
+
+    /// The user answered a request to apply a patch.
+    PatchApproval {
+        call_id: String,
+        files: Vec<PathBuf>,
+        decision: ReviewDecision,
+    },
+
+    /// A command run for a shell tool call finished.
+    Command {
+        call_id: String,
+        command: Vec<String>,
+        cwd: PathBuf,
+        /// See [`sandbox_name`].
+        sandbox: String,
+        /// Whether the command was allowed to use the network.
+        network_access: bool,
+        exit_code: i32,
+        duration_ms: u64,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        killed_reason: Option<ExecKilledReason>,
+    },
+
+    /// An interactive exec session was closed.
+    ExecSession {
+        exec_session_id: u32,
+        command: Vec<String>,
+        /// `None` when the session was closed before the process exited.
+        exit_code: Option<i32>,
+    },
+
+    /// A background task stopped running.
+    BackgroundTask {
+        task_id: u64,
+        command: Vec<String>,
+        cwd: PathBuf,
+        sandbox: String,
+        /// `None` when the task was killed.
+        exit_code: Option<i32>,
+    },
+
+    /// A patch was applied, or failed to apply.
+    Patch {
+        call_id: String,
+        /// True when no one was asked to approve the patch.
+        auto_approved: bool,
+        success: bool,
+        files: Vec<PatchFileSummary>,
+    },
+
+    /// Codex sent a request over the network.
+    NetworkRequest {
+        /// The URL, without its query string.
+        target: String,
+        /// What the request was for, e.g. `turn`.
+        purpose: String,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PatchFileSummary {
+    pub path: PathBuf,
+    pub action: PatchFileAction,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub move_path: Option<PathBuf>,
+    pub added_lines: usize,
+    pub removed_lines: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PatchFileAction {
+    Add,
+    Delete,
+    Update,
+}
+
+/// What an approval request is for, kept until the user decides so the
+/// decision can be recorded with it.
+pub(crate) enum ApprovalSubject {
+    Command { command: Vec<String>, cwd: PathBuf },
+    Patch { files: Vec<PathBuf> },
+}
+
+impl ApprovalSubject {
+    /// `"exec"` or `"patch"`.
+    pub(crate) fn kind(&self) -> &'static str {
+        match self {
+            ApprovalSubject::Command { .. } => "exec",
+            ApprovalSubject::Patch { .. } => "patch",
+        }
+    }
+
+    pub(crate) fn decided(self, call_id: String, decision: ReviewDecision) -> AuditEvent {
+        match self {
+            ApprovalSubject::Command { command, cwd } => AuditEvent::CommandApproval {
+                call_id,
+                command,
+                cwd,
+                decision,
+            },
+            ApprovalSubject::Patch { files } => AuditEvent::PatchApproval {
+                call_id,
+                files,
+                decision,
+            },
+        }
+    }
+}
+
+/// Appends [`AuditRecord`]s for one session.
+pub(crate) struct AuditLog {
+    session_id: Uuid,
+    file: Mutex<File>,
+    cipher: Option<LineCipher>,
+}
+
+impl AuditLog {
+    /// Open the audit log of `session_id` for appending, creating it if
+    /// needed. A resumed session appends to the log it started.
+    pub(crate) fn open(codex_home: &Path, session_id: Uuid, encrypt: bool) -> io::Result<Self> {
+        let path = audit_log_path(codex_home, &session_id);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut options = OpenOptions::new();
+        options.append(true).create(true);
+        #[cfg(unix)]
+        {
+            options.mode(0o600);
+        }
+        let file = options.open(&path)?;
+        let cipher = if encrypt {
+            Some(LineCipher::from_keychain()?)
+        } else {
+            None
+        };
+        Ok(Self {
+            session_id,
+            file: Mutex::new(file),
+            cipher,
+        })
+    }
+
+    /// Append `event`. Failures are logged rather than interrupting the
+    /// session.
+    pub(crate) fn record(&self, event: AuditEvent) {
+        if let Err(e) = self.append(event) {
+            warn!("failed to write audit record: {e}");
+        }
+    }
+
+    fn append(&self, event: AuditEvent) -> io::Result<()> {
+        let record = AuditRecord {
+            ts: chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true),
+            session_id: self.session_id,
+            event,
+        };
+        let mut line = serde_json::to_string(&record)?;
+        if let Some(cipher) = &self.cipher {
+            line = cipher.encrypt_line(&line)?;
+        }
+        line.push('\n');
+        // One write per record, so records from concurrent tool calls do not
+        // interleave.
+        self.file.lock_unchecked().write_all(line.as_bytes())
+    }
+}
+
+pub fn audit_log_path(codex_home: &Path, session_id: &Uuid) -> PathBuf {
+    codex_home
+        .join(AUDIT_SUBDIR)
+        .join(format!("{session_id}.jsonl"))
+}
+
+/// Find the audit log of the session whose id is, or starts with, `session`.
+pub fn find_audit_log(codex_home: &Path, session: &str) -> io::Result<PathBuf> {
+    let dir = codex_home.join(AUDIT_SUBDIR);
+    let paths: Vec<PathBuf> = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .collect(),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+        Err(e) => return Err(e),
+    };
+    let mut matches: Vec<PathBuf> = paths
+        .into_iter()
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "jsonl")
+                && path
+                    .file_stem()
+                    .and_then(|stem| stem.to_str())
+                    .is_some_and(|stem| stem.starts_with(session))
+        })
+        .collect();
+    match matches.len() {
+        0 => Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!("no audit log for session `{session}` in {}", dir.display()),
+        )),
+        1 => Ok(matches.remove(0)),
+        n => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("`{session}` matches {n} sessions; give more of the session id"),
+        )),
+    }
+}
+
+/// Read every record of the audit log at `path`, decrypting encrypted lines.
+pub fn read_audit_log(path: &Path) -> io::Result<Vec<AuditRecord>> {
+    let contents = std::fs::read_to_string(path)?;
+    let mut cipher = None;
+    let mut records = Vec::new();
+    for (index, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line = decode_line(line, &mut cipher)?;
+        let record = serde_json::from_str(&line).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}:{}: {e}", path.display(), index + 1),
+            )
+        })?;
+        records.push(record);
+    }
+    Ok(records)
+}
+
+/// How a command was sandboxed, as recorded in the `sandbox` field.
+pub(crate) fn sandbox_name(sandbox_type: SandboxType) -> String {
+    match sandbox_type {
+        SandboxType::None => "none",
+        SandboxType::MacosSeatbelt => "macos_seatbelt",
+        SandboxType::LinuxSeccomp => "linux_seccomp",
+        SandboxType::WindowsRestrictedToken => "windows_restricted_token",
+        SandboxType::Container => "container",
+    }
+    .to_string()
+}
+
+/// The files a patch touches, sorted by path, with line counts.
+pub(crate) fn summarize_patch(changes: &HashMap<PathBuf, FileChange>) -> Vec<PatchFileSummary> {
+    let mut files: Vec<PatchFileSummary> = changes
+        .iter()
+        .map(|(path, change)| {
+            let (action, move_path, added_lines, removed_lines) = match change {
+                FileChange::Add { content } => {
+                    (PatchFileAction::Add, None, content.lines().count(), 0)
+                }
+                FileChange::Delete => (PatchFileAction::Delete, None, 0, 0),
+                FileChange::Update {
+                    unified_diff,
+                    move_path,
+                } => {
+                    let (added, removed) = count_diff_lines(unified_diff);
+                    (PatchFileAction::Update, move_path.clone(), added, removed)
+                }
+            };
+            PatchFileSummary {
+                path: path.clone(),
+                action,
+                move_path,
+                added_lines,
+                removed_lines,
+            }
+        })
+        .collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+    files
+}
+
+fn count_diff_lines(unified_diff: &str) -> (usize, usize) {
+    let mut added = 0;
+    let mut removed = 0;
+    for line in unified_diff.lines() {
+        if line.starts_with('+') && !line.starts_with("+++") {
+            added += 1;
+        } else if line.starts_with('-') && !line.starts_with("---") {
+            removed += 1;
+        }
+    }
+    (added, removed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    #[test]
+    fn records_round_trip_and_are_found_by_id_prefix() {
+        let codex_home = TempDir::new().expect("tempdir");
+        let session_id = Uuid::new_v4();
+        let log = AuditLog::open(codex_home.path(), session_id, false).expect("open audit log");
+        let approval = ApprovalSubject::Command {
+            command: vec!["cargo".to_string(), "publish".to_string()],
+            cwd: PathBuf::from("/repo"),
+        }
+        .decided("call-1".to_string(), ReviewDecision::Denied);
+        let request = AuditEvent::NetworkRequest {
+            target: "https://api.openai.com/v1/responses".to_string(),
+            purpose: "turn".to_string(),
+        };
+        log.record(approval.clone());
+        log.record(request.clone());
+
+        let prefix = &session_id.to_string()[..8];
+        let path = find_audit_log(codex_home.path(), prefix).expect("find audit log");
+        let records = read_audit_log(&path).expect("read audit log");
+
+        assert_eq!(
+            records
+                .into_iter()
+                .map(|record| (record.session_id, record.event))
+                .collect::<Vec<_>>(),
+            vec![(session_id, approval), (session_id, request)]
+        );
+        let line = std::fs::read_to_string(&path).expect("read file");
+        assert!(line.contains(r#""type":"command_approval""#), "{line}");
+        assert!(line.contains(r#""decision":"denied""#), "{line}");
+    }
+
+    #[test]
+    fn summarizes_patch_changes() {
+        let changes = HashMap::from([
+            (
+                PathBuf::from("b.rs"),
+                FileChange::Update {
+                    unified_diff:
+                        "--- a/b.rs\n+++ b/b.rs\n@@ -1,2 +1,2 @@\n-old\n+new\n+more\n ctx\n"
+                            .to_string(),
+                    move_path: Some(PathBuf::from("c.rs")),
+                },
+            ),
+            (
+                PathBuf::from("a.rs"),
+                FileChange::Add {
+                    content: "one\ntwo\n".to_string(),
+                },
+            ),
+        ]);
+
+        assert_eq!(
+            summarize_patch(&changes),
+            vec![
+                PatchFileSummary {
+                    path: PathBuf::from("a.rs"),
+                    action: PatchFileAction::Add,
+                    move_path: None,
+                    added_lines: 2,
+                    removed_lines: 0,
+                },
+                PatchFileSummary {
+                    path: PathBuf::from("b.rs"),
+                    action: PatchFileAction::Update,
+                    move_path: Some(PathBuf::from("c.rs")),
+                    added_lines: 2,
+                    removed_lines: 1,
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,490 @@
+//! Long-running commands, such as dev servers, that keep running in the
+//! background while the conversation continues.
+//!
+//! Each task keeps the tail of its output so it can be inspected later, and
+//! an [`EventMsg::BackgroundTaskUpdate`] is sent when it stops running.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::ExitStatus;
+use std::sync::Arc;
+use std::sync::Mutex as StdMutex;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use async_channel::Sender;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::process::Child;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+use tracing::warn;
+
+use crate::audit_log::AuditEvent;
+use crate::audit_log::AuditLog;
+use crate::audit_log::sandbox_name;
+use crate::codex::MutexExt;
+use crate::config_types::ExecLimits;
+use crate::error::CodexErr;
+use crate::error::Result;
+use crate::exec::SandboxType;
+use crate::exec::kill_child;
+use crate::landlock::spawn_command_under_linux_sandbox;
+use crate::protocol::BackgroundTaskInfo;
+use crate::protocol::BackgroundTaskStatus;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::SandboxPolicy;
+use crate::seatbelt::SeatbeltExtensions;
+use crate::seatbelt::spawn_command_under_seatbelt;
+use crate::spawn::SpawnChildRequest;
+use crate::spawn::StdioPolicy;
+use crate::spawn::spawn_child_async;
+
+/// How much of a task's most recent output is kept.
+const OUTPUT_TAIL_MAX_BYTES: usize = 64 * 1024; // 64 KiB
+const READ_CHUNK_SIZE: usize = 8192;
+
+/// How long to keep reading output once a task's process has exited.
+/// Anything it left running that still holds the pipes open is abandoned.
+const OUTPUT_DRAIN_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Everything needed to start a background task.
+pub(crate) struct BackgroundTaskSpawn<'a> {
+    pub command: Vec<String>,
+    pub cwd: PathBuf,
+    pub env: HashMap<String, String>,
+    pub sandbox_type: SandboxType,
+    pub sandbox_policy: &'a SandboxPolicy,
+    pub seatbelt_extensions: &'a SeatbeltExtensions,
+    pub codex_linux_sandbox_exe: &'a Option<PathBuf>,
+    pub limits: &'a ExecLimits,
+    /// Where to record the task's exit status, if anywhere.
+    pub audit_log: Option<Arc<AuditLog>>,
+}
+
+#[derive(Default)]
+pub(crate) struct BackgroundTaskManager {
+    next_id: AtomicU64,
+    tasks: StdMutex<BTreeMap<u64, BackgroundTask>>,
+}
+
+struct BackgroundTask {
+    command: Vec<String>,
+    cwd: PathBuf,
+    state: Arc<StdMutex<TaskState>>,
+    kill: CancellationToken,
+    waiter: Option<JoinHandle<()>>,
+}
+
+struct TaskState {
+    status: BackgroundTaskStatus,
+    output_tail: Vec<u8>,
+}
+
+impl TaskState {
+    fn append_output(&mut self, chunk: &[u8]) {
+        self.output_tail.extend_from_slice(chunk);
+        let excess = self.output_tail.len().saturating_sub(OUTPUT_TAIL_MAX_BYTES);
+        self.output_tail.drain(..excess);
+    }
+}
+
+impl BackgroundTask {
+    fn info(&self, id: u64) -> BackgroundTaskInfo {
+        task_info(id, &self.command, &self.cwd, &self.state)
+    }
+}
+
+fn task_info(
+    id: u64,
+    command: &[String],
+    cwd: &Path,
+    state: &StdMutex<TaskState>,
+) -> BackgroundTaskInfo {
+    let state = state.lock_unchecked();
+    BackgroundTaskInfo {
+        id,
+        command: command.to_vec(),
+        cwd: cwd.to_path_buf(),
+        status: state.status.clone(),
+        output_tail: String::from_utf8_lossy(&state.output_tail).into_owned(),
+    }
+}
+
+impl BackgroundTaskManager {
+    /// Starts a task and returns its initial info. The info is sent on
+    /// `tx_event` under `sub_id` when the task starts and again when it stops
+    /// running.
+    pub(crate) async fn spawn(
+        &self,
+        request: BackgroundTaskSpawn<'_>,
+        sub_id: String,
+        tx_event: Sender<Event>,
+    ) -> Result<BackgroundTaskInfo> {
+        let command = request.command.clone();
+        let cwd = request.cwd.clone();
+        let audit_log = request.audit_log.clone();
+        let sandbox = sandbox_name(request.sandbox_type);
+        let mut child = spawn_child(request).await?;
+        let (Some(stdout), Some(stderr)) = (child.stdout.take(), child.stderr.take()) else {
+            return Err(CodexErr::Io(io::Error::other(
+                "background task was spawned without output pipes",
+            )));
+        };
+
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let state = Arc::new(StdMutex::new(TaskState {
+            status: BackgroundTaskStatus::Running,
+            output_tail: Vec::new(),
+        }));
+        let kill = CancellationToken::new();
+        let stop_reading = CancellationToken::new();
+        let readers = [
+            tokio::spawn(read_into_tail(
+                stdout,
+                Arc::clone(&state),
+                stop_reading.clone(),
+            )),
+            tokio::spawn(read_into_tail(
+                stderr,
+                Arc::clone(&state),
+                stop_reading.clone(),
+            )),
+        ];
+
+        // Announce the task before its waiter can report that it exited.
+        let info = task_info(id, &command, &cwd, &state);
+        let event = Event {
+            id: sub_id.clone(),
+            msg: EventMsg::BackgroundTaskUpdate(info.clone()),
+        };
+        if let Err(e) = tx_event.send(event).await {
+            warn!("failed to send BackgroundTaskUpdate event: {e}");
+        }
+
+        let waiter = tokio::spawn({
+            let command = command.clone();
+            let cwd = cwd.clone();
+            let state = Arc::clone(&state);
+            let kill = kill.clone();
+            async move {
+                let status = tokio::select! {
+                    status = child.wait() => exited_status(status),
+                    () = kill.cancelled() => {
+                        if let Err(e) = kill_child(&mut child, &stop_reading) {
+                            warn!("failed to kill background task {id}: {e}");
+                        }
+                        let _ = child.wait().await;
+                        BackgroundTaskStatus::Killed
+                    }
+                };
+                let _ = tokio::time::timeout(OUTPUT_DRAIN_TIMEOUT, async {
+                    for reader in readers {
+                        let _ = reader.await;
+                    }
+                })
+                .await;
+                stop_reading.cancel();
+
+                if let Some(audit_log) = audit_log {
+                    let exit_code = match &status {
+                        BackgroundTaskStatus::Exited { exit_code } => Some(*exit_code),
+                        _ => None,
+                    };
+                    audit_log.record(AuditEvent::BackgroundTask {
+                        task_id: id,
+                        command: command.clone(),
+                        cwd: cwd.clone(),
+                        sandbox,
+                        exit_code,
+                    });
+                }
+                state.lock_unchecked().status = status;
+                let event = Event {
+                    id: sub_id,
+                    msg: EventMsg::BackgroundTaskUpdate(task_info(id, &command, &cwd, &state)),
+                };
+                if let Err(e) = tx_event.send(event).await {
+                    warn!("failed to send BackgroundTaskUpdate event: {e}");
+                }
+            }
+        });
+
+        let task = BackgroundTask {
+            command,
+            cwd,
+            state,
+            kill,
+            waiter: Some(waiter),
+        };
+        self.tasks.lock_unchecked().insert(id, task);
+        Ok(info)
+    }
+
+    /// All tasks started so far, in the order they were started.
+    pub(crate) fn list(&self) -> Vec<BackgroundTaskInfo> {
+        let tasks = self.tasks.lock_unchecked();
+        tasks.iter().map(|(id, task)| task.info(*id)).collect()
+    }
+
+    /// Kills a running task. Its final info is sent once it has stopped.
+    pub(crate) fn kill(&self, id: u64) -> std::result::Result<(), String> {
+        let tasks = self.tasks.lock_unchecked();
+        let Some(task) = tasks.get(&id) else {
+            return Err(format!("no background task with id {id}"));
+        };
+        if task.info(id).status != BackgroundTaskStatus::Running {
+            return Err(format!("background task {id} is not running"));
+        }
+        task.kill.cancel();
+        Ok(())
+    }
+
+    /// Kills every running task and waits for all of them to stop.
+    pub(crate) async fn kill_all(&self) {
+        let waiters: Vec<JoinHandle<()>> = {
+            let mut tasks = self.tasks.lock_unchecked();
+            tasks
+                .values_mut()
+                .filter_map(|task| {
+                    task.kill.cancel();
+                    task.waiter.take()
+                })
+                .collect()
+        };
+        for waiter in waiters {
+            let _ = waiter.await;
+        }
+    }
+}
+
+async fn spawn_child(request: BackgroundTaskSpawn<'_>) -> Result<Child> {
+    let BackgroundTaskSpawn {
+        command,
+        cwd,
+        env,
+        sandbox_type,
+        sandbox_policy,
+        seatbelt_extensions,
+        codex_linux_sandbox_exe,
+        limits,
+        audit_log: _,
+    } = request;
+    let stdio_policy = StdioPolicy::RedirectForShellTool;
+    let child = match sandbox_type {
+        SandboxType::None => {
+            let (program, args) = command.split_first().ok_or_else(|| {
+                CodexErr::Io(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "command args are empty",
+                ))
+            })?;
+            spawn_child_async(SpawnChildRequest {
+                program: PathBuf::from(program),
+                args: args.into(),
+                arg0: None,
+                cwd,
+                sandbox_policy,
+                stdio_policy,
+                env,
+                limits,
+            })
+            .await?
+        }
+        SandboxType::MacosSeatbelt => {
+            spawn_command_under_seatbelt(
+                command,
+                sandbox_policy,
+                seatbelt_extensions,
+                cwd,
+                stdio_policy,
+                env,
+                limits,
+            )
+            .await?
+        }
+        SandboxType::LinuxSeccomp => {
+            let codex_linux_sandbox_exe = codex_linux_sandbox_exe
+                .as_ref()
+                .ok_or(CodexErr::LandlockSandboxExecutableNotProvided)?;
+            spawn_command_under_linux_sandbox(
+                codex_linux_sandbox_exe,
+                command,
+                sandbox_policy,
+                cwd,
+                stdio_policy,
+                env,
+                limits,
+                None,
+            )
+            .await?
+        }
+        SandboxType::WindowsRestrictedToken => {
+            return Err(CodexErr::Io(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "background tasks are not supported under the Windows sandbox",
+            )));
+        }
+        SandboxType::Container => {
+            return Err(CodexErr::Io(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "background tasks are not supported under the container backend",
+            )));
+        }
+    };
+    Ok(child)
+}
+
+async fn read_into_tail<R: AsyncRead + Unpin>(
+    mut reader: R,
+    state: Arc<StdMutex<TaskState>>,
+    stop_reading: CancellationToken,
+) {
+    let mut buf = [0u8; READ_CHUNK_SIZE];
+    loop {
+        let n = tokio::select! {
+            read = reader.read(&mut buf) => match read {
+                Ok(0) | Err(_) => break,
+                Ok(n) => n,
+            },
+            () = stop_reading.cancelled() => break,
+        };
+        state.lock_unchecked().append_output(&buf[..n]);
+    }
+}
+
+fn exited_status(status: io::Result<ExitStatus>) -> BackgroundTaskStatus {
+    let exit_code = match status {
+        Ok(status) => exit_code(status),
+        Err(e) => {
+            warn!("failed to wait for background task: {e}");
+            -1
+        }
+    };
+    BackgroundTaskStatus::Exited { exit_code }
+}
+
+#[cfg(unix)]
+fn exit_code(status: ExitStatus) -> i32 {
+    use std::os::unix::process::ExitStatusExt;
+    // Conventional shell encoding for a process killed by a signal.
+    status
+        .code()
+        .or_else(|| status.signal().map(|signal| 128 + signal))
+        .unwrap_or(-1)
+}
+
+#[cfg(not(unix))]
+fn exit_code(status: ExitStatus) -> i32 {
+    status.code().unwrap_or(-1)
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn bash(script: &str) -> Vec<String> {
+        vec!["bash".to_string(), "-c".to_string(), script.to_string()]
+    }
+
+    async fn spawn(
+        manager: &BackgroundTaskManager,
+        command: Vec<String>,
+        tx_event: Sender<Event>,
+    ) -> BackgroundTaskInfo {
+        let policy = SandboxPolicy::DangerFullAccess {
+            read_blocklist: vec![],
+        };
+        let request = BackgroundTaskSpawn {
+            command,
+            cwd: std::env::temp_dir(),
+            env: std::env::vars().collect(),
+            sandbox_type: SandboxType::None,
+            sandbox_policy: &policy,
+            seatbelt_extensions: &SeatbeltExtensions::default(),
+            codex_linux_sandbox_exe: &None,
+            limits: &ExecLimits::default(),
+            audit_log: None,
+        };
+        manager
+            .spawn(request, "sub".to_string(), tx_event)
+            .await
+            .unwrap()
+    }
+
+    async fn next_update(rx: &async_channel::Receiver<Event>) -> BackgroundTaskInfo {
+        let event = tokio::time::timeout(Duration::from_secs(10), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        match event.msg {
+            EventMsg::BackgroundTaskUpdate(info) => info,
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+
+    #[test]
+    fn output_tail_keeps_the_most_recent_bytes() {
+        let mut state = TaskState {
+            status: BackgroundTaskStatus::Running,
+            output_tail: Vec::new(),
+        };
+        state.append_output(&vec![b'a'; OUTPUT_TAIL_MAX_BYTES]);
+        state.append_output(b"bc");
+        assert_eq!(state.output_tail.len(), OUTPUT_TAIL_MAX_BYTES);
+        assert!(state.output_tail.ends_with(b"abc"));
+    }
+
+    #[tokio::test]
+    async fn finished_task_reports_exit_code_and_output() {
+        let manager = BackgroundTaskManager::default();
+        let (tx, rx) = async_channel::unbounded();
+        let started = spawn(&manager, bash("echo ready; echo oops >&2; exit 3"), tx).await;
+        assert_eq!(started.id, 0);
+        assert_eq!(started.status, BackgroundTaskStatus::Running);
+        assert_eq!(next_update(&rx).await, started);
+
+        let finished = next_update(&rx).await;
+        assert_eq!(
+            finished.status,
+            BackgroundTaskStatus::Exited { exit_code: 3 }
+        );
+        assert!(finished.output_tail.contains("ready"), "{finished:?}");
+        assert!(finished.output_tail.contains("oops"), "{finished:?}");
+        assert_eq!(manager.list(), vec![finished]);
+        assert_eq!(
+            manager.kill(0),
+            Err("background task 0 is not running".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn killed_task_stops_running() {
+        let manager = BackgroundTaskManager::default();
+        let (tx, rx) = async_channel::unbounded();
+        let started = spawn(&manager, bash("echo up; sleep 60"), tx).await;
+        assert_eq!(next_update(&rx).await, started);
+
+        // Wait until the task is producing output so the kill hits it mid-run.
+        let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+        while !manager.list()[0].output_tail.contains("up") {
+            assert!(tokio::time::Instant::now() < deadline, "task never started");
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        manager.kill(0).unwrap();
+
+        let killed = next_update(&rx).await;
+        assert_eq!(killed.status, BackgroundTaskStatus::Killed);
+        assert_eq!(
+            manager.kill(1),
+            Err("no background task with id 1".to_string())
+        );
+    }
+}
@@ -6,6 +6,7 @@ use futures::Stream;
 use futures::StreamExt;
 use futures::TryStreamExt;
 use reqwest::StatusCode;
+use serde::Deserialize;
 use serde_json::json;
 use std::pin::Pin;
 use std::task::Context;
@@ -19,10 +20,12 @@ use crate::ModelProviderInfo;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::client_common::ResponseStream;
+use crate::config_types::PromptCache;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::model_family::ModelFamily;
 use crate::openai_tools::create_tools_json_for_chat_completions_api;
+use crate::protocol::TokenUsage;
 use crate::util::backoff;
 use codex_protocol::models::ContentItem;
 use codex_protocol::models::ReasoningItemContent;
@@ -34,6 +37,7 @@ pub(crate) async fn stream_chat_completions(
     model_family: &ModelFamily,
     client: &reqwest::Client,
     provider: &ModelProviderInfo,
+    prompt_cache: &PromptCache,
 ) -> Result<ResponseStream> {
     // Build messages array
     let mut messages = Vec::<serde_json::Value>::new();
@@ -136,13 +140,27 @@ pub(crate) async fn stream_chat_completions(
         }
     }
 
-    let tools_json = create_tools_json_for_chat_completions_api(&prompt.tools)?;
-    let payload = json!({
+    if prompt_cache.enabled && prompt_cache.cache_control_markers {
+        add_cache_control_markers(&mut messages);
+    }
+
+    let mut payload = json!({
         "model": model_family.slug,
         "messages": messages,
         "stream": true,
-        "tools": tools_json,
     });
+    if model_family.supports_tools {
+        payload["tools"] = json!(create_tools_json_for_chat_completions_api(&prompt.tools)?);
+    }
+    if !model_family.stop_sequences.is_empty() {
+        payload["stop"] = json!(model_family.stop_sequences);
+    }
+    if let Some(max_tokens) = prompt.max_output_tokens {
+        payload["max_tokens"] = json!(max_tokens);
+    }
+    if provider.stream_include_usage {
+        payload["stream_options"] = json!({ "include_usage": true });
+    }
 
     debug!(
         "POST to {}: {}",
@@ -207,6 +225,64 @@ pub(crate) async fn stream_chat_completions(
     }
 }
 
+/// How long to wait for the trailing usage chunk once the turn has finished.
+const TRAILING_USAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Marks the system prompt and the most recent user message as cache
+/// breakpoints. Providers that support explicit prompt caching (e.g. Anthropic
+/// models via OpenRouter) only honor `cache_control` on content parts, so the
+/// plain string content is rewritten into a single text part.
+fn add_cache_control_markers(messages: &mut [serde_json::Value]) {
+    let last_user = messages.iter().rposition(|m| m["role"] == "user");
+    let breakpoints = [Some(0), last_user];
+    for idx in breakpoints.into_iter().flatten() {
+        let Some(message) = messages.get_mut(idx) else {
+            continue;
+        };
+        let Some(text) = message["content"].as_str().map(str::to_string) else {
+            continue;
+        };
+        message["content"] = json!([{
+            "type": "text",
+            "text": text,
+            "cache_control": { "type": "ephemeral" },
+        }]);
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionUsage {
+    prompt_tokens: u64,
+    completion_tokens: u64,
+    total_tokens: u64,
+    prompt_tokens_details: Option<ChatCompletionPromptTokensDetails>,
+    completion_tokens_details: Option<ChatCompletionCompletionTokensDetails>,
+}
+
+impl From<ChatCompletionUsage> for TokenUsage {
+    fn from(val: ChatCompletionUsage) -> Self {
+        TokenUsage {
+            input_tokens: val.prompt_tokens,
+            cached_input_tokens: val.prompt_tokens_details.and_then(|d| d.cached_tokens),
+            output_tokens: val.completion_tokens,
+            reasoning_output_tokens: val
+                .completion_tokens_details
+                .and_then(|d| d.reasoning_tokens),
+            total_tokens: val.total_tokens,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionPromptTokensDetails {
+    cached_tokens: Option<u64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatCompletionCompletionTokensDetails {
+    reasoning_tokens: Option<u64>,
+}
+
 /// Lightweight SSE processor for the Chat Completions streaming format. The
 /// output is mapped onto Codex's internal [`ResponseEvent`] so that the rest
 /// of the pipeline can stay agnostic of the underlying wire format.
@@ -235,9 +311,19 @@ async fn process_chat_sse<S>(
     let mut fn_call_state = FunctionCallState::default();
     let mut assistant_text = String::new();
     let mut reasoning_text = String::new();
+    // With `stream_options.include_usage`, usage arrives in a trailing chunk
+    // after the one carrying `finish_reason`, so completion is deferred until
+    // usage is known or the stream ends.
+    let mut token_usage: Option<TokenUsage> = None;
+    let mut finished = false;
 
     loop {
-        let sse = match timeout(idle_timeout, stream.next()).await {
+        let wait = if finished {
+            idle_timeout.min(TRAILING_USAGE_TIMEOUT)
+        } else {
+            idle_timeout
+        };
+        let sse = match timeout(wait, stream.next()).await {
             Ok(Some(Ok(ev))) => ev,
             Ok(Some(Err(e))) => {
                 let _ = tx_event
@@ -250,7 +336,18 @@ async fn process_chat_sse<S>(
                 let _ = tx_event
                     .send(Ok(ResponseEvent::Completed {
                         response_id: String::new(),
-                        token_usage: None,
+                        token_usage,
+                    }))
+                    .await;
+                return;
+            }
+            Err(_) if finished => {
+                // The turn already finished; do not fail it over a missing
+                // usage chunk.
+                let _ = tx_event
+                    .send(Ok(ResponseEvent::Completed {
+                        response_id: String::new(),
+                        token_usage,
                     }))
                     .await;
                 return;
@@ -296,7 +393,7 @@ async fn process_chat_sse<S>(
             let _ = tx_event
                 .send(Ok(ResponseEvent::Completed {
                     response_id: String::new(),
-                    token_usage: None,
+                    token_usage,
                 }))
                 .await;
             return;
@@ -309,6 +406,25 @@ async fn process_chat_sse<S>(
         };
         trace!("chat_completions received SSE chunk: {chunk:?}");
 
+        if let Some(usage) = chunk.get("usage").filter(|u| u.is_object())
+            && let Ok(usage) = serde_json::from_value::<ChatCompletionUsage>(usage.clone())
+        {
+            token_usage = Some(usage.into());
+        }
+
+        if finished {
+            if token_usage.is_some() {
+                let _ = tx_event
+                    .send(Ok(ResponseEvent::Completed {
+                        response_id: String::new(),
+                        token_usage,
+                    }))
+                    .await;
+                return;
+            }
+            continue;
+        }
+
         let choice_opt = chunk.get("choices").and_then(|c| c.get(0));
 
         if let Some(choice) = choice_opt {
@@ -440,17 +556,19 @@ async fn process_chat_sse<S>(
                 }
 
                 // Emit Completed regardless of reason so the agent can advance.
-                let _ = tx_event
-                    .send(Ok(ResponseEvent::Completed {
-                        response_id: String::new(),
-                        token_usage: None,
-                    }))
-                    .await;
-
-                // Prepare for potential next turn (should not happen in same stream).
-                // fn_call_state = FunctionCallState::default();
+                // If usage has not been reported yet, wait for the trailing
+                // usage chunk instead.
+                if token_usage.is_some() {
+                    let _ = tx_event
+                        .send(Ok(ResponseEvent::Completed {
+                            response_id: String::new(),
+                            token_usage,
+                        }))
+                        .await;
 
-                return; // End processing for this SSE stream.
+                    return; // End processing for this SSE stream.
+                }
+                finished = true;
             }
         }
     }
@@ -677,3 +795,135 @@ impl<S> AggregatedChatStream<S> {
         Self::new(inner, AggregateMode::Streaming)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    /// Feeds `chunks` to the parser as SSE `data:` lines. When `keep_open` is
+    /// set, the stream never ends after the last chunk.
+    async fn run_chat_sse(chunks: &[serde_json::Value], keep_open: bool) -> Vec<ResponseEvent> {
+        let events: Vec<Result<Bytes>> = chunks
+            .iter()
+            .map(|chunk| Ok(Bytes::from(format!("data: {chunk}\n\n"))))
+            .collect();
+        let stream = futures::stream::iter(events);
+        let (tx, mut rx) = mpsc::channel::<Result<ResponseEvent>>(16);
+        let idle_timeout = Duration::from_secs(60);
+        if keep_open {
+            tokio::spawn(process_chat_sse(
+                stream.chain(futures::stream::pending()),
+                tx,
+                idle_timeout,
+            ));
+        } else {
+            tokio::spawn(process_chat_sse(stream, tx, idle_timeout));
+        }
+
+        let mut out = Vec::new();
+        while let Some(ev) = rx.recv().await {
+            out.push(ev.expect("unexpected stream error"));
+        }
+        out
+    }
+
+    fn stop_chunk() -> serde_json::Value {
+        json!({"choices": [{"delta": {"content": "hi"}, "finish_reason": "stop"}]})
+    }
+
+    fn usage_chunk() -> serde_json::Value {
+        json!({
+            "choices": [],
+            "usage": {
+                "prompt_tokens": 100,
+                "completion_tokens": 20,
+                "total_tokens": 120,
+                "prompt_tokens_details": {"cached_tokens": 64},
+                "completion_tokens_details": {"reasoning_tokens": 5}
+            }
+        })
+    }
+
+    fn completed_usage(events: &[ResponseEvent]) -> Option<TokenUsage> {
+        match events.last() {
+            Some(ResponseEvent::Completed { token_usage, .. }) => token_usage.clone(),
+            other => panic!("expected Completed last, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn chat_usage_maps_cached_and_reasoning_tokens() {
+        let usage: ChatCompletionUsage =
+            serde_json::from_value(usage_chunk()["usage"].clone()).unwrap();
+        let usage = TokenUsage::from(usage);
+        assert_eq!(usage.input_tokens, 100);
+        assert_eq!(usage.cached_input_tokens, Some(64));
+        assert_eq!(usage.output_tokens, 20);
+        assert_eq!(usage.reasoning_output_tokens, Some(5));
+        assert_eq!(usage.total_tokens, 120);
+
+        let usage: ChatCompletionUsage = serde_json::from_value(json!({
+            "prompt_tokens": 1,
+            "completion_tokens": 2,
+            "total_tokens": 3
+        }))
+        .unwrap();
+        let usage = TokenUsage::from(usage);
+        assert_eq!(usage.cached_input_tokens, None);
+        assert_eq!(usage.reasoning_output_tokens, None);
+    }
+
+    #[tokio::test]
+    async fn completion_waits_for_trailing_usage_chunk() {
+        let events = run_chat_sse(&[stop_chunk(), usage_chunk()], true).await;
+
+        let completed = events
+            .iter()
+            .filter(|ev| matches!(ev, ResponseEvent::Completed { .. }))
+            .count();
+        assert_eq!(completed, 1);
+        let usage = completed_usage(&events).expect("usage from trailing chunk");
+        assert_eq!(usage.cached_input_tokens, Some(64));
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn completion_without_usage_is_sent_after_trailing_timeout() {
+        let start = tokio::time::Instant::now();
+        let events = run_chat_sse(&[stop_chunk()], true).await;
+
+        assert!(completed_usage(&events).is_none());
+        assert!(start.elapsed() >= TRAILING_USAGE_TIMEOUT);
+    }
+
+    #[tokio::test]
+    async fn completion_without_usage_is_sent_when_stream_ends() {
+        let events = run_chat_sse(&[stop_chunk()], false).await;
+        assert!(completed_usage(&events).is_none());
+    }
+
+    #[test]
+    fn cache_control_markers_wrap_system_and_last_user_message() {
+        let mut messages = vec![
+            json!({"role": "system", "content": "instructions"}),
+            json!({"role": "user", "content": "first"}),
+            json!({"role": "assistant", "content": "reply"}),
+            json!({"role": "user", "content": "second"}),
+            json!({"role": "tool", "tool_call_id": "c1", "content": "out"}),
+        ];
+        add_cache_control_markers(&mut messages);
+
+        let marked = |text: &str| {
+            json!([{
+                "type": "text",
+                "text": text,
+                "cache_control": {"type": "ephemeral"},
+            }])
+        };
+        assert_eq!(messages[0]["content"], marked("instructions"));
+        assert_eq!(messages[1]["content"], json!("first"));
+        assert_eq!(messages[2]["content"], json!("reply"));
+        assert_eq!(messages[3]["content"], marked("second"));
+        assert_eq!(messages[4]["content"], json!("out"));
+    }
+}
@@ -0,0 +1,202 @@
+//! Commits the working tree to a per-session `codex/<session-id>` branch after
+//! each successful turn, so the agent's edits can be reviewed turn by turn.
+//!
+//! The commit is built with git plumbing and a temporary index, so neither
+//! the user's index nor the checked-out branch is touched.
+
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use tokio::process::Command;
+use uuid::Uuid;
+
+/// Id of the empty tree, used as the diff base for the first checkpoint in a
+/// repository without commits.
+const EMPTY_TREE: &str = "4b825dc642cb6eb9a060e54bf8d69288fbee4904";
+
+/// A tree that differs from the branch tip and still needs a commit message.
+pub(crate) struct PendingCheckpoint {
+    repo_root: PathBuf,
+    branch_ref: String,
+    /// Current tip of the checkpoint branch, or `HEAD` for the first
+    /// checkpoint of the session.
+    parent: Option<String>,
+    /// Whether `branch_ref` already exists; it is created otherwise.
+    branch_exists: bool,
+    tree: String,
+    /// Patch from `parent` to `tree`, used to write the commit message.
+    pub(crate) diff: String,
+}
+
+pub(crate) fn branch_name(session_id: Uuid) -> String {
+    format!("codex/{session_id}")
+}
+
+/// Snapshot the working tree of the repository containing `cwd`. Returns
+/// `None` when `cwd` is not in a git repository or nothing changed since the
+/// last checkpoint.
+pub(crate) async fn prepare(cwd: &Path, session_id: Uuid) -> io::Result<Option<PendingCheckpoint>> {
+    let Ok(root) = git(cwd, &["rev-parse", "--show-toplevel"], None).await else {
+        return Ok(None);
+    };
+    let repo_root = PathBuf::from(root);
+    let branch_ref = format!("refs/heads/{}", branch_name(session_id));
+
+    let branch_tip = rev_parse(&repo_root, &branch_ref).await;
+    let branch_exists = branch_tip.is_some();
+    let parent = match branch_tip {
+        Some(tip) => Some(tip),
+        None => rev_parse(&repo_root, "HEAD").await,
+    };
+
+    let index_dir = tempfile::tempdir()?;
+    let index = index_dir.path().join("index");
+    if let Some(parent) = &parent {
+        git(&repo_root, &["read-tree", parent], Some(&index)).await?;
+    }
+    git(&repo_root, &["add", "--all"], Some(&index)).await?;
+    let tree = git(&repo_root, &["write-tree"], Some(&index)).await?;
+
+    let base_tree = match &parent {
+        Some(parent) => {
+            git(
+                &repo_root,
+                &["rev-parse", &format!("{parent}^{{tree}}")],
+                None,
+            )
+            .await?
+        }
+        None => EMPTY_TREE.to_string(),
+    };
+    if base_tree == tree {
+        return Ok(None);
+    }
+    let diff = git(
+        &repo_root,
+        &[
+            "diff-tree",
+            "-p",
+            "--no-color",
+            "--no-ext-diff",
+            &base_tree,
+            &tree,
+        ],
+        None,
+    )
+    .await?;
+
+    Ok(Some(PendingCheckpoint {
+        repo_root,
+        branch_ref,
+        parent,
+        branch_exists,
+        tree,
+        diff,
+    }))
+}
+
+impl PendingCheckpoint {
+    /// Commit the snapshot with `message` and move the branch to it. Returns
+    /// the new commit's SHA.
+    pub(crate) async fn commit(self, message: &str) -> io::Result<String> {
+        let mut args = vec!["commit-tree", self.tree.as_str(), "-m", message];
+        if let Some(parent) = &self.parent {
+            args.extend(["-p", parent.as_str()]);
+        }
+        let sha = git(&self.repo_root, &args, None).await?;
+
+        // Pass the expected old value so a concurrent update is not lost.
+        let old_value = match (&self.parent, self.branch_exists) {
+            (Some(parent), true) => parent.as_str(),
+            _ => "",
+        };
+        git(
+            &self.repo_root,
+            &["update-ref", &self.branch_ref, &sha, old_value],
+            None,
+        )
+        .await?;
+        Ok(sha)
+    }
+}
+
+async fn rev_parse(repo_root: &Path, rev: &str) -> Option<String> {
+    git(repo_root, &["rev-parse", "--verify", "--quiet", rev], None)
+        .await
+        .ok()
+}
+
+/// Run git in `cwd` and return its trimmed stdout. `index` replaces the
+/// repository's index file for this invocation.
+async fn git(cwd: &Path, args: &[&str], index: Option<&Path>) -> io::Result<String> {
+    let mut command = Command::new("git");
+    command.args(args).current_dir(cwd);
+    if let Some(index) = index {
+        command.env("GIT_INDEX_FILE", index);
+    }
+    let output = command.output().await?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "git {} failed: {}",
+            args.first().unwrap_or(&""),
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use tempfile::tempdir;
+
+    async fn init_repo(dir: &Path) {
+        for args in [
+            vec!["init", "--quiet"],
+            vec!["config", "user.name", "Test"],
+            vec!["config", "user.email", "test@example.com"],
+        ] {
+            git(dir, &args, None).await.unwrap();
+        }
+        fs::write(dir.join("a.txt"), "one\n").unwrap();
+        git(dir, &["add", "a.txt"], None).await.unwrap();
+        git(dir, &["commit", "--quiet", "-m", "initial"], None)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn commits_to_session_branch_without_touching_head_or_index() {
+        let dir = tempdir().unwrap();
+        init_repo(dir.path()).await;
+        let head = rev_parse(dir.path(), "HEAD").await.unwrap();
+        let session_id = Uuid::new_v4();
+
+        assert!(prepare(dir.path(), session_id).await.unwrap().is_none());
+
+        fs::write(dir.path().join("a.txt"), "two\n").unwrap();
+        let pending = prepare(dir.path(), session_id).await.unwrap().unwrap();
+        assert!(pending.diff.contains("+two"));
+        let sha = pending.commit("Update a.txt").await.unwrap();
+
+        let branch = format!("refs/heads/{}", branch_name(session_id));
+        assert_eq!(rev_parse(dir.path(), &branch).await, Some(sha.clone()));
+        assert_eq!(rev_parse(dir.path(), "HEAD").await, Some(head.clone()));
+        assert_eq!(
+            git(dir.path(), &["rev-parse", &format!("{sha}^")], None)
+                .await
+                .unwrap(),
+            head
+        );
+        let status = git(dir.path(), &["status", "--porcelain"], None)
+            .await
+            .unwrap();
+        assert_eq!(status, "M a.txt");
+
+        // Nothing changed since the last checkpoint.
+        assert!(prepare(dir.path(), session_id).await.unwrap().is_none());
+    }
+}
@@ -103,6 +103,7 @@ impl ModelClient {
                     &self.config.model_family,
                     &self.client,
                     &self.provider,
+                    &self.config.prompt_cache,
                 )
                 .await?;
 
@@ -208,8 +209,9 @@ impl ModelClient {
             store,
             stream: true,
             include,
-            prompt_cache_key: Some(self.session_id.to_string()),
+            prompt_cache_key: self.prompt_cache_key(),
             text,
+            max_output_tokens: prompt.max_output_tokens,
         };
 
         let mut attempt = 0;
@@ -353,6 +355,16 @@ impl ModelClient {
         }
     }
 
+    /// The URL requests are sent to, without its query string.
+    pub(crate) fn request_target(&self) -> String {
+        let auth = self.auth_manager.as_ref().and_then(|m| m.auth());
+        let url = self.provider.get_full_url(&auth);
+        match url.split_once('?') {
+            Some((target, _query)) => target.to_string(),
+            None => url,
+        }
+    }
+
     pub fn get_provider(&self) -> ModelProviderInfo {
         self.provider.clone()
     }
@@ -380,6 +392,21 @@ impl ModelClient {
     pub fn get_auth_manager(&self) -> Option<Arc<AuthManager>> {
         self.auth_manager.clone()
     }
+
+    /// Cache key for the Responses API: the configured key when set,
+    /// otherwise the session id. `None` when prompt caching is disabled.
+    fn prompt_cache_key(&self) -> Option<String> {
+        let prompt_cache = &self.config.prompt_cache;
+        if !prompt_cache.enabled {
+            return None;
+        }
+        Some(
+            prompt_cache
+                .key
+                .clone()
+                .unwrap_or_else(|| self.session_id.to_string()),
+        )
+    }
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -774,6 +801,7 @@ mod tests {
             stream_max_retries: Some(0),
             stream_idle_timeout_ms: Some(1000),
             requires_openai_auth: false,
+            stream_include_usage: false,
         };
 
         let events = collect_events(
@@ -834,6 +862,7 @@ mod tests {
             stream_max_retries: Some(0),
             stream_idle_timeout_ms: Some(1000),
             requires_openai_auth: false,
+            stream_include_usage: false,
         };
 
         let events = collect_events(&[sse1.as_bytes()], provider).await;
@@ -868,6 +897,7 @@ mod tests {
             stream_max_retries: Some(0),
             stream_idle_timeout_ms: Some(1000),
             requires_openai_auth: false,
+            stream_include_usage: false,
         };
 
         let events = collect_events(&[sse1.as_bytes()], provider).await;
@@ -973,6 +1003,7 @@ mod tests {
                 stream_max_retries: Some(0),
                 stream_idle_timeout_ms: Some(1000),
                 requires_openai_auth: false,
+                stream_include_usage: false,
             };
 
             let out = run_sse(evs, provider).await;
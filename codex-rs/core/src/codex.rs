@@ -14,6 +14,7 @@ use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::MaybeApplyPatchVerified;
 use codex_apply_patch::maybe_parse_apply_patch_verified;
 use codex_login::AuthManager;
+use codex_login::AuthMode;
 use codex_protocol::protocol::ConversationHistoryResponseEvent;
 use codex_protocol::protocol::TurnAbortReason;
 use codex_protocol::protocol::TurnAbortedEvent;
@@ -26,6 +27,7 @@ use tokio::task::AbortHandle;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
+use tracing::instrument;
 use tracing::trace;
 use tracing::warn;
 use uuid::Uuid;
@@ -36,11 +38,24 @@ use crate::apply_patch::ApplyPatchExec;
 use crate::apply_patch::CODEX_APPLY_PATCH_ARG1;
 use crate::apply_patch::InternalApplyPatchInvocation;
 use crate::apply_patch::convert_apply_patch_to_protocol;
+use crate::audit_log::ApprovalSubject;
+use crate::audit_log::AuditEvent;
+use crate::audit_log::AuditLog;
+use crate::background_task::BackgroundTaskManager;
+use crate::background_task::BackgroundTaskSpawn;
+use crate::checkpoint;
 use crate::client::ModelClient;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
+use crate::compact;
+use crate::compact::AUTO_COMPACT_KEEP_TURNS;
+use crate::compact::AUTO_COMPACT_PERCENT;
 use crate::config::Config;
+use crate::config_types::ContainerConfig;
+use crate::config_types::ExecLimits;
+use crate::config_types::ExecutionBackend;
 use crate::config_types::ShellEnvironmentPolicy;
+use crate::config_types::ToolOutputTruncation;
 use crate::conversation_history::ConversationHistory;
 use crate::environment_context::EnvironmentContext;
 use crate::error::CodexErr;
@@ -48,27 +63,39 @@ use crate::error::Result as CodexResult;
 use crate::error::SandboxErr;
 use crate::error::get_error_message_ui;
 use crate::exec::ExecParams;
+use crate::exec::ExecSandboxOptions;
 use crate::exec::ExecToolCallOutput;
 use crate::exec::SandboxType;
 use crate::exec::StdoutStream;
 use crate::exec::StreamOutput;
+use crate::exec::output_shows_sandbox_denial;
 use crate::exec::process_exec_tool_call;
+use crate::exec_command::CLOSE_SESSION_TOOL_NAME;
+use crate::exec_command::CloseSessionParams;
 use crate::exec_command::EXEC_COMMAND_TOOL_NAME;
 use crate::exec_command::ExecCommandParams;
 use crate::exec_command::ExecSessionManager;
 use crate::exec_command::WRITE_STDIN_TOOL_NAME;
 use crate::exec_command::WriteStdinParams;
+use crate::exec_command::sandboxed_argv;
 use crate::exec_env::create_env;
+use crate::exec_policy::ExecPolicy;
+use crate::exec_policy::RuleAction;
 use crate::mcp_connection_manager::McpConnectionManager;
+use crate::mcp_connection_manager::mcp_roots;
+use crate::mcp_sampling::McpSampler;
 use crate::mcp_tool_call::handle_mcp_tool_call;
 use crate::model_family::find_family_for_model;
 use crate::openai_tools::ApplyPatchToolArgs;
 use crate::openai_tools::ToolsConfig;
 use crate::openai_tools::ToolsConfigParams;
 use crate::openai_tools::get_openai_tools;
+use crate::output_salience::select_salient_lines;
 use crate::parse_command::parse_command;
 use crate::plan_tool::handle_update_plan;
 use crate::project_doc::get_user_instructions;
+use crate::project_doc::nested_doc_paths;
+use crate::project_doc::read_nested_doc;
 use crate::protocol::AgentMessageDeltaEvent;
 use crate::protocol::AgentMessageEvent;
 use crate::protocol::AgentReasoningDeltaEvent;
@@ -79,33 +106,59 @@ use crate::protocol::AgentReasoningSectionBreakEvent;
 use crate::protocol::ApplyPatchApprovalRequestEvent;
 use crate::protocol::AskForApproval;
 use crate::protocol::BackgroundEventEvent;
+use crate::protocol::ContextCompactedEvent;
+use crate::protocol::DegradedModeEvent;
+use crate::protocol::DegradedModeKind;
 use crate::protocol::ErrorEvent;
+use crate::protocol::ErrorKind;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecApprovalRequestEvent;
 use crate::protocol::ExecCommandBeginEvent;
 use crate::protocol::ExecCommandEndEvent;
+use crate::protocol::ExecKilledReason;
 use crate::protocol::FileChange;
 use crate::protocol::InputItem;
+use crate::protocol::ListBackgroundTasksResponseEvent;
 use crate::protocol::Op;
 use crate::protocol::PatchApplyBeginEvent;
 use crate::protocol::PatchApplyEndEvent;
+use crate::protocol::ProfileSwitchedEvent;
+use crate::protocol::ProtocolHandshake;
 use crate::protocol::ReviewDecision;
+use crate::protocol::SandboxDenial;
 use crate::protocol::SandboxPolicy;
 use crate::protocol::SessionConfiguredEvent;
 use crate::protocol::StreamErrorEvent;
 use crate::protocol::Submission;
 use crate::protocol::TaskCompleteEvent;
+use crate::protocol::TruncationReason;
 use crate::protocol::TurnDiffEvent;
+use crate::protocol::TurnRevertedEvent;
 use crate::protocol::WebSearchBeginEvent;
+use crate::redaction::Redactor;
+use crate::repo_map::RepoMap;
+use crate::rollout::ExecSessionTranscript;
 use crate::rollout::RolloutRecorder;
+use crate::run_limits::RunLimits;
 use crate::safety::SafetyCheck;
 use crate::safety::assess_command_safety;
 use crate::safety::assess_safety_for_untrusted_command;
+use crate::seatbelt::SeatbeltExtensions;
 use crate::shell;
+use crate::tokenizer::Tokenizer;
 use crate::turn_diff_tracker::TurnDiffTracker;
+use crate::turn_snapshot::SnapshotStore;
+use crate::turn_snapshot::TurnSnapshot;
+use crate::usage::attribute_token_usage;
+use crate::usage::estimate_token_usage;
+use crate::user_notification::ApprovalKind;
+use crate::user_notification::Notifier;
 use crate::user_notification::UserNotification;
+use crate::user_notification::command_summary;
+use crate::user_notification::files_summary;
 use crate::util::backoff;
+use crate::windows_sandbox::revoke_windows_sandbox_access;
 use codex_protocol::config_types::ReasoningEffort as ReasoningEffortConfig;
 use codex_protocol::config_types::ReasoningSummary as ReasoningSummaryConfig;
 use codex_protocol::models::ContentItem;
@@ -121,7 +174,7 @@ use codex_protocol::models::ShellToolCallParams;
 // unrecoverable and should abort the program. This avoids scattered `.unwrap()`
 // calls on `lock()` while still surfacing a clear panic message when a lock is
 // poisoned.
-trait MutexExt<T> {
+pub(crate) trait MutexExt<T> {
     fn lock_unchecked(&self) -> MutexGuard<'_, T>;
 }
 
@@ -138,6 +191,8 @@ pub struct Codex {
     next_id: AtomicU64,
     tx_sub: Sender<Submission>,
     rx_event: Receiver<Event>,
+    /// Negotiated with the client; events of other capabilities are dropped.
+    protocol: ProtocolHandshake,
 }
 
 /// Wrapper returned by [`Codex::spawn`] containing the spawned [`Codex`],
@@ -156,7 +211,7 @@ pub(crate) const MODEL_FORMAT_MAX_BYTES: usize = 10 * 1024; // 10 KiB
 pub(crate) const MODEL_FORMAT_MAX_LINES: usize = 256; // lines
 pub(crate) const MODEL_FORMAT_HEAD_LINES: usize = MODEL_FORMAT_MAX_LINES / 2;
 pub(crate) const MODEL_FORMAT_TAIL_LINES: usize = MODEL_FORMAT_MAX_LINES - MODEL_FORMAT_HEAD_LINES; // 128
-pub(crate) const MODEL_FORMAT_HEAD_BYTES: usize = MODEL_FORMAT_MAX_BYTES / 2;
+pub(crate) const MODEL_FORMAT_MAX_TOKENS: u64 = 2_560; // 10 KiB of English text
 
 impl Codex {
     /// Spawn a new [`Codex`] and initialize the session.
@@ -172,20 +227,23 @@ impl Codex {
 
         let config = Arc::new(config);
         let resume_path = config.experimental_resume.clone();
+        let protocol = config.protocol.negotiate();
 
         let configure_session = ConfigureSession {
             provider: config.model_provider.clone(),
             model: config.model.clone(),
             model_reasoning_effort: config.model_reasoning_effort,
             model_reasoning_summary: config.model_reasoning_summary,
-            user_instructions,
+            user_instructions: user_instructions.text,
+            project_docs: user_instructions.project_docs,
             base_instructions: config.base_instructions.clone(),
             approval_policy: config.approval_policy,
             sandbox_policy: config.sandbox_policy.clone(),
             disable_response_storage: config.disable_response_storage,
-            notify: config.notify.clone(),
+            notifier: Notifier::new(config.notify.clone(), config.notifications.clone()),
             cwd: config.cwd.clone(),
             resume_path,
+            protocol: protocol.clone(),
         };
 
         // Generate a unique ID for the lifetime of this Codex session.
@@ -214,6 +272,7 @@ impl Codex {
             next_id: AtomicU64::new(0),
             tx_sub,
             rx_event,
+            protocol,
         };
 
         Ok(CodexSpawnOk { codex, session_id })
@@ -241,12 +300,16 @@ impl Codex {
     }
 
     pub async fn next_event(&self) -> CodexResult<Event> {
-        let event = self
-            .rx_event
-            .recv()
-            .await
-            .map_err(|_| CodexErr::InternalAgentDied)?;
-        Ok(event)
+        loop {
+            let event = self
+                .rx_event
+                .recv()
+                .await
+                .map_err(|_| CodexErr::InternalAgentDied)?;
+            if self.protocol.allows(&event.msg) {
+                return Ok(event);
+            }
+        }
     }
 }
 
@@ -254,10 +317,44 @@ impl Codex {
 #[derive(Default)]
 struct State {
     approved_commands: HashSet<Vec<String>>,
+    /// The `[exec_policy]` rules, plus the command patterns the user allowed
+    /// for this session.
+    exec_policy: ExecPolicy,
     current_task: Option<AgentTask>,
-    pending_approvals: HashMap<String, oneshot::Sender<ReviewDecision>>,
+    pending_approvals: HashMap<String, PendingApproval>,
     pending_input: Vec<ResponseInputItem>,
     history: ConversationHistory,
+    /// Paths on which the Windows sandbox has changed ACLs; the changes are
+    /// revoked at shutdown.
+    windows_sandbox_acl_paths: HashSet<PathBuf>,
+    /// Turn id -> pre-images of the files touched during that turn, for
+    /// `Op::RevertTurn`.
+    turn_snapshots: HashMap<String, TurnSnapshot>,
+    /// Degraded modes already reported to the client, so each is only
+    /// reported once per session.
+    reported_degraded_modes: HashSet<DegradedModeKind>,
+    /// Tokens in the context window as of the last model response.
+    context_tokens: u64,
+    /// Set by `Op::Compact` while a task runs; the task compacts the history
+    /// before its next request.
+    compact_requested: bool,
+    /// `AGENTS.md` files below `cwd` added to the history so far.
+    nested_project_docs: HashSet<PathBuf>,
+}
+
+/// An approval request waiting for the user's decision.
+struct PendingApproval {
+    tx_approve: oneshot::Sender<ApprovalResponse>,
+    call_id: String,
+    subject: ApprovalSubject,
+}
+
+/// The user's answer to an approval request.
+#[derive(Default)]
+pub(crate) struct ApprovalResponse {
+    pub(crate) decision: ReviewDecision,
+    /// For patches approved only in part, the files whose changes to apply.
+    pub(crate) accepted_files: Option<Vec<PathBuf>>,
 }
 
 /// Context for an initialized model agent
@@ -270,18 +367,36 @@ pub(crate) struct Session {
     /// Manager for external MCP servers/tools.
     mcp_connection_manager: McpConnectionManager,
     session_manager: ExecSessionManager,
+    background_tasks: BackgroundTaskManager,
 
-    /// External notifier command (will be passed as args to exec()). When
-    /// `None` this feature is disabled.
-    notify: Option<Vec<String>>,
+    notifier: Notifier,
 
     /// Optional rollout recorder for persisting the conversation transcript so
     /// sessions can be replayed or inspected later.
     rollout: Mutex<Option<RolloutRecorder>>,
+    /// Set when `[audit]` is enabled and the session's audit log could be
+    /// opened.
+    audit_log: Option<Arc<AuditLog>>,
     state: Mutex<State>,
     codex_linux_sandbox_exe: Option<PathBuf>,
+    /// Set when shell commands run under the container execution backend.
+    container: Option<ContainerConfig>,
+    exec_limits: ExecLimits,
+    seatbelt_extensions: SeatbeltExtensions,
+    redactor: Redactor,
+    /// Set when `snapshot_turns` is enabled.
+    snapshot_store: Option<SnapshotStore>,
+    checkpoint_commits: bool,
+    run_limits: RunLimits,
+    tool_output_truncation: ToolOutputTruncation,
+    /// Client for the `summary_model`, if one is configured; compaction uses
+    /// the turn's client otherwise.
+    summary_client: Option<ModelClient>,
+    /// Context size at which the history is compacted automatically.
+    auto_compact_token_limit: Option<u64>,
     user_shell: shell::Shell,
     show_raw_agent_reasoning: bool,
+    project_doc_max_bytes: usize,
 }
 
 /// The context needed for a single turn of the conversation.
@@ -323,6 +438,9 @@ struct ConfigureSession {
     /// Model instructions that are appended to the base instructions.
     user_instructions: Option<String>,
 
+    /// `AGENTS.md` files included in `user_instructions`.
+    project_docs: Vec<PathBuf>,
+
     /// Base instructions override.
     base_instructions: Option<String>,
 
@@ -333,10 +451,8 @@ struct ConfigureSession {
     /// Disable server-side response storage (send full context each request)
     disable_response_storage: bool,
 
-    /// Optional external notifier command tokens. Present only when the
-    /// client wants the agent to spawn a program after each completed
-    /// turn.
-    notify: Option<Vec<String>>,
+    /// Delivers notifications to the `notify` program and the desktop.
+    notifier: Notifier,
 
     /// Working directory that should be treated as the *root* of the
     /// session. All relative paths supplied by the model as well as the
@@ -348,6 +464,9 @@ struct ConfigureSession {
     cwd: PathBuf,
 
     resume_path: Option<PathBuf>,
+
+    /// Protocol version and capabilities negotiated with the client.
+    protocol: ProtocolHandshake,
 }
 
 impl Session {
@@ -364,13 +483,15 @@ impl Session {
             model_reasoning_effort,
             model_reasoning_summary,
             user_instructions,
+            project_docs,
             base_instructions,
             approval_policy,
             sandbox_policy,
             disable_response_storage,
-            notify,
+            notifier,
             cwd,
             resume_path,
+            protocol,
         } = configure_session;
         debug!("Configuring session: model={model}; provider={provider:?}");
         if !cwd.is_absolute() {
@@ -388,7 +509,7 @@ impl Session {
         // - load history metadata
         let rollout_fut = async {
             match resume_path.as_ref() {
-                Some(path) => RolloutRecorder::resume(path, cwd.clone())
+                Some(path) => RolloutRecorder::resume(path, cwd.clone(), config.encryption.enabled)
                     .await
                     .map(|(rec, saved)| (saved.session_id, Some(saved), rec)),
                 None => {
@@ -400,13 +521,43 @@ impl Session {
             }
         };
 
-        let mcp_fut = McpConnectionManager::new(config.mcp_servers.clone());
+        let mcp_sampler = Arc::new(McpSampler::new(!disable_response_storage));
+        let mcp_fut = McpConnectionManager::new(
+            config.mcp_servers.clone(),
+            mcp_sampler.clone(),
+            mcp_roots(&cwd, &sandbox_policy),
+        );
         let default_shell_fut = shell::default_user_shell();
         let history_meta_fut = crate::message_history::history_metadata(&config);
+        let repo_map_fut = async {
+            if config.repo_map {
+                Some(
+                    RepoMap::generate(
+                        cwd.clone(),
+                        config.codex_home.clone(),
+                        config.project_ignore_matcher(),
+                    )
+                    .await,
+                )
+            } else {
+                None
+            }
+        };
 
         // Join all independent futures.
-        let (rollout_res, mcp_res, default_shell, (history_log_id, history_entry_count)) =
-            tokio::join!(rollout_fut, mcp_fut, default_shell_fut, history_meta_fut);
+        let (
+            rollout_res,
+            mcp_res,
+            default_shell,
+            (history_log_id, history_entry_count),
+            repo_map_res,
+        ) = tokio::join!(
+            rollout_fut,
+            mcp_fut,
+            default_shell_fut,
+            history_meta_fut,
+            repo_map_fut
+        );
 
         // Handle rollout result, which determines the session_id.
         struct RolloutResult {
@@ -443,6 +594,7 @@ impl Session {
                     id: INITIAL_SUBMIT_ID.to_owned(),
                     msg: EventMsg::Error(ErrorEvent {
                         message: message.clone(),
+                        kind: ErrorKind::Other,
                     }),
                 });
                 warn!("{message}");
@@ -464,11 +616,24 @@ impl Session {
         // Create the mutable state for the Session.
         let mut state = State {
             history: ConversationHistory::new(),
+            exec_policy: config.exec_policy.clone(),
             ..Default::default()
         };
         if let Some(restored_items) = restored_items {
+            // The rollout may predate the current redaction rules.
+            let restored_items: Vec<ResponseItem> = restored_items
+                .iter()
+                .map(|item| config.redactor.redact_item(item))
+                .collect();
             state.history.record_items(&restored_items);
         }
+        for event in startup_degraded_modes(&config, &auth_manager, &sandbox_policy) {
+            state.reported_degraded_modes.insert(event.kind);
+            post_session_configured_error_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::DegradedMode(event),
+            });
+        }
 
         // Handle MCP manager result and record any startup failures.
         let (mcp_connection_manager, failed_clients) = match mcp_res {
@@ -478,7 +643,10 @@ impl Session {
                 error!("{message}");
                 post_session_configured_error_events.push(Event {
                     id: INITIAL_SUBMIT_ID.to_owned(),
-                    msg: EventMsg::Error(ErrorEvent { message }),
+                    msg: EventMsg::Error(ErrorEvent {
+                        message,
+                        kind: ErrorKind::Other,
+                    }),
                 });
                 (McpConnectionManager::default(), Default::default())
             }
@@ -491,11 +659,44 @@ impl Session {
                 error!("{message}");
                 post_session_configured_error_events.push(Event {
                     id: INITIAL_SUBMIT_ID.to_owned(),
-                    msg: EventMsg::Error(ErrorEvent { message }),
+                    msg: EventMsg::Error(ErrorEvent {
+                        message,
+                        kind: ErrorKind::Other,
+                    }),
                 });
             }
         }
 
+        let audit_log = if config.audit.enabled {
+            match AuditLog::open(&config.codex_home, session_id, config.encryption.enabled) {
+                Ok(audit_log) => Some(Arc::new(audit_log)),
+                Err(e) => {
+                    let message = format!("failed to open the audit log: {e}");
+                    warn!("{message}");
+                    post_session_configured_error_events.push(Event {
+                        id: INITIAL_SUBMIT_ID.to_owned(),
+                        msg: EventMsg::Error(ErrorEvent {
+                            message,
+                            kind: ErrorKind::Other,
+                        }),
+                    });
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        if let Some(audit_log) = &audit_log {
+            audit_log.record(AuditEvent::SessionStart {
+                cwd: cwd.clone(),
+                model: model.clone(),
+                model_provider: config.model_provider_id.clone(),
+                approval_policy,
+                sandbox_policy: sandbox_policy.clone(),
+                resumed: resume_path.is_some(),
+            });
+        }
+
         // Now that `session_id` is final (may have been updated by resume),
         // construct the model client.
         let client = ModelClient::new(
@@ -506,6 +707,21 @@ impl Session {
             model_reasoning_summary,
             session_id,
         );
+        mcp_sampler.set_client(client.clone());
+        let summary_client = config.summary_model.as_ref().map(|summary_model| {
+            let mut summary_config = (*config).clone();
+            summary_config.model = summary_model.clone();
+            summary_config.model_family =
+                find_family_for_model(summary_model).unwrap_or_else(|| config.model_family.clone());
+            ModelClient::new(
+                Arc::new(summary_config),
+                Some(auth_manager.clone()),
+                provider.clone(),
+                model_reasoning_effort,
+                model_reasoning_summary,
+                session_id,
+            )
+        });
         let turn_context = TurnContext {
             client,
             tools_config: ToolsConfig::new(&ToolsConfigParams {
@@ -530,17 +746,37 @@ impl Session {
             tx_event: tx_event.clone(),
             mcp_connection_manager,
             session_manager: ExecSessionManager::default(),
-            notify,
+            background_tasks: BackgroundTaskManager::default(),
+            notifier,
             state: Mutex::new(state),
             rollout: Mutex::new(rollout_recorder),
+            audit_log,
             codex_linux_sandbox_exe: config.codex_linux_sandbox_exe.clone(),
+            container: match config.execution_backend {
+                ExecutionBackend::Local => None,
+                ExecutionBackend::Container => Some(config.container.clone()),
+            },
+            exec_limits: config.exec_limits,
+            seatbelt_extensions: config.seatbelt_extensions.clone(),
+            redactor: config.redactor.clone(),
+            snapshot_store: config
+                .snapshot_turns
+                .then(|| SnapshotStore::new(&config.codex_home)),
+            checkpoint_commits: config.checkpoint_commits,
+            run_limits: RunLimits::new(config.max_turns, config.max_wall_time),
+            tool_output_truncation: config.tool_output_truncation,
+            summary_client,
+            auto_compact_token_limit: config
+                .model_context_window
+                .map(|window| window * AUTO_COMPACT_PERCENT / 100),
             user_shell: default_shell,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            project_doc_max_bytes: config.project_doc_max_bytes,
         });
 
         // record the initial user instructions and environment context,
         // regardless of whether we restored items.
-        let mut conversation_items = Vec::<ResponseItem>::with_capacity(2);
+        let mut conversation_items = Vec::<ResponseItem>::with_capacity(3);
         if let Some(user_instructions) = turn_context.user_instructions.as_deref() {
             conversation_items.push(Prompt::format_user_instructions_message(user_instructions));
         }
@@ -550,6 +786,11 @@ impl Session {
             Some(turn_context.sandbox_policy.clone()),
             Some(sess.user_shell.clone()),
         )));
+        match repo_map_res {
+            Some(Ok(repo_map)) => conversation_items.push(repo_map.to_response_item()),
+            Some(Err(e)) => warn!("failed to build repo map: {e}"),
+            None => {}
+        }
         sess.record_conversation_items(&conversation_items).await;
 
         // Dispatch the SessionConfiguredEvent first and then report any errors.
@@ -560,6 +801,8 @@ impl Session {
                 model,
                 history_log_id,
                 history_entry_count,
+                project_docs,
+                protocol,
             }),
         })
         .chain(post_session_configured_error_events.into_iter());
@@ -604,8 +847,29 @@ impl Session {
         command: Vec<String>,
         cwd: PathBuf,
         reason: Option<String>,
-    ) -> oneshot::Receiver<ReviewDecision> {
+    ) -> oneshot::Receiver<ApprovalResponse> {
         let (tx_approve, rx_approve) = oneshot::channel();
+        let matched_rule = self
+            .state
+            .lock_unchecked()
+            .exec_policy
+            .check(&command)
+            .filter(|rule| rule.action == RuleAction::Ask)
+            .map(ToString::to_string);
+        let pending = PendingApproval {
+            tx_approve,
+            call_id: call_id.clone(),
+            subject: ApprovalSubject::Command {
+                command: command.clone(),
+                cwd: cwd.clone(),
+            },
+        };
+        let notification = UserNotification::ApprovalRequested {
+            turn_id: sub_id.clone(),
+            call_id: call_id.clone(),
+            kind: ApprovalKind::Exec,
+            summary: command_summary(&command),
+        };
         let event = Event {
             id: sub_id.clone(),
             msg: EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
@@ -613,13 +877,15 @@ impl Session {
                 command,
                 cwd,
                 reason,
+                matched_rule,
             }),
         };
         let _ = self.tx_event.send(event).await;
         {
             let mut state = self.state.lock_unchecked();
-            state.pending_approvals.insert(sub_id, tx_approve);
+            state.pending_approvals.insert(sub_id, pending);
         }
+        self.notifier.notify(notification);
         rx_approve
     }
 
@@ -630,13 +896,27 @@ impl Session {
         action: &ApplyPatchAction,
         reason: Option<String>,
         grant_root: Option<PathBuf>,
-    ) -> oneshot::Receiver<ReviewDecision> {
+    ) -> oneshot::Receiver<ApprovalResponse> {
         let (tx_approve, rx_approve) = oneshot::channel();
+        let changes = convert_apply_patch_to_protocol(action);
+        let mut files: Vec<PathBuf> = changes.keys().cloned().collect();
+        files.sort();
+        let notification = UserNotification::ApprovalRequested {
+            turn_id: sub_id.clone(),
+            call_id: call_id.clone(),
+            kind: ApprovalKind::Patch,
+            summary: files_summary(&files),
+        };
+        let pending = PendingApproval {
+            tx_approve,
+            call_id: call_id.clone(),
+            subject: ApprovalSubject::Patch { files },
+        };
         let event = Event {
             id: sub_id.clone(),
             msg: EventMsg::ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent {
                 call_id,
-                changes: convert_apply_patch_to_protocol(action),
+                changes,
                 reason,
                 grant_root,
             }),
@@ -644,15 +924,52 @@ impl Session {
         let _ = self.tx_event.send(event).await;
         {
             let mut state = self.state.lock_unchecked();
-            state.pending_approvals.insert(sub_id, tx_approve);
+            state.pending_approvals.insert(sub_id, pending);
         }
+        self.notifier.notify(notification);
         rx_approve
     }
 
-    pub fn notify_approval(&self, sub_id: &str, decision: ReviewDecision) {
-        let mut state = self.state.lock_unchecked();
-        if let Some(tx_approve) = state.pending_approvals.remove(sub_id) {
-            tx_approve.send(decision).ok();
+    pub fn notify_approval(
+        &self,
+        sub_id: &str,
+        decision: ReviewDecision,
+        accepted_files: Option<Vec<PathBuf>>,
+    ) {
+        let pending = self.state.lock_unchecked().pending_approvals.remove(sub_id);
+        let Some(PendingApproval {
+            tx_approve,
+            call_id,
+            subject,
+        }) = pending
+        else {
+            return;
+        };
+        crate::telemetry::record_approval(subject.kind(), decision);
+        self.audit(subject.decided(call_id, decision));
+        tx_approve
+            .send(ApprovalResponse {
+                decision,
+                accepted_files,
+            })
+            .ok();
+    }
+
+    /// Append `event` to the audit log, if there is one.
+    fn audit(&self, event: AuditEvent) {
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.record(event);
+        }
+    }
+
+    /// Record in the audit log that `client` is about to send a request to
+    /// its model provider.
+    fn audit_model_request(&self, client: &ModelClient, purpose: &str) {
+        if self.audit_log.is_some() {
+            self.audit(AuditEvent::NetworkRequest {
+                target: client.request_target(),
+                purpose: purpose.to_string(),
+            });
         }
     }
 
@@ -661,13 +978,27 @@ impl Session {
         state.approved_commands.insert(cmd);
     }
 
+    /// Runs commands matching `pattern`, a program and subcommand, without
+    /// approval but in the sandbox for the rest of the session, unless an
+    /// `[exec_policy]` rule says otherwise.
+    fn allow_command_pattern(&self, pattern: String) {
+        let mut state = self.state.lock_unchecked();
+        if !state.exec_policy.add_session_rule(pattern.clone()) {
+            warn!("ignoring allow_pattern `{pattern}`: expected a program and a subcommand");
+        }
+    }
+
     /// Records items to both the rollout and the chat completions/ZDR
-    /// transcript, if enabled.
+    /// transcript, if enabled. Secrets in tool output are redacted first.
     async fn record_conversation_items(&self, items: &[ResponseItem]) {
+        let items: Vec<ResponseItem> = items
+            .iter()
+            .map(|item| self.redactor.redact_item(item))
+            .collect();
         debug!("Recording items for conversation: {items:?}");
-        self.record_state_snapshot(items).await;
+        self.record_state_snapshot(&items).await;
 
-        self.state.lock_unchecked().history.record_items(items);
+        self.state.lock_unchecked().history.record_items(&items);
     }
 
     async fn record_state_snapshot(&self, items: &[ResponseItem]) {
@@ -688,6 +1019,24 @@ impl Session {
         }
     }
 
+    async fn record_exec_session_transcript(&self, mut transcript: ExecSessionTranscript) {
+        self.audit(AuditEvent::ExecSession {
+            exec_session_id: transcript.session_id,
+            command: transcript.command.clone(),
+            exit_code: transcript.exit_code,
+        });
+        transcript.output = self.redactor.redact(&transcript.output).into_owned();
+        let recorder = {
+            let guard = self.rollout.lock_unchecked();
+            guard.as_ref().cloned()
+        };
+        if let Some(rec) = recorder
+            && let Err(e) = rec.record_exec_session_transcript(transcript).await
+        {
+            error!("failed to record exec session transcript: {e:#}");
+        }
+    }
+
     async fn on_exec_command_begin(
         &self,
         turn_diff_tracker: &mut TurnDiffTracker,
@@ -699,6 +1048,7 @@ impl Session {
             command_for_display,
             cwd,
             apply_patch,
+            tokenizer: _,
         } = exec_command_context;
         let msg = match apply_patch {
             Some(ApplyPatchCommandContext {
@@ -706,6 +1056,14 @@ impl Session {
                 changes,
             }) => {
                 turn_diff_tracker.on_patch_begin(&changes);
+                // Update the snapshot as the turn goes so that an interrupted
+                // turn can be reverted too.
+                if let Some(snapshot) = turn_diff_tracker.snapshot() {
+                    self.state
+                        .lock_unchecked()
+                        .turn_snapshots
+                        .insert(sub_id.clone(), snapshot);
+                }
 
                 EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
                     call_id,
@@ -730,13 +1088,17 @@ impl Session {
         let _ = self.tx_event.send(event).await;
     }
 
+    #[allow(clippy::too_many_arguments)]
     async fn on_exec_command_end(
         &self,
         turn_diff_tracker: &mut TurnDiffTracker,
         sub_id: &str,
         call_id: &str,
         output: &ExecToolCallOutput,
+        sandbox_denied: bool,
+        sandbox_denials: Vec<SandboxDenial>,
         is_apply_patch: bool,
+        tokenizer: &dyn Tokenizer,
     ) {
         let ExecToolCallOutput {
             stdout,
@@ -744,11 +1106,13 @@ impl Session {
             aggregated_output,
             duration,
             exit_code,
+            killed_reason,
         } = output;
         // Send full stdout/stderr to clients; do not truncate.
         let stdout = stdout.text.clone();
         let stderr = stderr.text.clone();
-        let formatted_output = format_exec_output_str(output);
+        let formatted_output =
+            format_exec_output_str(output, tokenizer, self.tool_output_truncation);
         let aggregated_output: String = aggregated_output.text.clone();
 
         let msg = if is_apply_patch {
@@ -767,6 +1131,9 @@ impl Session {
                 exit_code: *exit_code,
                 duration: *duration,
                 formatted_output,
+                killed_reason: *killed_reason,
+                sandbox_denied,
+                sandbox_denials,
             })
         };
 
@@ -803,15 +1170,51 @@ impl Session {
         let is_apply_patch = begin_ctx.apply_patch.is_some();
         let sub_id = begin_ctx.sub_id.clone();
         let call_id = begin_ctx.call_id.clone();
+        let tokenizer = begin_ctx.tokenizer;
 
         self.on_exec_command_begin(turn_diff_tracker, begin_ctx.clone())
             .await;
 
+        // Under the container backend the container takes the place of the
+        // platform sandbox. A run the user escalated out of the sandbox gets
+        // full access inside the container rather than running on the host.
+        // `apply_patch` always runs on the host because it re-executes this
+        // binary.
+        let container_config = self.container.as_ref().filter(|_| !is_apply_patch);
+        let escalated_policy;
+        let (sandbox_type, sandbox_policy) = match container_config {
+            Some(_) if exec_args.sandbox_type == SandboxType::None => {
+                escalated_policy = SandboxPolicy::DangerFullAccess {
+                    read_blocklist: exec_args.sandbox_policy.get_read_blocklist().to_vec(),
+                };
+                (SandboxType::Container, &escalated_policy)
+            }
+            Some(_) => (SandboxType::Container, exec_args.sandbox_policy),
+            None => (exec_args.sandbox_type, exec_args.sandbox_policy),
+        };
+
+        if sandbox_type == SandboxType::WindowsRestrictedToken
+            && !sandbox_policy.has_full_disk_write_access()
+        {
+            let mut state = self.state.lock_unchecked();
+            for writable_root in sandbox_policy.get_writable_roots_with_cwd(&exec_args.params.cwd) {
+                state
+                    .windows_sandbox_acl_paths
+                    .extend(writable_root.read_only_subpaths);
+                state.windows_sandbox_acl_paths.insert(writable_root.root);
+            }
+        }
+
         let result = process_exec_tool_call(
             exec_args.params,
-            exec_args.sandbox_type,
-            exec_args.sandbox_policy,
-            exec_args.codex_linux_sandbox_exe,
+            sandbox_policy,
+            ExecSandboxOptions {
+                sandbox_type,
+                codex_linux_sandbox_exe: exec_args.codex_linux_sandbox_exe,
+                container_config,
+                seatbelt_extensions: &self.seatbelt_extensions,
+                limits: &self.exec_limits,
+            },
             exec_args.stdout_stream,
         )
         .await;
@@ -826,16 +1229,54 @@ impl Session {
                     stderr: StreamOutput::new(get_error_message_ui(e)),
                     aggregated_output: StreamOutput::new(get_error_message_ui(e)),
                     duration: Duration::default(),
+                    killed_reason: matches!(e, CodexErr::Sandbox(SandboxErr::Timeout))
+                        .then_some(ExecKilledReason::Timeout),
                 };
                 &output_stderr
             }
         };
+        if self.audit_log.is_some() {
+            self.audit(match &begin_ctx.apply_patch {
+                Some(ApplyPatchCommandContext {
+                    user_explicitly_approved_this_action,
+                    changes,
+                }) => AuditEvent::Patch {
+                    call_id: call_id.clone(),
+                    auto_approved: !user_explicitly_approved_this_action,
+                    success: borrowed.exit_code == 0,
+                    files: crate::audit_log::summarize_patch(changes),
+                },
+                None => AuditEvent::Command {
+                    call_id: call_id.clone(),
+                    command: begin_ctx.command_for_display.clone(),
+                    cwd: begin_ctx.cwd.clone(),
+                    sandbox: crate::audit_log::sandbox_name(sandbox_type),
+                    network_access: sandbox_type == SandboxType::None
+                        || sandbox_policy.has_full_network_access(),
+                    exit_code: borrowed.exit_code,
+                    duration_ms: borrowed.duration.as_millis() as u64,
+                    killed_reason: borrowed.killed_reason,
+                },
+            });
+        }
+        let sandbox_denials = match &result {
+            Err(CodexErr::Sandbox(SandboxErr::Denied(_, _, _, denials))) => denials.clone(),
+            _ => Vec::new(),
+        };
         self.on_exec_command_end(
             turn_diff_tracker,
             &sub_id,
             &call_id,
             borrowed,
+            !sandbox_denials.is_empty()
+                || matches!(
+                    &result,
+                    Err(CodexErr::Sandbox(SandboxErr::Denied(_, stdout, stderr, _)))
+                        if output_shows_sandbox_denial(stdout, stderr)
+                ),
+            sandbox_denials,
             is_apply_patch,
+            tokenizer,
         )
         .await;
 
@@ -845,6 +1286,47 @@ impl Session {
     /// Helper that emits a BackgroundEvent with the given message. This keeps
     /// the call‑sites terse so adding more diagnostics does not clutter the
     /// core agent logic.
+    /// Add the `AGENTS.md` files that apply to `touched` and are not in the
+    /// history yet, so that they guide the rest of the turn.
+    async fn record_nested_project_docs(
+        &self,
+        sub_id: &str,
+        turn_context: &TurnContext,
+        touched: &[PathBuf],
+    ) {
+        if self.project_doc_max_bytes == 0 || touched.is_empty() {
+            return;
+        }
+        let paths: Vec<PathBuf> = {
+            let state = self.state.lock_unchecked();
+            nested_doc_paths(&turn_context.cwd, touched)
+                .into_iter()
+                .filter(|path| !state.nested_project_docs.contains(path))
+                .collect()
+        };
+        for path in paths {
+            self.state
+                .lock_unchecked()
+                .nested_project_docs
+                .insert(path.clone());
+            match read_nested_doc(&path, self.project_doc_max_bytes).await {
+                Ok(Some(doc)) => {
+                    self.record_conversation_items(&[Prompt::format_user_instructions_message(
+                        &doc,
+                    )])
+                    .await;
+                    self.notify_background_event(
+                        sub_id,
+                        format!("Loaded instructions from {}", path.display()),
+                    )
+                    .await;
+                }
+                Ok(None) => {}
+                Err(e) => warn!("failed to read {}: {e}", path.display()),
+            }
+        }
+    }
+
     async fn notify_background_event(&self, sub_id: &str, message: impl Into<String>) {
         let event = Event {
             id: sub_id.to_string(),
@@ -855,6 +1337,32 @@ impl Session {
         let _ = self.tx_event.send(event).await;
     }
 
+    /// Report that the session runs in a degraded mode, unless `kind` was
+    /// already reported.
+    async fn notify_degraded_mode(
+        &self,
+        sub_id: &str,
+        kind: DegradedModeKind,
+        message: impl Into<String>,
+    ) {
+        if !self
+            .state
+            .lock_unchecked()
+            .reported_degraded_modes
+            .insert(kind)
+        {
+            return;
+        }
+        let event = Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::DegradedMode(DegradedModeEvent {
+                kind,
+                message: message.into(),
+            }),
+        };
+        let _ = self.tx_event.send(event).await;
+    }
+
     async fn notify_stream_error(&self, sub_id: &str, message: impl Into<String>) {
         let event = Event {
             id: sub_id.to_string(),
@@ -914,35 +1422,6 @@ impl Session {
             task.abort(TurnAbortReason::Interrupted);
         }
     }
-
-    /// Spawn the configured notifier (if any) with the given JSON payload as
-    /// the last argument. Failures are logged but otherwise ignored so that
-    /// notification issues do not interfere with the main workflow.
-    fn maybe_notify(&self, notification: UserNotification) {
-        let Some(notify_command) = &self.notify else {
-            return;
-        };
-
-        if notify_command.is_empty() {
-            return;
-        }
-
-        let Ok(json) = serde_json::to_string(&notification) else {
-            error!("failed to serialise notification payload");
-            return;
-        };
-
-        let mut command = std::process::Command::new(&notify_command[0]);
-        if notify_command.len() > 1 {
-            command.args(&notify_command[1..]);
-        }
-        command.arg(json);
-
-        // Fire-and-forget – we do not wait for completion.
-        if let Err(e) = command.spawn() {
-            warn!("failed to spawn notifier '{}': {e}", notify_command[0]);
-        }
-    }
 }
 
 impl Drop for Session {
@@ -958,6 +1437,8 @@ pub(crate) struct ExecCommandContext {
     pub(crate) command_for_display: Vec<String>,
     pub(crate) cwd: PathBuf,
     pub(crate) apply_patch: Option<ApplyPatchCommandContext>,
+    /// Counts tokens when the output is truncated for the model.
+    pub(crate) tokenizer: &'static dyn Tokenizer,
 }
 
 #[derive(Clone, Debug)]
@@ -994,19 +1475,34 @@ impl AgentTask {
         }
     }
 
-    fn compact(
+    fn compact(sess: Arc<Session>, turn_context: Arc<TurnContext>, sub_id: String) -> Self {
+        let handle = {
+            let sess = sess.clone();
+            let sub_id = sub_id.clone();
+            let tc = Arc::clone(&turn_context);
+            tokio::spawn(async move { run_compact_task(sess, tc.as_ref(), sub_id).await })
+                .abort_handle()
+        };
+        Self {
+            sess,
+            sub_id,
+            handle,
+        }
+    }
+
+    fn user_command(
         sess: Arc<Session>,
         turn_context: Arc<TurnContext>,
         sub_id: String,
-        input: Vec<InputItem>,
-        compact_instructions: String,
+        command: Vec<String>,
+        cwd: PathBuf,
     ) -> Self {
         let handle = {
             let sess = sess.clone();
             let sub_id = sub_id.clone();
             let tc = Arc::clone(&turn_context);
             tokio::spawn(async move {
-                run_compact_task(sess, tc.as_ref(), sub_id, input, compact_instructions).await
+                run_user_command_task(sess, tc.as_ref(), sub_id, command, cwd).await
             })
             .abort_handle()
         };
@@ -1036,7 +1532,7 @@ impl AgentTask {
 async fn submission_loop(
     sess: Arc<Session>,
     turn_context: TurnContext,
-    config: Arc<Config>,
+    mut config: Arc<Config>,
     rx_sub: Receiver<Submission>,
 ) {
     // Wrap once to avoid cloning TurnContext for each task.
@@ -1119,6 +1615,11 @@ async fn submission_loop(
 
                 // Install the new persistent context for subsequent tasks/turns.
                 turn_context = Arc::new(new_turn_context);
+                if cwd.is_some() || sandbox_policy.is_some() {
+                    sess.mcp_connection_manager
+                        .set_roots(mcp_roots(&new_cwd, &new_sandbox_policy))
+                        .await;
+                }
                 if cwd.is_some() || approval_policy.is_some() || sandbox_policy.is_some() {
                     sess.record_conversation_items(&[ResponseItem::from(EnvironmentContext::new(
                         cwd,
@@ -1130,6 +1631,85 @@ async fn submission_loop(
                     .await;
                 }
             }
+            Op::SwitchProfile { name } => {
+                let new_config = match config.reload_with_profile(&name) {
+                    Ok(new_config) => new_config,
+                    Err(e) => {
+                        let event = Event {
+                            id: sub.id,
+                            msg: EventMsg::Error(ErrorEvent {
+                                message: format!("Failed to switch to profile `{name}`: {e}"),
+                                kind: ErrorKind::Other,
+                            }),
+                        };
+                        sess.send_event(event).await;
+                        continue;
+                    }
+                };
+
+                // Like `OverrideTurnContext`, but every setting comes from the
+                // reloaded config; only the conversation itself carries over.
+                let prev = Arc::clone(&turn_context);
+                let client = ModelClient::new(
+                    Arc::new(new_config.clone()),
+                    prev.client.get_auth_manager(),
+                    new_config.model_provider.clone(),
+                    new_config.model_reasoning_effort,
+                    new_config.model_reasoning_summary,
+                    sess.session_id,
+                );
+                let tools_config = ToolsConfig::new(&ToolsConfigParams {
+                    model_family: &new_config.model_family,
+                    approval_policy: new_config.approval_policy,
+                    sandbox_policy: new_config.sandbox_policy.clone(),
+                    include_plan_tool: new_config.include_plan_tool,
+                    include_apply_patch_tool: new_config.include_apply_patch_tool,
+                    include_web_search_request: new_config.tools_web_search_request,
+                    use_streamable_shell_tool: new_config.use_experimental_streamable_shell_tool,
+                });
+                turn_context = Arc::new(TurnContext {
+                    client,
+                    tools_config,
+                    user_instructions: prev.user_instructions.clone(),
+                    base_instructions: new_config.base_instructions.clone(),
+                    approval_policy: new_config.approval_policy,
+                    sandbox_policy: new_config.sandbox_policy.clone(),
+                    shell_environment_policy: new_config.shell_environment_policy.clone(),
+                    cwd: prev.cwd.clone(),
+                    disable_response_storage: new_config.disable_response_storage,
+                });
+
+                let approval_changed = new_config.approval_policy != prev.approval_policy;
+                let sandbox_changed = new_config.sandbox_policy != prev.sandbox_policy;
+                if sandbox_changed {
+                    sess.mcp_connection_manager
+                        .set_roots(mcp_roots(&prev.cwd, &new_config.sandbox_policy))
+                        .await;
+                }
+                if approval_changed || sandbox_changed {
+                    sess.record_conversation_items(&[ResponseItem::from(EnvironmentContext::new(
+                        None,
+                        approval_changed.then_some(new_config.approval_policy),
+                        sandbox_changed.then(|| new_config.sandbox_policy.clone()),
+                        None,
+                    ))])
+                    .await;
+                }
+
+                let event = Event {
+                    id: sub.id,
+                    msg: EventMsg::ProfileSwitched(ProfileSwitchedEvent {
+                        profile: name,
+                        model: new_config.model.clone(),
+                        model_provider_id: new_config.model_provider_id.clone(),
+                        reasoning_effort: new_config.model_reasoning_effort,
+                        approval_policy: new_config.approval_policy,
+                        sandbox_policy: new_config.sandbox_policy.clone(),
+                    }),
+                };
+                config = Arc::new(new_config);
+                sess.send_event(event).await;
+            }
             Op::UserInput { items } => {
                 // attempt to inject input into current task
                 if let Err(items) = sess.inject_input(items) {
@@ -1201,18 +1781,36 @@ async fn submission_loop(
                     sess.set_task(task);
                 }
             }
-            Op::ExecApproval { id, decision } => match decision {
-                ReviewDecision::Abort => {
+            Op::ExecApproval {
+                id,
+                decision,
+                allow_pattern,
+            } => {
+                // Added before the command is released, so the next similar
+                // command is already covered.
+                if let Some(pattern) = allow_pattern
+                    && matches!(
+                        decision,
+                        ReviewDecision::Approved | ReviewDecision::ApprovedForSession
+                    )
+                {
+                    sess.allow_command_pattern(pattern);
+                }
+                sess.notify_approval(&id, decision, None);
+                if decision == ReviewDecision::Abort {
                     sess.interrupt_task();
                 }
-                other => sess.notify_approval(&id, other),
-            },
-            Op::PatchApproval { id, decision } => match decision {
-                ReviewDecision::Abort => {
+            }
+            Op::PatchApproval {
+                id,
+                decision,
+                accepted_files,
+            } => {
+                sess.notify_approval(&id, decision, accepted_files);
+                if decision == ReviewDecision::Abort {
                     sess.interrupt_task();
                 }
-                other => sess.notify_approval(&id, other),
-            },
+            }
             Op::AddToHistory { text } => {
                 let id = sess.session_id;
                 let config = config.clone();
@@ -1276,26 +1874,138 @@ async fn submission_loop(
                 }
             }
             Op::Compact => {
-                // Create a summarization request as user input
-                const SUMMARIZATION_PROMPT: &str = include_str!("prompt_for_compact_command.md");
-
-                // Attempt to inject input into current task
-                if let Err(items) = sess.inject_input(vec![InputItem::Text {
-                    text: "Start Summarization".to_string(),
-                }]) {
-                    let task = AgentTask::compact(
+                // A running task compacts before its next request.
+                let task_running = {
+                    let mut state = sess.state.lock_unchecked();
+                    state.compact_requested = state.current_task.is_some();
+                    state.compact_requested
+                };
+                if !task_running {
+                    let task = AgentTask::compact(sess.clone(), Arc::clone(&turn_context), sub.id);
+                    sess.set_task(task);
+                }
+            }
+            Op::RunUserCommand { argv, cwd } => {
+                // Unlike user input, the command cannot be folded into a
+                // running turn, and replacing the turn would abort it.
+                if sess.state.lock_unchecked().current_task.is_some() {
+                    let event = Event {
+                        id: sub.id,
+                        msg: EventMsg::Error(ErrorEvent {
+                            message: "Cannot run a command while a task is in progress".to_string(),
+                            kind: ErrorKind::Other,
+                        }),
+                    };
+                    sess.send_event(event).await;
+                } else {
+                    let task = AgentTask::user_command(
                         sess.clone(),
                         Arc::clone(&turn_context),
                         sub.id,
-                        items,
-                        SUMMARIZATION_PROMPT.to_string(),
+                        argv,
+                        cwd,
                     );
                     sess.set_task(task);
                 }
             }
+            Op::RunBackground { command } => {
+                // Approval is answered through this loop, so wait for it
+                // elsewhere.
+                let sess = sess.clone();
+                let turn_context = Arc::clone(&turn_context);
+                tokio::spawn(async move {
+                    start_background_task(sess, turn_context, sub.id, command).await;
+                });
+            }
+            Op::ListBackgroundTasks => {
+                let event = Event {
+                    id: sub.id,
+                    msg: EventMsg::ListBackgroundTasksResponse(ListBackgroundTasksResponseEvent {
+                        tasks: sess.background_tasks.list(),
+                    }),
+                };
+                sess.send_event(event).await;
+            }
+            Op::KillBackgroundTask { id } => {
+                if let Err(message) = sess.background_tasks.kill(id) {
+                    let event = Event {
+                        id: sub.id,
+                        msg: EventMsg::Error(ErrorEvent {
+                            message,
+                            kind: ErrorKind::Other,
+                        }),
+                    };
+                    sess.send_event(event).await;
+                }
+            }
+            Op::RegenerateRepoMap => {
+                // Building the map can take a while in a large repository;
+                // keep handling submissions meanwhile.
+                let sess = sess.clone();
+                let cwd = turn_context.cwd.clone();
+                let codex_home = config.codex_home.clone();
+                let exclude = config.project_ignore_matcher();
+                tokio::spawn(async move {
+                    match RepoMap::generate(cwd, codex_home, exclude).await {
+                        Ok(repo_map) => {
+                            sess.record_conversation_items(&[repo_map.to_response_item()])
+                                .await;
+                            sess.notify_background_event(
+                                &sub.id,
+                                format!("Repo map regenerated ({} files)", repo_map.files),
+                            )
+                            .await;
+                        }
+                        Err(e) => {
+                            let event = Event {
+                                id: sub.id,
+                                msg: EventMsg::Error(ErrorEvent {
+                                    message: format!("Failed to build repo map: {e}"),
+                                    kind: ErrorKind::Other,
+                                }),
+                            };
+                            sess.send_event(event).await;
+                        }
+                    }
+                });
+            }
+            Op::RevertTurn { turn_id } => {
+                let event = match revert_turn(&sess, &turn_id) {
+                    Ok(paths) => Event {
+                        id: sub.id,
+                        msg: EventMsg::TurnReverted(TurnRevertedEvent { turn_id, paths }),
+                    },
+                    Err(message) => Event {
+                        id: sub.id,
+                        msg: EventMsg::Error(ErrorEvent {
+                            message,
+                            kind: ErrorKind::Other,
+                        }),
+                    },
+                };
+                sess.send_event(event).await;
+            }
             Op::Shutdown => {
                 info!("Shutting down Codex instance");
 
+                sess.background_tasks.kill_all().await;
+
+                let acl_paths: Vec<PathBuf> =
+                    std::mem::take(&mut sess.state.lock_unchecked().windows_sandbox_acl_paths)
+                        .into_iter()
+                        .collect();
+                if !acl_paths.is_empty()
+                    && let Err(e) = revoke_windows_sandbox_access(acl_paths).await
+                {
+                    warn!("failed to revoke Windows sandbox access: {e}");
+                }
+
+                // Exec sessions do not outlive the conversation; keep what they
+                // printed in the rollout before it is closed.
+                for transcript in sess.session_manager.close_all().await {
+                    sess.record_exec_session_transcript(transcript).await;
+                }
+
                 // Gracefully flush and shutdown rollout recorder on session end so tests
                 // that inspect the rollout file do not race with the background writer.
                 let recorder_opt = sess.rollout.lock_unchecked().take();
@@ -1307,6 +2017,7 @@ async fn submission_loop(
                         id: sub.id.clone(),
                         msg: EventMsg::Error(ErrorEvent {
                             message: "Failed to shutdown rollout recorder".to_string(),
+                            kind: ErrorKind::Other,
                         }),
                     };
                     if let Err(e) = sess.tx_event.send(event).await {
@@ -1346,10 +2057,32 @@ async fn submission_loop(
     debug!("Agent loop exited");
 }
 
-/// Takes a user message as input and runs a loop where, at each turn, the model
-/// replies with either:
-///
-/// - requested function calls
+/// Restores the files touched during `turn_id` to their pre-images. Returns
+/// the paths that changed, or a message for the user.
+fn revert_turn(sess: &Session, turn_id: &str) -> Result<Vec<PathBuf>, String> {
+    let Some(store) = &sess.snapshot_store else {
+        return Err("Reverting turns requires `snapshot_turns = true` in config.toml".to_string());
+    };
+    let snapshot = {
+        let state = sess.state.lock_unchecked();
+        // Reverting under a running turn would race with its edits.
+        if state.current_task.is_some() {
+            return Err("Cannot revert a turn while a task is in progress".to_string());
+        }
+        state.turn_snapshots.get(turn_id).cloned()
+    };
+    let Some(snapshot) = snapshot else {
+        return Err(format!("No file changes recorded for turn {turn_id}"));
+    };
+    snapshot
+        .restore(store)
+        .map_err(|e| format!("Failed to revert turn {turn_id}: {e}"))
+}
+
+/// Takes a user message as input and runs a loop where, at each turn, the model
+/// replies with either:
+///
+/// - requested function calls
 /// - an assistant message
 ///
 /// While it is possible for the model to return multiple of these items in a
@@ -1359,6 +2092,11 @@ async fn submission_loop(
 ///   back to the model in the next turn.
 /// - If the model sends only an assistant message, we record it in the
 ///   conversation history and consider the task complete.
+#[instrument(
+    name = "turn",
+    skip_all,
+    fields(sub_id = %sub_id, model = %turn_context.client.get_model())
+)]
 async fn run_task(
     sess: Arc<Session>,
     turn_context: &TurnContext,
@@ -1381,11 +2119,18 @@ async fn run_task(
         .await;
 
     let mut last_agent_message: Option<String> = None;
+    let mut completed = false;
+    let mut truncated: Option<TruncationReason> = None;
     // Although from the perspective of codex.rs, TurnDiffTracker has the lifecycle of a Task which contains
     // many turns, from the perspective of the user, it is a single turn.
-    let mut turn_diff_tracker = TurnDiffTracker::new();
+    let mut turn_diff_tracker = match &sess.snapshot_store {
+        Some(store) => TurnDiffTracker::with_snapshot_store(store.clone()),
+        None => TurnDiffTracker::new(),
+    };
 
     loop {
+        maybe_compact_history(&sess, turn_context, &sub_id).await;
+
         // Note that pending_input would be something like a message the user
         // submitted through the UI while the model was running. Though the UI
         // may support this, the model might not.
@@ -1416,15 +2161,30 @@ async fn run_task(
                 })
             })
             .collect();
-        match run_turn(
+        if let Err(reason) = sess.run_limits.start_turn() {
+            truncated = Some(reason);
+            break;
+        }
+        let turn = run_turn(
             &sess,
             turn_context,
             &mut turn_diff_tracker,
             sub_id.clone(),
             turn_input,
-        )
-        .await
-        {
+        );
+        // A turn cut short by the deadline is dropped before any of it is
+        // recorded, so the history stays consistent.
+        let turn_result = match sess.run_limits.deadline() {
+            Some(deadline) => match tokio::time::timeout_at(deadline, turn).await {
+                Ok(turn_result) => turn_result,
+                Err(_) => {
+                    truncated = Some(TruncationReason::MaxWallTime);
+                    break;
+                }
+            },
+            None => turn.await,
+        };
+        match turn_result {
             Ok(turn_output) => {
                 let mut items_to_record_in_conversation_history = Vec::<ResponseItem>::new();
                 let mut responses = Vec::<ResponseInputItem>::new();
@@ -1525,25 +2285,42 @@ async fn run_task(
                         .await;
                 }
 
+                // AGENTS.md files next to the files changed so far apply from
+                // the next request on.
+                if !responses.is_empty() {
+                    sess.record_nested_project_docs(
+                        &sub_id,
+                        turn_context,
+                        &turn_diff_tracker.touched_paths(),
+                    )
+                    .await;
+                }
+
                 if responses.is_empty() {
                     debug!("Turn completed");
                     last_agent_message = get_last_assistant_message_from_turn(
                         &items_to_record_in_conversation_history,
                     );
-                    sess.maybe_notify(UserNotification::AgentTurnComplete {
+                    sess.notifier.notify(UserNotification::AgentTurnComplete {
                         turn_id: sub_id.clone(),
                         input_messages: turn_input_messages,
                         last_assistant_message: last_agent_message.clone(),
                     });
+                    completed = true;
                     break;
                 }
             }
             Err(e) => {
                 info!("Turn error: {e:#}");
+                sess.notifier.notify(UserNotification::Error {
+                    turn_id: sub_id.clone(),
+                    message: e.to_string(),
+                });
                 let event = Event {
                     id: sub_id.clone(),
                     msg: EventMsg::Error(ErrorEvent {
                         message: e.to_string(),
+                        kind: e.kind(),
                     }),
                 };
                 sess.tx_event.send(event).await.ok();
@@ -1552,14 +2329,104 @@ async fn run_task(
             }
         }
     }
+    let checkpoint_commit = if completed && sess.checkpoint_commits {
+        commit_checkpoint(&sess, turn_context, last_agent_message.as_deref()).await
+    } else {
+        None
+    };
     sess.remove_task(&sub_id);
     let event = Event {
         id: sub_id,
-        msg: EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }),
+        msg: EventMsg::TaskComplete(TaskCompleteEvent {
+            last_agent_message,
+            checkpoint_commit,
+            truncated,
+        }),
     };
     sess.tx_event.send(event).await.ok();
 }
 
+/// Commit the working tree to the session's checkpoint branch. Returns the
+/// commit SHA, or `None` if nothing changed or the commit failed.
+async fn commit_checkpoint(
+    sess: &Session,
+    turn_context: &TurnContext,
+    last_agent_message: Option<&str>,
+) -> Option<String> {
+    let pending = match checkpoint::prepare(&turn_context.cwd, sess.session_id).await {
+        Ok(Some(pending)) => pending,
+        Ok(None) => return None,
+        Err(e) => {
+            warn!("failed to prepare checkpoint commit: {e}");
+            return None;
+        }
+    };
+    sess.audit_model_request(&turn_context.client, "commit_message");
+    let message = match generate_checkpoint_message(turn_context, &pending.diff).await {
+        Ok(Some(message)) => message,
+        result => {
+            if let Err(e) = result {
+                warn!("failed to generate checkpoint commit message: {e}");
+            }
+            // Fall back to the first line of the agent's final message.
+            last_agent_message
+                .and_then(|message| message.lines().find(|line| !line.trim().is_empty()))
+                .map(|line| line.trim().to_string())
+                .unwrap_or_else(|| "Codex checkpoint".to_string())
+        }
+    };
+    match pending.commit(&message).await {
+        Ok(sha) => Some(sha),
+        Err(e) => {
+            warn!("failed to create checkpoint commit: {e}");
+            None
+        }
+    }
+}
+
+/// Ask the model for a commit message describing `diff`. The request is made
+/// outside the conversation, so it is not recorded in the history.
+async fn generate_checkpoint_message(
+    turn_context: &TurnContext,
+    diff: &str,
+) -> CodexResult<Option<String>> {
+    const CHECKPOINT_PROMPT: &str = include_str!("prompt_for_checkpoint_commit_message.md");
+    const MAX_DIFF_BYTES: usize = 32 * 1024;
+
+    let mut text = take_bytes_at_char_boundary(diff, MAX_DIFF_BYTES).to_string();
+    if text.len() < diff.len() {
+        text.push_str("\n[diff truncated]");
+    }
+    let prompt = Prompt {
+        input: vec![ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText { text }],
+        }],
+        store: !turn_context.disable_response_storage,
+        tools: Vec::new(),
+        base_instructions_override: Some(CHECKPOINT_PROMPT.to_string()),
+        max_output_tokens: None,
+    };
+
+    let mut stream = turn_context.client.clone().stream(&prompt).await?;
+    let mut message = None;
+    while let Some(event) = stream.next().await {
+        match event? {
+            ResponseEvent::OutputItemDone(item) => {
+                if let Some(text) = get_last_assistant_message_from_turn(&[item]) {
+                    message = Some(text);
+                }
+            }
+            ResponseEvent::Completed { .. } => break,
+            _ => {}
+        }
+    }
+    Ok(message
+        .map(|message| message.trim().to_string())
+        .filter(|message| !message.is_empty()))
+}
+
 async fn run_turn(
     sess: &Session,
     turn_context: &TurnContext,
@@ -1577,6 +2444,7 @@ async fn run_turn(
         store: !turn_context.disable_response_storage,
         tools,
         base_instructions_override: turn_context.base_instructions.clone(),
+        max_output_tokens: None,
     };
 
     let mut retries = 0;
@@ -1631,6 +2499,14 @@ struct ProcessedResponseItem {
     response: Option<ResponseInputItem>,
 }
 
+#[instrument(
+    name = "model_request",
+    skip_all,
+    fields(
+        model = %turn_context.client.get_model(),
+        provider = %turn_context.client.get_provider().name,
+    )
+)]
 async fn try_run_turn(
     sess: &Session,
     turn_context: &TurnContext,
@@ -1693,6 +2569,7 @@ async fn try_run_turn(
         })
     };
 
+    sess.audit_model_request(&turn_context.client, "turn");
     let mut stream = turn_context.client.clone().stream(&prompt).await?;
 
     let mut output = Vec::new();
@@ -1747,15 +2624,51 @@ async fn try_run_turn(
                 response_id: _,
                 token_usage,
             } => {
-                if let Some(token_usage) = token_usage {
-                    sess.tx_event
-                        .send(Event {
-                            id: sub_id.to_string(),
-                            msg: EventMsg::TokenCount(token_usage),
-                        })
-                        .await
-                        .ok();
-                }
+                let model_family = turn_context.client.get_model_family();
+                let tokenizer = model_family.tokenizer.tokenizer();
+                let instructions = prompt.get_full_instructions(&model_family);
+                let output_items: Vec<ResponseItem> = output
+                    .iter()
+                    .map(|processed| processed.item.clone())
+                    .collect();
+                // Providers that do not report usage (common among OSS
+                // servers) get an estimate from the model's tokenizer.
+                let token_usage = token_usage.unwrap_or_else(|| {
+                    estimate_token_usage(
+                        tokenizer,
+                        &instructions,
+                        &prompt.tools,
+                        &prompt.input,
+                        &output_items,
+                    )
+                });
+                let breakdown = attribute_token_usage(
+                    tokenizer,
+                    &instructions,
+                    &prompt.tools,
+                    &prompt.input,
+                    &output_items,
+                    &token_usage,
+                );
+                sess.state.lock_unchecked().context_tokens = token_usage.tokens_in_context_window();
+                crate::telemetry::record_token_usage(
+                    &turn_context.client.get_model(),
+                    &token_usage,
+                );
+                sess.tx_event
+                    .send(Event {
+                        id: sub_id.to_string(),
+                        msg: EventMsg::TokenCount(token_usage),
+                    })
+                    .await
+                    .ok();
+                sess.tx_event
+                    .send(Event {
+                        id: sub_id.to_string(),
+                        msg: EventMsg::TokenUsageBreakdown(breakdown),
+                    })
+                    .await
+                    .ok();
 
                 let unified_diff = turn_diff_tracker.get_unified_diff();
                 if let Ok(Some(unified_diff)) = unified_diff {
@@ -1810,13 +2723,7 @@ async fn try_run_turn(
     }
 }
 
-async fn run_compact_task(
-    sess: Arc<Session>,
-    turn_context: &TurnContext,
-    sub_id: String,
-    input: Vec<InputItem>,
-    compact_instructions: String,
-) {
+async fn run_compact_task(sess: Arc<Session>, turn_context: &TurnContext, sub_id: String) {
     let start_event = Event {
         id: sub_id.clone(),
         msg: EventMsg::TaskStarted,
@@ -1825,71 +2732,277 @@ async fn run_compact_task(
         return;
     }
 
-    let initial_input_for_turn: ResponseInputItem = ResponseInputItem::from(input);
-    let turn_input: Vec<ResponseItem> =
-        sess.turn_input_with_history(vec![initial_input_for_turn.clone().into()]);
+    match compact_history(&sess, turn_context, &sub_id, 0).await {
+        Ok(Some(_)) => {}
+        Ok(None) => {
+            sess.notify_background_event(&sub_id, "Nothing to compact yet")
+                .await;
+        }
+        Err(CodexErr::Interrupted) => return,
+        Err(e) => {
+            let event = Event {
+                id: sub_id.clone(),
+                msg: EventMsg::Error(ErrorEvent {
+                    message: e.to_string(),
+                    kind: e.kind(),
+                }),
+            };
+            sess.send_event(event).await;
+        }
+    }
 
-    let prompt = Prompt {
-        input: turn_input,
-        store: !turn_context.disable_response_storage,
-        tools: Vec::new(),
-        base_instructions_override: Some(compact_instructions.clone()),
+    sess.remove_task(&sub_id);
+    let event = Event {
+        id: sub_id,
+        msg: EventMsg::TaskComplete(TaskCompleteEvent {
+            last_agent_message: None,
+            checkpoint_commit: None,
+            truncated: None,
+        }),
     };
+    sess.send_event(event).await;
+}
 
-    let max_retries = turn_context.client.get_provider().stream_max_retries();
-    let mut retries = 0;
+/// Compact the history before the next request of a task if it nears the
+/// context window or `Op::Compact` was submitted while the task ran.
+async fn maybe_compact_history(sess: &Session, turn_context: &TurnContext, sub_id: &str) {
+    let (requested, near_limit) = {
+        let mut state = sess.state.lock_unchecked();
+        let near_limit = sess
+            .auto_compact_token_limit
+            .is_some_and(|limit| state.context_tokens >= limit);
+        (std::mem::take(&mut state.compact_requested), near_limit)
+    };
+    if !requested && !near_limit {
+        return;
+    }
+    // Always keep the turn in progress; near the limit, keep some of what
+    // led up to it too.
+    let keep_turns = if near_limit {
+        AUTO_COMPACT_KEEP_TURNS
+    } else {
+        1
+    };
+    if let Err(e) = compact_history(sess, turn_context, sub_id, keep_turns).await {
+        warn!("failed to compact history: {e}");
+        sess.notify_background_event(sub_id, format!("Failed to compact the conversation: {e}"))
+            .await;
+    }
+}
 
-    loop {
-        let attempt_result = drain_to_completed(&sess, turn_context, &sub_id, &prompt).await;
+/// Replace all but the last `keep_turns` turns of the history with a summary
+/// and report it with `ContextCompacted`. Returns the size of the summary in
+/// tokens, or `None` when there was nothing to compact.
+async fn compact_history(
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: &str,
+    keep_turns: usize,
+) -> CodexResult<Option<u64>> {
+    let history = sess.state.lock_unchecked().history.contents();
+    let Some(split) = compact::split_history(&history, keep_turns) else {
+        return Ok(None);
+    };
+    let transcript = compact::render_transcript(&split.older);
+    let client = sess.summary_client.as_ref().unwrap_or(&turn_context.client);
 
-        match attempt_result {
-            Ok(()) => break,
-            Err(CodexErr::Interrupted) => return,
-            Err(e) => {
-                if retries < max_retries {
-                    retries += 1;
-                    let delay = backoff(retries);
-                    sess.notify_stream_error(
-                        &sub_id,
-                        format!(
-                            "stream error: {e}; retrying {retries}/{max_retries} in {delay:?}…"
-                        ),
-                    )
-                    .await;
-                    tokio::time::sleep(delay).await;
-                    continue;
-                } else {
-                    let event = Event {
-                        id: sub_id.clone(),
-                        msg: EventMsg::Error(ErrorEvent {
-                            message: e.to_string(),
-                        }),
-                    };
-                    sess.send_event(event).await;
-                    return;
-                }
+    let max_retries = client.get_provider().stream_max_retries();
+    let mut retries = 0;
+    let summary = loop {
+        sess.audit_model_request(client, "compaction");
+        match compact::summarize(client, &transcript, !turn_context.disable_response_storage).await
+        {
+            Ok(summary) => break summary,
+            Err(CodexErr::Interrupted) => return Err(CodexErr::Interrupted),
+            Err(e) if retries < max_retries => {
+                retries += 1;
+                let delay = backoff(retries);
+                sess.notify_stream_error(
+                    sub_id,
+                    format!("stream error: {e}; retrying {retries}/{max_retries} in {delay:?}…"),
+                )
+                .await;
+                tokio::time::sleep(delay).await;
             }
+            Err(e) => return Err(e),
         }
+    };
+
+    let summary_tokens = client
+        .get_model_family()
+        .tokenizer
+        .tokenizer()
+        .count_tokens(&summary);
+    let mut items = split.context;
+    items.push(compact::summary_message(&summary));
+    items.extend(split.recent);
+    {
+        let mut state = sess.state.lock_unchecked();
+        state.history.replace(items);
+        // Unknown until the next response.
+        state.context_tokens = 0;
     }
+    sess.send_event(Event {
+        id: sub_id.to_string(),
+        msg: EventMsg::ContextCompacted(ContextCompactedEvent { summary_tokens }),
+    })
+    .await;
+    Ok(Some(summary_tokens))
+}
 
-    sess.remove_task(&sub_id);
-    let event = Event {
+/// Runs a command requested directly by the user and records the command
+/// and its result as a user message, so the model can take it into account
+/// on the next turn.
+async fn run_user_command_task(
+    sess: Arc<Session>,
+    turn_context: &TurnContext,
+    sub_id: String,
+    command: Vec<String>,
+    cwd: PathBuf,
+) {
+    let start_event = Event {
         id: sub_id.clone(),
-        msg: EventMsg::AgentMessage(AgentMessageEvent {
-            message: "Compact task completed".to_string(),
-        }),
+        msg: EventMsg::TaskStarted,
     };
-    sess.send_event(event).await;
+    if sess.tx_event.send(start_event).await.is_err() {
+        return;
+    }
+
+    let params = ExecParams {
+        command: command.clone(),
+        cwd,
+        timeout_ms: None,
+        env: create_env(&turn_context.shell_environment_policy),
+        with_escalated_permissions: None,
+        justification: None,
+    };
+    let mut turn_diff_tracker = TurnDiffTracker::new();
+    let call_id = format!("user_command_{}", Uuid::new_v4());
+    let output = handle_container_exec_with_params(
+        params,
+        &sess,
+        turn_context,
+        &mut turn_diff_tracker,
+        sub_id.clone(),
+        call_id,
+    )
+    .await;
+    let result = match output {
+        ResponseInputItem::FunctionCallOutput { output, .. } => output.content,
+        other => format!("{other:?}"),
+    };
+
+    let command = command_summary(&command);
+    let text = format!(
+        "<user_command>\n<command>{command}</command>\n<result>\n{result}\n</result>\n</user_command>"
+    );
+    // This is a user message, which `record_conversation_items` leaves
+    // alone, so the output is redacted here.
+    let text = sess.redactor.redact(&text).into_owned();
+    sess.record_conversation_items(&[ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText { text }],
+    }])
+    .await;
+
+    sess.remove_task(&sub_id);
     let event = Event {
-        id: sub_id.clone(),
+        id: sub_id,
         msg: EventMsg::TaskComplete(TaskCompleteEvent {
             last_agent_message: None,
+            checkpoint_commit: None,
+            truncated: None,
         }),
     };
     sess.send_event(event).await;
+}
+
+async fn start_background_task(
+    sess: Arc<Session>,
+    turn_context: Arc<TurnContext>,
+    sub_id: String,
+    command: Vec<String>,
+) {
+    let error = |message: String| Event {
+        id: sub_id.clone(),
+        msg: EventMsg::Error(ErrorEvent {
+            message,
+            kind: ErrorKind::Other,
+        }),
+    };
+
+    // Under the container backend every command must run in a container,
+    // which is removed once the command it was created for finishes.
+    if sess.container.is_some() {
+        let message = "background tasks are not supported under the container backend".to_string();
+        sess.send_event(error(message)).await;
+        return;
+    }
 
-    let mut state = sess.state.lock_unchecked();
-    state.history.keep_last_messages(1);
+    let safety = {
+        let state = sess.state.lock_unchecked();
+        assess_command_safety(
+            &command,
+            turn_context.approval_policy,
+            &turn_context.sandbox_policy,
+            &state.approved_commands,
+            &state.exec_policy,
+            false,
+        )
+    };
+    let sandbox_type = match safety {
+        SafetyCheck::AutoApprove { sandbox_type } => sandbox_type,
+        SafetyCheck::AskUser => {
+            let call_id = format!("background_task_{}", Uuid::new_v4());
+            let rx_approve = sess
+                .request_command_approval(
+                    sub_id.clone(),
+                    call_id,
+                    command.clone(),
+                    turn_context.cwd.clone(),
+                    None,
+                )
+                .await;
+            match rx_approve.await.unwrap_or_default().decision {
+                ReviewDecision::Approved => (),
+                ReviewDecision::ApprovedForSession => {
+                    sess.add_approved_command(command.clone());
+                }
+                ReviewDecision::Denied | ReviewDecision::Abort => {
+                    let message = "background task rejected by user".to_string();
+                    sess.send_event(error(message)).await;
+                    return;
+                }
+            }
+            SandboxType::None
+        }
+        SafetyCheck::Reject { reason } => {
+            sess.send_event(error(format!("background task rejected: {reason}")))
+                .await;
+            return;
+        }
+    };
+
+    let request = BackgroundTaskSpawn {
+        command,
+        cwd: turn_context.cwd.clone(),
+        env: create_env(&turn_context.shell_environment_policy),
+        sandbox_type,
+        sandbox_policy: &turn_context.sandbox_policy,
+        seatbelt_extensions: &sess.seatbelt_extensions,
+        codex_linux_sandbox_exe: &sess.codex_linux_sandbox_exe,
+        limits: &sess.exec_limits,
+        audit_log: sess.audit_log.clone(),
+    };
+    if let Err(e) = sess
+        .background_tasks
+        .spawn(request, sub_id.clone(), sess.tx_event.clone())
+        .await
+    {
+        let message = format!("failed to start background task: {e}");
+        sess.send_event(error(message)).await;
+    }
 }
 
 async fn handle_response_item(
@@ -2042,6 +3155,7 @@ async fn handle_response_item(
     Ok(output)
 }
 
+#[instrument(name = "tool_call", skip_all, fields(tool = %name, call_id = %call_id))]
 async fn handle_function_call(
     sess: &Session,
     turn_context: &TurnContext,
@@ -2102,7 +3216,6 @@ async fn handle_function_call(
         }
         "update_plan" => handle_update_plan(sess, arguments, sub_id, call_id).await,
         EXEC_COMMAND_TOOL_NAME => {
-            // TODO(mbolin): Sandbox check.
             let exec_params = match serde_json::from_str::<ExecCommandParams>(&arguments) {
                 Ok(params) => params,
                 Err(e) => {
@@ -2115,15 +3228,7 @@ async fn handle_function_call(
                     };
                 }
             };
-            let result = sess
-                .session_manager
-                .handle_exec_command_request(exec_params)
-                .await;
-            let function_call_output = crate::exec_command::result_into_payload(result);
-            ResponseInputItem::FunctionCallOutput {
-                call_id,
-                output: function_call_output,
-            }
+            handle_exec_command(sess, turn_context, sub_id, call_id, exec_params).await
         }
         WRITE_STDIN_TOOL_NAME => {
             let write_stdin_params = match serde_json::from_str::<WriteStdinParams>(&arguments) {
@@ -2138,10 +3243,15 @@ async fn handle_function_call(
                     };
                 }
             };
-            let result = sess
+            let mut result = sess
                 .session_manager
                 .handle_write_stdin_request(write_stdin_params)
                 .await;
+            if let Ok(output) = result.as_mut()
+                && let Some(transcript) = output.take_transcript()
+            {
+                sess.record_exec_session_transcript(transcript).await;
+            }
             let function_call_output: FunctionCallOutputPayload =
                 crate::exec_command::result_into_payload(result);
             ResponseInputItem::FunctionCallOutput {
@@ -2149,6 +3259,33 @@ async fn handle_function_call(
                 output: function_call_output,
             }
         }
+        CLOSE_SESSION_TOOL_NAME => {
+            let close_params = match serde_json::from_str::<CloseSessionParams>(&arguments) {
+                Ok(params) => params,
+                Err(e) => {
+                    return ResponseInputItem::FunctionCallOutput {
+                        call_id,
+                        output: FunctionCallOutputPayload {
+                            content: format!("failed to parse function arguments: {e}"),
+                            success: Some(false),
+                        },
+                    };
+                }
+            };
+            let mut result = sess
+                .session_manager
+                .handle_close_session_request(close_params)
+                .await;
+            if let Ok(output) = result.as_mut()
+                && let Some(transcript) = output.take_transcript()
+            {
+                sess.record_exec_session_transcript(transcript).await;
+            }
+            ResponseInputItem::FunctionCallOutput {
+                call_id,
+                output: crate::exec_command::result_into_payload(result),
+            }
+        }
         _ => {
             match sess.mcp_connection_manager.parse_tool_name(&name) {
                 Some((server, tool_name)) => {
@@ -2285,6 +3422,97 @@ fn maybe_translate_shell_command(
     params
 }
 
+/// Starts an interactive exec session after the same approval and sandbox
+/// checks as the shell tool. The PTY child runs under the sandbox the check
+/// selects, or unsandboxed once the user has approved it.
+async fn handle_exec_command(
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: String,
+    call_id: String,
+    params: ExecCommandParams,
+) -> ResponseInputItem {
+    let rejected = |content: String| ResponseInputItem::FunctionCallOutput {
+        call_id: call_id.clone(),
+        output: FunctionCallOutputPayload {
+            content,
+            success: Some(false),
+        },
+    };
+
+    // Under the container backend every command must run in the container,
+    // which has no way to host a PTY session.
+    if sess.container.is_some() {
+        return rejected("exec sessions are not supported under the container backend".to_string());
+    }
+
+    let command = params.argv();
+    let safety = {
+        let state = sess.state.lock_unchecked();
+        assess_command_safety(
+            &command,
+            turn_context.approval_policy,
+            &turn_context.sandbox_policy,
+            &state.approved_commands,
+            &state.exec_policy,
+            false,
+        )
+    };
+    let sandbox_type = match safety {
+        SafetyCheck::AutoApprove { sandbox_type } => sandbox_type,
+        SafetyCheck::AskUser => {
+            let rx_approve = sess
+                .request_command_approval(
+                    sub_id,
+                    call_id.clone(),
+                    command.clone(),
+                    turn_context.cwd.clone(),
+                    None,
+                )
+                .await;
+            match rx_approve.await.unwrap_or_default().decision {
+                ReviewDecision::Approved => (),
+                ReviewDecision::ApprovedForSession => {
+                    sess.add_approved_command(command.clone());
+                }
+                ReviewDecision::Denied | ReviewDecision::Abort => {
+                    return rejected("exec command rejected by user".to_string());
+                }
+            }
+            SandboxType::None
+        }
+        SafetyCheck::Reject { reason } => {
+            return rejected(format!("exec command rejected: {reason}"));
+        }
+    };
+
+    let argv = match sandboxed_argv(
+        command,
+        sandbox_type,
+        &turn_context.sandbox_policy,
+        &sess.seatbelt_extensions,
+        &turn_context.cwd,
+        &sess.codex_linux_sandbox_exe,
+    ) {
+        Ok(argv) => argv,
+        Err(e) => return rejected(e),
+    };
+
+    let mut result = sess
+        .session_manager
+        .handle_exec_command_request(params, argv, turn_context.cwd.clone())
+        .await;
+    if let Ok(output) = result.as_mut()
+        && let Some(transcript) = output.take_transcript()
+    {
+        sess.record_exec_session_transcript(transcript).await;
+    }
+    ResponseInputItem::FunctionCallOutput {
+        call_id,
+        output: crate::exec_command::result_into_payload(result),
+    }
+}
+
 async fn handle_container_exec_with_params(
     params: ExecParams,
     sess: &Session,
@@ -2315,6 +3543,18 @@ async fn handle_container_exec_with_params(
                 },
             };
         }
+        MaybeApplyPatchVerified::ShellParseError(error) if error.is_rejected_invocation() => {
+            // The script clearly invokes `apply_patch`, but in a way we
+            // refuse to guess the effect of. Tell the model how to fix it
+            // rather than running the script unchecked.
+            return ResponseInputItem::FunctionCallOutput {
+                call_id,
+                output: FunctionCallOutputPayload {
+                    content: format!("error: {error}"),
+                    success: Some(false),
+                },
+            };
+        }
         MaybeApplyPatchVerified::ShellParseError(error) => {
             trace!("Failed to parse shell command, {error:?}");
             None
@@ -2326,6 +3566,7 @@ async fn handle_container_exec_with_params(
         Some(ApplyPatchExec {
             action: ApplyPatchAction { patch, cwd, .. },
             user_explicitly_approved_this_action,
+            ..
         }) => {
             let path_to_codex = std::env::current_exe()
                 .ok()
@@ -2377,6 +3618,7 @@ async fn handle_container_exec_with_params(
                     turn_context.approval_policy,
                     &turn_context.sandbox_policy,
                     &state.approved_commands,
+                    &state.exec_policy,
                     params.with_escalated_permissions.unwrap_or(false),
                 )
             };
@@ -2397,7 +3639,7 @@ async fn handle_container_exec_with_params(
                     params.justification.clone(),
                 )
                 .await;
-            match rx_approve.await.unwrap_or_default() {
+            match rx_approve.await.unwrap_or_default().decision {
                 ReviewDecision::Approved => (),
                 ReviewDecision::ApprovedForSession => {
                     sess.add_approved_command(params.command.clone());
@@ -2429,6 +3671,9 @@ async fn handle_container_exec_with_params(
         }
     };
 
+    let rejected_files_note = apply_patch_exec
+        .as_ref()
+        .and_then(|exec| apply_patch::rejected_files_note(&exec.rejected_files));
     let exec_command_context = ExecCommandContext {
         sub_id: sub_id.clone(),
         call_id: call_id.clone(),
@@ -2438,11 +3683,13 @@ async fn handle_container_exec_with_params(
             |ApplyPatchExec {
                  action,
                  user_explicitly_approved_this_action,
+                 ..
              }| ApplyPatchCommandContext {
                 user_explicitly_approved_this_action,
                 changes: convert_apply_patch_to_protocol(&action),
             },
         ),
+        tokenizer: turn_context.client.get_model_family().tokenizer.tokenizer(),
     };
 
     let params = maybe_translate_shell_command(params, sess, turn_context);
@@ -2465,11 +3712,18 @@ async fn handle_container_exec_with_params(
         .await;
 
     match output_result {
-        Ok(output) => {
+        Ok(mut output) => {
+            if let Some(note) = rejected_files_note {
+                output.aggregated_output.text.push_str(&format!("\n{note}"));
+            }
             let ExecToolCallOutput { exit_code, .. } = &output;
 
             let is_success = *exit_code == 0;
-            let content = format_exec_output(&output);
+            let content = format_exec_output(
+                &output,
+                exec_command_context.tokenizer,
+                sess.tool_output_truncation,
+            );
             ResponseInputItem::FunctionCallOutput {
                 call_id: call_id.clone(),
                 output: FunctionCallOutputPayload {
@@ -2500,6 +3754,54 @@ async fn handle_container_exec_with_params(
     }
 }
 
+/// Degraded modes that are known when the session starts.
+fn startup_degraded_modes(
+    config: &Config,
+    auth_manager: &AuthManager,
+    sandbox_policy: &SandboxPolicy,
+) -> Vec<DegradedModeEvent> {
+    let mut events = Vec::new();
+
+    let wants_sandbox = config.execution_backend == ExecutionBackend::Local
+        && !matches!(sandbox_policy, SandboxPolicy::DangerFullAccess { .. });
+    let has_sandbox = match crate::safety::get_platform_sandbox() {
+        Some(SandboxType::LinuxSeccomp) => config.codex_linux_sandbox_exe.is_some(),
+        Some(_) => true,
+        None => false,
+    };
+    if wants_sandbox && !has_sandbox {
+        events.push(DegradedModeEvent {
+            kind: DegradedModeKind::SandboxUnavailable,
+            message: "no sandbox is available on this platform, so commands run unsandboxed once approved".to_string(),
+        });
+    }
+
+    let expires_at = auth_manager
+        .auth()
+        .filter(|auth| auth.mode == AuthMode::ChatGPT)
+        .and_then(|auth| auth.access_token_expires_at());
+    if let Some(expires_at) = expires_at {
+        let remaining = expires_at - chrono::Utc::now();
+        if remaining <= chrono::Duration::zero() {
+            events.push(DegradedModeEvent {
+                kind: DegradedModeKind::AuthExpiring,
+                message: "your ChatGPT sign-in has expired; run `codex login` to sign in again"
+                    .to_string(),
+            });
+        } else if remaining < chrono::Duration::hours(24) {
+            events.push(DegradedModeEvent {
+                kind: DegradedModeKind::AuthExpiring,
+                message: format!(
+                    "your ChatGPT sign-in expires in {}h; run `codex login` to renew it",
+                    remaining.num_hours().max(1)
+                ),
+            });
+        }
+    }
+
+    events
+}
+
 async fn handle_sandbox_error(
     turn_diff_tracker: &mut TurnDiffTracker,
     params: ExecParams,
@@ -2513,16 +3815,31 @@ async fn handle_sandbox_error(
     let sub_id = exec_command_context.sub_id.clone();
     let cwd = exec_command_context.cwd.clone();
 
+    if error.is_sandbox_unavailable() {
+        sess.notify_degraded_mode(
+            &sub_id,
+            DegradedModeKind::SandboxUnavailable,
+            format!("the {sandbox_type:?} sandbox could not be set up: {error}"),
+        )
+        .await;
+    }
+
+    let blocked = error.blocked_summary();
+
     // Early out if either the user never wants to be asked for approval, or
     // we're letting the model manage escalation requests. Otherwise, continue
     match turn_context.approval_policy {
         AskForApproval::Never | AskForApproval::OnRequest => {
+            let mut content =
+                format!("failed in sandbox {sandbox_type:?} with execution error: {error}");
+            if let Some(blocked) = &blocked {
+                content.push('\n');
+                content.push_str(blocked);
+            }
             return ResponseInputItem::FunctionCallOutput {
                 call_id,
                 output: FunctionCallOutputPayload {
-                    content: format!(
-                        "failed in sandbox {sandbox_type:?} with execution error: {error}"
-                    ),
+                    content,
                     success: Some(false),
                 },
             };
@@ -2537,7 +3854,9 @@ async fn handle_sandbox_error(
             output: FunctionCallOutputPayload {
                 content: format!(
                     "command timed out after {} milliseconds",
-                    params.timeout_duration().as_millis()
+                    params
+                        .timeout_duration_with_limits(&sess.exec_limits)
+                        .as_millis()
                 ),
                 success: Some(false),
             },
@@ -2562,11 +3881,14 @@ async fn handle_sandbox_error(
             call_id.clone(),
             params.command.clone(),
             cwd.clone(),
-            Some("command failed; retry without sandbox?".to_string()),
+            Some(match blocked {
+                Some(blocked) => format!("{blocked}; retry without sandbox?"),
+                None => "command failed; retry without sandbox?".to_string(),
+            }),
         )
         .await;
 
-    match rx_approve.await.unwrap_or_default() {
+    match rx_approve.await.unwrap_or_default().decision {
         ReviewDecision::Approved | ReviewDecision::ApprovedForSession => {
             // Persist this command as pre‑approved for the
             // remainder of the session so future
@@ -2602,7 +3924,11 @@ async fn handle_sandbox_error(
                     let ExecToolCallOutput { exit_code, .. } = &retry_output;
 
                     let is_success = *exit_code == 0;
-                    let content = format_exec_output(&retry_output);
+                    let content = format_exec_output(
+                        &retry_output,
+                        exec_command_context.tokenizer,
+                        sess.tool_output_truncation,
+                    );
 
                     ResponseInputItem::FunctionCallOutput {
                         call_id: call_id.clone(),
@@ -2634,19 +3960,37 @@ async fn handle_sandbox_error(
     }
 }
 
-fn format_exec_output_str(exec_output: &ExecToolCallOutput) -> String {
+fn format_exec_output_str(
+    exec_output: &ExecToolCallOutput,
+    tokenizer: &dyn Tokenizer,
+    truncation: ToolOutputTruncation,
+) -> String {
     let ExecToolCallOutput {
         aggregated_output, ..
     } = exec_output;
 
-    // Head+tail truncation for the model: show the beginning and end with an elision.
-    // Clients still receive full streams; only this formatted summary is capped.
+    // Truncation for the model: by default, show the beginning and end with an
+    // elision. Clients still receive full streams; only this formatted summary
+    // is capped.
 
     let s = aggregated_output.text.as_str();
     let total_lines = s.lines().count();
-    if s.len() <= MODEL_FORMAT_MAX_BYTES && total_lines <= MODEL_FORMAT_MAX_LINES {
+    // Output that is dense in tokens (numbers, symbols, non-English text, or
+    // anything under a SentencePiece vocabulary) gets a smaller byte budget
+    // so it also stays within the token budget.
+    let tokens = tokenizer.count_tokens(s);
+    let max_bytes = if tokens > MODEL_FORMAT_MAX_TOKENS {
+        let scaled = s.len() as u128 * u128::from(MODEL_FORMAT_MAX_TOKENS) / u128::from(tokens);
+        (scaled as usize).min(MODEL_FORMAT_MAX_BYTES)
+    } else {
+        MODEL_FORMAT_MAX_BYTES
+    };
+    if s.len() <= max_bytes && total_lines <= MODEL_FORMAT_MAX_LINES {
         return s.to_string();
     }
+    if truncation == ToolOutputTruncation::Salient {
+        return select_salient_lines(s, max_bytes, MODEL_FORMAT_MAX_LINES);
+    }
 
     let lines: Vec<&str> = s.lines().collect();
     let head_take = MODEL_FORMAT_HEAD_LINES.min(lines.len());
@@ -2668,15 +4012,15 @@ fn format_exec_output_str(exec_output: &ExecToolCallOutput) -> String {
     let marker = format!("\n[... omitted {omitted} of {total_lines} lines ...]\n\n");
 
     // Byte budgets for head/tail around the marker
-    let mut head_budget = MODEL_FORMAT_HEAD_BYTES.min(MODEL_FORMAT_MAX_BYTES);
-    let tail_budget = MODEL_FORMAT_MAX_BYTES.saturating_sub(head_budget + marker.len());
-    if tail_budget == 0 && marker.len() >= MODEL_FORMAT_MAX_BYTES {
+    let mut head_budget = max_bytes / 2;
+    let tail_budget = max_bytes.saturating_sub(head_budget + marker.len());
+    if tail_budget == 0 && marker.len() >= max_bytes {
         // Degenerate case: marker alone exceeds budget; return a clipped marker
-        return take_bytes_at_char_boundary(&marker, MODEL_FORMAT_MAX_BYTES).to_string();
+        return take_bytes_at_char_boundary(&marker, max_bytes).to_string();
     }
     if tail_budget == 0 {
         // Make room for the marker by shrinking head
-        head_budget = MODEL_FORMAT_MAX_BYTES.saturating_sub(marker.len());
+        head_budget = max_bytes.saturating_sub(marker.len());
     }
 
     // Enforce line-count cap by trimming head/tail lines
@@ -2684,11 +4028,11 @@ fn format_exec_output_str(exec_output: &ExecToolCallOutput) -> String {
     let tail_lines_text = tail_block;
     // Build final string respecting byte budgets
     let head_part = take_bytes_at_char_boundary(&head_lines_text, head_budget);
-    let mut result = String::with_capacity(MODEL_FORMAT_MAX_BYTES.min(s.len()));
+    let mut result = String::with_capacity(max_bytes.min(s.len()));
     result.push_str(head_part);
     result.push_str(&marker);
 
-    let remaining = MODEL_FORMAT_MAX_BYTES.saturating_sub(result.len());
+    let remaining = max_bytes.saturating_sub(result.len());
     let tail_budget_final = remaining;
     let tail_part = take_last_bytes_at_char_boundary(&tail_lines_text, tail_budget_final);
     result.push_str(tail_part);
@@ -2698,7 +4042,7 @@ fn format_exec_output_str(exec_output: &ExecToolCallOutput) -> String {
 
 // Truncate a &str to a byte budget at a char boundary (prefix)
 #[inline]
-fn take_bytes_at_char_boundary(s: &str, maxb: usize) -> &str {
+pub(crate) fn take_bytes_at_char_boundary(s: &str, maxb: usize) -> &str {
     if s.len() <= maxb {
         return s;
     }
@@ -2736,10 +4080,15 @@ fn take_last_bytes_at_char_boundary(s: &str, maxb: usize) -> &str {
 }
 
 /// Exec output is a pre-serialized JSON payload
-fn format_exec_output(exec_output: &ExecToolCallOutput) -> String {
+fn format_exec_output(
+    exec_output: &ExecToolCallOutput,
+    tokenizer: &dyn Tokenizer,
+    truncation: ToolOutputTruncation,
+) -> String {
     let ExecToolCallOutput {
         exit_code,
         duration,
+        killed_reason,
         ..
     } = exec_output;
 
@@ -2747,6 +4096,8 @@ fn format_exec_output(exec_output: &ExecToolCallOutput) -> String {
     struct ExecMetadata {
         exit_code: i32,
         duration_seconds: f32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        killed_reason: Option<ExecKilledReason>,
     }
 
     #[derive(Serialize)]
@@ -2758,13 +4109,14 @@ fn format_exec_output(exec_output: &ExecToolCallOutput) -> String {
     // round to 1 decimal place
     let duration_seconds = ((duration.as_secs_f32()) * 10.0).round() / 10.0;
 
-    let formatted_output = format_exec_output_str(exec_output);
+    let formatted_output = format_exec_output_str(exec_output, tokenizer, truncation);
 
     let payload = ExecOutput {
         output: &formatted_output,
         metadata: ExecMetadata {
             exit_code: *exit_code,
             duration_seconds,
+            killed_reason: *killed_reason,
         },
     };
 
@@ -2792,50 +4144,6 @@ fn get_last_assistant_message_from_turn(responses: &[ResponseItem]) -> Option<St
     })
 }
 
-async fn drain_to_completed(
-    sess: &Session,
-    turn_context: &TurnContext,
-    sub_id: &str,
-    prompt: &Prompt,
-) -> CodexResult<()> {
-    let mut stream = turn_context.client.clone().stream(prompt).await?;
-    loop {
-        let maybe_event = stream.next().await;
-        let Some(event) = maybe_event else {
-            return Err(CodexErr::Stream(
-                "stream closed before response.completed".into(),
-                None,
-            ));
-        };
-        match event {
-            Ok(ResponseEvent::OutputItemDone(item)) => {
-                // Record only to in-memory conversation history; avoid state snapshot.
-                let mut state = sess.state.lock_unchecked();
-                state.history.record_items(std::slice::from_ref(&item));
-            }
-            Ok(ResponseEvent::Completed {
-                response_id: _,
-                token_usage,
-            }) => {
-                // some providers don't return token usage, so we default
-                // TODO: consider approximate token usage
-                let token_usage = token_usage.unwrap_or_default();
-                sess.tx_event
-                    .send(Event {
-                        id: sub_id.to_string(),
-                        msg: EventMsg::TokenCount(token_usage),
-                    })
-                    .await
-                    .ok();
-
-                return Ok(());
-            }
-            Ok(_) => continue,
-            Err(e) => return Err(e),
-        }
-    }
-}
-
 fn convert_call_tool_result_to_function_call_output_payload(
     call_tool_result: &CallToolResult,
 ) -> FunctionCallOutputPayload {
@@ -2874,6 +4182,7 @@ fn convert_call_tool_result_to_function_call_output_payload(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::tokenizer::TokenizerKind;
     use mcp_types::ContentBlock;
     use mcp_types::TextContent;
     use pretty_assertions::assert_eq;
@@ -2925,9 +4234,14 @@ mod tests {
             stderr: StreamOutput::new(String::new()),
             aggregated_output: StreamOutput::new(full.clone()),
             duration: StdDuration::from_secs(1),
+            killed_reason: None,
         };
 
-        let out = format_exec_output_str(&exec);
+        let out = format_exec_output_str(
+            &exec,
+            TokenizerKind::O200k.tokenizer(),
+            ToolOutputTruncation::HeadTail,
+        );
 
         // Expect elision marker with correct counts
         let omitted = 400 - MODEL_FORMAT_MAX_LINES; // 144
@@ -2953,6 +4267,39 @@ mod tests {
         assert!(tail.ends_with(&expected_tail), "tail mismatch");
     }
 
+    #[test]
+    fn salient_truncation_keeps_error_from_the_middle() {
+        let mut lines: Vec<String> = (1..=2000).map(|i| format!("line{i}")).collect();
+        lines[999] = "error: linking with `cc` failed: exit status: 1".to_string();
+        let full = lines.join("\n");
+
+        let exec = ExecToolCallOutput {
+            exit_code: 1,
+            stdout: StreamOutput::new(String::new()),
+            stderr: StreamOutput::new(String::new()),
+            aggregated_output: StreamOutput::new(full.clone()),
+            duration: StdDuration::from_secs(1),
+            killed_reason: None,
+        };
+
+        let head_tail = format_exec_output_str(
+            &exec,
+            TokenizerKind::O200k.tokenizer(),
+            ToolOutputTruncation::HeadTail,
+        );
+        let salient = format_exec_output_str(
+            &exec,
+            TokenizerKind::O200k.tokenizer(),
+            ToolOutputTruncation::Salient,
+        );
+
+        assert!(!head_tail.contains("linking with `cc` failed"));
+        assert!(salient.contains("line998\nline999\nerror: linking with `cc` failed"));
+        assert!(salient.starts_with("line1\n"));
+        assert!(salient.ends_with("line2000"));
+        assert!(salient.len() <= MODEL_FORMAT_MAX_BYTES);
+    }
+
     #[test]
     fn model_truncation_respects_byte_budget() {
         // Construct a large output (about 100kB) so byte budget dominates
@@ -2967,9 +4314,14 @@ mod tests {
             stderr: StreamOutput::new(String::new()),
             aggregated_output: StreamOutput::new(full.clone()),
             duration: StdDuration::from_secs(1),
+            killed_reason: None,
         };
 
-        let out = format_exec_output_str(&exec);
+        let out = format_exec_output_str(
+            &exec,
+            TokenizerKind::O200k.tokenizer(),
+            ToolOutputTruncation::HeadTail,
+        );
         assert!(out.len() <= MODEL_FORMAT_MAX_BYTES, "exceeds byte budget");
         assert!(out.contains("omitted"), "should contain elision marker");
 
@@ -2989,6 +4341,39 @@ mod tests {
         );
     }
 
+    #[test]
+    fn model_truncation_respects_token_budget() {
+        // About 4 KiB of numbers: within the byte budget, but SentencePiece
+        // vocabularies spend a token on every digit.
+        let full = std::iter::repeat_n("1234567890 1234567890", 200)
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let exec = ExecToolCallOutput {
+            exit_code: 0,
+            stdout: StreamOutput::new(String::new()),
+            stderr: StreamOutput::new(String::new()),
+            aggregated_output: StreamOutput::new(full.clone()),
+            duration: StdDuration::from_secs(1),
+            killed_reason: None,
+        };
+
+        let out = format_exec_output_str(
+            &exec,
+            TokenizerKind::O200k.tokenizer(),
+            ToolOutputTruncation::HeadTail,
+        );
+        assert_eq!(out, full);
+
+        let tokenizer = TokenizerKind::SentencePiece.tokenizer();
+        let out = format_exec_output_str(&exec, tokenizer, ToolOutputTruncation::HeadTail);
+        assert!(out.contains("omitted"), "should contain elision marker");
+        assert!(
+            tokenizer.count_tokens(&out) <= MODEL_FORMAT_MAX_TOKENS,
+            "exceeds token budget"
+        );
+    }
+
     #[test]
     fn falls_back_to_content_when_structured_is_null() {
         let ctr = CallToolResult {
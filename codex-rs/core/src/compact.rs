@@ -0,0 +1,343 @@
+//! Compaction of the conversation history: older turns are summarized into a
+//! running synopsis that replaces them, while the session context
+//! (instructions, environment, repo map) and the most recent turns are kept
+//! verbatim.
+//!
+//! Core compacts automatically when a turn nears the model's context window,
+//! keeping the last [`AUTO_COMPACT_KEEP_TURNS`] turns, and `Op::Compact`
+//! compacts every turn but the one in progress. Both use the model set by
+//! `summary_model`, or the session's model when it is unset.
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::LocalShellAction;
+use codex_protocol::models::ResponseItem;
+use futures::prelude::*;
+
+use crate::client::ModelClient;
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::client_common::USER_INSTRUCTIONS_START;
+use crate::environment_context::ENVIRONMENT_CONTEXT_START;
+use crate::error::CodexErr;
+use crate::error::Result as CodexResult;
+use crate::repo_map::REPO_MAP_START;
+
+const SUMMARIZATION_PROMPT: &str = include_str!("prompt_for_compact_command.md");
+
+pub(crate) const SUMMARY_START: &str = "<conversation_summary>";
+const SUMMARY_END: &str = "</conversation_summary>";
+
+/// Share of the context window in use, in percent, at which the history is
+/// compacted before the next request.
+pub(crate) const AUTO_COMPACT_PERCENT: u64 = 90;
+/// User turns kept verbatim by automatic compaction, including the turn in
+/// progress.
+pub(crate) const AUTO_COMPACT_KEEP_TURNS: usize = 2;
+/// Longest text kept from a single item of the transcript given to the
+/// summarizer; tool output beyond this is rarely worth summarizing.
+const MAX_TRANSCRIPT_ENTRY_BYTES: usize = 4 * 1024;
+
+/// The history split around the turns to summarize.
+#[derive(Debug, PartialEq)]
+pub(crate) struct Split {
+    /// Session context, kept as is.
+    pub(crate) context: Vec<ResponseItem>,
+    /// Older turns, including any previous summary, to be summarized.
+    pub(crate) older: Vec<ResponseItem>,
+    /// The last turns, kept as is.
+    pub(crate) recent: Vec<ResponseItem>,
+}
+
+/// Split `items` so that the last `keep_turns` user turns are kept. Returns
+/// `None` when there is nothing to summarize.
+pub(crate) fn split_history(items: &[ResponseItem], keep_turns: usize) -> Option<Split> {
+    let turn_starts: Vec<usize> = items
+        .iter()
+        .enumerate()
+        .filter(|(_, item)| is_turn_start(item))
+        .map(|(index, _)| index)
+        .collect();
+    let recent_start = if keep_turns == 0 {
+        items.len()
+    } else if keep_turns < turn_starts.len() {
+        // Turns start with a user message, so no tool call is separated from
+        // its output.
+        turn_starts[turn_starts.len() - keep_turns]
+    } else {
+        return None;
+    };
+
+    let (context, older): (Vec<ResponseItem>, Vec<ResponseItem>) = items[..recent_start]
+        .iter()
+        .cloned()
+        .partition(is_session_context);
+    if older.iter().all(is_summary) {
+        return None;
+    }
+    Some(Split {
+        context,
+        older,
+        recent: items[recent_start..].to_vec(),
+    })
+}
+
+/// Render `items` as plain text for the summarizer. Reasoning is left out
+/// and long entries are truncated.
+pub(crate) fn render_transcript(items: &[ResponseItem]) -> String {
+    let entries: Vec<String> = items
+        .iter()
+        .filter_map(|item| {
+            let entry = match item {
+                ResponseItem::Message { role, content, .. } => {
+                    let text = message_text(content);
+                    match text.strip_prefix(SUMMARY_START) {
+                        Some(summary) => format!(
+                            "Summary of the earlier conversation:\n{}",
+                            summary.trim_end_matches(SUMMARY_END).trim()
+                        ),
+                        None => format!("{role}: {text}"),
+                    }
+                }
+                ResponseItem::FunctionCall {
+                    name, arguments, ..
+                } => format!("tool call {name}: {arguments}"),
+                ResponseItem::CustomToolCall { name, input, .. } => {
+                    format!("tool call {name}: {input}")
+                }
+                ResponseItem::LocalShellCall {
+                    action: LocalShellAction::Exec(exec),
+                    ..
+                } => format!("tool call shell: {}", exec.command.join(" ")),
+                ResponseItem::FunctionCallOutput { output, .. } => {
+                    format!("tool output: {}", output.content)
+                }
+                ResponseItem::CustomToolCallOutput { output, .. } => {
+                    format!("tool output: {output}")
+                }
+                ResponseItem::Reasoning { .. } | ResponseItem::Other => return None,
+            };
+            Some(truncate_entry(entry))
+        })
+        .collect();
+    entries.join("\n\n")
+}
+
+/// The message that replaces the summarized turns in the history.
+pub(crate) fn summary_message(summary: &str) -> ResponseItem {
+    ResponseItem::Message {
+        id: None,
+        role: "user".to_string(),
+        content: vec![ContentItem::InputText {
+            text: format!("{SUMMARY_START}\n{summary}\n{SUMMARY_END}"),
+        }],
+    }
+}
+
+/// Ask `client` to summarize `transcript` and return the summary.
+pub(crate) async fn summarize(
+    client: &ModelClient,
+    transcript: &str,
+    store: bool,
+) -> CodexResult<String> {
+    let prompt = Prompt {
+        input: vec![ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: transcript.to_string(),
+            }],
+        }],
+        store,
+        tools: Vec::new(),
+        base_instructions_override: Some(SUMMARIZATION_PROMPT.to_string()),
+        max_output_tokens: None,
+    };
+
+    let mut stream = client.clone().stream(&prompt).await?;
+    let mut summary = String::new();
+    loop {
+        let Some(event) = stream.next().await else {
+            return Err(CodexErr::Stream(
+                "stream closed before response.completed".into(),
+                None,
+            ));
+        };
+        match event? {
+            ResponseEvent::OutputItemDone(ResponseItem::Message { role, content, .. })
+                if role == "assistant" =>
+            {
+                summary = message_text(&content);
+            }
+            ResponseEvent::Completed { .. } => break,
+            _ => {}
+        }
+    }
+    let summary = summary.trim();
+    if summary.is_empty() {
+        return Err(CodexErr::Stream(
+            "summary model returned no text".to_string(),
+            None,
+        ));
+    }
+    Ok(summary.to_string())
+}
+
+fn message_text(content: &[ContentItem]) -> String {
+    content
+        .iter()
+        .map(|c| match c {
+            ContentItem::InputText { text } | ContentItem::OutputText { text } => text.as_str(),
+            ContentItem::InputImage { .. } => "[image]",
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn starts_with_any(item: &ResponseItem, prefixes: &[&str]) -> bool {
+    match item {
+        ResponseItem::Message { role, content, .. } if role == "user" => content.iter().any(|c| {
+            matches!(c, ContentItem::InputText { text }
+                    if prefixes.iter().any(|prefix| text.starts_with(prefix)))
+        }),
+        _ => false,
+    }
+}
+
+fn is_session_context(item: &ResponseItem) -> bool {
+    starts_with_any(
+        item,
+        &[
+            USER_INSTRUCTIONS_START,
+            ENVIRONMENT_CONTEXT_START,
+            REPO_MAP_START,
+        ],
+    )
+}
+
+fn is_summary(item: &ResponseItem) -> bool {
+    starts_with_any(item, &[SUMMARY_START])
+}
+
+fn is_turn_start(item: &ResponseItem) -> bool {
+    matches!(item, ResponseItem::Message { role, .. } if role == "user")
+        && !is_session_context(item)
+        && !is_summary(item)
+}
+
+fn truncate_entry(mut entry: String) -> String {
+    if entry.len() <= MAX_TRANSCRIPT_ENTRY_BYTES {
+        return entry;
+    }
+    let mut end = MAX_TRANSCRIPT_ENTRY_BYTES;
+    while !entry.is_char_boundary(end) {
+        end -= 1;
+    }
+    entry.truncate(end);
+    entry.push_str("\n[... truncated]");
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    fn message(role: &str, text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![if role == "user" {
+                ContentItem::InputText {
+                    text: text.to_string(),
+                }
+            } else {
+                ContentItem::OutputText {
+                    text: text.to_string(),
+                }
+            }],
+        }
+    }
+
+    fn history() -> Vec<ResponseItem> {
+        vec![
+            message("user", "<environment_context>\n</environment_context>"),
+            message("user", "fix the build"),
+            ResponseItem::FunctionCall {
+                id: None,
+                name: "shell".to_string(),
+                arguments: r#"{"command":["cargo","build"]}"#.to_string(),
+                call_id: "call-1".to_string(),
+            },
+            ResponseItem::FunctionCallOutput {
+                call_id: "call-1".to_string(),
+                output: FunctionCallOutputPayload {
+                    content: "error: missing semicolon".to_string(),
+                    success: Some(false),
+                },
+            },
+            message("assistant", "Fixed the missing semicolon."),
+            message("user", "now run the tests"),
+            message("assistant", "All tests pass."),
+        ]
+    }
+
+    #[test]
+    fn keeps_context_and_recent_turns() {
+        let items = history();
+
+        let split = split_history(&items, 1).unwrap();
+
+        assert_eq!(split.context, items[..1].to_vec());
+        assert_eq!(split.older, items[1..5].to_vec());
+        assert_eq!(split.recent, items[5..].to_vec());
+    }
+
+    #[test]
+    fn nothing_to_summarize_without_older_turns() {
+        let items = history();
+        assert_eq!(split_history(&items, 2), None);
+
+        // A previous summary alone is not summarized again.
+        let items = vec![items[0].clone(), summary_message("the build was fixed")];
+        assert_eq!(split_history(&items, 0), None);
+    }
+
+    #[test]
+    fn folds_previous_summary_into_the_transcript() {
+        let items = vec![
+            summary_message("the build was fixed"),
+            message("user", "now run the tests"),
+            message("assistant", "All tests pass."),
+        ];
+
+        let split = split_history(&items, 0).unwrap();
+
+        assert_eq!(split.context, Vec::new());
+        assert_eq!(split.recent, Vec::new());
+        assert_eq!(
+            render_transcript(&split.older),
+            "Summary of the earlier conversation:\nthe build was fixed\n\n\
+             user: now run the tests\n\n\
+             assistant: All tests pass."
+        );
+    }
+
+    #[test]
+    fn renders_tool_calls_and_truncates_long_output() {
+        let mut items = history()[1..4].to_vec();
+        if let ResponseItem::FunctionCallOutput { output, .. } = &mut items[2] {
+            output.content = "x".repeat(10_000);
+        }
+
+        let transcript = render_transcript(&items);
+
+        assert!(transcript.starts_with(
+            "user: fix the build\n\n\
+             tool call shell: {\"command\":[\"cargo\",\"build\"]}\n\n\
+             tool output: xxx"
+        ));
+        assert!(transcript.ends_with("\n[... truncated]"));
+        assert!(transcript.len() < 5 * 1024);
+    }
+}
@@ -1,32 +1,66 @@
+use crate::client_common::BASE_INSTRUCTIONS;
 use crate::config_profile::ConfigProfile;
+use crate::config_types::Audit;
+use crate::config_types::ContainerConfig;
+use crate::config_types::Encryption;
+use crate::config_types::ExecLimits;
+use crate::config_types::ExecPolicyToml;
+use crate::config_types::ExecutionBackend;
+use crate::config_types::FileSearch;
 use crate::config_types::History;
+use crate::config_types::LocalProvider;
 use crate::config_types::McpServerConfig;
+use crate::config_types::McpServerSettings;
+use crate::config_types::Notifications;
+use crate::config_types::PromptCache;
+use crate::config_types::RedactionToml;
+use crate::config_types::SandboxSeatbeltToml;
 use crate::config_types::SandboxWorkspaceWrite;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::ShellEnvironmentPolicyToml;
+use crate::config_types::Telemetry;
+use crate::config_types::ToolOutputTruncation;
 use crate::config_types::Tui;
 use crate::config_types::UriBasedFileOpener;
 use crate::config_types::Verbosity;
+use crate::exec_policy::ExecPolicy;
 use crate::git_info::resolve_root_git_project_for_trust;
 use crate::model_family::ModelFamily;
+use crate::model_family::default_model_family;
 use crate::model_family::find_family_for_model;
+use crate::model_provider_info::BUILT_IN_OSS_MODEL_PROVIDER_ID;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::built_in_model_providers;
+use crate::model_provider_info::create_oss_provider_with_base_url;
 use crate::openai_model_info::get_model_info;
+use crate::prompt_template;
 use crate::protocol::AskForApproval;
+use crate::protocol::ProtocolHandshake;
 use crate::protocol::SandboxPolicy;
+use crate::redaction::Redactor;
+use crate::seatbelt::SeatbeltExtensions;
 use codex_login::AuthMode;
 use codex_protocol::config_types::ReasoningEffort;
 use codex_protocol::config_types::ReasoningSummary;
 use codex_protocol::config_types::SandboxMode;
 use dirs::home_dir;
+use ignore::gitignore::Gitignore;
+use ignore::gitignore::GitignoreBuilder;
 use serde::Deserialize;
+use serde::Serialize;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
 use tempfile::NamedTempFile;
 use toml::Value as TomlValue;
 use toml_edit::DocumentMut;
+use toml_edit::TableLike;
+
+pub use crate::config_validation::ConfigIssue;
+pub use crate::config_validation::IssueSeverity;
+pub use crate::config_validation::validate_config;
 
 const OPENAI_DEFAULT_MODEL: &str = "gpt-5";
 
@@ -35,7 +69,12 @@ const OPENAI_DEFAULT_MODEL: &str = "gpt-5";
 /// the context window.
 pub(crate) const PROJECT_DOC_MAX_BYTES: usize = 32 * 1024; // 32 KiB
 
-const CONFIG_TOML_FILE: &str = "config.toml";
+pub(crate) const CONFIG_TOML_FILE: &str = "config.toml";
+
+/// Directory, checked into a repository, with project config and ignore
+/// rules layered over those in `CODEX_HOME`.
+const PROJECT_CONFIG_DIR: &str = ".codex";
+const PROJECT_IGNORE_FILE: &str = "ignore";
 
 const DEFAULT_RESPONSES_ORIGINATOR_HEADER: &str = "codex_cli_rs";
 
@@ -59,6 +98,9 @@ pub struct Config {
     /// Info needed to make an API request to the model.
     pub model_provider: ModelProviderInfo,
 
+    /// Name of the profile from `profiles` that was applied, if any.
+    pub active_profile: Option<String>,
+
     /// Approval policy for executing commands.
     pub approval_policy: AskForApproval,
 
@@ -66,6 +108,61 @@ pub struct Config {
 
     pub shell_environment_policy: ShellEnvironmentPolicy,
 
+    /// Where shell tool commands are executed.
+    pub execution_backend: ExecutionBackend,
+
+    /// Container settings used when `execution_backend` is `container`.
+    pub container: ContainerConfig,
+
+    /// Resource limits applied to each shell tool command.
+    pub exec_limits: ExecLimits,
+
+    /// Rules added to the Seatbelt profile on macOS.
+    pub seatbelt_extensions: SeatbeltExtensions,
+
+    /// User-defined rules that allow, require approval for, or deny
+    /// commands.
+    pub exec_policy: ExecPolicy,
+
+    /// Masks secrets in tool output before it reaches the model or the
+    /// rollout file.
+    pub redactor: Redactor,
+
+    /// When `true`, the pre-image of every file touched during a turn is kept
+    /// under `$CODEX_HOME/snapshots` so the turn can be reverted later.
+    pub snapshot_turns: bool,
+
+    /// When `true`, the working tree is committed to a `codex/<session-id>`
+    /// branch at the end of every turn that changed it.
+    pub checkpoint_commits: bool,
+
+    /// Model requests the session may make before its task is cut short.
+    /// Set by `codex exec --max-turns`.
+    pub max_turns: Option<u32>,
+
+    /// How long the session may run before its task is cut short. Set by
+    /// `codex exec --max-wall-time`.
+    pub max_wall_time: Option<Duration>,
+
+    /// How command output that exceeds the model's budget is shortened.
+    pub tool_output_truncation: ToolOutputTruncation,
+
+    /// When `true`, an outline of the files and definitions in `cwd` is added
+    /// to the conversation at the start of the session.
+    pub repo_map: bool,
+
+    /// Model that summarizes older turns when the history is compacted.
+    /// `None` uses the session's model.
+    pub summary_model: Option<String>,
+
+    /// Directory holding the project's `.codex` directory, found by walking
+    /// up from `cwd`.
+    pub project_root: Option<PathBuf>,
+
+    /// Patterns from `.codex/ignore`, in gitignore syntax relative to
+    /// `project_root`, for files that Codex does not list or suggest.
+    pub project_ignore: Vec<String>,
+
     /// When `true`, `AgentReasoning` events emitted by the backend will be
     /// suppressed from the frontend output. This can reduce visual noise when
     /// users are only interested in the final agent responses.
@@ -108,6 +205,10 @@ pub struct Config {
     /// If unset the feature is disabled.
     pub notify: Option<Vec<String>>,
 
+    /// Which events are reported, and whether they are also shown as
+    /// desktop notifications.
+    pub notifications: Notifications,
+
     /// The directory that should be treated as the current working directory
     /// for the session. All relative paths inside the business-logic layer are
     /// resolved against this path.
@@ -119,6 +220,19 @@ pub struct Config {
     /// Combined provider map (defaults merged with user-defined overrides).
     pub model_providers: HashMap<String, ModelProviderInfo>,
 
+    /// Model servers on this machine that `--oss` can target. The built-in
+    /// `oss` provider (Ollama) is not listed.
+    pub local_providers: HashMap<String, LocalProvider>,
+
+    /// Whether `--oss` may start `ollama serve` when no Ollama server is
+    /// running on this machine.
+    pub ollama_autostart: bool,
+
+    /// Protocol version and optional capabilities the client of the session
+    /// supports. Not read from `config.toml`: frontends in this workspace
+    /// support everything, while the MCP server sets what its client sent.
+    pub protocol: ProtocolHandshake,
+
     /// Maximum number of bytes to include from an AGENTS.md project doc file.
     pub project_doc_max_bytes: usize,
 
@@ -129,13 +243,31 @@ pub struct Config {
     /// Settings that govern if and what will be written to `~/.codex/history.jsonl`.
     pub history: History,
 
+    /// Whether rollouts and the message history are encrypted on disk.
+    pub encryption: Encryption,
+
+    /// Whether each session writes an audit log.
+    pub audit: Audit,
+
+    /// Provider-side prompt caching controls.
+    pub prompt_cache: PromptCache,
+
+    /// OTLP export of traces and metrics.
+    pub telemetry: Telemetry,
+
     /// Optional URI-based file opener. If set, citations to files in the model
     /// output will be hyperlinked using the specified URI scheme.
     pub file_opener: UriBasedFileOpener,
 
+    /// Which files `@` file search offers.
+    pub file_search: FileSearch,
+
     /// Collection of settings that are specific to the TUI.
     pub tui: Tui,
 
+    /// Collection of settings that are specific to `codex mcp`.
+    pub mcp_server: McpServerSettings,
+
     /// Path to the `codex-linux-sandbox` executable. This must be set if
     /// [`crate::exec::SandboxType::LinuxSeccomp`] is used. Note that this
     /// cannot be set in the config file: it must be set in code via
@@ -185,8 +317,8 @@ impl Config {
     /// **in between** the values parsed from `config.toml` and the
     /// strongly-typed overrides specified via [`ConfigOverrides`].
     ///
-    /// The precedence order is therefore: `config.toml` < `-c` overrides <
-    /// `ConfigOverrides`.
+    /// The precedence order is therefore: `config.toml` < the project's
+    /// `.codex/config.toml` < `-c` overrides < `ConfigOverrides`.
     pub fn load_with_cli_overrides(
         cli_overrides: Vec<(String, TomlValue)>,
         overrides: ConfigOverrides,
@@ -196,24 +328,61 @@ impl Config {
         // `Config` instance.
         let codex_home = find_codex_home()?;
 
-        // Step 1: parse `config.toml` into a generic JSON value.
-        let mut root_value = load_config_as_toml(&codex_home)?;
+        // Steps 1 and 2: parse `config.toml` into a generic JSON value and
+        // layer the project's `.codex/config.toml` over it.
+        let cwd = match &overrides.cwd {
+            Some(p) if p.is_absolute() => p.clone(),
+            Some(p) => std::env::current_dir()?.join(p),
+            None => std::env::current_dir()?,
+        };
+        let mut root_value = load_layered_config_as_toml(&codex_home, &cwd)?;
 
-        // Step 2: apply the `-c` overrides.
+        // Step 3: apply the `-c` overrides.
         for (path, value) in cli_overrides.into_iter() {
             apply_toml_override(&mut root_value, &path, value);
         }
 
-        // Step 3: deserialize into `ConfigToml` so that Serde can enforce the
+        // Step 4: deserialize into `ConfigToml` so that Serde can enforce the
         // correct types.
         let cfg: ConfigToml = root_value.try_into().map_err(|e| {
             tracing::error!("Failed to deserialize overridden config: {e}");
             std::io::Error::new(std::io::ErrorKind::InvalidData, e)
         })?;
 
-        // Step 4: merge with the strongly-typed overrides.
+        // Step 5: merge with the strongly-typed overrides.
         Self::load_from_base_config_with_overrides(cfg, overrides, codex_home)
     }
+
+    /// Reload the configuration from disk with the profile `name` applied,
+    /// to switch profiles in a running session. `cwd` and the settings that
+    /// only come from command-line flags carry over from `self`; `-c`
+    /// overrides are not reapplied.
+    pub fn reload_with_profile(&self, name: &str) -> std::io::Result<Self> {
+        let root_value = load_layered_config_as_toml(&self.codex_home, &self.cwd)?;
+        let cfg: ConfigToml = root_value.try_into().map_err(|e| {
+            tracing::error!("Failed to deserialize config: {e}");
+            std::io::Error::new(std::io::ErrorKind::InvalidData, e)
+        })?;
+        let overrides = ConfigOverrides {
+            cwd: Some(self.cwd.clone()),
+            config_profile: Some(name.to_string()),
+            codex_linux_sandbox_exe: self.codex_linux_sandbox_exe.clone(),
+            include_plan_tool: Some(self.include_plan_tool),
+            include_apply_patch_tool: Some(self.include_apply_patch_tool),
+            ..Default::default()
+        };
+        Self::load_from_base_config_with_overrides(cfg, overrides, self.codex_home.clone())
+    }
+}
+
+/// `CODEX_HOME/config.toml` with the `.codex/config.toml` of the project
+/// containing `cwd`, if any, layered over it.
+fn load_layered_config_as_toml(codex_home: &Path, cwd: &Path) -> std::io::Result<TomlValue> {
+    let mut root_value = load_config_as_toml(codex_home)?;
+    if let Some(project_root) = find_project_root(cwd, codex_home) {
+        merge_project_config(&mut root_value, &project_root)?;
+    }
+    Ok(root_value)
 }
 
 pub fn load_config_as_toml_with_cli_overrides(
@@ -257,16 +426,127 @@ pub fn load_config_as_toml(codex_home: &Path) -> std::io::Result<TomlValue> {
     }
 }
 
+/// Find the directory whose `.codex` subdirectory holds project config by
+/// walking up from `cwd`, stopping at the root of the enclosing git
+/// repository. `codex_home` is never taken for a project's `.codex`, so
+/// `~/.codex` is not found when working under the home directory.
+///
+/// Paths are canonicalized and each directory is visited once, so symlink
+/// cycles cannot make the walk loop.
+pub fn find_project_root(cwd: &Path, codex_home: &Path) -> Option<PathBuf> {
+    let codex_home = codex_home.canonicalize().ok();
+    let mut visited = HashSet::new();
+    let mut dir = cwd.canonicalize().ok()?;
+    loop {
+        if !visited.insert(dir.clone()) {
+            return None;
+        }
+        let candidate = dir.join(PROJECT_CONFIG_DIR);
+        if candidate.is_dir() && candidate.canonicalize().ok() != codex_home {
+            return Some(dir);
+        }
+        if dir.join(".git").exists() {
+            return None;
+        }
+        dir = dir.parent()?.to_path_buf();
+    }
+}
+
+/// Merge `project_root/.codex/config.toml`, if present, over `root`. The file
+/// is checked into the repository, so it is only applied once the project is
+/// trusted in `CODEX_HOME/config.toml`: it can configure commands to run and
+/// where requests are sent.
+fn merge_project_config(root: &mut TomlValue, project_root: &Path) -> std::io::Result<()> {
+    let config_path = project_root.join(PROJECT_CONFIG_DIR).join(CONFIG_TOML_FILE);
+    let contents = match std::fs::read_to_string(&config_path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    let mut project_value = toml::from_str::<TomlValue>(&contents).map_err(|e| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("failed to parse {}: {e}", config_path.display()),
+        )
+    })?;
+
+    let projects = root
+        .get("projects")
+        .cloned()
+        .and_then(|projects| projects.try_into().ok());
+    let trust = ConfigToml {
+        projects,
+        ..Default::default()
+    };
+    if !trust.is_cwd_trusted(project_root) {
+        tracing::warn!(
+            "ignoring {} because {} is not a trusted project",
+            config_path.display(),
+            project_root.display()
+        );
+        return Ok(());
+    }
+
+    // Trust is only granted from `CODEX_HOME`.
+    if let TomlValue::Table(table) = &mut project_value {
+        table.remove("projects");
+    }
+    merge_toml(root, project_value);
+    Ok(())
+}
+
+/// Merge `overlay` into `base`: tables are merged key by key, any other value
+/// in `overlay` replaces the one in `base`.
+fn merge_toml(base: &mut TomlValue, overlay: TomlValue) {
+    match (base, overlay) {
+        (TomlValue::Table(base), TomlValue::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_toml(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Read the patterns in `project_root/.codex/ignore`, skipping blank lines and
+/// `#` comments. Invalid patterns are an error.
+fn load_project_ignore(project_root: &Path) -> std::io::Result<Vec<String>> {
+    let ignore_path = project_root
+        .join(PROJECT_CONFIG_DIR)
+        .join(PROJECT_IGNORE_FILE);
+    let contents = match std::fs::read_to_string(&ignore_path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+    let patterns: Vec<String> = contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect();
+
+    let mut builder = GitignoreBuilder::new(project_root);
+    for pattern in &patterns {
+        builder.add_line(None, pattern).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("invalid pattern in {}: {e}", ignore_path.display()),
+            )
+        })?;
+    }
+    Ok(patterns)
+}
+
 /// Patch `CODEX_HOME/config.toml` project state.
 /// Use with caution.
 pub fn set_project_trusted(codex_home: &Path, project_path: &Path) -> anyhow::Result<()> {
-    let config_path = codex_home.join(CONFIG_TOML_FILE);
-    // Parse existing config if present; otherwise start a new document.
-    let mut doc = match std::fs::read_to_string(config_path.clone()) {
-        Ok(s) => s.parse::<DocumentMut>()?,
-        Err(e) if e.kind() == std::io::ErrorKind::NotFound => DocumentMut::new(),
-        Err(e) => return Err(e.into()),
-    };
+    let mut doc = read_config_document(codex_home)?;
 
     // Ensure we render a human-friendly structure:
     //
@@ -324,6 +604,92 @@ pub fn set_project_trusted(codex_home: &Path, project_path: &Path) -> anyhow::Re
     proj_tbl.set_implicit(false);
     proj_tbl["trust_level"] = toml_edit::value("trusted");
 
+    write_config_document(codex_home, &doc)
+}
+
+/// Value of the dotted `key` in `CODEX_HOME/config.toml`, e.g.
+/// `model_providers.openai.base_url`, or `None` if it is not set.
+pub fn get_config_value(codex_home: &Path, key: &str) -> std::io::Result<Option<TomlValue>> {
+    let mut value = load_config_as_toml(codex_home)?;
+    for segment in key.split('.') {
+        let TomlValue::Table(mut table) = value else {
+            return Ok(None);
+        };
+        match table.remove(segment) {
+            Some(v) => value = v,
+            None => return Ok(None),
+        }
+    }
+    Ok(Some(value))
+}
+
+/// Set the dotted `key` in `CODEX_HOME/config.toml` to `value`, creating the
+/// tables on the way as needed. The rest of the file, including comments, is
+/// left as it is. Nothing is written if the result would not load.
+pub fn set_config_value<T: Serialize>(
+    codex_home: &Path,
+    key: &str,
+    value: T,
+) -> anyhow::Result<()> {
+    let segments: Vec<&str> = key.split('.').collect();
+    let Some((last, parents)) = segments.split_last() else {
+        anyhow::bail!("empty config key");
+    };
+    if segments.iter().any(|s| s.is_empty()) {
+        anyhow::bail!("invalid config key `{key}`");
+    }
+    // Go through `toml::Value` so the value is written the way `toml` would
+    // read it back, e.g. enums by their serde names.
+    let value: toml_edit::Value = TomlValue::try_from(value)?.to_string().parse()?;
+
+    let mut doc = read_config_document(codex_home)?;
+    let mut table: &mut dyn TableLike = doc.as_table_mut();
+    for (idx, segment) in parents.iter().enumerate() {
+        match table.get(segment).map(toml_edit::Item::is_table_like) {
+            None => {
+                let mut new_table = toml_edit::Table::new();
+                new_table.set_implicit(true);
+                table.insert(segment, toml_edit::Item::Table(new_table));
+            }
+            Some(true) => {}
+            Some(false) => anyhow::bail!("`{}` is not a table", segments[..=idx].join(".")),
+        }
+        table = table
+            .get_mut(segment)
+            .and_then(toml_edit::Item::as_table_like_mut)
+            .ok_or_else(|| anyhow::anyhow!("`{segment}` table missing after initialization"))?;
+    }
+    match table.get_mut(last) {
+        // Keep the comments around an existing value.
+        Some(toml_edit::Item::Value(existing)) => {
+            let decor = existing.decor().clone();
+            *existing = value;
+            *existing.decor_mut() = decor;
+        }
+        _ => {
+            table.insert(last, toml_edit::Item::Value(value));
+        }
+    }
+
+    let contents = doc.to_string();
+    if let Err(e) = toml::from_str::<ConfigToml>(&contents) {
+        anyhow::bail!("cannot set `{key}`: {e}");
+    }
+    write_config_document(codex_home, &doc)
+}
+
+/// Parse `CODEX_HOME/config.toml` for editing, or start a new document if it
+/// does not exist.
+fn read_config_document(codex_home: &Path) -> anyhow::Result<DocumentMut> {
+    match std::fs::read_to_string(codex_home.join(CONFIG_TOML_FILE)) {
+        Ok(s) => Ok(s.parse::<DocumentMut>()?),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(DocumentMut::new()),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Atomically replace `CODEX_HOME/config.toml` with `doc`.
+fn write_config_document(codex_home: &Path, doc: &DocumentMut) -> anyhow::Result<()> {
     // ensure codex_home exists
     std::fs::create_dir_all(codex_home)?;
 
@@ -332,7 +698,7 @@ pub fn set_project_trusted(codex_home: &Path, project_path: &Path) -> anyhow::Re
     std::fs::write(tmp_file.path(), doc.to_string())?;
 
     // atomically move the tmp file into config.toml
-    tmp_file.persist(config_path)?;
+    tmp_file.persist(codex_home.join(CONFIG_TOML_FILE))?;
 
     Ok(())
 }
@@ -401,6 +767,51 @@ pub struct ConfigToml {
     #[serde(default)]
     pub shell_environment_policy: ShellEnvironmentPolicyToml,
 
+    /// Where shell tool commands are executed. Defaults to `local`.
+    pub execution_backend: Option<ExecutionBackend>,
+
+    /// Container settings used when `execution_backend` is `container`.
+    pub container: Option<ContainerConfig>,
+
+    /// Resource limits applied to each shell tool command.
+    pub exec_limits: Option<ExecLimits>,
+
+    /// User-defined rules that allow, require approval for, or deny
+    /// commands.
+    pub exec_policy: Option<ExecPolicyToml>,
+
+    /// Controls how secrets in tool output are redacted.
+    pub redaction: Option<RedactionToml>,
+
+    /// Keep the pre-image of every file touched during a turn so the turn can
+    /// be reverted later. Defaults to `false`.
+    pub snapshot_turns: Option<bool>,
+
+    /// Commit the working tree to a per-session branch after every turn that
+    /// changed it. Defaults to `false`.
+    pub checkpoint_commits: Option<bool>,
+
+    /// How command output that exceeds the model's budget is shortened.
+    /// Defaults to `head-tail`.
+    pub tool_output_truncation: Option<ToolOutputTruncation>,
+
+    /// Give the model an outline of the repository at session start.
+    /// Defaults to `false`.
+    pub repo_map: Option<bool>,
+
+    /// Model that summarizes older turns when the conversation is compacted.
+    /// Defaults to the session's model.
+    pub summary_model: Option<String>,
+
+    /// File whose contents replace the built-in instructions, with variables
+    /// such as `{{cwd}}` filled in. Relative paths are resolved against
+    /// `CODEX_HOME`.
+    pub base_instructions_template: Option<PathBuf>,
+
+    /// Files appended to the instructions, after the template, with the same
+    /// variables filled in.
+    pub base_instructions_fragments: Option<Vec<PathBuf>>,
+
     /// Sandbox mode to use.
     pub sandbox_mode: Option<SandboxMode>,
 
@@ -410,6 +821,9 @@ pub struct ConfigToml {
     /// Paths that should be blocked from read access when running commands.
     pub read_blocklist: Option<Vec<PathBuf>>,
 
+    /// Rules to add to the Seatbelt profile on macOS.
+    pub sandbox_seatbelt: Option<SandboxSeatbeltToml>,
+
     /// Disable server-side response storage (sends the full conversation
     /// context with every request). Currently necessary for OpenAI customers
     /// who have opted into Zero Data Retention (ZDR).
@@ -419,6 +833,11 @@ pub struct ConfigToml {
     #[serde(default)]
     pub notify: Option<Vec<String>>,
 
+    /// Which events trigger notifications, and whether they are shown on
+    /// the desktop.
+    #[serde(default)]
+    pub notifications: Option<Notifications>,
+
     /// System instructions.
     pub instructions: Option<String>,
 
@@ -430,6 +849,18 @@ pub struct ConfigToml {
     #[serde(default)]
     pub model_providers: HashMap<String, ModelProviderInfo>,
 
+    /// Model servers on this machine, each added as a model provider too.
+    #[serde(default)]
+    pub local_providers: HashMap<String, LocalProvider>,
+
+    /// Local provider that `--oss` selects instead of the built-in Ollama
+    /// provider.
+    pub oss_provider: Option<String>,
+
+    /// Whether `--oss` may start `ollama serve` when no Ollama server is
+    /// running. Defaults to `true`.
+    pub ollama_autostart: Option<bool>,
+
     /// Maximum number of bytes to include from an AGENTS.md project doc file.
     pub project_doc_max_bytes: Option<usize>,
 
@@ -444,13 +875,36 @@ pub struct ConfigToml {
     #[serde(default)]
     pub history: Option<History>,
 
+    /// Encrypt rollouts and the message history on disk.
+    #[serde(default)]
+    pub encryption: Option<Encryption>,
+
+    /// Per-session audit log of approvals, commands, patches and network
+    /// requests.
+    #[serde(default)]
+    pub audit: Option<Audit>,
+
+    /// Provider-side prompt caching controls.
+    #[serde(default)]
+    pub prompt_cache: Option<PromptCache>,
+
+    /// OTLP export of traces and metrics.
+    #[serde(default)]
+    pub telemetry: Option<Telemetry>,
+
     /// Optional URI-based file opener. If set, citations to files in the model
     /// output will be hyperlinked using the specified URI scheme.
     pub file_opener: Option<UriBasedFileOpener>,
 
+    /// Which files `@` file search offers.
+    pub file_search: Option<FileSearch>,
+
     /// Collection of settings that are specific to the TUI.
     pub tui: Option<Tui>,
 
+    /// Collection of settings that are specific to `codex mcp`.
+    pub mcp_server: Option<McpServerSettings>,
+
     /// When set to `true`, `AgentReasoning` events will be hidden from the
     /// UI/output. Defaults to `false`.
     pub hide_agent_reasoning: Option<bool>,
@@ -600,6 +1054,8 @@ pub struct ConfigOverrides {
     pub disable_response_storage: Option<bool>,
     pub show_raw_agent_reasoning: Option<bool>,
     pub tools_web_search_request: Option<bool>,
+    pub max_turns: Option<u32>,
+    pub max_wall_time: Option<Duration>,
 }
 
 impl Config {
@@ -627,9 +1083,12 @@ impl Config {
             disable_response_storage,
             show_raw_agent_reasoning,
             tools_web_search_request: override_tools_web_search_request,
+            max_turns,
+            max_wall_time,
         } = overrides;
 
-        let config_profile = match config_profile_key.as_ref().or(cfg.profile.as_ref()) {
+        let active_profile = config_profile_key.or_else(|| cfg.profile.clone());
+        let config_profile = match active_profile.as_ref() {
             Some(key) => cfg
                 .profiles
                 .get(key)
@@ -643,14 +1102,30 @@ impl Config {
             None => ConfigProfile::default(),
         };
 
-        let sandbox_policy = cfg.derive_sandbox_policy(sandbox_mode);
+        let sandbox_policy =
+            cfg.derive_sandbox_policy(sandbox_mode.or(config_profile.sandbox_mode));
 
         let mut model_providers = built_in_model_providers();
         // Merge user-defined providers into the built-in list.
         for (key, provider) in cfg.model_providers.into_iter() {
             model_providers.entry(key).or_insert(provider);
         }
+        for (key, local_provider) in &cfg.local_providers {
+            model_providers.entry(key.clone()).or_insert_with(|| {
+                let mut provider = create_oss_provider_with_base_url(&local_provider.base_url);
+                provider.name = local_provider.name.clone().unwrap_or_else(|| key.clone());
+                provider
+            });
+        }
 
+        // `--oss` selects the built-in Ollama provider unless `oss_provider`
+        // names another local one.
+        let model_provider = match model_provider {
+            Some(id) if id == BUILT_IN_OSS_MODEL_PROVIDER_ID => {
+                Some(cfg.oss_provider.clone().unwrap_or(id))
+            }
+            other => other,
+        };
         let model_provider_id = model_provider
             .or(config_profile.model_provider)
             .or(cfg.model_provider)
@@ -666,6 +1141,28 @@ impl Config {
             .clone();
 
         let shell_environment_policy = cfg.shell_environment_policy.clone().into();
+        let exec_policy = ExecPolicy::from_toml(cfg.exec_policy.clone().unwrap_or_default())
+            .map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("invalid regex in [exec_policy]: {e}"),
+                )
+            })?;
+        let seatbelt_extensions =
+            SeatbeltExtensions::from_toml(cfg.sandbox_seatbelt.clone().unwrap_or_default())
+                .map_err(|e| {
+                    std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("invalid [sandbox_seatbelt]: {e}"),
+                    )
+                })?;
+        let redactor =
+            Redactor::from_toml(cfg.redaction.clone().unwrap_or_default()).map_err(|e| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidData,
+                    format!("invalid regex in [redaction]: {e}"),
+                )
+            })?;
 
         let resolved_cwd = {
             use std::env;
@@ -686,6 +1183,12 @@ impl Config {
             }
         };
 
+        let project_root = find_project_root(&resolved_cwd, &codex_home);
+        let project_ignore = match &project_root {
+            Some(root) => load_project_ignore(root)?,
+            None => Vec::new(),
+        };
+
         let history = cfg.history.clone().unwrap_or_default();
 
         let tools_web_search_request = override_tools_web_search_request
@@ -697,16 +1200,10 @@ impl Config {
             .or(cfg.model)
             .unwrap_or_else(default_model);
         let model_family = find_family_for_model(&model).unwrap_or_else(|| {
-            let supports_reasoning_summaries =
-                cfg.model_supports_reasoning_summaries.unwrap_or(false);
-            ModelFamily {
-                slug: model.clone(),
-                family: model.clone(),
-                needs_special_apply_patch_instructions: false,
-                supports_reasoning_summaries,
-                uses_local_shell_tool: false,
-                apply_patch_tool_type: None,
-            }
+            default_model_family(
+                &model,
+                cfg.model_supports_reasoning_summaries.unwrap_or(false),
+            )
         });
 
         let openai_model_info = get_model_info(&model_family);
@@ -730,7 +1227,29 @@ impl Config {
             .or(cfg.experimental_instructions_file.as_ref());
         let file_base_instructions =
             Self::get_base_instructions(experimental_instructions_path, &resolved_cwd)?;
-        let base_instructions = base_instructions.or(file_base_instructions);
+        let base_instructions = match base_instructions.or(file_base_instructions) {
+            Some(instructions) => Some(instructions),
+            None => {
+                // Fragments from the config file come before those from the
+                // profile.
+                let fragments: Vec<PathBuf> = cfg
+                    .base_instructions_fragments
+                    .unwrap_or_default()
+                    .into_iter()
+                    .chain(
+                        config_profile
+                            .base_instructions_fragments
+                            .unwrap_or_default(),
+                    )
+                    .collect();
+                Self::get_templated_base_instructions(
+                    cfg.base_instructions_template.as_ref(),
+                    &fragments,
+                    &codex_home,
+                    &resolved_cwd,
+                )?
+            }
+        };
 
         let responses_originator_header: String = cfg
             .responses_originator_header_internal_override
@@ -743,6 +1262,7 @@ impl Config {
             model_max_output_tokens,
             model_provider_id,
             model_provider,
+            active_profile,
             cwd: resolved_cwd,
             approval_policy: approval_policy
                 .or(config_profile.approval_policy)
@@ -750,21 +1270,46 @@ impl Config {
                 .unwrap_or_else(AskForApproval::default),
             sandbox_policy,
             shell_environment_policy,
+            execution_backend: cfg.execution_backend.unwrap_or_default(),
+            container: cfg.container.clone().unwrap_or_default(),
+            exec_limits: cfg.exec_limits.unwrap_or_default(),
+            seatbelt_extensions,
+            exec_policy,
+            redactor,
+            snapshot_turns: cfg.snapshot_turns.unwrap_or(false),
+            checkpoint_commits: cfg.checkpoint_commits.unwrap_or(false),
+            max_turns,
+            max_wall_time,
+            tool_output_truncation: cfg.tool_output_truncation.unwrap_or_default(),
+            repo_map: cfg.repo_map.unwrap_or(false),
+            summary_model: cfg.summary_model.clone(),
+            project_root,
+            project_ignore,
             disable_response_storage: config_profile
                 .disable_response_storage
                 .or(cfg.disable_response_storage)
                 .or(disable_response_storage)
                 .unwrap_or(false),
             notify: cfg.notify,
+            notifications: cfg.notifications.unwrap_or_default(),
             user_instructions,
             base_instructions,
             mcp_servers: cfg.mcp_servers,
             model_providers,
+            local_providers: cfg.local_providers,
+            ollama_autostart: cfg.ollama_autostart.unwrap_or(true),
+            protocol: ProtocolHandshake::current(),
             project_doc_max_bytes: cfg.project_doc_max_bytes.unwrap_or(PROJECT_DOC_MAX_BYTES),
             codex_home,
             history,
+            encryption: cfg.encryption.unwrap_or_default(),
+            audit: cfg.audit.unwrap_or_default(),
+            prompt_cache: cfg.prompt_cache.clone().unwrap_or_default(),
+            telemetry: cfg.telemetry.clone().unwrap_or_default(),
             file_opener: cfg.file_opener.unwrap_or(UriBasedFileOpener::VsCode),
+            file_search: cfg.file_search.clone().unwrap_or_default(),
             tui: cfg.tui.clone().unwrap_or_default(),
+            mcp_server: cfg.mcp_server.clone().unwrap_or_default(),
             codex_linux_sandbox_exe,
 
             hide_agent_reasoning: cfg.hide_agent_reasoning.unwrap_or(false),
@@ -799,6 +1344,19 @@ impl Config {
         Ok(config)
     }
 
+    /// Matcher for the patterns in `.codex/ignore`.
+    pub(crate) fn project_ignore_matcher(&self) -> Gitignore {
+        let Some(project_root) = &self.project_root else {
+            return Gitignore::empty();
+        };
+        let mut builder = GitignoreBuilder::new(project_root);
+        for pattern in &self.project_ignore {
+            // Patterns were validated when the config was loaded.
+            let _ = builder.add_line(None, pattern);
+        }
+        builder.build().unwrap_or_else(|_| Gitignore::empty())
+    }
+
     fn load_instructions(codex_dir: Option<&Path>) -> Option<String> {
         let mut p = match codex_dir {
             Some(p) => p.to_path_buf(),
@@ -857,6 +1415,79 @@ impl Config {
             Ok(Some(s))
         }
     }
+
+    /// Render `base_instructions_template`, or the built-in instructions when
+    /// it is unset, followed by `fragments` in order. Returns `None` when
+    /// neither is configured.
+    fn get_templated_base_instructions(
+        template: Option<&PathBuf>,
+        fragments: &[PathBuf],
+        codex_home: &Path,
+        cwd: &Path,
+    ) -> std::io::Result<Option<String>> {
+        if template.is_none() && fragments.is_empty() {
+            return Ok(None);
+        }
+
+        let files = template
+            .into_iter()
+            .chain(fragments)
+            .map(|path| {
+                // Absolute paths replace `codex_home` when joined.
+                let full_path = codex_home.join(path);
+                std::fs::read_to_string(&full_path)
+                    .map(|contents| (full_path.clone(), contents))
+                    .map_err(|e| {
+                        std::io::Error::new(
+                            e.kind(),
+                            format!(
+                                "failed to read instructions file {}: {e}",
+                                full_path.display()
+                            ),
+                        )
+                    })
+            })
+            .collect::<std::io::Result<Vec<_>>>()?;
+
+        let unknown: Vec<String> = files
+            .iter()
+            .flat_map(|(path, contents)| {
+                prompt_template::unknown_variables(contents)
+                    .into_iter()
+                    .map(move |name| format!("{{{{{name}}}}} in {}", path.display()))
+            })
+            .collect();
+        if !unknown.is_empty() {
+            let available: Vec<String> = prompt_template::VARIABLES
+                .iter()
+                .map(|name| format!("{{{{{name}}}}}"))
+                .collect();
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "unknown variables in instructions: {}; available variables are {}",
+                    unknown.join(", "),
+                    available.join(", ")
+                ),
+            ));
+        }
+
+        let used: Vec<&str> = files
+            .iter()
+            .flat_map(|(_, contents)| prompt_template::variables(contents))
+            .collect();
+        let values = prompt_template::values_for(&used, cwd);
+        let mut sections: Vec<String> = Vec::new();
+        if template.is_none() {
+            sections.push(BASE_INSTRUCTIONS.trim_end().to_string());
+        }
+        sections.extend(files.iter().map(|(_, contents)| {
+            prompt_template::render(contents, &values)
+                .trim()
+                .to_string()
+        }));
+        Ok(Some(sections.join("\n\n")))
+    }
 }
 
 fn default_model() -> String {
@@ -901,6 +1532,10 @@ pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
 #[cfg(test)]
 mod tests {
     use crate::config_types::HistoryPersistence;
+    use crate::config_types::McpDisconnectPolicy;
+    use crate::config_types::McpServerAllowList;
+    use crate::config_types::OtlpProtocol;
+    use crate::config_types::ShellEnvironmentPolicyInherit;
 
     use super::*;
     use pretty_assertions::assert_eq;
@@ -938,6 +1573,105 @@ persistence = "none"
         );
     }
 
+    #[test]
+    fn test_prompt_cache_toml_parsing() {
+        let prompt_cache = r#"
+[prompt_cache]
+key = "shared-prefix"
+"#;
+        let prompt_cache_cfg = toml::from_str::<ConfigToml>(prompt_cache)
+            .expect("TOML deserialization should succeed");
+        assert_eq!(
+            Some(PromptCache {
+                enabled: true,
+                key: Some("shared-prefix".to_string()),
+                cache_control_markers: false,
+            }),
+            prompt_cache_cfg.prompt_cache
+        );
+
+        let prompt_cache_disabled = r#"
+[prompt_cache]
+enabled = false
+"#;
+        let prompt_cache_disabled_cfg = toml::from_str::<ConfigToml>(prompt_cache_disabled)
+            .expect("TOML deserialization should succeed");
+        assert_eq!(
+            Some(PromptCache {
+                enabled: false,
+                key: None,
+                cache_control_markers: false,
+            }),
+            prompt_cache_disabled_cfg.prompt_cache
+        );
+    }
+
+    #[test]
+    fn test_telemetry_toml_parsing() {
+        let telemetry = r#"
+[telemetry]
+enabled = true
+protocol = "http-protobuf"
+endpoint = "https://otel.example.com"
+headers = { "x-api-key" = "secret" }
+"#;
+        let telemetry_cfg =
+            toml::from_str::<ConfigToml>(telemetry).expect("TOML deserialization should succeed");
+        assert_eq!(
+            Some(Telemetry {
+                enabled: true,
+                endpoint: "https://otel.example.com".to_string(),
+                protocol: OtlpProtocol::HttpProtobuf,
+                headers: HashMap::from([("x-api-key".to_string(), "secret".to_string())]),
+                service_name: "codex".to_string(),
+                sample_ratio: 1.0,
+            }),
+            telemetry_cfg.telemetry
+        );
+    }
+
+    #[test]
+    fn test_mcp_server_toml_parsing() {
+        let mcp_server = r#"
+[mcp_server]
+on_disconnect = "abort"
+"#;
+        let mcp_server_cfg =
+            toml::from_str::<ConfigToml>(mcp_server).expect("TOML deserialization should succeed");
+        assert_eq!(
+            Some(McpServerSettings {
+                on_disconnect: McpDisconnectPolicy::Abort,
+                parked_conversation_ttl_secs: 600,
+                allow: McpServerAllowList::default(),
+            }),
+            mcp_server_cfg.mcp_server
+        );
+    }
+
+    #[test]
+    fn test_mcp_server_allow_list_parsing() {
+        let mcp_server = r#"
+[mcp_server.allow]
+sandbox_modes = ["read-only", "workspace-write"]
+approval_policies = ["on-request"]
+cwds = ["/srv/workspaces"]
+config_keys = ["model_reasoning_effort"]
+"#;
+        let mcp_server_cfg =
+            toml::from_str::<ConfigToml>(mcp_server).expect("TOML deserialization should succeed");
+        assert_eq!(
+            Some(McpServerAllowList {
+                sandbox_modes: Some(vec![SandboxMode::ReadOnly, SandboxMode::WorkspaceWrite]),
+                approval_policies: Some(vec![AskForApproval::OnRequest]),
+                model_providers: None,
+                profiles: None,
+                cwds: Some(vec![PathBuf::from("/srv/workspaces")]),
+                config_keys: Some(vec!["model_reasoning_effort".to_string()]),
+            }),
+            mcp_server_cfg.mcp_server.map(|settings| settings.allow)
+        );
+    }
+
     #[test]
     fn test_sandbox_config_parsing() {
         let sandbox_full_access = r#"
@@ -1080,6 +1814,7 @@ disable_response_storage = true
             stream_max_retries: Some(10),
             stream_idle_timeout_ms: Some(300_000),
             requires_openai_auth: false,
+            stream_include_usage: false,
         };
         let model_provider_map = {
             let mut model_provider_map = built_in_model_providers();
@@ -1142,17 +1877,43 @@ disable_response_storage = true
                 approval_policy: AskForApproval::Never,
                 sandbox_policy: SandboxPolicy::new_read_only_policy(),
                 shell_environment_policy: ShellEnvironmentPolicy::default(),
+                execution_backend: ExecutionBackend::default(),
+                container: ContainerConfig::default(),
+                exec_limits: ExecLimits::default(),
+                seatbelt_extensions: SeatbeltExtensions::default(),
+                exec_policy: ExecPolicy::default(),
+                redactor: Redactor::default(),
+                snapshot_turns: false,
+                checkpoint_commits: false,
+                max_turns: None,
+                max_wall_time: None,
+                tool_output_truncation: ToolOutputTruncation::default(),
+                repo_map: false,
+                summary_model: None,
+                project_root: None,
+                project_ignore: Vec::new(),
+                active_profile: Some("o3".to_string()),
                 disable_response_storage: false,
                 user_instructions: None,
                 notify: None,
+                notifications: Notifications::default(),
                 cwd: fixture.cwd(),
                 mcp_servers: HashMap::new(),
                 model_providers: fixture.model_provider_map.clone(),
+                local_providers: HashMap::new(),
+                ollama_autostart: true,
+                protocol: ProtocolHandshake::current(),
                 project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
                 codex_home: fixture.codex_home(),
                 history: History::default(),
+                encryption: Encryption::default(),
+                audit: Audit::default(),
+                prompt_cache: PromptCache::default(),
+                telemetry: Telemetry::default(),
                 file_opener: UriBasedFileOpener::VsCode,
+                file_search: FileSearch::default(),
                 tui: Tui::default(),
+                mcp_server: McpServerSettings::default(),
                 codex_linux_sandbox_exe: None,
                 hide_agent_reasoning: false,
                 show_raw_agent_reasoning: false,
@@ -1198,17 +1959,43 @@ disable_response_storage = true
             approval_policy: AskForApproval::UnlessTrusted,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
+            execution_backend: ExecutionBackend::default(),
+            container: ContainerConfig::default(),
+            exec_limits: ExecLimits::default(),
+            seatbelt_extensions: SeatbeltExtensions::default(),
+            exec_policy: ExecPolicy::default(),
+            redactor: Redactor::default(),
+            snapshot_turns: false,
+            checkpoint_commits: false,
+            max_turns: None,
+            max_wall_time: None,
+            tool_output_truncation: ToolOutputTruncation::default(),
+            repo_map: false,
+            summary_model: None,
+            project_root: None,
+            project_ignore: Vec::new(),
+            active_profile: Some("gpt3".to_string()),
             disable_response_storage: false,
             user_instructions: None,
             notify: None,
+            notifications: Notifications::default(),
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
             model_providers: fixture.model_provider_map.clone(),
+            local_providers: HashMap::new(),
+            ollama_autostart: true,
+            protocol: ProtocolHandshake::current(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             codex_home: fixture.codex_home(),
             history: History::default(),
+            encryption: Encryption::default(),
+            audit: Audit::default(),
+            prompt_cache: PromptCache::default(),
+            telemetry: Telemetry::default(),
             file_opener: UriBasedFileOpener::VsCode,
+            file_search: FileSearch::default(),
             tui: Tui::default(),
+            mcp_server: McpServerSettings::default(),
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
             show_raw_agent_reasoning: false,
@@ -1269,17 +2056,43 @@ disable_response_storage = true
             approval_policy: AskForApproval::OnFailure,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
+            execution_backend: ExecutionBackend::default(),
+            container: ContainerConfig::default(),
+            exec_limits: ExecLimits::default(),
+            seatbelt_extensions: SeatbeltExtensions::default(),
+            exec_policy: ExecPolicy::default(),
+            redactor: Redactor::default(),
+            snapshot_turns: false,
+            checkpoint_commits: false,
+            max_turns: None,
+            max_wall_time: None,
+            tool_output_truncation: ToolOutputTruncation::default(),
+            repo_map: false,
+            summary_model: None,
+            project_root: None,
+            project_ignore: Vec::new(),
+            active_profile: Some("zdr".to_string()),
             disable_response_storage: true,
             user_instructions: None,
             notify: None,
+            notifications: Notifications::default(),
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
             model_providers: fixture.model_provider_map.clone(),
+            local_providers: HashMap::new(),
+            ollama_autostart: true,
+            protocol: ProtocolHandshake::current(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             codex_home: fixture.codex_home(),
             history: History::default(),
+            encryption: Encryption::default(),
+            audit: Audit::default(),
+            prompt_cache: PromptCache::default(),
+            telemetry: Telemetry::default(),
             file_opener: UriBasedFileOpener::VsCode,
+            file_search: FileSearch::default(),
             tui: Tui::default(),
+            mcp_server: McpServerSettings::default(),
             codex_linux_sandbox_exe: None,
             hide_agent_reasoning: false,
             show_raw_agent_reasoning: false,
@@ -1302,6 +2115,210 @@ disable_response_storage = true
         Ok(())
     }
 
+    #[test]
+    fn test_base_instructions_template_with_profile_fragments() -> std::io::Result<()> {
+        let fixture = create_test_fixture()?;
+        let codex_home = fixture.codex_home();
+        std::fs::write(codex_home.join("prompt.md"), "You work in {{ cwd }}.\n")?;
+        std::fs::write(codex_home.join("style.md"), "Be brief.\n")?;
+        std::fs::write(codex_home.join("rust.md"), "Run cargo test on {{os}}.\n")?;
+
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+base_instructions_template = "prompt.md"
+base_instructions_fragments = ["style.md"]
+profile = "rust"
+
+[profiles.rust]
+base_instructions_fragments = ["rust.md"]
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                cwd: Some(fixture.cwd()),
+                ..Default::default()
+            },
+            codex_home,
+        )?;
+
+        assert_eq!(
+            config.base_instructions,
+            Some(format!(
+                "You work in {}.\n\nBe brief.\n\nRun cargo test on {}.",
+                fixture.cwd().display(),
+                std::env::consts::OS
+            ))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_base_instructions_template_rejects_unknown_variables() -> std::io::Result<()> {
+        let fixture = create_test_fixture()?;
+        let codex_home = fixture.codex_home();
+        let template = codex_home.join("prompt.md");
+        std::fs::write(&template, "{{cwd}} {{branch}} {{ user }}\n")?;
+
+        let cfg: ConfigToml = toml::from_str(r#"base_instructions_template = "prompt.md""#)
+            .expect("TOML deserialization should succeed");
+        let err = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                cwd: Some(fixture.cwd()),
+                ..Default::default()
+            },
+            codex_home,
+        )
+        .expect_err("unknown variables should be rejected");
+
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "unknown variables in instructions: {{{{branch}}}} in {path}, {{{{user}}}} in {path}; \
+                 available variables are {{{{cwd}}}}, {{{{os}}}}, {{{{git_branch}}}}, {{{{date}}}}",
+                path = template.display()
+            )
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_sandbox_seatbelt_rejects_rules_that_widen_the_policy() -> std::io::Result<()> {
+        let fixture = create_test_fixture()?;
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+[sandbox_seatbelt]
+rules = ["(allow network-outbound)"]
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let err = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                cwd: Some(fixture.cwd()),
+                ..Default::default()
+            },
+            fixture.codex_home(),
+        )
+        .expect_err("rules allowing network access should be rejected");
+
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "invalid [sandbox_seatbelt]: rule `(allow network-outbound)` allows \
+             `network-outbound`, which follows `sandbox_mode` instead"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_find_project_root_stops_at_git_root_and_skips_codex_home() -> std::io::Result<()> {
+        let home = TempDir::new()?;
+        let home_path = home.path().canonicalize()?;
+        let codex_home = home_path.join(".codex");
+        let outer = home_path.join("outer");
+        let repo = outer.join("repo");
+        let src = repo.join("src");
+        std::fs::create_dir_all(&codex_home)?;
+        std::fs::create_dir_all(&src)?;
+
+        // `~/.codex` is the user's, not a project's.
+        assert_eq!(find_project_root(&src, &codex_home), None);
+
+        std::fs::create_dir_all(outer.join(".codex"))?;
+        assert_eq!(find_project_root(&src, &codex_home), Some(outer.clone()));
+
+        // The walk does not leave the git repository.
+        std::fs::create_dir_all(repo.join(".git"))?;
+        assert_eq!(find_project_root(&src, &codex_home), None);
+
+        std::fs::create_dir_all(repo.join(".codex"))?;
+        assert_eq!(find_project_root(&src, &codex_home), Some(repo));
+        Ok(())
+    }
+
+    #[test]
+    fn test_project_config_is_layered_over_user_config_when_trusted() -> std::io::Result<()> {
+        let project = TempDir::new()?;
+        let project_root = project.path().canonicalize()?;
+        std::fs::create_dir_all(project_root.join(".codex"))?;
+        std::fs::write(
+            project_root.join(".codex").join("config.toml"),
+            r#"
+model = "o3"
+
+[shell_environment_policy]
+inherit = "core"
+
+[projects."/elsewhere"]
+trust_level = "trusted"
+"#,
+        )?;
+        let user_config = |trust_level: &str| -> TomlValue {
+            toml::from_str(&format!(
+                r#"
+model = "gpt-5"
+approval_policy = "on-request"
+
+[shell_environment_policy]
+ignore_default_excludes = true
+
+[projects.'{}']
+trust_level = "{trust_level}"
+"#,
+                project_root.display()
+            ))
+            .expect("TOML deserialization should succeed")
+        };
+
+        let mut untrusted = user_config("untrusted");
+        merge_project_config(&mut untrusted, &project_root)?;
+        assert_eq!(untrusted, user_config("untrusted"));
+
+        let mut trusted = user_config("trusted");
+        merge_project_config(&mut trusted, &project_root)?;
+        let cfg: ConfigToml = trusted
+            .try_into()
+            .expect("merged config should deserialize");
+        assert_eq!(cfg.model.as_deref(), Some("o3"));
+        assert_eq!(cfg.approval_policy, Some(AskForApproval::OnRequest));
+        let shell_environment_policy = cfg.shell_environment_policy;
+        assert_eq!(
+            shell_environment_policy.inherit,
+            Some(ShellEnvironmentPolicyInherit::Core)
+        );
+        assert_eq!(shell_environment_policy.ignore_default_excludes, Some(true));
+        assert_eq!(
+            cfg.projects
+                .map(|projects| projects.into_keys().collect::<Vec<_>>()),
+            Some(vec![project_root.display().to_string()])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_project_ignore_skips_comments_and_rejects_invalid_patterns() -> std::io::Result<()>
+    {
+        let project = TempDir::new()?;
+        let ignore_path = project.path().join(".codex").join("ignore");
+        assert_eq!(load_project_ignore(project.path())?, Vec::<String>::new());
+
+        std::fs::create_dir_all(project.path().join(".codex"))?;
+        std::fs::write(&ignore_path, "# generated code\nfixtures/\n\n*.snap\n")?;
+        assert_eq!(
+            load_project_ignore(project.path())?,
+            vec!["fixtures/".to_string(), "*.snap".to_string()]
+        );
+
+        std::fs::write(&ignore_path, "src/[a-\n")?;
+        let err = load_project_ignore(project.path()).expect_err("pattern should be rejected");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        Ok(())
+    }
+
     #[test]
     fn test_set_project_trusted_writes_explicit_tables() -> anyhow::Result<()> {
         let codex_home = TempDir::new().unwrap();
@@ -1370,5 +2387,122 @@ trust_level = "trusted"
         Ok(())
     }
 
+    #[test]
+    fn test_set_config_value_keeps_comments_and_creates_tables() -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+        let config_path = codex_home.path().join(CONFIG_TOML_FILE);
+        std::fs::write(
+            &config_path,
+            r#"# Default model.
+model = "o3" # fast
+
+[model_providers.local]
+name = "Local"
+base_url = "http://localhost:8080/v1"
+"#,
+        )?;
+
+        set_config_value(codex_home.path(), "model", "gpt-5")?;
+        set_config_value(
+            codex_home.path(),
+            "model_providers.local.base_url",
+            "http://localhost:9090/v1",
+        )?;
+        set_config_value(codex_home.path(), "tools.web_search", true)?;
+        set_config_value(codex_home.path(), "preferred_auth_method", AuthMode::ApiKey)?;
+
+        assert_eq!(
+            std::fs::read_to_string(&config_path)?,
+            r#"# Default model.
+model = "gpt-5" # fast
+preferred_auth_method = "apikey"
+
+[model_providers.local]
+name = "Local"
+base_url = "http://localhost:9090/v1"
+
+[tools]
+web_search = true
+"#
+        );
+        assert_eq!(
+            get_config_value(codex_home.path(), "model_providers.local.base_url")?,
+            Some(TomlValue::String("http://localhost:9090/v1".to_string()))
+        );
+        assert_eq!(
+            get_config_value(codex_home.path(), "model_providers.remote.base_url")?,
+            None
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_config_value_rejects_values_that_would_not_load() -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+        let config_path = codex_home.path().join(CONFIG_TOML_FILE);
+        std::fs::write(&config_path, "model = \"o3\"\n")?;
+
+        assert!(set_config_value(codex_home.path(), "hide_agent_reasoning", "yes").is_err());
+        assert!(set_config_value(codex_home.path(), "model.name", "o3").is_err());
+        assert_eq!(std::fs::read_to_string(&config_path)?, "model = \"o3\"\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reload_with_profile_applies_profile_settings() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cwd = TempDir::new()?;
+        let contents = r#"
+model = "o3"
+
+[profiles.careful]
+model = "gpt-5"
+model_provider = "oss"
+approval_policy = "untrusted"
+sandbox_mode = "read-only"
+"#;
+        std::fs::write(codex_home.path().join(CONFIG_TOML_FILE), contents)?;
+        let cfg: ConfigToml =
+            toml::from_str(contents).expect("TOML deserialization should succeed");
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                cwd: Some(cwd.path().to_path_buf()),
+                sandbox_mode: Some(SandboxMode::WorkspaceWrite),
+                include_plan_tool: Some(true),
+                ..Default::default()
+            },
+            codex_home.path().to_path_buf(),
+        )?;
+        assert_eq!(config.active_profile, None);
+
+        let careful = config.reload_with_profile("careful")?;
+        assert_eq!(
+            (
+                careful.active_profile.as_deref(),
+                careful.model.as_str(),
+                careful.model_provider_id.as_str(),
+                careful.approval_policy,
+                careful.sandbox_policy,
+                careful.include_plan_tool,
+                careful.cwd,
+            ),
+            (
+                Some("careful"),
+                "gpt-5",
+                "oss",
+                AskForApproval::UnlessTrusted,
+                SandboxPolicy::new_read_only_policy(),
+                true,
+                cwd.path().to_path_buf(),
+            )
+        );
+        assert!(config.reload_with_profile("missing").is_err());
+
+        Ok(())
+    }
+
     // No test enforcing the presence of a standalone [projects] header.
 }
@@ -7,6 +7,9 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 use wildmatch::WildMatchPattern;
 
+use crate::protocol::AskForApproval;
+use crate::protocol::TokenUsage;
+use codex_protocol::config_types::SandboxMode;
 use serde::Deserialize;
 use serde::Serialize;
 use strum_macros::Display;
@@ -20,6 +23,22 @@ pub struct McpServerConfig {
 
     #[serde(default)]
     pub env: Option<HashMap<String, String>>,
+
+    /// Whether the server may ask Codex to generate text with the session's
+    /// model (`sampling/createMessage`).
+    #[serde(default)]
+    pub sampling: McpSamplingPolicy,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum McpSamplingPolicy {
+    /// Sampling requests are rejected and the capability is not advertised.
+    #[default]
+    Deny,
+
+    /// Sampling requests are answered with the session's model.
+    Allow,
 }
 
 #[derive(Deserialize, Debug, Copy, Clone, PartialEq)]
@@ -74,9 +93,557 @@ pub enum HistoryPersistence {
     None,
 }
 
+/// Settings that control provider-side prompt caching.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct PromptCache {
+    /// When false, requests are sent without a cache key or cache-control
+    /// markers.
+    pub enabled: bool,
+
+    /// Cache key sent as `prompt_cache_key` with Responses API requests.
+    /// Defaults to the session id; set a fixed value to share cached prefixes
+    /// across sessions.
+    pub key: Option<String>,
+
+    /// If true, Chat Completions requests mark the system prompt and the
+    /// latest user message with `cache_control: {"type": "ephemeral"}`
+    /// breakpoints. Only honored by providers that support them.
+    pub cache_control_markers: bool,
+}
+
+impl Default for PromptCache {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            key: None,
+            cache_control_markers: false,
+        }
+    }
+}
+
+/// Where shell tool commands are executed.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExecutionBackend {
+    /// Run commands on the host, under the platform sandbox when one applies.
+    #[default]
+    Local,
+    /// Run commands inside a container (see [`ContainerConfig`]).
+    Container,
+}
+
+/// How command output longer than the model's budget is shortened.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ToolOutputTruncation {
+    /// Keep the first and last lines.
+    #[default]
+    HeadTail,
+    /// Keep error, warning and stack trace lines with some context around
+    /// them, plus the first and last lines.
+    Salient,
+}
+
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ContainerRuntime {
+    #[default]
+    Docker,
+    Podman,
+}
+
+impl ContainerRuntime {
+    /// Name of the runtime's CLI executable.
+    pub fn program(&self) -> &'static str {
+        match self {
+            ContainerRuntime::Docker => "docker",
+            ContainerRuntime::Podman => "podman",
+        }
+    }
+}
+
+/// API a local model server speaks, which decides how `--oss` checks that it
+/// is running and lists its models.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum LocalProviderApi {
+    /// Ollama's native API; missing models are pulled.
+    Ollama,
+    /// Any OpenAI-compatible server, such as LM Studio, vLLM or llama.cpp's
+    /// `llama-server`; models must already be loaded.
+    #[default]
+    Openai,
+}
+
+/// A model server on this machine that `--oss` can target, declared under
+/// `[local_providers.<id>]`. Each one is also available as a model provider
+/// with the same id.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct LocalProvider {
+    /// Friendly display name; defaults to the id.
+    pub name: Option<String>,
+
+    /// Base URL of the server's OpenAI-compatible API, e.g.
+    /// `http://localhost:1234/v1`.
+    pub base_url: String,
+
+    #[serde(default)]
+    pub api: LocalProviderApi,
+}
+
+/// When the container image is pulled before running a command.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum ContainerPullPolicy {
+    /// Pull only if the image is not present locally.
+    #[default]
+    Missing,
+    /// Pull before the first command run by this process, even if the image
+    /// is present locally.
+    Always,
+    /// Never pull; fail if the image is not present locally.
+    Never,
+}
+
+/// Settings for `execution_backend = "container"`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ContainerConfig {
+    pub runtime: ContainerRuntime,
+
+    /// Image that commands run in.
+    pub image: String,
+
+    pub pull: ContainerPullPolicy,
+
+    /// `--user` that commands run as, e.g. `"1000:1000"` or `"0:0"`. Defaults
+    /// to the host user's uid:gid on Unix, so that files written to the
+    /// workspace belong to the user rather than to root.
+    pub user: Option<String>,
+
+    /// Extra arguments inserted into `<runtime> run` before the image name
+    /// (e.g. `["--memory", "4g"]`).
+    pub extra_args: Vec<String>,
+}
+
+impl Default for ContainerConfig {
+    fn default() -> Self {
+        Self {
+            runtime: ContainerRuntime::default(),
+            image: "ubuntu:24.04".to_string(),
+            pull: ContainerPullPolicy::default(),
+            user: None,
+            extra_args: Vec::new(),
+        }
+    }
+}
+
+/// Per-command resource limits for shell tool calls. Unset limits are not
+/// enforced.
+#[derive(Deserialize, Debug, Copy, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct ExecLimits {
+    /// Wall-clock timeout for commands that do not request one, and the upper
+    /// bound for commands that do.
+    pub timeout_ms: Option<u64>,
+
+    /// CPU time limit in seconds (`RLIMIT_CPU`), applied to each process.
+    pub cpu_time_seconds: Option<u64>,
+
+    /// Address-space limit in bytes (`RLIMIT_AS`), applied to each process.
+    /// Runtimes that reserve large address ranges up front (Go, the JVM,
+    /// Node.js) may fail to start under low values.
+    pub max_memory_bytes: Option<u64>,
+
+    /// Combined stdout and stderr bytes after which the command is killed.
+    pub max_output_bytes: Option<u64>,
+}
+
+/// User-defined command rules from the `[exec_policy]` section. They are
+/// consulted before the built-in list of known-safe commands; a `deny` rule
+/// wins over an `ask` rule, which wins over an `allow` rule.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ExecPolicyToml {
+    /// Commands that run in the sandbox without asking for approval.
+    pub allow: Option<Vec<CommandRuleToml>>,
+
+    /// Commands that always require approval, even if they are known to be
+    /// safe.
+    pub ask: Option<Vec<CommandRuleToml>>,
+
+    /// Commands that are always rejected.
+    pub deny: Option<Vec<CommandRuleToml>>,
+}
+
+/// A single `[exec_policy]` rule.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(untagged)]
+pub enum CommandRuleToml {
+    /// Whitespace-separated glob patterns, e.g. `"git push --force"`. The
+    /// first pattern matches the program (or its file name) and the rest must
+    /// match arguments in the same order, though other arguments may come in
+    /// between.
+    Glob(String),
+
+    /// A regular expression matched against the whole command line, with
+    /// arguments joined by single spaces.
+    Regex { regex: String },
+}
+
+/// Settings for the `[redaction]` section, which controls how secrets in tool
+/// output are masked before they reach the model or the rollout file.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct RedactionToml {
+    /// Set to `false` to turn redaction off entirely. Defaults to `true`.
+    pub enabled: Option<bool>,
+
+    /// Additional regular expressions to redact. If a pattern has a capture
+    /// group, only the text matched by the first group is replaced.
+    pub patterns: Option<Vec<String>>,
+}
+
+/// Settings for the `[encryption]` section, which controls whether rollouts
+/// and the message history are encrypted on disk.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+#[serde(default)]
+pub struct Encryption {
+    /// Encrypt every line written to rollout and history files with a key
+    /// kept in the OS keychain. Defaults to `false`.
+    pub enabled: bool,
+}
+
+/// Settings for the `[audit]` section, which controls the per-session audit
+/// log under `$CODEX_HOME/audit`.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(default)]
+pub struct Audit {
+    /// Record approval decisions, commands, patches and network requests.
+    /// Defaults to `true`.
+    pub enabled: bool,
+}
+
+impl Default for Audit {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+/// Settings for the `[notifications]` section, which controls what is
+/// reported to the `notify` program and, optionally, as native desktop
+/// notifications.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Notifications {
+    /// Show native desktop notifications. Defaults to `false`.
+    pub desktop: bool,
+    /// The events that trigger a notification. Defaults to all of them.
+    pub events: Vec<NotificationEvent>,
+    /// URL that `codex exec` POSTs a summary of the run to when it finishes.
+    pub webhook: Option<String>,
+    /// How the TUI gets attention for an event while its terminal window is
+    /// unfocused. Defaults to `bell`.
+    pub terminal: TerminalNotification,
+    /// Also put the event in the terminal's title while it is unfocused, until
+    /// it regains focus. Defaults to `true`.
+    pub title: bool,
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self {
+            desktop: false,
+            events: vec![
+                NotificationEvent::ApprovalRequested,
+                NotificationEvent::AgentTurnComplete,
+                NotificationEvent::Error,
+            ],
+            webhook: None,
+            terminal: TerminalNotification::default(),
+            title: true,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum TerminalNotification {
+    /// Nothing beyond the title.
+    Off,
+    /// Ring the terminal bell (BEL), which most terminals turn into a dock
+    /// bounce, taskbar flash or urgency hint.
+    #[default]
+    Bell,
+    /// An OSC 9 desktop notification, supported by iTerm2, WezTerm, kitty,
+    /// Ghostty and Windows Terminal among others.
+    Osc9,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum NotificationEvent {
+    /// Codex is waiting for the user to approve a command or patch.
+    ApprovalRequested,
+    /// The agent finished its turn.
+    AgentTurnComplete,
+    /// A turn stopped because of an error.
+    Error,
+}
+
+/// Settings for the `[telemetry]` section, which exports traces and metrics
+/// over OTLP. Only honored when Codex is built with the `otel` feature.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Telemetry {
+    /// Export spans and metrics. Defaults to `false`.
+    pub enabled: bool,
+
+    /// OTLP collector endpoint.
+    pub endpoint: String,
+
+    pub protocol: OtlpProtocol,
+
+    /// Extra headers sent with every export request, e.g. an API key for a
+    /// hosted collector.
+    pub headers: HashMap<String, String>,
+
+    /// Reported as the `service.name` resource attribute.
+    pub service_name: String,
+
+    /// Fraction of turns whose spans are exported, from `0.0` to `1.0`.
+    pub sample_ratio: f64,
+}
+
+impl Default for Telemetry {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            endpoint: "http://localhost:4317".to_string(),
+            protocol: OtlpProtocol::default(),
+            headers: HashMap::new(),
+            service_name: "codex".to_string(),
+            sample_ratio: 1.0,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+pub enum OtlpProtocol {
+    #[default]
+    Grpc,
+    HttpProtobuf,
+}
+
+/// Which files `@` file search offers, on top of `.gitignore` and
+/// `.codexignore` files.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct FileSearch {
+    /// Globs, relative to the workspace, for files to leave out.
+    pub exclude: Vec<String>,
+
+    /// Include hidden files and directories.
+    pub include_hidden: bool,
+
+    /// Include files ignored by `.gitignore`, `.ignore` and git's excludes.
+    pub include_ignored: bool,
+}
+
 /// Collection of settings that are specific to the TUI.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct Tui {
+    /// Capture mouse events for wheel scrolling, click-to-focus and clickable
+    /// file citations. Disable to keep the terminal's own text selection.
+    pub mouse: bool,
+
+    /// Theme used to syntax-highlight code blocks and patch previews. One of
+    /// the themes bundled with the TUI, or `none` to disable highlighting.
+    pub syntax_theme: String,
+
+    /// Show a status bar under the composer with the model, approval and
+    /// sandbox policy, git branch, queued messages and context left. Can be
+    /// toggled with `/statusbar`.
+    pub status_bar: bool,
+
+    /// Split the screen: the transcript scrolls above, and a working pane
+    /// above the composer shows the running command's output or the current
+    /// turn's diff. Can be toggled with `/split`.
+    pub split_pane: bool,
+
+    /// Rows of the working pane in the split layout, including its title.
+    /// Resized with `Ctrl+↑` and `Ctrl+↓`.
+    pub working_pane_height: u16,
+
+    /// Watch the workspace for files changed outside the agent, e.g. edited
+    /// in an IDE, and offer to send their diff with the next message.
+    pub watch_files: bool,
+
+    /// Keep a persistent index of the files in large workspaces under
+    /// `$CODEX_HOME/file-index` so `@` file search does not walk the whole
+    /// tree on every keystroke.
+    pub file_index: bool,
+
+    /// Color theme: `auto` (follow the terminal's background), `dark`,
+    /// `light`, `high-contrast`, or the name of a palette in `themes`.
+    pub theme: String,
+
+    /// User-defined palettes, selected by name with `theme`.
+    pub themes: HashMap<String, ThemePalette>,
+
+    /// Custom slash commands, keyed by name (without the leading `/`).
+    pub slash_commands: HashMap<String, CustomSlashCommand>,
+
+    /// Prices used by `/usage` to estimate cost, keyed by model name.
+    pub model_pricing: HashMap<String, ModelPricing>,
+}
+
+/// What a model costs, in US dollars per million tokens.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ModelPricing {
+    pub input: f64,
+
+    /// Input tokens served from the prompt cache. Defaults to `input`.
+    #[serde(default)]
+    pub cached_input: Option<f64>,
+
+    /// Output tokens, reasoning included.
+    pub output: f64,
+}
+
+impl ModelPricing {
+    /// Estimated cost of `usage`, in US dollars.
+    pub fn cost(&self, usage: &TokenUsage) -> f64 {
+        let cached_input = self.cached_input.unwrap_or(self.input);
+        (usage.non_cached_input() as f64 * self.input
+            + usage.cached_input() as f64 * cached_input
+            + usage.output_tokens as f64 * self.output)
+            / 1_000_000.0
+    }
+}
+
+/// A slash command that sends a prompt from a template.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct CustomSlashCommand {
+    /// Shown next to the command in the popup.
+    #[serde(default)]
+    pub description: Option<String>,
+
+    /// The prompt to send. `$ARGUMENTS` is replaced with everything typed
+    /// after the command, and `$1` to `$9` with its individual
+    /// (shell-quoted) arguments. Without placeholders, the arguments are
+    /// appended after a blank line.
+    pub prompt: String,
+
+    /// Model for the turn the command starts, instead of the session's.
+    #[serde(default)]
+    pub model: Option<String>,
+
+    /// Profile whose model, reasoning, approval and sandbox settings apply
+    /// to the turn the command starts. `model` takes precedence over the
+    /// profile's model.
+    #[serde(default)]
+    pub profile: Option<String>,
+}
+
+/// A user-defined color theme. Each color is an ANSI name (`cyan`,
+/// `light-blue`), a 256-color index or `#rrggbb`; colors that are not set
+/// come from `base`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct ThemePalette {
+    /// Built-in theme to start from: `dark`, `light` or `high-contrast`.
+    /// Defaults to the theme `auto` would pick.
+    pub base: Option<String>,
+
+    /// Key hints, selection and status indicators.
+    pub accent: Option<String>,
+
+    /// Success markers and added lines.
+    pub success: Option<String>,
+
+    /// Errors, failures and deleted lines.
+    pub error: Option<String>,
+
+    /// Codex's own messages and commands.
+    pub codex: Option<String>,
+
+    /// Secondary text: `dim`, `default` for the normal foreground, or a color.
+    pub secondary: Option<String>,
+}
+
+impl Default for Tui {
+    fn default() -> Self {
+        Self {
+            mouse: true,
+            syntax_theme: "base16-ocean.dark".to_string(),
+            status_bar: false,
+            split_pane: false,
+            working_pane_height: 12,
+            watch_files: false,
+            file_index: false,
+            theme: "auto".to_string(),
+            themes: HashMap::new(),
+            slash_commands: HashMap::new(),
+            model_pricing: HashMap::new(),
+        }
+    }
+}
+
+/// Collection of settings that are specific to `codex mcp`.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct McpServerSettings {
+    /// What happens to a conversation's running turn when the client
+    /// disconnects.
+    pub on_disconnect: McpDisconnectPolicy,
+
+    /// How long a conversation without a listener is kept so a client can
+    /// reattach to it before it is shut down. Also bounds how long the server
+    /// waits for running turns once the client has disconnected.
+    pub parked_conversation_ttl_secs: u64,
+
+    /// Limits what a client may choose for each conversation it starts.
+    pub allow: McpServerAllowList,
+}
+
+impl Default for McpServerSettings {
+    fn default() -> Self {
+        Self {
+            on_disconnect: McpDisconnectPolicy::default(),
+            parked_conversation_ttl_secs: 600,
+            allow: McpServerAllowList::default(),
+        }
+    }
+}
+
+/// Values an MCP client may select for a conversation, whether directly or
+/// through config overrides. A list that is not set allows any value.
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
-pub struct Tui {}
+#[serde(default)]
+pub struct McpServerAllowList {
+    pub sandbox_modes: Option<Vec<SandboxMode>>,
+    pub approval_policies: Option<Vec<AskForApproval>>,
+    pub model_providers: Option<Vec<String>>,
+    pub profiles: Option<Vec<String>>,
+    /// Directories the conversation's working directory must be inside of.
+    pub cwds: Option<Vec<PathBuf>>,
+    /// Keys clients may set through free-form `config` overrides once any
+    /// list is set. An entry also covers the keys nested under it.
+    pub config_keys: Option<Vec<String>>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum McpDisconnectPolicy {
+    /// Let the running turn finish so its work is kept in the rollout.
+    #[default]
+    FinishTurn,
+
+    /// Interrupt the running turn right away.
+    Abort,
+}
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct SandboxWorkspaceWrite {
@@ -90,6 +657,25 @@ pub struct SandboxWorkspaceWrite {
     pub exclude_slash_tmp: bool,
 }
 
+/// Additions to the macOS Seatbelt profile from the `[sandbox_seatbelt]`
+/// section. Write and network access are not configured here: they follow
+/// `sandbox_mode` and `[sandbox_workspace_write]`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct SandboxSeatbeltToml {
+    /// Absolute paths, and everything under them, that commands may read
+    /// even when `read_blocklist` covers them.
+    pub readable_paths: Vec<PathBuf>,
+
+    /// Mach services that commands may look up, e.g.
+    /// `com.apple.SecurityServer`.
+    pub mach_services: Vec<String>,
+
+    /// SBPL rules, each a single `(allow ...)` or `(deny ...)` form,
+    /// appended to the end of the profile.
+    pub rules: Vec<String>,
+}
+
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 #[serde(rename_all = "kebab-case")]
 pub enum ShellEnvironmentPolicyInherit {
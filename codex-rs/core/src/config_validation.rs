@@ -0,0 +1,336 @@
+//! Checks `config.toml` for mistakes that loading it either rejects with a
+//! terse error or silently ignores, and suggests a fix for each.
+//!
+//! The schema is the one `serde` derives for [`ConfigToml`]: keys it does not
+//! know are reported as unknown and values it cannot deserialize as type
+//! mismatches, so the checks never drift from what loading accepts.
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::Path;
+
+use toml::Value as TomlValue;
+
+use crate::config::CONFIG_TOML_FILE;
+use crate::config::Config;
+use crate::config::ConfigOverrides;
+use crate::config::ConfigToml;
+use crate::model_provider_info::built_in_model_providers;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IssueSeverity {
+    /// Codex refuses to start with this config, or part of it cannot work.
+    Error,
+    /// The config loads, but probably not as intended.
+    Warning,
+}
+
+/// A problem found by [`validate_config`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigIssue {
+    pub severity: IssueSeverity,
+    /// Dotted path of the offending key, e.g. `profiles.fast.model_provider`.
+    pub key: Option<String>,
+    pub message: String,
+    /// What to change to resolve the issue.
+    pub fix: Option<String>,
+}
+
+impl ConfigIssue {
+    fn error(key: Option<String>, message: String, fix: Option<String>) -> Self {
+        Self {
+            severity: IssueSeverity::Error,
+            key,
+            message,
+            fix,
+        }
+    }
+
+    fn warning(key: Option<String>, message: String, fix: Option<String>) -> Self {
+        Self {
+            severity: IssueSeverity::Warning,
+            key,
+            message,
+            fix,
+        }
+    }
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.key {
+            Some(key) => write!(f, "`{key}`: {}", self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Check `CODEX_HOME/config.toml`. Returns no issues when the file does not
+/// exist.
+pub fn validate_config(codex_home: &Path) -> Vec<ConfigIssue> {
+    let path = codex_home.join(CONFIG_TOML_FILE);
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+        Err(e) => {
+            return vec![ConfigIssue::error(
+                None,
+                format!("cannot read {}: {e}", path.display()),
+                None,
+            )];
+        }
+    };
+    match toml::from_str::<TomlValue>(&contents) {
+        Ok(value) => validate_toml(value, codex_home),
+        Err(e) => vec![ConfigIssue::error(
+            None,
+            format!("{} is not valid TOML: {e}", path.display()),
+            Some("fix the syntax at the position shown".to_string()),
+        )],
+    }
+}
+
+fn validate_toml(value: TomlValue, codex_home: &Path) -> Vec<ConfigIssue> {
+    let mut issues = Vec::new();
+
+    let mut unknown_keys = Vec::new();
+    let mut record_unknown = |path: serde_ignored::Path<'_>| {
+        // Options show up as `?` segments; they are not part of the key.
+        unknown_keys.push(path.to_string().replace("?.", ""));
+    };
+    let deserializer = serde_ignored::Deserializer::new(value, &mut record_unknown);
+    let parsed: Result<ConfigToml, _> = serde_path_to_error::deserialize(deserializer);
+    for key in unknown_keys {
+        issues.push(ConfigIssue::warning(
+            Some(key),
+            "unknown key, ignored".to_string(),
+            Some("check its spelling and table against config.md, or remove it".to_string()),
+        ));
+    }
+
+    let cfg = match parsed {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            let key = e.path().to_string();
+            issues.push(ConfigIssue::error(
+                (key != ".").then_some(key),
+                e.inner().to_string(),
+                Some("change the value to the expected type".to_string()),
+            ));
+            return issues;
+        }
+    };
+
+    check_references(&cfg, &mut issues);
+    check_mcp_servers(&cfg, &mut issues);
+
+    // Anything else loading rejects, such as an invalid `exec_policy` regex
+    // or a missing instructions file. Skipped when the checks above already
+    // found the error loading would stop at.
+    if !issues.iter().any(|i| i.severity == IssueSeverity::Error)
+        && let Err(e) = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.to_path_buf(),
+        )
+    {
+        issues.push(ConfigIssue::error(None, e.to_string(), None));
+    }
+
+    issues
+}
+
+/// `profile` must name a table in `profiles`, every `model_provider` a
+/// built-in provider or a table in `model_providers` or `local_providers`,
+/// and `oss_provider` a table in `local_providers`.
+fn check_references(cfg: &ConfigToml, issues: &mut Vec<ConfigIssue>) {
+    if let Some(profile) = &cfg.profile
+        && !cfg.profiles.contains_key(profile)
+    {
+        let fix = match one_of(cfg.profiles.keys()) {
+            Some(names) => format!("add a `[profiles.{profile}]` table, or use one of {names}"),
+            None => format!("add a `[profiles.{profile}]` table, or remove `profile`"),
+        };
+        issues.push(ConfigIssue::error(
+            Some("profile".to_string()),
+            format!("profile `{profile}` is not defined"),
+            Some(fix),
+        ));
+    }
+
+    let mut providers: BTreeSet<String> = built_in_model_providers().into_keys().collect();
+    providers.extend(cfg.model_providers.keys().cloned());
+    providers.extend(cfg.local_providers.keys().cloned());
+    let mut references: Vec<(String, &String)> = cfg
+        .model_provider
+        .iter()
+        .map(|p| ("model_provider".to_string(), p))
+        .collect();
+    let mut profiles: Vec<_> = cfg.profiles.iter().collect();
+    profiles.sort_by_key(|(name, _)| name.as_str());
+    for (name, profile) in profiles {
+        if let Some(provider) = &profile.model_provider {
+            references.push((format!("profiles.{name}.model_provider"), provider));
+        }
+    }
+    for (key, provider) in references {
+        if providers.contains(provider) {
+            continue;
+        }
+        let fix = format!(
+            "add a `[model_providers.{provider}]` table, or use one of {}",
+            one_of(providers.iter()).unwrap_or_default()
+        );
+        issues.push(ConfigIssue::error(
+            Some(key),
+            format!("model provider `{provider}` is not defined"),
+            Some(fix),
+        ));
+    }
+
+    if let Some(provider) = &cfg.oss_provider
+        && !cfg.local_providers.contains_key(provider)
+    {
+        let fix = match one_of(cfg.local_providers.keys()) {
+            Some(names) => {
+                format!("add a `[local_providers.{provider}]` table, or use one of {names}")
+            }
+            None => format!("add a `[local_providers.{provider}]` table"),
+        };
+        issues.push(ConfigIssue::error(
+            Some("oss_provider".to_string()),
+            format!("local provider `{provider}` is not defined"),
+            Some(fix),
+        ));
+    }
+}
+
+/// Each MCP server's `command` must be an executable Codex can find.
+fn check_mcp_servers(cfg: &ConfigToml, issues: &mut Vec<ConfigIssue>) {
+    let mut servers: Vec<_> = cfg.mcp_servers.iter().collect();
+    servers.sort_by_key(|(name, _)| name.as_str());
+    for (name, server) in servers {
+        if which::which(&server.command).is_ok() {
+            continue;
+        }
+        issues.push(ConfigIssue::error(
+            Some(format!("mcp_servers.{name}.command")),
+            format!(
+                "`{}` was not found, so the `{name}` MCP server cannot start",
+                server.command
+            ),
+            Some("install it, or set `command` to its absolute path".to_string()),
+        ));
+    }
+}
+
+/// `names` as "`a`, `b`", or `None` if there are none.
+fn one_of<'a>(names: impl Iterator<Item = &'a String>) -> Option<String> {
+    let mut names: Vec<_> = names.map(|n| format!("`{n}`")).collect();
+    names.sort();
+    (!names.is_empty()).then(|| names.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn validate(toml: &str) -> Vec<ConfigIssue> {
+        let codex_home = TempDir::new().expect("tempdir");
+        std::fs::write(codex_home.path().join(CONFIG_TOML_FILE), toml).expect("write config");
+        validate_config(codex_home.path())
+    }
+
+    #[test]
+    fn reports_unknown_keys_and_dangling_references() {
+        let issues = validate(
+            r#"
+profile = "fats"
+modle = "o3"
+
+[profiles.fast]
+model_provider = "azure"
+
+[tui]
+colour = "red"
+"#,
+        );
+
+        assert_eq!(
+            issues
+                .iter()
+                .map(|i| (i.severity, i.to_string()))
+                .collect::<Vec<_>>(),
+            vec![
+                (
+                    IssueSeverity::Warning,
+                    "`modle`: unknown key, ignored".to_string()
+                ),
+                (
+                    IssueSeverity::Warning,
+                    "`tui.colour`: unknown key, ignored".to_string()
+                ),
+                (
+                    IssueSeverity::Error,
+                    "`profile`: profile `fats` is not defined".to_string()
+                ),
+                (
+                    IssueSeverity::Error,
+                    "`profiles.fast.model_provider`: model provider `azure` is not defined"
+                        .to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            issues[2].fix.as_deref(),
+            Some("add a `[profiles.fats]` table, or use one of `fast`")
+        );
+    }
+
+    #[test]
+    fn reports_type_mismatch_with_its_key() {
+        let issues = validate("hide_agent_reasoning = \"yes\"\n");
+
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, IssueSeverity::Error);
+        assert_eq!(issues[0].key.as_deref(), Some("hide_agent_reasoning"));
+    }
+
+    #[test]
+    fn reports_missing_mcp_server_command() {
+        let issues =
+            validate("[mcp_servers.docs]\ncommand = \"definitely-not-an-installed-mcp-server\"\n");
+
+        assert_eq!(
+            issues.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec![
+                "`mcp_servers.docs.command`: `definitely-not-an-installed-mcp-server` was not found, so the `docs` MCP server cannot start"
+            ]
+        );
+    }
+
+    #[test]
+    fn local_providers_can_be_referenced() {
+        let config = r#"
+model_provider = "lmstudio"
+oss_provider = "lmstudio"
+
+[local_providers.lmstudio]
+base_url = "http://localhost:1234/v1"
+"#;
+        assert_eq!(validate(config), Vec::new());
+
+        let issues = validate("oss_provider = \"vllm\"\n");
+        assert_eq!(
+            issues.iter().map(ToString::to_string).collect::<Vec<_>>(),
+            vec!["`oss_provider`: local provider `vllm` is not defined"]
+        );
+    }
+
+    #[test]
+    fn valid_config_has_no_issues() {
+        assert_eq!(validate("model = \"o3\"\n"), Vec::new());
+    }
+}
@@ -0,0 +1,545 @@
+//! Container execution backend: runs shell tool commands inside a Docker or
+//! Podman container with the workspace bind-mounted at the same path, so
+//! absolute paths in commands and tool output mean the same thing on both
+//! sides.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::LazyLock;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncReadExt;
+use tokio::io::BufReader;
+use tokio::process::Child;
+use tokio::process::Command;
+use uuid::Uuid;
+
+use crate::config_types::ContainerConfig;
+use crate::config_types::ContainerPullPolicy;
+use crate::config_types::ExecLimits;
+use crate::error::CodexErr;
+use crate::error::Result;
+use crate::exec::StdoutStream;
+use crate::protocol::ContainerImagePullEvent;
+use crate::protocol::ContainerImagePullStatus;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::SandboxPolicy;
+use crate::spawn::SpawnChildRequest;
+use crate::spawn::StdioPolicy;
+use crate::spawn::spawn_child_async;
+
+/// Host-specific variables that would break the container's own environment
+/// if forwarded.
+const HOST_ONLY_ENV_VARS: &[&str] = &[
+    "HOME", "LOGNAME", "PATH", "PWD", "SHELL", "TMPDIR", "USER", "OLDPWD",
+];
+
+/// Upper bound on a single image pull; large images on slow links can take a
+/// while, but a stalled registry must not block the turn forever.
+const IMAGE_PULL_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+
+/// Images (keyed by runtime and reference) that have already been checked or
+/// pulled by this process.
+static PREPARED_IMAGES: LazyLock<Mutex<HashSet<String>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new()));
+
+/// Spawn a shell tool command inside the configured container image, pulling
+/// the image first if the pull policy requires it. Pull progress is reported
+/// as [`EventMsg::ContainerImagePull`] events on `stdout_stream`. The runtime
+/// client's output is always piped back like any other shell tool call.
+///
+/// The container is started as `name` so that it can be stopped with
+/// [`kill_container`]: killing the runtime client alone leaves it running.
+#[allow(clippy::too_many_arguments)]
+pub async fn spawn_command_under_container(
+    command: Vec<String>,
+    sandbox_policy: &SandboxPolicy,
+    container: &ContainerConfig,
+    name: &str,
+    cwd: PathBuf,
+    env: HashMap<String, String>,
+    limits: &ExecLimits,
+    stdout_stream: Option<&StdoutStream>,
+) -> Result<Child> {
+    ensure_image(container, stdout_stream).await?;
+
+    let args =
+        create_container_command_args(command, sandbox_policy, container, name, &cwd, &env, limits);
+    let arg0 = None;
+    // CPU and memory limits are passed to the container rather than applied
+    // to the runtime client.
+    let child = spawn_child_async(SpawnChildRequest {
+        program: PathBuf::from(container.runtime.program()),
+        args,
+        arg0,
+        cwd,
+        sandbox_policy,
+        stdio_policy: StdioPolicy::RedirectForShellTool,
+        env,
+        limits: &ExecLimits::default(),
+    })
+    .await?;
+    Ok(child)
+}
+
+/// Returns a container name for the shell tool call `call_id`, falling back
+/// to a random id when there is none or it is not a valid container name.
+pub fn container_name(call_id: Option<&str>) -> String {
+    match call_id {
+        Some(call_id)
+            if !call_id.is_empty()
+                && call_id
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-')) =>
+        {
+            format!("codex-{call_id}")
+        }
+        _ => format!("codex-{}", Uuid::new_v4()),
+    }
+}
+
+/// Stops the container started as `name`. Errors are ignored: the container
+/// may already have exited and been removed.
+pub async fn kill_container(container: &ContainerConfig, name: &str) {
+    let _ = Command::new(container.runtime.program())
+        .args(["kill", name])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await;
+}
+
+/// Whether the container started as `name` was killed by its cgroup's OOM
+/// killer. Only meaningful before [`remove_container`].
+pub async fn container_oom_killed(container: &ContainerConfig, name: &str) -> bool {
+    Command::new(container.runtime.program())
+        .args(["inspect", "--format", "{{.State.OOMKilled}}", name])
+        .stdin(Stdio::null())
+        .stderr(Stdio::null())
+        .output()
+        .await
+        .is_ok_and(|output| String::from_utf8_lossy(&output.stdout).trim() == "true")
+}
+
+/// Removes the container started as `name`, stopping it first if needed.
+/// Errors are ignored: the container may already be gone.
+pub async fn remove_container(container: &ContainerConfig, name: &str) {
+    let _ = Command::new(container.runtime.program())
+        .args(["rm", "--force", name])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await;
+}
+
+/// Builds the `<runtime> run ...` arguments that reproduce `sandbox_policy`
+/// with bind mounts and network isolation, and `limits` with the runtime's
+/// own resource controls.
+fn create_container_command_args(
+    command: Vec<String>,
+    sandbox_policy: &SandboxPolicy,
+    container: &ContainerConfig,
+    name: &str,
+    cwd: &Path,
+    env: &HashMap<String, String>,
+    limits: &ExecLimits,
+) -> Vec<String> {
+    let mut args: Vec<String> = vec!["run".to_string()];
+    // With a memory limit the container is kept after it exits, so that
+    // whether it was OOM-killed can be checked before it is removed.
+    if limits.max_memory_bytes.is_none() {
+        args.push("--rm".to_string());
+    }
+    args.extend([
+        "--init".to_string(),
+        "--name".to_string(),
+        name.to_string(),
+        "--workdir".to_string(),
+        cwd.to_string_lossy().to_string(),
+    ]);
+
+    if let Some(user) = container_user(container) {
+        args.push("--user".to_string());
+        args.push(user);
+    }
+
+    if !sandbox_policy.has_full_network_access() {
+        args.push("--network".to_string());
+        args.push("none".to_string());
+    }
+
+    let writable_roots = sandbox_policy.get_writable_roots_with_cwd(cwd);
+    if !writable_roots.iter().any(|wr| wr.root == cwd) {
+        // The workspace is always visible; it is writable only under full
+        // access.
+        let mode = if sandbox_policy.has_full_disk_write_access() {
+            ""
+        } else {
+            ":ro"
+        };
+        args.push("--volume".to_string());
+        args.push(format!("{0}:{0}{mode}", cwd.to_string_lossy()));
+    }
+    for wr in &writable_roots {
+        args.push("--volume".to_string());
+        args.push(format!("{0}:{0}", wr.root.to_string_lossy()));
+        for ro in &wr.read_only_subpaths {
+            args.push("--volume".to_string());
+            args.push(format!("{0}:{0}:ro", ro.to_string_lossy()));
+        }
+    }
+
+    // Hide blocklisted paths: directories behind an empty tmpfs, files behind
+    // /dev/null.
+    for path in sandbox_policy.get_read_blocklist() {
+        let path = path.to_string_lossy();
+        if Path::new(path.as_ref()).is_dir() {
+            args.push("--tmpfs".to_string());
+            args.push(path.to_string());
+        } else {
+            args.push("--volume".to_string());
+            args.push(format!("/dev/null:{path}:ro"));
+        }
+    }
+
+    // Forward variables by name so their values come from the runtime
+    // client's environment rather than appearing on its command line.
+    let mut env_names: Vec<&String> = env
+        .keys()
+        .filter(|name| !HOST_ONLY_ENV_VARS.contains(&name.as_str()))
+        .collect();
+    env_names.sort();
+    for name in env_names {
+        args.push("--env".to_string());
+        args.push(name.clone());
+    }
+
+    if let Some(seconds) = limits.cpu_time_seconds {
+        args.push("--ulimit".to_string());
+        args.push(format!("cpu={seconds}:{}", seconds.saturating_add(1)));
+    }
+    // Unlike `RLIMIT_AS` on the host, this caps the container's total
+    // resident memory through its cgroup.
+    if let Some(bytes) = limits.max_memory_bytes {
+        args.push("--memory".to_string());
+        args.push(bytes.to_string());
+    }
+
+    args.extend(container.extra_args.iter().cloned());
+    args.push(container.image.clone());
+    args.extend(command);
+    args
+}
+
+/// The user commands run as: `[container] user`, or else the host user.
+fn container_user(container: &ContainerConfig) -> Option<String> {
+    if let Some(user) = &container.user {
+        return Some(user.clone());
+    }
+    #[cfg(unix)]
+    {
+        // SAFETY: getuid and getgid always succeed.
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        Some(format!("{uid}:{gid}"))
+    }
+    #[cfg(not(unix))]
+    {
+        None
+    }
+}
+
+async fn ensure_image(
+    container: &ContainerConfig,
+    stdout_stream: Option<&StdoutStream>,
+) -> Result<()> {
+    let program = container.runtime.program();
+    let image = &container.image;
+    let key = format!("{program} {image}");
+    if PREPARED_IMAGES
+        .lock()
+        .is_ok_and(|prepared| prepared.contains(&key))
+    {
+        return Ok(());
+    }
+
+    let needs_pull = match container.pull {
+        ContainerPullPolicy::Always => true,
+        ContainerPullPolicy::Missing | ContainerPullPolicy::Never => {
+            !image_exists(program, image).await?
+        }
+    };
+    if needs_pull {
+        if container.pull == ContainerPullPolicy::Never {
+            return Err(CodexErr::ContainerImageUnavailable(
+                image.clone(),
+                "image is not present locally and pull policy is `never`".to_string(),
+            ));
+        }
+        pull_image(program, image, stdout_stream).await?;
+    }
+
+    if let Ok(mut prepared) = PREPARED_IMAGES.lock() {
+        prepared.insert(key);
+    }
+    Ok(())
+}
+
+async fn image_exists(program: &str, image: &str) -> Result<bool> {
+    let status = Command::new(program)
+        .args(["image", "inspect", image])
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .await?;
+    Ok(status.success())
+}
+
+async fn pull_image(
+    program: &str,
+    image: &str,
+    stdout_stream: Option<&StdoutStream>,
+) -> Result<()> {
+    send_pull_event(stdout_stream, image, ContainerImagePullStatus::Started).await;
+
+    let mut child = Command::new(program)
+        .args(["pull", image])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let stderr_handle = child.stderr.take().map(|mut stderr| {
+        tokio::spawn(async move {
+            let mut buf = String::new();
+            let _ = stderr.read_to_string(&mut buf).await;
+            buf
+        })
+    });
+
+    let stdout = child.stdout.take();
+    let pull = async {
+        if let Some(stdout) = stdout {
+            let mut lines = BufReader::new(stdout).lines();
+            while let Some(line) = lines.next_line().await? {
+                let line = line.trim();
+                if line.is_empty() {
+                    continue;
+                }
+                send_pull_event(
+                    stdout_stream,
+                    image,
+                    ContainerImagePullStatus::Progress {
+                        message: line.to_string(),
+                    },
+                )
+                .await;
+            }
+        }
+        child.wait().await
+    };
+    // On timeout or interrupt the early return below drops `child`, which
+    // kills the runtime client.
+    let outcome = tokio::select! {
+        result = tokio::time::timeout(IMAGE_PULL_TIMEOUT, pull) => match result {
+            Ok(status) => Ok(status?),
+            Err(_) => Err(format!(
+                "pull timed out after {} seconds",
+                IMAGE_PULL_TIMEOUT.as_secs()
+            )),
+        },
+        _ = tokio::signal::ctrl_c() => Err("pull was interrupted".to_string()),
+    };
+    let status = match outcome {
+        Ok(status) => status,
+        Err(message) => {
+            send_pull_event(
+                stdout_stream,
+                image,
+                ContainerImagePullStatus::Failed {
+                    message: message.clone(),
+                },
+            )
+            .await;
+            return Err(CodexErr::ContainerImageUnavailable(
+                image.to_string(),
+                message,
+            ));
+        }
+    };
+    let stderr = match stderr_handle {
+        Some(handle) => handle.await.unwrap_or_default(),
+        None => String::new(),
+    };
+    if status.success() {
+        send_pull_event(stdout_stream, image, ContainerImagePullStatus::Completed).await;
+        Ok(())
+    } else {
+        let message = stderr.trim().to_string();
+        send_pull_event(
+            stdout_stream,
+            image,
+            ContainerImagePullStatus::Failed {
+                message: message.clone(),
+            },
+        )
+        .await;
+        Err(CodexErr::ContainerImageUnavailable(
+            image.to_string(),
+            message,
+        ))
+    }
+}
+
+async fn send_pull_event(
+    stdout_stream: Option<&StdoutStream>,
+    image: &str,
+    status: ContainerImagePullStatus,
+) {
+    let Some(stream) = stdout_stream else {
+        return;
+    };
+    let event = Event {
+        id: stream.sub_id.clone(),
+        msg: EventMsg::ContainerImagePull(ContainerImagePullEvent {
+            call_id: stream.call_id.clone(),
+            image: image.to_string(),
+            status,
+        }),
+    };
+    let _ = stream.tx_event.send(event).await;
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn args_for(policy: &SandboxPolicy, cwd: &Path) -> Vec<String> {
+        let env = HashMap::from([
+            ("PATH".to_string(), "/host/bin".to_string()),
+            ("RUST_LOG".to_string(), "info".to_string()),
+        ]);
+        create_container_command_args(
+            vec!["ls".to_string(), "-la".to_string()],
+            policy,
+            &ContainerConfig {
+                user: Some("1000:1000".to_string()),
+                ..Default::default()
+            },
+            "codex-call1",
+            cwd,
+            &env,
+            &ExecLimits::default(),
+        )
+    }
+
+    #[test]
+    fn read_only_policy_mounts_workspace_read_only_without_network() {
+        let tmp = TempDir::new().unwrap();
+        let cwd = tmp.path();
+        let cwd_str = cwd.to_string_lossy();
+
+        let args = args_for(&SandboxPolicy::new_read_only_policy(), cwd);
+
+        assert_eq!(
+            args,
+            vec![
+                "run".to_string(),
+                "--rm".to_string(),
+                "--init".to_string(),
+                "--name".to_string(),
+                "codex-call1".to_string(),
+                "--workdir".to_string(),
+                cwd_str.to_string(),
+                "--user".to_string(),
+                "1000:1000".to_string(),
+                "--network".to_string(),
+                "none".to_string(),
+                "--volume".to_string(),
+                format!("{cwd_str}:{cwd_str}:ro"),
+                "--env".to_string(),
+                "RUST_LOG".to_string(),
+                "ubuntu:24.04".to_string(),
+                "ls".to_string(),
+                "-la".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn workspace_write_policy_mounts_writable_roots_and_protects_git() {
+        let tmp = TempDir::new().unwrap();
+        let cwd = tmp.path();
+        std::fs::create_dir(cwd.join(".git")).unwrap();
+        let cwd_str = cwd.to_string_lossy();
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: true,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+            read_blocklist: vec![],
+        };
+
+        let args = args_for(&policy, cwd);
+
+        assert!(!args.contains(&"--network".to_string()));
+        assert!(args.contains(&format!("{cwd_str}:{cwd_str}")));
+        assert!(args.contains(&format!("{cwd_str}/.git:{cwd_str}/.git:ro")));
+    }
+
+    #[test]
+    fn memory_limited_containers_are_kept_until_checked() {
+        let tmp = TempDir::new().unwrap();
+        let args = create_container_command_args(
+            vec!["ls".to_string()],
+            &SandboxPolicy::new_read_only_policy(),
+            &ContainerConfig::default(),
+            "codex-call1",
+            tmp.path(),
+            &HashMap::new(),
+            &ExecLimits {
+                max_memory_bytes: Some(1 << 30),
+                ..Default::default()
+            },
+        );
+
+        assert!(!args.contains(&"--rm".to_string()));
+        assert!(
+            args.windows(2)
+                .any(|pair| pair == ["--memory".to_string(), (1u64 << 30).to_string()])
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn commands_run_as_the_host_user_by_default() {
+        let (uid, gid) = unsafe { (libc::getuid(), libc::getgid()) };
+        assert_eq!(
+            container_user(&ContainerConfig::default()),
+            Some(format!("{uid}:{gid}"))
+        );
+    }
+
+    #[test]
+    fn container_name_uses_call_id_when_valid() {
+        assert_eq!(container_name(Some("call_abc-1.2")), "codex-call_abc-1.2");
+
+        for call_id in [None, Some(""), Some("call/1"), Some("call 1")] {
+            let name = container_name(call_id);
+            assert!(name.starts_with("codex-"), "{name}");
+            assert!(Uuid::parse_str(&name["codex-".len()..]).is_ok(), "{name}");
+        }
+    }
+}
@@ -110,6 +110,22 @@ impl ConversationManager {
             .ok_or_else(|| CodexErr::ConversationNotFound(conversation_id))
     }
 
+    /// Ids of every conversation currently held in memory.
+    pub async fn list_conversations(&self) -> Vec<Uuid> {
+        let mut ids: Vec<Uuid> = self.conversations.read().await.keys().copied().collect();
+        ids.sort();
+        ids
+    }
+
+    /// Stops tracking a conversation. The conversation itself keeps running
+    /// until it is shut down or the last handle to it is dropped.
+    pub async fn remove_conversation(
+        &self,
+        conversation_id: Uuid,
+    ) -> Option<Arc<CodexConversation>> {
+        self.conversations.write().await.remove(&conversation_id)
+    }
+
     /// Fork an existing conversation by dropping the last `drop_last_messages`
     /// user/assistant messages from its transcript and starting a new
     /// conversation with identical configuration (unless overridden by the
@@ -0,0 +1,179 @@
+//! Optional at-rest encryption for the rollout and message history files.
+//!
+//! Both files are append-only JSONL, so each line is encrypted on its own with
+//! ChaCha20-Poly1305 and a fresh random nonce, and written as
+//! `codex-enc-v1:<base64(nonce || ciphertext)>`. Plaintext lines are passed
+//! through when reading, so a file may mix lines written before and after
+//! encryption was turned on.
+//!
+//! The key is generated on first use and kept in the OS keychain (Keychain on
+//! macOS, Credential Manager on Windows, the Secret Service on Linux), never
+//! on disk next to the files it protects.
+
+use std::borrow::Cow;
+use std::io;
+use std::sync::OnceLock;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use chacha20poly1305::ChaCha20Poly1305;
+use chacha20poly1305::Key;
+use chacha20poly1305::KeyInit;
+use chacha20poly1305::Nonce;
+use chacha20poly1305::aead::Aead;
+use chacha20poly1305::aead::AeadCore;
+use chacha20poly1305::aead::OsRng;
+
+const LINE_PREFIX: &str = "codex-enc-v1:";
+const NONCE_LEN: usize = 12;
+const KEYCHAIN_SERVICE: &str = "codex";
+const KEYCHAIN_ACCOUNT: &str = "at-rest-encryption-key";
+
+/// The key only has to be read from the keychain once per process.
+static KEYCHAIN_CIPHER: OnceLock<LineCipher> = OnceLock::new();
+
+/// Encrypts and decrypts single lines of a JSONL file.
+#[derive(Clone)]
+pub struct LineCipher {
+    cipher: ChaCha20Poly1305,
+}
+
+impl std::fmt::Debug for LineCipher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LineCipher").finish_non_exhaustive()
+    }
+}
+
+impl LineCipher {
+    pub fn new(key: &[u8; 32]) -> Self {
+        Self {
+            cipher: ChaCha20Poly1305::new(Key::from_slice(key)),
+        }
+    }
+
+    /// Load the key from the OS keychain, creating and storing one if there
+    /// is none yet.
+    pub fn from_keychain() -> io::Result<Self> {
+        if let Some(cipher) = KEYCHAIN_CIPHER.get() {
+            return Ok(cipher.clone());
+        }
+        let entry =
+            keyring::Entry::new(KEYCHAIN_SERVICE, KEYCHAIN_ACCOUNT).map_err(keychain_error)?;
+        let key = match entry.get_password() {
+            Ok(encoded) => decode_key(&encoded)?,
+            Err(keyring::Error::NoEntry) => {
+                let key: [u8; 32] = ChaCha20Poly1305::generate_key(&mut OsRng).into();
+                entry
+                    .set_password(&BASE64.encode(key))
+                    .map_err(keychain_error)?;
+                key
+            }
+            Err(e) => return Err(keychain_error(e)),
+        };
+        Ok(KEYCHAIN_CIPHER.get_or_init(|| Self::new(&key)).clone())
+    }
+
+    /// Encrypt one line. `line` must not contain the trailing newline.
+    pub fn encrypt_line(&self, line: &str) -> io::Result<String> {
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, line.as_bytes())
+            .map_err(|_| io::Error::other("failed to encrypt line"))?;
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+        Ok(format!("{LINE_PREFIX}{}", BASE64.encode(payload)))
+    }
+
+    /// Decrypt a line produced by `encrypt_line`.
+    pub fn decrypt_line(&self, line: &str) -> io::Result<String> {
+        let encoded = line
+            .strip_prefix(LINE_PREFIX)
+            .ok_or_else(|| invalid_data("line is not encrypted"))?;
+        let payload = BASE64
+            .decode(encoded.trim_end())
+            .map_err(|e| invalid_data(format!("invalid encrypted line: {e}")))?;
+        if payload.len() < NONCE_LEN {
+            return Err(invalid_data("encrypted line is too short"));
+        }
+        let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+        let plaintext = self
+            .cipher
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| {
+                invalid_data("failed to decrypt line; was it written with another key?")
+            })?;
+        String::from_utf8(plaintext).map_err(|e| invalid_data(e.to_string()))
+    }
+}
+
+pub fn is_encrypted_line(line: &str) -> bool {
+    line.starts_with(LINE_PREFIX)
+}
+
+/// Return `line` as plaintext, decrypting it if needed. The keychain is only
+/// consulted, and `cipher` filled in, once an encrypted line is seen, so files
+/// without encrypted lines can be read on machines without a keychain.
+pub fn decode_line<'a>(line: &'a str, cipher: &mut Option<LineCipher>) -> io::Result<Cow<'a, str>> {
+    if !is_encrypted_line(line) {
+        return Ok(Cow::Borrowed(line));
+    }
+    let cipher = match cipher {
+        Some(cipher) => cipher,
+        None => cipher.insert(LineCipher::from_keychain()?),
+    };
+    cipher.decrypt_line(line).map(Cow::Owned)
+}
+
+fn decode_key(encoded: &str) -> io::Result<[u8; 32]> {
+    BASE64
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+        .ok_or_else(|| invalid_data("the encryption key in the keychain is malformed"))
+}
+
+fn keychain_error(e: keyring::Error) -> io::Error {
+    io::Error::other(format!(
+        "failed to access the encryption key in the OS keychain: {e}"
+    ))
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn round_trips_and_uses_fresh_nonces() {
+        let cipher = LineCipher::new(&[7; 32]);
+        let line = r#"{"type":"message","role":"user"}"#;
+
+        let first = cipher.encrypt_line(line).unwrap();
+        let second = cipher.encrypt_line(line).unwrap();
+
+        assert!(is_encrypted_line(&first));
+        assert!(!first.contains("message"));
+        assert_ne!(first, second);
+        assert_eq!(cipher.decrypt_line(&first).unwrap(), line);
+    }
+
+    #[test]
+    fn rejects_lines_from_another_key() {
+        let line = LineCipher::new(&[1; 32]).encrypt_line("secret").unwrap();
+        let err = LineCipher::new(&[2; 32]).decrypt_line(&line).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn decode_passes_plaintext_through_without_a_key() {
+        let mut cipher = None;
+        let decoded = decode_line(r#"{"ts":1}"#, &mut cipher).unwrap();
+        assert_eq!(decoded, r#"{"ts":1}"#);
+        assert!(cipher.is_none());
+    }
+}
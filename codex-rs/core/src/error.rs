@@ -1,3 +1,5 @@
+use codex_protocol::protocol::ErrorKind;
+use codex_protocol::protocol::SandboxDenial;
 use reqwest::StatusCode;
 use serde_json;
 use std::io;
@@ -10,9 +12,10 @@ pub type Result<T> = std::result::Result<T, CodexErr>;
 
 #[derive(Error, Debug)]
 pub enum SandboxErr {
-    /// Error from sandbox execution
+    /// Error from sandbox execution, with what the sandbox recorded refusing
+    /// the command, if anything.
     #[error("sandbox denied exec error, exit code: {0}, stdout: {1}, stderr: {2}")]
-    Denied(i32, String, String),
+    Denied(i32, String, String, Vec<SandboxDenial>),
 
     /// Error from linux seccomp filter setup
     #[cfg(target_os = "linux")]
@@ -37,6 +40,35 @@ pub enum SandboxErr {
     LandlockRestrict,
 }
 
+impl SandboxErr {
+    /// Whether the sandbox itself could not be set up, as opposed to the
+    /// command failing or being denied inside it.
+    pub fn is_sandbox_unavailable(&self) -> bool {
+        match self {
+            SandboxErr::LandlockRestrict => true,
+            #[cfg(target_os = "linux")]
+            SandboxErr::SeccompInstall(_) | SandboxErr::SeccompBackend(_) => true,
+            _ => false,
+        }
+    }
+
+    /// What the sandbox recorded refusing the command, e.g.
+    /// `blocked: write to /etc/hosts`, or `None` if it recorded nothing.
+    pub fn blocked_summary(&self) -> Option<String> {
+        match self {
+            SandboxErr::Denied(_, _, _, denials) if !denials.is_empty() => Some(format!(
+                "blocked: {}",
+                denials
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum CodexErr {
     /// Returned by ResponsesClient when the SSE stream disconnects or errors out **after** the HTTP
@@ -98,6 +130,13 @@ pub enum CodexErr {
     #[error("codex-linux-sandbox was required but not provided")]
     LandlockSandboxExecutableNotProvided,
 
+    #[error("container execution backend was selected but no container config was provided")]
+    ContainerConfigNotProvided,
+
+    /// The container image could not be found or pulled.
+    #[error("container image `{0}` is unavailable: {1}")]
+    ContainerImageUnavailable(String, String),
+
     // -----------------------------------------------------------------
     // Automatic conversions for common external error types
     // -----------------------------------------------------------------
@@ -219,11 +258,40 @@ impl CodexErr {
     pub fn downcast_ref<T: std::any::Any>(&self) -> Option<&T> {
         (self as &dyn std::any::Any).downcast_ref::<T>()
     }
+
+    /// Classify the error for frontends; see [`ErrorKind`].
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            CodexErr::UnexpectedStatus(status, _)
+                if *status == StatusCode::UNAUTHORIZED || *status == StatusCode::FORBIDDEN =>
+            {
+                ErrorKind::Auth
+            }
+            CodexErr::EnvVar(_) => ErrorKind::Auth,
+            CodexErr::UsageLimitReached(_) | CodexErr::UsageNotIncluded => {
+                ErrorKind::BudgetExceeded
+            }
+            CodexErr::Stream(..)
+            | CodexErr::UnexpectedStatus(..)
+            | CodexErr::InternalServerError
+            | CodexErr::RetryLimit(_)
+            | CodexErr::Reqwest(_) => ErrorKind::Model,
+            CodexErr::LandlockSandboxExecutableNotProvided
+            | CodexErr::ContainerConfigNotProvided
+            | CodexErr::ContainerImageUnavailable(..) => ErrorKind::SandboxUnavailable,
+            CodexErr::Sandbox(e) if e.is_sandbox_unavailable() => ErrorKind::SandboxUnavailable,
+            #[cfg(target_os = "linux")]
+            CodexErr::LandlockRuleset(_) | CodexErr::LandlockPathFd(_) => {
+                ErrorKind::SandboxUnavailable
+            }
+            _ => ErrorKind::Other,
+        }
+    }
 }
 
 pub fn get_error_message_ui(e: &CodexErr) -> String {
     match e {
-        CodexErr::Sandbox(SandboxErr::Denied(_, _, stderr)) => stderr.to_string(),
+        CodexErr::Sandbox(SandboxErr::Denied(_, _, stderr, _)) => stderr.to_string(),
         // Timeouts are not sandbox errors from a UX perspective; present them plainly
         CodexErr::Sandbox(SandboxErr::Timeout) => "error: command timed out".to_string(),
         _ => e.to_string(),
@@ -317,4 +385,23 @@ mod tests {
             "You've hit your usage limit. Try again in less than a minute."
         );
     }
+
+    #[test]
+    fn kind_classifies_auth_budget_sandbox_and_model_errors() {
+        assert_eq!(
+            CodexErr::UnexpectedStatus(StatusCode::UNAUTHORIZED, String::new()).kind(),
+            ErrorKind::Auth
+        );
+        assert_eq!(CodexErr::UsageNotIncluded.kind(), ErrorKind::BudgetExceeded);
+        assert_eq!(
+            CodexErr::LandlockSandboxExecutableNotProvided.kind(),
+            ErrorKind::SandboxUnavailable
+        );
+        assert_eq!(CodexErr::InternalServerError.kind(), ErrorKind::Model);
+        assert_eq!(
+            CodexErr::UnexpectedStatus(StatusCode::BAD_REQUEST, String::new()).kind(),
+            ErrorKind::Model
+        );
+        assert_eq!(CodexErr::InternalAgentDied.kind(), ErrorKind::Other);
+    }
 }
@@ -5,6 +5,9 @@ use std::collections::HashMap;
 use std::io;
 use std::path::PathBuf;
 use std::process::ExitStatus;
+use std::sync::Arc;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 use std::time::Instant;
 
@@ -13,19 +16,34 @@ use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt;
 use tokio::io::BufReader;
 use tokio::process::Child;
-
+use tokio_util::sync::CancellationToken;
+use tracing::instrument;
+
+use crate::config_types::ContainerConfig;
+use crate::config_types::ExecLimits;
+use crate::container::container_name;
+use crate::container::container_oom_killed;
+use crate::container::kill_container;
+use crate::container::remove_container;
+use crate::container::spawn_command_under_container;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::SandboxErr;
+use crate::landlock::read_sandbox_denials;
 use crate::landlock::spawn_command_under_linux_sandbox;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
 use crate::protocol::ExecCommandOutputDeltaEvent;
+use crate::protocol::ExecKilledReason;
 use crate::protocol::ExecOutputStream;
+use crate::protocol::SandboxDenial;
 use crate::protocol::SandboxPolicy;
+use crate::seatbelt::SeatbeltExtensions;
 use crate::seatbelt::spawn_command_under_seatbelt;
+use crate::spawn::SpawnChildRequest;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
+use crate::windows_sandbox::spawn_command_under_windows_sandbox;
 use serde_bytes::ByteBuf;
 
 const DEFAULT_TIMEOUT_MS: u64 = 10_000;
@@ -33,6 +51,7 @@ const DEFAULT_TIMEOUT_MS: u64 = 10_000;
 // Hardcode these since it does not seem worth including the libc crate just
 // for these.
 const SIGKILL_CODE: i32 = 9;
+const SIGXCPU_CODE: i32 = 24;
 const TIMEOUT_CODE: i32 = 64;
 const EXIT_CODE_SIGNAL_BASE: i32 = 128; // conventional shell: 128 + signal
 
@@ -40,6 +59,16 @@ const EXIT_CODE_SIGNAL_BASE: i32 = 128; // conventional shell: 128 + signal
 const READ_CHUNK_SIZE: usize = 8192; // bytes per read
 const AGGREGATE_BUFFER_INITIAL_CAPACITY: usize = 8 * 1024; // 8 KiB
 
+/// Output is coalesced into [`EventMsg::ExecCommandOutputDelta`] events of up
+/// to this many bytes, sent at most every [`OUTPUT_DELTA_INTERVAL`] unless
+/// they fill up first.
+const OUTPUT_DELTA_MAX_BYTES: usize = 16 * 1024; // 16 KiB
+const OUTPUT_DELTA_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How long to keep reading output after killing a command. Anything that
+/// escaped its process group and still holds the pipes open is abandoned.
+const POST_KILL_READ_TIMEOUT: Duration = Duration::from_secs(2);
+
 #[derive(Debug, Clone)]
 pub struct ExecParams {
     pub command: Vec<String>,
@@ -54,6 +83,16 @@ impl ExecParams {
     pub fn timeout_duration(&self) -> Duration {
         Duration::from_millis(self.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS))
     }
+
+    /// Timeout after applying the configured wall-clock limit: it replaces
+    /// the default and caps any timeout the tool call requested.
+    pub fn timeout_duration_with_limits(&self, limits: &ExecLimits) -> Duration {
+        let timeout_ms = match (self.timeout_ms, limits.timeout_ms) {
+            (Some(requested), Some(limit)) => requested.min(limit),
+            (requested, limit) => requested.or(limit).unwrap_or(DEFAULT_TIMEOUT_MS),
+        };
+        Duration::from_millis(timeout_ms)
+    }
 }
 
 #[derive(Clone, Copy, Debug, PartialEq)]
@@ -65,6 +104,13 @@ pub enum SandboxType {
 
     /// Only available on Linux.
     LinuxSeccomp,
+
+    /// Only available on Windows.
+    WindowsRestrictedToken,
+
+    /// Runs the command inside a Docker or Podman container. Requires a
+    /// [`ContainerConfig`].
+    Container,
 }
 
 #[derive(Clone)]
@@ -74,35 +120,75 @@ pub struct StdoutStream {
     pub tx_event: Sender<Event>,
 }
 
+/// How a tool call's command is sandboxed and limited.
+#[derive(Debug, Clone, Copy)]
+pub struct ExecSandboxOptions<'a> {
+    pub sandbox_type: SandboxType,
+    /// Required for [`SandboxType::LinuxSeccomp`].
+    pub codex_linux_sandbox_exe: &'a Option<PathBuf>,
+    /// Required for [`SandboxType::Container`].
+    pub container_config: Option<&'a ContainerConfig>,
+    /// Only used by [`SandboxType::MacosSeatbelt`].
+    pub seatbelt_extensions: &'a SeatbeltExtensions,
+    pub limits: &'a ExecLimits,
+}
+
+#[instrument(
+    name = "exec",
+    skip_all,
+    fields(sandbox = ?options.sandbox_type, exit_code = tracing::field::Empty)
+)]
 pub async fn process_exec_tool_call(
     params: ExecParams,
-    sandbox_type: SandboxType,
     sandbox_policy: &SandboxPolicy,
-    codex_linux_sandbox_exe: &Option<PathBuf>,
+    options: ExecSandboxOptions<'_>,
     stdout_stream: Option<StdoutStream>,
 ) -> Result<ExecToolCallOutput> {
+    let ExecSandboxOptions {
+        sandbox_type,
+        codex_linux_sandbox_exe,
+        container_config,
+        seatbelt_extensions,
+        limits,
+    } = options;
     let start = Instant::now();
+    let timeout = params.timeout_duration_with_limits(limits);
+    let max_output_bytes = limits.max_output_bytes;
+    // Where the Linux sandbox records what it refused the command.
+    let mut denial_report: Option<tempfile::TempPath> = None;
+    // Whether the container runtime reports that the command's cgroup ran
+    // out of memory.
+    let mut oom_killed = false;
 
     let raw_output_result: std::result::Result<RawExecToolCallOutput, CodexErr> = match sandbox_type
     {
-        SandboxType::None => exec(params, sandbox_policy, stdout_stream.clone()).await,
+        SandboxType::None => {
+            exec(
+                params,
+                sandbox_policy,
+                timeout,
+                limits,
+                stdout_stream.clone(),
+            )
+            .await
+        }
         SandboxType::MacosSeatbelt => {
-            let timeout = params.timeout_duration();
             let ExecParams {
                 command, cwd, env, ..
             } = params;
             let child = spawn_command_under_seatbelt(
                 command,
                 sandbox_policy,
+                seatbelt_extensions,
                 cwd,
                 StdioPolicy::RedirectForShellTool,
                 env,
+                limits,
             )
             .await?;
-            consume_truncated_output(child, timeout, stdout_stream.clone()).await
+            consume_truncated_output(child, timeout, max_output_bytes, stdout_stream.clone()).await
         }
         SandboxType::LinuxSeccomp => {
-            let timeout = params.timeout_duration();
             let ExecParams {
                 command, cwd, env, ..
             } = params;
@@ -110,6 +196,11 @@ pub async fn process_exec_tool_call(
             let codex_linux_sandbox_exe = codex_linux_sandbox_exe
                 .as_ref()
                 .ok_or(CodexErr::LandlockSandboxExecutableNotProvided)?;
+            denial_report = tempfile::Builder::new()
+                .prefix("codex-sandbox-denials")
+                .tempfile()
+                .ok()
+                .map(tempfile::NamedTempFile::into_temp_path);
             let child = spawn_command_under_linux_sandbox(
                 codex_linux_sandbox_exe,
                 command,
@@ -117,34 +208,129 @@ pub async fn process_exec_tool_call(
                 cwd,
                 StdioPolicy::RedirectForShellTool,
                 env,
+                limits,
+                denial_report.as_deref(),
             )
             .await?;
 
-            consume_truncated_output(child, timeout, stdout_stream).await
+            consume_truncated_output(child, timeout, max_output_bytes, stdout_stream).await
+        }
+        SandboxType::WindowsRestrictedToken => {
+            let ExecParams {
+                command, cwd, env, ..
+            } = params;
+            let child = spawn_command_under_windows_sandbox(
+                command,
+                sandbox_policy,
+                cwd,
+                StdioPolicy::RedirectForShellTool,
+                env,
+                limits,
+            )
+            .await?;
+            consume_truncated_output(child, timeout, max_output_bytes, stdout_stream).await
+        }
+        SandboxType::Container => {
+            let ExecParams {
+                command, cwd, env, ..
+            } = params;
+
+            let container_config = container_config.ok_or(CodexErr::ContainerConfigNotProvided)?;
+            let name = container_name(stdout_stream.as_ref().map(|s| s.call_id.as_str()));
+            let child = spawn_command_under_container(
+                command,
+                sandbox_policy,
+                container_config,
+                &name,
+                cwd,
+                env,
+                limits,
+                stdout_stream.as_ref(),
+            )
+            .await?;
+            let output =
+                consume_truncated_output(child, timeout, max_output_bytes, stdout_stream).await;
+            // Killing the runtime client does not stop the container itself.
+            if output.as_ref().map_or(true, |output| output.killed) {
+                kill_container(container_config, &name).await;
+            }
+            // Such containers are not started with `--rm`.
+            if limits.max_memory_bytes.is_some() {
+                oom_killed = container_oom_killed(container_config, &name).await;
+                remove_container(container_config, &name).await;
+            }
+            output
         }
     };
     let duration = start.elapsed();
+    let sandbox_denials: Vec<SandboxDenial> = denial_report
+        .as_deref()
+        .map(read_sandbox_denials)
+        .unwrap_or_default();
     match raw_output_result {
         Ok(raw_output) => {
             let stdout = raw_output.stdout.from_utf8_lossy();
             let stderr = raw_output.stderr.from_utf8_lossy();
 
+            let mut killed_reason = raw_output.killed_reason;
             #[cfg(target_family = "unix")]
-            match raw_output.exit_status.signal() {
+            let signal = raw_output.exit_status.signal();
+            #[cfg(not(target_family = "unix"))]
+            let signal: Option<i32> = None;
+            let failed = signal.is_some() || raw_output.exit_status.code() != Some(0);
+            if killed_reason.is_none()
+                && !raw_output.killed
+                && failed
+                && limits.max_memory_bytes.is_some()
+                && (oom_killed || reports_out_of_memory(&stderr.text))
+            {
+                killed_reason = Some(ExecKilledReason::Memory);
+            }
+
+            #[cfg(target_family = "unix")]
+            match signal {
                 Some(TIMEOUT_CODE) => return Err(CodexErr::Sandbox(SandboxErr::Timeout)),
+                Some(_) if killed_reason.is_some() => {}
+                Some(SIGXCPU_CODE) if limits.cpu_time_seconds.is_some() => {
+                    killed_reason = Some(ExecKilledReason::CpuTime);
+                }
                 Some(signal) => {
                     return Err(CodexErr::Sandbox(SandboxErr::Signal(signal)));
                 }
                 None => {}
             }
 
-            let exit_code = raw_output.exit_status.code().unwrap_or(-1);
+            let exit_code = match (raw_output.exit_status.code(), killed_reason) {
+                (Some(code), _) => code,
+                #[cfg(target_family = "unix")]
+                (None, Some(_)) => raw_output
+                    .exit_status
+                    .signal()
+                    .map_or(-1, |signal| EXIT_CODE_SIGNAL_BASE + signal),
+                (None, _) => -1,
+            };
+            tracing::Span::current().record("exit_code", exit_code);
+
+            if killed_reason.is_some() {
+                return Ok(ExecToolCallOutput {
+                    exit_code,
+                    stdout,
+                    stderr,
+                    aggregated_output: raw_output.aggregated_output.from_utf8_lossy(),
+                    duration,
+                    killed_reason,
+                });
+            }
 
-            if exit_code != 0 && is_likely_sandbox_denied(sandbox_type, exit_code) {
+            if exit_code != 0
+                && (!sandbox_denials.is_empty()
+                    || is_likely_sandbox_denied(sandbox_type, sandbox_policy, exit_code))
+            {
                 return Err(CodexErr::Sandbox(SandboxErr::Denied(
                     exit_code,
                     stdout.text,
                     stderr.text,
+                    sandbox_denials,
                 )));
             }
 
@@ -154,6 +340,7 @@ pub async fn process_exec_tool_call(
                 stderr,
                 aggregated_output: raw_output.aggregated_output.from_utf8_lossy(),
                 duration,
+                killed_reason: None,
             })
         }
         Err(err) => {
@@ -163,16 +350,51 @@ pub async fn process_exec_tool_call(
     }
 }
 
+/// Messages that runtimes, allocators and libc print when an allocation
+/// fails, matched case-insensitively. Under `RLIMIT_AS` nothing is killed:
+/// allocations fail with `ENOMEM`, and what the process says about it is the
+/// only evidence.
+const OUT_OF_MEMORY_MARKERS: &[&str] = &[
+    // strerror(ENOMEM), e.g. `bash: fork: Cannot allocate memory`.
+    "cannot allocate memory",
+    // Go, Node.js, Perl, glibc's `xmalloc` and many others.
+    "out of memory",
+    // Rust's default allocation error handler.
+    "memory allocation of",
+    "std::bad_alloc",
+    "memoryerror",
+];
+
+/// Whether `stderr` of a failed command says that it ran out of memory.
+fn reports_out_of_memory(stderr: &str) -> bool {
+    let stderr = stderr.to_lowercase();
+    OUT_OF_MEMORY_MARKERS
+        .iter()
+        .any(|marker| stderr.contains(marker))
+}
+
 /// We don't have a fully deterministic way to tell if our command failed
 /// because of the sandbox - a command in the user's zshrc file might hit an
 /// error, but the command itself might fail or succeed for other reasons.
 /// For now, we conservatively check for 'command not found' (exit code 127),
 /// and can add additional cases as necessary.
-fn is_likely_sandbox_denied(sandbox_type: SandboxType, exit_code: i32) -> bool {
+fn is_likely_sandbox_denied(
+    sandbox_type: SandboxType,
+    sandbox_policy: &SandboxPolicy,
+    exit_code: i32,
+) -> bool {
     if sandbox_type == SandboxType::None {
         return false;
     }
 
+    // Escalated and approved commands run in the container with full access,
+    // so like unsandboxed commands their failures are their own.
+    if sandbox_type == SandboxType::Container
+        && matches!(sandbox_policy, SandboxPolicy::DangerFullAccess { .. })
+    {
+        return false;
+    }
+
     // Quick rejects: well-known non-sandbox shell exit codes
     // 127: command not found, 2: misuse of shell builtins
     if exit_code == 127 {
@@ -183,6 +405,37 @@ fn is_likely_sandbox_denied(sandbox_type: SandboxType, exit_code: i32) -> bool {
     true
 }
 
+/// Messages with which commands commonly report a sandbox restriction.
+const SANDBOX_DENIAL_MESSAGES: &[&str] = &[
+    "operation not permitted",
+    "permission denied",
+    "read-only file system",
+    "network is unreachable",
+    "could not resolve host",
+];
+
+/// Whether the output of a command that [`SandboxErr::Denied`] reported shows
+/// that it hit a sandbox restriction. `Denied` covers every failure inside
+/// the sandbox, since the exit code alone cannot tell them apart; this is the
+/// stricter check used to report denials to the user.
+pub(crate) fn output_shows_sandbox_denial(stdout: &str, stderr: &str) -> bool {
+    !sandbox_denial_lines(stderr).is_empty() || !sandbox_denial_lines(stdout).is_empty()
+}
+
+/// The lines of `output` in which a command reports a sandbox restriction,
+/// e.g. `touch: /etc/hosts: Operation not permitted`.
+pub fn sandbox_denial_lines(output: &str) -> Vec<&str> {
+    output
+        .lines()
+        .filter(|line| {
+            let line = line.to_lowercase();
+            SANDBOX_DENIAL_MESSAGES
+                .iter()
+                .any(|message| line.contains(message))
+        })
+        .collect()
+}
+
 #[derive(Debug)]
 pub struct StreamOutput<T> {
     pub text: T,
@@ -194,6 +447,9 @@ struct RawExecToolCallOutput {
     pub stdout: StreamOutput<Vec<u8>>,
     pub stderr: StreamOutput<Vec<u8>>,
     pub aggregated_output: StreamOutput<Vec<u8>>,
+    pub killed_reason: Option<ExecKilledReason>,
+    /// Whether the child was killed on timeout, output limit or Ctrl-C.
+    pub killed: bool,
 }
 
 impl StreamOutput<String> {
@@ -226,14 +482,18 @@ pub struct ExecToolCallOutput {
     pub stderr: StreamOutput<String>,
     pub aggregated_output: StreamOutput<String>,
     pub duration: Duration,
+    /// Set when a configured resource limit stopped the command. Timeouts are
+    /// reported as [`SandboxErr::Timeout`] instead.
+    pub killed_reason: Option<ExecKilledReason>,
 }
 
 async fn exec(
     params: ExecParams,
     sandbox_policy: &SandboxPolicy,
+    timeout: Duration,
+    limits: &ExecLimits,
     stdout_stream: Option<StdoutStream>,
 ) -> Result<RawExecToolCallOutput> {
-    let timeout = params.timeout_duration();
     let ExecParams {
         command, cwd, env, ..
     } = params;
@@ -245,24 +505,51 @@ async fn exec(
         ))
     })?;
     let arg0 = None;
-    let child = spawn_child_async(
-        PathBuf::from(program),
-        args.into(),
+    let child = spawn_child_async(SpawnChildRequest {
+        program: PathBuf::from(program),
+        args: args.into(),
         arg0,
         cwd,
         sandbox_policy,
-        StdioPolicy::RedirectForShellTool,
+        stdio_policy: StdioPolicy::RedirectForShellTool,
         env,
-    )
+        limits,
+    })
     .await?;
-    consume_truncated_output(child, timeout, stdout_stream).await
+    consume_truncated_output(child, timeout, limits.max_output_bytes, stdout_stream).await
+}
+
+/// Shared byte budget for a command's stdout and stderr.
+struct OutputBudget {
+    max_bytes: u64,
+    used: AtomicU64,
+    /// Cancelled the first time the budget is exceeded.
+    exceeded: CancellationToken,
+}
+
+impl OutputBudget {
+    /// Charges `n` bytes and returns how many of them fit in the budget.
+    fn take(&self, n: usize) -> usize {
+        let before = self.used.fetch_add(n as u64, Ordering::SeqCst);
+        let allowed = self.max_bytes.saturating_sub(before).min(n as u64) as usize;
+        if allowed < n {
+            self.exceeded.cancel();
+        }
+        allowed
+    }
+
+    fn is_exceeded(&self) -> bool {
+        self.exceeded.is_cancelled()
+    }
 }
 
 /// Consumes the output of a child process, truncating it so it is suitable for
-/// use as the output of a `shell` tool call. Also enforces specified timeout.
+/// use as the output of a `shell` tool call. Also enforces specified timeout
+/// and, if set, the output size limit.
 async fn consume_truncated_output(
     mut child: Child,
     timeout: Duration,
+    max_output_bytes: Option<u64>,
     stdout_stream: Option<StdoutStream>,
 ) -> Result<RawExecToolCallOutput> {
     // Both stdout and stderr were configured with `Stdio::piped()`
@@ -281,39 +568,64 @@ async fn consume_truncated_output(
     })?;
 
     let (agg_tx, agg_rx) = async_channel::unbounded::<Vec<u8>>();
+    // Both readers stop as soon as this is cancelled, even if the pipes are
+    // still open.
+    let stop_reading = CancellationToken::new();
+    let output_budget = max_output_bytes.map(|max_bytes| {
+        Arc::new(OutputBudget {
+            max_bytes,
+            used: AtomicU64::new(0),
+            exceeded: CancellationToken::new(),
+        })
+    });
 
     let stdout_handle = tokio::spawn(read_capped(
         BufReader::new(stdout_reader),
         stdout_stream.clone(),
         false,
         Some(agg_tx.clone()),
+        output_budget.clone(),
+        stop_reading.clone(),
     ));
     let stderr_handle = tokio::spawn(read_capped(
         BufReader::new(stderr_reader),
         stdout_stream.clone(),
         true,
         Some(agg_tx.clone()),
+        output_budget.clone(),
+        stop_reading.clone(),
     ));
 
-    let exit_status = tokio::select! {
+    let (exit_status, killed) = tokio::select! {
         result = tokio::time::timeout(timeout, child.wait()) => {
             match result {
-                Ok(Ok(exit_status)) => exit_status,
-                Ok(e) => e?,
+                Ok(Ok(exit_status)) => (exit_status, false),
+                Ok(e) => (e?, false),
                 Err(_) => {
                     // timeout
-                    child.start_kill()?;
+                    kill_child(&mut child, &stop_reading)?;
                     // Debatable whether `child.wait().await` should be called here.
-                    synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + TIMEOUT_CODE)
+                    (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + TIMEOUT_CODE), true)
                 }
             }
         }
+        _ = budget_exceeded(&output_budget) => {
+            kill_child(&mut child, &stop_reading)?;
+            (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + SIGKILL_CODE), true)
+        }
         _ = tokio::signal::ctrl_c() => {
-            child.start_kill()?;
-            synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + SIGKILL_CODE)
+            kill_child(&mut child, &stop_reading)?;
+            (synthetic_exit_status(EXIT_CODE_SIGNAL_BASE + SIGKILL_CODE), true)
         }
     };
 
+    // Also covers a command that exited on its own right after its output
+    // was cut off.
+    let killed_reason = output_budget
+        .as_ref()
+        .filter(|budget| budget.is_exceeded())
+        .map(|_| ExecKilledReason::OutputLimit);
+
     let stdout = stdout_handle.await??;
     let stderr = stderr_handle.await??;
 
@@ -333,43 +645,91 @@ async fn consume_truncated_output(
         stdout,
         stderr,
         aggregated_output,
+        killed_reason,
+        killed,
     })
 }
 
+/// Kills the child together with everything it spawned, then gives the
+/// readers [`POST_KILL_READ_TIMEOUT`] to collect what was already written.
+pub(crate) fn kill_child(child: &mut Child, stop_reading: &CancellationToken) -> io::Result<()> {
+    // `spawn_child_async` makes shell tool commands lead their own process
+    // group, so the group id is the child's pid.
+    #[cfg(unix)]
+    if let Some(pid) = child.id() {
+        let pgid = pid as libc::pid_t;
+        // SAFETY: killpg has no memory-safety preconditions.
+        if unsafe { libc::killpg(pgid, libc::SIGKILL) } == -1 {
+            let err = io::Error::last_os_error();
+            if err.raw_os_error() != Some(libc::ESRCH) {
+                return Err(err);
+            }
+        }
+    }
+    child.start_kill()?;
+
+    let stop_reading = stop_reading.clone();
+    tokio::spawn(async move {
+        tokio::time::sleep(POST_KILL_READ_TIMEOUT).await;
+        stop_reading.cancel();
+    });
+    Ok(())
+}
+
 async fn read_capped<R: AsyncRead + Unpin + Send + 'static>(
     mut reader: R,
     stream: Option<StdoutStream>,
     is_stderr: bool,
     aggregate_tx: Option<Sender<Vec<u8>>>,
+    output_budget: Option<Arc<OutputBudget>>,
+    stop_reading: CancellationToken,
 ) -> io::Result<StreamOutput<Vec<u8>>> {
     let mut buf = Vec::with_capacity(AGGREGATE_BUFFER_INITIAL_CAPACITY);
     let mut tmp = [0u8; READ_CHUNK_SIZE];
+    // Output not yet sent as a delta, and when it is due.
+    let mut pending_delta: Vec<u8> = Vec::new();
+    let mut delta_due: Option<tokio::time::Instant> = None;
 
     // No caps: append all bytes
 
     loop {
-        let n = reader.read(&mut tmp).await?;
+        let n = tokio::select! {
+            read = reader.read(&mut tmp) => read?,
+            _ = stop_reading.cancelled() => break,
+            _ = budget_exceeded(&output_budget) => break,
+            _ = tokio::time::sleep_until(delta_due.unwrap_or_else(tokio::time::Instant::now)),
+                if delta_due.is_some() =>
+            {
+                send_output_delta(&stream, is_stderr, std::mem::take(&mut pending_delta)).await;
+                delta_due = None;
+                continue;
+            }
+        };
         if n == 0 {
             break;
         }
 
-        if let Some(stream) = &stream {
-            let chunk = tmp[..n].to_vec();
-            let msg = EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
-                call_id: stream.call_id.clone(),
-                stream: if is_stderr {
-                    ExecOutputStream::Stderr
-                } else {
-                    ExecOutputStream::Stdout
-                },
-                chunk: ByteBuf::from(chunk),
-            });
-            let event = Event {
-                id: stream.sub_id.clone(),
-                msg,
-            };
-            #[allow(clippy::let_unit_value)]
-            let _ = stream.tx_event.send(event).await;
+        // Past the output limit nothing more is kept and the command is
+        // being killed, so stop reading instead of draining the pipe.
+        let (n, over_budget) = match &output_budget {
+            Some(budget) => {
+                let allowed = budget.take(n);
+                (allowed, allowed < n)
+            }
+            None => (n, false),
+        };
+        if n == 0 {
+            break;
+        }
+
+        if stream.is_some() {
+            pending_delta.extend_from_slice(&tmp[..n]);
+            if pending_delta.len() >= OUTPUT_DELTA_MAX_BYTES {
+                send_output_delta(&stream, is_stderr, std::mem::take(&mut pending_delta)).await;
+                delta_due = None;
+            } else if delta_due.is_none() {
+                delta_due = Some(tokio::time::Instant::now() + OUTPUT_DELTA_INTERVAL);
+            }
         }
 
         if let Some(tx) = &aggregate_tx {
@@ -377,8 +737,12 @@ async fn read_capped<R: AsyncRead + Unpin + Send + 'static>(
         }
 
         append_all(&mut buf, &tmp[..n]);
+        if over_budget {
+            break;
+        }
         // Continue reading to EOF to avoid back-pressure
     }
+    send_output_delta(&stream, is_stderr, pending_delta).await;
 
     Ok(StreamOutput {
         text: buf,
@@ -386,6 +750,37 @@ async fn read_capped<R: AsyncRead + Unpin + Send + 'static>(
     })
 }
 
+/// Sends `chunk` as an [`EventMsg::ExecCommandOutputDelta`] on `stream`.
+async fn send_output_delta(stream: &Option<StdoutStream>, is_stderr: bool, chunk: Vec<u8>) {
+    let Some(stream) = stream else {
+        return;
+    };
+    if chunk.is_empty() {
+        return;
+    }
+    let msg = EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
+        call_id: stream.call_id.clone(),
+        stream: if is_stderr {
+            ExecOutputStream::Stderr
+        } else {
+            ExecOutputStream::Stdout
+        },
+        chunk: ByteBuf::from(chunk),
+    });
+    let event = Event {
+        id: stream.sub_id.clone(),
+        msg,
+    };
+    let _ = stream.tx_event.send(event).await;
+}
+
+async fn budget_exceeded(output_budget: &Option<Arc<OutputBudget>>) {
+    match output_budget {
+        Some(budget) => budget.exceeded.cancelled().await,
+        None => std::future::pending().await,
+    }
+}
+
 #[cfg(unix)]
 fn synthetic_exit_status(code: i32) -> ExitStatus {
     use std::os::unix::process::ExitStatusExt;
@@ -398,3 +793,73 @@ fn synthetic_exit_status(code: i32) -> ExitStatus {
     #[expect(clippy::unwrap_used)]
     std::process::ExitStatus::from_raw(code.try_into().unwrap())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn out_of_memory_is_recognized_from_allocation_failures() {
+        assert!(reports_out_of_memory(
+            "bash: fork: Cannot allocate memory\n"
+        ));
+        assert!(reports_out_of_memory(
+            "memory allocation of 1073741824 bytes failed\n"
+        ));
+        assert!(reports_out_of_memory("fatal error: runtime: out of memory"));
+        assert!(reports_out_of_memory(
+            "terminate called after throwing an instance of 'std::bad_alloc'"
+        ));
+        assert!(reports_out_of_memory("MemoryError\n"));
+
+        assert!(!reports_out_of_memory(""));
+        assert!(!reports_out_of_memory(
+            "thread 'main' panicked at src/main.rs:2:5:\nassertion failed"
+        ));
+        assert!(!reports_out_of_memory("Killed\n"));
+    }
+
+    #[test]
+    fn full_access_container_failures_are_not_sandbox_denials() {
+        let full_access = SandboxPolicy::DangerFullAccess {
+            read_blocklist: Vec::new(),
+        };
+        let read_only = SandboxPolicy::new_read_only_policy();
+
+        assert!(!is_likely_sandbox_denied(
+            SandboxType::Container,
+            &full_access,
+            1
+        ));
+        assert!(is_likely_sandbox_denied(
+            SandboxType::Container,
+            &read_only,
+            1
+        ));
+        assert!(!is_likely_sandbox_denied(SandboxType::None, &read_only, 1));
+    }
+
+    #[test]
+    fn sandbox_denial_is_recognized_from_output() {
+        assert!(output_shows_sandbox_denial(
+            "",
+            "touch: /etc/hosts: Operation not permitted\n"
+        ));
+        assert!(output_shows_sandbox_denial(
+            "curl: (6) Could not resolve host: example.com",
+            ""
+        ));
+        assert!(!output_shows_sandbox_denial(
+            "test result: FAILED. 1 passed; 1 failed",
+            ""
+        ));
+    }
+
+    #[test]
+    fn sandbox_denial_lines_are_picked_out() {
+        assert_eq!(
+            sandbox_denial_lines("building\nmkdir: /usr/local/x: Read-only file system\ndone\n"),
+            vec!["mkdir: /usr/local/x: Read-only file system"]
+        );
+    }
+}
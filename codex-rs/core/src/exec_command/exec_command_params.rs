@@ -20,6 +20,14 @@ pub struct ExecCommandParams {
     pub(crate) login: bool,
 }
 
+impl ExecCommandParams {
+    /// The command line the PTY child runs, before any sandbox wrapping.
+    pub(crate) fn argv(&self) -> Vec<String> {
+        let shell_mode = if self.login { "-lc" } else { "-c" };
+        vec![self.shell.clone(), shell_mode.to_string(), self.cmd.clone()]
+    }
+}
+
 fn default_yield_time() -> u64 {
     10_000
 }
@@ -55,3 +63,8 @@ fn write_stdin_default_yield_time_ms() -> u64 {
 fn write_stdin_default_max_output_tokens() -> u64 {
     10_000
 }
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CloseSessionParams {
+    pub(crate) session_id: SessionId,
+}
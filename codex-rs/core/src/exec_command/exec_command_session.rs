@@ -1,17 +1,52 @@
+use std::sync::Arc;
 use std::sync::Mutex as StdMutex;
 
 use tokio::sync::broadcast;
 use tokio::sync::mpsc;
 use tokio::task::JoinHandle;
 
+use crate::exec_command::session_id::SessionId;
+use crate::rollout::ExecSessionTranscript;
+
+/// Upper bound on the output kept for the rollout transcript of one session.
+/// Output past this point is still streamed to the model but not recorded.
+const TRANSCRIPT_MAX_BYTES: usize = 256 * 1024;
+
+/// Output captured from the PTY over the whole life of a session.
+#[derive(Debug, Default)]
+pub(crate) struct TranscriptBuffer {
+    bytes: Vec<u8>,
+    truncated: bool,
+}
+
+impl TranscriptBuffer {
+    pub(crate) fn push(&mut self, chunk: &[u8]) {
+        let room = TRANSCRIPT_MAX_BYTES.saturating_sub(self.bytes.len());
+        if chunk.len() > room {
+            self.truncated = true;
+        }
+        self.bytes
+            .extend_from_slice(&chunk[..chunk.len().min(room)]);
+    }
+}
+
 #[derive(Debug)]
 pub(crate) struct ExecCommandSession {
+    /// The command as requested by the model, before any sandbox wrapping.
+    command: Vec<String>,
+
     /// Queue for writing bytes to the process stdin (PTY master write side).
     writer_tx: mpsc::Sender<Vec<u8>>,
     /// Broadcast stream of output chunks read from the PTY. New subscribers
     /// receive only chunks emitted after they subscribe.
     output_tx: broadcast::Sender<Vec<u8>>,
 
+    /// Everything read from the PTY so far, for the rollout transcript.
+    transcript: Arc<StdMutex<TranscriptBuffer>>,
+
+    /// Exit code of the child, set by the wait task once it exits.
+    exit_code: Arc<StdMutex<Option<i32>>>,
+
     /// Child killer handle for termination on drop (can signal independently
     /// of a thread blocked in `.wait()`).
     killer: StdMutex<Option<Box<dyn portable_pty::ChildKiller + Send + Sync>>>,
@@ -27,17 +62,24 @@ pub(crate) struct ExecCommandSession {
 }
 
 impl ExecCommandSession {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
+        command: Vec<String>,
         writer_tx: mpsc::Sender<Vec<u8>>,
         output_tx: broadcast::Sender<Vec<u8>>,
+        transcript: Arc<StdMutex<TranscriptBuffer>>,
+        exit_code: Arc<StdMutex<Option<i32>>>,
         killer: Box<dyn portable_pty::ChildKiller + Send + Sync>,
         reader_handle: JoinHandle<()>,
         writer_handle: JoinHandle<()>,
         wait_handle: JoinHandle<()>,
     ) -> Self {
         Self {
+            command,
             writer_tx,
             output_tx,
+            transcript,
+            exit_code,
             killer: StdMutex::new(Some(killer)),
             reader_handle: StdMutex::new(Some(reader_handle)),
             writer_handle: StdMutex::new(Some(writer_handle)),
@@ -52,16 +94,43 @@ impl ExecCommandSession {
     pub(crate) fn output_receiver(&self) -> broadcast::Receiver<Vec<u8>> {
         self.output_tx.subscribe()
     }
-}
 
-impl Drop for ExecCommandSession {
-    fn drop(&mut self) {
-        // Best-effort: terminate child first so blocking tasks can complete.
+    /// Exit code of the child, or `None` while it is still running.
+    pub(crate) fn exit_code(&self) -> Option<i32> {
+        self.exit_code.lock().ok().and_then(|code| *code)
+    }
+
+    /// Terminates the child without waiting for it to exit.
+    pub(crate) fn kill(&self) {
         if let Ok(mut killer_opt) = self.killer.lock()
             && let Some(mut killer) = killer_opt.take()
         {
             let _ = killer.kill();
         }
+    }
+
+    pub(crate) fn transcript(&self, session_id: SessionId) -> ExecSessionTranscript {
+        let (output, truncated) = match self.transcript.lock() {
+            Ok(buffer) => (
+                String::from_utf8_lossy(&buffer.bytes).to_string(),
+                buffer.truncated,
+            ),
+            Err(_) => (String::new(), false),
+        };
+        ExecSessionTranscript {
+            session_id: session_id.0,
+            command: self.command.clone(),
+            exit_code: self.exit_code(),
+            output,
+            truncated,
+        }
+    }
+}
+
+impl Drop for ExecCommandSession {
+    fn drop(&mut self) {
+        // Best-effort: terminate child first so blocking tasks can complete.
+        self.kill();
 
         // Abort background tasks; they may already have exited after kill.
         if let Ok(mut h) = self.reader_handle.lock()
@@ -81,3 +150,20 @@ impl Drop for ExecCommandSession {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn transcript_buffer_is_capped() {
+        let mut buffer = TranscriptBuffer::default();
+        buffer.push(&vec![b'a'; TRANSCRIPT_MAX_BYTES - 1]);
+        assert!(!buffer.truncated);
+
+        buffer.push(b"bc");
+        assert!(buffer.truncated);
+        assert_eq!(buffer.bytes.len(), TRANSCRIPT_MAX_BYTES);
+        assert_eq!(buffer.bytes.last(), Some(&b'b'));
+    }
+}
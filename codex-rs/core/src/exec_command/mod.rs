@@ -1,14 +1,19 @@
 mod exec_command_params;
 mod exec_command_session;
 mod responses_api;
+mod sandbox;
 mod session_id;
 mod session_manager;
 
+pub use exec_command_params::CloseSessionParams;
 pub use exec_command_params::ExecCommandParams;
 pub use exec_command_params::WriteStdinParams;
+pub use responses_api::CLOSE_SESSION_TOOL_NAME;
 pub use responses_api::EXEC_COMMAND_TOOL_NAME;
 pub use responses_api::WRITE_STDIN_TOOL_NAME;
+pub use responses_api::create_close_session_tool_for_responses_api;
 pub use responses_api::create_exec_command_tool_for_responses_api;
 pub use responses_api::create_write_stdin_tool_for_responses_api;
+pub(crate) use sandbox::sandboxed_argv;
 pub use session_manager::SessionManager as ExecSessionManager;
 pub use session_manager::result_into_payload;
@@ -5,6 +5,7 @@ use crate::openai_tools::ResponsesApiTool;
 
 pub const EXEC_COMMAND_TOOL_NAME: &str = "exec_command";
 pub const WRITE_STDIN_TOOL_NAME: &str = "write_stdin";
+pub const CLOSE_SESSION_TOOL_NAME: &str = "close_session";
 
 pub fn create_exec_command_tool_for_responses_api() -> ResponsesApiTool {
     let mut properties = BTreeMap::<String, JsonSchema>::new();
@@ -43,7 +44,8 @@ pub fn create_exec_command_tool_for_responses_api() -> ResponsesApiTool {
 
     ResponsesApiTool {
         name: EXEC_COMMAND_TOOL_NAME.to_owned(),
-        description: r#"Execute shell commands on the local machine with streaming output."#
+        description: r#"Execute shell commands on the local machine with streaming output.
+If the command is still running after yield_time_ms, it keeps running in a session you can drive with write_stdin and end with close_session, for example a REPL or a dev server."#
             .to_string(),
         strict: false,
         parameters: JsonSchema::Object {
@@ -96,3 +98,25 @@ Can write control characters (\u0003 for Ctrl-C), or an empty string to just pol
         },
     }
 }
+
+pub fn create_close_session_tool_for_responses_api() -> ResponsesApiTool {
+    let mut properties = BTreeMap::<String, JsonSchema>::new();
+    properties.insert(
+        "session_id".to_string(),
+        JsonSchema::Number {
+            description: Some("The ID of the exec_command session.".to_string()),
+        },
+    );
+
+    ResponsesApiTool {
+        name: CLOSE_SESSION_TOOL_NAME.to_owned(),
+        description: r#"Terminate an exec session that is no longer needed. Returns any output the process printed while shutting down."#
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["session_id".to_string()]),
+            additional_properties: Some(false),
+        },
+    }
+}
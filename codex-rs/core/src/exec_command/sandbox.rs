@@ -0,0 +1,122 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::exec::SandboxType;
+use crate::landlock::create_linux_sandbox_command_args;
+use crate::protocol::SandboxPolicy;
+use crate::seatbelt::MACOS_PATH_TO_SEATBELT_EXECUTABLE;
+use crate::seatbelt::SeatbeltExtensions;
+use crate::seatbelt::create_seatbelt_command_args;
+
+/// Wraps `command` so that the PTY child runs under `sandbox_type`.
+///
+/// A PTY child cannot be given a separate `argv[0]`, which the Linux sandbox
+/// helper relies on to be dispatched, so on Linux a short `bash` trampoline
+/// execs the helper under the expected name.
+pub(crate) fn sandboxed_argv(
+    command: Vec<String>,
+    sandbox_type: SandboxType,
+    sandbox_policy: &SandboxPolicy,
+    seatbelt_extensions: &SeatbeltExtensions,
+    cwd: &Path,
+    codex_linux_sandbox_exe: &Option<PathBuf>,
+) -> Result<Vec<String>, String> {
+    match sandbox_type {
+        SandboxType::None => Ok(command),
+        SandboxType::MacosSeatbelt => {
+            let mut argv = vec![MACOS_PATH_TO_SEATBELT_EXECUTABLE.to_string()];
+            argv.extend(create_seatbelt_command_args(
+                command,
+                sandbox_policy,
+                seatbelt_extensions,
+                cwd,
+            ));
+            Ok(argv)
+        }
+        SandboxType::LinuxSeccomp => {
+            let Some(exe) = codex_linux_sandbox_exe else {
+                return Err("codex-linux-sandbox executable not provided".to_string());
+            };
+            let mut argv = vec![
+                "/bin/bash".to_string(),
+                "-c".to_string(),
+                r#"exec -a codex-linux-sandbox "$0" "$@""#.to_string(),
+                exe.to_string_lossy().to_string(),
+            ];
+            argv.extend(create_linux_sandbox_command_args(
+                command,
+                sandbox_policy,
+                cwd,
+                None,
+            ));
+            Ok(argv)
+        }
+        SandboxType::WindowsRestrictedToken => {
+            Err("exec sessions are not supported under the Windows sandbox".to_string())
+        }
+        SandboxType::Container => {
+            Err("exec sessions are not supported under the container backend".to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn unsandboxed_command_is_unchanged() {
+        let command = vec!["bash".to_string(), "-lc".to_string(), "python3".to_string()];
+        let argv = sandboxed_argv(
+            command.clone(),
+            SandboxType::None,
+            &SandboxPolicy::new_read_only_policy(),
+            &SeatbeltExtensions::default(),
+            Path::new("/tmp"),
+            &None,
+        );
+        assert_eq!(argv, Ok(command));
+    }
+
+    #[test]
+    fn linux_sandbox_runs_helper_under_its_arg0() {
+        let command = vec!["bash".to_string(), "-lc".to_string(), "python3".to_string()];
+        let argv = sandboxed_argv(
+            command,
+            SandboxType::LinuxSeccomp,
+            &SandboxPolicy::new_read_only_policy(),
+            &SeatbeltExtensions::default(),
+            Path::new("/tmp"),
+            &Some(PathBuf::from("/opt/codex")),
+        )
+        .unwrap();
+        assert_eq!(
+            argv[..4],
+            [
+                "/bin/bash".to_string(),
+                "-c".to_string(),
+                r#"exec -a codex-linux-sandbox "$0" "$@""#.to_string(),
+                "/opt/codex".to_string(),
+            ]
+        );
+        assert_eq!(argv[4], "/tmp");
+        assert_eq!(argv[argv.len() - 4..], ["--", "bash", "-lc", "python3"]);
+    }
+
+    #[test]
+    fn linux_sandbox_requires_helper() {
+        let argv = sandboxed_argv(
+            vec!["python3".to_string()],
+            SandboxType::LinuxSeccomp,
+            &SandboxPolicy::new_read_only_policy(),
+            &SeatbeltExtensions::default(),
+            Path::new("/tmp"),
+            &None,
+        );
+        assert_eq!(
+            argv,
+            Err("codex-linux-sandbox executable not provided".to_string())
+        );
+    }
+}
@@ -1,6 +1,8 @@
 use std::collections::HashMap;
+use std::ffi::OsString;
 use std::io::ErrorKind;
 use std::io::Read;
+use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex as StdMutex;
 use std::sync::atomic::AtomicU32;
@@ -15,10 +17,13 @@ use tokio::time::Duration;
 use tokio::time::Instant;
 use tokio::time::timeout;
 
+use crate::exec_command::exec_command_params::CloseSessionParams;
 use crate::exec_command::exec_command_params::ExecCommandParams;
 use crate::exec_command::exec_command_params::WriteStdinParams;
 use crate::exec_command::exec_command_session::ExecCommandSession;
+use crate::exec_command::exec_command_session::TranscriptBuffer;
 use crate::exec_command::session_id::SessionId;
+use crate::rollout::ExecSessionTranscript;
 use codex_protocol::models::FunctionCallOutputPayload;
 
 #[derive(Debug, Default)]
@@ -33,9 +38,16 @@ pub struct ExecCommandOutput {
     exit_status: ExitStatus,
     original_token_count: Option<u64>,
     output: String,
+    /// Set once the session has ended and been removed from the manager.
+    transcript: Option<ExecSessionTranscript>,
 }
 
 impl ExecCommandOutput {
+    /// Takes the transcript of the session if this output ended it.
+    pub(crate) fn take_transcript(&mut self) -> Option<ExecSessionTranscript> {
+        self.transcript.take()
+    }
+
     fn to_text_output(&self) -> String {
         let wall_time_secs = self.wall_time.as_secs_f32();
         let termination_status = match self.exit_status {
@@ -43,6 +55,9 @@ impl ExecCommandOutput {
             ExitStatus::Ongoing(session_id) => {
                 format!("Process running with session ID {}", session_id.0)
             }
+            ExitStatus::Closed(session_id) => {
+                format!("Process with session ID {} was closed", session_id.0)
+            }
         };
         let truncation_status = match self.original_token_count {
             Some(tokens) => {
@@ -64,8 +79,16 @@ Output:
 pub enum ExitStatus {
     Exited(i32),
     Ongoing(SessionId),
+    Closed(SessionId),
 }
 
+/// How long [`SessionManager::handle_close_session_request`] waits for the
+/// killed process to exit and flush its remaining output.
+const CLOSE_GRACE_PERIOD: Duration = Duration::from_millis(250);
+
+/// Cap on the output returned when a session is closed.
+const CLOSE_OUTPUT_MAX_TOKENS: u64 = 10_000;
+
 pub fn result_into_payload(result: Result<ExecCommandOutput, String>) -> FunctionCallOutputPayload {
     match result {
         Ok(output) => FunctionCallOutputPayload {
@@ -80,10 +103,15 @@ pub fn result_into_payload(result: Result<ExecCommandOutput, String>) -> Functio
 }
 
 impl SessionManager {
-    /// Processes the request and is required to send a response via `outgoing`.
+    /// Spawns `argv` (the command from `params`, possibly wrapped in a
+    /// sandbox) in a new PTY and collects its output for up to
+    /// `yield_time_ms`. The session stays open if the process is still
+    /// running.
     pub async fn handle_exec_command_request(
         &self,
         params: ExecCommandParams,
+        argv: Vec<String>,
+        cwd: PathBuf,
     ) -> Result<ExecCommandOutput, String> {
         // Allocate a session id.
         let session_id = SessionId(
@@ -91,15 +119,14 @@ impl SessionManager {
                 .fetch_add(1, std::sync::atomic::Ordering::SeqCst),
         );
 
-        let (session, mut exit_rx) =
-            create_exec_command_session(params.clone())
-                .await
-                .map_err(|err| {
-                    format!(
-                        "failed to create exec command session for session id {}: {err}",
-                        session_id.0
-                    )
-                })?;
+        let (session, mut exit_rx) = create_exec_command_session(params.argv(), argv, cwd)
+            .await
+            .map_err(|err| {
+                format!(
+                    "failed to create exec command session for session id {}: {err}",
+                    session_id.0
+                )
+            })?;
 
         // Insert into session map.
         let mut output_rx = session.output_receiver();
@@ -159,10 +186,12 @@ impl SessionManager {
 
         let output = String::from_utf8_lossy(&collected).to_string();
 
-        let exit_status = if let Some(code) = exit_code {
-            ExitStatus::Exited(code)
-        } else {
-            ExitStatus::Ongoing(session_id)
+        let (exit_status, transcript) = match exit_code {
+            Some(code) => (
+                ExitStatus::Exited(code),
+                self.remove_session(session_id).await,
+            ),
+            None => (ExitStatus::Ongoing(session_id), None),
         };
 
         // If output exceeds cap, truncate the middle and record original token estimate.
@@ -172,6 +201,7 @@ impl SessionManager {
             exit_status,
             original_token_count,
             output,
+            transcript,
         })
     }
 
@@ -226,6 +256,21 @@ impl SessionManager {
             }
         }
 
+        // A process that exited while we were reading ends its session.
+        let exit_code = {
+            let sessions = self.sessions.lock().await;
+            sessions
+                .get(&session_id)
+                .and_then(ExecCommandSession::exit_code)
+        };
+        let (exit_status, transcript) = match exit_code {
+            Some(code) => (
+                ExitStatus::Exited(code),
+                self.remove_session(session_id).await,
+            ),
+            None => (ExitStatus::Ongoing(session_id), None),
+        };
+
         // Return structured output, truncating middle if over cap.
         let output = String::from_utf8_lossy(&collected).to_string();
         let cap_bytes_u64 = max_output_tokens.saturating_mul(4);
@@ -233,25 +278,94 @@ impl SessionManager {
         let (output, original_token_count) = truncate_middle(&output, cap_bytes);
         Ok(ExecCommandOutput {
             wall_time: Instant::now().duration_since(start_time),
-            exit_status: ExitStatus::Ongoing(session_id),
+            exit_status,
+            original_token_count,
+            output,
+            transcript,
+        })
+    }
+
+    /// Kills a session's process and returns whatever it printed while
+    /// shutting down.
+    pub async fn handle_close_session_request(
+        &self,
+        params: CloseSessionParams,
+    ) -> Result<ExecCommandOutput, String> {
+        let CloseSessionParams { session_id } = params;
+        let Some(session) = self.sessions.lock().await.remove(&session_id) else {
+            return Err(format!("unknown session id {}", session_id.0));
+        };
+
+        let mut output_rx = session.output_receiver();
+        session.kill();
+
+        let mut collected: Vec<u8> = Vec::new();
+        let start_time = Instant::now();
+        let deadline = start_time + CLOSE_GRACE_PERIOD;
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            match timeout(
+                (deadline - now).min(Duration::from_millis(10)),
+                output_rx.recv(),
+            )
+            .await
+            {
+                Ok(Ok(chunk)) => collected.extend_from_slice(&chunk),
+                Ok(Err(tokio::sync::broadcast::error::RecvError::Lagged(_))) => {}
+                Ok(Err(tokio::sync::broadcast::error::RecvError::Closed)) => break,
+                // Nothing arrived for a moment: done once the process is gone.
+                Err(_) if session.exit_code().is_some() => break,
+                Err(_) => {}
+            }
+        }
+
+        let output = String::from_utf8_lossy(&collected).to_string();
+        let (output, original_token_count) =
+            truncate_middle(&output, CLOSE_OUTPUT_MAX_TOKENS as usize * 4);
+        Ok(ExecCommandOutput {
+            wall_time: Instant::now().duration_since(start_time),
+            exit_status: ExitStatus::Closed(session_id),
             original_token_count,
             output,
+            transcript: Some(session.transcript(session_id)),
         })
     }
+
+    /// Kills every open session and returns their transcripts, ordered by
+    /// session id. Used when the conversation shuts down.
+    pub(crate) async fn close_all(&self) -> Vec<ExecSessionTranscript> {
+        let mut sessions: Vec<(SessionId, ExecCommandSession)> =
+            self.sessions.lock().await.drain().collect();
+        sessions.sort_by_key(|(session_id, _)| session_id.0);
+        sessions
+            .into_iter()
+            .map(|(session_id, session)| {
+                session.kill();
+                session.transcript(session_id)
+            })
+            .collect()
+    }
+
+    async fn remove_session(&self, session_id: SessionId) -> Option<ExecSessionTranscript> {
+        self.sessions
+            .lock()
+            .await
+            .remove(&session_id)
+            .map(|session| session.transcript(session_id))
+    }
 }
 
 /// Spawn PTY and child process per spawn_exec_command_session logic.
+/// `command` is what the model asked for and is kept for the transcript;
+/// `argv` is what actually runs.
 async fn create_exec_command_session(
-    params: ExecCommandParams,
+    command: Vec<String>,
+    argv: Vec<String>,
+    cwd: PathBuf,
 ) -> anyhow::Result<(ExecCommandSession, oneshot::Receiver<i32>)> {
-    let ExecCommandParams {
-        cmd,
-        yield_time_ms: _,
-        max_output_tokens: _,
-        shell,
-        login,
-    } = params;
-
     // Use the native pty implementation for the system
     let pty_system = native_pty_system();
 
@@ -263,11 +377,10 @@ async fn create_exec_command_session(
         pixel_height: 0,
     })?;
 
-    // Spawn a shell into the pty
-    let mut command_builder = CommandBuilder::new(shell);
-    let shell_mode_opt = if login { "-lc" } else { "-c" };
-    command_builder.arg(shell_mode_opt);
-    command_builder.arg(cmd);
+    // Spawn the command into the pty
+    let mut command_builder =
+        CommandBuilder::from_argv(argv.into_iter().map(OsString::from).collect());
+    command_builder.cwd(cwd);
 
     let mut child = pair.slave.spawn_command(command_builder)?;
     // Obtain a killer that can signal the process independently of `.wait()`.
@@ -281,12 +394,17 @@ async fn create_exec_command_session(
     // Reader task: drain PTY and forward chunks to output channel.
     let mut reader = pair.master.try_clone_reader()?;
     let output_tx_clone = output_tx.clone();
+    let transcript = Arc::new(StdMutex::new(TranscriptBuffer::default()));
+    let transcript_clone = transcript.clone();
     let reader_handle = tokio::task::spawn_blocking(move || {
         let mut buf = [0u8; 8192];
         loop {
             match reader.read(&mut buf) {
                 Ok(0) => break, // EOF
                 Ok(n) => {
+                    if let Ok(mut transcript) = transcript_clone.lock() {
+                        transcript.push(&buf[..n]);
+                    }
                     // Forward to broadcast; best-effort if there are subscribers.
                     let _ = output_tx_clone.send(buf[..n].to_vec());
                 }
@@ -327,18 +445,26 @@ async fn create_exec_command_session(
 
     // Keep the child alive until it exits, then signal exit code.
     let (exit_tx, exit_rx) = oneshot::channel::<i32>();
+    let exit_code = Arc::new(StdMutex::new(None));
+    let exit_code_clone = exit_code.clone();
     let wait_handle = tokio::task::spawn_blocking(move || {
         let code = match child.wait() {
             Ok(status) => status.exit_code() as i32,
             Err(_) => -1,
         };
+        if let Ok(mut exit_code) = exit_code_clone.lock() {
+            *exit_code = Some(code);
+        }
         let _ = exit_tx.send(code);
     });
 
     // Create and store the session with channels.
     let session = ExecCommandSession::new(
+        command,
         writer_tx,
         output_tx,
+        transcript,
+        exit_code,
         killer,
         reader_handle,
         writer_handle,
@@ -502,7 +628,7 @@ PY"#
             login: false,
         };
         let initial_output = match session_manager
-            .handle_exec_command_request(params.clone())
+            .handle_exec_command_request(params.clone(), params.argv(), std::env::temp_dir())
             .await
         {
             Ok(v) => v,
@@ -575,6 +701,133 @@ PY"#
         );
     }
 
+    /// A REPL-like process keeps its state across writes, runs in the
+    /// requested directory, and leaves a transcript once closed.
+    #[cfg(unix)]
+    #[allow(clippy::print_stderr)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn session_keeps_state_across_writes_and_records_transcript() {
+        use crate::exec_command::exec_command_params::CloseSessionParams;
+        use crate::exec_command::exec_command_params::ExecCommandParams;
+        use crate::exec_command::exec_command_params::WriteStdinParams;
+
+        let session_manager = SessionManager::default();
+        let cwd = tempfile::TempDir::new().unwrap();
+        let params = ExecCommandParams {
+            cmd: "pwd; while read line; do total=$((total + line)); echo \"total=$total\"; done"
+                .to_string(),
+            yield_time_ms: 200,
+            max_output_tokens: 1_000,
+            shell: "/bin/bash".to_string(),
+            login: false,
+        };
+        let first = match session_manager
+            .handle_exec_command_request(params.clone(), params.argv(), cwd.path().to_path_buf())
+            .await
+        {
+            Ok(v) => v,
+            Err(e) => {
+                if e.contains("openpty") || e.contains("Operation not permitted") {
+                    eprintln!("skipping test due to restricted PTY: {e}");
+                    return;
+                }
+                panic!("exec request failed unexpectedly: {e}");
+            }
+        };
+        let session_id = match first.exit_status {
+            ExitStatus::Ongoing(id) => id,
+            other => panic!("expected ongoing session, got {other:?}"),
+        };
+
+        for (chars, expected) in [("2\n", "total=2\r\n"), ("40\n", "total=42\r\n")] {
+            // Write once, then poll with empty writes until the reply shows up.
+            let mut chars = chars.to_string();
+            let mut output = String::new();
+            let deadline = Instant::now() + Duration::from_secs(10);
+            while !output.contains(expected) && Instant::now() < deadline {
+                let out = session_manager
+                    .handle_write_stdin_request(WriteStdinParams {
+                        session_id,
+                        chars: std::mem::take(&mut chars),
+                        yield_time_ms: 200,
+                        max_output_tokens: 1_000,
+                    })
+                    .await
+                    .expect("write stdin should succeed");
+                assert!(out.transcript.is_none());
+                output.push_str(&out.output);
+            }
+            assert!(output.contains(expected), "{output}");
+        }
+
+        let mut closed = session_manager
+            .handle_close_session_request(CloseSessionParams { session_id })
+            .await
+            .expect("close should succeed");
+        assert!(matches!(closed.exit_status, ExitStatus::Closed(id) if id == session_id));
+        let transcript = closed.take_transcript().expect("transcript on close");
+        assert_eq!(transcript.session_id, session_id.0);
+        assert_eq!(transcript.command, params.argv());
+        assert!(!transcript.truncated);
+        let cwd_name = cwd
+            .path()
+            .file_name()
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        assert!(
+            transcript.output.contains(&cwd_name),
+            "{}",
+            transcript.output
+        );
+        assert!(
+            transcript.output.contains("total=42"),
+            "{}",
+            transcript.output
+        );
+
+        let err = session_manager
+            .handle_close_session_request(CloseSessionParams { session_id })
+            .await
+            .expect_err("session is gone after close");
+        assert_eq!(err, format!("unknown session id {}", session_id.0));
+    }
+
+    /// A process that exits on its own ends its session with a transcript.
+    #[cfg(unix)]
+    #[allow(clippy::print_stderr)]
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn exited_process_ends_session() {
+        use crate::exec_command::exec_command_params::ExecCommandParams;
+
+        let session_manager = SessionManager::default();
+        let params = ExecCommandParams {
+            cmd: "echo done; exit 3".to_string(),
+            yield_time_ms: 5_000,
+            max_output_tokens: 1_000,
+            shell: "/bin/bash".to_string(),
+            login: false,
+        };
+        let mut out = match session_manager
+            .handle_exec_command_request(params.clone(), params.argv(), std::env::temp_dir())
+            .await
+        {
+            Ok(v) => v,
+            Err(e) => {
+                if e.contains("openpty") || e.contains("Operation not permitted") {
+                    eprintln!("skipping test due to restricted PTY: {e}");
+                    return;
+                }
+                panic!("exec request failed unexpectedly: {e}");
+            }
+        };
+        assert!(matches!(out.exit_status, ExitStatus::Exited(3)));
+        let transcript = out.take_transcript().expect("transcript on exit");
+        assert_eq!(transcript.exit_code, Some(3));
+        assert!(transcript.output.contains("done"), "{}", transcript.output);
+        assert!(session_manager.close_all().await.is_empty());
+    }
+
     #[cfg(unix)]
     fn extract_monotonic_numbers(s: &str) -> Vec<i64> {
         s.lines()
@@ -600,6 +853,7 @@ PY"#
             exit_status: ExitStatus::Exited(0),
             original_token_count: None,
             output: "hello".to_string(),
+            transcript: None,
         };
         let text = out.to_text_output();
         let expected = r#"Wall time: 1.234 seconds
@@ -616,6 +870,7 @@ hello"#;
             exit_status: ExitStatus::Ongoing(SessionId(42)),
             original_token_count: Some(1000),
             output: "abc".to_string(),
+            transcript: None,
         };
         let text = out.to_text_output();
         let expected = r#"Wall time: 0.500 seconds
@@ -0,0 +1,493 @@
+//! User-defined command rules from the `[exec_policy]` section of
+//! `config.toml`, consulted before the built-in list of known-safe commands.
+
+use std::fmt;
+use std::path::Path;
+
+use regex_lite::Regex;
+use wildmatch::WildMatchPattern;
+
+use crate::bash::try_parse_bash;
+use crate::bash::try_parse_word_only_commands_sequence;
+use crate::config_types::CommandRuleToml;
+use crate::config_types::ExecPolicyToml;
+use crate::is_safe_command::is_known_safe_command;
+
+type ArgumentPattern = WildMatchPattern<'*', '?'>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RuleAction {
+    Allow,
+    Ask,
+    Deny,
+}
+
+impl fmt::Display for RuleAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleAction::Allow => f.write_str("allow"),
+            RuleAction::Ask => f.write_str("ask"),
+            RuleAction::Deny => f.write_str("deny"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Matcher {
+    /// Patterns for the program and later arguments. Anchored patterns must
+    /// match the program and the arguments right after it, one each;
+    /// otherwise each pattern may match any later argument than the one
+    /// before it.
+    Glob {
+        patterns: Vec<ArgumentPattern>,
+        anchored: bool,
+    },
+    Regex(Regex),
+    /// A program whose first argument is `subcommand`.
+    Subcommand {
+        program: String,
+        subcommand: String,
+    },
+}
+
+/// A compiled `[exec_policy]` rule.
+#[derive(Debug, Clone)]
+pub struct CommandRule {
+    pub action: RuleAction,
+    /// The rule as written in `config.toml`.
+    pub source: String,
+    /// Added for the session by allowing the pattern of an approved command.
+    pub session: bool,
+    matcher: Matcher,
+}
+
+impl PartialEq for CommandRule {
+    fn eq(&self, other: &Self) -> bool {
+        self.action == other.action && self.source == other.source && self.session == other.session
+    }
+}
+
+impl fmt::Display for CommandRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.session {
+            write!(f, "session {} rule `{}`", self.action, self.source)
+        } else {
+            write!(f, "exec_policy {} rule `{}`", self.action, self.source)
+        }
+    }
+}
+
+impl CommandRule {
+    /// Allow rules are anchored, so that they cannot match a command that
+    /// merely mentions what they allow. Deny and ask rules are not: matching
+    /// too much only makes them stricter.
+    fn new(action: RuleAction, toml: CommandRuleToml) -> Result<Self, regex_lite::Error> {
+        let anchored = action == RuleAction::Allow;
+        match toml {
+            CommandRuleToml::Glob(source) => Ok(Self::new_glob(action, source, anchored)),
+            CommandRuleToml::Regex { regex } => {
+                let compiled = if anchored {
+                    Regex::new(&format!("^(?:{regex})$"))?
+                } else {
+                    Regex::new(&regex)?
+                };
+                Ok(Self {
+                    action,
+                    matcher: Matcher::Regex(compiled),
+                    source: regex,
+                    session: false,
+                })
+            }
+        }
+    }
+
+    fn new_glob(action: RuleAction, source: String, anchored: bool) -> Self {
+        let patterns = source
+            .split_whitespace()
+            .map(ArgumentPattern::new)
+            .collect();
+        Self {
+            action,
+            source,
+            session: false,
+            matcher: Matcher::Glob { patterns, anchored },
+        }
+    }
+
+    fn matches(&self, command: &[String]) -> bool {
+        match &self.matcher {
+            Matcher::Glob { patterns, anchored } => {
+                let (Some((program, args)), Some((first, rest))) =
+                    (patterns.split_first(), command.split_first())
+                else {
+                    return false;
+                };
+                if !program.matches(first) && !program.matches(program_name(first)) {
+                    return false;
+                }
+                if *anchored {
+                    args.len() <= rest.len()
+                        && args
+                            .iter()
+                            .zip(rest)
+                            .all(|(pattern, arg)| pattern.matches(arg))
+                } else {
+                    // Every remaining pattern must match a later argument
+                    // than the one before it.
+                    let mut rest = rest.iter();
+                    args.iter()
+                        .all(|pattern| rest.any(|arg| pattern.matches(arg)))
+                }
+            }
+            Matcher::Regex(regex) => regex.is_match(&command.join(" ")),
+            Matcher::Subcommand {
+                program,
+                subcommand,
+            } => match command {
+                [first, second, ..] => {
+                    second == subcommand && (first == program || program_name(first) == program)
+                }
+                _ => false,
+            },
+        }
+    }
+}
+
+/// The file name of `program`, e.g. `git` for `/usr/bin/git`.
+fn program_name(program: &str) -> &str {
+    Path::new(program)
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or(program)
+}
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ExecPolicy {
+    rules: Vec<CommandRule>,
+}
+
+impl ExecPolicy {
+    pub fn from_toml(toml: ExecPolicyToml) -> Result<Self, regex_lite::Error> {
+        let ExecPolicyToml { allow, ask, deny } = toml;
+        let mut rules = Vec::new();
+        // Rules are kept in precedence order so the first match wins.
+        for (action, list) in [
+            (RuleAction::Deny, deny),
+            (RuleAction::Ask, ask),
+            (RuleAction::Allow, allow),
+        ] {
+            for rule in list.unwrap_or_default() {
+                rules.push(CommandRule::new(action, rule)?);
+            }
+        }
+        Ok(Self { rules })
+    }
+
+    /// Adds a session allow rule for `pattern`, a program and subcommand
+    /// such as `cargo test` from [`command_pattern`]. It matches only
+    /// commands whose first argument is the subcommand, and has the lowest
+    /// precedence, so the existing deny and ask rules still win. Returns
+    /// `false`, adding nothing, for any other kind of pattern.
+    pub fn add_session_rule(&mut self, pattern: String) -> bool {
+        let (program, subcommand) = match pattern.split_whitespace().collect::<Vec<_>>()[..] {
+            [program, subcommand] => (program.to_string(), subcommand.to_string()),
+            _ => return false,
+        };
+        self.rules.push(CommandRule {
+            action: RuleAction::Allow,
+            source: pattern,
+            session: true,
+            matcher: Matcher::Subcommand {
+                program,
+                subcommand,
+            },
+        });
+        true
+    }
+
+    /// Returns the rule that decides how `command` is handled, or `None` if
+    /// the rules have no opinion and the built-in heuristics should decide.
+    ///
+    /// A `bash -lc` script made of plain commands is checked command by
+    /// command: one denied command denies the script, one command that needs
+    /// approval makes the script need it, and the script is allowed only if
+    /// every command is allowed by a rule or is known to be safe. Any other
+    /// script is checked as a whole against the deny and ask rules only.
+    pub fn check(&self, command: &[String]) -> Option<&CommandRule> {
+        if self.rules.is_empty() {
+            return None;
+        }
+
+        let (commands, can_allow) = match command {
+            [bash, flag, script] if bash == "bash" && flag == "-lc" => {
+                match try_parse_bash(script)
+                    .and_then(|tree| try_parse_word_only_commands_sequence(&tree, script))
+                    .filter(|commands| !commands.is_empty())
+                {
+                    Some(commands) => (commands, true),
+                    None => (vec![command.to_vec()], false),
+                }
+            }
+            _ => (vec![command.to_vec()], true),
+        };
+
+        let matched: Vec<Option<&CommandRule>> = commands
+            .iter()
+            .map(|command| {
+                self.rules.iter().find(|rule| {
+                    (can_allow || rule.action != RuleAction::Allow) && rule.matches(command)
+                })
+            })
+            .collect();
+        for action in [RuleAction::Deny, RuleAction::Ask] {
+            if let Some(rule) = matched.iter().flatten().find(|rule| rule.action == action) {
+                return Some(rule);
+            }
+        }
+
+        // Only allow rules are left.
+        let all_allowed = matched
+            .iter()
+            .zip(&commands)
+            .all(|(rule, command)| rule.is_some() || is_known_safe_command(command));
+        if all_allowed {
+            matched.into_iter().flatten().next()
+        } else {
+            None
+        }
+    }
+}
+
+/// A glob rule covering `command` and similar invocations of the same
+/// program and subcommand, e.g. `cargo test` for `cargo test -p codex-core`,
+/// for the user to allow for a session.
+///
+/// Known-safe commands in a `bash -lc` script are skipped. `None` if what is
+/// left is not exactly one command, or it has no subcommand: a rule for a
+/// bare program would allow anything it can be asked to run.
+pub fn command_pattern(command: &[String]) -> Option<String> {
+    let commands = match command {
+        [bash, flag, script] if bash == "bash" && flag == "-lc" => try_parse_bash(script)
+            .and_then(|tree| try_parse_word_only_commands_sequence(&tree, script))?,
+        _ => vec![command.to_vec()],
+    };
+    let mut unsafe_commands = commands
+        .iter()
+        .filter(|command| !is_known_safe_command(command));
+    let (Some(command), None) = (unsafe_commands.next(), unsafe_commands.next()) else {
+        return None;
+    };
+    let [program, subcommand, ..] = command.as_slice() else {
+        return None;
+    };
+    let is_word = subcommand
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_ascii_lowercase())
+        && subcommand
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !is_word {
+        return None;
+    }
+    Some(format!("{} {subcommand}", program_name(program)))
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn vec_str(args: &[&str]) -> Vec<String> {
+        args.iter().map(ToString::to_string).collect()
+    }
+
+    fn policy(toml: &str) -> ExecPolicy {
+        ExecPolicy::from_toml(toml::from_str(toml).unwrap()).unwrap()
+    }
+
+    fn check(policy: &ExecPolicy, command: &[&str]) -> Option<(RuleAction, String)> {
+        policy
+            .check(&vec_str(command))
+            .map(|rule| (rule.action, rule.source.clone()))
+    }
+
+    #[test]
+    fn glob_rules_match_arguments_in_order() {
+        let policy = policy(
+            r#"
+deny = ["git push *--force*"]
+allow = ["git push"]
+"#,
+        );
+        let deny = Some((RuleAction::Deny, "git push *--force*".to_string()));
+        let allow = Some((RuleAction::Allow, "git push".to_string()));
+
+        assert_eq!(check(&policy, &["git", "push", "--force"]), deny);
+        assert_eq!(
+            check(
+                &policy,
+                &["/usr/bin/git", "push", "origin", "--force-with-lease"]
+            ),
+            deny
+        );
+        assert_eq!(check(&policy, &["git", "push", "origin", "main"]), allow);
+        assert_eq!(check(&policy, &["git", "status"]), None);
+    }
+
+    #[test]
+    fn allow_rules_are_anchored() {
+        let policy = policy(
+            r#"
+allow = ["cargo test", "git push", "npm run *", { regex = "make( -j[0-9]+)?" }]
+"#,
+        );
+        let allowed = |command: &[&str]| {
+            check(&policy, command).is_some_and(|(action, _)| action == RuleAction::Allow)
+        };
+        assert!(allowed(&["cargo", "test", "-p", "codex-core"]));
+        assert!(allowed(&["npm", "run", "lint"]));
+        assert!(allowed(&["make", "-j8"]));
+        // What the rule allows must come first, not just appear somewhere.
+        assert!(!allowed(&["cargo", "run", "--bin", "x", "--", "test"]));
+        assert!(!allowed(&["git", "--force", "push"]));
+        assert!(!allowed(&["npm", "run"]));
+        assert!(!allowed(&["make", "-j8", "&&", "rm", "-rf", "/"]));
+        assert!(!allowed(&["sudo", "make"]));
+    }
+
+    #[test]
+    fn scripts_that_cannot_be_split_are_not_allowed() {
+        let policy = policy(
+            r#"
+deny = [{ regex = "rm -rf" }]
+allow = ["cargo build"]
+"#,
+        );
+        assert_eq!(
+            check(&policy, &["bash", "-lc", "cargo build; echo $(whoami)"]),
+            None
+        );
+        assert_eq!(
+            check(&policy, &["bash", "-lc", "cargo build > $(mktemp)"]),
+            None
+        );
+        assert_eq!(
+            check(&policy, &["bash", "-lc", "rm -rf $(pwd)"]),
+            Some((RuleAction::Deny, "rm -rf".to_string()))
+        );
+    }
+
+    #[test]
+    fn regex_rules_match_the_joined_command() {
+        let policy = policy(r#"ask = [{ regex = "^rm .*-r" }]"#);
+        assert_eq!(
+            check(&policy, &["rm", "-rf", "target"]),
+            Some((RuleAction::Ask, "^rm .*-r".to_string()))
+        );
+        assert_eq!(check(&policy, &["rm", "file"]), None);
+    }
+
+    #[test]
+    fn invalid_regex_is_an_error() {
+        let toml = toml::from_str(r#"deny = [{ regex = "(" }]"#).unwrap();
+        assert!(ExecPolicy::from_toml(toml).is_err());
+    }
+
+    #[test]
+    fn session_rules_have_the_lowest_precedence() {
+        let mut policy = policy(r#"ask = ["cargo test *--ignored*"]"#);
+        assert!(policy.add_session_rule("cargo test".to_string()));
+        assert_eq!(
+            check(&policy, &["bash", "-lc", "cargo test -p codex-core"]),
+            Some((RuleAction::Allow, "cargo test".to_string()))
+        );
+        assert_eq!(
+            check(&policy, &["cargo", "test", "--", "--ignored"]),
+            Some((RuleAction::Ask, "cargo test *--ignored*".to_string()))
+        );
+        assert_eq!(check(&policy, &["cargo", "build"]), None);
+    }
+
+    #[test]
+    fn session_rules_anchor_the_subcommand() {
+        let mut policy = ExecPolicy::default();
+        assert!(policy.add_session_rule("cargo test".to_string()));
+        assert!(policy.add_session_rule("git push".to_string()));
+
+        let allowed = |command: &[&str]| {
+            policy
+                .check(&vec_str(command))
+                .is_some_and(|rule| rule.session && rule.action == RuleAction::Allow)
+        };
+        assert!(allowed(&["cargo", "test", "-p", "codex-core"]));
+        assert!(allowed(&["/usr/bin/git", "push", "origin"]));
+        // The subcommand must come first, not just appear somewhere.
+        assert!(!allowed(&["cargo", "run", "--bin", "x", "--", "test"]));
+        assert!(!allowed(&["cargo", "run", "--", "test"]));
+        assert!(!allowed(&[
+            "git",
+            "-c",
+            "core.sshCommand=sh",
+            "fetch",
+            "push"
+        ]));
+        assert!(!allowed(&["cargo"]));
+    }
+
+    #[test]
+    fn session_rules_need_a_program_and_subcommand() {
+        let mut policy = ExecPolicy::default();
+        assert!(!policy.add_session_rule("bash".to_string()));
+        assert!(!policy.add_session_rule("git push origin".to_string()));
+        assert!(!policy.add_session_rule(String::new()));
+        assert_eq!(policy, ExecPolicy::default());
+    }
+
+    #[test]
+    fn command_patterns_cover_the_program_and_subcommand() {
+        let pattern = |command: &[&str]| command_pattern(&vec_str(command));
+        assert_eq!(
+            pattern(&["bash", "-lc", "cargo test -p codex-core"]),
+            Some("cargo test".to_string())
+        );
+        assert_eq!(
+            pattern(&["bash", "-lc", "ls && /usr/bin/git push origin"]),
+            Some("git push".to_string())
+        );
+        assert_eq!(pattern(&["rm", "-rf", "target"]), None);
+        assert_eq!(pattern(&["python3"]), None);
+        assert_eq!(pattern(&["bash", "-lc", "cargo build && cargo test"]), None);
+        assert_eq!(pattern(&["bash", "-lc", "echo $(whoami)"]), None);
+    }
+
+    #[test]
+    fn bash_scripts_are_checked_command_by_command() {
+        let policy = policy(
+            r#"
+deny = ["git push *--force*"]
+ask = ["cat *.env"]
+allow = ["cargo build"]
+"#,
+        );
+        assert_eq!(
+            check(&policy, &["bash", "-lc", "ls && git push --force"]),
+            Some((RuleAction::Deny, "git push *--force*".to_string()))
+        );
+        // `ask` wins over the built-in list of safe commands.
+        assert_eq!(
+            check(&policy, &["bash", "-lc", "cat .env"]),
+            Some((RuleAction::Ask, "cat *.env".to_string()))
+        );
+        assert_eq!(
+            check(&policy, &["bash", "-lc", "ls && cargo build"]),
+            Some((RuleAction::Allow, "cargo build".to_string()))
+        );
+        // `cargo test` is neither allowed nor known to be safe.
+        assert_eq!(
+            check(&policy, &["bash", "-lc", "cargo build && cargo test"]),
+            None
+        );
+    }
+}
@@ -109,6 +109,72 @@ pub async fn git_diff_to_remote(cwd: &Path) -> Option<GitDiffToRemote> {
     })
 }
 
+/// The diff of the changes staged in the index of the repository at `cwd`,
+/// empty when nothing is staged. Returns `None` if git fails, e.g. outside a
+/// repository.
+pub async fn staged_diff(cwd: &Path) -> Option<String> {
+    let output = run_git_command_with_timeout(&["diff", "--cached", "--no-color"], cwd).await?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Subject, body and abbreviated SHA of each non-merge commit in `range`,
+/// e.g. `v1.2.0..HEAD`, newest first. Returns `None` if git fails, e.g. for
+/// an unknown revision.
+pub async fn commit_log(cwd: &Path, range: &str) -> Option<String> {
+    let output = run_git_command_with_timeout(
+        &["log", "--no-merges", "--format=%h %s%n%n%b%n---", range],
+        cwd,
+    )
+    .await?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The branch checked out in a working tree and whether it has uncommitted
+/// changes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct BranchStatus {
+    /// `None` when `HEAD` is detached.
+    pub branch: Option<String>,
+    /// True when there are staged, unstaged or untracked changes.
+    pub dirty: bool,
+}
+
+/// The branch and dirty state of the repository at `cwd`, from a single
+/// `git status`. Returns `None` outside a repository or if git fails.
+pub async fn branch_status(cwd: &Path) -> Option<BranchStatus> {
+    let output =
+        run_git_command_with_timeout(&["status", "--porcelain=v1", "--branch"], cwd).await?;
+    if !output.status.success() {
+        return None;
+    }
+    parse_branch_status(&String::from_utf8_lossy(&output.stdout))
+}
+
+/// Parses `git status --porcelain=v1 --branch`, whose first line is a
+/// `## <branch>...<upstream>` header followed by one line per change.
+fn parse_branch_status(status: &str) -> Option<BranchStatus> {
+    let mut lines = status.lines();
+    let header = lines.next()?.strip_prefix("## ")?;
+    let branch = if let Some(unborn) = header.strip_prefix("No commits yet on ") {
+        Some(unborn.to_string())
+    } else if header.starts_with("HEAD (no branch)") {
+        None
+    } else {
+        let end = header.find("...").or_else(|| header.find(' '));
+        Some(end.map_or(header, |end| &header[..end]).to_string())
+    };
+    Some(BranchStatus {
+        branch,
+        dirty: lines.any(|line| !line.is_empty()),
+    })
+}
+
 /// Run a git command with a timeout to prevent blocking on large repositories
 async fn run_git_command_with_timeout(args: &[&str], cwd: &Path) -> Option<std::process::Output> {
     let result = timeout(
@@ -559,6 +625,54 @@ mod tests {
         (repo_path, branch)
     }
 
+    #[test]
+    fn test_parse_branch_status() {
+        assert_eq!(
+            parse_branch_status("## main...origin/main [ahead 1]\n M src/lib.rs\n"),
+            Some(BranchStatus {
+                branch: Some("main".to_string()),
+                dirty: true,
+            })
+        );
+        assert_eq!(
+            parse_branch_status("## feature/x\n"),
+            Some(BranchStatus {
+                branch: Some("feature/x".to_string()),
+                dirty: false,
+            })
+        );
+        assert_eq!(
+            parse_branch_status("## HEAD (no branch)\n?? new.txt\n"),
+            Some(BranchStatus {
+                branch: None,
+                dirty: true,
+            })
+        );
+        assert_eq!(
+            parse_branch_status("## No commits yet on trunk\n"),
+            Some(BranchStatus {
+                branch: Some("trunk".to_string()),
+                dirty: false,
+            })
+        );
+        assert_eq!(parse_branch_status(""), None);
+    }
+
+    #[tokio::test]
+    async fn test_branch_status_reports_changes() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        assert_eq!(branch_status(temp_dir.path()).await, None);
+
+        let repo_path = create_test_git_repo(&temp_dir).await;
+        let status = branch_status(&repo_path).await.expect("branch status");
+        assert!(!status.dirty);
+        assert!(status.branch.is_some());
+
+        fs::write(repo_path.join("untracked.txt"), "new").expect("write file");
+        let status = branch_status(&repo_path).await.expect("branch status");
+        assert!(status.dirty);
+    }
+
     #[tokio::test]
     async fn test_collect_git_info_non_git_directory() {
         let temp_dir = TempDir::new().expect("Failed to create temp dir");
@@ -723,6 +837,33 @@ mod tests {
         assert!(state.diff.contains("untracked.txt"));
     }
 
+    #[tokio::test]
+    async fn test_staged_diff_and_commit_log() {
+        let temp_dir = TempDir::new().expect("Failed to create temp dir");
+        let repo_path = create_test_git_repo(&temp_dir).await;
+
+        fs::write(repo_path.join("test.txt"), "modified").unwrap();
+        assert_eq!(staged_diff(&repo_path).await, Some(String::new()));
+
+        Command::new("git")
+            .args(["add", "test.txt"])
+            .current_dir(&repo_path)
+            .output()
+            .await
+            .expect("Failed to add files");
+        let diff = staged_diff(&repo_path)
+            .await
+            .expect("Should get staged diff");
+        assert!(diff.contains("+modified"));
+
+        let log = commit_log(&repo_path, "HEAD")
+            .await
+            .expect("Should get log");
+        assert!(log.contains("Initial commit"));
+        assert_eq!(commit_log(&repo_path, "no-such-rev..HEAD").await, None);
+        assert_eq!(staged_diff(temp_dir.path()).await, None);
+    }
+
     #[tokio::test]
     async fn test_get_git_working_tree_state_branch_fallback() {
         let temp_dir = TempDir::new().expect("Failed to create temp dir");
@@ -0,0 +1,104 @@
+//! Commit messages and changelogs written by the model, for `codex git
+//! commit-msg`. Each is a single request without tools, made with the
+//! configured model and provider.
+
+use std::sync::Arc;
+
+use codex_login::AuthManager;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use futures::prelude::*;
+use uuid::Uuid;
+
+use crate::client::ModelClient;
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::codex::take_bytes_at_char_boundary;
+use crate::config::Config;
+use crate::error::CodexErr;
+use crate::error::Result as CodexResult;
+
+const COMMIT_MESSAGE_PROMPT: &str = include_str!("prompt_for_commit_message.md");
+const CHANGELOG_PROMPT: &str = include_str!("prompt_for_changelog.md");
+
+/// Diffs and logs longer than this are cut to keep the request small.
+const MAX_INPUT_BYTES: usize = 64 * 1024;
+
+/// A Conventional Commits message for the staged `diff`.
+pub async fn generate_commit_message(
+    config: &Config,
+    auth_manager: Arc<AuthManager>,
+    diff: &str,
+) -> CodexResult<String> {
+    complete(config, auth_manager, COMMIT_MESSAGE_PROMPT, diff).await
+}
+
+/// A Markdown changelog of the commits in `log`, as printed by
+/// [`crate::git_info::commit_log`].
+pub async fn generate_changelog(
+    config: &Config,
+    auth_manager: Arc<AuthManager>,
+    log: &str,
+) -> CodexResult<String> {
+    complete(config, auth_manager, CHANGELOG_PROMPT, log).await
+}
+
+/// Send `input` with `instructions` and return the model's reply.
+async fn complete(
+    config: &Config,
+    auth_manager: Arc<AuthManager>,
+    instructions: &str,
+    input: &str,
+) -> CodexResult<String> {
+    let mut text = take_bytes_at_char_boundary(input, MAX_INPUT_BYTES).to_string();
+    if text.len() < input.len() {
+        text.push_str("\n[truncated]");
+    }
+    let client = ModelClient::new(
+        Arc::new(config.clone()),
+        Some(auth_manager),
+        config.model_provider.clone(),
+        config.model_reasoning_effort,
+        config.model_reasoning_summary,
+        Uuid::new_v4(),
+    );
+    let prompt = Prompt {
+        input: vec![ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText { text }],
+        }],
+        store: !config.disable_response_storage,
+        tools: Vec::new(),
+        base_instructions_override: Some(instructions.to_string()),
+        max_output_tokens: None,
+    };
+
+    let mut stream = client.stream(&prompt).await?;
+    let mut reply = String::new();
+    while let Some(event) = stream.next().await {
+        match event? {
+            ResponseEvent::OutputItemDone(ResponseItem::Message { role, content, .. })
+                if role == "assistant" =>
+            {
+                reply = content
+                    .iter()
+                    .filter_map(|item| match item {
+                        ContentItem::OutputText { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+            }
+            ResponseEvent::Completed { .. } => break,
+            _ => {}
+        }
+    }
+    let reply = reply.trim();
+    if reply.is_empty() {
+        return Err(CodexErr::Stream(
+            "the model returned no text".to_string(),
+            None,
+        ));
+    }
+    Ok(reply.to_string())
+}
@@ -1,4 +1,7 @@
+use crate::config_types::ExecLimits;
+use crate::protocol::SandboxDenial;
 use crate::protocol::SandboxPolicy;
+use crate::spawn::SpawnChildRequest;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
 use std::collections::HashMap;
@@ -13,6 +16,10 @@ use tokio::process::Child;
 /// helper accepts a list of `--sandbox-permission`/`-s` flags mirroring the
 /// public CLI. We convert the internal [`SandboxPolicy`] representation into
 /// the equivalent CLI options.
+///
+/// With `report_denials`, the helper records what the sandbox refused the
+/// command in that file; see [`read_sandbox_denials`].
+#[allow(clippy::too_many_arguments)]
 pub async fn spawn_command_under_linux_sandbox<P>(
     codex_linux_sandbox_exe: P,
     command: Vec<String>,
@@ -20,29 +27,33 @@ pub async fn spawn_command_under_linux_sandbox<P>(
     cwd: PathBuf,
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
+    limits: &ExecLimits,
+    report_denials: Option<&Path>,
 ) -> std::io::Result<Child>
 where
     P: AsRef<Path>,
 {
-    let args = create_linux_sandbox_command_args(command, sandbox_policy, &cwd);
+    let args = create_linux_sandbox_command_args(command, sandbox_policy, &cwd, report_denials);
     let arg0 = Some("codex-linux-sandbox");
-    spawn_child_async(
-        codex_linux_sandbox_exe.as_ref().to_path_buf(),
+    spawn_child_async(SpawnChildRequest {
+        program: codex_linux_sandbox_exe.as_ref().to_path_buf(),
         args,
         arg0,
         cwd,
         sandbox_policy,
         stdio_policy,
         env,
-    )
+        limits,
+    })
     .await
 }
 
 /// Converts the sandbox policy into the CLI invocation for `codex-linux-sandbox`.
-fn create_linux_sandbox_command_args(
+pub(crate) fn create_linux_sandbox_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
     cwd: &Path,
+    report_denials: Option<&Path>,
 ) -> Vec<String> {
     #[expect(clippy::expect_used)]
     let sandbox_policy_cwd = cwd.to_str().expect("cwd must be valid UTF-8").to_string();
@@ -51,16 +62,70 @@ fn create_linux_sandbox_command_args(
     let sandbox_policy_json =
         serde_json::to_string(sandbox_policy).expect("Failed to serialize SandboxPolicy to JSON");
 
-    let mut linux_cmd: Vec<String> = vec![
+    let mut linux_cmd: Vec<String> = Vec::new();
+    if let Some(report_denials) = report_denials {
+        linux_cmd.push("--report-denials".to_string());
+        linux_cmd.push(report_denials.to_string_lossy().into_owned());
+    }
+    linux_cmd.extend([
         sandbox_policy_cwd,
         sandbox_policy_json,
         // Separator so that command arguments starting with `-` are not parsed as
         // options of the helper itself.
         "--".to_string(),
-    ];
+    ]);
 
     // Append the original tool command.
     linux_cmd.extend(command);
 
     linux_cmd
 }
+
+/// Reads the denials that `codex-linux-sandbox --report-denials` recorded,
+/// one JSON [`SandboxDenial`] per line. A missing file or malformed line
+/// means nothing was recorded.
+pub(crate) fn read_sandbox_denials(path: &Path) -> Vec<SandboxDenial> {
+    let Ok(report) = std::fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    report
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn reads_recorded_denials_and_skips_malformed_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("denials.jsonl");
+        std::fs::write(
+            &path,
+            concat!(
+                r#"{"kind":"write","syscall":"openat","path":"/etc/hosts"}"#,
+                "\n",
+                r#"{"kind":"network","syscall":"connect"}"#,
+                "\n",
+                "{\"kind\":\"wri",
+            ),
+        )
+        .unwrap();
+        assert_eq!(
+            read_sandbox_denials(&path),
+            vec![
+                SandboxDenial::Write {
+                    syscall: "openat".to_string(),
+                    path: PathBuf::from("/etc/hosts"),
+                },
+                SandboxDenial::Network {
+                    syscall: "connect".to_string(),
+                },
+            ]
+        );
+        assert_eq!(read_sandbox_denials(&dir.path().join("missing")), vec![]);
+    }
+}
@@ -6,27 +6,38 @@
 #![deny(clippy::print_stdout, clippy::print_stderr)]
 
 mod apply_patch;
+pub mod audit_log;
+mod background_task;
 mod bash;
 mod chat_completions;
+mod checkpoint;
 mod client;
 mod client_common;
 pub mod codex;
 mod codex_conversation;
 pub use codex_conversation::CodexConversation;
+mod compact;
 pub mod config;
 pub mod config_profile;
 pub mod config_types;
+mod config_validation;
+pub mod container;
 mod conversation_history;
+pub mod encryption;
 mod environment_context;
 pub mod error;
 pub mod exec;
 mod exec_command;
 pub mod exec_env;
+pub mod exec_policy;
 mod flags;
 pub mod git_info;
+pub mod git_message;
 mod is_safe_command;
 pub mod landlock;
 mod mcp_connection_manager;
+pub use mcp_connection_manager::probe_mcp_server;
+mod mcp_sampling;
 mod mcp_tool_call;
 mod message_history;
 mod model_provider_info;
@@ -42,21 +53,39 @@ pub use conversation_manager::NewConversation;
 pub mod model_family;
 mod openai_model_info;
 mod openai_tools;
+mod output_salience;
 pub mod plan_tool;
 pub mod project_doc;
+mod prompt_template;
+pub mod redaction;
+mod repo_map;
 mod rollout;
+pub use rollout::RolloutSummary;
+pub use rollout::SavedSession;
+pub use rollout::find_latest_rollout;
+pub use rollout::list_rollouts;
+pub use rollout::read_rollout;
+pub use rollout::read_rollout_summary;
+mod run_limits;
 pub(crate) mod safety;
 pub mod seatbelt;
 pub mod shell;
 pub mod spawn;
+pub mod telemetry;
 pub mod terminal;
+pub mod tokenizer;
 mod tool_apply_patch;
 pub mod turn_diff_tracker;
+mod turn_snapshot;
+mod usage;
 pub mod user_agent;
 mod user_notification;
 pub mod util;
+pub mod windows_sandbox;
 pub use apply_patch::CODEX_APPLY_PATCH_ARG1;
 pub use safety::get_platform_sandbox;
+pub use windows_sandbox::CODEX_WINDOWS_SANDBOX_ARG1;
+pub use windows_sandbox::CODEX_WINDOWS_SANDBOX_REVOKE_ARG;
 // Re-export the protocol types from the standalone `codex-protocol` crate so existing
 // `codex_core::protocol::...` references continue to work across the workspace.
 pub use codex_protocol::protocol;
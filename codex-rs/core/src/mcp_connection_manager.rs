@@ -9,14 +9,22 @@
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::ffi::OsString;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::RwLock;
 use std::time::Duration;
 
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
+use codex_mcp_client::ConnectionState;
 use codex_mcp_client::McpClient;
+use codex_mcp_client::ReconnectPolicy;
+use codex_mcp_client::validate_tool_arguments;
 use mcp_types::ClientCapabilities;
+use mcp_types::ClientCapabilitiesRoots;
 use mcp_types::Implementation;
+use mcp_types::Root;
 use mcp_types::Tool;
 
 use serde_json::json;
@@ -26,7 +34,10 @@ use tokio::task::JoinSet;
 use tracing::info;
 use tracing::warn;
 
+use crate::config_types::McpSamplingPolicy;
 use crate::config_types::McpServerConfig;
+use crate::mcp_sampling::McpSampler;
+use crate::protocol::SandboxPolicy;
 
 /// Delimiter used to separate the server name from the tool name in a fully
 /// qualified tool name.
@@ -39,10 +50,57 @@ const MAX_TOOL_NAME_LENGTH: usize = 64;
 /// Timeout for the `tools/list` request.
 const LIST_TOOLS_TIMEOUT: Duration = Duration::from_secs(10);
 
+/// Timeout for the `initialize` request.
+const INITIALIZE_TIMEOUT: Option<Duration> = Some(Duration::from_secs(10));
+
 /// Map that holds a startup error for every MCP server that could **not** be
 /// spawned successfully.
 pub type ClientStartErrors = HashMap<String, anyhow::Error>;
 
+fn initialize_params(allow_sampling: bool) -> mcp_types::InitializeRequestParams {
+    mcp_types::InitializeRequestParams {
+        capabilities: ClientCapabilities {
+            experimental: None,
+            roots: Some(ClientCapabilitiesRoots {
+                list_changed: Some(true),
+            }),
+            sampling: allow_sampling.then(|| json!({})),
+            // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
+            // indicates this should be an empty object.
+            elicitation: Some(json!({})),
+        },
+        client_info: Implementation {
+            name: "codex-mcp-client".to_owned(),
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            title: Some("Codex".into()),
+        },
+        protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_owned(),
+    }
+}
+
+/// Start the server described by `config`, complete the `initialize`
+/// handshake and list its tools, e.g. for `codex doctor`. Returns the number
+/// of tools. The server is stopped again when this returns.
+pub async fn probe_mcp_server(config: McpServerConfig) -> Result<usize> {
+    let McpServerConfig {
+        command, args, env, ..
+    } = config;
+    let client = McpClient::new_stdio_client(
+        command.into(),
+        args.into_iter().map(OsString::from).collect(),
+        env,
+    )
+    .await?;
+    client
+        .initialize(initialize_params(false), None, INITIALIZE_TIMEOUT)
+        .await?;
+    let tools = client
+        .list_tools(None, Some(LIST_TOOLS_TIMEOUT))
+        .await
+        .context("tools/list failed")?;
+    Ok(tools.tools.len())
+}
+
 fn qualify_tools(tools: Vec<ToolInfo>) -> HashMap<String, ToolInfo> {
     let mut used_names = HashSet::new();
     let mut qualified_tools = HashMap::new();
@@ -90,8 +148,9 @@ pub(crate) struct McpConnectionManager {
     /// the user configuration.
     clients: HashMap<String, std::sync::Arc<McpClient>>,
 
-    /// Fully qualified tool name -> tool instance.
-    tools: HashMap<String, ToolInfo>,
+    /// Fully qualified tool name -> tool instance. Updated when a server is
+    /// respawned after exiting.
+    tools: Arc<RwLock<HashMap<String, ToolInfo>>>,
 }
 
 impl McpConnectionManager {
@@ -103,8 +162,13 @@ impl McpConnectionManager {
     ///
     /// Servers that fail to start are reported in `ClientStartErrors`: the
     /// user should be informed about these errors.
+    ///
+    /// Sampling requests from servers that allow it are answered by
+    /// `sampler`, and `roots/list` requests with `roots`.
     pub async fn new(
         mcp_servers: HashMap<String, McpServerConfig>,
+        sampler: Arc<McpSampler>,
+        roots: Vec<Root>,
     ) -> Result<(Self, ClientStartErrors)> {
         // Early exit if no servers are configured.
         if mcp_servers.is_empty() {
@@ -126,8 +190,15 @@ impl McpConnectionManager {
                 continue;
             }
 
+            let sampler = sampler.clone();
+            let roots = roots.clone();
             join_set.spawn(async move {
-                let McpServerConfig { command, args, env } = cfg;
+                let McpServerConfig {
+                    command,
+                    args,
+                    env,
+                    sampling,
+                } = cfg;
                 let client_res = McpClient::new_stdio_client(
                     command.into(),
                     args.into_iter().map(OsString::from).collect(),
@@ -136,27 +207,25 @@ impl McpConnectionManager {
                 .await;
                 match client_res {
                     Ok(client) => {
+                        let allow_sampling = sampling == McpSamplingPolicy::Allow;
+                        if allow_sampling {
+                            let server_name = server_name.clone();
+                            client.set_sampling_handler(move |params| {
+                                let sampler = sampler.clone();
+                                let server_name = server_name.clone();
+                                async move { sampler.create_message(&server_name, params).await }
+                            });
+                        }
+
+                        if let Err(e) = client.set_roots(roots).await {
+                            return (server_name, Err(e));
+                        }
+
                         // Initialize the client.
-                        let params = mcp_types::InitializeRequestParams {
-                            capabilities: ClientCapabilities {
-                                experimental: None,
-                                roots: None,
-                                sampling: None,
-                                // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
-                                // indicates this should be an empty object.
-                                elicitation: Some(json!({})),
-                            },
-                            client_info: Implementation {
-                                name: "codex-mcp-client".to_owned(),
-                                version: env!("CARGO_PKG_VERSION").to_owned(),
-                                title: Some("Codex".into()),
-                            },
-                            protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_owned(),
-                        };
+                        let params = initialize_params(allow_sampling);
                         let initialize_notification_params = None;
-                        let timeout = Some(Duration::from_secs(10));
                         match client
-                            .initialize(params, initialize_notification_params, timeout)
+                            .initialize(params, initialize_notification_params, INITIALIZE_TIMEOUT)
                             .await
                         {
                             Ok(_response) => (server_name, Ok(client)),
@@ -186,7 +255,41 @@ impl McpConnectionManager {
 
         let all_tools = list_all_tools(&clients).await?;
 
-        let tools = qualify_tools(all_tools);
+        let tools = Arc::new(RwLock::new(qualify_tools(all_tools)));
+
+        for (server_name, client) in &clients {
+            client.set_reconnect_policy(ReconnectPolicy::default());
+            client.on_connection_state_change({
+                let server_name = server_name.clone();
+                let tools = Arc::downgrade(&tools);
+                move |state| {
+                    let Some(tools) = tools.upgrade() else {
+                        return;
+                    };
+                    match state {
+                        ConnectionState::Reconnected {
+                            tools: server_tools,
+                        } => {
+                            info!("MCP server `{server_name}` was restarted");
+                            replace_server_tools(
+                                &mut write_tools(&tools),
+                                &server_name,
+                                server_tools.clone(),
+                            );
+                        }
+                        ConnectionState::Closed => {
+                            warn!("MCP server `{server_name}` exited and will not be restarted");
+                            replace_server_tools(
+                                &mut write_tools(&tools),
+                                &server_name,
+                                Vec::new(),
+                            );
+                        }
+                        ConnectionState::Disconnected | ConnectionState::Reconnecting { .. } => {}
+                    }
+                }
+            });
+        }
 
         Ok((Self { clients, tools }, errors))
     }
@@ -194,13 +297,15 @@ impl McpConnectionManager {
     /// Returns a single map that contains **all** tools. Each key is the
     /// fully-qualified name for the tool.
     pub fn list_all_tools(&self) -> HashMap<String, Tool> {
-        self.tools
+        read_tools(&self.tools)
             .iter()
             .map(|(name, tool)| (name.clone(), tool.tool.clone()))
             .collect()
     }
 
-    /// Invoke the tool indicated by the (server, tool) pair.
+    /// Invoke the tool indicated by the (server, tool) pair. Arguments that do
+    /// not match the tool's input schema are rejected without contacting the
+    /// server.
     pub async fn call_tool(
         &self,
         server: &str,
@@ -214,14 +319,30 @@ impl McpConnectionManager {
             .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?
             .clone();
 
+        if let Some(info) = read_tools(&self.tools)
+            .values()
+            .find(|info| info.server_name == server && info.tool_name == tool)
+        {
+            validate_tool_arguments(&info.tool, arguments.as_ref())?;
+        }
+
         client
             .call_tool(tool.to_string(), arguments, timeout)
             .await
             .with_context(|| format!("tool call failed for `{server}/{tool}`"))
     }
 
+    /// Reports new workspace roots to every server that is still running.
+    pub async fn set_roots(&self, roots: Vec<Root>) {
+        for (server_name, client) in &self.clients {
+            if let Err(e) = client.set_roots(roots.clone()).await {
+                warn!("failed to update the roots of MCP server `{server_name}`: {e:#}");
+            }
+        }
+    }
+
     pub fn parse_tool_name(&self, tool_name: &str) -> Option<(String, String)> {
-        self.tools
+        read_tools(&self.tools)
             .get(tool_name)
             .map(|tool| (tool.server_name.clone(), tool.tool_name.clone()))
     }
@@ -273,6 +394,67 @@ async fn list_all_tools(
     Ok(aggregated)
 }
 
+/// The roots reported to MCP servers: the session's working directory
+/// followed by any additional writable roots of the sandbox. Temporary
+/// directories the sandbox also allows writing to are left out.
+pub(crate) fn mcp_roots(cwd: &Path, sandbox_policy: &SandboxPolicy) -> Vec<Root> {
+    let mut paths = vec![cwd.to_path_buf()];
+    if let SandboxPolicy::WorkspaceWrite { writable_roots, .. } = sandbox_policy {
+        for root in writable_roots {
+            if !paths.contains(root) {
+                paths.push(root.clone());
+            }
+        }
+    }
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let uri = url::Url::from_file_path(&path).ok()?;
+            Some(Root {
+                name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned()),
+                uri: uri.to_string(),
+            })
+        })
+        .collect()
+}
+
+/// Swaps the tools of `server_name` for `server_tools`, re-qualifying the
+/// names of every server so collisions are resolved the same way as at
+/// startup.
+fn replace_server_tools(
+    tools: &mut HashMap<String, ToolInfo>,
+    server_name: &str,
+    server_tools: Vec<Tool>,
+) {
+    let others = std::mem::take(tools)
+        .into_values()
+        .filter(|info| info.server_name != server_name);
+    let replacements = server_tools.into_iter().map(|tool| ToolInfo {
+        server_name: server_name.to_string(),
+        tool_name: tool.name.clone(),
+        tool,
+    });
+    *tools = qualify_tools(others.chain(replacements).collect());
+}
+
+fn read_tools(
+    tools: &RwLock<HashMap<String, ToolInfo>>,
+) -> std::sync::RwLockReadGuard<'_, HashMap<String, ToolInfo>> {
+    tools
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+fn write_tools(
+    tools: &RwLock<HashMap<String, ToolInfo>>,
+) -> std::sync::RwLockWriteGuard<'_, HashMap<String, ToolInfo>> {
+    tools
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
 fn is_valid_mcp_server_name(server_name: &str) -> bool {
     !server_name.is_empty()
         && server_name
@@ -332,6 +514,56 @@ mod tests {
         assert!(qualified_tools.contains_key("server1__duplicate_tool"));
     }
 
+    #[test]
+    fn test_replace_server_tools_keeps_other_servers() {
+        let mut tools = qualify_tools(vec![
+            create_test_tool("server1", "old_tool"),
+            create_test_tool("server2", "other_tool"),
+        ]);
+
+        let new_tool = create_test_tool("server1", "new_tool").tool;
+        replace_server_tools(&mut tools, "server1", vec![new_tool]);
+
+        let mut keys: Vec<_> = tools.keys().cloned().collect();
+        keys.sort();
+        assert_eq!(keys, vec!["server1__new_tool", "server2__other_tool"]);
+
+        replace_server_tools(&mut tools, "server1", Vec::new());
+        let keys: Vec<_> = tools.keys().cloned().collect();
+        assert_eq!(keys, vec!["server2__other_tool"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_mcp_roots_lists_cwd_then_writable_roots() {
+        let cwd = Path::new("/work/my project");
+        let sandbox_policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![cwd.to_path_buf(), "/data".into()],
+            network_access: false,
+            exclude_tmpdir_env_var: false,
+            exclude_slash_tmp: false,
+            read_blocklist: Vec::new(),
+        };
+
+        assert_eq!(
+            mcp_roots(cwd, &sandbox_policy),
+            vec![
+                Root {
+                    name: Some("my project".to_string()),
+                    uri: "file:///work/my%20project".to_string(),
+                },
+                Root {
+                    name: Some("data".to_string()),
+                    uri: "file:///data".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            mcp_roots(cwd, &SandboxPolicy::new_read_only_policy()).len(),
+            1
+        );
+    }
+
     #[test]
     fn test_qualify_tools_long_names_same_server() {
         let server_name = "my_server";
@@ -0,0 +1,248 @@
+//! Answers `sampling/createMessage` requests from MCP servers with the
+//! session's model.
+//!
+//! Only servers configured with `sampling = "allow"` advertise the sampling
+//! capability and get a handler; requests from any other server are rejected
+//! by the MCP client. The request's messages are sent as-is, with the
+//! server's system prompt in place of Codex's instructions and without tools
+//! or conversation history, and the model may generate at most the request's
+//! `maxTokens`.
+
+use std::sync::OnceLock;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use futures::prelude::*;
+use mcp_types::CreateMessageRequestParams;
+use mcp_types::CreateMessageResult;
+use mcp_types::CreateMessageResultContent;
+use mcp_types::Role;
+use mcp_types::SamplingMessageContent;
+use mcp_types::TextContent;
+use tracing::info;
+
+use crate::client::ModelClient;
+use crate::client_common::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::error::CodexErr;
+use crate::error::Result as CodexResult;
+
+/// Shared by the sampling handlers of every MCP server. The model client is
+/// set once the session has created it, which happens after the servers are
+/// started.
+pub(crate) struct McpSampler {
+    client: OnceLock<ModelClient>,
+    store: bool,
+}
+
+impl McpSampler {
+    pub(crate) fn new(store: bool) -> Self {
+        Self {
+            client: OnceLock::new(),
+            store,
+        }
+    }
+
+    pub(crate) fn set_client(&self, client: ModelClient) {
+        let _ = self.client.set(client);
+    }
+
+    pub(crate) async fn create_message(
+        &self,
+        server: &str,
+        params: CreateMessageRequestParams,
+    ) -> Result<CreateMessageResult, String> {
+        let Some(client) = self.client.get() else {
+            return Err("the Codex session is still starting".to_string());
+        };
+        let prompt = build_prompt(params, self.store)?;
+        info!("answering sampling request from MCP server `{server}`");
+        let text = complete(client, &prompt)
+            .await
+            .map_err(|e| format!("model request failed: {e}"))?;
+        Ok(CreateMessageResult {
+            content: CreateMessageResultContent::TextContent(TextContent {
+                annotations: None,
+                text,
+                r#type: "text".to_string(),
+            }),
+            model: client.get_model(),
+            role: Role::Assistant,
+            stop_reason: Some("endTurn".to_string()),
+        })
+    }
+}
+
+fn build_prompt(params: CreateMessageRequestParams, store: bool) -> Result<Prompt, String> {
+    let max_output_tokens = u64::try_from(params.max_tokens)
+        .map_err(|_| format!("invalid maxTokens: {}", params.max_tokens))?;
+    let mut input = Vec::with_capacity(params.messages.len());
+    for message in params.messages {
+        let role = match message.role {
+            Role::User => "user",
+            Role::Assistant => "assistant",
+        };
+        let content = match (&message.role, message.content) {
+            (Role::User, SamplingMessageContent::TextContent(text)) => {
+                ContentItem::InputText { text: text.text }
+            }
+            (Role::Assistant, SamplingMessageContent::TextContent(text)) => {
+                ContentItem::OutputText { text: text.text }
+            }
+            (Role::User, SamplingMessageContent::ImageContent(image)) => ContentItem::InputImage {
+                image_url: format!("data:{};base64,{}", image.mime_type, image.data),
+            },
+            (Role::Assistant, SamplingMessageContent::ImageContent(_)) => {
+                return Err("images are only supported in user messages".to_string());
+            }
+            (_, SamplingMessageContent::AudioContent(_)) => {
+                return Err("audio content is not supported".to_string());
+            }
+        };
+        input.push(ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![content],
+        });
+    }
+    Ok(Prompt {
+        input,
+        store,
+        tools: Vec::new(),
+        base_instructions_override: Some(params.system_prompt.unwrap_or_default()),
+        max_output_tokens: Some(max_output_tokens),
+    })
+}
+
+/// Streams `prompt` and returns the text of the final assistant message.
+async fn complete(client: &ModelClient, prompt: &Prompt) -> CodexResult<String> {
+    let mut stream = client.clone().stream(prompt).await?;
+    let mut text = String::new();
+    loop {
+        let Some(event) = stream.next().await else {
+            return Err(CodexErr::Stream(
+                "stream closed before response.completed".into(),
+                None,
+            ));
+        };
+        match event? {
+            ResponseEvent::OutputItemDone(ResponseItem::Message { role, content, .. })
+                if role == "assistant" =>
+            {
+                text = content
+                    .iter()
+                    .filter_map(|c| match c {
+                        ContentItem::OutputText { text } => Some(text.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+            }
+            ResponseEvent::Completed { .. } => return Ok(text),
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::ImageContent;
+    use mcp_types::SamplingMessage;
+    use pretty_assertions::assert_eq;
+
+    fn text(role: Role, text: &str) -> SamplingMessage {
+        SamplingMessage {
+            content: SamplingMessageContent::TextContent(TextContent {
+                annotations: None,
+                text: text.to_string(),
+                r#type: "text".to_string(),
+            }),
+            role,
+        }
+    }
+
+    fn params(messages: Vec<SamplingMessage>) -> CreateMessageRequestParams {
+        CreateMessageRequestParams {
+            include_context: None,
+            max_tokens: 100,
+            messages,
+            metadata: None,
+            model_preferences: None,
+            stop_sequences: None,
+            system_prompt: Some("Be brief.".to_string()),
+            temperature: None,
+        }
+    }
+
+    #[test]
+    fn build_prompt_maps_roles_and_system_prompt() {
+        let image = SamplingMessage {
+            content: SamplingMessageContent::ImageContent(ImageContent {
+                annotations: None,
+                data: "AAAA".to_string(),
+                mime_type: "image/png".to_string(),
+                r#type: "image".to_string(),
+            }),
+            role: Role::User,
+        };
+        let prompt = build_prompt(
+            params(vec![
+                text(Role::User, "question"),
+                text(Role::Assistant, "answer"),
+                image,
+            ]),
+            false,
+        )
+        .expect("valid prompt");
+
+        assert_eq!(
+            prompt.base_instructions_override.as_deref(),
+            Some("Be brief.")
+        );
+        assert!(prompt.tools.is_empty());
+        assert_eq!(prompt.max_output_tokens, Some(100));
+        assert_eq!(
+            prompt.input,
+            vec![
+                ResponseItem::Message {
+                    id: None,
+                    role: "user".to_string(),
+                    content: vec![ContentItem::InputText {
+                        text: "question".to_string()
+                    }],
+                },
+                ResponseItem::Message {
+                    id: None,
+                    role: "assistant".to_string(),
+                    content: vec![ContentItem::OutputText {
+                        text: "answer".to_string()
+                    }],
+                },
+                ResponseItem::Message {
+                    id: None,
+                    role: "user".to_string(),
+                    content: vec![ContentItem::InputImage {
+                        image_url: "data:image/png;base64,AAAA".to_string()
+                    }],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn build_prompt_rejects_assistant_images() {
+        let image = SamplingMessage {
+            content: SamplingMessageContent::ImageContent(ImageContent {
+                annotations: None,
+                data: "AAAA".to_string(),
+                mime_type: "image/png".to_string(),
+                r#type: "image".to_string(),
+            }),
+            role: Role::Assistant,
+        };
+        let Err(err) = build_prompt(params(vec![image]), false) else {
+            panic!("assistant images must be rejected");
+        };
+        assert_eq!(err, "images are only supported in user messages");
+    }
+}
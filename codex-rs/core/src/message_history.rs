@@ -13,6 +13,9 @@
 //! trailing `\n`) and write it with a **single `write(2)` system call** while
 //! the file descriptor is opened with the `O_APPEND` flag. POSIX guarantees
 //! that writes up to `PIPE_BUF` bytes are atomic in that case.
+//!
+//! When `[encryption]` is enabled each line is encrypted instead; see
+//! [`crate::encryption`].
 
 use std::fs::File;
 use std::fs::OpenOptions;
@@ -29,6 +32,8 @@ use uuid::Uuid;
 
 use crate::config::Config;
 use crate::config_types::HistoryPersistence;
+use crate::encryption::LineCipher;
+use crate::encryption::decode_line;
 
 #[cfg(unix)]
 use std::os::unix::fs::OpenOptionsExt;
@@ -90,6 +95,9 @@ pub(crate) async fn append_entry(text: &str, session_id: &Uuid, config: &Config)
     };
     let mut line = serde_json::to_string(&entry)
         .map_err(|e| std::io::Error::other(format!("failed to serialise history entry: {e}")))?;
+    if config.encryption.enabled {
+        line = LineCipher::from_keychain()?.encrypt_line(&line)?;
+    }
     line.push('\n');
 
     // Open in append-only mode.
@@ -227,6 +235,7 @@ pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<Hist
         return None;
     }
 
+    let mut cipher = None;
     let reader = BufReader::new(&file);
     for (idx, line_res) in reader.lines().enumerate() {
         let line = match line_res {
@@ -238,6 +247,13 @@ pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<Hist
         };
 
         if idx == offset {
+            let line = match decode_line(&line, &mut cipher) {
+                Ok(line) => line,
+                Err(e) => {
+                    tracing::warn!(error = %e, "failed to decrypt history entry");
+                    return None;
+                }
+            };
             match serde_json::from_str::<HistoryEntry>(&line) {
                 Ok(entry) => return Some(entry),
                 Err(e) => {
@@ -1,3 +1,4 @@
+use crate::tokenizer::TokenizerKind;
 use crate::tool_apply_patch::ApplyPatchToolType;
 
 /// A model family is a group of models that share certain characteristics.
@@ -29,6 +30,18 @@ pub struct ModelFamily {
     /// Present if the model performs better when `apply_patch` is provided as
     /// a tool call instead of just a bash command
     pub apply_patch_tool_type: Option<ApplyPatchToolType>,
+
+    /// The vocabulary used to count tokens for context accounting and
+    /// output truncation.
+    pub tokenizer: TokenizerKind,
+
+    /// False for models that cannot call tools; Chat Completions requests to
+    /// them carry no tools.
+    pub supports_tools: bool,
+
+    /// Stop sequences sent with Chat Completions requests, for local models
+    /// whose chat template needs them.
+    pub stop_sequences: Vec<String>,
 }
 
 macro_rules! model_family {
@@ -43,6 +56,9 @@ macro_rules! model_family {
             supports_reasoning_summaries: false,
             uses_local_shell_tool: false,
             apply_patch_tool_type: None,
+            tokenizer: TokenizerKind::O200k,
+            supports_tools: true,
+            stop_sequences: Vec::new(),
         };
         // apply overrides
         $(
@@ -54,7 +70,7 @@ macro_rules! model_family {
 
 macro_rules! simple_model_family {
     (
-        $slug:expr, $family:expr
+        $slug:expr, $family:expr, $tokenizer:expr
     ) => {{
         Some(ModelFamily {
             slug: $slug.to_string(),
@@ -63,10 +79,30 @@ macro_rules! simple_model_family {
             supports_reasoning_summaries: false,
             uses_local_shell_tool: false,
             apply_patch_tool_type: None,
+            tokenizer: $tokenizer,
+            supports_tools: true,
+            stop_sequences: Vec::new(),
         })
     }};
 }
 
+/// The family of a model that [`find_family_for_model`] does not know.
+pub fn default_model_family(slug: &str, supports_reasoning_summaries: bool) -> ModelFamily {
+    ModelFamily {
+        slug: slug.to_string(),
+        family: slug.to_string(),
+        needs_special_apply_patch_instructions: false,
+        supports_reasoning_summaries,
+        uses_local_shell_tool: false,
+        apply_patch_tool_type: None,
+        // Models without a known family are most likely open-weight models
+        // served by an OSS provider.
+        tokenizer: TokenizerKind::SentencePiece,
+        supports_tools: true,
+        stop_sequences: Vec::new(),
+    }
+}
+
 /// Returns a `ModelFamily` for the given model slug, or `None` if the slug
 /// does not match any known model family.
 pub fn find_family_for_model(slug: &str) -> Option<ModelFamily> {
@@ -99,9 +135,9 @@ pub fn find_family_for_model(slug: &str) -> Option<ModelFamily> {
     } else if slug.starts_with("gpt-oss") {
         model_family!(slug, "gpt-oss", apply_patch_tool_type: Some(ApplyPatchToolType::Function))
     } else if slug.starts_with("gpt-4o") {
-        simple_model_family!(slug, "gpt-4o")
+        simple_model_family!(slug, "gpt-4o", TokenizerKind::O200k)
     } else if slug.starts_with("gpt-3.5") {
-        simple_model_family!(slug, "gpt-3.5")
+        simple_model_family!(slug, "gpt-3.5", TokenizerKind::Cl100k)
     } else if slug.starts_with("gpt-5") {
         model_family!(
             slug, "gpt-5",
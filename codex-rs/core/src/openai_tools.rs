@@ -519,6 +519,9 @@ pub(crate) fn get_openai_tools(
             tools.push(OpenAiTool::Function(
                 crate::exec_command::create_write_stdin_tool_for_responses_api(),
             ));
+            tools.push(OpenAiTool::Function(
+                crate::exec_command::create_close_session_tool_for_responses_api(),
+            ));
         }
     }
 
@@ -0,0 +1,265 @@
+//! Truncation of long command output that keeps the lines most likely to
+//! matter to the model: errors, warnings and stack traces, with a few lines of
+//! context around each, plus the start and end of the output.
+//!
+//! This is a line-level heuristic and needs no model call, so it is cheap
+//! enough to run on every tool result.
+
+use std::sync::LazyLock;
+
+use regex_lite::Regex;
+
+/// Lines always kept from the start of the output (the command's banner or
+/// first diagnostics).
+const HEAD_LINES: usize = 8;
+/// Lines always kept from the end of the output, where summaries such as
+/// `test result:` or `Build FAILED` usually are.
+const TAIL_LINES: usize = 32;
+/// Lines kept before and after each salient line.
+const CONTEXT_LINES: usize = 2;
+/// Upper bound on the size of one `[... omitted N lines ...]` marker.
+const MARKER_RESERVE: usize = 48;
+
+#[expect(clippy::unwrap_used)]
+static ERROR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)\berror\b|\berr!|\bfailed\b|\bfailure\b|\bfatal\b|\bpanic(?:ked)?\b|exception\b|traceback \(most recent call last\)|segmentation fault|assertion.*failed|undefined reference|\bfail\b",
+    )
+    .unwrap()
+});
+
+#[expect(clippy::unwrap_used)]
+static WARNING_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)\bwarn(?:ing)?\b|\bdeprecated\b").unwrap());
+
+/// Frames of Python, JavaScript/Java, Rust and Go stack traces, and the
+/// location lines rustc and many linters print under a diagnostic.
+#[expect(clippy::unwrap_used)]
+static FRAME_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"^\s*(?:File "[^"]+", line \d+|at \S+|\d+: \S+|#\d+ |-->\s+\S+:\d+|Caused by\b|goroutine \d+ )"#,
+    )
+    .unwrap()
+});
+
+/// Relevance of a single line, in increasing order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Salience {
+    None,
+    Warning,
+    Frame,
+    Error,
+}
+
+fn classify(line: &str) -> Salience {
+    if ERROR_RE.is_match(line) {
+        Salience::Error
+    } else if FRAME_RE.is_match(line) {
+        Salience::Frame
+    } else if WARNING_RE.is_match(line) {
+        Salience::Warning
+    } else {
+        Salience::None
+    }
+}
+
+/// Reduce `text` to at most `max_bytes` bytes and `max_lines` lines, keeping
+/// the most relevant lines in their original order. Runs of dropped lines are
+/// replaced with an `[... omitted N lines ...]` marker.
+pub(crate) fn select_salient_lines(text: &str, max_bytes: usize, max_lines: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut selection = Selection {
+        lines: &lines,
+        kept: vec![false; lines.len()],
+        kept_lines: 0,
+        // One marker for the gap between the head and the tail.
+        used_bytes: MARKER_RESERVE,
+        max_bytes,
+        max_lines,
+    };
+
+    let head_end = HEAD_LINES.min(lines.len());
+    for index in 0..head_end {
+        selection.try_keep(index, index + 1);
+    }
+    let tail_start = lines.len().saturating_sub(TAIL_LINES).max(head_end);
+    for index in tail_start..lines.len() {
+        selection.try_keep(index, index + 1);
+    }
+
+    // Most relevant first; among equals, earlier first, since the first error
+    // is usually the cause of the rest.
+    let mut candidates: Vec<(Salience, usize)> = lines
+        .iter()
+        .enumerate()
+        .map(|(index, line)| (classify(line), index))
+        .filter(|(salience, _)| *salience != Salience::None)
+        .collect();
+    candidates.sort_by(|a, b| b.0.cmp(&a.0).then(a.1.cmp(&b.1)));
+    for (_, index) in candidates {
+        let start = index.saturating_sub(CONTEXT_LINES);
+        let end = (index + CONTEXT_LINES + 1).min(lines.len());
+        if !selection.try_keep(start, end) {
+            // Fall back to the line on its own.
+            selection.try_keep(index, index + 1);
+        }
+    }
+
+    let output = selection.render();
+    if output.is_empty() && !text.is_empty() {
+        // Not even one line fits (e.g. minified output on a single line).
+        return take_prefix(text, max_bytes).to_string();
+    }
+    output
+}
+
+struct Selection<'a> {
+    lines: &'a [&'a str],
+    kept: Vec<bool>,
+    kept_lines: usize,
+    used_bytes: usize,
+    max_bytes: usize,
+    max_lines: usize,
+}
+
+impl Selection<'_> {
+    /// Keep lines `start..end` if they all fit in the remaining budget.
+    /// Returns whether they were kept.
+    fn try_keep(&mut self, start: usize, end: usize) -> bool {
+        let new: Vec<usize> = (start..end).filter(|&i| !self.kept[i]).collect();
+        if new.is_empty() {
+            return true;
+        }
+        let mut bytes = new.iter().map(|&i| self.lines[i].len() + 1).sum::<usize>();
+        // A block with dropped lines on both sides splits a gap in two, which
+        // adds a marker.
+        let dropped_before = start > 0 && !self.kept[start - 1];
+        let dropped_after = end < self.lines.len() && !self.kept[end];
+        if dropped_before && dropped_after {
+            bytes += MARKER_RESERVE;
+        }
+        if self.kept_lines + new.len() > self.max_lines || self.used_bytes + bytes > self.max_bytes
+        {
+            return false;
+        }
+        for i in new {
+            self.kept[i] = true;
+            self.kept_lines += 1;
+        }
+        self.used_bytes += bytes;
+        true
+    }
+
+    fn render(&self) -> String {
+        let mut out = String::with_capacity(self.used_bytes);
+        let mut omitted = 0;
+        for (line, kept) in self.lines.iter().zip(&self.kept) {
+            if !kept {
+                omitted += 1;
+                continue;
+            }
+            if omitted > 0 {
+                out.push_str(&format!("[... omitted {omitted} lines ...]\n"));
+                omitted = 0;
+            }
+            out.push_str(line);
+            out.push('\n');
+        }
+        if omitted > 0 && omitted < self.lines.len() {
+            out.push_str(&format!("[... omitted {omitted} lines ...]\n"));
+        }
+        if out.ends_with('\n') {
+            out.pop();
+        }
+        out
+    }
+}
+
+fn take_prefix(s: &str, max_bytes: usize) -> &str {
+    if s.len() <= max_bytes {
+        return s;
+    }
+    let mut end = max_bytes;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn build_log(noise_lines: usize, insert_at: usize, block: &[&str]) -> String {
+        let mut lines: Vec<String> = (0..noise_lines)
+            .map(|i| format!("   Compiling crate-{i} v0.1.0"))
+            .collect();
+        for (offset, line) in block.iter().enumerate() {
+            lines.insert(insert_at + offset, line.to_string());
+        }
+        lines.join("\n")
+    }
+
+    #[test]
+    fn classifies_errors_frames_and_warnings() {
+        assert_eq!(classify("error[E0308]: mismatched types"), Salience::Error);
+        assert_eq!(
+            classify("thread 'main' panicked at src/main.rs:2:5"),
+            Salience::Error
+        );
+        assert_eq!(
+            classify(r#"  File "app.py", line 3, in <module>"#),
+            Salience::Frame
+        );
+        assert_eq!(
+            classify("    at Object.<anonymous> (index.js:1:7)"),
+            Salience::Frame
+        );
+        assert_eq!(classify("  --> src/lib.rs:10:5"), Salience::Frame);
+        assert_eq!(classify("warning: unused variable: `x`"), Salience::Warning);
+        assert_eq!(classify("   Compiling serde v1.0.0"), Salience::None);
+    }
+
+    #[test]
+    fn keeps_an_error_buried_in_the_middle_of_a_long_log() {
+        let log = build_log(
+            2_000,
+            1_000,
+            &[
+                "error[E0425]: cannot find value `x` in this scope",
+                "  --> src/main.rs:4:13",
+            ],
+        );
+
+        let out = select_salient_lines(&log, 10 * 1024, 256);
+
+        assert!(out.len() <= 10 * 1024);
+        assert!(out.lines().count() <= 256 + 8);
+        assert!(out.contains("error[E0425]: cannot find value `x` in this scope"));
+        assert!(out.contains("  --> src/main.rs:4:13"));
+        // Context around the error and both ends of the log are kept.
+        assert!(out.contains("Compiling crate-999 v0.1.0"));
+        assert!(out.starts_with("   Compiling crate-0 v0.1.0\n"));
+        assert!(out.ends_with("Compiling crate-1999 v0.1.0"));
+        assert!(out.contains("[... omitted "));
+    }
+
+    #[test]
+    fn errors_win_over_warnings_when_the_budget_is_tight() {
+        let mut block = vec!["warning: unused import"; 50];
+        block.push("error: could not compile `app`");
+        let log = build_log(1_000, 500, &block);
+
+        let out = select_salient_lines(&log, 4 * 1024, 64);
+
+        assert!(out.len() <= 4 * 1024);
+        assert!(out.contains("error: could not compile `app`"));
+    }
+
+    #[test]
+    fn falls_back_to_a_prefix_when_no_line_fits() {
+        let text = "x".repeat(1_000);
+        assert_eq!(select_salient_lines(&text, 100, 10), "x".repeat(100));
+    }
+}
@@ -11,8 +11,17 @@
 //!     current working directory (inclusive) and concatenate their contents in
 //!     that order.
 //! 3.  We do **not** walk past the Git root.
+//!
+//! Later files take precedence over earlier ones, so the file closest to the
+//! working directory wins when instructions conflict.
+//!
+//! During a session, `AGENTS.md` files in subdirectories of the working
+//! directory are loaded once the agent changes a file below them, see
+//! [`nested_doc_paths`]. They take precedence over the files loaded at the
+//! start of the session, for the files under their directory.
 
 use crate::config::Config;
+use std::path::Path;
 use std::path::PathBuf;
 use tokio::io::AsyncReadExt;
 use tracing::error;
@@ -24,20 +33,46 @@ const CANDIDATE_FILENAMES: &[&str] = &["AGENTS.md"];
 /// be concatenated with the following separator.
 const PROJECT_DOC_SEPARATOR: &str = "\n\n--- project-doc ---\n\n";
 
+/// The user instructions for a session and the project docs they include.
+#[derive(Debug, Default)]
+pub(crate) struct UserInstructions {
+    pub(crate) text: Option<String>,
+    /// `AGENTS.md` files included in `text`, from the repository root down to
+    /// the working directory.
+    pub(crate) project_docs: Vec<PathBuf>,
+}
+
 /// Combines `Config::instructions` and `AGENTS.md` (if present) into a single
 /// string of instructions.
-pub(crate) async fn get_user_instructions(config: &Config) -> Option<String> {
-    match read_project_docs(config).await {
-        Ok(Some(project_doc)) => match &config.user_instructions {
-            Some(original_instructions) => Some(format!(
-                "{original_instructions}{PROJECT_DOC_SEPARATOR}{project_doc}"
-            )),
-            None => Some(project_doc),
+pub(crate) async fn get_user_instructions(config: &Config) -> UserInstructions {
+    let docs = match discover_project_doc_paths(config) {
+        Ok(paths) => read_docs(paths, config.project_doc_max_bytes).await,
+        Err(e) => Err(e),
+    };
+    match docs {
+        Ok(docs) if !docs.is_empty() => {
+            let project_doc = join_docs(&docs);
+            let text = match &config.user_instructions {
+                Some(original_instructions) => {
+                    format!("{original_instructions}{PROJECT_DOC_SEPARATOR}{project_doc}")
+                }
+                None => project_doc,
+            };
+            UserInstructions {
+                text: Some(text),
+                project_docs: docs.into_iter().map(|(path, _)| path).collect(),
+            }
+        }
+        Ok(_) => UserInstructions {
+            text: config.user_instructions.clone(),
+            project_docs: Vec::new(),
         },
-        Ok(None) => config.user_instructions.clone(),
         Err(e) => {
             error!("error trying to find project doc: {e:#}");
-            config.user_instructions.clone()
+            UserInstructions {
+                text: config.user_instructions.clone(),
+                project_docs: Vec::new(),
+            }
         }
     }
 }
@@ -49,19 +84,30 @@ pub(crate) async fn get_user_instructions(config: &Config) -> Option<String> {
 /// function returns `Ok(None)`. Unexpected I/O failures bubble up as `Err` so
 /// callers can decide how to handle them.
 pub async fn read_project_docs(config: &Config) -> std::io::Result<Option<String>> {
-    let max_total = config.project_doc_max_bytes;
-
-    if max_total == 0 {
-        return Ok(None);
-    }
-
     let paths = discover_project_doc_paths(config)?;
-    if paths.is_empty() {
-        return Ok(None);
+    let docs = read_docs(paths, config.project_doc_max_bytes).await?;
+    if docs.is_empty() {
+        Ok(None)
+    } else {
+        Ok(Some(join_docs(&docs)))
     }
+}
+
+fn join_docs(docs: &[(PathBuf, String)]) -> String {
+    docs.iter()
+        .map(|(_, text)| text.as_str())
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
 
+/// Read `paths` in order, stopping once `max_total` bytes have been read.
+/// Missing and empty files are skipped.
+async fn read_docs(
+    paths: Vec<PathBuf>,
+    max_total: usize,
+) -> std::io::Result<Vec<(PathBuf, String)>> {
     let mut remaining: u64 = max_total as u64;
-    let mut parts: Vec<String> = Vec::new();
+    let mut parts: Vec<(PathBuf, String)> = Vec::new();
 
     for p in paths {
         if remaining == 0 {
@@ -89,16 +135,63 @@ pub async fn read_project_docs(config: &Config) -> std::io::Result<Option<String
 
         let text = String::from_utf8_lossy(&data).to_string();
         if !text.trim().is_empty() {
-            parts.push(text);
+            parts.push((p, text));
             remaining = remaining.saturating_sub(data.len() as u64);
         }
     }
 
-    if parts.is_empty() {
-        Ok(None)
-    } else {
-        Ok(Some(parts.join("\n\n")))
+    Ok(parts)
+}
+
+/// `AGENTS.md` files in the directories strictly below `cwd` that contain
+/// one of `touched`, ordered from the outermost directory inwards and without
+/// duplicates. Paths outside `cwd` are ignored: the docs above it were loaded
+/// at the start of the session.
+pub(crate) fn nested_doc_paths(cwd: &Path, touched: &[PathBuf]) -> Vec<PathBuf> {
+    let cwd = cwd.canonicalize().unwrap_or_else(|_| cwd.to_path_buf());
+    let mut found: Vec<PathBuf> = Vec::new();
+    for path in touched {
+        let path = cwd.join(path);
+        // The file itself may have been deleted; its directory is what
+        // matters.
+        let Some(dir) = path.parent() else {
+            continue;
+        };
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        let Ok(relative) = dir.strip_prefix(&cwd) else {
+            continue;
+        };
+        let mut current = cwd.clone();
+        for component in relative.components() {
+            current.push(component);
+            for name in CANDIDATE_FILENAMES {
+                let candidate = current.join(name);
+                if candidate.is_file() && !found.contains(&candidate) {
+                    found.push(candidate);
+                }
+            }
+        }
     }
+    found
+}
+
+/// Read the `AGENTS.md` at `path`, limited to `max_bytes`, and wrap it with a
+/// note on where it applies. Returns `None` for an empty or missing file.
+pub(crate) async fn read_nested_doc(
+    path: &Path,
+    max_bytes: usize,
+) -> std::io::Result<Option<String>> {
+    let docs = read_docs(vec![path.to_path_buf()], max_bytes).await?;
+    let dir = path.parent().unwrap_or(path);
+    Ok(docs.into_iter().next().map(|(_, text)| {
+        format!(
+            "Instructions from {}. They apply to files under {} and take precedence over \
+             AGENTS.md files in its parent directories.\n\n{}",
+            path.display(),
+            dir.display(),
+            text.trim_end()
+        )
+    }))
 }
 
 /// Discover the list of AGENTS.md files using the same search rules as
@@ -207,7 +300,9 @@ mod tests {
     async fn no_doc_file_returns_none() {
         let tmp = tempfile::tempdir().expect("tempdir");
 
-        let res = get_user_instructions(&make_config(&tmp, 4096, None)).await;
+        let res = get_user_instructions(&make_config(&tmp, 4096, None))
+            .await
+            .text;
         assert!(
             res.is_none(),
             "Expected None when AGENTS.md is absent and no system instructions provided"
@@ -223,6 +318,7 @@ mod tests {
 
         let res = get_user_instructions(&make_config(&tmp, 4096, None))
             .await
+            .text
             .expect("doc expected");
 
         assert_eq!(
@@ -242,6 +338,7 @@ mod tests {
 
         let res = get_user_instructions(&make_config(&tmp, LIMIT, None))
             .await
+            .text
             .expect("doc expected");
 
         assert_eq!(res.len(), LIMIT, "doc should be truncated to LIMIT bytes");
@@ -272,7 +369,10 @@ mod tests {
         let mut cfg = make_config(&repo, 4096, None);
         cfg.cwd = nested;
 
-        let res = get_user_instructions(&cfg).await.expect("doc expected");
+        let res = get_user_instructions(&cfg)
+            .await
+            .text
+            .expect("doc expected");
         assert_eq!(res, "root level doc");
     }
 
@@ -282,7 +382,9 @@ mod tests {
         let tmp = tempfile::tempdir().expect("tempdir");
         fs::write(tmp.path().join("AGENTS.md"), "something").unwrap();
 
-        let res = get_user_instructions(&make_config(&tmp, 0, None)).await;
+        let res = get_user_instructions(&make_config(&tmp, 0, None))
+            .await
+            .text;
         assert!(
             res.is_none(),
             "With limit 0 the function should return None"
@@ -300,6 +402,7 @@ mod tests {
 
         let res = get_user_instructions(&make_config(&tmp, 4096, Some(INSTRUCTIONS)))
             .await
+            .text
             .expect("should produce a combined instruction string");
 
         let expected = format!("{INSTRUCTIONS}{PROJECT_DOC_SEPARATOR}{}", "proj doc");
@@ -315,7 +418,9 @@ mod tests {
 
         const INSTRUCTIONS: &str = "some instructions";
 
-        let res = get_user_instructions(&make_config(&tmp, 4096, Some(INSTRUCTIONS))).await;
+        let res = get_user_instructions(&make_config(&tmp, 4096, Some(INSTRUCTIONS)))
+            .await
+            .text;
 
         assert_eq!(res, Some(INSTRUCTIONS.to_string()));
     }
@@ -342,9 +447,59 @@ mod tests {
         fs::write(nested.join("AGENTS.md"), "crate doc").unwrap();
 
         let mut cfg = make_config(&repo, 4096, None);
-        cfg.cwd = nested;
+        cfg.cwd = nested.clone();
 
-        let res = get_user_instructions(&cfg).await.expect("doc expected");
-        assert_eq!(res, "root doc\n\ncrate doc");
+        let instructions = get_user_instructions(&cfg).await;
+        assert_eq!(instructions.text.as_deref(), Some("root doc\n\ncrate doc"));
+        let repo_root = repo.path().canonicalize().unwrap();
+        assert_eq!(
+            instructions.project_docs,
+            vec![
+                repo_root.join("AGENTS.md"),
+                repo_root.join("workspace/crate_a/AGENTS.md"),
+            ]
+        );
+    }
+
+    /// Docs below `cwd` are found for the files touched under them, from the
+    /// outermost directory inwards.
+    #[tokio::test]
+    async fn finds_nested_docs_for_touched_files() {
+        let repo = tempfile::tempdir().expect("tempdir");
+        let root = repo.path().canonicalize().unwrap();
+        fs::write(root.join("AGENTS.md"), "root doc").unwrap();
+        fs::create_dir_all(root.join("crates/app/src")).unwrap();
+        fs::create_dir_all(root.join("docs")).unwrap();
+        fs::write(root.join("crates/AGENTS.md"), "crates doc").unwrap();
+        fs::write(root.join("crates/app/AGENTS.md"), "app doc\n").unwrap();
+
+        let touched = vec![
+            PathBuf::from("crates/app/src/main.rs"),
+            root.join("crates/app/Cargo.toml"),
+            PathBuf::from("docs/guide.md"),
+            PathBuf::from("README.md"),
+        ];
+        let paths = nested_doc_paths(&root, &touched);
+        assert_eq!(
+            paths,
+            vec![
+                root.join("crates/AGENTS.md"),
+                root.join("crates/app/AGENTS.md"),
+            ]
+        );
+
+        let doc = read_nested_doc(&paths[1], 4096)
+            .await
+            .unwrap()
+            .expect("doc expected");
+        assert_eq!(
+            doc,
+            format!(
+                "Instructions from {}. They apply to files under {} and take precedence \
+                 over AGENTS.md files in its parent directories.\n\napp doc",
+                paths[1].display(),
+                root.join("crates/app").display()
+            )
+        );
     }
 }
@@ -0,0 +1,135 @@
+//! Variable interpolation for `base_instructions_template` and the
+//! instruction fragments configured alongside it.
+//!
+//! Variables are written `{{name}}`, optionally with spaces inside the
+//! braces. Unknown names are an error rather than being left in place, so a
+//! typo does not silently reach the model.
+
+use std::collections::HashMap;
+use std::path::Path;
+
+/// The variables a template may use.
+pub(crate) const VARIABLES: &[&str] = &["cwd", "os", "git_branch", "date"];
+
+const OPEN: &str = "{{";
+const CLOSE: &str = "}}";
+
+/// Names of the variables used in `template`, in order of first use.
+pub(crate) fn variables(template: &str) -> Vec<&str> {
+    let mut names: Vec<&str> = Vec::new();
+    let mut rest = template;
+    while let Some((name, after)) = next_variable(rest) {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+        rest = after;
+    }
+    names
+}
+
+/// Replace each variable in `template` with its value in `values`. Variables
+/// without a value are replaced with an empty string; check them with
+/// [`unknown_variables`] first.
+pub(crate) fn render(template: &str, values: &HashMap<&str, String>) -> String {
+    let mut out = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find(OPEN) {
+        let Some((name, after)) = next_variable(rest) else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        out.push_str(values.get(name).map(String::as_str).unwrap_or_default());
+        rest = after;
+    }
+    out.push_str(rest);
+    out
+}
+
+/// The variables used in `template` that are not in [`VARIABLES`].
+pub(crate) fn unknown_variables(template: &str) -> Vec<&str> {
+    variables(template)
+        .into_iter()
+        .filter(|name| !VARIABLES.contains(name))
+        .collect()
+}
+
+/// Values for the variables in [`VARIABLES`] that `used` refers to. The git
+/// branch is only looked up when it is used, and is empty outside a git
+/// repository or on a detached HEAD.
+pub(crate) fn values_for(used: &[&str], cwd: &Path) -> HashMap<&'static str, String> {
+    let mut values = HashMap::new();
+    for &name in VARIABLES {
+        if !used.contains(&name) {
+            continue;
+        }
+        let value = match name {
+            "cwd" => cwd.display().to_string(),
+            "os" => std::env::consts::OS.to_string(),
+            "git_branch" => current_git_branch(cwd).unwrap_or_default(),
+            "date" => chrono::Local::now().format("%Y-%m-%d").to_string(),
+            _ => continue,
+        };
+        values.insert(name, value);
+    }
+    values
+}
+
+/// The first `{{name}}` in `text` and the text after it.
+fn next_variable(text: &str) -> Option<(&str, &str)> {
+    let start = text.find(OPEN)? + OPEN.len();
+    let len = text[start..].find(CLOSE)?;
+    Some((
+        text[start..start + len].trim(),
+        &text[start + len + CLOSE.len()..],
+    ))
+}
+
+fn current_git_branch(cwd: &Path) -> Option<String> {
+    let output = std::process::Command::new("git")
+        .args(["symbolic-ref", "--short", "-q", "HEAD"])
+        .current_dir(cwd)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let branch = String::from_utf8(output.stdout).ok()?.trim().to_string();
+    (!branch.is_empty()).then_some(branch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn renders_known_variables() {
+        let template = "Working in {{cwd}} on {{ os }}.\nStill {{cwd}}. Keep }} and {{";
+        let values = HashMap::from([("cwd", "/repo".to_string()), ("os", "linux".to_string())]);
+
+        assert_eq!(variables(template), vec!["cwd", "os"]);
+        assert_eq!(
+            render(template, &values),
+            "Working in /repo on linux.\nStill /repo. Keep }} and {{"
+        );
+    }
+
+    #[test]
+    fn reports_unknown_variables_once() {
+        let template = "{{cwd}} {{branch}} {{ user }} {{branch}}";
+        assert_eq!(unknown_variables(template), vec!["branch", "user"]);
+    }
+
+    #[test]
+    fn looks_up_only_used_values() {
+        let values = values_for(&["os", "date"], Path::new("/repo"));
+
+        assert_eq!(values.get("os"), Some(&std::env::consts::OS.to_string()));
+        assert_eq!(
+            values.get("date").map(String::len),
+            Some("2025-01-01".len())
+        );
+        assert_eq!(values.get("cwd"), None);
+        assert_eq!(values.get("git_branch"), None);
+    }
+}
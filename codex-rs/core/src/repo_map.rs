@@ -0,0 +1,490 @@
+//! A compact outline of the repository (a "repo map") that is given to the
+//! model at the start of a session, so it knows which files exist and what
+//! they define before it starts searching.
+//!
+//! Files are listed in path order, respecting `.gitignore` and the project's
+//! `.codex/ignore`. For source files
+//! in a supported language, the top-level definitions and their direct
+//! members are extracted with tree-sitter. Extracted symbols are cached per
+//! file under `$CODEX_HOME/repo_maps`, keyed by size and modification time, so
+//! regenerating the map only parses files that changed.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::UNIX_EPOCH;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use ignore::WalkBuilder;
+use ignore::gitignore::Gitignore;
+use serde::Deserialize;
+use serde::Serialize;
+use sha1::Digest;
+use sha1::Sha1;
+use tracing::warn;
+use tree_sitter::Language;
+use tree_sitter::Node;
+use tree_sitter::Parser;
+
+pub(crate) const REPO_MAP_START: &str = "<repo_map>";
+const REPO_MAP_END: &str = "</repo_map>";
+
+/// Budget for the rendered map.
+const MAX_MAP_BYTES: usize = 16 * 1024;
+/// Files larger than this are listed but not parsed.
+const MAX_PARSE_BYTES: u64 = 256 * 1024;
+/// Stop walking the repository after this many files.
+const MAX_FILES: usize = 10_000;
+/// Definitions whose members are listed too.
+const CONTAINER_KEYWORDS: &[&str] = &["impl", "trait", "mod", "class", "interface"];
+/// Per-file symbol limits tried in turn until the map fits its budget.
+const SYMBOL_LIMITS: &[usize] = &[usize::MAX, 32, 16, 8, 4, 0];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct Symbol {
+    /// Short keyword such as `fn`, `struct` or `class`.
+    kind: String,
+    name: String,
+    /// 0 for top-level definitions, 1 for members (methods, impl items).
+    depth: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedFile {
+    size: u64,
+    modified_ms: u64,
+    symbols: Vec<Symbol>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Cache {
+    files: HashMap<String, CachedFile>,
+}
+
+#[derive(Debug)]
+struct FileEntry {
+    /// Path relative to the repository root, with `/` separators.
+    path: String,
+    size: u64,
+    symbols: Vec<Symbol>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct RepoMap {
+    pub(crate) text: String,
+    /// Number of files listed in the map.
+    pub(crate) files: usize,
+}
+
+impl RepoMap {
+    /// Build the map for `root` on the blocking thread pool, leaving out
+    /// files matched by `exclude`.
+    pub(crate) async fn generate(
+        root: PathBuf,
+        codex_home: PathBuf,
+        exclude: Gitignore,
+    ) -> io::Result<Self> {
+        tokio::task::spawn_blocking(move || Self::build(&root, &codex_home, exclude))
+            .await
+            .map_err(io::Error::other)?
+    }
+
+    /// Build the map for `root`, reusing and then updating the symbol cache
+    /// under `codex_home`.
+    fn build(root: &Path, codex_home: &Path, exclude: Gitignore) -> io::Result<Self> {
+        let cache_path = cache_path(root, codex_home);
+        let old_cache: Cache = fs::read(&cache_path)
+            .ok()
+            .and_then(|bytes| serde_json::from_slice(&bytes).ok())
+            .unwrap_or_default();
+
+        let mut new_cache = Cache::default();
+        let mut entries = Vec::new();
+        for path in walk(root, exclude) {
+            let Ok(metadata) = fs::metadata(&path) else {
+                continue;
+            };
+            let Some(relative) = relative_path(root, &path) else {
+                continue;
+            };
+            let size = metadata.len();
+            let modified_ms = metadata
+                .modified()
+                .ok()
+                .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                .map_or(0, |d| d.as_millis() as u64);
+
+            let symbols = match old_cache.files.get(&relative) {
+                Some(cached) if cached.size == size && cached.modified_ms == modified_ms => {
+                    cached.symbols.clone()
+                }
+                _ => extract_symbols(&path, size),
+            };
+            new_cache.files.insert(
+                relative.clone(),
+                CachedFile {
+                    size,
+                    modified_ms,
+                    symbols: symbols.clone(),
+                },
+            );
+            entries.push(FileEntry {
+                path: relative,
+                size,
+                symbols,
+            });
+        }
+        entries.sort_by(|a, b| a.path.cmp(&b.path));
+
+        if let Err(e) = write_cache(&cache_path, &new_cache) {
+            warn!(
+                "failed to write repo map cache {}: {e}",
+                cache_path.display()
+            );
+        }
+
+        Ok(Self {
+            text: render(&entries, MAX_MAP_BYTES),
+            files: entries.len(),
+        })
+    }
+
+    /// The map as a user message for the conversation history.
+    pub(crate) fn to_response_item(&self) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: format!("{REPO_MAP_START}\n{}\n{REPO_MAP_END}", self.text),
+            }],
+        }
+    }
+}
+
+fn cache_path(root: &Path, codex_home: &Path) -> PathBuf {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    let mut hasher = Sha1::new();
+    hasher.update(root.to_string_lossy().as_bytes());
+    let digest = hasher.finalize();
+    let name: String = digest.iter().map(|b| format!("{b:02x}")).collect();
+    codex_home.join("repo_maps").join(format!("{name}.json"))
+}
+
+fn write_cache(path: &Path, cache: &Cache) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_vec(cache)?)
+}
+
+fn walk(root: &Path, exclude: Gitignore) -> impl Iterator<Item = PathBuf> {
+    WalkBuilder::new(root)
+        // Respect .gitignore even outside a git repository.
+        .require_git(false)
+        .filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|t| t.is_dir());
+            !exclude.matched(entry.path(), is_dir).is_ignore()
+        })
+        .build()
+        .filter_map(Result::ok)
+        .filter(|entry| entry.file_type().is_some_and(|t| t.is_file()))
+        .map(ignore::DirEntry::into_path)
+        .take(MAX_FILES)
+}
+
+fn relative_path(root: &Path, path: &Path) -> Option<String> {
+    let relative = path.strip_prefix(root).ok()?;
+    let parts: Vec<String> = relative
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().into_owned())
+        .collect();
+    Some(parts.join("/"))
+}
+
+/// Node kinds that define a symbol, with the keyword shown for them and the
+/// field holding their name.
+struct LanguageSpec {
+    language: Language,
+    definitions: &'static [(&'static str, &'static str, &'static str)],
+}
+
+fn language_for(path: &Path) -> Option<LanguageSpec> {
+    const RUST: &[(&str, &str, &str)] = &[
+        ("function_item", "fn", "name"),
+        ("function_signature_item", "fn", "name"),
+        ("struct_item", "struct", "name"),
+        ("enum_item", "enum", "name"),
+        ("union_item", "union", "name"),
+        ("trait_item", "trait", "name"),
+        ("type_item", "type", "name"),
+        ("const_item", "const", "name"),
+        ("static_item", "static", "name"),
+        ("mod_item", "mod", "name"),
+        ("macro_definition", "macro", "name"),
+        ("impl_item", "impl", "type"),
+    ];
+    const PYTHON: &[(&str, &str, &str)] = &[
+        ("function_definition", "def", "name"),
+        ("class_definition", "class", "name"),
+    ];
+    const JAVASCRIPT: &[(&str, &str, &str)] = &[
+        ("function_declaration", "function", "name"),
+        ("generator_function_declaration", "function", "name"),
+        ("class_declaration", "class", "name"),
+        ("method_definition", "method", "name"),
+        ("interface_declaration", "interface", "name"),
+        ("type_alias_declaration", "type", "name"),
+        ("enum_declaration", "enum", "name"),
+    ];
+    const GO: &[(&str, &str, &str)] = &[
+        ("function_declaration", "func", "name"),
+        ("method_declaration", "func", "name"),
+        ("type_spec", "type", "name"),
+    ];
+
+    let extension = path.extension()?.to_str()?;
+    let (language, definitions): (Language, _) = match extension {
+        "rs" => (tree_sitter_rust::LANGUAGE.into(), RUST),
+        "py" | "pyi" => (tree_sitter_python::LANGUAGE.into(), PYTHON),
+        "js" | "jsx" | "mjs" | "cjs" => (tree_sitter_javascript::LANGUAGE.into(), JAVASCRIPT),
+        "ts" | "mts" | "cts" => (
+            tree_sitter_typescript::LANGUAGE_TYPESCRIPT.into(),
+            JAVASCRIPT,
+        ),
+        "tsx" => (tree_sitter_typescript::LANGUAGE_TSX.into(), JAVASCRIPT),
+        "go" => (tree_sitter_go::LANGUAGE.into(), GO),
+        _ => return None,
+    };
+    Some(LanguageSpec {
+        language,
+        definitions,
+    })
+}
+
+fn extract_symbols(path: &Path, size: u64) -> Vec<Symbol> {
+    if size > MAX_PARSE_BYTES {
+        return Vec::new();
+    }
+    let Some(spec) = language_for(path) else {
+        return Vec::new();
+    };
+    let Ok(source) = fs::read_to_string(path) else {
+        return Vec::new();
+    };
+    symbols_in_source(&spec, &source)
+}
+
+fn symbols_in_source(spec: &LanguageSpec, source: &str) -> Vec<Symbol> {
+    let mut parser = Parser::new();
+    if parser.set_language(&spec.language).is_err() {
+        return Vec::new();
+    }
+    let Some(tree) = parser.parse(source, None) else {
+        return Vec::new();
+    };
+    let mut symbols = Vec::new();
+    collect_symbols(spec, tree.root_node(), source, 0, &mut symbols);
+    symbols
+}
+
+fn collect_symbols(
+    spec: &LanguageSpec,
+    node: Node,
+    source: &str,
+    depth: usize,
+    symbols: &mut Vec<Symbol>,
+) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        let definition = spec
+            .definitions
+            .iter()
+            .find(|(kind, _, _)| *kind == child.kind());
+        match definition {
+            Some((_, keyword, name_field)) => {
+                if let Some(name) = child
+                    .child_by_field_name(name_field)
+                    .and_then(|n| n.utf8_text(source.as_bytes()).ok())
+                {
+                    symbols.push(Symbol {
+                        kind: (*keyword).to_string(),
+                        name: name.to_string(),
+                        depth,
+                    });
+                }
+                // Only go one level deep, and only into types and modules:
+                // members are useful, locals are noise.
+                if depth == 0 && CONTAINER_KEYWORDS.contains(keyword) {
+                    collect_symbols(spec, child, source, depth + 1, symbols);
+                }
+            }
+            // Look through wrappers such as `export`, decorators, Go's
+            // `type (...)` groups and class or impl bodies.
+            None => collect_symbols(spec, child, source, depth, symbols),
+        }
+    }
+}
+
+/// Render the map within `max_bytes`, listing fewer symbols per file until it
+/// fits and dropping trailing files as a last resort.
+fn render(entries: &[FileEntry], max_bytes: usize) -> String {
+    for &limit in SYMBOL_LIMITS {
+        let text = render_with_limit(entries, limit);
+        if text.len() <= max_bytes {
+            return text;
+        }
+    }
+
+    let mut text = String::new();
+    for (index, entry) in entries.iter().enumerate() {
+        let line = format_path_line(entry);
+        let remaining = entries.len() - index;
+        let marker = format!("[... {remaining} more files ...]");
+        if text.len() + line.len() + 1 + marker.len() > max_bytes {
+            text.push_str(&marker);
+            return text;
+        }
+        text.push_str(&line);
+        text.push('\n');
+    }
+    text.trim_end().to_string()
+}
+
+fn render_with_limit(entries: &[FileEntry], limit: usize) -> String {
+    let mut text = String::new();
+    for entry in entries {
+        text.push_str(&format_path_line(entry));
+        text.push('\n');
+        // Members are the first thing to go when the budget is tight.
+        let symbols = entry
+            .symbols
+            .iter()
+            .filter(|s| limit == usize::MAX || s.depth == 0)
+            .take(limit);
+        for symbol in symbols {
+            let indent = "  ".repeat(symbol.depth + 1);
+            text.push_str(&format!("{indent}{} {}\n", symbol.kind, symbol.name));
+        }
+    }
+    text.trim_end().to_string()
+}
+
+fn format_path_line(entry: &FileEntry) -> String {
+    if entry.size > MAX_PARSE_BYTES {
+        format!("{} ({} KiB)", entry.path, entry.size / 1024)
+    } else {
+        entry.path.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    fn names(symbols: &[Symbol]) -> Vec<String> {
+        symbols
+            .iter()
+            .map(|s| format!("{}{} {}", "  ".repeat(s.depth), s.kind, s.name))
+            .collect()
+    }
+
+    #[test]
+    fn extracts_rust_definitions_and_members() {
+        let spec = language_for(Path::new("lib.rs")).unwrap();
+        let source = r#"
+pub struct Config { value: u32 }
+
+impl Config {
+    pub fn new() -> Self {
+        fn helper() {}
+        Self { value: 0 }
+    }
+}
+
+enum Mode { A, B }
+"#;
+        assert_eq!(
+            names(&symbols_in_source(&spec, source)),
+            vec!["struct Config", "impl Config", "  fn new", "enum Mode"]
+        );
+    }
+
+    #[test]
+    fn extracts_python_classes_and_methods() {
+        let spec = language_for(Path::new("app.py")).unwrap();
+        let source = "class App:\n    def run(self):\n        pass\n\ndef main():\n    pass\n";
+        assert_eq!(
+            names(&symbols_in_source(&spec, source)),
+            vec!["class App", "  def run", "def main"]
+        );
+    }
+
+    #[test]
+    fn builds_map_respecting_gitignore_and_reuses_cache() {
+        let repo = tempdir().unwrap();
+        let home = tempdir().unwrap();
+        fs::write(repo.path().join(".gitignore"), "target/\n").unwrap();
+        fs::create_dir_all(repo.path().join("src")).unwrap();
+        fs::create_dir_all(repo.path().join("target")).unwrap();
+        fs::write(repo.path().join("src/main.rs"), "fn main() {}\n").unwrap();
+        fs::write(repo.path().join("target/out.rs"), "fn built() {}\n").unwrap();
+        fs::write(repo.path().join("README.md"), "# Demo\n").unwrap();
+
+        let map = RepoMap::build(repo.path(), home.path(), Gitignore::empty()).unwrap();
+        assert_eq!(map.text, "README.md\nsrc/main.rs\n  fn main");
+        assert_eq!(map.files, 2);
+        assert!(cache_path(repo.path(), home.path()).exists());
+
+        // A second build gives the same result from the cache.
+        assert_eq!(
+            RepoMap::build(repo.path(), home.path(), Gitignore::empty()).unwrap(),
+            map
+        );
+    }
+
+    #[test]
+    fn leaves_out_files_matched_by_exclude() {
+        let repo = tempdir().unwrap();
+        let home = tempdir().unwrap();
+        fs::create_dir_all(repo.path().join("fixtures")).unwrap();
+        fs::write(repo.path().join("fixtures/big.json"), "{}\n").unwrap();
+        fs::write(repo.path().join("lib.py"), "def main():\n    pass\n").unwrap();
+        let mut builder = ignore::gitignore::GitignoreBuilder::new(repo.path());
+        builder.add_line(None, "fixtures/").unwrap();
+
+        let map = RepoMap::build(repo.path(), home.path(), builder.build().unwrap()).unwrap();
+
+        assert_eq!(map.text, "lib.py\n  def main");
+    }
+
+    #[test]
+    fn render_drops_symbols_before_files() {
+        let entries: Vec<FileEntry> = (0..4)
+            .map(|i| FileEntry {
+                path: format!("src/file{i}.rs"),
+                size: 10,
+                symbols: (0..20)
+                    .map(|j| Symbol {
+                        kind: "fn".to_string(),
+                        name: format!("function_{j}"),
+                        depth: 0,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        let text = render(&entries, 400);
+
+        assert!(text.len() <= 400);
+        for i in 0..4 {
+            assert!(text.contains(&format!("src/file{i}.rs")));
+        }
+        assert!(text.contains("  fn function_0"));
+        assert!(!text.contains("function_19"));
+    }
+}
@@ -2,8 +2,11 @@
 
 use std::fs::File;
 use std::fs::{self};
+use std::io::BufRead;
+use std::io::BufReader;
 use std::io::Error as IoError;
 use std::path::Path;
+use std::path::PathBuf;
 
 use serde::Deserialize;
 use serde::Serialize;
@@ -20,6 +23,8 @@ use tracing::warn;
 use uuid::Uuid;
 
 use crate::config::Config;
+use crate::encryption::LineCipher;
+use crate::encryption::decode_line;
 use crate::git_info::GitInfo;
 use crate::git_info::collect_git_info;
 use codex_protocol::models::ResponseItem;
@@ -31,6 +36,10 @@ pub struct SessionMeta {
     pub id: Uuid,
     pub timestamp: String,
     pub instructions: Option<String>,
+    /// Working directory the session started in. Missing from rollouts
+    /// written by older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
 }
 
 #[derive(Serialize)]
@@ -44,6 +53,20 @@ struct SessionMetaWithGit {
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct SessionStateSnapshot {}
 
+/// Everything an interactive exec session printed, recorded once the session
+/// ends so the rollout shows what the agent saw across its tool calls.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ExecSessionTranscript {
+    pub session_id: u32,
+    /// The command as the model requested it, without any sandbox wrapper.
+    pub command: Vec<String>,
+    /// `None` when the session was closed before the process exited.
+    pub exit_code: Option<i32>,
+    pub output: String,
+    /// Set when `output` was capped and later output was dropped.
+    pub truncated: bool,
+}
+
 #[derive(Serialize, Deserialize, Default, Clone)]
 pub struct SavedSession {
     pub session: SessionMeta,
@@ -63,6 +86,9 @@ pub struct SavedSession {
 /// $ jq -C . ~/.codex/sessions/rollout-2025-05-07T17-24-21-5973b6c0-94b8-487b-a530-2aeb6098ae0e.jsonl
 /// $ fx ~/.codex/sessions/rollout-2025-05-07T17-24-21-5973b6c0-94b8-487b-a530-2aeb6098ae0e.jsonl
 /// ```
+///
+/// When `[encryption]` is enabled each line is encrypted instead; see
+/// [`crate::encryption`].
 #[derive(Clone)]
 pub(crate) struct RolloutRecorder {
     tx: Sender<RolloutCmd>,
@@ -71,6 +97,7 @@ pub(crate) struct RolloutRecorder {
 enum RolloutCmd {
     AddItems(Vec<ResponseItem>),
     UpdateState(SessionStateSnapshot),
+    AddExecSessionTranscript(ExecSessionTranscript),
     Shutdown { ack: oneshot::Sender<()> },
 }
 
@@ -88,6 +115,11 @@ impl RolloutRecorder {
             session_id,
             timestamp,
         } = create_log_file(config, uuid)?;
+        let cipher = if config.encryption.enabled {
+            Some(LineCipher::from_keychain()?)
+        } else {
+            None
+        };
 
         let timestamp_format: &[FormatItem] = format_description!(
             "[year]-[month]-[day]T[hour]:[minute]:[second].[subsecond digits:3]Z"
@@ -109,11 +141,13 @@ impl RolloutRecorder {
         // driver instead of blocking the runtime.
         tokio::task::spawn(rollout_writer(
             tokio::fs::File::from_std(file),
+            cipher,
             rx,
             Some(SessionMeta {
                 timestamp,
                 id: session_id,
                 instructions,
+                cwd: Some(cwd.clone()),
             }),
             cwd,
         ));
@@ -157,71 +191,44 @@ impl RolloutRecorder {
             .map_err(|e| IoError::other(format!("failed to queue rollout state: {e}")))
     }
 
+    pub(crate) async fn record_exec_session_transcript(
+        &self,
+        transcript: ExecSessionTranscript,
+    ) -> std::io::Result<()> {
+        self.tx
+            .send(RolloutCmd::AddExecSessionTranscript(transcript))
+            .await
+            .map_err(|e| IoError::other(format!("failed to queue exec session transcript: {e}")))
+    }
+
+    /// Resume recording into the rollout at `path`. Encrypted lines are
+    /// decrypted as they are read; new lines are encrypted when `encrypt` is
+    /// set, regardless of how the existing lines were written.
     pub async fn resume(
         path: &Path,
         cwd: std::path::PathBuf,
+        encrypt: bool,
     ) -> std::io::Result<(Self, SavedSession)> {
         info!("Resuming rollout from {path:?}");
         let text = tokio::fs::read_to_string(path).await?;
-        let mut lines = text.lines();
-        let meta_line = lines
-            .next()
-            .ok_or_else(|| IoError::other("empty session file"))?;
-        let session: SessionMeta = serde_json::from_str(meta_line)
-            .map_err(|e| IoError::other(format!("failed to parse session meta: {e}")))?;
-        let mut items = Vec::new();
-        let mut state = SessionStateSnapshot::default();
-
-        for line in lines {
-            if line.trim().is_empty() {
-                continue;
-            }
-            let v: Value = match serde_json::from_str(line) {
-                Ok(v) => v,
-                Err(_) => continue,
-            };
-            if v.get("record_type")
-                .and_then(|rt| rt.as_str())
-                .map(|s| s == "state")
-                .unwrap_or(false)
-            {
-                if let Ok(s) = serde_json::from_value::<SessionStateSnapshot>(v.clone()) {
-                    state = s
-                }
-                continue;
-            }
-            match serde_json::from_value::<ResponseItem>(v.clone()) {
-                Ok(item) => match item {
-                    ResponseItem::Message { .. }
-                    | ResponseItem::LocalShellCall { .. }
-                    | ResponseItem::FunctionCall { .. }
-                    | ResponseItem::FunctionCallOutput { .. }
-                    | ResponseItem::CustomToolCall { .. }
-                    | ResponseItem::CustomToolCallOutput { .. }
-                    | ResponseItem::Reasoning { .. } => items.push(item),
-                    ResponseItem::Other => {}
-                },
-                Err(e) => {
-                    warn!("failed to parse item: {v:?}, error: {e}");
-                }
-            }
-        }
-
-        let saved = SavedSession {
-            session: session.clone(),
-            items: items.clone(),
-            state: state.clone(),
-            session_id: session.id,
-        };
+        let mut cipher = None;
+        let saved = parse_rollout(&text, &mut cipher)?;
 
         let file = std::fs::OpenOptions::new()
             .append(true)
             .read(true)
             .open(path)?;
 
+        let cipher = match (encrypt, cipher) {
+            (false, _) => None,
+            (true, Some(cipher)) => Some(cipher),
+            (true, None) => Some(LineCipher::from_keychain()?),
+        };
+
         let (tx, rx) = mpsc::channel::<RolloutCmd>(256);
         tokio::task::spawn(rollout_writer(
             tokio::fs::File::from_std(file),
+            cipher,
             rx,
             None,
             cwd,
@@ -246,6 +253,136 @@ impl RolloutRecorder {
     }
 }
 
+/// Read the session recorded in the rollout at `path`, such as for
+/// `codex --replay`. Encrypted lines are decrypted with the key in the OS
+/// keychain.
+pub fn read_rollout(path: &Path) -> std::io::Result<SavedSession> {
+    let text = fs::read_to_string(path)?;
+    parse_rollout(&text, &mut None)
+}
+
+/// Parse the lines of a rollout. `cipher` is set once an encrypted line was
+/// decrypted, so that a resumed rollout keeps using the same key.
+fn parse_rollout(text: &str, cipher: &mut Option<LineCipher>) -> std::io::Result<SavedSession> {
+    let mut lines = text.lines();
+    let meta_line = lines
+        .next()
+        .ok_or_else(|| IoError::other("empty session file"))?;
+    let meta_line = decode_line(meta_line, cipher)?;
+    let session: SessionMeta = serde_json::from_str(&meta_line)
+        .map_err(|e| IoError::other(format!("failed to parse session meta: {e}")))?;
+    let mut items = Vec::new();
+    let mut state = SessionStateSnapshot::default();
+
+    for line in lines {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let line = decode_line(line, cipher)?;
+        let v: Value = match serde_json::from_str(&line) {
+            Ok(v) => v,
+            Err(_) => continue,
+        };
+        match v.get("record_type").and_then(|rt| rt.as_str()) {
+            Some("state") => {
+                if let Ok(s) = serde_json::from_value::<SessionStateSnapshot>(v.clone()) {
+                    state = s
+                }
+                continue;
+            }
+            // Other records (such as exec session transcripts) are kept
+            // for inspection only and are not replayed into the history.
+            Some(_) => continue,
+            None => {}
+        }
+        match serde_json::from_value::<ResponseItem>(v.clone()) {
+            Ok(item) => match item {
+                ResponseItem::Message { .. }
+                | ResponseItem::LocalShellCall { .. }
+                | ResponseItem::FunctionCall { .. }
+                | ResponseItem::FunctionCallOutput { .. }
+                | ResponseItem::CustomToolCall { .. }
+                | ResponseItem::CustomToolCallOutput { .. }
+                | ResponseItem::Reasoning { .. } => items.push(item),
+                ResponseItem::Other => {}
+            },
+            Err(e) => {
+                warn!("failed to parse item: {v:?}, error: {e}");
+            }
+        }
+    }
+
+    Ok(SavedSession {
+        session_id: session.id,
+        session,
+        items,
+        state,
+    })
+}
+
+/// A rollout found under `~/.codex/sessions`, described by its first line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RolloutSummary {
+    pub path: PathBuf,
+    pub session_id: Uuid,
+    pub timestamp: String,
+    pub cwd: Option<PathBuf>,
+}
+
+/// The rollout of the most recent session started in `cwd`, for `codex exec
+/// --resume-last`. Rollouts that cannot be read are skipped.
+pub fn find_latest_rollout(codex_home: &Path, cwd: &Path) -> Option<PathBuf> {
+    rollouts_newest_first(codex_home)
+        .find(|summary| summary.cwd.as_deref() == Some(cwd))
+        .map(|summary| summary.path)
+}
+
+/// Every readable rollout under `codex_home`, newest first.
+pub fn list_rollouts(codex_home: &Path) -> Vec<RolloutSummary> {
+    rollouts_newest_first(codex_home).collect()
+}
+
+fn rollouts_newest_first(codex_home: &Path) -> impl Iterator<Item = RolloutSummary> {
+    // Directories are named by date and files by start time, so reverse
+    // name order is newest first.
+    fn entries_newest_first(dir: &Path) -> Vec<PathBuf> {
+        let mut entries: Vec<PathBuf> = fs::read_dir(dir)
+            .map(|entries| entries.flatten().map(|entry| entry.path()).collect())
+            .unwrap_or_default();
+        entries.sort_unstable_by(|a, b| b.cmp(a));
+        entries
+    }
+
+    let sessions = codex_home.join(SESSIONS_SUBDIR);
+    entries_newest_first(&sessions)
+        .into_iter()
+        .flat_map(|year| entries_newest_first(&year))
+        .flat_map(|month| entries_newest_first(&month))
+        .flat_map(|day| entries_newest_first(&day))
+        .filter(|path| {
+            path.file_name()
+                .and_then(|name| name.to_str())
+                .is_some_and(|name| name.starts_with("rollout-") && name.ends_with(".jsonl"))
+        })
+        .filter_map(|path| read_rollout_summary(&path))
+}
+
+/// Describe the rollout at `path` from its first line.
+pub fn read_rollout_summary(path: &Path) -> Option<RolloutSummary> {
+    let mut first_line = String::new();
+    BufReader::new(File::open(path).ok()?)
+        .read_line(&mut first_line)
+        .ok()?;
+    let meta_line = decode_line(first_line.trim_end(), &mut None).ok()?;
+    let meta = serde_json::from_str::<SessionMeta>(&meta_line).ok()?;
+    Some(RolloutSummary {
+        path: path.to_path_buf(),
+        session_id: meta.id,
+        timestamp: meta.timestamp,
+        cwd: meta.cwd,
+    })
+}
+
 struct LogFileInfo {
     /// Opened file handle to the rollout file.
     file: File,
@@ -293,11 +430,12 @@ fn create_log_file(config: &Config, session_id: Uuid) -> std::io::Result<LogFile
 
 async fn rollout_writer(
     file: tokio::fs::File,
+    cipher: Option<LineCipher>,
     mut rx: mpsc::Receiver<RolloutCmd>,
     mut meta: Option<SessionMeta>,
     cwd: std::path::PathBuf,
 ) -> std::io::Result<()> {
-    let mut writer = JsonlWriter { file };
+    let mut writer = JsonlWriter { file, cipher };
 
     // If we have a meta, collect git info asynchronously and write meta first
     if let Some(session_meta) = meta.take() {
@@ -344,6 +482,20 @@ async fn rollout_writer(
                     })
                     .await?;
             }
+            RolloutCmd::AddExecSessionTranscript(transcript) => {
+                #[derive(Serialize)]
+                struct ExecSessionLine<'a> {
+                    record_type: &'static str,
+                    #[serde(flatten)]
+                    transcript: &'a ExecSessionTranscript,
+                }
+                writer
+                    .write_line(&ExecSessionLine {
+                        record_type: "exec_session",
+                        transcript: &transcript,
+                    })
+                    .await?;
+            }
             RolloutCmd::Shutdown { ack } => {
                 let _ = ack.send(());
             }
@@ -355,14 +507,120 @@ async fn rollout_writer(
 
 struct JsonlWriter {
     file: tokio::fs::File,
+    cipher: Option<LineCipher>,
 }
 
 impl JsonlWriter {
     async fn write_line(&mut self, item: &impl serde::Serialize) -> std::io::Result<()> {
         let mut json = serde_json::to_string(item)?;
+        if let Some(cipher) = &self.cipher {
+            json = cipher.encrypt_line(&json)?;
+        }
         json.push('\n');
         let _ = self.file.write_all(json.as_bytes()).await;
         self.file.flush().await?;
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn write_rollout(codex_home: &Path, day: &str, name: &str, cwd: &str) -> PathBuf {
+        let dir = codex_home.join(SESSIONS_SUBDIR).join(day);
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join(name);
+        let meta = SessionMeta {
+            cwd: Some(PathBuf::from(cwd)),
+            ..SessionMeta::default()
+        };
+        fs::write(
+            &path,
+            format!("{}\n", serde_json::to_string(&meta).unwrap()),
+        )
+        .unwrap();
+        path
+    }
+
+    #[test]
+    fn finds_the_latest_rollout_for_cwd() {
+        let codex_home = TempDir::new().unwrap();
+        let home = codex_home.path();
+        write_rollout(
+            home,
+            "2025/01/01",
+            "rollout-2025-01-01T09-00-00-a.jsonl",
+            "/repo",
+        );
+        let latest = write_rollout(
+            home,
+            "2025/01/02",
+            "rollout-2025-01-02T09-00-00-b.jsonl",
+            "/repo",
+        );
+        write_rollout(
+            home,
+            "2025/01/02",
+            "rollout-2025-01-02T10-00-00-c.jsonl",
+            "/other",
+        );
+
+        assert_eq!(find_latest_rollout(home, Path::new("/repo")), Some(latest));
+        assert_eq!(find_latest_rollout(home, Path::new("/elsewhere")), None);
+    }
+
+    #[test]
+    fn lists_rollouts_newest_first() {
+        let codex_home = TempDir::new().unwrap();
+        let home = codex_home.path();
+        let older = write_rollout(
+            home,
+            "2025/01/01",
+            "rollout-2025-01-01T09-00-00-a.jsonl",
+            "/repo",
+        );
+        let newer = write_rollout(
+            home,
+            "2025/01/02",
+            "rollout-2025-01-02T09-00-00-b.jsonl",
+            "/other",
+        );
+        fs::write(home.join(SESSIONS_SUBDIR).join("2025/01/02/notes.txt"), "").unwrap();
+
+        let paths: Vec<PathBuf> = list_rollouts(home)
+            .into_iter()
+            .map(|summary| summary.path)
+            .collect();
+        assert_eq!(paths, vec![newer, older]);
+    }
+
+    #[test]
+    fn reads_the_items_of_a_rollout() {
+        let codex_home = TempDir::new().unwrap();
+        let path = write_rollout(
+            codex_home.path(),
+            "2025/01/01",
+            "rollout-2025-01-01T09-00-00-a.jsonl",
+            "/repo",
+        );
+        let message = ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![codex_protocol::models::ContentItem::InputText {
+                text: "hello".to_string(),
+            }],
+        };
+        let mut text = fs::read_to_string(&path).unwrap();
+        text.push_str("{\"record_type\":\"state\"}\n");
+        text.push_str(&format!("{}\n", serde_json::to_string(&message).unwrap()));
+        fs::write(&path, text).unwrap();
+
+        let saved = read_rollout(&path).unwrap();
+        assert_eq!(saved.session.cwd, Some(PathBuf::from("/repo")));
+        assert_eq!(saved.items, vec![message]);
+    }
+}
@@ -0,0 +1,77 @@
+//! Limits on how long a headless run may go on, set by `codex exec
+//! --max-turns` and `--max-wall-time`. Once one is reached, the running task
+//! ends with a `TaskComplete` event that carries the reason, and later tasks
+//! end before making a model request.
+
+use std::sync::atomic::AtomicU32;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+
+use tokio::time::Instant;
+
+use crate::protocol::TruncationReason;
+
+pub(crate) struct RunLimits {
+    /// Model requests allowed over the whole session.
+    max_turns: Option<u32>,
+    /// When the session has to stop, counted from its start.
+    deadline: Option<Instant>,
+    turns_taken: AtomicU32,
+}
+
+impl RunLimits {
+    pub(crate) fn new(max_turns: Option<u32>, max_wall_time: Option<Duration>) -> Self {
+        Self {
+            max_turns,
+            deadline: max_wall_time.map(|wall_time| Instant::now() + wall_time),
+            turns_taken: AtomicU32::new(0),
+        }
+    }
+
+    /// Count a model request against `max_turns`, or return the limit that
+    /// forbids making it.
+    pub(crate) fn start_turn(&self) -> Result<(), TruncationReason> {
+        if self
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline)
+        {
+            return Err(TruncationReason::MaxWallTime);
+        }
+        let max_turns = self.max_turns.unwrap_or(u32::MAX);
+        self.turns_taken
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |taken| {
+                (taken < max_turns).then_some(taken + 1)
+            })
+            .map(|_| ())
+            .map_err(|_| TruncationReason::MaxTurns)
+    }
+
+    pub(crate) fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn turns_are_counted_across_tasks() {
+        let limits = RunLimits::new(Some(2), None);
+        assert_eq!(limits.start_turn(), Ok(()));
+        assert_eq!(limits.start_turn(), Ok(()));
+        assert_eq!(limits.start_turn(), Err(TruncationReason::MaxTurns));
+        assert_eq!(limits.start_turn(), Err(TruncationReason::MaxTurns));
+    }
+
+    #[test]
+    fn no_turn_starts_after_the_deadline() {
+        let limits = RunLimits::new(Some(2), Some(Duration::ZERO));
+        assert_eq!(limits.start_turn(), Err(TruncationReason::MaxWallTime));
+
+        let limits = RunLimits::new(None, None);
+        assert_eq!(limits.start_turn(), Ok(()));
+        assert_eq!(limits.deadline(), None);
+    }
+}
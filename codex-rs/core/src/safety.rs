@@ -7,6 +7,8 @@ use codex_apply_patch::ApplyPatchAction;
 use codex_apply_patch::ApplyPatchFileChange;
 
 use crate::exec::SandboxType;
+use crate::exec_policy::ExecPolicy;
+use crate::exec_policy::RuleAction;
 use crate::is_safe_command::is_known_safe_command;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
@@ -71,13 +73,48 @@ pub fn assess_patch_safety(
 /// - the user has explicitly approved the command
 /// - the command is on the "known safe" list
 /// - `DangerFullAccess` was specified and `UnlessTrusted` was not
+///
+/// `[exec_policy]` deny and ask rules take precedence over all of these,
+/// except that a command the user approved for the session is not asked
+/// about again. An `[exec_policy]` allow rule, from `config.toml` or added
+/// for the session, spares the approval but not the sandbox.
 pub fn assess_command_safety(
     command: &[String],
     approval_policy: AskForApproval,
     sandbox_policy: &SandboxPolicy,
     approved: &HashSet<Vec<String>>,
+    exec_policy: &ExecPolicy,
     with_escalated_permissions: bool,
 ) -> SafetyCheck {
+    if let Some(rule) = exec_policy.check(command) {
+        match rule.action {
+            RuleAction::Deny => {
+                return SafetyCheck::Reject {
+                    reason: format!("command is denied by {rule}"),
+                };
+            }
+            RuleAction::Ask if approved.contains(command) => {}
+            RuleAction::Ask if approval_policy == AskForApproval::Never => {
+                return SafetyCheck::Reject {
+                    reason: format!(
+                        "command requires approval by {rule}, but approval_policy is never"
+                    ),
+                };
+            }
+            RuleAction::Ask => return SafetyCheck::AskUser,
+            // An allow rule spares the approval, not the sandbox. Without
+            // one, the command is assessed as usual.
+            RuleAction::Allow => {
+                if !with_escalated_permissions
+                    && let Some(sandbox_type) =
+                        sandbox_enforcing_policy(get_platform_sandbox(), sandbox_policy)
+                {
+                    return SafetyCheck::AutoApprove { sandbox_type };
+                }
+            }
+        }
+    }
+
     // A command is "trusted" because either:
     // - it belongs to a set of commands we consider "safe" by default, or
     // - the user has explicitly approved the command for this session
@@ -124,7 +161,7 @@ pub(crate) fn assess_safety_for_untrusted_command(
             if with_escalated_permissions {
                 SafetyCheck::AskUser
             } else {
-                match get_platform_sandbox() {
+                match sandbox_enforcing_policy(get_platform_sandbox(), sandbox_policy) {
                     Some(sandbox_type) => SafetyCheck::AutoApprove { sandbox_type },
                     // Fall back to asking since the command is untrusted and
                     // we do not have a sandbox available
@@ -136,7 +173,7 @@ pub(crate) fn assess_safety_for_untrusted_command(
         | (Never, WorkspaceWrite { .. })
         | (OnFailure, ReadOnly { .. })
         | (OnFailure, WorkspaceWrite { .. }) => {
-            match get_platform_sandbox() {
+            match sandbox_enforcing_policy(get_platform_sandbox(), sandbox_policy) {
                 Some(sandbox_type) => SafetyCheck::AutoApprove { sandbox_type },
                 None => {
                     if matches!(approval_policy, OnFailure) {
@@ -164,11 +201,29 @@ pub fn get_platform_sandbox() -> Option<SandboxType> {
         Some(SandboxType::MacosSeatbelt)
     } else if cfg!(target_os = "linux") {
         Some(SandboxType::LinuxSeccomp)
+    } else if cfg!(target_os = "windows") {
+        Some(SandboxType::WindowsRestrictedToken)
     } else {
         None
     }
 }
 
+/// Returns `sandbox_type` if it can enforce every restriction in
+/// `sandbox_policy`. The Windows restricted token limits file access but not
+/// the network, so commands that must stay offline cannot be auto-approved
+/// under it.
+fn sandbox_enforcing_policy(
+    sandbox_type: Option<SandboxType>,
+    sandbox_policy: &SandboxPolicy,
+) -> Option<SandboxType> {
+    match sandbox_type {
+        Some(SandboxType::WindowsRestrictedToken) if !sandbox_policy.has_full_network_access() => {
+            None
+        }
+        other => other,
+    }
+}
+
 fn is_write_patch_constrained_to_writable_paths(
     action: &ApplyPatchAction,
     sandbox_policy: &SandboxPolicy,
@@ -316,6 +371,7 @@ mod tests {
             approval_policy,
             &sandbox_policy,
             &approved,
+            &ExecPolicy::default(),
             request_escalated_privileges,
         );
 
@@ -337,13 +393,141 @@ mod tests {
             approval_policy,
             &sandbox_policy,
             &approved,
+            &ExecPolicy::default(),
             request_escalated_privileges,
         );
 
-        let expected = match get_platform_sandbox() {
+        let expected = match sandbox_enforcing_policy(get_platform_sandbox(), &sandbox_policy) {
             Some(sandbox_type) => SafetyCheck::AutoApprove { sandbox_type },
             None => SafetyCheck::AskUser,
         };
         assert_eq!(safety_check, expected);
     }
+
+    #[test]
+    fn exec_policy_rules_take_precedence() {
+        let exec_policy = ExecPolicy::from_toml(
+            toml::from_str(
+                r#"
+deny = ["git push *--force*"]
+ask = ["cat *.env"]
+allow = ["cargo build"]
+"#,
+            )
+            .unwrap(),
+        )
+        .unwrap();
+        let sandbox_policy = SandboxPolicy::new_read_only_policy();
+        let assess = |command: &[&str], approval_policy, approved: &HashSet<Vec<String>>| {
+            let command: Vec<String> = command.iter().map(ToString::to_string).collect();
+            assess_command_safety(
+                &command,
+                approval_policy,
+                &sandbox_policy,
+                approved,
+                &exec_policy,
+                false,
+            )
+        };
+        let none = HashSet::new();
+
+        assert_eq!(
+            assess(
+                &["git", "push", "--force"],
+                AskForApproval::OnRequest,
+                &none
+            ),
+            SafetyCheck::Reject {
+                reason: "command is denied by exec_policy deny rule `git push *--force*`"
+                    .to_string()
+            }
+        );
+        // `cat` is on the built-in list of safe commands.
+        assert_eq!(
+            assess(&["cat", ".env"], AskForApproval::OnRequest, &none),
+            SafetyCheck::AskUser
+        );
+        assert_eq!(
+            assess(&["cat", ".env"], AskForApproval::Never, &none),
+            SafetyCheck::Reject {
+                reason: "command requires approval by exec_policy ask rule `cat *.env`, but approval_policy is never".to_string()
+            }
+        );
+        let approved = HashSet::from([vec!["cat".to_string(), ".env".to_string()]]);
+        assert_eq!(
+            assess(&["cat", ".env"], AskForApproval::OnRequest, &approved),
+            SafetyCheck::AutoApprove {
+                sandbox_type: SandboxType::None
+            }
+        );
+        // An allow rule keeps the command in the sandbox.
+        let expected = match sandbox_enforcing_policy(get_platform_sandbox(), &sandbox_policy) {
+            Some(sandbox_type) => SafetyCheck::AutoApprove { sandbox_type },
+            None => SafetyCheck::AskUser,
+        };
+        assert_eq!(
+            assess(&["cargo", "build"], AskForApproval::UnlessTrusted, &none),
+            expected
+        );
+    }
+
+    #[test]
+    fn session_rules_run_commands_in_the_sandbox() {
+        let mut exec_policy = ExecPolicy::default();
+        assert!(exec_policy.add_session_rule("cargo test".to_string()));
+        let sandbox_policy = SandboxPolicy::new_workspace_write_policy();
+        let assess = |command: &[&str], with_escalated_permissions| {
+            let command: Vec<String> = command.iter().map(ToString::to_string).collect();
+            assess_command_safety(
+                &command,
+                AskForApproval::UnlessTrusted,
+                &sandbox_policy,
+                &HashSet::new(),
+                &exec_policy,
+                with_escalated_permissions,
+            )
+        };
+
+        let expected = match sandbox_enforcing_policy(get_platform_sandbox(), &sandbox_policy) {
+            Some(sandbox_type) => SafetyCheck::AutoApprove { sandbox_type },
+            None => SafetyCheck::AskUser,
+        };
+        assert_eq!(
+            assess(&["cargo", "test", "-p", "codex-core"], false),
+            expected
+        );
+        assert_eq!(
+            assess(&["cargo", "test", "-p", "codex-core"], true),
+            SafetyCheck::AskUser
+        );
+        assert_eq!(
+            assess(&["cargo", "run", "--", "test"], false),
+            SafetyCheck::AskUser
+        );
+    }
+
+    #[test]
+    fn windows_sandbox_is_not_used_when_network_must_be_blocked() {
+        let offline = SandboxPolicy::new_workspace_write_policy();
+        assert_eq!(
+            sandbox_enforcing_policy(Some(SandboxType::WindowsRestrictedToken), &offline),
+            None
+        );
+        assert_eq!(
+            sandbox_enforcing_policy(Some(SandboxType::LinuxSeccomp), &offline),
+            Some(SandboxType::LinuxSeccomp)
+        );
+
+        let online = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![],
+            network_access: true,
+            exclude_tmpdir_env_var: false,
+            exclude_slash_tmp: false,
+            read_blocklist: vec![],
+        };
+        assert_eq!(
+            sandbox_enforcing_policy(Some(SandboxType::WindowsRestrictedToken), &online),
+            Some(SandboxType::WindowsRestrictedToken)
+        );
+    }
 }
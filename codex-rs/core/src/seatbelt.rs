@@ -3,8 +3,11 @@ use std::path::Path;
 use std::path::PathBuf;
 use tokio::process::Child;
 
+use crate::config_types::ExecLimits;
+use crate::config_types::SandboxSeatbeltToml;
 use crate::protocol::SandboxPolicy;
 use crate::spawn::CODEX_SANDBOX_ENV_VAR;
+use crate::spawn::SpawnChildRequest;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
 
@@ -14,35 +17,194 @@ const MACOS_SEATBELT_BASE_POLICY: &str = include_str!("seatbelt_base_policy.sbpl
 /// to defend against an attacker trying to inject a malicious version on the
 /// PATH. If /usr/bin/sandbox-exec has been tampered with, then the attacker
 /// already has root access.
-const MACOS_PATH_TO_SEATBELT_EXECUTABLE: &str = "/usr/bin/sandbox-exec";
+pub(crate) const MACOS_PATH_TO_SEATBELT_EXECUTABLE: &str = "/usr/bin/sandbox-exec";
+
+/// Operations whose `allow` rules would grant what the sandbox policy
+/// decides, matched as prefixes.
+const POLICY_OPERATION_PREFIXES: &[&str] = &["file-write", "network", "system-socket"];
+
+/// Rules from `[sandbox_seatbelt]` added to the end of the profile, checked
+/// when the config is loaded.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct SeatbeltExtensions {
+    readable_paths: Vec<PathBuf>,
+    mach_services: Vec<String>,
+    rules: Vec<String>,
+}
+
+impl SeatbeltExtensions {
+    pub fn from_toml(toml: SandboxSeatbeltToml) -> Result<Self, String> {
+        let SandboxSeatbeltToml {
+            readable_paths,
+            mach_services,
+            rules,
+        } = toml;
+        if let Some(path) = readable_paths.iter().find(|path| !path.is_absolute()) {
+            return Err(format!(
+                "readable path `{}` is not absolute",
+                path.display()
+            ));
+        }
+        if let Some(service) = mach_services
+            .iter()
+            .find(|service| !is_mach_service_name(service))
+        {
+            return Err(format!("`{service}` is not a mach service name"));
+        }
+        let rules = rules
+            .iter()
+            .map(|rule| -> Result<String, String> {
+                let rule = rule.trim();
+                validate_rule(rule).map_err(|e| format!("rule `{rule}` {e}"))?;
+                Ok(rule.to_string())
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(Self {
+            readable_paths,
+            mach_services,
+            rules,
+        })
+    }
+
+    /// The profile section and the `-D` parameters it refers to.
+    fn policy(&self) -> (String, Vec<String>) {
+        if *self == Self::default() {
+            return (String::new(), Vec::new());
+        }
+        let mut lines = vec!["; from [sandbox_seatbelt] in config.toml".to_string()];
+        let mut args = Vec::new();
+        for (index, path) in self.readable_paths.iter().enumerate() {
+            let canonical = path.canonicalize().unwrap_or_else(|_| path.clone());
+            let param = format!("READABLE_PATH_{index}");
+            args.push(format!("-D{param}={}", canonical.to_string_lossy()));
+            lines.push(format!("(allow file-read* (subpath (param \"{param}\")))"));
+        }
+        for service in &self.mach_services {
+            lines.push(format!("(allow mach-lookup (global-name \"{service}\"))"));
+        }
+        lines.extend(self.rules.iter().cloned());
+        (lines.join("\n"), args)
+    }
+}
+
+/// Service names are quoted into the profile, so only the characters of
+/// reverse-DNS names are accepted.
+fn is_mach_service_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_'))
+}
+
+/// Checks that `rule` is a single balanced `(allow ...)` or `(deny ...)`
+/// form, so that it cannot swallow or end the rest of the profile, and that
+/// an `allow` rule does not grant writes or network access.
+fn validate_rule(rule: &str) -> Result<(), String> {
+    if rule.is_empty() {
+        return Err("is empty".to_string());
+    }
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, c) in rule.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            ';' => return Err("must not contain comments".to_string()),
+            '(' => depth += 1,
+            ')' => {
+                depth = depth.checked_sub(1).ok_or("has unbalanced parentheses")?;
+                if depth == 0 && index + 1 != rule.len() {
+                    return Err("must be a single form".to_string());
+                }
+            }
+            _ if depth == 0 => return Err("must be a single form".to_string()),
+            _ => {}
+        }
+    }
+    if in_string || depth != 0 {
+        return Err("has unbalanced parentheses or quotes".to_string());
+    }
+
+    // The action and operations come before the first filter.
+    let head_end = rule[1..]
+        .find('(')
+        .map_or(rule.len() - 1, |index| index + 1);
+    let mut words = rule[1..head_end].split_whitespace();
+    match words.next() {
+        Some("allow") => {
+            let policy_operation = words.find(|operation| {
+                matches!(*operation, "default" | "file*")
+                    || POLICY_OPERATION_PREFIXES
+                        .iter()
+                        .any(|prefix| operation.starts_with(prefix))
+            });
+            if let Some(operation) = policy_operation {
+                return Err(format!(
+                    "allows `{operation}`, which follows `sandbox_mode` instead"
+                ));
+            }
+            Ok(())
+        }
+        Some("deny") => Ok(()),
+        _ => Err("must start with `allow` or `deny`".to_string()),
+    }
+}
 
 pub async fn spawn_command_under_seatbelt(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
+    extensions: &SeatbeltExtensions,
     cwd: PathBuf,
     stdio_policy: StdioPolicy,
     mut env: HashMap<String, String>,
+    limits: &ExecLimits,
 ) -> std::io::Result<Child> {
-    let args = create_seatbelt_command_args(command, sandbox_policy, &cwd);
+    let args = create_seatbelt_command_args(command, sandbox_policy, extensions, &cwd);
     let arg0 = None;
     env.insert(CODEX_SANDBOX_ENV_VAR.to_string(), "seatbelt".to_string());
-    spawn_child_async(
-        PathBuf::from(MACOS_PATH_TO_SEATBELT_EXECUTABLE),
+    spawn_child_async(SpawnChildRequest {
+        program: PathBuf::from(MACOS_PATH_TO_SEATBELT_EXECUTABLE),
         args,
         arg0,
         cwd,
         sandbox_policy,
         stdio_policy,
         env,
-    )
+        limits,
+    })
     .await
 }
 
-fn create_seatbelt_command_args(
+pub(crate) fn create_seatbelt_command_args(
     command: Vec<String>,
     sandbox_policy: &SandboxPolicy,
+    extensions: &SeatbeltExtensions,
     cwd: &Path,
 ) -> Vec<String> {
+    let (full_policy, params) = create_seatbelt_profile(sandbox_policy, extensions, cwd);
+    let mut seatbelt_args: Vec<String> = vec!["-p".to_string(), full_policy];
+    seatbelt_args.extend(params);
+    seatbelt_args.push("--".to_string());
+    seatbelt_args.extend(command);
+    seatbelt_args
+}
+
+/// The profile `sandbox-exec` is given for `sandbox_policy`, and the `-D`
+/// parameters that the profile refers to.
+pub fn create_seatbelt_profile(
+    sandbox_policy: &SandboxPolicy,
+    extensions: &SeatbeltExtensions,
+    cwd: &Path,
+) -> (String, Vec<String>) {
     let (file_write_policy, extra_cli_args) = {
         if sandbox_policy.has_full_disk_write_access() {
             // Allegedly, this is more permissive than `(allow file-write*)`.
@@ -96,27 +258,15 @@ fn create_seatbelt_command_args(
         }
     };
 
-    let (file_read_policy, mut read_blocklist_args) =
-        if sandbox_policy.get_read_blocklist().is_empty() {
-            (
-                "; allow read-only file operations\n(allow file-read*)".to_string(),
-                Vec::new(),
-            )
-        } else {
-            let mut args = Vec::new();
-            let mut deny_parts = Vec::new();
-            for (idx, path) in sandbox_policy.get_read_blocklist().iter().enumerate() {
-                let canon = path.canonicalize().unwrap_or_else(|_| path.clone());
-                let param = format!("READ_BLOCK_{idx}");
-                args.push(format!("-D{param}={}", canon.to_string_lossy()));
-                deny_parts.push(format!("(literal (param \"{param}\"))"));
-            }
-            let policy = format!(
-                "; allow read-only file operations\n(allow file-read*)\n(deny file-read* {})",
-                deny_parts.join(" ")
-            );
-            (policy, args)
-        };
+    let file_read_policy = "; allow read-only file operations\n(allow file-read*)";
+    let mut read_blocklist_args = Vec::new();
+    let mut deny_parts = Vec::new();
+    for (idx, path) in sandbox_policy.get_read_blocklist().iter().enumerate() {
+        let canon = path.canonicalize().unwrap_or_else(|_| path.clone());
+        let param = format!("READ_BLOCK_{idx}");
+        read_blocklist_args.push(format!("-D{param}={}", canon.to_string_lossy()));
+        deny_parts.push(format!("(literal (param \"{param}\"))"));
+    }
 
     // TODO(mbolin): apply_patch calls must also honor the SandboxPolicy.
     let network_policy = if sandbox_policy.has_full_network_access() {
@@ -125,22 +275,36 @@ fn create_seatbelt_command_args(
         ""
     };
 
-    let full_policy = format!(
+    let mut full_policy = format!(
         "{MACOS_SEATBELT_BASE_POLICY}\n{file_read_policy}\n{file_write_policy}\n{network_policy}"
     );
+    // After the sandbox policy, so that the rules win over the ones above.
+    let (extensions_policy, extensions_args) = extensions.policy();
+    if !extensions_policy.is_empty() {
+        full_policy.push('\n');
+        full_policy.push_str(&extensions_policy);
+    }
+    // Last, so that no `file-read*` rule can re-allow a blocked path.
+    if !deny_parts.is_empty() {
+        full_policy.push_str(&format!(
+            "\n; read_blocklist\n(deny file-read* {})",
+            deny_parts.join(" ")
+        ));
+    }
 
-    let mut seatbelt_args: Vec<String> = vec!["-p".to_string(), full_policy];
-    seatbelt_args.extend(extra_cli_args);
-    seatbelt_args.extend(read_blocklist_args);
-    seatbelt_args.push("--".to_string());
-    seatbelt_args.extend(command);
-    seatbelt_args
+    let mut params = extra_cli_args;
+    params.extend(read_blocklist_args);
+    params.extend(extensions_args);
+    (full_policy, params)
 }
 
 #[cfg(test)]
 mod tests {
     use super::MACOS_SEATBELT_BASE_POLICY;
+    use super::SeatbeltExtensions;
     use super::create_seatbelt_command_args;
+    use super::create_seatbelt_profile;
+    use crate::config_types::SandboxSeatbeltToml;
     use crate::protocol::SandboxPolicy;
     use pretty_assertions::assert_eq;
     use std::fs;
@@ -180,6 +344,7 @@ mod tests {
         let args = create_seatbelt_command_args(
             vec!["/bin/echo".to_string(), "hello".to_string()],
             &policy,
+            &SeatbeltExtensions::default(),
             &cwd,
         );
 
@@ -256,6 +421,7 @@ mod tests {
         let args = create_seatbelt_command_args(
             vec!["/bin/echo".to_string(), "hello".to_string()],
             &policy,
+            &SeatbeltExtensions::default(),
             root_with_git.as_path(),
         );
 
@@ -319,6 +485,143 @@ mod tests {
         assert_eq!(expected_args, args);
     }
 
+    #[test]
+    fn seatbelt_extensions_are_appended_to_the_profile() {
+        let tmp = TempDir::new().expect("tempdir");
+        let readable = tmp.path().canonicalize().expect("canonicalize tempdir");
+        let extensions = SeatbeltExtensions::from_toml(SandboxSeatbeltToml {
+            readable_paths: vec![readable.clone()],
+            mach_services: vec!["com.apple.SecurityServer".to_string()],
+            rules: vec![" (deny file-read* (subpath \"/Volumes\")) ".to_string()],
+        })
+        .expect("valid extensions");
+
+        let (policy, params) = create_seatbelt_profile(
+            &SandboxPolicy::new_read_only_policy(),
+            &extensions,
+            tmp.path(),
+        );
+
+        let expected_policy = format!(
+            r#"{MACOS_SEATBELT_BASE_POLICY}
+; allow read-only file operations
+(allow file-read*)
+
+
+; from [sandbox_seatbelt] in config.toml
+(allow file-read* (subpath (param "READABLE_PATH_0")))
+(allow mach-lookup (global-name "com.apple.SecurityServer"))
+(deny file-read* (subpath "/Volumes"))"#,
+        );
+        assert_eq!(expected_policy, policy);
+        assert_eq!(
+            vec![format!("-DREADABLE_PATH_0={}", readable.to_string_lossy())],
+            params
+        );
+    }
+
+    #[test]
+    fn read_blocklist_wins_over_seatbelt_extensions() {
+        let tmp = TempDir::new().expect("tempdir");
+        let readable = tmp.path().canonicalize().expect("canonicalize tempdir");
+        let blocked = readable.join("secret");
+        let extensions = SeatbeltExtensions::from_toml(SandboxSeatbeltToml {
+            readable_paths: vec![readable.clone()],
+            rules: vec![format!(
+                "(allow file-read* (literal \"{}\"))",
+                blocked.display()
+            )],
+            ..Default::default()
+        })
+        .expect("valid extensions");
+
+        let (policy, params) = create_seatbelt_profile(
+            &SandboxPolicy::ReadOnly {
+                read_blocklist: vec![blocked.clone()],
+            },
+            &extensions,
+            tmp.path(),
+        );
+
+        let expected_policy = format!(
+            r#"{MACOS_SEATBELT_BASE_POLICY}
+; allow read-only file operations
+(allow file-read*)
+
+
+; from [sandbox_seatbelt] in config.toml
+(allow file-read* (subpath (param "READABLE_PATH_0")))
+(allow file-read* (literal "{}"))
+; read_blocklist
+(deny file-read* (literal (param "READ_BLOCK_0")))"#,
+            blocked.display()
+        );
+        assert_eq!(expected_policy, policy);
+        assert_eq!(
+            vec![
+                format!("-DREAD_BLOCK_0={}", blocked.to_string_lossy()),
+                format!("-DREADABLE_PATH_0={}", readable.to_string_lossy()),
+            ],
+            params
+        );
+    }
+
+    #[test]
+    fn seatbelt_extensions_are_validated() {
+        let rejected = |toml: SandboxSeatbeltToml| {
+            SeatbeltExtensions::from_toml(toml).expect_err("extensions should be rejected")
+        };
+        let rules = |rule: &str| SandboxSeatbeltToml {
+            rules: vec![rule.to_string()],
+            ..Default::default()
+        };
+
+        assert_eq!(
+            "readable path `relative/dir` is not absolute",
+            rejected(SandboxSeatbeltToml {
+                readable_paths: vec![PathBuf::from("relative/dir")],
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            "`com.example\"))` is not a mach service name",
+            rejected(SandboxSeatbeltToml {
+                mach_services: vec!["com.example\"))".to_string()],
+                ..Default::default()
+            })
+        );
+        assert_eq!(
+            "rule `(allow file-write* (subpath \"/\"))` allows `file-write*`, which follows `sandbox_mode` instead",
+            rejected(rules("(allow file-write* (subpath \"/\"))"))
+        );
+        assert_eq!(
+            "rule `(allow default)` allows `default`, which follows `sandbox_mode` instead",
+            rejected(rules("(allow default)"))
+        );
+        assert_eq!(
+            "rule `(deny process-exec) (allow default)` must be a single form",
+            rejected(rules("(deny process-exec) (allow default)"))
+        );
+        assert_eq!(
+            "rule `(deny file-read* (literal \")\")` has unbalanced parentheses or quotes",
+            rejected(rules("(deny file-read* (literal \")\")"))
+        );
+        assert_eq!(
+            "rule `(deny process-exec ; x)` must not contain comments",
+            rejected(rules("(deny process-exec ; x)"))
+        );
+        assert_eq!(
+            "rule `(version 1)` must start with `allow` or `deny`",
+            rejected(rules("(version 1)"))
+        );
+
+        // Parentheses inside strings do not count.
+        assert!(
+            SeatbeltExtensions::from_toml(rules("(allow file-read* (literal \"/tmp/a)b\"))"))
+                .is_ok()
+        );
+    }
+
     struct PopulatedTmp {
         root_with_git: PathBuf,
         root_without_git: PathBuf,
@@ -272,6 +272,7 @@ mod tests {
             use std::path::PathBuf;
 
             use crate::exec::ExecParams;
+            use crate::exec::ExecSandboxOptions;
             use crate::exec::SandboxType;
             use crate::exec::process_exec_tool_call;
             use crate::protocol::SandboxPolicy;
@@ -318,11 +319,16 @@ mod tests {
                     with_escalated_permissions: None,
                     justification: None,
                 },
-                SandboxType::None,
                 &SandboxPolicy::DangerFullAccess {
                     read_blocklist: Vec::new(),
                 },
-                &None,
+                ExecSandboxOptions {
+                    sandbox_type: SandboxType::None,
+                    codex_linux_sandbox_exe: &None,
+                    container_config: None,
+                    seatbelt_extensions: &crate::seatbelt::SeatbeltExtensions::default(),
+                    limits: &crate::config_types::ExecLimits::default(),
+                },
                 None,
             )
             .await
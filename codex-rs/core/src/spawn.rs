@@ -5,6 +5,7 @@ use tokio::process::Child;
 use tokio::process::Command;
 use tracing::trace;
 
+use crate::config_types::ExecLimits;
 use crate::protocol::SandboxPolicy;
 
 /// Experimental environment variable that will be set to some non-empty value
@@ -28,6 +29,19 @@ pub enum StdioPolicy {
     Inherit,
 }
 
+/// Everything needed to spawn a child process for a tool call.
+#[derive(Debug)]
+pub(crate) struct SpawnChildRequest<'a> {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+    pub arg0: Option<&'a str>,
+    pub cwd: PathBuf,
+    pub sandbox_policy: &'a SandboxPolicy,
+    pub stdio_policy: StdioPolicy,
+    pub env: HashMap<String, String>,
+    pub limits: &'a ExecLimits,
+}
+
 /// Spawns the appropriate child process for the ExecParams and SandboxPolicy,
 /// ensuring the args and environment variables used to create the `Command`
 /// (and `Child`) honor the configuration.
@@ -35,18 +49,20 @@ pub enum StdioPolicy {
 /// For now, we take `SandboxPolicy` as a parameter to spawn_child() because
 /// we need to determine whether to set the
 /// `CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR` environment variable.
-pub(crate) async fn spawn_child_async(
-    program: PathBuf,
-    args: Vec<String>,
-    #[cfg_attr(not(unix), allow(unused_variables))] arg0: Option<&str>,
-    cwd: PathBuf,
-    sandbox_policy: &SandboxPolicy,
-    stdio_policy: StdioPolicy,
-    env: HashMap<String, String>,
-) -> std::io::Result<Child> {
-    trace!(
-        "spawn_child_async: {program:?} {args:?} {arg0:?} {cwd:?} {sandbox_policy:?} {stdio_policy:?} {env:?}"
-    );
+pub(crate) async fn spawn_child_async(request: SpawnChildRequest<'_>) -> std::io::Result<Child> {
+    trace!("spawn_child_async: {request:?}");
+    let SpawnChildRequest {
+        program,
+        args,
+        #[cfg_attr(not(unix), allow(unused_variables))]
+        arg0,
+        cwd,
+        sandbox_policy,
+        stdio_policy,
+        env,
+        #[cfg_attr(not(unix), allow(unused_variables))]
+        limits,
+    } = request;
 
     let mut cmd = Command::new(&program);
     #[cfg(unix)]
@@ -85,6 +101,39 @@ pub(crate) async fn spawn_child_async(
         });
     }
 
+    // Kernel-enforced limits are inherited by everything the command spawns.
+    // Wall-clock and output limits are enforced by the caller.
+    #[cfg(unix)]
+    if limits.cpu_time_seconds.is_some() || limits.max_memory_bytes.is_some() {
+        let cpu_time_seconds = limits.cpu_time_seconds;
+        let max_memory_bytes = limits.max_memory_bytes;
+        unsafe {
+            cmd.pre_exec(move || {
+                if let Some(seconds) = cpu_time_seconds {
+                    // SIGXCPU at the soft limit, SIGKILL a second later if
+                    // the process ignores it.
+                    let limit = libc::rlimit {
+                        rlim_cur: seconds as libc::rlim_t,
+                        rlim_max: seconds.saturating_add(1) as libc::rlim_t,
+                    };
+                    if libc::setrlimit(libc::RLIMIT_CPU, &limit) == -1 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                if let Some(bytes) = max_memory_bytes {
+                    let limit = libc::rlimit {
+                        rlim_cur: bytes as libc::rlim_t,
+                        rlim_max: bytes as libc::rlim_t,
+                    };
+                    if libc::setrlimit(libc::RLIMIT_AS, &limit) == -1 {
+                        return Err(std::io::Error::last_os_error());
+                    }
+                }
+                Ok(())
+            });
+        }
+    }
+
     match stdio_policy {
         StdioPolicy::RedirectForShellTool => {
             // Do not create a file descriptor for stdin because otherwise some
@@ -94,6 +143,11 @@ pub(crate) async fn spawn_child_async(
             cmd.stdin(Stdio::null());
 
             cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+
+            // Lead a new process group so that a timeout or limit can kill
+            // everything the command spawned, not just the command itself.
+            #[cfg(unix)]
+            cmd.process_group(0);
         }
         StdioPolicy::Inherit => {
             // Inherit stdin, stdout, and stderr from the parent process.
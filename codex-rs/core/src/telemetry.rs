@@ -0,0 +1,268 @@
+//! Export of traces and metrics over OTLP, configured by `[telemetry]` in
+//! `config.toml`.
+//!
+//! Core emits ordinary `tracing` spans for turns (`turn`), model requests
+//! (`model_request`), tool calls (`tool_call`) and commands (`exec`), and
+//! counts tokens and approval decisions with the helpers below. A binary
+//! exports them by adding the layer returned by [`init`] to its subscriber.
+//! Without the `otel` feature, [`init`] reports that export is unavailable
+//! and the counters do nothing.
+
+use tracing::Subscriber;
+use tracing_subscriber::Layer;
+use tracing_subscriber::registry::LookupSpan;
+
+use crate::config_types::Telemetry;
+use crate::protocol::ReviewDecision;
+use crate::protocol::TokenUsage;
+
+/// A subscriber layer, boxed so that callers need not name its type.
+pub type BoxedLayer<S> = Box<dyn Layer<S> + Send + Sync + 'static>;
+
+/// Keeps the exporters running. Dropping it flushes whatever has not been
+/// exported yet, so hold it until the process is about to exit.
+pub struct TelemetryGuard {
+    #[cfg(feature = "otel")]
+    tracer_provider: opentelemetry_sdk::trace::SdkTracerProvider,
+    #[cfg(feature = "otel")]
+    meter_provider: opentelemetry_sdk::metrics::SdkMeterProvider,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        #[cfg(feature = "otel")]
+        {
+            if let Err(e) = self.tracer_provider.shutdown() {
+                tracing::warn!("failed to flush exported spans: {e}");
+            }
+            if let Err(e) = self.meter_provider.shutdown() {
+                tracing::warn!("failed to flush exported metrics: {e}");
+            }
+        }
+    }
+}
+
+/// Start exporting as configured by `settings`. Returns `Ok(None)` when
+/// telemetry is disabled, and an error when it is enabled but cannot be
+/// started.
+pub fn init<S>(settings: &Telemetry) -> std::io::Result<Option<(BoxedLayer<S>, TelemetryGuard)>>
+where
+    S: Subscriber + for<'span> LookupSpan<'span> + Send + Sync,
+{
+    if !settings.enabled {
+        return Ok(None);
+    }
+    #[cfg(feature = "otel")]
+    {
+        otlp::init(settings).map(Some)
+    }
+    #[cfg(not(feature = "otel"))]
+    {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "this build of Codex does not include OTLP export (the `otel` feature)",
+        ))
+    }
+}
+
+/// Add the token usage of one model response to the `codex.tokens` counter.
+pub(crate) fn record_token_usage(model: &str, usage: &TokenUsage) {
+    #[cfg(feature = "otel")]
+    otlp::record_token_usage(model, usage);
+    #[cfg(not(feature = "otel"))]
+    let _ = (model, usage);
+}
+
+/// Count a user's answer to an approval request in `codex.approvals`.
+/// `kind` is `"exec"` or `"patch"`.
+pub(crate) fn record_approval(kind: &'static str, decision: ReviewDecision) {
+    #[cfg(feature = "otel")]
+    otlp::record_approval(kind, decision);
+    #[cfg(not(feature = "otel"))]
+    let _ = (kind, decision);
+}
+
+#[cfg(feature = "otel")]
+mod otlp {
+    use std::collections::HashMap;
+    use std::sync::OnceLock;
+
+    use opentelemetry::KeyValue;
+    use opentelemetry::global;
+    use opentelemetry::metrics::Counter;
+    use opentelemetry::trace::TracerProvider as _;
+    use opentelemetry_otlp::MetricExporter;
+    use opentelemetry_otlp::SpanExporter;
+    use opentelemetry_otlp::WithExportConfig;
+    use opentelemetry_otlp::WithHttpConfig;
+    use opentelemetry_otlp::WithTonicConfig;
+    use opentelemetry_otlp::tonic_types::metadata::MetadataKey;
+    use opentelemetry_otlp::tonic_types::metadata::MetadataMap;
+    use opentelemetry_otlp::tonic_types::metadata::MetadataValue;
+    use opentelemetry_sdk::Resource;
+    use opentelemetry_sdk::metrics::SdkMeterProvider;
+    use opentelemetry_sdk::trace::Sampler;
+    use opentelemetry_sdk::trace::SdkTracerProvider;
+    use tracing::Subscriber;
+    use tracing_subscriber::Layer;
+    use tracing_subscriber::filter::Targets;
+    use tracing_subscriber::registry::LookupSpan;
+
+    use super::BoxedLayer;
+    use super::TelemetryGuard;
+    use crate::config_types::OtlpProtocol;
+    use crate::config_types::Telemetry;
+    use crate::protocol::ReviewDecision;
+    use crate::protocol::TokenUsage;
+
+    pub(super) fn init<S>(settings: &Telemetry) -> std::io::Result<(BoxedLayer<S>, TelemetryGuard)>
+    where
+        S: Subscriber + for<'span> LookupSpan<'span> + Send + Sync,
+    {
+        let (span_exporter, metric_exporter) = match settings.protocol {
+            OtlpProtocol::Grpc => {
+                let metadata = metadata(&settings.headers)?;
+                let span_exporter = SpanExporter::builder()
+                    .with_tonic()
+                    .with_endpoint(settings.endpoint.clone())
+                    .with_metadata(metadata.clone())
+                    .build();
+                let metric_exporter = MetricExporter::builder()
+                    .with_tonic()
+                    .with_endpoint(settings.endpoint.clone())
+                    .with_metadata(metadata)
+                    .build();
+                (span_exporter, metric_exporter)
+            }
+            OtlpProtocol::HttpProtobuf => {
+                // Over HTTP the endpoint is per signal.
+                let base = settings.endpoint.trim_end_matches('/');
+                let span_exporter = SpanExporter::builder()
+                    .with_http()
+                    .with_endpoint(format!("{base}/v1/traces"))
+                    .with_headers(settings.headers.clone())
+                    .build();
+                let metric_exporter = MetricExporter::builder()
+                    .with_http()
+                    .with_endpoint(format!("{base}/v1/metrics"))
+                    .with_headers(settings.headers.clone())
+                    .build();
+                (span_exporter, metric_exporter)
+            }
+        };
+        let span_exporter = span_exporter.map_err(std::io::Error::other)?;
+        let metric_exporter = metric_exporter.map_err(std::io::Error::other)?;
+
+        let resource = Resource::builder()
+            .with_service_name(settings.service_name.clone())
+            .build();
+        let tracer_provider = SdkTracerProvider::builder()
+            .with_batch_exporter(span_exporter)
+            .with_sampler(Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(
+                settings.sample_ratio,
+            ))))
+            .with_resource(resource.clone())
+            .build();
+        let meter_provider = SdkMeterProvider::builder()
+            .with_periodic_exporter(metric_exporter)
+            .with_resource(resource)
+            .build();
+        global::set_meter_provider(meter_provider.clone());
+
+        // Only Codex's own spans: the exporter's HTTP and gRPC clients emit
+        // spans too, and exporting those would feed back into the exporter.
+        let layer = tracing_opentelemetry::layer()
+            .with_tracer(tracer_provider.tracer("codex"))
+            .with_filter(Targets::new().with_target("codex_core", tracing::Level::INFO))
+            .boxed();
+        let guard = TelemetryGuard {
+            tracer_provider,
+            meter_provider,
+        };
+        Ok((layer, guard))
+    }
+
+    fn metadata(headers: &HashMap<String, String>) -> std::io::Result<MetadataMap> {
+        let mut metadata = MetadataMap::new();
+        for (name, value) in headers {
+            let invalid = |e: String| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("invalid telemetry header `{name}`: {e}"),
+                )
+            };
+            let key = MetadataKey::from_bytes(name.to_ascii_lowercase().as_bytes())
+                .map_err(|e| invalid(e.to_string()))?;
+            let value =
+                MetadataValue::try_from(value.as_str()).map_err(|e| invalid(e.to_string()))?;
+            metadata.insert(key, value);
+        }
+        Ok(metadata)
+    }
+
+    struct Instruments {
+        tokens: Counter<u64>,
+        approvals: Counter<u64>,
+    }
+
+    /// Created on first use, from the meter provider installed by [`init`]
+    /// or, when telemetry is disabled, the no-op default.
+    fn instruments() -> &'static Instruments {
+        static INSTRUMENTS: OnceLock<Instruments> = OnceLock::new();
+        INSTRUMENTS.get_or_init(|| {
+            let meter = global::meter("codex");
+            Instruments {
+                tokens: meter
+                    .u64_counter("codex.tokens")
+                    .with_description("Tokens used by model responses")
+                    .with_unit("{token}")
+                    .build(),
+                approvals: meter
+                    .u64_counter("codex.approvals")
+                    .with_description("Answers to approval requests")
+                    .build(),
+            }
+        })
+    }
+
+    pub(super) fn record_token_usage(model: &str, usage: &TokenUsage) {
+        let tokens = &instruments().tokens;
+        // Split so that the types add up to the total: cached input is part
+        // of the input count, and reasoning part of the output count.
+        let reasoning = usage.reasoning_output_tokens.unwrap_or(0);
+        let counts = [
+            (
+                "input",
+                usage.input_tokens.saturating_sub(usage.cached_input()),
+            ),
+            ("cached_input", usage.cached_input()),
+            ("output", usage.output_tokens.saturating_sub(reasoning)),
+            ("reasoning_output", reasoning),
+        ];
+        for (kind, count) in counts {
+            tokens.add(
+                count,
+                &[
+                    KeyValue::new("model", model.to_string()),
+                    KeyValue::new("type", kind),
+                ],
+            );
+        }
+    }
+
+    pub(super) fn record_approval(kind: &'static str, decision: ReviewDecision) {
+        let decision = match decision {
+            ReviewDecision::Approved => "approved",
+            ReviewDecision::ApprovedForSession => "approved_for_session",
+            ReviewDecision::Denied => "denied",
+            ReviewDecision::Abort => "abort",
+        };
+        instruments().approvals.add(
+            1,
+            &[
+                KeyValue::new("kind", kind),
+                KeyValue::new("decision", decision),
+            ],
+        );
+    }
+}
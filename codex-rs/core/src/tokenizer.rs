@@ -0,0 +1,208 @@
+//! Token counting for the vocabularies used by the supported model families.
+//!
+//! The BPE vocabularies themselves are not bundled, so each tokenizer counts
+//! tokens the way its vocabulary's pre-tokenizer splits text: into words,
+//! digit groups, punctuation and whitespace. Every piece costs one token
+//! unless it is long enough that the vocabulary is unlikely to hold it whole.
+//! This tracks real counts far more closely than a flat bytes-per-token ratio,
+//! particularly for code, numbers and non-English text, and for open-weight
+//! models whose SentencePiece vocabularies split text differently from
+//! tiktoken.
+
+use std::fmt;
+
+/// Counts the tokens a model's vocabulary splits text into.
+pub trait Tokenizer: fmt::Debug + Send + Sync {
+    fn count_tokens(&self, text: &str) -> u64;
+}
+
+/// The vocabulary a model family tokenizes text with.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenizerKind {
+    /// tiktoken `cl100k_base`, used by GPT-3.5 and GPT-4.
+    Cl100k,
+    /// tiktoken `o200k_base`, used by GPT-4o, GPT-4.1, GPT-5, the o-series
+    /// and gpt-oss.
+    O200k,
+    /// SentencePiece vocabularies, used by open-weight models such as Llama,
+    /// Mistral and Gemma.
+    SentencePiece,
+}
+
+impl TokenizerKind {
+    pub fn tokenizer(self) -> &'static dyn Tokenizer {
+        match self {
+            TokenizerKind::Cl100k => &CL100K,
+            TokenizerKind::O200k => &O200K,
+            TokenizerKind::SentencePiece => &SENTENCE_PIECE,
+        }
+    }
+}
+
+/// tiktoken splits text into words carrying their leading space, groups of up
+/// to three digits, runs of punctuation and runs of whitespace.
+static CL100K: PieceTokenizer = PieceTokenizer {
+    word_chars: 6,
+    digit_chars: 3,
+    punctuation_chars: 2,
+    whitespace_chars: 4,
+    non_ascii_bytes: 2,
+};
+
+/// `o200k_base` has twice the vocabulary of `cl100k_base`, so longer words,
+/// whitespace runs and non-Latin text more often fit in a single token.
+static O200K: PieceTokenizer = PieceTokenizer {
+    word_chars: 7,
+    digit_chars: 3,
+    punctuation_chars: 3,
+    whitespace_chars: 8,
+    non_ascii_bytes: 3,
+};
+
+/// SentencePiece vocabularies are smaller, split every digit and most
+/// punctuation into its own token and encode each space beyond the one
+/// joined to the following word separately.
+static SENTENCE_PIECE: PieceTokenizer = PieceTokenizer {
+    word_chars: 5,
+    digit_chars: 1,
+    punctuation_chars: 1,
+    whitespace_chars: 1,
+    non_ascii_bytes: 3,
+};
+
+#[derive(Debug)]
+struct PieceTokenizer {
+    /// Letters per token in words too long to be a single token.
+    word_chars: u64,
+    /// Digits per token.
+    digit_chars: u64,
+    /// Punctuation characters per token.
+    punctuation_chars: u64,
+    /// Whitespace characters per token, not counting a space joined to the
+    /// following word.
+    whitespace_chars: u64,
+    /// UTF-8 bytes per token for characters outside ASCII.
+    non_ascii_bytes: u64,
+}
+
+impl Tokenizer for PieceTokenizer {
+    fn count_tokens(&self, text: &str) -> u64 {
+        let mut tokens = 0;
+        let mut pieces = pieces(text).peekable();
+        while let Some((class, piece)) = pieces.next() {
+            tokens += match class {
+                CharClass::Letter => self.run_tokens(piece, self.word_chars),
+                CharClass::Digit => self.run_tokens(piece, self.digit_chars),
+                CharClass::Punctuation => self.run_tokens(piece, self.punctuation_chars),
+                CharClass::Whitespace => {
+                    let mut chars = piece.chars().count() as u64;
+                    // A single space before a word is part of the word's token.
+                    if piece.ends_with(' ') && pieces.peek().is_some() {
+                        chars -= 1;
+                    }
+                    chars.div_ceil(self.whitespace_chars)
+                }
+            };
+        }
+        tokens
+    }
+}
+
+impl PieceTokenizer {
+    fn run_tokens(&self, piece: &str, chars_per_token: u64) -> u64 {
+        let ascii = piece.bytes().filter(u8::is_ascii).count() as u64;
+        let non_ascii = piece.len() as u64 - ascii;
+        ascii.div_ceil(chars_per_token) + non_ascii.div_ceil(self.non_ascii_bytes)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Letter,
+    Digit,
+    Punctuation,
+    Whitespace,
+}
+
+impl From<char> for CharClass {
+    fn from(c: char) -> Self {
+        if c.is_alphabetic() || c == '_' {
+            CharClass::Letter
+        } else if c.is_numeric() {
+            CharClass::Digit
+        } else if c.is_whitespace() {
+            CharClass::Whitespace
+        } else {
+            CharClass::Punctuation
+        }
+    }
+}
+
+/// Splits `text` into maximal runs of characters of the same class.
+fn pieces(text: &str) -> impl Iterator<Item = (CharClass, &str)> {
+    let mut rest = text;
+    std::iter::from_fn(move || {
+        let class = CharClass::from(rest.chars().next()?);
+        let end = rest
+            .char_indices()
+            .find(|(_, c)| CharClass::from(*c) != class)
+            .map_or(rest.len(), |(i, _)| i);
+        let (piece, tail) = rest.split_at(end);
+        rest = tail;
+        Some((class, piece))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn count(kind: TokenizerKind, text: &str) -> u64 {
+        kind.tokenizer().count_tokens(text)
+    }
+
+    #[test]
+    fn common_words_are_one_token_each() {
+        for kind in [
+            TokenizerKind::Cl100k,
+            TokenizerKind::O200k,
+            TokenizerKind::SentencePiece,
+        ] {
+            assert_eq!(count(kind, ""), 0);
+            assert_eq!(count(kind, "hello world"), 2, "{kind:?}");
+            assert_eq!(count(kind, "the quick brown fox"), 4, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn sentence_piece_splits_digits_and_whitespace() {
+        let text = "    let x = 12345;";
+        assert_eq!(count(TokenizerKind::O200k, text), 7);
+        assert_eq!(count(TokenizerKind::Cl100k, text), 7);
+        assert_eq!(count(TokenizerKind::SentencePiece, text), 12);
+    }
+
+    #[test]
+    fn larger_vocabularies_need_fewer_tokens_for_non_ascii_text() {
+        let text = "こんにちは世界";
+        assert_eq!(count(TokenizerKind::Cl100k, text), 11);
+        assert_eq!(count(TokenizerKind::O200k, text), 7);
+    }
+
+    #[test]
+    fn pieces_group_characters_by_class() {
+        let pieces: Vec<(CharClass, &str)> = pieces("foo_bar(42),  x").collect();
+        assert_eq!(
+            pieces,
+            vec![
+                (CharClass::Letter, "foo_bar"),
+                (CharClass::Punctuation, "("),
+                (CharClass::Digit, "42"),
+                (CharClass::Punctuation, "),"),
+                (CharClass::Whitespace, "  "),
+                (CharClass::Letter, "x"),
+            ]
+        );
+    }
+}
@@ -8,9 +8,13 @@ use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
 use sha1::digest::Output;
+use tracing::warn;
 use uuid::Uuid;
 
 use crate::protocol::FileChange;
+use crate::turn_snapshot::SnapshotEntry;
+use crate::turn_snapshot::SnapshotStore;
+use crate::turn_snapshot::TurnSnapshot;
 
 const ZERO_OID: &str = "0000000000000000000000000000000000000000";
 const DEV_NULL: &str = "/dev/null";
@@ -40,6 +44,12 @@ pub struct TurnDiffTracker {
     temp_name_to_current_path: HashMap<String, PathBuf>,
     /// Cache of known git worktree roots to avoid repeated filesystem walks.
     git_root_cache: Vec<PathBuf>,
+    /// When set, baselines are also written to this store so the turn can be
+    /// reverted after it ends.
+    snapshot_store: Option<SnapshotStore>,
+    /// Internal filename -> blob id of the stored baseline, or `None` if the
+    /// file did not exist. Files whose baseline could not be stored are absent.
+    snapshot_blobs: HashMap<String, Option<String>>,
 }
 
 impl TurnDiffTracker {
@@ -47,6 +57,15 @@ impl TurnDiffTracker {
         Self::default()
     }
 
+    /// Like `new`, but also keeps the pre-image of every touched file in
+    /// `store` so that `snapshot` can describe how to revert the turn.
+    pub(crate) fn with_snapshot_store(store: SnapshotStore) -> Self {
+        Self {
+            snapshot_store: Some(store),
+            ..Self::default()
+        }
+    }
+
     /// Front-run apply patch calls to track the starting contents of any modified files.
     /// - Creates an in-memory baseline snapshot for files that already exist on disk when first seen.
     /// - For additions, we intentionally do not create a baseline snapshot so that diffs are proper additions.
@@ -88,6 +107,7 @@ impl TurnDiffTracker {
                 };
 
                 if let Some(baseline_file_info) = baseline_file_info {
+                    self.store_snapshot_blob(&internal, &baseline_file_info);
                     self.baseline_file_info
                         .insert(internal.clone(), baseline_file_info);
                 }
@@ -127,6 +147,57 @@ impl TurnDiffTracker {
         }
     }
 
+    fn store_snapshot_blob(&mut self, internal: &str, info: &BaselineFileInfo) {
+        let Some(store) = &self.snapshot_store else {
+            return;
+        };
+        if info.oid == ZERO_OID {
+            self.snapshot_blobs.insert(internal.to_string(), None);
+            return;
+        }
+        // Key by the hash of the bytes we hold; `info.oid` may come from git,
+        // which applies filters such as line-ending conversion.
+        let oid = format!("{:x}", git_blob_sha1_hex_bytes(&info.content));
+        match store.put(&oid, &info.content) {
+            Ok(()) => {
+                self.snapshot_blobs.insert(internal.to_string(), Some(oid));
+            }
+            Err(e) => {
+                warn!(
+                    "failed to snapshot {}; it will not be reverted: {e}",
+                    info.path.display()
+                );
+            }
+        }
+    }
+
+    /// Current paths of the files touched so far, including renamed ones at
+    /// their new location.
+    pub(crate) fn touched_paths(&self) -> Vec<PathBuf> {
+        self.temp_name_to_current_path.values().cloned().collect()
+    }
+
+    /// Describe how to restore every file touched so far to its baseline.
+    /// Returns `None` unless the tracker was created with a snapshot store.
+    pub(crate) fn snapshot(&self) -> Option<TurnSnapshot> {
+        self.snapshot_store.as_ref()?;
+        let mut entries: Vec<SnapshotEntry> = self
+            .snapshot_blobs
+            .iter()
+            .filter_map(|(internal, blob)| {
+                let info = self.baseline_file_info.get(internal)?;
+                Some(SnapshotEntry {
+                    original_path: info.path.clone(),
+                    current_path: self.get_path_for_internal(internal)?,
+                    blob: blob.clone(),
+                    mode: info.mode,
+                })
+            })
+            .collect();
+        entries.sort_by(|a, b| a.original_path.cmp(&b.original_path));
+        Some(TurnSnapshot { entries })
+    }
+
     fn get_path_for_internal(&self, internal: &str) -> Option<PathBuf> {
         self.temp_name_to_current_path
             .get(internal)
@@ -380,7 +451,7 @@ fn git_blob_sha1_hex_bytes(data: &[u8]) -> Output<sha1::Sha1> {
 }
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
-enum FileMode {
+pub(crate) enum FileMode {
     Regular,
     #[cfg(unix)]
     Executable,
@@ -883,4 +954,50 @@ index {ZERO_OID}..{right_oid}
         };
         assert_eq!(combined, expected_combined);
     }
+
+    #[test]
+    fn snapshot_reverts_move_and_add() {
+        let home = tempdir().unwrap();
+        let dir = tempdir().unwrap();
+        let src = dir.path().join("src.txt");
+        let dest = dir.path().join("dst.txt");
+        let added = dir.path().join("added.txt");
+        fs::write(&src, "line\n").unwrap();
+
+        let store = SnapshotStore::new(home.path());
+        let mut acc = TurnDiffTracker::with_snapshot_store(store.clone());
+        acc.on_patch_begin(&HashMap::from([
+            (
+                src.clone(),
+                FileChange::Update {
+                    unified_diff: "".to_owned(),
+                    move_path: Some(dest.clone()),
+                },
+            ),
+            (
+                added.clone(),
+                FileChange::Add {
+                    content: "new\n".to_string(),
+                },
+            ),
+        ]));
+
+        // Simulate apply.
+        fs::rename(&src, &dest).unwrap();
+        fs::write(&dest, "line2\n").unwrap();
+        fs::write(&added, "new\n").unwrap();
+
+        let snapshot = acc.snapshot().unwrap();
+        snapshot.restore(&store).unwrap();
+
+        assert_eq!(fs::read_to_string(&src).unwrap(), "line\n");
+        assert!(!dest.exists());
+        assert!(!added.exists());
+    }
+
+    #[test]
+    fn snapshot_is_none_without_store() {
+        let acc = TurnDiffTracker::new();
+        assert_eq!(acc.snapshot(), None);
+    }
 }
@@ -0,0 +1,190 @@
+//! Pre-images of the files touched during a turn, so that the turn can be
+//! reverted later.
+//!
+//! File contents are stored once per distinct blob under
+//! `$CODEX_HOME/snapshots/objects`, keyed by their git blob id, so snapshots of
+//! unchanged files are shared across turns and sessions. The per-turn
+//! manifest that maps paths to blobs is kept in memory by the session.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+use crate::turn_diff_tracker::FileMode;
+
+/// Content-addressed blob store for file snapshots.
+#[derive(Clone, Debug)]
+pub(crate) struct SnapshotStore {
+    objects_dir: PathBuf,
+}
+
+impl SnapshotStore {
+    pub(crate) fn new(codex_home: &Path) -> Self {
+        Self {
+            objects_dir: codex_home.join("snapshots").join("objects"),
+        }
+    }
+
+    /// Write `content` under `oid` unless a blob with that id already exists.
+    pub(crate) fn put(&self, oid: &str, content: &[u8]) -> io::Result<()> {
+        let path = self.blob_path(oid);
+        if path.exists() {
+            return Ok(());
+        }
+        let dir = path
+            .parent()
+            .ok_or_else(|| io::Error::other("snapshot blob path has no parent"))?;
+        fs::create_dir_all(dir)?;
+        // Write to a temporary file first so an interrupted write never leaves
+        // a truncated blob behind under its final name.
+        let tmp = dir.join(format!(".{oid}.tmp"));
+        fs::write(&tmp, content)?;
+        fs::rename(&tmp, &path)
+    }
+
+    fn get(&self, oid: &str) -> io::Result<Vec<u8>> {
+        fs::read(self.blob_path(oid))
+    }
+
+    fn blob_path(&self, oid: &str) -> PathBuf {
+        let (prefix, rest) = oid.split_at(2.min(oid.len()));
+        self.objects_dir.join(prefix).join(rest)
+    }
+}
+
+/// How to put one file back the way it was before the turn.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct SnapshotEntry {
+    /// Where the file was before the turn.
+    pub(crate) original_path: PathBuf,
+    /// Where the file is after the turn; differs from `original_path` when the
+    /// turn moved it.
+    pub(crate) current_path: PathBuf,
+    /// Blob holding the pre-image, or `None` if the file did not exist.
+    pub(crate) blob: Option<String>,
+    pub(crate) mode: FileMode,
+}
+
+/// The pre-images of every file touched during one turn.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct TurnSnapshot {
+    pub(crate) entries: Vec<SnapshotEntry>,
+}
+
+impl TurnSnapshot {
+    /// Restore every file to its pre-image, removing files the turn created
+    /// and moving renamed files back. Returns the paths that were written or
+    /// removed.
+    pub(crate) fn restore(&self, store: &SnapshotStore) -> io::Result<Vec<PathBuf>> {
+        let mut paths = Vec::new();
+        for entry in &self.entries {
+            if entry.current_path != entry.original_path {
+                remove_if_exists(&entry.current_path)?;
+                paths.push(entry.current_path.clone());
+            }
+            match &entry.blob {
+                Some(oid) => {
+                    let content = store.get(oid)?;
+                    write_with_mode(&entry.original_path, &content, entry.mode)?;
+                }
+                None => remove_if_exists(&entry.original_path)?,
+            }
+            paths.push(entry.original_path.clone());
+        }
+        Ok(paths)
+    }
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        other => other,
+    }
+}
+
+fn write_with_mode(path: &Path, content: &[u8], mode: FileMode) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    // A symlink in the way would be followed by `fs::write`.
+    remove_if_exists(path)?;
+    match mode {
+        #[cfg(unix)]
+        FileMode::Symlink => {
+            use std::os::unix::ffi::OsStrExt;
+            let target = std::ffi::OsStr::from_bytes(content);
+            std::os::unix::fs::symlink(target, path)
+        }
+        #[cfg(unix)]
+        FileMode::Executable => {
+            use std::os::unix::fs::PermissionsExt;
+            fs::write(path, content)?;
+            fs::set_permissions(path, fs::Permissions::from_mode(0o755))
+        }
+        _ => fs::write(path, content),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    #[test]
+    fn restore_rewrites_removes_and_moves_back() {
+        let home = tempdir().unwrap();
+        let work = tempdir().unwrap();
+        let store = SnapshotStore::new(home.path());
+
+        let updated = work.path().join("updated.txt");
+        let added = work.path().join("added.txt");
+        let moved_from = work.path().join("old.txt");
+        let moved_to = work.path().join("new.txt");
+        fs::write(&updated, "after\n").unwrap();
+        fs::write(&added, "new\n").unwrap();
+        fs::write(&moved_to, "moved\n").unwrap();
+
+        store.put("aa11", b"before\n").unwrap();
+        store.put("bb22", b"original\n").unwrap();
+        let snapshot = TurnSnapshot {
+            entries: vec![
+                SnapshotEntry {
+                    original_path: updated.clone(),
+                    current_path: updated.clone(),
+                    blob: Some("aa11".to_string()),
+                    mode: FileMode::Regular,
+                },
+                SnapshotEntry {
+                    original_path: added.clone(),
+                    current_path: added.clone(),
+                    blob: None,
+                    mode: FileMode::Regular,
+                },
+                SnapshotEntry {
+                    original_path: moved_from.clone(),
+                    current_path: moved_to.clone(),
+                    blob: Some("bb22".to_string()),
+                    mode: FileMode::Regular,
+                },
+            ],
+        };
+
+        let paths = snapshot.restore(&store).unwrap();
+
+        assert_eq!(
+            paths,
+            vec![
+                updated.clone(),
+                added.clone(),
+                moved_to.clone(),
+                moved_from.clone()
+            ]
+        );
+        assert_eq!(fs::read_to_string(&updated).unwrap(), "before\n");
+        assert!(!added.exists());
+        assert!(!moved_to.exists());
+        assert_eq!(fs::read_to_string(&moved_from).unwrap(), "original\n");
+    }
+}
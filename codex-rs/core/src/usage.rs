@@ -0,0 +1,351 @@
+//! Attributes the token totals a provider reports for a model request to the
+//! parts of that request: instructions, tool definitions, messages, reasoning
+//! and the calls made to each tool.
+//!
+//! Providers only report how many input and output tokens a request used, so
+//! every part is weighted by the tokens in its serialized JSON, as counted by
+//! the model family's tokenizer, and the totals are split in proportion to
+//! those weights. Providers that report no usage at all get the weights
+//! themselves as an estimate.
+
+use std::collections::HashMap;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+
+use crate::client_common::USER_INSTRUCTIONS_START;
+use crate::environment_context::ENVIRONMENT_CONTEXT_START;
+use crate::openai_tools::OpenAiTool;
+use crate::protocol::TokenUsage;
+use crate::protocol::TokenUsageBreakdown;
+use crate::protocol::ToolTokenUsage;
+use crate::repo_map::REPO_MAP_START;
+use crate::tokenizer::Tokenizer;
+
+/// Tool name used for outputs whose call is not part of the request.
+const UNKNOWN_TOOL: &str = "unknown";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Instructions,
+    ToolSchemas,
+    Messages,
+    Reasoning,
+    Tool(String),
+}
+
+/// Estimates the usage of a request for providers that do not report it.
+pub(crate) fn estimate_token_usage(
+    tokenizer: &dyn Tokenizer,
+    instructions: &str,
+    tools: &[OpenAiTool],
+    input: &[ResponseItem],
+    output: &[ResponseItem],
+) -> TokenUsage {
+    let weights = Weights::new(tokenizer, instructions, tools, input, output);
+    let input_tokens = weights.input.iter().map(|(_, w)| w).sum();
+    let output_tokens = weights.output.iter().map(|(_, w)| w).sum();
+    TokenUsage {
+        input_tokens,
+        cached_input_tokens: None,
+        output_tokens,
+        reasoning_output_tokens: None,
+        total_tokens: input_tokens + output_tokens,
+    }
+}
+
+/// Splits `usage` across the request built from `instructions`, `tools` and
+/// `input`, and the `output` items the model produced in response.
+pub(crate) fn attribute_token_usage(
+    tokenizer: &dyn Tokenizer,
+    instructions: &str,
+    tools: &[OpenAiTool],
+    input: &[ResponseItem],
+    output: &[ResponseItem],
+    usage: &TokenUsage,
+) -> TokenUsageBreakdown {
+    let Weights {
+        input: input_weights,
+        output: output_weights,
+    } = Weights::new(tokenizer, instructions, tools, input, output);
+
+    // Reasoning output is reported separately; the rest of the output is
+    // split across the other items the model produced.
+    let reasoning_output = usage
+        .reasoning_output_tokens
+        .unwrap_or(0)
+        .min(usage.output_tokens);
+
+    let mut breakdown = TokenUsageBreakdown::default();
+    for (segment, tokens) in split(&input_weights, usage.input_tokens, Segment::Instructions)
+        .into_iter()
+        .chain(split(
+            &output_weights,
+            usage.output_tokens - reasoning_output,
+            Segment::Messages,
+        ))
+        .chain(std::iter::once((Segment::Reasoning, reasoning_output)))
+    {
+        add_tokens(&mut breakdown, segment, tokens);
+    }
+    breakdown.tools.retain(|tool| tool.tokens > 0);
+    breakdown.tools.sort_by(|a, b| a.name.cmp(&b.name));
+    breakdown
+}
+
+struct Weights {
+    input: Vec<(Segment, u64)>,
+    /// Reasoning items are left out: their text is not returned in full.
+    output: Vec<(Segment, u64)>,
+}
+
+impl Weights {
+    fn new(
+        tokenizer: &dyn Tokenizer,
+        instructions: &str,
+        tools: &[OpenAiTool],
+        input: &[ResponseItem],
+        output: &[ResponseItem],
+    ) -> Self {
+        let tool_names = tool_names_by_call_id(input.iter().chain(output));
+
+        let mut input_weights = vec![(Segment::Instructions, tokenizer.count_tokens(instructions))];
+        let schema_weight = tools
+            .iter()
+            .map(|tool| serde_json::to_string(tool).map_or(0, |json| tokenizer.count_tokens(&json)))
+            .sum();
+        input_weights.push((Segment::ToolSchemas, schema_weight));
+        input_weights.extend(
+            input
+                .iter()
+                .filter_map(|item| weigh_item(tokenizer, item, &tool_names)),
+        );
+
+        let output_weights = output
+            .iter()
+            .filter(|item| !matches!(item, ResponseItem::Reasoning { .. }))
+            .filter_map(|item| weigh_item(tokenizer, item, &tool_names))
+            .collect();
+
+        Self {
+            input: input_weights,
+            output: output_weights,
+        }
+    }
+}
+
+fn tool_names_by_call_id<'a>(
+    items: impl Iterator<Item = &'a ResponseItem>,
+) -> HashMap<&'a str, &'a str> {
+    items
+        .filter_map(|item| match item {
+            ResponseItem::FunctionCall { call_id, name, .. }
+            | ResponseItem::CustomToolCall { call_id, name, .. } => {
+                Some((call_id.as_str(), name.as_str()))
+            }
+            ResponseItem::LocalShellCall {
+                call_id: Some(call_id),
+                ..
+            } => Some((call_id.as_str(), "local_shell")),
+            _ => None,
+        })
+        .collect()
+}
+
+fn weigh_item(
+    tokenizer: &dyn Tokenizer,
+    item: &ResponseItem,
+    tool_names: &HashMap<&str, &str>,
+) -> Option<(Segment, u64)> {
+    let segment = match item {
+        ResponseItem::Message { role, content, .. } => {
+            let is_instructions = role == "user"
+                && content.iter().any(|c| {
+                    matches!(c, ContentItem::InputText { text }
+                        if text.starts_with(USER_INSTRUCTIONS_START)
+                            || text.starts_with(ENVIRONMENT_CONTEXT_START)
+                            || text.starts_with(REPO_MAP_START))
+                });
+            if is_instructions {
+                Segment::Instructions
+            } else {
+                Segment::Messages
+            }
+        }
+        ResponseItem::Reasoning { .. } => Segment::Reasoning,
+        ResponseItem::FunctionCall { name, .. } | ResponseItem::CustomToolCall { name, .. } => {
+            Segment::Tool(name.clone())
+        }
+        ResponseItem::LocalShellCall { .. } => Segment::Tool("local_shell".to_string()),
+        ResponseItem::FunctionCallOutput { call_id, .. }
+        | ResponseItem::CustomToolCallOutput { call_id, .. } => Segment::Tool(
+            tool_names
+                .get(call_id.as_str())
+                .copied()
+                .unwrap_or(UNKNOWN_TOOL)
+                .to_string(),
+        ),
+        ResponseItem::Other => return None,
+    };
+    let weight = serde_json::to_string(item).map_or(0, |json| tokenizer.count_tokens(&json));
+    Some((segment, weight))
+}
+
+/// Splits `total` in proportion to `weights`, rounding so the shares add up
+/// to exactly `total`. With no weight at all, everything goes to `fallback`.
+fn split(weights: &[(Segment, u64)], total: u64, fallback: Segment) -> Vec<(Segment, u64)> {
+    let sum: u128 = weights.iter().map(|(_, w)| u128::from(*w)).sum();
+    if sum == 0 {
+        return vec![(fallback, total)];
+    }
+    let mut shares: Vec<(Segment, u64, u128)> = weights
+        .iter()
+        .map(|(segment, weight)| {
+            let exact = u128::from(*weight) * u128::from(total);
+            (segment.clone(), (exact / sum) as u64, exact % sum)
+        })
+        .collect();
+
+    // Hand the tokens lost to rounding down to the largest remainders.
+    let assigned: u64 = shares.iter().map(|(_, share, _)| share).sum();
+    let mut order: Vec<usize> = (0..shares.len()).collect();
+    order.sort_by(|a, b| shares[*b].2.cmp(&shares[*a].2));
+    for index in order.into_iter().take((total - assigned) as usize) {
+        shares[index].1 += 1;
+    }
+    shares
+        .into_iter()
+        .map(|(segment, share, _)| (segment, share))
+        .collect()
+}
+
+fn add_tokens(breakdown: &mut TokenUsageBreakdown, segment: Segment, tokens: u64) {
+    match segment {
+        Segment::Instructions => breakdown.instructions += tokens,
+        Segment::ToolSchemas => breakdown.tool_schemas += tokens,
+        Segment::Messages => breakdown.messages += tokens,
+        Segment::Reasoning => breakdown.reasoning += tokens,
+        Segment::Tool(name) => match breakdown.tools.iter_mut().find(|t| t.name == name) {
+            Some(tool) => tool.tokens += tokens,
+            None => breakdown.tools.push(ToolTokenUsage { name, tokens }),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokenizer::TokenizerKind;
+    use codex_protocol::models::FunctionCallOutputPayload;
+    use pretty_assertions::assert_eq;
+
+    fn tokenizer() -> &'static dyn Tokenizer {
+        TokenizerKind::O200k.tokenizer()
+    }
+
+    fn user_message(text: &str) -> ResponseItem {
+        ResponseItem::Message {
+            id: None,
+            role: "user".to_string(),
+            content: vec![ContentItem::InputText {
+                text: text.to_string(),
+            }],
+        }
+    }
+
+    fn function_call(call_id: &str, name: &str, arguments: &str) -> ResponseItem {
+        ResponseItem::FunctionCall {
+            id: None,
+            name: name.to_string(),
+            arguments: arguments.to_string(),
+            call_id: call_id.to_string(),
+        }
+    }
+
+    fn function_call_output(call_id: &str, content: &str) -> ResponseItem {
+        ResponseItem::FunctionCallOutput {
+            call_id: call_id.to_string(),
+            output: FunctionCallOutputPayload {
+                content: content.to_string(),
+                success: Some(true),
+            },
+        }
+    }
+
+    #[test]
+    fn split_rounds_to_the_exact_total() {
+        let weights = vec![
+            (Segment::Instructions, 1),
+            (Segment::Messages, 1),
+            (Segment::Reasoning, 1),
+        ];
+        let shares = split(&weights, 10, Segment::Instructions);
+        assert_eq!(
+            shares,
+            vec![
+                (Segment::Instructions, 4),
+                (Segment::Messages, 3),
+                (Segment::Reasoning, 3),
+            ]
+        );
+    }
+
+    #[test]
+    fn split_without_weights_uses_fallback() {
+        assert_eq!(
+            split(&[], 7, Segment::Messages),
+            vec![(Segment::Messages, 7)]
+        );
+    }
+
+    #[test]
+    fn outputs_are_attributed_to_the_tool_that_was_called() {
+        let big_output = "x ".repeat(2_000);
+        let input = vec![
+            user_message("<environment_context>\n  <cwd>/repo</cwd>\n</environment_context>"),
+            user_message("list the files"),
+            function_call("call_1", "shell", r#"{"command":["ls"]}"#),
+            function_call_output("call_1", &big_output),
+        ];
+        let output = vec![function_call("call_2", "update_plan", r#"{"plan":[]}"#)];
+        let usage = TokenUsage {
+            input_tokens: 1_000,
+            cached_input_tokens: None,
+            output_tokens: 50,
+            reasoning_output_tokens: Some(30),
+            total_tokens: 1_050,
+        };
+
+        let breakdown =
+            attribute_token_usage(tokenizer(), "be helpful", &[], &input, &output, &usage);
+
+        assert_eq!(breakdown.total(), 1_050);
+        assert_eq!(breakdown.reasoning, 30);
+        assert_eq!(breakdown.tool_schemas, 0);
+        let names: Vec<&str> = breakdown.tools.iter().map(|t| t.name.as_str()).collect();
+        assert_eq!(names, vec!["shell", "update_plan"]);
+        // The large shell output dominates the prompt.
+        assert!(breakdown.tools[0].tokens > 900, "{breakdown:?}");
+        // The only non-reasoning output is the update_plan call.
+        assert_eq!(breakdown.tools[1].tokens, 20);
+        assert!(breakdown.instructions > 0);
+        assert!(breakdown.messages > 0);
+    }
+
+    #[test]
+    fn estimated_usage_counts_input_and_output_tokens() {
+        let input = vec![user_message("list the files")];
+        let output = vec![function_call("call_1", "shell", r#"{"command":["ls"]}"#)];
+
+        let usage = estimate_token_usage(tokenizer(), "be helpful", &[], &input, &output);
+
+        let count_json = |item: &ResponseItem| {
+            tokenizer().count_tokens(&serde_json::to_string(item).unwrap_or_default())
+        };
+        let input_tokens = tokenizer().count_tokens("be helpful") + count_json(&input[0]);
+        let output_tokens = count_json(&output[0]);
+        assert_eq!(usage.input_tokens, input_tokens);
+        assert_eq!(usage.output_tokens, output_tokens);
+        assert_eq!(usage.total_tokens, input_tokens + output_tokens);
+    }
+}
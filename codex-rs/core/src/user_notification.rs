@@ -1,4 +1,22 @@
+//! Notifications about events that need the user's attention, so that the
+//! TUI can be left in the background while a long task runs.
+//!
+//! A [`UserNotification`] whose event is listed in `[notifications] events`
+//! is passed as JSON to the `notify` program, when one is configured, and
+//! shown as a native desktop notification when `[notifications] desktop` is
+//! set: through `osascript` on macOS and `notify-rust` elsewhere.
+
+use std::path::PathBuf;
+
 use serde::Serialize;
+use tracing::error;
+use tracing::warn;
+
+use crate::config_types::NotificationEvent;
+use crate::config_types::Notifications;
+
+/// Desktop notifications show a few lines at most; longer bodies are cut.
+const DESKTOP_BODY_MAX_CHARS: usize = 200;
 
 /// User can configure a program that will receive notifications. Each
 /// notification is serialized as JSON and passed as an argument to the
@@ -16,11 +34,189 @@ pub(crate) enum UserNotification {
         /// The last message sent by the assistant in the turn.
         last_assistant_message: Option<String>,
     },
+
+    #[serde(rename_all = "kebab-case")]
+    ApprovalRequested {
+        turn_id: String,
+        call_id: String,
+        kind: ApprovalKind,
+
+        /// The command line to run, or the files the patch changes.
+        summary: String,
+    },
+
+    #[serde(rename_all = "kebab-case")]
+    Error { turn_id: String, message: String },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub(crate) enum ApprovalKind {
+    Exec,
+    Patch,
+}
+
+impl UserNotification {
+    fn event(&self) -> NotificationEvent {
+        match self {
+            UserNotification::AgentTurnComplete { .. } => NotificationEvent::AgentTurnComplete,
+            UserNotification::ApprovalRequested { .. } => NotificationEvent::ApprovalRequested,
+            UserNotification::Error { .. } => NotificationEvent::Error,
+        }
+    }
+
+    /// Title and body of the desktop notification.
+    fn title_and_body(&self) -> (&'static str, String) {
+        let (title, body) = match self {
+            UserNotification::AgentTurnComplete {
+                input_messages,
+                last_assistant_message,
+                ..
+            } => (
+                "Codex: turn complete",
+                last_assistant_message
+                    .clone()
+                    .unwrap_or_else(|| input_messages.join(" ")),
+            ),
+            UserNotification::ApprovalRequested {
+                kind: ApprovalKind::Exec,
+                summary,
+                ..
+            } => ("Codex: approval needed", format!("Run {summary}?")),
+            UserNotification::ApprovalRequested {
+                kind: ApprovalKind::Patch,
+                summary,
+                ..
+            } => ("Codex: approval needed", format!("Edit {summary}?")),
+            UserNotification::Error { message, .. } => ("Codex: error", message.clone()),
+        };
+        (title, truncate_chars(body.trim(), DESKTOP_BODY_MAX_CHARS))
+    }
+}
+
+/// `command` as the user would type it, for
+/// [`UserNotification::ApprovalRequested`].
+pub(crate) fn command_summary(command: &[String]) -> String {
+    match command {
+        [bash, flag, script] if bash == "bash" && flag == "-lc" => script.clone(),
+        _ => shlex::try_join(command.iter().map(String::as_str))
+            .unwrap_or_else(|_| command.join(" ")),
+    }
+}
+
+/// `files` as a comma-separated list, for
+/// [`UserNotification::ApprovalRequested`].
+pub(crate) fn files_summary(files: &[PathBuf]) -> String {
+    files
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Delivers [`UserNotification`]s as configured by `notify` and
+/// `[notifications]`.
+pub(crate) struct Notifier {
+    /// External notifier command (will be passed as args to exec()). When
+    /// `None` no program is run.
+    program: Option<Vec<String>>,
+    settings: Notifications,
+}
+
+impl Notifier {
+    pub(crate) fn new(program: Option<Vec<String>>, settings: Notifications) -> Self {
+        Self { program, settings }
+    }
+
+    /// Deliver `notification` if its event is enabled. Failures are logged
+    /// but otherwise ignored so that notification issues do not interfere
+    /// with the main workflow.
+    pub(crate) fn notify(&self, notification: UserNotification) {
+        if !self.settings.events.contains(&notification.event()) {
+            return;
+        }
+        if let Some(program) = &self.program {
+            run_program(program, &notification);
+        }
+        if self.settings.desktop {
+            show_desktop_notification(&notification);
+        }
+    }
+}
+
+/// Spawn `program` with the JSON payload as the last argument.
+fn run_program(program: &[String], notification: &UserNotification) {
+    let Some((name, args)) = program.split_first() else {
+        return;
+    };
+
+    let Ok(json) = serde_json::to_string(notification) else {
+        error!("failed to serialise notification payload");
+        return;
+    };
+
+    let mut command = std::process::Command::new(name);
+    command.args(args).arg(json);
+
+    // Fire-and-forget – we do not wait for completion.
+    if let Err(e) = command.spawn() {
+        warn!("failed to spawn notifier '{name}': {e}");
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn show_desktop_notification(notification: &UserNotification) {
+    let (title, body) = notification.title_and_body();
+    let script = format!(
+        "display notification {} with title {}",
+        applescript_string(&body),
+        applescript_string(title)
+    );
+    let result = std::process::Command::new("osascript")
+        .arg("-e")
+        .arg(script)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn();
+    if let Err(e) = result {
+        warn!("failed to show desktop notification: {e}");
+    }
+}
+
+/// `s` as an AppleScript string literal.
+#[cfg(target_os = "macos")]
+fn applescript_string(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(not(target_os = "macos"))]
+fn show_desktop_notification(notification: &UserNotification) {
+    let (title, body) = notification.title_and_body();
+    // Showing a notification talks to the notification daemon and may
+    // block, so keep it off the session's task.
+    std::thread::spawn(move || {
+        let result = notify_rust::Notification::new()
+            .appname("Codex")
+            .summary(title)
+            .body(&body)
+            .show();
+        if let Err(e) = result {
+            warn!("failed to show desktop notification: {e}");
+        }
+    });
+}
+
+fn truncate_chars(s: &str, max_chars: usize) -> String {
+    match s.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}…", &s[..end]),
+        None => s.to_string(),
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pretty_assertions::assert_eq;
 
     #[test]
     fn test_user_notification() {
@@ -37,4 +233,38 @@ mod tests {
             r#"{"type":"agent-turn-complete","turn-id":"12345","input-messages":["Rename `foo` to `bar` and update the callsites."],"last-assistant-message":"Rename complete and verified `cargo build` succeeds."}"#
         );
     }
+
+    #[test]
+    fn approval_request_payload_and_desktop_text() {
+        let notification = UserNotification::ApprovalRequested {
+            turn_id: "7".to_string(),
+            call_id: "call-1".to_string(),
+            kind: ApprovalKind::Exec,
+            summary: command_summary(&[
+                "bash".to_string(),
+                "-lc".to_string(),
+                "cargo publish".to_string(),
+            ]),
+        };
+
+        assert_eq!(
+            serde_json::to_string(&notification).unwrap(),
+            r#"{"type":"approval-requested","turn-id":"7","call-id":"call-1","kind":"exec","summary":"cargo publish"}"#
+        );
+        assert_eq!(
+            notification.title_and_body(),
+            ("Codex: approval needed", "Run cargo publish?".to_string())
+        );
+    }
+
+    #[test]
+    fn desktop_body_is_truncated() {
+        let notification = UserNotification::Error {
+            turn_id: "7".to_string(),
+            message: "é".repeat(DESKTOP_BODY_MAX_CHARS + 1),
+        };
+
+        let (_, body) = notification.title_and_body();
+        assert_eq!(body, format!("{}…", "é".repeat(DESKTOP_BODY_MAX_CHARS)));
+    }
 }
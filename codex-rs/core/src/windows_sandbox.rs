@@ -0,0 +1,134 @@
+use crate::config_types::ExecLimits;
+use crate::protocol::SandboxPolicy;
+use crate::spawn::SpawnChildRequest;
+use crate::spawn::StdioPolicy;
+use crate::spawn::spawn_child_async;
+use std::collections::HashMap;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Stdio;
+use tokio::process::Child;
+use tokio::process::Command;
+
+/// Because the "arg0 trick" is not available on Windows, the Windows sandbox
+/// helper is invoked by re-executing the current binary with this value as
+/// `argv[1]`.
+pub const CODEX_WINDOWS_SANDBOX_ARG1: &str = "--codex-run-as-windows-sandbox";
+
+/// When passed after [`CODEX_WINDOWS_SANDBOX_ARG1`], the helper removes the
+/// access it granted on the paths that follow instead of running a command.
+pub const CODEX_WINDOWS_SANDBOX_REVOKE_ARG: &str = "--revoke-access";
+
+/// Spawn a shell tool command under the Windows restricted-token sandbox
+/// helper (codex-windows-sandbox).
+///
+/// The helper runs inside the current executable and accepts the same
+/// arguments as `codex-linux-sandbox`: the cwd the policy is relative to, the
+/// serialized [`SandboxPolicy`], and the command to run.
+///
+/// Fails with [`std::io::ErrorKind::Unsupported`] if the policy restricts
+/// writes and has a `read_blocklist`, which the helper cannot enforce.
+pub async fn spawn_command_under_windows_sandbox(
+    command: Vec<String>,
+    sandbox_policy: &SandboxPolicy,
+    cwd: PathBuf,
+    stdio_policy: StdioPolicy,
+    env: HashMap<String, String>,
+    limits: &ExecLimits,
+) -> std::io::Result<Child> {
+    if !sandbox_policy.has_full_disk_write_access() && !sandbox_policy.has_full_disk_read_access() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Unsupported,
+            "read_blocklist is not supported by the Windows sandbox",
+        ));
+    }
+
+    let codex_exe = std::env::current_exe()?;
+    let args = create_windows_sandbox_command_args(command, sandbox_policy, &cwd);
+    let arg0 = None;
+    spawn_child_async(SpawnChildRequest {
+        program: codex_exe,
+        args,
+        arg0,
+        cwd,
+        sandbox_policy,
+        stdio_policy,
+        env,
+        limits,
+    })
+    .await
+}
+
+/// Removes the ACL entries that sandboxed commands added to `paths`. The
+/// helper grants access on first use and leaves it in place for later
+/// commands, so this is called once when the session ends.
+pub async fn revoke_windows_sandbox_access(paths: Vec<PathBuf>) -> std::io::Result<()> {
+    let codex_exe = std::env::current_exe()?;
+    let status = Command::new(codex_exe)
+        .arg(CODEX_WINDOWS_SANDBOX_ARG1)
+        .arg(CODEX_WINDOWS_SANDBOX_REVOKE_ARG)
+        .args(paths)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .status()
+        .await?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!(
+            "Windows sandbox helper exited with {status}"
+        )))
+    }
+}
+
+/// Converts the sandbox policy into the arguments for the Windows sandbox
+/// helper.
+fn create_windows_sandbox_command_args(
+    command: Vec<String>,
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+) -> Vec<String> {
+    #[expect(clippy::expect_used)]
+    let sandbox_policy_cwd = cwd.to_str().expect("cwd must be valid UTF-8").to_string();
+
+    #[expect(clippy::expect_used)]
+    let sandbox_policy_json =
+        serde_json::to_string(sandbox_policy).expect("Failed to serialize SandboxPolicy to JSON");
+
+    let mut windows_cmd: Vec<String> = vec![
+        CODEX_WINDOWS_SANDBOX_ARG1.to_string(),
+        sandbox_policy_cwd,
+        sandbox_policy_json,
+        // Separator so that command arguments starting with `-` are not parsed as
+        // options of the helper itself.
+        "--".to_string(),
+    ];
+
+    // Append the original tool command.
+    windows_cmd.extend(command);
+
+    windows_cmd
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn read_blocklist_is_rejected_before_spawning() {
+        let policy = SandboxPolicy::ReadOnly {
+            read_blocklist: vec![PathBuf::from("/secret")],
+        };
+        let err = spawn_command_under_windows_sandbox(
+            vec!["echo".to_string()],
+            &policy,
+            std::env::temp_dir(),
+            StdioPolicy::RedirectForShellTool,
+            HashMap::new(),
+            &ExecLimits::default(),
+        )
+        .await
+        .expect_err("read_blocklist must be rejected");
+        assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+    }
+}
@@ -0,0 +1,190 @@
+#![cfg(unix)]
+#![allow(clippy::unwrap_used)]
+
+use codex_core::CodexConversation;
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::BackgroundTaskInfo;
+use codex_core::protocol::BackgroundTaskStatus;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::load_sse_fixture_with_id;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_with_timeout;
+use std::sync::Arc;
+use tempfile::TempDir;
+use tokio::time::Duration;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+async fn start_conversation(
+    server: &MockServer,
+    cwd: &TempDir,
+    codex_home: &TempDir,
+) -> Arc<CodexConversation> {
+    let model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    let mut config = load_default_config_for_test(codex_home);
+    config.cwd = cwd.path().to_path_buf();
+    config.model_provider = model_provider;
+    config.approval_policy = AskForApproval::Never;
+    config.sandbox_policy = SandboxPolicy::DangerFullAccess {
+        read_blocklist: vec![],
+    };
+
+    let conversation_manager =
+        ConversationManager::with_auth(CodexAuth::from_api_key("Test API Key"));
+    conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation
+}
+
+async fn next_task_update(codex: &CodexConversation) -> BackgroundTaskInfo {
+    match wait_for_event_with_timeout(
+        codex,
+        |ev| matches!(ev, EventMsg::BackgroundTaskUpdate(_)),
+        Duration::from_secs(10),
+    )
+    .await
+    {
+        EventMsg::BackgroundTaskUpdate(info) => info,
+        _ => unreachable!(),
+    }
+}
+
+async fn list_tasks(codex: &CodexConversation) -> Vec<BackgroundTaskInfo> {
+    codex.submit(Op::ListBackgroundTasks).await.unwrap();
+    match wait_for_event(codex, |ev| {
+        matches!(ev, EventMsg::ListBackgroundTasksResponse(_))
+    })
+    .await
+    {
+        EventMsg::ListBackgroundTasksResponse(ev) => ev.tasks,
+        _ => unreachable!(),
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn background_task_keeps_running_across_turns_until_killed() {
+    let server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(
+                    load_sse_fixture_with_id("tests/fixtures/completed_template.json", "resp"),
+                    "text/event-stream",
+                ),
+        )
+        .expect(1)
+        .mount(&server)
+        .await;
+    let cwd = TempDir::new().unwrap();
+    let codex_home = TempDir::new().unwrap();
+    let codex = start_conversation(&server, &cwd, &codex_home).await;
+
+    codex
+        .submit(Op::RunBackground {
+            command: vec![
+                "bash".to_string(),
+                "-c".to_string(),
+                "echo serving; sleep 600".to_string(),
+            ],
+        })
+        .await
+        .unwrap();
+    let started = next_task_update(&codex).await;
+    assert_eq!(started.id, 0);
+    assert_eq!(started.status, BackgroundTaskStatus::Running);
+    assert_eq!(started.cwd, cwd.path());
+
+    // The conversation carries on while the task runs.
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let deadline = tokio::time::Instant::now() + Duration::from_secs(10);
+    loop {
+        let tasks = list_tasks(&codex).await;
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].status, BackgroundTaskStatus::Running);
+        if tasks[0].output_tail.contains("serving") {
+            break;
+        }
+        assert!(
+            tokio::time::Instant::now() < deadline,
+            "no output: {tasks:?}"
+        );
+        tokio::time::sleep(Duration::from_millis(50)).await;
+    }
+
+    codex
+        .submit(Op::KillBackgroundTask { id: 0 })
+        .await
+        .unwrap();
+    let killed = next_task_update(&codex).await;
+    assert_eq!(killed.status, BackgroundTaskStatus::Killed);
+    assert!(killed.output_tail.contains("serving"), "{killed:?}");
+
+    codex
+        .submit(Op::KillBackgroundTask { id: 0 })
+        .await
+        .unwrap();
+    let EventMsg::Error(err) = wait_for_event(&codex, |ev| matches!(ev, EventMsg::Error(_))).await
+    else {
+        unreachable!();
+    };
+    assert_eq!(err.message, "background task 0 is not running");
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn background_task_reports_exit_code() {
+    let server = MockServer::start().await;
+    let cwd = TempDir::new().unwrap();
+    let codex_home = TempDir::new().unwrap();
+    let codex = start_conversation(&server, &cwd, &codex_home).await;
+
+    codex
+        .submit(Op::RunBackground {
+            command: vec![
+                "bash".to_string(),
+                "-c".to_string(),
+                "pwd; exit 7".to_string(),
+            ],
+        })
+        .await
+        .unwrap();
+    let started = next_task_update(&codex).await;
+    assert_eq!(started.status, BackgroundTaskStatus::Running);
+
+    let finished = next_task_update(&codex).await;
+    assert_eq!(
+        finished.status,
+        BackgroundTaskStatus::Exited { exit_code: 7 }
+    );
+    let cwd_name = cwd.path().file_name().unwrap().to_string_lossy();
+    assert!(
+        finished.output_tail.contains(cwd_name.as_ref()),
+        "{finished:?}"
+    );
+}
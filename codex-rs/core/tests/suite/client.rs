@@ -641,6 +641,7 @@ async fn azure_overrides_assign_properties_used_for_responses_url() {
         stream_max_retries: None,
         stream_idle_timeout_ms: None,
         requires_openai_auth: false,
+        stream_include_usage: false,
     };
 
     // Init session
@@ -717,6 +718,7 @@ async fn env_var_overrides_loaded_auth() {
         stream_max_retries: None,
         stream_idle_timeout_ms: None,
         requires_openai_auth: false,
+        stream_include_usage: false,
     };
 
     // Init session
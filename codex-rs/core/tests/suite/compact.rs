@@ -82,10 +82,64 @@ where
 }
 
 const FIRST_REPLY: &str = "FIRST_REPLY";
+const SECOND_REPLY: &str = "SECOND_REPLY";
 const SUMMARY_TEXT: &str = "SUMMARY_ONLY_CONTEXT";
-const SUMMARIZE_TRIGGER: &str = "Start Summarization";
+const SUMMARIZER_INSTRUCTIONS: &str = "You are a summarization assistant";
+const SECOND_USER_MSG: &str = "second turn";
 const THIRD_USER_MSG: &str = "next turn";
 
+/// SSE event for a completed response that reports `total_tokens`.
+fn ev_completed_with_tokens(id: &str, total_tokens: u64) -> Value {
+    serde_json::json!({
+        "type": "response.completed",
+        "response": {
+            "id": id,
+            "usage": {"input_tokens":total_tokens,"input_tokens_details":null,"output_tokens":0,"output_tokens_details":null,"total_tokens":total_tokens}
+        }
+    })
+}
+
+/// Mount `bodies` to be served in order, one per request.
+async fn mount_sse_sequence(server: &MockServer, bodies: Vec<String>) {
+    for body in bodies {
+        Mock::given(method("POST"))
+            .and(path("/v1/responses"))
+            .respond_with(sse_response(body))
+            .up_to_n_times(1)
+            .mount(server)
+            .await;
+    }
+}
+
+/// (role, text) of every message in the request's input.
+fn input_messages(body: &Value) -> Vec<(String, String)> {
+    body["input"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .filter(|item| item["type"].as_str() == Some("message"))
+        .map(|item| {
+            (
+                item["role"].as_str().unwrap_or_default().to_string(),
+                item["content"][0]["text"]
+                    .as_str()
+                    .unwrap_or_default()
+                    .to_string(),
+            )
+        })
+        .collect()
+}
+
+async fn submit_text(codex: &codex_core::CodexConversation, text: &str) {
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text { text: text.into() }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+}
+
 #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
 async fn summarize_context_three_requests_and_instructions() {
     if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
@@ -116,14 +170,13 @@ async fn summarize_context_three_requests_and_instructions() {
     // Mount three expectations, one per request, matched by body content.
     let first_matcher = |req: &wiremock::Request| {
         let body = std::str::from_utf8(&req.body).unwrap_or("");
-        body.contains("\"text\":\"hello world\"")
-            && !body.contains(&format!("\"text\":\"{SUMMARIZE_TRIGGER}\""))
+        body.contains("\"text\":\"hello world\"") && !body.contains(SUMMARIZER_INSTRUCTIONS)
     };
     mount_sse_once(&server, first_matcher, sse1).await;
 
     let second_matcher = |req: &wiremock::Request| {
         let body = std::str::from_utf8(&req.body).unwrap_or("");
-        body.contains(&format!("\"text\":\"{SUMMARIZE_TRIGGER}\""))
+        body.contains(SUMMARIZER_INSTRUCTIONS)
     };
     mount_sse_once(&server, second_matcher, sse2).await;
 
@@ -149,42 +202,27 @@ async fn summarize_context_three_requests_and_instructions() {
         .conversation;
 
     // 1) Normal user input – should hit server once.
-    codex
-        .submit(Op::UserInput {
-            items: vec![InputItem::Text {
-                text: "hello world".into(),
-            }],
-        })
-        .await
-        .unwrap();
-    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    submit_text(&codex, "hello world").await;
 
     // 2) Summarize – second hit with summarization instructions.
     codex.submit(Op::Compact).await.unwrap();
+    let compacted = wait_for_event(&codex, |ev| matches!(ev, EventMsg::ContextCompacted(_))).await;
+    let EventMsg::ContextCompacted(compacted) = compacted else {
+        unreachable!()
+    };
+    assert!(compacted.summary_tokens > 0);
     wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
 
     // 3) Next user input – third hit; history should include only the summary.
-    codex
-        .submit(Op::UserInput {
-            items: vec![InputItem::Text {
-                text: THIRD_USER_MSG.into(),
-            }],
-        })
-        .await
-        .unwrap();
-    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    submit_text(&codex, THIRD_USER_MSG).await;
 
     // Inspect the three captured requests.
     let requests = server.received_requests().await.unwrap();
     assert_eq!(requests.len(), 3, "expected exactly three requests");
 
-    let req1 = &requests[0];
-    let req2 = &requests[1];
-    let req3 = &requests[2];
-
-    let body1 = req1.body_json::<serde_json::Value>().unwrap();
-    let body2 = req2.body_json::<serde_json::Value>().unwrap();
-    let body3 = req3.body_json::<serde_json::Value>().unwrap();
+    let body1 = requests[0].body_json::<serde_json::Value>().unwrap();
+    let body2 = requests[1].body_json::<serde_json::Value>().unwrap();
+    let body3 = requests[2].body_json::<serde_json::Value>().unwrap();
 
     // System instructions should change for the summarization turn.
     let instr1 = body1.get("instructions").and_then(|v| v.as_str()).unwrap();
@@ -194,58 +232,133 @@ async fn summarize_context_three_requests_and_instructions() {
         "summarization should override base instructions"
     );
     assert!(
-        instr2.contains("You are a summarization assistant"),
+        instr2.contains(SUMMARIZER_INSTRUCTIONS),
         "summarization instructions not applied"
     );
 
-    // The summarization request should include the injected user input marker.
-    let input2 = body2.get("input").and_then(|v| v.as_array()).unwrap();
-    // The last item is the user message created from the injected input.
-    let last2 = input2.last().unwrap();
-    assert_eq!(last2.get("type").unwrap().as_str().unwrap(), "message");
-    assert_eq!(last2.get("role").unwrap().as_str().unwrap(), "user");
-    let text2 = last2["content"][0]["text"].as_str().unwrap();
-    assert!(text2.contains(SUMMARIZE_TRIGGER));
-
-    // Third request must contain only the summary from step 2 as prior history plus new user msg.
-    let input3 = body3.get("input").and_then(|v| v.as_array()).unwrap();
-    println!("third request body: {body3}");
+    // The summarizer gets the conversation as a single transcript.
+    let messages2 = input_messages(&body2);
+    assert_eq!(messages2.len(), 1);
+    let (role2, transcript) = &messages2[0];
+    assert_eq!(role2, "user");
+    assert!(transcript.contains("user: hello world"));
+    assert!(transcript.contains(&format!("assistant: {FIRST_REPLY}")));
     assert!(
-        input3.len() >= 2,
-        "expected summary + new user message in third request"
+        !transcript.contains("<environment_context>"),
+        "session context is kept, not summarized"
     );
 
-    // Collect all (role, text) message tuples.
-    let mut messages: Vec<(String, String)> = Vec::new();
-    for item in input3 {
-        if item["type"].as_str() == Some("message") {
-            let role = item["role"].as_str().unwrap_or_default().to_string();
-            let text = item["content"][0]["text"]
-                .as_str()
-                .unwrap_or_default()
-                .to_string();
-            messages.push((role, text));
-        }
-    }
-
-    // Exactly one assistant message should remain after compaction and the new user message is present.
-    let assistant_count = messages.iter().filter(|(r, _)| r == "assistant").count();
-    assert_eq!(
-        assistant_count, 1,
-        "exactly one assistant message should remain after compaction"
+    // Third request must contain the summary in place of the earlier turn.
+    let messages3 = input_messages(&body3);
+    assert!(
+        messages3
+            .iter()
+            .any(|(r, t)| r == "user" && t.contains("<environment_context>")),
+        "third request should keep the environment context"
+    );
+    assert!(
+        messages3.iter().any(|(r, t)| r == "user"
+            && t.starts_with("<conversation_summary>")
+            && t.contains(SUMMARY_TEXT)),
+        "third request should include the summary"
     );
     assert!(
-        messages
+        !messages3.iter().any(|(r, _)| r == "assistant"),
+        "no assistant message should remain after compaction"
+    );
+    assert!(
+        messages3
             .iter()
             .any(|(r, t)| r == "user" && t == THIRD_USER_MSG),
         "third request should include the new user message"
     );
     assert!(
-        !messages.iter().any(|(_, t)| t.contains("hello world")),
+        !messages3.iter().any(|(_, t)| t == "hello world"),
         "third request should not include the original user input"
     );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn compacts_older_turns_with_summary_model_near_context_limit() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    let server = MockServer::start().await;
+    // Every turn reports a context above 90% of the 1000-token window.
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_assistant_message("m1", FIRST_REPLY),
+                ev_completed_with_tokens("r1", 950),
+            ]),
+            sse(vec![
+                ev_assistant_message("m2", SECOND_REPLY),
+                ev_completed_with_tokens("r2", 950),
+            ]),
+            sse(vec![
+                ev_assistant_message("m3", SUMMARY_TEXT),
+                ev_completed_with_tokens("r3", 100),
+            ]),
+            sse(vec![ev_completed_with_tokens("r4", 300)]),
+        ],
+    )
+    .await;
+
+    let model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+    let home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&home);
+    config.model_provider = model_provider;
+    config.model_context_window = Some(1000);
+    config.summary_model = Some("summary-model".to_string());
+    let session_model = config.model.clone();
+    let conversation_manager = ConversationManager::with_auth(CodexAuth::from_api_key("dummy"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .unwrap()
+        .conversation;
+
+    submit_text(&codex, "hello world").await;
+    // Near the limit, but the last two turns are always kept: nothing to
+    // compact yet.
+    submit_text(&codex, SECOND_USER_MSG).await;
+    submit_text(&codex, THIRD_USER_MSG).await;
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 4, "expected exactly four requests");
+    let bodies: Vec<Value> = requests
+        .iter()
+        .map(|r| r.body_json::<Value>().unwrap())
+        .collect();
+
+    // The summary request goes to the summary model and covers only the
+    // first turn.
+    assert_eq!(bodies[2]["model"].as_str(), Some("summary-model"));
+    let transcript = &input_messages(&bodies[2])[0].1;
+    assert!(transcript.contains("user: hello world"));
+    assert!(!transcript.contains(SECOND_USER_MSG));
+
+    // The turn itself still uses the session model, with the first turn
+    // replaced by the summary.
+    assert_eq!(bodies[3]["model"].as_str(), Some(session_model.as_str()));
+    let messages = input_messages(&bodies[3]);
+    let texts: Vec<&str> = messages.iter().map(|(_, t)| t.as_str()).collect();
+    assert!(!texts.contains(&"hello world"));
+    assert!(!texts.contains(&FIRST_REPLY));
     assert!(
-        !messages.iter().any(|(_, t)| t.contains(SUMMARIZE_TRIGGER)),
-        "third request should not include the summarize trigger"
+        texts
+            .iter()
+            .any(|t| t.starts_with("<conversation_summary>") && t.contains(SUMMARY_TEXT))
     );
+    assert!(texts.contains(&SECOND_USER_MSG));
+    assert!(texts.contains(&SECOND_REPLY));
+    assert_eq!(texts.last(), Some(&THIRD_USER_MSG));
 }
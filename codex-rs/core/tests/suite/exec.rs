@@ -2,11 +2,14 @@
 
 use std::collections::HashMap;
 
+use codex_core::config_types::ExecLimits;
 use codex_core::exec::ExecParams;
+use codex_core::exec::ExecSandboxOptions;
 use codex_core::exec::ExecToolCallOutput;
 use codex_core::exec::SandboxType;
 use codex_core::exec::process_exec_tool_call;
 use codex_core::protocol::SandboxPolicy;
+use codex_core::seatbelt::SeatbeltExtensions;
 use codex_core::spawn::CODEX_SANDBOX_ENV_VAR;
 use tempfile::TempDir;
 
@@ -39,7 +42,19 @@ async fn run_test_cmd(tmp: TempDir, cmd: Vec<&str>) -> Result<ExecToolCallOutput
 
     let policy = SandboxPolicy::new_read_only_policy();
 
-    process_exec_tool_call(params, sandbox_type, &policy, &None, None).await
+    process_exec_tool_call(
+        params,
+        &policy,
+        ExecSandboxOptions {
+            sandbox_type,
+            codex_linux_sandbox_exe: &None,
+            container_config: None,
+            seatbelt_extensions: &SeatbeltExtensions::default(),
+            limits: &ExecLimits::default(),
+        },
+        None,
+    )
+    .await
 }
 
 /// Command succeeds with exit code 0 normally
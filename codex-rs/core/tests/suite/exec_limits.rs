@@ -0,0 +1,203 @@
+#![cfg(unix)]
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use codex_core::config_types::ExecLimits;
+use codex_core::error::CodexErr;
+use codex_core::error::SandboxErr;
+use codex_core::exec::ExecParams;
+use codex_core::exec::ExecSandboxOptions;
+use codex_core::exec::SandboxType;
+use codex_core::exec::process_exec_tool_call;
+use codex_core::protocol::ExecKilledReason;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::seatbelt::SeatbeltExtensions;
+
+fn sh(script: &str, timeout_ms: Option<u64>) -> ExecParams {
+    ExecParams {
+        command: vec!["/bin/sh".to_string(), "-c".to_string(), script.to_string()],
+        cwd: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+        timeout_ms,
+        env: HashMap::new(),
+        with_escalated_permissions: None,
+        justification: None,
+    }
+}
+
+#[tokio::test]
+async fn output_limit_kills_command_and_truncates_output() {
+    let limits = ExecLimits {
+        max_output_bytes: Some(1024),
+        ..Default::default()
+    };
+    let policy = SandboxPolicy::new_read_only_policy();
+
+    let output = process_exec_tool_call(
+        sh("yes", Some(5_000)),
+        &policy,
+        ExecSandboxOptions {
+            sandbox_type: SandboxType::None,
+            codex_linux_sandbox_exe: &None,
+            container_config: None,
+            seatbelt_extensions: &SeatbeltExtensions::default(),
+            limits: &limits,
+        },
+        None,
+    )
+    .await
+    .expect("process_exec_tool_call");
+
+    assert_eq!(output.killed_reason, Some(ExecKilledReason::OutputLimit));
+    assert_eq!(output.aggregated_output.text.len(), 1024);
+    assert_eq!(output.stdout.text.len(), 1024);
+    assert_ne!(output.exit_code, 0);
+}
+
+#[tokio::test]
+async fn output_under_limit_is_not_reported() {
+    let limits = ExecLimits {
+        max_output_bytes: Some(1024),
+        ..Default::default()
+    };
+    let policy = SandboxPolicy::new_read_only_policy();
+
+    let output = process_exec_tool_call(
+        sh("printf hello", Some(5_000)),
+        &policy,
+        ExecSandboxOptions {
+            sandbox_type: SandboxType::None,
+            codex_linux_sandbox_exe: &None,
+            container_config: None,
+            seatbelt_extensions: &SeatbeltExtensions::default(),
+            limits: &limits,
+        },
+        None,
+    )
+    .await
+    .expect("process_exec_tool_call");
+
+    assert_eq!(output.killed_reason, None);
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout.text, "hello");
+}
+
+#[tokio::test]
+async fn cpu_time_limit_is_reported() {
+    let limits = ExecLimits {
+        cpu_time_seconds: Some(1),
+        ..Default::default()
+    };
+    let policy = SandboxPolicy::new_read_only_policy();
+
+    let output = process_exec_tool_call(
+        sh("while :; do :; done", Some(10_000)),
+        &policy,
+        ExecSandboxOptions {
+            sandbox_type: SandboxType::None,
+            codex_linux_sandbox_exe: &None,
+            container_config: None,
+            seatbelt_extensions: &SeatbeltExtensions::default(),
+            limits: &limits,
+        },
+        None,
+    )
+    .await
+    .expect("process_exec_tool_call");
+
+    assert_eq!(output.killed_reason, Some(ExecKilledReason::CpuTime));
+}
+
+// macOS does not enforce `RLIMIT_AS`.
+#[cfg(target_os = "linux")]
+#[tokio::test]
+async fn memory_limit_allocation_failure_is_reported() {
+    if std::process::Command::new("perl")
+        .arg("-v")
+        .output()
+        .is_err()
+    {
+        eprintln!("perl not found, skipping memory_limit_allocation_failure_is_reported");
+        return;
+    }
+    let limits = ExecLimits {
+        max_memory_bytes: Some(256 << 20),
+        ..Default::default()
+    };
+    let policy = SandboxPolicy::new_read_only_policy();
+
+    // Perl says "Out of memory!" when an allocation fails.
+    let output = process_exec_tool_call(
+        sh("perl -e '$x = \"x\" x (1 << 31)'", Some(5_000)),
+        &policy,
+        ExecSandboxOptions {
+            sandbox_type: SandboxType::None,
+            codex_linux_sandbox_exe: &None,
+            container_config: None,
+            seatbelt_extensions: &SeatbeltExtensions::default(),
+            limits: &limits,
+        },
+        None,
+    )
+    .await
+    .expect("process_exec_tool_call");
+
+    assert_eq!(output.killed_reason, Some(ExecKilledReason::Memory));
+    assert_ne!(output.exit_code, 0);
+}
+
+#[tokio::test]
+async fn kill_under_memory_limit_is_a_plain_signal() {
+    // Nothing says that the command ran out of memory.
+    let limits = ExecLimits {
+        max_memory_bytes: Some(4 << 30),
+        ..Default::default()
+    };
+    let policy = SandboxPolicy::new_read_only_policy();
+
+    let result = process_exec_tool_call(
+        sh("kill -KILL $$", Some(5_000)),
+        &policy,
+        ExecSandboxOptions {
+            sandbox_type: SandboxType::None,
+            codex_linux_sandbox_exe: &None,
+            container_config: None,
+            seatbelt_extensions: &SeatbeltExtensions::default(),
+            limits: &limits,
+        },
+        None,
+    )
+    .await;
+    assert!(
+        matches!(result, Err(CodexErr::Sandbox(SandboxErr::Signal(9)))),
+        "expected SIGKILL, got {result:?}"
+    );
+}
+
+#[tokio::test]
+async fn timeout_limit_caps_requested_timeout() {
+    let limits = ExecLimits {
+        timeout_ms: Some(200),
+        ..Default::default()
+    };
+    let policy = SandboxPolicy::new_read_only_policy();
+
+    let result = process_exec_tool_call(
+        sh("sleep 5", Some(60_000)),
+        &policy,
+        ExecSandboxOptions {
+            sandbox_type: SandboxType::None,
+            codex_linux_sandbox_exe: &None,
+            container_config: None,
+            seatbelt_extensions: &SeatbeltExtensions::default(),
+            limits: &limits,
+        },
+        None,
+    )
+    .await;
+
+    assert!(
+        matches!(result, Err(CodexErr::Sandbox(SandboxErr::Timeout))),
+        "expected timeout, got {result:?}"
+    );
+}
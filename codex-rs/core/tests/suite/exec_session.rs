@@ -0,0 +1,180 @@
+#![cfg(unix)]
+#![allow(clippy::unwrap_used)]
+
+use codex_core::ConversationManager;
+use codex_core::ModelProviderInfo;
+use codex_core::built_in_model_providers;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_login::CodexAuth;
+use core_test_support::load_default_config_for_test;
+use core_test_support::wait_for_event;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+use walkdir::WalkDir;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+/// Build an SSE stream body from a list of JSON events.
+fn sse(events: Vec<Value>) -> String {
+    use std::fmt::Write as _;
+    let mut out = String::new();
+    for ev in events {
+        let kind = ev.get("type").and_then(|v| v.as_str()).unwrap();
+        writeln!(&mut out, "event: {kind}").unwrap();
+        write!(&mut out, "data: {ev}\n\n").unwrap();
+    }
+    out
+}
+
+fn ev_completed(id: &str) -> Value {
+    json!({
+        "type": "response.completed",
+        "response": {
+            "id": id,
+            "usage": {"input_tokens":0,"input_tokens_details":null,"output_tokens":0,"output_tokens_details":null,"total_tokens":0}
+        }
+    })
+}
+
+fn ev_function_call(call_id: &str, name: &str, arguments: Value) -> Value {
+    json!({
+        "type": "response.output_item.done",
+        "item": {
+            "type": "function_call",
+            "id": format!("fc_{call_id}"),
+            "call_id": call_id,
+            "name": name,
+            "arguments": arguments.to_string(),
+        }
+    })
+}
+
+fn function_call_output(body: &Value, call_id: &str) -> String {
+    body["input"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|item| item["type"] == "function_call_output" && item["call_id"] == call_id)
+        .and_then(|item| item["output"].as_str())
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn exec_session_is_driven_across_calls_and_recorded_in_rollout() {
+    let server = MockServer::start().await;
+
+    let bodies = [
+        sse(vec![
+            ev_function_call(
+                "open",
+                "exec_command",
+                json!({ "cmd": "cat", "yield_time_ms": 200, "login": false }),
+            ),
+            ev_completed("r1"),
+        ]),
+        sse(vec![
+            ev_function_call(
+                "write",
+                "write_stdin",
+                json!({ "session_id": 0, "chars": "ping\n", "yield_time_ms": 500 }),
+            ),
+            ev_completed("r2"),
+        ]),
+        sse(vec![
+            ev_function_call("close", "close_session", json!({ "session_id": 0 })),
+            ev_completed("r3"),
+        ]),
+        sse(vec![ev_completed("r4")]),
+    ];
+    for body in bodies {
+        Mock::given(method("POST"))
+            .and(path("/v1/responses"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_raw(body, "text/event-stream"),
+            )
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+    }
+
+    let model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+
+    let cwd = TempDir::new().unwrap();
+    let codex_home = TempDir::new().unwrap();
+    let mut config = load_default_config_for_test(&codex_home);
+    config.cwd = cwd.path().to_path_buf();
+    config.model_provider = model_provider;
+    config.approval_policy = AskForApproval::Never;
+    config.sandbox_policy = SandboxPolicy::DangerFullAccess {
+        read_blocklist: vec![],
+    };
+    config.use_experimental_streamable_shell_tool = true;
+
+    let conversation_manager =
+        ConversationManager::with_auth(CodexAuth::from_api_key("Test API Key"));
+    let codex = conversation_manager
+        .new_conversation(config)
+        .await
+        .expect("create new conversation")
+        .conversation;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "start cat".into(),
+            }],
+        })
+        .await
+        .unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 4);
+    let opened = function_call_output(&requests[1].body_json::<Value>().unwrap(), "open");
+    assert!(
+        opened.contains("Process running with session ID 0"),
+        "unexpected output: {opened}"
+    );
+    let written = function_call_output(&requests[2].body_json::<Value>().unwrap(), "write");
+    assert!(written.contains("ping"), "unexpected output: {written}");
+    let closed = function_call_output(&requests[3].body_json::<Value>().unwrap(), "close");
+    assert!(
+        closed.contains("Process with session ID 0 was closed"),
+        "unexpected output: {closed}"
+    );
+
+    codex.submit(Op::Shutdown).await.unwrap();
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::ShutdownComplete)).await;
+
+    let rollout = WalkDir::new(codex_home.path().join("sessions"))
+        .into_iter()
+        .filter_map(Result::ok)
+        .find(|entry| entry.file_type().is_file())
+        .map(|entry| std::fs::read_to_string(entry.path()).unwrap())
+        .expect("rollout file");
+    let transcript = rollout
+        .lines()
+        .map(|line| serde_json::from_str::<Value>(line).unwrap())
+        .find(|record| record["record_type"] == "exec_session")
+        .expect("exec_session record in rollout");
+    assert_eq!(transcript["session_id"], 0);
+    assert_eq!(transcript["command"], json!(["/bin/bash", "-c", "cat"]));
+    assert!(
+        transcript["output"].as_str().unwrap().contains("ping"),
+        "unexpected transcript: {transcript}"
+    );
+}
@@ -4,7 +4,9 @@ use std::collections::HashMap;
 use std::path::PathBuf;
 
 use async_channel::Receiver;
+use codex_core::config_types::ExecLimits;
 use codex_core::exec::ExecParams;
+use codex_core::exec::ExecSandboxOptions;
 use codex_core::exec::SandboxType;
 use codex_core::exec::StdoutStream;
 use codex_core::exec::process_exec_tool_call;
@@ -13,6 +15,7 @@ use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecCommandOutputDeltaEvent;
 use codex_core::protocol::ExecOutputStream;
 use codex_core::protocol::SandboxPolicy;
+use codex_core::seatbelt::SeatbeltExtensions;
 
 fn collect_stdout_events(rx: Receiver<Event>) -> Vec<u8> {
     let mut out = Vec::new();
@@ -59,9 +62,14 @@ async fn test_exec_stdout_stream_events_echo() {
 
     let result = process_exec_tool_call(
         params,
-        SandboxType::None,
         &policy,
-        &None,
+        ExecSandboxOptions {
+            sandbox_type: SandboxType::None,
+            codex_linux_sandbox_exe: &None,
+            container_config: None,
+            seatbelt_extensions: &SeatbeltExtensions::default(),
+            limits: &ExecLimits::default(),
+        },
         Some(stdout_stream),
     )
     .await;
@@ -109,9 +117,14 @@ async fn test_exec_stderr_stream_events_echo() {
 
     let result = process_exec_tool_call(
         params,
-        SandboxType::None,
         &policy,
-        &None,
+        ExecSandboxOptions {
+            sandbox_type: SandboxType::None,
+            codex_linux_sandbox_exe: &None,
+            container_config: None,
+            seatbelt_extensions: &SeatbeltExtensions::default(),
+            limits: &ExecLimits::default(),
+        },
         Some(stdout_stream),
     )
     .await;
@@ -160,9 +173,20 @@ async fn test_aggregated_output_interleaves_in_order() {
 
     let policy = SandboxPolicy::new_read_only_policy();
 
-    let result = process_exec_tool_call(params, SandboxType::None, &policy, &None, None)
-        .await
-        .expect("process_exec_tool_call");
+    let result = process_exec_tool_call(
+        params,
+        &policy,
+        ExecSandboxOptions {
+            sandbox_type: SandboxType::None,
+            codex_linux_sandbox_exe: &None,
+            container_config: None,
+            seatbelt_extensions: &SeatbeltExtensions::default(),
+            limits: &ExecLimits::default(),
+        },
+        None,
+    )
+    .await
+    .expect("process_exec_tool_call");
 
     assert_eq!(result.exit_code, 0);
     assert_eq!(result.stdout.text, "O1\nO2\n");
@@ -170,3 +194,66 @@ async fn test_aggregated_output_interleaves_in_order() {
     assert_eq!(result.aggregated_output.text, "O1\nE1\nO2\nE2\n");
     assert_eq!(result.aggregated_output.truncated_after_lines, None);
 }
+
+#[tokio::test]
+async fn test_exec_output_deltas_are_coalesced() {
+    let (tx, rx) = async_channel::unbounded::<Event>();
+
+    let stdout_stream = StdoutStream {
+        sub_id: "test-sub".to_string(),
+        call_id: "call-3".to_string(),
+        tx_event: tx,
+    };
+
+    // Ten separate writes, spread over roughly 100ms.
+    let cmd = vec![
+        "/bin/sh".to_string(),
+        "-c".to_string(),
+        "for i in 0 1 2 3 4 5 6 7 8 9; do printf \"$i\\n\"; sleep 0.01; done".to_string(),
+    ];
+
+    let params = ExecParams {
+        command: cmd,
+        cwd: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
+        timeout_ms: Some(5_000),
+        env: HashMap::new(),
+        with_escalated_permissions: None,
+        justification: None,
+    };
+
+    let policy = SandboxPolicy::new_read_only_policy();
+
+    let result = process_exec_tool_call(
+        params,
+        &policy,
+        ExecSandboxOptions {
+            sandbox_type: SandboxType::None,
+            codex_linux_sandbox_exe: &None,
+            container_config: None,
+            seatbelt_extensions: &SeatbeltExtensions::default(),
+            limits: &ExecLimits::default(),
+        },
+        Some(stdout_stream),
+    )
+    .await
+    .expect("process_exec_tool_call");
+    assert_eq!(result.exit_code, 0);
+
+    let mut chunks = Vec::new();
+    while let Ok(ev) = rx.try_recv() {
+        if let EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent { chunk, .. }) = ev.msg
+        {
+            chunks.push(chunk);
+        }
+    }
+    let streamed: Vec<u8> = chunks.iter().flat_map(|c| c.iter().copied()).collect();
+    assert_eq!(
+        String::from_utf8_lossy(&streamed),
+        "0\n1\n2\n3\n4\n5\n6\n7\n8\n9\n"
+    );
+    assert!(
+        chunks.len() < 10,
+        "expected coalesced deltas, got {}",
+        chunks.len()
+    );
+}
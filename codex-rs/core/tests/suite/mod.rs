@@ -1,12 +1,17 @@
 // Aggregates all former standalone integration tests as modules.
 
+mod apply_patch_invocation;
+mod background_tasks;
 mod cli_stream;
 mod client;
 mod compact;
 mod exec;
+mod exec_limits;
+mod exec_session;
 mod exec_stream_events;
 mod live_cli;
 mod prompt_caching;
 mod seatbelt;
 mod stream_error_allows_next_turn;
 mod stream_no_completed;
+mod user_command;
@@ -2,6 +2,7 @@
 
 use codex_core::ConversationManager;
 use codex_core::ModelProviderInfo;
+use codex_core::WireApi;
 use codex_core::built_in_model_providers;
 use codex_core::model_family::find_family_for_model;
 use codex_core::protocol::AskForApproval;
@@ -558,3 +559,137 @@ async fn per_turn_overrides_keep_cached_prefix_and_key_constant() {
     );
     assert_eq!(body2["input"], expected_body2);
 }
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn prompt_cache_config_controls_cache_key() {
+    use pretty_assertions::assert_eq;
+
+    let server = MockServer::start().await;
+
+    let sse = sse_completed("resp");
+    let template = ResponseTemplate::new(200)
+        .insert_header("content-type", "text/event-stream")
+        .set_body_raw(sse, "text/event-stream");
+
+    // Expect one POST per conversation.
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(template)
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let model_provider = ModelProviderInfo {
+        base_url: Some(format!("{}/v1", server.uri())),
+        ..built_in_model_providers()["openai"].clone()
+    };
+
+    let cwd = TempDir::new().unwrap();
+    let codex_home = TempDir::new().unwrap();
+    let conversation_manager =
+        ConversationManager::with_auth(CodexAuth::from_api_key("Test API Key"));
+
+    for (enabled, key) in [(true, Some("shared-prefix")), (false, Some("ignored"))] {
+        let mut config = load_default_config_for_test(&codex_home);
+        config.cwd = cwd.path().to_path_buf();
+        config.model_provider = model_provider.clone();
+        config.prompt_cache.enabled = enabled;
+        config.prompt_cache.key = key.map(str::to_string);
+
+        let codex = conversation_manager
+            .new_conversation(config)
+            .await
+            .expect("create new conversation")
+            .conversation;
+        codex
+            .submit(Op::UserInput {
+                items: vec![InputItem::Text {
+                    text: "hello".into(),
+                }],
+            })
+            .await
+            .unwrap();
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    }
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2, "expected two POST requests");
+
+    let body0 = requests[0].body_json::<serde_json::Value>().unwrap();
+    assert_eq!(
+        body0["prompt_cache_key"],
+        serde_json::json!("shared-prefix")
+    );
+
+    let body1 = requests[1].body_json::<serde_json::Value>().unwrap();
+    assert!(
+        body1.get("prompt_cache_key").is_none(),
+        "prompt_cache_key should be omitted when prompt caching is disabled"
+    );
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn chat_stream_options_follow_provider_setting() {
+    let server = MockServer::start().await;
+
+    let sse = concat!(
+        "data: {\"choices\":[{\"delta\":{\"content\":\"hi\"},\"finish_reason\":\"stop\"}]}\n\n",
+        "data: [DONE]\n\n"
+    );
+    Mock::given(method("POST"))
+        .and(path("/v1/chat/completions"))
+        .respond_with(
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_raw(sse, "text/event-stream"),
+        )
+        .expect(2)
+        .mount(&server)
+        .await;
+
+    let cwd = TempDir::new().unwrap();
+    let codex_home = TempDir::new().unwrap();
+    let conversation_manager =
+        ConversationManager::with_auth(CodexAuth::from_api_key("Test API Key"));
+
+    for stream_include_usage in [true, false] {
+        let mut config = load_default_config_for_test(&codex_home);
+        config.cwd = cwd.path().to_path_buf();
+        config.model_provider = ModelProviderInfo {
+            base_url: Some(format!("{}/v1", server.uri())),
+            wire_api: WireApi::Chat,
+            stream_include_usage,
+            ..built_in_model_providers()["openai"].clone()
+        };
+
+        let codex = conversation_manager
+            .new_conversation(config)
+            .await
+            .expect("create new conversation")
+            .conversation;
+        codex
+            .submit(Op::UserInput {
+                items: vec![InputItem::Text {
+                    text: "hello".into(),
+                }],
+            })
+            .await
+            .unwrap();
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+    }
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2, "expected two POST requests");
+
+    let body0 = requests[0].body_json::<serde_json::Value>().unwrap();
+    assert_eq!(
+        body0["stream_options"],
+        serde_json::json!({ "include_usage": true })
+    );
+
+    let body1 = requests[1].body_json::<serde_json::Value>().unwrap();
+    assert!(
+        body1.get("stream_options").is_none(),
+        "stream_options should be omitted unless the provider opts in"
+    );
+}
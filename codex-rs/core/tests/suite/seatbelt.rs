@@ -7,7 +7,9 @@ use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
 
+use codex_core::config_types::ExecLimits;
 use codex_core::protocol::SandboxPolicy;
+use codex_core::seatbelt::SeatbeltExtensions;
 use codex_core::seatbelt::spawn_command_under_seatbelt;
 use codex_core::spawn::CODEX_SANDBOX_ENV_VAR;
 use codex_core::spawn::StdioPolicy;
@@ -211,9 +213,11 @@ async fn touch(path: &Path, policy: &SandboxPolicy) -> bool {
             path.to_string_lossy().to_string(),
         ],
         policy,
+        &SeatbeltExtensions::default(),
         std::env::current_dir().expect("should be able to get current dir"),
         StdioPolicy::RedirectForShellTool,
         HashMap::new(),
+        &ExecLimits::default(),
     )
     .await
     .expect("should be able to spawn command under seatbelt");
@@ -230,9 +234,11 @@ async fn cat(path: &Path, policy: &SandboxPolicy) -> bool {
     let mut child = spawn_command_under_seatbelt(
         vec!["/bin/cat".to_string(), path.to_string_lossy().to_string()],
         policy,
+        &SeatbeltExtensions::default(),
         std::env::current_dir().expect("should be able to get current dir"),
         StdioPolicy::RedirectForShellTool,
         HashMap::new(),
+        &ExecLimits::default(),
     )
     .await
     .expect("should be able to spawn command under seatbelt");
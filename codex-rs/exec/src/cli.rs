@@ -1,4 +1,6 @@
+use clap::Args;
 use clap::Parser;
+use clap::Subcommand;
 use clap::ValueEnum;
 use codex_common::CliConfigOverrides;
 use std::path::PathBuf;
@@ -55,7 +57,11 @@ pub struct Cli {
     #[arg(long = "color", value_enum, default_value_t = Color::Auto)]
     pub color: Color,
 
-    /// Print events to stdout as JSONL.
+    /// Format of the output printed to stdout.
+    #[arg(long = "output-format", value_enum, default_value_t = OutputFormat::Human)]
+    pub output_format: OutputFormat,
+
+    /// Print events to stdout as JSONL. Shorthand for `--output-format json`.
     #[arg(long = "json", default_value_t = false)]
     pub json: bool,
 
@@ -63,10 +69,69 @@ pub struct Cli {
     #[arg(long = "output-last-message")]
     pub last_message_file: Option<PathBuf>,
 
+    /// POST a JSON summary of the run to this URL when it finishes. Overrides
+    /// `notifications.webhook` in config.toml.
+    #[arg(long = "notify-webhook", value_name = "URL")]
+    pub notify_webhook: Option<String>,
+
+    /// Run the prompts of a YAML script one after another in the same
+    /// conversation instead of a single prompt. See `codex-rs/README.md`.
+    #[arg(long = "script", value_name = "FILE", conflicts_with = "prompt")]
+    pub script: Option<PathBuf>,
+
+    /// Continue the most recent session started in the working directory,
+    /// e.g. one cut short by Ctrl-C or a CI timeout, instead of starting a
+    /// new one. Without a prompt, the agent is asked to finish its task.
+    #[arg(
+        long = "resume-last",
+        default_value_t = false,
+        conflicts_with = "script"
+    )]
+    pub resume_last: bool,
+
+    /// With `--script`, run the remaining steps after a step fails.
+    #[arg(long = "keep-going", default_value_t = false, requires = "script")]
+    pub keep_going: bool,
+
+    /// Exit with status 8 when a patch fails to apply, instead of counting
+    /// it as a warning.
+    #[arg(long = "fail-on-patch-error", default_value_t = false)]
+    pub fail_on_patch_error: bool,
+
+    /// Stop after this many model requests, over all steps of a `--script`.
+    #[arg(long = "max-turns", value_name = "N")]
+    pub max_turns: Option<u32>,
+
+    /// Stop once the run has gone on for this many seconds.
+    #[arg(long = "max-wall-time", value_name = "SECONDS")]
+    pub max_wall_time: Option<u64>,
+
     /// Initial instructions for the agent. If not provided as an argument (or
     /// if `-` is used), instructions are read from stdin.
     #[arg(value_name = "PROMPT")]
     pub prompt: Option<String>,
+
+    #[command(subcommand)]
+    pub command: Option<ExecCommand>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ExecCommand {
+    /// Review a GitHub pull request and print the review comments as JSON.
+    /// Requires the GitHub CLI (`gh`), signed in to the repository's host.
+    Review(ReviewArgs),
+}
+
+#[derive(Debug, Clone, Copy, Args)]
+pub struct ReviewArgs {
+    /// Number of the pull request to review, in the repository of the
+    /// working directory.
+    #[arg(long = "pr", value_name = "NUMBER")]
+    pub pr: u64,
+
+    /// Also post the comments to the pull request as a review.
+    #[arg(long = "post", default_value_t = false)]
+    pub post: bool,
 }
 
 #[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
@@ -77,3 +142,54 @@ pub enum Color {
     #[default]
     Auto,
 }
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum OutputFormat {
+    /// Render events for a person reading the terminal.
+    #[default]
+    Human,
+    /// One JSON object per line: the effective configuration, the prompt,
+    /// each event as serialized from `EventMsg`, and a final run summary.
+    Json,
+}
+
+/// Arguments for `codex ask`, which answers a question about the code in the
+/// working directory without changing anything.
+#[derive(Parser, Debug)]
+pub struct AskCli {
+    /// Model the agent should use.
+    #[arg(long, short = 'm')]
+    pub model: Option<String>,
+
+    #[arg(long = "oss", default_value_t = false)]
+    pub oss: bool,
+
+    /// Configuration profile from config.toml to specify default options.
+    #[arg(long = "profile", short = 'p')]
+    pub config_profile: Option<String>,
+
+    /// Tell the agent to use the specified directory as its working root.
+    #[clap(long = "cd", short = 'C', value_name = "DIR")]
+    pub cwd: Option<PathBuf>,
+
+    /// Allow running Codex outside a Git repository.
+    #[arg(long = "skip-git-repo-check", default_value_t = false)]
+    pub skip_git_repo_check: bool,
+
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+
+    /// Specifies color settings for use in the output.
+    #[arg(long = "color", value_enum, default_value_t = Color::Auto)]
+    pub color: Color,
+
+    /// Specifies file where the answer should also be written.
+    #[arg(long = "output-last-message")]
+    pub last_message_file: Option<PathBuf>,
+
+    /// The question to answer. If not provided as an argument (or if `-` is
+    /// used), it is read from stdin.
+    #[arg(value_name = "QUESTION")]
+    pub question: Option<String>,
+}
@@ -0,0 +1,83 @@
+use std::path::PathBuf;
+
+use codex_core::config::Config;
+use codex_core::protocol::ErrorEvent;
+use codex_core::protocol::Event;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::ExecCommandBeginEvent;
+use codex_core::protocol::StreamErrorEvent;
+use codex_core::protocol::TaskCompleteEvent;
+use owo_colors::OwoColorize;
+use owo_colors::Style;
+
+use crate::event_processor::CodexStatus;
+use crate::event_processor::EventProcessor;
+use crate::event_processor::handle_last_message;
+use crate::event_processor_with_human_output::escape_command;
+
+/// Used by `codex ask`: prints only the final answer to stdout, with brief
+/// progress and errors on stderr. `codex exec review` prints the comments it
+/// parses from the answer instead, so it leaves the answer out.
+pub(crate) struct EventProcessorWithAnswerOutput {
+    // ANSI escapes for stderr must be added using .style() with one of these
+    // fields so that --color=never is respected.
+    dimmed: Style,
+    red: Style,
+    print_answer: bool,
+    last_message_path: Option<PathBuf>,
+}
+
+impl EventProcessorWithAnswerOutput {
+    pub(crate) fn create_with_ansi(
+        with_ansi: bool,
+        print_answer: bool,
+        last_message_path: Option<PathBuf>,
+    ) -> Self {
+        let (dimmed, red) = if with_ansi {
+            (Style::new().dimmed(), Style::new().red())
+        } else {
+            (Style::new(), Style::new())
+        };
+        Self {
+            dimmed,
+            red,
+            print_answer,
+            last_message_path,
+        }
+    }
+}
+
+impl EventProcessor for EventProcessorWithAnswerOutput {
+    fn print_config_summary(&mut self, _config: &Config, _prompt: &str) {}
+
+    fn process_event(&mut self, event: Event) -> CodexStatus {
+        match event.msg {
+            EventMsg::Error(ErrorEvent { message, .. }) => {
+                eprintln!("{} {message}", "ERROR:".style(self.red));
+            }
+            EventMsg::StreamError(StreamErrorEvent { message }) => {
+                eprintln!("{}", message.style(self.dimmed));
+            }
+            EventMsg::ExecCommandBegin(ExecCommandBeginEvent { command, .. }) => {
+                let command = escape_command(&command);
+                eprintln!("{}", format!("$ {command}").style(self.dimmed));
+            }
+            EventMsg::TaskComplete(TaskCompleteEvent {
+                last_agent_message, ..
+            }) => {
+                match last_agent_message.as_deref() {
+                    Some(answer) if self.print_answer => println!("{answer}"),
+                    Some(_) => {}
+                    None => eprintln!("{}", "No answer was produced.".style(self.red)),
+                }
+                if let Some(output_file) = self.last_message_path.as_deref() {
+                    handle_last_message(last_agent_message.as_deref(), output_file);
+                }
+                return CodexStatus::InitiateShutdown;
+            }
+            EventMsg::ShutdownComplete => return CodexStatus::Shutdown,
+            _ => {}
+        }
+        CodexStatus::Running
+    }
+}
@@ -1,6 +1,8 @@
 use codex_common::elapsed::format_duration;
 use codex_common::elapsed::format_elapsed;
 use codex_core::config::Config;
+use codex_core::plan_tool::PlanItemArg;
+use codex_core::plan_tool::StepStatus;
 use codex_core::plan_tool::UpdatePlanArgs;
 use codex_core::protocol::AgentMessageDeltaEvent;
 use codex_core::protocol::AgentMessageEvent;
@@ -8,22 +10,31 @@ use codex_core::protocol::AgentReasoningDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentEvent;
 use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::BackgroundTaskInfo;
+use codex_core::protocol::BackgroundTaskStatus;
+use codex_core::protocol::ContainerImagePullEvent;
+use codex_core::protocol::ContainerImagePullStatus;
+use codex_core::protocol::ContextCompactedEvent;
+use codex_core::protocol::DegradedModeEvent;
 use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
+use codex_core::protocol::ExecCommandOutputDeltaEvent;
 use codex_core::protocol::FileChange;
 use codex_core::protocol::McpInvocation;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
+use codex_core::protocol::ProfileSwitchedEvent;
 use codex_core::protocol::SessionConfiguredEvent;
 use codex_core::protocol::StreamErrorEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnDiffEvent;
+use codex_core::protocol::TurnRevertedEvent;
 use codex_core::protocol::WebSearchBeginEvent;
 use owo_colors::OwoColorize;
 use owo_colors::Style;
@@ -36,6 +47,7 @@ use std::time::Instant;
 use crate::event_processor::CodexStatus;
 use crate::event_processor::EventProcessor;
 use crate::event_processor::handle_last_message;
+use crate::run_summary::truncation_message;
 use codex_common::create_config_summary_entries;
 
 /// This should be configurable. When used in CI, users may not want to impose
@@ -113,10 +125,25 @@ impl EventProcessorWithHumanOutput {
             }
         }
     }
+
+    /// Ends the line of a message or reasoning that was being streamed when
+    /// its turn ended early, so the next turn starts with a fresh header.
+    fn close_streams(&mut self) {
+        if self.answer_started || self.reasoning_started || self.raw_reasoning_started {
+            println!();
+        }
+        self.answer_started = false;
+        self.reasoning_started = false;
+        self.raw_reasoning_started = false;
+    }
 }
 
 struct ExecCommandBegin {
     command: Vec<String>,
+    /// Output lines already printed as they were streamed.
+    streamed_lines: usize,
+    /// Streamed output after the last complete line.
+    partial_line: Vec<u8>,
 }
 
 struct PatchApplyBegin {
@@ -169,10 +196,15 @@ impl EventProcessor for EventProcessorWithHumanOutput {
     fn process_event(&mut self, event: Event) -> CodexStatus {
         let Event { id: _, msg } = event;
         match msg {
-            EventMsg::Error(ErrorEvent { message }) => {
+            EventMsg::Error(ErrorEvent { message, .. }) => {
+                self.close_streams();
                 let prefix = "ERROR:".style(self.red);
                 ts_println!(self, "{prefix} {message}");
             }
+            EventMsg::DegradedMode(DegradedModeEvent { message, .. }) => {
+                let prefix = "WARNING:".style(self.bold);
+                ts_println!(self, "{prefix} {message}");
+            }
             EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
                 ts_println!(self, "{}", message.style(self.dimmed));
             }
@@ -180,9 +212,19 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 ts_println!(self, "{}", message.style(self.dimmed));
             }
             EventMsg::TaskStarted => {
-                // Ignore.
+                self.close_streams();
             }
-            EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
+            EventMsg::TaskComplete(TaskCompleteEvent {
+                last_agent_message,
+                checkpoint_commit,
+                truncated,
+            }) => {
+                if let Some(sha) = checkpoint_commit {
+                    ts_println!(self, "{} {sha}", "checkpoint".style(self.magenta));
+                }
+                if let Some(reason) = truncated {
+                    ts_println!(self, "{}", truncation_message(reason).style(self.red));
+                }
                 if let Some(output_file) = self.last_message_path.as_deref() {
                     handle_last_message(last_agent_message.as_deref(), output_file);
                 }
@@ -191,6 +233,9 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::TokenCount(token_usage) => {
                 ts_println!(self, "tokens used: {}", token_usage.blended_total());
             }
+            EventMsg::TokenUsageBreakdown(_) => {
+                // Ignore.
+            }
             EventMsg::AgentMessageDelta(AgentMessageDeltaEvent { delta }) => {
                 if !self.answer_started {
                     ts_println!(self, "{}\n", "codex".style(self.italic).style(self.magenta));
@@ -275,6 +320,8 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     call_id.clone(),
                     ExecCommandBegin {
                         command: command.clone(),
+                        streamed_lines: 0,
+                        partial_line: Vec::new(),
                     },
                 );
                 ts_println!(
@@ -285,15 +332,36 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     cwd.to_string_lossy(),
                 );
             }
-            EventMsg::ExecCommandOutputDelta(_) => {}
+            EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
+                call_id,
+                chunk,
+                ..
+            }) => {
+                let Some(exec_command) = self.call_id_to_command.get_mut(&call_id) else {
+                    return CodexStatus::Running;
+                };
+                exec_command.partial_line.extend_from_slice(&chunk);
+                while let Some(newline) = exec_command.partial_line.iter().position(|&b| b == b'\n')
+                {
+                    let line: Vec<u8> = exec_command.partial_line.drain(..=newline).collect();
+                    if exec_command.streamed_lines < MAX_OUTPUT_LINES_FOR_EXEC_TOOL_CALL {
+                        let line = String::from_utf8_lossy(&line);
+                        println!("{}", line.trim_end_matches(['\r', '\n']).style(self.dimmed));
+                    }
+                    exec_command.streamed_lines += 1;
+                }
+            }
             EventMsg::ExecCommandEnd(ExecCommandEndEvent {
                 call_id,
                 aggregated_output,
                 duration,
                 exit_code,
+                sandbox_denied,
+                sandbox_denials,
                 ..
             }) => {
                 let exec_command = self.call_id_to_command.remove(&call_id);
+                let streamed_lines = exec_command.as_ref().map_or(0, |c| c.streamed_lines);
                 let (duration, call) = if let Some(ExecCommandBegin { command, .. }) = exec_command
                 {
                     (
@@ -304,22 +372,60 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     ("".to_string(), format!("exec('{call_id}')"))
                 };
 
+                // Lines already streamed live are not repeated.
                 let truncated_output = aggregated_output
                     .lines()
                     .take(MAX_OUTPUT_LINES_FOR_EXEC_TOOL_CALL)
+                    .skip(streamed_lines)
                     .collect::<Vec<_>>()
                     .join("\n");
+                if streamed_lines > 0 && !truncated_output.is_empty() {
+                    println!("{}", truncated_output.style(self.dimmed));
+                }
                 match exit_code {
                     0 => {
                         let title = format!("{call} succeeded{duration}:");
                         ts_println!(self, "{}", title.style(self.green));
                     }
+                    _ if sandbox_denied => {
+                        let title =
+                            format!("{call} exited {exit_code}{duration} (denied by sandbox):");
+                        ts_println!(self, "{}", title.style(self.red));
+                    }
                     _ => {
                         let title = format!("{call} exited {exit_code}{duration}:");
                         ts_println!(self, "{}", title.style(self.red));
                     }
                 }
-                println!("{}", truncated_output.style(self.dimmed));
+                if streamed_lines == 0 {
+                    println!("{}", truncated_output.style(self.dimmed));
+                }
+                for denial in sandbox_denials {
+                    println!("{}", format!("blocked: {denial}").style(self.red));
+                }
+            }
+            EventMsg::ContainerImagePull(ContainerImagePullEvent { image, status, .. }) => {
+                match status {
+                    ContainerImagePullStatus::Started => {
+                        ts_println!(
+                            self,
+                            "{} {}",
+                            "pulling image".style(self.magenta),
+                            image.style(self.bold),
+                        );
+                    }
+                    ContainerImagePullStatus::Progress { message } => {
+                        println!("{}", message.style(self.dimmed));
+                    }
+                    ContainerImagePullStatus::Completed => {
+                        ts_println!(self, "{}", format!("pulled {image}").style(self.green));
+                    }
+                    ContainerImagePullStatus::Failed { message } => {
+                        let title = format!("failed to pull {image}:");
+                        ts_println!(self, "{}", title.style(self.red));
+                        println!("{}", message.style(self.dimmed));
+                    }
+                }
             }
             EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
                 call_id: _,
@@ -483,6 +589,33 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 ts_println!(self, "{}", "turn diff:".style(self.magenta));
                 println!("{unified_diff}");
             }
+            EventMsg::TurnReverted(TurnRevertedEvent { turn_id, paths }) => {
+                ts_println!(
+                    self,
+                    "{} {}",
+                    "reverted turn".style(self.magenta),
+                    turn_id.style(self.dimmed)
+                );
+                for path in paths {
+                    println!("{}", path.display().to_string().style(self.dimmed));
+                }
+            }
+            EventMsg::ContextCompacted(ContextCompactedEvent { summary_tokens }) => {
+                ts_println!(
+                    self,
+                    "{} {}",
+                    "compacted conversation".style(self.magenta),
+                    format!("into a {summary_tokens}-token summary").style(self.dimmed)
+                );
+            }
+            EventMsg::ProfileSwitched(ProfileSwitchedEvent { profile, model, .. }) => {
+                ts_println!(
+                    self,
+                    "{} {}",
+                    "switched to profile".style(self.magenta),
+                    format!("{profile} (model: {model})").style(self.dimmed)
+                );
+            }
             EventMsg::ExecApprovalRequest(_) => {
                 // Should we exit?
             }
@@ -510,6 +643,8 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     model,
                     history_log_id: _,
                     history_entry_count: _,
+                    project_docs,
+                    protocol: _,
                 } = session_configured_event;
 
                 ts_println!(
@@ -520,12 +655,25 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 );
 
                 ts_println!(self, "model: {}", model);
+                for path in project_docs {
+                    ts_println!(self, "instructions: {}", path.display());
+                }
                 println!();
             }
             EventMsg::PlanUpdate(plan_update_event) => {
                 let UpdatePlanArgs { explanation, plan } = plan_update_event;
-                ts_println!(self, "explanation: {explanation:?}");
-                ts_println!(self, "plan: {plan:?}");
+                ts_println!(self, "{}", "plan".style(self.magenta));
+                if let Some(explanation) = explanation {
+                    println!("{}", explanation.style(self.italic));
+                }
+                for PlanItemArg { step, status } in plan {
+                    let marker = match status {
+                        StepStatus::Completed => "[x]",
+                        StepStatus::InProgress => "[~]",
+                        StepStatus::Pending => "[ ]",
+                    };
+                    println!("  {marker} {step}");
+                }
             }
             EventMsg::GetHistoryEntryResponse(_) => {
                 // Currently ignored in exec output.
@@ -533,14 +681,28 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::McpListToolsResponse(_) => {
                 // Currently ignored in exec output.
             }
-            EventMsg::TurnAborted(abort_reason) => match abort_reason.reason {
-                TurnAbortReason::Interrupted => {
-                    ts_println!(self, "task interrupted");
-                }
-                TurnAbortReason::Replaced => {
-                    ts_println!(self, "task aborted: replaced by a new task");
+            EventMsg::BackgroundTaskUpdate(info) => {
+                ts_println!(
+                    self,
+                    "{} {}",
+                    "background".style(self.magenta),
+                    describe_background_task(&info).style(self.dimmed),
+                );
+            }
+            EventMsg::ListBackgroundTasksResponse(_) => {
+                // Currently ignored in exec output.
+            }
+            EventMsg::TurnAborted(abort_reason) => {
+                self.close_streams();
+                match abort_reason.reason {
+                    TurnAbortReason::Interrupted => {
+                        ts_println!(self, "task interrupted");
+                    }
+                    TurnAbortReason::Replaced => {
+                        ts_println!(self, "task aborted: replaced by a new task");
+                    }
                 }
-            },
+            }
             EventMsg::ShutdownComplete => return CodexStatus::Shutdown,
             EventMsg::ConversationHistory(_) => {}
         }
@@ -548,7 +710,7 @@ impl EventProcessor for EventProcessorWithHumanOutput {
     }
 }
 
-fn escape_command(command: &[String]) -> String {
+pub(crate) fn escape_command(command: &[String]) -> String {
     try_join(command.iter().map(|s| s.as_str())).unwrap_or_else(|_| command.join(" "))
 }
 
@@ -565,6 +727,16 @@ fn format_file_change(change: &FileChange) -> &'static str {
     }
 }
 
+/// One line about a background task, e.g. `#2 exited with 1: npm run dev`.
+fn describe_background_task(info: &BackgroundTaskInfo) -> String {
+    let status = match info.status {
+        BackgroundTaskStatus::Running => "started".to_string(),
+        BackgroundTaskStatus::Exited { exit_code } => format!("exited with {exit_code}"),
+        BackgroundTaskStatus::Killed => "killed".to_string(),
+    };
+    format!("#{} {status}: {}", info.id, escape_command(&info.command))
+}
+
 fn format_mcp_invocation(invocation: &McpInvocation) -> String {
     // Build fully-qualified tool name: server.tool
     let fq_tool_name = format!("{}.{}", invocation.server, invocation.tool);
@@ -582,3 +754,33 @@ fn format_mcp_invocation(invocation: &McpInvocation) -> String {
         format!("{fq_tool_name}({args_str})")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn describes_background_tasks() {
+        let mut info = BackgroundTaskInfo {
+            id: 2,
+            command: vec![
+                "npm".to_string(),
+                "run".to_string(),
+                "dev server".to_string(),
+            ],
+            cwd: PathBuf::from("/repo"),
+            status: BackgroundTaskStatus::Running,
+            output_tail: String::new(),
+        };
+        assert_eq!(
+            describe_background_task(&info),
+            "#2 started: npm run 'dev server'"
+        );
+        info.status = BackgroundTaskStatus::Exited { exit_code: 1 };
+        assert_eq!(
+            describe_background_task(&info),
+            "#2 exited with 1: npm run 'dev server'"
+        );
+    }
+}
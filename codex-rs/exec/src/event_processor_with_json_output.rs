@@ -45,19 +45,27 @@ impl EventProcessor for EventProcessorWithJsonOutput {
                 // Suppress streaming events in JSON mode.
                 CodexStatus::Running
             }
-            EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
-                if let Some(output_file) = self.last_message_path.as_deref() {
-                    handle_last_message(last_agent_message.as_deref(), output_file);
-                }
-                CodexStatus::InitiateShutdown
-            }
             EventMsg::ShutdownComplete => CodexStatus::Shutdown,
             _ => {
-                if let Ok(line) = serde_json::to_string(&event) {
-                    println!("{line}");
+                print_event(&event);
+                if let EventMsg::TaskComplete(TaskCompleteEvent {
+                    last_agent_message, ..
+                }) = &event.msg
+                {
+                    if let Some(output_file) = self.last_message_path.as_deref() {
+                        handle_last_message(last_agent_message.as_deref(), output_file);
+                    }
+                    return CodexStatus::InitiateShutdown;
                 }
                 CodexStatus::Running
             }
         }
     }
 }
+
+fn print_event(event: &Event) {
+    match serde_json::to_string(event) {
+        Ok(line) => println!("{line}"),
+        Err(e) => eprintln!("Failed to serialize event: {e}"),
+    }
+}
@@ -1,39 +1,156 @@
 mod cli;
 mod event_processor;
+mod event_processor_with_answer_output;
 mod event_processor_with_human_output;
 mod event_processor_with_json_output;
+mod plain;
+mod plain_completion;
+mod replay;
+mod review;
+mod run_summary;
+mod script;
+mod webhook;
 
 use std::io::IsTerminal;
 use std::io::Read;
+use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
 
+pub use cli::AskCli;
 pub use cli::Cli;
+pub use cli::ExecCommand;
+pub use cli::OutputFormat;
+pub use cli::ReviewArgs;
+use codex_common::exit_code::ExitCode;
 use codex_core::BUILT_IN_OSS_MODEL_PROVIDER_ID;
+use codex_core::CodexConversation;
 use codex_core::ConversationManager;
 use codex_core::NewConversation;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
 use codex_core::protocol::AskForApproval;
+use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::InputItem;
 use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
+use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::util::is_inside_git_repo;
 use codex_login::AuthManager;
 use codex_ollama::DEFAULT_OSS_MODEL;
 use codex_protocol::config_types::SandboxMode;
+use event_processor_with_answer_output::EventProcessorWithAnswerOutput;
 use event_processor_with_human_output::EventProcessorWithHumanOutput;
 use event_processor_with_json_output::EventProcessorWithJsonOutput;
+pub use plain::run_plain;
+pub use plain::should_use_plain_mode;
+pub use replay::run_replay;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
+use tracing_subscriber::Layer;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
 
 use crate::event_processor::CodexStatus;
 use crate::event_processor::EventProcessor;
+use crate::run_summary::RunSummary;
+use crate::run_summary::StepReport;
+use crate::run_summary::StepStatus;
+use crate::run_summary::truncation_message;
+use crate::script::ScriptStep;
 
-pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()> {
+/// Instructions prepended to the question in `codex ask`.
+const ASK_PROMPT: &str = include_str!("ask_prompt.md");
+
+/// Prompt of a `--resume-last` run without one of its own.
+const RESUME_PROMPT: &str = "Your previous run was interrupted before you finished the task. Continue from where you left off: the commands and edits you completed, and their results, are in the conversation above, so do not redo work that already succeeded.";
+
+/// How [`run`] drives the agent and reports its progress.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum RunMode {
+    /// `codex exec`: work on a task and print every event.
+    Exec,
+    /// `codex ask`: answer a question in a read-only session and print only
+    /// the answer.
+    Ask,
+    /// `codex exec review`: review a pull request in a read-only session and
+    /// print the comments parsed from the answer.
+    Review { pr: u64, post: bool },
+}
+
+pub async fn run_main(
+    mut cli: Cli,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+) -> anyhow::Result<()> {
+    let mode = match cli.command.take() {
+        Some(ExecCommand::Review(ReviewArgs { pr, post })) => {
+            if cli.script.is_some() || cli.resume_last {
+                eprintln!("--script and --resume-last cannot be used with `codex exec review`.");
+                ExitCode::ConfigError.exit();
+            }
+            RunMode::Review { pr, post }
+        }
+        None => RunMode::Exec,
+    };
+    run(cli, mode, codex_linux_sandbox_exe).await
+}
+
+/// Answers a question about the workspace under a read-only sandbox, without
+/// the plan or apply_patch tools, and prints just the answer. No retrieval
+/// or code search tool is added; the agent searches with shell commands.
+pub async fn run_ask(cli: AskCli, codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()> {
+    let AskCli {
+        model,
+        oss,
+        config_profile,
+        cwd,
+        skip_git_repo_check,
+        config_overrides,
+        color,
+        last_message_file,
+        question,
+    } = cli;
+    let cli = Cli {
+        images: Vec::new(),
+        model,
+        oss,
+        sandbox_mode: None,
+        config_profile,
+        full_auto: false,
+        dangerously_bypass_approvals_and_sandbox: false,
+        cwd,
+        skip_git_repo_check,
+        config_overrides,
+        color,
+        output_format: OutputFormat::Human,
+        json: false,
+        last_message_file,
+        notify_webhook: None,
+        script: None,
+        resume_last: false,
+        keep_going: false,
+        fail_on_patch_error: false,
+        max_turns: None,
+        max_wall_time: None,
+        prompt: question,
+        command: None,
+    };
+    run(cli, RunMode::Ask, codex_linux_sandbox_exe).await
+}
+
+async fn run(
+    cli: Cli,
+    mode: RunMode,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+) -> anyhow::Result<()> {
     let Cli {
         images,
         model: model_cli_arg,
@@ -45,47 +162,59 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         skip_git_repo_check,
         color,
         last_message_file,
-        json: json_mode,
+        notify_webhook,
+        output_format,
+        json,
         sandbox_mode: sandbox_mode_cli_arg,
+        script,
+        resume_last,
+        keep_going,
+        fail_on_patch_error,
+        max_turns,
+        max_wall_time,
         prompt,
         config_overrides,
+        command: _,
     } = cli;
 
-    // Determine the prompt based on CLI arg and/or stdin.
-    let prompt = match prompt {
-        Some(p) if p != "-" => p,
-        // Either `-` was passed or no positional arg.
-        maybe_dash => {
-            // When no arg (None) **and** stdin is a TTY, bail out early – unless the
-            // user explicitly forced reading via `-`.
-            let force_stdin = matches!(maybe_dash.as_deref(), Some("-"));
-
-            if std::io::stdin().is_terminal() && !force_stdin {
-                eprintln!(
-                    "No prompt provided. Either specify one as an argument or pipe the prompt into stdin."
-                );
-                std::process::exit(1);
+    let pull_request = match mode {
+        RunMode::Review { pr, .. } => {
+            let cwd = match &cwd {
+                Some(cwd) => cwd.clone(),
+                None => std::env::current_dir()?,
+            };
+            match review::fetch_pull_request(pr, &cwd).await {
+                Ok(pull_request) => Some(pull_request),
+                Err(e) => {
+                    eprintln!("Error fetching pull request #{pr}: {e}");
+                    ExitCode::TaskFailed.exit();
+                }
             }
+        }
+        RunMode::Exec | RunMode::Ask => None,
+    };
 
-            // Ensure the user knows we are waiting on stdin, as they may
-            // have gotten into this state by mistake. If so, and they are not
-            // writing to stdin, Codex will hang indefinitely, so this should
-            // help them debug in that case.
-            if !force_stdin {
-                eprintln!("Reading prompt from stdin...");
-            }
-            let mut buffer = String::new();
-            if let Err(e) = std::io::stdin().read_to_string(&mut buffer) {
-                eprintln!("Failed to read prompt from stdin: {e}");
-                std::process::exit(1);
-            } else if buffer.trim().is_empty() {
-                eprintln!("No prompt provided via stdin.");
-                std::process::exit(1);
+    let steps = match &script {
+        Some(path) => match script::load_script(path) {
+            Ok(steps) => steps,
+            Err(e) => {
+                eprintln!("Error loading script {}: {e}", path.display());
+                ExitCode::ConfigError.exit();
             }
-            buffer
+        },
+        None => {
+            let prompt = match (mode, &pull_request) {
+                (RunMode::Review { .. }, Some(pull_request)) => review::review_prompt(pull_request),
+                (RunMode::Ask, _) => format!("{ASK_PROMPT}{}", read_prompt(prompt)),
+                (RunMode::Exec, _) if resume_last && prompt.is_none() => RESUME_PROMPT.to_string(),
+                _ => read_prompt(prompt),
+            };
+            vec![ScriptStep::from_prompt(prompt)]
         }
     };
 
+    let json_mode = json || output_format == OutputFormat::Json;
+
     let (stdout_with_ansi, stderr_with_ansi) = match color {
         cli::Color::Always => (true, true),
         cli::Color::Never => (false, false),
@@ -95,21 +224,11 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         ),
     };
 
-    // TODO(mbolin): Take a more thoughtful approach to logging.
-    let default_level = "error";
-    let _ = tracing_subscriber::fmt()
-        // Fallback to the `default_level` log filter if the environment
-        // variable is not set _or_ contains an invalid value
-        .with_env_filter(
-            EnvFilter::try_from_default_env()
-                .or_else(|_| EnvFilter::try_new(default_level))
-                .unwrap_or_else(|_| EnvFilter::new(default_level)),
-        )
-        .with_ansi(stderr_with_ansi)
-        .with_writer(std::io::stderr)
-        .try_init();
-
-    let sandbox_mode = if full_auto {
+    // `codex ask` and `codex exec review` only look at the workspace.
+    let read_only = mode != RunMode::Exec;
+    let sandbox_mode = if read_only {
+        Some(SandboxMode::ReadOnly)
+    } else if full_auto {
         Some(SandboxMode::WorkspaceWrite)
     } else if dangerously_bypass_approvals_and_sandbox {
         Some(SandboxMode::DangerFullAccess)
@@ -146,24 +265,106 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         model_provider,
         codex_linux_sandbox_exe,
         base_instructions: None,
-        include_plan_tool: None,
-        include_apply_patch_tool: None,
+        include_plan_tool: read_only.then_some(false),
+        include_apply_patch_tool: read_only.then_some(false),
         disable_response_storage: oss.then_some(true),
         show_raw_agent_reasoning: oss.then_some(true),
         tools_web_search_request: None,
+        max_turns,
+        max_wall_time: max_wall_time.map(Duration::from_secs),
     };
     // Parse `-c` overrides.
     let cli_kv_overrides = match config_overrides.parse_overrides() {
         Ok(v) => v,
         Err(e) => {
             eprintln!("Error parsing -c overrides: {e}");
-            std::process::exit(1);
+            ExitCode::ConfigError.exit();
         }
     };
 
-    let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides)?;
+    let mut config =
+        match Config::load_with_cli_overrides(cli_kv_overrides.clone(), overrides.clone()) {
+            Ok(config) => config,
+            Err(e) => {
+                eprintln!("Error loading configuration: {e}");
+                ExitCode::ConfigError.exit();
+            }
+        };
+
+    // The restored history holds every tool call that completed, with its
+    // result, so the agent picks up where the interrupted run stopped.
+    if resume_last {
+        match codex_core::find_latest_rollout(&config.codex_home, &config.cwd) {
+            Some(path) => {
+                eprintln!("Resuming the session recorded in {}", path.display());
+                config.experimental_resume = Some(path);
+            }
+            None => {
+                eprintln!(
+                    "No previous session was started in {}; nothing to resume.",
+                    config.cwd.display()
+                );
+                ExitCode::ConfigError.exit();
+            }
+        }
+    }
+
+    // A step that overrides the sandbox gets the policy config.toml gives
+    // that mode, e.g. with its `[sandbox_workspace_write]` settings.
+    let mut step_sandbox_policies = Vec::with_capacity(steps.len());
+    for step in &steps {
+        let Some(sandbox_mode) = step.sandbox else {
+            step_sandbox_policies.push(None);
+            continue;
+        };
+        let overrides = ConfigOverrides {
+            sandbox_mode: Some(sandbox_mode),
+            ..overrides.clone()
+        };
+        match Config::load_with_cli_overrides(cli_kv_overrides.clone(), overrides) {
+            Ok(step_config) => step_sandbox_policies.push(Some(step_config.sandbox_policy)),
+            Err(e) => {
+                eprintln!("Error loading configuration: {e}");
+                ExitCode::ConfigError.exit();
+            }
+        }
+    }
+
+    // Spans and metrics are exported over OTLP when `[telemetry]` is
+    // enabled; the guard flushes them on exit.
+    let (otel_layer, _telemetry_guard) = match codex_core::telemetry::init(&config.telemetry) {
+        Ok(telemetry) => telemetry.unzip(),
+        Err(e) => {
+            eprintln!("Telemetry export disabled: {e}");
+            (None, None)
+        }
+    };
+
+    // TODO(mbolin): Take a more thoughtful approach to logging.
+    let default_level = "error";
+    let fmt_layer = tracing_subscriber::fmt::layer()
+        .with_ansi(stderr_with_ansi)
+        .with_writer(std::io::stderr)
+        // Fallback to the `default_level` log filter if the environment
+        // variable is not set _or_ contains an invalid value
+        .with_filter(
+            EnvFilter::try_from_default_env()
+                .or_else(|_| EnvFilter::try_new(default_level))
+                .unwrap_or_else(|_| EnvFilter::new(default_level)),
+        );
+    let _ = tracing_subscriber::registry()
+        .with(fmt_layer)
+        .with(otel_layer)
+        .try_init();
+
     let mut event_processor: Box<dyn EventProcessor> = if json_mode {
         Box::new(EventProcessorWithJsonOutput::new(last_message_file.clone()))
+    } else if read_only {
+        Box::new(EventProcessorWithAnswerOutput::create_with_ansi(
+            stderr_with_ansi,
+            mode == RunMode::Ask,
+            last_message_file.clone(),
+        ))
     } else {
         Box::new(EventProcessorWithHumanOutput::create_with_ansi(
             stdout_with_ansi,
@@ -172,21 +373,36 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         ))
     };
 
-    if oss {
-        codex_ollama::ensure_oss_ready(&config)
+    // Keeps an `ollama serve` started for this run alive until it ends.
+    let ollama_server = if oss {
+        codex_ollama::ensure_oss_ready(&mut config)
             .await
-            .map_err(|e| anyhow::anyhow!("OSS setup failed: {e}"))?;
-    }
+            .map_err(|e| anyhow::anyhow!("OSS setup failed: {e}"))?
+    } else {
+        None
+    };
 
     // Print the effective configuration and prompt so users can see what Codex
     // is using.
-    event_processor.print_config_summary(&config, &prompt);
+    event_processor.print_config_summary(&config, &steps[0].prompt);
 
     if !skip_git_repo_check && !is_inside_git_repo(&config.cwd.to_path_buf()) {
         eprintln!("Not inside a trusted directory and --skip-git-repo-check was not specified.");
-        std::process::exit(1);
+        drop(ollama_server);
+        ExitCode::ConfigError.exit();
     }
 
+    // `codex ask` only prints an answer; the webhook reports on tasks.
+    let notify_webhook = match mode {
+        RunMode::Exec | RunMode::Review { .. } => {
+            notify_webhook.or_else(|| config.notifications.webhook.clone())
+        }
+        RunMode::Ask => None,
+    };
+
+    let cwd = config.cwd.clone();
+    let run_turn_settings = (config.approval_policy, config.sandbox_policy.clone());
+
     let conversation_manager = ConversationManager::new(AuthManager::shared(
         config.codex_home.clone(),
         config.preferred_auth_method,
@@ -195,17 +411,30 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         conversation_id: _,
         conversation,
         session_configured,
-    } = conversation_manager.new_conversation(config).await?;
+    } = match conversation_manager.new_conversation(config).await {
+        Ok(new_conversation) => new_conversation,
+        Err(e) => {
+            eprintln!("Failed to start a session: {e}");
+            drop(ollama_server);
+            ExitCode::from(e.kind()).exit();
+        }
+    };
     info!("Codex initialized with event: {session_configured:?}");
+    let session_id = session_configured.session_id.to_string();
 
     let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<Event>();
+    // Events stop being forwarded after Ctrl-C, so the `TurnAborted` event
+    // is never seen here; record the interrupt directly.
+    let interrupted = Arc::new(AtomicBool::new(false));
     {
         let conversation = conversation.clone();
+        let interrupted = interrupted.clone();
         tokio::spawn(async move {
             loop {
                 tokio::select! {
                     _ = tokio::signal::ctrl_c() => {
                         tracing::debug!("Keyboard interrupt");
+                        interrupted.store(true, Ordering::Relaxed);
                         // Immediately notify Codex to abort any in‑flight task.
                         conversation.submit(Op::Interrupt).await.ok();
 
@@ -247,30 +476,129 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         info!("Sent images with event ID: {initial_images_event_id}");
         while let Ok(event) = conversation.next_event().await {
             if event.id == initial_images_event_id
-                && matches!(
-                    event.msg,
-                    EventMsg::TaskComplete(TaskCompleteEvent {
-                        last_agent_message: _,
-                    })
-                )
+                && matches!(event.msg, EventMsg::TaskComplete(TaskCompleteEvent { .. }))
             {
                 break;
             }
         }
     }
 
-    // Send the prompt.
-    let items: Vec<InputItem> = vec![InputItem::Text { text: prompt }];
-    let initial_prompt_task_id = conversation.submit(Op::UserInput { items }).await?;
-    info!("Sent prompt with event ID: {initial_prompt_task_id}");
+    // Run the steps in order. Each ends when its task completes; the first
+    // error decides the exit code.
+    let scripted = script.is_some();
+    let mut turn_settings = run_turn_settings.clone();
+    let mut step_index = 0;
+    let step_settings = |index: usize| {
+        (
+            steps[index].approval_policy.unwrap_or(run_turn_settings.0),
+            step_sandbox_policies[index]
+                .clone()
+                .unwrap_or_else(|| run_turn_settings.1.clone()),
+        )
+    };
+    start_step(
+        &conversation,
+        &steps[0],
+        step_settings(0),
+        &mut turn_settings,
+    )
+    .await?;
 
-    // Run the loop until the task is complete.
+    let mut exit_code = ExitCode::Success;
+    let mut step_error: Option<String> = None;
+    let mut run_summary = RunSummary::new(fail_on_patch_error);
     while let Some(event) = rx.recv().await {
+        if let EventMsg::Error(ErrorEvent { kind, message }) = &event.msg {
+            if exit_code == ExitCode::Success {
+                exit_code = ExitCode::from(*kind);
+            }
+            if step_error.is_none() {
+                step_error = Some(message.clone());
+            }
+        }
+        if let EventMsg::TaskComplete(TaskCompleteEvent {
+            truncated: Some(reason),
+            ..
+        }) = &event.msg
+            && step_error.is_none()
+        {
+            step_error = Some(truncation_message(*reason).to_string());
+        }
+        // Nobody can answer here, so a step that allows approval requests
+        // has them denied.
+        match &event.msg {
+            EventMsg::ExecApprovalRequest(_) => {
+                conversation
+                    .submit(Op::ExecApproval {
+                        id: event.id.clone(),
+                        decision: ReviewDecision::Denied,
+                        allow_pattern: None,
+                    })
+                    .await?;
+            }
+            EventMsg::ApplyPatchApprovalRequest(_) => {
+                conversation
+                    .submit(Op::PatchApproval {
+                        id: event.id.clone(),
+                        decision: ReviewDecision::Denied,
+                        accepted_files: None,
+                    })
+                    .await?;
+            }
+            _ => {}
+        }
+        run_summary.observe(&event.msg);
         let shutdown: CodexStatus = event_processor.process_event(event);
         match shutdown {
             CodexStatus::Running => continue,
             CodexStatus::InitiateShutdown => {
-                conversation.submit(Op::Shutdown).await?;
+                // The current step's task completed.
+                let step = &steps[step_index];
+                let mut failure = step_error.take();
+                if failure.is_none()
+                    && let Some(check) = &step.check
+                    && let Err(e) = script::run_check(check, &cwd).await
+                {
+                    failure = Some(e);
+                    if exit_code == ExitCode::Success {
+                        exit_code = ExitCode::TaskFailed;
+                    }
+                }
+                let failed = failure.is_some();
+                if scripted {
+                    report_step(
+                        &mut run_summary,
+                        step_index,
+                        steps.len(),
+                        step.label(step_index),
+                        failure,
+                    );
+                }
+                step_index += 1;
+                if step_index < steps.len() && (!failed || keep_going) {
+                    start_step(
+                        &conversation,
+                        &steps[step_index],
+                        step_settings(step_index),
+                        &mut turn_settings,
+                    )
+                    .await?;
+                } else {
+                    for (index, step) in steps.iter().enumerate().skip(step_index) {
+                        eprintln!(
+                            "[{}/{}] {}: skipped",
+                            index + 1,
+                            steps.len(),
+                            step.label(index)
+                        );
+                        run_summary.record_step(StepReport {
+                            name: step.label(index),
+                            status: StepStatus::Skipped,
+                            error: None,
+                        });
+                    }
+                    conversation.submit(Op::Shutdown).await?;
+                }
             }
             CodexStatus::Shutdown => {
                 break;
@@ -278,5 +606,171 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         }
     }
 
+    if interrupted.load(Ordering::Relaxed) {
+        exit_code = ExitCode::Interrupted;
+    } else if exit_code == ExitCode::Success {
+        exit_code = run_summary.exit_code();
+    }
+    if let RunMode::Review { post, .. } = mode
+        && let Some(pull_request) = &pull_request
+        && matches!(exit_code, ExitCode::Success | ExitCode::SuccessWithWarnings)
+        && !report_review(
+            pull_request,
+            run_summary.last_agent_message(),
+            post,
+            json_mode,
+            &cwd,
+        )
+        .await
+    {
+        exit_code = ExitCode::TaskFailed;
+    }
+    if json_mode || notify_webhook.is_some() {
+        let report = run_summary.into_report(session_id, exit_code);
+        if json_mode {
+            println!("{}", serde_json::json!({ "summary": report }));
+        }
+        if let Some(url) = notify_webhook {
+            webhook::send(&url, &report).await;
+        }
+    }
+    if exit_code != ExitCode::Success {
+        // `exit` skips destructors, so stop the Ollama server first.
+        drop(ollama_server);
+        exit_code.exit();
+    }
+    Ok(())
+}
+
+/// The prompt from the command line, or from stdin when it is absent or `-`.
+fn read_prompt(prompt: Option<String>) -> String {
+    match prompt {
+        Some(p) if p != "-" => p,
+        // Either `-` was passed or no positional arg.
+        maybe_dash => {
+            // When no arg (None) **and** stdin is a TTY, bail out early – unless the
+            // user explicitly forced reading via `-`.
+            let force_stdin = matches!(maybe_dash.as_deref(), Some("-"));
+
+            if std::io::stdin().is_terminal() && !force_stdin {
+                eprintln!(
+                    "No prompt provided. Either specify one as an argument or pipe the prompt into stdin."
+                );
+                ExitCode::ConfigError.exit();
+            }
+
+            // Ensure the user knows we are waiting on stdin, as they may
+            // have gotten into this state by mistake. If so, and they are not
+            // writing to stdin, Codex will hang indefinitely, so this should
+            // help them debug in that case.
+            if !force_stdin {
+                eprintln!("Reading prompt from stdin...");
+            }
+            let mut buffer = String::new();
+            if let Err(e) = std::io::stdin().read_to_string(&mut buffer) {
+                eprintln!("Failed to read prompt from stdin: {e}");
+                ExitCode::ConfigError.exit();
+            } else if buffer.trim().is_empty() {
+                eprintln!("No prompt provided via stdin.");
+                ExitCode::ConfigError.exit();
+            }
+            buffer
+        }
+    }
+}
+
+/// Submit the prompt of `step`, first changing the approval policy and
+/// sandbox to `settings` if the previous step used different ones.
+async fn start_step(
+    conversation: &CodexConversation,
+    step: &ScriptStep,
+    settings: (AskForApproval, SandboxPolicy),
+    current: &mut (AskForApproval, SandboxPolicy),
+) -> anyhow::Result<()> {
+    if settings != *current {
+        conversation
+            .submit(Op::OverrideTurnContext {
+                cwd: None,
+                approval_policy: Some(settings.0),
+                sandbox_policy: Some(settings.1.clone()),
+                model: None,
+                effort: None,
+                summary: None,
+            })
+            .await?;
+        *current = settings;
+    }
+    let items: Vec<InputItem> = vec![InputItem::Text {
+        text: step.prompt.clone(),
+    }];
+    let task_id = conversation.submit(Op::UserInput { items }).await?;
+    info!("Sent prompt with event ID: {task_id}");
     Ok(())
 }
+
+/// Print the comments of a `codex exec review` run as JSON and post them to
+/// the pull request with `post`. Returns whether both succeeded.
+async fn report_review(
+    pull_request: &review::PullRequest,
+    reply: Option<&str>,
+    post: bool,
+    json_mode: bool,
+    cwd: &Path,
+) -> bool {
+    let comments = match reply.map(review::parse_review) {
+        Some(Ok(comments)) => comments,
+        Some(Err(e)) => {
+            eprintln!("Error reading the review comments from the agent's reply: {e}");
+            return false;
+        }
+        None => {
+            eprintln!("The agent did not reply with a review.");
+            return false;
+        }
+    };
+    let review = serde_json::json!({
+        "pr": pull_request.number,
+        "comments": comments,
+    });
+    if json_mode {
+        println!("{}", serde_json::json!({ "review": review }));
+    } else {
+        println!("{review:#}");
+    }
+    if post {
+        if let Err(e) = review::post_review(pull_request, &comments, cwd).await {
+            eprintln!("Error posting the review: {e}");
+            return false;
+        }
+        eprintln!(
+            "Posted {} comment(s) to #{}.",
+            comments.len(),
+            pull_request.number
+        );
+    }
+    true
+}
+
+/// Print the outcome of a `--script` step to stderr and add it to the run
+/// summary.
+fn report_step(
+    run_summary: &mut RunSummary,
+    index: usize,
+    total: usize,
+    name: String,
+    failure: Option<String>,
+) {
+    match &failure {
+        Some(reason) => eprintln!("[{}/{total}] {name}: failed: {reason}", index + 1),
+        None => eprintln!("[{}/{total}] {name}: passed", index + 1),
+    }
+    run_summary.record_step(StepReport {
+        name,
+        status: if failure.is_some() {
+            StepStatus::Failed
+        } else {
+            StepStatus::Passed
+        },
+        error: failure,
+    });
+}
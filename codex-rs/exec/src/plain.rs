@@ -0,0 +1,443 @@
+//! `codex --plain`: the interactive session as line-oriented output, for
+//! terminals the full-screen TUI cannot drive, such as Emacs shell buffers,
+//! `TERM=dumb` and CI consoles.
+//!
+//! Events are printed by the same processor as `codex exec`. Messages are
+//! read one line at a time (a line ending in `\` continues on the next), and
+//! approval requests are answered at a simple prompt. When stdin is a
+//! terminal, lines are edited with readline key bindings, previous messages
+//! are kept in `$CODEX_HOME/plain_history` and searched with Ctrl-R, and Tab
+//! completes slash commands and `@` file names.
+
+use std::io::IsTerminal;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::mpsc as std_mpsc;
+
+use codex_core::ConversationManager;
+use codex_core::NewConversation;
+use codex_core::config::Config;
+use codex_core::config_types::HistoryPersistence;
+use codex_core::protocol::ApplyPatchApprovalRequestEvent;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::ExecApprovalRequestEvent;
+use codex_core::protocol::FileChange;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
+use codex_core::protocol::TokenUsage;
+use codex_login::AuthManager;
+use rustyline::Editor;
+use rustyline::error::ReadlineError;
+use rustyline::history::FileHistory;
+use rustyline::history::History;
+use tokio::sync::mpsc;
+
+use crate::event_processor::CodexStatus;
+use crate::event_processor::EventProcessor;
+use crate::event_processor_with_human_output::EventProcessorWithHumanOutput;
+use crate::event_processor_with_human_output::escape_command;
+use crate::plain_completion::PlainHelper;
+use crate::plain_completion::SLASH_COMMANDS;
+
+/// Whether the full-screen TUI cannot be used here: `TERM=dumb`, or stdin
+/// or stdout is not a terminal.
+pub fn should_use_plain_mode() -> bool {
+    std::env::var("TERM").is_ok_and(|term| term == "dumb")
+        || !std::io::stdin().is_terminal()
+        || !std::io::stdout().is_terminal()
+}
+
+/// Runs an interactive session with line-oriented output until stdin ends,
+/// the user types `/quit` or presses Ctrl-C at the prompt. Returns the
+/// tokens used.
+pub async fn run_plain(
+    config: Config,
+    prompt: Option<String>,
+    images: Vec<PathBuf>,
+) -> anyhow::Result<TokenUsage> {
+    let with_ansi = std::io::stdout().is_terminal()
+        && !std::env::var("TERM").is_ok_and(|term| term == "dumb");
+    let mut event_processor =
+        EventProcessorWithHumanOutput::create_with_ansi(with_ansi, &config, None);
+    event_processor.print_config_summary(&config, prompt.as_deref().unwrap_or_default());
+
+    let mut input = LineInput::spawn(&config);
+    let conversation_manager = ConversationManager::new(AuthManager::shared(
+        config.codex_home.clone(),
+        config.preferred_auth_method,
+    ));
+    let NewConversation { conversation, .. } = conversation_manager.new_conversation(config).await?;
+
+    let mut usage = TokenUsage::default();
+    let mut next_items: Option<Vec<InputItem>> = if prompt.is_some() || !images.is_empty() {
+        let mut items: Vec<InputItem> = images
+            .into_iter()
+            .map(|path| InputItem::LocalImage { path })
+            .collect();
+        if let Some(text) = prompt {
+            items.push(InputItem::Text { text });
+        }
+        Some(items)
+    } else {
+        None
+    };
+
+    loop {
+        let items = match next_items.take() {
+            Some(items) => items,
+            None => {
+                println!();
+                let message = tokio::select! {
+                    message = input.read_message() => message,
+                    _ = tokio::signal::ctrl_c() => None,
+                };
+                match message.as_deref().map(str::trim) {
+                    None => break,
+                    Some(command) if SLASH_COMMANDS.contains(&command) => break,
+                    Some("") => continue,
+                    Some(text) => vec![InputItem::Text {
+                        text: text.to_string(),
+                    }],
+                }
+            }
+        };
+        conversation.submit(Op::UserInput { items }).await?;
+
+        // Print the turn's events until it ends.
+        loop {
+            let event = tokio::select! {
+                event = conversation.next_event() => event?,
+                _ = tokio::signal::ctrl_c() => {
+                    conversation.submit(Op::Interrupt).await?;
+                    continue;
+                }
+            };
+            match &event.msg {
+                EventMsg::ExecApprovalRequest(request) => {
+                    let decision = ask_exec_approval(request, &mut input).await;
+                    conversation
+                        .submit(Op::ExecApproval {
+                            id: event.id.clone(),
+                            decision,
+                            allow_pattern: None,
+                        })
+                        .await?;
+                }
+                EventMsg::ApplyPatchApprovalRequest(request) => {
+                    let decision = ask_patch_approval(request, &mut input).await;
+                    conversation
+                        .submit(Op::PatchApproval {
+                            id: event.id.clone(),
+                            decision,
+                            accepted_files: None,
+                        })
+                        .await?;
+                }
+                EventMsg::TokenCount(turn_usage) => add_usage(&mut usage, turn_usage),
+                _ => {}
+            }
+            let turn_ended = matches!(
+                event.msg,
+                EventMsg::TaskComplete(_) | EventMsg::TurnAborted(_) | EventMsg::Error(_)
+            );
+            if let CodexStatus::Shutdown = event_processor.process_event(event) {
+                return Ok(usage);
+            }
+            if turn_ended {
+                break;
+            }
+        }
+    }
+
+    conversation.submit(Op::Shutdown).await?;
+    while let Ok(event) = conversation.next_event().await {
+        if matches!(event.msg, EventMsg::ShutdownComplete) {
+            break;
+        }
+    }
+    Ok(usage)
+}
+
+fn add_usage(total: &mut TokenUsage, usage: &TokenUsage) {
+    total.input_tokens += usage.input_tokens;
+    total.cached_input_tokens = match (total.cached_input_tokens, usage.cached_input_tokens) {
+        (None, None) => None,
+        (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+    };
+    total.output_tokens += usage.output_tokens;
+    total.reasoning_output_tokens =
+        match (total.reasoning_output_tokens, usage.reasoning_output_tokens) {
+            (None, None) => None,
+            (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+        };
+    total.total_tokens += usage.total_tokens;
+}
+
+async fn ask_exec_approval(
+    request: &ExecApprovalRequestEvent,
+    input: &mut LineInput,
+) -> ReviewDecision {
+    println!("\ncodex wants to run: {}", escape_command(&request.command));
+    println!("in {}", request.cwd.display());
+    if let Some(reason) = &request.reason {
+        println!("reason: {reason}");
+    }
+    if let Some(rule) = &request.matched_rule {
+        println!("required by {rule}");
+    }
+    loop {
+        let prompt = "Allow command? [y]es, [a]lways this session, [n]o, [c]ancel turn: ";
+        let Some(answer) = input.read_answer(prompt).await else {
+            return ReviewDecision::Abort;
+        };
+        match parse_decision(&answer, true) {
+            Some(decision) => return decision,
+            None => println!("Please answer y, a, n or c."),
+        }
+    }
+}
+
+async fn ask_patch_approval(
+    request: &ApplyPatchApprovalRequestEvent,
+    input: &mut LineInput,
+) -> ReviewDecision {
+    println!("\ncodex wants to change:");
+    let mut paths: Vec<&PathBuf> = request.changes.keys().collect();
+    paths.sort();
+    for path in paths {
+        match &request.changes[path] {
+            FileChange::Add { .. } => println!("A {}", path.display()),
+            FileChange::Delete => println!("D {}", path.display()),
+            FileChange::Update {
+                unified_diff,
+                move_path,
+            } => {
+                match move_path {
+                    Some(dest) => println!("M {} -> {}", path.display(), dest.display()),
+                    None => println!("M {}", path.display()),
+                }
+                println!("{}", unified_diff.trim_end());
+            }
+        }
+    }
+    if let Some(reason) = &request.reason {
+        println!("reason: {reason}");
+    }
+    if let Some(root) = &request.grant_root {
+        println!(
+            "This grants write access to {} for the rest of the session.",
+            root.display()
+        );
+    }
+    loop {
+        let prompt = "Apply changes? [y]es, [n]o, [c]ancel turn: ";
+        let Some(answer) = input.read_answer(prompt).await else {
+            return ReviewDecision::Abort;
+        };
+        match parse_decision(&answer, false) {
+            Some(decision) => return decision,
+            None => println!("Please answer y, n or c."),
+        }
+    }
+}
+
+/// The decision for an answer at an approval prompt; `allow_always` offers
+/// approving for the session.
+fn parse_decision(answer: &str, allow_always: bool) -> Option<ReviewDecision> {
+    match answer.trim().to_ascii_lowercase().as_str() {
+        "y" | "yes" => Some(ReviewDecision::Approved),
+        "a" | "always" if allow_always => Some(ReviewDecision::ApprovedForSession),
+        "n" | "no" => Some(ReviewDecision::Denied),
+        "c" | "cancel" => Some(ReviewDecision::Abort),
+        _ => None,
+    }
+}
+
+/// Lines typed by the user, read on a thread of their own so that a pending
+/// read does not keep the process alive at exit. The thread only reads when
+/// asked to, so that nothing typed ahead is consumed by the wrong prompt.
+struct LineInput {
+    /// The prompt to read the next line at, and whether to add the line to
+    /// the history.
+    prompts: std_mpsc::Sender<(String, bool)>,
+    lines: mpsc::UnboundedReceiver<Option<String>>,
+}
+
+impl LineInput {
+    fn spawn(config: &Config) -> Self {
+        let (prompts, prompt_rx) = std_mpsc::channel::<(String, bool)>();
+        let (tx, lines) = mpsc::unbounded_channel();
+        let helper = PlainHelper::new(config);
+        let history_path = (config.history.persistence == HistoryPersistence::SaveAll
+            && !config.encryption.enabled)
+            .then(|| config.codex_home.join(HISTORY_FILENAME));
+        std::thread::spawn(move || {
+            let mut reader = LineReader::new(helper, history_path);
+            while let Ok((prompt, remember)) = prompt_rx.recv() {
+                let line = reader.read_line(&prompt, remember);
+                let eof = matches!(line, ReadResult::Eof);
+                if tx.send(line.into_line()).is_err() || eof {
+                    break;
+                }
+            }
+        });
+        Self { prompts, lines }
+    }
+
+    /// The next line, or `None` at the end of input or on Ctrl-C.
+    async fn read_line(&mut self, prompt: &str, remember: bool) -> Option<String> {
+        self.prompts.send((prompt.to_string(), remember)).ok()?;
+        self.lines.recv().await.flatten()
+    }
+
+    /// The answer at an approval prompt, which is not kept in the history.
+    async fn read_answer(&mut self, prompt: &str) -> Option<String> {
+        self.read_line(prompt, false).await
+    }
+
+    /// The next message: lines ending in `\` are joined with the next one.
+    async fn read_message(&mut self) -> Option<String> {
+        let mut lines: Vec<String> = Vec::new();
+        loop {
+            let prompt = if lines.is_empty() { "> " } else { ". " };
+            let Some(line) = self.read_line(prompt, true).await else {
+                return (!lines.is_empty()).then(|| join_continued_lines(&lines));
+            };
+            let continued = line.ends_with('\\');
+            lines.push(line);
+            if !continued {
+                return Some(join_continued_lines(&lines));
+            }
+        }
+    }
+}
+
+enum ReadResult {
+    Line(String),
+    Interrupted,
+    Eof,
+}
+
+impl ReadResult {
+    fn into_line(self) -> Option<String> {
+        match self {
+            ReadResult::Line(line) => Some(line),
+            ReadResult::Interrupted | ReadResult::Eof => None,
+        }
+    }
+}
+
+/// File in `CODEX_HOME` that lines typed at the prompt are saved to, unless
+/// `[history] persistence = "none"` or history is encrypted.
+const HISTORY_FILENAME: &str = "plain_history";
+
+/// Reads with line editing, history and completion when stdin is a
+/// terminal, and plain lines from stdin otherwise.
+enum LineReader {
+    Editor {
+        editor: Box<Editor<PlainHelper, FileHistory>>,
+        history_path: Option<PathBuf>,
+    },
+    Stdin(std::io::Lines<std::io::StdinLock<'static>>),
+}
+
+impl LineReader {
+    fn new(helper: PlainHelper, history_path: Option<PathBuf>) -> Self {
+        if std::io::stdin().is_terminal()
+            && let Ok(mut editor) = Editor::<PlainHelper, FileHistory>::new()
+        {
+            editor.set_helper(Some(helper));
+            if let Some(path) = &history_path
+                && path.exists()
+                && let Err(err) = editor.load_history(path)
+            {
+                tracing::warn!("failed to load {}: {err}", path.display());
+            }
+            return LineReader::Editor {
+                editor: Box::new(editor),
+                history_path,
+            };
+        }
+        LineReader::Stdin(std::io::stdin().lines())
+    }
+
+    fn read_line(&mut self, prompt: &str, remember: bool) -> ReadResult {
+        match self {
+            LineReader::Editor {
+                editor,
+                history_path,
+            } => match editor.readline(prompt) {
+                Ok(line) => {
+                    if remember
+                        && !line.trim().is_empty()
+                        && editor.add_history_entry(line.as_str()).is_ok()
+                    {
+                        save_history(editor.history_mut(), history_path.as_deref());
+                    }
+                    ReadResult::Line(line)
+                }
+                // Ctrl-C is read as a key while editing rather than raised
+                // as a signal.
+                Err(ReadlineError::Interrupted) => ReadResult::Interrupted,
+                Err(_) => ReadResult::Eof,
+            },
+            LineReader::Stdin(lines) => {
+                print!("{prompt}");
+                let _ = std::io::stdout().flush();
+                match lines.next() {
+                    Some(Ok(line)) => ReadResult::Line(line),
+                    _ => ReadResult::Eof,
+                }
+            }
+        }
+    }
+}
+
+/// Appends the lines added since the last save to the history file.
+fn save_history(history: &mut FileHistory, path: Option<&std::path::Path>) {
+    let Some(path) = path else {
+        return;
+    };
+    if let Err(err) = history.append(path) {
+        tracing::warn!("failed to save {}: {err}", path.display());
+    }
+}
+
+/// Joins the lines of a message, dropping the `\` that continued each one.
+fn join_continued_lines(lines: &[String]) -> String {
+    lines
+        .iter()
+        .map(|line| line.strip_suffix('\\').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_approval_answers() {
+        assert_eq!(parse_decision(" Y ", true), Some(ReviewDecision::Approved));
+        assert_eq!(
+            parse_decision("always", true),
+            Some(ReviewDecision::ApprovedForSession)
+        );
+        // Patches cannot be approved for the session.
+        assert_eq!(parse_decision("a", false), None);
+        assert_eq!(parse_decision("no", false), Some(ReviewDecision::Denied));
+        assert_eq!(parse_decision("c", false), Some(ReviewDecision::Abort));
+        assert_eq!(parse_decision("", true), None);
+    }
+
+    #[test]
+    fn joins_continued_lines() {
+        let lines = vec![
+            "fix the tests\\".to_string(),
+            "then run them".to_string(),
+        ];
+        assert_eq!(join_continued_lines(&lines), "fix the tests\nthen run them");
+    }
+}
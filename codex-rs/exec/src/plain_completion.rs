@@ -0,0 +1,164 @@
+//! Tab completion at the `codex --plain` prompt: slash commands at the start
+//! of a message, and files in the working directory for an `@` word, found
+//! with the same fuzzy search as the TUI's `@` popup.
+
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+
+use codex_core::config::Config;
+use codex_file_search::ScoringOptions;
+use codex_file_search::WalkOptions;
+use rustyline::Context;
+use rustyline::Helper;
+use rustyline::completion::Completer;
+use rustyline::completion::Pair;
+use rustyline::highlight::Highlighter;
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use tokio_util::sync::CancellationToken;
+
+/// Commands understood at the message prompt.
+pub(crate) const SLASH_COMMANDS: &[&str] = &["/exit", "/quit"];
+
+const MAX_FILE_COMPLETIONS: NonZeroUsize = NonZeroUsize::new(20).unwrap();
+const NUM_FILE_SEARCH_THREADS: NonZeroUsize = NonZeroUsize::new(2).unwrap();
+
+pub(crate) struct PlainHelper {
+    cwd: PathBuf,
+    walk_options: WalkOptions,
+    index_path: Option<PathBuf>,
+}
+
+impl PlainHelper {
+    pub(crate) fn new(config: &Config) -> Self {
+        Self {
+            cwd: config.cwd.clone(),
+            walk_options: WalkOptions {
+                exclude: [
+                    config.project_ignore.as_slice(),
+                    &config.file_search.exclude,
+                ]
+                .concat(),
+                include_hidden: config.file_search.include_hidden,
+                include_ignored: config.file_search.include_ignored,
+            },
+            index_path: config.tui.file_index.then(|| {
+                codex_file_search::workspace_file_path(
+                    &config.codex_home.join("file-index"),
+                    &config.cwd,
+                )
+            }),
+        }
+    }
+
+    fn complete_file(&self, query: &str) -> Vec<Pair> {
+        if query.is_empty() {
+            return Vec::new();
+        }
+        let results = codex_file_search::run(
+            query,
+            MAX_FILE_COMPLETIONS,
+            &self.cwd,
+            self.walk_options.clone(),
+            NUM_FILE_SEARCH_THREADS,
+            CancellationToken::new(),
+            false,
+            ScoringOptions::default(),
+            self.index_path.as_deref(),
+        );
+        let Ok(results) = results else {
+            return Vec::new();
+        };
+        results
+            .matches
+            .into_iter()
+            .map(|file_match| Pair {
+                display: file_match.path.clone(),
+                replacement: file_match.path,
+            })
+            .collect()
+    }
+}
+
+/// The word being completed, without its leading `@` for files.
+#[derive(Debug, PartialEq, Eq)]
+enum CompletionTarget<'a> {
+    SlashCommand(&'a str),
+    File(&'a str),
+}
+
+/// The start of the word that ends at `pos` in `line`, and what it names.
+fn completion_target(line: &str, pos: usize) -> Option<(usize, CompletionTarget<'_>)> {
+    let before = line.get(..pos)?;
+    let start = before
+        .char_indices()
+        .rfind(|(_, c)| c.is_whitespace())
+        .map_or(0, |(idx, c)| idx + c.len_utf8());
+    let word = &before[start..];
+    if start == 0 && word.starts_with('/') {
+        Some((start, CompletionTarget::SlashCommand(word)))
+    } else {
+        word.strip_prefix('@')
+            .map(|query| (start, CompletionTarget::File(query)))
+    }
+}
+
+impl Completer for PlainHelper {
+    type Candidate = Pair;
+
+    fn complete(
+        &self,
+        line: &str,
+        pos: usize,
+        _ctx: &Context<'_>,
+    ) -> rustyline::Result<(usize, Vec<Pair>)> {
+        let candidates = match completion_target(line, pos) {
+            Some((start, CompletionTarget::SlashCommand(prefix))) => (
+                start,
+                SLASH_COMMANDS
+                    .iter()
+                    .filter(|command| command.starts_with(prefix))
+                    .map(|command| Pair {
+                        display: command.to_string(),
+                        replacement: command.to_string(),
+                    })
+                    .collect(),
+            ),
+            // Like the TUI, the `@` word is replaced by the path.
+            Some((start, CompletionTarget::File(query))) => (start, self.complete_file(query)),
+            None => (pos, Vec::new()),
+        };
+        Ok(candidates)
+    }
+}
+
+impl Hinter for PlainHelper {
+    type Hint = String;
+}
+
+impl Highlighter for PlainHelper {}
+
+impl Validator for PlainHelper {}
+
+impl Helper for PlainHelper {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn finds_the_word_under_the_cursor() {
+        assert_eq!(
+            completion_target("/qu", 3),
+            Some((0, CompletionTarget::SlashCommand("/qu")))
+        );
+        assert_eq!(
+            completion_target("look at @src/ma please", 15),
+            Some((8, CompletionTarget::File("src/ma")))
+        );
+        // Slash commands only start a message.
+        assert_eq!(completion_target("run /usr/bin", 12), None);
+        assert_eq!(completion_target("plain words", 5), None);
+    }
+}
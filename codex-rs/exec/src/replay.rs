@@ -0,0 +1,179 @@
+//! `codex --replay <FILE>`: prints a session recorded in a rollout, such as
+//! one under `$CODEX_HOME/sessions`, as plain lines, to look back at a
+//! session or attach to a bug report. Nothing is sent to the model.
+
+use std::io::IsTerminal;
+use std::path::Path;
+
+use codex_core::SavedSession;
+use codex_core::config::Config;
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::LocalShellAction;
+use codex_protocol::models::ReasoningItemReasoningSummary;
+use codex_protocol::models::ResponseItem;
+use owo_colors::OwoColorize;
+use owo_colors::Style;
+
+use crate::event_processor_with_human_output::escape_command;
+
+/// Lines of tool output shown for each call, as in `codex exec`.
+const MAX_OUTPUT_LINES: usize = 20;
+
+/// Prints the session recorded in the rollout at `path`.
+pub fn run_replay(path: &Path, config: &Config) -> anyhow::Result<()> {
+    let saved = codex_core::read_rollout(path)
+        .map_err(|err| anyhow::anyhow!("failed to read {}: {err}", path.display()))?;
+    let with_ansi =
+        std::io::stdout().is_terminal() && !std::env::var("TERM").is_ok_and(|term| term == "dumb");
+    let replay = Replay::new(with_ansi, !config.hide_agent_reasoning);
+    replay.print(&saved);
+    Ok(())
+}
+
+struct Replay {
+    bold: Style,
+    italic: Style,
+    dimmed: Style,
+    magenta: Style,
+    cyan: Style,
+    red: Style,
+    show_agent_reasoning: bool,
+}
+
+impl Replay {
+    fn new(with_ansi: bool, show_agent_reasoning: bool) -> Self {
+        let style = |style: Style| if with_ansi { style } else { Style::new() };
+        Self {
+            bold: style(Style::new().bold()),
+            italic: style(Style::new().italic()),
+            dimmed: style(Style::new().dimmed()),
+            magenta: style(Style::new().magenta()),
+            cyan: style(Style::new().cyan()),
+            red: style(Style::new().red()),
+            show_agent_reasoning,
+        }
+    }
+
+    fn print(&self, saved: &SavedSession) {
+        let meta = &saved.session;
+        println!(
+            "{} {}",
+            "session".style(self.bold),
+            meta.id.to_string().style(self.dimmed)
+        );
+        println!("{} {}", "started:".style(self.bold), meta.timestamp);
+        if let Some(cwd) = &meta.cwd {
+            println!("{} {}", "workdir:".style(self.bold), cwd.display());
+        }
+        println!("--------");
+        for item in &saved.items {
+            self.print_item(item);
+        }
+    }
+
+    fn print_item(&self, item: &ResponseItem) {
+        match item {
+            ResponseItem::Message { role, content, .. } => {
+                let text = message_text(content);
+                // Instructions and the environment context are sent as user
+                // messages wrapped in tags; they are not something the user
+                // typed.
+                if role == "user" && text.trim_start().starts_with('<') {
+                    return;
+                }
+                let header = if role == "assistant" { "codex" } else { role };
+                println!("\n{}", header.style(self.italic).style(self.magenta));
+                println!("{text}");
+            }
+            ResponseItem::Reasoning { summary, .. } => {
+                if !self.show_agent_reasoning || summary.is_empty() {
+                    return;
+                }
+                println!("\n{}", "thinking".style(self.italic).style(self.magenta));
+                for ReasoningItemReasoningSummary::SummaryText { text } in summary {
+                    println!("{}", text.style(self.italic));
+                }
+            }
+            ResponseItem::LocalShellCall {
+                action: LocalShellAction::Exec(action),
+                ..
+            } => {
+                self.print_call("exec", &escape_command(&action.command));
+            }
+            ResponseItem::FunctionCall {
+                name, arguments, ..
+            } => match shell_command(name, arguments) {
+                Some(command) => self.print_call("exec", &escape_command(&command)),
+                None => self.print_call("tool", &format!("{name}({arguments})")),
+            },
+            ResponseItem::CustomToolCall { name, input, .. } => {
+                self.print_call("tool", name);
+                self.print_output(input, true);
+            }
+            ResponseItem::FunctionCallOutput { output, .. } => {
+                self.print_output(&output.content, output.success != Some(false));
+            }
+            ResponseItem::CustomToolCallOutput { output, .. } => {
+                self.print_output(output, true);
+            }
+            ResponseItem::Other => {}
+        }
+    }
+
+    fn print_call(&self, kind: &str, description: &str) {
+        println!(
+            "\n{} {}",
+            kind.style(self.magenta),
+            description.style(self.bold)
+        );
+    }
+
+    fn print_output(&self, output: &str, success: bool) {
+        let style = if success { self.dimmed } else { self.red };
+        let mut lines = output.lines();
+        for line in lines.by_ref().take(MAX_OUTPUT_LINES) {
+            println!("{}", line.style(style));
+        }
+        let remaining = lines.count();
+        if remaining > 0 {
+            println!("{}", format!("... {remaining} more lines").style(self.cyan));
+        }
+    }
+}
+
+fn message_text(content: &[ContentItem]) -> String {
+    content
+        .iter()
+        .map(|item| match item {
+            ContentItem::InputText { text } | ContentItem::OutputText { text } => text.as_str(),
+            ContentItem::InputImage { .. } => "[image]",
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The command of a `shell` tool call, whose arguments are JSON such as
+/// `{"command": ["ls", "-l"]}`.
+fn shell_command(name: &str, arguments: &str) -> Option<Vec<String>> {
+    if name != "shell" {
+        return None;
+    }
+    let arguments: serde_json::Value = serde_json::from_str(arguments).ok()?;
+    serde_json::from_value(arguments.get("command")?.clone()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn reads_shell_commands_from_tool_calls() {
+        assert_eq!(
+            shell_command("shell", r#"{"command":["ls","-l"],"timeout_ms":1000}"#),
+            Some(vec!["ls".to_string(), "-l".to_string()])
+        );
+        assert_eq!(shell_command("update_plan", r#"{"command":["ls"]}"#), None);
+        assert_eq!(shell_command("shell", "not json"), None);
+    }
+}
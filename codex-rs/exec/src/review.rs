@@ -0,0 +1,283 @@
+//! `codex exec review --pr <number>`: review a GitHub pull request in a
+//! read-only session and report the comments as JSON, optionally posting
+//! them back to the pull request. GitHub is reached through the `gh` CLI,
+//! which takes care of the host and credentials of the repository.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::path::Path;
+use std::process::Stdio;
+
+use serde::Deserialize;
+use serde::Serialize;
+use tokio::io::AsyncWriteExt;
+
+/// Instructions that precede the pull request in the prompt.
+const REVIEW_PROMPT: &str = include_str!("review_prompt.md");
+
+/// Diffs longer than this are cut to keep the prompt within the context
+/// window; the model is told so.
+const MAX_DIFF_BYTES: usize = 200 * 1024;
+
+pub(crate) struct PullRequest {
+    pub number: u64,
+    title: String,
+    body: String,
+    head_sha: String,
+    diff: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PullRequestView {
+    title: String,
+    body: String,
+    head_ref_oid: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ReviewComment {
+    pub file: String,
+    pub line: u64,
+    pub severity: Severity,
+    pub suggestion: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub(crate) enum Severity {
+    Error,
+    Warning,
+    Info,
+}
+
+impl Severity {
+    fn as_str(self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ReviewReply {
+    comments: Vec<ReviewComment>,
+}
+
+/// Fetch the title, description, head commit and diff of pull request
+/// `number` of the repository in `cwd`.
+pub(crate) async fn fetch_pull_request(number: u64, cwd: &Path) -> anyhow::Result<PullRequest> {
+    let number_arg = number.to_string();
+    let view = gh(
+        &["pr", "view", &number_arg, "--json", "title,body,headRefOid"],
+        None,
+        cwd,
+    )
+    .await?;
+    let view: PullRequestView = serde_json::from_str(&view)?;
+    let diff = gh(&["pr", "diff", &number_arg], None, cwd).await?;
+    Ok(PullRequest {
+        number,
+        title: view.title,
+        body: view.body,
+        head_sha: view.head_ref_oid,
+        diff,
+    })
+}
+
+/// The prompt asking the model to review `pr`.
+pub(crate) fn review_prompt(pr: &PullRequest) -> String {
+    let mut diff = pr.diff.as_str();
+    let mut note = "";
+    if diff.len() > MAX_DIFF_BYTES {
+        let mut end = MAX_DIFF_BYTES;
+        while !diff.is_char_boundary(end) {
+            end -= 1;
+        }
+        diff = &diff[..end];
+        note = "The diff is too long to show in full; it is cut short below.\n\n";
+    }
+    format!(
+        "{REVIEW_PROMPT}Pull request #{}: {}\n\n{}\n\n{note}```diff\n{diff}\n```\n",
+        pr.number,
+        pr.title,
+        pr.body.trim()
+    )
+}
+
+/// The comments in the model's reply, which may be wrapped in a Markdown
+/// code block despite the instructions.
+pub(crate) fn parse_review(reply: &str) -> anyhow::Result<Vec<ReviewComment>> {
+    let reply = reply.trim();
+    let json = match (reply.find('{'), reply.rfind('}')) {
+        (Some(start), Some(end)) if start < end => &reply[start..=end],
+        _ => anyhow::bail!("the reply does not contain a JSON object"),
+    };
+    let reply: ReviewReply = serde_json::from_str(json)?;
+    Ok(reply.comments)
+}
+
+/// Post `comments` to `pr` as a review. Comments on lines the diff does not
+/// show cannot be attached to the code, so they go in the review body.
+pub(crate) async fn post_review(
+    pr: &PullRequest,
+    comments: &[ReviewComment],
+    cwd: &Path,
+) -> anyhow::Result<()> {
+    let commentable = commentable_lines(&pr.diff);
+    let (inline, general): (Vec<_>, Vec<_>) = comments.iter().partition(|comment| {
+        commentable
+            .get(&comment.file)
+            .is_some_and(|lines| lines.contains(&comment.line))
+    });
+
+    let mut body = format!("Codex found {} issue(s).", comments.len());
+    for comment in general {
+        body.push_str(&format!(
+            "\n\n`{}:{}`: {}",
+            comment.file,
+            comment.line,
+            comment_body(comment)
+        ));
+    }
+    let request = serde_json::json!({
+        "commit_id": pr.head_sha,
+        "event": "COMMENT",
+        "body": body,
+        "comments": inline
+            .iter()
+            .map(|comment| serde_json::json!({
+                "path": comment.file,
+                "line": comment.line,
+                "side": "RIGHT",
+                "body": comment_body(comment),
+            }))
+            .collect::<Vec<_>>(),
+    });
+    let endpoint = format!("repos/{{owner}}/{{repo}}/pulls/{}/reviews", pr.number);
+    gh(
+        &["api", "--method", "POST", &endpoint, "--input", "-"],
+        Some(request.to_string()),
+        cwd,
+    )
+    .await?;
+    Ok(())
+}
+
+fn comment_body(comment: &ReviewComment) -> String {
+    format!("**{}**: {}", comment.severity.as_str(), comment.suggestion)
+}
+
+/// Lines of the new version of each file that `diff` adds or shows as
+/// context, which are the lines a review comment can be attached to.
+fn commentable_lines(diff: &str) -> HashMap<String, HashSet<u64>> {
+    let mut lines: HashMap<String, HashSet<u64>> = HashMap::new();
+    let mut file: Option<String> = None;
+    let mut in_hunk = false;
+    let mut new_line = 0;
+    for line in diff.lines() {
+        if line.starts_with("diff --git ") {
+            file = None;
+            in_hunk = false;
+        } else if let Some(header) = line.strip_prefix("@@ ") {
+            // @@ -old_start,old_len +new_start,new_len @@
+            new_line = header
+                .split_whitespace()
+                .find_map(|range| range.strip_prefix('+'))
+                .and_then(|range| range.split(',').next())
+                .and_then(|start| start.parse().ok())
+                .unwrap_or(0);
+            in_hunk = true;
+        } else if !in_hunk && let Some(path) = line.strip_prefix("+++ ") {
+            file = path.strip_prefix("b/").map(str::to_string);
+        } else if in_hunk && (line.starts_with('+') || line.starts_with(' ')) {
+            if let Some(file) = &file {
+                lines.entry(file.clone()).or_default().insert(new_line);
+            }
+            new_line += 1;
+        }
+    }
+    lines
+}
+
+/// Run `gh` with `args` in `cwd`, feeding it `input`, and return its stdout.
+async fn gh(args: &[&str], input: Option<String>, cwd: &Path) -> anyhow::Result<String> {
+    let mut child = tokio::process::Command::new("gh")
+        .args(args)
+        .current_dir(cwd)
+        .stdin(if input.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("could not run the GitHub CLI `gh`: {e}"))?;
+    if let (Some(input), Some(mut stdin)) = (input, child.stdin.take()) {
+        stdin.write_all(input.as_bytes()).await?;
+    }
+    let output = child.wait_with_output().await?;
+    if !output.status.success() {
+        anyhow::bail!(
+            "`gh {}` failed ({}): {}",
+            args.join(" "),
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_comments_from_a_fenced_reply() {
+        let reply = r#"```json
+{"comments": [{"file": "src/lib.rs", "line": 7, "severity": "warning", "suggestion": "Handle the error."}]}
+```"#;
+
+        assert_eq!(
+            parse_review(reply).unwrap(),
+            vec![ReviewComment {
+                file: "src/lib.rs".to_string(),
+                line: 7,
+                severity: Severity::Warning,
+                suggestion: "Handle the error.".to_string(),
+            }]
+        );
+        assert!(parse_review("Looks good to me!").is_err());
+    }
+
+    #[test]
+    fn commentable_lines_are_added_and_context_lines() {
+        let diff = "\
+diff --git a/src/lib.rs b/src/lib.rs
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -10,3 +10,4 @@ fn main() {
+ let a = 1;
+-let b = 2;
++let b = 3;
+++++ not a header
+ let c = 4;
+diff --git a/old.rs b/old.rs
+--- a/old.rs
++++ /dev/null
+@@ -1 +0,0 @@
+-gone
+";
+
+        let lines = commentable_lines(diff);
+        assert_eq!(lines.len(), 1);
+        let mut src_lines: Vec<u64> = lines["src/lib.rs"].iter().copied().collect();
+        src_lines.sort_unstable();
+        assert_eq!(src_lines, vec![10, 11, 12, 13]);
+    }
+}
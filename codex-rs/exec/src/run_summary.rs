@@ -0,0 +1,284 @@
+//! The summary of a `codex exec` run that ends the `--output-format json`
+//! stream and is sent to `--notify-webhook`.
+
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use codex_common::exit_code::ExitCode;
+use codex_core::protocol::DegradedModeEvent;
+use codex_core::protocol::DegradedModeKind;
+use codex_core::protocol::ErrorEvent;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::ExecCommandEndEvent;
+use codex_core::protocol::FileChange;
+use codex_core::protocol::PatchApplyBeginEvent;
+use codex_core::protocol::PatchApplyEndEvent;
+use codex_core::protocol::TaskCompleteEvent;
+use codex_core::protocol::TruncationReason;
+use serde::Serialize;
+
+#[derive(Debug, Serialize)]
+pub(crate) struct RunReport {
+    /// `"task-complete"` or `"task-failed"`.
+    #[serde(rename = "type")]
+    kind: &'static str,
+    session_id: String,
+    /// The exit code `codex exec` is about to exit with.
+    exit_code: i32,
+    last_agent_message: Option<String>,
+    /// The first error reported during the run, or the first failed
+    /// `--script` check.
+    error: Option<String>,
+    token_usage: TokenTotals,
+    /// Files changed by successfully applied patches, sorted.
+    changed_files: Vec<PathBuf>,
+    /// The `--max-turns` or `--max-wall-time` limit that stopped the run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    truncated: Option<TruncationReason>,
+    /// Problems the run recovered from; see [`ExitCode::SuccessWithWarnings`].
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    warnings: Vec<String>,
+    /// The outcome of each step of a `--script` run.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    steps: Vec<StepReport>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct StepReport {
+    pub name: String,
+    pub status: StepStatus,
+    /// Why the step failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub(crate) enum StepStatus {
+    Passed,
+    Failed,
+    /// Not run because an earlier step failed.
+    Skipped,
+}
+
+/// Token usage summed over every model request in the run.
+#[derive(Debug, Default, Serialize)]
+struct TokenTotals {
+    input_tokens: u64,
+    cached_input_tokens: u64,
+    output_tokens: u64,
+    reasoning_output_tokens: u64,
+    total_tokens: u64,
+}
+
+/// Collects what the [`RunReport`] reports from the events of a run, and
+/// classifies a run without errors by what went wrong along the way.
+#[derive(Default)]
+pub(crate) struct RunSummary {
+    /// `--fail-on-patch-error`: a patch that fails to apply fails the run
+    /// instead of being a warning.
+    fail_on_patch_error: bool,
+    last_agent_message: Option<String>,
+    first_error: Option<String>,
+    token_usage: TokenTotals,
+    /// Files of patches that have begun but not yet finished, by call id.
+    pending_patches: HashMap<String, Vec<PathBuf>>,
+    changed_files: BTreeSet<PathBuf>,
+    truncated: Option<TruncationReason>,
+    failed_patches: usize,
+    sandbox_denials: usize,
+    /// Messages of `DegradedMode` events that affect the run.
+    degraded: Vec<String>,
+    steps: Vec<StepReport>,
+}
+
+impl RunSummary {
+    pub(crate) fn new(fail_on_patch_error: bool) -> Self {
+        Self {
+            fail_on_patch_error,
+            ..Self::default()
+        }
+    }
+
+    pub(crate) fn last_agent_message(&self) -> Option<&str> {
+        self.last_agent_message.as_deref()
+    }
+
+    pub(crate) fn observe(&mut self, msg: &EventMsg) {
+        match msg {
+            EventMsg::TaskComplete(TaskCompleteEvent {
+                last_agent_message,
+                truncated,
+                ..
+            }) => {
+                if last_agent_message.is_some() {
+                    self.last_agent_message = last_agent_message.clone();
+                }
+                if truncated.is_some() {
+                    self.truncated = *truncated;
+                }
+            }
+            EventMsg::Error(ErrorEvent { message, .. }) => {
+                if self.first_error.is_none() {
+                    self.first_error = Some(message.clone());
+                }
+            }
+            EventMsg::TokenCount(usage) => {
+                let totals = &mut self.token_usage;
+                totals.input_tokens += usage.input_tokens;
+                totals.cached_input_tokens += usage.cached_input();
+                totals.output_tokens += usage.output_tokens;
+                totals.reasoning_output_tokens += usage.reasoning_output_tokens.unwrap_or(0);
+                totals.total_tokens += usage.total_tokens;
+            }
+            EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
+                call_id, changes, ..
+            }) => {
+                let files = changes
+                    .iter()
+                    .map(|(path, change)| match change {
+                        FileChange::Update {
+                            move_path: Some(dest),
+                            ..
+                        } => dest.clone(),
+                        _ => path.clone(),
+                    })
+                    .collect();
+                self.pending_patches.insert(call_id.clone(), files);
+            }
+            EventMsg::PatchApplyEnd(PatchApplyEndEvent {
+                call_id, success, ..
+            }) => {
+                if let Some(files) = self.pending_patches.remove(call_id)
+                    && *success
+                {
+                    self.changed_files.extend(files);
+                }
+                if !*success {
+                    self.failed_patches += 1;
+                }
+            }
+            EventMsg::ExecCommandEnd(ExecCommandEndEvent {
+                sandbox_denied: true,
+                ..
+            }) => {
+                self.sandbox_denials += 1;
+            }
+            EventMsg::DegradedMode(DegradedModeEvent { kind, message }) => {
+                // An expiring sign-in does not affect this run.
+                if *kind != DegradedModeKind::AuthExpiring {
+                    self.degraded.push(message.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    pub(crate) fn record_step(&mut self, step: StepReport) {
+        if self.first_error.is_none() {
+            self.first_error = step.error.clone();
+        }
+        self.steps.push(step);
+    }
+
+    /// The exit code of a run that reported no errors: a run limit, then a
+    /// failed patch with `--fail-on-patch-error`, then a sandbox denial, then
+    /// any warning makes it non-zero.
+    pub(crate) fn exit_code(&self) -> ExitCode {
+        if self.truncated.is_some() {
+            ExitCode::BudgetExceeded
+        } else if self.fail_on_patch_error && self.failed_patches > 0 {
+            ExitCode::PatchFailed
+        } else if self.sandbox_denials > 0 {
+            ExitCode::SandboxDenied
+        } else if !self.warnings().is_empty() {
+            ExitCode::SuccessWithWarnings
+        } else {
+            ExitCode::Success
+        }
+    }
+
+    fn warnings(&self) -> Vec<String> {
+        let mut warnings = Vec::new();
+        if !self.fail_on_patch_error && self.failed_patches > 0 {
+            warnings.push(format!("{} patch(es) failed to apply", self.failed_patches));
+        }
+        warnings.extend(self.degraded.iter().cloned());
+        warnings
+    }
+
+    pub(crate) fn into_report(self, session_id: String, exit_code: ExitCode) -> RunReport {
+        let warnings = self.warnings();
+        RunReport {
+            kind: match exit_code {
+                ExitCode::Success | ExitCode::SuccessWithWarnings => "task-complete",
+                _ => "task-failed",
+            },
+            session_id,
+            exit_code: exit_code.code(),
+            last_agent_message: self.last_agent_message,
+            error: self.first_error,
+            token_usage: self.token_usage,
+            changed_files: self.changed_files.into_iter().collect(),
+            truncated: self.truncated,
+            warnings,
+            steps: self.steps,
+        }
+    }
+}
+
+/// Why a task that ended early with `reason` stopped.
+pub(crate) fn truncation_message(reason: TruncationReason) -> &'static str {
+    match reason {
+        TruncationReason::MaxTurns => "stopped after reaching the --max-turns limit",
+        TruncationReason::MaxWallTime => "stopped after reaching the --max-wall-time limit",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn failed_patch() -> EventMsg {
+        EventMsg::PatchApplyEnd(PatchApplyEndEvent {
+            call_id: "call-1".to_string(),
+            stdout: String::new(),
+            stderr: "hunk did not apply".to_string(),
+            success: false,
+        })
+    }
+
+    #[test]
+    fn failed_patch_is_a_warning_unless_it_fails_the_run() {
+        let mut summary = RunSummary::new(false);
+        summary.observe(&failed_patch());
+        assert_eq!(summary.exit_code(), ExitCode::SuccessWithWarnings);
+        assert_eq!(
+            summary.warnings(),
+            vec!["1 patch(es) failed to apply".to_string()]
+        );
+
+        let mut summary = RunSummary::new(true);
+        summary.observe(&failed_patch());
+        assert_eq!(summary.exit_code(), ExitCode::PatchFailed);
+        assert_eq!(summary.warnings(), Vec::<String>::new());
+    }
+
+    #[test]
+    fn expiring_sign_in_is_not_a_warning() {
+        let mut summary = RunSummary::new(false);
+        summary.observe(&EventMsg::DegradedMode(DegradedModeEvent {
+            kind: DegradedModeKind::AuthExpiring,
+            message: "Your sign-in expires in 3 hours.".to_string(),
+        }));
+        assert_eq!(summary.exit_code(), ExitCode::Success);
+
+        summary.observe(&EventMsg::DegradedMode(DegradedModeEvent {
+            kind: DegradedModeKind::SandboxUnavailable,
+            message: "Commands run without a sandbox.".to_string(),
+        }));
+        assert_eq!(summary.exit_code(), ExitCode::SuccessWithWarnings);
+    }
+}
@@ -0,0 +1,159 @@
+//! `codex exec --script`: run an ordered list of prompts in one conversation.
+//!
+//! ```yaml
+//! steps:
+//!   - name: add test
+//!     prompt: Add a failing test for the parser bug in src/parse.rs.
+//!     sandbox: workspace-write
+//!   - prompt: Fix the bug.
+//!     check: cargo test -p parser
+//! ```
+
+use std::path::Path;
+use std::process::Stdio;
+
+use codex_core::protocol::AskForApproval;
+use codex_protocol::config_types::SandboxMode;
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ScriptFile {
+    steps: Vec<ScriptStep>,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ScriptStep {
+    /// Shown in the per-step report instead of the step number.
+    #[serde(default)]
+    pub name: Option<String>,
+
+    pub prompt: String,
+
+    /// Sandbox for the commands of this step instead of the run's.
+    #[serde(default)]
+    pub sandbox: Option<SandboxMode>,
+
+    /// Approval policy for this step instead of `never`. `codex exec` cannot
+    /// ask, so every request for approval is denied.
+    #[serde(default)]
+    pub approval_policy: Option<AskForApproval>,
+
+    /// Shell command run in the working directory once the step's turn is
+    /// done. The step fails unless it exits with status 0.
+    #[serde(default)]
+    pub check: Option<String>,
+}
+
+impl ScriptStep {
+    /// The single step of a run without `--script`.
+    pub(crate) fn from_prompt(prompt: String) -> Self {
+        Self {
+            name: None,
+            prompt,
+            sandbox: None,
+            approval_policy: None,
+            check: None,
+        }
+    }
+
+    /// `name`, or "step N" for the step at `index`.
+    pub(crate) fn label(&self, index: usize) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("step {}", index + 1),
+        }
+    }
+}
+
+pub(crate) fn load_script(path: &Path) -> anyhow::Result<Vec<ScriptStep>> {
+    let contents = std::fs::read_to_string(path)?;
+    parse_script(&contents)
+}
+
+fn parse_script(contents: &str) -> anyhow::Result<Vec<ScriptStep>> {
+    let script: ScriptFile = serde_yaml::from_str(contents)?;
+    if script.steps.is_empty() {
+        anyhow::bail!("the script has no steps");
+    }
+    Ok(script.steps)
+}
+
+/// Run a step's `check` command in `cwd`. Its output is passed through to
+/// stderr so that stdout stays machine-readable with `--output-format json`.
+/// Returns why the check failed.
+pub(crate) async fn run_check(check: &str, cwd: &Path) -> Result<(), String> {
+    #[cfg(windows)]
+    let mut command = {
+        let mut command = tokio::process::Command::new("cmd");
+        command.arg("/C").arg(check);
+        command
+    };
+    #[cfg(not(windows))]
+    let mut command = {
+        let mut command = tokio::process::Command::new("sh");
+        command.arg("-c").arg(check);
+        command
+    };
+    let output = command
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .map_err(|e| format!("check `{check}` could not be started: {e}"))?;
+    eprint!("{}", String::from_utf8_lossy(&output.stdout));
+    eprint!("{}", String::from_utf8_lossy(&output.stderr));
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(format!("check `{check}` failed ({})", output.status))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #![allow(clippy::unwrap_used)]
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_steps_with_overrides() {
+        let steps = parse_script(
+            r#"
+steps:
+  - prompt: Explain the parser.
+    sandbox: read-only
+  - name: fix
+    prompt: Fix the parser bug.
+    approval_policy: untrusted
+    check: cargo test
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            steps,
+            vec![
+                ScriptStep {
+                    sandbox: Some(SandboxMode::ReadOnly),
+                    ..ScriptStep::from_prompt("Explain the parser.".to_string())
+                },
+                ScriptStep {
+                    name: Some("fix".to_string()),
+                    approval_policy: Some(AskForApproval::UnlessTrusted),
+                    check: Some("cargo test".to_string()),
+                    ..ScriptStep::from_prompt("Fix the parser bug.".to_string())
+                },
+            ]
+        );
+        assert_eq!(steps[0].label(0), "step 1");
+        assert_eq!(steps[1].label(1), "fix");
+    }
+
+    #[test]
+    fn rejects_unknown_fields_and_empty_scripts() {
+        assert!(parse_script("steps:\n  - prompt: hi\n    sandbx: read-only\n").is_err());
+        assert!(parse_script("steps: []\n").is_err());
+    }
+}
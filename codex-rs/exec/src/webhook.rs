@@ -0,0 +1,24 @@
+//! `--notify-webhook`: POST a summary of the run to a URL when `codex exec`
+//! finishes, so chat and CI systems can react without wrapping the binary.
+
+use std::time::Duration;
+
+use crate::run_summary::RunReport;
+
+/// How long to wait for the webhook before giving up.
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// POST `report` to `url` as JSON. A failed delivery is reported but does
+/// not change the outcome of the run.
+pub(crate) async fn send(url: &str, report: &RunReport) {
+    let result = reqwest::Client::new()
+        .post(url)
+        .timeout(WEBHOOK_TIMEOUT)
+        .json(report)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status);
+    if let Err(e) = result {
+        eprintln!("Failed to send webhook notification: {e}");
+    }
+}
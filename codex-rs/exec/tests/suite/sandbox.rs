@@ -1,4 +1,5 @@
 #![cfg(unix)]
+use codex_core::config_types::ExecLimits;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::spawn::StdioPolicy;
 use std::collections::HashMap;
@@ -16,8 +17,18 @@ async fn spawn_command_under_sandbox(
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
 ) -> std::io::Result<Child> {
+    use codex_core::seatbelt::SeatbeltExtensions;
     use codex_core::seatbelt::spawn_command_under_seatbelt;
-    spawn_command_under_seatbelt(command, sandbox_policy, cwd, stdio_policy, env).await
+    spawn_command_under_seatbelt(
+        command,
+        sandbox_policy,
+        &SeatbeltExtensions::default(),
+        cwd,
+        stdio_policy,
+        env,
+        &ExecLimits::default(),
+    )
+    .await
 }
 
 #[cfg(target_os = "linux")]
@@ -37,6 +48,8 @@ async fn spawn_command_under_sandbox(
         cwd,
         stdio_policy,
         env,
+        &ExecLimits::default(),
+        None,
     )
     .await
 }
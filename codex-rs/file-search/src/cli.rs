@@ -4,7 +4,8 @@ use std::path::PathBuf;
 use clap::ArgAction;
 use clap::Parser;
 
-/// Fuzzy matches filenames under a directory.
+/// Fuzzy matches filenames under a directory, or searches file contents with
+/// `--content`.
 #[derive(Parser)]
 #[command(version)]
 pub struct Cli {
@@ -37,6 +38,30 @@ pub struct Cli {
     #[arg(short, long, action = ArgAction::Append)]
     pub exclude: Vec<String>,
 
+    /// Include hidden files and directories.
+    #[arg(long, default_value = "false")]
+    pub hidden: bool,
+
+    /// Include files ignored by .gitignore, .ignore and git's excludes.
+    /// Files matched by .codexignore and --exclude are still left out.
+    #[arg(long, default_value = "false")]
+    pub no_ignore: bool,
+
+    /// Search file contents for lines matching the pattern (a regular
+    /// expression) instead of fuzzy matching file names.
+    #[arg(long, default_value = "false")]
+    pub content: bool,
+
+    /// Keep a persistent index of the tree in this file and search it instead
+    /// of walking large trees. The index is created once the tree has enough
+    /// files to benefit from it, and kept up to date on later searches.
+    #[arg(long, value_name = "FILE")]
+    pub index: Option<PathBuf>,
+
+    /// Only match files, not directories.
+    #[arg(long, default_value = "false")]
+    pub files_only: bool,
+
     /// Search pattern.
     pub pattern: Option<String>,
 }
@@ -0,0 +1,223 @@
+use grep::regex::RegexMatcherBuilder;
+use grep::searcher::BinaryDetection;
+use grep::searcher::SearcherBuilder;
+use grep::searcher::sinks::Lossy;
+use serde::Serialize;
+use std::num::NonZero;
+use std::path::Path;
+use std::sync::Mutex;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use tokio_util::sync::CancellationToken;
+
+use crate::WalkOptions;
+use crate::walk::walk_builder;
+
+/// Maximum number of characters kept in [`ContentMatch::preview`].
+const MAX_PREVIEW_CHARS: usize = 200;
+
+/// A single line whose contents matched the search pattern.
+///
+/// * `path` – Path to the file (relative to the search directory).
+/// * `line_number` – 1-based line number of the match.
+/// * `preview` – The matching line with surrounding whitespace trimmed,
+///   truncated to a reasonable length for display.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ContentMatch {
+    pub path: String,
+    pub line_number: u64,
+    pub preview: String,
+}
+
+pub struct ContentSearchResults {
+    pub matches: Vec<ContentMatch>,
+    /// `true` when the search stopped early because `limit` was reached.
+    pub truncated: bool,
+}
+
+/// Search the contents of every file under `search_directory` for lines that
+/// match the regular expression `pattern_text`, using the same matcher and
+/// searcher as ripgrep. Matching is smart-case and binary files are skipped.
+///
+/// The walk stops as soon as `limit` matches have been collected or
+/// `cancel` is triggered. Matches are returned sorted by path, then line.
+pub fn run_content_search(
+    pattern_text: &str,
+    limit: NonZero<usize>,
+    search_directory: &Path,
+    walk_options: &WalkOptions,
+    threads: NonZero<usize>,
+    cancel: CancellationToken,
+) -> anyhow::Result<ContentSearchResults> {
+    let matcher = RegexMatcherBuilder::new()
+        .case_smart(true)
+        .line_terminator(Some(b'\n'))
+        .build(pattern_text)?;
+
+    let walker = walk_builder(search_directory, search_directory, walk_options)?
+        .threads(threads.get())
+        .build_parallel();
+
+    let matches: Mutex<Vec<ContentMatch>> = Mutex::new(Vec::new());
+    let truncated = AtomicBool::new(false);
+    walker.run(|| {
+        let matcher = matcher.clone();
+        let mut searcher = SearcherBuilder::new()
+            .binary_detection(BinaryDetection::quit(b'\x00'))
+            .line_number(true)
+            .build();
+        let cancel = cancel.clone();
+        let matches = &matches;
+        let truncated = &truncated;
+
+        Box::new(move |entry| {
+            if cancel.is_cancelled() || truncated.load(Ordering::Relaxed) {
+                return ignore::WalkState::Quit;
+            }
+            let Ok(entry) = entry else {
+                return ignore::WalkState::Continue;
+            };
+            if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                return ignore::WalkState::Continue;
+            }
+            let Some(rel_path) = entry
+                .path()
+                .strip_prefix(search_directory)
+                .ok()
+                .and_then(|p| p.to_str())
+            else {
+                return ignore::WalkState::Continue;
+            };
+
+            // Unreadable files are skipped rather than failing the search.
+            let _ = searcher.search_path(
+                &matcher,
+                entry.path(),
+                Lossy(|line_number, line| {
+                    #[expect(clippy::unwrap_used)]
+                    let mut matches = matches.lock().unwrap();
+                    if matches.len() >= limit.get() {
+                        truncated.store(true, Ordering::Relaxed);
+                        return Ok(false);
+                    }
+                    matches.push(ContentMatch {
+                        path: rel_path.to_string(),
+                        line_number,
+                        preview: make_preview(line),
+                    });
+                    Ok(!cancel.is_cancelled())
+                }),
+            );
+
+            ignore::WalkState::Continue
+        })
+    });
+
+    // If the search was cancelled, we return early with an empty result.
+    if cancel.is_cancelled() {
+        return Ok(ContentSearchResults {
+            matches: Vec::new(),
+            truncated: false,
+        });
+    }
+
+    #[expect(clippy::unwrap_used)]
+    let mut matches = matches.into_inner().unwrap();
+    matches.sort_by(|a, b| {
+        a.path
+            .cmp(&b.path)
+            .then_with(|| a.line_number.cmp(&b.line_number))
+    });
+
+    Ok(ContentSearchResults {
+        matches,
+        truncated: truncated.load(Ordering::Relaxed),
+    })
+}
+
+fn make_preview(line: &str) -> String {
+    let trimmed = line.trim();
+    match trimmed.char_indices().nth(MAX_PREVIEW_CHARS) {
+        Some((idx, _)) => format!("{}…", &trimmed[..idx]),
+        None => trimmed.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn search(dir: &Path, pattern: &str, limit: usize) -> ContentSearchResults {
+        run_content_search(
+            pattern,
+            NonZero::new(limit).unwrap(),
+            dir,
+            &WalkOptions::default(),
+            NonZero::new(2).unwrap(),
+            CancellationToken::new(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn finds_matching_lines_with_line_numbers() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("a.rs"),
+            "fn main() {\n    let needle = 1;\n}\n",
+        )
+        .unwrap();
+        std::fs::write(dir.path().join("b.txt"), "Needle here\nnothing\n").unwrap();
+        std::fs::write(dir.path().join("c.txt"), "nothing at all\n").unwrap();
+
+        let results = search(dir.path(), "needle", 10);
+
+        assert_eq!(
+            results.matches,
+            vec![
+                ContentMatch {
+                    path: "a.rs".to_string(),
+                    line_number: 2,
+                    preview: "let needle = 1;".to_string(),
+                },
+                ContentMatch {
+                    path: "b.txt".to_string(),
+                    line_number: 1,
+                    preview: "Needle here".to_string(),
+                },
+            ]
+        );
+        assert!(!results.truncated);
+    }
+
+    #[test]
+    fn uppercase_pattern_is_case_sensitive() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "needle\nNeedle\n").unwrap();
+
+        let results = search(dir.path(), "Needle", 10);
+
+        let lines: Vec<u64> = results.matches.iter().map(|m| m.line_number).collect();
+        assert_eq!(lines, vec![2]);
+    }
+
+    #[test]
+    fn stops_at_limit_and_reports_truncation() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "x\nx\nx\nx\n").unwrap();
+
+        let results = search(dir.path(), "x", 2);
+
+        assert_eq!(results.matches.len(), 2);
+        assert!(results.truncated);
+    }
+
+    #[test]
+    fn preview_is_truncated() {
+        let long = "a".repeat(MAX_PREVIEW_CHARS + 10);
+        let preview = make_preview(&format!("  {long}  "));
+        assert_eq!(preview.chars().count(), MAX_PREVIEW_CHARS + 1);
+        assert!(preview.ends_with('…'));
+    }
+}
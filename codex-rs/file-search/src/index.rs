@@ -0,0 +1,447 @@
+//! Persistent index of the files under a search directory.
+//!
+//! Walking a tree with more than a million files on every keystroke is too
+//! slow, so [`crate::run`] can consult an index stored on disk instead. The
+//! index records the files and subdirectories of every directory together
+//! with the directory's mtime. A directory's mtime changes whenever an entry
+//! is added, removed or renamed in it, so keeping the index current only
+//! takes a `stat` per directory: directories whose mtime changed are listed
+//! again, and new subdirectories are walked in full.
+//!
+//! Small trees are never indexed. The first search with an index path walks
+//! the tree as usual, and only when it finds at least [`MIN_INDEXED_FILES`]
+//! files is the index built, in the background, for later searches.
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::hash::DefaultHasher;
+use std::hash::Hash;
+use std::hash::Hasher;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::OnceLock;
+use std::time::Duration;
+use std::time::Instant;
+use std::time::UNIX_EPOCH;
+
+use crate::WalkOptions;
+use crate::walk::walk_builder;
+
+/// Trees with fewer files than this are walked on every search.
+pub const MIN_INDEXED_FILES: usize = 100_000;
+
+/// Bumped whenever the on-disk format changes; older indexes are rebuilt.
+const INDEX_VERSION: u32 = 1;
+
+/// How long an index loaded in this process is trusted before its
+/// directories are checked for changes again.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Where per-workspace state for `search_directory`, such as its index or
+/// [`RecentFiles`](crate::RecentFiles), is kept inside `cache_dir`.
+pub fn workspace_file_path(cache_dir: &Path, search_directory: &Path) -> PathBuf {
+    let canonical =
+        std::fs::canonicalize(search_directory).unwrap_or_else(|_| search_directory.to_path_buf());
+    let mut hasher = DefaultHasher::new();
+    canonical.hash(&mut hasher);
+    cache_dir.join(format!("{:016x}.json", hasher.finish()))
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct FileIndex {
+    version: u32,
+    root: PathBuf,
+    walk_options: WalkOptions,
+    /// Keyed by the directory's path relative to `root`; `""` is the root.
+    dirs: BTreeMap<String, IndexedDir>,
+}
+
+#[derive(Debug, Default, PartialEq, Serialize, Deserialize)]
+struct IndexedDir {
+    mtime_ns: u64,
+    files: Vec<String>,
+    subdirs: Vec<String>,
+}
+
+struct LoadedIndex {
+    index: FileIndex,
+    files: Arc<Vec<String>>,
+    refreshed_at: Instant,
+}
+
+enum CacheEntry {
+    Loaded(Box<LoadedIndex>),
+    Building,
+}
+
+/// Indexes loaded by this process, keyed by index path.
+fn cache() -> &'static Mutex<HashMap<PathBuf, CacheEntry>> {
+    static CACHE: OnceLock<Mutex<HashMap<PathBuf, CacheEntry>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Returns the files under `search_directory` from the index at
+/// `index_path`, bringing it up to date first. `None` means there is no
+/// usable index and the caller should walk the tree.
+pub(crate) fn indexed_files(
+    index_path: &Path,
+    search_directory: &Path,
+    walk_options: &WalkOptions,
+) -> Option<Arc<Vec<String>>> {
+    #[expect(clippy::unwrap_used)]
+    let mut cache = cache().lock().unwrap();
+    match cache.get_mut(index_path) {
+        Some(CacheEntry::Loaded(loaded))
+            if loaded.index.root == search_directory
+                && loaded.index.walk_options == *walk_options =>
+        {
+            if loaded.refreshed_at.elapsed() >= REFRESH_INTERVAL {
+                refresh_and_save(loaded, index_path);
+            }
+            return Some(loaded.files.clone());
+        }
+        Some(CacheEntry::Building) => return None,
+        _ => {}
+    }
+
+    let index = load(index_path)?;
+    if index.version != INDEX_VERSION
+        || index.root != search_directory
+        || index.walk_options != *walk_options
+    {
+        return None;
+    }
+    let mut loaded = Box::new(LoadedIndex {
+        files: Arc::new(index.files()),
+        index,
+        refreshed_at: Instant::now(),
+    });
+    refresh_and_save(&mut loaded, index_path);
+    let files = loaded.files.clone();
+    cache.insert(index_path.to_path_buf(), CacheEntry::Loaded(loaded));
+    Some(files)
+}
+
+/// Builds the index for `search_directory` on a background thread and saves
+/// it to `index_path`. Does nothing if a build is already running.
+pub(crate) fn build_in_background(
+    index_path: &Path,
+    search_directory: &Path,
+    walk_options: &WalkOptions,
+) {
+    {
+        #[expect(clippy::unwrap_used)]
+        let mut cache = cache().lock().unwrap();
+        if matches!(cache.get(index_path), Some(CacheEntry::Building)) {
+            return;
+        }
+        cache.insert(index_path.to_path_buf(), CacheEntry::Building);
+    }
+
+    let index_path = index_path.to_path_buf();
+    let search_directory = search_directory.to_path_buf();
+    let walk_options = walk_options.clone();
+    std::thread::spawn(move || {
+        let mut index = FileIndex {
+            version: INDEX_VERSION,
+            root: search_directory,
+            walk_options,
+            dirs: BTreeMap::new(),
+        };
+        let built = index.load_subtree("").is_ok() && save(&index, &index_path).is_ok();
+
+        #[expect(clippy::unwrap_used)]
+        let mut cache = cache().lock().unwrap();
+        if built {
+            let loaded = LoadedIndex {
+                files: Arc::new(index.files()),
+                index,
+                refreshed_at: Instant::now(),
+            };
+            cache.insert(index_path, CacheEntry::Loaded(Box::new(loaded)));
+        } else {
+            cache.remove(&index_path);
+        }
+    });
+}
+
+fn refresh_and_save(loaded: &mut LoadedIndex, index_path: &Path) {
+    loaded.refreshed_at = Instant::now();
+    if loaded.index.refresh() {
+        loaded.files = Arc::new(loaded.index.files());
+        // A failed save only costs a longer refresh in the next process.
+        let _ = save(&loaded.index, index_path);
+    }
+}
+
+fn load(index_path: &Path) -> Option<FileIndex> {
+    let contents = std::fs::read(index_path).ok()?;
+    serde_json::from_slice(&contents).ok()
+}
+
+fn save(index: &FileIndex, index_path: &Path) -> anyhow::Result<()> {
+    if let Some(parent) = index_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    // Write to a temporary file first so a concurrent reader never sees a
+    // partially written index.
+    let tmp_path = index_path.with_extension("json.tmp");
+    std::fs::write(&tmp_path, serde_json::to_vec(index)?)?;
+    std::fs::rename(&tmp_path, index_path)?;
+    Ok(())
+}
+
+impl FileIndex {
+    /// Paths of all indexed files and directories relative to `root`.
+    /// Directories are marked with a trailing `/`.
+    fn files(&self) -> Vec<String> {
+        let mut files = Vec::new();
+        for (dir, entry) in &self.dirs {
+            if !dir.is_empty() {
+                files.push(format!("{dir}/"));
+            }
+            for name in &entry.files {
+                if let Some(path) = Path::new(dir).join(name).to_str() {
+                    files.push(path.to_string());
+                }
+            }
+        }
+        files
+    }
+
+    /// Re-lists every directory whose mtime changed since it was indexed.
+    /// Returns `true` if anything changed.
+    fn refresh(&mut self) -> bool {
+        let mut changed = false;
+        let mut stack = vec![String::new()];
+        while let Some(dir) = stack.pop() {
+            let Some(mtime_ns) = dir_mtime_ns(&self.root.join(&dir)) else {
+                // Removed; its parent was re-listed (or will be) as well.
+                self.remove_subtree(&dir);
+                changed = true;
+                continue;
+            };
+            let Some(entry) = self.dirs.get(&dir) else {
+                continue;
+            };
+            if entry.mtime_ns != mtime_ns {
+                if self.rescan_dir(&dir).is_err() {
+                    continue;
+                }
+                changed = true;
+            }
+            if let Some(entry) = self.dirs.get(&dir) {
+                stack.extend(entry.subdirs.iter().map(|name| child_key(&dir, name)));
+            }
+        }
+        changed
+    }
+
+    /// Lists `dir` again, walking subdirectories that are new and dropping
+    /// the ones that are gone.
+    fn rescan_dir(&mut self, dir: &str) -> anyhow::Result<()> {
+        let mut listing = IndexedDir::default();
+        for entry in self.walk(dir, Some(1))? {
+            if entry.depth() == 0 {
+                listing.mtime_ns = dir_mtime_ns(entry.path()).unwrap_or_default();
+                continue;
+            }
+            let Some(name) = entry.path().file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            if entry.file_type().is_some_and(|ft| ft.is_dir()) {
+                listing.subdirs.push(name.to_string());
+            } else {
+                listing.files.push(name.to_string());
+            }
+        }
+
+        let old_subdirs = self
+            .dirs
+            .get(dir)
+            .map(|entry| entry.subdirs.clone())
+            .unwrap_or_default();
+        for name in &old_subdirs {
+            if !listing.subdirs.contains(name) {
+                self.remove_subtree(&child_key(dir, name));
+            }
+        }
+        for name in &listing.subdirs {
+            if !old_subdirs.contains(name) {
+                self.load_subtree(&child_key(dir, name))?;
+            }
+        }
+        self.dirs.insert(dir.to_string(), listing);
+        Ok(())
+    }
+
+    /// Walks `dir` recursively and adds everything in it to the index.
+    fn load_subtree(&mut self, dir: &str) -> anyhow::Result<()> {
+        for entry in self.walk(dir, None)? {
+            let Some(key) = entry
+                .path()
+                .strip_prefix(&self.root)
+                .ok()
+                .and_then(|p| p.to_str())
+                .map(str::to_string)
+            else {
+                continue;
+            };
+            let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+            if is_dir {
+                let mtime_ns = dir_mtime_ns(entry.path()).unwrap_or_default();
+                self.dirs.insert(
+                    key.clone(),
+                    IndexedDir {
+                        mtime_ns,
+                        ..Default::default()
+                    },
+                );
+            }
+            if entry.depth() == 0 {
+                continue;
+            }
+
+            let Some(name) = entry.path().file_name().and_then(|n| n.to_str()) else {
+                continue;
+            };
+            let parent = Path::new(&key)
+                .parent()
+                .and_then(|p| p.to_str())
+                .unwrap_or_default();
+            if let Some(parent) = self.dirs.get_mut(parent) {
+                if is_dir {
+                    parent.subdirs.push(name.to_string());
+                } else {
+                    parent.files.push(name.to_string());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_subtree(&mut self, dir: &str) {
+        let prefix = Path::new(dir);
+        self.dirs
+            .retain(|key, _| !Path::new(key).starts_with(prefix));
+    }
+
+    fn walk(&self, dir: &str, max_depth: Option<usize>) -> anyhow::Result<Vec<ignore::DirEntry>> {
+        let walker = walk_builder(&self.root, &self.root.join(dir), &self.walk_options)?
+            .max_depth(max_depth)
+            .build();
+        Ok(walker.filter_map(Result::ok).collect())
+    }
+}
+
+fn child_key(dir: &str, name: &str) -> String {
+    Path::new(dir).join(name).to_string_lossy().into_owned()
+}
+
+fn dir_mtime_ns(path: &Path) -> Option<u64> {
+    let metadata = std::fs::metadata(path).ok()?;
+    if !metadata.is_dir() {
+        return None;
+    }
+    let mtime = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    u64::try_from(mtime.as_nanos()).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn build(root: &Path) -> FileIndex {
+        let mut index = FileIndex {
+            version: INDEX_VERSION,
+            root: root.to_path_buf(),
+            walk_options: WalkOptions::default(),
+            dirs: BTreeMap::new(),
+        };
+        index.load_subtree("").unwrap();
+        index
+    }
+
+    fn sorted_files(index: &FileIndex) -> Vec<String> {
+        let mut files: Vec<String> = index
+            .files()
+            .into_iter()
+            .filter(|path| !path.ends_with('/'))
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn refresh_picks_up_added_and_removed_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        std::fs::create_dir_all(root.join("src/old")).unwrap();
+        std::fs::write(root.join("README.md"), "").unwrap();
+        std::fs::write(root.join("src/lib.rs"), "").unwrap();
+        std::fs::write(root.join("src/old/mod.rs"), "").unwrap();
+
+        let mut index = build(root);
+        assert_eq!(
+            sorted_files(&index),
+            vec![
+                "README.md".to_string(),
+                Path::new("src")
+                    .join("lib.rs")
+                    .to_string_lossy()
+                    .into_owned(),
+                Path::new("src/old")
+                    .join("mod.rs")
+                    .to_string_lossy()
+                    .into_owned(),
+            ]
+        );
+        assert!(index.files().contains(&"src/".to_string()));
+        assert!(!index.refresh());
+
+        std::fs::remove_dir_all(root.join("src/old")).unwrap();
+        std::fs::create_dir_all(root.join("src/new")).unwrap();
+        std::fs::write(root.join("src/new/mod.rs"), "").unwrap();
+        std::fs::write(root.join("src/main.rs"), "").unwrap();
+        // Some filesystems only keep whole seconds, so make sure the change
+        // is visible regardless of timestamp granularity.
+        index.dirs.get_mut("src").unwrap().mtime_ns = 0;
+
+        assert!(index.refresh());
+        assert_eq!(
+            sorted_files(&index),
+            vec![
+                "README.md".to_string(),
+                Path::new("src")
+                    .join("lib.rs")
+                    .to_string_lossy()
+                    .into_owned(),
+                Path::new("src")
+                    .join("main.rs")
+                    .to_string_lossy()
+                    .into_owned(),
+                Path::new("src/new")
+                    .join("mod.rs")
+                    .to_string_lossy()
+                    .into_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn index_round_trips_through_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("a.txt"), "").unwrap();
+        let index = build(dir.path());
+
+        let index_path = dir.path().join("cache").join("index.json");
+        save(&index, &index_path).unwrap();
+
+        assert_eq!(load(&index_path), Some(index));
+    }
+}
@@ -1,5 +1,3 @@
-use ignore::WalkBuilder;
-use ignore::overrides::OverrideBuilder;
 use nucleo_matcher::Matcher;
 use nucleo_matcher::Utf32Str;
 use nucleo_matcher::pattern::AtomKind;
@@ -12,20 +10,41 @@ use std::cmp::Reverse;
 use std::collections::BinaryHeap;
 use std::num::NonZero;
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::Mutex;
 use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicUsize;
 use std::sync::atomic::Ordering;
+use std::sync::mpsc;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::Duration;
+use tokio_util::sync::CancellationToken;
+
+use crate::scoring::PathScorer;
 use tokio::process::Command;
 
 mod cli;
+mod content;
+mod index;
+mod recent;
+mod scoring;
+mod walk;
 
 pub use cli::Cli;
+pub use content::ContentMatch;
+pub use content::ContentSearchResults;
+pub use content::run_content_search;
+pub use index::MIN_INDEXED_FILES;
+pub use index::workspace_file_path;
+pub use recent::RecentFiles;
+pub use scoring::ScoringOptions;
+pub use walk::CODEX_IGNORE_FILENAME;
+pub use walk::WalkOptions;
 
 /// A single match result returned from the search.
 ///
 /// * `score` – Relevance score returned by `nucleo_matcher`.
 /// * `path`  – Path to the matched file (relative to the search directory).
+///   Directories end with a `/`.
 /// * `indices` – Optional list of character indices that matched the query.
 ///   These are only filled when the caller of [`run`] sets
 ///   `compute_indices` to `true`.  The indices vector follows the
@@ -40,6 +59,7 @@ pub struct FileMatch {
     pub indices: Option<Vec<u32>>, // Sorted & deduplicated when present
 }
 
+#[derive(Debug)]
 pub struct FileSearchResults {
     pub matches: Vec<FileMatch>,
     pub total_match_count: usize,
@@ -47,7 +67,9 @@ pub struct FileSearchResults {
 
 pub trait Reporter {
     fn report_match(&self, file_match: &FileMatch);
+    fn report_content_match(&self, content_match: &ContentMatch);
     fn warn_matches_truncated(&self, total_match_count: usize, shown_match_count: usize);
+    fn warn_content_matches_truncated(&self, shown_match_count: usize);
     fn warn_no_search_pattern(&self, search_directory: &Path);
 }
 
@@ -59,7 +81,12 @@ pub async fn run_main<T: Reporter>(
         compute_indices,
         json: _,
         exclude,
+        hidden,
+        no_ignore,
         threads,
+        content,
+        index,
+        files_only,
     }: Cli,
     reporter: T,
 ) -> anyhow::Result<()> {
@@ -93,7 +120,30 @@ pub async fn run_main<T: Reporter>(
         }
     };
 
-    let cancel_flag = Arc::new(AtomicBool::new(false));
+    let walk_options = WalkOptions {
+        exclude,
+        include_hidden: hidden,
+        include_ignored: no_ignore,
+    };
+    let cancel = CancellationToken::new();
+    if content {
+        let ContentSearchResults { matches, truncated } = run_content_search(
+            &pattern_text,
+            limit,
+            &search_directory,
+            &walk_options,
+            threads,
+            cancel,
+        )?;
+        for content_match in &matches {
+            reporter.report_content_match(content_match);
+        }
+        if truncated {
+            reporter.warn_content_matches_truncated(matches.len());
+        }
+        return Ok(());
+    }
+
     let FileSearchResults {
         total_match_count,
         matches,
@@ -101,10 +151,15 @@ pub async fn run_main<T: Reporter>(
         &pattern_text,
         limit,
         &search_directory,
-        exclude,
+        walk_options,
         threads,
-        cancel_flag,
+        cancel,
         compute_indices,
+        ScoringOptions {
+            include_directories: !files_only,
+            ..Default::default()
+        },
+        index.as_deref(),
     )?;
     let match_count = matches.len();
     let matches_truncated = total_match_count > match_count;
@@ -119,18 +174,102 @@ pub async fn run_main<T: Reporter>(
     Ok(())
 }
 
-/// The worker threads will periodically check `cancel_flag` to see if they
-/// should stop processing files.
+/// A snapshot of the best matches found so far, sent by [`run_streaming`].
+#[derive(Debug)]
+pub struct FileSearchUpdate {
+    pub results: FileSearchResults,
+    /// `true` for the last update of a search, sent once every candidate has
+    /// been scored. Earlier updates are superseded by later ones.
+    pub complete: bool,
+}
+
+/// How often [`run_streaming`] sends a snapshot while the tree is walked.
+const SNAPSHOT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Runs [`run_streaming`] to completion and returns its final results, or no
+/// matches if `cancel` was triggered.
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     pattern_text: &str,
     limit: NonZero<usize>,
     search_directory: &Path,
-    exclude: Vec<String>,
+    walk_options: WalkOptions,
     threads: NonZero<usize>,
-    cancel_flag: Arc<AtomicBool>,
+    cancel: CancellationToken,
     compute_indices: bool,
+    scoring: ScoringOptions,
+    index_path: Option<&Path>,
 ) -> anyhow::Result<FileSearchResults> {
+    let (tx, rx) = mpsc::channel();
+    run_streaming(
+        pattern_text,
+        limit,
+        search_directory,
+        walk_options,
+        threads,
+        cancel,
+        compute_indices,
+        scoring,
+        index_path,
+        tx,
+    )?;
+    Ok(rx
+        .into_iter()
+        .find(|update| update.complete)
+        .map(|update| update.results)
+        .unwrap_or(FileSearchResults {
+            matches: Vec::new(),
+            total_match_count: 0,
+        }))
+}
+
+/// Searches like [`run`], but sends the top `limit` matches found so far over
+/// `updates` every [`SNAPSHOT_INTERVAL`] while the tree is walked, so callers
+/// can show progressive results on huge trees. The last update has
+/// `complete` set. Once `cancel` is triggered the workers stop and no further
+/// updates are sent.
+///
+/// When `index_path` is set, the file list is read from the persistent index
+/// at that path (see [`workspace_file_path`]) instead of walking the tree. Without
+/// an index there, the tree is walked and, if it has at least
+/// [`MIN_INDEXED_FILES`] files, the index is built in the background for the
+/// next search.
+///
+/// `scoring` adjusts the fuzzy score for path structure and recent use, and
+/// decides whether directories are candidates.
+#[allow(clippy::too_many_arguments)]
+pub fn run_streaming(
+    pattern_text: &str,
+    limit: NonZero<usize>,
+    search_directory: &Path,
+    walk_options: WalkOptions,
+    threads: NonZero<usize>,
+    cancel: CancellationToken,
+    compute_indices: bool,
+    scoring: ScoringOptions,
+    index_path: Option<&Path>,
+    updates: mpsc::Sender<FileSearchUpdate>,
+) -> anyhow::Result<()> {
     let pattern = create_pattern(pattern_text);
+    let scorer = PathScorer::new(pattern_text, scoring);
+    let finish = |tops: Vec<TopMatches>| {
+        if !cancel.is_cancelled() {
+            let results = collect_results(tops, &pattern, limit, compute_indices);
+            let _ = updates.send(FileSearchUpdate {
+                results,
+                complete: true,
+            });
+        }
+    };
+
+    if let Some(index_path) = index_path
+        && let Some(files) = index::indexed_files(index_path, search_directory, &walk_options)
+    {
+        let best_lists = search_indexed_files(&files, &pattern, &scorer, limit, threads, &cancel);
+        finish(best_lists.iter().map(BestMatchesList::top).collect());
+        return Ok(());
+    }
+
     // Create one BestMatchesList per worker thread so that each worker can
     // operate independently. The results across threads will be merged when
     // the traversal is complete.
@@ -144,95 +283,195 @@ pub fn run(
                 limit.get(),
                 pattern.clone(),
                 Matcher::new(nucleo_matcher::Config::DEFAULT),
+                scorer.clone(),
             ))
         })
         .collect();
 
+    // Workers copy their best matches here from time to time so snapshots
+    // can be taken without touching the lists they are updating.
+    let published: Mutex<Vec<TopMatches>> =
+        Mutex::new(vec![TopMatches::default(); num_best_matches_lists]);
+    let published_changed = AtomicBool::new(false);
+
     // Use the same tree-walker library that ripgrep uses. We use it directly so
     // that we can leverage the parallelism it provides.
-    let mut walk_builder = WalkBuilder::new(search_directory);
-    walk_builder.threads(num_walk_builder_threads);
-    if !exclude.is_empty() {
-        let mut override_builder = OverrideBuilder::new(search_directory);
-        for exclude in exclude {
-            // The `!` prefix is used to indicate an exclude pattern.
-            let exclude_pattern = format!("!{exclude}");
-            override_builder.add(&exclude_pattern)?;
-        }
-        let override_matcher = override_builder.build()?;
-        walk_builder.overrides(override_matcher);
-    }
-    let walker = walk_builder.build_parallel();
-
-    // Each worker created by `WalkParallel::run()` will have its own
-    // `BestMatchesList` to update.
-    let index_counter = AtomicUsize::new(0);
-    walker.run(|| {
-        let index = index_counter.fetch_add(1, Ordering::Relaxed);
-        let best_list_ptr = best_matchers_per_worker[index].get();
-        let best_list = unsafe { &mut *best_list_ptr };
-
-        // Each worker keeps a local counter so we only read the atomic flag
-        // every N entries which is cheaper than checking on every file.
-        const CHECK_INTERVAL: usize = 1024;
-        let mut processed = 0;
-
-        let cancel = cancel_flag.clone();
-
-        Box::new(move |entry| {
-            if let Some(path) = get_file_path(&entry, search_directory) {
-                best_list.insert(path);
+    let walker = walk::walk_builder(search_directory, search_directory, &walk_options)?
+        .threads(num_walk_builder_threads)
+        .build_parallel();
+
+    let (done_tx, done_rx) = mpsc::channel::<()>();
+    std::thread::scope(|scope| {
+        // Sends a snapshot whenever a worker has published new matches, until
+        // the walk is done. `done_rx` is moved in as it cannot be shared.
+        let (pattern, published, published_changed, updates) =
+            (&pattern, &published, &published_changed, &updates);
+        let cancel = &cancel;
+        scope.spawn(move || {
+            while let Err(RecvTimeoutError::Timeout) = done_rx.recv_timeout(SNAPSHOT_INTERVAL) {
+                if cancel.is_cancelled() || !published_changed.swap(false, Ordering::Relaxed) {
+                    continue;
+                }
+                #[expect(clippy::unwrap_used)]
+                let tops = published.lock().unwrap().clone();
+                let results = collect_results(tops, pattern, limit, compute_indices);
+                let update = FileSearchUpdate {
+                    results,
+                    complete: false,
+                };
+                if updates.send(update).is_err() {
+                    break;
+                }
             }
+        });
 
-            processed += 1;
-            if processed % CHECK_INTERVAL == 0 && cancel.load(Ordering::Relaxed) {
-                ignore::WalkState::Quit
-            } else {
+        // Each worker created by `WalkParallel::run()` will have its own
+        // `BestMatchesList` to update.
+        let index_counter = AtomicUsize::new(0);
+        walker.run(|| {
+            let index = index_counter.fetch_add(1, Ordering::Relaxed);
+            let best_list_ptr = best_matchers_per_worker[index].get();
+            let best_list = unsafe { &mut *best_list_ptr };
+
+            // Each worker keeps a local counter so we only check the
+            // cancellation token and publish its matches every N entries,
+            // which is cheaper than doing so on every file.
+            const CHECK_INTERVAL: usize = 1024;
+            let mut processed = 0;
+
+            let cancel = cancel.clone();
+            let include_directories = scorer.include_directories();
+            // Reused to append the `/` that marks a directory.
+            let mut dir_path = String::new();
+
+            Box::new(move |entry| {
+                match get_path(&entry, search_directory) {
+                    Some((path, false)) => best_list.insert(path),
+                    Some((path, true)) if include_directories && !path.is_empty() => {
+                        dir_path.clear();
+                        dir_path.push_str(path);
+                        dir_path.push('/');
+                        best_list.insert(&dir_path);
+                    }
+                    _ => {}
+                }
+
+                processed += 1;
+                if processed % CHECK_INTERVAL != 0 {
+                    return ignore::WalkState::Continue;
+                }
+                if cancel.is_cancelled() {
+                    return ignore::WalkState::Quit;
+                }
+                if std::mem::take(&mut best_list.changed) {
+                    #[expect(clippy::unwrap_used)]
+                    let mut published = published.lock().unwrap();
+                    published[index] = best_list.top();
+                    published_changed.store(true, Ordering::Relaxed);
+                }
                 ignore::WalkState::Continue
-            }
-        })
+            })
+        });
+        drop(done_tx);
     });
 
-    fn get_file_path<'a>(
+    /// The entry's path relative to `search_directory`, and whether it is a
+    /// directory.
+    fn get_path<'a>(
         entry_result: &'a Result<ignore::DirEntry, ignore::Error>,
         search_directory: &std::path::Path,
-    ) -> Option<&'a str> {
+    ) -> Option<(&'a str, bool)> {
         let entry = match entry_result {
             Ok(e) => e,
             Err(_) => return None,
         };
-        if entry.file_type().is_some_and(|ft| ft.is_dir()) {
-            return None;
-        }
+        let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
         let path = entry.path();
         match path.strip_prefix(search_directory) {
-            Ok(rel_path) => rel_path.to_str(),
+            Ok(rel_path) => rel_path.to_str().map(|p| (p, is_dir)),
             Err(_) => None,
         }
     }
 
-    // If the cancel flag is set, we return early with an empty result.
-    if cancel_flag.load(Ordering::Relaxed) {
-        return Ok(FileSearchResults {
-            matches: Vec::new(),
-            total_match_count: 0,
-        });
+    let best_lists: Vec<BestMatchesList> = best_matchers_per_worker
+        .into_iter()
+        .map(UnsafeCell::into_inner)
+        .collect();
+
+    if let Some(index_path) = index_path
+        && !cancel.is_cancelled()
+        && best_lists.iter().map(|list| list.num_files).sum::<usize>() >= MIN_INDEXED_FILES
+    {
+        index::build_in_background(index_path, search_directory, &walk_options);
     }
 
-    // Merge results across best_matchers_per_worker.
+    finish(best_lists.iter().map(BestMatchesList::top).collect());
+    Ok(())
+}
+
+/// Scores `files` on `threads` threads, each keeping its own
+/// `BestMatchesList`.
+fn search_indexed_files(
+    files: &[String],
+    pattern: &Pattern,
+    scorer: &PathScorer,
+    limit: NonZero<usize>,
+    threads: NonZero<usize>,
+    cancel: &CancellationToken,
+) -> Vec<BestMatchesList> {
+    const CHECK_INTERVAL: usize = 1024;
+    let chunk_size = files.len().div_ceil(threads.get()).max(1);
+    std::thread::scope(|scope| {
+        let handles: Vec<_> = files
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let mut best_list = BestMatchesList::new(
+                        limit.get(),
+                        pattern.clone(),
+                        Matcher::new(nucleo_matcher::Config::DEFAULT),
+                        scorer.clone(),
+                    );
+                    let include_directories = scorer.include_directories();
+                    for (i, path) in chunk.iter().enumerate() {
+                        if i % CHECK_INTERVAL == 0 && cancel.is_cancelled() {
+                            break;
+                        }
+                        if include_directories || !path.ends_with('/') {
+                            best_list.insert(path);
+                        }
+                    }
+                    best_list
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .filter_map(|handle| handle.join().ok())
+            .collect()
+    })
+}
+
+/// Merges the best matches of every worker into sorted results.
+fn collect_results(
+    tops: Vec<TopMatches>,
+    pattern: &Pattern,
+    limit: NonZero<usize>,
+    compute_indices: bool,
+) -> FileSearchResults {
+    // Merge results across workers.
     let mut global_heap: BinaryHeap<Reverse<(u32, String)>> = BinaryHeap::new();
     let mut total_match_count = 0;
-    for best_list_cell in best_matchers_per_worker.iter() {
-        let best_list = unsafe { &*best_list_cell.get() };
-        total_match_count += best_list.num_matches;
-        for &Reverse((score, ref line)) in best_list.binary_heap.iter() {
+    for top in tops {
+        total_match_count += top.num_matches;
+        for (score, line) in top.matches {
             if global_heap.len() < limit.get() {
-                global_heap.push(Reverse((score, line.clone())));
+                global_heap.push(Reverse((score, line)));
             } else if let Some(min_element) = global_heap.peek()
                 && score > min_element.0.0
             {
                 global_heap.pop();
-                global_heap.push(Reverse((score, line.clone())));
+                global_heap.push(Reverse((score, line)));
             }
         }
     }
@@ -273,10 +512,10 @@ pub fn run(
         })
         .collect();
 
-    Ok(FileSearchResults {
+    FileSearchResults {
         matches,
         total_match_count,
-    })
+    }
 }
 
 /// Sort matches in-place by descending score, then ascending path.
@@ -290,9 +529,15 @@ fn sort_matches(matches: &mut [(u32, String)]) {
 /// Maintains the `max_count` best matches for a given pattern.
 struct BestMatchesList {
     max_count: usize,
+    /// Number of paths passed to `insert`.
+    num_files: usize,
     num_matches: usize,
+    /// Set whenever a match is inserted; cleared when the worker publishes
+    /// its matches for a snapshot.
+    changed: bool,
     pattern: Pattern,
     matcher: Matcher,
+    scorer: PathScorer,
     binary_heap: BinaryHeap<Reverse<(u32, String)>>,
 
     /// Internal buffer for converting strings to UTF-32.
@@ -300,23 +545,29 @@ struct BestMatchesList {
 }
 
 impl BestMatchesList {
-    fn new(max_count: usize, pattern: Pattern, matcher: Matcher) -> Self {
+    fn new(max_count: usize, pattern: Pattern, matcher: Matcher, scorer: PathScorer) -> Self {
         Self {
             max_count,
+            num_files: 0,
             num_matches: 0,
+            changed: false,
             pattern,
             matcher,
+            scorer,
             binary_heap: BinaryHeap::new(),
             utf32buf: Vec::<char>::new(),
         }
     }
 
     fn insert(&mut self, line: &str) {
+        self.num_files += 1;
         let haystack: Utf32Str<'_> = Utf32Str::new(line, &mut self.utf32buf);
         if let Some(score) = self.pattern.score(haystack, &mut self.matcher) {
+            let score = score.saturating_add(self.scorer.bonus(line));
             // In the tests below, we verify that score() returns None for a
             // non-match, so we can categorically increment the count here.
             self.num_matches += 1;
+            self.changed = true;
 
             if self.binary_heap.len() < self.max_count {
                 self.binary_heap.push(Reverse((score, line.to_string())));
@@ -328,6 +579,20 @@ impl BestMatchesList {
             }
         }
     }
+
+    fn top(&self) -> TopMatches {
+        TopMatches {
+            matches: self.binary_heap.iter().map(|r| r.0.clone()).collect(),
+            num_matches: self.num_matches,
+        }
+    }
+}
+
+/// A copy of a worker's best matches, in no particular order.
+#[derive(Clone, Default)]
+struct TopMatches {
+    matches: Vec<(u32, String)>,
+    num_matches: usize,
 }
 
 struct WorkerCount {
@@ -366,6 +631,7 @@ fn create_pattern(pattern: &str) -> Pattern {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Arc;
 
     #[test]
     fn verify_score_is_none_for_non_match() {
@@ -397,4 +663,173 @@ mod tests {
 
         assert_eq!(matches, expected);
     }
+
+    #[test]
+    fn indexed_files_are_split_across_threads_and_merged() {
+        let files: Vec<String> = ["src/main.rs", "src/lib.rs", "README.md", "docs/main.md"]
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let pattern = create_pattern("main");
+        let limit = NonZero::new(10).unwrap();
+
+        let best_lists = search_indexed_files(
+            &files,
+            &pattern,
+            &PathScorer::new("main", ScoringOptions::default()),
+            limit,
+            NonZero::new(3).unwrap(),
+            &CancellationToken::new(),
+        );
+        let tops = best_lists.iter().map(BestMatchesList::top).collect();
+        let results = collect_results(tops, &pattern, limit, false);
+
+        let mut paths: Vec<String> = results.matches.into_iter().map(|m| m.path).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec!["docs/main.md".to_string(), "src/main.rs".to_string()]
+        );
+        assert_eq!(results.total_match_count, 2);
+    }
+
+    fn search_paths(dir: &Path, pattern: &str, scoring: ScoringOptions) -> Vec<String> {
+        let results = run(
+            pattern,
+            NonZero::new(10).unwrap(),
+            dir,
+            WalkOptions::default(),
+            NonZero::new(2).unwrap(),
+            CancellationToken::new(),
+            false,
+            scoring,
+            None,
+        )
+        .unwrap();
+        results.matches.into_iter().map(|m| m.path).collect()
+    }
+
+    fn stream(dir: &Path, cancel: CancellationToken) -> Vec<FileSearchUpdate> {
+        let (tx, rx) = mpsc::channel();
+        run_streaming(
+            "lib",
+            NonZero::new(10).unwrap(),
+            dir,
+            WalkOptions::default(),
+            NonZero::new(2).unwrap(),
+            cancel,
+            false,
+            ScoringOptions::default(),
+            None,
+            tx,
+        )
+        .unwrap();
+        rx.into_iter().collect()
+    }
+
+    #[test]
+    fn streaming_ends_with_a_complete_update() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "").unwrap();
+
+        let updates = stream(dir.path(), CancellationToken::new());
+
+        let last = updates.last().unwrap();
+        assert!(last.complete);
+        assert_eq!(last.results.matches.len(), 1);
+        assert!(updates[..updates.len() - 1].iter().all(|u| !u.complete));
+    }
+
+    #[test]
+    fn cancelled_search_sends_no_updates() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("lib.rs"), "").unwrap();
+        let cancel = CancellationToken::new();
+        cancel.cancel();
+
+        assert!(stream(dir.path(), cancel).is_empty());
+    }
+
+    #[test]
+    fn directories_are_matched_with_a_trailing_slash() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("widgets")).unwrap();
+        std::fs::write(dir.path().join("widgets").join("mod.rs"), "").unwrap();
+
+        let mut paths = search_paths(dir.path(), "widgets", ScoringOptions::default());
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                "widgets/".to_string(),
+                Path::new("widgets")
+                    .join("mod.rs")
+                    .to_string_lossy()
+                    .into_owned(),
+            ]
+        );
+
+        let files_only = ScoringOptions {
+            include_directories: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            search_paths(dir.path(), "widgets", files_only),
+            vec![
+                Path::new("widgets")
+                    .join("mod.rs")
+                    .to_string_lossy()
+                    .into_owned()
+            ]
+        );
+    }
+
+    #[test]
+    fn segment_aligned_matches_rank_first() {
+        let dir = tempfile::tempdir().unwrap();
+        for path in ["src/main.rs", "src/domain.rs", "resources/main.rs"] {
+            let path = dir.path().join(path);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, "").unwrap();
+        }
+
+        let paths = search_paths(dir.path(), "src/main", ScoringOptions::default());
+        assert_eq!(
+            paths.first(),
+            Some(
+                &Path::new("src")
+                    .join("main.rs")
+                    .to_string_lossy()
+                    .into_owned()
+            )
+        );
+    }
+
+    #[test]
+    fn recent_files_rank_first() {
+        let dir = tempfile::tempdir().unwrap();
+        let paths: Vec<String> = ["a", "b"]
+            .into_iter()
+            .map(|parent| {
+                std::fs::create_dir(dir.path().join(parent)).unwrap();
+                std::fs::write(dir.path().join(parent).join("config.rs"), "").unwrap();
+                Path::new(parent)
+                    .join("config.rs")
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .collect();
+
+        let mut recent_files = RecentFiles::default();
+        recent_files.record([paths[1].as_str()]);
+        let scoring = ScoringOptions {
+            include_directories: false,
+            recent_files: Some(Arc::new(recent_files)),
+            ..Default::default()
+        };
+        assert_eq!(
+            search_paths(dir.path(), "config", scoring),
+            vec![paths[1].clone(), paths[0].clone()]
+        );
+    }
 }
@@ -3,6 +3,7 @@ use std::path::Path;
 
 use clap::Parser;
 use codex_file_search::Cli;
+use codex_file_search::ContentMatch;
 use codex_file_search::FileMatch;
 use codex_file_search::Reporter;
 use codex_file_search::run_main;
@@ -69,6 +70,28 @@ impl Reporter for StdioReporter {
         }
     }
 
+    fn report_content_match(&self, content_match: &ContentMatch) {
+        if self.write_output_as_json {
+            println!("{}", serde_json::to_string(&content_match).unwrap());
+        } else {
+            println!(
+                "{}:{}:{}",
+                content_match.path, content_match.line_number, content_match.preview
+            );
+        }
+    }
+
+    fn warn_content_matches_truncated(&self, shown_match_count: usize) {
+        if self.write_output_as_json {
+            let value = json!({"matches_truncated": true});
+            println!("{}", serde_json::to_string(&value).unwrap());
+        } else {
+            eprintln!(
+                "Warning: showing the first {shown_match_count} matching lines. Provide a more specific pattern or increase the --limit.",
+            );
+        }
+    }
+
     fn warn_no_search_pattern(&self, search_directory: &Path) {
         eprintln!(
             "No search pattern specified. Showing the contents of the current directory ({}):",
@@ -0,0 +1,149 @@
+//! Files recently mentioned or edited in a workspace, kept in a small JSON
+//! file so matches for them can be ranked first ("frecency": frequency
+//! weighted by recency).
+
+use serde::Deserialize;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+/// Older entries are dropped once a workspace has this many.
+const MAX_ENTRIES: usize = 500;
+
+/// Upper bound on the bonus a single file can earn.
+const MAX_BONUS: u32 = 64;
+
+const HOUR: u64 = 60 * 60;
+const DAY: u64 = 24 * HOUR;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecentFiles {
+    /// Keyed by path relative to the workspace, as returned by the search.
+    entries: HashMap<String, RecentEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct RecentEntry {
+    /// How often the file was used.
+    count: u32,
+    /// When the file was last used, in seconds since the Unix epoch.
+    last_used: u64,
+}
+
+impl RecentFiles {
+    /// Reads the recent files at `path`; a missing or unreadable file gives
+    /// an empty list.
+    pub fn load(path: &Path) -> Self {
+        std::fs::read(path)
+            .ok()
+            .and_then(|contents| serde_json::from_slice(&contents).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, serde_json::to_vec(self)?)?;
+        Ok(())
+    }
+
+    /// Records a use of each of `paths`, relative to the workspace.
+    pub fn record<'a>(&mut self, paths: impl IntoIterator<Item = &'a str>) {
+        let now = now_secs();
+        for path in paths {
+            let entry = self.entries.entry(path.to_string()).or_insert(RecentEntry {
+                count: 0,
+                last_used: now,
+            });
+            entry.count = entry.count.saturating_add(1);
+            entry.last_used = now;
+        }
+
+        if self.entries.len() > MAX_ENTRIES {
+            let mut by_age: Vec<(String, u64)> = self
+                .entries
+                .iter()
+                .map(|(path, entry)| (path.clone(), entry.last_used))
+                .collect();
+            by_age.sort_by_key(|(_, last_used)| *last_used);
+            let excess = self.entries.len() - MAX_ENTRIES;
+            for (path, _) in by_age.into_iter().take(excess) {
+                self.entries.remove(&path);
+            }
+        }
+    }
+
+    /// Bonus for `path` at time `now` (seconds since the Unix epoch): the
+    /// number of uses, weighted by how recently the file was last used.
+    pub(crate) fn bonus(&self, path: &str, now: u64) -> u32 {
+        let Some(entry) = self.entries.get(path) else {
+            return 0;
+        };
+        let age = now.saturating_sub(entry.last_used);
+        let weight = if age <= HOUR {
+            16
+        } else if age <= DAY {
+            8
+        } else if age <= 7 * DAY {
+            4
+        } else if age <= 30 * DAY {
+            2
+        } else {
+            1
+        };
+        entry.count.saturating_mul(weight).min(MAX_BONUS)
+    }
+}
+
+pub(crate) fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn bonus_grows_with_use_and_fades_with_age() {
+        let mut recent = RecentFiles::default();
+        recent.record(["src/lib.rs"]);
+        recent.record(["src/lib.rs", "README.md"]);
+        let now = now_secs();
+
+        assert_eq!(recent.bonus("src/lib.rs", now), 32);
+        assert_eq!(recent.bonus("README.md", now), 16);
+        assert_eq!(recent.bonus("README.md", now + 2 * DAY), 4);
+        assert_eq!(recent.bonus("src/main.rs", now), 0);
+    }
+
+    #[test]
+    fn bonus_is_capped() {
+        let mut recent = RecentFiles::default();
+        for _ in 0..100 {
+            recent.record(["src/lib.rs"]);
+        }
+        assert_eq!(recent.bonus("src/lib.rs", now_secs()), MAX_BONUS);
+    }
+
+    #[test]
+    fn round_trips_through_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("recent").join("files.json");
+        let mut recent = RecentFiles::default();
+        recent.record(["a.txt"]);
+        recent.save(&path).unwrap();
+
+        assert_eq!(RecentFiles::load(&path), recent);
+        assert_eq!(
+            RecentFiles::load(&dir.path().join("missing.json")),
+            RecentFiles::default()
+        );
+    }
+}
@@ -0,0 +1,146 @@
+use std::sync::Arc;
+
+use crate::recent::RecentFiles;
+use crate::recent::now_secs;
+
+/// Tweaks applied on top of the fuzzy score from `nucleo_matcher`, which
+/// knows nothing about paths.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScoringOptions {
+    /// Offer directories, marked with a trailing `/`, as well as files.
+    pub include_directories: bool,
+    /// Added for every query atom that starts a path segment, such as `src`
+    /// and `main` for `src/main.rs` when the query is `src/main`.
+    pub segment_bonus: u32,
+    /// Added when the last query atom occurs in the file name itself rather
+    /// than only in the directories leading to it.
+    pub basename_bonus: u32,
+    /// Files the user and agent have been working on, which rank higher the
+    /// more often and more recently they were used.
+    pub recent_files: Option<Arc<RecentFiles>>,
+}
+
+impl Default for ScoringOptions {
+    fn default() -> Self {
+        Self {
+            include_directories: true,
+            segment_bonus: 24,
+            basename_bonus: 48,
+            recent_files: None,
+        }
+    }
+}
+
+/// Computes the path-aware bonus for a query.
+#[derive(Debug, Clone)]
+pub(crate) struct PathScorer {
+    options: ScoringOptions,
+    /// Lowercased query atoms, split on whitespace and path separators.
+    atoms: Vec<String>,
+    /// Seconds since the Unix epoch when the search started, for the age of
+    /// recent files.
+    now: u64,
+}
+
+impl PathScorer {
+    pub(crate) fn new(pattern_text: &str, options: ScoringOptions) -> Self {
+        let atoms = pattern_text
+            .split(|c: char| c.is_whitespace() || c == '/' || c == '\\')
+            // Negated atoms never match, so they cannot earn a bonus.
+            .filter(|atom| !atom.starts_with('!'))
+            .map(|atom| atom.trim_start_matches(['^', '\'']).trim_end_matches('$'))
+            .filter(|atom| !atom.is_empty())
+            .map(str::to_ascii_lowercase)
+            .collect();
+        Self {
+            options,
+            atoms,
+            now: now_secs(),
+        }
+    }
+
+    pub(crate) fn include_directories(&self) -> bool {
+        self.options.include_directories
+    }
+
+    /// Bonus for `path`, which the pattern is already known to match.
+    pub(crate) fn bonus(&self, path: &str) -> u32 {
+        let mut bonus = self
+            .options
+            .recent_files
+            .as_ref()
+            .map_or(0, |recent_files| recent_files.bonus(path, self.now));
+        let Some(last_atom) = self.atoms.last() else {
+            return bonus;
+        };
+        let trimmed = path.trim_end_matches(['/', '\\']);
+        let segments: Vec<&str> = trimmed.split(['/', '\\']).collect();
+
+        for atom in &self.atoms {
+            if segments
+                .iter()
+                .any(|segment| starts_with_ignore_ascii_case(segment, atom))
+            {
+                bonus = bonus.saturating_add(self.options.segment_bonus);
+            }
+        }
+        let basename = segments.last().copied().unwrap_or_default();
+        if basename.to_ascii_lowercase().contains(last_atom.as_str()) {
+            bonus = bonus.saturating_add(self.options.basename_bonus);
+        }
+        bonus
+    }
+}
+
+fn starts_with_ignore_ascii_case(haystack: &str, prefix: &str) -> bool {
+    haystack
+        .as_bytes()
+        .get(..prefix.len())
+        .is_some_and(|start| start.eq_ignore_ascii_case(prefix.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn segment_and_basename_alignment_earn_bonuses() {
+        let scorer = PathScorer::new("src/main", ScoringOptions::default());
+
+        // Both atoms start a segment and `main` is in the file name.
+        assert_eq!(scorer.bonus("src/main.rs"), 24 + 24 + 48);
+        // `main` starts a segment, but it is a directory.
+        assert_eq!(scorer.bonus("src/main/lib.rs"), 24 + 24);
+        // Neither atom lines up with a segment.
+        assert_eq!(scorer.bonus("resources/domain_test.rs"), 48);
+        assert_eq!(scorer.bonus("tests/rsrc/xmain"), 48);
+    }
+
+    #[test]
+    fn directories_are_scored_by_their_own_name() {
+        let scorer = PathScorer::new("core", ScoringOptions::default());
+        assert_eq!(scorer.bonus("codex-rs/core/"), 24 + 48);
+    }
+
+    #[test]
+    fn recent_files_earn_a_bonus() {
+        let mut recent_files = RecentFiles::default();
+        recent_files.record(["docs/core.md"]);
+        let scorer = PathScorer::new(
+            "core",
+            ScoringOptions {
+                recent_files: Some(Arc::new(recent_files)),
+                ..Default::default()
+            },
+        );
+        assert_eq!(scorer.bonus("docs/core.md"), 16 + 24 + 48);
+        assert_eq!(scorer.bonus("src/core.rs"), 24 + 48);
+    }
+
+    #[test]
+    fn pattern_syntax_is_ignored() {
+        let scorer = PathScorer::new("^src !test main$", ScoringOptions::default());
+        assert_eq!(scorer.atoms, vec!["src".to_string(), "main".to_string()]);
+    }
+}
@@ -0,0 +1,120 @@
+use ignore::WalkBuilder;
+use ignore::overrides::OverrideBuilder;
+use serde::Deserialize;
+use serde::Serialize;
+use std::path::Path;
+
+/// Name of the per-directory ignore file, in `.gitignore` syntax, for files
+/// that should not show up in searches but are still tracked by git.
+pub const CODEX_IGNORE_FILENAME: &str = ".codexignore";
+
+/// Which files a search considers. `.gitignore` rules (and `.ignore`, git's
+/// global and repository excludes) apply unless `include_ignored` is set;
+/// `.codexignore` files and `exclude` always apply on top of them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WalkOptions {
+    /// Globs, relative to the search directory, for files to leave out.
+    pub exclude: Vec<String>,
+    /// Include hidden files and directories.
+    pub include_hidden: bool,
+    /// Include files ignored by `.gitignore`, `.ignore` and git's excludes.
+    pub include_ignored: bool,
+}
+
+/// A `WalkBuilder` for `dir` with `options` applied. `root` is the search
+/// directory that `options.exclude` globs are relative to.
+pub(crate) fn walk_builder(
+    root: &Path,
+    dir: &Path,
+    options: &WalkOptions,
+) -> anyhow::Result<WalkBuilder> {
+    let mut walk_builder = WalkBuilder::new(dir);
+    walk_builder
+        .hidden(!options.include_hidden)
+        .add_custom_ignore_filename(CODEX_IGNORE_FILENAME);
+    if options.include_ignored {
+        walk_builder
+            .git_ignore(false)
+            .git_global(false)
+            .git_exclude(false)
+            .ignore(false);
+    }
+    if options.include_hidden {
+        // Hidden files are wanted, git's internals are not.
+        walk_builder.filter_entry(|entry| entry.file_name() != ".git");
+    }
+    if !options.exclude.is_empty() {
+        let mut override_builder = OverrideBuilder::new(root);
+        for exclude in &options.exclude {
+            // The `!` prefix is used to indicate an exclude pattern.
+            let exclude_pattern = format!("!{exclude}");
+            override_builder.add(&exclude_pattern)?;
+        }
+        walk_builder.overrides(override_builder.build()?);
+    }
+    Ok(walk_builder)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn walk(root: &Path, options: &WalkOptions) -> Vec<String> {
+        let mut files: Vec<String> = walk_builder(root, root, options)
+            .unwrap()
+            .build()
+            .filter_map(Result::ok)
+            .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
+            .filter_map(|entry| {
+                entry
+                    .path()
+                    .strip_prefix(root)
+                    .ok()
+                    .and_then(|p| p.to_str())
+                    .map(|p| p.replace('\\', "/"))
+            })
+            .collect();
+        files.sort();
+        files
+    }
+
+    #[test]
+    fn codexignore_and_excludes_layer_on_top_of_gitignore() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        // `.gitignore` is only honored inside a git repository.
+        std::fs::create_dir(root.join(".git")).unwrap();
+        std::fs::create_dir_all(root.join("gen")).unwrap();
+        std::fs::create_dir_all(root.join("target")).unwrap();
+        std::fs::write(root.join(".gitignore"), "target/\n").unwrap();
+        std::fs::write(root.join(".codexignore"), "gen/\n").unwrap();
+        std::fs::write(root.join(".env"), "").unwrap();
+        std::fs::write(root.join("gen/out.rs"), "").unwrap();
+        std::fs::write(root.join("target/app"), "").unwrap();
+        std::fs::write(root.join("main.rs"), "").unwrap();
+        std::fs::write(root.join("notes.log"), "").unwrap();
+
+        let options = WalkOptions {
+            exclude: vec!["*.log".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(walk(root, &options), vec!["main.rs".to_string()]);
+
+        let options = WalkOptions {
+            exclude: vec!["*.log".to_string()],
+            include_hidden: true,
+            include_ignored: true,
+        };
+        assert_eq!(
+            walk(root, &options),
+            vec![
+                ".codexignore".to_string(),
+                ".env".to_string(),
+                ".gitignore".to_string(),
+                "main.rs".to_string(),
+                "target/app".to_string(),
+            ]
+        );
+    }
+}
@@ -0,0 +1,701 @@
+//! `codex-linux-sandbox --report-denials FILE`: records what the sandbox
+//! refuses the command, so that Codex can say e.g. "blocked: write to
+//! /etc/hosts" instead of guessing from the command's output.
+//!
+//! The command runs in a child process whose seccomp filter hands the
+//! watched syscalls to this process through a seccomp user notification
+//! listener. Network syscalls are refused with `EPERM`, as the network filter
+//! does without reporting. Writes are let through once their path has been
+//! read from the child's memory: Landlock still decides, so a path that is
+//! misread can only make the report wrong, never the sandbox weaker. Each
+//! denial is appended to `FILE` as one JSON [`SandboxDenial`] per line.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::fs::File;
+use std::io::Write;
+use std::os::fd::AsRawFd;
+use std::os::fd::FromRawFd;
+use std::os::fd::OwnedFd;
+use std::os::fd::RawFd;
+use std::os::unix::ffi::OsStringExt;
+use std::os::unix::fs::FileExt;
+use std::path::Component;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_core::error::SandboxErr;
+use codex_core::protocol::SandboxDenial;
+use codex_core::protocol::SandboxPolicy;
+use seccompiler::BpfProgram;
+use seccompiler::SeccompAction;
+use seccompiler::SeccompCmpArgLen;
+use seccompiler::SeccompCmpOp;
+use seccompiler::SeccompCondition;
+use seccompiler::SeccompRule;
+use seccompiler::apply_filter;
+
+use crate::landlock::apply_filesystem_policy_to_current_thread;
+use crate::landlock::apply_sandbox_policy_to_current_thread;
+use crate::landlock::compile_seccomp_filter;
+use crate::landlock::install_network_seccomp_filter_on_current_thread;
+use crate::landlock::network_seccomp_rules;
+use crate::linux_run_main::exec_command;
+
+// From <linux/seccomp.h> and <linux/filter.h>; the libc crate does not
+// define the user notification API.
+const SECCOMP_SET_MODE_FILTER: libc::c_ulong = 1;
+const SECCOMP_FILTER_FLAG_NEW_LISTENER: libc::c_ulong = 1 << 3;
+const SECCOMP_RET_ERRNO: u32 = 0x0005_0000;
+const SECCOMP_RET_USER_NOTIF: u32 = 0x7fc0_0000;
+const SECCOMP_USER_NOTIF_FLAG_CONTINUE: u32 = 1;
+const SECCOMP_IOCTL_NOTIF_RECV: libc::c_ulong = 0xc050_2100;
+const SECCOMP_IOCTL_NOTIF_SEND: libc::c_ulong = 0xc018_2101;
+const SECCOMP_IOCTL_NOTIF_ID_VALID: libc::c_ulong = 0x4008_2102;
+const BPF_RET_K: u16 = 0x06;
+
+/// How often the supervisor checks whether the command has exited.
+const POLL_INTERVAL_MS: libc::c_int = 100;
+
+/// Denials past this many are dropped; the first ones explain the failure.
+const MAX_REPORTED_DENIALS: usize = 16;
+
+const PATH_MAX: usize = 4096;
+
+/// Paths are read from the child's memory a page at a time, so that one
+/// ending just before an unmapped page can still be read.
+const PAGE_SIZE: u64 = 4096;
+
+// Layouts shared with the kernel, which reads and writes fields that this
+// file does not.
+#[repr(C)]
+#[allow(dead_code)]
+struct SeccompData {
+    nr: libc::c_int,
+    arch: u32,
+    instruction_pointer: u64,
+    args: [u64; 6],
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+struct SeccompNotif {
+    id: u64,
+    pid: u32,
+    flags: u32,
+    data: SeccompData,
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+struct SeccompNotifResp {
+    id: u64,
+    val: i64,
+    error: i32,
+    flags: u32,
+}
+
+#[repr(C)]
+#[allow(dead_code)]
+struct SockFprog {
+    len: libc::c_ushort,
+    filter: *const libc::c_void,
+}
+
+/// Network syscalls that are reported when refused. The network filter's
+/// other syscalls, such as `sendmsg` and `getsockopt`, are just as often
+/// used on local sockets and stay plain `EPERM`; `sendmsg` also hands the
+/// listener to the supervisor.
+const REPORTED_NETWORK_SYSCALLS: &[(i64, &str)] = &[
+    (libc::SYS_connect, "connect"),
+    (libc::SYS_bind, "bind"),
+    (libc::SYS_listen, "listen"),
+    (libc::SYS_accept, "accept"),
+    (libc::SYS_accept4, "accept4"),
+    (libc::SYS_socket, "socket"),
+    (libc::SYS_socketpair, "socketpair"),
+];
+
+#[derive(Clone, Copy, Debug)]
+enum Watch {
+    Network,
+    /// A write to the path in argument `path`, relative to the directory fd
+    /// in argument `dirfd` if there is one. With `flags`, only calls whose
+    /// open flags in that argument ask for writing are watched.
+    Write {
+        dirfd: Option<usize>,
+        path: usize,
+        flags: Option<usize>,
+    },
+}
+
+#[derive(Clone, Copy, Debug)]
+struct WatchedSyscall {
+    name: &'static str,
+    watch: Watch,
+}
+
+/// Runs `command` under `sandbox_policy`, recording what the sandbox refuses
+/// it in `report_path`, and exits as the command did.
+pub(crate) fn run_with_denial_report(
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+    report_path: &Path,
+    command: &[String],
+) -> ! {
+    let watched = watched_syscalls(sandbox_policy);
+    if watched.is_empty() {
+        run_without_report(sandbox_policy, cwd, command);
+    }
+    let report = File::create(report_path);
+    let filters = compile_filters(sandbox_policy, &watched);
+    let socket = socket_pair();
+    let (Ok(report), Ok((notify_prog, errno_prog)), Ok((supervisor_socket, child_socket))) =
+        (report, filters, socket)
+    else {
+        // Reporting is best effort; the sandbox is not.
+        run_without_report(sandbox_policy, cwd, command);
+    };
+    let writable_roots = writable_roots(sandbox_policy, cwd);
+
+    let supervisor_pid = unsafe { libc::getpid() };
+    match unsafe { libc::fork() } {
+        -1 => run_without_report(sandbox_policy, cwd, command),
+        0 => {
+            drop(supervisor_socket);
+            run_child(
+                supervisor_pid,
+                child_socket,
+                sandbox_policy,
+                cwd,
+                &notify_prog,
+                errno_prog.as_ref(),
+                command,
+            );
+        }
+        child => {
+            drop(child_socket);
+            let mut supervisor = Supervisor {
+                watched,
+                writable_roots,
+                report,
+                recorded: Vec::new(),
+            };
+            supervisor.run(child, supervisor_socket);
+        }
+    }
+}
+
+fn run_without_report(sandbox_policy: &SandboxPolicy, cwd: &Path, command: &[String]) -> ! {
+    if let Err(e) = apply_sandbox_policy_to_current_thread(sandbox_policy, cwd) {
+        panic!("error running landlock: {e:?}");
+    }
+    exec_command(command);
+}
+
+/// The syscalls the supervisor is handed, by syscall number.
+fn watched_syscalls(sandbox_policy: &SandboxPolicy) -> BTreeMap<i64, WatchedSyscall> {
+    let mut watched = BTreeMap::new();
+    if !sandbox_policy.has_full_network_access() {
+        for &(nr, name) in REPORTED_NETWORK_SYSCALLS {
+            let watch = Watch::Network;
+            watched.insert(nr, WatchedSyscall { name, watch });
+        }
+    }
+    if !sandbox_policy.has_full_disk_write_access() {
+        let mut write = |nr: i64, name: &'static str, dirfd, path, flags| {
+            let watch = Watch::Write { dirfd, path, flags };
+            watched.insert(nr, WatchedSyscall { name, watch });
+        };
+        write(libc::SYS_openat, "openat", Some(0), 1, Some(2));
+        write(libc::SYS_mkdirat, "mkdirat", Some(0), 1, None);
+        write(libc::SYS_mknodat, "mknodat", Some(0), 1, None);
+        write(libc::SYS_unlinkat, "unlinkat", Some(0), 1, None);
+        write(libc::SYS_renameat2, "renameat2", Some(2), 3, None);
+        write(libc::SYS_linkat, "linkat", Some(2), 3, None);
+        write(libc::SYS_symlinkat, "symlinkat", Some(1), 2, None);
+        write(libc::SYS_truncate, "truncate", None, 0, None);
+        // Older syscalls that aarch64 never had.
+        #[cfg(target_arch = "x86_64")]
+        {
+            write(libc::SYS_open, "open", None, 0, Some(1));
+            write(libc::SYS_creat, "creat", None, 0, None);
+            write(libc::SYS_mkdir, "mkdir", None, 0, None);
+            write(libc::SYS_mknod, "mknod", None, 0, None);
+            write(libc::SYS_rmdir, "rmdir", None, 0, None);
+            write(libc::SYS_unlink, "unlink", None, 0, None);
+            write(libc::SYS_rename, "rename", None, 1, None);
+            write(libc::SYS_renameat, "renameat", Some(2), 3, None);
+            write(libc::SYS_link, "link", None, 1, None);
+            write(libc::SYS_symlink, "symlink", None, 1, None);
+        }
+    }
+    watched
+}
+
+/// The filter that hands `watched` to the supervisor, and the one that
+/// refuses the rest of the network filter's syscalls with `EPERM`.
+fn compile_filters(
+    sandbox_policy: &SandboxPolicy,
+    watched: &BTreeMap<i64, WatchedSyscall>,
+) -> Result<(BpfProgram, Option<BpfProgram>), SandboxErr> {
+    let mut errno_rules = if sandbox_policy.has_full_network_access() {
+        BTreeMap::new()
+    } else {
+        network_seccomp_rules()?
+    };
+    let mut notify_rules = BTreeMap::new();
+    for (&nr, syscall) in watched {
+        let rules = match syscall.watch {
+            Watch::Network => errno_rules.remove(&nr).unwrap_or_default(),
+            Watch::Write {
+                flags: Some(flags), ..
+            } => write_flag_rules(flags)?,
+            Watch::Write { flags: None, .. } => Vec::new(),
+        };
+        notify_rules.insert(nr, rules);
+    }
+
+    let mut notify_prog =
+        compile_seccomp_filter(notify_rules, SeccompAction::Errno(libc::EPERM as u32))?;
+    notify_instead_of_eperm(&mut notify_prog);
+    let errno_prog = if errno_rules.is_empty() {
+        None
+    } else {
+        Some(compile_seccomp_filter(
+            errno_rules,
+            SeccompAction::Errno(libc::EPERM as u32),
+        )?)
+    };
+    Ok((notify_prog, errno_prog))
+}
+
+/// Rules matching open flags that ask for writing. Rules are alternatives,
+/// so each flag gets its own.
+fn write_flag_rules(arg: usize) -> Result<Vec<SeccompRule>, SandboxErr> {
+    [libc::O_WRONLY, libc::O_RDWR, libc::O_CREAT, libc::O_TRUNC]
+        .into_iter()
+        .map(|flag| {
+            let flag = flag as u64;
+            let condition = SeccompCondition::new(
+                arg as u8,
+                SeccompCmpArgLen::Dword,
+                SeccompCmpOp::MaskedEq(flag),
+                flag,
+            )?;
+            Ok(SeccompRule::new(vec![condition])?)
+        })
+        .collect()
+}
+
+/// seccompiler has no action for user notifications, so the filter is
+/// compiled to return `EPERM` and those returns are rewritten.
+fn notify_instead_of_eperm(prog: &mut BpfProgram) {
+    for insn in prog.iter_mut() {
+        if insn.code == BPF_RET_K && insn.k == SECCOMP_RET_ERRNO | libc::EPERM as u32 {
+            insn.k = SECCOMP_RET_USER_NOTIF;
+        }
+    }
+}
+
+fn socket_pair() -> std::io::Result<(OwnedFd, OwnedFd)> {
+    let mut fds = [0 as RawFd; 2];
+    let flags = libc::SOCK_STREAM | libc::SOCK_CLOEXEC;
+    if unsafe { libc::socketpair(libc::AF_UNIX, flags, 0, fds.as_mut_ptr()) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(unsafe { (OwnedFd::from_raw_fd(fds[0]), OwnedFd::from_raw_fd(fds[1])) })
+}
+
+/// Sandboxes this process, hands the supervisor the listener for its
+/// notifications and runs the command.
+fn run_child(
+    supervisor: libc::pid_t,
+    socket: OwnedFd,
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+    notify_prog: &BpfProgram,
+    errno_prog: Option<&BpfProgram>,
+    command: &[String],
+) -> ! {
+    // The command must not outlive the supervisor that answers for it.
+    unsafe {
+        libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL);
+        if libc::getppid() != supervisor {
+            libc::_exit(1);
+        }
+    }
+
+    if let Err(e) = apply_filesystem_policy_to_current_thread(sandbox_policy, cwd) {
+        panic!("error running landlock: {e:?}");
+    }
+    match install_notify_filter(notify_prog) {
+        Ok(listener) => {
+            // `sendmsg` is not watched, so this does not wait on the
+            // supervisor, which cannot answer before it has the listener.
+            if let Err(e) = send_fd(&socket, &listener) {
+                panic!("error sending the seccomp listener: {e}");
+            }
+            drop(listener);
+            if let Some(errno_prog) = errno_prog
+                && let Err(e) = apply_filter(errno_prog)
+            {
+                panic!("error running landlock: {e:?}");
+            }
+        }
+        // Kernels before 5.0 have no user notifications; the command runs
+        // without a report.
+        Err(_) => {
+            if !sandbox_policy.has_full_network_access()
+                && let Err(e) = install_network_seccomp_filter_on_current_thread()
+            {
+                panic!("error running landlock: {e:?}");
+            }
+        }
+    }
+    drop(socket);
+    exec_command(command);
+}
+
+fn install_notify_filter(prog: &BpfProgram) -> std::io::Result<OwnedFd> {
+    let fprog = SockFprog {
+        len: prog.len() as libc::c_ushort,
+        filter: prog.as_ptr().cast(),
+    };
+    let listener = unsafe {
+        if libc::prctl(libc::PR_SET_NO_NEW_PRIVS, 1, 0, 0, 0) != 0 {
+            return Err(std::io::Error::last_os_error());
+        }
+        libc::syscall(
+            libc::SYS_seccomp,
+            SECCOMP_SET_MODE_FILTER,
+            SECCOMP_FILTER_FLAG_NEW_LISTENER,
+            &fprog as *const SockFprog,
+        )
+    };
+    if listener < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(unsafe { OwnedFd::from_raw_fd(listener as RawFd) })
+}
+
+/// Control message buffer for one fd, aligned for `cmsghdr`.
+type FdControl = [u64; 4];
+
+fn send_fd(socket: &OwnedFd, fd: &OwnedFd) -> std::io::Result<()> {
+    let mut byte = [0u8; 1];
+    let mut iov = libc::iovec {
+        iov_base: byte.as_mut_ptr().cast(),
+        iov_len: byte.len(),
+    };
+    let mut control: FdControl = [0; 4];
+    let sent = unsafe {
+        let mut msg: libc::msghdr = std::mem::zeroed();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr().cast();
+        msg.msg_controllen = libc::CMSG_SPACE(size_of::<RawFd>() as u32) as _;
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        (*cmsg).cmsg_level = libc::SOL_SOCKET;
+        (*cmsg).cmsg_type = libc::SCM_RIGHTS;
+        (*cmsg).cmsg_len = libc::CMSG_LEN(size_of::<RawFd>() as u32) as _;
+        std::ptr::write_unaligned(libc::CMSG_DATA(cmsg).cast::<RawFd>(), fd.as_raw_fd());
+        libc::sendmsg(socket.as_raw_fd(), &msg, 0)
+    };
+    if sent < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+/// Receives the fd sent with [`send_fd`], or `None` if the child exited or
+/// ran without a listener.
+fn recv_fd(socket: &OwnedFd) -> Option<OwnedFd> {
+    let mut byte = [0u8; 1];
+    let mut iov = libc::iovec {
+        iov_base: byte.as_mut_ptr().cast(),
+        iov_len: byte.len(),
+    };
+    let mut control: FdControl = [0; 4];
+    unsafe {
+        let mut msg: libc::msghdr = std::mem::zeroed();
+        msg.msg_iov = &mut iov;
+        msg.msg_iovlen = 1;
+        msg.msg_control = control.as_mut_ptr().cast();
+        msg.msg_controllen = size_of::<FdControl>() as _;
+        loop {
+            let received = libc::recvmsg(socket.as_raw_fd(), &mut msg, libc::MSG_CMSG_CLOEXEC);
+            if received > 0 {
+                break;
+            }
+            if received == 0
+                || std::io::Error::last_os_error().kind() != std::io::ErrorKind::Interrupted
+            {
+                return None;
+            }
+        }
+        let cmsg = libc::CMSG_FIRSTHDR(&msg);
+        if cmsg.is_null()
+            || (*cmsg).cmsg_level != libc::SOL_SOCKET
+            || (*cmsg).cmsg_type != libc::SCM_RIGHTS
+        {
+            return None;
+        }
+        let fd = std::ptr::read_unaligned(libc::CMSG_DATA(cmsg).cast::<RawFd>());
+        Some(OwnedFd::from_raw_fd(fd))
+    }
+}
+
+/// Where Landlock lets the command write: the writable roots and
+/// `/dev/null`, as the file system sees them.
+fn writable_roots(sandbox_policy: &SandboxPolicy, cwd: &Path) -> Vec<PathBuf> {
+    sandbox_policy
+        .get_writable_roots_with_cwd(cwd)
+        .into_iter()
+        .map(|writable_root| {
+            let root = writable_root.root;
+            root.canonicalize().unwrap_or(root)
+        })
+        .chain([PathBuf::from("/dev/null")])
+        .collect()
+}
+
+struct Supervisor {
+    watched: BTreeMap<i64, WatchedSyscall>,
+    writable_roots: Vec<PathBuf>,
+    report: File,
+    recorded: Vec<SandboxDenial>,
+}
+
+impl Supervisor {
+    /// Answers the child's notifications until it exits, then exits the
+    /// same way.
+    fn run(&mut self, child: libc::pid_t, socket: OwnedFd) -> ! {
+        // Like `system(3)`: the terminal's interrupt is for the command.
+        unsafe {
+            libc::signal(libc::SIGINT, libc::SIG_IGN);
+            libc::signal(libc::SIGQUIT, libc::SIG_IGN);
+        }
+        let listener = recv_fd(&socket);
+        drop(socket);
+
+        let mut status: libc::c_int = 0;
+        loop {
+            let options = if listener.is_some() { libc::WNOHANG } else { 0 };
+            let waited = unsafe { libc::waitpid(child, &mut status, options) };
+            if waited == child {
+                break;
+            }
+            if waited < 0
+                && std::io::Error::last_os_error().kind() != std::io::ErrorKind::Interrupted
+            {
+                std::process::exit(1);
+            }
+            if let Some(listener) = &listener {
+                let mut pollfd = libc::pollfd {
+                    fd: listener.as_raw_fd(),
+                    events: libc::POLLIN,
+                    revents: 0,
+                };
+                let ready = unsafe { libc::poll(&mut pollfd, 1, POLL_INTERVAL_MS) };
+                if ready > 0 && pollfd.revents & libc::POLLIN != 0 {
+                    self.answer(listener);
+                }
+            }
+        }
+        exit_like(status);
+    }
+
+    fn answer(&mut self, listener: &OwnedFd) {
+        let listener = listener.as_raw_fd();
+        let mut request: SeccompNotif = unsafe { std::mem::zeroed() };
+        if unsafe {
+            libc::ioctl(
+                listener,
+                SECCOMP_IOCTL_NOTIF_RECV as _,
+                &mut request as *mut SeccompNotif,
+            )
+        } != 0
+        {
+            // The process was killed while it waited.
+            return;
+        }
+        let mut response = SeccompNotifResp {
+            id: request.id,
+            val: 0,
+            error: 0,
+            flags: 0,
+        };
+        let denial = match self.watched.get(&i64::from(request.data.nr)) {
+            Some(WatchedSyscall {
+                name,
+                watch: Watch::Network,
+            }) => {
+                response.error = -libc::EPERM;
+                Some(SandboxDenial::Network {
+                    syscall: name.to_string(),
+                })
+            }
+            Some(WatchedSyscall {
+                name,
+                watch: Watch::Write { dirfd, path, .. },
+            }) => {
+                response.flags = SECCOMP_USER_NOTIF_FLAG_CONTINUE;
+                let dirfd = dirfd.map(|arg| request.data.args[arg] as u32 as i32);
+                read_path(request.pid, request.data.args[*path])
+                    .and_then(|path| resolve_path(request.pid, dirfd, path))
+                    .filter(|path| !self.is_writable(path))
+                    .map(|path| SandboxDenial::Write {
+                        syscall: name.to_string(),
+                        path,
+                    })
+            }
+            None => {
+                response.flags = SECCOMP_USER_NOTIF_FLAG_CONTINUE;
+                None
+            }
+        };
+        // What was read from the process's memory only means something if
+        // it is still waiting on this syscall, rather than a new process
+        // having taken its pid.
+        let still_waiting = unsafe {
+            libc::ioctl(
+                listener,
+                SECCOMP_IOCTL_NOTIF_ID_VALID as _,
+                &request.id as *const u64,
+            )
+        } == 0;
+        unsafe {
+            libc::ioctl(
+                listener,
+                SECCOMP_IOCTL_NOTIF_SEND as _,
+                &mut response as *mut SeccompNotifResp,
+            );
+        }
+        if still_waiting && let Some(denial) = denial {
+            self.record(denial);
+        }
+    }
+
+    fn is_writable(&self, path: &Path) -> bool {
+        self.writable_roots
+            .iter()
+            .any(|root| path.starts_with(root))
+    }
+
+    fn record(&mut self, denial: SandboxDenial) {
+        if self.recorded.len() >= MAX_REPORTED_DENIALS || self.recorded.contains(&denial) {
+            return;
+        }
+        if let Ok(line) = serde_json::to_string(&denial) {
+            let _ = writeln!(self.report, "{line}");
+        }
+        self.recorded.push(denial);
+    }
+}
+
+/// Reads the NUL-terminated path at `addr` in the memory of process `pid`.
+fn read_path(pid: u32, addr: u64) -> Option<PathBuf> {
+    let mem = File::open(format!("/proc/{pid}/mem")).ok()?;
+    let mut bytes = Vec::new();
+    let mut addr = addr;
+    while bytes.len() < PATH_MAX {
+        let to_page_end = (PAGE_SIZE - addr % PAGE_SIZE) as usize;
+        let mut buf = vec![0u8; to_page_end.min(PATH_MAX - bytes.len())];
+        let n = mem.read_at(&mut buf, addr).ok()?;
+        if n == 0 {
+            return None;
+        }
+        if let Some(end) = buf[..n].iter().position(|&b| b == 0) {
+            bytes.extend_from_slice(&buf[..end]);
+            return Some(PathBuf::from(OsString::from_vec(bytes)));
+        }
+        bytes.extend_from_slice(&buf[..n]);
+        addr += n as u64;
+    }
+    None
+}
+
+/// The absolute path process `pid` names with `path`, relative to `dirfd`
+/// or to its working directory. The parent directory is canonicalized to
+/// match the writable roots; the path itself may not exist yet.
+fn resolve_path(pid: u32, dirfd: Option<i32>, path: PathBuf) -> Option<PathBuf> {
+    let path = if path.is_absolute() {
+        path
+    } else {
+        let base = match dirfd {
+            None | Some(libc::AT_FDCWD) => format!("/proc/{pid}/cwd"),
+            Some(fd) => format!("/proc/{pid}/fd/{fd}"),
+        };
+        std::fs::read_link(base).ok()?.join(path)
+    };
+    let path = normalize(&path);
+    let canonical = path
+        .parent()
+        .zip(path.file_name())
+        .and_then(|(parent, name)| Some(parent.canonicalize().ok()?.join(name)));
+    Some(canonical.unwrap_or(path))
+}
+
+/// Removes `.` and `..` without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+/// Exits with the child's exit code, or by the signal that killed it.
+fn exit_like(status: libc::c_int) -> ! {
+    if libc::WIFSIGNALED(status) {
+        let signal = libc::WTERMSIG(status);
+        unsafe {
+            libc::signal(signal, libc::SIG_DFL);
+            libc::raise(signal);
+        }
+        std::process::exit(128 + signal);
+    }
+    std::process::exit(libc::WEXITSTATUS(status));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn watched_syscalls_notify_instead_of_failing() {
+        let policy = SandboxPolicy::new_read_only_policy();
+        let watched = watched_syscalls(&policy);
+        let (notify_prog, errno_prog) = compile_filters(&policy, &watched).unwrap();
+
+        let returns = |prog: &BpfProgram, k: u32| {
+            prog.iter()
+                .filter(|insn| insn.code == BPF_RET_K && insn.k == k)
+                .count()
+        };
+        let eperm = SECCOMP_RET_ERRNO | libc::EPERM as u32;
+        assert!(returns(&notify_prog, SECCOMP_RET_USER_NOTIF) > 0);
+        assert_eq!(returns(&notify_prog, eperm), 0);
+        // `sendmsg` and the like are still refused, but by the other filter.
+        let errno_prog = errno_prog.unwrap();
+        assert!(returns(&errno_prog, eperm) > 0);
+        assert_eq!(returns(&errno_prog, SECCOMP_RET_USER_NOTIF), 0);
+    }
+
+    #[test]
+    fn normalize_resolves_dots_lexically() {
+        assert_eq!(
+            normalize(Path::new("/repo/./src/../../etc/hosts")),
+            PathBuf::from("/etc/hosts")
+        );
+        assert_eq!(normalize(Path::new("/..")), PathBuf::from("/"));
+    }
+}
@@ -35,6 +35,14 @@ pub(crate) fn apply_sandbox_policy_to_current_thread(
         install_network_seccomp_filter_on_current_thread()?;
     }
 
+    apply_filesystem_policy_to_current_thread(sandbox_policy, cwd)
+}
+
+/// The Landlock half of [`apply_sandbox_policy_to_current_thread`].
+pub(crate) fn apply_filesystem_policy_to_current_thread(
+    sandbox_policy: &SandboxPolicy,
+    cwd: &Path,
+) -> Result<()> {
     if !sandbox_policy.has_full_disk_write_access() {
         let writable_roots = sandbox_policy
             .get_writable_roots_with_cwd(cwd)
@@ -113,7 +121,21 @@ fn install_read_blocklist_landlock_rules_on_current_thread(blocklist: &[PathBuf]
 
 /// Installs a seccomp filter that blocks outbound network access except for
 /// AF_UNIX domain sockets.
-fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(), SandboxErr> {
+pub(crate) fn install_network_seccomp_filter_on_current_thread()
+-> std::result::Result<(), SandboxErr> {
+    let prog = compile_seccomp_filter(
+        network_seccomp_rules()?,
+        SeccompAction::Errno(libc::EPERM as u32),
+    )?;
+
+    apply_filter(&prog)?;
+
+    Ok(())
+}
+
+/// The syscalls the network filter denies, keyed by syscall number.
+pub(crate) fn network_seccomp_rules()
+-> std::result::Result<BTreeMap<i64, Vec<SeccompRule>>, SandboxErr> {
     // Build rule map.
     let mut rules: BTreeMap<i64, Vec<SeccompRule>> = BTreeMap::new();
 
@@ -153,10 +175,19 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
     rules.insert(libc::SYS_socket, vec![unix_only_rule.clone()]);
     rules.insert(libc::SYS_socketpair, vec![unix_only_rule]); // always deny (Unix can use socketpair but fine, keep open?)
 
+    Ok(rules)
+}
+
+/// Compiles a filter that allows every syscall except those matching
+/// `rules`, which get `match_action`.
+pub(crate) fn compile_seccomp_filter(
+    rules: BTreeMap<i64, Vec<SeccompRule>>,
+    match_action: SeccompAction,
+) -> std::result::Result<BpfProgram, SandboxErr> {
     let filter = SeccompFilter::new(
         rules,
-        SeccompAction::Allow,                     // default – allow
-        SeccompAction::Errno(libc::EPERM as u32), // when rule matches – return EPERM
+        SeccompAction::Allow, // default – allow
+        match_action,         // when rule matches
         if cfg!(target_arch = "x86_64") {
             TargetArch::x86_64
         } else if cfg!(target_arch = "aarch64") {
@@ -166,9 +197,5 @@ fn install_network_seccomp_filter_on_current_thread() -> std::result::Result<(),
         },
     )?;
 
-    let prog: BpfProgram = filter.try_into()?;
-
-    apply_filter(&prog)?;
-
-    Ok(())
+    Ok(filter.try_into()?)
 }
@@ -2,10 +2,16 @@ use clap::Parser;
 use std::ffi::CString;
 use std::path::PathBuf;
 
+use crate::denials::run_with_denial_report;
 use crate::landlock::apply_sandbox_policy_to_current_thread;
 
 #[derive(Debug, Parser)]
 pub struct LandlockCommand {
+    /// Record what the sandbox refuses the command in this file, one JSON
+    /// `SandboxDenial` per line.
+    #[arg(long = "report-denials", value_name = "FILE")]
+    pub report_denials: Option<PathBuf>,
+
     /// It is possible that the cwd used in the context of the sandbox policy
     /// is different from the cwd of the process to spawn.
     pub sandbox_policy_cwd: PathBuf,
@@ -19,19 +25,34 @@ pub struct LandlockCommand {
 
 pub fn run_main() -> ! {
     let LandlockCommand {
+        report_denials,
         sandbox_policy_cwd,
         sandbox_policy,
         command,
     } = LandlockCommand::parse();
 
+    if command.is_empty() {
+        panic!("No command specified to execute.");
+    }
+
+    if let Some(report_denials) = report_denials {
+        run_with_denial_report(
+            &sandbox_policy,
+            &sandbox_policy_cwd,
+            &report_denials,
+            &command,
+        );
+    }
+
     if let Err(e) = apply_sandbox_policy_to_current_thread(&sandbox_policy, &sandbox_policy_cwd) {
         panic!("error running landlock: {e:?}");
     }
 
-    if command.is_empty() {
-        panic!("No command specified to execute.");
-    }
+    exec_command(&command);
+}
 
+/// Replaces this process with `command`.
+pub(crate) fn exec_command(command: &[String]) -> ! {
     #[expect(clippy::expect_used)]
     let c_command =
         CString::new(command[0].as_str()).expect("Failed to convert command to CString");
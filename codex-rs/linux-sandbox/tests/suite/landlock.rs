@@ -1,12 +1,16 @@
 #![cfg(target_os = "linux")]
+use codex_core::config_types::ExecLimits;
 use codex_core::config_types::ShellEnvironmentPolicy;
 use codex_core::error::CodexErr;
 use codex_core::error::SandboxErr;
 use codex_core::exec::ExecParams;
+use codex_core::exec::ExecSandboxOptions;
 use codex_core::exec::SandboxType;
 use codex_core::exec::process_exec_tool_call;
 use codex_core::exec_env::create_env;
+use codex_core::protocol::SandboxDenial;
 use codex_core::protocol::SandboxPolicy;
+use codex_core::seatbelt::SeatbeltExtensions;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use tempfile::NamedTempFile;
@@ -58,9 +62,14 @@ async fn run_cmd(cmd: &[&str], writable_roots: &[PathBuf], timeout_ms: u64) {
     let codex_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
     let res = process_exec_tool_call(
         params,
-        SandboxType::LinuxSeccomp,
         &sandbox_policy,
-        &codex_linux_sandbox_exe,
+        ExecSandboxOptions {
+            sandbox_type: SandboxType::LinuxSeccomp,
+            codex_linux_sandbox_exe: &codex_linux_sandbox_exe,
+            container_config: None,
+            seatbelt_extensions: &SeatbeltExtensions::default(),
+            limits: &ExecLimits::default(),
+        },
         None,
     )
     .await
@@ -121,6 +130,46 @@ async fn test_writable_root() {
     .await;
 }
 
+#[tokio::test]
+async fn test_denied_write_is_reported() {
+    let tmpdir = tempfile::tempdir().unwrap();
+    let file_path = tmpdir.path().canonicalize().unwrap().join("test");
+    let params = ExecParams {
+        command: vec!["touch".to_string(), file_path.to_string_lossy().to_string()],
+        cwd: std::env::current_dir().unwrap(),
+        timeout_ms: Some(LONG_TIMEOUT_MS),
+        env: create_env_from_core_vars(),
+        with_escalated_permissions: None,
+        justification: None,
+    };
+    let sandbox_program = env!("CARGO_BIN_EXE_codex-linux-sandbox");
+    let codex_linux_sandbox_exe = Some(PathBuf::from(sandbox_program));
+    let result = process_exec_tool_call(
+        params,
+        &SandboxPolicy::new_read_only_policy(),
+        ExecSandboxOptions {
+            sandbox_type: SandboxType::LinuxSeccomp,
+            codex_linux_sandbox_exe: &codex_linux_sandbox_exe,
+            container_config: None,
+            seatbelt_extensions: &SeatbeltExtensions::default(),
+            limits: &ExecLimits::default(),
+        },
+        None,
+    )
+    .await;
+
+    let Err(CodexErr::Sandbox(SandboxErr::Denied(_, _, _, denials))) = result else {
+        panic!("expected sandbox denied error, got: {result:?}");
+    };
+    assert!(
+        denials.iter().any(|denial| matches!(
+            denial,
+            SandboxDenial::Write { path, .. } if *path == file_path
+        )),
+        "{denials:?}"
+    );
+}
+
 #[tokio::test]
 #[should_panic(expected = "Sandbox(Timeout)")]
 async fn test_timeout() {
@@ -150,16 +199,21 @@ async fn assert_network_blocked(cmd: &[&str]) {
     let codex_linux_sandbox_exe: Option<PathBuf> = Some(PathBuf::from(sandbox_program));
     let result = process_exec_tool_call(
         params,
-        SandboxType::LinuxSeccomp,
         &sandbox_policy,
-        &codex_linux_sandbox_exe,
+        ExecSandboxOptions {
+            sandbox_type: SandboxType::LinuxSeccomp,
+            codex_linux_sandbox_exe: &codex_linux_sandbox_exe,
+            container_config: None,
+            seatbelt_extensions: &SeatbeltExtensions::default(),
+            limits: &ExecLimits::default(),
+        },
         None,
     )
     .await;
 
     let (exit_code, stdout, stderr) = match result {
         Ok(output) => (output.exit_code, output.stdout.text, output.stderr.text),
-        Err(CodexErr::Sandbox(SandboxErr::Denied(exit_code, stdout, stderr))) => {
+        Err(CodexErr::Sandbox(SandboxErr::Denied(exit_code, stdout, stderr, _))) => {
             (exit_code, stdout, stderr)
         }
         _ => {
@@ -262,9 +316,14 @@ async fn read_blocklist_blocks_file() {
     };
     let ok = process_exec_tool_call(
         params_ok,
-        SandboxType::LinuxSeccomp,
         &sandbox_policy,
-        &codex_linux_sandbox_exe,
+        ExecSandboxOptions {
+            sandbox_type: SandboxType::LinuxSeccomp,
+            codex_linux_sandbox_exe: &codex_linux_sandbox_exe,
+            container_config: None,
+            seatbelt_extensions: &SeatbeltExtensions::default(),
+            limits: &ExecLimits::default(),
+        },
         None,
     )
     .await
@@ -282,9 +341,14 @@ async fn read_blocklist_blocks_file() {
     };
     let blocked = process_exec_tool_call(
         params_block,
-        SandboxType::LinuxSeccomp,
         &sandbox_policy,
-        &codex_linux_sandbox_exe,
+        ExecSandboxOptions {
+            sandbox_type: SandboxType::LinuxSeccomp,
+            codex_linux_sandbox_exe: &codex_linux_sandbox_exe,
+            container_config: None,
+            seatbelt_extensions: &SeatbeltExtensions::default(),
+            limits: &ExecLimits::default(),
+        },
         None,
     )
     .await
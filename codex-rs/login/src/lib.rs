@@ -166,6 +166,12 @@ impl CodexAuth {
             .and_then(|t| t.id_token.chatgpt_plan_type.as_ref().map(|p| p.as_string()))
     }
 
+    /// When the current access token expires, if signed in with ChatGPT.
+    pub fn access_token_expires_at(&self) -> Option<DateTime<Utc>> {
+        self.get_current_token_data()
+            .and_then(|t| t.access_token_expires_at())
+    }
+
     fn get_current_auth_json(&self) -> Option<AuthDotJson> {
         #[expect(clippy::unwrap_used)]
         self.auth_dot_json.lock().unwrap().clone()
@@ -1,4 +1,6 @@
 use base64::Engine;
+use chrono::DateTime;
+use chrono::Utc;
 use serde::Deserialize;
 use serde::Serialize;
 use thiserror::Error;
@@ -44,6 +46,11 @@ impl TokenData {
             .is_none_or(|plan| plan.is_plan_that_should_use_api_key())
     }
 
+    /// When the access token expires, from its `exp` claim.
+    pub fn access_token_expires_at(&self) -> Option<DateTime<Utc>> {
+        jwt_expiry(&self.access_token)
+    }
+
     pub fn is_openai_email(&self) -> bool {
         self.id_token
             .email
@@ -155,6 +162,20 @@ pub(crate) fn parse_id_token(id_token: &str) -> Result<IdTokenInfo, IdTokenInfoE
     })
 }
 
+fn jwt_expiry(jwt: &str) -> Option<DateTime<Utc>> {
+    #[derive(Deserialize)]
+    struct ExpClaim {
+        exp: i64,
+    }
+
+    let payload_b64 = jwt.split('.').nth(1)?;
+    let payload_bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+        .decode(payload_b64)
+        .ok()?;
+    let claim: ExpClaim = serde_json::from_slice(&payload_bytes).ok()?;
+    DateTime::from_timestamp(claim.exp, 0)
+}
+
 fn deserialize_id_token<'de, D>(deserializer: D) -> Result<IdTokenInfo, D::Error>
 where
     D: serde::Deserializer<'de>,
@@ -209,4 +230,14 @@ mod tests {
             Some(PlanType::Known(KnownPlan::Pro))
         );
     }
+
+    #[test]
+    fn jwt_expiry_reads_exp_claim() {
+        let payload = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .encode(br#"{"exp":1700000000,"sub":"user"}"#);
+        let jwt = format!("e30.{payload}.sig");
+
+        assert_eq!(jwt_expiry(&jwt), DateTime::from_timestamp(1_700_000_000, 0));
+        assert_eq!(jwt_expiry("not-a-jwt"), None);
+    }
 }
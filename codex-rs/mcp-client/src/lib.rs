@@ -1,3 +1,12 @@
 mod mcp_client;
+mod reconnect;
+mod schema;
+mod server_requests;
+mod tool_call;
 
 pub use mcp_client::McpClient;
+pub use reconnect::ConnectionState;
+pub use reconnect::ReconnectPolicy;
+pub use schema::SchemaViolation;
+pub use tool_call::ToolCallError;
+pub use tool_call::validate_tool_arguments;
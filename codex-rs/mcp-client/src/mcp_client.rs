@@ -5,7 +5,11 @@
 //!      communicates over stdio.
 //!   2. Sending MCP requests and pairing them with their corresponding
 //!      responses.
-//!   3. Offering a convenience helper for the common `tools/list` request.
+//!   3. Answering the requests the server sends back: `roots/list` and
+//!      `sampling/createMessage` when the owner configures them.
+//!   4. Offering convenience helpers for the common `tools/list` and
+//!      `tools/call` requests, including [`McpClient::call_tool_typed`], which
+//!      checks both sides of a call against the tool's declared schemas.
 //!
 //! The crate hides all JSON‐RPC framing details behind a typed API. Users
 //! interact with the [`ModelContextProtocolRequest`] trait from `mcp-types` to
@@ -13,6 +17,7 @@
 
 use std::collections::HashMap;
 use std::ffi::OsString;
+use std::future::Future;
 use std::sync::Arc;
 use std::sync::atomic::AtomicI64;
 use std::sync::atomic::Ordering;
@@ -23,6 +28,8 @@ use anyhow::Result;
 use anyhow::anyhow;
 use mcp_types::CallToolRequest;
 use mcp_types::CallToolRequestParams;
+use mcp_types::CreateMessageRequestParams;
+use mcp_types::CreateMessageResult;
 use mcp_types::InitializeRequest;
 use mcp_types::InitializeRequestParams;
 use mcp_types::InitializedNotification;
@@ -37,6 +44,9 @@ use mcp_types::ListToolsResult;
 use mcp_types::ModelContextProtocolNotification;
 use mcp_types::ModelContextProtocolRequest;
 use mcp_types::RequestId;
+use mcp_types::Root;
+use mcp_types::RootsListChangedNotification;
+use mcp_types::Tool;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
 use tokio::io::AsyncBufReadExt;
@@ -52,30 +62,86 @@ use tracing::error;
 use tracing::info;
 use tracing::warn;
 
+use crate::reconnect::ConnectionState;
+use crate::reconnect::ConnectionStateCallback;
+use crate::reconnect::ReconnectPolicy;
+use crate::reconnect::is_replayable;
+use crate::server_requests::ClientHandlers;
+use crate::server_requests::spawn_server_request_handler;
+use crate::tool_call::ToolCallError;
+use crate::tool_call::checked_structured_content;
+use crate::tool_call::validate_tool_arguments;
+
 /// Capacity of the bounded channels used for transporting messages between the
 /// client API and the IO tasks.
 const CHANNEL_CAPACITY: usize = 128;
 
-/// Internal representation of a pending request sender.
-type PendingSender = oneshot::Sender<JSONRPCMessage>;
+/// Timeout for each request of the handshake with a respawned server.
+const RECONNECT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
 
-/// A running MCP client instance.
-pub struct McpClient {
-    /// Retain this child process until the client is dropped. The Tokio runtime
-    /// will make a "best effort" to reap the process after it exits, but it is
-    /// not a guarantee. See the `kill_on_drop` documentation for details.
-    #[allow(dead_code)]
+/// A request that is waiting for its response.
+struct PendingRequest {
+    tx: oneshot::Sender<JSONRPCMessage>,
+    /// The request as sent, kept so it can be resent to a respawned server.
+    request: JSONRPCRequest,
+}
+
+/// Map of `request.id -> PendingRequest` used to dispatch responses back to
+/// the originating caller.
+type PendingMap = Arc<Mutex<HashMap<i64, PendingRequest>>>;
+
+/// How to spawn the server process, kept so it can be respawned.
+struct ServerCommand {
+    program: OsString,
+    args: Vec<OsString>,
+    env: Option<HashMap<String, String>>,
+}
+
+/// One running server process.
+struct Connection {
+    /// Retain this child process until the connection is dropped. The Tokio
+    /// runtime will make a "best effort" to reap the process after it exits,
+    /// but it is not a guarantee. See the `kill_on_drop` documentation for
+    /// details.
     child: tokio::process::Child,
 
     /// Channel for sending JSON-RPC messages *to* the background writer task.
     outgoing_tx: mpsc::Sender<JSONRPCMessage>,
 
-    /// Map of `request.id -> oneshot::Sender` used to dispatch responses back
-    /// to the originating caller.
-    pending: Arc<Mutex<HashMap<i64, PendingSender>>>,
+    /// Incremented for every spawned process so a stale exit notification
+    /// can be told apart from one for the current process.
+    generation: u64,
+
+    /// Cleared once the server is gone for good.
+    open: bool,
+}
+
+/// A running MCP client instance.
+pub struct McpClient {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    command: ServerCommand,
+
+    /// The current server process. Held while a request is registered and
+    /// written, and for the whole of a reconnect, so no request can slip
+    /// between the old process and the new one.
+    connection: Mutex<Connection>,
+
+    pending: PendingMap,
 
     /// Monotonically increasing counter used to generate request IDs.
     id_counter: AtomicI64,
+
+    /// Parameters of the `initialize` handshake, repeated after a reconnect.
+    handshake: std::sync::Mutex<Option<(InitializeRequestParams, Option<serde_json::Value>)>>,
+
+    reconnect_policy: std::sync::Mutex<Option<ReconnectPolicy>>,
+
+    on_state_change: std::sync::Mutex<Option<ConnectionStateCallback>>,
+
+    handlers: ClientHandlers,
 }
 
 impl McpClient {
@@ -87,104 +153,72 @@ impl McpClient {
         args: Vec<OsString>,
         env: Option<HashMap<String, String>>,
     ) -> std::io::Result<Self> {
-        let mut child = Command::new(program)
-            .args(args)
-            .env_clear()
-            .envs(create_env_for_mcp_server(env))
-            .stdin(std::process::Stdio::piped())
-            .stdout(std::process::Stdio::piped())
-            .stderr(std::process::Stdio::null())
-            // As noted in the `kill_on_drop` documentation, the Tokio runtime makes
-            // a "best effort" to reap-after-exit to avoid zombie processes, but it
-            // is not a guarantee.
-            .kill_on_drop(true)
-            .spawn()?;
-
-        let stdin = child
-            .stdin
-            .take()
-            .ok_or_else(|| std::io::Error::other("failed to capture child stdin"))?;
-        let stdout = child
-            .stdout
-            .take()
-            .ok_or_else(|| std::io::Error::other("failed to capture child stdout"))?;
-
-        let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
-        let pending: Arc<Mutex<HashMap<i64, PendingSender>>> = Arc::new(Mutex::new(HashMap::new()));
-
-        // Spawn writer task. It listens on the `outgoing_rx` channel and
-        // writes messages to the child's STDIN.
-        let writer_handle = {
-            let mut stdin = stdin;
-            tokio::spawn(async move {
-                while let Some(msg) = outgoing_rx.recv().await {
-                    match serde_json::to_string(&msg) {
-                        Ok(json) => {
-                            debug!("MCP message to server: {json}");
-                            if stdin.write_all(json.as_bytes()).await.is_err() {
-                                error!("failed to write message to child stdin");
-                                break;
-                            }
-                            if stdin.write_all(b"\n").await.is_err() {
-                                error!("failed to write newline to child stdin");
-                                break;
-                            }
-                            if stdin.flush().await.is_err() {
-                                error!("failed to flush child stdin");
-                                break;
-                            }
-                        }
-                        Err(e) => error!("failed to serialize JSONRPCMessage: {e}"),
-                    }
-                }
-            })
-        };
+        let command = ServerCommand { program, args, env };
+        let pending = PendingMap::default();
+        let handlers = ClientHandlers::default();
+        let (connection, closed) = spawn_connection(&command, &pending, &handlers, 0)?;
+
+        let inner = Arc::new(Inner {
+            command,
+            connection: Mutex::new(connection),
+            pending,
+            id_counter: AtomicI64::new(1),
+            handshake: std::sync::Mutex::new(None),
+            reconnect_policy: std::sync::Mutex::new(None),
+            on_state_change: std::sync::Mutex::new(None),
+            handlers,
+        });
+        watch_connection(&inner, 0, closed);
+
+        Ok(Self { inner })
+    }
 
-        // Spawn reader task. It reads line-delimited JSON from the child's
-        // STDOUT and dispatches responses to the pending map.
-        let reader_handle = {
-            let pending = pending.clone();
-            let mut lines = BufReader::new(stdout).lines();
-
-            tokio::spawn(async move {
-                while let Ok(Some(line)) = lines.next_line().await {
-                    debug!("MCP message from server: {line}");
-                    match serde_json::from_str::<JSONRPCMessage>(&line) {
-                        Ok(JSONRPCMessage::Response(resp)) => {
-                            Self::dispatch_response(resp, &pending).await;
-                        }
-                        Ok(JSONRPCMessage::Error(err)) => {
-                            Self::dispatch_error(err, &pending).await;
-                        }
-                        Ok(JSONRPCMessage::Notification(JSONRPCNotification { .. })) => {
-                            // For now we only log server-initiated notifications.
-                            info!("<- notification: {}", line);
-                        }
-                        Ok(other) => {
-                            // Batch responses and requests are currently not
-                            // expected from the server – log and ignore.
-                            info!("<- unhandled message: {:?}", other);
-                        }
-                        Err(e) => {
-                            error!("failed to deserialize JSONRPCMessage: {e}; line = {}", line)
-                        }
-                    }
-                }
-            })
-        };
+    /// Respawn the server when it exits instead of failing every request from
+    /// then on. Reconnection is disabled until this is called.
+    pub fn set_reconnect_policy(&self, policy: ReconnectPolicy) {
+        *lock(&self.inner.reconnect_policy) = Some(policy);
+    }
 
-        // We intentionally *detach* the tasks. They will keep running in the
-        // background as long as their respective resources (channels/stdin/
-        // stdout) are alive. Dropping `McpClient` cancels the tasks due to
-        // dropped resources.
-        let _ = (writer_handle, reader_handle);
+    /// Register a callback for changes of the connection to the server. It
+    /// runs on the client's background task and must not block.
+    pub fn on_connection_state_change<F>(&self, callback: F)
+    where
+        F: Fn(&ConnectionState) + Send + Sync + 'static,
+    {
+        *lock(&self.inner.on_state_change) = Some(Arc::new(callback));
+    }
 
-        Ok(Self {
-            child,
-            outgoing_tx,
-            pending,
-            id_counter: AtomicI64::new(1),
-        })
+    /// Answer `sampling/createMessage` requests from the server with
+    /// `handler`. An `Err` is reported to the server as a rejected request.
+    /// The owner is responsible for advertising the `sampling` capability
+    /// in [`initialize`](Self::initialize).
+    pub fn set_sampling_handler<F, Fut>(&self, handler: F)
+    where
+        F: Fn(CreateMessageRequestParams) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = std::result::Result<CreateMessageResult, String>> + Send + 'static,
+    {
+        *lock(&self.inner.handlers.sampling) =
+            Some(Arc::new(move |params| Box::pin(handler(params))));
+    }
+
+    /// Set the roots reported to the server in answer to `roots/list`. Until
+    /// this is called the client does not support `roots/list`; the owner is
+    /// responsible for advertising the `roots` capability in
+    /// [`initialize`](Self::initialize). Changing the roots after the
+    /// handshake notifies the server with
+    /// `notifications/roots/list_changed`.
+    pub async fn set_roots(&self, roots: Vec<Root>) -> Result<()> {
+        let changed = {
+            let mut current = lock(&self.inner.handlers.roots);
+            let changed = current.as_ref().is_some_and(|current| *current != roots);
+            *current = Some(roots);
+            changed
+        };
+        if changed && lock(&self.inner.handshake).is_some() {
+            self.send_notification::<RootsListChangedNotification>(None)
+                .await?;
+        }
+        Ok(())
     }
 
     /// Send an arbitrary MCP request and await the typed result.
@@ -201,10 +235,106 @@ impl McpClient {
         R: ModelContextProtocolRequest,
         R::Params: Serialize,
         R::Result: DeserializeOwned,
+    {
+        let (id, request) = self.inner.build_request::<R>(params)?;
+        let rx = {
+            let connection = self.inner.connection.lock().await;
+            if !connection.open {
+                return Err(anyhow!("connection to the MCP server is closed"));
+            }
+            self.inner
+                .send_on(&connection.outgoing_tx, id, request)
+                .await?
+        };
+        self.inner.await_response::<R>(id, rx, timeout).await
+    }
+
+    pub async fn send_notification<N>(&self, params: N::Params) -> Result<()>
+    where
+        N: ModelContextProtocolNotification,
+        N::Params: Serialize,
+    {
+        let connection = self.inner.connection.lock().await;
+        if !connection.open {
+            return Err(anyhow!("connection to the MCP server is closed"));
+        }
+        send_notification_on::<N>(&connection.outgoing_tx, params).await
+    }
+
+    /// Negotiates the initialization with the MCP server. Sends an `initialize`
+    /// request with the specified `initialize_params` and then the
+    /// `notifications/initialized` notification once the response has been
+    /// received. Returns the response to the `initialize` request.
+    pub async fn initialize(
+        &self,
+        initialize_params: InitializeRequestParams,
+        initialize_notification_params: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<mcp_types::InitializeResult> {
+        *lock(&self.inner.handshake) = Some((
+            initialize_params.clone(),
+            initialize_notification_params.clone(),
+        ));
+        let response = self
+            .send_request::<InitializeRequest>(initialize_params, timeout)
+            .await?;
+        self.send_notification::<InitializedNotification>(initialize_notification_params)
+            .await?;
+        Ok(response)
+    }
+
+    /// Convenience wrapper around `tools/list`.
+    pub async fn list_tools(
+        &self,
+        params: Option<ListToolsRequestParams>,
+        timeout: Option<Duration>,
+    ) -> Result<ListToolsResult> {
+        self.send_request::<ListToolsRequest>(params, timeout).await
+    }
+
+    /// Convenience wrapper around `tools/call`.
+    pub async fn call_tool(
+        &self,
+        name: String,
+        arguments: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<mcp_types::CallToolResult> {
+        let params = CallToolRequestParams { name, arguments };
+        debug!("MCP tool call: {params:?}");
+        self.send_request::<CallToolRequest>(params, timeout).await
+    }
+
+    /// Calls `tool` and deserializes its structured content into `T`.
+    ///
+    /// `arguments` are checked against the tool's input schema before the
+    /// request is sent, and the structured content against its output schema
+    /// (when it declares one) before deserializing.
+    pub async fn call_tool_typed<T: DeserializeOwned>(
+        &self,
+        tool: &Tool,
+        arguments: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> std::result::Result<T, ToolCallError> {
+        validate_tool_arguments(tool, arguments.as_ref())?;
+        let result = self
+            .call_tool(tool.name.clone(), arguments, timeout)
+            .await?;
+        let content = checked_structured_content(tool, result)?;
+        serde_json::from_value(content).map_err(|source| ToolCallError::Deserialize {
+            tool: tool.name.clone(),
+            source,
+        })
+    }
+}
+
+impl Inner {
+    fn build_request<R>(&self, params: R::Params) -> Result<(i64, JSONRPCRequest)>
+    where
+        R: ModelContextProtocolRequest,
+        R::Params: Serialize,
     {
         // Create a new unique ID.
         let id = self.id_counter.fetch_add(1, Ordering::SeqCst);
-        let request_id = RequestId::Integer(id);
 
         // Serialize params -> JSON. For many request types `Params` is
         // `Option<T>` and `None` should be encoded as *absence* of the field.
@@ -215,15 +345,23 @@ impl McpClient {
             Some(params_json)
         };
 
-        let jsonrpc_request = JSONRPCRequest {
-            id: request_id.clone(),
+        let request = JSONRPCRequest {
+            id: RequestId::Integer(id),
             jsonrpc: JSONRPC_VERSION.to_string(),
             method: R::METHOD.to_string(),
             params: params_field,
         };
+        Ok((id, request))
+    }
 
-        let message = JSONRPCMessage::Request(jsonrpc_request);
-
+    /// Registers `request` as pending and hands it to the writer task behind
+    /// `outgoing_tx`. Returns the channel its response will arrive on.
+    async fn send_on(
+        &self,
+        outgoing_tx: &mpsc::Sender<JSONRPCMessage>,
+        id: i64,
+        request: JSONRPCRequest,
+    ) -> Result<oneshot::Receiver<JSONRPCMessage>> {
         // oneshot channel for the response.
         let (tx, rx) = oneshot::channel();
 
@@ -231,27 +369,49 @@ impl McpClient {
         // the response arrives immediately cannot be lost.
         {
             let mut guard = self.pending.lock().await;
-            guard.insert(id, tx);
+            guard.insert(
+                id,
+                PendingRequest {
+                    tx,
+                    request: request.clone(),
+                },
+            );
         }
 
         // Send to writer task.
-        if self.outgoing_tx.send(message).await.is_err() {
+        if outgoing_tx
+            .send(JSONRPCMessage::Request(request))
+            .await
+            .is_err()
+        {
+            self.pending.lock().await.remove(&id);
             return Err(anyhow!(
                 "failed to send message to writer task - channel closed"
             ));
         }
+        Ok(rx)
+    }
 
-        // Await the response, optionally bounded by a timeout.
+    async fn await_response<R>(
+        &self,
+        id: i64,
+        rx: oneshot::Receiver<JSONRPCMessage>,
+        timeout: Option<Duration>,
+    ) -> Result<R::Result>
+    where
+        R: ModelContextProtocolRequest,
+        R::Result: DeserializeOwned,
+    {
+        // Await the response, optionally bounded by a timeout. The channel
+        // only closes without a reply when the server went away and the
+        // request could not be replayed.
         let msg = match timeout {
             Some(duration) => {
                 match time::timeout(duration, rx).await {
                     Ok(Ok(msg)) => msg,
                     Ok(Err(_)) => {
-                        // Channel closed without a reply – remove the pending entry.
-                        let mut guard = self.pending.lock().await;
-                        guard.remove(&id);
                         return Err(anyhow!(
-                            "response channel closed before a reply was received"
+                            "connection to the MCP server was lost before a reply was received"
                         ));
                     }
                     Err(_) => {
@@ -262,9 +422,9 @@ impl McpClient {
                     }
                 }
             }
-            None => rx
-                .await
-                .map_err(|_| anyhow!("response channel closed before a reply was received"))?,
+            None => rx.await.map_err(|_| {
+                anyhow!("connection to the MCP server was lost before a reply was received")
+            })?,
         };
 
         match msg {
@@ -283,108 +443,140 @@ impl McpClient {
         }
     }
 
-    pub async fn send_notification<N>(&self, params: N::Params) -> Result<()>
+    /// Sends a request to a connection that is not installed yet, which is
+    /// how the handshake with a respawned server is done.
+    async fn request_via<R>(
+        &self,
+        outgoing_tx: &mpsc::Sender<JSONRPCMessage>,
+        params: R::Params,
+    ) -> Result<R::Result>
     where
-        N: ModelContextProtocolNotification,
-        N::Params: Serialize,
+        R: ModelContextProtocolRequest,
+        R::Params: Serialize,
+        R::Result: DeserializeOwned,
     {
-        // Serialize params -> JSON. For many request types `Params` is
-        // `Option<T>` and `None` should be encoded as *absence* of the field.
-        let params_json = serde_json::to_value(&params)?;
-        let params_field = if params_json.is_null() {
-            None
-        } else {
-            Some(params_json)
-        };
-
-        let method = N::METHOD.to_string();
-        let jsonrpc_notification = JSONRPCNotification {
-            jsonrpc: JSONRPC_VERSION.to_string(),
-            method: method.clone(),
-            params: params_field,
-        };
-
-        let notification = JSONRPCMessage::Notification(jsonrpc_notification);
-        self.outgoing_tx
-            .send(notification)
+        let (id, request) = self.build_request::<R>(params)?;
+        let rx = self.send_on(outgoing_tx, id, request).await?;
+        self.await_response::<R>(id, rx, Some(RECONNECT_HANDSHAKE_TIMEOUT))
             .await
-            .with_context(|| format!("failed to send notification `{method}` to writer task"))
     }
 
-    /// Negotiates the initialization with the MCP server. Sends an `initialize`
-    /// request with the specified `initialize_params` and then the
-    /// `notifications/initialized` notification once the response has been
-    /// received. Returns the response to the `initialize` request.
-    pub async fn initialize(
-        &self,
-        initialize_params: InitializeRequestParams,
-        initialize_notification_params: Option<serde_json::Value>,
-        timeout: Option<Duration>,
-    ) -> Result<mcp_types::InitializeResult> {
-        let response = self
-            .send_request::<InitializeRequest>(initialize_params, timeout)
-            .await?;
-        self.send_notification::<InitializedNotification>(initialize_notification_params)
-            .await?;
-        Ok(response)
+    fn notify(&self, state: &ConnectionState) {
+        let callback = lock(&self.on_state_change).clone();
+        if let Some(callback) = callback {
+            callback(state);
+        }
     }
 
-    /// Convenience wrapper around `tools/list`.
-    pub async fn list_tools(
-        &self,
-        params: Option<ListToolsRequestParams>,
-        timeout: Option<Duration>,
-    ) -> Result<ListToolsResult> {
-        self.send_request::<ListToolsRequest>(params, timeout).await
-    }
+    /// Called when the server process of `generation` exits. Either respawns
+    /// it according to the reconnect policy or closes the client.
+    async fn connection_lost(self: Arc<Self>, generation: u64) {
+        let mut connection = self.connection.lock().await;
+        if connection.generation != generation || !connection.open {
+            return;
+        }
+        warn!("MCP server exited");
+        self.notify(&ConnectionState::Disconnected);
 
-    /// Convenience wrapper around `tools/call`.
-    pub async fn call_tool(
-        &self,
-        name: String,
-        arguments: Option<serde_json::Value>,
-        timeout: Option<Duration>,
-    ) -> Result<mcp_types::CallToolResult> {
-        let params = CallToolRequestParams { name, arguments };
-        debug!("MCP tool call: {params:?}");
-        self.send_request::<CallToolRequest>(params, timeout).await
-    }
+        // Nothing will answer the requests sent to the old process.
+        let orphaned: Vec<PendingRequest> = self
+            .pending
+            .lock()
+            .await
+            .drain()
+            .map(|(_, pending)| pending)
+            .collect();
+
+        let policy = lock(&self.reconnect_policy).clone();
+        let Some(policy) = policy else {
+            connection.open = false;
+            self.notify(&ConnectionState::Closed);
+            return;
+        };
 
-    /// Internal helper: route a JSON-RPC *response* object to the pending map.
-    async fn dispatch_response(
-        resp: JSONRPCResponse,
-        pending: &Arc<Mutex<HashMap<i64, PendingSender>>>,
-    ) {
-        let id = match resp.id {
-            RequestId::Integer(i) => i,
-            RequestId::String(_) => {
-                // We only ever generate integer IDs. Receiving a string here
-                // means we will not find a matching entry in `pending`.
-                error!("response with string ID - no matching pending request");
-                return;
+        let mut next_generation = generation;
+        for attempt in 1..=policy.max_attempts {
+            self.notify(&ConnectionState::Reconnecting { attempt });
+            time::sleep(policy.backoff(attempt)).await;
+            next_generation += 1;
+            match self.respawn(next_generation).await {
+                Ok((new_connection, closed, tools)) => {
+                    *connection = new_connection;
+                    watch_connection(&self, next_generation, closed);
+                    info!("MCP server respawned after {attempt} attempt(s)");
+                    self.notify(&ConnectionState::Reconnected {
+                        tools: tools.clone(),
+                    });
+                    self.replay(&connection.outgoing_tx, orphaned, &policy, &tools)
+                        .await;
+                    return;
+                }
+                Err(e) => warn!("failed to respawn MCP server (attempt {attempt}): {e:#}"),
             }
-        };
+        }
 
-        if let Some(tx) = pending.lock().await.remove(&id) {
-            // Ignore send errors – the receiver might have been dropped.
-            let _ = tx.send(JSONRPCMessage::Response(resp));
-        } else {
-            warn!(id, "no pending request found for response");
+        error!(
+            "giving up on the MCP server after {} attempt(s)",
+            policy.max_attempts
+        );
+        connection.open = false;
+        self.notify(&ConnectionState::Closed);
+    }
+
+    /// Spawns a new server process and repeats the handshake with it.
+    async fn respawn(
+        &self,
+        generation: u64,
+    ) -> Result<(Connection, oneshot::Receiver<()>, Vec<Tool>)> {
+        let (connection, closed) =
+            spawn_connection(&self.command, &self.pending, &self.handlers, generation)?;
+        let handshake = lock(&self.handshake).clone();
+        let mut tools = Vec::new();
+        if let Some((params, notification_params)) = handshake {
+            self.request_via::<InitializeRequest>(&connection.outgoing_tx, params)
+                .await?;
+            send_notification_on::<InitializedNotification>(
+                &connection.outgoing_tx,
+                notification_params,
+            )
+            .await?;
+            tools = self
+                .request_via::<ListToolsRequest>(&connection.outgoing_tx, None)
+                .await?
+                .tools;
         }
+        Ok((connection, closed, tools))
     }
 
-    /// Internal helper: route a JSON-RPC *error* object to the pending map.
-    async fn dispatch_error(
-        err: mcp_types::JSONRPCError,
-        pending: &Arc<Mutex<HashMap<i64, PendingSender>>>,
+    /// Resends the requests that are safe to repeat. The others are dropped,
+    /// which fails them for their callers.
+    async fn replay(
+        &self,
+        outgoing_tx: &mpsc::Sender<JSONRPCMessage>,
+        orphaned: Vec<PendingRequest>,
+        policy: &ReconnectPolicy,
+        tools: &[Tool],
     ) {
-        let id = match err.id {
-            RequestId::Integer(i) => i,
-            RequestId::String(_) => return, // see comment above
-        };
-
-        if let Some(tx) = pending.lock().await.remove(&id) {
-            let _ = tx.send(JSONRPCMessage::Error(err));
+        for pending in orphaned {
+            // The caller already gave up (e.g. timed out).
+            if pending.tx.is_closed() {
+                continue;
+            }
+            let request = &pending.request;
+            if !policy.replay_idempotent
+                || !is_replayable(&request.method, request.params.as_ref(), tools)
+            {
+                debug!("not replaying `{}` after reconnect", request.method);
+                continue;
+            }
+            let RequestId::Integer(id) = request.id else {
+                continue;
+            };
+            let message = JSONRPCMessage::Request(request.clone());
+            self.pending.lock().await.insert(id, pending);
+            if outgoing_tx.send(message).await.is_err() {
+                self.pending.lock().await.remove(&id);
+            }
         }
     }
 }
@@ -395,10 +587,206 @@ impl Drop for McpClient {
         // `kill_on_drop(true)` above, this extra check has the benefit of
         // forcing the process to be reaped immediately if it has already exited
         // instead of waiting for the Tokio runtime to reap it later.
-        let _ = self.child.try_wait();
+        if let Ok(mut connection) = self.inner.connection.try_lock() {
+            let _ = connection.child.try_wait();
+        }
     }
 }
 
+/// Spawns the server process along with the tasks that write to its stdin and
+/// read from its stdout. The returned receiver resolves once the server's
+/// stdout is closed.
+fn spawn_connection(
+    command: &ServerCommand,
+    pending: &PendingMap,
+    handlers: &ClientHandlers,
+    generation: u64,
+) -> std::io::Result<(Connection, oneshot::Receiver<()>)> {
+    let mut child = Command::new(&command.program)
+        .args(&command.args)
+        .env_clear()
+        .envs(create_env_for_mcp_server(command.env.clone()))
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        // As noted in the `kill_on_drop` documentation, the Tokio runtime makes
+        // a "best effort" to reap-after-exit to avoid zombie processes, but it
+        // is not a guarantee.
+        .kill_on_drop(true)
+        .spawn()?;
+
+    let stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| std::io::Error::other("failed to capture child stdin"))?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| std::io::Error::other("failed to capture child stdout"))?;
+
+    let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
+    let (closed_tx, closed_rx) = oneshot::channel();
+
+    // Spawn writer task. It listens on the `outgoing_rx` channel and
+    // writes messages to the child's STDIN.
+    let writer_handle = {
+        let mut stdin = stdin;
+        tokio::spawn(async move {
+            while let Some(msg) = outgoing_rx.recv().await {
+                match serde_json::to_string(&msg) {
+                    Ok(json) => {
+                        debug!("MCP message to server: {json}");
+                        if stdin.write_all(json.as_bytes()).await.is_err() {
+                            error!("failed to write message to child stdin");
+                            break;
+                        }
+                        if stdin.write_all(b"\n").await.is_err() {
+                            error!("failed to write newline to child stdin");
+                            break;
+                        }
+                        if stdin.flush().await.is_err() {
+                            error!("failed to flush child stdin");
+                            break;
+                        }
+                    }
+                    Err(e) => error!("failed to serialize JSONRPCMessage: {e}"),
+                }
+            }
+        })
+    };
+
+    // Spawn reader task. It reads line-delimited JSON from the child's
+    // STDOUT and dispatches responses to the pending map.
+    let reader_handle = {
+        let pending = pending.clone();
+        let handlers = handlers.clone();
+        let outgoing_tx = outgoing_tx.clone();
+        let mut lines = BufReader::new(stdout).lines();
+
+        tokio::spawn(async move {
+            while let Ok(Some(line)) = lines.next_line().await {
+                debug!("MCP message from server: {line}");
+                match serde_json::from_str::<JSONRPCMessage>(&line) {
+                    Ok(JSONRPCMessage::Response(resp)) => {
+                        dispatch_response(resp, &pending).await;
+                    }
+                    Ok(JSONRPCMessage::Error(err)) => {
+                        dispatch_error(err, &pending).await;
+                    }
+                    Ok(JSONRPCMessage::Request(request)) => {
+                        spawn_server_request_handler(request, &handlers, outgoing_tx.clone());
+                    }
+                    Ok(JSONRPCMessage::Notification(JSONRPCNotification { .. })) => {
+                        // For now we only log server-initiated notifications.
+                        info!("<- notification: {}", line);
+                    }
+                    Err(e) => {
+                        error!("failed to deserialize JSONRPCMessage: {e}; line = {}", line)
+                    }
+                }
+            }
+            let _ = closed_tx.send(());
+        })
+    };
+
+    // We intentionally *detach* the tasks. They will keep running in the
+    // background as long as their respective resources (channels/stdin/
+    // stdout) are alive. Dropping the connection cancels the tasks due to
+    // dropped resources.
+    let _ = (writer_handle, reader_handle);
+
+    Ok((
+        Connection {
+            child,
+            outgoing_tx,
+            generation,
+            open: true,
+        },
+        closed_rx,
+    ))
+}
+
+/// Reports the exit of the server process of `generation` to the client, as
+/// long as the client is still alive.
+fn watch_connection(inner: &Arc<Inner>, generation: u64, closed: oneshot::Receiver<()>) {
+    let inner = Arc::downgrade(inner);
+    tokio::spawn(async move {
+        let _ = closed.await;
+        if let Some(inner) = inner.upgrade() {
+            inner.connection_lost(generation).await;
+        }
+    });
+}
+
+async fn send_notification_on<N>(
+    outgoing_tx: &mpsc::Sender<JSONRPCMessage>,
+    params: N::Params,
+) -> Result<()>
+where
+    N: ModelContextProtocolNotification,
+    N::Params: Serialize,
+{
+    // Serialize params -> JSON. For many request types `Params` is
+    // `Option<T>` and `None` should be encoded as *absence* of the field.
+    let params_json = serde_json::to_value(&params)?;
+    let params_field = if params_json.is_null() {
+        None
+    } else {
+        Some(params_json)
+    };
+
+    let method = N::METHOD.to_string();
+    let jsonrpc_notification = JSONRPCNotification {
+        jsonrpc: JSONRPC_VERSION.to_string(),
+        method: method.clone(),
+        params: params_field,
+    };
+
+    let notification = JSONRPCMessage::Notification(jsonrpc_notification);
+    outgoing_tx
+        .send(notification)
+        .await
+        .with_context(|| format!("failed to send notification `{method}` to writer task"))
+}
+
+/// Internal helper: route a JSON-RPC *response* object to the pending map.
+async fn dispatch_response(resp: JSONRPCResponse, pending: &PendingMap) {
+    let id = match resp.id {
+        RequestId::Integer(i) => i,
+        RequestId::String(_) => {
+            // We only ever generate integer IDs. Receiving a string here
+            // means we will not find a matching entry in `pending`.
+            error!("response with string ID - no matching pending request");
+            return;
+        }
+    };
+
+    if let Some(pending) = pending.lock().await.remove(&id) {
+        // Ignore send errors – the receiver might have been dropped.
+        let _ = pending.tx.send(JSONRPCMessage::Response(resp));
+    } else {
+        warn!(id, "no pending request found for response");
+    }
+}
+
+/// Internal helper: route a JSON-RPC *error* object to the pending map.
+async fn dispatch_error(err: mcp_types::JSONRPCError, pending: &PendingMap) {
+    let id = match err.id {
+        RequestId::Integer(i) => i,
+        RequestId::String(_) => return, // see comment in `dispatch_response`
+    };
+
+    if let Some(pending) = pending.lock().await.remove(&id) {
+        let _ = pending.tx.send(JSONRPCMessage::Error(err));
+    }
+}
+
+fn lock<T>(mutex: &std::sync::Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
 /// Environment variables that are always included when spawning a new MCP
 /// server.
 #[rustfmt::skip]
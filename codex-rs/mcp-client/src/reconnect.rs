@@ -0,0 +1,181 @@
+//! Recovery from an MCP server that exits while the client is in use.
+//!
+//! Reconnection is opt-in through [`crate::McpClient::set_reconnect_policy`].
+//! When enabled, the client respawns the server with exponential backoff,
+//! repeats the `initialize` handshake with the parameters of the original
+//! one, lists the tools of the new process and then either resends or fails
+//! each request that was still waiting for a response.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use mcp_types::CallToolRequest;
+use mcp_types::GetPromptRequest;
+use mcp_types::ListPromptsRequest;
+use mcp_types::ListResourceTemplatesRequest;
+use mcp_types::ListResourcesRequest;
+use mcp_types::ListToolsRequest;
+use mcp_types::ModelContextProtocolRequest;
+use mcp_types::PingRequest;
+use mcp_types::ReadResourceRequest;
+use mcp_types::Tool;
+use serde_json::Value;
+
+/// How the client recovers from a server that exits.
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    /// Number of respawn attempts before giving up.
+    pub max_attempts: u32,
+    /// Delay before the first attempt; doubled after each failed one.
+    pub initial_backoff: Duration,
+    /// Upper bound for the delay between attempts.
+    pub max_backoff: Duration,
+    /// Resend pending requests that are safe to repeat once the new server
+    /// is ready. Other pending requests always fail.
+    pub replay_idempotent: bool,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 5,
+            initial_backoff: Duration::from_millis(200),
+            max_backoff: Duration::from_secs(10),
+            replay_idempotent: true,
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Delay before the given (1-based) attempt.
+    pub(crate) fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+}
+
+/// Connection changes reported to the callback registered with
+/// [`crate::McpClient::on_connection_state_change`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionState {
+    /// The server exited or closed its output.
+    Disconnected,
+    /// A new server process is about to be spawned.
+    Reconnecting { attempt: u32 },
+    /// A new server process completed the handshake. `tools` is its tool
+    /// list, which is empty when the client had not been initialized.
+    Reconnected { tools: Vec<Tool> },
+    /// The server is gone for good, either because reconnection is disabled
+    /// or because every attempt failed. Further requests fail immediately.
+    Closed,
+}
+
+pub(crate) type ConnectionStateCallback = Arc<dyn Fn(&ConnectionState) + Send + Sync>;
+
+/// Whether a request can be sent again to a new server process without
+/// repeating a side effect. Tool calls qualify only when the tool describes
+/// itself as read-only or idempotent.
+pub(crate) fn is_replayable(method: &str, params: Option<&Value>, tools: &[Tool]) -> bool {
+    const READ_ONLY_METHODS: &[&str] = &[
+        PingRequest::METHOD,
+        ListToolsRequest::METHOD,
+        ListResourcesRequest::METHOD,
+        ListResourceTemplatesRequest::METHOD,
+        ReadResourceRequest::METHOD,
+        ListPromptsRequest::METHOD,
+        GetPromptRequest::METHOD,
+    ];
+    if READ_ONLY_METHODS.contains(&method) {
+        return true;
+    }
+    if method != CallToolRequest::METHOD {
+        return false;
+    }
+    let Some(name) = params.and_then(|p| p.get("name")).and_then(Value::as_str) else {
+        return false;
+    };
+    tools
+        .iter()
+        .find(|tool| tool.name == name)
+        .and_then(|tool| tool.annotations.as_ref())
+        .is_some_and(|annotations| {
+            annotations.read_only_hint == Some(true) || annotations.idempotent_hint == Some(true)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::ToolAnnotations;
+    use mcp_types::ToolInputSchema;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn tool(name: &str, annotations: Option<ToolAnnotations>) -> Tool {
+        Tool {
+            annotations,
+            description: None,
+            input_schema: ToolInputSchema {
+                properties: None,
+                required: None,
+                r#type: "object".to_string(),
+            },
+            name: name.to_string(),
+            output_schema: None,
+            title: None,
+        }
+    }
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        let policy = ReconnectPolicy {
+            max_attempts: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_millis(500),
+            replay_idempotent: true,
+        };
+        let delays: Vec<Duration> = (1..=5).map(|attempt| policy.backoff(attempt)).collect();
+        assert_eq!(
+            delays,
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                Duration::from_millis(400),
+                Duration::from_millis(500),
+                Duration::from_millis(500),
+            ]
+        );
+    }
+
+    #[test]
+    fn only_side_effect_free_requests_are_replayable() {
+        let idempotent = ToolAnnotations {
+            destructive_hint: None,
+            idempotent_hint: Some(true),
+            open_world_hint: None,
+            read_only_hint: None,
+            title: None,
+        };
+        let tools = vec![tool("lookup", Some(idempotent)), tool("write", None)];
+
+        assert!(is_replayable("tools/list", None, &tools));
+        assert!(is_replayable(
+            "tools/call",
+            Some(&json!({ "name": "lookup" })),
+            &tools
+        ));
+        assert!(!is_replayable(
+            "tools/call",
+            Some(&json!({ "name": "write" })),
+            &tools
+        ));
+        assert!(!is_replayable(
+            "tools/call",
+            Some(&json!({ "name": "missing" })),
+            &tools
+        ));
+        assert!(!is_replayable("sampling/createMessage", None, &tools));
+    }
+}
@@ -0,0 +1,356 @@
+//! Validation of JSON values against the schemas MCP servers declare for
+//! their tools.
+//!
+//! Tool schemas are JSON Schema documents, but in practice servers only use a
+//! small part of the specification. The validator covers that part (`type`,
+//! `enum`, `const`, `properties`, `required`, `additionalProperties`, `items`,
+//! the length and range bounds, and `allOf`/`anyOf`/`oneOf`) and ignores every
+//! other keyword, so a schema it does not understand never rejects a value.
+
+use std::fmt;
+
+use serde_json::Map;
+use serde_json::Value;
+
+/// A single way in which a value fails to match a schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    /// JSON Pointer to the offending part of the value; empty for the value
+    /// itself.
+    pub path: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.message)
+        } else {
+            write!(f, "{}: {}", self.path, self.message)
+        }
+    }
+}
+
+/// Checks `instance` against `schema` and returns every violation found.
+pub fn validate(schema: &Value, instance: &Value) -> Vec<SchemaViolation> {
+    let mut violations = Vec::new();
+    validate_at(schema, instance, "", &mut violations);
+    violations
+}
+
+fn validate_at(schema: &Value, instance: &Value, path: &str, out: &mut Vec<SchemaViolation>) {
+    let schema = match schema {
+        Value::Object(schema) => schema,
+        Value::Bool(false) => {
+            push(out, path, "no value is allowed here".to_string());
+            return;
+        }
+        _ => return,
+    };
+
+    if let Some(expected) = schema.get("type")
+        && !matches_type(expected, instance)
+    {
+        push(
+            out,
+            path,
+            format!(
+                "expected {}, got {}",
+                describe_type(expected),
+                kind(instance)
+            ),
+        );
+        // Every other keyword assumes the type is right.
+        return;
+    }
+
+    if let Some(Value::Array(allowed)) = schema.get("enum")
+        && !allowed.contains(instance)
+    {
+        push(
+            out,
+            path,
+            format!("{instance} is not one of {}", Value::Array(allowed.clone())),
+        );
+    }
+    if let Some(expected) = schema.get("const")
+        && expected != instance
+    {
+        push(out, path, format!("expected {expected}, got {instance}"));
+    }
+
+    match instance {
+        Value::Object(object) => validate_object(schema, object, path, out),
+        Value::Array(items) => validate_array(schema, items, path, out),
+        Value::String(s) => validate_string(schema, s, path, out),
+        Value::Number(n) => {
+            if let Some(n) = n.as_f64() {
+                validate_number(schema, n, path, out);
+            }
+        }
+        Value::Null | Value::Bool(_) => {}
+    }
+
+    if let Some(Value::Array(all)) = schema.get("allOf") {
+        for sub in all {
+            validate_at(sub, instance, path, out);
+        }
+    }
+    if let Some(Value::Array(any)) = schema.get("anyOf")
+        && !any.iter().any(|sub| validate(sub, instance).is_empty())
+    {
+        push(
+            out,
+            path,
+            "does not match any of the allowed schemas".to_string(),
+        );
+    }
+    if let Some(Value::Array(one)) = schema.get("oneOf") {
+        let matching = one
+            .iter()
+            .filter(|sub| validate(sub, instance).is_empty())
+            .count();
+        if matching != 1 {
+            push(
+                out,
+                path,
+                format!("must match exactly one of the allowed schemas, matched {matching}"),
+            );
+        }
+    }
+}
+
+fn validate_object(
+    schema: &Map<String, Value>,
+    object: &Map<String, Value>,
+    path: &str,
+    out: &mut Vec<SchemaViolation>,
+) {
+    if let Some(Value::Array(required)) = schema.get("required") {
+        for name in required.iter().filter_map(Value::as_str) {
+            if !object.contains_key(name) {
+                push(out, path, format!("missing required property `{name}`"));
+            }
+        }
+    }
+
+    let properties = schema.get("properties").and_then(Value::as_object);
+    for (name, value) in object {
+        let child = format!("{path}/{}", escape_pointer(name));
+        match properties.and_then(|p| p.get(name)) {
+            Some(sub) => validate_at(sub, value, &child, out),
+            None => match schema.get("additionalProperties") {
+                Some(Value::Bool(false)) => {
+                    push(out, path, format!("unexpected property `{name}`"));
+                }
+                Some(sub @ Value::Object(_)) => validate_at(sub, value, &child, out),
+                _ => {}
+            },
+        }
+    }
+}
+
+fn validate_array(
+    schema: &Map<String, Value>,
+    items: &[Value],
+    path: &str,
+    out: &mut Vec<SchemaViolation>,
+) {
+    if let Some(min) = schema.get("minItems").and_then(Value::as_u64)
+        && (items.len() as u64) < min
+    {
+        push(out, path, format!("expected at least {min} items"));
+    }
+    if let Some(max) = schema.get("maxItems").and_then(Value::as_u64)
+        && (items.len() as u64) > max
+    {
+        push(out, path, format!("expected at most {max} items"));
+    }
+    if let Some(sub) = schema.get("items") {
+        for (index, item) in items.iter().enumerate() {
+            validate_at(sub, item, &format!("{path}/{index}"), out);
+        }
+    }
+}
+
+fn validate_string(
+    schema: &Map<String, Value>,
+    s: &str,
+    path: &str,
+    out: &mut Vec<SchemaViolation>,
+) {
+    let len = s.chars().count() as u64;
+    if let Some(min) = schema.get("minLength").and_then(Value::as_u64)
+        && len < min
+    {
+        push(out, path, format!("expected at least {min} characters"));
+    }
+    if let Some(max) = schema.get("maxLength").and_then(Value::as_u64)
+        && len > max
+    {
+        push(out, path, format!("expected at most {max} characters"));
+    }
+}
+
+fn validate_number(
+    schema: &Map<String, Value>,
+    n: f64,
+    path: &str,
+    out: &mut Vec<SchemaViolation>,
+) {
+    if let Some(min) = schema.get("minimum").and_then(Value::as_f64)
+        && n < min
+    {
+        push(out, path, format!("{n} is less than the minimum of {min}"));
+    }
+    if let Some(max) = schema.get("maximum").and_then(Value::as_f64)
+        && n > max
+    {
+        push(
+            out,
+            path,
+            format!("{n} is greater than the maximum of {max}"),
+        );
+    }
+}
+
+fn matches_type(expected: &Value, instance: &Value) -> bool {
+    match expected {
+        Value::String(name) => matches_type_name(name, instance),
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .any(|name| matches_type_name(name, instance)),
+        _ => true,
+    }
+}
+
+fn matches_type_name(name: &str, instance: &Value) -> bool {
+    match name {
+        "null" => instance.is_null(),
+        "boolean" => instance.is_boolean(),
+        "string" => instance.is_string(),
+        "array" => instance.is_array(),
+        "object" => instance.is_object(),
+        "number" => instance.is_number(),
+        "integer" => match instance {
+            Value::Number(n) => {
+                n.is_i64() || n.is_u64() || n.as_f64().is_some_and(|f| f.fract() == 0.0)
+            }
+            _ => false,
+        },
+        // Unknown type names are not ours to reject.
+        _ => true,
+    }
+}
+
+fn describe_type(expected: &Value) -> String {
+    match expected {
+        Value::Array(names) => names
+            .iter()
+            .filter_map(Value::as_str)
+            .collect::<Vec<_>>()
+            .join(" or "),
+        Value::String(name) => name.clone(),
+        other => other.to_string(),
+    }
+}
+
+fn kind(instance: &Value) -> &'static str {
+    match instance {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+fn escape_pointer(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+fn push(out: &mut Vec<SchemaViolation>, path: &str, message: String) {
+    out.push(SchemaViolation {
+        path: path.to_string(),
+        message,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    fn messages(schema: Value, instance: Value) -> Vec<String> {
+        let mut messages: Vec<String> = validate(&schema, &instance)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        messages.sort();
+        messages
+    }
+
+    #[test]
+    fn accepts_matching_object() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "path": { "type": "string" },
+                "limit": { "type": "integer", "minimum": 1 },
+                "tags": { "type": "array", "items": { "type": "string" } },
+            },
+            "required": ["path"],
+        });
+        let instance = json!({ "path": "src", "limit": 5, "tags": ["a", "b"] });
+        assert_eq!(messages(schema, instance), Vec::<String>::new());
+    }
+
+    #[test]
+    fn reports_nested_violations_with_pointers() {
+        let schema = json!({
+            "type": "object",
+            "properties": {
+                "limit": { "type": "integer", "minimum": 1 },
+                "tags": { "type": "array", "items": { "type": "string" } },
+                "mode": { "enum": ["fast", "slow"] },
+            },
+            "required": ["path"],
+            "additionalProperties": false,
+        });
+        let instance = json!({ "limit": 0, "tags": ["a", 2], "mode": "medium", "extra": 1 });
+        assert_eq!(
+            messages(schema, instance),
+            vec![
+                "/limit: 0 is less than the minimum of 1".to_string(),
+                "/mode: \"medium\" is not one of [\"fast\",\"slow\"]".to_string(),
+                "/tags/1: expected string, got number".to_string(),
+                "missing required property `path`".to_string(),
+                "unexpected property `extra`".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_unknown_keywords() {
+        let schema = json!({ "$ref": "#/definitions/thing", "format": "uri" });
+        assert_eq!(messages(schema, json!(42)), Vec::<String>::new());
+    }
+
+    #[test]
+    fn checks_union_types_and_one_of() {
+        assert_eq!(
+            messages(json!({ "type": ["string", "null"] }), json!(true)),
+            vec!["expected string or null, got boolean".to_string()]
+        );
+        assert_eq!(
+            messages(
+                json!({ "oneOf": [{ "type": "number" }, { "type": "integer" }] }),
+                json!(3)
+            ),
+            vec!["must match exactly one of the allowed schemas, matched 2".to_string()]
+        );
+    }
+}
@@ -0,0 +1,140 @@
+//! Requests the server sends to the client.
+//!
+//! The client answers `ping` itself, answers `roots/list` with the roots set
+//! through [`crate::McpClient::set_roots`], and hands `sampling/createMessage`
+//! to the handler registered with [`crate::McpClient::set_sampling_handler`].
+//! Every other method, and the latter two before they are configured, is
+//! answered with an error.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+
+use mcp_types::CreateMessageRequest;
+use mcp_types::CreateMessageRequestParams;
+use mcp_types::CreateMessageResult;
+use mcp_types::JSONRPC_VERSION;
+use mcp_types::JSONRPCError;
+use mcp_types::JSONRPCErrorError;
+use mcp_types::JSONRPCMessage;
+use mcp_types::JSONRPCRequest;
+use mcp_types::JSONRPCResponse;
+use mcp_types::ListRootsRequest;
+use mcp_types::ListRootsResult;
+use mcp_types::ModelContextProtocolRequest;
+use mcp_types::PingRequest;
+use mcp_types::Root;
+use serde_json::json;
+use tokio::sync::mpsc;
+use tracing::warn;
+
+/// The MCP specification suggests this code for a sampling request the user
+/// (or the client's policy) declined.
+const SAMPLING_REJECTED_ERROR_CODE: i64 = -1;
+const METHOD_NOT_FOUND_ERROR_CODE: i64 = -32601;
+const INVALID_PARAMS_ERROR_CODE: i64 = -32602;
+
+pub(crate) type SamplingFuture =
+    Pin<Box<dyn Future<Output = Result<CreateMessageResult, String>> + Send>>;
+
+pub(crate) type SamplingHandler =
+    Arc<dyn Fn(CreateMessageRequestParams) -> SamplingFuture + Send + Sync>;
+
+/// What the client answers server requests with. Shared by the client and
+/// the reader task of every connection.
+#[derive(Clone, Default)]
+pub(crate) struct ClientHandlers {
+    pub(crate) sampling: Arc<std::sync::Mutex<Option<SamplingHandler>>>,
+    pub(crate) roots: Arc<std::sync::Mutex<Option<Vec<Root>>>>,
+}
+
+/// Answers `request` on `outgoing_tx`. Runs on its own task so a slow
+/// sampling request does not hold up the responses the reader dispatches.
+pub(crate) fn spawn_server_request_handler(
+    request: JSONRPCRequest,
+    handlers: &ClientHandlers,
+    outgoing_tx: mpsc::Sender<JSONRPCMessage>,
+) {
+    let sampling_handler = lock(&handlers.sampling).clone();
+    let roots = lock(&handlers.roots).clone();
+    tokio::spawn(async move {
+        let JSONRPCRequest {
+            id, method, params, ..
+        } = request;
+        let outcome = handle(&method, params, sampling_handler, roots).await;
+        let message = match outcome {
+            Ok(result) => JSONRPCMessage::Response(JSONRPCResponse {
+                id,
+                jsonrpc: JSONRPC_VERSION.to_string(),
+                result,
+            }),
+            Err(error) => {
+                warn!(
+                    "rejecting `{method}` request from MCP server: {}",
+                    error.message
+                );
+                JSONRPCMessage::Error(JSONRPCError {
+                    error,
+                    id,
+                    jsonrpc: JSONRPC_VERSION.to_string(),
+                })
+            }
+        };
+        let _ = outgoing_tx.send(message).await;
+    });
+}
+
+async fn handle(
+    method: &str,
+    params: Option<serde_json::Value>,
+    sampling_handler: Option<SamplingHandler>,
+    roots: Option<Vec<Root>>,
+) -> Result<serde_json::Value, JSONRPCErrorError> {
+    match method {
+        PingRequest::METHOD => Ok(json!({})),
+        ListRootsRequest::METHOD => match roots {
+            Some(roots) => Ok(ListRootsResult { roots }.into()),
+            None => Err(error(
+                METHOD_NOT_FOUND_ERROR_CODE,
+                "roots are not supported by this client".to_string(),
+            )),
+        },
+        CreateMessageRequest::METHOD => {
+            let Some(handler) = sampling_handler else {
+                return Err(error(
+                    METHOD_NOT_FOUND_ERROR_CODE,
+                    "sampling is not supported by this client".to_string(),
+                ));
+            };
+            let params: CreateMessageRequestParams =
+                serde_json::from_value(params.unwrap_or_default()).map_err(|e| {
+                    error(
+                        INVALID_PARAMS_ERROR_CODE,
+                        format!("invalid sampling/createMessage params: {e}"),
+                    )
+                })?;
+            let result = handler(params)
+                .await
+                .map_err(|message| error(SAMPLING_REJECTED_ERROR_CODE, message))?;
+            Ok(result.into())
+        }
+        _ => Err(error(
+            METHOD_NOT_FOUND_ERROR_CODE,
+            format!("method not found: {method}"),
+        )),
+    }
+}
+
+fn error(code: i64, message: String) -> JSONRPCErrorError {
+    JSONRPCErrorError {
+        code,
+        data: None,
+        message,
+    }
+}
+
+fn lock<T>(mutex: &std::sync::Mutex<T>) -> std::sync::MutexGuard<'_, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
@@ -0,0 +1,123 @@
+//! Errors returned by [`crate::McpClient::call_tool_typed`] and the schema
+//! checks it performs.
+
+use mcp_types::CallToolResult;
+use mcp_types::ContentBlock;
+use mcp_types::Tool;
+use serde_json::Value;
+
+use crate::schema::SchemaViolation;
+use crate::schema::validate;
+
+#[derive(Debug, thiserror::Error)]
+pub enum ToolCallError {
+    #[error("arguments for tool `{tool}` do not match its input schema: {}", join(.violations))]
+    InvalidArguments {
+        tool: String,
+        violations: Vec<SchemaViolation>,
+    },
+
+    #[error("tool `{tool}` reported an error: {message}")]
+    ToolFailed {
+        tool: String,
+        message: String,
+        result: Box<CallToolResult>,
+    },
+
+    #[error("tool `{tool}` returned no structured content")]
+    MissingStructuredContent {
+        tool: String,
+        result: Box<CallToolResult>,
+    },
+
+    #[error("structured content from tool `{tool}` does not match its output schema: {}", join(.violations))]
+    InvalidOutput {
+        tool: String,
+        violations: Vec<SchemaViolation>,
+    },
+
+    #[error("failed to deserialize structured content from tool `{tool}`: {source}")]
+    Deserialize {
+        tool: String,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error(transparent)]
+    Transport(#[from] anyhow::Error),
+}
+
+/// Checks `arguments` against the input schema `tool` declares. Missing
+/// arguments are checked as an empty object, which is what servers receive.
+pub fn validate_tool_arguments(
+    tool: &Tool,
+    arguments: Option<&Value>,
+) -> Result<(), ToolCallError> {
+    let empty = Value::Object(Default::default());
+    let violations = validate(
+        &serde_json::to_value(&tool.input_schema).unwrap_or_default(),
+        arguments.unwrap_or(&empty),
+    );
+    if violations.is_empty() {
+        Ok(())
+    } else {
+        Err(ToolCallError::InvalidArguments {
+            tool: tool.name.clone(),
+            violations,
+        })
+    }
+}
+
+/// Checks the structured content of `result` against the output schema
+/// `tool` declares, if any, and returns that content.
+pub(crate) fn checked_structured_content(
+    tool: &Tool,
+    result: CallToolResult,
+) -> Result<Value, ToolCallError> {
+    if result.is_error == Some(true) {
+        return Err(ToolCallError::ToolFailed {
+            tool: tool.name.clone(),
+            message: text_content(&result),
+            result: Box::new(result),
+        });
+    }
+    let Some(content) = result.structured_content.clone() else {
+        return Err(ToolCallError::MissingStructuredContent {
+            tool: tool.name.clone(),
+            result: Box::new(result),
+        });
+    };
+    if let Some(output_schema) = &tool.output_schema {
+        let violations = validate(
+            &serde_json::to_value(output_schema).unwrap_or_default(),
+            &content,
+        );
+        if !violations.is_empty() {
+            return Err(ToolCallError::InvalidOutput {
+                tool: tool.name.clone(),
+                violations,
+            });
+        }
+    }
+    Ok(content)
+}
+
+fn text_content(result: &CallToolResult) -> String {
+    result
+        .content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::TextContent(text) => Some(text.text.as_str()),
+            _ => None,
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn join(violations: &[SchemaViolation]) -> String {
+    violations
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
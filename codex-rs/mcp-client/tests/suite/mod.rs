@@ -0,0 +1,5 @@
+// Aggregates all former standalone integration tests as modules.
+#[cfg(unix)]
+mod reconnect;
+#[cfg(unix)]
+mod server_requests;
@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use codex_mcp_client::ConnectionState;
+use codex_mcp_client::McpClient;
+use codex_mcp_client::ReconnectPolicy;
+use mcp_types::ClientCapabilities;
+use mcp_types::ContentBlock;
+use mcp_types::Implementation;
+use mcp_types::InitializeRequestParams;
+use mcp_types::MCP_SCHEMA_VERSION;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+use tokio::time::timeout;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A stdio MCP server that exits the first time `tools/call` is invoked
+/// (recorded in `$MARKER`) and answers it normally afterwards.
+const FLAKY_SERVER: &str = r#"
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/^{"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"capabilities":{},"protocolVersion":"2025-06-18","serverInfo":{"name":"flaky","version":"0.0.0"}}}\n' "$id" ;;
+    *'"method":"tools/list"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"tools":[{"name":"lookup","inputSchema":{"type":"object"},"annotations":{"idempotentHint":true}},{"name":"write","inputSchema":{"type":"object"}}]}}\n' "$id" ;;
+    *'"method":"tools/call"'*)
+      if [ ! -e "$MARKER" ]; then : > "$MARKER"; exit 1; fi
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"content":[{"type":"text","text":"ok"}]}}\n' "$id" ;;
+  esac
+done
+"#;
+
+#[tokio::test]
+async fn test_idempotent_call_is_replayed_after_reconnect() {
+    let home = TempDir::new().expect("create temp dir");
+    let client = spawn_flaky_server(home.path()).await;
+    let states = record_states(&client);
+    client.set_reconnect_policy(fast_policy());
+
+    let result = timeout(
+        DEFAULT_TIMEOUT,
+        client.call_tool("lookup".to_string(), None, None),
+    )
+    .await
+    .expect("call timed out")
+    .expect("call succeeds after reconnect");
+    let ContentBlock::TextContent(text) = &result.content[0] else {
+        panic!("unexpected content: {:?}", result.content);
+    };
+    assert_eq!(text.text, "ok");
+
+    let states = states.lock().expect("states lock").clone();
+    assert_eq!(states.len(), 3, "states: {states:?}");
+    assert_eq!(states[0], ConnectionState::Disconnected);
+    assert_eq!(states[1], ConnectionState::Reconnecting { attempt: 1 });
+    let ConnectionState::Reconnected { tools } = &states[2] else {
+        panic!("expected Reconnected, got {:?}", states[2]);
+    };
+    let names: Vec<&str> = tools.iter().map(|tool| tool.name.as_str()).collect();
+    assert_eq!(names, vec!["lookup", "write"]);
+}
+
+#[tokio::test]
+async fn test_non_idempotent_call_fails_but_client_recovers() {
+    let home = TempDir::new().expect("create temp dir");
+    let client = spawn_flaky_server(home.path()).await;
+    client.set_reconnect_policy(fast_policy());
+
+    let err = timeout(
+        DEFAULT_TIMEOUT,
+        client.call_tool("write".to_string(), None, None),
+    )
+    .await
+    .expect("call timed out")
+    .expect_err("call is not replayed");
+    assert!(
+        err.to_string()
+            .contains("connection to the MCP server was lost"),
+        "unexpected error: {err}"
+    );
+
+    // The respawned server answers new requests.
+    timeout(
+        DEFAULT_TIMEOUT,
+        client.call_tool("write".to_string(), None, None),
+    )
+    .await
+    .expect("call timed out")
+    .expect("call succeeds on the new server");
+}
+
+#[tokio::test]
+async fn test_pending_call_fails_without_reconnect_policy() {
+    let home = TempDir::new().expect("create temp dir");
+    let client = spawn_flaky_server(home.path()).await;
+    let states = record_states(&client);
+
+    timeout(
+        DEFAULT_TIMEOUT,
+        client.call_tool("lookup".to_string(), None, None),
+    )
+    .await
+    .expect("call timed out")
+    .expect_err("server exited");
+    let err = client
+        .call_tool("lookup".to_string(), None, None)
+        .await
+        .expect_err("client is closed");
+    assert!(
+        err.to_string()
+            .contains("connection to the MCP server is closed"),
+        "unexpected error: {err}"
+    );
+    assert_eq!(
+        states.lock().expect("states lock").clone(),
+        vec![ConnectionState::Disconnected, ConnectionState::Closed]
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+#[expect(clippy::expect_used)]
+async fn spawn_flaky_server(home: &Path) -> McpClient {
+    let env = HashMap::from([(
+        "MARKER".to_string(),
+        home.join("crashed").to_string_lossy().into_owned(),
+    )]);
+    let client = McpClient::new_stdio_client(
+        OsString::from("sh"),
+        vec![OsString::from("-c"), OsString::from(FLAKY_SERVER)],
+        Some(env),
+    )
+    .await
+    .expect("spawn server");
+    let params = InitializeRequestParams {
+        capabilities: ClientCapabilities {
+            experimental: None,
+            roots: None,
+            sampling: None,
+            elicitation: None,
+        },
+        client_info: Implementation {
+            name: "reconnect test".to_string(),
+            version: "0.0.0".to_string(),
+            title: None,
+        },
+        protocol_version: MCP_SCHEMA_VERSION.to_string(),
+    };
+    client
+        .initialize(params, None, Some(DEFAULT_TIMEOUT))
+        .await
+        .expect("initialize");
+    client
+}
+
+fn fast_policy() -> ReconnectPolicy {
+    ReconnectPolicy {
+        max_attempts: 3,
+        initial_backoff: Duration::from_millis(10),
+        max_backoff: Duration::from_millis(50),
+        replay_idempotent: true,
+    }
+}
+
+fn record_states(client: &McpClient) -> Arc<Mutex<Vec<ConnectionState>>> {
+    let states = Arc::new(Mutex::new(Vec::new()));
+    client.on_connection_state_change({
+        let states = states.clone();
+        move |state| {
+            if let Ok(mut states) = states.lock() {
+                states.push(state.clone());
+            }
+        }
+    });
+    states
+}
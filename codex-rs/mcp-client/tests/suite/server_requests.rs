@@ -0,0 +1,185 @@
+use std::ffi::OsString;
+use std::time::Duration;
+
+use codex_mcp_client::McpClient;
+use mcp_types::ClientCapabilities;
+use mcp_types::ClientCapabilitiesRoots;
+use mcp_types::CreateMessageResult;
+use mcp_types::CreateMessageResultContent;
+use mcp_types::Implementation;
+use mcp_types::InitializeRequestParams;
+use mcp_types::MCP_SCHEMA_VERSION;
+use mcp_types::Role;
+use mcp_types::Root;
+use mcp_types::SamplingMessageContent;
+use mcp_types::TextContent;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tokio::time::timeout;
+
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A stdio MCP server whose `relay` tool sends the request named by its
+/// `method` argument back to the client and returns the client's reply as
+/// structured content. Relaying `count` instead returns how many
+/// `notifications/roots/list_changed` the server has received.
+const RELAY_SERVER: &str = r#"
+changed=0
+while IFS= read -r line; do
+  id=$(printf '%s' "$line" | sed -n 's/^{"id":\([0-9]*\).*/\1/p')
+  case "$line" in
+    *'"method":"initialize"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"capabilities":{},"protocolVersion":"2025-06-18","serverInfo":{"name":"relay","version":"0.0.0"}}}\n' "$id" ;;
+    *'"method":"notifications/roots/list_changed"'*)
+      changed=$((changed + 1)) ;;
+    *'"method":"count"'*)
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"content":[],"structuredContent":{"changed":%s}}}\n' "$id" "$changed" ;;
+    *'"method":"tools/call"'*)
+      case "$line" in
+        *'"method":"ping"'*)
+          printf '{"jsonrpc":"2.0","id":"s1","method":"ping"}\n' ;;
+        *'"method":"roots/list"'*)
+          printf '{"jsonrpc":"2.0","id":"s1","method":"roots/list"}\n' ;;
+        *)
+          printf '{"jsonrpc":"2.0","id":"s1","method":"sampling/createMessage","params":{"messages":[{"role":"user","content":{"type":"text","text":"hi"}}],"maxTokens":10}}\n' ;;
+      esac
+      IFS= read -r reply
+      printf '{"jsonrpc":"2.0","id":%s,"result":{"content":[],"structuredContent":%s}}\n' "$id" "$reply" ;;
+  esac
+done
+"#;
+
+#[tokio::test]
+async fn test_sampling_request_is_answered_by_handler() {
+    let client = spawn_relay_server().await;
+    client.set_sampling_handler(|params| async move {
+        let SamplingMessageContent::TextContent(text) = &params.messages[0].content else {
+            return Err("expected text".to_string());
+        };
+        Ok(CreateMessageResult {
+            content: CreateMessageResultContent::TextContent(TextContent {
+                annotations: None,
+                text: format!("echo: {}", text.text),
+                r#type: "text".to_string(),
+            }),
+            model: "test-model".to_string(),
+            role: Role::Assistant,
+            stop_reason: Some("endTurn".to_string()),
+        })
+    });
+
+    let reply = relay(&client, "sampling/createMessage").await;
+    assert_eq!(reply["id"], json!("s1"));
+    assert_eq!(reply["result"]["content"]["text"], json!("echo: hi"));
+    assert_eq!(reply["result"]["model"], json!("test-model"));
+}
+
+#[tokio::test]
+async fn test_sampling_rejection_and_missing_handler() {
+    let client = spawn_relay_server().await;
+
+    let reply = relay(&client, "sampling/createMessage").await;
+    assert_eq!(reply["error"]["code"], json!(-32601));
+
+    client.set_sampling_handler(|_params| async { Err("declined by policy".to_string()) });
+    let reply = relay(&client, "sampling/createMessage").await;
+    assert_eq!(reply["error"]["code"], json!(-1));
+    assert_eq!(reply["error"]["message"], json!("declined by policy"));
+}
+
+#[tokio::test]
+async fn test_ping_from_server_is_answered() {
+    let client = spawn_relay_server().await;
+
+    let reply = relay(&client, "ping").await;
+    assert_eq!(reply, json!({ "id": "s1", "jsonrpc": "2.0", "result": {} }));
+}
+
+#[tokio::test]
+async fn test_roots_list_and_change_notification() {
+    let client = spawn_relay_server().await;
+
+    let reply = relay(&client, "roots/list").await;
+    assert_eq!(reply["error"]["code"], json!(-32601));
+
+    let workspace = Root {
+        name: Some("workspace".to_string()),
+        uri: "file:///work".to_string(),
+    };
+    client
+        .set_roots(vec![workspace.clone()])
+        .await
+        .expect("set roots");
+    let reply = relay(&client, "roots/list").await;
+    assert_eq!(
+        reply["result"],
+        json!({ "roots": [{ "name": "workspace", "uri": "file:///work" }] })
+    );
+    assert_eq!(relay(&client, "count").await, json!({ "changed": 0 }));
+
+    // Setting the same roots again is not a change.
+    client.set_roots(vec![workspace]).await.expect("set roots");
+    client
+        .set_roots(vec![Root {
+            name: None,
+            uri: "file:///elsewhere".to_string(),
+        }])
+        .await
+        .expect("set roots");
+    assert_eq!(relay(&client, "count").await, json!({ "changed": 1 }));
+    let reply = relay(&client, "roots/list").await;
+    assert_eq!(
+        reply["result"],
+        json!({ "roots": [{ "uri": "file:///elsewhere" }] })
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+#[expect(clippy::expect_used)]
+async fn spawn_relay_server() -> McpClient {
+    let client = McpClient::new_stdio_client(
+        OsString::from("sh"),
+        vec![OsString::from("-c"), OsString::from(RELAY_SERVER)],
+        None,
+    )
+    .await
+    .expect("spawn server");
+    let params = InitializeRequestParams {
+        capabilities: ClientCapabilities {
+            experimental: None,
+            roots: Some(ClientCapabilitiesRoots {
+                list_changed: Some(true),
+            }),
+            sampling: Some(json!({})),
+            elicitation: None,
+        },
+        client_info: Implementation {
+            name: "sampling test".to_string(),
+            version: "0.0.0".to_string(),
+            title: None,
+        },
+        protocol_version: MCP_SCHEMA_VERSION.to_string(),
+    };
+    client
+        .initialize(params, None, Some(DEFAULT_TIMEOUT))
+        .await
+        .expect("initialize");
+    client
+}
+
+/// Asks the server to send a `method` request to the client and returns the
+/// client's reply.
+#[expect(clippy::expect_used)]
+async fn relay(client: &McpClient, method: &str) -> serde_json::Value {
+    let result = timeout(
+        DEFAULT_TIMEOUT,
+        client.call_tool("relay".to_string(), Some(json!({ "method": method })), None),
+    )
+    .await
+    .expect("call timed out")
+    .expect("relay call");
+    result.structured_content.expect("structured content")
+}
@@ -0,0 +1,32 @@
+use std::net::SocketAddr;
+
+use clap::Parser;
+use codex_common::CliConfigOverrides;
+
+#[derive(Parser, Debug, Default)]
+#[command(version)]
+pub struct Cli {
+    /// Serve the JSON-RPC message stream over Streamable HTTP (`/mcp`) and
+    /// WebSocket (`/ws`) on this address instead of stdio.
+    #[arg(long, value_name = "ADDR")]
+    pub listen: Option<SocketAddr>,
+
+    /// Require HTTP and WebSocket clients to send
+    /// `Authorization: Bearer <TOKEN>`. Ignored without `--listen`.
+    #[arg(
+        long,
+        value_name = "TOKEN",
+        env = "CODEX_MCP_AUTH_TOKEN",
+        hide_env_values = true
+    )]
+    pub auth_token: Option<String>,
+
+    /// Also accept browser requests from this origin, e.g.
+    /// `https://app.example.com`. Requests whose `Origin` is neither
+    /// loopback nor listed are refused. Ignored without `--listen`.
+    #[arg(long = "allowed-origin", value_name = "ORIGIN")]
+    pub allowed_origins: Vec<String>,
+
+    #[clap(skip)]
+    pub config_overrides: CliConfigOverrides,
+}
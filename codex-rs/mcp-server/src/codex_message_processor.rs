@@ -13,6 +13,7 @@ use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
+use codex_core::protocol::ProtocolHandshake;
 use codex_core::protocol::ReviewDecision;
 use codex_login::AuthManager;
 use codex_protocol::mcp_protocol::AuthMode;
@@ -21,9 +22,15 @@ use mcp_types::JSONRPCErrorError;
 use mcp_types::RequestId;
 use tokio::sync::Mutex;
 use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
 use tracing::error;
 use uuid::Uuid;
 
+use crate::codex_tool_config::ensure_config_allowed;
+use crate::codex_tool_config::ensure_overrides_allowed;
+use crate::conversation_fanout::ConversationFanout;
+use crate::conversation_fanout::Delivery;
+use crate::conversation_tracker::ConversationTracker;
 use crate::error_code::INTERNAL_ERROR_CODE;
 use crate::error_code::INVALID_REQUEST_ERROR_CODE;
 use crate::json_to_toml::json_to_toml;
@@ -75,6 +82,12 @@ impl ActiveLogin {
     }
 }
 
+struct ConversationListener {
+    conversation_id: Uuid,
+    cancel: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
 /// Handles JSON-RPC messages for Codex conversations.
 pub(crate) struct CodexMessageProcessor {
     auth_manager: Arc<AuthManager>,
@@ -82,7 +95,9 @@ pub(crate) struct CodexMessageProcessor {
     outgoing: Arc<OutgoingMessageSender>,
     codex_linux_sandbox_exe: Option<PathBuf>,
     config: Arc<Config>,
-    conversation_listeners: HashMap<Uuid, oneshot::Sender<()>>,
+    conversation_listeners: Mutex<HashMap<Uuid, ConversationListener>>,
+    conversation_tracker: ConversationTracker,
+    conversation_fanout: ConversationFanout,
     active_login: Arc<Mutex<Option<ActiveLogin>>>,
     // Queue of pending interrupt requests per conversation. We reply when TurnAborted arrives.
     pending_interrupts: Arc<Mutex<HashMap<Uuid, Vec<RequestId>>>>,
@@ -95,25 +110,34 @@ impl CodexMessageProcessor {
         outgoing: Arc<OutgoingMessageSender>,
         codex_linux_sandbox_exe: Option<PathBuf>,
         config: Arc<Config>,
+        conversation_fanout: ConversationFanout,
     ) -> Self {
+        let conversation_tracker = ConversationTracker::new(
+            config.mcp_server.clone(),
+            conversation_manager.clone(),
+            outgoing.clone(),
+        );
         Self {
             auth_manager,
             conversation_manager,
             outgoing,
             codex_linux_sandbox_exe,
             config,
-            conversation_listeners: HashMap::new(),
+            conversation_listeners: Mutex::new(HashMap::new()),
+            conversation_fanout,
+            conversation_tracker,
             active_login: Arc::new(Mutex::new(None)),
             pending_interrupts: Arc::new(Mutex::new(HashMap::new())),
         }
     }
 
-    pub async fn process_request(&mut self, request: ClientRequest) {
+    pub async fn process_request(&self, request: ClientRequest) {
         match request {
             ClientRequest::NewConversation { request_id, params } => {
-                // Do not tokio::spawn() to process new_conversation()
-                // asynchronously because we need to ensure the conversation is
-                // created before processing any subsequent messages.
+                // Not handled on its own task (see
+                // `MessageProcessor::handles_in_order`) because the
+                // conversation must exist before any subsequent message is
+                // processed.
                 self.process_new_conversation(request_id, params).await;
             }
             ClientRequest::SendUserMessage { request_id, params } => {
@@ -149,7 +173,7 @@ impl CodexMessageProcessor {
         }
     }
 
-    async fn login_chatgpt(&mut self, request_id: RequestId) {
+    async fn login_chatgpt(&self, request_id: RequestId) {
         let config = self.config.as_ref();
 
         let opts = LoginServerOptions {
@@ -251,7 +275,7 @@ impl CodexMessageProcessor {
         }
     }
 
-    async fn cancel_login_chatgpt(&mut self, request_id: RequestId, login_id: Uuid) {
+    async fn cancel_login_chatgpt(&self, request_id: RequestId, login_id: Uuid) {
         let mut guard = self.active_login.lock().await;
         if guard.as_ref().map(|l| l.login_id) == Some(login_id) {
             if let Some(active) = guard.take() {
@@ -275,7 +299,7 @@ impl CodexMessageProcessor {
         }
     }
 
-    async fn logout_chatgpt(&mut self, request_id: RequestId) {
+    async fn logout_chatgpt(&self, request_id: RequestId) {
         {
             // Cancel any active login attempt.
             let mut guard = self.active_login.lock().await;
@@ -355,7 +379,17 @@ impl CodexMessageProcessor {
     }
 
     async fn process_new_conversation(&self, request_id: RequestId, params: NewConversationParams) {
-        let config = match derive_config_from_params(params, self.codex_linux_sandbox_exe.clone()) {
+        let allow = &self.config.mcp_server.allow;
+        let config =
+            ensure_overrides_allowed(params.config.iter().flatten().map(|(k, _)| k), allow)
+                .and_then(|()| {
+                    derive_config_from_params(params, self.codex_linux_sandbox_exe.clone())
+                })
+                .and_then(|config| {
+                    ensure_config_allowed(&config, allow)?;
+                    Ok(config)
+                });
+        let config = match config {
             Ok(config) => config,
             Err(err) => {
                 let error = JSONRPCErrorError {
@@ -372,12 +406,17 @@ impl CodexMessageProcessor {
             Ok(conversation_id) => {
                 let NewConversation {
                     conversation_id,
+                    conversation,
                     session_configured,
-                    ..
                 } = conversation_id;
+                self.conversation_tracker
+                    .track(conversation_id, conversation)
+                    .await;
                 let response = NewConversationResponse {
                     conversation_id: ConversationId(conversation_id),
                     model: session_configured.model,
+                    protocol_version: session_configured.protocol.protocol_version,
+                    capabilities: session_configured.protocol.capabilities,
                 };
                 self.outgoing.send_response(request_id, response).await;
             }
@@ -486,7 +525,7 @@ impl CodexMessageProcessor {
     }
 
     async fn interrupt_conversation(
-        &mut self,
+        &self,
         request_id: RequestId,
         params: InterruptConversationParams,
     ) {
@@ -516,11 +555,14 @@ impl CodexMessageProcessor {
     }
 
     async fn add_conversation_listener(
-        &mut self,
+        &self,
         request_id: RequestId,
         params: AddConversationListenerParams,
     ) {
-        let AddConversationListenerParams { conversation_id } = params;
+        let AddConversationListenerParams {
+            conversation_id,
+            broadcast,
+        } = params;
         let Ok(conversation) = self
             .conversation_manager
             .get_conversation(conversation_id.0)
@@ -536,70 +578,73 @@ impl CodexMessageProcessor {
         };
 
         let subscription_id = Uuid::new_v4();
+        let missed_events = self
+            .conversation_tracker
+            .attach(conversation_id.0, subscription_id)
+            .await;
+        let mut deliveries = self
+            .conversation_fanout
+            .subscribe(
+                conversation_id.0,
+                conversation.clone(),
+                subscription_id,
+                broadcast,
+                missed_events,
+                &self.conversation_tracker,
+            )
+            .await;
+        let response = AddConversationSubscriptionResponse { subscription_id };
+        self.outgoing.send_response(request_id, response).await;
+
         let (cancel_tx, mut cancel_rx) = oneshot::channel();
-        self.conversation_listeners
-            .insert(subscription_id, cancel_tx);
-        let outgoing_for_task = self.outgoing.clone();
-        let pending_interrupts = self.pending_interrupts.clone();
-        tokio::spawn(async move {
+        let listener = EventForwarder {
+            conversation_id,
+            subscription_id,
+            conversation,
+            outgoing: self.outgoing.clone(),
+            pending_interrupts: self.pending_interrupts.clone(),
+        };
+        let task = tokio::spawn(async move {
             loop {
                 tokio::select! {
                     _ = &mut cancel_rx => {
                         // User has unsubscribed, so exit this task.
                         break;
                     }
-                    event = conversation.next_event() => {
-                        let event = match event {
-                            Ok(event) => event,
-                            Err(err) => {
-                                tracing::warn!("conversation.next_event() failed with: {err}");
-                                break;
-                            }
-                        };
-
-                        // For now, we send a notification for every event,
-                        // JSON-serializing the `Event` as-is, but we will move
-                        // to creating a special enum for notifications with a
-                        // stable wire format.
-                        let method = format!("codex/event/{}", event.msg);
-                        let mut params = match serde_json::to_value(event.clone()) {
-                            Ok(serde_json::Value::Object(map)) => map,
-                            Ok(_) => {
-                                tracing::error!("event did not serialize to an object");
-                                continue;
-                            }
-                            Err(err) => {
-                                tracing::error!("failed to serialize event: {err}");
-                                continue;
-                            }
-                        };
-                        params.insert("conversationId".to_string(), conversation_id.to_string().into());
-
-                        outgoing_for_task.send_notification(OutgoingNotification {
-                            method,
-                            params: Some(params.into()),
-                        })
-                        .await;
-
-                        apply_bespoke_event_handling(event.clone(), conversation_id, conversation.clone(), outgoing_for_task.clone(), pending_interrupts.clone()).await;
-                    }
+                    delivery = deliveries.recv() => match delivery {
+                        Some(delivery) => listener.forward(delivery).await,
+                        None => break,
+                    },
                 }
             }
         });
-        let response = AddConversationSubscriptionResponse { subscription_id };
-        self.outgoing.send_response(request_id, response).await;
+        self.conversation_listeners.lock().await.insert(
+            subscription_id,
+            ConversationListener {
+                conversation_id: conversation_id.0,
+                cancel: cancel_tx,
+                task,
+            },
+        );
     }
 
     async fn remove_conversation_listener(
-        &mut self,
+        &self,
         request_id: RequestId,
         params: RemoveConversationListenerParams,
     ) {
         let RemoveConversationListenerParams { subscription_id } = params;
-        match self.conversation_listeners.remove(&subscription_id) {
-            Some(sender) => {
-                // Signal the spawned task to exit and acknowledge.
-                let _ = sender.send(());
+        let listener = self
+            .conversation_listeners
+            .lock()
+            .await
+            .remove(&subscription_id);
+        match listener {
+            Some(listener) => {
+                // Signal the spawned task to exit, wait for the conversation
+                // to be let go of if it was the last listener, and
+                // acknowledge.
+                self.stop_listener(subscription_id, listener).await;
                 let response = RemoveConversationSubscriptionResponse {};
                 self.outgoing.send_response(request_id, response).await;
             }
@@ -614,6 +659,32 @@ impl CodexMessageProcessor {
         }
     }
 
+    /// Stops every listener and applies the `[mcp_server]` disconnect policy
+    /// to the conversations started by the client.
+    pub(crate) async fn on_client_disconnect(&self) {
+        self.conversation_tracker.mark_disconnected();
+        let listeners: Vec<(Uuid, ConversationListener)> =
+            self.conversation_listeners.lock().await.drain().collect();
+        for (subscription_id, listener) in listeners {
+            self.stop_listener(subscription_id, listener).await;
+        }
+        self.conversation_tracker.apply_disconnect_policy().await;
+    }
+
+    async fn stop_listener(&self, subscription_id: Uuid, listener: ConversationListener) {
+        let _ = listener.cancel.send(());
+        let _ = listener.task.await;
+        let unread = self
+            .conversation_fanout
+            .unsubscribe(listener.conversation_id, subscription_id)
+            .await;
+        // Detach only after the fanout stopped reading, should this have been
+        // the last listener, so the conversation never has two consumers.
+        self.conversation_tracker
+            .detach(listener.conversation_id, subscription_id, unread)
+            .await;
+    }
+
     async fn git_diff_to_origin(&self, request_id: RequestId, cwd: PathBuf) {
         let diff = git_diff_to_remote(&cwd).await;
         match diff {
@@ -636,6 +707,65 @@ impl CodexMessageProcessor {
     }
 }
 
+/// Forwards the events of a conversation to a listener.
+struct EventForwarder {
+    conversation_id: ConversationId,
+    subscription_id: Uuid,
+    conversation: Arc<CodexConversation>,
+    outgoing: Arc<OutgoingMessageSender>,
+    pending_interrupts: Arc<Mutex<HashMap<Uuid, Vec<RequestId>>>>,
+}
+
+impl EventForwarder {
+    async fn forward(&self, delivery: Delivery) {
+        let Delivery { event, primary } = delivery;
+
+        // For now, we send a notification for every event,
+        // JSON-serializing the `Event` as-is, but we will move
+        // to creating a special enum for notifications with a
+        // stable wire format.
+        let method = format!("codex/event/{}", event.msg);
+        let mut params = match serde_json::to_value(event.clone()) {
+            Ok(serde_json::Value::Object(map)) => map,
+            Ok(_) => {
+                tracing::error!("event did not serialize to an object");
+                return;
+            }
+            Err(err) => {
+                tracing::error!("failed to serialize event: {err}");
+                return;
+            }
+        };
+        params.insert(
+            "conversationId".to_string(),
+            self.conversation_id.to_string().into(),
+        );
+        params.insert(
+            "subscriptionId".to_string(),
+            self.subscription_id.to_string().into(),
+        );
+
+        self.outgoing
+            .send_notification(OutgoingNotification {
+                method,
+                params: Some(params.into()),
+            })
+            .await;
+
+        if !primary {
+            return;
+        }
+        apply_bespoke_event_handling(
+            event,
+            self.conversation_id,
+            self.conversation.clone(),
+            self.outgoing.clone(),
+            self.pending_interrupts.clone(),
+        )
+        .await;
+    }
+}
+
 async fn apply_bespoke_event_handling(
     event: Event,
     conversation_id: ConversationId,
@@ -672,6 +802,7 @@ async fn apply_bespoke_event_handling(
             command,
             cwd,
             reason,
+            matched_rule,
         }) => {
             let params = ExecCommandApprovalParams {
                 conversation_id,
@@ -679,6 +810,7 @@ async fn apply_bespoke_event_handling(
                 command,
                 cwd,
                 reason,
+                matched_rule,
             };
             let value = serde_json::to_value(&params).unwrap_or_default();
             let rx = outgoing
@@ -724,6 +856,8 @@ fn derive_config_from_params(
         base_instructions,
         include_plan_tool,
         include_apply_patch_tool,
+        protocol_version,
+        capabilities,
     } = params;
     let overrides = ConfigOverrides {
         model,
@@ -739,6 +873,8 @@ fn derive_config_from_params(
         disable_response_storage: None,
         show_raw_agent_reasoning: None,
         tools_web_search_request: None,
+        max_turns: None,
+        max_wall_time: None,
     };
 
     let cli_overrides = cli_overrides
@@ -747,7 +883,14 @@ fn derive_config_from_params(
         .map(|(k, v)| (k, json_to_toml(v)))
         .collect();
 
-    Config::load_with_cli_overrides(cli_overrides, overrides)
+    let mut config = Config::load_with_cli_overrides(cli_overrides, overrides)?;
+    // Clients that predate the handshake send neither field and get
+    // `ProtocolHandshake::legacy()`.
+    config.protocol = ProtocolHandshake {
+        protocol_version: protocol_version.unwrap_or(0),
+        capabilities: capabilities.unwrap_or_default(),
+    };
+    Ok(config)
 }
 
 async fn on_patch_approval_response(
@@ -764,6 +907,7 @@ async fn on_patch_approval_response(
                 .submit(Op::PatchApproval {
                     id: event_id.clone(),
                     decision: ReviewDecision::Denied,
+                    accepted_files: None,
                 })
                 .await
             {
@@ -785,6 +929,7 @@ async fn on_patch_approval_response(
         .submit(Op::PatchApproval {
             id: event_id,
             decision: response.decision,
+            accepted_files: None,
         })
         .await
     {
@@ -821,6 +966,7 @@ async fn on_exec_approval_response(
         .submit(Op::ExecApproval {
             id: event_id,
             decision: response.decision,
+            allow_pattern: None,
         })
         .await
     {
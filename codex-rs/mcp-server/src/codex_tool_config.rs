@@ -1,6 +1,9 @@
 //! Configuration object accepted by the `codex` MCP tool-call.
 
+use codex_core::config::Config;
+use codex_core::config_types::McpServerAllowList;
 use codex_core::protocol::AskForApproval;
+use codex_core::protocol::SandboxPolicy;
 use codex_protocol::config_types::SandboxMode;
 use mcp_types::Tool;
 use mcp_types::ToolInputSchema;
@@ -9,6 +12,7 @@ use schemars::r#gen::SchemaSettings;
 use serde::Deserialize;
 use serde::Serialize;
 use std::collections::HashMap;
+use std::io::ErrorKind;
 use std::path::PathBuf;
 
 use crate::json_to_toml::json_to_toml;
@@ -28,6 +32,11 @@ pub struct CodexToolCallParam {
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub profile: Option<String>,
 
+    /// Key of the model provider to use, from `model_providers` in
+    /// config.toml (e.g. "openai", "oss").
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model_provider: Option<String>,
+
     /// Working directory for the session. If relative, it is resolved against
     /// the server process's current working directory.
     #[serde(default, skip_serializing_if = "Option::is_none")]
@@ -132,15 +141,18 @@ pub(crate) fn create_tool_for_codex_tool_call_param() -> Tool {
 
 impl CodexToolCallParam {
     /// Returns the initial user prompt to start the Codex conversation and the
-    /// effective Config object generated from the supplied parameters.
+    /// effective Config object generated from the supplied parameters, which
+    /// must stay within `allow`.
     pub fn into_config(
         self,
         codex_linux_sandbox_exe: Option<PathBuf>,
+        allow: &McpServerAllowList,
     ) -> std::io::Result<(String, codex_core::config::Config)> {
         let Self {
             prompt,
             model,
             profile,
+            model_provider,
             cwd,
             approval_policy,
             sandbox,
@@ -156,7 +168,7 @@ impl CodexToolCallParam {
             cwd: cwd.map(PathBuf::from),
             approval_policy: approval_policy.map(Into::into),
             sandbox_mode: sandbox.map(Into::into),
-            model_provider: None,
+            model_provider,
             codex_linux_sandbox_exe,
             base_instructions,
             include_plan_tool,
@@ -164,20 +176,120 @@ impl CodexToolCallParam {
             disable_response_storage: None,
             show_raw_agent_reasoning: None,
             tools_web_search_request: None,
+            max_turns: None,
+            max_wall_time: None,
         };
 
+        let cli_overrides = cli_overrides.unwrap_or_default();
+        ensure_overrides_allowed(cli_overrides.keys(), allow)?;
         let cli_overrides = cli_overrides
-            .unwrap_or_default()
             .into_iter()
             .map(|(k, v)| (k, json_to_toml(v)))
             .collect();
 
         let cfg = codex_core::config::Config::load_with_cli_overrides(cli_overrides, overrides)?;
+        ensure_config_allowed(&cfg, allow)?;
 
         Ok((prompt, cfg))
     }
 }
 
+/// Config keys whose effective value [`ensure_config_allowed`] checks, so they
+/// may be overridden whenever `[mcp_server.allow]` is set.
+const CHECKED_CONFIG_KEYS: &[&str] = &[
+    "sandbox_mode",
+    "approval_policy",
+    "model_provider",
+    "profile",
+];
+
+/// Rejects free-form `config` overrides once `[mcp_server.allow]` sets any
+/// list, unless the key is checked against the effective configuration or
+/// listed in `config_keys`. Otherwise an override such as `notify` or
+/// `sandbox_workspace_write.writable_roots` would slip past the allow-list.
+pub(crate) fn ensure_overrides_allowed<'a>(
+    keys: impl IntoIterator<Item = &'a String>,
+    allow: &McpServerAllowList,
+) -> std::io::Result<()> {
+    if *allow == McpServerAllowList::default() {
+        return Ok(());
+    }
+    let allowed_keys = allow.config_keys.as_deref().unwrap_or_default();
+    for key in keys {
+        let covered = |allowed: &str| {
+            key == allowed
+                || key
+                    .strip_prefix(allowed)
+                    .is_some_and(|rest| rest.starts_with('.'))
+        };
+        let checked = CHECKED_CONFIG_KEYS.contains(&key.as_str());
+        if !checked && !allowed_keys.iter().any(|allowed| covered(allowed)) {
+            return Err(std::io::Error::new(
+                ErrorKind::PermissionDenied,
+                format!("config override `{key}` is not allowed by [mcp_server.allow]"),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Rejects a conversation configuration that selects anything outside the
+/// server's `[mcp_server.allow]` lists. The effective configuration is checked
+/// so that values reached through `config` overrides or a profile count too.
+pub(crate) fn ensure_config_allowed(
+    config: &Config,
+    allow: &McpServerAllowList,
+) -> std::io::Result<()> {
+    let deny = |what: String| {
+        Err(std::io::Error::new(
+            ErrorKind::PermissionDenied,
+            format!("{what} is not allowed by [mcp_server.allow]"),
+        ))
+    };
+
+    let sandbox_mode = match config.sandbox_policy {
+        SandboxPolicy::ReadOnly { .. } => SandboxMode::ReadOnly,
+        SandboxPolicy::WorkspaceWrite { .. } => SandboxMode::WorkspaceWrite,
+        SandboxPolicy::DangerFullAccess { .. } => SandboxMode::DangerFullAccess,
+    };
+    if let Some(allowed) = &allow.sandbox_modes
+        && !allowed.contains(&sandbox_mode)
+    {
+        return deny(format!("sandbox mode `{sandbox_mode}`"));
+    }
+    if let Some(allowed) = &allow.approval_policies
+        && !allowed.contains(&config.approval_policy)
+    {
+        return deny(format!("approval policy `{}`", config.approval_policy));
+    }
+    if let Some(allowed) = &allow.model_providers
+        && !allowed.contains(&config.model_provider_id)
+    {
+        return deny(format!("model provider `{}`", config.model_provider_id));
+    }
+    if let Some(allowed) = &allow.profiles
+        && let Some(profile) = &config.active_profile
+        && !allowed.contains(profile)
+    {
+        return deny(format!("profile `{profile}`"));
+    }
+    if let Some(allowed) = &allow.cwds {
+        // Resolve symlinks and `..` so the cwd cannot escape its root.
+        let cwd = config
+            .cwd
+            .canonicalize()
+            .unwrap_or_else(|_| config.cwd.clone());
+        let inside = allowed.iter().any(|root| {
+            let root = root.canonicalize().unwrap_or_else(|_| root.clone());
+            cwd.starts_with(root)
+        });
+        if !inside {
+            return deny(format!("working directory `{}`", config.cwd.display()));
+        }
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct CodexToolCallReplyParam {
@@ -219,11 +331,129 @@ pub(crate) fn create_tool_for_codex_tool_call_reply_param() -> Tool {
     }
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CodexToolCallListConversationsParam {
+    /// Maximum number of archived sessions to return, newest first.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<usize>,
+}
+
+/// Builds a `Tool` definition for the `codex-list-conversations` tool-call.
+pub(crate) fn create_tool_for_codex_tool_call_list_conversations_param() -> Tool {
+    let schema = SchemaSettings::draft2019_09()
+        .with(|s| {
+            s.inline_subschemas = true;
+            s.option_add_null_type = false;
+        })
+        .into_generator()
+        .into_root_schema_for::<CodexToolCallListConversationsParam>();
+
+    #[expect(clippy::expect_used)]
+    let schema_value = serde_json::to_value(&schema)
+        .expect("Codex list conversations tool schema should serialise to JSON");
+
+    let tool_input_schema =
+        serde_json::from_value::<ToolInputSchema>(schema_value).unwrap_or_else(|e| {
+            panic!("failed to create Tool from schema: {e}");
+        });
+
+    Tool {
+        name: "codex-list-conversations".to_string(),
+        title: Some("Codex List Conversations".to_string()),
+        input_schema: tool_input_schema,
+        output_schema: None,
+        description: Some(
+            "List the Codex sessions that are active in this server and the archived sessions that can be resumed.".to_string(),
+        ),
+        annotations: None,
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CodexToolCallResumeParam {
+    /// The *session id* of an archived conversation to resume.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+
+    /// Path to the rollout file of the conversation to resume. Takes
+    /// precedence over `sessionId`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rollout_path: Option<String>,
+
+    /// The *next user prompt* to continue the Codex conversation.
+    pub prompt: String,
+}
+
+/// Builds a `Tool` definition for the `codex-resume` tool-call.
+pub(crate) fn create_tool_for_codex_tool_call_resume_param() -> Tool {
+    let schema = SchemaSettings::draft2019_09()
+        .with(|s| {
+            s.inline_subschemas = true;
+            s.option_add_null_type = false;
+        })
+        .into_generator()
+        .into_root_schema_for::<CodexToolCallResumeParam>();
+
+    #[expect(clippy::expect_used)]
+    let schema_value =
+        serde_json::to_value(&schema).expect("Codex resume tool schema should serialise to JSON");
+
+    let tool_input_schema =
+        serde_json::from_value::<ToolInputSchema>(schema_value).unwrap_or_else(|e| {
+            panic!("failed to create Tool from schema: {e}");
+        });
+
+    Tool {
+        name: "codex-resume".to_string(),
+        title: Some("Codex Resume".to_string()),
+        input_schema: tool_input_schema,
+        output_schema: None,
+        description: Some(
+            "Resume an archived Codex session from its rollout file, by session id or path, and continue it with a prompt.".to_string(),
+        ),
+        annotations: None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use pretty_assertions::assert_eq;
 
+    #[test]
+    fn overrides_are_refused_once_an_allow_list_is_set() {
+        let keys = |keys: &[&str]| keys.iter().map(|k| k.to_string()).collect::<Vec<_>>();
+        let restricted = McpServerAllowList {
+            sandbox_modes: Some(vec![SandboxMode::ReadOnly]),
+            config_keys: Some(vec!["tools".to_string()]),
+            ..Default::default()
+        };
+
+        // Without any list every override is accepted.
+        assert!(
+            ensure_overrides_allowed(&keys(&["notify"]), &McpServerAllowList::default()).is_ok()
+        );
+
+        for key in ["notify", "sandbox_workspace_write.writable_roots", "toolsx"] {
+            let err = ensure_overrides_allowed(&keys(&[key]), &restricted)
+                .expect_err("override should be refused");
+            assert_eq!(ErrorKind::PermissionDenied, err.kind());
+            assert_eq!(
+                format!("config override `{key}` is not allowed by [mcp_server.allow]"),
+                err.to_string()
+            );
+        }
+
+        // Keys that are checked against the effective config, and keys under
+        // `config_keys`, are accepted.
+        assert!(
+            ensure_overrides_allowed(&keys(&["sandbox_mode", "tools.web_search"]), &restricted)
+                .is_ok()
+        );
+    }
+
     /// We include a test to verify the exact JSON schema as "executable
     /// documentation" for the schema. When can track changes to this test as a
     /// way to audit changes to the generated schema.
@@ -282,6 +512,10 @@ mod tests {
                 "description": "Optional override for the model name (e.g. \"o3\", \"o4-mini\").",
                 "type": "string"
               },
+              "model-provider": {
+                "description": "Key of the model provider to use, from `model_providers` in config.toml (e.g. \"openai\", \"oss\").",
+                "type": "string"
+              },
               "profile": {
                 "description": "Configuration profile from config.toml to specify default options.",
                 "type": "string"
@@ -331,4 +565,59 @@ mod tests {
         });
         assert_eq!(expected_tool_json, tool_json);
     }
+
+    #[test]
+    fn verify_codex_tool_list_conversations_json_schema() {
+        let tool = create_tool_for_codex_tool_call_list_conversations_param();
+        let tool_json = serde_json::to_value(&tool).expect("tool serializes");
+        let expected_tool_json = serde_json::json!({
+          "description": "List the Codex sessions that are active in this server and the archived sessions that can be resumed.",
+          "inputSchema": {
+            "properties": {
+              "limit": {
+                "description": "Maximum number of archived sessions to return, newest first.",
+                "format": "uint",
+                "minimum": 0.0,
+                "type": "integer"
+              },
+            },
+            "type": "object",
+          },
+          "name": "codex-list-conversations",
+          "title": "Codex List Conversations",
+        });
+        assert_eq!(expected_tool_json, tool_json);
+    }
+
+    #[test]
+    fn verify_codex_tool_resume_json_schema() {
+        let tool = create_tool_for_codex_tool_call_resume_param();
+        let tool_json = serde_json::to_value(&tool).expect("tool serializes");
+        let expected_tool_json = serde_json::json!({
+          "description": "Resume an archived Codex session from its rollout file, by session id or path, and continue it with a prompt.",
+          "inputSchema": {
+            "properties": {
+              "prompt": {
+                "description": "The *next user prompt* to continue the Codex conversation.",
+                "type": "string"
+              },
+              "rolloutPath": {
+                "description": "Path to the rollout file of the conversation to resume. Takes precedence over `sessionId`.",
+                "type": "string"
+              },
+              "sessionId": {
+                "description": "The *session id* of an archived conversation to resume.",
+                "type": "string"
+              },
+            },
+            "required": [
+              "prompt",
+            ],
+            "type": "object",
+          },
+          "name": "codex-resume",
+          "title": "Codex Resume",
+        });
+        assert_eq!(expected_tool_json, tool_json);
+    }
 }
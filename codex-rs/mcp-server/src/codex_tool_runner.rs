@@ -2,7 +2,9 @@
 //! Tokio task. Separated from `message_processor.rs` to keep that file small
 //! and to make future feature-growth easier to manage.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use codex_core::CodexConversation;
@@ -14,13 +16,23 @@ use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
+use codex_core::protocol::ExecCommandBeginEvent;
+use codex_core::protocol::ExecCommandEndEvent;
+use codex_core::protocol::FileChange;
 use codex_core::protocol::InputItem;
 use codex_core::protocol::Op;
+use codex_core::protocol::PatchApplyBeginEvent;
+use codex_core::protocol::PatchApplyEndEvent;
 use codex_core::protocol::Submission;
 use codex_core::protocol::TaskCompleteEvent;
+use codex_core::protocol::TokenUsage;
+use codex_core::protocol::TurnAbortedEvent;
+use codex_core::protocol::TurnDiffEvent;
 use mcp_types::CallToolResult;
 use mcp_types::ContentBlock;
+use mcp_types::ProgressToken;
 use mcp_types::RequestId;
+use mcp_types::ResourceLink;
 use mcp_types::TextContent;
 use serde_json::json;
 use tokio::sync::Mutex;
@@ -44,6 +56,7 @@ pub async fn run_codex_tool_session(
     outgoing: Arc<OutgoingMessageSender>,
     conversation_manager: Arc<ConversationManager>,
     running_requests_id_to_codex_uuid: Arc<Mutex<HashMap<RequestId, Uuid>>>,
+    progress_token: Option<ProgressToken>,
 ) {
     let NewConversation {
         conversation_id,
@@ -110,6 +123,7 @@ pub async fn run_codex_tool_session(
         outgoing,
         id,
         running_requests_id_to_codex_uuid,
+        progress_token,
     )
     .await;
 }
@@ -121,6 +135,7 @@ pub async fn run_codex_tool_session_reply(
     prompt: String,
     running_requests_id_to_codex_uuid: Arc<Mutex<HashMap<RequestId, Uuid>>>,
     session_id: Uuid,
+    progress_token: Option<ProgressToken>,
 ) {
     running_requests_id_to_codex_uuid
         .lock()
@@ -146,6 +161,7 @@ pub async fn run_codex_tool_session_reply(
         outgoing,
         request_id,
         running_requests_id_to_codex_uuid,
+        progress_token,
     )
     .await;
 }
@@ -155,12 +171,18 @@ async fn run_codex_tool_session_inner(
     outgoing: Arc<OutgoingMessageSender>,
     request_id: RequestId,
     running_requests_id_to_codex_uuid: Arc<Mutex<HashMap<RequestId, Uuid>>>,
+    progress_token: Option<ProgressToken>,
 ) {
     let request_id_str = match &request_id {
         RequestId::String(s) => s.clone(),
         RequestId::Integer(n) => n.to_string(),
     };
 
+    // Progress only ever increases, as MCP requires; there is no total
+    // because the number of steps in a turn is not known up front.
+    let mut progress = 0.0;
+    let mut summary = ToolCallSummary::default();
+
     // Stream events until the task needs to pause for user interaction or
     // completes.
     loop {
@@ -173,12 +195,24 @@ async fn run_codex_tool_session_inner(
                     )
                     .await;
 
+                summary.observe(&event.msg);
+
+                if let Some(progress_token) = &progress_token
+                    && let Some(message) = progress_message(&event.msg)
+                {
+                    progress += 1.0;
+                    outgoing
+                        .send_progress_notification(progress_token.clone(), progress, message)
+                        .await;
+                }
+
                 match event.msg {
                     EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
                         command,
                         cwd,
                         call_id,
                         reason: _,
+                        matched_rule: _,
                     }) => {
                         handle_exec_approval_request(
                             command,
@@ -221,20 +255,10 @@ async fn run_codex_tool_session_inner(
                         .await;
                         continue;
                     }
-                    EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
-                        let text = match last_agent_message {
-                            Some(msg) => msg.clone(),
-                            None => "".to_string(),
-                        };
-                        let result = CallToolResult {
-                            content: vec![ContentBlock::TextContent(TextContent {
-                                r#type: "text".to_string(),
-                                text,
-                                annotations: None,
-                            })],
-                            is_error: None,
-                            structured_content: None,
-                        };
+                    EventMsg::TaskComplete(TaskCompleteEvent {
+                        last_agent_message, ..
+                    }) => {
+                        let result = summary.into_result(last_agent_message);
                         outgoing.send_response(request_id.clone(), result).await;
                         // unregister the id so we don't keep it in the map
                         running_requests_id_to_codex_uuid
@@ -243,6 +267,29 @@ async fn run_codex_tool_session_inner(
                             .remove(&request_id);
                         break;
                     }
+                    EventMsg::TurnAborted(TurnAbortedEvent { reason }) => {
+                        // A request cancelled with `notifications/cancelled`
+                        // has already been unregistered, and MCP says it gets
+                        // no response. Any other abort still ends the call.
+                        let still_running = running_requests_id_to_codex_uuid
+                            .lock()
+                            .await
+                            .remove(&request_id)
+                            .is_some();
+                        if still_running {
+                            let result = CallToolResult {
+                                content: vec![ContentBlock::TextContent(TextContent {
+                                    r#type: "text".to_string(),
+                                    text: format!("Codex turn aborted: {reason:?}"),
+                                    annotations: None,
+                                })],
+                                is_error: Some(true),
+                                structured_content: None,
+                            };
+                            outgoing.send_response(request_id.clone(), result).await;
+                        }
+                        break;
+                    }
                     EventMsg::SessionConfigured(_) => {
                         tracing::error!("unexpected SessionConfigured event");
                     }
@@ -259,23 +306,30 @@ async fn run_codex_tool_session_inner(
                     | EventMsg::AgentReasoningRawContentDelta(_)
                     | EventMsg::TaskStarted
                     | EventMsg::TokenCount(_)
+                    | EventMsg::TokenUsageBreakdown(_)
                     | EventMsg::AgentReasoning(_)
                     | EventMsg::AgentReasoningSectionBreak(_)
                     | EventMsg::McpToolCallBegin(_)
                     | EventMsg::McpToolCallEnd(_)
                     | EventMsg::McpListToolsResponse(_)
+                    | EventMsg::BackgroundTaskUpdate(_)
+                    | EventMsg::ListBackgroundTasksResponse(_)
                     | EventMsg::ExecCommandBegin(_)
                     | EventMsg::ExecCommandOutputDelta(_)
                     | EventMsg::ExecCommandEnd(_)
+                    | EventMsg::ContainerImagePull(_)
                     | EventMsg::BackgroundEvent(_)
+                    | EventMsg::DegradedMode(_)
                     | EventMsg::StreamError(_)
                     | EventMsg::PatchApplyBegin(_)
                     | EventMsg::PatchApplyEnd(_)
                     | EventMsg::TurnDiff(_)
+                    | EventMsg::TurnReverted(_)
+                    | EventMsg::ContextCompacted(_)
+                    | EventMsg::ProfileSwitched(_)
                     | EventMsg::WebSearchBegin(_)
                     | EventMsg::GetHistoryEntryResponse(_)
                     | EventMsg::PlanUpdate(_)
-                    | EventMsg::TurnAborted(_)
                     | EventMsg::ConversationHistory(_)
                     | EventMsg::ShutdownComplete => {
                         // For now, we do not do anything extra for these
@@ -305,3 +359,230 @@ async fn run_codex_tool_session_inner(
         }
     }
 }
+
+/// What a turn changed, gathered from its events so the final tool result
+/// can carry it as `structuredContent` and resource links.
+#[derive(Default)]
+struct ToolCallSummary {
+    changed_files: BTreeMap<PathBuf, &'static str>,
+    unified_diff: Option<String>,
+    token_usage: Option<TokenUsage>,
+}
+
+impl ToolCallSummary {
+    fn observe(&mut self, msg: &EventMsg) {
+        match msg {
+            EventMsg::PatchApplyBegin(PatchApplyBeginEvent { changes, .. }) => {
+                for (path, change) in changes {
+                    let (path, kind) = match change {
+                        FileChange::Add { .. } => (path, "add"),
+                        FileChange::Delete => (path, "delete"),
+                        FileChange::Update {
+                            move_path: Some(dest),
+                            ..
+                        } => {
+                            self.changed_files.insert(path.clone(), "delete");
+                            (dest, "add")
+                        }
+                        FileChange::Update { .. } => (path, "update"),
+                    };
+                    // A file added earlier in the turn is still new, however
+                    // it was edited afterwards.
+                    let kind = match self.changed_files.get(path) {
+                        Some(&"add") if kind == "update" => "add",
+                        _ => kind,
+                    };
+                    self.changed_files.insert(path.clone(), kind);
+                }
+            }
+            EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => {
+                self.unified_diff = Some(unified_diff.clone());
+            }
+            EventMsg::TokenCount(usage) => self.token_usage = Some(usage.clone()),
+            _ => {}
+        }
+    }
+
+    fn into_result(self, last_agent_message: Option<String>) -> CallToolResult {
+        let Self {
+            changed_files,
+            unified_diff,
+            token_usage,
+        } = self;
+
+        let mut content = vec![ContentBlock::TextContent(TextContent {
+            r#type: "text".to_string(),
+            text: last_agent_message.clone().unwrap_or_default(),
+            annotations: None,
+        })];
+        // Deleted files have nothing left to link to.
+        for path in changed_files
+            .iter()
+            .filter(|(_, kind)| **kind != "delete")
+            .map(|(path, _)| path)
+        {
+            content.push(ContentBlock::ResourceLink(ResourceLink {
+                annotations: None,
+                description: None,
+                mime_type: None,
+                name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned())
+                    .unwrap_or_else(|| path.display().to_string()),
+                size: None,
+                title: None,
+                r#type: "resource_link".to_string(),
+                uri: format!("file://{}", path.display()),
+            }));
+        }
+
+        let changed_files: Vec<serde_json::Value> = changed_files
+            .into_iter()
+            .map(|(path, kind)| json!({ "path": path, "kind": kind }))
+            .collect();
+        let structured_content = json!({
+            "lastAgentMessage": last_agent_message,
+            "changedFiles": changed_files,
+            "unifiedDiff": unified_diff,
+            "tokenUsage": token_usage,
+        });
+
+        CallToolResult {
+            content,
+            is_error: None,
+            structured_content: Some(structured_content),
+        }
+    }
+}
+
+/// A one-line description of `msg` for an MCP progress notification, or
+/// `None` for events that are not worth reporting as activity.
+fn progress_message(msg: &EventMsg) -> Option<String> {
+    match msg {
+        EventMsg::ExecCommandBegin(ExecCommandBeginEvent { command, .. }) => {
+            let command = shlex::try_join(command.iter().map(String::as_str))
+                .unwrap_or_else(|_| command.join(" "));
+            Some(format!("Running {command}"))
+        }
+        EventMsg::ExecCommandEnd(ExecCommandEndEvent { exit_code, .. }) => {
+            Some(format!("Command exited with code {exit_code}"))
+        }
+        EventMsg::PatchApplyBegin(PatchApplyBeginEvent { changes, .. }) => {
+            let mut paths: Vec<String> = changes
+                .keys()
+                .map(|path| path.display().to_string())
+                .collect();
+            paths.sort();
+            Some(format!("Applying patch to {}", paths.join(", ")))
+        }
+        EventMsg::PatchApplyEnd(PatchApplyEndEvent { success, .. }) => Some(if *success {
+            "Patch applied".to_string()
+        } else {
+            "Patch failed to apply".to_string()
+        }),
+        EventMsg::TokenCount(usage) => Some(format!("{} tokens used", usage.total_tokens)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn maps_activity_events_to_progress_messages() {
+        let begin = EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
+            call_id: "call".to_string(),
+            command: vec!["rg".to_string(), "two words".to_string()],
+            cwd: PathBuf::from("/repo"),
+            parsed_cmd: Vec::new(),
+        });
+        assert_eq!(
+            progress_message(&begin).as_deref(),
+            Some("Running rg 'two words'")
+        );
+
+        let patch = EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
+            call_id: "call".to_string(),
+            auto_approved: true,
+            changes: HashMap::from([(
+                PathBuf::from("src/lib.rs"),
+                FileChange::Add {
+                    content: String::new(),
+                },
+            )]),
+        });
+        assert_eq!(
+            progress_message(&patch).as_deref(),
+            Some("Applying patch to src/lib.rs")
+        );
+
+        let tokens = EventMsg::TokenCount(TokenUsage {
+            total_tokens: 1200,
+            ..Default::default()
+        });
+        assert_eq!(
+            progress_message(&tokens).as_deref(),
+            Some("1200 tokens used")
+        );
+
+        assert_eq!(progress_message(&EventMsg::TaskStarted), None);
+    }
+
+    #[test]
+    fn summarizes_changed_files_as_structured_content_and_links() {
+        let mut summary = ToolCallSummary::default();
+        summary.observe(&EventMsg::PatchApplyBegin(PatchApplyBeginEvent {
+            call_id: "call".to_string(),
+            auto_approved: true,
+            changes: HashMap::from([
+                (
+                    PathBuf::from("/repo/new.rs"),
+                    FileChange::Add {
+                        content: "fn main() {}\n".to_string(),
+                    },
+                ),
+                (PathBuf::from("/repo/old.rs"), FileChange::Delete),
+            ]),
+        }));
+        summary.observe(&EventMsg::TurnDiff(TurnDiffEvent {
+            unified_diff: "diff".to_string(),
+        }));
+        summary.observe(&EventMsg::TokenCount(TokenUsage {
+            total_tokens: 10,
+            ..Default::default()
+        }));
+
+        let result = summary.into_result(Some("Done".to_string()));
+        let result = serde_json::to_value(&result).expect("result serializes");
+        assert_eq!(
+            json!({
+                "content": [
+                    { "type": "text", "text": "Done" },
+                    {
+                        "type": "resource_link",
+                        "name": "new.rs",
+                        "uri": "file:///repo/new.rs"
+                    }
+                ],
+                "structuredContent": {
+                    "lastAgentMessage": "Done",
+                    "changedFiles": [
+                        { "path": "/repo/new.rs", "kind": "add" },
+                        { "path": "/repo/old.rs", "kind": "delete" }
+                    ],
+                    "unifiedDiff": "diff",
+                    "tokenUsage": {
+                        "input_tokens": 0,
+                        "cached_input_tokens": null,
+                        "output_tokens": 0,
+                        "reasoning_output_tokens": null,
+                        "total_tokens": 10
+                    }
+                }
+            }),
+            result
+        );
+    }
+}
@@ -0,0 +1,197 @@
+//! Lets several `addConversationListener` subscriptions share one
+//! conversation.
+//!
+//! A conversation's events can only be read by a single consumer, so the
+//! first subscription starts a reader task that hands each event to the
+//! subscriptions entitled to it:
+//!
+//! - the *primary* subscription, which is the earliest non-broadcast one (or
+//!   the earliest broadcast one when there is none) and also answers
+//!   approval requests;
+//! - every broadcast subscription.
+//!
+//! The fanout is shared by every client of the server, so a conversation
+//! started by one client can be observed by another.
+//!
+//! Each subscription has its own bounded queue. The primary queue is
+//! lossless because the conversation cannot make progress without it, while
+//! a broadcast subscription that falls behind loses events instead of
+//! stalling the others.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use codex_core::CodexConversation;
+use codex_core::protocol::Event;
+use tokio::sync::Mutex;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tokio::task::JoinHandle;
+use uuid::Uuid;
+
+use crate::conversation_tracker::ConversationTracker;
+
+/// Number of events a subscription may have queued before a broadcast
+/// subscription starts losing events.
+const SUBSCRIPTION_QUEUE_CAPACITY: usize = 1024;
+
+/// An event handed to a subscription.
+pub(crate) struct Delivery {
+    pub event: Event,
+    /// Whether the subscription is the primary one for this event and must
+    /// answer approval requests.
+    pub primary: bool,
+}
+
+struct Subscriber {
+    subscription_id: Uuid,
+    broadcast: bool,
+    tx: mpsc::Sender<Delivery>,
+}
+
+type Subscribers = Arc<std::sync::Mutex<Vec<Subscriber>>>;
+
+struct Reader {
+    subscribers: Subscribers,
+    cancel: oneshot::Sender<()>,
+    task: JoinHandle<()>,
+}
+
+/// Owns the reader task of every conversation that has subscriptions.
+#[derive(Clone, Default)]
+pub(crate) struct ConversationFanout {
+    readers: Arc<Mutex<HashMap<Uuid, Reader>>>,
+}
+
+impl ConversationFanout {
+    /// Adds a subscription, starting the conversation's reader if it is the
+    /// first one. `backlog` holds events to deliver before reading new ones,
+    /// and a new reader reports turn state to `conversation_tracker`.
+    pub(crate) async fn subscribe(
+        &self,
+        conversation_id: Uuid,
+        conversation: Arc<CodexConversation>,
+        subscription_id: Uuid,
+        broadcast: bool,
+        backlog: Vec<Event>,
+        conversation_tracker: &ConversationTracker,
+    ) -> mpsc::Receiver<Delivery> {
+        let (tx, rx) = mpsc::channel(SUBSCRIPTION_QUEUE_CAPACITY);
+        let subscriber = Subscriber {
+            subscription_id,
+            broadcast,
+            tx,
+        };
+
+        let mut readers = self.readers.lock().await;
+        if let Some(reader) = readers.get(&conversation_id)
+            && !reader.task.is_finished()
+        {
+            lock_subscribers(&reader.subscribers).push(subscriber);
+            return rx;
+        }
+
+        let subscribers: Subscribers = Arc::new(std::sync::Mutex::new(vec![subscriber]));
+        let (cancel_tx, mut cancel_rx) = oneshot::channel();
+        let task = tokio::spawn({
+            let subscribers = subscribers.clone();
+            let conversation_tracker = conversation_tracker.clone();
+            async move {
+                for event in backlog {
+                    conversation_tracker
+                        .observe(conversation_id, &event.msg)
+                        .await;
+                    dispatch(&subscribers, event).await;
+                }
+                loop {
+                    tokio::select! {
+                        _ = &mut cancel_rx => break,
+                        event = conversation.next_event() => match event {
+                            Ok(event) => {
+                                conversation_tracker.observe(conversation_id, &event.msg).await;
+                                dispatch(&subscribers, event).await;
+                            }
+                            Err(err) => {
+                                tracing::warn!("conversation.next_event() failed with: {err}");
+                                break;
+                            }
+                        },
+                    }
+                }
+            }
+        });
+        readers.insert(
+            conversation_id,
+            Reader {
+                subscribers,
+                cancel: cancel_tx,
+                task,
+            },
+        );
+        rx
+    }
+
+    /// Removes a subscription. Once the last one is gone the reader is
+    /// stopped, and this only returns after it has let go of the
+    /// conversation. Returns whether the conversation is left without a
+    /// reader.
+    pub(crate) async fn unsubscribe(&self, conversation_id: Uuid, subscription_id: Uuid) -> bool {
+        let mut readers = self.readers.lock().await;
+        let Some(reader) = readers.get(&conversation_id) else {
+            return true;
+        };
+        let now_empty = {
+            let mut subscribers = lock_subscribers(&reader.subscribers);
+            subscribers.retain(|s| s.subscription_id != subscription_id);
+            subscribers.is_empty()
+        };
+        if now_empty && let Some(reader) = readers.remove(&conversation_id) {
+            let _ = reader.cancel.send(());
+            let _ = reader.task.await;
+        }
+        now_empty
+    }
+}
+
+fn lock_subscribers(subscribers: &Subscribers) -> std::sync::MutexGuard<'_, Vec<Subscriber>> {
+    subscribers
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Hands `event` to the primary subscription and every broadcast one.
+async fn dispatch(subscribers: &Subscribers, event: Event) {
+    let (primary, broadcast) = {
+        let subscribers = lock_subscribers(subscribers);
+        let primary = subscribers
+            .iter()
+            .find(|s| !s.broadcast)
+            .or_else(|| subscribers.first())
+            .map(|s| (s.subscription_id, s.tx.clone()));
+        let primary_id = primary.as_ref().map(|(id, _)| *id);
+        let broadcast: Vec<(Uuid, mpsc::Sender<Delivery>)> = subscribers
+            .iter()
+            .filter(|s| s.broadcast && Some(s.subscription_id) != primary_id)
+            .map(|s| (s.subscription_id, s.tx.clone()))
+            .collect();
+        (primary, broadcast)
+    };
+
+    for (subscription_id, tx) in broadcast {
+        let delivery = Delivery {
+            event: event.clone(),
+            primary: false,
+        };
+        if let Err(mpsc::error::TrySendError::Full(_)) = tx.try_send(delivery) {
+            tracing::warn!("subscription {subscription_id} is falling behind; dropping event");
+        }
+    }
+    if let Some((_, tx)) = primary {
+        let _ = tx
+            .send(Delivery {
+                event,
+                primary: true,
+            })
+            .await;
+    }
+}
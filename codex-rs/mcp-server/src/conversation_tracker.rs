@@ -0,0 +1,331 @@
+//! Keeps conversations started with `newConversation` alive while no client
+//! is listening to them, and applies the configured [`McpDisconnectPolicy`]
+//! when the client disconnects.
+//!
+//! A conversation that loses its last listener is *parked*: its events are
+//! held so a listener added later receives everything it missed. A parked
+//! conversation is shut down once `parked_conversation_ttl_secs` passes
+//! without a listener being added.
+
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+
+use codex_core::CodexConversation;
+use codex_core::ConversationManager;
+use codex_core::config_types::McpDisconnectPolicy;
+use codex_core::config_types::McpServerSettings;
+use codex_core::protocol::Event;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
+use codex_protocol::mcp_protocol::ConversationExpiredNotification;
+use codex_protocol::mcp_protocol::ConversationId;
+use codex_protocol::mcp_protocol::ConversationParkedNotification;
+use codex_protocol::mcp_protocol::ServerNotification;
+use tokio::sync::Mutex;
+use tokio::sync::Notify;
+use tokio::sync::oneshot;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::outgoing_message::OutgoingMessageSender;
+
+/// How long to wait for conversations to acknowledge a shutdown once the
+/// client has disconnected.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone)]
+pub(crate) struct ConversationTracker {
+    settings: McpServerSettings,
+    conversation_manager: Arc<ConversationManager>,
+    outgoing: Arc<OutgoingMessageSender>,
+    conversations: Arc<Mutex<HashMap<Uuid, TrackedConversation>>>,
+    /// Notified whenever a turn ends or a conversation stops being tracked.
+    changed: Arc<Notify>,
+    disconnected: watch::Sender<bool>,
+}
+
+struct TrackedConversation {
+    conversation: Arc<CodexConversation>,
+    listeners: HashSet<Uuid>,
+    turn_running: bool,
+    parked: Option<Parked>,
+}
+
+/// Collects the events of a conversation nobody is listening to.
+struct Parked {
+    reattach: oneshot::Sender<()>,
+    task: JoinHandle<Vec<Event>>,
+}
+
+impl ConversationTracker {
+    pub(crate) fn new(
+        settings: McpServerSettings,
+        conversation_manager: Arc<ConversationManager>,
+        outgoing: Arc<OutgoingMessageSender>,
+    ) -> Self {
+        Self {
+            settings,
+            conversation_manager,
+            outgoing,
+            conversations: Arc::new(Mutex::new(HashMap::new())),
+            changed: Arc::new(Notify::new()),
+            disconnected: watch::channel(false).0,
+        }
+    }
+
+    fn ttl(&self) -> Duration {
+        Duration::from_secs(self.settings.parked_conversation_ttl_secs)
+    }
+
+    pub(crate) async fn track(&self, conversation_id: Uuid, conversation: Arc<CodexConversation>) {
+        self.conversations.lock().await.insert(
+            conversation_id,
+            TrackedConversation {
+                conversation,
+                listeners: HashSet::new(),
+                turn_running: false,
+                parked: None,
+            },
+        );
+    }
+
+    /// Registers a listener and returns the events held while the
+    /// conversation was parked, which the listener must deliver first.
+    pub(crate) async fn attach(&self, conversation_id: Uuid, subscription_id: Uuid) -> Vec<Event> {
+        let parked = {
+            let mut conversations = self.conversations.lock().await;
+            let Some(tracked) = conversations.get_mut(&conversation_id) else {
+                return Vec::new();
+            };
+            tracked.listeners.insert(subscription_id);
+            tracked.parked.take()
+        };
+        let Some(Parked { reattach, task }) = parked else {
+            return Vec::new();
+        };
+        let _ = reattach.send(());
+        task.await.unwrap_or_default()
+    }
+
+    /// Unregisters a listener once it has stopped reading events, parking
+    /// the conversation if it was the last one. `unread` is false while
+    /// another client still reads the conversation, which then cannot be
+    /// parked.
+    pub(crate) async fn detach(&self, conversation_id: Uuid, subscription_id: Uuid, unread: bool) {
+        let mut conversations = self.conversations.lock().await;
+        let Some(tracked) = conversations.get_mut(&conversation_id) else {
+            return;
+        };
+        tracked.listeners.remove(&subscription_id);
+        if unread && tracked.listeners.is_empty() && tracked.parked.is_none() {
+            self.park(conversation_id, tracked).await;
+        }
+    }
+
+    /// Updates the turn state of a conversation from one of its events.
+    pub(crate) async fn observe(&self, conversation_id: Uuid, msg: &EventMsg) {
+        let turn_running = match msg {
+            EventMsg::TaskStarted => true,
+            EventMsg::TaskComplete(_) | EventMsg::TurnAborted(_) => false,
+            EventMsg::ShutdownComplete => {
+                self.conversations.lock().await.remove(&conversation_id);
+                self.changed.notify_waiters();
+                return;
+            }
+            _ => return,
+        };
+        if let Some(tracked) = self.conversations.lock().await.get_mut(&conversation_id) {
+            tracked.turn_running = turn_running;
+        }
+        if !turn_running {
+            self.changed.notify_waiters();
+        }
+    }
+
+    async fn park(&self, conversation_id: Uuid, tracked: &mut TrackedConversation) {
+        let (reattach, reattach_rx) = oneshot::channel();
+        let task = tokio::spawn(self.clone().run_parked(
+            conversation_id,
+            tracked.conversation.clone(),
+            reattach_rx,
+        ));
+        tracked.parked = Some(Parked { reattach, task });
+
+        if !*self.disconnected.borrow() {
+            self.outgoing
+                .send_server_notification(ServerNotification::ConversationParked(
+                    ConversationParkedNotification {
+                        conversation_id: ConversationId(conversation_id),
+                        expires_in_secs: self.settings.parked_conversation_ttl_secs,
+                    },
+                ))
+                .await;
+        }
+    }
+
+    /// Holds the events of a parked conversation until a listener is added,
+    /// and shuts the conversation down if none is added before the TTL.
+    async fn run_parked(
+        self,
+        conversation_id: Uuid,
+        conversation: Arc<CodexConversation>,
+        mut reattach: oneshot::Receiver<()>,
+    ) -> Vec<Event> {
+        let mut events = Vec::new();
+        let mut disconnected = self.disconnected.subscribe();
+        let expiry = tokio::time::sleep(self.ttl());
+        tokio::pin!(expiry);
+        loop {
+            tokio::select! {
+                _ = &mut reattach => return events,
+                () = &mut expiry => {
+                    self.expire(conversation_id, &conversation).await;
+                    return events;
+                }
+                Ok(()) = disconnected.changed() => {
+                    // Nobody is left to answer approval requests.
+                    for event in events.drain(..) {
+                        deny_approval_request(&conversation, &event).await;
+                    }
+                }
+                event = conversation.next_event() => {
+                    let Ok(event) = event else {
+                        self.conversations.lock().await.remove(&conversation_id);
+                        self.changed.notify_waiters();
+                        return events;
+                    };
+                    self.observe(conversation_id, &event.msg).await;
+                    if *disconnected.borrow() {
+                        deny_approval_request(&conversation, &event).await;
+                    } else {
+                        events.push(event);
+                    }
+                }
+            }
+        }
+    }
+
+    async fn expire(&self, conversation_id: Uuid, conversation: &CodexConversation) {
+        {
+            let mut conversations = self.conversations.lock().await;
+            // A listener that is being added has already taken the parked
+            // state; let it have the conversation.
+            match conversations.get(&conversation_id) {
+                Some(tracked) if tracked.parked.is_some() => {
+                    conversations.remove(&conversation_id);
+                }
+                _ => return,
+            }
+        }
+        self.conversation_manager
+            .remove_conversation(conversation_id)
+            .await;
+        if let Err(e) = conversation.submit(Op::Shutdown).await {
+            warn!("failed to shut down expired conversation {conversation_id}: {e}");
+        }
+        self.changed.notify_waiters();
+        let drain = async {
+            while let Ok(event) = conversation.next_event().await {
+                if matches!(event.msg, EventMsg::ShutdownComplete) {
+                    break;
+                }
+            }
+        };
+        let _ = tokio::time::timeout(SHUTDOWN_TIMEOUT, drain).await;
+
+        if !*self.disconnected.borrow() {
+            self.outgoing
+                .send_server_notification(ServerNotification::ConversationExpired(
+                    ConversationExpiredNotification {
+                        conversation_id: ConversationId(conversation_id),
+                    },
+                ))
+                .await;
+        }
+    }
+
+    /// Records that the client has gone away. Conversations parked from now
+    /// on deny approval requests instead of holding them for a listener.
+    pub(crate) fn mark_disconnected(&self) {
+        self.disconnected.send_replace(true);
+    }
+
+    /// Applies the disconnect policy once every listener has stopped: running
+    /// turns are interrupted or allowed to finish, then every conversation is
+    /// shut down so its rollout is flushed.
+    pub(crate) async fn apply_disconnect_policy(&self) {
+        {
+            let mut conversations = self.conversations.lock().await;
+            for (conversation_id, tracked) in conversations.iter_mut() {
+                if tracked.parked.is_none() {
+                    tracked.listeners.clear();
+                    self.park(*conversation_id, tracked).await;
+                }
+            }
+        }
+        if self.settings.on_disconnect == McpDisconnectPolicy::Abort {
+            for conversation in self.conversations_where(|t| t.turn_running).await {
+                let _ = conversation.submit(Op::Interrupt).await;
+            }
+        }
+
+        self.wait_until(self.ttl(), |t| !t.turn_running).await;
+        for conversation in self.conversations_where(|_| true).await {
+            let _ = conversation.submit(Op::Shutdown).await;
+        }
+        self.wait_until(SHUTDOWN_TIMEOUT, |_| false).await;
+    }
+
+    async fn conversations_where(
+        &self,
+        predicate: impl Fn(&TrackedConversation) -> bool,
+    ) -> Vec<Arc<CodexConversation>> {
+        self.conversations
+            .lock()
+            .await
+            .values()
+            .filter(|tracked| predicate(tracked))
+            .map(|tracked| tracked.conversation.clone())
+            .collect()
+    }
+
+    /// Waits until `done` holds for every tracked conversation.
+    async fn wait_until(&self, timeout: Duration, done: impl Fn(&TrackedConversation) -> bool) {
+        let wait = async {
+            loop {
+                let changed = self.changed.notified();
+                if self.conversations.lock().await.values().all(&done) {
+                    return;
+                }
+                changed.await;
+            }
+        };
+        if tokio::time::timeout(timeout, wait).await.is_err() {
+            warn!("timed out waiting for conversations after the client disconnected");
+        }
+    }
+}
+
+async fn deny_approval_request(conversation: &CodexConversation, event: &Event) {
+    let op = match event.msg {
+        EventMsg::ExecApprovalRequest(_) => Op::ExecApproval {
+            id: event.id.clone(),
+            decision: ReviewDecision::Denied,
+            allow_pattern: None,
+        },
+        EventMsg::ApplyPatchApprovalRequest(_) => Op::PatchApproval {
+            id: event.id.clone(),
+            decision: ReviewDecision::Denied,
+            accepted_files: None,
+        },
+        _ => return,
+    };
+    if let Err(e) = conversation.submit(op).await {
+        warn!("failed to deny approval request: {e}");
+    }
+}
@@ -141,6 +141,7 @@ async fn on_exec_approval_response(
         .submit(Op::ExecApproval {
             id: event_id,
             decision: response.decision,
+            allow_pattern: None,
         })
         .await
     {
@@ -0,0 +1,651 @@
+//! Serves the JSON-RPC message stream over HTTP for clients that cannot
+//! spawn the server and talk to it over stdio.
+//!
+//! - `POST`, `GET` and `DELETE /mcp` implement the MCP Streamable HTTP
+//!   transport. A session starts with a POST carrying `initialize`; its
+//!   response has the `Mcp-Session-Id` header that later requests must echo.
+//!   A POST carrying requests is answered with an SSE stream that closes once
+//!   every request has been answered, and `GET /mcp` opens a stream for
+//!   messages the server initiates. `DELETE /mcp` ends the session, and so
+//!   does going [`SESSION_IDLE_TIMEOUT`] without a request or an open stream.
+//! - `GET /ws` upgrades to a WebSocket on which each text frame is one
+//!   JSON-RPC message, in either direction.
+//!
+//! Every session and WebSocket connection is a separate client with its own
+//! message processor, and closing it applies the `[mcp_server]` disconnect
+//! policy. Conversations are shared between clients.
+//!
+//! Browsers send an `Origin` header with every WebSocket handshake and
+//! cross-site POST, so requests from origins other than loopback ones and
+//! those passed with `--allowed-origin` are refused; otherwise any web page
+//! could drive the agent through a server on localhost. Listening on a
+//! non-loopback address requires `--auth-token`.
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::convert::Infallible;
+use std::io::ErrorKind;
+use std::io::Result as IoResult;
+use std::net::IpAddr;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use axum::Router;
+use axum::body::Bytes;
+use axum::extract::Request;
+use axum::extract::State;
+use axum::extract::WebSocketUpgrade;
+use axum::extract::ws::Message;
+use axum::extract::ws::WebSocket;
+use axum::http::HeaderMap;
+use axum::http::HeaderValue;
+use axum::http::StatusCode;
+use axum::http::header::AUTHORIZATION;
+use axum::http::header::ORIGIN;
+use axum::middleware::Next;
+use axum::response::IntoResponse;
+use axum::response::Response;
+use axum::response::sse::Event as SseEvent;
+use axum::response::sse::KeepAlive;
+use axum::response::sse::Sse;
+use axum::routing::get;
+use codex_core::config::Config;
+use futures::SinkExt;
+use futures::Stream;
+use futures::StreamExt;
+use mcp_types::InitializeRequest;
+use mcp_types::JSONRPCMessage;
+use mcp_types::ModelContextProtocolRequest;
+use mcp_types::RequestId;
+use tokio::net::TcpListener;
+use tokio::sync::Mutex;
+use tokio::sync::mpsc;
+use tracing::error;
+use tracing::info;
+use uuid::Uuid;
+
+use crate::CHANNEL_CAPACITY;
+use crate::message_processor::SharedState;
+use crate::outgoing_message::OutgoingMessage;
+use crate::spawn_message_processor;
+
+const SESSION_ID_HEADER: &str = "mcp-session-id";
+
+/// Number of server-initiated messages held for a session while it has no
+/// open stream to deliver them on.
+const SESSION_BACKLOG_CAPACITY: usize = 1024;
+
+/// A session with no open stream that has not been used for this long is
+/// ended as if the client had sent `DELETE /mcp`.
+const SESSION_IDLE_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// How often sessions are checked for [`SESSION_IDLE_TIMEOUT`].
+const SESSION_EXPIRY_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Clone)]
+struct AppState {
+    auth_token: Option<Arc<str>>,
+    allowed_origins: Arc<[String]>,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+    config: Arc<Config>,
+    shared: SharedState,
+    sessions: Arc<Mutex<HashMap<String, HttpSession>>>,
+}
+
+impl AppState {
+    /// Starts a message processor for a new client and returns the channels
+    /// to talk to it.
+    fn connect(
+        &self,
+    ) -> (
+        mpsc::Sender<JSONRPCMessage>,
+        mpsc::Receiver<OutgoingMessage>,
+    ) {
+        let (incoming_tx, incoming_rx) = mpsc::channel(CHANNEL_CAPACITY);
+        let (outgoing_tx, outgoing_rx) = mpsc::channel(CHANNEL_CAPACITY);
+        spawn_message_processor(
+            incoming_rx,
+            outgoing_tx,
+            self.codex_linux_sandbox_exe.clone(),
+            self.config.clone(),
+            self.shared.clone(),
+        );
+        (incoming_tx, outgoing_rx)
+    }
+}
+
+/// A Streamable HTTP session. Dropping it closes the processor's input,
+/// which ends the session.
+struct HttpSession {
+    incoming_tx: mpsc::Sender<JSONRPCMessage>,
+    routes: Arc<std::sync::Mutex<Routes>>,
+}
+
+/// Where the messages of a Streamable HTTP session are delivered.
+struct Routes {
+    next_stream_id: u64,
+    /// Open POST streams and the number of requests each still waits on.
+    post_streams: HashMap<u64, (mpsc::Sender<JSONRPCMessage>, usize)>,
+    /// The POST stream each unanswered request arrived on.
+    pending_requests: HashMap<RequestId, u64>,
+    /// The stream opened with `GET /mcp`, if any.
+    standalone: Option<mpsc::Sender<JSONRPCMessage>>,
+    /// Server-initiated messages that arrived while no stream was open.
+    backlog: VecDeque<JSONRPCMessage>,
+    /// When the client last sent a request or was sent a message.
+    last_activity: Instant,
+}
+
+impl Routes {
+    fn new() -> Self {
+        Self {
+            next_stream_id: 0,
+            post_streams: HashMap::new(),
+            pending_requests: HashMap::new(),
+            standalone: None,
+            backlog: VecDeque::new(),
+            last_activity: Instant::now(),
+        }
+    }
+
+    /// Whether the client has no open stream and has not been active for
+    /// [`SESSION_IDLE_TIMEOUT`], so it most likely went away.
+    fn is_idle(&self, now: Instant) -> bool {
+        let has_open_stream = self.standalone.as_ref().is_some_and(|tx| !tx.is_closed())
+            || self.post_streams.values().any(|(tx, _)| !tx.is_closed());
+        !has_open_stream
+            && now.saturating_duration_since(self.last_activity) >= SESSION_IDLE_TIMEOUT
+    }
+
+    /// Picks the stream `message` goes to, or holds on to the message when
+    /// there is none.
+    fn route(
+        &mut self,
+        message: JSONRPCMessage,
+    ) -> Option<(mpsc::Sender<JSONRPCMessage>, JSONRPCMessage)> {
+        self.last_activity = Instant::now();
+        let answered = match &message {
+            JSONRPCMessage::Response(response) => Some(&response.id),
+            JSONRPCMessage::Error(error) => Some(&error.id),
+            JSONRPCMessage::Request(_) | JSONRPCMessage::Notification(_) => None,
+        };
+        if let Some(stream_id) = answered.and_then(|id| self.pending_requests.remove(id)) {
+            let (tx, remaining) = self.post_streams.get_mut(&stream_id)?;
+            let tx = tx.clone();
+            *remaining -= 1;
+            if *remaining == 0 {
+                // The stream closes once its sender is dropped.
+                self.post_streams.remove(&stream_id);
+            }
+            return Some((tx, message));
+        }
+
+        if let Some(tx) = self.standalone.as_ref().filter(|tx| !tx.is_closed()) {
+            return Some((tx.clone(), message));
+        }
+        let latest_post_stream = self
+            .post_streams
+            .iter()
+            .filter(|(_, (tx, _))| !tx.is_closed())
+            .max_by_key(|(stream_id, _)| **stream_id)
+            .map(|(_, (tx, _))| tx.clone());
+        if let Some(tx) = latest_post_stream {
+            return Some((tx, message));
+        }
+        if self.backlog.len() == SESSION_BACKLOG_CAPACITY {
+            self.backlog.pop_front();
+        }
+        self.backlog.push_back(message);
+        None
+    }
+}
+
+pub(crate) async fn serve(
+    addr: SocketAddr,
+    auth_token: Option<String>,
+    allowed_origins: Vec<String>,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+    config: Arc<Config>,
+    shared: SharedState,
+) -> IoResult<()> {
+    check_listen_addr(addr, auth_token.as_deref())?;
+    let state = AppState {
+        auth_token: auth_token.map(Arc::from),
+        allowed_origins: allowed_origins.into(),
+        codex_linux_sandbox_exe,
+        config,
+        shared,
+        sessions: Arc::new(Mutex::new(HashMap::new())),
+    };
+    tokio::spawn(expire_idle_sessions(state.sessions.clone()));
+    let app = Router::new()
+        .route(
+            "/mcp",
+            get(open_standalone_stream)
+                .post(post_messages)
+                .delete(delete_session),
+        )
+        .route("/ws", get(upgrade_websocket))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            require_auth_token,
+        ))
+        .layer(axum::middleware::from_fn_with_state(
+            state.clone(),
+            require_allowed_origin,
+        ))
+        .with_state(state);
+
+    let listener = TcpListener::bind(addr).await?;
+    let local_addr = listener.local_addr()?;
+    // Let scripts that asked for port 0 find out which port was picked.
+    #[allow(clippy::print_stderr)]
+    {
+        eprintln!("codex-mcp-server listening on http://{local_addr}");
+    }
+    axum::serve(listener, app).await
+}
+
+/// Anyone who can reach a non-loopback address could run commands through
+/// the server, so it must not listen there without a token.
+fn check_listen_addr(addr: SocketAddr, auth_token: Option<&str>) -> IoResult<()> {
+    if addr.ip().is_loopback() || auth_token.is_some() {
+        return Ok(());
+    }
+    Err(std::io::Error::new(
+        ErrorKind::InvalidInput,
+        format!("refusing to listen on non-loopback address {addr} without --auth-token"),
+    ))
+}
+
+async fn require_allowed_origin(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    // Clients other than browsers send no `Origin`.
+    if let Some(origin) = request.headers().get(ORIGIN) {
+        let allowed = origin
+            .to_str()
+            .is_ok_and(|origin| is_allowed_origin(origin, &state.allowed_origins));
+        if !allowed {
+            return (StatusCode::FORBIDDEN, "origin not allowed").into_response();
+        }
+    }
+    next.run(request).await
+}
+
+fn is_allowed_origin(origin: &str, allowed_origins: &[String]) -> bool {
+    allowed_origins
+        .iter()
+        .any(|allowed| allowed.trim_end_matches('/').eq_ignore_ascii_case(origin))
+        || origin_host(origin).is_some_and(is_loopback_host)
+}
+
+/// The host of an origin such as `http://[::1]:8080`. Opaque origins like
+/// `null` have none.
+fn origin_host(origin: &str) -> Option<&str> {
+    let (scheme, authority) = origin.split_once("://")?;
+    if !matches!(scheme, "http" | "https") {
+        return None;
+    }
+    match authority.strip_prefix('[') {
+        Some(bracketed) => bracketed.split_once(']').map(|(host, _)| host),
+        None => Some(
+            authority
+                .split_once(':')
+                .map_or(authority, |(host, _)| host),
+        ),
+    }
+}
+
+fn is_loopback_host(host: &str) -> bool {
+    host.eq_ignore_ascii_case("localhost")
+        || host.parse::<IpAddr>().is_ok_and(|ip| ip.is_loopback())
+}
+
+async fn require_auth_token(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    if let Some(expected) = state.auth_token.as_deref() {
+        let provided = request
+            .headers()
+            .get(AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "));
+        if !provided.is_some_and(|token| constant_time_eq(token, expected)) {
+            return StatusCode::UNAUTHORIZED.into_response();
+        }
+    }
+    next.run(request).await
+}
+
+fn constant_time_eq(a: &str, b: &str) -> bool {
+    a.len() == b.len()
+        && a.bytes()
+            .zip(b.bytes())
+            .fold(0u8, |diff, (x, y)| diff | (x ^ y))
+            == 0
+}
+
+async fn post_messages(State(state): State<AppState>, headers: HeaderMap, body: Bytes) -> Response {
+    let messages = match parse_messages(&body) {
+        Ok(messages) => messages,
+        Err(err) => {
+            return (StatusCode::BAD_REQUEST, format!("invalid JSON-RPC: {err}")).into_response();
+        }
+    };
+
+    let (session_id, incoming_tx, routes) = match session_id(&headers) {
+        Some(session_id) => {
+            let sessions = state.sessions.lock().await;
+            let Some(session) = sessions.get(session_id) else {
+                return (StatusCode::NOT_FOUND, "unknown session").into_response();
+            };
+            (
+                session_id.to_string(),
+                session.incoming_tx.clone(),
+                session.routes.clone(),
+            )
+        }
+        None if messages.iter().any(is_initialize) => start_session(&state).await,
+        None => {
+            return (StatusCode::BAD_REQUEST, "missing Mcp-Session-Id header").into_response();
+        }
+    };
+
+    let request_ids: Vec<RequestId> = messages
+        .iter()
+        .filter_map(|message| match message {
+            JSONRPCMessage::Request(request) => Some(request.id.clone()),
+            _ => None,
+        })
+        .collect();
+    lock_routes(&routes).last_activity = Instant::now();
+    let stream = if request_ids.is_empty() {
+        None
+    } else {
+        let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+        let mut routes = lock_routes(&routes);
+        let stream_id = routes.next_stream_id;
+        routes.next_stream_id += 1;
+        routes
+            .post_streams
+            .insert(stream_id, (tx, request_ids.len()));
+        for request_id in request_ids {
+            routes.pending_requests.insert(request_id, stream_id);
+        }
+        Some(rx)
+    };
+
+    for message in messages {
+        if incoming_tx.send(message).await.is_err() {
+            return (StatusCode::NOT_FOUND, "session has ended").into_response();
+        }
+    }
+
+    let mut response = match stream {
+        Some(rx) => sse_response(rx),
+        None => StatusCode::ACCEPTED.into_response(),
+    };
+    if let Ok(value) = HeaderValue::from_str(&session_id) {
+        response.headers_mut().insert(SESSION_ID_HEADER, value);
+    }
+    response
+}
+
+async fn open_standalone_stream(State(state): State<AppState>, headers: HeaderMap) -> Response {
+    let Some(routes) = find_session_routes(&state, &headers).await else {
+        return (StatusCode::NOT_FOUND, "unknown session").into_response();
+    };
+    let (tx, rx) = mpsc::channel(CHANNEL_CAPACITY);
+    let backlog: Vec<JSONRPCMessage> = {
+        let mut routes = lock_routes(&routes);
+        routes.last_activity = Instant::now();
+        routes.standalone = Some(tx.clone());
+        routes.backlog.drain(..).collect()
+    };
+    for message in backlog {
+        let _ = tx.send(message).await;
+    }
+    sse_response(rx)
+}
+
+async fn delete_session(State(state): State<AppState>, headers: HeaderMap) -> StatusCode {
+    let Some(session_id) = session_id(&headers) else {
+        return StatusCode::BAD_REQUEST;
+    };
+    match state.sessions.lock().await.remove(session_id) {
+        Some(_) => StatusCode::OK,
+        None => StatusCode::NOT_FOUND,
+    }
+}
+
+async fn upgrade_websocket(State(state): State<AppState>, ws: WebSocketUpgrade) -> Response {
+    ws.on_upgrade(move |socket| serve_websocket(socket, state))
+}
+
+async fn serve_websocket(socket: WebSocket, state: AppState) {
+    let (incoming_tx, mut outgoing_rx) = state.connect();
+    let (mut sink, mut stream) = socket.split();
+    loop {
+        tokio::select! {
+            frame = stream.next() => match frame {
+                Some(Ok(Message::Text(text))) => {
+                    match serde_json::from_str::<JSONRPCMessage>(text.as_str()) {
+                        Ok(message) => {
+                            if incoming_tx.send(message).await.is_err() {
+                                break;
+                            }
+                        }
+                        Err(e) => error!("Failed to deserialize JSONRPCMessage: {e}"),
+                    }
+                }
+                Some(Ok(Message::Close(_))) | None => break,
+                Some(Ok(_)) => {}
+                Some(Err(e)) => {
+                    error!("WebSocket receive failed: {e}");
+                    break;
+                }
+            },
+            outgoing_message = outgoing_rx.recv() => {
+                let Some(outgoing_message) = outgoing_message else {
+                    break;
+                };
+                let message: JSONRPCMessage = outgoing_message.into();
+                match serde_json::to_string(&message) {
+                    Ok(json) => {
+                        if let Err(e) = sink.send(Message::Text(json.into())).await {
+                            error!("WebSocket send failed: {e}");
+                            break;
+                        }
+                    }
+                    Err(e) => error!("Failed to serialize JSONRPCMessage: {e}"),
+                }
+            }
+        }
+    }
+    info!("WebSocket client disconnected");
+}
+
+/// Registers a new session whose outgoing messages are routed to the
+/// streams of its HTTP requests.
+async fn start_session(
+    state: &AppState,
+) -> (
+    String,
+    mpsc::Sender<JSONRPCMessage>,
+    Arc<std::sync::Mutex<Routes>>,
+) {
+    let (incoming_tx, mut outgoing_rx) = state.connect();
+    let routes = Arc::new(std::sync::Mutex::new(Routes::new()));
+    tokio::spawn({
+        let routes = routes.clone();
+        async move {
+            while let Some(outgoing_message) = outgoing_rx.recv().await {
+                let routed = lock_routes(&routes).route(outgoing_message.into());
+                if let Some((tx, message)) = routed {
+                    // A client that went away simply stops receiving.
+                    let _ = tx.send(message).await;
+                }
+            }
+        }
+    });
+
+    let session_id = Uuid::new_v4().to_string();
+    state.sessions.lock().await.insert(
+        session_id.clone(),
+        HttpSession {
+            incoming_tx: incoming_tx.clone(),
+            routes: routes.clone(),
+        },
+    );
+    (session_id, incoming_tx, routes)
+}
+
+/// Ends the sessions of clients that went away without `DELETE /mcp`.
+/// Dropping a session closes its processor's input, which applies the
+/// disconnect policy just like `DELETE /mcp` does.
+async fn expire_idle_sessions(sessions: Arc<Mutex<HashMap<String, HttpSession>>>) {
+    let mut interval = tokio::time::interval(SESSION_EXPIRY_INTERVAL);
+    loop {
+        interval.tick().await;
+        let now = Instant::now();
+        sessions.lock().await.retain(|session_id, session| {
+            let idle = lock_routes(&session.routes).is_idle(now);
+            if idle {
+                info!("ending idle session {session_id}");
+            }
+            !idle
+        });
+    }
+}
+
+async fn find_session_routes(
+    state: &AppState,
+    headers: &HeaderMap,
+) -> Option<Arc<std::sync::Mutex<Routes>>> {
+    let session_id = session_id(headers)?;
+    let sessions = state.sessions.lock().await;
+    sessions
+        .get(session_id)
+        .map(|session| session.routes.clone())
+}
+
+fn session_id(headers: &HeaderMap) -> Option<&str> {
+    headers
+        .get(SESSION_ID_HEADER)
+        .and_then(|value| value.to_str().ok())
+}
+
+fn lock_routes(routes: &std::sync::Mutex<Routes>) -> std::sync::MutexGuard<'_, Routes> {
+    routes
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// Parses a POST body holding either one JSON-RPC message or a batch.
+fn parse_messages(body: &[u8]) -> serde_json::Result<Vec<JSONRPCMessage>> {
+    match serde_json::from_slice::<serde_json::Value>(body)? {
+        serde_json::Value::Array(values) => {
+            values.into_iter().map(serde_json::from_value).collect()
+        }
+        value => Ok(vec![serde_json::from_value(value)?]),
+    }
+}
+
+fn is_initialize(message: &JSONRPCMessage) -> bool {
+    matches!(message, JSONRPCMessage::Request(request) if request.method == InitializeRequest::METHOD)
+}
+
+fn sse_response(rx: mpsc::Receiver<JSONRPCMessage>) -> Response {
+    Sse::new(sse_events(rx))
+        .keep_alive(KeepAlive::default())
+        .into_response()
+}
+
+fn sse_events(
+    rx: mpsc::Receiver<JSONRPCMessage>,
+) -> impl Stream<Item = Result<SseEvent, Infallible>> {
+    futures::stream::unfold(rx, |mut rx| async move {
+        let message = rx.recv().await?;
+        let event = match serde_json::to_string(&message) {
+            Ok(json) => SseEvent::default().data(json),
+            Err(e) => {
+                error!("Failed to serialize JSONRPCMessage: {e}");
+                SseEvent::default().comment("unserializable message")
+            }
+        };
+        Some((Ok(event), rx))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn only_loopback_and_listed_origins_are_allowed() {
+        let allowed = vec!["https://app.example.com/".to_string()];
+        for origin in [
+            "http://localhost:3000",
+            "http://127.0.0.1",
+            "https://127.0.0.2:8443",
+            "http://[::1]:8080",
+            "https://app.example.com",
+        ] {
+            assert!(is_allowed_origin(origin, &allowed), "{origin}");
+        }
+        for origin in [
+            "https://evil.example",
+            "http://localhost.evil.example",
+            "http://127.0.0.1.evil.example",
+            "null",
+            "file://",
+        ] {
+            assert!(!is_allowed_origin(origin, &allowed), "{origin}");
+        }
+    }
+
+    #[test]
+    fn sessions_without_streams_expire_after_the_idle_timeout() {
+        let mut routes = Routes::new();
+        let start = routes.last_activity;
+        let later = start + SESSION_IDLE_TIMEOUT;
+        assert!(!routes.is_idle(start));
+        assert!(routes.is_idle(later));
+
+        // An open stream keeps the session alive however long it is quiet.
+        let (tx, rx) = mpsc::channel(1);
+        routes.standalone = Some(tx);
+        assert!(!routes.is_idle(later));
+        drop(rx);
+        assert!(routes.is_idle(later));
+
+        // So does any activity.
+        routes.route(JSONRPCMessage::Notification(
+            mcp_types::JSONRPCNotification {
+                jsonrpc: mcp_types::JSONRPC_VERSION.to_string(),
+                method: "notifications/message".to_string(),
+                params: None,
+            },
+        ));
+        assert!(!routes.is_idle(later));
+    }
+
+    #[test]
+    fn non_loopback_listen_needs_a_token() {
+        let loopback: SocketAddr = "127.0.0.1:0".parse().unwrap();
+        let any: SocketAddr = "0.0.0.0:0".parse().unwrap();
+        assert!(check_listen_addr(loopback, None).is_ok());
+        assert!(check_listen_addr(any, Some("token")).is_ok());
+        let err = check_listen_addr(any, None).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidInput);
+    }
+}
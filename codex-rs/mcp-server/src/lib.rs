@@ -1,41 +1,55 @@
 //! Prototype MCP server.
 #![deny(clippy::print_stdout, clippy::print_stderr)]
 
+use std::collections::HashMap;
 use std::io::ErrorKind;
 use std::io::Result as IoResult;
 use std::path::PathBuf;
+use std::sync::Arc;
 
-use codex_common::CliConfigOverrides;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
 
 use mcp_types::JSONRPCMessage;
+use mcp_types::JSONRPCRequest;
 use tokio::io::AsyncBufReadExt;
 use tokio::io::AsyncWriteExt;
 use tokio::io::BufReader;
 use tokio::io::{self};
 use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tokio::task::JoinSet;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
 
+mod cli;
 mod codex_message_processor;
 mod codex_tool_config;
 mod codex_tool_runner;
+mod conversation_fanout;
+mod conversation_tracker;
 mod error_code;
 mod exec_approval;
+mod http_transport;
 mod json_to_toml;
 pub(crate) mod message_processor;
 mod outgoing_message;
 mod patch_approval;
 
 use crate::message_processor::MessageProcessor;
+use crate::message_processor::SharedState;
 use crate::outgoing_message::OutgoingMessage;
 use crate::outgoing_message::OutgoingMessageSender;
 
+pub use crate::cli::Cli;
+pub use crate::codex_tool_config::CodexToolCallApprovalPolicy;
+pub use crate::codex_tool_config::CodexToolCallListConversationsParam;
 pub use crate::codex_tool_config::CodexToolCallParam;
 pub use crate::codex_tool_config::CodexToolCallReplyParam;
+pub use crate::codex_tool_config::CodexToolCallResumeParam;
+pub use crate::codex_tool_config::CodexToolCallSandboxMode;
 pub use crate::exec_approval::ExecApprovalElicitRequestParams;
 pub use crate::exec_approval::ExecApprovalResponse;
 pub use crate::patch_approval::PatchApprovalElicitRequestParams;
@@ -46,10 +60,7 @@ pub use crate::patch_approval::PatchApprovalResponse;
 /// plenty for an interactive CLI.
 const CHANNEL_CAPACITY: usize = 128;
 
-pub async fn run_main(
-    codex_linux_sandbox_exe: Option<PathBuf>,
-    cli_config_overrides: CliConfigOverrides,
-) -> IoResult<()> {
+pub async fn run_main(codex_linux_sandbox_exe: Option<PathBuf>, cli: Cli) -> IoResult<()> {
     // Install a simple subscriber so `tracing` output is visible.  Users can
     // control the log level with `RUST_LOG`.
     tracing_subscriber::fmt()
@@ -57,13 +68,50 @@ pub async fn run_main(
         .with_env_filter(EnvFilter::from_default_env())
         .init();
 
+    // Parse CLI overrides once and derive the base Config eagerly so later
+    // components do not need to work with raw TOML values.
+    let cli_kv_overrides = cli.config_overrides.parse_overrides().map_err(|e| {
+        std::io::Error::new(
+            ErrorKind::InvalidInput,
+            format!("error parsing -c overrides: {e}"),
+        )
+    })?;
+    let config = Config::load_with_cli_overrides(cli_kv_overrides, ConfigOverrides::default())
+        .map_err(|e| {
+            std::io::Error::new(ErrorKind::InvalidData, format!("error loading config: {e}"))
+        })?;
+    let shared = SharedState::new(&config);
+    let config = Arc::new(config);
+
+    match cli.listen {
+        Some(addr) => {
+            http_transport::serve(
+                addr,
+                cli.auth_token,
+                cli.allowed_origins,
+                codex_linux_sandbox_exe,
+                config,
+                shared,
+            )
+            .await
+        }
+        None => run_stdio(codex_linux_sandbox_exe, config, shared).await,
+    }
+}
+
+async fn run_stdio(
+    codex_linux_sandbox_exe: Option<PathBuf>,
+    config: Arc<Config>,
+    shared: SharedState,
+) -> IoResult<()> {
     // Set up channels.
-    let (incoming_tx, mut incoming_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
+    let (incoming_tx, incoming_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
     let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<OutgoingMessage>(CHANNEL_CAPACITY);
 
     // Task: read from stdin, push to `incoming_tx`.
+    // `incoming_tx` moves into this task so that stdin EOF closes the
+    // channel and lets the processor apply the client disconnect policy.
     let stdin_reader_handle = tokio::spawn({
-        let incoming_tx = incoming_tx.clone();
         async move {
             let stdin = io::stdin();
             let reader = BufReader::new(stdin);
@@ -85,40 +133,14 @@ pub async fn run_main(
         }
     });
 
-    // Parse CLI overrides once and derive the base Config eagerly so later
-    // components do not need to work with raw TOML values.
-    let cli_kv_overrides = cli_config_overrides.parse_overrides().map_err(|e| {
-        std::io::Error::new(
-            ErrorKind::InvalidInput,
-            format!("error parsing -c overrides: {e}"),
-        )
-    })?;
-    let config = Config::load_with_cli_overrides(cli_kv_overrides, ConfigOverrides::default())
-        .map_err(|e| {
-            std::io::Error::new(ErrorKind::InvalidData, format!("error loading config: {e}"))
-        })?;
-
     // Task: process incoming messages.
-    let processor_handle = tokio::spawn({
-        let outgoing_message_sender = OutgoingMessageSender::new(outgoing_tx);
-        let mut processor = MessageProcessor::new(
-            outgoing_message_sender,
-            codex_linux_sandbox_exe,
-            std::sync::Arc::new(config),
-        );
-        async move {
-            while let Some(msg) = incoming_rx.recv().await {
-                match msg {
-                    JSONRPCMessage::Request(r) => processor.process_request(r).await,
-                    JSONRPCMessage::Response(r) => processor.process_response(r).await,
-                    JSONRPCMessage::Notification(n) => processor.process_notification(n).await,
-                    JSONRPCMessage::Error(e) => processor.process_error(e),
-                }
-            }
-
-            info!("processor task exited (channel closed)");
-        }
-    });
+    let processor_handle = spawn_message_processor(
+        incoming_rx,
+        outgoing_tx,
+        codex_linux_sandbox_exe,
+        config,
+        shared,
+    );
 
     // Task: write outgoing messages to stdout.
     let stdout_writer_handle = tokio::spawn(async move {
@@ -154,3 +176,65 @@ pub async fn run_main(
 
     Ok(())
 }
+
+/// Spawns the message processor of one client. It runs until `incoming_rx`
+/// is closed, then applies the client disconnect policy.
+pub(crate) fn spawn_message_processor(
+    mut incoming_rx: mpsc::Receiver<JSONRPCMessage>,
+    outgoing_tx: mpsc::Sender<OutgoingMessage>,
+    codex_linux_sandbox_exe: Option<PathBuf>,
+    config: Arc<Config>,
+    shared: SharedState,
+) -> JoinHandle<()> {
+    let outgoing_message_sender = OutgoingMessageSender::new(outgoing_tx);
+    let processor = Arc::new(MessageProcessor::new(
+        outgoing_message_sender,
+        codex_linux_sandbox_exe,
+        config,
+        shared,
+    ));
+    tokio::spawn(async move {
+        let mut in_flight = JoinSet::new();
+        // One task per conversation that handles its requests in order.
+        let mut conversation_queues: HashMap<String, mpsc::UnboundedSender<JSONRPCRequest>> =
+            HashMap::new();
+        while let Some(msg) = incoming_rx.recv().await {
+            // Reap requests that have been answered.
+            while in_flight.try_join_next().is_some() {}
+            match msg {
+                JSONRPCMessage::Request(r) if MessageProcessor::handles_in_order(&r) => {
+                    processor.process_request(r).await
+                }
+                JSONRPCMessage::Request(r) => match MessageProcessor::conversation_of(&r) {
+                    Some(conversation) => {
+                        let queue = conversation_queues.entry(conversation).or_insert_with(|| {
+                            let (tx, mut rx) = mpsc::unbounded_channel();
+                            let processor = processor.clone();
+                            in_flight.spawn(async move {
+                                while let Some(r) = rx.recv().await {
+                                    processor.process_request(r).await;
+                                }
+                            });
+                            tx
+                        });
+                        let _ = queue.send(r);
+                    }
+                    None => {
+                        let processor = processor.clone();
+                        in_flight.spawn(async move { processor.process_request(r).await });
+                    }
+                },
+                JSONRPCMessage::Response(r) => processor.process_response(r).await,
+                JSONRPCMessage::Notification(n) => processor.process_notification(n).await,
+                JSONRPCMessage::Error(e) => processor.process_error(e),
+            }
+        }
+
+        info!("processor task exited (channel closed)");
+        // Let requests that are still being handled finish so that whatever
+        // they start is subject to the disconnect policy.
+        conversation_queues.clear();
+        while in_flight.join_next().await.is_some() {}
+        processor.on_client_disconnect().await;
+    })
+}
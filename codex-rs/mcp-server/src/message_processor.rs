@@ -1,17 +1,31 @@
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use std::time::Instant;
 
 use crate::codex_message_processor::CodexMessageProcessor;
+use crate::codex_tool_config::CodexToolCallListConversationsParam;
 use crate::codex_tool_config::CodexToolCallParam;
 use crate::codex_tool_config::CodexToolCallReplyParam;
+use crate::codex_tool_config::CodexToolCallResumeParam;
+use crate::codex_tool_config::create_tool_for_codex_tool_call_list_conversations_param;
 use crate::codex_tool_config::create_tool_for_codex_tool_call_param;
 use crate::codex_tool_config::create_tool_for_codex_tool_call_reply_param;
+use crate::codex_tool_config::create_tool_for_codex_tool_call_resume_param;
+use crate::codex_tool_config::ensure_config_allowed;
+use crate::conversation_fanout::ConversationFanout;
 use crate::error_code::INVALID_REQUEST_ERROR_CODE;
 use crate::outgoing_message::OutgoingMessageSender;
 use codex_protocol::mcp_protocol::ClientRequest;
 
 use codex_core::ConversationManager;
 use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_core::config_types::McpDisconnectPolicy;
+use codex_core::config_types::McpServerSettings;
+use codex_core::protocol::Op;
 use codex_core::protocol::Submission;
 use codex_login::AuthManager;
 use mcp_types::CallToolRequestParams;
@@ -25,6 +39,7 @@ use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
 use mcp_types::ListToolsResult;
 use mcp_types::ModelContextProtocolRequest;
+use mcp_types::ProgressToken;
 use mcp_types::RequestId;
 use mcp_types::ServerCapabilitiesTools;
 use mcp_types::ServerNotification;
@@ -35,13 +50,37 @@ use tokio::sync::Mutex;
 use tokio::task;
 use uuid::Uuid;
 
+/// State shared by the message processors of every client connected to the
+/// server, so that clients can attach to each other's conversations.
+#[derive(Clone)]
+pub(crate) struct SharedState {
+    auth_manager: Arc<AuthManager>,
+    conversation_manager: Arc<ConversationManager>,
+    conversation_fanout: ConversationFanout,
+}
+
+impl SharedState {
+    pub(crate) fn new(config: &Config) -> Self {
+        let auth_manager =
+            AuthManager::shared(config.codex_home.clone(), config.preferred_auth_method);
+        let conversation_manager = Arc::new(ConversationManager::new(auth_manager.clone()));
+        Self {
+            auth_manager,
+            conversation_manager,
+            conversation_fanout: ConversationFanout::default(),
+        }
+    }
+}
+
 pub(crate) struct MessageProcessor {
     codex_message_processor: CodexMessageProcessor,
     outgoing: Arc<OutgoingMessageSender>,
-    initialized: bool,
+    initialized: AtomicBool,
     codex_linux_sandbox_exe: Option<PathBuf>,
+    codex_home: PathBuf,
     conversation_manager: Arc<ConversationManager>,
     running_requests_id_to_codex_uuid: Arc<Mutex<HashMap<RequestId, Uuid>>>,
+    mcp_server_settings: McpServerSettings,
 }
 
 impl MessageProcessor {
@@ -51,29 +90,65 @@ impl MessageProcessor {
         outgoing: OutgoingMessageSender,
         codex_linux_sandbox_exe: Option<PathBuf>,
         config: Arc<Config>,
+        shared: SharedState,
     ) -> Self {
         let outgoing = Arc::new(outgoing);
-        let auth_manager =
-            AuthManager::shared(config.codex_home.clone(), config.preferred_auth_method);
-        let conversation_manager = Arc::new(ConversationManager::new(auth_manager.clone()));
+        let SharedState {
+            auth_manager,
+            conversation_manager,
+            conversation_fanout,
+        } = shared;
+        let mcp_server_settings = config.mcp_server.clone();
+        let codex_home = config.codex_home.clone();
         let codex_message_processor = CodexMessageProcessor::new(
             auth_manager,
             conversation_manager.clone(),
             outgoing.clone(),
             codex_linux_sandbox_exe.clone(),
             config,
+            conversation_fanout,
         );
         Self {
             codex_message_processor,
             outgoing,
-            initialized: false,
+            initialized: AtomicBool::new(false),
             codex_linux_sandbox_exe,
+            codex_home,
             conversation_manager,
             running_requests_id_to_codex_uuid: Arc::new(Mutex::new(HashMap::new())),
+            mcp_server_settings,
         }
     }
 
-    pub(crate) async fn process_request(&mut self, request: JSONRPCRequest) {
+    /// Whether `request` must be handled before any later message is read.
+    /// Every other request is handled on its own task so that a slow one,
+    /// such as a `codex` tool call, does not hold up `tools/list` or `ping`.
+    pub(crate) fn handles_in_order(request: &JSONRPCRequest) -> bool {
+        // The client must not send anything but pings before `initialize`
+        // is answered, and a new conversation must exist before the
+        // requests that refer to it.
+        request.method == mcp_types::InitializeRequest::METHOD
+            || request.method == "newConversation"
+    }
+
+    /// The conversation `request` acts on, if any. Requests for the same
+    /// conversation are handled one after another, in the order they
+    /// arrived, so that e.g. an interrupt cannot overtake the message it is
+    /// meant to stop.
+    pub(crate) fn conversation_of(request: &JSONRPCRequest) -> Option<String> {
+        let params = request.params.as_ref()?;
+        let id = if request.method == mcp_types::CallToolRequest::METHOD {
+            match params.get("name")?.as_str()? {
+                "codex-reply" | "codex-resume" => params.get("arguments")?.get("sessionId")?,
+                _ => return None,
+            }
+        } else {
+            params.get("conversationId")?
+        };
+        id.as_str().map(str::to_string)
+    }
+
+    pub(crate) async fn process_request(&self, request: JSONRPCRequest) {
         if let Ok(request_json) = serde_json::to_value(request.clone())
             && let Ok(codex_request) = serde_json::from_value::<ClientRequest>(request_json)
         {
@@ -87,6 +162,14 @@ impl MessageProcessor {
 
         // Hold on to the ID so we can respond.
         let request_id = request.id.clone();
+        // `_meta` is not part of the typed params, so read the progress token
+        // before converting the request.
+        let progress_token = request
+            .params
+            .as_ref()
+            .and_then(|params| params.get("_meta"))
+            .and_then(|meta| meta.get("progressToken"))
+            .and_then(|token| serde_json::from_value::<ProgressToken>(token.clone()).ok());
 
         let client_request = match McpClientRequest::try_from(request) {
             Ok(client_request) => client_request,
@@ -129,7 +212,8 @@ impl MessageProcessor {
                 self.handle_list_tools(request_id, params).await;
             }
             McpClientRequest::CallToolRequest(params) => {
-                self.handle_call_tool(request_id, params).await;
+                self.handle_call_tool(request_id, params, progress_token)
+                    .await;
             }
             McpClientRequest::SetLevelRequest(params) => {
                 self.handle_set_level(params);
@@ -141,14 +225,14 @@ impl MessageProcessor {
     }
 
     /// Handle a standalone JSON-RPC response originating from the peer.
-    pub(crate) async fn process_response(&mut self, response: JSONRPCResponse) {
+    pub(crate) async fn process_response(&self, response: JSONRPCResponse) {
         tracing::info!("<- response: {:?}", response);
         let JSONRPCResponse { id, result, .. } = response;
         self.outgoing.notify_client_response(id, result).await
     }
 
     /// Handle a fire-and-forget JSON-RPC notification.
-    pub(crate) async fn process_notification(&mut self, notification: JSONRPCNotification) {
+    pub(crate) async fn process_notification(&self, notification: JSONRPCNotification) {
         let server_notification = match ServerNotification::try_from(notification) {
             Ok(n) => n,
             Err(e) => {
@@ -185,18 +269,66 @@ impl MessageProcessor {
     }
 
     /// Handle an error object received from the peer.
-    pub(crate) fn process_error(&mut self, err: JSONRPCError) {
+    pub(crate) fn process_error(&self, err: JSONRPCError) {
         tracing::error!("<- error: {:?}", err);
     }
 
+    /// Called once the client has closed its end of the connection. Running
+    /// turns are interrupted or allowed to finish according to
+    /// `[mcp_server] on_disconnect`, after which every conversation is shut
+    /// down.
+    pub(crate) async fn on_client_disconnect(&self) {
+        self.codex_message_processor.on_client_disconnect().await;
+
+        // Conversations driven through the `codex` tool end with the tool
+        // call, so only their in-flight calls need handling here.
+        let ttl = Duration::from_secs(self.mcp_server_settings.parked_conversation_ttl_secs);
+        let deadline = Instant::now() + ttl;
+        let mut session_ids: Vec<Uuid> = self
+            .running_requests_id_to_codex_uuid
+            .lock()
+            .await
+            .values()
+            .copied()
+            .collect();
+        session_ids.sort();
+        session_ids.dedup();
+        if self.mcp_server_settings.on_disconnect == McpDisconnectPolicy::Abort {
+            for session_id in &session_ids {
+                if let Ok(conversation) = self
+                    .conversation_manager
+                    .get_conversation(*session_id)
+                    .await
+                {
+                    let _ = conversation.submit(Op::Interrupt).await;
+                }
+            }
+        } else {
+            while Instant::now() < deadline
+                && !self
+                    .running_requests_id_to_codex_uuid
+                    .lock()
+                    .await
+                    .is_empty()
+            {
+                tokio::time::sleep(Duration::from_millis(100)).await;
+            }
+        }
+        for session_id in session_ids {
+            if let Ok(conversation) = self.conversation_manager.get_conversation(session_id).await {
+                let _ = conversation.submit(Op::Shutdown).await;
+            }
+        }
+    }
+
     async fn handle_initialize(
-        &mut self,
+        &self,
         id: RequestId,
         params: <mcp_types::InitializeRequest as ModelContextProtocolRequest>::Params,
     ) {
         tracing::info!("initialize -> params: {:?}", params);
 
-        if self.initialized {
+        if self.initialized.swap(true, Ordering::SeqCst) {
             // Already initialised: send JSON-RPC error response.
             let error = JSONRPCErrorError {
                 code: INVALID_REQUEST_ERROR_CODE,
@@ -207,8 +339,6 @@ impl MessageProcessor {
             return;
         }
 
-        self.initialized = true;
-
         // Build a minimal InitializeResult. Fill with placeholders.
         let result = mcp_types::InitializeResult {
             capabilities: mcp_types::ServerCapabilities {
@@ -312,6 +442,8 @@ impl MessageProcessor {
             tools: vec![
                 create_tool_for_codex_tool_call_param(),
                 create_tool_for_codex_tool_call_reply_param(),
+                create_tool_for_codex_tool_call_list_conversations_param(),
+                create_tool_for_codex_tool_call_resume_param(),
             ],
             next_cursor: None,
         };
@@ -324,14 +456,26 @@ impl MessageProcessor {
         &self,
         id: RequestId,
         params: <mcp_types::CallToolRequest as mcp_types::ModelContextProtocolRequest>::Params,
+        progress_token: Option<ProgressToken>,
     ) {
         tracing::info!("tools/call -> params: {:?}", params);
         let CallToolRequestParams { name, arguments } = params;
 
         match name.as_str() {
-            "codex" => self.handle_tool_call_codex(id, arguments).await,
+            "codex" => {
+                self.handle_tool_call_codex(id, arguments, progress_token)
+                    .await
+            }
             "codex-reply" => {
-                self.handle_tool_call_codex_session_reply(id, arguments)
+                self.handle_tool_call_codex_session_reply(id, arguments, progress_token)
+                    .await
+            }
+            "codex-list-conversations" => {
+                self.handle_tool_call_codex_list_conversations(id, arguments)
+                    .await
+            }
+            "codex-resume" => {
+                self.handle_tool_call_codex_resume(id, arguments, progress_token)
                     .await
             }
             _ => {
@@ -349,10 +493,18 @@ impl MessageProcessor {
             }
         }
     }
-    async fn handle_tool_call_codex(&self, id: RequestId, arguments: Option<serde_json::Value>) {
+    async fn handle_tool_call_codex(
+        &self,
+        id: RequestId,
+        arguments: Option<serde_json::Value>,
+        progress_token: Option<ProgressToken>,
+    ) {
         let (initial_prompt, config): (String, Config) = match arguments {
             Some(json_val) => match serde_json::from_value::<CodexToolCallParam>(json_val) {
-                Ok(tool_cfg) => match tool_cfg.into_config(self.codex_linux_sandbox_exe.clone()) {
+                Ok(tool_cfg) => match tool_cfg.into_config(
+                    self.codex_linux_sandbox_exe.clone(),
+                    &self.mcp_server_settings.allow,
+                ) {
                     Ok(cfg) => cfg,
                     Err(e) => {
                         let result = CallToolResult {
@@ -420,6 +572,7 @@ impl MessageProcessor {
                 outgoing,
                 conversation_manager,
                 running_requests_id_to_codex_uuid,
+                progress_token,
             )
             .await;
         });
@@ -429,6 +582,7 @@ impl MessageProcessor {
         &self,
         request_id: RequestId,
         arguments: Option<serde_json::Value>,
+        progress_token: Option<ProgressToken>,
     ) {
         tracing::info!("tools/call -> params: {:?}", arguments);
 
@@ -526,12 +680,199 @@ impl MessageProcessor {
                     prompt,
                     running_requests_id_to_codex_uuid,
                     session_id,
+                    progress_token,
+                )
+                .await;
+            }
+        });
+    }
+
+    async fn handle_tool_call_codex_list_conversations(
+        &self,
+        request_id: RequestId,
+        arguments: Option<serde_json::Value>,
+    ) {
+        let CodexToolCallListConversationsParam { limit } = match arguments {
+            Some(json_val) => {
+                match serde_json::from_value::<CodexToolCallListConversationsParam>(json_val) {
+                    Ok(params) => params,
+                    Err(e) => {
+                        self.send_tool_call_error(
+                            request_id,
+                            format!("Failed to parse parameters for codex-list-conversations: {e}"),
+                        )
+                        .await;
+                        return;
+                    }
+                }
+            }
+            None => CodexToolCallListConversationsParam::default(),
+        };
+
+        let active = self.conversation_manager.list_conversations().await;
+        let archived: Vec<serde_json::Value> = codex_core::list_rollouts(&self.codex_home)
+            .into_iter()
+            .filter(|summary| !active.contains(&summary.session_id))
+            .take(limit.unwrap_or(usize::MAX))
+            .map(|summary| {
+                json!({
+                    "sessionId": summary.session_id,
+                    "rolloutPath": summary.path,
+                    "timestamp": summary.timestamp,
+                    "cwd": summary.cwd,
+                })
+            })
+            .collect();
+        let active: Vec<serde_json::Value> = active
+            .into_iter()
+            .map(|session_id| json!({ "sessionId": session_id }))
+            .collect();
+        let structured = json!({
+            "active": active,
+            "archived": archived,
+        });
+
+        let result = CallToolResult {
+            content: vec![ContentBlock::TextContent(TextContent {
+                r#type: "text".to_string(),
+                text: structured.to_string(),
+                annotations: None,
+            })],
+            is_error: None,
+            structured_content: Some(structured),
+        };
+        self.send_response::<mcp_types::CallToolRequest>(request_id, result)
+            .await;
+    }
+
+    async fn handle_tool_call_codex_resume(
+        &self,
+        request_id: RequestId,
+        arguments: Option<serde_json::Value>,
+        progress_token: Option<ProgressToken>,
+    ) {
+        let CodexToolCallResumeParam {
+            session_id,
+            rollout_path,
+            prompt,
+        } = match arguments {
+            Some(json_val) => match serde_json::from_value::<CodexToolCallResumeParam>(json_val) {
+                Ok(params) => params,
+                Err(e) => {
+                    self.send_tool_call_error(
+                        request_id,
+                        format!("Failed to parse parameters for codex-resume: {e}"),
+                    )
+                    .await;
+                    return;
+                }
+            },
+            None => {
+                self.send_tool_call_error(
+                    request_id,
+                    "Missing arguments for codex-resume tool-call; the `prompt` field and one of `sessionId` or `rolloutPath` are required.".to_string(),
                 )
                 .await;
+                return;
             }
+        };
+
+        let summary = match (rollout_path, session_id) {
+            (Some(path), _) => codex_core::read_rollout_summary(&PathBuf::from(&path))
+                .ok_or_else(|| format!("Failed to read rollout file: {path}")),
+            (None, Some(session_id)) => match Uuid::parse_str(&session_id) {
+                Ok(session_id) => codex_core::list_rollouts(&self.codex_home)
+                    .into_iter()
+                    .find(|summary| summary.session_id == session_id)
+                    .ok_or_else(|| format!("No rollout found for session_id: {session_id}")),
+                Err(e) => Err(format!("Failed to parse session_id: {e}")),
+            },
+            (None, None) => {
+                Err("codex-resume requires either `sessionId` or `rolloutPath`.".to_string())
+            }
+        };
+        let summary = match summary {
+            Ok(summary) => summary,
+            Err(message) => {
+                self.send_tool_call_error(request_id, message).await;
+                return;
+            }
+        };
+
+        if self
+            .conversation_manager
+            .get_conversation(summary.session_id)
+            .await
+            .is_ok()
+        {
+            self.send_tool_call_error(
+                request_id,
+                format!(
+                    "Session {} is already active; continue it with codex-reply.",
+                    summary.session_id
+                ),
+            )
+            .await;
+            return;
+        }
+
+        let overrides = ConfigOverrides {
+            cwd: summary.cwd,
+            codex_linux_sandbox_exe: self.codex_linux_sandbox_exe.clone(),
+            ..Default::default()
+        };
+        let loaded = Config::load_with_cli_overrides(Vec::new(), overrides).and_then(|config| {
+            ensure_config_allowed(&config, &self.mcp_server_settings.allow)?;
+            Ok(config)
+        });
+        let mut config = match loaded {
+            Ok(config) => config,
+            Err(e) => {
+                self.send_tool_call_error(
+                    request_id,
+                    format!("Failed to load Codex configuration: {e}"),
+                )
+                .await;
+                return;
+            }
+        };
+        config.experimental_resume = Some(summary.path);
+
+        let outgoing = self.outgoing.clone();
+        let conversation_manager = self.conversation_manager.clone();
+        let running_requests_id_to_codex_uuid = self.running_requests_id_to_codex_uuid.clone();
+
+        // The resumed session keeps the id recorded in its rollout, so
+        // `codex-reply` can continue it afterwards.
+        task::spawn(async move {
+            crate::codex_tool_runner::run_codex_tool_session(
+                request_id,
+                prompt,
+                config,
+                outgoing,
+                conversation_manager,
+                running_requests_id_to_codex_uuid,
+                progress_token,
+            )
+            .await;
         });
     }
 
+    async fn send_tool_call_error(&self, request_id: RequestId, text: String) {
+        tracing::error!("{text}");
+        let result = CallToolResult {
+            content: vec![ContentBlock::TextContent(TextContent {
+                r#type: "text".to_owned(),
+                text,
+                annotations: None,
+            })],
+            is_error: Some(true),
+            structured_content: None,
+        };
+        self.send_response::<mcp_types::CallToolRequest>(request_id, result)
+            .await;
+    }
+
     fn handle_set_level(
         &self,
         params: <mcp_types::SetLevelRequest as mcp_types::ModelContextProtocolRequest>::Params,
@@ -561,16 +902,16 @@ impl MessageProcessor {
             RequestId::Integer(i) => i.to_string(),
         };
 
-        // Obtain the session_id while holding the first lock, then release.
-        let session_id = {
-            let map_guard = self.running_requests_id_to_codex_uuid.lock().await;
-            match map_guard.get(&request_id) {
-                Some(id) => *id, // Uuid is Copy
-                None => {
-                    tracing::warn!("Session not found for request_id: {}", request_id_string);
-                    return;
-                }
-            }
+        // Unregister the request before interrupting so the tool-call runner
+        // knows the abort came from the client and does not respond to it.
+        let Some(session_id) = self
+            .running_requests_id_to_codex_uuid
+            .lock()
+            .await
+            .remove(&request_id)
+        else {
+            tracing::warn!("Session not found for request_id: {}", request_id_string);
+            return;
         };
         tracing::info!("session_id: {session_id}");
 
@@ -592,13 +933,7 @@ impl MessageProcessor {
             .await;
         if let Err(e) = err {
             tracing::error!("Failed to submit interrupt to Codex: {e}");
-            return;
         }
-        // unregister the id so we don't keep it in the map
-        self.running_requests_id_to_codex_uuid
-            .lock()
-            .await
-            .remove(&request_id);
     }
 
     fn handle_progress_notification(
@@ -11,6 +11,10 @@ use mcp_types::JSONRPCMessage;
 use mcp_types::JSONRPCNotification;
 use mcp_types::JSONRPCRequest;
 use mcp_types::JSONRPCResponse;
+use mcp_types::ModelContextProtocolNotification;
+use mcp_types::ProgressNotification;
+use mcp_types::ProgressNotificationParams;
+use mcp_types::ProgressToken;
 use mcp_types::RequestId;
 use mcp_types::Result;
 use serde::Serialize;
@@ -133,6 +137,30 @@ impl OutgoingMessageSender {
         let _ = self.sender.send(outgoing_message).await;
     }
 
+    /// Report progress on a request that carried `_meta.progressToken`.
+    pub(crate) async fn send_progress_notification(
+        &self,
+        progress_token: ProgressToken,
+        progress: f64,
+        message: String,
+    ) {
+        let params = ProgressNotificationParams {
+            message: Some(message),
+            progress,
+            progress_token,
+            total: None,
+        };
+        let Ok(params) = serde_json::to_value(params) else {
+            warn!("Failed to serialize progress notification");
+            return;
+        };
+        self.send_notification(OutgoingNotification {
+            method: ProgressNotification::METHOD.to_string(),
+            params: Some(params),
+        })
+        .await;
+    }
+
     pub(crate) async fn send_notification(&self, notification: OutgoingNotification) {
         let outgoing_message = OutgoingMessage::Notification(notification);
         let _ = self.sender.send(outgoing_message).await;
@@ -241,6 +269,7 @@ pub(crate) struct OutgoingError {
 #[cfg(test)]
 mod tests {
     use codex_core::protocol::EventMsg;
+    use codex_core::protocol::ProtocolHandshake;
     use codex_core::protocol::SessionConfiguredEvent;
     use pretty_assertions::assert_eq;
     use serde_json::json;
@@ -260,6 +289,8 @@ mod tests {
                 model: "gpt-4o".to_string(),
                 history_log_id: 1,
                 history_entry_count: 1000,
+                project_docs: Vec::new(),
+                protocol: ProtocolHandshake::current(),
             }),
         };
 
@@ -289,6 +320,8 @@ mod tests {
             model: "gpt-4o".to_string(),
             history_log_id: 1,
             history_entry_count: 1000,
+            project_docs: Vec::new(),
+            protocol: ProtocolHandshake::legacy(),
         };
         let event = Event {
             id: "1".to_string(),
@@ -317,6 +350,10 @@ mod tests {
                 "model": session_configured_event.model,
                 "history_log_id": session_configured_event.history_log_id,
                 "history_entry_count": session_configured_event.history_entry_count,
+                "protocol": {
+                    "protocol_version": 0,
+                    "capabilities": [],
+                },
                 "type": "session_configured",
             }
         });
@@ -163,6 +163,28 @@ impl McpProcess {
         .await
     }
 
+    /// Send a `ping` JSON-RPC request.
+    pub async fn send_ping_request(&mut self) -> anyhow::Result<i64> {
+        self.send_request(mcp_types::PingRequest::METHOD, None)
+            .await
+    }
+
+    /// Send a `tools/list` JSON-RPC request.
+    pub async fn send_list_tools_request(&mut self) -> anyhow::Result<i64> {
+        self.send_request(mcp_types::ListToolsRequest::METHOD, None)
+            .await
+    }
+
+    /// Send a `notifications/cancelled` notification for `request_id`.
+    pub async fn send_cancelled_notification(&mut self, request_id: i64) -> anyhow::Result<()> {
+        self.send_jsonrpc_message(JSONRPCMessage::Notification(JSONRPCNotification {
+            jsonrpc: JSONRPC_VERSION.into(),
+            method: mcp_types::CancelledNotification::METHOD.into(),
+            params: Some(json!({ "requestId": request_id })),
+        }))
+        .await
+    }
+
     /// Send a `newConversation` JSON-RPC request.
     pub async fn send_new_conversation_request(
         &mut self,
@@ -7,6 +7,7 @@ use codex_core::protocol::FileChange;
 use codex_core::protocol::ReviewDecision;
 use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
 use codex_mcp_server::CodexToolCallParam;
+use codex_mcp_server::CodexToolCallSandboxMode;
 use codex_mcp_server::ExecApprovalElicitRequestParams;
 use codex_mcp_server::ExecApprovalResponse;
 use codex_mcp_server::PatchApprovalElicitRequestParams;
@@ -15,7 +16,6 @@ use mcp_types::ElicitRequest;
 use mcp_types::ElicitRequestParamsRequestedSchema;
 use mcp_types::JSONRPC_VERSION;
 use mcp_types::JSONRPCRequest;
-use mcp_types::JSONRPCResponse;
 use mcp_types::ModelContextProtocolRequest;
 use mcp_types::RequestId;
 use pretty_assertions::assert_eq;
@@ -129,20 +129,15 @@ async fn shell_command_approval_triggers_elicitation() -> anyhow::Result<()> {
         mcp_process.read_stream_until_response_message(RequestId::Integer(codex_request_id)),
     )
     .await??;
+    assert_eq!(RequestId::Integer(codex_request_id), codex_response.id);
     assert_eq!(
-        JSONRPCResponse {
-            jsonrpc: JSONRPC_VERSION.into(),
-            id: RequestId::Integer(codex_request_id),
-            result: json!({
-                "content": [
-                    {
-                        "text": "Enjoy your new git repo!",
-                        "type": "text"
-                    }
-                ]
-            }),
-        },
-        codex_response
+        json!([
+            {
+                "text": "Enjoy your new git repo!",
+                "type": "text"
+            }
+        ]),
+        codex_response.result["content"]
     );
 
     assert!(
@@ -273,21 +268,35 @@ async fn patch_approval_triggers_elicitation() -> anyhow::Result<()> {
         mcp_process.read_stream_until_response_message(RequestId::Integer(codex_request_id)),
     )
     .await??;
+    assert_eq!(RequestId::Integer(codex_request_id), codex_response.id);
     assert_eq!(
-        JSONRPCResponse {
-            jsonrpc: JSONRPC_VERSION.into(),
-            id: RequestId::Integer(codex_request_id),
-            result: json!({
-                "content": [
-                    {
-                        "text": "Patch has been applied successfully!",
-                        "type": "text"
-                    }
-                ]
-            }),
-        },
-        codex_response
+        json!([
+            {
+                "text": "Patch has been applied successfully!",
+                "type": "text"
+            },
+            {
+                "name": "destination_file.txt",
+                "type": "resource_link",
+                "uri": format!("file://{}", test_file.display())
+            }
+        ]),
+        codex_response.result["content"]
+    );
+    let structured_content = &codex_response.result["structuredContent"];
+    assert_eq!(
+        json!([{ "kind": "update", "path": test_file }]),
+        structured_content["changedFiles"]
     );
+    assert_eq!(
+        json!("Patch has been applied successfully!"),
+        structured_content["lastAgentMessage"]
+    );
+    let unified_diff = structured_content["unifiedDiff"]
+        .as_str()
+        .unwrap_or_default();
+    assert!(unified_diff.contains("-original content\n+modified content\n"));
+    assert!(structured_content["tokenUsage"]["total_tokens"].is_u64());
 
     let file_contents = std::fs::read_to_string(test_file.as_path())?;
     assert_eq!(file_contents, "modified content\n");
@@ -340,20 +349,15 @@ async fn codex_tool_passes_base_instructions() -> anyhow::Result<()> {
         mcp_process.read_stream_until_response_message(RequestId::Integer(codex_request_id)),
     )
     .await??;
+    assert_eq!(RequestId::Integer(codex_request_id), codex_response.id);
     assert_eq!(
-        JSONRPCResponse {
-            jsonrpc: JSONRPC_VERSION.into(),
-            id: RequestId::Integer(codex_request_id),
-            result: json!({
-                "content": [
-                    {
-                        "text": "Enjoy!",
-                        "type": "text"
-                    }
-                ]
-            }),
-        },
-        codex_response
+        json!([
+            {
+                "text": "Enjoy!",
+                "type": "text"
+            }
+        ]),
+        codex_response.result["content"]
     );
 
     let requests = server.received_requests().await.unwrap();
@@ -364,6 +368,162 @@ async fn codex_tool_passes_base_instructions() -> anyhow::Result<()> {
     Ok(())
 }
 
+/// A `codex` tool call that waits for an approval must not hold up other
+/// requests from the same client.
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn test_requests_are_answered_while_codex_tool_call_is_in_flight() {
+    if env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    if let Err(err) = requests_are_answered_while_codex_tool_call_is_in_flight().await {
+        panic!("failure: {err}");
+    }
+}
+
+async fn requests_are_answered_while_codex_tool_call_is_in_flight() -> anyhow::Result<()> {
+    let workdir = TempDir::new()?;
+    let McpHandle {
+        process: mut mcp_process,
+        server: _server,
+        dir: _dir,
+    } = create_mcp_process(vec![create_shell_sse_response(
+        vec!["git".to_string(), "init".to_string()],
+        Some(workdir.path()),
+        Some(5_000),
+        "call1234",
+    )?])
+    .await?;
+
+    mcp_process
+        .send_codex_tool_call(CodexToolCallParam {
+            prompt: "run `git init`".to_string(),
+            ..Default::default()
+        })
+        .await?;
+    // The tool call now waits for the approval it asked for.
+    let elicitation_request = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp_process.read_stream_until_request_message(),
+    )
+    .await??;
+    assert_eq!(ElicitRequest::METHOD, elicitation_request.method);
+
+    let ping_id = mcp_process.send_ping_request().await?;
+    let ping_response = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp_process.read_stream_until_response_message(RequestId::Integer(ping_id)),
+    )
+    .await??;
+    assert_eq!(json!({}), ping_response.result);
+
+    let list_tools_id = mcp_process.send_list_tools_request().await?;
+    let list_tools_response = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp_process.read_stream_until_response_message(RequestId::Integer(list_tools_id)),
+    )
+    .await??;
+    let tools = list_tools_response.result["tools"]
+        .as_array()
+        .ok_or_else(|| anyhow::anyhow!("tools/list result has no tools"))?;
+    assert!(tools.iter().any(|tool| tool["name"] == "codex"));
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_codex_tool_rejects_overrides_outside_allow_list() {
+    // Apparently `#[tokio::test]` must return `()`, so we create a helper
+    // function that returns `Result` so we can use `?` in favor of `unwrap`.
+    if let Err(err) = codex_tool_rejects_overrides_outside_allow_list().await {
+        panic!("failure: {err}");
+    }
+}
+
+async fn codex_tool_rejects_overrides_outside_allow_list() -> anyhow::Result<()> {
+    let server = create_mock_chat_completions_server(Vec::new()).await;
+    let codex_home = TempDir::new()?;
+    create_config_toml(codex_home.path(), &server.uri())?;
+    let config_toml = codex_home.path().join("config.toml");
+    let mut contents = std::fs::read_to_string(&config_toml)?;
+    contents.push_str(
+        r#"
+[mcp_server.allow]
+sandbox_modes = ["read-only"]
+"#,
+    );
+    std::fs::write(&config_toml, contents)?;
+    let mut mcp_process = McpProcess::new(codex_home.path()).await?;
+    timeout(DEFAULT_READ_TIMEOUT, mcp_process.initialize()).await??;
+
+    // Both the dedicated parameter and a raw config override are checked, and
+    // overrides of keys that the allow-list does not cover are refused.
+    let denied_sandbox = "sandbox mode `danger-full-access` is not allowed by [mcp_server.allow]";
+    let requests = [
+        (
+            CodexToolCallParam {
+                prompt: "Hello".to_string(),
+                sandbox: Some(CodexToolCallSandboxMode::DangerFullAccess),
+                ..Default::default()
+            },
+            denied_sandbox,
+        ),
+        (
+            CodexToolCallParam {
+                prompt: "Hello".to_string(),
+                config: Some(HashMap::from([(
+                    "sandbox_mode".to_string(),
+                    json!("danger-full-access"),
+                )])),
+                ..Default::default()
+            },
+            denied_sandbox,
+        ),
+        (
+            CodexToolCallParam {
+                prompt: "Hello".to_string(),
+                config: Some(HashMap::from([(
+                    "notify".to_string(),
+                    json!(["/bin/sh", "-c", "true"]),
+                )])),
+                ..Default::default()
+            },
+            "config override `notify` is not allowed by [mcp_server.allow]",
+        ),
+        (
+            CodexToolCallParam {
+                prompt: "Hello".to_string(),
+                config: Some(HashMap::from([(
+                    "sandbox_workspace_write.writable_roots".to_string(),
+                    json!(["/"]),
+                )])),
+                ..Default::default()
+            },
+            "config override `sandbox_workspace_write.writable_roots` is not allowed by [mcp_server.allow]",
+        ),
+    ];
+    for (request, message) in requests {
+        let codex_request_id = mcp_process.send_codex_tool_call(request).await?;
+        let codex_response = timeout(
+            DEFAULT_READ_TIMEOUT,
+            mcp_process.read_stream_until_response_message(RequestId::Integer(codex_request_id)),
+        )
+        .await??;
+        assert_eq!(json!(true), codex_response.result["isError"]);
+        assert_eq!(
+            json!(format!(
+                "Failed to load Codex configuration from overrides: {message}"
+            )),
+            codex_response.result["content"][0]["text"]
+        );
+    }
+
+    Ok(())
+}
+
 fn create_expected_patch_approval_elicitation_request(
     elicitation_request_id: RequestId,
     changes: HashMap<PathBuf, FileChange>,
@@ -0,0 +1,333 @@
+use std::collections::BTreeSet;
+use std::path::Path;
+
+use codex_protocol::mcp_protocol::AddConversationListenerParams;
+use codex_protocol::mcp_protocol::AddConversationSubscriptionResponse;
+use codex_protocol::mcp_protocol::ConversationId;
+use codex_protocol::mcp_protocol::InputItem;
+use codex_protocol::mcp_protocol::NewConversationParams;
+use codex_protocol::mcp_protocol::NewConversationResponse;
+use codex_protocol::mcp_protocol::RemoveConversationListenerParams;
+use codex_protocol::mcp_protocol::SendUserMessageParams;
+use mcp_test_support::McpProcess;
+use mcp_test_support::create_final_assistant_message_sse_response;
+use mcp_test_support::create_mock_chat_completions_server;
+use mcp_test_support::to_response;
+use mcp_types::JSONRPCResponse;
+use mcp_types::RequestId;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tempfile::TempDir;
+use tokio::time::timeout;
+use uuid::Uuid;
+
+const DEFAULT_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[tokio::test]
+async fn test_parked_conversation_replays_events_to_new_listener() {
+    let responses = vec![
+        create_final_assistant_message_sse_response("Done").expect("build mock assistant message"),
+    ];
+    let server = create_mock_chat_completions_server(responses).await;
+    let codex_home = TempDir::new().expect("create temp dir");
+    create_config_toml(codex_home.path(), &server.uri(), 600).expect("write config.toml");
+
+    let mut mcp = McpProcess::new(codex_home.path())
+        .await
+        .expect("spawn mcp process");
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize())
+        .await
+        .expect("init timed out")
+        .expect("init failed");
+
+    let conversation_id = new_conversation(&mut mcp).await;
+    let subscription_id = add_listener(&mut mcp, conversation_id, false).await;
+    let remove_id = mcp
+        .send_remove_conversation_listener_request(RemoveConversationListenerParams {
+            subscription_id,
+        })
+        .await
+        .expect("send removeConversationListener");
+
+    // The conversation is parked before the removal is acknowledged.
+    let parked = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_notification_message("codex/event/conversation_parked"),
+    )
+    .await
+    .expect("conversation_parked timeout")
+    .expect("conversation_parked notification");
+    timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(remove_id)),
+    )
+    .await
+    .expect("removeConversationListener timeout")
+    .expect("removeConversationListener resp");
+    assert_eq!(
+        parked.params,
+        Some(json!({
+            "conversationId": conversation_id,
+            "expiresInSecs": 600,
+        }))
+    );
+
+    // Run a turn while nobody is listening.
+    let send_id = mcp
+        .send_send_user_message_request(SendUserMessageParams {
+            conversation_id,
+            items: vec![InputItem::Text {
+                text: "Hello".to_string(),
+            }],
+        })
+        .await
+        .expect("send sendUserMessage");
+    timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(send_id)),
+    )
+    .await
+    .expect("sendUserMessage timeout")
+    .expect("sendUserMessage response");
+
+    // The events of the turn reach the listener added afterwards.
+    add_listener(&mut mcp, conversation_id, false).await;
+    let task_complete = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_notification_message("codex/event/task_complete"),
+    )
+    .await
+    .expect("task_complete timeout")
+    .expect("task_complete notification");
+    let params = task_complete.params.expect("task_complete params");
+    assert_eq!(params["conversationId"], json!(conversation_id));
+    assert_eq!(params["msg"]["last_agent_message"], json!("Done"));
+}
+
+#[tokio::test]
+async fn test_parked_conversation_expires_after_ttl() {
+    let server = create_mock_chat_completions_server(Vec::new()).await;
+    let codex_home = TempDir::new().expect("create temp dir");
+    create_config_toml(codex_home.path(), &server.uri(), 1).expect("write config.toml");
+
+    let mut mcp = McpProcess::new(codex_home.path())
+        .await
+        .expect("spawn mcp process");
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize())
+        .await
+        .expect("init timed out")
+        .expect("init failed");
+
+    let conversation_id = new_conversation(&mut mcp).await;
+    let subscription_id = add_listener(&mut mcp, conversation_id, false).await;
+    remove_listener(&mut mcp, subscription_id).await;
+
+    let expired = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_notification_message("codex/event/conversation_expired"),
+    )
+    .await
+    .expect("conversation_expired timeout")
+    .expect("conversation_expired notification");
+    assert_eq!(
+        expired.params,
+        Some(json!({ "conversationId": conversation_id }))
+    );
+
+    // The conversation is gone, so a new listener cannot be added.
+    let add_listener_id = mcp
+        .send_add_conversation_listener_request(AddConversationListenerParams {
+            conversation_id,
+            broadcast: false,
+        })
+        .await
+        .expect("send addConversationListener");
+    let err = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_error_message(RequestId::Integer(add_listener_id)),
+    )
+    .await
+    .expect("addConversationListener timeout")
+    .expect("addConversationListener error");
+    assert_eq!(
+        err.error.message,
+        format!("conversation not found: {}", conversation_id.0)
+    );
+}
+
+#[tokio::test]
+async fn test_broadcast_listeners_share_a_conversation() {
+    let responses = vec![
+        create_final_assistant_message_sse_response("Done").expect("build mock assistant message"),
+        create_final_assistant_message_sse_response("Done").expect("build mock assistant message"),
+    ];
+    let server = create_mock_chat_completions_server(responses).await;
+    let codex_home = TempDir::new().expect("create temp dir");
+    create_config_toml(codex_home.path(), &server.uri(), 600).expect("write config.toml");
+
+    let mut mcp = McpProcess::new(codex_home.path())
+        .await
+        .expect("spawn mcp process");
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize())
+        .await
+        .expect("init timed out")
+        .expect("init failed");
+
+    let conversation_id = new_conversation(&mut mcp).await;
+    let primary = add_listener(&mut mcp, conversation_id, false).await;
+    let observer = add_listener(&mut mcp, conversation_id, true).await;
+    let standby = add_listener(&mut mcp, conversation_id, false).await;
+
+    // The primary and broadcast listeners both see the turn.
+    send_message(&mut mcp, conversation_id).await;
+    assert_eq!(
+        read_task_complete_subscriptions(&mut mcp, 2).await,
+        BTreeSet::from([primary, observer])
+    );
+
+    // Once the primary is gone, the standby listener takes over.
+    remove_listener(&mut mcp, primary).await;
+    send_message(&mut mcp, conversation_id).await;
+    assert_eq!(
+        read_task_complete_subscriptions(&mut mcp, 2).await,
+        BTreeSet::from([observer, standby])
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+#[expect(clippy::expect_used)]
+async fn new_conversation(mcp: &mut McpProcess) -> ConversationId {
+    let request_id = mcp
+        .send_new_conversation_request(NewConversationParams::default())
+        .await
+        .expect("send newConversation");
+    let response: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(request_id)),
+    )
+    .await
+    .expect("newConversation timeout")
+    .expect("newConversation resp");
+    let NewConversationResponse {
+        conversation_id, ..
+    } = to_response::<_>(response).expect("deserialize newConversation response");
+    conversation_id
+}
+
+#[expect(clippy::expect_used)]
+async fn add_listener(
+    mcp: &mut McpProcess,
+    conversation_id: ConversationId,
+    broadcast: bool,
+) -> Uuid {
+    let request_id = mcp
+        .send_add_conversation_listener_request(AddConversationListenerParams {
+            conversation_id,
+            broadcast,
+        })
+        .await
+        .expect("send addConversationListener");
+    let response: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(request_id)),
+    )
+    .await
+    .expect("addConversationListener timeout")
+    .expect("addConversationListener resp");
+    let AddConversationSubscriptionResponse { subscription_id } =
+        to_response::<_>(response).expect("deserialize addConversationListener response");
+    subscription_id
+}
+
+#[expect(clippy::expect_used)]
+async fn send_message(mcp: &mut McpProcess, conversation_id: ConversationId) {
+    let request_id = mcp
+        .send_send_user_message_request(SendUserMessageParams {
+            conversation_id,
+            items: vec![InputItem::Text {
+                text: "Hello".to_string(),
+            }],
+        })
+        .await
+        .expect("send sendUserMessage");
+    timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(request_id)),
+    )
+    .await
+    .expect("sendUserMessage timeout")
+    .expect("sendUserMessage response");
+}
+
+/// Reads `count` task_complete notifications and returns the subscriptions
+/// they were delivered to.
+#[expect(clippy::expect_used)]
+async fn read_task_complete_subscriptions(mcp: &mut McpProcess, count: usize) -> BTreeSet<Uuid> {
+    let mut subscriptions = BTreeSet::new();
+    for _ in 0..count {
+        let notification = timeout(
+            DEFAULT_READ_TIMEOUT,
+            mcp.read_stream_until_notification_message("codex/event/task_complete"),
+        )
+        .await
+        .expect("task_complete timeout")
+        .expect("task_complete notification");
+        let params = notification.params.expect("task_complete params");
+        let subscription_id = params["subscriptionId"]
+            .as_str()
+            .and_then(|id| Uuid::parse_str(id).ok())
+            .expect("task_complete subscriptionId");
+        subscriptions.insert(subscription_id);
+    }
+    subscriptions
+}
+
+#[expect(clippy::expect_used)]
+async fn remove_listener(mcp: &mut McpProcess, subscription_id: Uuid) {
+    let request_id = mcp
+        .send_remove_conversation_listener_request(RemoveConversationListenerParams {
+            subscription_id,
+        })
+        .await
+        .expect("send removeConversationListener");
+    timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(request_id)),
+    )
+    .await
+    .expect("removeConversationListener timeout")
+    .expect("removeConversationListener resp");
+}
+
+fn create_config_toml(
+    codex_home: &Path,
+    server_uri: &str,
+    parked_conversation_ttl_secs: u64,
+) -> std::io::Result<()> {
+    let config_toml = codex_home.join("config.toml");
+    std::fs::write(
+        config_toml,
+        format!(
+            r#"
+model = "mock-model"
+approval_policy = "never"
+sandbox_mode = "danger-full-access"
+
+model_provider = "mock_provider"
+
+[model_providers.mock_provider]
+name = "Mock provider for test"
+base_url = "{server_uri}/v1"
+wire_api = "chat"
+request_max_retries = 0
+stream_max_retries = 0
+
+[mcp_server]
+parked_conversation_ttl_secs = {parked_conversation_ttl_secs}
+"#
+        ),
+    )
+}
@@ -7,6 +7,8 @@ use codex_protocol::mcp_protocol::NewConversationParams;
 use codex_protocol::mcp_protocol::NewConversationResponse;
 use codex_protocol::mcp_protocol::SendUserMessageParams;
 use codex_protocol::mcp_protocol::SendUserMessageResponse;
+use codex_protocol::protocol::PROTOCOL_VERSION;
+use codex_protocol::protocol::ProtocolCapability;
 use mcp_test_support::McpProcess;
 use mcp_test_support::create_final_assistant_message_sse_response;
 use mcp_test_support::create_mock_chat_completions_server;
@@ -45,6 +47,8 @@ async fn test_conversation_create_and_send_message_ok() {
     let new_conv_id = mcp
         .send_new_conversation_request(NewConversationParams {
             model: Some("o3".to_string()),
+            protocol_version: Some(PROTOCOL_VERSION),
+            capabilities: Some(vec![ProtocolCapability::TokenUsageBreakdown]),
             ..Default::default()
         })
         .await
@@ -59,13 +63,20 @@ async fn test_conversation_create_and_send_message_ok() {
     let NewConversationResponse {
         conversation_id,
         model,
+        protocol_version,
+        capabilities,
     } = to_response::<NewConversationResponse>(new_conv_resp)
         .expect("deserialize newConversation response");
     assert_eq!(model, "o3");
+    assert_eq!(protocol_version, PROTOCOL_VERSION);
+    assert_eq!(capabilities, vec![ProtocolCapability::TokenUsageBreakdown]);
 
     // Add a listener so we receive notifications for this conversation (not strictly required for this test).
     let add_listener_id = mcp
-        .send_add_conversation_listener_request(AddConversationListenerParams { conversation_id })
+        .send_add_conversation_listener_request(AddConversationListenerParams {
+            conversation_id,
+            broadcast: false,
+        })
         .await
         .expect("send addConversationListener");
     let _sub: AddConversationSubscriptionResponse =
@@ -0,0 +1,313 @@
+use std::process::Stdio;
+
+use assert_cmd::prelude::*;
+use futures::SinkExt;
+use futures::StreamExt;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::BufReader;
+use tokio::process::Child;
+use tokio::process::Command;
+use tokio::time::timeout;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::tungstenite::client::IntoClientRequest;
+
+const DEFAULT_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+const AUTH_TOKEN: &str = "test-token";
+
+#[tokio::test]
+async fn test_http_rejects_missing_bearer_token() {
+    let codex_home = TempDir::new().expect("create temp dir");
+    let (_server, base_url) = spawn_listening_server(&codex_home).await;
+
+    let response = reqwest::Client::new()
+        .post(format!("{base_url}/mcp"))
+        .json(&initialize_request(0))
+        .send()
+        .await
+        .expect("send initialize");
+    assert_eq!(response.status(), reqwest::StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn test_streamable_http_session() {
+    let codex_home = TempDir::new().expect("create temp dir");
+    let (_server, base_url) = spawn_listening_server(&codex_home).await;
+    let client = reqwest::Client::new();
+    let url = format!("{base_url}/mcp");
+
+    // A request without a session must start one.
+    let response = client
+        .post(&url)
+        .bearer_auth(AUTH_TOKEN)
+        .json(&json!({ "jsonrpc": "2.0", "id": 0, "method": "tools/list" }))
+        .send()
+        .await
+        .expect("send tools/list");
+    assert_eq!(response.status(), reqwest::StatusCode::BAD_REQUEST);
+
+    let response = client
+        .post(&url)
+        .bearer_auth(AUTH_TOKEN)
+        .json(&initialize_request(1))
+        .send()
+        .await
+        .expect("send initialize");
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+    let session_id = response
+        .headers()
+        .get("mcp-session-id")
+        .and_then(|value| value.to_str().ok())
+        .expect("Mcp-Session-Id header")
+        .to_string();
+    let messages = read_sse_messages(response).await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0]["id"], json!(1));
+    assert_eq!(
+        messages[0]["result"]["serverInfo"]["name"],
+        json!("codex-mcp-server")
+    );
+
+    let response = client
+        .post(&url)
+        .bearer_auth(AUTH_TOKEN)
+        .header("mcp-session-id", &session_id)
+        .json(&json!({ "jsonrpc": "2.0", "method": "notifications/initialized" }))
+        .send()
+        .await
+        .expect("send notifications/initialized");
+    assert_eq!(response.status(), reqwest::StatusCode::ACCEPTED);
+
+    let response = client
+        .post(&url)
+        .bearer_auth(AUTH_TOKEN)
+        .header("mcp-session-id", &session_id)
+        .json(&json!({ "jsonrpc": "2.0", "id": 2, "method": "tools/list" }))
+        .send()
+        .await
+        .expect("send tools/list");
+    let messages = read_sse_messages(response).await;
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0]["id"], json!(2));
+    let tool_names: Vec<&str> = messages[0]["result"]["tools"]
+        .as_array()
+        .expect("tools array")
+        .iter()
+        .filter_map(|tool| tool["name"].as_str())
+        .collect();
+    assert!(tool_names.contains(&"codex"), "tools: {tool_names:?}");
+
+    let response = client
+        .delete(&url)
+        .bearer_auth(AUTH_TOKEN)
+        .header("mcp-session-id", &session_id)
+        .send()
+        .await
+        .expect("send DELETE");
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+
+    let response = client
+        .post(&url)
+        .bearer_auth(AUTH_TOKEN)
+        .header("mcp-session-id", &session_id)
+        .json(&json!({ "jsonrpc": "2.0", "id": 3, "method": "tools/list" }))
+        .send()
+        .await
+        .expect("send tools/list");
+    assert_eq!(response.status(), reqwest::StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn test_websocket_initialize() {
+    let codex_home = TempDir::new().expect("create temp dir");
+    let (_server, base_url) = spawn_listening_server(&codex_home).await;
+
+    let mut request = format!("{}/ws", base_url.replacen("http", "ws", 1))
+        .into_client_request()
+        .expect("build WebSocket request");
+    request.headers_mut().insert(
+        "Authorization",
+        format!("Bearer {AUTH_TOKEN}")
+            .parse()
+            .expect("header value"),
+    );
+    let (mut socket, _) = tokio_tungstenite::connect_async(request)
+        .await
+        .expect("connect WebSocket");
+
+    socket
+        .send(Message::text(initialize_request(7).to_string()))
+        .await
+        .expect("send initialize");
+    let frame = timeout(DEFAULT_READ_TIMEOUT, socket.next())
+        .await
+        .expect("initialize response timeout")
+        .expect("WebSocket closed")
+        .expect("WebSocket error");
+    let Message::Text(text) = frame else {
+        panic!("expected a text frame, got {frame:?}");
+    };
+    let response: Value = serde_json::from_str(text.as_str()).expect("parse response");
+    assert_eq!(response["id"], json!(7));
+    assert_eq!(
+        response["result"]["serverInfo"]["name"],
+        json!("codex-mcp-server")
+    );
+}
+
+#[tokio::test]
+async fn test_http_rejects_foreign_origin() {
+    let codex_home = TempDir::new().expect("create temp dir");
+    let (_server, base_url) = spawn_listening_server(&codex_home).await;
+    let client = reqwest::Client::new();
+
+    let response = client
+        .post(format!("{base_url}/mcp"))
+        .bearer_auth(AUTH_TOKEN)
+        .header("Origin", "https://evil.example")
+        .json(&initialize_request(0))
+        .send()
+        .await
+        .expect("send initialize");
+    assert_eq!(response.status(), reqwest::StatusCode::FORBIDDEN);
+
+    let response = client
+        .post(format!("{base_url}/mcp"))
+        .bearer_auth(AUTH_TOKEN)
+        .header("Origin", "http://localhost:5173")
+        .json(&initialize_request(1))
+        .send()
+        .await
+        .expect("send initialize");
+    assert_eq!(response.status(), reqwest::StatusCode::OK);
+}
+
+#[tokio::test]
+async fn test_websocket_rejects_foreign_origin() {
+    let codex_home = TempDir::new().expect("create temp dir");
+    let (_server, base_url) = spawn_listening_server(&codex_home).await;
+
+    let mut request = format!("{}/ws", base_url.replacen("http", "ws", 1))
+        .into_client_request()
+        .expect("build WebSocket request");
+    request.headers_mut().insert(
+        "Authorization",
+        format!("Bearer {AUTH_TOKEN}")
+            .parse()
+            .expect("header value"),
+    );
+    request.headers_mut().insert(
+        "Origin",
+        "https://evil.example".parse().expect("header value"),
+    );
+    let err = tokio_tungstenite::connect_async(request)
+        .await
+        .expect_err("handshake from a foreign origin should fail");
+    let tokio_tungstenite::tungstenite::Error::Http(response) = err else {
+        panic!("expected an HTTP error, got {err:?}");
+    };
+    assert_eq!(response.status(), 403);
+}
+
+#[tokio::test]
+async fn test_listen_on_non_loopback_address_requires_token() {
+    let codex_home = TempDir::new().expect("create temp dir");
+    let program = std::process::Command::cargo_bin("codex-mcp-server")
+        .expect("should find binary for codex-mcp-server")
+        .get_program()
+        .to_owned();
+    let output = Command::new(program)
+        .args(["--listen", "0.0.0.0:0"])
+        .env("CODEX_HOME", codex_home.path())
+        .env_remove("CODEX_MCP_AUTH_TOKEN")
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    let output = timeout(DEFAULT_READ_TIMEOUT, output)
+        .await
+        .expect("codex-mcp-server should exit")
+        .expect("run codex-mcp-server");
+
+    assert!(!output.status.success());
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(
+        stderr
+            .contains("refusing to listen on non-loopback address 0.0.0.0:0 without --auth-token"),
+        "stderr: {stderr}"
+    );
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Starts `codex-mcp-server --listen` on a free port and returns the process
+/// with the base URL it serves.
+#[expect(clippy::expect_used)]
+async fn spawn_listening_server(codex_home: &TempDir) -> (Child, String) {
+    let program = std::process::Command::cargo_bin("codex-mcp-server")
+        .expect("should find binary for codex-mcp-server")
+        .get_program()
+        .to_owned();
+    let mut server = Command::new(program)
+        .args(["--listen", "127.0.0.1:0", "--auth-token", AUTH_TOKEN])
+        .env("CODEX_HOME", codex_home.path())
+        .env_remove("RUST_LOG")
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .expect("codex-mcp-server proc should start");
+
+    let stderr = server.stderr.take().expect("mcp should have stderr fd");
+    let mut lines = BufReader::new(stderr).lines();
+    let base_url = timeout(DEFAULT_READ_TIMEOUT, async {
+        while let Some(line) = lines.next_line().await.expect("read stderr") {
+            if let Some(url) = line.strip_prefix("codex-mcp-server listening on ") {
+                return url.to_string();
+            }
+        }
+        panic!("codex-mcp-server exited before listening");
+    })
+    .await
+    .expect("listen timeout");
+    (server, base_url)
+}
+
+fn initialize_request(id: i64) -> Value {
+    json!({
+        "jsonrpc": "2.0",
+        "id": id,
+        "method": "initialize",
+        "params": {
+            "capabilities": {},
+            "clientInfo": { "name": "http test", "version": "0.0.0" },
+            "protocolVersion": mcp_types::MCP_SCHEMA_VERSION,
+        },
+    })
+}
+
+/// Reads an SSE response to the end and returns the JSON-RPC messages it
+/// carried.
+#[expect(clippy::expect_used)]
+async fn read_sse_messages(response: reqwest::Response) -> Vec<Value> {
+    assert_eq!(
+        response
+            .headers()
+            .get("content-type")
+            .and_then(|value| value.to_str().ok()),
+        Some("text/event-stream")
+    );
+    let body = timeout(DEFAULT_READ_TIMEOUT, response.text())
+        .await
+        .expect("SSE stream timeout")
+        .expect("read SSE stream");
+    body.lines()
+        .filter_map(|line| line.strip_prefix("data: "))
+        .map(|data| serde_json::from_str(data).expect("parse SSE data"))
+        .collect()
+}
@@ -5,6 +5,7 @@ use std::path::Path;
 
 use codex_core::protocol::TurnAbortReason;
 use codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR;
+use codex_mcp_server::CodexToolCallParam;
 use codex_protocol::mcp_protocol::AddConversationListenerParams;
 use codex_protocol::mcp_protocol::InterruptConversationParams;
 use codex_protocol::mcp_protocol::InterruptConversationResponse;
@@ -12,6 +13,7 @@ use codex_protocol::mcp_protocol::NewConversationParams;
 use codex_protocol::mcp_protocol::NewConversationResponse;
 use codex_protocol::mcp_protocol::SendUserMessageParams;
 use codex_protocol::mcp_protocol::SendUserMessageResponse;
+use mcp_types::JSONRPCNotification;
 use mcp_types::JSONRPCResponse;
 use mcp_types::RequestId;
 use tempfile::TempDir;
@@ -93,7 +95,10 @@ async fn shell_command_interruption() -> anyhow::Result<()> {
 
     // 2) addConversationListener
     let add_listener_id = mcp
-        .send_add_conversation_listener_request(AddConversationListenerParams { conversation_id })
+        .send_add_conversation_listener_request(AddConversationListenerParams {
+            conversation_id,
+            broadcast: false,
+        })
         .await?;
     let _add_listener_resp: JSONRPCResponse = timeout(
         DEFAULT_READ_TIMEOUT,
@@ -136,10 +141,101 @@ async fn shell_command_interruption() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn test_codex_tool_call_cancellation() {
+    if std::env::var(CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+        println!(
+            "Skipping test because it cannot execute when network is disabled in a Codex sandbox."
+        );
+        return;
+    }
+
+    if let Err(err) = codex_tool_call_cancellation().await {
+        panic!("failure: {err}");
+    }
+}
+
+async fn codex_tool_call_cancellation() -> anyhow::Result<()> {
+    let shell_command = vec!["sleep".to_string(), "10".to_string()];
+
+    let tmp = TempDir::new()?;
+    let codex_home = tmp.path().join("codex_home");
+    std::fs::create_dir(&codex_home)?;
+    let working_directory = tmp.path().join("workdir");
+    std::fs::create_dir(&working_directory)?;
+
+    let server = create_mock_chat_completions_server(vec![create_shell_sse_response(
+        shell_command,
+        Some(&working_directory),
+        Some(10_000),
+        "call_sleep",
+    )?])
+    .await;
+    create_config_toml(&codex_home, server.uri())?;
+
+    let mut mcp = McpProcess::new(&codex_home).await?;
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize()).await??;
+
+    let codex_request_id = mcp
+        .send_codex_tool_call(CodexToolCallParam {
+            prompt: "run a sleep command".to_string(),
+            cwd: Some(working_directory.to_string_lossy().into_owned()),
+            ..Default::default()
+        })
+        .await?;
+    timeout(
+        DEFAULT_READ_TIMEOUT,
+        read_until_codex_event(&mut mcp, "exec_command_begin"),
+    )
+    .await??;
+
+    mcp.send_cancelled_notification(codex_request_id).await?;
+
+    // The turn is aborted; the cancelled call itself gets no response, which
+    // the reader would reject as unexpected.
+    let aborted = timeout(
+        DEFAULT_READ_TIMEOUT,
+        read_until_codex_event(&mut mcp, "turn_aborted"),
+    )
+    .await??;
+    assert_eq!(
+        Some("interrupted"),
+        aborted
+            .params
+            .as_ref()
+            .and_then(|params| params.get("msg"))
+            .and_then(|msg| msg.get("reason"))
+            .and_then(|reason| reason.as_str())
+    );
+
+    Ok(())
+}
+
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
 
+async fn read_until_codex_event(
+    mcp: &mut McpProcess,
+    event_type: &str,
+) -> anyhow::Result<JSONRPCNotification> {
+    loop {
+        let notification = mcp
+            .read_stream_until_notification_message("codex/event")
+            .await?;
+        let matches = notification
+            .params
+            .as_ref()
+            .and_then(|params| params.get("msg"))
+            .and_then(|msg| msg.get("type"))
+            .and_then(|t| t.as_str())
+            == Some(event_type);
+        if matches {
+            return Ok(notification);
+        }
+    }
+}
+
 fn create_config_toml(codex_home: &Path, server_uri: String) -> std::io::Result<()> {
     let config_toml = codex_home.join("config.toml");
     std::fs::write(
@@ -2,7 +2,9 @@
 mod auth;
 mod codex_message_processor_flow;
 mod codex_tool;
+mod conversation_parking;
 mod create_conversation;
+mod http_transport;
 mod interrupt;
 mod login;
 mod send_message;
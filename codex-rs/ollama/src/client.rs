@@ -4,7 +4,12 @@ use futures::stream::BoxStream;
 use serde_json::Value as JsonValue;
 use std::collections::VecDeque;
 use std::io;
+use std::time::Duration;
 
+use crate::models::LocalModel;
+use crate::models::ModelDetails;
+use crate::models::local_models_from_value;
+use crate::models::model_details_from_value;
 use crate::parser::pull_events_from_value;
 use crate::pull::PullEvent;
 use crate::pull::PullProgressReporter;
@@ -17,6 +22,27 @@ use codex_core::config::Config;
 
 const OLLAMA_CONNECTION_ERROR: &str = "No running Ollama server detected. Start it with: `ollama serve` (after installing). Install instructions: https://github.com/ollama/ollama?tab=readme-ov-file#ollama";
 
+/// Attempts at pulling a model, including the first, before giving up.
+pub const MAX_PULL_ATTEMPTS: u32 = 5;
+
+/// Wait before resuming an interrupted pull, multiplied by the attempt.
+const PULL_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Whether `model` is among the `installed` model names. Ollama lists models
+/// pulled without a tag under `:latest`.
+fn is_installed(installed: &[String], model: &str) -> bool {
+    installed
+        .iter()
+        .any(|name| name == model || (!model.contains(':') && *name == format!("{model}:latest")))
+}
+
+fn model_not_found(model: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("model {model} is not installed"),
+    )
+}
+
 /// Client for interacting with a local Ollama instance.
 pub struct OllamaClient {
     client: reqwest::Client,
@@ -52,7 +78,14 @@ impl OllamaClient {
     }
 
     /// Build a client from a provider definition and verify the server is reachable.
-    async fn try_from_provider(provider: &ModelProviderInfo) -> io::Result<Self> {
+    pub(crate) async fn try_from_provider(provider: &ModelProviderInfo) -> io::Result<Self> {
+        let client = Self::from_provider(provider);
+        client.probe_server().await?;
+        Ok(client)
+    }
+
+    /// Build a client from a provider definition without contacting the server.
+    pub(crate) fn from_provider(provider: &ModelProviderInfo) -> Self {
         #![expect(clippy::expect_used)]
         let base_url = provider
             .base_url
@@ -66,17 +99,20 @@ impl OllamaClient {
             .connect_timeout(std::time::Duration::from_secs(5))
             .build()
             .unwrap_or_else(|_| reqwest::Client::new());
-        let client = Self {
+        Self {
             client,
             host_root,
             uses_openai_compat,
-        };
-        client.probe_server().await?;
-        Ok(client)
+        }
+    }
+
+    /// Root URL of the server's native API, e.g. `http://localhost:11434`.
+    pub(crate) fn host_root(&self) -> &str {
+        &self.host_root
     }
 
     /// Probe whether the server is reachable by hitting the appropriate health endpoint.
-    async fn probe_server(&self) -> io::Result<()> {
+    pub(crate) async fn probe_server(&self) -> io::Result<()> {
         let url = if self.uses_openai_compat {
             format!("{}/v1/models", self.host_root.trim_end_matches('/'))
         } else {
@@ -100,6 +136,13 @@ impl OllamaClient {
 
     /// Return the list of model names known to the local Ollama instance.
     pub async fn fetch_models(&self) -> io::Result<Vec<String>> {
+        let models = self.list_local_models().await?;
+        Ok(models.into_iter().map(|m| m.name).collect())
+    }
+
+    /// Return the models installed in the local Ollama instance, with their
+    /// size and quantization.
+    pub async fn list_local_models(&self) -> io::Result<Vec<LocalModel>> {
         let tags_url = format!("{}/api/tags", self.host_root.trim_end_matches('/'));
         let resp = self
             .client
@@ -111,17 +154,54 @@ impl OllamaClient {
             return Ok(Vec::new());
         }
         let val = resp.json::<JsonValue>().await.map_err(io::Error::other)?;
-        let names = val
-            .get("models")
-            .and_then(|m| m.as_array())
-            .map(|arr| {
-                arr.iter()
-                    .filter_map(|v| v.get("name").and_then(|n| n.as_str()))
-                    .map(|s| s.to_string())
-                    .collect::<Vec<_>>()
-            })
-            .unwrap_or_default();
-        Ok(names)
+        Ok(local_models_from_value(&val))
+    }
+
+    /// Return the details of `model`, or a `NotFound` error if it is not
+    /// installed.
+    pub async fn show_model(&self, model: &str) -> io::Result<ModelDetails> {
+        let url = format!("{}/api/show", self.host_root.trim_end_matches('/'));
+        let resp = self
+            .client
+            .post(url)
+            .json(&serde_json::json!({"model": model}))
+            .send()
+            .await
+            .map_err(io::Error::other)?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(model_not_found(model));
+        }
+        if !resp.status().is_success() {
+            return Err(io::Error::other(format!(
+                "failed to show model: HTTP {}",
+                resp.status()
+            )));
+        }
+        let val = resp.json::<JsonValue>().await.map_err(io::Error::other)?;
+        Ok(model_details_from_value(&val))
+    }
+
+    /// Delete `model` from the local Ollama instance, or return a `NotFound`
+    /// error if it is not installed.
+    pub async fn delete_model(&self, model: &str) -> io::Result<()> {
+        let url = format!("{}/api/delete", self.host_root.trim_end_matches('/'));
+        let resp = self
+            .client
+            .delete(url)
+            .json(&serde_json::json!({"model": model}))
+            .send()
+            .await
+            .map_err(io::Error::other)?;
+        if resp.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(model_not_found(model));
+        }
+        if !resp.status().is_success() {
+            return Err(io::Error::other(format!(
+                "failed to delete model: HTTP {}",
+                resp.status()
+            )));
+        }
+        Ok(())
     }
 
     /// Start a model pull and emit streaming events. The returned stream ends when
@@ -183,38 +263,130 @@ impl OllamaClient {
         Ok(Box::pin(s))
     }
 
+    /// Pull a model like [`Self::pull_model_stream`], but resume when the
+    /// download is interrupted. Ollama keeps the layers it has partially
+    /// downloaded and checks each one against its sha256 digest, so a new
+    /// pull continues where the previous one stopped. Errors before any
+    /// progress (e.g. an unknown model) end the stream right away; later
+    /// ones are retried up to [`MAX_PULL_ATTEMPTS`] times in total.
+    pub fn pull_model_stream_resumable<'a>(&'a self, model: &'a str) -> BoxStream<'a, PullEvent> {
+        let s = async_stream::stream! {
+            let mut attempt = 1;
+            let mut made_progress = false;
+            loop {
+                let failure = match self.pull_model_stream(model).await {
+                    Ok(mut stream) => {
+                        let mut failure = None;
+                        while let Some(event) = stream.next().await {
+                            match event {
+                                PullEvent::Success => {
+                                    yield PullEvent::Success;
+                                    return;
+                                }
+                                PullEvent::Error(err) if !made_progress => {
+                                    yield PullEvent::Error(err);
+                                    return;
+                                }
+                                PullEvent::Error(err) => {
+                                    failure = Some(err);
+                                    break;
+                                }
+                                PullEvent::ChunkProgress { .. } => {
+                                    made_progress = true;
+                                    yield event;
+                                }
+                                PullEvent::Status(_) => yield event,
+                            }
+                        }
+                        failure.unwrap_or_else(|| "connection closed".to_string())
+                    }
+                    Err(err) => err.to_string(),
+                };
+                if attempt >= MAX_PULL_ATTEMPTS {
+                    yield PullEvent::Error(format!(
+                        "{failure} (gave up after {attempt} attempts)"
+                    ));
+                    return;
+                }
+                yield PullEvent::Status(format!(
+                    "Download of {model} interrupted ({failure}); resuming..."
+                ));
+                tokio::time::sleep(PULL_RETRY_DELAY * attempt).await;
+                attempt += 1;
+            }
+        };
+
+        Box::pin(s)
+    }
+
     /// High-level helper to pull a model and drive a progress reporter.
+    /// Interrupted downloads are resumed, and the model is checked to be
+    /// installed afterwards.
     pub async fn pull_with_reporter(
         &self,
         model: &str,
         reporter: &mut dyn PullProgressReporter,
     ) -> io::Result<()> {
-        reporter.on_event(&PullEvent::Status(format!("Pulling model {model}...")))?;
-        let mut stream = self.pull_model_stream(model).await?;
-        while let Some(event) = stream.next().await {
+        self.pull_many_with_reporter(&[model.to_string()], reporter)
+            .await
+    }
+
+    /// Pull several models at once, with the progress of all of them
+    /// combined in `reporter`. Layers shared between models are counted
+    /// once. Every model is pulled even if another one fails.
+    ///
+    /// Parallelism is per model, not per layer: `/api/pull` has the Ollama
+    /// server fetch (and verify) the layers itself, and there is no endpoint
+    /// for fetching a single layer, so the layers of one model arrive in
+    /// whatever order the server downloads them.
+    pub async fn pull_many_with_reporter(
+        &self,
+        models: &[String],
+        reporter: &mut dyn PullProgressReporter,
+    ) -> io::Result<()> {
+        for model in models {
+            reporter.on_event(&PullEvent::Status(format!("Pulling model {model}...")))?;
+        }
+        let streams = models.iter().map(|model| {
+            self.pull_model_stream_resumable(model)
+                .map(move |event| (model.as_str(), event))
+        });
+        let mut events = futures::stream::select_all(streams);
+
+        let mut failures = Vec::new();
+        let mut pulled = Vec::new();
+        while let Some((model, event)) = events.next().await {
             reporter.on_event(&event)?;
             match event {
-                PullEvent::Success => {
-                    return Ok(());
-                }
-                PullEvent::Error(err) => {
-                    // Empirically, ollama returns a 200 OK response even when
-                    // the output stream includes an error message. Verify with:
-                    //
-                    // `curl -i http://localhost:11434/api/pull -d '{ "model": "foobarbaz" }'`
-                    //
-                    // As such, we have to check the event stream, not the
-                    // HTTP response status, to determine whether to return Err.
-                    return Err(io::Error::other(format!("Pull failed: {err}")));
-                }
-                PullEvent::ChunkProgress { .. } | PullEvent::Status(_) => {
-                    continue;
+                PullEvent::Success => pulled.push(model),
+                // Empirically, ollama returns a 200 OK response even when
+                // the output stream includes an error message. Verify with:
+                //
+                // `curl -i http://localhost:11434/api/pull -d '{ "model": "foobarbaz" }'`
+                //
+                // As such, we have to check the event stream, not the
+                // HTTP response status, to determine whether to return Err.
+                PullEvent::Error(err) => failures.push(format!("{model}: {err}")),
+                PullEvent::ChunkProgress { .. } | PullEvent::Status(_) => {}
+            }
+        }
+
+        if !pulled.is_empty() {
+            let installed = self.fetch_models().await?;
+            for model in pulled {
+                if !is_installed(&installed, model) {
+                    failures.push(format!("{model}: not installed after the pull"));
                 }
             }
         }
-        Err(io::Error::other(
-            "Pull stream ended unexpectedly without success.",
-        ))
+        match failures.as_slice() {
+            [] => Ok(()),
+            [failure] => Err(io::Error::other(format!("Pull failed: {failure}"))),
+            _ => Err(io::Error::other(format!(
+                "Pulls failed: {}",
+                failures.join("; ")
+            ))),
+        }
     }
 
     /// Low-level constructor given a raw host root, e.g. "http://localhost:11434".
@@ -268,6 +440,153 @@ mod tests {
         assert!(models.contains(&"mistral".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_delete_model_reports_missing_model() {
+        if std::env::var(codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+            tracing::info!(
+                "{} is set; skipping test_delete_model_reports_missing_model",
+                codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR
+            );
+            return;
+        }
+
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("DELETE"))
+            .and(wiremock::matchers::path("/api/delete"))
+            .and(wiremock::matchers::body_json(
+                serde_json::json!({"model": "mistral"}),
+            ))
+            .respond_with(wiremock::ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("DELETE"))
+            .and(wiremock::matchers::path("/api/delete"))
+            .respond_with(wiremock::ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let client = OllamaClient::from_host_root(server.uri());
+        client.delete_model("mistral").await.expect("delete model");
+        let err = client
+            .delete_model("llama3.2:3b")
+            .await
+            .expect_err("missing model");
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+    }
+
+    /// Records the events it is given.
+    #[derive(Default)]
+    struct RecordingReporter(Vec<PullEvent>);
+
+    impl PullProgressReporter for RecordingReporter {
+        fn on_event(&mut self, event: &PullEvent) -> io::Result<()> {
+            self.0.push(event.clone());
+            Ok(())
+        }
+    }
+
+    fn ndjson(lines: &[serde_json::Value]) -> String {
+        lines.iter().map(|line| format!("{line}\n")).collect()
+    }
+
+    #[tokio::test]
+    async fn test_pull_resumes_after_interruption() {
+        if std::env::var(codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+            tracing::info!(
+                "{} is set; skipping test_pull_resumes_after_interruption",
+                codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR
+            );
+            return;
+        }
+
+        let server = wiremock::MockServer::start().await;
+        // The first attempt fails part-way through the layer...
+        wiremock::Mock::given(wiremock::matchers::method("POST"))
+            .and(wiremock::matchers::path("/api/pull"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(ndjson(&[
+                serde_json::json!({"status": "pulling abc", "digest": "sha256:abc", "total": 100, "completed": 40}),
+                serde_json::json!({"error": "connection reset by peer"}),
+            ])))
+            .up_to_n_times(1)
+            .mount(&server)
+            .await;
+        // ...and the second one picks up where it stopped.
+        wiremock::Mock::given(wiremock::matchers::method("POST"))
+            .and(wiremock::matchers::path("/api/pull"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_string(ndjson(&[
+                serde_json::json!({"status": "pulling abc", "digest": "sha256:abc", "total": 100, "completed": 100}),
+                serde_json::json!({"status": "verifying sha256 digest"}),
+                serde_json::json!({"status": "success"}),
+            ])))
+            .mount(&server)
+            .await;
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/api/tags"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_raw(
+                serde_json::json!({"models": [{"name": "mistral:latest"}]}).to_string(),
+                "application/json",
+            ))
+            .mount(&server)
+            .await;
+
+        let client = OllamaClient::from_host_root(server.uri());
+        let mut reporter = RecordingReporter::default();
+        client
+            .pull_with_reporter("mistral", &mut reporter)
+            .await
+            .expect("pull should resume and succeed");
+
+        let completed: Vec<Option<u64>> = reporter
+            .0
+            .iter()
+            .filter_map(|event| match event {
+                PullEvent::ChunkProgress { completed, .. } => Some(*completed),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(completed, vec![Some(40), Some(100)]);
+        assert!(
+            reporter
+                .0
+                .iter()
+                .any(|event| matches!(event, PullEvent::Status(s) if s.contains("interrupted")))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_pull_fails_fast_for_unknown_model() {
+        if std::env::var(codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+            tracing::info!(
+                "{} is set; skipping test_pull_fails_fast_for_unknown_model",
+                codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR
+            );
+            return;
+        }
+
+        let server = wiremock::MockServer::start().await;
+        wiremock::Mock::given(wiremock::matchers::method("POST"))
+            .and(wiremock::matchers::path("/api/pull"))
+            .respond_with(
+                wiremock::ResponseTemplate::new(200).set_body_string(ndjson(&[
+                    serde_json::json!({"status": "pulling manifest"}),
+                    serde_json::json!({"error": "pull model manifest: file does not exist"}),
+                ])),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OllamaClient::from_host_root(server.uri());
+        let err = client
+            .pull_with_reporter("foobarbaz", &mut RecordingReporter::default())
+            .await
+            .expect_err("unknown model");
+        assert_eq!(
+            err.to_string(),
+            "Pull failed: foobarbaz: pull model manifest: file does not exist"
+        );
+    }
+
     #[tokio::test]
     async fn test_probe_server_happy_path_openai_compat_and_native() {
         if std::env::var(codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
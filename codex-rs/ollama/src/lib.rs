@@ -1,28 +1,65 @@
 mod client;
+mod models;
+mod openai_compat;
 mod parser;
 mod pull;
+mod server;
 mod url;
 
 pub use client::OllamaClient;
 use codex_core::config::Config;
+use codex_core::config_types::LocalProviderApi;
+pub use models::LocalModel;
+pub use models::ModelDetails;
+pub use openai_compat::OpenAiCompatClient;
+pub use openai_compat::ServedModel;
 pub use pull::CliProgressReporter;
 pub use pull::PullEvent;
 pub use pull::PullProgressReporter;
 pub use pull::TuiProgressReporter;
+pub use server::ManagedOllamaServer;
 
 /// Default OSS model to use when `--oss` is passed without an explicit `-m`.
 pub const DEFAULT_OSS_MODEL: &str = "gpt-oss:20b";
 
-/// Prepare the local OSS environment when `--oss` is selected.
+/// Prepare the local OSS environment when `--oss` is selected, for the
+/// selected provider: the built-in Ollama provider, or one of
+/// `local_providers` (see [`LocalProviderApi`]).
 ///
-/// - Ensures a local Ollama server is reachable.
+/// Returns the Ollama server started for this session, if one had to be
+/// started; keep it alive until the session ends.
+pub async fn ensure_oss_ready(config: &mut Config) -> std::io::Result<Option<ManagedOllamaServer>> {
+    let api = config
+        .local_providers
+        .get(&config.model_provider_id)
+        .map(|provider| provider.api)
+        .unwrap_or(LocalProviderApi::Ollama);
+    match api {
+        LocalProviderApi::Ollama => ensure_ollama_ready(config).await,
+        LocalProviderApi::Openai => openai_compat::ensure_openai_compat_ready(config)
+            .await
+            .map(|()| None),
+    }
+}
+
+/// - Ensures a local Ollama server is reachable, starting `ollama serve` if
+///   none is running and `ollama_autostart` allows it.
 /// - Checks if the model exists locally and pulls it if missing.
-pub async fn ensure_oss_ready(config: &Config) -> std::io::Result<()> {
-    // Only download when the requested model is the default OSS model (or when -m is not provided).
-    let model = config.model.as_ref();
+/// - Fills in the model's context window, tool support and stop sequences
+///   from what Ollama reports about it.
+async fn ensure_ollama_ready(config: &mut Config) -> std::io::Result<Option<ManagedOllamaServer>> {
+    let model = config.model.clone();
+    let model = model.as_str();
 
-    // Verify local Ollama is reachable.
-    let ollama_client = crate::OllamaClient::try_from_oss_provider(config).await?;
+    // Verify local Ollama is reachable, or start it.
+    let ollama_client = crate::OllamaClient::from_provider(&config.model_provider);
+    let server = match ollama_client.probe_server().await {
+        Ok(()) => None,
+        Err(err) if config.ollama_autostart => {
+            Some(server::start_ollama_serve(&ollama_client, err).await?)
+        }
+        Err(err) => return Err(err),
+    };
 
     // If the model is not present locally, pull it.
     match ollama_client.fetch_models().await {
@@ -40,5 +77,77 @@ pub async fn ensure_oss_ready(config: &Config) -> std::io::Result<()> {
         }
     }
 
-    Ok(())
+    match ollama_client.show_model(model).await {
+        Ok(details) => apply_model_details(config, &details),
+        Err(err) => {
+            tracing::warn!("Failed to query details of {model} from Ollama: {err}.");
+        }
+    }
+
+    Ok(server)
+}
+
+/// Configure `config` for a local model described by `details`. Values the
+/// user set explicitly, such as `model_context_window`, are kept.
+fn apply_model_details(config: &mut Config, details: &ModelDetails) {
+    if config.model_context_window.is_none() {
+        config.model_context_window = details.context_window();
+    }
+    if let Some(supports_tools) = details.supports_tools() {
+        config.model_family.supports_tools = supports_tools;
+    }
+    if config.model_family.stop_sequences.is_empty() {
+        config.model_family.stop_sequences = details.stop_sequences();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::config::ConfigOverrides;
+    use codex_core::config::ConfigToml;
+
+    fn details(context_length: u64, capabilities: &[&str], parameters: &str) -> ModelDetails {
+        ModelDetails {
+            family: None,
+            format: None,
+            parameter_size: None,
+            quantization_level: None,
+            parameters: Some(parameters.to_string()),
+            license: None,
+            context_length: Some(context_length),
+            capabilities: capabilities.iter().map(|c| c.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_apply_model_details_keeps_explicit_context_window() {
+        let codex_home = tempfile::tempdir().expect("tempdir");
+        let mut config = Config::load_from_base_config_with_overrides(
+            ConfigToml::default(),
+            ConfigOverrides {
+                model: Some("llama3.2:3b".to_string()),
+                ..Default::default()
+            },
+            codex_home.path().to_path_buf(),
+        )
+        .expect("config");
+        assert_eq!(config.model_context_window, None);
+
+        apply_model_details(
+            &mut config,
+            &details(131_072, &["completion"], "stop \"<|eot_id|>\""),
+        );
+        assert_eq!(config.model_context_window, Some(131_072));
+        assert!(!config.model_family.supports_tools);
+        assert_eq!(
+            config.model_family.stop_sequences,
+            vec!["<|eot_id|>".to_string()]
+        );
+
+        config.model_context_window = Some(8_192);
+        apply_model_details(&mut config, &details(131_072, &["tools"], ""));
+        assert_eq!(config.model_context_window, Some(8_192));
+        assert!(config.model_family.supports_tools);
+    }
 }
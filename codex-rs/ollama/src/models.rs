@@ -0,0 +1,227 @@
+use serde_json::Value as JsonValue;
+
+/// A model installed in the local Ollama instance, as listed by `/api/tags`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LocalModel {
+    pub name: String,
+    /// Size on disk, in bytes.
+    pub size: Option<u64>,
+    /// e.g. "20.9B".
+    pub parameter_size: Option<String>,
+    /// e.g. "MXFP4" or "Q4_K_M".
+    pub quantization_level: Option<String>,
+    pub modified_at: Option<String>,
+}
+
+/// Details of a single model, as reported by `/api/show`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ModelDetails {
+    pub family: Option<String>,
+    pub format: Option<String>,
+    pub parameter_size: Option<String>,
+    pub quantization_level: Option<String>,
+    /// Default parameters from the Modelfile, one `name value` per line.
+    pub parameters: Option<String>,
+    pub license: Option<String>,
+    /// Context length the model was trained with.
+    pub context_length: Option<u64>,
+    /// e.g. "completion", "tools", "thinking". Empty on Ollama versions that
+    /// do not report capabilities.
+    pub capabilities: Vec<String>,
+}
+
+impl ModelDetails {
+    /// Values of the `name` parameter in the Modelfile, unquoted.
+    fn parameter_values(&self, name: &str) -> Vec<String> {
+        self.parameters
+            .iter()
+            .flat_map(|parameters| parameters.lines())
+            .filter_map(|line| line.trim().split_once(char::is_whitespace))
+            .filter(|(key, _)| *key == name)
+            .map(|(_, value)| value.trim().trim_matches('"').to_string())
+            .collect()
+    }
+
+    /// Context window Ollama serves the model with: the Modelfile's
+    /// `num_ctx` if set, else the length the model was trained with.
+    pub fn context_window(&self) -> Option<u64> {
+        self.parameter_values("num_ctx")
+            .first()
+            .and_then(|num_ctx| num_ctx.parse().ok())
+            .or(self.context_length)
+    }
+
+    /// Whether the model can call tools, if Ollama reports capabilities.
+    pub fn supports_tools(&self) -> Option<bool> {
+        if self.capabilities.is_empty() {
+            None
+        } else {
+            Some(self.capabilities.iter().any(|c| c == "tools"))
+        }
+    }
+
+    /// Stop sequences from the Modelfile.
+    pub fn stop_sequences(&self) -> Vec<String> {
+        self.parameter_values("stop")
+    }
+}
+
+fn string_field(value: &JsonValue, key: &str) -> Option<String> {
+    value
+        .get(key)
+        .and_then(|v| v.as_str())
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+}
+
+// Convert the response of `/api/tags` into the list of local models.
+pub(crate) fn local_models_from_value(value: &JsonValue) -> Vec<LocalModel> {
+    value
+        .get("models")
+        .and_then(|m| m.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|model| {
+                    let name = string_field(model, "name")?;
+                    let details = model.get("details").unwrap_or(&JsonValue::Null);
+                    Some(LocalModel {
+                        name,
+                        size: model.get("size").and_then(|s| s.as_u64()),
+                        parameter_size: string_field(details, "parameter_size"),
+                        quantization_level: string_field(details, "quantization_level"),
+                        modified_at: string_field(model, "modified_at"),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+// Convert the response of `/api/show` into the model's details.
+pub(crate) fn model_details_from_value(value: &JsonValue) -> ModelDetails {
+    let details = value.get("details").unwrap_or(&JsonValue::Null);
+    ModelDetails {
+        family: string_field(details, "family"),
+        format: string_field(details, "format"),
+        parameter_size: string_field(details, "parameter_size"),
+        quantization_level: string_field(details, "quantization_level"),
+        parameters: string_field(value, "parameters"),
+        license: string_field(value, "license"),
+        // Keys are prefixed with the architecture, e.g. `llama.context_length`.
+        context_length: value
+            .get("model_info")
+            .and_then(|info| info.as_object())
+            .and_then(|info| {
+                info.iter()
+                    .find(|(key, _)| key.ends_with(".context_length"))
+                    .and_then(|(_, length)| length.as_u64())
+            }),
+        capabilities: value
+            .get("capabilities")
+            .and_then(|c| c.as_array())
+            .map(|arr| {
+                arr.iter()
+                    .filter_map(|c| c.as_str())
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_models_from_tags() {
+        let v: JsonValue = serde_json::json!({
+            "models": [
+                {
+                    "name": "gpt-oss:20b",
+                    "modified_at": "2025-08-05T10:00:00Z",
+                    "size": 13_780_173_839u64,
+                    "details": {"parameter_size": "20.9B", "quantization_level": "MXFP4"}
+                },
+                {"name": "mistral"},
+                {"size": 1}
+            ]
+        });
+        assert_eq!(
+            local_models_from_value(&v),
+            vec![
+                LocalModel {
+                    name: "gpt-oss:20b".to_string(),
+                    size: Some(13_780_173_839),
+                    parameter_size: Some("20.9B".to_string()),
+                    quantization_level: Some("MXFP4".to_string()),
+                    modified_at: Some("2025-08-05T10:00:00Z".to_string()),
+                },
+                LocalModel {
+                    name: "mistral".to_string(),
+                    size: None,
+                    parameter_size: None,
+                    quantization_level: None,
+                    modified_at: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_model_details_from_show() {
+        let v: JsonValue = serde_json::json!({
+            "license": "Apache License",
+            "parameters": "temperature 1",
+            "details": {
+                "format": "gguf",
+                "family": "gptoss",
+                "parameter_size": "20.9B",
+                "quantization_level": "MXFP4"
+            },
+            "model_info": {"general.architecture": "gptoss", "gptoss.context_length": 131072},
+            "capabilities": ["completion", "tools", "thinking"]
+        });
+        let details = model_details_from_value(&v);
+        assert_eq!(
+            details,
+            ModelDetails {
+                family: Some("gptoss".to_string()),
+                format: Some("gguf".to_string()),
+                parameter_size: Some("20.9B".to_string()),
+                quantization_level: Some("MXFP4".to_string()),
+                parameters: Some("temperature 1".to_string()),
+                license: Some("Apache License".to_string()),
+                context_length: Some(131_072),
+                capabilities: vec![
+                    "completion".to_string(),
+                    "tools".to_string(),
+                    "thinking".to_string()
+                ],
+            }
+        );
+        assert_eq!(details.context_window(), Some(131_072));
+        assert_eq!(details.supports_tools(), Some(true));
+        assert!(details.stop_sequences().is_empty());
+    }
+
+    #[test]
+    fn test_model_details_parameters() {
+        let v: JsonValue = serde_json::json!({
+            "parameters": "num_ctx                        8192\nstop                           \"<|start_header_id|>\"\nstop                           \"<|eot_id|>\"",
+            "model_info": {"llama.context_length": 131072},
+            "capabilities": ["completion"]
+        });
+        let details = model_details_from_value(&v);
+        assert_eq!(details.context_window(), Some(8192));
+        assert_eq!(details.supports_tools(), Some(false));
+        assert_eq!(
+            details.stop_sequences(),
+            vec!["<|start_header_id|>".to_string(), "<|eot_id|>".to_string()]
+        );
+
+        let older = model_details_from_value(&serde_json::json!({}));
+        assert_eq!(older.supports_tools(), None);
+        assert_eq!(older.context_window(), None);
+    }
+}
@@ -0,0 +1,215 @@
+use serde_json::Value as JsonValue;
+use std::io;
+
+use codex_core::ModelProviderInfo;
+use codex_core::config::Config;
+use codex_core::model_family::default_model_family;
+use codex_core::model_family::find_family_for_model;
+
+use crate::DEFAULT_OSS_MODEL;
+
+/// A model served by an OpenAI-compatible server, as listed by `/models`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ServedModel {
+    pub id: String,
+    /// Context length the server runs the model with, when it reports one
+    /// (vLLM's `max_model_len`).
+    pub context_window: Option<u64>,
+}
+
+/// Client for a local server that speaks only the OpenAI-compatible API,
+/// such as LM Studio, vLLM or llama.cpp's `llama-server`.
+pub struct OpenAiCompatClient {
+    client: reqwest::Client,
+    base_url: String,
+    name: String,
+}
+
+impl OpenAiCompatClient {
+    /// Build a client for `provider` and verify the server is reachable.
+    pub async fn try_from_provider(provider: &ModelProviderInfo) -> io::Result<Self> {
+        let base_url = provider.base_url.clone().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("provider {} has no base_url", provider.name),
+            )
+        })?;
+        let client = reqwest::Client::builder()
+            .connect_timeout(std::time::Duration::from_secs(5))
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+        let client = Self {
+            client,
+            base_url: base_url.trim_end_matches('/').to_string(),
+            name: provider.name.clone(),
+        };
+        client.fetch_models().await?;
+        Ok(client)
+    }
+
+    /// Return the models the server currently serves.
+    pub async fn fetch_models(&self) -> io::Result<Vec<ServedModel>> {
+        let url = format!("{}/models", self.base_url);
+        let resp = self.client.get(url).send().await.map_err(|err| {
+            tracing::warn!("Failed to connect to {}: {err:?}", self.name);
+            io::Error::other(self.connection_error())
+        })?;
+        if !resp.status().is_success() {
+            tracing::warn!(
+                "Failed to list models at {}: HTTP {}",
+                self.base_url,
+                resp.status()
+            );
+            return Err(io::Error::other(self.connection_error()));
+        }
+        let val = resp.json::<JsonValue>().await.map_err(io::Error::other)?;
+        Ok(served_models_from_value(&val))
+    }
+
+    fn connection_error(&self) -> String {
+        format!(
+            "No OpenAI-compatible server ({}) detected at {}. Start it, e.g. LM Studio's local server, `vllm serve` or `llama-server`, and load a model.",
+            self.name, self.base_url
+        )
+    }
+}
+
+// Convert the response of `/models` into the served models.
+fn served_models_from_value(value: &JsonValue) -> Vec<ServedModel> {
+    value
+        .get("data")
+        .and_then(|d| d.as_array())
+        .map(|arr| {
+            arr.iter()
+                .filter_map(|model| {
+                    Some(ServedModel {
+                        id: model.get("id")?.as_str()?.to_string(),
+                        context_window: model.get("max_model_len").and_then(|l| l.as_u64()),
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// The OpenAI-compatible counterpart of the Ollama bootstrap: checks that
+/// the server is up and serves the model. Such servers cannot download
+/// models, so a missing one is an error, except that the default Ollama
+/// model (asked for by `--oss` without `-m`) falls back to the first model
+/// the server has loaded.
+pub(crate) async fn ensure_openai_compat_ready(config: &mut Config) -> io::Result<()> {
+    let client = OpenAiCompatClient::try_from_provider(&config.model_provider).await?;
+    let models = client.fetch_models().await?;
+
+    let served = match models.iter().find(|m| m.id == config.model) {
+        Some(served) => served,
+        None => match models.first() {
+            Some(first) if config.model == DEFAULT_OSS_MODEL => {
+                tracing::info!(
+                    "{} does not serve {DEFAULT_OSS_MODEL}; using {} instead.",
+                    client.name,
+                    first.id
+                );
+                config.model = first.id.clone();
+                config.model_family = find_family_for_model(&first.id)
+                    .unwrap_or_else(|| default_model_family(&first.id, false));
+                first
+            }
+            _ => {
+                let available = models
+                    .iter()
+                    .map(|m| m.id.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "Model {} is not served by {}. Loaded models: {}. Load it in the server or pick one with -m.",
+                        config.model,
+                        client.name,
+                        if available.is_empty() {
+                            "none"
+                        } else {
+                            &available
+                        }
+                    ),
+                ));
+            }
+        },
+    };
+
+    if config.model_context_window.is_none() {
+        config.model_context_window = served.context_window;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_served_models_from_value() {
+        let v: JsonValue = serde_json::json!({
+            "object": "list",
+            "data": [
+                {"id": "qwen2.5-coder-7b-instruct", "object": "model"},
+                {"id": "meta-llama/Llama-3.1-8B-Instruct", "max_model_len": 32768},
+                {"object": "model"}
+            ]
+        });
+        assert_eq!(
+            served_models_from_value(&v),
+            vec![
+                ServedModel {
+                    id: "qwen2.5-coder-7b-instruct".to_string(),
+                    context_window: None,
+                },
+                ServedModel {
+                    id: "meta-llama/Llama-3.1-8B-Instruct".to_string(),
+                    context_window: Some(32_768),
+                },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_try_from_provider_err_when_server_missing() {
+        if std::env::var(codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+            tracing::info!(
+                "{} set; skipping test_try_from_provider_err_when_server_missing",
+                codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR
+            );
+            return;
+        }
+
+        let server = wiremock::MockServer::start().await;
+        let provider =
+            codex_core::create_oss_provider_with_base_url(&format!("{}/v1", server.uri()));
+        let err = OpenAiCompatClient::try_from_provider(&provider)
+            .await
+            .err()
+            .expect("expected error");
+        assert!(err.to_string().starts_with("No OpenAI-compatible server"));
+
+        wiremock::Mock::given(wiremock::matchers::method("GET"))
+            .and(wiremock::matchers::path("/v1/models"))
+            .respond_with(wiremock::ResponseTemplate::new(200).set_body_raw(
+                serde_json::json!({"data": [{"id": "local-model"}]}).to_string(),
+                "application/json",
+            ))
+            .mount(&server)
+            .await;
+        let client = OpenAiCompatClient::try_from_provider(&provider)
+            .await
+            .expect("client should be created when the server is up");
+        let ids: Vec<String> = client
+            .fetch_models()
+            .await
+            .expect("fetch models")
+            .into_iter()
+            .map(|m| m.id)
+            .collect();
+        assert_eq!(ids, vec!["local-model".to_string()]);
+    }
+}
@@ -0,0 +1,120 @@
+use std::io;
+use std::io::Write;
+use std::process::Stdio;
+use std::time::Duration;
+
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncRead;
+use tokio::io::BufReader;
+use tokio::process::Child;
+use tokio::process::Command;
+
+use crate::OllamaClient;
+
+/// How long to wait for a freshly started server to answer.
+const STARTUP_TIMEOUT: Duration = Duration::from_secs(15);
+const STARTUP_POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// An `ollama serve` process started by Codex because no server was running.
+/// The process is stopped when this is dropped, so keep it alive for as long
+/// as the session uses the server.
+pub struct ManagedOllamaServer {
+    _child: Child,
+}
+
+/// Start `ollama serve` for `client`'s host and wait until it answers.
+/// Only servers on this machine can be started; for other hosts, or when
+/// `ollama` is not installed, `unreachable` (the error from probing the
+/// server) is returned as is.
+pub(crate) async fn start_ollama_serve(
+    client: &OllamaClient,
+    unreachable: io::Error,
+) -> io::Result<ManagedOllamaServer> {
+    let Some(host) = local_host(client.host_root()) else {
+        return Err(unreachable);
+    };
+
+    let mut child = match Command::new("ollama")
+        .arg("serve")
+        .env("OLLAMA_HOST", &host)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Err(unreachable),
+        Err(err) => {
+            return Err(io::Error::other(format!(
+                "failed to start `ollama serve`: {err}"
+            )));
+        }
+    };
+    let _ = writeln!(
+        io::stderr(),
+        "No Ollama server was running; started `ollama serve` on {host} for this session."
+    );
+    if let Some(stdout) = child.stdout.take() {
+        tokio::spawn(forward_logs(stdout));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        tokio::spawn(forward_logs(stderr));
+    }
+
+    let deadline = tokio::time::Instant::now() + STARTUP_TIMEOUT;
+    loop {
+        if client.probe_server().await.is_ok() {
+            return Ok(ManagedOllamaServer { _child: child });
+        }
+        if let Some(status) = child.try_wait()? {
+            return Err(io::Error::other(format!(
+                "`ollama serve` exited with {status}; see the log for its output"
+            )));
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return Err(io::Error::other(format!(
+                "`ollama serve` did not answer within {}s",
+                STARTUP_TIMEOUT.as_secs()
+            )));
+        }
+        tokio::time::sleep(STARTUP_POLL_INTERVAL).await;
+    }
+}
+
+/// Route the server's output to the log rather than the terminal.
+async fn forward_logs(output: impl AsyncRead + Unpin) {
+    let mut lines = BufReader::new(output).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        tracing::debug!(target: "ollama", "{line}");
+    }
+}
+
+/// `host:port` for `OLLAMA_HOST` if `host_root` points at this machine.
+fn local_host(host_root: &str) -> Option<String> {
+    let url = reqwest::Url::parse(host_root).ok()?;
+    let host = url.host_str()?;
+    if !matches!(host, "localhost" | "127.0.0.1" | "[::1]" | "0.0.0.0") {
+        return None;
+    }
+    let port = url.port_or_known_default()?;
+    Some(format!("{host}:{port}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_local_host() {
+        assert_eq!(
+            local_host("http://localhost:11434"),
+            Some("localhost:11434".to_string())
+        );
+        assert_eq!(
+            local_host("http://127.0.0.1:8080"),
+            Some("127.0.0.1:8080".to_string())
+        );
+        assert_eq!(local_host("http://my-ollama.example.com:11434"), None);
+    }
+}
@@ -0,0 +1,205 @@
+type ClientMethod = ClientRequest["method"];
+type ServerMethod = ServerRequest["method"];
+
+/** Params of the client request `M`; requests without params take none. */
+type ParamsOf<M extends ClientMethod> =
+  Extract<ClientRequest, { method: M }> extends { params: infer P } ? [params: P] : [];
+
+/** A conversation event, as delivered to a conversation listener. */
+export type EventNotification = Event & {
+  conversationId: string;
+  subscriptionId: string;
+};
+
+/** Answers a request from the server with the response for its method. */
+export type ServerRequestHandler = (
+  request: ServerRequest,
+) => Promise<ServerResponses[ServerMethod]>;
+
+/** The parts of a Node.js `Readable` the client uses. */
+export interface ReadableLike {
+  on(event: "data", listener: (chunk: { toString(): string }) => void): unknown;
+}
+
+/** The parts of a Node.js `Writable` the client uses. */
+export interface WritableLike {
+  write(chunk: string): unknown;
+}
+
+/** A JSON-RPC error returned by the server. */
+export class CodexRequestError extends Error {
+  constructor(
+    message: string,
+    readonly code: number,
+    readonly data?: unknown,
+  ) {
+    super(message);
+    this.name = "CodexRequestError";
+  }
+}
+
+// JSON numbers, whereas the generated `RequestId` uses `bigint`.
+type JsonRpcId = number | string;
+
+type JsonRpcMessage = {
+  id?: JsonRpcId;
+  method?: string;
+  params?: unknown;
+  result?: unknown;
+  error?: { code: number; message: string; data?: unknown };
+};
+
+type Pending = {
+  resolve: (result: unknown) => void;
+  reject: (error: Error) => void;
+};
+
+const EVENT_METHOD_PREFIX = "codex/event/";
+
+/**
+ * Client for `codex mcp`, speaking newline-delimited JSON-RPC over the
+ * server's stdin and stdout:
+ *
+ *   const proc = spawn("codex", ["mcp"]);
+ *   const client = new CodexClient(proc.stdout, proc.stdin);
+ *   const { conversationId } = await client.request("newConversation", {
+ *     protocolVersion: PROTOCOL_VERSION,
+ *     capabilities: ["token_usage_breakdown"],
+ *   });
+ *
+ * Events of capabilities that were not requested are not sent, so a client
+ * only sees the variants of `EventMsg` it was written for.
+ */
+export class CodexClient {
+  private nextId = 0;
+  private buffered = "";
+  private readonly pending = new Map<JsonRpcId, Pending>();
+  private readonly eventListeners = new Set<(event: EventNotification) => void>();
+  private readonly notificationListeners = new Set<
+    (notification: ServerNotification) => void
+  >();
+  private serverRequestHandler?: ServerRequestHandler;
+
+  constructor(
+    input: ReadableLike,
+    private readonly output: WritableLike,
+  ) {
+    input.on("data", (chunk) => this.receive(chunk.toString()));
+  }
+
+  /** Send a request and resolve with the server's typed response. */
+  request<M extends ClientMethod>(
+    method: M,
+    ...params: ParamsOf<M>
+  ): Promise<ClientResponses[M]> {
+    const id = this.nextId++;
+    return new Promise((resolve, reject) => {
+      this.pending.set(id, {
+        resolve: (result) => resolve(result as ClientResponses[M]),
+        reject,
+      });
+      const [body] = params as unknown[];
+      this.send({ id, method, params: body });
+    });
+  }
+
+  /** Listen to the events of conversations this client listens to. */
+  onEvent(listener: (event: EventNotification) => void): () => void {
+    this.eventListeners.add(listener);
+    return () => this.eventListeners.delete(listener);
+  }
+
+  /** Listen to notifications that are not tied to a conversation listener. */
+  onNotification(
+    listener: (notification: ServerNotification) => void,
+  ): () => void {
+    this.notificationListeners.add(listener);
+    return () => this.notificationListeners.delete(listener);
+  }
+
+  /**
+   * Answer the server's requests, such as approvals. Without a handler they
+   * are rejected with a JSON-RPC error.
+   */
+  handleServerRequests(handler: ServerRequestHandler): void {
+    this.serverRequestHandler = handler;
+  }
+
+  /** Fail the requests still waiting for a response, e.g. once the server exited. */
+  close(reason = "connection closed"): void {
+    for (const { reject } of this.pending.values()) {
+      reject(new Error(reason));
+    }
+    this.pending.clear();
+  }
+
+  private send(message: JsonRpcMessage): void {
+    this.output.write(`${JSON.stringify({ jsonrpc: "2.0", ...message })}\n`);
+  }
+
+  private receive(data: string): void {
+    this.buffered += data;
+    let newline: number;
+    while ((newline = this.buffered.indexOf("\n")) >= 0) {
+      const line = this.buffered.slice(0, newline).trim();
+      this.buffered = this.buffered.slice(newline + 1);
+      if (line) {
+        this.dispatch(JSON.parse(line) as JsonRpcMessage);
+      }
+    }
+  }
+
+  private dispatch(message: JsonRpcMessage): void {
+    if (message.method === undefined) {
+      this.settle(message);
+    } else if (message.id !== undefined) {
+      void this.answer(message.id, message.method, message.params);
+    } else {
+      this.notify(message.method, message.params);
+    }
+  }
+
+  private settle({ id, result, error }: JsonRpcMessage): void {
+    const pending = id === undefined ? undefined : this.pending.get(id);
+    if (id === undefined || pending === undefined) {
+      return;
+    }
+    this.pending.delete(id);
+    if (error) {
+      pending.reject(new CodexRequestError(error.message, error.code, error.data));
+    } else {
+      pending.resolve(result);
+    }
+  }
+
+  private async answer(id: JsonRpcId, method: string, params: unknown): Promise<void> {
+    if (!this.serverRequestHandler) {
+      this.send({ id, error: { code: -32601, message: `unhandled request ${method}` } });
+      return;
+    }
+    try {
+      const request = { id, method, params } as unknown as ServerRequest;
+      const result = await this.serverRequestHandler(request);
+      this.send({ id, result });
+    } catch (err) {
+      this.send({ id, error: { code: -32603, message: String(err) } });
+    }
+  }
+
+  private notify(method: string, params: unknown): void {
+    if (!method.startsWith(EVENT_METHOD_PREFIX) || typeof params !== "object" || !params) {
+      return;
+    }
+    if ("msg" in params) {
+      for (const listener of this.eventListeners) {
+        listener(params as EventNotification);
+      }
+    } else {
+      const type = method.slice(EVENT_METHOD_PREFIX.length);
+      const notification = { type, data: params } as ServerNotification;
+      for (const listener of this.notificationListeners) {
+        listener(notification);
+      }
+    }
+  }
+}
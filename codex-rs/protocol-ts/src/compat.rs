@@ -0,0 +1,357 @@
+//! Compares the JSON Schemas of the protocol against a committed snapshot to
+//! find changes that would break existing clients.
+//!
+//! A change is breaking when a message that was valid before may no longer
+//! be, or may no longer be understood: a type, field or variant was removed,
+//! a field became required, or a field's type changed. New optional fields,
+//! new variants and new types are additions.
+
+use std::collections::BTreeMap;
+use std::collections::BTreeSet;
+use std::fmt;
+
+use serde_json::Map;
+use serde_json::Value;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct CompatReport {
+    pub breaking: Vec<String>,
+    pub additions: Vec<String>,
+}
+
+impl CompatReport {
+    pub fn is_breaking(&self) -> bool {
+        !self.breaking.is_empty()
+    }
+}
+
+impl fmt::Display for CompatReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.breaking {
+            writeln!(f, "- {change}")?;
+        }
+        for change in &self.additions {
+            writeln!(f, "+ {change}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compare the schemas of the root types in `new` (keyed by type name)
+/// against those in `old`.
+pub fn compare_schemas(
+    old: &BTreeMap<String, Value>,
+    new: &BTreeMap<String, Value>,
+) -> CompatReport {
+    let mut report = CompatReport::default();
+    for (name, old_schema) in old {
+        match new.get(name) {
+            Some(new_schema) => compare(name, old_schema, new_schema, &mut report),
+            None => report.breaking.push(format!("{name} was removed")),
+        }
+    }
+    for name in new.keys().filter(|name| !old.contains_key(*name)) {
+        report.additions.push(format!("{name} was added"));
+    }
+    report.breaking.sort();
+    report.breaking.dedup();
+    report.additions.sort();
+    report.additions.dedup();
+    report
+}
+
+fn compare(path: &str, old: &Value, new: &Value, report: &mut CompatReport) {
+    let (Value::Object(old), Value::Object(new)) = (old, new) else {
+        if old != new {
+            report
+                .breaking
+                .push(format!("{path} changed from {old} to {new}"));
+        }
+        return;
+    };
+
+    compare_defs(old, new, report);
+
+    for key in ["$ref", "type", "const", "format"] {
+        if old.get(key) != new.get(key) {
+            report.breaking.push(format!(
+                "{path}: `{key}` changed from {} to {}",
+                show(old.get(key)),
+                show(new.get(key))
+            ));
+        }
+    }
+
+    if let (Some(Value::Array(old_values)), Some(Value::Array(new_values))) =
+        (old.get("enum"), new.get("enum"))
+    {
+        for value in old_values.iter().filter(|v| !new_values.contains(v)) {
+            report
+                .breaking
+                .push(format!("{path}: value {value} was removed"));
+        }
+        for value in new_values.iter().filter(|v| !old_values.contains(v)) {
+            report
+                .additions
+                .push(format!("{path}: value {value} was added"));
+        }
+    }
+
+    compare_properties(path, old, new, report);
+
+    for key in ["items", "additionalProperties"] {
+        if let (Some(old_items), Some(new_items)) = (old.get(key), new.get(key)) {
+            compare(&format!("{path}[]"), old_items, new_items, report);
+        }
+    }
+
+    for key in ["oneOf", "anyOf", "allOf"] {
+        if let (Some(Value::Array(old_variants)), Some(Value::Array(new_variants))) =
+            (old.get(key), new.get(key))
+        {
+            compare_variants(path, old_variants, new_variants, report);
+        }
+    }
+}
+
+/// Definitions referenced through `$ref` are compared by name, so a change
+/// to a shared type is reported once.
+fn compare_defs(old: &Map<String, Value>, new: &Map<String, Value>, report: &mut CompatReport) {
+    let (Some(Value::Object(old_defs)), Some(Value::Object(new_defs))) =
+        (old.get("$defs"), new.get("$defs"))
+    else {
+        return;
+    };
+    for (name, old_def) in old_defs {
+        match new_defs.get(name) {
+            Some(new_def) => compare(name, old_def, new_def, report),
+            None => report.breaking.push(format!("{name} was removed")),
+        }
+    }
+    for name in new_defs.keys().filter(|name| !old_defs.contains_key(*name)) {
+        report.additions.push(format!("{name} was added"));
+    }
+}
+
+fn compare_properties(
+    path: &str,
+    old: &Map<String, Value>,
+    new: &Map<String, Value>,
+    report: &mut CompatReport,
+) {
+    let empty = Map::new();
+    let old_properties = old
+        .get("properties")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    let new_properties = new
+        .get("properties")
+        .and_then(Value::as_object)
+        .unwrap_or(&empty);
+    let old_required = required(old);
+    let new_required = required(new);
+
+    for (name, old_property) in old_properties {
+        let field = format!("{path}.{name}");
+        match new_properties.get(name) {
+            Some(new_property) => {
+                if new_required.contains(name.as_str()) && !old_required.contains(name.as_str()) {
+                    report.breaking.push(format!("{field} became required"));
+                }
+                compare(&field, old_property, new_property, report);
+            }
+            None => report.breaking.push(format!("{field} was removed")),
+        }
+    }
+    for name in new_properties.keys() {
+        if old_properties.contains_key(name) {
+            continue;
+        }
+        let field = format!("{path}.{name}");
+        if new_required.contains(name.as_str()) {
+            report
+                .breaking
+                .push(format!("{field} was added as a required field"));
+        } else {
+            report.additions.push(format!("{field} was added"));
+        }
+    }
+}
+
+fn required(schema: &Map<String, Value>) -> BTreeSet<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|required| required.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// Variants of an enum are matched by their tag, e.g. `type=exec_command_end`,
+/// so that reordering them or adding one in the middle is not a change.
+fn compare_variants(path: &str, old: &[Value], new: &[Value], report: &mut CompatReport) {
+    let new_by_key: BTreeMap<String, &Value> = new
+        .iter()
+        .enumerate()
+        .map(|(i, variant)| (variant_key(variant, i), variant))
+        .collect();
+    let old_keys: BTreeSet<String> = old
+        .iter()
+        .enumerate()
+        .map(|(i, variant)| variant_key(variant, i))
+        .collect();
+
+    for (i, old_variant) in old.iter().enumerate() {
+        let key = variant_key(old_variant, i);
+        let variant_path = format!("{path}({key})");
+        match new_by_key.get(&key) {
+            Some(new_variant) => compare(&variant_path, old_variant, new_variant, report),
+            None => report
+                .breaking
+                .push(format!("{variant_path} variant was removed")),
+        }
+    }
+    for key in new_by_key.keys().filter(|key| !old_keys.contains(*key)) {
+        report
+            .additions
+            .push(format!("{path}({key}) variant was added"));
+    }
+}
+
+fn variant_key(variant: &Value, index: usize) -> String {
+    if let Some(value) = single_value(variant) {
+        return value.to_string();
+    }
+    if let Some(properties) = variant.get("properties").and_then(Value::as_object) {
+        for (name, property) in properties {
+            if let Some(value) = single_value(property) {
+                return format!("{name}={value}");
+            }
+        }
+        // Externally tagged variants are objects with a single property.
+        if properties.len() == 1
+            && let Some(name) = properties.keys().next()
+        {
+            return name.clone();
+        }
+    }
+    if let Some(Value::String(reference)) = variant.get("$ref") {
+        return reference.clone();
+    }
+    if let Some(Value::String(ty)) = variant.get("type") {
+        return ty.clone();
+    }
+    format!("#{index}")
+}
+
+/// The only value `schema` accepts, e.g. the tag of an enum variant.
+fn single_value(schema: &Value) -> Option<&Value> {
+    schema.get("const").or_else(|| match schema.get("enum") {
+        Some(Value::Array(values)) if values.len() == 1 => values.first(),
+        _ => None,
+    })
+}
+
+fn show(value: Option<&Value>) -> String {
+    value.map_or_else(|| "nothing".to_string(), Value::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn schemas(schema: Value) -> BTreeMap<String, Value> {
+        BTreeMap::from([("Event".to_string(), schema)])
+    }
+
+    #[test]
+    fn additions_are_not_breaking() {
+        let old = schemas(json!({
+            "type": "object",
+            "properties": {"id": {"type": "string"}},
+            "required": ["id"],
+            "oneOf": [
+                {"properties": {"type": {"const": "started"}}},
+            ],
+        }));
+        let new = schemas(json!({
+            "type": "object",
+            "properties": {"id": {"type": "string"}, "note": {"type": "string"}},
+            "required": ["id"],
+            "oneOf": [
+                {"properties": {"type": {"const": "error"}}},
+                {"properties": {"type": {"const": "started"}}},
+            ],
+        }));
+
+        let report = compare_schemas(&old, &new);
+        assert_eq!(
+            report,
+            CompatReport {
+                breaking: Vec::new(),
+                additions: vec![
+                    "Event(type=\"error\") variant was added".to_string(),
+                    "Event.note was added".to_string(),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn removals_and_type_changes_are_breaking() {
+        let old = schemas(json!({
+            "$defs": {
+                "Duration": {"type": "object", "properties": {"secs": {"type": "integer"}}},
+            },
+            "properties": {
+                "id": {"type": "string"},
+                "call_id": {"type": "string"},
+                "kind": {"enum": ["auth", "other"]},
+            },
+            "required": ["id"],
+        }));
+        let new = schemas(json!({
+            "$defs": {},
+            "properties": {
+                "id": {"type": "integer"},
+                "kind": {"enum": ["other"]},
+                "token": {"type": "string"},
+            },
+            "required": ["id", "token"],
+        }));
+
+        let report = compare_schemas(&old, &new);
+        assert_eq!(
+            report.breaking,
+            vec![
+                "Duration was removed".to_string(),
+                "Event.call_id was removed".to_string(),
+                "Event.id: `type` changed from \"string\" to \"integer\"".to_string(),
+                "Event.kind: value \"auth\" was removed".to_string(),
+                "Event.token was added as a required field".to_string(),
+            ]
+        );
+        assert!(report.is_breaking());
+    }
+
+    #[test]
+    fn optional_field_becoming_required_is_breaking() {
+        let old = schemas(json!({
+            "properties": {"id": {"type": "string"}, "note": {"type": "string"}},
+            "required": ["id"],
+        }));
+        let new = schemas(json!({
+            "properties": {"id": {"type": "string"}, "note": {"type": "string"}},
+            "required": ["id", "note"],
+        }));
+
+        let report = compare_schemas(&old, &new);
+        assert_eq!(
+            report,
+            CompatReport {
+                breaking: vec!["Event.note became required".to_string()],
+                additions: Vec::new(),
+            }
+        );
+    }
+}
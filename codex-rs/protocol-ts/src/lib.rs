@@ -1,6 +1,12 @@
+mod compat;
+
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
+use codex_protocol::protocol::PROTOCOL_VERSION;
+use schemars::JsonSchema;
+use schemars::generate::SchemaSettings;
+use std::collections::BTreeMap;
 use std::ffi::OsStr;
 use std::fs;
 use std::io::Read;
@@ -10,45 +16,99 @@ use std::path::PathBuf;
 use std::process::Command;
 use ts_rs::TS;
 
+pub use compat::CompatReport;
+
 const HEADER: &str = "// GENERATED CODE! DO NOT MODIFY BY HAND!\n\n";
 
+/// Body of the generated `client.ts`, after its imports and response maps.
+const CLIENT_TEMPLATE: &str = include_str!("client_template.ts");
+
+/// Response type of each `ClientRequest` method.
+const CLIENT_RESPONSES: &[(&str, &str)] = &[
+    ("newConversation", "NewConversationResponse"),
+    ("sendUserMessage", "SendUserMessageResponse"),
+    ("sendUserTurn", "SendUserTurnResponse"),
+    ("interruptConversation", "InterruptConversationResponse"),
+    (
+        "addConversationListener",
+        "AddConversationSubscriptionResponse",
+    ),
+    (
+        "removeConversationListener",
+        "RemoveConversationSubscriptionResponse",
+    ),
+    ("gitDiffToRemote", "GitDiffToRemoteResponse"),
+    ("loginChatGpt", "LoginChatGptResponse"),
+    ("cancelLoginChatGpt", "CancelLoginChatGptResponse"),
+    ("logoutChatGpt", "LogoutChatGptResponse"),
+    ("getAuthStatus", "GetAuthStatusResponse"),
+];
+
+/// Response type of each `ServerRequest` method.
+const SERVER_RESPONSES: &[(&str, &str)] = &[
+    ("applyPatchApproval", "ApplyPatchApprovalResponse"),
+    ("execCommandApproval", "ExecCommandApprovalResponse"),
+];
+
+/// Calls `$callback!(module::Type)` for each root type of the protocol. The
+/// types they reference are exported along with them.
+macro_rules! for_each_protocol_type {
+    ($callback:ident) => {
+        $callback!(mcp_protocol::ConversationId);
+        $callback!(mcp_protocol::InputItem);
+        $callback!(mcp_protocol::ClientRequest);
+        $callback!(mcp_protocol::ServerRequest);
+        $callback!(mcp_protocol::NewConversationParams);
+        $callback!(mcp_protocol::NewConversationResponse);
+        $callback!(mcp_protocol::AddConversationListenerParams);
+        $callback!(mcp_protocol::AddConversationSubscriptionResponse);
+        $callback!(mcp_protocol::RemoveConversationListenerParams);
+        $callback!(mcp_protocol::RemoveConversationSubscriptionResponse);
+        $callback!(mcp_protocol::SendUserMessageParams);
+        $callback!(mcp_protocol::SendUserMessageResponse);
+        $callback!(mcp_protocol::SendUserTurnParams);
+        $callback!(mcp_protocol::SendUserTurnResponse);
+        $callback!(mcp_protocol::InterruptConversationParams);
+        $callback!(mcp_protocol::InterruptConversationResponse);
+        $callback!(mcp_protocol::GitDiffToRemoteParams);
+        $callback!(mcp_protocol::GitDiffToRemoteResponse);
+        $callback!(mcp_protocol::LoginChatGptResponse);
+        $callback!(mcp_protocol::LoginChatGptCompleteNotification);
+        $callback!(mcp_protocol::ConversationParkedNotification);
+        $callback!(mcp_protocol::ConversationExpiredNotification);
+        $callback!(mcp_protocol::CancelLoginChatGptParams);
+        $callback!(mcp_protocol::CancelLoginChatGptResponse);
+        $callback!(mcp_protocol::LogoutChatGptParams);
+        $callback!(mcp_protocol::LogoutChatGptResponse);
+        $callback!(mcp_protocol::GetAuthStatusParams);
+        $callback!(mcp_protocol::GetAuthStatusResponse);
+        $callback!(mcp_protocol::ApplyPatchApprovalParams);
+        $callback!(mcp_protocol::ApplyPatchApprovalResponse);
+        $callback!(mcp_protocol::ExecCommandApprovalParams);
+        $callback!(mcp_protocol::ExecCommandApprovalResponse);
+        $callback!(mcp_protocol::ServerNotification);
+        // Core protocol: the submissions and events of a conversation.
+        $callback!(protocol::Submission);
+        $callback!(protocol::Op);
+        $callback!(protocol::Event);
+        $callback!(protocol::EventMsg);
+    };
+}
+
 pub fn generate_ts(out_dir: &Path, prettier: Option<&Path>) -> Result<()> {
     ensure_dir(out_dir)?;
 
     // Generate TS bindings
-    codex_protocol::mcp_protocol::ConversationId::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::InputItem::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::ClientRequest::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::ServerRequest::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::NewConversationParams::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::NewConversationResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::AddConversationListenerParams::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::AddConversationSubscriptionResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::RemoveConversationListenerParams::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::RemoveConversationSubscriptionResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::SendUserMessageParams::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::SendUserMessageResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::SendUserTurnParams::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::SendUserTurnResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::InterruptConversationParams::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::InterruptConversationResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::GitDiffToRemoteParams::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::GitDiffToRemoteResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::LoginChatGptResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::LoginChatGptCompleteNotification::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::CancelLoginChatGptParams::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::CancelLoginChatGptResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::LogoutChatGptParams::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::LogoutChatGptResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::GetAuthStatusParams::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::GetAuthStatusResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::ApplyPatchApprovalParams::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::ApplyPatchApprovalResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::ExecCommandApprovalParams::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::ExecCommandApprovalResponse::export_all_to(out_dir)?;
-    codex_protocol::mcp_protocol::ServerNotification::export_all_to(out_dir)?;
+    macro_rules! export_ts {
+        ($module:ident :: $name:ident) => {
+            codex_protocol::$module::$name::export_all_to(out_dir)?;
+        };
+    }
+    for_each_protocol_type!(export_ts);
 
+    generate_version_ts(out_dir)?;
     generate_index_ts(out_dir)?;
+    generate_client_ts(out_dir)?;
 
     // Prepend header to each generated .ts file
     let ts_files = ts_files_in(out_dir)?;
@@ -73,6 +133,95 @@ pub fn generate_ts(out_dir: &Path, prettier: Option<&Path>) -> Result<()> {
     Ok(())
 }
 
+/// Write a JSON Schema (draft 2020-12) for each root type of the protocol to
+/// `<Type>.json` in `out_dir`, for clients not written in TypeScript and for
+/// validating messages at runtime. Each schema holds the types it references
+/// under `$defs`.
+pub fn generate_json_schema(out_dir: &Path) -> Result<()> {
+    ensure_dir(out_dir)?;
+    for (name, schema) in json_schemas()? {
+        let path = out_dir.join(format!("{name}.json"));
+        let mut json = serde_json::to_string_pretty(&schema)?;
+        json.push('\n');
+        fs::write(&path, json).with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Compare the protocol against the JSON Schemas in `snapshot_dir`, written
+/// earlier by [`generate_json_schema`]. Fails when a change breaks existing
+/// clients but [`PROTOCOL_VERSION`] was not bumped; the returned report
+/// lists all changes either way.
+pub fn check_json_schema(snapshot_dir: &Path) -> Result<CompatReport> {
+    let mut snapshot = BTreeMap::new();
+    let mut snapshot_version = None;
+    for path in files_with_extension(snapshot_dir, "json").with_context(|| {
+        format!(
+            "No schema snapshot in {}; write one with --json-schema-out",
+            snapshot_dir.display()
+        )
+    })? {
+        let Some(name) = path.file_stem().map(|s| s.to_string_lossy().into_owned()) else {
+            continue;
+        };
+        let content =
+            fs::read(&path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let schema: serde_json::Value = serde_json::from_slice(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))?;
+        if let Some(version) = schema.get(VERSION_KEY).and_then(|v| v.as_u64()) {
+            snapshot_version = Some(version);
+        }
+        snapshot.insert(name, schema);
+    }
+    let Some(snapshot_version) = snapshot_version else {
+        return Err(anyhow!(
+            "No schema snapshot in {}; write one with --json-schema-out",
+            snapshot_dir.display()
+        ));
+    };
+
+    let current: BTreeMap<String, serde_json::Value> = json_schemas()?
+        .into_iter()
+        .map(|(name, schema)| (name.to_string(), schema))
+        .collect();
+    let report = compat::compare_schemas(&snapshot, &current);
+    if report.is_breaking() && u64::from(PROTOCOL_VERSION) <= snapshot_version {
+        return Err(anyhow!(
+            "Breaking protocol changes since the snapshot (version {snapshot_version}):\n{report}\
+             Bump PROTOCOL_VERSION in codex-rs/protocol/src/protocol.rs and update the snapshot."
+        ));
+    }
+    Ok(report)
+}
+
+/// Key under which the generated JSON Schemas record [`PROTOCOL_VERSION`].
+const VERSION_KEY: &str = "x-protocol-version";
+
+fn json_schemas() -> Result<Vec<(&'static str, serde_json::Value)>> {
+    let mut schemas = Vec::new();
+    macro_rules! collect_json_schema {
+        ($module:ident :: $name:ident) => {
+            schemas.push((
+                stringify!($name),
+                json_schema_for::<codex_protocol::$module::$name>()?,
+            ));
+        };
+    }
+    for_each_protocol_type!(collect_json_schema);
+    Ok(schemas)
+}
+
+fn json_schema_for<T: JsonSchema>() -> Result<serde_json::Value> {
+    let schema = SchemaSettings::draft2020_12()
+        .into_generator()
+        .into_root_schema_for::<T>();
+    let mut schema = serde_json::to_value(schema)?;
+    if let Some(object) = schema.as_object_mut() {
+        object.insert(VERSION_KEY.to_string(), PROTOCOL_VERSION.into());
+    }
+    Ok(schema)
+}
+
 fn ensure_dir(dir: &Path) -> Result<()> {
     fs::create_dir_all(dir)
         .with_context(|| format!("Failed to create output directory {}", dir.display()))
@@ -101,13 +250,17 @@ fn prepend_header_if_missing(path: &Path) -> Result<()> {
 }
 
 fn ts_files_in(dir: &Path) -> Result<Vec<PathBuf>> {
+    files_with_extension(dir, "ts")
+}
+
+fn files_with_extension(dir: &Path, extension: &str) -> Result<Vec<PathBuf>> {
     let mut files = Vec::new();
     for entry in
         fs::read_dir(dir).with_context(|| format!("Failed to read dir {}", dir.display()))?
     {
         let entry = entry?;
         let path = entry.path();
-        if path.is_file() && path.extension() == Some(OsStr::new("ts")) {
+        if path.is_file() && path.extension() == Some(OsStr::new(extension)) {
             files.push(path);
         }
     }
@@ -123,7 +276,11 @@ fn generate_index_ts(out_dir: &Path) -> Result<PathBuf> {
         .into_iter()
         .filter_map(|p| {
             let stem = p.file_stem()?.to_string_lossy().into_owned();
-            if stem == "index" { None } else { Some(stem) }
+            if matches!(stem.as_str(), "index" | "client" | "version") {
+                None
+            } else {
+                Some(stem)
+            }
         })
         .collect();
     stems.sort();
@@ -132,6 +289,8 @@ fn generate_index_ts(out_dir: &Path) -> Result<PathBuf> {
     for name in stems {
         entries.push(format!("export type {{ {name} }} from \"./{name}\";\n"));
     }
+    entries.push("export { PROTOCOL_VERSION } from \"./version\";\n".to_string());
+    entries.push("export * from \"./client\";\n".to_string());
 
     let mut content =
         String::with_capacity(HEADER.len() + entries.iter().map(|s| s.len()).sum::<usize>());
@@ -147,3 +306,70 @@ fn generate_index_ts(out_dir: &Path) -> Result<PathBuf> {
         .with_context(|| format!("Failed to write {}", index_path.display()))?;
     Ok(index_path)
 }
+
+/// Generate version.ts, which exports the protocol version the bindings
+/// were generated for.
+fn generate_version_ts(out_dir: &Path) -> Result<PathBuf> {
+    let version_path = out_dir.join("version.ts");
+    let content = format!("{HEADER}export const PROTOCOL_VERSION = {PROTOCOL_VERSION};\n");
+    fs::write(&version_path, content)
+        .with_context(|| format!("Failed to write {}", version_path.display()))?;
+    Ok(version_path)
+}
+
+/// Generate client.ts: a typed JSON-RPC client for `codex mcp` that
+/// correlates responses with requests and dispatches events.
+fn generate_client_ts(out_dir: &Path) -> Result<PathBuf> {
+    let mut imports: Vec<&str> = vec![
+        "ClientRequest",
+        "Event",
+        "ServerNotification",
+        "ServerRequest",
+    ];
+    imports.extend(
+        CLIENT_RESPONSES
+            .iter()
+            .chain(SERVER_RESPONSES)
+            .map(|(_, response)| *response),
+    );
+    imports.sort_unstable();
+    imports.dedup();
+
+    let mut content = String::from(HEADER);
+    for name in imports {
+        content.push_str(&format!("import type {{ {name} }} from \"./{name}\";\n"));
+    }
+    content.push('\n');
+    content.push_str(&response_map("ClientResponses", CLIENT_RESPONSES));
+    content.push_str(&response_map("ServerResponses", SERVER_RESPONSES));
+    content.push_str(CLIENT_TEMPLATE);
+
+    let client_path = out_dir.join("client.ts");
+    fs::write(&client_path, content)
+        .with_context(|| format!("Failed to write {}", client_path.display()))?;
+    Ok(client_path)
+}
+
+fn response_map(name: &str, responses: &[(&str, &str)]) -> String {
+    let mut map = format!("export type {name} = {{\n");
+    for (method, response) in responses {
+        map.push_str(&format!("  {method}: {response};\n"));
+    }
+    map.push_str("};\n\n");
+    map
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn protocol_is_compatible_with_its_own_snapshot() -> Result<()> {
+        let snapshot = tempfile::tempdir()?;
+        generate_json_schema(snapshot.path())?;
+
+        let report = check_json_schema(snapshot.path())?;
+        assert_eq!(report, CompatReport::default());
+        Ok(())
+    }
+}
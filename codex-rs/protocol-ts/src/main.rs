@@ -6,15 +6,40 @@ use std::path::PathBuf;
 #[command(about = "Generate TypeScript bindings for the Codex protocol")]
 struct Args {
     /// Output directory where .ts files will be written
-    #[arg(short = 'o', long = "out", value_name = "DIR")]
-    out_dir: PathBuf,
+    #[arg(
+        short = 'o',
+        long = "out",
+        value_name = "DIR",
+        required_unless_present_any = ["check", "json_schema_out"]
+    )]
+    out_dir: Option<PathBuf>,
 
     /// Optional path to the Prettier executable to format generated files
     #[arg(short = 'p', long = "prettier", value_name = "PRETTIER_BIN")]
     prettier: Option<PathBuf>,
+
+    /// Also write JSON Schemas for the protocol types to this directory
+    #[arg(long = "json-schema-out", value_name = "DIR")]
+    json_schema_out: Option<PathBuf>,
+
+    /// Compare the protocol against the JSON Schema snapshot in this
+    /// directory and fail on breaking changes unless the protocol version
+    /// was bumped
+    #[arg(long = "check", value_name = "SNAPSHOT_DIR")]
+    check: Option<PathBuf>,
 }
 
 fn main() -> Result<()> {
     let args = Args::parse();
-    codex_protocol_ts::generate_ts(&args.out_dir, args.prettier.as_deref())
+    if let Some(snapshot_dir) = &args.check {
+        let report = codex_protocol_ts::check_json_schema(snapshot_dir)?;
+        print!("{report}");
+    }
+    if let Some(out_dir) = &args.out_dir {
+        codex_protocol_ts::generate_ts(out_dir, args.prettier.as_deref())?;
+    }
+    if let Some(json_schema_out) = &args.json_schema_out {
+        codex_protocol_ts::generate_json_schema(json_schema_out)?;
+    }
+    Ok(())
 }
@@ -12,6 +12,7 @@ use std::time::Duration;
 
 use mcp_types::CallToolResult;
 use mcp_types::Tool as McpTool;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
 use serde_bytes::ByteBuf;
@@ -26,8 +27,102 @@ use crate::models::ResponseItem;
 use crate::parse_command::ParsedCommand;
 use crate::plan_tool::UpdatePlanArgs;
 
+/// Version of this protocol, embedded in the generated TypeScript bindings
+/// and JSON Schemas. Bump it with any change that breaks existing clients,
+/// such as removing a field or variant or making a field required;
+/// `codex-protocol-ts --check` fails on such changes otherwise.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Optional families of events. A session only sends the events of the
+/// capabilities negotiated in its [`ProtocolHandshake`], so that clients
+/// written against an older protocol never receive variants they do not know.
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize, JsonSchema, TS,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum ProtocolCapability {
+    /// [`EventMsg::DegradedMode`].
+    DegradedMode,
+    /// [`EventMsg::TokenUsageBreakdown`].
+    TokenUsageBreakdown,
+    /// [`EventMsg::ContextCompacted`].
+    ContextCompacted,
+    /// [`EventMsg::ContainerImagePull`].
+    ContainerImagePull,
+    /// [`EventMsg::BackgroundTaskUpdate`].
+    BackgroundTasks,
+}
+
+impl ProtocolCapability {
+    /// Every capability this build of the protocol supports.
+    pub const ALL: &[ProtocolCapability] = &[
+        ProtocolCapability::DegradedMode,
+        ProtocolCapability::TokenUsageBreakdown,
+        ProtocolCapability::ContextCompacted,
+        ProtocolCapability::ContainerImagePull,
+        ProtocolCapability::BackgroundTasks,
+    ];
+}
+
+/// The protocol version and capabilities a client supports when it starts a
+/// session, and, in [`SessionConfiguredEvent`], the ones the session settled
+/// on.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ProtocolHandshake {
+    pub protocol_version: u32,
+    pub capabilities: Vec<ProtocolCapability>,
+}
+
+impl ProtocolHandshake {
+    /// Everything this build of the protocol supports.
+    pub fn current() -> Self {
+        Self {
+            protocol_version: PROTOCOL_VERSION,
+            capabilities: ProtocolCapability::ALL.to_vec(),
+        }
+    }
+
+    /// What is assumed of a client that does not state a version: version 0
+    /// and none of the optional events.
+    pub fn legacy() -> Self {
+        Self {
+            protocol_version: 0,
+            capabilities: Vec::new(),
+        }
+    }
+
+    /// The lower of the two protocol versions and the capabilities both
+    /// this build and the client support.
+    pub fn negotiate(&self) -> Self {
+        let mut capabilities: Vec<ProtocolCapability> = self
+            .capabilities
+            .iter()
+            .copied()
+            .filter(|capability| ProtocolCapability::ALL.contains(capability))
+            .collect();
+        capabilities.sort();
+        capabilities.dedup();
+        Self {
+            protocol_version: self.protocol_version.min(PROTOCOL_VERSION),
+            capabilities,
+        }
+    }
+
+    /// Whether `msg` may be sent to a client with these capabilities.
+    pub fn allows(&self, msg: &EventMsg) -> bool {
+        msg.required_capability()
+            .is_none_or(|capability| self.capabilities.contains(&capability))
+    }
+}
+
+impl Default for ProtocolHandshake {
+    fn default() -> Self {
+        Self::current()
+    }
+}
+
 /// Submission Queue Entry - requests from user
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct Submission {
     /// Unique id for this Submission to correlate with Events
     pub id: String,
@@ -36,7 +131,7 @@ pub struct Submission {
 }
 
 /// Submission operation
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[allow(clippy::large_enum_variant)]
 #[non_exhaustive]
@@ -110,12 +205,24 @@ pub enum Op {
         summary: Option<ReasoningSummaryConfig>,
     },
 
+    /// Reload the configuration with the profile `name` from `config.toml`
+    /// applied, and use its model, provider, reasoning settings, approval
+    /// policy and sandbox for subsequent turns. The working directory and
+    /// conversation are kept. Reported via `EventMsg::ProfileSwitched`, or an
+    /// error event if the profile does not exist or does not load.
+    SwitchProfile { name: String },
+
     /// Approve a command execution
     ExecApproval {
         /// The id of the submission we are approving
         id: String,
         /// The user's decision in response to the request.
         decision: ReviewDecision,
+        /// With an approving decision, also run commands with this program
+        /// and subcommand (e.g. `"cargo test"`) without asking, but in the
+        /// sandbox, for the rest of the session.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        allow_pattern: Option<String>,
     },
 
     /// Approve a code patch
@@ -124,6 +231,11 @@ pub enum Op {
         id: String,
         /// The user's decision in response to the request.
         decision: ReviewDecision,
+        /// With an approving decision, apply only the changes to these files
+        /// (keys of the request's `changes`) and reject the rest. `None`
+        /// applies the whole patch.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        accepted_files: Option<Vec<PathBuf>>,
     },
 
     /// Append an entry to the persistent cross-session message history.
@@ -146,17 +258,81 @@ pub enum Op {
     /// Reply is delivered via `EventMsg::McpListToolsResponse`.
     ListMcpTools,
 
-    /// Request the agent to summarize the current conversation context.
-    /// The agent will use its existing context (either conversation history or previous response id)
-    /// to generate a summary which will be returned as an AgentMessage event.
+    /// Replace the conversation so far with a summary, keeping the session
+    /// context. Runs before the next request if a task is in progress.
+    /// Reported via `EventMsg::ContextCompacted`.
     Compact,
+
+    /// Run a command on the user's behalf, e.g. to show the agent the output
+    /// the user is looking at, or an edited re-run of a command that failed.
+    /// The command goes through the same approval and sandbox checks as a
+    /// model `shell` call, and its output is recorded in the conversation as
+    /// user-provided context so the model sees it on the next turn. Rejected
+    /// with an error event while a task is running.
+    RunUserCommand {
+        /// Program and arguments, executed directly; wrap a script in
+        /// `bash -lc` to run it through a shell.
+        argv: Vec<String>,
+        /// Working directory to run the command in.
+        cwd: PathBuf,
+    },
+
+    /// Start a long-running command, such as a dev server, in the background.
+    /// Unlike `RunUserCommand` it does not block the conversation: it goes
+    /// through the same approval and sandbox checks, then keeps running while
+    /// the conversation continues. `EventMsg::BackgroundTaskUpdate` is sent
+    /// when it starts and again when it exits.
+    RunBackground {
+        /// Program and arguments, executed directly in the session's working
+        /// directory; wrap a script in `bash -lc` to run it through a shell.
+        command: Vec<String>,
+    },
+
+    /// Request the background tasks started in this session, including ones
+    /// that have exited. Reply is delivered via
+    /// `EventMsg::ListBackgroundTasksResponse`.
+    ListBackgroundTasks,
+
+    /// Kill a running background task and everything it spawned.
+    KillBackgroundTask {
+        /// Id from the task's `BackgroundTaskInfo`.
+        id: u64,
+    },
+
+    /// Restore the files touched during a previous turn to their contents
+    /// from before that turn. Only available when `snapshot_turns` is
+    /// enabled; files changed by later turns are overwritten as well. Reply
+    /// is delivered via `EventMsg::TurnReverted`.
+    RevertTurn {
+        /// Id of the submission that started the turn.
+        turn_id: String,
+    },
+
+    /// Rebuild the repository map for the session's working directory and
+    /// add it to the conversation, superseding the one from session start.
+    /// Completion is reported with a `BackgroundEvent`.
+    RegenerateRepoMap,
+
     /// Request to shut down codex instance.
     Shutdown,
 }
 
 /// Determines the conditions under which the user is consulted to approve
 /// running the command proposed by Codex.
-#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize, Display, TS)]
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    Default,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    Display,
+    JsonSchema,
+    TS,
+)]
 #[serde(rename_all = "kebab-case")]
 #[strum(serialize_all = "kebab-case")]
 pub enum AskForApproval {
@@ -183,7 +359,7 @@ pub enum AskForApproval {
 }
 
 /// Determines execution restrictions for model shell commands.
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Display, TS)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Display, JsonSchema, TS)]
 #[strum(serialize_all = "kebab-case")]
 #[serde(tag = "mode", rename_all = "kebab-case")]
 pub enum SandboxPolicy {
@@ -390,8 +566,11 @@ impl SandboxPolicy {
 
 /// User input
 #[non_exhaustive]
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
 #[serde(tag = "type", rename_all = "snake_case")]
+// `mcp_protocol::InputItem` is exported as `InputItem`.
+#[schemars(rename = "CoreInputItem")]
+#[ts(rename = "CoreInputItem")]
 pub enum InputItem {
     Text {
         text: String,
@@ -409,7 +588,7 @@ pub enum InputItem {
 }
 
 /// Event Queue Entry - events from agent
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct Event {
     /// Submission `id` that this event is correlated with.
     pub id: String,
@@ -418,13 +597,17 @@ pub struct Event {
 }
 
 /// Response event from the agent
-#[derive(Debug, Clone, Deserialize, Serialize, Display)]
+#[derive(Debug, Clone, Deserialize, Serialize, Display, JsonSchema, TS)]
 #[serde(tag = "type", rename_all = "snake_case")]
 #[strum(serialize_all = "snake_case")]
 pub enum EventMsg {
     /// Error while executing a submission
     Error(ErrorEvent),
 
+    /// The session keeps working, but in a degraded state the user should be
+    /// told about. Sent at most once per kind per session.
+    DegradedMode(DegradedModeEvent),
+
     /// Agent has started a task
     TaskStarted,
 
@@ -435,6 +618,14 @@ pub enum EventMsg {
     /// used in the current session.
     TokenCount(TokenUsage),
 
+    /// Estimated attribution of the tokens reported by the preceding
+    /// `TokenCount` event, sent once per model request.
+    TokenUsageBreakdown(TokenUsageBreakdown),
+
+    /// Older turns of the conversation were replaced with a summary, either
+    /// automatically near the context limit or because of `Op::Compact`.
+    ContextCompacted(ContextCompactedEvent),
+
     /// Agent text output message
     AgentMessage(AgentMessageEvent),
 
@@ -458,6 +649,10 @@ pub enum EventMsg {
     /// Ack the client's configure message.
     SessionConfigured(SessionConfiguredEvent),
 
+    /// The session switched to another profile in response to
+    /// `Op::SwitchProfile`.
+    ProfileSwitched(ProfileSwitchedEvent),
+
     McpToolCallBegin(McpToolCallBeginEvent),
 
     McpToolCallEnd(McpToolCallEndEvent),
@@ -472,6 +667,10 @@ pub enum EventMsg {
 
     ExecCommandEnd(ExecCommandEndEvent),
 
+    /// Progress of a container image pull triggered by a command that runs
+    /// under the container execution backend.
+    ContainerImagePull(ContainerImagePullEvent),
+
     ExecApprovalRequest(ExecApprovalRequestEvent),
 
     ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent),
@@ -491,12 +690,21 @@ pub enum EventMsg {
 
     TurnDiff(TurnDiffEvent),
 
+    /// The files touched during a turn were restored by `Op::RevertTurn`.
+    TurnReverted(TurnRevertedEvent),
+
     /// Response to GetHistoryEntryRequest.
     GetHistoryEntryResponse(GetHistoryEntryResponseEvent),
 
     /// List of MCP tools available to the agent.
     McpListToolsResponse(McpListToolsResponseEvent),
 
+    /// A background task started or stopped running.
+    BackgroundTaskUpdate(BackgroundTaskInfo),
+
+    /// List of background tasks started in this session.
+    ListBackgroundTasksResponse(ListBackgroundTasksResponseEvent),
+
     PlanUpdate(UpdatePlanArgs),
 
     TurnAborted(TurnAbortedEvent),
@@ -507,19 +715,94 @@ pub enum EventMsg {
     ConversationHistory(ConversationHistoryResponseEvent),
 }
 
+impl EventMsg {
+    /// The capability a client must have negotiated to receive this event,
+    /// or `None` for the events every client understands.
+    pub fn required_capability(&self) -> Option<ProtocolCapability> {
+        match self {
+            EventMsg::DegradedMode(_) => Some(ProtocolCapability::DegradedMode),
+            EventMsg::TokenUsageBreakdown(_) => Some(ProtocolCapability::TokenUsageBreakdown),
+            EventMsg::ContextCompacted(_) => Some(ProtocolCapability::ContextCompacted),
+            EventMsg::ContainerImagePull(_) => Some(ProtocolCapability::ContainerImagePull),
+            EventMsg::BackgroundTaskUpdate(_) => Some(ProtocolCapability::BackgroundTasks),
+            _ => None,
+        }
+    }
+}
+
 // Individual event payload types matching each `EventMsg` variant.
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ErrorEvent {
     pub message: String,
+    #[serde(default)]
+    pub kind: ErrorKind,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// Broad category of an error, so that frontends can react to it, e.g. by
+/// picking an exit code, without parsing the message.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// Missing or rejected credentials.
+    Auth,
+    /// Invalid configuration.
+    Config,
+    /// The configured sandbox or execution backend cannot be used.
+    SandboxUnavailable,
+    /// A usage limit or budget was reached.
+    BudgetExceeded,
+    /// The model provider returned an error, or its response could not be
+    /// streamed to completion.
+    Model,
+    /// Anything else; the task failed.
+    #[default]
+    Other,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct DegradedModeEvent {
+    pub kind: DegradedModeKind,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum DegradedModeKind {
+    /// The ChatGPT sign-in expires within a day.
+    AuthExpiring,
+    /// Commands cannot be sandboxed on this machine, so those the user
+    /// approves run without a sandbox.
+    SandboxUnavailable,
+    /// `config.toml` has problems that `codex config doctor` reports.
+    ConfigInvalid,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TaskCompleteEvent {
     pub last_agent_message: Option<String>,
+    /// SHA of the checkpoint commit made for this task, when
+    /// `checkpoint_commits` is enabled and the task changed the working tree.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checkpoint_commit: Option<String>,
+    /// Set when the task was cut short by a limit on the run.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub truncated: Option<TruncationReason>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+/// The limit that ended a task early; see `codex exec --max-turns` and
+/// `--max-wall-time`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Display, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum TruncationReason {
+    /// The run made as many model requests as allowed.
+    MaxTurns,
+    /// The run went on for as long as allowed.
+    MaxWallTime,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default, JsonSchema, TS)]
 pub struct TokenUsage {
     pub input_tokens: u64,
     pub cached_input_tokens: Option<u64>,
@@ -541,6 +824,17 @@ impl TokenUsage {
         self.input_tokens.saturating_sub(self.cached_input())
     }
 
+    /// Share of input tokens served from the provider's prompt cache, or
+    /// `None` when the provider did not report cache hits.
+    pub fn cached_input_percent(&self) -> Option<u8> {
+        let cached = self.cached_input_tokens?;
+        if self.input_tokens == 0 {
+            return None;
+        }
+        let percent = (cached as f64 / self.input_tokens as f64) * 100.0;
+        Some(percent.clamp(0.0, 100.0).round() as u8)
+    }
+
     /// Primary count for display as a single absolute value: non-cached input + output.
     pub fn blended_total(&self) -> u64 {
         self.non_cached_input() + self.output_tokens
@@ -583,6 +877,61 @@ impl TokenUsage {
     }
 }
 
+/// Where the tokens of one model request went. Providers only report totals,
+/// so each part is sized by its serialized length and scaled to the reported
+/// input and output counts; the parts add up to `input_tokens + output_tokens`.
+#[derive(Debug, Clone, Deserialize, Serialize, Default, PartialEq, Eq, JsonSchema, TS)]
+pub struct TokenUsageBreakdown {
+    /// Base and user instructions, including the environment context.
+    pub instructions: u64,
+    /// Definitions of the tools offered to the model.
+    pub tool_schemas: u64,
+    /// User and assistant messages.
+    pub messages: u64,
+    /// Reasoning sent back to the model plus reasoning it generated.
+    pub reasoning: u64,
+    /// Calls to each tool and their outputs, sorted by tool name.
+    pub tools: Vec<ToolTokenUsage>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct ToolTokenUsage {
+    pub name: String,
+    pub tokens: u64,
+}
+
+impl TokenUsageBreakdown {
+    pub fn total(&self) -> u64 {
+        self.instructions
+            + self.tool_schemas
+            + self.messages
+            + self.reasoning
+            + self.tools.iter().map(|tool| tool.tokens).sum::<u64>()
+    }
+
+    /// Adds `other` into `self`, merging tools by name.
+    pub fn add(&mut self, other: &TokenUsageBreakdown) {
+        self.instructions += other.instructions;
+        self.tool_schemas += other.tool_schemas;
+        self.messages += other.messages;
+        self.reasoning += other.reasoning;
+        for tool in &other.tools {
+            match self.tools.iter_mut().find(|t| t.name == tool.name) {
+                Some(existing) => existing.tokens += tool.tokens,
+                None => self.tools.push(tool.clone()),
+            }
+        }
+        self.tools.sort_by(|a, b| a.name.cmp(&b.name));
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ContextCompactedEvent {
+    /// Size of the summary that replaced the older turns, as counted by the
+    /// summary model's tokenizer.
+    pub summary_tokens: u64,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct FinalOutput {
     pub token_usage: TokenUsage,
@@ -616,40 +965,40 @@ impl fmt::Display for FinalOutput {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct AgentMessageEvent {
     pub message: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct AgentMessageDeltaEvent {
     pub delta: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct AgentReasoningEvent {
     pub text: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct AgentReasoningRawContentEvent {
     pub text: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct AgentReasoningRawContentDeltaEvent {
     pub delta: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct AgentReasoningSectionBreakEvent {}
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct AgentReasoningDeltaEvent {
     pub delta: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct McpInvocation {
     /// Name of the MCP server as defined in the config.
     pub server: String,
@@ -659,18 +1008,19 @@ pub struct McpInvocation {
     pub arguments: Option<serde_json::Value>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct McpToolCallBeginEvent {
     /// Identifier so this can be paired with the McpToolCallEnd event.
     pub call_id: String,
     pub invocation: McpInvocation,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct McpToolCallEndEvent {
     /// Identifier for the corresponding McpToolCallBegin that finished.
     pub call_id: String,
     pub invocation: McpInvocation,
+    #[ts(type = "{ secs: number, nanos: number }")]
     pub duration: Duration,
     /// Result of the tool call. Note this could be an error.
     pub result: Result<CallToolResult, String>,
@@ -685,7 +1035,7 @@ impl McpToolCallEndEvent {
     }
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct WebSearchBeginEvent {
     pub call_id: String,
     pub query: String,
@@ -693,13 +1043,13 @@ pub struct WebSearchBeginEvent {
 
 /// Response payload for `Op::GetHistory` containing the current session's
 /// in-memory transcript.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ConversationHistoryResponseEvent {
     pub conversation_id: Uuid,
     pub entries: Vec<ResponseItem>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ExecCommandBeginEvent {
     /// Identifier so this can be paired with the ExecCommandEnd event.
     pub call_id: String,
@@ -710,7 +1060,7 @@ pub struct ExecCommandBeginEvent {
     pub parsed_cmd: Vec<ParsedCommand>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ExecCommandEndEvent {
     /// Identifier for the ExecCommandBegin that finished.
     pub call_id: String,
@@ -724,19 +1074,71 @@ pub struct ExecCommandEndEvent {
     /// The command's exit code.
     pub exit_code: i32,
     /// The duration of the command execution.
+    #[ts(type = "{ secs: number, nanos: number }")]
     pub duration: Duration,
     /// Formatted output from the command, as seen by the model.
     pub formatted_output: String,
+    /// Set when the command was stopped because it exceeded a configured
+    /// resource limit rather than exiting on its own.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub killed_reason: Option<ExecKilledReason>,
+    /// Set when the command most likely failed because the sandbox denied it
+    /// something, such as a write outside the writable roots.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub sandbox_denied: bool,
+    /// What the sandbox refused the command, when the sandbox recorded it.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sandbox_denials: Vec<SandboxDenial>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// An operation the sandbox refused a command, as observed by the sandbox
+/// itself rather than guessed from the command's output. Currently only the
+/// Linux sandbox records these.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SandboxDenial {
+    /// A write to a path outside the writable roots.
+    Write { syscall: String, path: PathBuf },
+    /// A network syscall such as `connect`.
+    Network { syscall: String },
+}
+
+impl fmt::Display for SandboxDenial {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SandboxDenial::Write { path, .. } => write!(f, "write to {}", path.display()),
+            SandboxDenial::Network { syscall } => write!(f, "network access ({syscall})"),
+        }
+    }
+}
+
+/// The resource limit that stopped a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, JsonSchema, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecKilledReason {
+    /// Wall-clock timeout.
+    Timeout,
+    /// CPU time limit.
+    CpuTime,
+    /// Output size limit; the captured output is truncated.
+    OutputLimit,
+    /// Memory limit. Reported when the container runtime says the command
+    /// was OOM-killed, or the command failed saying that an allocation
+    /// failed.
+    Memory,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 #[serde(rename_all = "snake_case")]
 pub enum ExecOutputStream {
     Stdout,
     Stderr,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// Output from a running command, sent as it arrives. Small writes are
+/// coalesced, so a chunk may span several writes and need not end on a line
+/// or UTF-8 boundary.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ExecCommandOutputDeltaEvent {
     /// Identifier for the ExecCommandBegin that produced this chunk.
     pub call_id: String,
@@ -744,10 +1146,35 @@ pub struct ExecCommandOutputDeltaEvent {
     pub stream: ExecOutputStream,
     /// Raw bytes from the stream (may not be valid UTF-8).
     #[serde(with = "serde_bytes")]
+    #[schemars(with = "Vec<u8>")]
+    #[ts(type = "Array<number>")]
     pub chunk: ByteBuf,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ContainerImagePullEvent {
+    /// Identifier for the exec call that required the image.
+    pub call_id: String,
+    /// Image reference being pulled.
+    pub image: String,
+    pub status: ContainerImagePullStatus,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ContainerImagePullStatus {
+    Started,
+    /// A line of progress output from the container runtime.
+    Progress {
+        message: String,
+    },
+    Completed,
+    Failed {
+        message: String,
+    },
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ExecApprovalRequestEvent {
     /// Identifier for the associated exec call, if available.
     pub call_id: String,
@@ -758,9 +1185,13 @@ pub struct ExecApprovalRequestEvent {
     /// Optional human-readable reason for the approval (e.g. retry without sandbox).
     #[serde(skip_serializing_if = "Option::is_none")]
     pub reason: Option<String>,
+    /// The `[exec_policy]` rule that requires approval for this command, if
+    /// any, e.g. "exec_policy ask rule `git push`".
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub matched_rule: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct ApplyPatchApprovalRequestEvent {
     /// Responses API call id for the associated patch apply call, if available.
     pub call_id: String,
@@ -773,17 +1204,17 @@ pub struct ApplyPatchApprovalRequestEvent {
     pub grant_root: Option<PathBuf>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct BackgroundEventEvent {
     pub message: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct StreamErrorEvent {
     pub message: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct PatchApplyBeginEvent {
     /// Identifier so this can be paired with the PatchApplyEnd event.
     pub call_id: String,
@@ -793,7 +1224,7 @@ pub struct PatchApplyBeginEvent {
     pub changes: HashMap<PathBuf, FileChange>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct PatchApplyEndEvent {
     /// Identifier for the PatchApplyBegin that finished.
     pub call_id: String,
@@ -805,12 +1236,19 @@ pub struct PatchApplyEndEvent {
     pub success: bool,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TurnDiffEvent {
     pub unified_diff: String,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct TurnRevertedEvent {
+    pub turn_id: String,
+    /// Files that were restored, created again or removed.
+    pub paths: Vec<PathBuf>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct GetHistoryEntryResponseEvent {
     pub offset: usize,
     pub log_id: u64,
@@ -820,13 +1258,41 @@ pub struct GetHistoryEntryResponseEvent {
 }
 
 /// Response payload for `Op::ListMcpTools`.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct McpListToolsResponseEvent {
     /// Fully qualified tool name -> tool definition.
     pub tools: std::collections::HashMap<String, McpTool>,
 }
 
-#[derive(Debug, Default, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum BackgroundTaskStatus {
+    Running,
+    Exited {
+        exit_code: i32,
+    },
+    /// Stopped by `Op::KillBackgroundTask` or because the session ended.
+    Killed,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
+pub struct BackgroundTaskInfo {
+    pub id: u64,
+    pub command: Vec<String>,
+    pub cwd: PathBuf,
+    pub status: BackgroundTaskStatus,
+    /// The most recent output of the task, stdout and stderr interleaved.
+    pub output_tail: String,
+}
+
+/// Response payload for `Op::ListBackgroundTasks`.
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ListBackgroundTasksResponseEvent {
+    /// Tasks in the order they were started.
+    pub tasks: Vec<BackgroundTaskInfo>,
+}
+
+#[derive(Debug, Default, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct SessionConfiguredEvent {
     /// Unique id for this session.
     pub session_id: Uuid,
@@ -839,10 +1305,31 @@ pub struct SessionConfiguredEvent {
 
     /// Current number of entries in the history log.
     pub history_entry_count: usize,
+
+    /// `AGENTS.md` files whose instructions were loaded, from the repository
+    /// root down to `cwd`. Files in subdirectories are loaded later, once the
+    /// agent changes a file below them.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub project_docs: Vec<PathBuf>,
+
+    /// Protocol version and optional capabilities negotiated with the client.
+    /// Events of other capabilities are not sent in this session.
+    #[serde(default = "ProtocolHandshake::legacy")]
+    pub protocol: ProtocolHandshake,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
+pub struct ProfileSwitchedEvent {
+    pub profile: String,
+    pub model: String,
+    pub model_provider_id: String,
+    pub reasoning_effort: ReasoningEffortConfig,
+    pub approval_policy: AskForApproval,
+    pub sandbox_policy: SandboxPolicy,
 }
 
 /// User's decision in response to an ExecApprovalRequest.
-#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, TS)]
+#[derive(Debug, Default, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, JsonSchema, TS)]
 #[serde(rename_all = "snake_case")]
 pub enum ReviewDecision {
     /// User has approved this command and the agent should execute it.
@@ -863,7 +1350,7 @@ pub enum ReviewDecision {
     Abort,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
 #[serde(rename_all = "snake_case")]
 pub enum FileChange {
     Add {
@@ -884,12 +1371,12 @@ pub struct Chunk {
     pub inserted_lines: Vec<String>,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, Deserialize, Serialize, JsonSchema, TS)]
 pub struct TurnAbortedEvent {
     pub reason: TurnAbortReason,
 }
 
-#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, TS)]
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema, TS)]
 #[serde(rename_all = "snake_case")]
 pub enum TurnAbortReason {
     Interrupted,
@@ -912,12 +1399,129 @@ mod tests {
                 model: "codex-mini-latest".to_string(),
                 history_log_id: 0,
                 history_entry_count: 0,
+                project_docs: Vec::new(),
+                protocol: ProtocolHandshake {
+                    protocol_version: 1,
+                    capabilities: vec![ProtocolCapability::TokenUsageBreakdown],
+                },
             }),
         };
         let serialized = serde_json::to_string(&event).unwrap();
         assert_eq!(
             serialized,
-            r#"{"id":"1234","msg":{"type":"session_configured","session_id":"67e55044-10b1-426f-9247-bb680e5fe0c8","model":"codex-mini-latest","history_log_id":0,"history_entry_count":0}}"#
+            r#"{"id":"1234","msg":{"type":"session_configured","session_id":"67e55044-10b1-426f-9247-bb680e5fe0c8","model":"codex-mini-latest","history_log_id":0,"history_entry_count":0,"protocol":{"protocol_version":1,"capabilities":["token_usage_breakdown"]}}}"#
+        );
+    }
+
+    #[test]
+    fn negotiate_keeps_the_lower_version_and_known_capabilities() {
+        let offered = ProtocolHandshake {
+            protocol_version: PROTOCOL_VERSION + 1,
+            capabilities: vec![
+                ProtocolCapability::BackgroundTasks,
+                ProtocolCapability::DegradedMode,
+                ProtocolCapability::BackgroundTasks,
+            ],
+        };
+        let negotiated = offered.negotiate();
+        assert_eq!(negotiated.protocol_version, PROTOCOL_VERSION);
+        assert_eq!(
+            negotiated.capabilities,
+            vec![
+                ProtocolCapability::DegradedMode,
+                ProtocolCapability::BackgroundTasks,
+            ]
+        );
+
+        let degraded = EventMsg::DegradedMode(DegradedModeEvent {
+            kind: DegradedModeKind::AuthExpiring,
+            message: String::new(),
+        });
+        let compacted = EventMsg::ContextCompacted(ContextCompactedEvent { summary_tokens: 0 });
+        assert!(negotiated.allows(&EventMsg::TaskStarted));
+        assert!(negotiated.allows(&degraded));
+        assert!(!negotiated.allows(&compacted));
+        assert!(!ProtocolHandshake::legacy().allows(&degraded));
+    }
+
+    #[test]
+    fn session_configured_without_protocol_is_legacy() {
+        let event: SessionConfiguredEvent = serde_json::from_str(
+            r#"{"session_id":"67e55044-10b1-426f-9247-bb680e5fe0c8","model":"m","history_log_id":0,"history_entry_count":0}"#,
+        )
+        .unwrap();
+        assert_eq!(event.protocol, ProtocolHandshake::legacy());
+    }
+
+    #[test]
+    fn sandbox_denials_explain_what_was_blocked() {
+        let write = SandboxDenial::Write {
+            syscall: "openat".to_string(),
+            path: PathBuf::from("/etc/hosts"),
+        };
+        assert_eq!(write.to_string(), "write to /etc/hosts");
+        assert_eq!(
+            serde_json::to_string(&write).unwrap(),
+            r#"{"kind":"write","syscall":"openat","path":"/etc/hosts"}"#
+        );
+        let network = SandboxDenial::Network {
+            syscall: "connect".to_string(),
+        };
+        assert_eq!(network.to_string(), "network access (connect)");
+    }
+
+    #[test]
+    fn cached_input_percent() {
+        let usage = TokenUsage {
+            input_tokens: 2000,
+            cached_input_tokens: Some(1500),
+            output_tokens: 100,
+            reasoning_output_tokens: None,
+            total_tokens: 2100,
+        };
+        assert_eq!(usage.cached_input_percent(), Some(75));
+
+        let unreported = TokenUsage {
+            cached_input_tokens: None,
+            ..usage.clone()
+        };
+        assert_eq!(unreported.cached_input_percent(), None);
+
+        let empty = TokenUsage::default();
+        assert_eq!(empty.cached_input_percent(), None);
+    }
+
+    #[test]
+    fn token_usage_breakdown_add_merges_tools_by_name() {
+        let tool = |name: &str, tokens| ToolTokenUsage {
+            name: name.to_string(),
+            tokens,
+        };
+        let mut total = TokenUsageBreakdown {
+            instructions: 10,
+            tool_schemas: 5,
+            messages: 3,
+            reasoning: 0,
+            tools: vec![tool("shell", 7)],
+        };
+        total.add(&TokenUsageBreakdown {
+            instructions: 10,
+            tool_schemas: 5,
+            messages: 4,
+            reasoning: 2,
+            tools: vec![tool("update_plan", 1), tool("shell", 3)],
+        });
+
+        assert_eq!(
+            total,
+            TokenUsageBreakdown {
+                instructions: 20,
+                tool_schemas: 10,
+                messages: 7,
+                reasoning: 2,
+                tools: vec![tool("shell", 10), tool("update_plan", 1)],
+            }
         );
+        assert_eq!(total.total(), 50);
     }
 }
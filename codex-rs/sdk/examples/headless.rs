@@ -0,0 +1,63 @@
+//! Run the agent on one prompt in the current directory, printing what it
+//! does as it goes.
+//!
+//! ```text
+//! cargo run -p codex --example headless -- "explain this repository"
+//! ```
+//!
+//! Commands run without asking, in a sandbox that can only write to the
+//! current directory. On Linux, point `CODEX_LINUX_SANDBOX_EXE` at the
+//! `codex-linux-sandbox` binary to enable the sandbox.
+
+use std::io::Write;
+
+use codex::ApprovalMode;
+use codex::Codex;
+use codex::Decision;
+use codex::Event;
+use codex::SandboxMode;
+
+#[tokio::main]
+async fn main() -> codex::Result<()> {
+    let prompt = std::env::args().skip(1).collect::<Vec<_>>().join(" ");
+    if prompt.is_empty() {
+        eprintln!("usage: headless <prompt>");
+        std::process::exit(2);
+    }
+
+    let mut builder = Codex::builder()
+        .approval_mode(ApprovalMode::Never)
+        .sandbox_mode(SandboxMode::WorkspaceWrite);
+    if let Some(exe) = std::env::var_os("CODEX_LINUX_SANDBOX_EXE") {
+        builder = builder.linux_sandbox_exe(exe);
+    }
+    let codex = builder.build()?;
+    let conversation = codex.start_conversation().await?;
+    eprintln!("model: {}  cwd: {}", codex.model(), codex.cwd().display());
+
+    conversation.send(prompt).await?;
+    loop {
+        match conversation.next_event().await? {
+            Event::AgentMessageDelta { delta } => {
+                print!("{delta}");
+                let _ = std::io::stdout().flush();
+            }
+            Event::AgentMessage { .. } => println!(),
+            Event::CommandStarted { command, .. } => eprintln!("$ {}", command.join(" ")),
+            Event::CommandFinished { exit_code, .. } if exit_code != 0 => {
+                eprintln!("  exited with {exit_code}");
+            }
+            Event::ApprovalRequested(request) => {
+                // Not expected with `ApprovalMode::Never`.
+                conversation.respond(&request, Decision::Deny).await?;
+            }
+            Event::Notice { message } => eprintln!("note: {message}"),
+            Event::Error { message } => eprintln!("error: {message}"),
+            Event::TurnComplete { .. } | Event::TurnAborted => break,
+            _ => {}
+        }
+    }
+
+    conversation.shutdown().await?;
+    Ok(())
+}
@@ -0,0 +1,136 @@
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_core::ConversationManager;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use codex_login::AuthManager;
+
+use crate::ApprovalMode;
+use crate::Conversation;
+use crate::Error;
+use crate::Result;
+use crate::SandboxMode;
+
+/// Entry point of the crate: holds the loaded configuration and starts
+/// conversations with it. Create one with [`Codex::builder`].
+pub struct Codex {
+    manager: ConversationManager,
+    config: Config,
+}
+
+impl Codex {
+    pub fn builder() -> CodexBuilder {
+        CodexBuilder::default()
+    }
+
+    /// Start a new conversation. Conversations are independent of each
+    /// other and may run concurrently.
+    pub async fn start_conversation(&self) -> Result<Conversation> {
+        let new_conversation = self.manager.new_conversation(self.config.clone()).await?;
+        Ok(Conversation::new(
+            new_conversation.conversation,
+            new_conversation.conversation_id.to_string(),
+            self.config.model.clone(),
+        ))
+    }
+
+    /// The model new conversations use.
+    pub fn model(&self) -> &str {
+        &self.config.model
+    }
+
+    /// The directory the agent works in.
+    pub fn cwd(&self) -> &Path {
+        &self.config.cwd
+    }
+}
+
+/// Builds a [`Codex`] from `$CODEX_HOME/config.toml` (`~/.codex` by
+/// default), with the settings made here taking precedence over it. Settings
+/// that are not made here come from the config file.
+#[derive(Debug, Default, Clone)]
+pub struct CodexBuilder {
+    overrides: ConfigOverrides,
+}
+
+impl CodexBuilder {
+    /// Directory the agent works in. Defaults to the current directory.
+    pub fn cwd(mut self, cwd: impl Into<PathBuf>) -> Self {
+        self.overrides.cwd = Some(cwd.into());
+        self
+    }
+
+    pub fn model(mut self, model: impl Into<String>) -> Self {
+        self.overrides.model = Some(model.into());
+        self
+    }
+
+    /// Key of an entry in `model_providers`, or a built-in provider such as
+    /// `openai` or `oss`.
+    pub fn model_provider(mut self, provider: impl Into<String>) -> Self {
+        self.overrides.model_provider = Some(provider.into());
+        self
+    }
+
+    /// Name of a profile in the config file to apply.
+    pub fn profile(mut self, profile: impl Into<String>) -> Self {
+        self.overrides.config_profile = Some(profile.into());
+        self
+    }
+
+    pub fn approval_mode(mut self, mode: ApprovalMode) -> Self {
+        self.overrides.approval_policy = Some(mode.into());
+        self
+    }
+
+    pub fn sandbox_mode(mut self, mode: SandboxMode) -> Self {
+        self.overrides.sandbox_mode = Some(mode.into());
+        self
+    }
+
+    /// Replace the built-in instructions given to the model.
+    pub fn base_instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.overrides.base_instructions = Some(instructions.into());
+        self
+    }
+
+    /// Path to the `codex-linux-sandbox` executable, needed for
+    /// [`SandboxMode::ReadOnly`] and [`SandboxMode::WorkspaceWrite`] on Linux.
+    pub fn linux_sandbox_exe(mut self, exe: impl Into<PathBuf>) -> Self {
+        self.overrides.codex_linux_sandbox_exe = Some(exe.into());
+        self
+    }
+
+    /// Let the model keep a plan of its work, reported as it is updated.
+    pub fn include_plan_tool(mut self, include: bool) -> Self {
+        self.overrides.include_plan_tool = Some(include);
+        self
+    }
+
+    /// Give the model a dedicated tool for editing files rather than having
+    /// it edit them through shell commands.
+    pub fn include_apply_patch_tool(mut self, include: bool) -> Self {
+        self.overrides.include_apply_patch_tool = Some(include);
+        self
+    }
+
+    /// Let the model search the web.
+    pub fn web_search(mut self, enable: bool) -> Self {
+        self.overrides.tools_web_search_request = Some(enable);
+        self
+    }
+
+    /// Load the configuration and set up authentication. Credentials are
+    /// those saved by `codex login`, or `OPENAI_API_KEY`.
+    pub fn build(self) -> Result<Codex> {
+        let config =
+            Config::load_with_cli_overrides(Vec::new(), self.overrides).map_err(Error::Config)?;
+        let auth_manager =
+            AuthManager::shared(config.codex_home.clone(), config.preferred_auth_method);
+        Ok(Codex {
+            manager: ConversationManager::new(auth_manager),
+            config,
+        })
+    }
+}
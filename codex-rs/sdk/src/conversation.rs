@@ -0,0 +1,136 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::CodexConversation;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+
+use crate::ApprovalKind;
+use crate::ApprovalRequest;
+use crate::Decision;
+use crate::Error;
+use crate::Event;
+use crate::Result;
+
+/// A conversation with the agent, started with
+/// [`Codex::start_conversation`](crate::Codex::start_conversation).
+///
+/// Input is sent with [`send`](Self::send) and the agent's progress is read
+/// with [`next_event`](Self::next_event) until [`Event::TurnComplete`].
+pub struct Conversation {
+    inner: Arc<CodexConversation>,
+    id: String,
+    model: String,
+}
+
+impl Conversation {
+    pub(crate) fn new(inner: Arc<CodexConversation>, id: String, model: String) -> Self {
+        Self { inner, id, model }
+    }
+
+    /// Unique id of the conversation, also used to name its rollout file.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// The model the conversation is using.
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Start a turn with `text` as the user's message. If a turn is already
+    /// running, the message is added to it.
+    pub async fn send(&self, text: impl Into<String>) -> Result<()> {
+        self.send_with_images(text, Vec::new()).await
+    }
+
+    /// Like [`send`](Self::send), attaching the images at `images`.
+    pub async fn send_with_images(
+        &self,
+        text: impl Into<String>,
+        images: Vec<PathBuf>,
+    ) -> Result<()> {
+        let mut items = vec![InputItem::Text { text: text.into() }];
+        items.extend(
+            images
+                .into_iter()
+                .map(|path| InputItem::LocalImage { path }),
+        );
+        self.inner.submit(Op::UserInput { items }).await?;
+        Ok(())
+    }
+
+    /// Wait for the next event. Internal events that are not part of this
+    /// crate's API are skipped.
+    pub async fn next_event(&self) -> Result<Event> {
+        loop {
+            let event = self.inner.next_event().await?;
+            if let Some(event) = Event::from_core(event) {
+                return Ok(event);
+            }
+        }
+    }
+
+    /// Answer an [`Event::ApprovalRequested`].
+    pub async fn respond(&self, request: &ApprovalRequest, decision: Decision) -> Result<()> {
+        let decision = decision.into();
+        let op = match request.kind {
+            ApprovalKind::Command { .. } => Op::ExecApproval {
+                id: request.id.clone(),
+                decision,
+                allow_pattern: None,
+            },
+            ApprovalKind::Patch { .. } => Op::PatchApproval {
+                id: request.id.clone(),
+                decision,
+                accepted_files: None,
+            },
+        };
+        self.inner.submit(op).await?;
+        Ok(())
+    }
+
+    /// Interrupt the running turn. An [`Event::TurnAborted`] follows.
+    pub async fn interrupt(&self) -> Result<()> {
+        self.inner.submit(Op::Interrupt).await?;
+        Ok(())
+    }
+
+    /// Shut the conversation down. An [`Event::Shutdown`] follows.
+    pub async fn shutdown(&self) -> Result<()> {
+        self.inner.submit(Op::Shutdown).await?;
+        Ok(())
+    }
+
+    /// Run one turn with `prompt` and return the agent's final message.
+    ///
+    /// Every approval request is denied, so configure an [`ApprovalMode`]
+    /// that does not ask, or drive the turn with [`next_event`] to answer
+    /// them yourself.
+    ///
+    /// [`ApprovalMode`]: crate::ApprovalMode
+    /// [`next_event`]: Self::next_event
+    pub async fn run(&self, prompt: impl Into<String>) -> Result<Option<String>> {
+        self.send(prompt).await?;
+        let mut error = None;
+        loop {
+            match self.next_event().await? {
+                Event::ApprovalRequested(request) => {
+                    self.respond(&request, Decision::Deny).await?;
+                }
+                Event::Error { message } => error = Some(message),
+                Event::TurnComplete { last_message } => {
+                    return match error {
+                        Some(message) => Err(Error::Turn(message)),
+                        None => Ok(last_message),
+                    };
+                }
+                Event::TurnAborted => return Err(Error::Turn("turn was aborted".to_string())),
+                Event::Shutdown => {
+                    return Err(Error::Agent("conversation shut down".to_string()));
+                }
+                _ => {}
+            }
+        }
+    }
+}
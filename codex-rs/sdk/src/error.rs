@@ -0,0 +1,29 @@
+use std::io;
+
+use codex_core::error::CodexErr;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// Errors returned by this crate.
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum Error {
+    /// The configuration under `CODEX_HOME` could not be loaded.
+    #[error("failed to load config: {0}")]
+    Config(#[source] io::Error),
+
+    /// The agent failed, e.g. because the model provider could not be
+    /// reached or the conversation has shut down.
+    #[error("{0}")]
+    Agent(String),
+
+    /// The turn ended with an error reported by the agent.
+    #[error("turn failed: {0}")]
+    Turn(String),
+}
+
+impl From<CodexErr> for Error {
+    fn from(err: CodexErr) -> Self {
+        Error::Agent(err.to_string())
+    }
+}
@@ -0,0 +1,218 @@
+//! Events reported by a [`Conversation`](crate::Conversation).
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use codex_core::protocol::Event as CoreEvent;
+use codex_core::protocol::EventMsg;
+
+/// Something that happened in a conversation. New variants may be added in
+/// minor releases, so matches need a wildcard arm.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum Event {
+    /// A chunk of the agent's reply as it is streamed.
+    AgentMessageDelta { delta: String },
+    /// A complete message from the agent.
+    AgentMessage { message: String },
+    /// A summary of the agent's reasoning.
+    Reasoning { text: String },
+    /// The agent started running a command.
+    CommandStarted { command: Vec<String>, cwd: PathBuf },
+    /// A command finished. `output` is stdout and stderr interleaved.
+    CommandFinished {
+        exit_code: i32,
+        output: String,
+        duration: Duration,
+    },
+    /// The agent finished applying a patch.
+    PatchApplied { success: bool },
+    /// The agent is waiting for an answer, given with
+    /// [`Conversation::respond`](crate::Conversation::respond).
+    ApprovalRequested(ApprovalRequest),
+    /// Tokens used so far in the turn.
+    TokenUsage(TokenUsage),
+    /// Informational message, e.g. a retried request or a missing sandbox.
+    Notice { message: String },
+    /// The turn failed. A [`Event::TurnComplete`] follows.
+    Error { message: String },
+    /// The turn ended. `last_message` is the agent's final reply, if any.
+    TurnComplete { last_message: Option<String> },
+    /// The turn was interrupted before it completed.
+    TurnAborted,
+    /// The conversation has shut down; no more events follow.
+    Shutdown,
+}
+
+/// An action the agent wants to take and that needs approval under the
+/// configured [`ApprovalMode`](crate::ApprovalMode).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApprovalRequest {
+    pub(crate) id: String,
+    pub kind: ApprovalKind,
+    /// Why the agent wants to do this, when it said.
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum ApprovalKind {
+    /// Run `command` in `cwd`.
+    Command { command: Vec<String>, cwd: PathBuf },
+    /// Edit, create or delete `paths`.
+    Patch { paths: Vec<PathBuf> },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct TokenUsage {
+    pub input_tokens: u64,
+    pub cached_input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+}
+
+impl Event {
+    /// Convert an event from core, or `None` for events that are not part of
+    /// this crate's API.
+    pub(crate) fn from_core(event: CoreEvent) -> Option<Self> {
+        let CoreEvent { id, msg } = event;
+        let event = match msg {
+            EventMsg::AgentMessageDelta(ev) => Event::AgentMessageDelta { delta: ev.delta },
+            EventMsg::AgentMessage(ev) => Event::AgentMessage {
+                message: ev.message,
+            },
+            EventMsg::AgentReasoning(ev) => Event::Reasoning { text: ev.text },
+            EventMsg::ExecCommandBegin(ev) => Event::CommandStarted {
+                command: ev.command,
+                cwd: ev.cwd,
+            },
+            EventMsg::ExecCommandEnd(ev) => Event::CommandFinished {
+                exit_code: ev.exit_code,
+                output: ev.aggregated_output,
+                duration: ev.duration,
+            },
+            EventMsg::PatchApplyEnd(ev) => Event::PatchApplied {
+                success: ev.success,
+            },
+            EventMsg::ExecApprovalRequest(ev) => Event::ApprovalRequested(ApprovalRequest {
+                id,
+                kind: ApprovalKind::Command {
+                    command: ev.command,
+                    cwd: ev.cwd,
+                },
+                reason: ev.reason,
+            }),
+            EventMsg::ApplyPatchApprovalRequest(ev) => {
+                let mut paths: Vec<PathBuf> = ev.changes.into_keys().collect();
+                paths.sort();
+                Event::ApprovalRequested(ApprovalRequest {
+                    id,
+                    kind: ApprovalKind::Patch { paths },
+                    reason: ev.reason,
+                })
+            }
+            EventMsg::TokenCount(usage) => Event::TokenUsage(TokenUsage {
+                input_tokens: usage.input_tokens,
+                cached_input_tokens: usage.cached_input_tokens.unwrap_or(0),
+                output_tokens: usage.output_tokens,
+                total_tokens: usage.total_tokens,
+            }),
+            EventMsg::BackgroundEvent(ev) => Event::Notice {
+                message: ev.message,
+            },
+            EventMsg::StreamError(ev) => Event::Notice {
+                message: ev.message,
+            },
+            EventMsg::DegradedMode(ev) => Event::Notice {
+                message: ev.message,
+            },
+            EventMsg::Error(ev) => Event::Error {
+                message: ev.message,
+            },
+            EventMsg::TaskComplete(ev) => Event::TurnComplete {
+                last_message: ev.last_agent_message,
+            },
+            EventMsg::TurnAborted(_) => Event::TurnAborted,
+            EventMsg::ShutdownComplete => Event::Shutdown,
+            _ => return None,
+        };
+        Some(event)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::protocol::AgentMessageDeltaEvent;
+    use codex_core::protocol::ApplyPatchApprovalRequestEvent;
+    use codex_core::protocol::FileChange;
+    use codex_core::protocol::TaskCompleteEvent;
+    use pretty_assertions::assert_eq;
+    use std::collections::HashMap;
+
+    fn core_event(msg: EventMsg) -> CoreEvent {
+        CoreEvent {
+            id: "sub-1".to_string(),
+            msg,
+        }
+    }
+
+    #[test]
+    fn converts_agent_output_and_turn_completion() {
+        assert_eq!(
+            Event::from_core(core_event(EventMsg::AgentMessageDelta(
+                AgentMessageDeltaEvent {
+                    delta: "Hel".to_string(),
+                }
+            ))),
+            Some(Event::AgentMessageDelta {
+                delta: "Hel".to_string()
+            })
+        );
+        assert_eq!(
+            Event::from_core(core_event(EventMsg::TaskComplete(TaskCompleteEvent {
+                last_agent_message: Some("Hello".to_string()),
+                checkpoint_commit: None,
+                truncated: None,
+            }))),
+            Some(Event::TurnComplete {
+                last_message: Some("Hello".to_string())
+            })
+        );
+        assert_eq!(Event::from_core(core_event(EventMsg::TaskStarted)), None);
+    }
+
+    #[test]
+    fn patch_approval_lists_paths_in_order() {
+        let changes = HashMap::from([
+            (PathBuf::from("b.txt"), FileChange::Delete),
+            (
+                PathBuf::from("a.txt"),
+                FileChange::Add {
+                    content: "hi\n".to_string(),
+                },
+            ),
+        ]);
+
+        let event = Event::from_core(core_event(EventMsg::ApplyPatchApprovalRequest(
+            ApplyPatchApprovalRequestEvent {
+                call_id: "call-1".to_string(),
+                changes,
+                reason: None,
+                grant_root: None,
+            },
+        )));
+
+        assert_eq!(
+            event,
+            Some(Event::ApprovalRequested(ApprovalRequest {
+                id: "sub-1".to_string(),
+                kind: ApprovalKind::Patch {
+                    paths: vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")],
+                },
+                reason: None,
+            }))
+        );
+    }
+}
@@ -0,0 +1,48 @@
+//! Embed the Codex agent in a Rust application.
+//!
+//! This crate is the stable interface to Codex: the types here follow
+//! semver, while the `codex-core` crate that implements the agent changes
+//! freely. Enums and structs that are likely to grow are `#[non_exhaustive]`.
+//!
+//! ```no_run
+//! use codex::ApprovalMode;
+//! use codex::Codex;
+//! use codex::SandboxMode;
+//!
+//! # async fn example() -> codex::Result<()> {
+//! let codex = Codex::builder()
+//!     .cwd("/path/to/project")
+//!     .approval_mode(ApprovalMode::Never)
+//!     .sandbox_mode(SandboxMode::WorkspaceWrite)
+//!     .build()?;
+//! let conversation = codex.start_conversation().await?;
+//! let reply = conversation.run("Add a README").await?;
+//! println!("{}", reply.unwrap_or_default());
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! See `examples/headless.rs` for a turn driven event by event.
+
+// Like codex-core, library code must not write to the embedding
+// application's stdout/stderr.
+#![deny(clippy::print_stdout, clippy::print_stderr)]
+
+mod builder;
+mod conversation;
+mod error;
+mod event;
+mod policy;
+
+pub use builder::Codex;
+pub use builder::CodexBuilder;
+pub use conversation::Conversation;
+pub use error::Error;
+pub use error::Result;
+pub use event::ApprovalKind;
+pub use event::ApprovalRequest;
+pub use event::Event;
+pub use event::TokenUsage;
+pub use policy::ApprovalMode;
+pub use policy::Decision;
+pub use policy::SandboxMode;
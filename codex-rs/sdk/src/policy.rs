@@ -0,0 +1,77 @@
+//! Policies that decide what the agent may do without asking.
+
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::ReviewDecision;
+use codex_core::protocol_config_types::SandboxMode as CoreSandboxMode;
+
+/// When the agent asks for approval before running a command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ApprovalMode {
+    /// Ask before running anything that is not known to be safe.
+    Untrusted,
+    /// Run commands in the sandbox and ask only if one fails there.
+    OnFailure,
+    /// The model decides when to ask.
+    OnRequest,
+    /// Never ask; failures are reported back to the model.
+    Never,
+}
+
+impl From<ApprovalMode> for AskForApproval {
+    fn from(mode: ApprovalMode) -> Self {
+        match mode {
+            ApprovalMode::Untrusted => AskForApproval::UnlessTrusted,
+            ApprovalMode::OnFailure => AskForApproval::OnFailure,
+            ApprovalMode::OnRequest => AskForApproval::OnRequest,
+            ApprovalMode::Never => AskForApproval::Never,
+        }
+    }
+}
+
+/// What commands run by the agent may access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SandboxMode {
+    /// Read anywhere, write nowhere.
+    ReadOnly,
+    /// Write inside the working directory and temporary directories.
+    WorkspaceWrite,
+    /// No sandbox. Only for environments that are already isolated.
+    DangerFullAccess,
+}
+
+impl From<SandboxMode> for CoreSandboxMode {
+    fn from(mode: SandboxMode) -> Self {
+        match mode {
+            SandboxMode::ReadOnly => CoreSandboxMode::ReadOnly,
+            SandboxMode::WorkspaceWrite => CoreSandboxMode::WorkspaceWrite,
+            SandboxMode::DangerFullAccess => CoreSandboxMode::DangerFullAccess,
+        }
+    }
+}
+
+/// The answer to an [`ApprovalRequest`](crate::ApprovalRequest).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Decision {
+    /// Allow this one action.
+    Approve,
+    /// Allow this action and identical ones for the rest of the session.
+    ApproveForSession,
+    /// Refuse the action; the agent carries on without it.
+    Deny,
+    /// Refuse the action and stop the turn.
+    Abort,
+}
+
+impl From<Decision> for ReviewDecision {
+    fn from(decision: Decision) -> Self {
+        match decision {
+            Decision::Approve => ReviewDecision::Approved,
+            Decision::ApproveForSession => ReviewDecision::ApprovedForSession,
+            Decision::Deny => ReviewDecision::Denied,
+            Decision::Abort => ReviewDecision::Abort,
+        }
+    }
+}
@@ -2,7 +2,12 @@ use crate::app_backtrack::BacktrackState;
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 use crate::chatwidget::ChatWidget;
+use crate::file_citation::FileCitation;
+use crate::file_citation::open_in_editor;
 use crate::file_search::FileSearchManager;
+use crate::history_cell;
+use crate::reader_app::ReaderApp;
+use crate::session_export;
 use crate::transcript_app::TranscriptApp;
 use crate::tui;
 use crate::tui::TuiEvent;
@@ -10,11 +15,15 @@ use codex_ansi_escape::ansi_escape_line;
 use codex_core::ConversationManager;
 use codex_core::config::Config;
 use codex_core::protocol::TokenUsage;
+use codex_file_search::WalkOptions;
 use codex_login::AuthManager;
 use color_eyre::eyre::Result;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
+use crossterm::event::MouseButton;
+use crossterm::event::MouseEvent;
+use crossterm::event::MouseEventKind;
 use crossterm::terminal::supports_keyboard_enhancement;
 use ratatui::style::Stylize;
 use ratatui::text::Line;
@@ -42,6 +51,8 @@ pub(crate) struct App {
 
     // Transcript overlay state
     pub(crate) transcript_overlay: Option<TranscriptApp>,
+    // Reading-mode overlay for agent messages
+    pub(crate) reader_overlay: Option<ReaderApp>,
     pub(crate) deferred_history_lines: Vec<Line<'static>>,
 
     pub(crate) enhanced_keys_supported: bool,
@@ -60,6 +71,7 @@ impl App {
         config: Config,
         initial_prompt: Option<String>,
         initial_images: Vec<PathBuf>,
+        startup_commands: Vec<String>,
     ) -> Result<TokenUsage> {
         use tokio_stream::StreamExt;
         let (app_event_tx, mut app_event_rx) = unbounded_channel();
@@ -69,7 +81,7 @@ impl App {
 
         let enhanced_keys_supported = supports_keyboard_enhancement().unwrap_or(false);
 
-        let chat_widget = ChatWidget::new(
+        let mut chat_widget = ChatWidget::new(
             config.clone(),
             conversation_manager.clone(),
             tui.frame_requester(),
@@ -78,8 +90,31 @@ impl App {
             initial_images,
             enhanced_keys_supported,
         );
+        chat_widget.set_startup_commands(startup_commands);
 
-        let file_search = FileSearchManager::new(config.cwd.clone(), app_event_tx.clone());
+        let file_search = FileSearchManager::new(
+            config.cwd.clone(),
+            WalkOptions {
+                exclude: [
+                    config.project_ignore.as_slice(),
+                    &config.file_search.exclude,
+                ]
+                .concat(),
+                include_hidden: config.file_search.include_hidden,
+                include_ignored: config.file_search.include_ignored,
+            },
+            config.tui.file_index.then(|| {
+                codex_file_search::workspace_file_path(
+                    &config.codex_home.join("file-index"),
+                    &config.cwd,
+                )
+            }),
+            codex_file_search::workspace_file_path(
+                &config.codex_home.join("recent-files"),
+                &config.cwd,
+            ),
+            app_event_tx.clone(),
+        );
 
         let mut app = Self {
             server: conversation_manager,
@@ -90,6 +125,7 @@ impl App {
             enhanced_keys_supported,
             transcript_lines: Vec::new(),
             transcript_overlay: None,
+            reader_overlay: None,
             deferred_history_lines: Vec::new(),
             commit_anim_running: Arc::new(AtomicBool::new(false)),
             backtrack: BacktrackState::default(),
@@ -117,13 +153,34 @@ impl App {
         tui: &mut tui::Tui,
         event: TuiEvent,
     ) -> Result<bool> {
-        if self.transcript_overlay.is_some() {
-            let _ = self.handle_backtrack_overlay_event(tui, event).await?;
+        if let Some(reader) = &mut self.reader_overlay {
+            reader.handle_event(tui, event)?;
+            if reader.is_done {
+                self.close_reader_overlay(tui);
+            }
+        } else if self.transcript_overlay.is_some() {
+            if let TuiEvent::Mouse(
+                mouse_event @ MouseEvent {
+                    kind: MouseEventKind::Down(MouseButton::Left),
+                    ..
+                },
+            ) = event
+            {
+                self.handle_transcript_click(tui, mouse_event);
+            } else if !matches!(
+                event,
+                TuiEvent::Key(key_event) if self.handle_transcript_citation_key(tui, key_event)
+            ) {
+                let _ = self.handle_backtrack_overlay_event(tui, event).await?;
+            }
         } else {
             match event {
                 TuiEvent::Key(key_event) => {
                     self.handle_key_event(tui, key_event).await;
                 }
+                TuiEvent::Mouse(mouse_event) => {
+                    self.handle_mouse_event(tui, mouse_event);
+                }
                 TuiEvent::Paste(pasted) => {
                     // Many terminals convert newlines to \r when pasting (e.g., iTerm2),
                     // but tui-textarea expects \n. Normalize CR to LF.
@@ -177,7 +234,7 @@ impl App {
                     tui.frame_requester().schedule_frame();
                 }
                 self.transcript_lines.extend(lines.clone());
-                if self.transcript_overlay.is_some() {
+                if self.overlay_is_open() {
                     self.deferred_history_lines.extend(lines);
                 } else {
                     tui.insert_history_lines(lines);
@@ -191,10 +248,13 @@ impl App {
                 self.transcript_lines.extend(cell.transcript_lines());
                 let display = cell.display_lines();
                 if !display.is_empty() {
-                    if self.transcript_overlay.is_some() {
+                    if self.overlay_is_open() {
                         self.deferred_history_lines.extend(display);
                     } else {
                         tui.insert_history_lines(display);
+                        if let Some(image) = cell.inline_image() {
+                            tui.insert_history_image(image.clone());
+                        }
                     }
                 }
             }
@@ -246,13 +306,52 @@ impl App {
                 ));
                 tui.frame_requester().schedule_frame();
             }
+            AppEvent::GitStatusUpdated(status) => {
+                self.chat_widget.set_git_status(status);
+            }
+            AppEvent::ExternalFileChanges { paths, since } => {
+                self.chat_widget.on_external_file_changes(paths, since);
+            }
+            AppEvent::ExternalChangesDiff(diff) => {
+                self.chat_widget.on_external_changes_diff(diff);
+            }
+            AppEvent::ShellCommandOutput(chunk) => {
+                self.chat_widget.on_shell_command_output(&chunk);
+            }
+            AppEvent::ShellCommandFinished {
+                command,
+                output,
+                duration,
+            } => {
+                self.chat_widget
+                    .on_shell_command_finished(command, output, duration);
+            }
+            AppEvent::ExportTranscript { format, path } => {
+                let cell = match session_export::export(&self.transcript_lines, format, &path) {
+                    Ok(()) => history_cell::new_session_exported(&path),
+                    Err(e) => history_cell::new_error_event(format!(
+                        "Failed to export conversation to {}: {e}",
+                        path.display()
+                    )),
+                };
+                self.app_event_tx
+                    .send(AppEvent::InsertHistoryCell(Box::new(cell)));
+            }
             AppEvent::StartFileSearch(query) => {
                 if !query.is_empty() {
                     self.file_search.on_user_query(query);
                 }
             }
-            AppEvent::FileSearchResult { query, matches } => {
-                self.chat_widget.apply_file_search_result(query, matches);
+            AppEvent::FileSearchResult {
+                query,
+                matches,
+                content_matches,
+            } => {
+                self.chat_widget
+                    .apply_file_search_result(query, matches, content_matches);
+            }
+            AppEvent::RecordRecentFiles(paths) => {
+                self.file_search.record_recent_files(paths);
             }
             AppEvent::UpdateReasoningEffort(effort) => {
                 self.chat_widget.set_reasoning_effort(effort);
@@ -270,6 +369,108 @@ impl App {
         Ok(true)
     }
 
+    /// Wheel scrolling and clicks on the history above the composer open the
+    /// transcript overlay, since the history itself lives in the terminal's
+    /// scrollback, which mouse capture keeps the terminal from scrolling.
+    fn handle_mouse_event(&mut self, tui: &mut tui::Tui, mouse_event: MouseEvent) {
+        match mouse_event.kind {
+            MouseEventKind::ScrollUp => self.open_transcript_overlay(tui),
+            MouseEventKind::Down(MouseButton::Left)
+                if mouse_event.row < tui.terminal.viewport_area.y =>
+            {
+                self.open_transcript_overlay(tui);
+            }
+            _ => {}
+        }
+    }
+
+    /// A click on the transcript's footer returns focus to the composer; a
+    /// click on a file citation opens it in the user's editor.
+    fn handle_transcript_click(&mut self, tui: &mut tui::Tui, mouse_event: MouseEvent) {
+        let Some(overlay) = &self.transcript_overlay else {
+            return;
+        };
+        let viewport = tui.terminal.viewport_area;
+        if overlay.is_footer_row(viewport, mouse_event.row) {
+            self.close_transcript_overlay(tui);
+            tui.frame_requester().schedule_frame();
+            return;
+        }
+        let Some(citation) = overlay.citation_at(
+            viewport,
+            mouse_event.column,
+            mouse_event.row,
+            &self.config.cwd,
+        ) else {
+            return;
+        };
+        self.open_citation(tui, &citation);
+    }
+
+    /// In the transcript, Tab and Shift+Tab select the next and previous file
+    /// citation, and Enter opens the selected one in the user's editor.
+    /// Returns whether the key was handled.
+    fn handle_transcript_citation_key(&mut self, tui: &mut tui::Tui, key_event: KeyEvent) -> bool {
+        let Some(overlay) = &mut self.transcript_overlay else {
+            return false;
+        };
+        if overlay.is_searching() || key_event.kind != KeyEventKind::Press {
+            return false;
+        }
+        match key_event.code {
+            KeyCode::Tab | KeyCode::BackTab => {
+                let forward = key_event.code == KeyCode::Tab;
+                overlay.select_citation(tui.terminal.viewport_area, forward, &self.config.cwd);
+                tui.frame_requester().schedule_frame();
+                true
+            }
+            KeyCode::Enter => {
+                let Some(citation) = overlay.selected_citation().cloned() else {
+                    return false;
+                };
+                self.open_citation(tui, &citation);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn open_citation(&mut self, tui: &mut tui::Tui, citation: &FileCitation) {
+        if let Err(e) = open_in_editor(tui, citation) {
+            self.app_event_tx.send(AppEvent::InsertHistoryCell(Box::new(
+                history_cell::new_error_event(format!(
+                    "Failed to open {}: {e}",
+                    citation.path.display()
+                )),
+            )));
+        }
+    }
+
+    fn overlay_is_open(&self) -> bool {
+        self.transcript_overlay.is_some() || self.reader_overlay.is_some()
+    }
+
+    /// Open the last agent message in the full-screen reader.
+    fn open_reader_overlay(&mut self, tui: &mut tui::Tui) {
+        let messages = self.chat_widget.agent_messages().to_vec();
+        let config = self.chat_widget.config_ref().clone();
+        if let Some(reader) = ReaderApp::new(messages, config) {
+            let _ = tui.enter_alt_screen();
+            self.reader_overlay = Some(reader);
+            tui.frame_requester().schedule_frame();
+        }
+    }
+
+    fn close_reader_overlay(&mut self, tui: &mut tui::Tui) {
+        let _ = tui.leave_alt_screen();
+        self.reader_overlay = None;
+        if !self.deferred_history_lines.is_empty() {
+            let lines = std::mem::take(&mut self.deferred_history_lines);
+            tui.insert_history_lines(lines);
+        }
+        tui.frame_requester().schedule_frame();
+    }
+
     pub(crate) fn token_usage(&self) -> codex_core::protocol::TokenUsage {
         self.chat_widget.token_usage().clone()
     }
@@ -282,10 +483,15 @@ impl App {
                 kind: KeyEventKind::Press,
                 ..
             } => {
-                // Enter alternate screen and set viewport to full size.
-                let _ = tui.enter_alt_screen();
-                self.transcript_overlay = Some(TranscriptApp::new(self.transcript_lines.clone()));
-                tui.frame_requester().schedule_frame();
+                self.open_transcript_overlay(tui);
+            }
+            KeyEvent {
+                code: KeyCode::Char('o'),
+                modifiers: crossterm::event::KeyModifiers::CONTROL,
+                kind: KeyEventKind::Press,
+                ..
+            } => {
+                self.open_reader_overlay(tui);
             }
             // Esc primes/advances backtracking only in normal (not working) mode
             // with an empty composer. In any other state, forward Esc so the
@@ -33,6 +33,15 @@ impl App {
         tui: &mut tui::Tui,
         event: TuiEvent,
     ) -> Result<bool> {
+        // While a search is being typed, Esc and Enter belong to its prompt.
+        if self
+            .transcript_overlay
+            .as_ref()
+            .is_some_and(TranscriptApp::is_searching)
+        {
+            self.overlay_forward_event(tui, event)?;
+            return Ok(true);
+        }
         if self.backtrack.overlay_preview_active {
             match event {
                 TuiEvent::Key(KeyEvent {
@@ -1,9 +1,16 @@
+use codex_core::git_info::BranchStatus;
 use codex_core::protocol::ConversationHistoryResponseEvent;
 use codex_core::protocol::Event;
+use codex_file_search::ContentMatch;
 use codex_file_search::FileMatch;
 use ratatui::text::Line;
 
+use crate::history_cell::CommandOutput;
 use crate::history_cell::HistoryCell;
+use crate::session_export::ExportFormat;
+use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
 
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::SandboxPolicy;
@@ -35,11 +42,49 @@ pub(crate) enum AppEvent {
     FileSearchResult {
         query: String,
         matches: Vec<FileMatch>,
+        /// Lines inside files that match the query, shown as "search in
+        /// files" results.
+        content_matches: Vec<ContentMatch>,
     },
 
+    /// Files were picked from the `@` popup or edited by the agent; rank them
+    /// higher in later file searches.
+    RecordRecentFiles(Vec<PathBuf>),
+
     /// Result of computing a `/diff` command.
     DiffResult(String),
 
+    /// Result of refreshing the git branch shown in the status bar; `None`
+    /// outside a repository.
+    GitStatusUpdated(Option<BranchStatus>),
+
+    /// Output of a `!command` running in the user's shell, as it arrives.
+    ShellCommandOutput(Vec<u8>),
+
+    /// A `!command` exited (or could not be started).
+    ShellCommandFinished {
+        command: String,
+        output: CommandOutput,
+        duration: Duration,
+    },
+
+    /// Files changed in the workspace (`tui.watch_files`), relative to its
+    /// root. `since` is when the first of the changes was seen.
+    ExternalFileChanges {
+        paths: Vec<PathBuf>,
+        since: Instant,
+    },
+
+    /// The diff of the externally changed files, to send with the next
+    /// message.
+    ExternalChangesDiff(String),
+
+    /// Write the conversation transcript to `path` (`/export`).
+    ExportTranscript {
+        format: ExportFormat,
+        path: PathBuf,
+    },
+
     InsertHistoryLines(Vec<Line<'static>>),
     InsertHistoryCell(Box<dyn HistoryCell>),
 
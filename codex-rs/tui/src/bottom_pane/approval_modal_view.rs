@@ -31,12 +31,18 @@ impl ApprovalModalView {
         self.queue.push(req);
     }
 
-    /// Advance to next request if the current one is finished.
+    /// Advance to next request if the current one is finished, batching the
+    /// queued commands similar to it.
     fn maybe_advance(&mut self) {
         if self.current.is_complete()
             && let Some(req) = self.queue.pop()
         {
             self.current = UserApprovalWidget::new(req, self.app_event_tx.clone());
+            let queue = std::mem::take(&mut self.queue);
+            self.queue = queue
+                .into_iter()
+                .filter_map(|req| self.current.try_batch(req))
+                .collect();
         }
     }
 }
@@ -66,7 +72,9 @@ impl BottomPaneView for ApprovalModalView {
     }
 
     fn try_consume_approval_request(&mut self, req: ApprovalRequest) -> Option<ApprovalRequest> {
-        self.enqueue_request(req);
+        if let Some(req) = self.current.try_batch(req) {
+            self.enqueue_request(req);
+        }
         None
     }
 }
@@ -82,6 +90,7 @@ mod tests {
             id: "test".to_string(),
             command: vec!["echo".to_string(), "hi".to_string()],
             reason: None,
+            matched_rule: None,
         }
     }
 
@@ -106,4 +115,52 @@ mod tests {
         assert!(view.current.is_complete());
         assert!(view.is_complete());
     }
+
+    #[test]
+    fn queued_similar_commands_join_the_current_request() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let cargo_test = |id: &str, krate: &str| ApprovalRequest::Exec {
+            id: id.to_string(),
+            command: vec![
+                "bash".to_string(),
+                "-lc".to_string(),
+                format!("cargo test -p {krate}"),
+            ],
+            reason: None,
+            matched_rule: None,
+        };
+        let mut view = ApprovalModalView::new(cargo_test("1", "core"), AppEventSender::new(tx));
+        assert!(
+            view.try_consume_approval_request(cargo_test("2", "tui"))
+                .is_none()
+        );
+        view.enqueue_request(make_exec_request());
+        assert_eq!(view.queue.len(), 1);
+
+        let (tx2, _rx2) = unbounded_channel::<AppEvent>();
+        let mut pane = BottomPane::new(super::super::BottomPaneParams {
+            app_event_tx: AppEventSender::new(tx2),
+            frame_requester: crate::tui::FrameRequester::test_dummy(),
+            has_input_focus: true,
+            enhanced_keys_supported: false,
+            placeholder_text: "Ask Codex to do anything".to_string(),
+        });
+        view.handle_key_event(
+            &mut pane,
+            KeyEvent::new(
+                crossterm::event::KeyCode::Enter,
+                crossterm::event::KeyModifiers::NONE,
+            ),
+        );
+        let approved: Vec<String> = std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|ev| match ev {
+                AppEvent::CodexOp(codex_core::protocol::Op::ExecApproval { id, .. }) => Some(id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(approved, vec!["1".to_string(), "2".to_string()]);
+        // The unrelated request is shown next.
+        assert!(view.queue.is_empty());
+        assert!(!view.is_complete());
+    }
 }
@@ -22,7 +22,9 @@ use ratatui::widgets::StatefulWidgetRef;
 use ratatui::widgets::WidgetRef;
 
 use super::chat_composer_history::ChatComposerHistory;
+use super::command_popup::CommandItem;
 use super::command_popup::CommandPopup;
+use super::command_popup::CustomCommandInfo;
 use super::file_search_popup::FileSearchPopup;
 use crate::slash_command::SlashCommand;
 
@@ -32,14 +34,23 @@ use crate::bottom_pane::textarea::TextArea;
 use crate::bottom_pane::textarea::TextAreaState;
 use crate::clipboard_paste::normalize_pasted_path;
 use crate::clipboard_paste::pasted_image_format;
+use codex_file_search::ContentMatch;
 use codex_file_search::FileMatch;
+use regex_lite::Regex;
 use std::cell::RefCell;
 use std::collections::HashMap;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::LazyLock;
 use std::time::Duration;
 use std::time::Instant;
 
+/// Matches the placeholder `attach_image` inserts, e.g. `[image 640x480 PNG]`.
+static IMAGE_PLACEHOLDER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    #[expect(clippy::unwrap_used)]
+    Regex::new(r"\[image \d+x\d+ [^\]]+\]").unwrap()
+});
+
 // Heuristic thresholds for detecting paste-like input bursts.
 const PASTE_BURST_MIN_CHARS: u16 = 3;
 const PASTE_BURST_CHAR_INTERVAL: Duration = Duration::from_millis(8);
@@ -52,7 +63,11 @@ const LARGE_PASTE_CHAR_THRESHOLD: usize = 1000;
 /// Result returned when the user interacts with the text area.
 pub enum InputResult {
     Submitted(String),
-    Command(SlashCommand),
+    /// A slash command and any text typed after it on the same line.
+    Command(SlashCommand, String),
+    /// A custom slash command from `tui.slash_commands`, by name, and any
+    /// text typed after it on the same line.
+    CustomCommand(String, String),
     None,
 }
 
@@ -85,6 +100,10 @@ pub(crate) struct ChatComposer {
     history: ChatComposerHistory,
     ctrl_c_quit_hint: bool,
     esc_backtrack_hint: bool,
+    rerun_failed_hint: bool,
+    read_message_hint: bool,
+    share_output_hint: bool,
+    external_changes_hint: bool,
     use_shift_enter_hint: bool,
     dismissed_file_popup_token: Option<String>,
     current_file_query: Option<String>,
@@ -92,7 +111,12 @@ pub(crate) struct ChatComposer {
     token_usage_info: Option<TokenUsageInfo>,
     has_focus: bool,
     attached_images: Vec<AttachedImage>,
+    /// Text and images of the last submitted message, which Up in an empty
+    /// composer loads back for editing.
+    last_submission: Option<(String, Vec<PathBuf>)>,
     placeholder_text: String,
+    /// Custom slash commands listed in the command popup.
+    custom_commands: Vec<CustomCommandInfo>,
     // Heuristic state to detect non-bracketed paste bursts.
     last_plain_char_time: Option<Instant>,
     consecutive_plain_char_burst: u16,
@@ -126,6 +150,10 @@ impl ChatComposer {
             history: ChatComposerHistory::new(),
             ctrl_c_quit_hint: false,
             esc_backtrack_hint: false,
+            rerun_failed_hint: false,
+            read_message_hint: false,
+            share_output_hint: false,
+            external_changes_hint: false,
             use_shift_enter_hint,
             dismissed_file_popup_token: None,
             current_file_query: None,
@@ -133,7 +161,9 @@ impl ChatComposer {
             token_usage_info: None,
             has_focus: has_input_focus,
             attached_images: Vec::new(),
+            last_submission: None,
             placeholder_text,
+            custom_commands: Vec::new(),
             last_plain_char_time: None,
             consecutive_plain_char_burst: 0,
             paste_burst_until: None,
@@ -194,6 +224,20 @@ impl ChatComposer {
         });
     }
 
+    /// Percentage of the context window left for the conversation, once the
+    /// model has reported token usage and its window size is known.
+    pub(crate) fn context_left_percent(&self) -> Option<u8> {
+        let info = self.token_usage_info.as_ref()?;
+        let context_window = info.model_context_window?;
+        if context_window == 0 {
+            return Some(100);
+        }
+        Some(
+            info.last_token_usage
+                .percent_of_context_window_remaining(context_window, info.initial_prompt_tokens),
+        )
+    }
+
     /// Record the history metadata advertised by `SessionConfiguredEvent` so
     /// that the composer can navigate cross-session history.
     pub(crate) fn set_history_metadata(&mut self, log_id: u64, entry_count: usize) {
@@ -256,14 +300,6 @@ impl ChatComposer {
         }
     }
 
-    /// Replace the entire composer content with `text` and reset cursor.
-    pub(crate) fn set_text_content(&mut self, text: String) {
-        self.textarea.set_text(&text);
-        self.textarea.set_cursor(0);
-        self.sync_command_popup();
-        self.sync_file_search_popup();
-    }
-
     /// Get the current composer text.
     #[cfg(test)]
     pub(crate) fn current_text(&self) -> String {
@@ -279,13 +315,67 @@ impl ChatComposer {
             .push(AttachedImage { placeholder, path });
     }
 
+    /// Replace the composer content with a message that was submitted before,
+    /// re-attaching `image_paths` to the image placeholders in `text` in
+    /// order. The cursor is placed at the end so the message can be edited.
+    pub(crate) fn set_text_with_images(&mut self, text: &str, image_paths: Vec<PathBuf>) {
+        self.textarea.set_text("");
+        self.pending_pastes.clear();
+        self.attached_images.clear();
+
+        let mut paths = image_paths.into_iter();
+        let mut rest = text;
+        while let Some(m) = IMAGE_PLACEHOLDER_REGEX.find(rest) {
+            let Some(path) = paths.next() else {
+                break;
+            };
+            self.textarea.insert_str(&rest[..m.start()]);
+            self.textarea.insert_element(m.as_str());
+            self.attached_images.push(AttachedImage {
+                placeholder: m.as_str().to_string(),
+                path,
+            });
+            rest = &rest[m.end()..];
+        }
+        self.textarea.insert_str(rest);
+        // Images whose placeholder was typed over still go with the message.
+        for path in paths {
+            let name = path
+                .file_name()
+                .map_or_else(String::new, |n| n.to_string_lossy().into_owned());
+            let placeholder = format!("[image {name}]");
+            self.textarea.insert_element(&placeholder);
+            self.attached_images
+                .push(AttachedImage { placeholder, path });
+        }
+        self.sync_command_popup();
+        self.sync_file_search_popup();
+    }
+
+    /// Empty the composer, returning its text (with large pastes expanded)
+    /// and attached images.
+    pub(crate) fn take_draft(&mut self) -> (String, Vec<PathBuf>) {
+        let mut text = self.textarea.text().to_string();
+        self.textarea.set_text("");
+        for (placeholder, actual) in self.pending_pastes.drain(..) {
+            text = text.replace(&placeholder, &actual);
+        }
+        let images = self.take_recent_submission_images();
+        (text.trim().to_string(), images)
+    }
+
     pub fn take_recent_submission_images(&mut self) -> Vec<PathBuf> {
         let images = std::mem::take(&mut self.attached_images);
         images.into_iter().map(|img| img.path).collect()
     }
 
     /// Integrate results from an asynchronous file search.
-    pub(crate) fn on_file_search_result(&mut self, query: String, matches: Vec<FileMatch>) {
+    pub(crate) fn on_file_search_result(
+        &mut self,
+        query: String,
+        matches: Vec<FileMatch>,
+        content_matches: Vec<ContentMatch>,
+    ) {
         // Only apply if user is still editing a token starting with `query`.
         let current_opt = Self::current_at_token(&self.textarea);
         let Some(current_token) = current_opt else {
@@ -297,7 +387,7 @@ impl ChatComposer {
         }
 
         if let ActivePopup::File(popup) = &mut self.active_popup {
-            popup.set_matches(&query, matches);
+            popup.set_matches(&query, matches, content_matches);
         }
     }
 
@@ -331,6 +421,11 @@ impl ChatComposer {
         result
     }
 
+    /// Set the custom slash commands offered alongside the built-in ones.
+    pub(crate) fn set_custom_commands(&mut self, commands: Vec<CustomCommandInfo>) {
+        self.custom_commands = commands;
+    }
+
     /// Return true if either the slash-command popup or the file-search popup is active.
     pub(crate) fn popup_active(&self) -> bool {
         !matches!(self.active_popup, ActivePopup::None)
@@ -391,10 +486,25 @@ impl ChatComposer {
                 ..
             } => {
                 if let Some(cmd) = popup.selected_command() {
+                    let args = self
+                        .textarea
+                        .text()
+                        .lines()
+                        .next()
+                        .and_then(|line| line.trim_start().strip_prefix('/'))
+                        .and_then(|rest| rest.trim_start().split_once(char::is_whitespace))
+                        .map(|(_, args)| args.trim().to_string())
+                        .unwrap_or_default();
+
                     // Clear textarea so no residual text remains.
                     self.textarea.set_text("");
 
-                    let result = (InputResult::Command(*cmd), true);
+                    let result = match cmd {
+                        CommandItem::Builtin(cmd) => (InputResult::Command(*cmd, args), true),
+                        CommandItem::Custom(info) => {
+                            (InputResult::CustomCommand(info.name.clone(), args), true)
+                        }
+                    };
 
                     // Hide popup since the command has been dispatched.
                     self.active_popup = ActivePopup::None;
@@ -450,8 +560,12 @@ impl ChatComposer {
                     self.active_popup = ActivePopup::None;
                     return (InputResult::None, true);
                 };
+                if let Some(file) = popup.selected_file() {
+                    self.app_event_tx
+                        .send(AppEvent::RecordRecentFiles(vec![PathBuf::from(file)]));
+                }
 
-                let sel_path = sel.to_string();
+                let sel_path = sel;
                 // If selected path looks like an image (png/jpeg), attach as image instead of inserting text.
                 let is_image = Self::is_image_path(&sel_path);
                 if is_image {
@@ -667,6 +781,21 @@ impl ChatComposer {
                 self.app_event_tx.send(AppEvent::ExitRequest);
                 (InputResult::None, true)
             }
+            // Up in an empty composer brings back the last message for editing,
+            // images included, with the cursor at the end. Pressing Up again
+            // from the start of the unedited text continues into history.
+            KeyEvent {
+                code: KeyCode::Up,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } if self.textarea.is_empty() && !self.history.is_browsing() => {
+                let Some((text, images)) = self.last_submission.clone() else {
+                    return self.handle_up_down_navigation(key_event);
+                };
+                self.set_text_with_images(&text, images);
+                self.history.mark_latest_recalled(&text);
+                (InputResult::None, true)
+            }
             // -------------------------------------------------------------
             // History navigation (Up / Down) – only when the composer is not
             // empty or when the cursor is at the correct position, to avoid
@@ -675,24 +804,7 @@ impl ChatComposer {
             KeyEvent {
                 code: KeyCode::Up | KeyCode::Down,
                 ..
-            } => {
-                if self
-                    .history
-                    .should_handle_navigation(self.textarea.text(), self.textarea.cursor())
-                {
-                    let replace_text = match key_event.code {
-                        KeyCode::Up => self.history.navigate_up(&self.app_event_tx),
-                        KeyCode::Down => self.history.navigate_down(&self.app_event_tx),
-                        _ => unreachable!(),
-                    };
-                    if let Some(text) = replace_text {
-                        self.textarea.set_text(&text);
-                        self.textarea.set_cursor(0);
-                        return (InputResult::None, true);
-                    }
-                }
-                self.handle_input_basic(key_event)
-            }
+            } => self.handle_up_down_navigation(key_event),
             KeyEvent {
                 code: KeyCode::Enter,
                 modifiers: KeyModifiers::NONE,
@@ -731,7 +843,7 @@ impl ChatComposer {
                     if text.is_empty() {
                         return (InputResult::None, true);
                     }
-                    self.history.record_local_submission(&text);
+                    self.record_submission(&text);
                     return (InputResult::Submitted(text), true);
                 }
 
@@ -764,9 +876,7 @@ impl ChatComposer {
                 self.pending_pastes.clear();
 
                 text = text.trim().to_string();
-                if !text.is_empty() {
-                    self.history.record_local_submission(&text);
-                }
+                self.record_submission(&text);
                 // Do not clear attached_images here; ChatWidget drains them via take_recent_submission_images().
                 (InputResult::Submitted(text), true)
             }
@@ -774,6 +884,37 @@ impl ChatComposer {
         }
     }
 
+    fn handle_up_down_navigation(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
+        if self
+            .history
+            .should_handle_navigation(self.textarea.text(), self.textarea.cursor())
+        {
+            let replace_text = match key_event.code {
+                KeyCode::Up => self.history.navigate_up(&self.app_event_tx),
+                KeyCode::Down => self.history.navigate_down(&self.app_event_tx),
+                _ => unreachable!(),
+            };
+            if let Some(text) = replace_text {
+                self.textarea.set_text(&text);
+                self.textarea.set_cursor(0);
+                return (InputResult::None, true);
+            }
+        }
+        self.handle_input_basic(key_event)
+    }
+
+    fn record_submission(&mut self, text: &str) {
+        self.history.record_local_submission(text);
+        if !text.is_empty() || !self.attached_images.is_empty() {
+            let images = self
+                .attached_images
+                .iter()
+                .map(|i| i.path.clone())
+                .collect();
+            self.last_submission = Some((text.to_string(), images));
+        }
+    }
+
     /// Handle generic Input events that modify the textarea content.
     fn handle_input_basic(&mut self, input: KeyEvent) -> (InputResult, bool) {
         // If we have a buffered non-bracketed paste burst and enough time has
@@ -1088,7 +1229,7 @@ impl ChatComposer {
             }
             _ => {
                 if input_starts_with_slash {
-                    let mut command_popup = CommandPopup::new();
+                    let mut command_popup = CommandPopup::new(&self.custom_commands);
                     command_popup.on_composer_text_change(first_line.to_string());
                     self.active_popup = ActivePopup::Command(command_popup);
                 }
@@ -1149,6 +1290,22 @@ impl ChatComposer {
     pub(crate) fn set_esc_backtrack_hint(&mut self, show: bool) {
         self.esc_backtrack_hint = show;
     }
+
+    pub(crate) fn set_rerun_failed_hint(&mut self, show: bool) {
+        self.rerun_failed_hint = show;
+    }
+
+    pub(crate) fn set_read_message_hint(&mut self, show: bool) {
+        self.read_message_hint = show;
+    }
+
+    pub(crate) fn set_share_output_hint(&mut self, show: bool) {
+        self.share_output_hint = show;
+    }
+
+    pub(crate) fn set_external_changes_hint(&mut self, show: bool) {
+        self.external_changes_hint = show;
+    }
 }
 
 impl WidgetRef for ChatComposer {
@@ -1201,6 +1358,30 @@ impl WidgetRef for ChatComposer {
                     hint.push(Span::from(" edit prev"));
                 }
 
+                if !self.ctrl_c_quit_hint && self.rerun_failed_hint {
+                    hint.push(Span::from("   "));
+                    hint.push("Ctrl+R".set_style(key_hint_style));
+                    hint.push(Span::from(" edit failed cmd"));
+                }
+
+                if !self.ctrl_c_quit_hint && self.share_output_hint {
+                    hint.push(Span::from("   "));
+                    hint.push("Ctrl+S".set_style(key_hint_style));
+                    hint.push(Span::from(" share output"));
+                }
+
+                if !self.ctrl_c_quit_hint && self.external_changes_hint {
+                    hint.push(Span::from("   "));
+                    hint.push("Ctrl+G".set_style(key_hint_style));
+                    hint.push(Span::from(" send changes"));
+                }
+
+                if !self.ctrl_c_quit_hint && self.read_message_hint {
+                    hint.push(Span::from("   "));
+                    hint.push("Ctrl+O".set_style(key_hint_style));
+                    hint.push(Span::from(" read"));
+                }
+
                 // Append token/context usage info to the footer hints when available.
                 if let Some(token_usage_info) = &self.token_usage_info {
                     let token_usage = &token_usage_info.total_token_usage;
@@ -1209,16 +1390,11 @@ impl WidgetRef for ChatComposer {
                         Span::from(format!("{} tokens used", token_usage.blended_total()))
                             .style(Style::default().add_modifier(Modifier::DIM)),
                     );
-                    let last_token_usage = &token_usage_info.last_token_usage;
-                    if let Some(context_window) = token_usage_info.model_context_window {
-                        let percent_remaining: u8 = if context_window > 0 {
-                            last_token_usage.percent_of_context_window_remaining(
-                                context_window,
-                                token_usage_info.initial_prompt_tokens,
-                            )
-                        } else {
-                            100
-                        };
+                    if let Some(cached_percent) = token_usage.cached_input_percent() {
+                        hint.push(Span::from("   "));
+                        hint.push(format!("{cached_percent}% cached").dim());
+                    }
+                    if let Some(percent_remaining) = self.context_left_percent() {
                         hint.push(Span::from("   "));
                         hint.push(
                             Span::from(format!("{percent_remaining}% context left"))
@@ -1558,6 +1734,37 @@ mod tests {
         }
     }
 
+    #[test]
+    fn footer_shows_cached_percent() {
+        use insta::assert_snapshot;
+        use ratatui::Terminal;
+        use ratatui::backend::TestBackend;
+
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut terminal = match Terminal::new(TestBackend::new(100, 10)) {
+            Ok(t) => t,
+            Err(e) => panic!("Failed to create terminal: {e}"),
+        };
+
+        let mut composer =
+            ChatComposer::new(true, sender, false, "Ask Codex to do anything".to_string());
+        let usage = TokenUsage {
+            input_tokens: 12_000,
+            cached_input_tokens: Some(9_000),
+            output_tokens: 400,
+            reasoning_output_tokens: None,
+            total_tokens: 12_400,
+        };
+        composer.set_token_usage(usage.clone(), usage, Some(200_000));
+
+        terminal
+            .draw(|f| f.render_widget_ref(composer, f.area()))
+            .unwrap_or_else(|e| panic!("Failed to draw composer: {e}"));
+
+        assert_snapshot!(terminal.backend());
+    }
+
     #[test]
     fn slash_init_dispatches_command_and_does_not_submit_literal_text() {
         use crossterm::event::KeyCode;
@@ -1583,17 +1790,87 @@ mod tests {
         // When a slash command is dispatched, the composer should return a
         // Command result (not submit literal text) and clear its textarea.
         match result {
-            InputResult::Command(cmd) => {
+            InputResult::Command(cmd, _) => {
                 assert_eq!(cmd.command(), "init");
             }
             InputResult::Submitted(text) => {
                 panic!("expected command dispatch, but composer submitted literal text: {text}")
             }
+            InputResult::CustomCommand(name, _) => {
+                panic!("expected a built-in command, got /{name}")
+            }
             InputResult::None => panic!("expected Command result for '/init'"),
         }
         assert!(composer.textarea.is_empty(), "composer should be cleared");
     }
 
+    #[test]
+    fn custom_slash_command_passes_name_and_args() {
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer =
+            ChatComposer::new(true, sender, false, "Ask Codex to do anything".to_string());
+        composer.set_custom_commands(vec![CustomCommandInfo {
+            name: "release-notes".to_string(),
+            description: "draft release notes".to_string(),
+        }]);
+
+        for ch in "/release-notes v1.2".chars() {
+            let _ = composer.handle_key_event(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
+        }
+        let (result, _needs_redraw) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        match result {
+            InputResult::CustomCommand(name, args) => {
+                assert_eq!(name, "release-notes");
+                assert_eq!(args, "v1.2");
+            }
+            InputResult::Command(cmd, _) => panic!("expected the custom command, got /{cmd:?}"),
+            InputResult::Submitted(text) => panic!("expected a command, got text: {text}"),
+            InputResult::None => panic!("expected a custom command"),
+        }
+        assert!(composer.textarea.is_empty(), "composer should be cleared");
+    }
+
+    #[test]
+    fn slash_command_passes_trailing_text_as_args() {
+        use crossterm::event::KeyCode;
+        use crossterm::event::KeyEvent;
+        use crossterm::event::KeyModifiers;
+
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer =
+            ChatComposer::new(true, sender, false, "Ask Codex to do anything".to_string());
+
+        for ch in "/usage detail ".chars() {
+            let _ = composer.handle_key_event(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
+        }
+
+        let (result, _needs_redraw) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+        match result {
+            InputResult::Command(cmd, args) => {
+                assert_eq!(cmd, SlashCommand::Usage);
+                assert_eq!(args, "detail");
+            }
+            InputResult::Submitted(text) => {
+                panic!("expected command dispatch, but composer submitted literal text: {text}")
+            }
+            InputResult::CustomCommand(name, _) => {
+                panic!("expected a built-in command, got /{name}")
+            }
+            InputResult::None => panic!("expected Command result for '/usage detail'"),
+        }
+        assert!(composer.textarea.is_empty(), "composer should be cleared");
+    }
+
     #[test]
     fn slash_tab_completion_moves_cursor_to_end() {
         use crossterm::event::KeyCode;
@@ -1635,12 +1912,15 @@ mod tests {
             composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
 
         match result {
-            InputResult::Command(cmd) => {
+            InputResult::Command(cmd, _) => {
                 assert_eq!(cmd.command(), "mention");
             }
             InputResult::Submitted(text) => {
                 panic!("expected command dispatch, but composer submitted literal text: {text}")
             }
+            InputResult::CustomCommand(name, _) => {
+                panic!("expected a built-in command, got /{name}")
+            }
             InputResult::None => panic!("expected Command result for '/mention'"),
         }
         assert!(composer.textarea.is_empty(), "composer should be cleared");
@@ -1855,6 +2135,67 @@ mod tests {
         assert_eq!(vec![path], imgs);
     }
 
+    #[test]
+    fn up_in_empty_composer_loads_last_message_for_editing() {
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer =
+            ChatComposer::new(true, sender, false, "Ask Codex to do anything".to_string());
+        let enter = KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE);
+        let up = KeyEvent::new(KeyCode::Up, KeyModifiers::NONE);
+
+        composer.handle_paste("older".into());
+        composer.handle_key_event(enter);
+        let path = PathBuf::from("/tmp/image1.png");
+        composer.attach_image(path.clone(), 32, 16, "PNG");
+        composer.handle_paste(" describe".into());
+        composer.handle_key_event(enter);
+        composer.take_recent_submission_images();
+
+        composer.handle_key_event(up);
+        assert_eq!(composer.current_text(), "[image 32x16 PNG] describe");
+        assert_eq!(composer.textarea.cursor(), composer.current_text().len());
+        assert_eq!(
+            composer.attached_images,
+            vec![AttachedImage {
+                placeholder: "[image 32x16 PNG]".to_string(),
+                path,
+            }]
+        );
+
+        // Up moves to the start of the text, then on to older history.
+        composer.handle_key_event(up);
+        assert_eq!(composer.textarea.cursor(), 0);
+        composer.handle_key_event(up);
+        assert_eq!(composer.current_text(), "older");
+    }
+
+    #[test]
+    fn restoring_a_message_reattaches_images_in_order() {
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer =
+            ChatComposer::new(true, sender, false, "Ask Codex to do anything".to_string());
+        let (a, b, c) = (
+            PathBuf::from("/tmp/a.png"),
+            PathBuf::from("/tmp/b.png"),
+            PathBuf::from("/tmp/c.png"),
+        );
+        composer.set_text_with_images(
+            "[image 1x1 PNG] vs [image 2x2 JPEG]",
+            vec![a.clone(), b.clone(), c.clone()],
+        );
+        assert_eq!(
+            composer.current_text(),
+            "[image 1x1 PNG] vs [image 2x2 JPEG][image c.png]"
+        );
+
+        let (text, images) = composer.take_draft();
+        assert_eq!(text, "[image 1x1 PNG] vs [image 2x2 JPEG][image c.png]");
+        assert_eq!(images, vec![a, b, c]);
+        assert!(composer.is_empty());
+    }
+
     #[test]
     fn attach_image_without_text_submits_empty_text_and_images() {
         let (tx, _rx) = unbounded_channel::<AppEvent>();
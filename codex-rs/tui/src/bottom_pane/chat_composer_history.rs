@@ -69,6 +69,22 @@ impl ChatComposerHistory {
         self.last_history_text = None;
     }
 
+    /// Whether the user is currently stepping through history entries.
+    pub fn is_browsing(&self) -> bool {
+        self.history_cursor.is_some()
+    }
+
+    /// Note that `text`, the latest local submission, was loaded into the
+    /// composer for editing, so that Up from the start of the unedited text
+    /// moves on to the entry before it rather than recalling it again.
+    pub fn mark_latest_recalled(&mut self, text: &str) {
+        if self.local_history.last().is_some_and(|last| last == text) {
+            let total_entries = self.history_entry_count + self.local_history.len();
+            self.history_cursor = Some(total_entries as isize - 1);
+            self.last_history_text = Some(text.to_string());
+        }
+    }
+
     /// Should Up/Down key presses be interpreted as history navigation given
     /// the current content and cursor position of `textarea`?
     pub fn should_handle_navigation(&self, text: &str, cursor: usize) -> bool {
@@ -0,0 +1,209 @@
+use codex_core::protocol::Op;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::StatefulWidgetRef;
+use ratatui::widgets::Widget;
+use std::cell::RefCell;
+use std::path::PathBuf;
+
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+
+use super::BottomPane;
+use super::CancellationEvent;
+use super::bottom_pane_view::BottomPaneView;
+use super::textarea::TextArea;
+use super::textarea::TextAreaState;
+
+/// Columns reserved on the left of the editor for the `▌ ` prefix.
+const PREFIX_WIDTH: u16 = 2;
+
+/// Small single-purpose editor used to tweak a failed command before running
+/// it again as a user-initiated command in the directory it originally ran in.
+pub(crate) struct CommandEditView {
+    textarea: TextArea,
+    cwd: PathBuf,
+    textarea_state: RefCell<TextAreaState>,
+    complete: bool,
+    app_event_tx: AppEventSender,
+}
+
+impl CommandEditView {
+    pub fn new(command: String, cwd: PathBuf, app_event_tx: AppEventSender) -> Self {
+        let mut textarea = TextArea::new();
+        textarea.set_text(&command);
+        textarea.set_cursor(command.len());
+        Self {
+            textarea,
+            cwd,
+            textarea_state: RefCell::new(TextAreaState::default()),
+            complete: false,
+            app_event_tx,
+        }
+    }
+
+    fn submit(&mut self) {
+        let script = self.textarea.text().trim();
+        if script.is_empty() {
+            return;
+        }
+        self.app_event_tx
+            .send(AppEvent::CodexOp(Op::RunUserCommand {
+                argv: vec!["bash".to_string(), "-lc".to_string(), script.to_string()],
+                cwd: self.cwd.clone(),
+            }));
+        self.complete = true;
+    }
+
+    fn textarea_rect(area: Rect) -> Rect {
+        Rect {
+            x: area.x.saturating_add(PREFIX_WIDTH),
+            y: area.y.saturating_add(1),
+            width: area.width.saturating_sub(PREFIX_WIDTH),
+            height: area.height.saturating_sub(2),
+        }
+    }
+}
+
+impl BottomPaneView for CommandEditView {
+    fn handle_key_event(&mut self, _pane: &mut BottomPane, key_event: KeyEvent) {
+        match key_event {
+            KeyEvent {
+                code: KeyCode::Esc, ..
+            } => self.complete = true,
+            KeyEvent {
+                code: KeyCode::Enter,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } => self.submit(),
+            other => self.textarea.input(other),
+        }
+    }
+
+    fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    fn on_ctrl_c(&mut self, _pane: &mut BottomPane) -> CancellationEvent {
+        self.complete = true;
+        CancellationEvent::Handled
+    }
+
+    fn desired_height(&self, width: u16) -> u16 {
+        // Title, editor, footer hint.
+        let editor_height = self
+            .textarea
+            .desired_height(width.saturating_sub(PREFIX_WIDTH))
+            .max(1);
+        editor_height + 2
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        if area.height == 0 {
+            return;
+        }
+        let textarea_rect = Self::textarea_rect(area);
+
+        let mut lines = vec![Line::from(vec![
+            "▌ ".dim(),
+            "Edit command and re-run".bold(),
+        ])];
+        for _ in 0..textarea_rect.height {
+            lines.push(Line::from("▌ ".dim()));
+        }
+        lines.push(Line::from(vec![
+            "  ".into(),
+            "⏎".cyan(),
+            " run   ".dim(),
+            "Ctrl+J".cyan(),
+            " newline   ".dim(),
+            "Esc".cyan(),
+            " cancel".dim(),
+        ]));
+        Paragraph::new(lines).render(area, buf);
+
+        let mut state = self.textarea_state.borrow_mut();
+        StatefulWidgetRef::render_ref(&(&self.textarea), textarea_rect, buf, &mut state);
+    }
+
+    fn cursor_pos(&self, area: Rect) -> Option<(u16, u16)> {
+        let state = self.textarea_state.borrow();
+        self.textarea
+            .cursor_pos_with_state(Self::textarea_rect(area), &state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app_event::AppEvent;
+    use insta::assert_snapshot;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    #[test]
+    fn renders_prefilled_command() {
+        let (tx_raw, _rx) = unbounded_channel::<AppEvent>();
+        let view = CommandEditView::new(
+            "cargo test -p codex-core".to_string(),
+            PathBuf::from("/workspace"),
+            AppEventSender::new(tx_raw),
+        );
+        let width = 50;
+        let height = view.desired_height(width);
+        let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+        terminal
+            .draw(|f| view.render(f.area(), f.buffer_mut()))
+            .unwrap();
+        assert_snapshot!(terminal.backend());
+    }
+
+    #[test]
+    fn enter_runs_edited_command() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx_raw);
+        let mut view = CommandEditView::new(
+            "cargo tset".to_string(),
+            PathBuf::from("/workspace/crate"),
+            tx.clone(),
+        );
+        let mut pane = BottomPane::new(super::super::BottomPaneParams {
+            app_event_tx: tx,
+            frame_requester: crate::tui::FrameRequester::test_dummy(),
+            has_input_focus: true,
+            enhanced_keys_supported: false,
+            placeholder_text: String::new(),
+        });
+
+        for _ in 0..3 {
+            view.handle_key_event(&mut pane, KeyEvent::from(KeyCode::Backspace));
+        }
+        for c in "est".chars() {
+            view.handle_key_event(&mut pane, KeyEvent::from(KeyCode::Char(c)));
+        }
+        view.handle_key_event(&mut pane, KeyEvent::from(KeyCode::Enter));
+
+        assert!(view.is_complete());
+        match rx.try_recv() {
+            Ok(AppEvent::CodexOp(Op::RunUserCommand { argv, cwd })) => {
+                assert_eq!(
+                    argv,
+                    vec![
+                        "bash".to_string(),
+                        "-lc".to_string(),
+                        "cargo test".to_string()
+                    ]
+                );
+                assert_eq!(cwd, PathBuf::from("/workspace/crate"));
+            }
+            other => panic!("expected RunUserCommand, got {other:?}"),
+        }
+    }
+}
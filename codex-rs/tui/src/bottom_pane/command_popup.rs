@@ -10,17 +10,54 @@ use crate::slash_command::SlashCommand;
 use crate::slash_command::built_in_slash_commands;
 use codex_common::fuzzy_match::fuzzy_match;
 
+/// A custom slash command from `tui.slash_commands`, as the popup lists it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CustomCommandInfo {
+    pub(crate) name: String,
+    pub(crate) description: String,
+}
+
+/// An entry of the popup: a built-in command or a custom one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum CommandItem {
+    Builtin(SlashCommand),
+    Custom(CustomCommandInfo),
+}
+
+impl CommandItem {
+    /// Command string without the leading '/'.
+    pub(crate) fn command(&self) -> &str {
+        match self {
+            CommandItem::Builtin(cmd) => cmd.command(),
+            CommandItem::Custom(info) => &info.name,
+        }
+    }
+
+    fn description(&self) -> &str {
+        match self {
+            CommandItem::Builtin(cmd) => cmd.description(),
+            CommandItem::Custom(info) => &info.description,
+        }
+    }
+}
+
 pub(crate) struct CommandPopup {
     command_filter: String,
-    all_commands: Vec<(&'static str, SlashCommand)>,
+    all_commands: Vec<CommandItem>,
     state: ScrollState,
 }
 
 impl CommandPopup {
-    pub(crate) fn new() -> Self {
+    /// A popup listing the built-in commands followed by `custom` ones.
+    pub(crate) fn new(custom: &[CustomCommandInfo]) -> Self {
+        let all_commands = built_in_slash_commands()
+            .into_iter()
+            .map(|(_, cmd)| CommandItem::Builtin(cmd))
+            .chain(custom.iter().cloned().map(CommandItem::Custom))
+            .collect();
         Self {
             command_filter: String::new(),
-            all_commands: built_in_slash_commands(),
+            all_commands,
             state: ScrollState::new(),
         }
     }
@@ -64,17 +101,17 @@ impl CommandPopup {
 
     /// Compute fuzzy-filtered matches paired with optional highlight indices and score.
     /// Sorted by ascending score, then by command name for stability.
-    fn filtered(&self) -> Vec<(&SlashCommand, Option<Vec<usize>>, i32)> {
+    fn filtered(&self) -> Vec<(&CommandItem, Option<Vec<usize>>, i32)> {
         let filter = self.command_filter.trim();
-        let mut out: Vec<(&SlashCommand, Option<Vec<usize>>, i32)> = Vec::new();
+        let mut out: Vec<(&CommandItem, Option<Vec<usize>>, i32)> = Vec::new();
         if filter.is_empty() {
-            for (_, cmd) in self.all_commands.iter() {
+            for cmd in self.all_commands.iter() {
                 out.push((cmd, None, 0));
             }
             // Keep the original presentation order when no filter is applied.
             return out;
         } else {
-            for (_, cmd) in self.all_commands.iter() {
+            for cmd in self.all_commands.iter() {
                 if let Some((indices, score)) = fuzzy_match(cmd.command(), filter) {
                     out.push((cmd, Some(indices), score));
                 }
@@ -85,7 +122,7 @@ impl CommandPopup {
         out
     }
 
-    fn filtered_commands(&self) -> Vec<&SlashCommand> {
+    fn filtered_commands(&self) -> Vec<&CommandItem> {
         self.filtered().into_iter().map(|(c, _, _)| c).collect()
     }
 
@@ -107,7 +144,7 @@ impl CommandPopup {
     }
 
     /// Return currently selected command, if any.
-    pub(crate) fn selected_command(&self) -> Option<&SlashCommand> {
+    pub(crate) fn selected_command(&self) -> Option<&CommandItem> {
         let matches = self.filtered_commands();
         self.state
             .selected_idx
@@ -141,7 +178,7 @@ mod tests {
 
     #[test]
     fn filter_includes_init_when_typing_prefix() {
-        let mut popup = CommandPopup::new();
+        let mut popup = CommandPopup::new(&[]);
         // Simulate the composer line starting with '/in' so the popup filters
         // matching commands by prefix.
         popup.on_composer_text_change("/in".to_string());
@@ -157,7 +194,7 @@ mod tests {
 
     #[test]
     fn selecting_init_by_exact_match() {
-        let mut popup = CommandPopup::new();
+        let mut popup = CommandPopup::new(&[]);
         popup.on_composer_text_change("/init".to_string());
 
         // When an exact match exists, the selected command should be that
@@ -168,4 +205,23 @@ mod tests {
             None => panic!("expected a selected command for exact match"),
         }
     }
+
+    #[test]
+    fn lists_custom_commands_after_built_ins() {
+        let release_notes = CustomCommandInfo {
+            name: "release-notes".to_string(),
+            description: "draft release notes".to_string(),
+        };
+        let mut popup = CommandPopup::new(std::slice::from_ref(&release_notes));
+        assert_eq!(
+            popup.filtered_commands().last(),
+            Some(&&CommandItem::Custom(release_notes.clone()))
+        );
+
+        popup.on_composer_text_change("/release v1.2".to_string());
+        assert_eq!(
+            popup.selected_command(),
+            Some(&CommandItem::Custom(release_notes))
+        );
+    }
 }
@@ -1,3 +1,4 @@
+use codex_file_search::ContentMatch;
 use codex_file_search::FileMatch;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
@@ -19,6 +20,8 @@ pub(crate) struct FileSearchPopup {
     waiting: bool,
     /// Cached matches; paths relative to the search dir.
     matches: Vec<FileMatch>,
+    /// Lines inside files that match the query, listed after `matches`.
+    content_matches: Vec<ContentMatch>,
     /// Shared selection/scroll state.
     state: ScrollState,
 }
@@ -30,6 +33,7 @@ impl FileSearchPopup {
             pending_query: String::new(),
             waiting: true,
             matches: Vec::new(),
+            content_matches: Vec::new(),
             state: ScrollState::new(),
         }
     }
@@ -50,6 +54,7 @@ impl FileSearchPopup {
 
         if !keep_existing {
             self.matches.clear();
+            self.content_matches.clear();
             self.state.reset();
         }
     }
@@ -61,44 +66,73 @@ impl FileSearchPopup {
         self.pending_query.clear();
         self.waiting = false;
         self.matches.clear();
+        self.content_matches.clear();
         // Reset selection/scroll state when showing the empty prompt.
         self.state.reset();
     }
 
     /// Replace matches when a `FileSearchResult` arrives.
     /// Replace matches. Only applied when `query` matches `pending_query`.
-    pub(crate) fn set_matches(&mut self, query: &str, matches: Vec<FileMatch>) {
+    pub(crate) fn set_matches(
+        &mut self,
+        query: &str,
+        matches: Vec<FileMatch>,
+        content_matches: Vec<ContentMatch>,
+    ) {
         if query != self.pending_query {
             return; // stale
         }
 
         self.display_query = query.to_string();
         self.matches = matches;
+        self.content_matches = content_matches;
         self.waiting = false;
-        let len = self.matches.len();
+        let len = self.len();
         self.state.clamp_selection(len);
         self.state.ensure_visible(len, len.min(MAX_POPUP_ROWS));
     }
 
     /// Move selection cursor up.
     pub(crate) fn move_up(&mut self) {
-        let len = self.matches.len();
+        let len = self.len();
         self.state.move_up_wrap(len);
         self.state.ensure_visible(len, len.min(MAX_POPUP_ROWS));
     }
 
     /// Move selection cursor down.
     pub(crate) fn move_down(&mut self) {
-        let len = self.matches.len();
+        let len = self.len();
         self.state.move_down_wrap(len);
         self.state.ensure_visible(len, len.min(MAX_POPUP_ROWS));
     }
 
-    pub(crate) fn selected_match(&self) -> Option<&str> {
-        self.state
-            .selected_idx
-            .and_then(|idx| self.matches.get(idx))
-            .map(|file_match| file_match.path.as_str())
+    /// Text to insert for the selected row: the file path, or `path:line`
+    /// for a content match.
+    pub(crate) fn selected_match(&self) -> Option<String> {
+        let idx = self.state.selected_idx?;
+        match self.matches.get(idx) {
+            Some(file_match) => Some(file_match.path.clone()),
+            None => self
+                .content_matches
+                .get(idx - self.matches.len())
+                .map(|m| format!("{}:{}", m.path, m.line_number)),
+        }
+    }
+
+    /// Path of the file behind the selected row, for either kind of match.
+    pub(crate) fn selected_file(&self) -> Option<&str> {
+        let idx = self.state.selected_idx?;
+        match self.matches.get(idx) {
+            Some(file_match) => Some(&file_match.path),
+            None => self
+                .content_matches
+                .get(idx - self.matches.len())
+                .map(|m| m.path.as_str()),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.matches.len() + self.content_matches.len()
     }
 
     pub(crate) fn calculate_required_height(&self) -> u16 {
@@ -108,29 +142,32 @@ impl FileSearchPopup {
         // up to MAX_RESULTS regardless of the waiting flag so the list
         // remains stable while a newer search is in-flight.
 
-        self.matches.len().clamp(1, MAX_POPUP_ROWS) as u16
+        self.len().clamp(1, MAX_POPUP_ROWS) as u16
     }
 }
 
 impl WidgetRef for &FileSearchPopup {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
         // Convert matches to GenericDisplayRow, translating indices to usize at the UI boundary.
-        let rows_all: Vec<GenericDisplayRow> = if self.matches.is_empty() {
-            Vec::new()
-        } else {
-            self.matches
-                .iter()
-                .map(|m| GenericDisplayRow {
-                    name: m.path.clone(),
-                    match_indices: m
-                        .indices
-                        .as_ref()
-                        .map(|v| v.iter().map(|&i| i as usize).collect()),
-                    is_current: false,
-                    description: None,
-                })
-                .collect()
-        };
+        let rows_all: Vec<GenericDisplayRow> = self
+            .matches
+            .iter()
+            .map(|m| GenericDisplayRow {
+                name: m.path.clone(),
+                match_indices: m
+                    .indices
+                    .as_ref()
+                    .map(|v| v.iter().map(|&i| i as usize).collect()),
+                is_current: false,
+                description: None,
+            })
+            .chain(self.content_matches.iter().map(|m| GenericDisplayRow {
+                name: format!("{}:{}", m.path, m.line_number),
+                match_indices: None,
+                is_current: false,
+                description: Some(m.preview.clone()),
+            }))
+            .collect();
 
         if self.waiting && rows_all.is_empty() {
             // Render a minimal waiting stub using the shared renderer (no rows -> "no matches").
@@ -6,6 +6,7 @@ use crate::tui::FrameRequester;
 use crate::user_approval_widget::ApprovalRequest;
 use bottom_pane_view::BottomPaneView;
 use codex_core::protocol::TokenUsage;
+use codex_file_search::ContentMatch;
 use codex_file_search::FileMatch;
 use crossterm::event::KeyEvent;
 use ratatui::buffer::Buffer;
@@ -18,12 +19,14 @@ mod approval_modal_view;
 mod bottom_pane_view;
 mod chat_composer;
 mod chat_composer_history;
+mod command_edit_view;
 mod command_popup;
 mod file_search_popup;
 mod list_selection_view;
 mod popup_consts;
 mod scroll_state;
 mod selection_popup_common;
+mod status_bar;
 mod textarea;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -34,11 +37,13 @@ pub(crate) enum CancellationEvent {
 
 pub(crate) use chat_composer::ChatComposer;
 pub(crate) use chat_composer::InputResult;
+pub(crate) use command_popup::CustomCommandInfo;
 
 use crate::status_indicator_widget::StatusIndicatorWidget;
 use approval_modal_view::ApprovalModalView;
 pub(crate) use list_selection_view::SelectionAction;
 pub(crate) use list_selection_view::SelectionItem;
+pub(crate) use status_bar::StatusBarInfo;
 
 /// Pane displayed in the lower half of the chat UI.
 pub(crate) struct BottomPane {
@@ -61,6 +66,10 @@ pub(crate) struct BottomPane {
     status: Option<StatusIndicatorWidget>,
     /// Queued user messages to show under the status indicator.
     queued_user_messages: Vec<String>,
+    /// Slot in the queue of the message being edited in the composer.
+    editing_queued_message: Option<usize>,
+    /// Shown in the status bar on the bottom row; `None` hides the bar.
+    status_bar: Option<StatusBarInfo>,
 }
 
 pub(crate) struct BottomPaneParams {
@@ -90,6 +99,8 @@ impl BottomPane {
             ctrl_c_quit_hint: false,
             status: None,
             queued_user_messages: Vec::new(),
+            editing_queued_message: None,
+            status_bar: None,
             esc_backtrack_hint: false,
         }
     }
@@ -115,7 +126,7 @@ impl BottomPane {
             .saturating_add(top_margin)
     }
 
-    fn layout(&self, area: Rect) -> [Rect; 2] {
+    fn layout(&self, area: Rect) -> [Rect; 3] {
         // Prefer showing the status header when space is extremely tight.
         // Drop the top spacer if there is only one row available.
         let mut top_margin = if self.active_view.is_some() { 0 } else { 1 };
@@ -133,7 +144,7 @@ impl BottomPane {
             0
         };
 
-        let [_, status, content, _] = Layout::vertical([
+        let [_, status, content, bottom] = Layout::vertical([
             Constraint::Max(top_margin),
             Constraint::Max(status_height),
             Constraint::Min(1),
@@ -141,18 +152,19 @@ impl BottomPane {
         ])
         .areas(area);
 
-        [status, content]
+        [status, content, bottom]
     }
 
     pub fn cursor_pos(&self, area: Rect) -> Option<(u16, u16)> {
-        // Hide the cursor whenever an overlay view is active (e.g. the
-        // status indicator shown while a task is running, or approval modal).
-        // In these states the textarea is not interactable, so we should not
-        // show its caret.
-        if self.active_view.is_some() {
-            None
+        // Hide the composer cursor whenever an overlay view is active (e.g.
+        // the status indicator shown while a task is running, or approval
+        // modal). In these states the textarea is not interactable, so we
+        // should not show its caret; views that take text input report their
+        // own.
+        let [_, content, _] = self.layout(area);
+        if let Some(view) = self.active_view.as_ref() {
+            view.cursor_pos(content)
         } else {
-            let [_, content] = self.layout(area);
             self.composer.cursor_pos(content)
         }
     }
@@ -223,12 +235,19 @@ impl BottomPane {
         self.request_redraw();
     }
 
-    /// Replace the composer text with `text`.
-    pub(crate) fn set_composer_text(&mut self, text: String) {
-        self.composer.set_text_content(text);
+    /// Load a previously submitted message into the composer for editing.
+    pub(crate) fn set_composer_text_with_images(&mut self, text: &str, image_paths: Vec<PathBuf>) {
+        self.composer.set_text_with_images(text, image_paths);
         self.request_redraw();
     }
 
+    /// Empty the composer, returning its text and attached images.
+    pub(crate) fn take_composer_draft(&mut self) -> (String, Vec<PathBuf>) {
+        let draft = self.composer.take_draft();
+        self.request_redraw();
+        draft
+    }
+
     /// Get the current composer text (for tests and programmatic checks).
     #[cfg(test)]
     pub(crate) fn composer_text(&self) -> String {
@@ -292,7 +311,10 @@ impl BottomPane {
                 ));
             }
             if let Some(status) = self.status.as_mut() {
-                status.set_queued_messages(self.queued_user_messages.clone());
+                status.set_queued_messages(
+                    self.queued_user_messages.clone(),
+                    self.editing_queued_message,
+                );
             }
             self.request_redraw();
         } else {
@@ -320,11 +342,41 @@ impl BottomPane {
         self.request_redraw();
     }
 
-    /// Update the queued messages shown under the status header.
-    pub(crate) fn set_queued_user_messages(&mut self, queued: Vec<String>) {
+    /// Show a small editor prefilled with `command`; submitting it runs the
+    /// edited command as a user-initiated shell command.
+    pub(crate) fn show_command_edit_view(&mut self, command: String, cwd: PathBuf) {
+        let view = command_edit_view::CommandEditView::new(command, cwd, self.app_event_tx.clone());
+        self.active_view = Some(Box::new(view));
+        self.request_redraw();
+    }
+
+    pub(crate) fn set_rerun_failed_hint(&mut self, show: bool) {
+        self.composer.set_rerun_failed_hint(show);
+        self.request_redraw();
+    }
+
+    pub(crate) fn set_read_message_hint(&mut self, show: bool) {
+        self.composer.set_read_message_hint(show);
+        self.request_redraw();
+    }
+
+    pub(crate) fn set_share_output_hint(&mut self, show: bool) {
+        self.composer.set_share_output_hint(show);
+        self.request_redraw();
+    }
+
+    pub(crate) fn set_external_changes_hint(&mut self, show: bool) {
+        self.composer.set_external_changes_hint(show);
+        self.request_redraw();
+    }
+
+    /// Update the queued messages shown under the status header. `editing`
+    /// is the slot of a queued message currently taken into the composer.
+    pub(crate) fn set_queued_user_messages(&mut self, queued: Vec<String>, editing: Option<usize>) {
         self.queued_user_messages = queued.clone();
+        self.editing_queued_message = editing;
         if let Some(status) = self.status.as_mut() {
-            status.set_queued_messages(queued);
+            status.set_queued_messages(queued, editing);
         }
         self.request_redraw();
     }
@@ -344,6 +396,17 @@ impl BottomPane {
         !self.is_task_running && self.active_view.is_none() && !self.composer.popup_active()
     }
 
+    /// Offer `commands` in the slash-command popup after the built-in ones.
+    pub(crate) fn set_custom_commands(&mut self, commands: Vec<CustomCommandInfo>) {
+        self.composer.set_custom_commands(commands);
+    }
+
+    /// Show the status bar with `info`, or hide it with `None`.
+    pub(crate) fn set_status_bar(&mut self, info: Option<StatusBarInfo>) {
+        self.status_bar = info;
+        self.request_redraw();
+    }
+
     /// Update the *context-window remaining* indicator in the composer. This
     /// is forwarded directly to the underlying `ChatComposer`.
     pub(crate) fn set_token_usage(
@@ -403,8 +466,14 @@ impl BottomPane {
         }
     }
 
-    pub(crate) fn on_file_search_result(&mut self, query: String, matches: Vec<FileMatch>) {
-        self.composer.on_file_search_result(query, matches);
+    pub(crate) fn on_file_search_result(
+        &mut self,
+        query: String,
+        matches: Vec<FileMatch>,
+        content_matches: Vec<ContentMatch>,
+    ) {
+        self.composer
+            .on_file_search_result(query, matches, content_matches);
         self.request_redraw();
     }
 
@@ -429,7 +498,17 @@ impl BottomPane {
 
 impl WidgetRef for &BottomPane {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
-        let [status_area, content] = self.layout(area);
+        let [status_area, content, bottom] = self.layout(area);
+
+        // The status bar takes the padding row under the composer.
+        if let Some(info) = &self.status_bar {
+            status_bar::status_bar_line(
+                info,
+                self.queued_user_messages.len(),
+                self.composer.context_left_percent(),
+            )
+            .render_ref(bottom, buf);
+        }
 
         // When a modal view is active, it owns the whole content area.
         if let Some(view) = &self.active_view {
@@ -460,6 +539,7 @@ mod tests {
             id: "1".to_string(),
             command: vec!["echo".into(), "ok".into()],
             reason: None,
+            matched_rule: None,
         }
     }
 
@@ -609,6 +689,50 @@ mod tests {
         );
     }
 
+    #[test]
+    fn status_bar_fills_bottom_row_and_counts_queued_messages() {
+        let (tx_raw, _rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx_raw);
+        let mut pane = BottomPane::new(BottomPaneParams {
+            app_event_tx: tx,
+            frame_requester: crate::tui::FrameRequester::test_dummy(),
+            has_input_focus: true,
+            enhanced_keys_supported: false,
+            placeholder_text: "Ask Codex to do anything".to_string(),
+        });
+        pane.set_status_bar(Some(StatusBarInfo {
+            model: "gpt-5".to_string(),
+            provider: "openai".to_string(),
+            approval_policy: codex_core::protocol::AskForApproval::Never,
+            sandbox_policy: codex_core::protocol::SandboxPolicy::new_read_only_policy(),
+            git: None,
+        }));
+        pane.set_task_running(true);
+        pane.set_queued_user_messages(vec!["next".to_string()], None);
+
+        let width = 80;
+        let height = pane.desired_height(width);
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        (&pane).render_ref(area, &mut buf);
+
+        let bottom: String = (0..width)
+            .map(|x| buf[(x, height - 1)].symbol().to_string())
+            .collect();
+        assert_eq!(
+            bottom.trim_end(),
+            " gpt-5 (openai)  │  never · read-only  │  1 queued"
+        );
+
+        pane.set_status_bar(None);
+        let mut buf = Buffer::empty(area);
+        (&pane).render_ref(area, &mut buf);
+        let bottom: String = (0..width)
+            .map(|x| buf[(x, height - 1)].symbol().to_string())
+            .collect();
+        assert_eq!(bottom.trim(), "");
+    }
+
     #[test]
     fn bottom_padding_present_with_status_above_composer() {
         let (tx_raw, _rx) = unbounded_channel::<AppEvent>();
@@ -0,0 +1,136 @@
+//! The one-line status bar under the composer.
+//!
+//! The chat widget supplies the session settings and git state, which only
+//! change on events it already handles; the bottom pane adds the queue length
+//! and context left it tracks itself.
+
+use codex_core::git_info::BranchStatus;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::SandboxPolicy;
+use ratatui::style::Color;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+
+/// Cells in the context gauge.
+const GAUGE_WIDTH: usize = 10;
+
+/// The part of the status bar that comes from the session and the repository.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct StatusBarInfo {
+    pub(crate) model: String,
+    pub(crate) provider: String,
+    pub(crate) approval_policy: AskForApproval,
+    pub(crate) sandbox_policy: SandboxPolicy,
+    /// `None` outside a git repository, or until the first `git status`
+    /// returns.
+    pub(crate) git: Option<BranchStatus>,
+}
+
+pub(crate) fn status_bar_line(
+    info: &StatusBarInfo,
+    queued_messages: usize,
+    context_left_percent: Option<u8>,
+) -> Line<'static> {
+    let mut segments: Vec<Vec<Span<'static>>> = vec![
+        vec![
+            info.model.clone().bold(),
+            format!(" ({})", info.provider).dim(),
+        ],
+        vec![
+            info.approval_policy.to_string().into(),
+            " · ".dim(),
+            sandbox_label(&info.sandbox_policy).into(),
+        ],
+    ];
+    if let Some(git) = &info.git {
+        let branch = git.branch.clone().unwrap_or_else(|| "detached".to_string());
+        let mut segment = vec!["⎇ ".dim(), branch.cyan()];
+        if git.dirty {
+            segment.push("*".bold());
+        }
+        segments.push(segment);
+    }
+    if queued_messages > 0 {
+        segments.push(vec![format!("{queued_messages} queued").magenta()]);
+    }
+    if let Some(percent) = context_left_percent {
+        segments.push(context_gauge(percent));
+    }
+
+    let mut spans: Vec<Span<'static>> = vec![" ".into()];
+    for (idx, segment) in segments.into_iter().enumerate() {
+        if idx > 0 {
+            spans.push("  │  ".dim());
+        }
+        spans.extend(segment);
+    }
+    Line::from(spans)
+}
+
+/// Short name of the sandbox, without the writable roots `/status` lists.
+fn sandbox_label(policy: &SandboxPolicy) -> &'static str {
+    match policy {
+        SandboxPolicy::DangerFullAccess { .. } => "full access",
+        SandboxPolicy::ReadOnly { .. } => "read-only",
+        SandboxPolicy::WorkspaceWrite {
+            network_access: true,
+            ..
+        } => "workspace-write + network",
+        SandboxPolicy::WorkspaceWrite { .. } => "workspace-write",
+    }
+}
+
+fn context_gauge(percent_left: u8) -> Vec<Span<'static>> {
+    let filled = (usize::from(percent_left) * GAUGE_WIDTH).div_ceil(100);
+    let color = match percent_left {
+        51.. => Color::Green,
+        21..=50 => Color::Cyan,
+        _ => Color::Red,
+    };
+    vec![
+        "context ".dim(),
+        Span::styled("▰".repeat(filled), Style::default().fg(color)),
+        "▱".repeat(GAUGE_WIDTH - filled).dim(),
+        format!(" {percent_left}% left").dim(),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn text(line: &Line<'_>) -> String {
+        line.spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn shows_settings_branch_queue_and_context() {
+        let info = StatusBarInfo {
+            model: "gpt-5".to_string(),
+            provider: "openai".to_string(),
+            approval_policy: AskForApproval::OnRequest,
+            sandbox_policy: SandboxPolicy::new_workspace_write_policy(),
+            git: Some(BranchStatus {
+                branch: Some("main".to_string()),
+                dirty: true,
+            }),
+        };
+        assert_eq!(
+            text(&status_bar_line(&info, 2, Some(64))),
+            " gpt-5 (openai)  │  on-request · workspace-write  │  ⎇ main*  │  2 queued  │  context ▰▰▰▰▰▰▰▱▱▱ 64% left"
+        );
+
+        let info = StatusBarInfo {
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            git: None,
+            ..info
+        };
+        assert_eq!(
+            text(&status_bar_line(&info, 0, None)),
+            " gpt-5 (openai)  │  on-request · read-only"
+        );
+    }
+}
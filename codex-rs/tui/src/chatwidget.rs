@@ -1,9 +1,23 @@
+use std::collections::BTreeSet;
 use std::collections::HashMap;
 use std::collections::VecDeque;
+use std::path::Path;
 use std::path::PathBuf;
+use std::str::FromStr;
 use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
 
+use chrono::Local;
 use codex_core::config::Config;
+use codex_core::config::load_config_as_toml_with_cli_overrides;
+use codex_core::config::validate_config;
+use codex_core::config_profile::ConfigProfile;
+use codex_core::config_types::CustomSlashCommand;
+use codex_core::config_types::NotificationEvent;
+use codex_core::git_info::BranchStatus;
+use codex_core::git_info::branch_status;
+use codex_core::model_family::find_family_for_model;
 use codex_core::protocol::AgentMessageDeltaEvent;
 use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningDeltaEvent;
@@ -12,6 +26,12 @@ use codex_core::protocol::AgentReasoningRawContentDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::BackgroundEventEvent;
+use codex_core::protocol::BackgroundTaskInfo;
+use codex_core::protocol::ContainerImagePullEvent;
+use codex_core::protocol::ContainerImagePullStatus;
+use codex_core::protocol::ContextCompactedEvent;
+use codex_core::protocol::DegradedModeEvent;
+use codex_core::protocol::DegradedModeKind;
 use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
@@ -19,16 +39,20 @@ use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::InputItem;
+use codex_core::protocol::ListBackgroundTasksResponseEvent;
 use codex_core::protocol::McpListToolsResponseEvent;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
+use codex_core::protocol::ProfileSwitchedEvent;
 use codex_core::protocol::StreamErrorEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TokenUsage;
+use codex_core::protocol::TokenUsageBreakdown;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnDiffEvent;
+use codex_core::protocol::TurnRevertedEvent;
 use codex_core::protocol::WebSearchBeginEvent;
 use codex_protocol::parse_command::ParsedCommand;
 use crossterm::event::KeyCode;
@@ -43,6 +67,7 @@ use ratatui::layout::Rect;
 use ratatui::widgets::Widget;
 use ratatui::widgets::WidgetRef;
 use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::oneshot;
 use tracing::debug;
 
 use crate::app_event::AppEvent;
@@ -50,19 +75,36 @@ use crate::app_event_sender::AppEventSender;
 use crate::bottom_pane::BottomPane;
 use crate::bottom_pane::BottomPaneParams;
 use crate::bottom_pane::CancellationEvent;
+use crate::bottom_pane::CustomCommandInfo;
 use crate::bottom_pane::InputResult;
 use crate::bottom_pane::SelectionAction;
 use crate::bottom_pane::SelectionItem;
+use crate::bottom_pane::StatusBarInfo;
+use crate::diff_render::create_file_diffs;
+use crate::exec_command::strip_bash_lc_and_escape;
+use crate::file_watcher::FileWatcher;
 use crate::get_git_diff::get_git_diff;
+use crate::get_git_diff::get_git_diff_for_paths;
 use crate::history_cell;
 use crate::history_cell::CommandOutput;
 use crate::history_cell::ExecCell;
 use crate::history_cell::HistoryCell;
 use crate::history_cell::PatchEventType;
+use crate::history_cell::TurnTokenUsage;
+use crate::session_export::parse_export_args;
+use crate::shell_passthrough::display_command;
+use crate::shell_passthrough::parse_shell_command;
+use crate::shell_passthrough::share_text;
+use crate::shell_passthrough::spawn_shell_command;
 use crate::slash_command::SlashCommand;
+use crate::slash_command::expand_prompt_template;
+use crate::slash_command::parse_slash_command;
+use crate::terminal_notification;
 use crate::tui::FrameRequester;
 // streaming internals are provided by crate::streaming and crate::markdown_stream
 use crate::user_approval_widget::ApprovalRequest;
+use crate::warning_banners::WarningBanners;
+use crate::working_pane::WorkingPane;
 mod interrupts;
 use self::interrupts::InterruptManager;
 mod agent;
@@ -78,6 +120,8 @@ use codex_core::ConversationManager;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol_config_types::ReasoningEffort as ReasoningEffortConfig;
+use codex_core::protocol_config_types::ReasoningSummary as ReasoningSummaryConfig;
+use codex_file_search::ContentMatch;
 use codex_file_search::FileMatch;
 use tempfile::TempDir;
 use uuid::Uuid;
@@ -85,9 +129,15 @@ use uuid::Uuid;
 // Track information about an in-flight exec command.
 struct RunningCommand {
     command: Vec<String>,
+    cwd: PathBuf,
     parsed_cmd: Vec<ParsedCommand>,
 }
 
+struct FailedCommand {
+    command: Vec<String>,
+    cwd: PathBuf,
+}
+
 pub(crate) struct ChatWidget {
     app_event_tx: AppEventSender,
     codex_op_tx: UnboundedSender<Op>,
@@ -97,6 +147,11 @@ pub(crate) struct ChatWidget {
     initial_user_message: Option<UserMessage>,
     total_token_usage: TokenUsage,
     last_token_usage: TokenUsage,
+    // Estimated attribution of the tokens used this session and this turn
+    total_usage_breakdown: TokenUsageBreakdown,
+    turn_usage_breakdown: TokenUsageBreakdown,
+    // Tokens reported for each turn, for the /usage chart
+    turn_token_usage: Vec<TurnTokenUsage>,
     // Stream lifecycle controller
     stream: StreamController,
     running_commands: HashMap<String, RunningCommand>,
@@ -115,13 +170,42 @@ pub(crate) struct ChatWidget {
     last_history_was_exec: bool,
     // User messages queued while a turn is in progress
     queued_user_messages: VecDeque<UserMessage>,
+    // Slot in the queue of the queued message taken into the composer (Alt+↑)
+    editing_queued_message: Option<usize>,
+    /// Branch and dirty state shown in the status bar.
+    git_status: Option<BranchStatus>,
+    // Unified diff of this turn's changes, shown in the working pane
+    turn_diff: Option<String>,
     // Hold temp dirs for editor diff files so they are not GC'd immediately
     open_diff_temps: Vec<TempDir>,
+    // Most recent command that exited non-zero; Ctrl+R opens it for editing
+    last_failed_command: Option<FailedCommand>,
+    // The `!command` running in the user's shell; sending on it kills the command
+    shell_command_cancel: Option<oneshot::Sender<()>>,
+    // Message sharing the output of the last `!command`; Ctrl+S puts it in the composer
+    shell_output_to_share: Option<String>,
+    // Watches the workspace for files changed outside the agent (`tui.watch_files`)
+    _file_watcher: Option<FileWatcher>,
+    // Files changed outside the agent since the last message was sent
+    external_changes: BTreeSet<PathBuf>,
+    // Diff of `external_changes` the user chose (Ctrl+G) to send with the next message
+    external_changes_context: Option<String>,
+    // When the last task stopped; file changes seen before then were the agent's
+    task_stopped_at: Option<Instant>,
+    // Final agent messages of this session; Ctrl+O opens them in a reader
+    agent_messages: Vec<String>,
+    // Degraded-mode warnings shown above the active cell until dismissed
+    warning_banners: WarningBanners,
+    // `--run` commands still to run; each waits for the turn before it
+    startup_commands: VecDeque<String>,
 }
 
 struct UserMessage {
     text: String,
     image_paths: Vec<PathBuf>,
+    /// Set when a custom slash command pins the model or profile for the
+    /// turn this message starts.
+    turn_settings: Option<TurnSettings>,
 }
 
 impl From<String> for UserMessage {
@@ -129,18 +213,111 @@ impl From<String> for UserMessage {
         Self {
             text,
             image_paths: Vec::new(),
+            turn_settings: None,
         }
     }
 }
 
+/// Settings for a single turn that differ from the session's.
+struct TurnSettings {
+    model: String,
+    effort: ReasoningEffortConfig,
+    summary: ReasoningSummaryConfig,
+    approval_policy: AskForApproval,
+    sandbox_policy: SandboxPolicy,
+}
+
+fn start_file_watcher(config: &Config, app_event_tx: &AppEventSender) -> Option<FileWatcher> {
+    if !config.tui.watch_files {
+        return None;
+    }
+    FileWatcher::start(&config.cwd, app_event_tx.clone())
+        .inspect_err(|e| tracing::warn!("failed to watch {}: {e}", config.cwd.display()))
+        .ok()
+}
+
+/// The custom slash commands from `config` that the popup can offer: names
+/// of built-in commands and names that could not be typed are skipped.
+fn custom_commands(config: &Config) -> Vec<CustomCommandInfo> {
+    let mut commands: Vec<CustomCommandInfo> = config
+        .tui
+        .slash_commands
+        .iter()
+        .filter(|(name, _)| {
+            let valid = !name.is_empty()
+                && !name.contains(|c: char| c.is_whitespace() || c == '/')
+                && SlashCommand::from_str(name).is_err();
+            if !valid {
+                tracing::warn!("ignoring custom slash command `/{name}`");
+            }
+            valid
+        })
+        .map(|(name, command)| CustomCommandInfo {
+            name: name.clone(),
+            description: command
+                .description
+                .clone()
+                .unwrap_or_else(|| "custom command".to_string()),
+        })
+        .collect();
+    commands.sort_by(|a, b| a.name.cmp(&b.name));
+    commands
+}
+
+/// The settings `profile` sets, e.g. "model o3 · approval on-request".
+fn profile_summary(profile: &ConfigProfile) -> String {
+    let mut parts = Vec::new();
+    if let Some(model) = &profile.model {
+        parts.push(format!("model {model}"));
+    }
+    if let Some(provider) = &profile.model_provider {
+        parts.push(format!("provider {provider}"));
+    }
+    if let Some(effort) = profile.model_reasoning_effort {
+        parts.push(format!("reasoning {effort}"));
+    }
+    if let Some(approval) = profile.approval_policy {
+        parts.push(format!("approval {approval}"));
+    }
+    if let Some(sandbox) = profile.sandbox_mode {
+        parts.push(format!("sandbox {sandbox}"));
+    }
+    if parts.is_empty() {
+        "same settings as without a profile".to_string()
+    } else {
+        parts.join(" · ")
+    }
+}
+
 fn create_initial_user_message(text: String, image_paths: Vec<PathBuf>) -> Option<UserMessage> {
     if text.is_empty() && image_paths.is_empty() {
         None
     } else {
-        Some(UserMessage { text, image_paths })
+        Some(UserMessage {
+            text,
+            image_paths,
+            turn_settings: None,
+        })
     }
 }
 
+/// Banner pointing at `codex config doctor` when `config.toml` has problems.
+fn config_issues_banner(codex_home: &Path) -> Option<DegradedModeEvent> {
+    let issues = validate_config(codex_home);
+    let first = issues.first()?;
+    let message = match issues.len() {
+        1 => format!("{first}. Run `codex config doctor` for a fix."),
+        n => format!(
+            "{first}, and {} more. Run `codex config doctor` for fixes.",
+            n - 1
+        ),
+    };
+    Some(DegradedModeEvent {
+        kind: DegradedModeKind::ConfigInvalid,
+        message,
+    })
+}
+
 impl ChatWidget {
     fn flush_answer_stream_with_separator(&mut self) {
         let sink = AppEventHistorySink(self.app_event_tx.clone());
@@ -151,6 +328,8 @@ impl ChatWidget {
         self.bottom_pane
             .set_history_metadata(event.history_log_id, event.history_entry_count);
         self.session_id = Some(event.session_id);
+        self.refresh_status_bar();
+        self.refresh_git_status();
         self.add_to_history(history_cell::new_session_info(
             &self.config,
             event,
@@ -158,11 +337,17 @@ impl ChatWidget {
         ));
         if let Some(user_message) = self.initial_user_message.take() {
             self.submit_user_message(user_message);
+        } else {
+            self.run_startup_commands();
         }
         self.request_redraw();
     }
 
     fn on_agent_message(&mut self, message: String) {
+        if !message.trim().is_empty() {
+            self.agent_messages.push(message.clone());
+            self.bottom_pane.set_read_message_hint(true);
+        }
         let sink = AppEventHistorySink(self.app_event_tx.clone());
         let finished = self.stream.apply_final_answer(&message, &sink);
         self.handle_if_stream_finished(finished);
@@ -215,6 +400,12 @@ impl ChatWidget {
         self.bottom_pane.clear_ctrl_c_quit_hint();
         self.bottom_pane.set_task_running(true);
         self.stream.reset_headers_for_new_turn();
+        self.turn_usage_breakdown = TokenUsageBreakdown::default();
+        self.turn_token_usage.push(TurnTokenUsage {
+            model: self.config.model.clone(),
+            usage: TokenUsage::default(),
+        });
+        self.turn_diff = None;
         self.full_reasoning_buffer.clear();
         self.reasoning_buffer.clear();
         self.request_redraw();
@@ -229,15 +420,46 @@ impl ChatWidget {
         }
         // Mark task stopped and request redraw now that all content is in history.
         self.bottom_pane.set_task_running(false);
+        self.task_stopped_at = Some(Instant::now());
         self.running_commands.clear();
+        self.refresh_git_status();
         self.request_redraw();
 
-        // If there is a queued user message, send exactly one now to begin the next turn.
-        self.maybe_send_next_queued_input();
+        // A queued message or startup command starts the next turn right
+        // away, so only notify when the agent is waiting for the user.
+        if self.queued_user_messages.is_empty() && self.startup_commands.is_empty() {
+            terminal_notification::notify(
+                &self.config.notifications,
+                NotificationEvent::AgentTurnComplete,
+            );
+        }
+
+        // If there is a queued user message, send exactly one now to begin the
+        // next turn; startup commands continue once the queue is empty.
+        if self.queued_user_messages.is_empty() && !self.startup_commands.is_empty() {
+            self.run_startup_commands();
+        } else {
+            self.maybe_send_next_queued_input();
+        }
+    }
+
+    fn on_token_usage_breakdown(&mut self, breakdown: TokenUsageBreakdown) {
+        self.total_usage_breakdown.add(&breakdown);
+        self.turn_usage_breakdown.add(&breakdown);
     }
 
     fn on_token_count(&mut self, token_usage: TokenUsage) {
         self.total_token_usage = add_token_usage(&self.total_token_usage, &token_usage);
+        // Usage reported outside a turn, e.g. by /compact, counts as a turn.
+        if self.turn_token_usage.is_empty() || !self.bottom_pane.is_task_running() {
+            self.turn_token_usage.push(TurnTokenUsage {
+                model: self.config.model.clone(),
+                usage: TokenUsage::default(),
+            });
+        }
+        if let Some(turn) = self.turn_token_usage.last_mut() {
+            turn.usage = add_token_usage(&turn.usage, &token_usage);
+        }
         self.last_token_usage = token_usage;
         self.bottom_pane.set_token_usage(
             self.total_token_usage.clone(),
@@ -252,6 +474,8 @@ impl ChatWidget {
         self.finalize_active_exec_cell_as_failed();
         self.add_to_history(history_cell::new_error_event(message));
         self.bottom_pane.set_task_running(false);
+        self.task_stopped_at = Some(Instant::now());
+        terminal_notification::notify(&self.config.notifications, NotificationEvent::Error);
         self.running_commands.clear();
         self.stream.clear_all();
         self.request_redraw();
@@ -260,6 +484,11 @@ impl ChatWidget {
         self.maybe_send_next_queued_input();
     }
 
+    fn on_degraded_mode(&mut self, ev: DegradedModeEvent) {
+        self.warning_banners.push(ev);
+        self.request_redraw();
+    }
+
     fn on_plan_update(&mut self, update: codex_core::plan_tool::UpdatePlanArgs) {
         self.add_to_history(history_cell::new_plan_update(update));
     }
@@ -282,6 +511,125 @@ impl ChatWidget {
         );
     }
 
+    /// Runs a `!command` in the user's shell. The bottom pane shows it as a
+    /// running task, so messages typed meanwhile are queued and Ctrl+C kills
+    /// it.
+    fn run_shell_command(&mut self, command: String) {
+        if self.bottom_pane.is_task_running() {
+            self.add_to_history(history_cell::new_error_event(
+                "Wait for the current task to finish before running a shell command.".to_string(),
+            ));
+            return;
+        }
+        self.flush_active_exec_cell();
+        self.active_exec_cell = Some(history_cell::new_active_exec_command(
+            display_command(&command),
+            Vec::new(),
+            !self.last_history_was_exec,
+        ));
+        self.shell_command_cancel = Some(spawn_shell_command(
+            command,
+            self.config.cwd.clone(),
+            self.app_event_tx.clone(),
+        ));
+        self.bottom_pane.set_task_running(true);
+        self.request_redraw();
+    }
+
+    pub(crate) fn on_shell_command_output(&mut self, chunk: &[u8]) {
+        if let Some(cell) = self.active_exec_cell.as_mut() {
+            cell.append_live_output(chunk);
+            self.request_redraw();
+        }
+    }
+
+    pub(crate) fn on_shell_command_finished(
+        &mut self,
+        command: String,
+        output: CommandOutput,
+        duration: Duration,
+    ) {
+        self.shell_command_cancel = None;
+        self.active_exec_cell = None;
+        let include_header = !self.last_history_was_exec;
+        self.shell_output_to_share = Some(share_text(&command, &output, duration));
+        self.add_to_history(history_cell::new_completed_exec_command(
+            display_command(&command),
+            Vec::new(),
+            output,
+            include_header,
+            duration,
+        ));
+        self.last_history_was_exec = true;
+        self.bottom_pane.set_share_output_hint(true);
+        self.bottom_pane.set_task_running(false);
+        self.task_stopped_at = Some(Instant::now());
+        self.refresh_git_status();
+        self.maybe_send_next_queued_input();
+    }
+
+    /// A batch of files changed in the workspace. Batches that started while
+    /// a task was running are the agent's own edits (or a `!command`'s) and
+    /// are not reported.
+    pub(crate) fn on_external_file_changes(&mut self, paths: Vec<PathBuf>, since: Instant) {
+        if self.bottom_pane.is_task_running()
+            || self.task_stopped_at.is_some_and(|stopped| since < stopped)
+        {
+            return;
+        }
+        // A diff taken before these changes would be stale.
+        self.external_changes_context = None;
+        self.add_to_history(history_cell::new_external_file_changes(&paths));
+        self.external_changes.extend(paths);
+        self.bottom_pane.set_external_changes_hint(true);
+    }
+
+    /// Ctrl+G: takes the diff of the externally changed files so it is sent
+    /// with the next message.
+    fn share_external_changes(&mut self) {
+        let paths: Vec<PathBuf> = self.external_changes.iter().cloned().collect();
+        let cwd = self.config.cwd.clone();
+        let tx = self.app_event_tx.clone();
+        tokio::spawn(async move {
+            let diff = match get_git_diff_for_paths(&cwd, &paths).await {
+                Ok(diff) => diff,
+                Err(e) => {
+                    tracing::warn!("failed to diff externally changed files: {e}");
+                    String::new()
+                }
+            };
+            tx.send(AppEvent::ExternalChangesDiff(diff));
+        });
+    }
+
+    pub(crate) fn on_external_changes_diff(&mut self, diff: String) {
+        if self.external_changes.is_empty() {
+            // The message it was meant for has already been sent.
+            return;
+        }
+        let files: Vec<String> = self
+            .external_changes
+            .iter()
+            .map(|path| format!("- {}", path.display()))
+            .collect();
+        let mut context = format!(
+            "These files were changed outside of your session since my last message; \
+             re-read them instead of relying on earlier contents:\n{}",
+            files.join("\n")
+        );
+        if !diff.trim().is_empty() {
+            context.push_str(&format!(
+                "\n\nTheir diff against HEAD:\n\n```diff\n{}\n```",
+                diff.trim_end()
+            ));
+        }
+        self.external_changes_context = Some(context);
+        self.bottom_pane.set_external_changes_hint(false);
+        self.add_to_history(history_cell::new_external_changes_attached(
+            self.external_changes.len(),
+        ));
+    }
+
     fn on_exec_command_begin(&mut self, ev: ExecCommandBeginEvent) {
         self.flush_answer_stream_with_separator();
         let ev2 = ev.clone();
@@ -290,18 +638,31 @@ impl ChatWidget {
 
     fn on_exec_command_output_delta(
         &mut self,
-        _ev: codex_core::protocol::ExecCommandOutputDeltaEvent,
+        ev: codex_core::protocol::ExecCommandOutputDeltaEvent,
     ) {
-        // TODO: Handle streaming exec output if/when implemented
+        // Output for a command whose begin event is still deferred has no
+        // cell to show in; the end event carries the full output anyway.
+        if !self.running_commands.contains_key(&ev.call_id) {
+            return;
+        }
+        if let Some(cell) = self.active_exec_cell.as_mut() {
+            cell.append_live_output(&ev.chunk);
+            self.request_redraw();
+        }
     }
 
     fn on_patch_apply_begin(&mut self, event: PatchApplyBeginEvent) {
         let changes_for_editor = event.changes.clone();
+        // Files the agent edits are likely to be mentioned next.
+        self.app_event_tx.send(AppEvent::RecordRecentFiles(
+            event.changes.keys().cloned().collect(),
+        ));
         self.add_to_history(history_cell::new_patch_event(
             PatchEventType::ApplyBegin {
                 auto_approved: event.auto_approved,
             },
             event.changes,
+            &self.config.tui.syntax_theme,
         ));
         // If the patch was auto-approved, proactively open editor diffs for all changes
         // so users can quickly review what was applied.
@@ -338,6 +699,30 @@ impl ChatWidget {
         self.add_to_history(history_cell::new_web_search_call(ev.query));
     }
 
+    fn on_container_image_pull(&mut self, ev: ContainerImagePullEvent) {
+        let ContainerImagePullEvent { image, status, .. } = ev;
+        match status {
+            ContainerImagePullStatus::Started => {
+                self.flush_answer_stream_with_separator();
+                self.add_to_history(history_cell::new_container_image_pull(image));
+                self.bottom_pane
+                    .update_status_header("Pulling image".to_string());
+            }
+            ContainerImagePullStatus::Progress { message } => {
+                debug!("ContainerImagePull: {message}");
+            }
+            ContainerImagePullStatus::Completed => {
+                self.bottom_pane.update_status_header("Working".to_string());
+            }
+            ContainerImagePullStatus::Failed { message } => {
+                self.add_to_history(history_cell::new_error_event(format!(
+                    "Failed to pull {image}: {message}"
+                )));
+            }
+        }
+        self.request_redraw();
+    }
+
     fn on_get_history_entry_response(
         &mut self,
         event: codex_core::protocol::GetHistoryEntryResponseEvent,
@@ -357,6 +742,34 @@ impl ChatWidget {
 
     fn on_turn_diff(&mut self, unified_diff: String) {
         debug!("TurnDiffEvent: {unified_diff}");
+        self.turn_diff = Some(unified_diff);
+        if self.config.tui.split_pane {
+            self.request_redraw();
+        }
+    }
+
+    fn on_turn_reverted(&mut self, ev: TurnRevertedEvent) {
+        self.add_to_history(history_cell::new_turn_reverted(&ev.paths));
+    }
+
+    fn on_profile_switched(&mut self, ev: ProfileSwitchedEvent) {
+        self.add_to_history(history_cell::new_profile_switched(&ev));
+        if let Some(provider) = self.config.model_providers.get(&ev.model_provider_id) {
+            self.config.model_provider = provider.clone();
+        }
+        self.config.active_profile = Some(ev.profile);
+        self.config.model_family =
+            find_family_for_model(&ev.model).unwrap_or_else(|| self.config.model_family.clone());
+        self.config.model = ev.model;
+        self.config.model_provider_id = ev.model_provider_id;
+        self.config.model_reasoning_effort = ev.reasoning_effort;
+        self.config.approval_policy = ev.approval_policy;
+        self.config.sandbox_policy = ev.sandbox_policy;
+        self.refresh_status_bar();
+    }
+
+    fn on_context_compacted(&mut self, ev: ContextCompactedEvent) {
+        self.add_to_history(history_cell::new_context_compacted(ev.summary_tokens));
     }
 
     fn on_background_event(&mut self, message: String) {
@@ -406,6 +819,7 @@ impl ChatWidget {
         if finished {
             if self.task_complete_pending {
                 self.bottom_pane.set_task_running(false);
+                self.task_stopped_at = Some(Instant::now());
                 self.task_complete_pending = false;
             }
             // A completed stream indicates non-exec content was just inserted.
@@ -426,7 +840,16 @@ impl ChatWidget {
     pub(crate) fn handle_exec_end_now(&mut self, ev: ExecCommandEndEvent) {
         let running = self.running_commands.remove(&ev.call_id);
         let (command, parsed) = match running {
-            Some(rc) => (rc.command, rc.parsed_cmd),
+            Some(rc) => {
+                if ev.exit_code != 0 {
+                    self.last_failed_command = Some(FailedCommand {
+                        command: rc.command.clone(),
+                        cwd: rc.cwd,
+                    });
+                    self.bottom_pane.set_rerun_failed_hint(true);
+                }
+                (rc.command, rc.parsed_cmd)
+            }
             None => (vec![ev.call_id.clone()], Vec::new()),
         };
         self.pending_exec_completions.push((
@@ -437,6 +860,7 @@ impl ChatWidget {
                 stdout: ev.stdout.clone(),
                 stderr: ev.stderr.clone(),
                 formatted_output: ev.formatted_output.clone(),
+                sandbox_denials: ev.sandbox_denials.clone(),
             },
         ));
 
@@ -476,8 +900,13 @@ impl ChatWidget {
             id,
             command: ev.command,
             reason: ev.reason,
+            matched_rule: ev.matched_rule,
         };
         self.bottom_pane.push_approval_request(request);
+        terminal_notification::notify(
+            &self.config.notifications,
+            NotificationEvent::ApprovalRequested,
+        );
         self.request_redraw();
     }
 
@@ -490,14 +919,20 @@ impl ChatWidget {
         self.add_to_history(history_cell::new_patch_event(
             PatchEventType::ApprovalRequest,
             ev.changes.clone(),
+            &self.config.tui.syntax_theme,
         ));
 
         let request = ApprovalRequest::ApplyPatch {
             id,
             reason: ev.reason,
             grant_root: ev.grant_root,
+            files: create_file_diffs(&ev.changes, &self.config.tui.syntax_theme),
         };
         self.bottom_pane.push_approval_request(request);
+        terminal_notification::notify(
+            &self.config.notifications,
+            NotificationEvent::ApprovalRequested,
+        );
         self.request_redraw();
 
         // Open editor diffs for all proposed changes so users can review
@@ -532,10 +967,16 @@ impl ChatWidget {
             .status();
         if precheck.is_err() {
             let msg = match editor_cmd {
-                "code" => "Could not open editor diffs: VS Code CLI 'code' not found. In VS Code, run: Shell Command: Install 'code' command in PATH.",
-                "code-insiders" => "Could not open editor diffs: VS Code Insiders CLI 'code-insiders' not found. Install the CLI or switch file_opener.",
+                "code" => {
+                    "Could not open editor diffs: VS Code CLI 'code' not found. In VS Code, run: Shell Command: Install 'code' command in PATH."
+                }
+                "code-insiders" => {
+                    "Could not open editor diffs: VS Code Insiders CLI 'code-insiders' not found. Install the CLI or switch file_opener."
+                }
                 other => {
-                    let hint = format!("Could not open editor diffs: '{other}' not found on PATH. Ensure the editor CLI is installed or set file_opener accordingly.");
+                    let hint = format!(
+                        "Could not open editor diffs: '{other}' not found on PATH. Ensure the editor CLI is installed or set file_opener accordingly."
+                    );
                     // As we need a &str, temporarily store in a local String and then use as_str
                     // within a separate binding.
                     // However, we can't return a reference to a temporary, so below we just
@@ -546,7 +987,10 @@ impl ChatWidget {
                 }
             };
             if msg == "__OTHER_EDITOR__" {
-                let hint = format!("Could not open editor diffs: '{}' not found on PATH. Ensure the editor CLI is installed or set file_opener accordingly.", editor_cmd);
+                let hint = format!(
+                    "Could not open editor diffs: '{}' not found on PATH. Ensure the editor CLI is installed or set file_opener accordingly.",
+                    editor_cmd
+                );
                 self.add_to_history(history_cell::new_stream_error_event(hint));
             } else {
                 self.add_to_history(history_cell::new_stream_error_event(msg.to_string()));
@@ -612,38 +1056,38 @@ impl ChatWidget {
             let b = before_path_for_temp.clone();
             let a = after_path_for_temp.clone();
             if tokio::runtime::Handle::try_current().is_ok() {
-            tokio::spawn(async move {
-                let status = tokio::process::Command::new(&cmd)
-                    .arg("--diff")
-                    .arg(&b)
-                    .arg(&a)
-                    .status()
-                    .await;
-                if status.is_err() {
-                    #[cfg(target_os = "macos")]
-                    {
-                        // Best-effort macOS fallback for VS Code variants only.
-                        let bundle = if cmd == "code-insiders" {
-                            Some("com.microsoft.VSCodeInsiders")
-                        } else if cmd == "code" {
-                            Some("com.microsoft.VSCode")
-                        } else {
-                            None
-                        };
-                        if let Some(bundle) = bundle {
-                            let _ = tokio::process::Command::new("open")
-                                .arg("-b")
-                                .arg(bundle)
-                                .arg("--args")
-                                .arg("--diff")
-                                .arg(&b)
-                                .arg(&a)
-                                .status()
-                                .await;
+                tokio::spawn(async move {
+                    let status = tokio::process::Command::new(&cmd)
+                        .arg("--diff")
+                        .arg(&b)
+                        .arg(&a)
+                        .status()
+                        .await;
+                    if status.is_err() {
+                        #[cfg(target_os = "macos")]
+                        {
+                            // Best-effort macOS fallback for VS Code variants only.
+                            let bundle = if cmd == "code-insiders" {
+                                Some("com.microsoft.VSCodeInsiders")
+                            } else if cmd == "code" {
+                                Some("com.microsoft.VSCode")
+                            } else {
+                                None
+                            };
+                            if let Some(bundle) = bundle {
+                                let _ = tokio::process::Command::new("open")
+                                    .arg("-b")
+                                    .arg(bundle)
+                                    .arg("--args")
+                                    .arg("--diff")
+                                    .arg(&b)
+                                    .arg(&a)
+                                    .status()
+                                    .await;
+                            }
                         }
                     }
-                }
-            });
+                });
             }
         }
     }
@@ -654,6 +1098,7 @@ impl ChatWidget {
             ev.call_id.clone(),
             RunningCommand {
                 command: ev.command.clone(),
+                cwd: ev.cwd.clone(),
                 parsed_cmd: ev.parsed_cmd.clone(),
             },
         );
@@ -694,18 +1139,44 @@ impl ChatWidget {
         ));
     }
 
-    fn layout_areas(&self, area: Rect) -> [Rect; 2] {
+    fn layout_areas(&self, area: Rect) -> [Rect; 3] {
         Layout::vertical([
-            Constraint::Max(
-                self.active_exec_cell
-                    .as_ref()
-                    .map_or(0, |c| c.desired_height(area.width)),
-            ),
+            Constraint::Max(self.warning_banners.desired_height(area.width)),
+            Constraint::Max(self.active_area_height(area.width)),
             Constraint::Min(self.bottom_pane.desired_height(area.width)),
         ])
         .areas(area)
     }
 
+    /// Height above the composer for the running command, or for the working
+    /// pane in the split layout.
+    fn active_area_height(&self, width: u16) -> u16 {
+        if self.config.tui.split_pane {
+            self.config.tui.working_pane_height.clamp(
+                crate::working_pane::MIN_HEIGHT,
+                crate::working_pane::MAX_HEIGHT,
+            )
+        } else {
+            self.active_exec_cell
+                .as_ref()
+                .map_or(0, |c| c.desired_height(width))
+        }
+    }
+
+    fn resize_working_pane(&mut self, taller: bool) {
+        let height = self.config.tui.working_pane_height;
+        let height = if taller {
+            height.saturating_add(2)
+        } else {
+            height.saturating_sub(2)
+        };
+        self.config.tui.working_pane_height = height.clamp(
+            crate::working_pane::MIN_HEIGHT,
+            crate::working_pane::MAX_HEIGHT,
+        );
+        self.request_redraw();
+    }
+
     pub(crate) fn new(
         config: Config,
         conversation_manager: Arc<ConversationManager>,
@@ -718,18 +1189,25 @@ impl ChatWidget {
         let mut rng = rand::rng();
         let placeholder = EXAMPLE_PROMPTS[rng.random_range(0..EXAMPLE_PROMPTS.len())].to_string();
         let codex_op_tx = spawn_agent(config.clone(), app_event_tx.clone(), conversation_manager);
+        let mut warning_banners = WarningBanners::default();
+        if let Some(banner) = config_issues_banner(&config.codex_home) {
+            warning_banners.push(banner);
+        }
+        let mut bottom_pane = BottomPane::new(BottomPaneParams {
+            frame_requester: frame_requester.clone(),
+            app_event_tx: app_event_tx.clone(),
+            has_input_focus: true,
+            enhanced_keys_supported,
+            placeholder_text: placeholder,
+        });
+        bottom_pane.set_custom_commands(custom_commands(&config));
+        let file_watcher = start_file_watcher(&config, &app_event_tx);
 
         Self {
             app_event_tx: app_event_tx.clone(),
             frame_requester: frame_requester.clone(),
             codex_op_tx,
-            bottom_pane: BottomPane::new(BottomPaneParams {
-                frame_requester,
-                app_event_tx,
-                has_input_focus: true,
-                enhanced_keys_supported,
-                placeholder_text: placeholder,
-            }),
+            bottom_pane,
             active_exec_cell: None,
             config: config.clone(),
             initial_user_message: create_initial_user_message(
@@ -738,6 +1216,9 @@ impl ChatWidget {
             ),
             total_token_usage: TokenUsage::default(),
             last_token_usage: TokenUsage::default(),
+            total_usage_breakdown: TokenUsageBreakdown::default(),
+            turn_usage_breakdown: TokenUsageBreakdown::default(),
+            turn_token_usage: Vec::new(),
             stream: StreamController::new(config),
             running_commands: HashMap::new(),
             pending_exec_completions: Vec::new(),
@@ -748,8 +1229,21 @@ impl ChatWidget {
             session_id: None,
             last_history_was_exec: false,
             queued_user_messages: VecDeque::new(),
+            editing_queued_message: None,
+            git_status: None,
+            turn_diff: None,
             show_welcome_banner: true,
             open_diff_temps: Vec::new(),
+            last_failed_command: None,
+            shell_command_cancel: None,
+            shell_output_to_share: None,
+            _file_watcher: file_watcher,
+            external_changes: BTreeSet::new(),
+            external_changes_context: None,
+            task_stopped_at: None,
+            agent_messages: Vec::new(),
+            warning_banners,
+            startup_commands: VecDeque::new(),
         }
     }
 
@@ -767,23 +1261,29 @@ impl ChatWidget {
 
         let codex_op_tx =
             spawn_agent_from_existing(conversation, session_configured, app_event_tx.clone());
+        let mut bottom_pane = BottomPane::new(BottomPaneParams {
+            frame_requester: frame_requester.clone(),
+            app_event_tx: app_event_tx.clone(),
+            has_input_focus: true,
+            enhanced_keys_supported,
+            placeholder_text: placeholder,
+        });
+        bottom_pane.set_custom_commands(custom_commands(&config));
+        let file_watcher = start_file_watcher(&config, &app_event_tx);
 
         Self {
             app_event_tx: app_event_tx.clone(),
             frame_requester: frame_requester.clone(),
             codex_op_tx,
-            bottom_pane: BottomPane::new(BottomPaneParams {
-                frame_requester,
-                app_event_tx,
-                has_input_focus: true,
-                enhanced_keys_supported,
-                placeholder_text: placeholder,
-            }),
+            bottom_pane,
             active_exec_cell: None,
             config: config.clone(),
             initial_user_message: None,
             total_token_usage: TokenUsage::default(),
             last_token_usage: TokenUsage::default(),
+            total_usage_breakdown: TokenUsageBreakdown::default(),
+            turn_usage_breakdown: TokenUsageBreakdown::default(),
+            turn_token_usage: Vec::new(),
             stream: StreamController::new(config),
             running_commands: HashMap::new(),
             pending_exec_completions: Vec::new(),
@@ -794,17 +1294,28 @@ impl ChatWidget {
             session_id: None,
             last_history_was_exec: false,
             queued_user_messages: VecDeque::new(),
+            editing_queued_message: None,
+            git_status: None,
+            turn_diff: None,
             show_welcome_banner: false,
             open_diff_temps: Vec::new(),
+            last_failed_command: None,
+            shell_command_cancel: None,
+            shell_output_to_share: None,
+            _file_watcher: file_watcher,
+            external_changes: BTreeSet::new(),
+            external_changes_context: None,
+            task_stopped_at: None,
+            agent_messages: Vec::new(),
+            warning_banners: WarningBanners::default(),
+            startup_commands: VecDeque::new(),
         }
     }
 
     pub fn desired_height(&self, width: u16) -> u16 {
-        self.bottom_pane.desired_height(width)
-            + self
-                .active_exec_cell
-                .as_ref()
-                .map_or(0, |c| c.desired_height(width))
+        self.warning_banners.desired_height(width)
+            + self.bottom_pane.desired_height(width)
+            + self.active_area_height(width)
     }
 
     pub(crate) fn handle_key_event(&mut self, key_event: KeyEvent) {
@@ -826,35 +1337,92 @@ impl ChatWidget {
 
         match key_event {
             KeyEvent {
-                code: KeyCode::Up,
-                modifiers: KeyModifiers::ALT,
+                code: KeyCode::Char('x'),
+                modifiers: KeyModifiers::CONTROL,
+                kind: KeyEventKind::Press,
+                ..
+            } if !self.warning_banners.is_empty() => {
+                self.warning_banners.dismiss_all();
+                self.request_redraw();
+            }
+            KeyEvent {
+                code: KeyCode::Char('r'),
+                modifiers: KeyModifiers::CONTROL,
                 kind: KeyEventKind::Press,
                 ..
-            } if !self.queued_user_messages.is_empty() => {
-                // Prefer the most recently queued item.
-                if let Some(user_message) = self.queued_user_messages.pop_back() {
-                    self.bottom_pane.set_composer_text(user_message.text);
-                    self.refresh_queued_user_messages();
-                    self.request_redraw();
+            } if self.last_failed_command.is_some() && !self.bottom_pane.is_task_running() => {
+                if let Some(FailedCommand { command, cwd }) = self.last_failed_command.take() {
+                    self.bottom_pane.set_rerun_failed_hint(false);
+                    self.bottom_pane
+                        .show_command_edit_view(strip_bash_lc_and_escape(&command), cwd);
                 }
             }
+            KeyEvent {
+                code: KeyCode::Char('s'),
+                modifiers: KeyModifiers::CONTROL,
+                kind: KeyEventKind::Press,
+                ..
+            } if self.shell_output_to_share.is_some() => {
+                if let Some(text) = self.shell_output_to_share.take() {
+                    self.bottom_pane.set_share_output_hint(false);
+                    self.bottom_pane.handle_paste(text);
+                }
+            }
+            KeyEvent {
+                code: KeyCode::Char('g'),
+                modifiers: KeyModifiers::CONTROL,
+                kind: KeyEventKind::Press,
+                ..
+            } if !self.external_changes.is_empty() && self.external_changes_context.is_none() => {
+                self.share_external_changes();
+            }
+            KeyEvent {
+                code: code @ (KeyCode::Up | KeyCode::Down),
+                modifiers: KeyModifiers::CONTROL,
+                kind: KeyEventKind::Press,
+                ..
+            } if self.config.tui.split_pane => {
+                self.resize_working_pane(code == KeyCode::Up);
+            }
+            KeyEvent {
+                code: code @ (KeyCode::Up | KeyCode::Down),
+                modifiers: KeyModifiers::ALT,
+                kind: KeyEventKind::Press,
+                ..
+            } if self.editing_queued_message.is_some()
+                || (code == KeyCode::Up && !self.queued_user_messages.is_empty()) =>
+            {
+                self.edit_adjacent_queued_message(code == KeyCode::Up);
+            }
             _ => {
                 match self.bottom_pane.handle_key_event(key_event) {
+                    InputResult::Submitted(text) if parse_shell_command(&text).is_some() => {
+                        if let Some(command) = parse_shell_command(&text) {
+                            self.run_shell_command(command.to_string());
+                        }
+                    }
                     InputResult::Submitted(text) => {
                         // If a task is running, queue the user input to be sent after the turn completes.
                         let user_message = UserMessage {
                             text,
                             image_paths: self.bottom_pane.take_recent_submission_images(),
+                            turn_settings: None,
                         };
+                        let editing_slot = self.editing_queued_message.take();
                         if self.bottom_pane.is_task_running() {
-                            self.queued_user_messages.push_back(user_message);
-                            self.refresh_queued_user_messages();
+                            self.queue_user_message(user_message, editing_slot);
                         } else {
                             self.submit_user_message(user_message);
+                            if editing_slot.is_some() {
+                                self.refresh_queued_user_messages();
+                            }
                         }
                     }
-                    InputResult::Command(cmd) => {
-                        self.dispatch_command(cmd);
+                    InputResult::Command(cmd, args) => {
+                        self.dispatch_command(cmd, &args);
+                    }
+                    InputResult::CustomCommand(name, args) => {
+                        self.run_custom_command(&name, &args);
                     }
                     InputResult::None => {}
                 }
@@ -877,7 +1445,7 @@ impl ChatWidget {
         self.request_redraw();
     }
 
-    fn dispatch_command(&mut self, cmd: SlashCommand) {
+    fn dispatch_command(&mut self, cmd: SlashCommand, args: &str) {
         match cmd {
             SlashCommand::New => {
                 self.app_event_tx.send(AppEvent::NewSession);
@@ -891,12 +1459,24 @@ impl ChatWidget {
                 self.clear_token_usage();
                 self.app_event_tx.send(AppEvent::CodexOp(Op::Compact));
             }
+            SlashCommand::Map => {
+                self.app_event_tx
+                    .send(AppEvent::CodexOp(Op::RegenerateRepoMap));
+            }
             SlashCommand::Model => {
                 self.open_model_popup();
             }
             SlashCommand::Approvals => {
                 self.open_approvals_popup();
             }
+            SlashCommand::Profile if !args.is_empty() => {
+                self.app_event_tx.send(AppEvent::CodexOp(Op::SwitchProfile {
+                    name: args.to_string(),
+                }));
+            }
+            SlashCommand::Profile => {
+                self.open_profile_popup();
+            }
             SlashCommand::Quit => {
                 self.app_event_tx.send(AppEvent::ExitRequest);
             }
@@ -923,12 +1503,42 @@ impl ChatWidget {
                     tx.send(AppEvent::DiffResult(text));
                 });
             }
+            SlashCommand::Export => match parse_export_args(args, &self.config.cwd, Local::now()) {
+                Ok((format, path)) => {
+                    self.app_event_tx
+                        .send(AppEvent::ExportTranscript { format, path });
+                }
+                Err(usage) => {
+                    self.add_to_history(history_cell::new_error_event(usage));
+                }
+            },
+            SlashCommand::Tasks => {
+                self.dispatch_tasks_command(args);
+            }
             SlashCommand::Mention => {
                 self.insert_str("@");
             }
             SlashCommand::Status => {
                 self.add_status_output();
             }
+            SlashCommand::Statusbar => {
+                self.config.tui.status_bar = !self.config.tui.status_bar;
+                self.refresh_status_bar();
+                self.refresh_git_status();
+            }
+            SlashCommand::Split => {
+                self.config.tui.split_pane = !self.config.tui.split_pane;
+                self.request_redraw();
+            }
+            SlashCommand::Usage => {
+                self.add_to_history(history_cell::new_usage_output(
+                    &self.turn_token_usage,
+                    &self.config.tui.model_pricing,
+                    &self.turn_usage_breakdown,
+                    &self.total_usage_breakdown,
+                    args == "detail",
+                ));
+            }
             SlashCommand::Mcp => {
                 self.add_mcp_output();
             }
@@ -973,6 +1583,105 @@ impl ChatWidget {
         }
     }
 
+    /// Sends the prompt of the custom slash command `name`, or queues it
+    /// while a turn is running. Returns whether a prompt was sent or queued.
+    fn run_custom_command(&mut self, name: &str, args: &str) -> bool {
+        let Some(command) = self.config.tui.slash_commands.get(name).cloned() else {
+            return false;
+        };
+        let text = match expand_prompt_template(&command.prompt, args) {
+            Ok(text) => text,
+            Err(e) => {
+                self.add_to_history(history_cell::new_error_event(format!("/{name}: {e}")));
+                return false;
+            }
+        };
+        let turn_settings = match self.turn_settings_for(&command) {
+            Ok(settings) => settings,
+            Err(e) => {
+                self.add_to_history(history_cell::new_error_event(format!("/{name}: {e}")));
+                return false;
+            }
+        };
+        let user_message = UserMessage {
+            text,
+            image_paths: Vec::new(),
+            turn_settings,
+        };
+        if self.bottom_pane.is_task_running() {
+            self.queue_user_message(user_message, None);
+        } else {
+            self.submit_user_message(user_message);
+        }
+        true
+    }
+
+    /// Sets the commands and prompts passed with `--run`, run in order once
+    /// the session is configured.
+    pub(crate) fn set_startup_commands(&mut self, commands: Vec<String>) {
+        self.startup_commands = commands.into();
+    }
+
+    /// Runs startup commands until one starts a turn; the rest continue when
+    /// that turn completes.
+    fn run_startup_commands(&mut self) {
+        while let Some(entry) = self.startup_commands.pop_front() {
+            if self.run_startup_command(&entry) {
+                break;
+            }
+        }
+    }
+
+    /// Runs a slash command or sends a prompt from `--run`. Returns whether
+    /// it started a turn.
+    fn run_startup_command(&mut self, entry: &str) -> bool {
+        let Some((name, args)) = parse_slash_command(entry) else {
+            let text = entry.trim().to_string();
+            let starts_turn = !text.is_empty();
+            self.submit_text_message(text);
+            return starts_turn;
+        };
+        if let Ok(cmd) = SlashCommand::from_str(name) {
+            self.dispatch_command(cmd, args);
+            return matches!(cmd, SlashCommand::Init | SlashCommand::Compact);
+        }
+        if self.config.tui.slash_commands.contains_key(name) {
+            return self.run_custom_command(name, args);
+        }
+        self.add_to_history(history_cell::new_error_event(format!(
+            "--run: unknown command /{name}"
+        )));
+        false
+    }
+
+    /// The settings a custom command pins for its turn, if it pins any.
+    fn turn_settings_for(
+        &self,
+        command: &CustomSlashCommand,
+    ) -> Result<Option<TurnSettings>, String> {
+        if command.model.is_none() && command.profile.is_none() {
+            return Ok(None);
+        }
+        let profile_config;
+        let base = match &command.profile {
+            Some(profile) => {
+                profile_config = self
+                    .config
+                    .reload_with_profile(profile)
+                    .map_err(|e| format!("failed to load profile `{profile}`: {e}"))?;
+                &profile_config
+            }
+            None => &self.config,
+        };
+        Ok(Some(TurnSettings {
+            model: command.model.clone().unwrap_or_else(|| base.model.clone()),
+            effort: base.model_reasoning_effort,
+            summary: base.model_reasoning_summary,
+            approval_policy: base.approval_policy,
+            sandbox_policy: base.sandbox_policy.clone(),
+        }))
+    }
+
     pub(crate) fn handle_paste(&mut self, text: String) {
         self.bottom_pane.handle_paste(text);
     }
@@ -1002,26 +1711,55 @@ impl ChatWidget {
     }
 
     fn submit_user_message(&mut self, user_message: UserMessage) {
-        let UserMessage { text, image_paths } = user_message;
+        let UserMessage {
+            text,
+            image_paths,
+            turn_settings,
+        } = user_message;
+        // The user has moved on; stop offering to re-run the failed command
+        // or to share the shell command's output.
+        if self.last_failed_command.take().is_some() {
+            self.bottom_pane.set_rerun_failed_hint(false);
+        }
+        if self.shell_output_to_share.take().is_some() {
+            self.bottom_pane.set_share_output_hint(false);
+        }
         let mut items: Vec<InputItem> = Vec::new();
+        if !self.external_changes.is_empty() {
+            self.external_changes.clear();
+            self.bottom_pane.set_external_changes_hint(false);
+        }
+        if let Some(context) = self.external_changes_context.take() {
+            items.push(InputItem::Text { text: context });
+        }
 
         if !text.is_empty() {
             items.push(InputItem::Text { text: text.clone() });
         }
 
-        for path in image_paths {
-            items.push(InputItem::LocalImage { path });
+        for path in &image_paths {
+            items.push(InputItem::LocalImage { path: path.clone() });
         }
 
         if items.is_empty() {
             return;
         }
 
-        self.codex_op_tx
-            .send(Op::UserInput { items })
-            .unwrap_or_else(|e| {
-                tracing::error!("failed to send message: {e}");
-            });
+        let op = match turn_settings {
+            None => Op::UserInput { items },
+            Some(settings) => Op::UserTurn {
+                items,
+                cwd: self.config.cwd.clone(),
+                approval_policy: settings.approval_policy,
+                sandbox_policy: settings.sandbox_policy,
+                model: settings.model,
+                effort: settings.effort,
+                summary: settings.summary,
+            },
+        };
+        self.codex_op_tx.send(op).unwrap_or_else(|e| {
+            tracing::error!("failed to send message: {e}");
+        });
 
         // Persist the text to cross-session message history.
         if !text.is_empty() {
@@ -1032,10 +1770,12 @@ impl ChatWidget {
                 });
         }
 
-        // Only show the text portion in conversation history.
-        if !text.is_empty() {
+        if !text.is_empty() || !image_paths.is_empty() {
             self.add_to_history(history_cell::new_user_prompt(text.clone()));
         }
+        for path in &image_paths {
+            self.add_to_history(history_cell::new_user_image(path));
+        }
     }
 
     pub(crate) fn handle_codex_event(&mut self, event: Event) {
@@ -1068,7 +1808,9 @@ impl ChatWidget {
             EventMsg::TaskStarted => self.on_task_started(),
             EventMsg::TaskComplete(TaskCompleteEvent { .. }) => self.on_task_complete(),
             EventMsg::TokenCount(token_usage) => self.on_token_count(token_usage),
-            EventMsg::Error(ErrorEvent { message }) => self.on_error(message),
+            EventMsg::TokenUsageBreakdown(breakdown) => self.on_token_usage_breakdown(breakdown),
+            EventMsg::Error(ErrorEvent { message, .. }) => self.on_error(message),
+            EventMsg::DegradedMode(ev) => self.on_degraded_mode(ev),
             EventMsg::TurnAborted(ev) => match ev.reason {
                 TurnAbortReason::Interrupted => {
                     self.on_error("Tell the model what to do differently".to_owned())
@@ -1085,13 +1827,19 @@ impl ChatWidget {
             EventMsg::PatchApplyBegin(ev) => self.on_patch_apply_begin(ev),
             EventMsg::PatchApplyEnd(ev) => self.on_patch_apply_end(ev),
             EventMsg::ExecCommandEnd(ev) => self.on_exec_command_end(ev),
+            EventMsg::ContainerImagePull(ev) => self.on_container_image_pull(ev),
             EventMsg::McpToolCallBegin(ev) => self.on_mcp_tool_call_begin(ev),
             EventMsg::McpToolCallEnd(ev) => self.on_mcp_tool_call_end(ev),
             EventMsg::WebSearchBegin(ev) => self.on_web_search_begin(ev),
             EventMsg::GetHistoryEntryResponse(ev) => self.on_get_history_entry_response(ev),
             EventMsg::McpListToolsResponse(ev) => self.on_list_mcp_tools(ev),
+            EventMsg::BackgroundTaskUpdate(task) => self.on_background_task_update(task),
+            EventMsg::ListBackgroundTasksResponse(ev) => self.on_list_background_tasks(ev),
             EventMsg::ShutdownComplete => self.on_shutdown_complete(),
             EventMsg::TurnDiff(TurnDiffEvent { unified_diff }) => self.on_turn_diff(unified_diff),
+            EventMsg::TurnReverted(ev) => self.on_turn_reverted(ev),
+            EventMsg::ContextCompacted(ev) => self.on_context_compacted(ev),
+            EventMsg::ProfileSwitched(ev) => self.on_profile_switched(ev),
             EventMsg::BackgroundEvent(BackgroundEventEvent { message }) => {
                 self.on_background_event(message)
             }
@@ -1124,12 +1872,69 @@ impl ChatWidget {
             return;
         }
         if let Some(user_message) = self.queued_user_messages.pop_front() {
+            if let Some(slot) = self.editing_queued_message.as_mut() {
+                *slot = slot.saturating_sub(1);
+            }
             self.submit_user_message(user_message);
         }
         // Update the list to reflect the remaining queued messages (if any).
         self.refresh_queued_user_messages();
     }
 
+    /// Queue `user_message` to be sent once the running turn completes: back
+    /// in `slot` if it was a queued message being edited, last otherwise.
+    /// Empty messages are dropped, which is how a queued message is removed.
+    fn queue_user_message(&mut self, user_message: UserMessage, slot: Option<usize>) {
+        if !user_message.text.is_empty() || !user_message.image_paths.is_empty() {
+            let len = self.queued_user_messages.len();
+            let slot = slot.map_or(len, |slot| slot.min(len));
+            self.queued_user_messages.insert(slot, user_message);
+        }
+        self.refresh_queued_user_messages();
+    }
+
+    /// Alt+↑/Alt+↓: take the queued message older/newer than the one in the
+    /// composer into the composer for editing, starting with the newest.
+    /// The message being edited goes back into its slot first; moving past
+    /// the newest message ends editing.
+    fn edit_adjacent_queued_message(&mut self, older: bool) {
+        let target = match self.editing_queued_message.take() {
+            None if older => self.queued_user_messages.len().checked_sub(1),
+            None => None,
+            Some(0) if older => {
+                // Already at the oldest message; keep editing it.
+                self.editing_queued_message = Some(0);
+                return;
+            }
+            Some(slot) => {
+                let (text, image_paths) = self.bottom_pane.take_composer_draft();
+                let requeued = !text.is_empty() || !image_paths.is_empty();
+                self.queue_user_message(
+                    UserMessage {
+                        text,
+                        image_paths,
+                        turn_settings: None,
+                    },
+                    Some(slot),
+                );
+                match (older, requeued) {
+                    (true, _) => Some(slot - 1),
+                    (false, true) => Some(slot + 1),
+                    (false, false) => Some(slot),
+                }
+            }
+        };
+        if let Some(index) = target
+            && let Some(user_message) = self.queued_user_messages.remove(index)
+        {
+            self.bottom_pane
+                .set_composer_text_with_images(&user_message.text, user_message.image_paths);
+            self.editing_queued_message = Some(index);
+        }
+        self.refresh_queued_user_messages();
+        self.request_redraw();
+    }
+
     /// Rebuild and update the queued user messages from the current queue.
     fn refresh_queued_user_messages(&mut self) {
         let messages: Vec<String> = self
@@ -1137,7 +1942,8 @@ impl ChatWidget {
             .iter()
             .map(|m| m.text.clone())
             .collect();
-        self.bottom_pane.set_queued_user_messages(messages);
+        self.bottom_pane
+            .set_queued_user_messages(messages, self.editing_queued_message);
     }
 
     pub(crate) fn add_diff_in_progress(&mut self) {
@@ -1238,14 +2044,62 @@ impl ChatWidget {
         );
     }
 
+    /// Open a popup listing the profiles in config.toml with their key
+    /// settings; choosing one switches the session to it.
+    pub(crate) fn open_profile_popup(&mut self) {
+        let config_toml =
+            match load_config_as_toml_with_cli_overrides(&self.config.codex_home, Vec::new()) {
+                Ok(config_toml) => config_toml,
+                Err(e) => {
+                    self.add_to_history(history_cell::new_error_event(format!(
+                        "Failed to read config.toml: {e}"
+                    )));
+                    return;
+                }
+            };
+        if config_toml.profiles.is_empty() {
+            self.add_to_history(history_cell::empty_profiles_output());
+            return;
+        }
+
+        let mut profiles: Vec<_> = config_toml.profiles.into_iter().collect();
+        profiles.sort_by(|(a, _), (b, _)| a.cmp(b));
+        let mut items: Vec<SelectionItem> = Vec::new();
+        for (name, profile) in profiles {
+            let is_current = self.config.active_profile.as_deref() == Some(name.as_str());
+            let description = Some(profile_summary(&profile));
+            let profile_name = name.clone();
+            let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
+                tx.send(AppEvent::CodexOp(Op::SwitchProfile {
+                    name: profile_name.clone(),
+                }));
+            })];
+            items.push(SelectionItem {
+                name,
+                description,
+                is_current,
+                actions,
+            });
+        }
+
+        self.bottom_pane.show_selection_view(
+            "Select profile".to_string(),
+            Some("Switch the model, provider, approvals and sandbox for this session".to_string()),
+            Some("Press Enter to confirm or Esc to go back".to_string()),
+            items,
+        );
+    }
+
     /// Set the approval policy in the widget's config copy.
     pub(crate) fn set_approval_policy(&mut self, policy: AskForApproval) {
         self.config.approval_policy = policy;
+        self.refresh_status_bar();
     }
 
     /// Set the sandbox policy in the widget's config copy.
     pub(crate) fn set_sandbox_policy(&mut self, policy: SandboxPolicy) {
         self.config.sandbox_policy = policy;
+        self.refresh_status_bar();
     }
 
     /// Set the reasoning effort in the widget's config copy.
@@ -1256,6 +2110,39 @@ impl ChatWidget {
     /// Set the model in the widget's config copy.
     pub(crate) fn set_model(&mut self, model: String) {
         self.config.model = model;
+        self.refresh_status_bar();
+    }
+
+    pub(crate) fn set_git_status(&mut self, status: Option<BranchStatus>) {
+        self.git_status = status;
+        self.refresh_status_bar();
+    }
+
+    /// Push the settings shown in the status bar to the bottom pane, or hide
+    /// the bar when it is turned off.
+    fn refresh_status_bar(&mut self) {
+        let info = self.config.tui.status_bar.then(|| StatusBarInfo {
+            model: self.config.model.clone(),
+            provider: self.config.model_provider_id.clone(),
+            approval_policy: self.config.approval_policy,
+            sandbox_policy: self.config.sandbox_policy.clone(),
+            git: self.git_status.clone(),
+        });
+        self.bottom_pane.set_status_bar(info);
+    }
+
+    /// Look up the branch for the status bar in the background. Runs when
+    /// the session starts and after each turn, when the agent may have
+    /// changed the working tree.
+    fn refresh_git_status(&self) {
+        if !self.config.tui.status_bar {
+            return;
+        }
+        let tx = self.app_event_tx.clone();
+        let cwd = self.config.cwd.clone();
+        tokio::spawn(async move {
+            tx.send(AppEvent::GitStatusUpdated(branch_status(&cwd).await));
+        });
     }
 
     pub(crate) fn add_mcp_output(&mut self) {
@@ -1267,14 +2154,22 @@ impl ChatWidget {
     }
 
     /// Forward file-search results to the bottom pane.
-    pub(crate) fn apply_file_search_result(&mut self, query: String, matches: Vec<FileMatch>) {
-        self.bottom_pane.on_file_search_result(query, matches);
+    pub(crate) fn apply_file_search_result(
+        &mut self,
+        query: String,
+        matches: Vec<FileMatch>,
+        content_matches: Vec<ContentMatch>,
+    ) {
+        self.bottom_pane
+            .on_file_search_result(query, matches, content_matches);
     }
 
     /// Handle Ctrl-C key press.
     fn on_ctrl_c(&mut self) {
         if self.bottom_pane.on_ctrl_c() == CancellationEvent::Ignored {
-            if self.bottom_pane.is_task_running() {
+            if let Some(cancel) = self.shell_command_cancel.take() {
+                let _ = cancel.send(());
+            } else if self.bottom_pane.is_task_running() {
                 self.submit_op(Op::Interrupt);
             } else if self.bottom_pane.ctrl_c_quit_hint_visible() {
                 self.submit_op(Op::Shutdown);
@@ -1319,6 +2214,37 @@ impl ChatWidget {
         self.add_to_history(history_cell::new_mcp_tools_output(&self.config, ev.tools));
     }
 
+    fn on_background_task_update(&mut self, task: BackgroundTaskInfo) {
+        self.add_to_history(history_cell::new_background_task_update(&task));
+    }
+
+    fn on_list_background_tasks(&mut self, ev: ListBackgroundTasksResponseEvent) {
+        self.add_to_history(history_cell::new_background_tasks_output(&ev.tasks));
+    }
+
+    /// `/tasks` lists background tasks, `/tasks run <command>` starts one and
+    /// `/tasks kill <id>` stops one.
+    fn dispatch_tasks_command(&mut self, args: &str) {
+        let (subcommand, rest) = args
+            .split_once(char::is_whitespace)
+            .map_or((args, ""), |(subcommand, rest)| (subcommand, rest.trim()));
+        let op = match (subcommand, rest) {
+            ("", _) => Some(Op::ListBackgroundTasks),
+            ("run", script) if !script.is_empty() => Some(Op::RunBackground {
+                command: vec!["bash".to_string(), "-lc".to_string(), script.to_string()],
+            }),
+            ("kill", id) => id.parse().ok().map(|id| Op::KillBackgroundTask { id }),
+            _ => None,
+        };
+        let Some(op) = op else {
+            self.add_to_history(history_cell::new_error_event(
+                "Usage: /tasks, /tasks run <command> or /tasks kill <id>".to_string(),
+            ));
+            return;
+        };
+        self.submit_op(op);
+    }
+
     /// Programmatically submit a user text message as if typed in the
     /// composer. The text will be added to conversation history and sent to
     /// the agent.
@@ -1337,6 +2263,11 @@ impl ChatWidget {
         self.session_id
     }
 
+    /// Final agent messages of this session, oldest first.
+    pub(crate) fn agent_messages(&self) -> &[String] {
+        &self.agent_messages
+    }
+
     /// Return a reference to the widget's current config (includes any
     /// runtime overrides applied via TUI, e.g., model or approval policy).
     pub(crate) fn config_ref(&self) -> &Config {
@@ -1345,6 +2276,9 @@ impl ChatWidget {
 
     pub(crate) fn clear_token_usage(&mut self) {
         self.total_token_usage = TokenUsage::default();
+        self.total_usage_breakdown = TokenUsageBreakdown::default();
+        self.turn_usage_breakdown = TokenUsageBreakdown::default();
+        self.turn_token_usage.clear();
         self.bottom_pane.set_token_usage(
             self.total_token_usage.clone(),
             self.last_token_usage.clone(),
@@ -1353,7 +2287,7 @@ impl ChatWidget {
     }
 
     pub fn cursor_pos(&self, area: Rect) -> Option<(u16, u16)> {
-        let [_, bottom_pane_area] = self.layout_areas(area);
+        let [_, _, bottom_pane_area] = self.layout_areas(area);
         self.bottom_pane.cursor_pos(bottom_pane_area)
     }
 }
@@ -1413,9 +2347,16 @@ fn apply_unified_diff_to_text(original: &str, unified_diff: &str) -> Option<Stri
 
 impl WidgetRef for &ChatWidget {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
-        let [active_cell_area, bottom_pane_area] = self.layout_areas(area);
+        let [banner_area, active_cell_area, bottom_pane_area] = self.layout_areas(area);
+        (&self.warning_banners).render_ref(banner_area, buf);
         (&self.bottom_pane).render(bottom_pane_area, buf);
-        if let Some(cell) = &self.active_exec_cell {
+        if self.config.tui.split_pane {
+            WorkingPane {
+                active_exec: self.active_exec_cell.as_ref(),
+                turn_diff: self.turn_diff.as_deref(),
+            }
+            .render_ref(active_cell_area, buf);
+        } else if let Some(cell) = &self.active_exec_cell {
             cell.render_ref(active_cell_area, buf);
         }
     }
@@ -12,12 +12,17 @@ use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningDeltaEvent;
 use codex_core::protocol::AgentReasoningEvent;
 use codex_core::protocol::ApplyPatchApprovalRequestEvent;
+use codex_core::protocol::BackgroundTaskInfo;
+use codex_core::protocol::BackgroundTaskStatus;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
+use codex_core::protocol::ExecCommandOutputDeltaEvent;
+use codex_core::protocol::ExecOutputStream;
 use codex_core::protocol::FileChange;
+use codex_core::protocol::ListBackgroundTasksResponseEvent;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
 use codex_core::protocol::StreamErrorEvent;
@@ -33,6 +38,8 @@ use std::io::BufRead;
 use std::io::BufReader;
 use std::io::Read;
 use std::path::PathBuf;
+use std::time::Duration;
+use std::time::Instant;
 use tokio::sync::mpsc::unbounded_channel;
 
 fn test_config() -> Config {
@@ -173,6 +180,9 @@ fn make_chatwidget_manual() -> (
         initial_user_message: None,
         total_token_usage: TokenUsage::default(),
         last_token_usage: TokenUsage::default(),
+        total_usage_breakdown: TokenUsageBreakdown::default(),
+        turn_usage_breakdown: TokenUsageBreakdown::default(),
+        turn_token_usage: Vec::new(),
         stream: StreamController::new(cfg),
         running_commands: HashMap::new(),
         pending_exec_completions: Vec::new(),
@@ -185,7 +195,20 @@ fn make_chatwidget_manual() -> (
         show_welcome_banner: true,
         last_history_was_exec: false,
         queued_user_messages: std::collections::VecDeque::new(),
+        editing_queued_message: None,
+        git_status: None,
+        turn_diff: None,
         open_diff_temps: Vec::new(),
+        last_failed_command: None,
+        shell_command_cancel: None,
+        shell_output_to_share: None,
+        _file_watcher: None,
+        external_changes: BTreeSet::new(),
+        external_changes_context: None,
+        task_stopped_at: None,
+        agent_messages: Vec::new(),
+        warning_banners: WarningBanners::default(),
+        startup_commands: std::collections::VecDeque::new(),
     };
     (widget, rx, op_rx)
 }
@@ -269,6 +292,48 @@ fn alt_up_edits_most_recent_queued_message() {
     );
 }
 
+#[test]
+fn alt_arrows_walk_queued_messages_and_requeue_edits_in_place() {
+    let (mut chat, _rx, _op_rx) = make_chatwidget_manual();
+    chat.bottom_pane.set_task_running(true);
+    for text in ["first", "second", "third"] {
+        chat.queued_user_messages
+            .push_back(UserMessage::from(text.to_string()));
+    }
+    chat.refresh_queued_user_messages();
+    let queued = |chat: &ChatWidget| -> Vec<String> {
+        chat.queued_user_messages
+            .iter()
+            .map(|m| m.text.clone())
+            .collect()
+    };
+
+    chat.handle_key_event(KeyEvent::new(KeyCode::Up, KeyModifiers::ALT));
+    chat.handle_key_event(KeyEvent::new(KeyCode::Up, KeyModifiers::ALT));
+    assert_eq!(chat.bottom_pane.composer_text(), "second");
+    assert_eq!(queued(&chat), vec!["first", "third"]);
+    assert_eq!(chat.editing_queued_message, Some(1));
+
+    // Submitting the edit puts it back in its slot.
+    chat.handle_paste(" edited".to_string());
+    chat.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    assert_eq!(queued(&chat), vec!["first", "second edited", "third"]);
+    assert_eq!(chat.editing_queued_message, None);
+
+    // Alt+Down past the newest message ends editing, leaving it queued.
+    chat.handle_key_event(KeyEvent::new(KeyCode::Up, KeyModifiers::ALT));
+    chat.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::ALT));
+    assert_eq!(chat.bottom_pane.composer_text(), "");
+    assert_eq!(queued(&chat), vec!["first", "second edited", "third"]);
+    assert_eq!(chat.editing_queued_message, None);
+
+    // Clearing the composer and submitting removes the message.
+    chat.handle_key_event(KeyEvent::new(KeyCode::Up, KeyModifiers::ALT));
+    chat.handle_key_event(KeyEvent::new(KeyCode::Char('u'), KeyModifiers::CONTROL));
+    chat.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    assert_eq!(queued(&chat), vec!["first", "second edited"]);
+}
+
 #[test]
 fn exec_history_cell_shows_working_then_completed() {
     let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
@@ -300,6 +365,9 @@ fn exec_history_cell_shows_working_then_completed() {
             exit_code: 0,
             duration: std::time::Duration::from_millis(5),
             formatted_output: "done".into(),
+            killed_reason: None,
+            sandbox_denied: false,
+            sandbox_denials: Vec::new(),
         }),
     });
 
@@ -351,6 +419,9 @@ fn exec_history_cell_shows_working_then_failed() {
             exit_code: 2,
             duration: std::time::Duration::from_millis(7),
             formatted_output: "".into(),
+            killed_reason: None,
+            sandbox_denied: false,
+            sandbox_denials: Vec::new(),
         }),
     });
 
@@ -413,6 +484,107 @@ fn interrupt_exec_marks_failed_snapshot() {
     assert_snapshot!("interrupt_exec_marks_failed", exec_blob);
 }
 
+#[test]
+fn usage_detail_charts_turns_and_shows_last_turn_and_session_per_tool() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
+    let tool = |name: &str, tokens| codex_core::protocol::ToolTokenUsage {
+        name: name.to_string(),
+        tokens,
+    };
+
+    let turns = [
+        TokenUsageBreakdown {
+            instructions: 300,
+            tool_schemas: 200,
+            messages: 100,
+            reasoning: 50,
+            tools: vec![tool("shell", 350)],
+        },
+        TokenUsageBreakdown {
+            instructions: 300,
+            tool_schemas: 200,
+            messages: 150,
+            reasoning: 0,
+            tools: vec![tool("shell", 400), tool("update_plan", 50)],
+        },
+    ];
+    let usages = [
+        TokenUsage {
+            input_tokens: 100_000,
+            cached_input_tokens: Some(0),
+            output_tokens: 10_000,
+            reasoning_output_tokens: None,
+            total_tokens: 110_000,
+        },
+        TokenUsage {
+            input_tokens: 200_000,
+            cached_input_tokens: Some(90_000),
+            output_tokens: 10_000,
+            reasoning_output_tokens: None,
+            total_tokens: 210_000,
+        },
+    ];
+    chat.config.tui.model_pricing.insert(
+        chat.config.model.clone(),
+        codex_core::config_types::ModelPricing {
+            input: 1.25,
+            cached_input: Some(0.125),
+            output: 10.0,
+        },
+    );
+    for (breakdown, usage) in turns.into_iter().zip(usages) {
+        chat.handle_codex_event(Event {
+            id: "turn".into(),
+            msg: EventMsg::TaskStarted,
+        });
+        chat.handle_codex_event(Event {
+            id: "turn".into(),
+            msg: EventMsg::TokenUsageBreakdown(breakdown),
+        });
+        chat.handle_codex_event(Event {
+            id: "turn".into(),
+            msg: EventMsg::TokenCount(usage),
+        });
+    }
+    let _ = drain_insert_history(&mut rx);
+
+    chat.dispatch_command(SlashCommand::Usage, "detail");
+
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1, "expected one /usage cell");
+    assert_snapshot!("usage_detail", lines_to_single_string(&cells[0]));
+}
+
+#[test]
+fn profile_switched_updates_session_settings() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
+
+    chat.handle_codex_event(Event {
+        id: "switch".into(),
+        msg: EventMsg::ProfileSwitched(ProfileSwitchedEvent {
+            profile: "careful".to_string(),
+            model: "o3".to_string(),
+            model_provider_id: "openai".to_string(),
+            reasoning_effort: ReasoningEffortConfig::High,
+            approval_policy: AskForApproval::UnlessTrusted,
+            sandbox_policy: SandboxPolicy::new_read_only_policy(),
+        }),
+    });
+
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1, "expected one profile cell");
+    let text = lines_to_single_string(&cells[0]);
+    assert!(text.contains("Switched to profile careful"), "{text}");
+    assert!(text.contains("model o3 (openai)"), "{text}");
+    assert_eq!(chat.config.active_profile.as_deref(), Some("careful"));
+    assert_eq!(chat.config.model, "o3");
+    assert_eq!(chat.config.approval_policy, AskForApproval::UnlessTrusted);
+    assert_eq!(
+        chat.config.sandbox_policy,
+        SandboxPolicy::new_read_only_policy()
+    );
+}
+
 #[test]
 fn exec_history_extends_previous_when_consecutive() {
     let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
@@ -442,6 +614,9 @@ fn exec_history_extends_previous_when_consecutive() {
             exit_code: 0,
             duration: std::time::Duration::from_millis(5),
             formatted_output: "one".into(),
+            killed_reason: None,
+            sandbox_denied: false,
+            sandbox_denials: Vec::new(),
         }),
     });
     let first_cells = drain_insert_history(&mut rx);
@@ -472,6 +647,9 @@ fn exec_history_extends_previous_when_consecutive() {
             exit_code: 0,
             duration: std::time::Duration::from_millis(5),
             formatted_output: "two".into(),
+            killed_reason: None,
+            sandbox_denied: false,
+            sandbox_denials: Vec::new(),
         }),
     });
     let second_cells = drain_insert_history(&mut rx);
@@ -713,6 +891,7 @@ fn approval_modal_exec_snapshot() {
         command: vec!["bash".into(), "-lc".into(), "echo hello world".into()],
         cwd: std::env::current_dir().unwrap_or_else(|_| PathBuf::from(".")),
         reason: Some("Model wants to run a command".into()),
+        matched_rule: None,
     };
     chat.handle_codex_event(Event {
         id: "sub-approve".into(),
@@ -729,6 +908,31 @@ fn approval_modal_exec_snapshot() {
     assert_snapshot!("approval_modal_exec", terminal.backend());
 }
 
+// Snapshot test: command approval modal for a command matched by an
+// `[exec_policy]` ask rule.
+#[test]
+fn approval_modal_exec_matched_rule_snapshot() {
+    let (mut chat, _rx, _op_rx) = make_chatwidget_manual();
+    let ev = ExecApprovalRequestEvent {
+        call_id: "call-approve-cmd".into(),
+        command: vec!["git".into(), "push".into()],
+        cwd: PathBuf::from("/tmp"),
+        reason: None,
+        matched_rule: Some("exec_policy ask rule `git push`".into()),
+    };
+    chat.handle_codex_event(Event {
+        id: "sub-approve".into(),
+        msg: EventMsg::ExecApprovalRequest(ev),
+    });
+    let height = chat.desired_height(80);
+    let mut terminal = ratatui::Terminal::new(ratatui::backend::TestBackend::new(80, height))
+        .expect("create terminal");
+    terminal
+        .draw(|f| f.render_widget_ref(&chat, f.area()))
+        .expect("draw approval modal");
+    assert_snapshot!("approval_modal_exec_matched_rule", terminal.backend());
+}
+
 // Snapshot test: patch approval modal
 #[test]
 fn approval_modal_patch_snapshot() {
@@ -836,6 +1040,7 @@ fn status_widget_and_approval_modal_snapshot() {
         command: vec!["echo".into(), "hello world".into()],
         cwd: std::path::PathBuf::from("/tmp"),
         reason: Some("Codex wants to run a command".into()),
+        matched_rule: None,
     };
     chat.handle_codex_event(Event {
         id: "sub-approve-exec".into(),
@@ -982,7 +1187,7 @@ fn apply_patch_approval_sends_op_with_submission_id() {
     // Expect a CodexOp with PatchApproval carrying the submission id, not call id
     let mut found = false;
     while let Ok(app_ev) = rx.try_recv() {
-        if let AppEvent::CodexOp(Op::PatchApproval { id, decision }) = app_ev {
+        if let AppEvent::CodexOp(Op::PatchApproval { id, decision, .. }) = app_ev {
             assert_eq!(id, "sub-123");
             assert!(matches!(
                 decision,
@@ -1032,7 +1237,7 @@ fn apply_patch_full_flow_integration_like() {
         .try_recv()
         .expect("expected op forwarded to codex channel");
     match forwarded {
-        Op::PatchApproval { id, decision } => {
+        Op::PatchApproval { id, decision, .. } => {
             assert_eq!(id, "sub-xyz");
             assert!(matches!(
                 decision,
@@ -1332,6 +1537,8 @@ fn multiple_agent_messages_in_single_turn_emit_multiple_headers() {
         id: "s1".into(),
         msg: EventMsg::TaskComplete(TaskCompleteEvent {
             last_agent_message: None,
+            checkpoint_commit: None,
+            truncated: None,
         }),
     });
 
@@ -1456,3 +1663,441 @@ fn deltas_then_same_final_message_are_rendered_snapshot() {
         .collect::<String>();
     assert_snapshot!(combined);
 }
+
+#[test]
+fn ctrl_r_reruns_edited_failed_command() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
+
+    chat.handle_codex_event(Event {
+        id: "call-fail".into(),
+        msg: EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
+            call_id: "call-fail".into(),
+            command: vec!["bash".into(), "-lc".into(), "cargo tset".into()],
+            cwd: PathBuf::from("/workspace/crate"),
+            parsed_cmd: vec![],
+        }),
+    });
+    chat.handle_codex_event(Event {
+        id: "call-fail".into(),
+        msg: EventMsg::ExecCommandEnd(ExecCommandEndEvent {
+            call_id: "call-fail".into(),
+            stdout: String::new(),
+            stderr: "error: no such command: `tset`".into(),
+            aggregated_output: "error: no such command: `tset`".into(),
+            exit_code: 101,
+            duration: std::time::Duration::from_millis(5),
+            formatted_output: "error: no such command: `tset`".into(),
+            killed_reason: None,
+            sandbox_denied: false,
+            sandbox_denials: Vec::new(),
+        }),
+    });
+    let _ = drain_insert_history(&mut rx);
+
+    chat.handle_key_event(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+    for _ in 0..3 {
+        chat.handle_key_event(KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE));
+    }
+    for c in "est".chars() {
+        chat.handle_key_event(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+    }
+    chat.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+
+    let mut rerun = None;
+    while let Ok(ev) = rx.try_recv() {
+        if let AppEvent::CodexOp(Op::RunUserCommand { argv, cwd }) = ev {
+            rerun = Some((argv, cwd));
+        }
+    }
+    assert_eq!(
+        rerun,
+        Some((
+            vec![
+                "bash".to_string(),
+                "-lc".to_string(),
+                "cargo test".to_string()
+            ],
+            PathBuf::from("/workspace/crate")
+        ))
+    );
+}
+
+#[test]
+fn exec_output_deltas_render_live_in_active_cell() {
+    let (mut chat, _rx, _op_rx) = make_chatwidget_manual();
+
+    chat.handle_codex_event(Event {
+        id: "call-build".into(),
+        msg: EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
+            call_id: "call-build".into(),
+            command: vec!["bash".into(), "-lc".into(), "cargo build".into()],
+            cwd: PathBuf::from("/workspace"),
+            parsed_cmd: vec![],
+        }),
+    });
+    for (call_id, chunk) in [
+        ("call-build", "   Compiling a\n   Compil"),
+        ("call-build", "ing b\n"),
+        ("call-other", "not this command\n"),
+    ] {
+        chat.handle_codex_event(Event {
+            id: call_id.into(),
+            msg: EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
+                call_id: call_id.into(),
+                stream: ExecOutputStream::Stderr,
+                chunk: chunk.as_bytes().to_vec().into(),
+            }),
+        });
+    }
+
+    let rendered = lines_to_single_string(
+        &chat
+            .active_exec_cell
+            .as_ref()
+            .expect("active exec cell")
+            .display_lines(),
+    );
+    assert!(rendered.contains("  └    Compiling a\n"), "{rendered}");
+    assert!(rendered.contains("       Compiling b\n"), "{rendered}");
+    assert!(!rendered.contains("not this command"), "{rendered}");
+}
+
+#[test]
+fn split_pane_shows_running_output_and_resizes_with_ctrl_arrows() {
+    let (mut chat, _rx, _op_rx) = make_chatwidget_manual();
+    chat.config.tui.split_pane = true;
+    chat.config.tui.working_pane_height = 6;
+    let idle_height = chat.desired_height(60);
+
+    chat.handle_codex_event(Event {
+        id: "call-test".into(),
+        msg: EventMsg::ExecCommandBegin(ExecCommandBeginEvent {
+            call_id: "call-test".into(),
+            command: vec!["bash".into(), "-lc".into(), "cargo test".into()],
+            cwd: PathBuf::from("/workspace"),
+            parsed_cmd: vec![],
+        }),
+    });
+    let output: String = (1..=8).map(|i| format!("test case_{i} ... ok\n")).collect();
+    chat.handle_codex_event(Event {
+        id: "call-test".into(),
+        msg: EventMsg::ExecCommandOutputDelta(ExecCommandOutputDeltaEvent {
+            call_id: "call-test".into(),
+            stream: ExecOutputStream::Stdout,
+            chunk: output.into_bytes().into(),
+        }),
+    });
+    // The working pane keeps its height however much the command prints.
+    assert_eq!(chat.desired_height(60), idle_height);
+
+    let height = chat.desired_height(60);
+    let area = Rect::new(0, 0, 60, height);
+    let mut buf = Buffer::empty(area);
+    (&chat).render_ref(area, &mut buf);
+    let rows: Vec<String> = (0..height)
+        .map(|y| (0..60).map(|x| buf[(x, y)].symbol()).collect::<String>())
+        .collect();
+    assert!(rows[0].starts_with("─ running"), "{rows:#?}");
+    assert_eq!(rows[5].trim_end(), "    test case_8 ... ok", "{rows:#?}");
+    assert!(!rows.iter().any(|row| row.contains("case_3")), "{rows:#?}");
+
+    chat.handle_key_event(KeyEvent::new(KeyCode::Up, KeyModifiers::CONTROL));
+    assert_eq!(chat.desired_height(60), idle_height + 2);
+    for _ in 0..10 {
+        chat.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::CONTROL));
+    }
+    assert_eq!(chat.config.tui.working_pane_height, 3);
+}
+
+#[test]
+fn custom_slash_commands_expand_their_prompt_and_pin_the_model() {
+    let (mut chat, _rx, mut op_rx) = make_chatwidget_manual();
+    chat.config.tui.slash_commands.insert(
+        "release-notes".to_string(),
+        CustomSlashCommand {
+            description: None,
+            prompt: "Write release notes for the changes since $1.".to_string(),
+            model: Some("o3".to_string()),
+            profile: None,
+        },
+    );
+    chat.config.tui.slash_commands.insert(
+        "security-review".to_string(),
+        CustomSlashCommand {
+            description: Some("review for vulnerabilities".to_string()),
+            prompt: "Review the diff for security issues.".to_string(),
+            model: None,
+            profile: None,
+        },
+    );
+    chat.config.tui.slash_commands.insert(
+        "diff".to_string(),
+        CustomSlashCommand {
+            description: None,
+            prompt: "shadows a built-in".to_string(),
+            model: None,
+            profile: None,
+        },
+    );
+    assert_eq!(
+        custom_commands(&chat.config),
+        vec![
+            CustomCommandInfo {
+                name: "release-notes".to_string(),
+                description: "custom command".to_string(),
+            },
+            CustomCommandInfo {
+                name: "security-review".to_string(),
+                description: "review for vulnerabilities".to_string(),
+            },
+        ]
+    );
+
+    chat.run_custom_command("release-notes", "v1.2 ");
+    let ops: Vec<Op> = std::iter::from_fn(|| op_rx.try_recv().ok()).collect();
+    assert!(
+        matches!(
+            ops.as_slice(),
+            [Op::UserTurn { items, model, .. }, Op::AddToHistory { .. }]
+                if model == "o3"
+                    && matches!(
+                        items.as_slice(),
+                        [InputItem::Text { text }]
+                            if text == "Write release notes for the changes since v1.2."
+                    )
+        ),
+        "unexpected ops: {ops:?}"
+    );
+
+    chat.run_custom_command("security-review", "focus on auth");
+    let ops: Vec<Op> = std::iter::from_fn(|| op_rx.try_recv().ok()).collect();
+    assert!(
+        matches!(
+            ops.as_slice(),
+            [Op::UserInput { items }, Op::AddToHistory { .. }]
+                if matches!(
+                    items.as_slice(),
+                    [InputItem::Text { text }]
+                        if text == "Review the diff for security issues.\n\nfocus on auth"
+                )
+        ),
+        "unexpected ops: {ops:?}"
+    );
+}
+
+#[test]
+fn tasks_command_sends_background_task_ops() {
+    let (mut chat, mut rx, mut op_rx) = make_chatwidget_manual();
+
+    chat.dispatch_command(SlashCommand::Tasks, "run npm run dev");
+    chat.dispatch_command(SlashCommand::Tasks, "kill 3");
+    chat.dispatch_command(SlashCommand::Tasks, "");
+    let ops: Vec<Op> = std::iter::from_fn(|| op_rx.try_recv().ok()).collect();
+    assert!(
+        matches!(
+            ops.as_slice(),
+            [
+                Op::RunBackground { command },
+                Op::KillBackgroundTask { id: 3 },
+                Op::ListBackgroundTasks,
+            ] if command == &["bash", "-lc", "npm run dev"]
+        ),
+        "unexpected ops: {ops:?}"
+    );
+
+    chat.dispatch_command(SlashCommand::Tasks, "kill dev");
+    assert!(op_rx.try_recv().is_err());
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1, "expected a usage error");
+    assert!(lines_to_single_string(&cells[0]).contains("Usage: /tasks"));
+}
+
+#[test]
+fn tasks_list_shows_status_and_recent_output() {
+    let (mut chat, mut rx, _op_rx) = make_chatwidget_manual();
+
+    let task = |id, script: &str, status, output_tail: &str| BackgroundTaskInfo {
+        id,
+        command: vec!["bash".to_string(), "-lc".to_string(), script.to_string()],
+        cwd: PathBuf::from("/repo"),
+        status,
+        output_tail: output_tail.to_string(),
+    };
+    chat.handle_codex_event(Event {
+        id: "tasks".into(),
+        msg: EventMsg::ListBackgroundTasksResponse(ListBackgroundTasksResponseEvent {
+            tasks: vec![
+                task(
+                    0,
+                    "npm run dev",
+                    BackgroundTaskStatus::Running,
+                    "> dev\n> vite\n\n  ready in 312 ms\n  Local: http://localhost:5173/\n  press h for help\n  watching for changes\n",
+                ),
+                task(
+                    1,
+                    "cargo test",
+                    BackgroundTaskStatus::Exited { exit_code: 101 },
+                    "test result: FAILED. 3 passed; 1 failed\n",
+                ),
+                task(2, "sleep 600", BackgroundTaskStatus::Killed, ""),
+            ],
+        }),
+    });
+
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(cells.len(), 1, "expected one /tasks cell");
+    assert_snapshot!("tasks_list", lines_to_single_string(&cells[0]));
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn bang_commands_run_in_the_shell_and_offer_to_share_the_output() {
+    let (mut chat, mut rx, mut op_rx) = make_chatwidget_manual();
+    chat.config.cwd = std::env::temp_dir();
+    chat.handle_paste("!echo hello".to_string());
+    chat.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    assert!(chat.bottom_pane.is_task_running());
+
+    // Feed the shell events back the way the app loop does.
+    let mut cells = Vec::new();
+    loop {
+        match rx.recv().await.expect("event") {
+            AppEvent::ShellCommandOutput(chunk) => chat.on_shell_command_output(&chunk),
+            AppEvent::ShellCommandFinished {
+                command, output, ..
+            } => {
+                chat.on_shell_command_finished(command, output, Duration::from_millis(5));
+                break;
+            }
+            AppEvent::InsertHistoryCell(cell) => cells.push(cell.display_lines()),
+            _ => {}
+        }
+    }
+    cells.extend(drain_insert_history(&mut rx));
+    assert!(!chat.bottom_pane.is_task_running());
+    let text = lines_to_single_string(cells.last().expect("exec cell"));
+    assert!(text.contains("✓ echo hello"), "unexpected cell: {text}");
+    assert!(text.contains("hello\n"), "unexpected cell: {text}");
+    assert!(
+        op_rx.try_recv().is_err(),
+        "shell commands must not reach the agent"
+    );
+
+    chat.handle_key_event(KeyEvent::new(KeyCode::Char('s'), KeyModifiers::CONTROL));
+    assert_eq!(
+        chat.bottom_pane.composer_text(),
+        "I ran `echo hello` in my shell. It exited with code 0 after 5ms. Its output:\n\n```\nhello\n```"
+    );
+}
+
+#[test]
+fn external_file_changes_are_reported_and_sent_with_the_next_message() {
+    let (mut chat, mut rx, mut op_rx) = make_chatwidget_manual();
+
+    // A batch that started before the last task stopped holds the agent's edits.
+    let during_turn = Instant::now();
+    chat.task_stopped_at = Some(Instant::now());
+    chat.on_external_file_changes(vec![PathBuf::from("agent.rs")], during_turn);
+    assert!(drain_insert_history(&mut rx).is_empty());
+
+    chat.on_external_file_changes(
+        ["src/a.rs", "src/b.rs", "c.txt", "d.txt"]
+            .into_iter()
+            .map(PathBuf::from)
+            .collect(),
+        Instant::now(),
+    );
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(
+        lines_to_single_string(&cells[0]),
+        "\n✎ Changed outside Codex · src/a.rs, src/b.rs, c.txt, +1 more\n"
+    );
+
+    chat.on_external_changes_diff("--- a/c.txt\n+++ b/c.txt\n@@ -1 +1 @@\n-old\n+new\n".into());
+    let cells = drain_insert_history(&mut rx);
+    assert_eq!(
+        lines_to_single_string(&cells[0]),
+        "\n✎ The changes to 4 files will be sent with your next message\n"
+    );
+
+    chat.handle_paste("continue".to_string());
+    chat.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+    let ops: Vec<Op> = std::iter::from_fn(|| op_rx.try_recv().ok()).collect();
+    let Some(Op::UserInput { items }) = ops.first() else {
+        panic!("unexpected ops: {ops:?}");
+    };
+    let texts: Vec<&str> = items
+        .iter()
+        .filter_map(|item| match item {
+            InputItem::Text { text } => Some(text.as_str()),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(
+        texts,
+        vec![
+            "These files were changed outside of your session since my last message; \
+             re-read them instead of relying on earlier contents:\n\
+             - c.txt\n- d.txt\n- src/a.rs\n- src/b.rs\n\n\
+             Their diff against HEAD:\n\n\
+             ```diff\n--- a/c.txt\n+++ b/c.txt\n@@ -1 +1 @@\n-old\n+new\n```",
+            "continue",
+        ]
+    );
+    assert!(chat.external_changes.is_empty());
+}
+
+#[test]
+fn startup_commands_run_in_order_and_wait_for_each_turn() {
+    let (mut chat, mut rx, mut op_rx) = make_chatwidget_manual();
+    chat.set_startup_commands(vec![
+        "/profile fast".to_string(),
+        "explain this repo".to_string(),
+        "/nonexistent".to_string(),
+        "now list the tests".to_string(),
+    ]);
+
+    chat.run_startup_commands();
+    let switched = std::iter::from_fn(|| rx.try_recv().ok())
+        .any(|ev| matches!(ev, AppEvent::CodexOp(Op::SwitchProfile { name }) if name == "fast"));
+    assert!(switched, "expected /profile fast to switch profiles");
+    let ops: Vec<Op> = std::iter::from_fn(|| op_rx.try_recv().ok()).collect();
+    assert!(
+        matches!(
+            ops.as_slice(),
+            [Op::UserInput { items }, Op::AddToHistory { .. }]
+                if matches!(items.as_slice(), [InputItem::Text { text }] if text == "explain this repo")
+        ),
+        "unexpected ops: {ops:?}"
+    );
+    assert_eq!(chat.startup_commands.len(), 2);
+
+    chat.handle_codex_event(Event {
+        id: "s1".into(),
+        msg: EventMsg::TaskStarted,
+    });
+    chat.handle_codex_event(Event {
+        id: "s1".into(),
+        msg: EventMsg::TaskComplete(TaskCompleteEvent {
+            last_agent_message: None,
+            checkpoint_commit: None,
+            truncated: None,
+        }),
+    });
+    let ops: Vec<Op> = std::iter::from_fn(|| op_rx.try_recv().ok()).collect();
+    assert!(
+        matches!(
+            ops.as_slice(),
+            [Op::UserInput { items }, Op::AddToHistory { .. }]
+                if matches!(items.as_slice(), [InputItem::Text { text }] if text == "now list the tests")
+        ),
+        "unexpected ops: {ops:?}"
+    );
+    assert!(chat.startup_commands.is_empty());
+    let cells = drain_insert_history(&mut rx);
+    assert!(
+        cells
+            .iter()
+            .any(|lines| lines_to_single_string(lines).contains("unknown command /nonexistent")),
+        "expected an error for the unknown command"
+    );
+}
@@ -19,4 +19,14 @@ lazy_static::lazy_static! {
     pub(crate) static ref CITATION_REGEX: Regex = Regex::new(
         r"【F:([^†]+)†L(\d+)(?:-L(\d+|\?))?】"
     ).expect("failed to compile citation regex");
+
+    /// Regular expression that matches rendered citations of a line in a file,
+    /// such as `src/main.rs:10` or `src/main.rs:10:4`.
+    ///
+    /// Capture groups:
+    /// 1. file path (must have an extension)
+    /// 2. line number (digits)
+    pub(crate) static ref FILE_LINE_REGEX: Regex = Regex::new(
+        r"([\w.~/\\-]*\.\w+):(\d+)(?::\d+)?"
+    ).expect("failed to compile file line regex");
 }
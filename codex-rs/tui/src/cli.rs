@@ -18,8 +18,8 @@ pub struct Cli {
     pub model: Option<String>,
 
     /// Convenience flag to select the local open source model provider.
-    /// Equivalent to -c model_provider=oss; verifies a local Ollama server is
-    /// running.
+    /// Equivalent to -c model_provider=oss; verifies a local Ollama server, or
+    /// the local provider named by `oss_provider`, is running.
     #[arg(long = "oss", default_value_t = false)]
     pub oss: bool,
 
@@ -58,6 +58,23 @@ pub struct Cli {
     #[arg(long = "search", default_value_t = false)]
     pub web_search: bool,
 
+    /// Print the session as plain lines instead of the full-screen interface,
+    /// for dumb terminals, editor shell buffers and CI consoles. Used
+    /// automatically when TERM=dumb or stdin or stdout is not a terminal.
+    #[arg(long = "plain", default_value_t = false)]
+    pub plain: bool,
+
+    /// Slash command or prompt to run once the session starts, after
+    /// [PROMPT]. Repeat to run several in order; each prompt waits for the
+    /// previous turn to finish.
+    #[arg(long = "run", value_name = "COMMAND")]
+    pub run: Vec<String>,
+
+    /// Print the session recorded in this rollout file, e.g. one under
+    /// `~/.codex/sessions`, and exit without starting a new session.
+    #[arg(long = "replay", value_name = "FILE", conflicts_with_all = ["prompt", "run"])]
+    pub replay: Option<PathBuf>,
+
     #[clap(skip)]
     pub config_overrides: CliConfigOverrides,
 }
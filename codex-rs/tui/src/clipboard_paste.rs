@@ -47,8 +47,23 @@ pub struct PastedImageInfo {
 }
 
 /// Capture image from system clipboard, encode to PNG, and return bytes + info.
+///
+/// The native clipboard (the macOS pasteboard, Windows DIB, X11) is read
+/// through `arboard`. When that finds no image on Linux and the BSDs, e.g.
+/// under Wayland, `wl-paste` and then `xclip` are asked for an image target.
 pub fn paste_image_as_png() -> Result<(Vec<u8>, PastedImageInfo), PasteImageError> {
     tracing::debug!("attempting clipboard image read");
+    let native = paste_native_image();
+    #[cfg(all(unix, not(target_os = "macos")))]
+    if native.is_err()
+        && let Some(bytes) = paste_image_with_command_line_tools()
+    {
+        return encode_png_from_bytes(&bytes);
+    }
+    native
+}
+
+fn paste_native_image() -> Result<(Vec<u8>, PastedImageInfo), PasteImageError> {
     let mut cb = arboard::Clipboard::new()
         .map_err(|e| PasteImageError::ClipboardUnavailable(e.to_string()))?;
     let img = cb
@@ -57,12 +72,37 @@ pub fn paste_image_as_png() -> Result<(Vec<u8>, PastedImageInfo), PasteImageErro
     let w = img.width as u32;
     let h = img.height as u32;
 
-    let mut png: Vec<u8> = Vec::new();
     let Some(rgba_img) = image::RgbaImage::from_raw(w, h, img.bytes.into_owned()) else {
         return Err(PasteImageError::EncodeFailed("invalid RGBA buffer".into()));
     };
-    let dyn_img = image::DynamicImage::ImageRgba8(rgba_img);
     tracing::debug!("clipboard image decoded RGBA {w}x{h}");
+    encode_png(&image::DynamicImage::ImageRgba8(rgba_img))
+}
+
+/// Decode an image the clipboard holds as PNG or JPEG bytes, re-encoding it
+/// to PNG unless it already is one.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn encode_png_from_bytes(bytes: &[u8]) -> Result<(Vec<u8>, PastedImageInfo), PasteImageError> {
+    let format = image::guess_format(bytes).map_err(|e| PasteImageError::NoImage(e.to_string()))?;
+    let dyn_img = image::load_from_memory_with_format(bytes, format)
+        .map_err(|e| PasteImageError::EncodeFailed(e.to_string()))?;
+    if format != image::ImageFormat::Png {
+        return encode_png(&dyn_img);
+    }
+    Ok((
+        bytes.to_vec(),
+        PastedImageInfo {
+            width: dyn_img.width(),
+            height: dyn_img.height(),
+            encoded_format: EncodedImageFormat::Png,
+        },
+    ))
+}
+
+fn encode_png(
+    dyn_img: &image::DynamicImage,
+) -> Result<(Vec<u8>, PastedImageInfo), PasteImageError> {
+    let mut png: Vec<u8> = Vec::new();
     {
         let mut cursor = std::io::Cursor::new(&mut png);
         dyn_img
@@ -77,13 +117,60 @@ pub fn paste_image_as_png() -> Result<(Vec<u8>, PastedImageInfo), PasteImageErro
     Ok((
         png,
         PastedImageInfo {
-            width: w,
-            height: h,
+            width: dyn_img.width(),
+            height: dyn_img.height(),
             encoded_format: EncodedImageFormat::Png,
         },
     ))
 }
 
+/// Clipboard targets (MIME types) that can be decoded, most preferred first.
+#[cfg(all(unix, not(target_os = "macos")))]
+const IMAGE_TARGETS: [&str; 3] = ["image/png", "image/jpeg", "image/jpg"];
+
+/// Picks the image target to request from a clipboard's list of targets, one
+/// per line as `wl-paste --list-types` and `xclip -t TARGETS` print them.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn pick_image_target(targets: &str) -> Option<&'static str> {
+    IMAGE_TARGETS
+        .into_iter()
+        .find(|wanted| targets.lines().any(|target| target.trim() == *wanted))
+}
+
+/// Reads an image from the clipboard with `wl-paste` (Wayland) or `xclip`
+/// (X11), whichever the session has and is installed.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn paste_image_with_command_line_tools() -> Option<Vec<u8>> {
+    if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+        let image = run_capture("wl-paste", &["--list-types"]).and_then(|targets| {
+            let target = pick_image_target(&String::from_utf8_lossy(&targets))?;
+            run_capture("wl-paste", &["--no-newline", "--type", target])
+        });
+        if image.is_some() {
+            return image;
+        }
+    }
+    if std::env::var_os("DISPLAY").is_some() {
+        let targets = run_capture("xclip", &["-selection", "clipboard", "-t", "TARGETS", "-o"])?;
+        let target = pick_image_target(&String::from_utf8_lossy(&targets))?;
+        return run_capture("xclip", &["-selection", "clipboard", "-t", target, "-o"]);
+    }
+    None
+}
+
+/// Stdout of `program`, or `None` if it is missing, fails or prints nothing.
+#[cfg(all(unix, not(target_os = "macos")))]
+fn run_capture(program: &str, args: &[&str]) -> Option<Vec<u8>> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .stdin(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .output()
+        .inspect_err(|e| tracing::debug!("failed to run {program}: {e}"))
+        .ok()?;
+    (output.status.success() && !output.stdout.is_empty()).then_some(output.stdout)
+}
+
 /// Convenience: write to a temp file and return its path + info.
 pub fn paste_image_to_temp_png() -> Result<(PathBuf, PastedImageInfo), PasteImageError> {
     let (png, info) = paste_image_as_png()?;
@@ -282,3 +369,41 @@ mod pasted_paths_tests {
         );
     }
 }
+
+#[cfg(all(test, unix, not(target_os = "macos")))]
+mod clipboard_image_tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn picks_the_preferred_image_target() {
+        let targets = "TIMESTAMP\nTARGETS\nimage/jpeg\nimage/png\ntext/plain\n";
+        assert_eq!(pick_image_target(targets), Some("image/png"));
+        assert_eq!(
+            pick_image_target("image/jpeg\nimage/bmp"),
+            Some("image/jpeg")
+        );
+        assert_eq!(pick_image_target("UTF8_STRING\ntext/plain"), None);
+    }
+
+    #[test]
+    fn reencodes_jpeg_clipboard_data_as_png() {
+        let img = image::DynamicImage::ImageRgb8(image::RgbImage::new(30, 20));
+        let mut jpeg = Vec::new();
+        img.write_to(
+            &mut std::io::Cursor::new(&mut jpeg),
+            image::ImageFormat::Jpeg,
+        )
+        .expect("encode jpeg");
+
+        let (png, info) = encode_png_from_bytes(&jpeg).expect("decode");
+        assert!(png.starts_with(b"\x89PNG"));
+        assert_eq!((info.width, info.height), (30, 20));
+        assert_eq!(info.encoded_format, EncodedImageFormat::Png);
+
+        assert!(matches!(
+            encode_png_from_bytes(b"not an image"),
+            Err(PasteImageError::NoImage(_))
+        ));
+    }
+}
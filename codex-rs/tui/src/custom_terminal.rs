@@ -359,6 +359,7 @@ where
         let mut frame = self.get_frame();
 
         render_callback(&mut frame).map_err(Into::into)?;
+        crate::theme::apply_to_buffer(frame.buffer_mut());
 
         // We can't change the cursor position right away because we have to flush the frame to
         // stdout first. But we also can't keep the frame around, since it holds a &mut to
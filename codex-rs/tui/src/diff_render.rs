@@ -5,12 +5,14 @@ use ratatui::style::Style;
 use ratatui::text::Line as RtLine;
 use ratatui::text::Span as RtSpan;
 use std::collections::HashMap;
+use std::path::Path;
 use std::path::PathBuf;
 
 use crate::common::DEFAULT_WRAP_COLS;
 use codex_core::protocol::FileChange;
 
 use crate::history_cell::PatchEventType;
+use crate::syntax::Highlighter;
 
 const SPACES_AFTER_LINE_NUMBER: usize = 6;
 
@@ -25,6 +27,7 @@ pub(crate) fn create_diff_summary(
     title: &str,
     changes: &HashMap<PathBuf, FileChange>,
     event_type: PatchEventType,
+    syntax_theme: &str,
 ) -> Vec<RtLine<'static>> {
     struct FileSummary {
         display_path: String,
@@ -162,17 +165,20 @@ pub(crate) fn create_diff_summary(
     );
 
     if show_details {
-        out.extend(render_patch_details(changes));
+        out.extend(render_patch_details(changes, syntax_theme));
     }
 
     out
 }
 
-fn render_patch_details(changes: &HashMap<PathBuf, FileChange>) -> Vec<RtLine<'static>> {
+/// Renders the lines of each change. Added and context lines are
+/// syntax-highlighted by the file's language; removed lines stay plain.
+fn render_patch_details(
+    changes: &HashMap<PathBuf, FileChange>,
+    syntax_theme: &str,
+) -> Vec<RtLine<'static>> {
     let mut out: Vec<RtLine<'static>> = Vec::new();
-    let term_cols: usize = terminal::size()
-        .map(|(w, _)| w as usize)
-        .unwrap_or(DEFAULT_WRAP_COLS.into());
+    let term_cols = term_cols();
 
     for (index, (path, change)) in changes.iter().enumerate() {
         let is_first_file = index == 0;
@@ -183,104 +189,168 @@ fn render_patch_details(changes: &HashMap<PathBuf, FileChange>) -> Vec<RtLine<'s
                 RtSpan::styled("...", style_dim()),
             ]));
         }
-        match change {
-            FileChange::Add { content } => {
-                for (i, raw) in content.lines().enumerate() {
-                    let ln = i + 1;
-                    out.extend(push_wrapped_diff_line(
-                        ln,
-                        DiffLineType::Insert,
-                        raw,
-                        term_cols,
-                    ));
-                }
+        out.extend(render_file_change(path, change, syntax_theme, term_cols));
+        out.push(RtLine::from(RtSpan::raw("")));
+    }
+
+    out
+}
+
+/// The diff of each changed file on its own, ordered by path, for reviewing
+/// a patch file by file.
+pub(crate) fn create_file_diffs(
+    changes: &HashMap<PathBuf, FileChange>,
+    syntax_theme: &str,
+) -> Vec<(PathBuf, Vec<RtLine<'static>>)> {
+    let term_cols = term_cols();
+    let mut files: Vec<(PathBuf, Vec<RtLine<'static>>)> = changes
+        .iter()
+        .map(|(path, change)| {
+            (
+                path.clone(),
+                render_file_change(path, change, syntax_theme, term_cols),
+            )
+        })
+        .collect();
+    files.sort_by(|(a, _), (b, _)| a.cmp(b));
+    files
+}
+
+fn term_cols() -> usize {
+    terminal::size()
+        .map(|(w, _)| w as usize)
+        .unwrap_or(DEFAULT_WRAP_COLS.into())
+}
+
+fn render_file_change(
+    path: &Path,
+    change: &FileChange,
+    syntax_theme: &str,
+    term_cols: usize,
+) -> Vec<RtLine<'static>> {
+    let mut out: Vec<RtLine<'static>> = Vec::new();
+    match change {
+        FileChange::Add { content } => {
+            let mut highlighter = Highlighter::for_path(path, syntax_theme);
+            for (i, raw) in content.lines().enumerate() {
+                let ln = i + 1;
+                out.extend(push_wrapped_diff_spans(
+                    ln,
+                    DiffLineType::Insert,
+                    highlight(&mut highlighter, raw),
+                    term_cols,
+                ));
             }
-            FileChange::Delete => {
-                let original = std::fs::read_to_string(path).unwrap_or_default();
-                for (i, raw) in original.lines().enumerate() {
-                    let ln = i + 1;
-                    out.extend(push_wrapped_diff_line(
-                        ln,
-                        DiffLineType::Delete,
-                        raw,
-                        term_cols,
-                    ));
-                }
+        }
+        FileChange::Delete => {
+            let original = std::fs::read_to_string(path).unwrap_or_default();
+            for (i, raw) in original.lines().enumerate() {
+                let ln = i + 1;
+                out.extend(push_wrapped_diff_line(
+                    ln,
+                    DiffLineType::Delete,
+                    raw,
+                    term_cols,
+                ));
             }
-            FileChange::Update {
-                unified_diff,
-                move_path: _,
-            } => {
-                if let Ok(patch) = diffy::Patch::from_str(unified_diff) {
-                    let mut is_first_hunk = true;
-                    for h in patch.hunks() {
-                        // Render a simple separator between non-contiguous hunks
-                        // instead of diff-style @@ headers.
-                        if !is_first_hunk {
-                            out.push(RtLine::from(vec![
-                                RtSpan::raw("    "),
-                                RtSpan::styled("⋮", style_dim()),
-                            ]));
-                        }
-                        is_first_hunk = false;
-
-                        let mut old_ln = h.old_range().start();
-                        let mut new_ln = h.new_range().start();
-                        for l in h.lines() {
-                            match l {
-                                diffy::Line::Insert(text) => {
-                                    let s = text.trim_end_matches('\n');
-                                    out.extend(push_wrapped_diff_line(
-                                        new_ln,
-                                        DiffLineType::Insert,
-                                        s,
-                                        term_cols,
-                                    ));
-                                    new_ln += 1;
-                                }
-                                diffy::Line::Delete(text) => {
-                                    let s = text.trim_end_matches('\n');
-                                    out.extend(push_wrapped_diff_line(
-                                        old_ln,
-                                        DiffLineType::Delete,
-                                        s,
-                                        term_cols,
-                                    ));
-                                    old_ln += 1;
-                                }
-                                diffy::Line::Context(text) => {
-                                    let s = text.trim_end_matches('\n');
-                                    out.extend(push_wrapped_diff_line(
-                                        new_ln,
-                                        DiffLineType::Context,
-                                        s,
-                                        term_cols,
-                                    ));
-                                    old_ln += 1;
-                                    new_ln += 1;
-                                }
+        }
+        FileChange::Update {
+            unified_diff,
+            move_path,
+        } => {
+            let mut highlighter =
+                Highlighter::for_path(move_path.as_deref().unwrap_or(path), syntax_theme);
+            if let Ok(patch) = diffy::Patch::from_str(unified_diff) {
+                let mut is_first_hunk = true;
+                for h in patch.hunks() {
+                    // Render a simple separator between non-contiguous hunks
+                    // instead of diff-style @@ headers.
+                    if !is_first_hunk {
+                        out.push(RtLine::from(vec![
+                            RtSpan::raw("    "),
+                            RtSpan::styled("⋮", style_dim()),
+                        ]));
+                    }
+                    is_first_hunk = false;
+
+                    let mut old_ln = h.old_range().start();
+                    let mut new_ln = h.new_range().start();
+                    for l in h.lines() {
+                        match l {
+                            diffy::Line::Insert(text) => {
+                                let s = text.trim_end_matches('\n');
+                                out.extend(push_wrapped_diff_spans(
+                                    new_ln,
+                                    DiffLineType::Insert,
+                                    highlight(&mut highlighter, s),
+                                    term_cols,
+                                ));
+                                new_ln += 1;
+                            }
+                            diffy::Line::Delete(text) => {
+                                let s = text.trim_end_matches('\n');
+                                out.extend(push_wrapped_diff_line(
+                                    old_ln,
+                                    DiffLineType::Delete,
+                                    s,
+                                    term_cols,
+                                ));
+                                old_ln += 1;
+                            }
+                            diffy::Line::Context(text) => {
+                                let s = text.trim_end_matches('\n');
+                                out.extend(push_wrapped_diff_spans(
+                                    new_ln,
+                                    DiffLineType::Context,
+                                    highlight(&mut highlighter, s),
+                                    term_cols,
+                                ));
+                                old_ln += 1;
+                                new_ln += 1;
                             }
                         }
                     }
                 }
             }
         }
-
-        out.push(RtLine::from(RtSpan::raw("")));
     }
-
     out
 }
 
+/// Highlights one line of new-file content, or returns it as a single plain
+/// span when the file's language is not known.
+fn highlight(highlighter: &mut Option<Highlighter>, text: &str) -> Vec<RtSpan<'static>> {
+    match highlighter {
+        Some(highlighter) => highlighter.highlight(text),
+        None => vec![RtSpan::raw(text.to_string())],
+    }
+}
+
 fn push_wrapped_diff_line(
     line_number: usize,
     kind: DiffLineType,
     text: &str,
     term_cols: usize,
+) -> Vec<RtLine<'static>> {
+    push_wrapped_diff_spans(
+        line_number,
+        kind,
+        vec![RtSpan::raw(text.to_string())],
+        term_cols,
+    )
+}
+
+/// Like `push_wrapped_diff_line`, for content that is already split into
+/// styled spans. Span styles take precedence over the insert/delete color.
+fn push_wrapped_diff_spans(
+    line_number: usize,
+    kind: DiffLineType,
+    content: Vec<RtSpan<'static>>,
+    term_cols: usize,
 ) -> Vec<RtLine<'static>> {
     let indent = "    ";
     let ln_str = line_number.to_string();
-    let mut remaining_text: &str = text;
+    let mut remaining = content;
 
     // Reserve a fixed number of spaces after the line number so that content starts
     // at a consistent column. Content includes a 1-character diff sign prefix
@@ -304,13 +374,15 @@ fn push_wrapped_diff_line(
         let available_content_cols = term_cols
             .saturating_sub(if first { prefix_cols + 1 } else { prefix_cols })
             .max(1);
-        let split_at_byte_index = remaining_text
-            .char_indices()
-            .nth(available_content_cols)
-            .map(|(i, _)| i)
-            .unwrap_or_else(|| remaining_text.len());
-        let (chunk, rest) = remaining_text.split_at(split_at_byte_index);
-        remaining_text = rest;
+        let (chunk, rest) = split_spans_at(remaining, available_content_cols);
+        remaining = rest;
+        let chunk: Vec<RtSpan<'static>> = chunk
+            .into_iter()
+            .map(|span| match line_style {
+                Some(style) => RtSpan::styled(span.content, style.patch(span.style)),
+                None => span,
+            })
+            .collect();
 
         if first {
             let mut spans: Vec<RtSpan<'static>> = Vec::new();
@@ -319,13 +391,12 @@ fn push_wrapped_diff_line(
             spans.push(RtSpan::raw(" ".repeat(gap_after_ln)));
             // Always include a sign character at the start of the displayed chunk
             // ('+' for insert, '-' for delete, ' ' for context) so gutters align.
-            let sign_char = sign_opt.unwrap_or(' ');
-            let display_chunk = format!("{sign_char}{chunk}");
-            let content_span = match line_style {
-                Some(style) => RtSpan::styled(display_chunk, style),
-                None => RtSpan::raw(display_chunk),
-            };
-            spans.push(content_span);
+            let sign_char = sign_opt.unwrap_or(' ').to_string();
+            spans.push(match line_style {
+                Some(style) => RtSpan::styled(sign_char, style),
+                None => RtSpan::raw(sign_char),
+            });
+            spans.extend(chunk);
             let mut line = RtLine::from(spans);
             if let Some(style) = line_style {
                 line.style = line.style.patch(style);
@@ -339,23 +410,57 @@ fn push_wrapped_diff_line(
                 " ".repeat(ln_str.len()),
                 " ".repeat(gap_after_ln)
             );
-            let content_span = match line_style {
-                Some(style) => RtSpan::styled(chunk.to_string(), style),
-                None => RtSpan::raw(chunk.to_string()),
-            };
-            let mut line = RtLine::from(vec![RtSpan::raw(hang_prefix), content_span]);
+            let mut spans = vec![RtSpan::raw(hang_prefix)];
+            spans.extend(chunk);
+            let mut line = RtLine::from(spans);
             if let Some(style) = line_style {
                 line.style = line.style.patch(style);
             }
             lines.push(line);
         }
-        if remaining_text.is_empty() {
+        if remaining.is_empty() {
             break;
         }
     }
     lines
 }
 
+/// Splits `spans` after `count` characters, cutting the span that straddles
+/// the boundary in two.
+fn split_spans_at(
+    spans: Vec<RtSpan<'static>>,
+    count: usize,
+) -> (Vec<RtSpan<'static>>, Vec<RtSpan<'static>>) {
+    let mut head = Vec::new();
+    let mut tail = Vec::new();
+    let mut left = count;
+    for span in spans {
+        if left == 0 {
+            tail.push(span);
+            continue;
+        }
+        let chars = span.content.chars().count();
+        if chars <= left {
+            left -= chars;
+            head.push(span);
+            continue;
+        }
+        let split = span
+            .content
+            .char_indices()
+            .nth(left)
+            .map(|(i, _)| i)
+            .unwrap_or(span.content.len());
+        let (first, rest) = span.content.split_at(split);
+        head.push(RtSpan::styled(first.to_string(), span.style));
+        tail.push(RtSpan::styled(rest.to_string(), span.style));
+        left = 0;
+    }
+    // Drop empty spans so an exactly consumed line ends the loop.
+    tail.retain(|span| !span.content.is_empty());
+    (head, tail)
+}
+
 fn style_dim() -> Style {
     Style::default().add_modifier(Modifier::DIM)
 }
@@ -401,8 +506,12 @@ mod tests {
             },
         );
 
-        let lines =
-            create_diff_summary("proposed patch", &changes, PatchEventType::ApprovalRequest);
+        let lines = create_diff_summary(
+            "proposed patch",
+            &changes,
+            PatchEventType::ApprovalRequest,
+            crate::syntax::DEFAULT_SYNTAX_THEME,
+        );
 
         snapshot_lines("add_details", lines, 80, 10);
     }
@@ -423,8 +532,12 @@ mod tests {
             },
         );
 
-        let lines =
-            create_diff_summary("proposed patch", &changes, PatchEventType::ApprovalRequest);
+        let lines = create_diff_summary(
+            "proposed patch",
+            &changes,
+            PatchEventType::ApprovalRequest,
+            crate::syntax::DEFAULT_SYNTAX_THEME,
+        );
 
         snapshot_lines("update_details_with_rename", lines, 80, 12);
     }
@@ -458,8 +571,12 @@ mod tests {
             },
         );
 
-        let lines =
-            create_diff_summary("proposed patch", &changes, PatchEventType::ApprovalRequest);
+        let lines = create_diff_summary(
+            "proposed patch",
+            &changes,
+            PatchEventType::ApprovalRequest,
+            crate::syntax::DEFAULT_SYNTAX_THEME,
+        );
 
         snapshot_lines("single_line_replacement_counts", lines, 80, 8);
     }
@@ -480,8 +597,12 @@ mod tests {
             },
         );
 
-        let lines =
-            create_diff_summary("proposed patch", &changes, PatchEventType::ApprovalRequest);
+        let lines = create_diff_summary(
+            "proposed patch",
+            &changes,
+            PatchEventType::ApprovalRequest,
+            crate::syntax::DEFAULT_SYNTAX_THEME,
+        );
 
         snapshot_lines("blank_context_line", lines, 80, 10);
     }
@@ -503,10 +624,44 @@ mod tests {
             },
         );
 
-        let lines =
-            create_diff_summary("proposed patch", &changes, PatchEventType::ApprovalRequest);
+        let lines = create_diff_summary(
+            "proposed patch",
+            &changes,
+            PatchEventType::ApprovalRequest,
+            crate::syntax::DEFAULT_SYNTAX_THEME,
+        );
 
         // Height is large enough to show both hunks and the separator
         snapshot_lines("vertical_ellipsis_between_hunks", lines, 80, 16);
     }
+
+    #[test]
+    fn added_lines_are_highlighted_and_wrap_across_spans() {
+        let mut highlighter = Highlighter::for_path(
+            std::path::Path::new("main.rs"),
+            crate::syntax::DEFAULT_SYNTAX_THEME,
+        );
+        let content = highlight(&mut highlighter, "fn main() { let answer = 42; }");
+        assert!(content.len() > 1, "{content:?}");
+
+        let lines = push_wrapped_diff_spans(1, DiffLineType::Insert, content, 30);
+        let text: Vec<String> = lines
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.content.as_ref()).collect())
+            .collect();
+        assert_eq!(
+            text,
+            vec![
+                "    1     +fn main() { let ans".to_string(),
+                "           wer = 42; }".to_string(),
+            ]
+        );
+        // The sign keeps the insert color; code spans carry their own colors.
+        assert_eq!(lines[0].spans[3].style.fg, Some(Color::Green));
+        assert!(
+            lines[0].spans[4..]
+                .iter()
+                .any(|s| s.style.fg.is_some_and(|fg| fg != Color::Green))
+        );
+    }
 }
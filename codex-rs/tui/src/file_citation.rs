@@ -0,0 +1,273 @@
+//! Clickable file citations: finding the citation under a mouse click in a
+//! rendered line, or every citation in it for keyboard selection, and
+//! opening it in the user's editor.
+
+use std::ops::Range;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use ratatui::text::Line;
+use unicode_width::UnicodeWidthChar;
+
+use crate::citation_regex::CITATION_REGEX;
+use crate::citation_regex::FILE_LINE_REGEX;
+use crate::tui;
+
+/// Characters that surround a citation in rendered text but are not part of
+/// the path, e.g. markdown link brackets or trailing punctuation.
+const SURROUNDING: &[char] = &[
+    '(', ')', '[', ']', '<', '>', '{', '}', '`', '\'', '"', ',', '.', ';', '!', '?',
+];
+
+/// Editors that go to a line with `-g path:line` rather than `+line path`.
+const GOTO_FLAG_EDITORS: &[&str] = &["code", "code-insiders", "codium", "cursor", "windsurf"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FileCitation {
+    pub(crate) path: PathBuf,
+    pub(crate) line: Option<u32>,
+}
+
+/// A citation of a line in an existing file, found in a rendered line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CitationLink {
+    /// Byte range of the citation in the line's text.
+    pub(crate) range: Range<usize>,
+    pub(crate) citation: FileCitation,
+}
+
+/// Every citation of a line in an existing file in `line`, in order: raw
+/// citations (`【F:src/lib.rs†L12】`) and rendered ones (`src/lib.rs:12`).
+pub(crate) fn citation_links(line: &Line<'_>, cwd: &Path) -> Vec<CitationLink> {
+    let text: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
+    let mut links: Vec<CitationLink> = Vec::new();
+    for regex in [&*CITATION_REGEX, &*FILE_LINE_REGEX] {
+        for caps in regex.captures_iter(&text) {
+            let Some(range) = caps.get(0).map(|m| m.range()) else {
+                continue;
+            };
+            if links
+                .iter()
+                .any(|link| link.range.start < range.end && range.start < link.range.end)
+            {
+                continue;
+            }
+            if let Some(citation) = resolve(&caps[1], caps[2].parse().ok(), cwd) {
+                links.push(CitationLink { range, citation });
+            }
+        }
+    }
+    links.sort_by_key(|link| link.range.start);
+    links
+}
+
+/// Returns the citation under display column `column` of `line`, if the word
+/// there names an existing file. Both rendered citations (`src/lib.rs:12`)
+/// and raw ones (`【F:src/lib.rs†L12】`) are recognized; relative paths are
+/// resolved against `cwd`.
+pub(crate) fn citation_at(line: &Line<'_>, column: u16, cwd: &Path) -> Option<FileCitation> {
+    let text: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
+    let word = word_at(&text, column as usize)?;
+
+    if let Some(caps) = CITATION_REGEX.captures(word) {
+        return resolve(&caps[1], caps[2].parse().ok(), cwd);
+    }
+
+    let word = word.trim_matches(SURROUNDING);
+    let (path, line_number) = split_line_suffix(word);
+    resolve(path, line_number, cwd)
+}
+
+/// The whitespace-delimited word covering display column `column`.
+fn word_at(text: &str, column: usize) -> Option<&str> {
+    let mut offset = 0;
+    let mut hit = None;
+    for (i, c) in text.char_indices() {
+        let width = c.width().unwrap_or(0);
+        if column < offset + width.max(1) {
+            hit = Some(i);
+            break;
+        }
+        offset += width;
+    }
+    let hit = hit?;
+    if text[hit..].starts_with(char::is_whitespace) {
+        return None;
+    }
+    let start = text[..hit]
+        .rfind(char::is_whitespace)
+        .map(|i| i + text[i..].chars().next().map_or(1, char::len_utf8))
+        .unwrap_or(0);
+    let end = text[hit..]
+        .find(char::is_whitespace)
+        .map(|i| hit + i)
+        .unwrap_or(text.len());
+    Some(&text[start..end])
+}
+
+/// Splits `path:line` (and `path:line:column`) into the path and line.
+fn split_line_suffix(word: &str) -> (&str, Option<u32>) {
+    let Some((rest, last)) = word.rsplit_once(':') else {
+        return (word, None);
+    };
+    let Ok(number) = last.parse::<u32>() else {
+        return (word, None);
+    };
+    match rest.rsplit_once(':') {
+        Some((path, line)) => match line.parse::<u32>() {
+            Ok(line) => (path, Some(line)),
+            Err(_) => (rest, Some(number)),
+        },
+        None => (rest, Some(number)),
+    }
+}
+
+fn resolve(path: &str, line: Option<u32>, cwd: &Path) -> Option<FileCitation> {
+    if path.is_empty() {
+        return None;
+    }
+    let path = cwd.join(path);
+    path.is_file().then_some(FileCitation { path, line })
+}
+
+/// The command line that opens `citation` in `editor`, which is split like a
+/// shell would split it. Falls back to `vi` when no editor is configured.
+fn editor_command(editor: Option<&str>, citation: &FileCitation) -> Vec<String> {
+    let mut argv = editor
+        .and_then(shlex::split)
+        .filter(|argv| !argv.is_empty())
+        .unwrap_or_else(|| vec!["vi".to_string()]);
+    let path = citation.path.to_string_lossy().into_owned();
+    let program = Path::new(&argv[0])
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    match citation.line {
+        Some(line) if GOTO_FLAG_EDITORS.contains(&program.as_str()) => {
+            argv.push("-g".to_string());
+            argv.push(format!("{path}:{line}"));
+        }
+        Some(line) => {
+            argv.push(format!("+{line}"));
+            argv.push(path);
+        }
+        None => argv.push(path),
+    }
+    argv
+}
+
+/// Opens `citation` in `$VISUAL` or `$EDITOR`, or in VS Code when running in
+/// its terminal without either set, handing it the terminal until it exits.
+pub(crate) fn open_in_editor(tui: &mut tui::Tui, citation: &FileCitation) -> std::io::Result<()> {
+    let editor = std::env::var("VISUAL")
+        .ok()
+        .filter(|v| !v.trim().is_empty())
+        .or_else(|| std::env::var("EDITOR").ok())
+        .or_else(|| {
+            std::env::var("TERM_PROGRAM")
+                .is_ok_and(|program| program == "vscode")
+                .then(|| "code".to_string())
+        });
+    let argv = editor_command(editor.as_deref(), citation);
+    let mut command = Command::new(&argv[0]);
+    command.args(&argv[1..]);
+    let status = tui.with_restored_terminal(|| command.status())??;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(std::io::Error::other(format!(
+            "{} exited with {status}",
+            argv[0]
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn finds_rendered_and_raw_citations_under_the_column() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir(dir.path().join("src")).expect("mkdir");
+        std::fs::write(dir.path().join("src/lib.rs"), "fn main() {}\n").expect("write");
+
+        let line = Line::from("see [src/lib.rs:12] and 【F:src/lib.rs†L3-L5】 here");
+        let expected = |line| {
+            Some(FileCitation {
+                path: dir.path().join("src/lib.rs"),
+                line: Some(line),
+            })
+        };
+        assert_eq!(citation_at(&line, 8, dir.path()), expected(12));
+        assert_eq!(citation_at(&line, 26, dir.path()), expected(3));
+        assert_eq!(citation_at(&line, 3, dir.path()), None);
+        assert_eq!(citation_at(&line, 0, dir.path()), None);
+        assert_eq!(citation_at(&line, 200, dir.path()), None);
+
+        let missing = Line::from("src/missing.rs:1");
+        assert_eq!(citation_at(&missing, 2, dir.path()), None);
+    }
+
+    #[test]
+    fn links_every_citation_of_an_existing_file() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::create_dir(dir.path().join("src")).expect("mkdir");
+        std::fs::write(dir.path().join("src/lib.rs"), "fn main() {}\n").expect("write");
+
+        let line = Line::from(vec![
+            "see ".into(),
+            "src/lib.rs:12".into(),
+            ", src/missing.rs:3 and 【F:src/lib.rs†L3-L5】 or src/lib.rs:4:2.".into(),
+        ]);
+        let text: String = line.spans.iter().map(|s| s.content.as_ref()).collect();
+        let links: Vec<(&str, Option<u32>)> = citation_links(&line, dir.path())
+            .into_iter()
+            .map(|link| (&text[link.range], link.citation.line))
+            .collect();
+        assert_eq!(
+            links,
+            vec![
+                ("src/lib.rs:12", Some(12)),
+                ("【F:src/lib.rs†L3-L5】", Some(3)),
+                ("src/lib.rs:4:2", Some(4)),
+            ]
+        );
+    }
+
+    #[test]
+    fn splits_line_and_column_suffixes() {
+        assert_eq!(split_line_suffix("a.rs:10:4"), ("a.rs", Some(10)));
+        assert_eq!(split_line_suffix("a.rs:10"), ("a.rs", Some(10)));
+        assert_eq!(split_line_suffix("a.rs"), ("a.rs", None));
+        assert_eq!(split_line_suffix("C:a.rs"), ("C:a.rs", None));
+    }
+
+    #[test]
+    fn editor_command_splits_editor_and_adds_line() {
+        let citation = FileCitation {
+            path: PathBuf::from("/tmp/a.rs"),
+            line: Some(7),
+        };
+        assert_eq!(
+            editor_command(Some("nvim -R"), &citation),
+            vec!["nvim", "-R", "+7", "/tmp/a.rs"]
+        );
+        assert_eq!(
+            editor_command(Some("code --wait"), &citation),
+            vec!["code", "--wait", "-g", "/tmp/a.rs:7"]
+        );
+        assert_eq!(
+            editor_command(
+                None,
+                &FileCitation {
+                    line: None,
+                    ..citation
+                }
+            ),
+            vec!["vi", "/tmp/a.rs"]
+        );
+    }
+}
@@ -17,21 +17,39 @@
 //!    recent query.
 //! 4. If there is a in-flight search that is not a prefix of the latest thing
 //!    the user typed, it is cancelled.
+//!
+//! While a search walks a large tree, the best matches found so far are sent
+//! to the popup as they improve, so results show up before the walk ends.
+//!
+//! Once the query is long enough, each search also looks for the query inside
+//! file contents so the popup can offer "search in files" results below the
+//! file name matches.
+//!
+//! Files picked from the popup or edited by the agent are remembered per
+//! workspace (see [`FileSearchManager::record_recent_files`]) and ranked
+//! above equally good matches in later searches.
 
 use codex_file_search as file_search;
+use codex_file_search::RecentFiles;
+use codex_file_search::ScoringOptions;
+use codex_file_search::WalkOptions;
 use std::num::NonZeroUsize;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
-use std::sync::atomic::AtomicBool;
-use std::sync::atomic::Ordering;
+use std::sync::mpsc;
 use std::thread;
 use std::time::Duration;
+use tokio_util::sync::CancellationToken;
 
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
 
 const MAX_FILE_SEARCH_RESULTS: NonZeroUsize = NonZeroUsize::new(8).unwrap();
+const MAX_CONTENT_SEARCH_RESULTS: NonZeroUsize = NonZeroUsize::new(8).unwrap();
+/// Shorter queries match too many lines to be useful as content results.
+const MIN_CONTENT_SEARCH_QUERY_LEN: usize = 3;
 const NUM_FILE_SEARCH_THREADS: NonZeroUsize = NonZeroUsize::new(2).unwrap();
 
 /// How long to wait after a keystroke before firing the first search when none
@@ -46,6 +64,15 @@ pub(crate) struct FileSearchManager {
     state: Arc<Mutex<SearchState>>,
 
     search_dir: PathBuf,
+    /// Which files are searched: `.codex/ignore` and `file_search.exclude`
+    /// patterns, and whether hidden or git-ignored files are included.
+    walk_options: WalkOptions,
+    /// Persistent file index consulted instead of walking `search_dir`
+    /// (`tui.file_index`).
+    index_path: Option<PathBuf>,
+    /// Files recently used in this workspace, and where they are saved.
+    recent_files: Arc<Mutex<RecentFiles>>,
+    recent_files_path: PathBuf,
     app_tx: AppEventSender,
 }
 
@@ -58,26 +85,65 @@ struct SearchState {
 
     /// If there is an active search, this will be the query being searched.
     active_search: Option<ActiveSearch>,
+
+    /// Identifies the next search, so a finished search only clears its own
+    /// `active_search`.
+    next_search_id: u64,
 }
 
 struct ActiveSearch {
+    id: u64,
     query: String,
-    cancellation_token: Arc<AtomicBool>,
+    cancellation_token: CancellationToken,
 }
 
 impl FileSearchManager {
-    pub fn new(search_dir: PathBuf, tx: AppEventSender) -> Self {
+    pub fn new(
+        search_dir: PathBuf,
+        walk_options: WalkOptions,
+        index_path: Option<PathBuf>,
+        recent_files_path: PathBuf,
+        tx: AppEventSender,
+    ) -> Self {
+        let recent_files = RecentFiles::load(&recent_files_path);
         Self {
             state: Arc::new(Mutex::new(SearchState {
                 latest_query: String::new(),
                 is_search_scheduled: false,
                 active_search: None,
+                next_search_id: 0,
             })),
             search_dir,
+            walk_options,
+            index_path,
+            recent_files: Arc::new(Mutex::new(recent_files)),
+            recent_files_path,
             app_tx: tx,
         }
     }
 
+    /// Remembers that `paths` were just used, so they rank higher in later
+    /// searches. Paths outside the search directory are ignored.
+    pub fn record_recent_files(&self, paths: Vec<PathBuf>) {
+        let relative: Vec<String> = paths
+            .iter()
+            .filter_map(|path| relative_to(&self.search_dir, path))
+            .collect();
+        if relative.is_empty() {
+            return;
+        }
+
+        #[expect(clippy::unwrap_used)]
+        let mut recent_files = self.recent_files.lock().unwrap();
+        recent_files.record(relative.iter().map(String::as_str));
+        if let Err(e) = recent_files.save(&self.recent_files_path) {
+            tracing::warn!(
+                "failed to save recent files to {}: {e:#}",
+                self.recent_files_path.display()
+            );
+        }
+    }
+
     /// Call whenever the user edits the `@` token.
     pub fn on_user_query(&self, query: String) {
         {
@@ -97,9 +163,7 @@ impl FileSearchManager {
             if let Some(active_search) = &st.active_search
                 && !query.starts_with(&active_search.query)
             {
-                active_search
-                    .cancellation_token
-                    .store(true, Ordering::Relaxed);
+                active_search.cancellation_token.cancel();
                 st.active_search = None;
             }
 
@@ -116,6 +180,9 @@ impl FileSearchManager {
         // debounce timer.
         let state = self.state.clone();
         let search_dir = self.search_dir.clone();
+        let walk_options = self.walk_options.clone();
+        let index_path = self.index_path.clone();
+        let recent_files = self.recent_files.clone();
         let tx_clone = self.app_tx.clone();
         thread::spawn(move || {
             // Always do a minimum debounce, but then poll until the
@@ -131,64 +198,126 @@ impl FileSearchManager {
 
             // The debounce timer has expired, so start a search using the
             // latest query.
-            let cancellation_token = Arc::new(AtomicBool::new(false));
-            let token = cancellation_token.clone();
-            let query = {
+            let cancellation_token = CancellationToken::new();
+            let (search_id, query) = {
                 #[expect(clippy::unwrap_used)]
                 let mut st = state.lock().unwrap();
                 let query = st.latest_query.clone();
+                let search_id = st.next_search_id;
+                st.next_search_id += 1;
                 st.is_search_scheduled = false;
                 st.active_search = Some(ActiveSearch {
+                    id: search_id,
                     query: query.clone(),
-                    cancellation_token: token,
+                    cancellation_token: cancellation_token.clone(),
                 });
-                query
+                (search_id, query)
+            };
+            #[expect(clippy::unwrap_used)]
+            let recent_files = recent_files.lock().unwrap().clone();
+            let scoring = ScoringOptions {
+                recent_files: Some(Arc::new(recent_files)),
+                ..Default::default()
             };
 
             FileSearchManager::spawn_file_search(
                 query,
                 search_dir,
+                walk_options,
+                scoring,
+                index_path,
                 tx_clone,
+                search_id,
                 cancellation_token,
                 state,
             );
         });
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn spawn_file_search(
         query: String,
         search_dir: PathBuf,
+        walk_options: WalkOptions,
+        scoring: ScoringOptions,
+        index_path: Option<PathBuf>,
         tx: AppEventSender,
-        cancellation_token: Arc<AtomicBool>,
+        search_id: u64,
+        cancellation_token: CancellationToken,
         search_state: Arc<Mutex<SearchState>>,
     ) {
         let compute_indices = true;
         std::thread::spawn(move || {
-            let matches = file_search::run(
-                &query,
-                MAX_FILE_SEARCH_RESULTS,
-                &search_dir,
-                Vec::new(),
-                NUM_FILE_SEARCH_THREADS,
-                cancellation_token.clone(),
-                compute_indices,
-            )
-            .map(|res| res.matches)
-            .unwrap_or_default();
-
-            let is_cancelled = cancellation_token.load(Ordering::Relaxed);
-            if !is_cancelled {
-                tx.send(AppEvent::FileSearchResult { query, matches });
+            let (updates_tx, updates_rx) = mpsc::channel();
+            let matches = thread::scope(|scope| {
+                scope.spawn(|| {
+                    file_search::run_streaming(
+                        &query,
+                        MAX_FILE_SEARCH_RESULTS,
+                        &search_dir,
+                        walk_options.clone(),
+                        NUM_FILE_SEARCH_THREADS,
+                        cancellation_token.clone(),
+                        compute_indices,
+                        scoring,
+                        index_path.as_deref(),
+                        updates_tx,
+                    )
+                });
+
+                // Show snapshots as they arrive; the complete results are
+                // sent below together with the content matches.
+                let mut matches = Vec::new();
+                for update in updates_rx {
+                    if cancellation_token.is_cancelled() {
+                        break;
+                    }
+                    matches = update.results.matches;
+                    if !update.complete {
+                        tx.send(AppEvent::FileSearchResult {
+                            query: query.clone(),
+                            matches: matches.clone(),
+                            content_matches: Vec::new(),
+                        });
+                    }
+                }
+                matches
+            });
+
+            // The query is treated as a regex; one that does not compile
+            // simply yields no content results.
+            let content_matches = if query.chars().count() >= MIN_CONTENT_SEARCH_QUERY_LEN
+                && !cancellation_token.is_cancelled()
+            {
+                file_search::run_content_search(
+                    &query,
+                    MAX_CONTENT_SEARCH_RESULTS,
+                    &search_dir,
+                    &walk_options,
+                    NUM_FILE_SEARCH_THREADS,
+                    cancellation_token.clone(),
+                )
+                .map(|res| res.matches)
+                .unwrap_or_default()
+            } else {
+                Vec::new()
+            };
+
+            if !cancellation_token.is_cancelled() {
+                tx.send(AppEvent::FileSearchResult {
+                    query,
+                    matches,
+                    content_matches,
+                });
             }
 
-            // Reset the active search state. Do a pointer comparison to verify
-            // that we are clearing the ActiveSearch that corresponds to the
-            // cancellation token we were given.
+            // Reset the active search state, unless a newer search has
+            // already replaced this one.
             {
                 #[expect(clippy::unwrap_used)]
                 let mut st = search_state.lock().unwrap();
                 if let Some(active_search) = &st.active_search
-                    && Arc::ptr_eq(&active_search.cancellation_token, &cancellation_token)
+                    && active_search.id == search_id
                 {
                     st.active_search = None;
                 }
@@ -196,3 +325,17 @@ impl FileSearchManager {
         });
     }
 }
+
+/// `path` relative to `search_dir`, in the form search results use. Relative
+/// paths are taken to already be relative to `search_dir`.
+fn relative_to(search_dir: &Path, path: &Path) -> Option<String> {
+    let relative = if path.is_absolute() {
+        path.strip_prefix(search_dir).ok()?
+    } else {
+        path
+    };
+    relative
+        .to_str()
+        .filter(|relative| !relative.is_empty())
+        .map(str::to_string)
+}
@@ -0,0 +1,176 @@
+//! `tui.watch_files`: notices files changed in the workspace outside the
+//! agent, e.g. saved from an IDE, so the user can send their diff with the
+//! next message.
+//!
+//! Filesystem events are batched until the workspace has been quiet for
+//! [`DEBOUNCE`], then sent as one [`AppEvent::ExternalFileChanges`]. The
+//! watcher cannot tell who wrote a file; the chat widget drops batches that
+//! started while a turn was running.
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::mpsc;
+use std::time::Duration;
+use std::time::Instant;
+
+use ignore::gitignore::Gitignore;
+use notify::Event;
+use notify::EventKind;
+use notify::RecommendedWatcher;
+use notify::RecursiveMode;
+use notify::Watcher;
+
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+
+/// How long the workspace must be quiet before a batch is reported, so a
+/// save that touches several files (or a branch switch) is one batch.
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Watches the workspace until dropped.
+pub(crate) struct FileWatcher {
+    _watcher: RecommendedWatcher,
+}
+
+impl FileWatcher {
+    pub(crate) fn start(root: &Path, app_event_tx: AppEventSender) -> notify::Result<Self> {
+        // Events carry resolved paths (e.g. `/private/var` for `/var` on
+        // macOS), so the root they are made relative to must be resolved too.
+        let root = root.canonicalize()?;
+        let (path_tx, path_rx) = mpsc::channel::<PathBuf>();
+        let filter = PathFilter::new(&root);
+        let mut watcher = notify::recommended_watcher(move |res: notify::Result<Event>| {
+            let event = match res {
+                Ok(event) => event,
+                Err(e) => {
+                    tracing::debug!("file watcher error: {e}");
+                    return;
+                }
+            };
+            if !matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) {
+                return;
+            }
+            for path in event.paths {
+                if let Some(relative) = filter.relevant(&path) {
+                    let _ = path_tx.send(relative);
+                }
+            }
+        })?;
+        watcher.watch(&root, RecursiveMode::Recursive)?;
+
+        // Ends when the watcher, and with it the sending half, is dropped.
+        std::thread::spawn(move || debounce(path_rx, app_event_tx));
+        Ok(Self { _watcher: watcher })
+    }
+}
+
+fn debounce(path_rx: mpsc::Receiver<PathBuf>, app_event_tx: AppEventSender) {
+    while let Ok(first) = path_rx.recv() {
+        let since = Instant::now();
+        let mut paths = BTreeSet::from([first]);
+        let disconnected = loop {
+            match path_rx.recv_timeout(DEBOUNCE) {
+                Ok(path) => {
+                    paths.insert(path);
+                }
+                Err(mpsc::RecvTimeoutError::Timeout) => break false,
+                Err(mpsc::RecvTimeoutError::Disconnected) => break true,
+            }
+        };
+        app_event_tx.send(AppEvent::ExternalFileChanges {
+            paths: paths.into_iter().collect(),
+            since,
+        });
+        if disconnected {
+            return;
+        }
+    }
+}
+
+/// Decides which paths are worth reporting: files in the workspace that are
+/// neither in `.git` nor ignored by the root `.gitignore`.
+struct PathFilter {
+    root: PathBuf,
+    gitignore: Gitignore,
+}
+
+impl PathFilter {
+    fn new(root: &Path) -> Self {
+        let (gitignore, err) = Gitignore::new(root.join(".gitignore"));
+        if let Some(err) = err
+            && root.join(".gitignore").exists()
+        {
+            tracing::warn!("failed to parse .gitignore for the file watcher: {err}");
+        }
+        Self {
+            root: root.to_path_buf(),
+            gitignore,
+        }
+    }
+
+    /// `path` relative to the workspace root, or `None` when it should not
+    /// be reported.
+    fn relevant(&self, path: &Path) -> Option<PathBuf> {
+        let relative = path.strip_prefix(&self.root).ok()?;
+        if relative.as_os_str().is_empty() || relative.starts_with(".git") {
+            return None;
+        }
+        if self
+            .gitignore
+            .matched_path_or_any_parents(relative, path.is_dir())
+            .is_ignore()
+        {
+            return None;
+        }
+        Some(relative.to_path_buf())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    #[test]
+    fn skips_git_and_ignored_paths() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join(".gitignore"), "target/\n*.log\n").expect("write");
+        let filter = PathFilter::new(dir.path());
+
+        assert_eq!(
+            filter.relevant(&dir.path().join("src/main.rs")),
+            Some(PathBuf::from("src/main.rs"))
+        );
+        assert_eq!(filter.relevant(&dir.path().join(".git/index")), None);
+        assert_eq!(filter.relevant(&dir.path().join("target/debug/app")), None);
+        assert_eq!(filter.relevant(&dir.path().join("build.log")), None);
+        assert_eq!(filter.relevant(Path::new("/elsewhere/file.rs")), None);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn reports_a_batch_of_changed_files() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let _watcher =
+            FileWatcher::start(dir.path(), AppEventSender::new(tx_raw)).expect("watcher");
+
+        std::fs::write(dir.path().join("a.txt"), "a").expect("write");
+        std::fs::write(dir.path().join("b.txt"), "b").expect("write");
+
+        let event = tokio::time::timeout(Duration::from_secs(10), rx.recv())
+            .await
+            .expect("no batch reported")
+            .expect("event");
+        match event {
+            AppEvent::ExternalFileChanges { paths, .. } => {
+                assert_eq!(paths, vec![PathBuf::from("a.txt"), PathBuf::from("b.txt")])
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+}
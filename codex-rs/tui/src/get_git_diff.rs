@@ -7,6 +7,7 @@
 
 use std::io;
 use std::path::Path;
+use std::path::PathBuf;
 use std::process::Stdio;
 use tokio::process::Command;
 
@@ -61,6 +62,58 @@ pub(crate) async fn get_git_diff() -> io::Result<(bool, String)> {
     Ok((true, format!("{tracked_diff}{untracked_diff}")))
 }
 
+/// The diff of `paths` in the repository at `cwd` against `HEAD`, untracked
+/// files included, without color. Empty outside a repository.
+pub(crate) async fn get_git_diff_for_paths(cwd: &Path, paths: &[PathBuf]) -> io::Result<String> {
+    let cwd = cwd.to_string_lossy().into_owned();
+    let mut tracked_args = vec!["-C", &cwd, "diff", "--no-color", "HEAD", "--"];
+    let mut untracked_args = vec![
+        "-C",
+        &cwd,
+        "ls-files",
+        "--others",
+        "--exclude-standard",
+        "--",
+    ];
+    let paths: Vec<String> = paths
+        .iter()
+        .map(|path| path.to_string_lossy().into_owned())
+        .collect();
+    tracked_args.extend(paths.iter().map(String::as_str));
+    untracked_args.extend(paths.iter().map(String::as_str));
+
+    let (tracked_diff, untracked_output) = tokio::join!(
+        run_git_capture_diff(&tracked_args),
+        run_git_capture_stdout(&untracked_args),
+    );
+    // Fails outside a repository, or before the first commit.
+    let Ok(mut diff) = tracked_diff else {
+        return Ok(String::new());
+    };
+    for file in untracked_output?
+        .lines()
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+    {
+        let args = [
+            "-C",
+            &cwd,
+            "diff",
+            "--no-color",
+            "--no-index",
+            "--",
+            null_device(),
+            file,
+        ];
+        diff.push_str(&run_git_capture_diff(&args).await?);
+    }
+    Ok(diff)
+}
+
+fn null_device() -> &'static str {
+    if cfg!(windows) { "NUL" } else { "/dev/null" }
+}
+
 /// Helper that executes `git` with the given `args` and returns `stdout` as a
 /// UTF-8 string. Any non-zero exit status is considered an *error*.
 async fn run_git_capture_stdout(args: &[&str]) -> io::Result<String> {
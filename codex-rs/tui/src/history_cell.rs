@@ -8,16 +8,23 @@ use base64::Engine;
 use codex_ansi_escape::ansi_escape_line;
 use codex_common::create_config_summary_entries;
 use codex_common::elapsed::format_duration;
+use codex_common::summarize_sandbox_policy;
 use codex_core::config::Config;
+use codex_core::config_types::ModelPricing;
 use codex_core::plan_tool::PlanItemArg;
 use codex_core::plan_tool::StepStatus;
 use codex_core::plan_tool::UpdatePlanArgs;
 use codex_core::project_doc::discover_project_doc_paths;
+use codex_core::protocol::BackgroundTaskInfo;
+use codex_core::protocol::BackgroundTaskStatus;
 use codex_core::protocol::FileChange;
 use codex_core::protocol::McpInvocation;
+use codex_core::protocol::ProfileSwitchedEvent;
+use codex_core::protocol::SandboxDenial;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol::SessionConfiguredEvent;
 use codex_core::protocol::TokenUsage;
+use codex_core::protocol::TokenUsageBreakdown;
 use codex_login::get_auth_file;
 use codex_login::try_read_auth_json;
 use codex_protocol::parse_command::ParsedCommand;
@@ -35,6 +42,7 @@ use ratatui::widgets::WidgetRef;
 use ratatui::widgets::Wrap;
 use std::collections::HashMap;
 use std::io::Cursor;
+use std::path::Path;
 use std::path::PathBuf;
 use std::time::Duration;
 use std::time::Instant;
@@ -47,6 +55,8 @@ pub(crate) struct CommandOutput {
     pub(crate) stdout: String,
     pub(crate) stderr: String,
     pub(crate) formatted_output: String,
+    /// What the sandbox recorded refusing the command.
+    pub(crate) sandbox_denials: Vec<SandboxDenial>,
 }
 
 pub(crate) enum PatchEventType {
@@ -64,6 +74,12 @@ pub(crate) trait HistoryCell: std::fmt::Debug + Send + Sync {
         self.display_lines()
     }
 
+    /// Image drawn below the display lines when the terminal supports inline
+    /// graphics; the display lines must make sense without it.
+    fn inline_image(&self) -> Option<&DynamicImage> {
+        None
+    }
+
     fn desired_height(&self, width: u16) -> u16 {
         Paragraph::new(Text::from(self.display_lines()))
             .wrap(Wrap { trim: false })
@@ -107,16 +123,22 @@ pub(crate) struct ExecCell {
     start_time: Option<Instant>,
     duration: Option<Duration>,
     include_header: bool,
+    /// Tail of the output streamed so far, shown until the command completes.
+    live_output: Vec<u8>,
 }
 impl HistoryCell for ExecCell {
     fn display_lines(&self) -> Vec<Line<'static>> {
-        exec_command_lines(
+        let mut lines = exec_command_lines(
             &self.command,
             &self.parsed,
             self.output.as_ref(),
             self.start_time,
             self.include_header,
-        )
+        );
+        if self.output.is_none() {
+            lines.extend(live_output_lines(&self.live_output));
+        }
+        lines
     }
 
     fn transcript_lines(&self) -> Vec<Line<'static>> {
@@ -176,6 +198,35 @@ impl WidgetRef for &ExecCell {
 }
 
 impl ExecCell {
+    /// Append output streamed by a running command, keeping only the last
+    /// [`LIVE_OUTPUT_MAX_BYTES`].
+    pub(crate) fn append_live_output(&mut self, chunk: &[u8]) {
+        self.live_output.extend_from_slice(chunk);
+        if self.live_output.len() > LIVE_OUTPUT_MAX_BYTES {
+            let excess = self.live_output.len() - LIVE_OUTPUT_MAX_BYTES;
+            self.live_output.drain(..excess);
+        }
+    }
+
+    /// The command followed by all the output it has streamed so far (up to
+    /// [`LIVE_OUTPUT_MAX_BYTES`]), for the working pane of the split layout.
+    pub(crate) fn working_lines(&self) -> Vec<Line<'static>> {
+        let mut lines = exec_command_lines(
+            &self.command,
+            &self.parsed,
+            self.output.as_ref(),
+            self.start_time,
+            false,
+        );
+        let text = String::from_utf8_lossy(&self.live_output);
+        lines.extend(text.lines().map(|raw| {
+            let mut line = ansi_escape_line(raw);
+            line.spans.insert(0, "    ".into());
+            line
+        }));
+        lines
+    }
+
     /// Convert an active exec cell into a failed, completed exec cell.
     /// Replaces the spinner with a red ✗ and sets a zero/elapsed duration.
     pub(crate) fn into_failed(mut self) -> ExecCell {
@@ -190,25 +241,34 @@ impl ExecCell {
             stdout: String::new(),
             stderr: String::new(),
             formatted_output: String::new(),
+            sandbox_denials: Vec::new(),
         });
         self
     }
 }
 
+/// An image with a caption. The caption doubles as the placeholder shown
+/// where the terminal cannot draw the image.
 #[derive(Debug)]
-struct CompletedMcpToolCallWithImageOutput {
-    _image: DynamicImage,
+pub(crate) struct ImageHistoryCell {
+    lines: Vec<Line<'static>>,
+    image: Option<DynamicImage>,
 }
-impl HistoryCell for CompletedMcpToolCallWithImageOutput {
+
+impl HistoryCell for ImageHistoryCell {
     fn display_lines(&self) -> Vec<Line<'static>> {
-        vec![
-            Line::from(""),
-            Line::from("tool result (image output omitted)"),
-        ]
+        self.lines.clone()
+    }
+
+    fn inline_image(&self) -> Option<&DynamicImage> {
+        self.image.as_ref()
     }
 }
 
 const TOOL_CALL_MAX_LINES: usize = 5;
+/// Enough streamed output to fill [`TOOL_CALL_MAX_LINES`] of any sensible
+/// width; older output is dropped.
+const LIVE_OUTPUT_MAX_BYTES: usize = 16 * 1024;
 
 fn title_case(s: &str) -> String {
     if s.is_empty() {
@@ -255,6 +315,8 @@ pub(crate) fn new_session_info(
         session_id: _,
         history_log_id: _,
         history_entry_count: _,
+        project_docs: _,
+        protocol: _,
     } = event;
     if is_first_event {
         let cwd_str = match relativize_to_home(&config.cwd) {
@@ -307,6 +369,29 @@ pub(crate) fn new_user_prompt(message: String) -> PlainHistoryCell {
     PlainHistoryCell { lines }
 }
 
+/// An image the user attached to their prompt. Unreadable images still get
+/// a caption naming the file.
+pub(crate) fn new_user_image(path: &Path) -> ImageHistoryCell {
+    let image = ImageReader::open(path)
+        .and_then(|reader| reader.with_guessed_format())
+        .map_err(image::ImageError::IoError)
+        .and_then(|reader| reader.decode())
+        .inspect_err(|e| error!("failed to read attached image {}: {e}", path.display()))
+        .ok();
+    let name = path.file_name().map_or_else(
+        || path.display().to_string(),
+        |n| n.to_string_lossy().into_owned(),
+    );
+    let mut caption = vec![padded_emoji("🖼").into(), name.into()];
+    if let Some(image) = &image {
+        caption.push(format!(" {}x{}", image.width(), image.height()).dim());
+    }
+    ImageHistoryCell {
+        lines: vec![Line::from(caption)],
+        image,
+    }
+}
+
 pub(crate) fn new_active_exec_command(
     command: Vec<String>,
     parsed: Vec<ParsedCommand>,
@@ -319,6 +404,7 @@ pub(crate) fn new_active_exec_command(
         start_time: Some(Instant::now()),
         duration: None,
         include_header,
+        live_output: Vec::new(),
     }
 }
 
@@ -336,6 +422,7 @@ pub(crate) fn new_completed_exec_command(
         start_time: None,
         duration: Some(duration),
         include_header,
+        live_output: Vec::new(),
     }
 }
 
@@ -482,6 +569,18 @@ pub(crate) fn new_active_mcp_tool_call(invocation: McpInvocation) -> PlainHistor
     PlainHistoryCell { lines }
 }
 
+pub(crate) fn new_container_image_pull(image: String) -> PlainHistoryCell {
+    let lines: Vec<Line<'static>> = vec![
+        Line::from(""),
+        Line::from(vec![
+            padded_emoji("📦").into(),
+            "Pulling container image ".into(),
+            image.bold(),
+        ]),
+    ];
+    PlainHistoryCell { lines }
+}
+
 pub(crate) fn new_web_search_call(query: String) -> PlainHistoryCell {
     let lines: Vec<Line<'static>> = vec![
         Line::from(""),
@@ -494,7 +593,7 @@ pub(crate) fn new_web_search_call(query: String) -> PlainHistoryCell {
 /// TODO(rgwood-dd): Handle images properly even if they're not the first result.
 fn try_new_completed_mcp_tool_call_with_image_output(
     result: &Result<mcp_types::CallToolResult, String>,
-) -> Option<CompletedMcpToolCallWithImageOutput> {
+) -> Option<ImageHistoryCell> {
     match result {
         Ok(mcp_types::CallToolResult { content, .. }) => {
             if let Some(mcp_types::ContentBlock::ImageContent(image)) = content.first() {
@@ -521,7 +620,18 @@ fn try_new_completed_mcp_tool_call_with_image_output(
                     }
                 };
 
-                Some(CompletedMcpToolCallWithImageOutput { _image: image })
+                let lines = vec![
+                    Line::from(""),
+                    Line::from(format!(
+                        "tool result (image {}x{})",
+                        image.width(),
+                        image.height()
+                    )),
+                ];
+                Some(ImageHistoryCell {
+                    lines,
+                    image: Some(image),
+                })
             } else {
                 None
             }
@@ -805,6 +915,387 @@ pub(crate) fn new_status_output(
     PlainHistoryCell { lines }
 }
 
+/// Tokens reported for one turn, and the model that spent them.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct TurnTokenUsage {
+    pub(crate) model: String,
+    pub(crate) usage: TokenUsage,
+}
+
+/// Most turns charted by `/usage`; earlier turns only count toward the
+/// totals.
+const USAGE_CHART_TURNS: usize = 12;
+
+/// Widest bar in the `/usage` chart.
+const USAGE_BAR_WIDTH: usize = 20;
+
+/// Render the tokens and estimated cost of each turn, and where the tokens
+/// of the last turn and of the whole session went. With `detail`, tool calls
+/// are broken down per tool.
+pub(crate) fn new_usage_output(
+    turns: &[TurnTokenUsage],
+    pricing: &HashMap<String, ModelPricing>,
+    last_turn: &TokenUsageBreakdown,
+    session: &TokenUsageBreakdown,
+    detail: bool,
+) -> PlainHistoryCell {
+    let command = if detail { "/usage detail" } else { "/usage" };
+    let mut lines: Vec<Line<'static>> = vec![
+        Line::from(""),
+        Line::from(command.magenta()),
+        Line::from(vec!["📊 ".into(), "Token Usage".bold()]),
+    ];
+
+    let turns: Vec<&TurnTokenUsage> = turns.iter().filter(|t| !t.usage.is_zero()).collect();
+    if turns.is_empty() && session.total() == 0 {
+        lines.push(Line::from("  • No token usage reported yet.".italic()));
+        return PlainHistoryCell { lines };
+    }
+
+    if !turns.is_empty() {
+        push_turn_usage_chart(&mut lines, &turns, pricing);
+    }
+    if session.total() == 0 {
+        return PlainHistoryCell { lines };
+    }
+
+    for (title, breakdown) in [("Last turn", last_turn), ("Session", session)] {
+        let total = breakdown.total();
+        lines.push(Line::from(vec![
+            format!("  {title}: ").bold(),
+            format!("{total} tokens").into(),
+        ]));
+        let percent = |tokens: u64| {
+            if total == 0 {
+                0
+            } else {
+                (tokens as f64 / total as f64 * 100.0).round() as u64
+            }
+        };
+        let tool_tokens: u64 = breakdown.tools.iter().map(|tool| tool.tokens).sum();
+        for (label, tokens) in [
+            ("Instructions", breakdown.instructions),
+            ("Tool schemas", breakdown.tool_schemas),
+            ("Messages", breakdown.messages),
+            ("Reasoning", breakdown.reasoning),
+            ("Tool calls", tool_tokens),
+        ] {
+            lines.push(Line::from(format!(
+                "  • {label}: {tokens} ({}%)",
+                percent(tokens)
+            )));
+        }
+        if detail {
+            let mut tools: Vec<_> = breakdown.tools.iter().collect();
+            tools.sort_by(|a, b| b.tokens.cmp(&a.tokens).then_with(|| a.name.cmp(&b.name)));
+            for tool in tools {
+                lines.push(Line::from(format!(
+                    "    • {}: {} ({}%)",
+                    tool.name,
+                    tool.tokens,
+                    percent(tool.tokens)
+                )));
+            }
+        }
+    }
+
+    let hint = if detail {
+        "  Estimated from the size of each part of the requests."
+    } else {
+        "  Estimated from the size of each part of the requests. Run /usage detail to list each tool."
+    };
+    lines.push(Line::from(hint.dim()));
+
+    PlainHistoryCell { lines }
+}
+
+/// Charts the tokens, estimated cost and cache hits of the last turns,
+/// followed by the totals of all of them.
+fn push_turn_usage_chart(
+    lines: &mut Vec<Line<'static>>,
+    turns: &[&TurnTokenUsage],
+    pricing: &HashMap<String, ModelPricing>,
+) {
+    let cost = |turn: &TurnTokenUsage| {
+        pricing
+            .get(&turn.model)
+            .map(|pricing| pricing.cost(&turn.usage))
+    };
+    let first = turns.len().saturating_sub(USAGE_CHART_TURNS);
+    let title = if first > 0 {
+        format!("  Per turn (last {USAGE_CHART_TURNS} of {}):", turns.len())
+    } else {
+        "  Per turn:".to_string()
+    };
+    lines.push(Line::from(title.bold()));
+
+    let max_tokens = turns[first..]
+        .iter()
+        .map(|turn| turn.usage.blended_total())
+        .max()
+        .unwrap_or(0);
+    let number_width = turns.len().to_string().len();
+    for (idx, turn) in turns.iter().enumerate().skip(first) {
+        let tokens = turn.usage.blended_total();
+        let mut spans: Vec<Span<'static>> = vec![
+            format!("  {:>number_width$} ", idx + 1).dim(),
+            format!("{:<USAGE_BAR_WIDTH$}", usage_bar(tokens, max_tokens)).cyan(),
+            format!(" {:>6}", format_token_count(tokens)).into(),
+        ];
+        if let Some(cost) = cost(turn) {
+            spans.push(format!(" · {}", format_cost(cost)).into());
+        }
+        if let Some(percent) = turn.usage.cached_input_percent() {
+            spans.push(format!(" · {percent}% cached").dim());
+        }
+        lines.push(Line::from(spans));
+    }
+
+    let mut total = TokenUsage::default();
+    for turn in turns {
+        total = TokenUsage {
+            input_tokens: total.input_tokens + turn.usage.input_tokens,
+            cached_input_tokens: match (total.cached_input_tokens, turn.usage.cached_input_tokens) {
+                (None, None) => None,
+                (a, b) => Some(a.unwrap_or(0) + b.unwrap_or(0)),
+            },
+            output_tokens: total.output_tokens + turn.usage.output_tokens,
+            reasoning_output_tokens: None,
+            total_tokens: total.total_tokens + turn.usage.total_tokens,
+        };
+    }
+    let mut spans: Vec<Span<'static>> = vec![
+        "  Cumulative: ".bold(),
+        format!("{} tokens", format_token_count(total.blended_total())).into(),
+    ];
+    if let Some(percent) = total.cached_input_percent() {
+        spans.push(format!(" · {percent}% of input cached").into());
+    }
+    let costs: Option<Vec<f64>> = turns.iter().map(|turn| cost(turn)).collect();
+    match costs {
+        Some(costs) => spans.push(format!(" · {}", format_cost(costs.iter().sum())).into()),
+        None => {
+            let mut unpriced: Vec<&str> = turns
+                .iter()
+                .filter(|turn| !pricing.contains_key(&turn.model))
+                .map(|turn| turn.model.as_str())
+                .collect();
+            unpriced.sort_unstable();
+            unpriced.dedup();
+            lines.push(Line::from(spans));
+            lines.push(Line::from(
+                format!(
+                    "  Add [tui.model_pricing] for {} to estimate cost.",
+                    unpriced.join(", ")
+                )
+                .dim(),
+            ));
+            return;
+        }
+    }
+    lines.push(Line::from(spans));
+}
+
+/// A horizontal bar for `value` out of `max`, drawn in eighths of a cell.
+fn usage_bar(value: u64, max: u64) -> String {
+    const PARTIAL: [&str; 8] = ["", "▏", "▎", "▍", "▌", "▋", "▊", "▉"];
+    if max == 0 {
+        return String::new();
+    }
+    let eighths = (value as u128 * (USAGE_BAR_WIDTH as u128 * 8) / max as u128) as usize;
+    // Any usage at all gets at least a sliver.
+    let eighths = if value > 0 { eighths.max(1) } else { 0 };
+    format!("{}{}", "█".repeat(eighths / 8), PARTIAL[eighths % 8])
+}
+
+/// `950`, `12.3k`, `1.2M`.
+fn format_token_count(tokens: u64) -> String {
+    match tokens {
+        0..1_000 => tokens.to_string(),
+        1_000..1_000_000 => format!("{:.1}k", tokens as f64 / 1_000.0),
+        _ => format!("{:.1}M", tokens as f64 / 1_000_000.0),
+    }
+}
+
+fn format_cost(dollars: f64) -> String {
+    if dollars > 0.0 && dollars < 0.01 {
+        "<$0.01".to_string()
+    } else {
+        format!("${dollars:.2}")
+    }
+}
+
+/// Number of output lines shown for each task by `/tasks`.
+const BACKGROUND_TASK_OUTPUT_LINES: usize = 5;
+
+pub(crate) fn new_background_tasks_output(tasks: &[BackgroundTaskInfo]) -> PlainHistoryCell {
+    let mut lines: Vec<Line<'static>> = vec![
+        Line::from(""),
+        Line::from("/tasks".magenta()),
+        Line::from(vec!["⚙ ".into(), "Background Tasks".bold()]),
+    ];
+
+    if tasks.is_empty() {
+        lines.push(Line::from(
+            "  • No background tasks. Start one with /tasks run <command>.".italic(),
+        ));
+        return PlainHistoryCell { lines };
+    }
+
+    for task in tasks {
+        let status = match task.status {
+            BackgroundTaskStatus::Running => "running".green(),
+            BackgroundTaskStatus::Exited { exit_code: 0 } => "exited".dim(),
+            BackgroundTaskStatus::Exited { exit_code } => format!("exited ({exit_code})").red(),
+            BackgroundTaskStatus::Killed => "killed".red(),
+        };
+        lines.push(Line::from(vec![
+            format!("  • #{} ", task.id).bold(),
+            strip_bash_lc_and_escape(&task.command).into(),
+            " — ".dim(),
+            status,
+        ]));
+        let output: Vec<&str> = task
+            .output_tail
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        let skip = output.len().saturating_sub(BACKGROUND_TASK_OUTPUT_LINES);
+        for line in &output[skip..] {
+            lines.push(Line::from(format!("    {line}").dim()));
+        }
+    }
+    lines.push(Line::from(
+        "  Stop a running task with /tasks kill <id>.".dim(),
+    ));
+
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_background_task_update(task: &BackgroundTaskInfo) -> PlainHistoryCell {
+    let status = match task.status {
+        BackgroundTaskStatus::Running => "started".into(),
+        BackgroundTaskStatus::Exited { exit_code: 0 } => "exited".into(),
+        BackgroundTaskStatus::Exited { exit_code } => format!("exited with code {exit_code}").red(),
+        BackgroundTaskStatus::Killed => "was killed".red(),
+    };
+    let lines: Vec<Line<'static>> = vec![
+        Line::from(""),
+        Line::from(vec![
+            "⚙ ".into(),
+            format!("Background task #{}", task.id).bold(),
+            " ".into(),
+            status,
+            ": ".into(),
+            strip_bash_lc_and_escape(&task.command).dim(),
+        ]),
+    ];
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_turn_reverted(paths: &[PathBuf]) -> PlainHistoryCell {
+    let mut lines: Vec<Line<'static>> = vec![
+        Line::from(""),
+        Line::from(vec!["↺ ".into(), "Reverted turn".bold()]),
+    ];
+    for path in paths {
+        lines.push(Line::from(format!("  └ {}", path.display()).dim()));
+    }
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_session_exported(path: &Path) -> PlainHistoryCell {
+    let lines: Vec<Line<'static>> = vec![
+        Line::from(""),
+        Line::from(vec!["⇪ ".into(), "Exported conversation".bold()]),
+        Line::from(format!("  └ {}", path.display()).dim()),
+    ];
+    PlainHistoryCell { lines }
+}
+
+/// Most changed files named in the one-line summary of external changes.
+const EXTERNAL_CHANGES_MAX_NAMED: usize = 3;
+
+pub(crate) fn new_external_file_changes(paths: &[PathBuf]) -> PlainHistoryCell {
+    let mut names: Vec<String> = paths
+        .iter()
+        .take(EXTERNAL_CHANGES_MAX_NAMED)
+        .map(|path| path.display().to_string())
+        .collect();
+    if paths.len() > EXTERNAL_CHANGES_MAX_NAMED {
+        names.push(format!(
+            "+{} more",
+            paths.len() - EXTERNAL_CHANGES_MAX_NAMED
+        ));
+    }
+    let lines: Vec<Line<'static>> = vec![
+        Line::from(""),
+        Line::from(vec![
+            "✎ ".into(),
+            "Changed outside Codex".bold(),
+            format!(" · {}", names.join(", ")).dim(),
+        ]),
+    ];
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_external_changes_attached(file_count: usize) -> PlainHistoryCell {
+    let noun = if file_count == 1 { "file" } else { "files" };
+    let lines: Vec<Line<'static>> = vec![
+        Line::from(""),
+        Line::from(vec![
+            "✎ ".into(),
+            format!("The changes to {file_count} {noun} will be sent with your next message").dim(),
+        ]),
+    ];
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_context_compacted(summary_tokens: u64) -> PlainHistoryCell {
+    let lines: Vec<Line<'static>> = vec![
+        Line::from(""),
+        Line::from(vec![
+            "⇣ ".into(),
+            "Compacted conversation".bold(),
+            format!(" · older turns replaced by a {summary_tokens}-token summary").dim(),
+        ]),
+    ];
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn new_profile_switched(ev: &ProfileSwitchedEvent) -> PlainHistoryCell {
+    let lines: Vec<Line<'static>> = vec![
+        Line::from(""),
+        Line::from(vec![
+            "⇄ ".into(),
+            "Switched to profile ".bold(),
+            ev.profile.clone().magenta().bold(),
+        ]),
+        Line::from(
+            format!(
+                "  └ model {} ({}) · approval {} · sandbox {}",
+                ev.model,
+                ev.model_provider_id,
+                ev.approval_policy,
+                summarize_sandbox_policy(&ev.sandbox_policy)
+            )
+            .dim(),
+        ),
+    ];
+    PlainHistoryCell { lines }
+}
+
+pub(crate) fn empty_profiles_output() -> PlainHistoryCell {
+    let lines: Vec<Line<'static>> = vec![
+        Line::from(""),
+        Line::from("/profile".magenta()),
+        Line::from(""),
+        Line::from("  • No profiles configured.".italic()),
+        Line::from("    Define them as [profiles.<name>] tables in config.toml.".dim()),
+    ];
+    PlainHistoryCell { lines }
+}
+
 /// Render a summary of configured MCP servers from the current `Config`.
 pub(crate) fn empty_mcp_output() -> PlainHistoryCell {
     let lines: Vec<Line<'static>> = vec![
@@ -1019,6 +1510,7 @@ pub(crate) fn new_plan_update(update: UpdatePlanArgs) -> PlainHistoryCell {
 pub(crate) fn new_patch_event(
     event_type: PatchEventType,
     changes: HashMap<PathBuf, FileChange>,
+    syntax_theme: &str,
 ) -> PlainHistoryCell {
     let title = match &event_type {
         PatchEventType::ApprovalRequest => "proposed patch",
@@ -1036,7 +1528,8 @@ pub(crate) fn new_patch_event(
         }
     };
 
-    let mut lines: Vec<Line<'static>> = create_diff_summary(title, &changes, event_type);
+    let mut lines: Vec<Line<'static>> =
+        create_diff_summary(title, &changes, event_type, syntax_theme);
     // Add leading blank separator for the cell
     lines.insert(0, Line::from(""));
 
@@ -1056,6 +1549,7 @@ pub(crate) fn new_patch_apply_failure(stderr: String) -> PlainHistoryCell {
                 stdout: String::new(),
                 stderr,
                 formatted_output: String::new(),
+                sandbox_denials: Vec::new(),
             }),
             true,
             true,
@@ -1135,6 +1629,7 @@ fn output_lines(
         exit_code,
         stdout,
         stderr,
+        sandbox_denials,
         ..
     } = match output {
         Some(output) if only_err && output.exit_code == 0 => return vec![],
@@ -1185,9 +1680,36 @@ fn output_lines(
         out.push(line);
     }
 
+    for denial in sandbox_denials {
+        out.push(Line::from(vec![
+            "    ".into(),
+            format!("blocked: {denial}").red(),
+        ]));
+    }
+
     out
 }
 
+/// The last [`TOOL_CALL_MAX_LINES`] lines of output from a running command.
+fn live_output_lines(live_output: &[u8]) -> Vec<Line<'static>> {
+    let text = String::from_utf8_lossy(live_output);
+    let lines: Vec<&str> = text.lines().collect();
+    let tail_start = lines.len().saturating_sub(TOOL_CALL_MAX_LINES);
+    lines[tail_start..]
+        .iter()
+        .enumerate()
+        .map(|(i, raw)| {
+            let mut line = ansi_escape_line(raw);
+            let prefix = if i == 0 { "  └ " } else { "    " };
+            line.spans.insert(0, prefix.into());
+            line.spans.iter_mut().for_each(|span| {
+                span.style = span.style.add_modifier(Modifier::DIM);
+            });
+            line
+        })
+        .collect()
+}
+
 fn format_mcp_invocation<'a>(invocation: McpInvocation) -> Line<'a> {
     let args_str = invocation
         .arguments
@@ -1228,4 +1750,25 @@ mod tests {
         // Continuation lines align under the text block.
         assert_eq!(lines[3].spans[0].content, "    ");
     }
+    #[test]
+    fn user_images_keep_a_caption_when_unreadable() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let path = dir.path().join("shot.png");
+        image::RgbaImage::new(4, 3).save(&path).expect("save png");
+        let text = |cell: &ImageHistoryCell| -> String {
+            cell.display_lines()[0]
+                .spans
+                .iter()
+                .map(|s| s.content.as_ref())
+                .collect()
+        };
+
+        let cell = new_user_image(&path);
+        assert_eq!(text(&cell), format!("{}shot.png 4x3", padded_emoji("🖼")));
+        assert!(cell.inline_image().is_some());
+
+        let missing = new_user_image(&dir.path().join("gone.png"));
+        assert_eq!(text(&missing), format!("{}gone.png", padded_emoji("🖼")));
+        assert!(missing.inline_image().is_none());
+    }
 }
@@ -1,3 +1,4 @@
+use std::collections::VecDeque;
 use std::fmt;
 use std::io;
 use std::io::Write;
@@ -13,6 +14,9 @@ use crossterm::style::SetAttribute;
 use crossterm::style::SetBackgroundColor;
 use crossterm::style::SetColors;
 use crossterm::style::SetForegroundColor;
+use crossterm::terminal::Clear;
+use crossterm::terminal::ClearType;
+use ratatui::buffer::Buffer;
 use ratatui::layout::Size;
 use ratatui::style::Color;
 use ratatui::style::Modifier;
@@ -113,6 +117,166 @@ pub fn insert_history_lines_to_writer<B, W>(
     .ok();
 }
 
+/// How many logical lines [`InsertedHistory`] keeps. A line takes at least one
+/// row, so this is enough to fill the rows above the viewport of any screen.
+const MAX_INSERTED_HISTORY_LINES: usize = 1000;
+
+/// The newest history cells inserted above the viewport, kept unwrapped so
+/// that they can be wrapped again when the terminal width changes. Older
+/// cells are dropped once more than [`MAX_INSERTED_HISTORY_LINES`] lines are
+/// kept, and all of them once an image is inserted, since the rows above an
+/// image cannot be found again.
+#[derive(Debug, Default)]
+pub struct InsertedHistory {
+    cells: VecDeque<Vec<Line<'static>>>,
+    line_count: usize,
+    /// Width the cells were wrapped at when they were inserted.
+    width: Option<u16>,
+}
+
+impl InsertedHistory {
+    /// Record `cell` as inserted with `width` columns.
+    pub fn push(&mut self, cell: Vec<Line<'static>>, width: u16) {
+        if self.width != Some(width) {
+            self.clear();
+            self.width = Some(width);
+        }
+        self.line_count += cell.len();
+        self.cells.push_back(cell);
+        while self.cells.len() > 1
+            && let Some(oldest) = self.cells.front()
+            && self.line_count - oldest.len() >= MAX_INSERTED_HISTORY_LINES
+        {
+            self.line_count -= oldest.len();
+            self.cells.pop_front();
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.line_count = 0;
+        self.width = None;
+    }
+
+    /// Width the history was last wrapped at, if there is any.
+    pub fn width(&self) -> Option<u16> {
+        self.width
+    }
+
+    fn wrapped_rows(&self, width: u16) -> Vec<Line<'static>> {
+        self.cells
+            .iter()
+            .flat_map(|cell| word_wrap_lines(cell, width))
+            .collect()
+    }
+}
+
+/// Wrap the history right above the viewport again at the viewport's width.
+pub(crate) fn rewrap_history(terminal: &mut tui::Terminal, history: &mut InsertedHistory) {
+    let mut out = std::io::stdout();
+    rewrap_history_to_writer(terminal, &mut out, history);
+}
+
+/// Like `rewrap_history`, but writes ANSI to the provided writer.
+///
+/// Only the rows the history was last drawn on are redrawn, so anything
+/// printed before Codex started stays, and nothing is scrolled, so neither
+/// the scrollback nor its scroll position change. The viewport moves up or
+/// down to stay right below the history; when the history no longer fits
+/// above the viewport, its oldest rows are left out.
+pub fn rewrap_history_to_writer<B, W>(
+    terminal: &mut crate::custom_terminal::Terminal<B>,
+    writer: &mut W,
+    history: &mut InsertedHistory,
+) where
+    B: ratatui::backend::Backend,
+    W: Write,
+{
+    let mut area = terminal.viewport_area;
+    let width = area.width.max(1);
+    let Some(old_width) = history.width else {
+        return;
+    };
+    if old_width == width {
+        return;
+    }
+    let screen_size = terminal.backend().size().unwrap_or(Size::new(0, 0));
+
+    let old_rows = history.wrapped_rows(old_width).len();
+    let top = area
+        .top()
+        .saturating_sub(u16::try_from(old_rows).unwrap_or(u16::MAX));
+    let capacity = usize::from(
+        screen_size
+            .height
+            .saturating_sub(area.height)
+            .saturating_sub(top),
+    );
+    let rows = history.wrapped_rows(width);
+    let rows = &rows[rows.len().saturating_sub(capacity)..];
+
+    for (row, line) in (top..).zip(rows) {
+        queue!(writer, MoveTo(0, row), Clear(ClearType::UntilNewLine)).ok();
+        write_spans(writer, line.iter()).ok();
+    }
+    // Clear the rows of the old viewport; the caller redraws it below.
+    area.y = top + rows.len() as u16;
+    queue!(writer, MoveTo(0, area.y), Clear(ClearType::FromCursorDown)).ok();
+    terminal.set_viewport_area(area);
+    history.width = Some(width);
+
+    queue!(
+        writer,
+        MoveTo(
+            terminal.last_known_cursor_pos.x,
+            terminal.last_known_cursor_pos.y
+        )
+    )
+    .ok();
+}
+
+/// Insert an inline image above the viewport. `image` holds the graphics
+/// protocol's escape sequences as produced by [`crate::terminal_image::render`].
+pub(crate) fn insert_history_image(terminal: &mut tui::Terminal, image: &Buffer) {
+    let mut out = std::io::stdout();
+    insert_history_image_to_writer(terminal, &mut out, image);
+}
+
+/// Reserves the image's rows with blank lines, then writes its cells over
+/// them, so the image scrolls into history like any other line. The caller
+/// must keep the image no taller than the rows the viewport leaves free.
+fn insert_history_image_to_writer<B, W>(
+    terminal: &mut crate::custom_terminal::Terminal<B>,
+    writer: &mut W,
+    image: &Buffer,
+) where
+    B: ratatui::backend::Backend,
+    W: Write,
+{
+    let height = image.area.height;
+    insert_history_lines_to_writer(terminal, writer, vec![Line::default(); height as usize]);
+
+    let top = terminal.viewport_area.top().saturating_sub(height);
+    for y in 0..height {
+        queue!(writer, MoveTo(0, top + y)).ok();
+        for x in 0..image.area.width {
+            let cell = &image[(image.area.x + x, image.area.y + y)];
+            if !cell.skip {
+                queue!(writer, Print(cell.symbol())).ok();
+            }
+        }
+    }
+    queue!(
+        writer,
+        SetAttribute(crossterm::style::Attribute::Reset),
+        MoveTo(
+            terminal.last_known_cursor_pos.x,
+            terminal.last_known_cursor_pos.y
+        )
+    )
+    .ok();
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct SetScrollRegion(pub std::ops::Range<u16>);
 
@@ -228,9 +392,10 @@ where
     let mut bg = Color::Reset;
     let mut last_modifier = Modifier::empty();
     for span in content {
+        let style = crate::theme::apply(span.style);
         let mut modifier = Modifier::empty();
-        modifier.insert(span.style.add_modifier);
-        modifier.remove(span.style.sub_modifier);
+        modifier.insert(style.add_modifier);
+        modifier.remove(style.sub_modifier);
         if modifier != last_modifier {
             let diff = ModifierDiff {
                 from: last_modifier,
@@ -239,8 +404,8 @@ where
             diff.queue(&mut writer)?;
             last_modifier = modifier;
         }
-        let next_fg = span.style.fg.unwrap_or(Color::Reset);
-        let next_bg = span.style.bg.unwrap_or(Color::Reset);
+        let next_fg = style.fg.unwrap_or(Color::Reset);
+        let next_bg = style.bg.unwrap_or(Color::Reset);
         if next_fg != fg || next_bg != bg {
             queue!(
                 writer,
@@ -385,6 +550,25 @@ fn slice_line_spans(
 mod tests {
     use super::*;
 
+    #[test]
+    fn inserted_history_keeps_whole_cells_up_to_its_bound() {
+        let cell = |lines: usize| vec![Line::from("x"); lines];
+        let mut history = InsertedHistory::default();
+        history.push(cell(600), 80);
+        history.push(cell(300), 80);
+        assert_eq!(2, history.cells.len());
+
+        // The oldest cell goes once the others hold enough lines on their own.
+        history.push(cell(700), 80);
+        assert_eq!(2, history.cells.len());
+        assert_eq!(1000, history.line_count);
+
+        // Cells inserted at another width cannot be wrapped with the others.
+        history.push(cell(1), 40);
+        assert_eq!(1, history.cells.len());
+        assert_eq!(Some(40), history.width());
+    }
+
     #[test]
     fn writes_bold_then_regular_spans() {
         use ratatui::style::Stylize;
@@ -413,6 +597,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn image_cells_are_written_over_reserved_rows() {
+        let backend = ratatui::backend::TestBackend::new(10, 6);
+        let mut term = crate::custom_terminal::Terminal::with_options(backend).expect("terminal");
+        term.set_viewport_area(ratatui::layout::Rect::new(0, 5, 10, 1));
+
+        let mut image = Buffer::with_lines(["ab", "cd"]);
+        image[(1, 0)].set_skip(true);
+        let mut out: Vec<u8> = Vec::new();
+        insert_history_lines_to_writer(&mut term, &mut out, vec![Line::from("caption")]);
+        insert_history_image_to_writer(&mut term, &mut out, &image);
+
+        let mut parser = vt100::Parser::new(6, 10, 0);
+        parser.process(&out);
+        let rows: Vec<String> = parser.screen().rows(0, 10).collect();
+        assert_eq!(rows[2..5], ["caption", "a", "cd"]);
+    }
+
     #[test]
     fn line_height_counts_double_width_emoji() {
         let line = Line::from("😀😀😀"); // each emoji ~ width 2
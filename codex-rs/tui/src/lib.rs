@@ -4,6 +4,7 @@
 #![deny(clippy::print_stdout, clippy::print_stderr)]
 #![deny(clippy::disallowed_methods)]
 use app::App;
+use codex_common::exit_code::ExitCode;
 use codex_core::BUILT_IN_OSS_MODEL_PROVIDER_ID;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
@@ -38,24 +39,37 @@ mod common;
 pub mod custom_terminal;
 mod diff_render;
 mod exec_command;
+mod file_citation;
 mod file_search;
+mod file_watcher;
 mod get_git_diff;
 mod history_cell;
 pub mod insert_history;
+mod link_regex;
 pub mod live_wrap;
 mod markdown;
 mod markdown_stream;
 pub mod onboarding;
+mod pager_search;
+mod reader_app;
 mod render;
+mod session_export;
 mod session_log;
+mod shell_passthrough;
 mod shimmer;
 mod slash_command;
 mod status_indicator_widget;
 mod streaming;
+mod syntax;
+mod terminal_image;
+mod terminal_notification;
 mod text_formatting;
+mod theme;
 mod transcript_app;
 mod tui;
 mod user_approval_widget;
+mod warning_banners;
+mod working_pane;
 
 // Internal vt100-based replay tests live as a separate source file to keep them
 // close to the widget code. Include them in unit tests.
@@ -131,6 +145,8 @@ pub async fn run_main(
         disable_response_storage: cli.oss.then_some(true),
         show_raw_agent_reasoning: cli.oss.then_some(true),
         tools_web_search_request: cli.web_search.then_some(true),
+        max_turns: None,
+        max_wall_time: None,
     };
     let raw_overrides = cli.config_overrides.raw_overrides.clone();
     let overrides_cli = codex_common::CliConfigOverrides { raw_overrides };
@@ -139,7 +155,7 @@ pub async fn run_main(
         #[allow(clippy::print_stderr)]
         Err(e) => {
             eprintln!("Error parsing -c overrides: {e}");
-            std::process::exit(1);
+            ExitCode::ConfigError.exit();
         }
     };
 
@@ -151,7 +167,8 @@ pub async fn run_main(
             Ok(config) => config,
             Err(err) => {
                 eprintln!("Error loading configuration: {err}");
-                std::process::exit(1);
+                eprintln!("Run `codex config doctor` for details.");
+                ExitCode::ConfigError.exit();
             }
         }
     };
@@ -163,7 +180,7 @@ pub async fn run_main(
             Ok(codex_home) => codex_home,
             Err(err) => {
                 eprintln!("Error finding codex home: {err}");
-                std::process::exit(1);
+                ExitCode::ConfigError.exit();
             }
         };
 
@@ -171,7 +188,7 @@ pub async fn run_main(
             Ok(config_toml) => config_toml,
             Err(err) => {
                 eprintln!("Error loading config.toml: {err}");
-                std::process::exit(1);
+                ExitCode::ConfigError.exit();
             }
         }
     };
@@ -217,13 +234,43 @@ pub async fn run_main(
         .with_target(false)
         .with_filter(env_filter());
 
-    if cli.oss {
-        codex_ollama::ensure_oss_ready(&config)
+    // Keeps an `ollama serve` started for this session alive until it ends.
+    let _ollama_server = if cli.oss {
+        codex_ollama::ensure_oss_ready(&mut config)
             .await
-            .map_err(|e| std::io::Error::other(format!("OSS setup failed: {e}")))?;
+            .map_err(|e| std::io::Error::other(format!("OSS setup failed: {e}")))?
+    } else {
+        None
+    };
+
+    // Spans and metrics are exported over OTLP when `[telemetry]` is
+    // enabled; the guard flushes them when the TUI exits.
+    #[allow(clippy::print_stderr)]
+    let (otel_layer, _telemetry_guard) = match codex_core::telemetry::init(&config.telemetry) {
+        Ok(telemetry) => telemetry.unzip(),
+        Err(e) => {
+            eprintln!("Telemetry export disabled: {e}");
+            (None, None)
+        }
+    };
+
+    let _ = tracing_subscriber::registry()
+        .with(file_layer)
+        .with(otel_layer)
+        .try_init();
+
+    if let Some(path) = &cli.replay {
+        return codex_exec::run_replay(path, &config)
+            .map(|()| codex_core::protocol::TokenUsage::default())
+            .map_err(|err| std::io::Error::other(err.to_string()));
     }
 
-    let _ = tracing_subscriber::registry().with(file_layer).try_init();
+    if cli.plain || codex_exec::should_use_plain_mode() {
+        let Cli { prompt, images, .. } = cli;
+        return codex_exec::run_plain(config, prompt, images)
+            .await
+            .map_err(|err| std::io::Error::other(err.to_string()));
+    }
 
     run_ratatui_app(cli, config, should_show_trust_screen)
         .await
@@ -247,8 +294,12 @@ async fn run_ratatui_app(
         tracing::error!("panic: {info}");
         prev_hook(info);
     }));
-    let mut terminal = tui::init()?;
+    let mut terminal = tui::init(config.tui.mouse)?;
     terminal.clear()?;
+    // Ask the terminal about graphics support before the event stream starts
+    // consuming its replies.
+    terminal_image::detect();
+    theme::init(&config.tui);
 
     let mut tui = Tui::new(terminal);
 
@@ -302,7 +353,12 @@ async fn run_ratatui_app(
     // Initialize high-fidelity session event logging if enabled.
     session_log::maybe_init(&config);
 
-    let Cli { prompt, images, .. } = cli;
+    let Cli {
+        prompt,
+        images,
+        run,
+        ..
+    } = cli;
 
     let auth_manager = AuthManager::shared(config.codex_home.clone(), config.preferred_auth_method);
     let login_status = get_login_status(&config);
@@ -318,6 +374,8 @@ async fn run_ratatui_app(
                 login_status,
                 preferred_auth_method: config.preferred_auth_method,
                 auth_manager: auth_manager.clone(),
+                model_provider: config.model_provider.clone(),
+                codex_linux_sandbox_exe: config.codex_linux_sandbox_exe.clone(),
             },
             &mut tui,
         )
@@ -328,7 +386,7 @@ async fn run_ratatui_app(
         }
     }
 
-    let app_result = App::run(&mut tui, auth_manager, config, prompt, images).await;
+    let app_result = App::run(&mut tui, auth_manager, config, prompt, images, run).await;
 
     restore();
     // Mark the end of the recorded session.
@@ -0,0 +1,16 @@
+#![expect(clippy::expect_used)]
+
+use regex_lite::Regex;
+
+// Defined in its own file for the same reason as `citation_regex`: the
+// `expect_used` allowance cannot be scoped to the `lazy_static!` macro.
+lazy_static::lazy_static! {
+    /// Regular expression that matches link targets in markdown.
+    ///
+    /// Capture groups:
+    /// 1. the target of a markdown link such as `[text](https://example.com)`
+    /// 2. a bare `http` or `https` URL
+    pub(crate) static ref LINK_REGEX: Regex = Regex::new(
+        r"\]\(([^)\s]+)\)|(https?://[^\s)>\]]+)"
+    ).expect("failed to compile link regex");
+}
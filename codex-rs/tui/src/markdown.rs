@@ -1,4 +1,5 @@
 use crate::citation_regex::CITATION_REGEX;
+use crate::syntax::Highlighter;
 use codex_core::config::Config;
 use codex_core::config_types::UriBasedFileOpener;
 use ratatui::text::Line;
@@ -11,7 +12,13 @@ pub(crate) fn append_markdown(
     lines: &mut Vec<Line<'static>>,
     config: &Config,
 ) {
-    append_markdown_with_opener_and_cwd(markdown_source, lines, config.file_opener, &config.cwd);
+    append_markdown_with_opener_and_cwd(
+        markdown_source,
+        lines,
+        config.file_opener,
+        &config.cwd,
+        &config.tui.syntax_theme,
+    );
 }
 
 fn append_markdown_with_opener_and_cwd(
@@ -19,6 +26,7 @@ fn append_markdown_with_opener_and_cwd(
     lines: &mut Vec<Line<'static>>,
     file_opener: UriBasedFileOpener,
     cwd: &Path,
+    syntax_theme: &str,
 ) {
     // Historically, we fed the entire `markdown_source` into the renderer in
     // one pass. However, fenced code blocks sometimes lost leading whitespace
@@ -34,9 +42,13 @@ fn append_markdown_with_opener_and_cwd(
                 let rendered = tui_markdown::from_str(&processed);
                 crate::render::line_utils::push_owned_lines(&rendered.lines, lines);
             }
-            Segment::Code { content, .. } => {
-                // Emit the code content exactly as-is, line by line.
-                // We don't attempt syntax highlighting to avoid whitespace bugs.
+            Segment::Code { lang, content } => {
+                // Emit the code content exactly as-is, line by line. Blocks
+                // tagged with a known language are colorized span by span,
+                // which leaves the text (and its whitespace) untouched.
+                let mut highlighter = lang
+                    .as_deref()
+                    .and_then(|lang| Highlighter::for_language(lang, syntax_theme));
                 for line in content.split_inclusive('\n') {
                     // split_inclusive keeps the trailing \n; we want lines without it.
                     let line = if let Some(stripped) = line.strip_suffix('\n') {
@@ -44,7 +56,10 @@ fn append_markdown_with_opener_and_cwd(
                     } else {
                         line
                     };
-                    let owned_line: Line<'static> = Line::from(Span::raw(line.to_string()));
+                    let owned_line: Line<'static> = match highlighter.as_mut() {
+                        Some(highlighter) => Line::from(highlighter.highlight(line)),
+                        None => Line::from(Span::raw(line.to_string())),
+                    };
                     lines.push(owned_line);
                 }
             }
@@ -110,7 +125,7 @@ fn rewrite_file_citations<'a>(
 enum Segment {
     Text(String),
     Code {
-        _lang: Option<String>,
+        lang: Option<String>,
         content: String,
     },
 }
@@ -202,7 +217,7 @@ fn split_text_and_fences(src: &str) -> Vec<Segment> {
                     if trimmed == fence_token {
                         // End code block: emit segment without fences
                         segments.push(Segment::Code {
-                            _lang: code_lang.take(),
+                            lang: code_lang.take(),
                             content: code_content.clone(),
                         });
                         code_content.clear();
@@ -227,7 +242,7 @@ fn split_text_and_fences(src: &str) -> Vec<Segment> {
                     } else {
                         // Close the indented code block and reprocess this line as normal text.
                         segments.push(Segment::Code {
-                            _lang: None,
+                            lang: None,
                             content: code_content.clone(),
                         });
                         code_content.clear();
@@ -244,7 +259,7 @@ fn split_text_and_fences(src: &str) -> Vec<Segment> {
     if code_mode != CodeMode::None {
         // Unterminated code fence: treat accumulated content as a code segment.
         segments.push(Segment::Code {
-            _lang: code_lang.take(),
+            lang: code_lang.take(),
             content: code_content.clone(),
         });
     } else if !curr_text.is_empty() {
@@ -303,7 +318,13 @@ mod tests {
         // The helper itself always rewrites – this test validates behaviour of
         // append_markdown when `file_opener` is None.
         let mut out = Vec::new();
-        append_markdown_with_opener_and_cwd(markdown, &mut out, UriBasedFileOpener::None, cwd);
+        append_markdown_with_opener_and_cwd(
+            markdown,
+            &mut out,
+            UriBasedFileOpener::None,
+            cwd,
+            "none",
+        );
         // Convert lines back to string for comparison.
         let rendered: String = out
             .iter()
@@ -321,7 +342,7 @@ mod tests {
         let src = "```\n  indented\n\t\twith tabs\n    four spaces\n```\n";
         let cwd = Path::new("/");
         let mut out = Vec::new();
-        append_markdown_with_opener_and_cwd(src, &mut out, UriBasedFileOpener::None, cwd);
+        append_markdown_with_opener_and_cwd(src, &mut out, UriBasedFileOpener::None, cwd, "none");
         let rendered: Vec<String> = out
             .iter()
             .map(|l| {
@@ -346,7 +367,7 @@ mod tests {
         let src = "Before 【F:/x.rs†L1】\n```\nInside 【F:/x.rs†L2】\n```\nAfter 【F:/x.rs†L3】\n";
         let cwd = Path::new("/");
         let mut out = Vec::new();
-        append_markdown_with_opener_and_cwd(src, &mut out, UriBasedFileOpener::VsCode, cwd);
+        append_markdown_with_opener_and_cwd(src, &mut out, UriBasedFileOpener::VsCode, cwd, "none");
         let rendered: Vec<String> = out
             .iter()
             .map(|l| {
@@ -367,7 +388,7 @@ mod tests {
         let src = "Before\n    code 1\n\tcode with tab\n        code 2\nAfter\n";
         let cwd = Path::new("/");
         let mut out = Vec::new();
-        append_markdown_with_opener_and_cwd(src, &mut out, UriBasedFileOpener::None, cwd);
+        append_markdown_with_opener_and_cwd(src, &mut out, UriBasedFileOpener::None, cwd, "none");
         let rendered: Vec<String> = out
             .iter()
             .map(|l| {
@@ -394,7 +415,7 @@ mod tests {
         let src = "Start 【F:/x.rs†L1】\n\n    Inside 【F:/x.rs†L2】\n\nEnd 【F:/x.rs†L3】\n";
         let cwd = Path::new("/");
         let mut out = Vec::new();
-        append_markdown_with_opener_and_cwd(src, &mut out, UriBasedFileOpener::VsCode, cwd);
+        append_markdown_with_opener_and_cwd(src, &mut out, UriBasedFileOpener::VsCode, cwd, "none");
         let rendered: Vec<String> = out
             .iter()
             .map(|l| {
@@ -418,7 +439,7 @@ mod tests {
         let src = "Hi! How can I help with codex-rs today? Want me to explore the repo, run tests, or work on a specific change?\n";
         let cwd = Path::new("/");
         let mut out = Vec::new();
-        append_markdown_with_opener_and_cwd(src, &mut out, UriBasedFileOpener::None, cwd);
+        append_markdown_with_opener_and_cwd(src, &mut out, UriBasedFileOpener::None, cwd, "none");
         assert_eq!(
             out.len(),
             1,
@@ -435,4 +456,29 @@ mod tests {
             "Hi! How can I help with codex-rs today? Want me to explore the repo, run tests, or work on a specific change?"
         );
     }
+
+    #[test]
+    fn fenced_code_blocks_are_highlighted_by_language() {
+        let src = "```rust\n    let x = 1;\n```\n```nosuchlang\nlet y = 2;\n```\n";
+        let cwd = Path::new("/");
+        let mut out = Vec::new();
+        append_markdown_with_opener_and_cwd(
+            src,
+            &mut out,
+            UriBasedFileOpener::None,
+            cwd,
+            crate::syntax::DEFAULT_SYNTAX_THEME,
+        );
+        let rendered: Vec<String> = out
+            .iter()
+            .map(|l| l.spans.iter().map(|s| s.content.clone()).collect())
+            .collect();
+        assert_eq!(
+            rendered,
+            vec!["    let x = 1;".to_string(), "let y = 2;".to_string()]
+        );
+        assert!(out[0].spans.len() > 1, "{:?}", out[0]);
+        assert!(out[0].spans.iter().any(|s| s.style.fg.is_some()));
+        assert_eq!(out[1].spans, vec![Span::raw("let y = 2;")]);
+    }
 }
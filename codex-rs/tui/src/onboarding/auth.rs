@@ -1,5 +1,6 @@
 #![allow(clippy::unwrap_used)]
 
+use codex_core::config::set_config_value;
 use codex_login::AuthManager;
 use codex_login::CLIENT_ID;
 use codex_login::ServerOptions;
@@ -28,6 +29,7 @@ use crate::onboarding::onboarding_screen::KeyboardHandler;
 use crate::onboarding::onboarding_screen::StepStateProvider;
 use crate::shimmer::shimmer_spans;
 use crate::tui::FrameRequester;
+use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 
@@ -341,6 +343,8 @@ impl AuthModeWidget {
                 let sign_in_state = self.sign_in_state.clone();
                 let request_frame = self.request_frame.clone();
                 let auth_manager = self.auth_manager.clone();
+                let codex_home = self.codex_home.clone();
+                let preferred_auth_method = self.preferred_auth_method;
                 tokio::spawn(async move {
                     let auth_url = child.auth_url.clone();
                     {
@@ -356,6 +360,11 @@ impl AuthModeWidget {
                         Ok(()) => {
                             // Force the auth manager to reload the new auth information.
                             auth_manager.reload();
+                            persist_auth_method(
+                                &codex_home,
+                                preferred_auth_method,
+                                AuthMode::ChatGPT,
+                            );
 
                             *sign_in_state.write().unwrap() = SignInState::ChatGptSuccessMessage;
                             request_frame.schedule_frame();
@@ -382,6 +391,11 @@ impl AuthModeWidget {
             // We already have an API key configured (e.g., from auth.json or env),
             // so mark this step complete immediately.
             *self.sign_in_state.write().unwrap() = SignInState::EnvVarFound;
+            persist_auth_method(
+                &self.codex_home,
+                self.preferred_auth_method,
+                AuthMode::ApiKey,
+            );
         } else {
             *self.sign_in_state.write().unwrap() = SignInState::EnvVarMissing;
         }
@@ -389,6 +403,17 @@ impl AuthModeWidget {
     }
 }
 
+/// Remember the sign-in method the user picked, so later sessions use it
+/// when both are available.
+fn persist_auth_method(codex_home: &Path, preferred: AuthMode, chosen: AuthMode) {
+    if chosen == preferred {
+        return;
+    }
+    if let Err(e) = set_config_value(codex_home, "preferred_auth_method", chosen) {
+        tracing::warn!("failed to save preferred_auth_method: {e}");
+    }
+}
+
 impl StepStateProvider for AuthModeWidget {
     fn get_step_state(&self) -> StepState {
         let sign_in_state = self.sign_in_state.read().unwrap();
@@ -1,5 +1,6 @@
 mod auth;
 pub mod onboarding_screen;
+mod preflight;
 mod trust_directory;
 pub use trust_directory::TrustDirectorySelection;
 mod welcome;
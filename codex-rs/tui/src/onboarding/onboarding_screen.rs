@@ -1,3 +1,4 @@
+use codex_core::ModelProviderInfo;
 use codex_core::util::is_inside_git_repo;
 use codex_login::AuthManager;
 use crossterm::event::KeyCode;
@@ -14,6 +15,7 @@ use codex_login::AuthMode;
 use crate::LoginStatus;
 use crate::onboarding::auth::AuthModeWidget;
 use crate::onboarding::auth::SignInState;
+use crate::onboarding::preflight::PreflightWidget;
 use crate::onboarding::trust_directory::TrustDirectorySelection;
 use crate::onboarding::trust_directory::TrustDirectoryWidget;
 use crate::onboarding::welcome::WelcomeWidget;
@@ -30,6 +32,7 @@ enum Step {
     Welcome(WelcomeWidget),
     Auth(AuthModeWidget),
     TrustDirectory(TrustDirectoryWidget),
+    Preflight(PreflightWidget),
 }
 
 pub(crate) trait KeyboardHandler {
@@ -60,6 +63,8 @@ pub(crate) struct OnboardingScreenArgs {
     pub login_status: LoginStatus,
     pub preferred_auth_method: AuthMode,
     pub auth_manager: Arc<AuthManager>,
+    pub model_provider: ModelProviderInfo,
+    pub codex_linux_sandbox_exe: Option<PathBuf>,
 }
 
 impl OnboardingScreen {
@@ -72,6 +77,8 @@ impl OnboardingScreen {
             login_status,
             preferred_auth_method,
             auth_manager,
+            model_provider,
+            codex_linux_sandbox_exe,
         } = args;
         let mut steps: Vec<Step> = vec![Step::Welcome(WelcomeWidget {
             is_logged_in: !matches!(login_status, LoginStatus::NotAuthenticated),
@@ -85,7 +92,7 @@ impl OnboardingScreen {
                 codex_home: codex_home.clone(),
                 login_status,
                 preferred_auth_method,
-                auth_manager,
+                auth_manager: auth_manager.clone(),
             }))
         }
         let is_git_repo = is_inside_git_repo(&cwd);
@@ -105,7 +112,15 @@ impl OnboardingScreen {
                 error: None,
             }))
         }
-        // TODO: add git warning.
+        steps.push(Step::Preflight(PreflightWidget {
+            request_frame: tui.frame_requester(),
+            auth_manager,
+            model_provider,
+            codex_linux_sandbox_exe,
+            results: Arc::new(RwLock::new(None)),
+            started: false,
+            acknowledged: false,
+        }));
         Self {
             request_frame: tui.frame_requester(),
             steps,
@@ -151,6 +166,14 @@ impl OnboardingScreen {
                 .any(|step| matches!(step.get_step_state(), StepState::InProgress))
     }
 
+    /// Starts the preflight checks once the steps before them are done, so
+    /// that they see the credentials the user just signed in with.
+    fn start_active_preflight(&mut self) {
+        if let Some(Step::Preflight(widget)) = self.current_steps_mut().into_iter().last() {
+            widget.start();
+        }
+    }
+
     pub fn directory_trust_decision(&self) -> Option<TrustDirectorySelection> {
         self.steps
             .iter()
@@ -262,6 +285,7 @@ impl KeyboardHandler for Step {
             Step::Welcome(_) => (),
             Step::Auth(widget) => widget.handle_key_event(key_event),
             Step::TrustDirectory(widget) => widget.handle_key_event(key_event),
+            Step::Preflight(widget) => widget.handle_key_event(key_event),
         }
     }
 }
@@ -272,6 +296,7 @@ impl StepStateProvider for Step {
             Step::Welcome(w) => w.get_step_state(),
             Step::Auth(w) => w.get_step_state(),
             Step::TrustDirectory(w) => w.get_step_state(),
+            Step::Preflight(w) => w.get_step_state(),
         }
     }
 }
@@ -288,6 +313,9 @@ impl WidgetRef for Step {
             Step::TrustDirectory(widget) => {
                 widget.render_ref(area, buf);
             }
+            Step::Preflight(widget) => {
+                widget.render_ref(area, buf);
+            }
         }
     }
 }
@@ -299,6 +327,7 @@ pub(crate) async fn run_onboarding_app(
     use tokio_stream::StreamExt;
 
     let mut onboarding_screen = OnboardingScreen::new(tui, args);
+    onboarding_screen.start_active_preflight();
 
     tui.draw(u16::MAX, |frame| {
         frame.render_widget_ref(&onboarding_screen, frame.area());
@@ -314,6 +343,7 @@ pub(crate) async fn run_onboarding_app(
                     onboarding_screen.handle_key_event(key_event);
                 }
                 TuiEvent::Draw => {
+                    onboarding_screen.start_active_preflight();
                     let _ = tui.draw(u16::MAX, |frame| {
                         frame.render_widget_ref(&onboarding_screen, frame.area());
                     });
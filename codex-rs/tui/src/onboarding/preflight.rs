@@ -0,0 +1,475 @@
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::RwLock;
+use std::time::Duration;
+
+use codex_core::ModelProviderInfo;
+use codex_login::AuthManager;
+use codex_login::AuthMode;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::prelude::Widget;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::WidgetRef;
+use ratatui::widgets::Wrap;
+
+use crate::onboarding::onboarding_screen::KeyboardHandler;
+use crate::onboarding::onboarding_screen::StepStateProvider;
+use crate::shimmer::shimmer_spans;
+use crate::tui::FrameRequester;
+
+use super::onboarding_screen::StepState;
+
+/// How long to wait for the model provider to answer before calling it
+/// unreachable.
+const API_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum CheckStatus {
+    Pass,
+    /// Codex works, with something missing.
+    Warn,
+    /// Codex will not work until this is fixed.
+    Fail,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct PreflightCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+    /// How to fix a check that did not pass.
+    pub hint: Option<String>,
+}
+
+impl PreflightCheck {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+            hint: None,
+        }
+    }
+
+    fn problem(
+        name: &'static str,
+        status: CheckStatus,
+        detail: impl Into<String>,
+        hint: impl Into<String>,
+    ) -> Self {
+        Self {
+            name,
+            status,
+            detail: detail.into(),
+            hint: Some(hint.into()),
+        }
+    }
+}
+
+/// Checks the model provider, credentials and local tools once the steps
+/// before it are done, and waits for the user when one of them did not pass.
+pub(crate) struct PreflightWidget {
+    pub request_frame: FrameRequester,
+    pub auth_manager: Arc<AuthManager>,
+    pub model_provider: ModelProviderInfo,
+    pub codex_linux_sandbox_exe: Option<PathBuf>,
+    /// Results, once all checks have finished.
+    pub results: Arc<RwLock<Option<Vec<PreflightCheck>>>>,
+    pub started: bool,
+    pub acknowledged: bool,
+}
+
+impl PreflightWidget {
+    /// Runs the checks in the background; the results are rendered when they
+    /// are all in.
+    pub(crate) fn start(&mut self) {
+        if self.started {
+            return;
+        }
+        self.started = true;
+        let results = self.results.clone();
+        let request_frame = self.request_frame.clone();
+        let auth_manager = self.auth_manager.clone();
+        let provider = self.model_provider.clone();
+        let sandbox_exe = self.codex_linux_sandbox_exe.clone();
+        tokio::spawn(async move {
+            let mut checks = api_checks(&provider, &auth_manager).await;
+            checks.push(git_check().await);
+            checks.push(sandbox_check(sandbox_exe.as_deref()));
+            checks.push(ripgrep_check().await);
+            *results
+                .write()
+                .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(checks);
+            request_frame.schedule_frame();
+        });
+    }
+
+    fn results(&self) -> Option<Vec<PreflightCheck>> {
+        self.results
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+}
+
+impl WidgetRef for &PreflightWidget {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let mut lines: Vec<Line> = vec![
+            Line::from(vec![
+                Span::raw("> "),
+                Span::styled(
+                    "Checking your environment",
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+            ]),
+            Line::from(""),
+        ];
+        let Some(results) = self.results() else {
+            let mut spans = vec![Span::raw("  ")];
+            spans.extend(shimmer_spans("Running checks"));
+            lines.push(Line::from(spans));
+            Paragraph::new(lines).render(area, buf);
+            return;
+        };
+
+        for check in &results {
+            let marker = match check.status {
+                CheckStatus::Pass => "✓".green(),
+                CheckStatus::Warn => "!".magenta(),
+                CheckStatus::Fail => "✗".red(),
+            };
+            lines.push(Line::from(vec![
+                Span::raw("  "),
+                marker,
+                Span::raw(" "),
+                Span::raw(check.name).bold(),
+                Span::raw(" "),
+                Span::raw(check.detail.clone()).dim(),
+            ]));
+            if let Some(hint) = &check.hint {
+                lines.push(Line::from(format!("    {hint}")).cyan());
+            }
+        }
+        if results
+            .iter()
+            .any(|check| check.status != CheckStatus::Pass)
+        {
+            lines.push(Line::from(""));
+            lines.push(Line::from("  Press Enter to continue").add_modifier(Modifier::DIM));
+        }
+
+        Paragraph::new(lines)
+            .wrap(Wrap { trim: false })
+            .render(area, buf);
+    }
+}
+
+impl KeyboardHandler for PreflightWidget {
+    fn handle_key_event(&mut self, key_event: KeyEvent) {
+        if key_event.code == KeyCode::Enter && self.results().is_some() {
+            self.acknowledged = true;
+        }
+    }
+}
+
+impl StepStateProvider for PreflightWidget {
+    fn get_step_state(&self) -> StepState {
+        match self.results() {
+            Some(results)
+                if self.acknowledged
+                    || results
+                        .iter()
+                        .all(|check| check.status == CheckStatus::Pass) =>
+            {
+                StepState::Complete
+            }
+            _ => StepState::InProgress,
+        }
+    }
+}
+
+/// Whether the model provider answers and accepts the credentials, checked
+/// with a request that lists the provider's models.
+async fn api_checks(
+    provider: &ModelProviderInfo,
+    auth_manager: &AuthManager,
+) -> Vec<PreflightCheck> {
+    let auth = match provider.api_key() {
+        Ok(Some(key)) => Some((AuthMode::ApiKey, key)),
+        _ => match auth_manager.auth() {
+            Some(auth) => auth.get_token().await.ok().map(|token| (auth.mode, token)),
+            None => None,
+        },
+    };
+    let base_url = provider.base_url.clone().unwrap_or_else(|| {
+        match auth {
+            Some((AuthMode::ChatGPT, _)) => "https://chatgpt.com/backend-api/codex",
+            _ => "https://api.openai.com/v1",
+        }
+        .to_string()
+    });
+    let needs_auth = provider.requires_openai_auth || provider.env_key.is_some();
+
+    let client = match reqwest::Client::builder().timeout(API_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            return vec![PreflightCheck::problem(
+                "API",
+                CheckStatus::Fail,
+                format!("could not create an HTTP client: {e}"),
+                "Check your TLS and proxy configuration.",
+            )];
+        }
+    };
+    let mut request = client.get(format!("{}/models", base_url.trim_end_matches('/')));
+    if let Some((_, token)) = &auth {
+        request = request.bearer_auth(token);
+    }
+    let status = request
+        .send()
+        .await
+        .map(|response| response.status().as_u16());
+    api_results(
+        provider,
+        &base_url,
+        status.map_err(|e| e.to_string()),
+        auth.map(|(mode, _)| mode),
+        needs_auth,
+    )
+}
+
+/// Turns the outcome of the models request into the API and auth checks.
+fn api_results(
+    provider: &ModelProviderInfo,
+    base_url: &str,
+    status: Result<u16, String>,
+    auth_mode: Option<AuthMode>,
+    needs_auth: bool,
+) -> Vec<PreflightCheck> {
+    let host = reqwest::Url::parse(base_url)
+        .ok()
+        .and_then(|url| url.host_str().map(str::to_string))
+        .unwrap_or_else(|| base_url.to_string());
+    let status = match status {
+        Ok(status) => status,
+        Err(e) => {
+            return vec![PreflightCheck::problem(
+                "API",
+                CheckStatus::Fail,
+                format!("could not reach {host}: {e}"),
+                format!(
+                    "Check your network connection, HTTPS_PROXY, and the base_url of the `{}` provider.",
+                    provider.name
+                ),
+            )];
+        }
+    };
+    let mut checks = vec![PreflightCheck::pass("API", format!("{host} is reachable"))];
+    if !needs_auth {
+        return checks;
+    }
+    match auth_mode {
+        // The ChatGPT backend does not serve the models list; the token was
+        // refreshed while loading it, which is the check that matters.
+        Some(AuthMode::ChatGPT) => {
+            checks.push(PreflightCheck::pass("Auth", "signed in with ChatGPT"));
+        }
+        Some(AuthMode::ApiKey) if matches!(status, 401 | 403) => {
+            checks.push(PreflightCheck::problem(
+                "Auth",
+                CheckStatus::Fail,
+                format!("{host} rejected your API key (HTTP {status})"),
+                "Run `codex login` again, or check that your API key is current.",
+            ));
+        }
+        Some(AuthMode::ApiKey) => checks.push(PreflightCheck::pass("Auth", "API key accepted")),
+        None => {
+            let variable = provider.env_key.as_deref().unwrap_or("OPENAI_API_KEY");
+            checks.push(PreflightCheck::problem(
+                "Auth",
+                CheckStatus::Fail,
+                "no credentials found",
+                format!("Run `codex login` or set {variable}."),
+            ));
+        }
+    }
+    checks
+}
+
+async fn git_check() -> PreflightCheck {
+    match tool_version("git").await {
+        Some(version) => PreflightCheck::pass("git", version),
+        None => PreflightCheck::problem(
+            "git",
+            CheckStatus::Warn,
+            "not found",
+            "Install git so Codex can show diffs and undo its changes.",
+        ),
+    }
+}
+
+async fn ripgrep_check() -> PreflightCheck {
+    match tool_version("rg").await {
+        Some(version) => PreflightCheck::pass("ripgrep", version),
+        None => PreflightCheck::problem(
+            "ripgrep",
+            CheckStatus::Warn,
+            "not found",
+            "Install ripgrep (`rg`) so the agent can search code quickly.",
+        ),
+    }
+}
+
+/// The first line `<program> --version` prints, or `None` when it cannot run.
+async fn tool_version(program: &str) -> Option<String> {
+    let output = tokio::process::Command::new(program)
+        .arg("--version")
+        .output()
+        .await
+        .ok()
+        .filter(|output| output.status.success())?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Some(stdout.lines().next().unwrap_or_default().trim().to_string())
+}
+
+fn sandbox_check(codex_linux_sandbox_exe: Option<&Path>) -> PreflightCheck {
+    if cfg!(target_os = "macos") {
+        let seatbelt = Path::new("/usr/bin/sandbox-exec");
+        if seatbelt.exists() {
+            PreflightCheck::pass("Sandbox", "Seatbelt (/usr/bin/sandbox-exec)")
+        } else {
+            PreflightCheck::problem(
+                "Sandbox",
+                CheckStatus::Fail,
+                "/usr/bin/sandbox-exec not found",
+                "Commands cannot be sandboxed; use --sandbox danger-full-access only in an isolated environment.",
+            )
+        }
+    } else if cfg!(target_os = "linux") {
+        match codex_linux_sandbox_exe {
+            Some(exe) if exe.exists() => {
+                PreflightCheck::pass("Sandbox", format!("Landlock ({})", exe.display()))
+            }
+            _ => PreflightCheck::problem(
+                "Sandbox",
+                CheckStatus::Fail,
+                "codex-linux-sandbox not found",
+                "Reinstall Codex, or run it through the `codex` binary, which provides the Landlock sandbox.",
+            ),
+        }
+    } else {
+        PreflightCheck::problem(
+            "Sandbox",
+            CheckStatus::Warn,
+            "not available on this platform",
+            "Commands run unsandboxed; keep approvals on.",
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::WireApi;
+    use pretty_assertions::assert_eq;
+
+    fn provider() -> ModelProviderInfo {
+        ModelProviderInfo {
+            name: "OpenAI".to_string(),
+            base_url: None,
+            env_key: None,
+            env_key_instructions: None,
+            wire_api: WireApi::Responses,
+            query_params: None,
+            http_headers: None,
+            env_http_headers: None,
+            request_max_retries: None,
+            stream_max_retries: None,
+            stream_idle_timeout_ms: None,
+            requires_openai_auth: true,
+            stream_include_usage: false,
+        }
+    }
+
+    #[test]
+    fn unreachable_api_skips_the_auth_check() {
+        let checks = api_results(
+            &provider(),
+            "https://api.openai.com/v1",
+            Err("connection refused".to_string()),
+            Some(AuthMode::ApiKey),
+            true,
+        );
+        assert_eq!(checks.len(), 1);
+        assert_eq!(checks[0].status, CheckStatus::Fail);
+        assert_eq!(
+            checks[0].detail,
+            "could not reach api.openai.com: connection refused"
+        );
+    }
+
+    #[test]
+    fn rejected_and_missing_credentials_fail_the_auth_check() {
+        let rejected = api_results(
+            &provider(),
+            "https://api.openai.com/v1",
+            Ok(401),
+            Some(AuthMode::ApiKey),
+            true,
+        );
+        assert_eq!(
+            rejected[1],
+            PreflightCheck::problem(
+                "Auth",
+                CheckStatus::Fail,
+                "api.openai.com rejected your API key (HTTP 401)",
+                "Run `codex login` again, or check that your API key is current.",
+            )
+        );
+
+        let missing = api_results(
+            &provider(),
+            "https://api.openai.com/v1",
+            Ok(200),
+            None,
+            true,
+        );
+        assert_eq!(missing[1].detail, "no credentials found");
+
+        let chatgpt = api_results(
+            &provider(),
+            "https://chatgpt.com/backend-api/codex",
+            Ok(404),
+            Some(AuthMode::ChatGPT),
+            true,
+        );
+        assert_eq!(
+            chatgpt[1],
+            PreflightCheck::pass("Auth", "signed in with ChatGPT")
+        );
+    }
+
+    #[test]
+    fn providers_without_auth_only_check_reachability() {
+        let checks = api_results(
+            &provider(),
+            "http://localhost:11434/v1",
+            Ok(200),
+            None,
+            false,
+        );
+        assert_eq!(
+            checks,
+            vec![PreflightCheck::pass("API", "localhost is reachable")]
+        );
+    }
+}
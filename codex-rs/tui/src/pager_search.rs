@@ -0,0 +1,426 @@
+//! `/` search for the full-screen pagers (transcript and reader overlays).
+//!
+//! Matches are highlighted while the pattern is typed; `n`/`N` then move
+//! between matching lines. Searches ignore case and treat the pattern
+//! literally unless toggled with `Alt+c` (match case) or `Alt+r` (regex).
+
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+use ratatui::style::Color;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::style::Styled;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use regex_lite::Regex;
+use regex_lite::RegexBuilder;
+
+/// What a key typed into the search prompt did.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum SearchInput {
+    /// The pattern or a toggle changed; matches should be looked up again.
+    Changed,
+    /// Enter kept the pattern.
+    Confirmed,
+    /// Esc restored the previous pattern.
+    Cancelled,
+    Ignored,
+}
+
+#[derive(Default)]
+pub(crate) struct PagerSearch {
+    /// Pattern being typed after `/`; `None` when not entering a search.
+    input: Option<String>,
+    /// Pattern in effect before the current prompt was opened.
+    previous: String,
+    /// Confirmed pattern, or the one being typed.
+    pattern: String,
+    case_sensitive: bool,
+    regex: bool,
+    /// Compiled `pattern`; `None` when it is empty or invalid.
+    matcher: Option<Regex>,
+    /// Line the incremental search starts from.
+    anchor: usize,
+    /// Index of the wrapped line holding the current match.
+    pub(crate) current_match: Option<usize>,
+}
+
+impl PagerSearch {
+    pub(crate) fn is_editing(&self) -> bool {
+        self.input.is_some()
+    }
+
+    pub(crate) fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// Opens the prompt; the incremental search starts at `scroll_offset`.
+    pub(crate) fn start(&mut self, scroll_offset: usize) {
+        self.input = Some(String::new());
+        self.previous = std::mem::take(&mut self.pattern);
+        self.anchor = scroll_offset;
+        self.current_match = None;
+        self.compile();
+    }
+
+    pub(crate) fn handle_input_key(&mut self, key_event: KeyEvent) -> SearchInput {
+        let Some(input) = &mut self.input else {
+            return SearchInput::Ignored;
+        };
+        match key_event.code {
+            KeyCode::Esc => {
+                self.input = None;
+                self.pattern = std::mem::take(&mut self.previous);
+                self.current_match = None;
+                self.compile();
+                return SearchInput::Cancelled;
+            }
+            KeyCode::Enter => {
+                self.input = None;
+                return SearchInput::Confirmed;
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::ALT) => {
+                self.case_sensitive = !self.case_sensitive;
+            }
+            KeyCode::Char('r') if key_event.modifiers.contains(KeyModifiers::ALT) => {
+                self.regex = !self.regex;
+            }
+            KeyCode::Char(c)
+                if !key_event
+                    .modifiers
+                    .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) =>
+            {
+                input.push(c);
+            }
+            _ => return SearchInput::Ignored,
+        }
+        self.pattern = input.clone();
+        self.current_match = None;
+        self.compile();
+        SearchInput::Changed
+    }
+
+    fn compile(&mut self) {
+        self.matcher = if self.pattern.is_empty() {
+            None
+        } else {
+            let pattern = if self.regex {
+                self.pattern.clone()
+            } else {
+                regex_lite::escape(&self.pattern)
+            };
+            RegexBuilder::new(&pattern)
+                .case_insensitive(!self.case_sensitive)
+                .build()
+                .ok()
+        };
+    }
+
+    fn is_invalid(&self) -> bool {
+        !self.pattern.is_empty() && self.matcher.is_none()
+    }
+
+    /// Moves to the next (or previous) line matching the pattern and scrolls
+    /// it into view. Returns a message to show when there is nothing to move
+    /// to.
+    pub(crate) fn find(
+        &mut self,
+        lines: &[Line<'static>],
+        scroll_offset: &mut usize,
+        height: usize,
+        forward: bool,
+    ) -> Option<String> {
+        if self.is_invalid() {
+            self.current_match = None;
+            return Some(format!("invalid regex: {}", self.pattern));
+        }
+        let matcher = self.matcher.as_ref()?;
+        let matches: Vec<usize> = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| !match_ranges(matcher, &line_text(line)).is_empty())
+            .map(|(i, _)| i)
+            .collect();
+        // While typing, search from where the prompt was opened; otherwise
+        // from the current match, or the top of the page without one.
+        let from = if self.is_editing() {
+            self.anchor
+        } else {
+            *scroll_offset
+        };
+        let next = match (self.current_match, forward) {
+            (Some(current), true) => matches.iter().find(|&&line| line > current),
+            (None, true) => matches.iter().find(|&&line| line >= from),
+            (Some(current), false) => matches.iter().rev().find(|&&line| line < current),
+            (None, false) => matches.iter().rev().find(|&&line| line < from),
+        };
+        // Wrap around once the last match in either direction is passed.
+        let next = next.or(if forward {
+            matches.first()
+        } else {
+            matches.last()
+        });
+        match next {
+            Some(&line) => {
+                self.current_match = Some(line);
+                if line < *scroll_offset || line >= *scroll_offset + height {
+                    *scroll_offset = line.saturating_sub(height / 3);
+                }
+                None
+            }
+            None => {
+                self.current_match = None;
+                Some(format!("pattern not found: {}", self.pattern))
+            }
+        }
+    }
+
+    /// Returns `line` (the wrapped line at `index`) with every match
+    /// underlined, or reversed when it holds the current match.
+    pub(crate) fn highlight(&self, line: &Line<'static>, index: usize) -> Line<'static> {
+        let Some(matcher) = &self.matcher else {
+            return line.clone();
+        };
+        let ranges = match_ranges(matcher, &line_text(line));
+        if ranges.is_empty() {
+            return line.clone();
+        }
+        let modifier = if self.current_match == Some(index) {
+            Modifier::REVERSED
+        } else {
+            Modifier::UNDERLINED
+        };
+        add_modifier_to_ranges(line, &ranges, modifier)
+    }
+
+    /// The footer line shown while the pattern is typed.
+    pub(crate) fn prompt_line(&self) -> Option<Line<'static>> {
+        let input = self.input.as_ref()?;
+        let key_hint_style = Style::default().fg(Color::Cyan);
+        let toggle = |on: bool, label: &'static str| {
+            if on {
+                Span::from(label).reversed()
+            } else {
+                Span::from(label).dim()
+            }
+        };
+        let mut spans = vec![
+            " /".set_style(key_hint_style),
+            input.clone().into(),
+            "   ".into(),
+            toggle(self.case_sensitive, "Aa"),
+            " ".into(),
+            "Alt+c".set_style(key_hint_style).dim(),
+            "  ".into(),
+            toggle(self.regex, ".*"),
+            " ".into(),
+            "Alt+r".set_style(key_hint_style).dim(),
+        ];
+        if self.is_invalid() {
+            spans.extend(["   ".into(), "invalid regex".red()]);
+        }
+        Some(Line::from(spans))
+    }
+}
+
+/// `line` with `modifier` added to the text in the byte `ranges` of its
+/// content, splitting spans where a range starts or ends inside one.
+pub(crate) fn add_modifier_to_ranges(
+    line: &Line<'static>,
+    ranges: &[(usize, usize)],
+    modifier: Modifier,
+) -> Line<'static> {
+    let mut spans = Vec::new();
+    let mut offset = 0;
+    for span in &line.spans {
+        let content = span.content.as_ref();
+        let span_end = offset + content.len();
+        let mut pos = offset;
+        for &(start, end) in ranges {
+            let (start, end) = (start.max(pos), end.min(span_end));
+            if start >= end {
+                continue;
+            }
+            if start > pos {
+                spans.push(Span::styled(
+                    content[pos - offset..start - offset].to_string(),
+                    span.style,
+                ));
+            }
+            spans.push(Span::styled(
+                content[start - offset..end - offset].to_string(),
+                span.style.add_modifier(modifier),
+            ));
+            pos = end;
+        }
+        if pos < span_end {
+            spans.push(Span::styled(
+                content[pos - offset..].to_string(),
+                span.style,
+            ));
+        }
+        offset = span_end;
+    }
+    Line::from(spans).style(line.style)
+}
+
+fn line_text(line: &Line<'_>) -> String {
+    line.spans.iter().map(|s| s.content.as_ref()).collect()
+}
+
+/// Byte ranges of the non-empty matches of `matcher` in `text`.
+fn match_ranges(matcher: &Regex, text: &str) -> Vec<(usize, usize)> {
+    matcher
+        .find_iter(text)
+        .filter(|m| !m.is_empty())
+        .map(|m| (m.start(), m.end()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyEventKind;
+    use crossterm::event::KeyEventState;
+    use pretty_assertions::assert_eq;
+
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent {
+            code,
+            modifiers,
+            kind: KeyEventKind::Press,
+            state: KeyEventState::NONE,
+        }
+    }
+
+    fn type_pattern(search: &mut PagerSearch, pattern: &str) {
+        for c in pattern.chars() {
+            search.handle_input_key(key(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+    }
+
+    fn lines(texts: &[&str]) -> Vec<Line<'static>> {
+        texts.iter().map(|t| Line::from(t.to_string())).collect()
+    }
+
+    #[test]
+    fn incremental_search_moves_between_matches_and_wraps() {
+        let lines = lines(&["alpha", "Needle one", "beta", "needle two", "gamma"]);
+        let mut search = PagerSearch::default();
+        let mut scroll = 0;
+
+        search.start(scroll);
+        type_pattern(&mut search, "need");
+        assert_eq!(search.find(&lines, &mut scroll, 3, true), None);
+        assert_eq!(search.current_match, Some(1));
+        type_pattern(&mut search, "le t");
+        assert_eq!(search.find(&lines, &mut scroll, 3, true), None);
+        assert_eq!(search.current_match, Some(3));
+        assert_eq!(
+            search.handle_input_key(key(KeyCode::Enter, KeyModifiers::NONE)),
+            SearchInput::Confirmed
+        );
+        assert_eq!(search.pattern(), "needle t");
+
+        search.start(scroll);
+        type_pattern(&mut search, "needle");
+        assert_eq!(
+            search.handle_input_key(key(KeyCode::Enter, KeyModifiers::NONE)),
+            SearchInput::Confirmed
+        );
+        search.current_match = None;
+        scroll = 0;
+        let mut found = Vec::new();
+        for _ in 0..3 {
+            search.find(&lines, &mut scroll, 3, true);
+            found.push(search.current_match);
+        }
+        assert_eq!(found, vec![Some(1), Some(3), Some(1)]);
+        search.find(&lines, &mut scroll, 3, false);
+        assert_eq!(search.current_match, Some(3));
+    }
+
+    #[test]
+    fn toggles_switch_case_and_regex_matching() {
+        let lines = lines(&["Needle", "needle", "n33dle"]);
+        let mut search = PagerSearch::default();
+        let mut scroll = 0;
+
+        search.start(0);
+        search.handle_input_key(key(KeyCode::Char('c'), KeyModifiers::ALT));
+        type_pattern(&mut search, "needle");
+        search.find(&lines, &mut scroll, 3, true);
+        assert_eq!(search.current_match, Some(1));
+
+        search.handle_input_key(key(KeyCode::Esc, KeyModifiers::NONE));
+        search.start(0);
+        search.handle_input_key(key(KeyCode::Char('r'), KeyModifiers::ALT));
+        type_pattern(&mut search, r"n\d+dle");
+        search.find(&lines, &mut scroll, 3, true);
+        assert_eq!(search.current_match, Some(2));
+
+        type_pattern(&mut search, "(");
+        assert_eq!(
+            search.find(&lines, &mut scroll, 3, true),
+            Some(r"invalid regex: n\d+dle(".to_string())
+        );
+        assert_eq!(search.current_match, None);
+    }
+
+    #[test]
+    fn cancel_restores_the_previous_pattern() {
+        let mut search = PagerSearch::default();
+        search.start(0);
+        type_pattern(&mut search, "old");
+        search.handle_input_key(key(KeyCode::Enter, KeyModifiers::NONE));
+
+        search.start(0);
+        type_pattern(&mut search, "new");
+        assert_eq!(
+            search.handle_input_key(key(KeyCode::Esc, KeyModifiers::NONE)),
+            SearchInput::Cancelled
+        );
+        assert_eq!(search.pattern(), "old");
+        assert!(!search.is_editing());
+    }
+
+    #[test]
+    fn highlight_splits_spans_around_matches() {
+        let mut search = PagerSearch::default();
+        search.start(0);
+        type_pattern(&mut search, "bar");
+        let line = Line::from(vec![Span::raw("foo ba"), Span::raw("r foo")]);
+        search.current_match = Some(0);
+        let highlighted = search.highlight(&line, 0);
+        let contents: Vec<&str> = highlighted
+            .spans
+            .iter()
+            .map(|s| s.content.as_ref())
+            .collect();
+        assert_eq!(contents, vec!["foo ", "ba", "r", " foo"]);
+        assert!(
+            highlighted.spans[1]
+                .style
+                .add_modifier
+                .contains(Modifier::REVERSED)
+        );
+        assert!(
+            !highlighted.spans[3]
+                .style
+                .add_modifier
+                .contains(Modifier::REVERSED)
+        );
+        let other = search.highlight(&line, 1);
+        assert!(
+            other.spans[1]
+                .style
+                .add_modifier
+                .contains(Modifier::UNDERLINED)
+        );
+    }
+}
@@ -0,0 +1,546 @@
+use std::io::Result;
+use std::process::Stdio;
+
+use crate::file_citation::citation_at;
+use crate::file_citation::open_in_editor;
+use crate::insert_history;
+use crate::link_regex::LINK_REGEX;
+use crate::markdown::append_markdown;
+use crate::pager_search::PagerSearch;
+use crate::pager_search::SearchInput;
+use crate::transcript_app::MOUSE_SCROLL_LINES;
+use crate::tui;
+use crate::tui::TuiEvent;
+use codex_core::config::Config;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyEventKind;
+use crossterm::event::KeyModifiers;
+use crossterm::event::MouseButton;
+use crossterm::event::MouseEvent;
+use crossterm::event::MouseEventKind;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::style::Style;
+use ratatui::style::Styled;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::WidgetRef;
+
+/// Full-screen pager for reading agent messages, with search and link
+/// following.
+pub(crate) struct ReaderApp {
+    /// Markdown source of every agent message in the session, oldest first.
+    messages: Vec<String>,
+    /// Index into `messages` of the message being read.
+    index: usize,
+    lines: Vec<Line<'static>>,
+    links: Vec<String>,
+    selected_link: Option<usize>,
+    pub(crate) scroll_offset: usize,
+    search: PagerSearch,
+    /// One-line message shown in the footer, e.g. "pattern not found".
+    status: Option<String>,
+    config: Config,
+    pub(crate) is_done: bool,
+}
+
+impl ReaderApp {
+    /// Opens the last of `messages`. Returns `None` if there are none.
+    pub(crate) fn new(messages: Vec<String>, config: Config) -> Option<Self> {
+        let index = messages.len().checked_sub(1)?;
+        let mut app = Self {
+            messages,
+            index,
+            lines: Vec::new(),
+            links: Vec::new(),
+            selected_link: None,
+            scroll_offset: 0,
+            search: PagerSearch::default(),
+            status: None,
+            config,
+            is_done: false,
+        };
+        app.show_message(index);
+        Some(app)
+    }
+
+    fn show_message(&mut self, index: usize) {
+        self.index = index;
+        let source = &self.messages[index];
+        self.lines.clear();
+        append_markdown(source, &mut self.lines, &self.config);
+        self.links = extract_links(source);
+        self.selected_link = None;
+        self.scroll_offset = 0;
+        self.search.current_match = None;
+        self.status = None;
+    }
+
+    pub(crate) fn handle_event(&mut self, tui: &mut tui::Tui, event: TuiEvent) -> Result<()> {
+        match event {
+            TuiEvent::Key(key_event) => self.handle_key_event(tui, key_event),
+            TuiEvent::Mouse(mouse_event) => self.handle_mouse_event(tui, mouse_event),
+            TuiEvent::Draw => {
+                tui.draw(u16::MAX, |frame| {
+                    self.render(frame.area(), frame.buffer);
+                })?;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        let title = format!(
+            "/ R E A D I N G   ({}/{})",
+            self.index + 1,
+            self.messages.len()
+        );
+        Span::from("/ ".repeat(area.width as usize / 2))
+            .dim()
+            .render_ref(area, buf);
+        Span::from(title).dim().render_ref(area, buf);
+
+        let content_area = content_area(area);
+        let wrapped = self.wrapped_lines(content_area.width);
+        self.scroll_offset = self
+            .scroll_offset
+            .min(wrapped.len().saturating_sub(content_area.height as usize));
+        let start = self.scroll_offset;
+        let end = (start + content_area.height as usize).min(wrapped.len());
+        let page: Vec<Line<'static>> = wrapped[start..end]
+            .iter()
+            .enumerate()
+            .map(|(i, line)| self.search.highlight(line, start + i))
+            .collect();
+        Paragraph::new(page).render_ref(content_area, buf);
+
+        let sep_rect = Rect::new(area.x, content_area.bottom(), area.width, 1);
+        Span::from("─".repeat(sep_rect.width as usize))
+            .dim()
+            .render_ref(sep_rect, buf);
+        let max_scroll = wrapped.len().saturating_sub(content_area.height as usize);
+        let percent = if max_scroll == 0 {
+            100
+        } else {
+            self.scroll_offset * 100 / max_scroll
+        };
+        let pct_text = format!(" {percent}% ");
+        let pct_w = pct_text.chars().count() as u16;
+        if sep_rect.width > pct_w {
+            Span::from(pct_text).dim().render_ref(
+                Rect::new(sep_rect.right() - pct_w - 1, sep_rect.y, pct_w, 1),
+                buf,
+            );
+        }
+
+        let footer_rect = Rect::new(area.x, sep_rect.y + 1, area.width, 2);
+        Paragraph::new(self.footer_lines()).render_ref(footer_rect, buf);
+    }
+
+    fn footer_lines(&self) -> Vec<Line<'static>> {
+        let key_hint_style = Style::default().fg(Color::Cyan);
+        if let Some(prompt) = self.search.prompt_line() {
+            let mut lines = vec![prompt];
+            lines.extend(
+                self.status
+                    .clone()
+                    .map(|status| Line::from(format!(" {status}")).dim()),
+            );
+            return lines;
+        }
+        let mut hints1: Vec<Span<'static>> = vec![
+            " ".into(),
+            "↑".set_style(key_hint_style),
+            "/".into(),
+            "↓".set_style(key_hint_style),
+            " scroll   ".into(),
+            "PgUp".set_style(key_hint_style),
+            "/".into(),
+            "PgDn".set_style(key_hint_style),
+            " page   ".into(),
+            "/".set_style(key_hint_style),
+            " search   ".into(),
+            "n".set_style(key_hint_style),
+            "/".into(),
+            "N".set_style(key_hint_style),
+            " next/prev match".into(),
+        ];
+        if self.messages.len() > 1 {
+            hints1.extend([
+                "   ".into(),
+                "[".set_style(key_hint_style),
+                "/".into(),
+                "]".set_style(key_hint_style),
+                " prev/next message".into(),
+            ]);
+        }
+        let mut hints2: Vec<Span<'static>> =
+            vec![" ".into(), "q".set_style(key_hint_style), " quit".into()];
+        if !self.links.is_empty() {
+            hints2.extend([
+                "   ".into(),
+                "Tab".set_style(key_hint_style),
+                " select link".into(),
+            ]);
+        }
+        if let Some(status) = &self.status {
+            hints2.extend(["   ".into(), status.clone().into()]);
+        } else if let Some(link) = self.selected_link.and_then(|i| self.links.get(i)) {
+            hints2.extend([
+                "   ".into(),
+                "⏎".set_style(key_hint_style),
+                format!(" open {link}").into(),
+            ]);
+        }
+        vec![Line::from(hints1).dim(), Line::from(hints2).dim()]
+    }
+
+    fn wrapped_lines(&self, width: u16) -> Vec<Line<'static>> {
+        insert_history::word_wrap_lines(&self.lines, width)
+    }
+
+    fn handle_key_event(&mut self, tui: &mut tui::Tui, key_event: KeyEvent) {
+        if !matches!(key_event.kind, KeyEventKind::Press | KeyEventKind::Repeat) {
+            return;
+        }
+        if self.search.is_editing() {
+            self.handle_search_input_key(tui, key_event);
+            tui.frame_requester().schedule_frame();
+            return;
+        }
+        let page_height = content_area(tui.terminal.viewport_area).height as usize;
+        match key_event {
+            KeyEvent {
+                code: KeyCode::Char('q') | KeyCode::Esc,
+                ..
+            }
+            | KeyEvent {
+                code: KeyCode::Char('o') | KeyCode::Char('c'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => {
+                self.is_done = true;
+            }
+            KeyEvent {
+                code: KeyCode::Up | KeyCode::Char('k'),
+                ..
+            } => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(1);
+            }
+            KeyEvent {
+                code: KeyCode::Down | KeyCode::Char('j'),
+                ..
+            } => {
+                self.scroll_offset = self.scroll_offset.saturating_add(1);
+            }
+            KeyEvent {
+                code: KeyCode::PageUp | KeyCode::Char('b'),
+                ..
+            } => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(page_height);
+            }
+            KeyEvent {
+                code: KeyCode::PageDown | KeyCode::Char(' '),
+                ..
+            } => {
+                self.scroll_offset = self.scroll_offset.saturating_add(page_height);
+            }
+            KeyEvent {
+                code: KeyCode::Home | KeyCode::Char('g'),
+                ..
+            } => {
+                self.scroll_offset = 0;
+            }
+            KeyEvent {
+                code: KeyCode::End | KeyCode::Char('G'),
+                ..
+            } => {
+                self.scroll_offset = usize::MAX;
+            }
+            KeyEvent {
+                code: KeyCode::Char('/'),
+                ..
+            } => {
+                self.search.start(self.scroll_offset);
+                self.status = None;
+            }
+            KeyEvent {
+                code: KeyCode::Char('n'),
+                ..
+            } => {
+                self.find_match(tui.terminal.viewport_area, true);
+            }
+            KeyEvent {
+                code: KeyCode::Char('N'),
+                ..
+            } => {
+                self.find_match(tui.terminal.viewport_area, false);
+            }
+            KeyEvent {
+                code: KeyCode::Char('['),
+                ..
+            } if self.index > 0 => {
+                self.show_message(self.index - 1);
+            }
+            KeyEvent {
+                code: KeyCode::Char(']'),
+                ..
+            } if self.index + 1 < self.messages.len() => {
+                self.show_message(self.index + 1);
+            }
+            KeyEvent {
+                code: KeyCode::Tab, ..
+            } => {
+                self.select_link(true);
+            }
+            KeyEvent {
+                code: KeyCode::BackTab,
+                ..
+            } => {
+                self.select_link(false);
+            }
+            KeyEvent {
+                code: KeyCode::Enter,
+                ..
+            } => {
+                if let Some(link) = self.selected_link.and_then(|i| self.links.get(i)) {
+                    self.status = match open_link(link) {
+                        Ok(()) => Some(format!("opened {link}")),
+                        Err(e) => Some(format!("failed to open {link}: {e}")),
+                    };
+                }
+            }
+            _ => {
+                return;
+            }
+        }
+        tui.frame_requester().schedule_frame();
+    }
+
+    fn handle_mouse_event(&mut self, tui: &mut tui::Tui, mouse_event: MouseEvent) {
+        match mouse_event.kind {
+            MouseEventKind::ScrollUp => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(MOUSE_SCROLL_LINES);
+            }
+            MouseEventKind::ScrollDown => {
+                self.scroll_offset = self.scroll_offset.saturating_add(MOUSE_SCROLL_LINES);
+            }
+            MouseEventKind::Down(MouseButton::Left) => {
+                let area = content_area(tui.terminal.viewport_area);
+                if mouse_event.row < area.y
+                    || mouse_event.row >= area.bottom()
+                    || mouse_event.column < area.x
+                {
+                    return;
+                }
+                let wrapped = self.wrapped_lines(area.width);
+                let index = self.scroll_offset + (mouse_event.row - area.y) as usize;
+                let Some(citation) = wrapped.get(index).and_then(|line| {
+                    citation_at(line, mouse_event.column - area.x, &self.config.cwd)
+                }) else {
+                    return;
+                };
+                let name = citation.path.display().to_string();
+                self.status = match open_in_editor(tui, &citation) {
+                    Ok(()) => None,
+                    Err(e) => Some(format!("failed to open {name}: {e}")),
+                };
+            }
+            _ => return,
+        }
+        tui.frame_requester().schedule_frame();
+    }
+
+    fn handle_search_input_key(&mut self, tui: &mut tui::Tui, key_event: KeyEvent) {
+        match self.search.handle_input_key(key_event) {
+            SearchInput::Changed => {
+                self.find_match(tui.terminal.viewport_area, true);
+            }
+            SearchInput::Confirmed | SearchInput::Cancelled => {
+                self.status = None;
+            }
+            SearchInput::Ignored => {}
+        }
+    }
+
+    /// Moves to the next (or previous) line matching the search and scrolls
+    /// it into view.
+    fn find_match(&mut self, viewport: Rect, forward: bool) {
+        let area = content_area(viewport);
+        let wrapped = self.wrapped_lines(area.width);
+        self.status = self.search.find(
+            &wrapped,
+            &mut self.scroll_offset,
+            area.height as usize,
+            forward,
+        );
+    }
+
+    fn select_link(&mut self, forward: bool) {
+        let count = self.links.len();
+        if count == 0 {
+            return;
+        }
+        self.status = None;
+        self.selected_link = Some(match (self.selected_link, forward) {
+            (None, true) => 0,
+            (None, false) => count - 1,
+            (Some(i), true) => (i + 1) % count,
+            (Some(i), false) => (i + count - 1) % count,
+        });
+    }
+}
+
+fn content_area(area: Rect) -> Rect {
+    // Reserve 1 line for the header and 3 for the separator and footer.
+    let mut area = area;
+    area.y = area.y.saturating_add(1);
+    area.height = area.height.saturating_sub(4);
+    area
+}
+
+fn extract_links(markdown: &str) -> Vec<String> {
+    let mut links: Vec<String> = Vec::new();
+    for captures in LINK_REGEX.captures_iter(markdown) {
+        let Some(link) = captures.get(1).or_else(|| captures.get(2)) else {
+            continue;
+        };
+        let link = link.as_str().trim_end_matches(['.', ',', ';', ':']);
+        if !links.iter().any(|l| l == link) {
+            links.push(link.to_string());
+        }
+    }
+    links
+}
+
+fn open_link(link: &str) -> std::io::Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        std::process::Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = std::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        std::process::Command::new("xdg-open")
+    };
+    command
+        .arg(link)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .map(|_| ())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use codex_core::config::ConfigOverrides;
+    use codex_core::config::ConfigToml;
+    use pretty_assertions::assert_eq;
+
+    fn test_config() -> Config {
+        Config::load_from_base_config_with_overrides(
+            ConfigToml::default(),
+            ConfigOverrides::default(),
+            std::env::temp_dir(),
+        )
+        .expect("config")
+    }
+
+    fn render_to_string(app: &mut ReaderApp, area: Rect) -> String {
+        let mut buf = Buffer::empty(area);
+        app.render(area, &mut buf);
+        let mut s = String::new();
+        for y in area.y..area.bottom() {
+            for x in area.x..area.right() {
+                s.push(buf[(x, y)].symbol().chars().next().unwrap_or(' '));
+            }
+            s.push('\n');
+        }
+        s
+    }
+
+    /// Types `pattern` into the search prompt and confirms it.
+    fn search_for(app: &mut ReaderApp, pattern: &str) {
+        app.search.start(0);
+        for c in pattern.chars() {
+            app.search
+                .handle_input_key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        app.search
+            .handle_input_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        app.search.current_match = None;
+    }
+
+    #[test]
+    fn opens_the_last_message() {
+        assert!(ReaderApp::new(Vec::new(), test_config()).is_none());
+
+        let messages = vec!["first answer".to_string(), "# Design\n\nsecond".to_string()];
+        let Some(mut app) = ReaderApp::new(messages, test_config()) else {
+            panic!("reader should open");
+        };
+        let screen = render_to_string(&mut app, Rect::new(0, 0, 60, 12));
+        assert!(screen.contains("R E A D I N G   (2/2)"), "{screen}");
+        assert!(screen.contains("second"), "{screen}");
+        assert!(!screen.contains("first answer"), "{screen}");
+
+        app.show_message(0);
+        let screen = render_to_string(&mut app, Rect::new(0, 0, 60, 12));
+        assert!(screen.contains("first answer"), "{screen}");
+    }
+
+    #[test]
+    fn search_moves_to_matching_lines_and_wraps() {
+        let text = (0..30)
+            .map(|i| {
+                if i % 10 == 5 {
+                    format!("line {i} needle")
+                } else {
+                    format!("line {i}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n\n");
+        let Some(mut app) = ReaderApp::new(vec![text], test_config()) else {
+            panic!("reader should open");
+        };
+        let viewport = Rect::new(0, 0, 40, 10);
+        search_for(&mut app, "NEEDLE");
+
+        let mut found = Vec::new();
+        for _ in 0..4 {
+            app.find_match(viewport, true);
+            found.push(app.search.current_match);
+        }
+        assert_eq!(found[0], found[3]);
+        assert!(found[0] < found[1] && found[1] < found[2]);
+
+        app.find_match(viewport, false);
+        assert_eq!(app.search.current_match, found[2]);
+
+        search_for(&mut app, "missing");
+        app.find_match(viewport, true);
+        assert_eq!(app.search.current_match, None);
+        assert_eq!(app.status.as_deref(), Some("pattern not found: missing"));
+    }
+
+    #[test]
+    fn links_are_extracted_once_in_order() {
+        let markdown = "See [the docs](https://example.com/docs) and https://example.com/b.\n\
+                        Again: [docs](https://example.com/docs)";
+        assert_eq!(
+            extract_links(markdown),
+            vec![
+                "https://example.com/docs".to_string(),
+                "https://example.com/b".to_string(),
+            ]
+        );
+    }
+}
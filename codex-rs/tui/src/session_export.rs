@@ -0,0 +1,349 @@
+//! `/export`: writes the conversation to a self-contained Markdown or HTML
+//! file.
+//!
+//! The export is rendered from the history cells' transcript lines, the same
+//! lines the transcript overlay shows, so prompts, agent messages, commands
+//! with their exit codes and applied diffs look the way they did on screen.
+
+use std::path::Path;
+use std::path::PathBuf;
+
+use chrono::DateTime;
+use chrono::Local;
+use ratatui::style::Color;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::text::Line;
+
+const TITLE: &str = "Codex session";
+
+const USAGE: &str = "Usage: /export [md|html] [path]";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ExportFormat {
+    Markdown,
+    Html,
+}
+
+impl ExportFormat {
+    fn from_keyword(keyword: &str) -> Option<Self> {
+        match keyword.to_ascii_lowercase().as_str() {
+            "md" | "markdown" => Some(Self::Markdown),
+            "html" | "htm" => Some(Self::Html),
+            _ => None,
+        }
+    }
+
+    /// The format implied by `path`'s extension; Markdown unless it is HTML.
+    fn for_path(path: &Path) -> Self {
+        path.extension()
+            .and_then(|ext| Self::from_keyword(&ext.to_string_lossy()))
+            .unwrap_or(Self::Markdown)
+    }
+
+    fn extension(self) -> &'static str {
+        match self {
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+}
+
+/// Parses the arguments of `/export [md|html] [path]` into the format and
+/// the file to write. Without a format the path's extension picks one;
+/// without a path the file is named after `now` and placed in `cwd`.
+/// Relative paths are resolved against `cwd`.
+pub(crate) fn parse_export_args(
+    args: &str,
+    cwd: &Path,
+    now: DateTime<Local>,
+) -> Result<(ExportFormat, PathBuf), String> {
+    let words = shlex::split(args).ok_or_else(|| USAGE.to_string())?;
+    let (format, path) = match words.as_slice() {
+        [] => (ExportFormat::Markdown, None),
+        [word] => match ExportFormat::from_keyword(word) {
+            Some(format) => (format, None),
+            None => (ExportFormat::for_path(Path::new(word)), Some(word)),
+        },
+        [keyword, path] => match ExportFormat::from_keyword(keyword) {
+            Some(format) => (format, Some(path)),
+            None => return Err(USAGE.to_string()),
+        },
+        _ => return Err(USAGE.to_string()),
+    };
+    let path = match path {
+        Some(path) => cwd.join(path),
+        None => cwd.join(format!(
+            "codex-session-{}.{}",
+            now.format("%Y%m%d-%H%M%S"),
+            format.extension()
+        )),
+    };
+    Ok((format, path))
+}
+
+/// Renders `lines` as a complete document in `format`.
+pub(crate) fn render(lines: &[Line<'static>], format: ExportFormat) -> String {
+    match format {
+        ExportFormat::Markdown => render_markdown(lines),
+        ExportFormat::Html => render_html(lines),
+    }
+}
+
+/// Writes `lines` to `path` in `format`.
+pub(crate) fn export(
+    lines: &[Line<'static>],
+    format: ExportFormat,
+    path: &Path,
+) -> std::io::Result<()> {
+    std::fs::write(path, render(lines, format))
+}
+
+fn line_text(line: &Line<'_>) -> String {
+    line.spans.iter().map(|s| s.content.as_ref()).collect()
+}
+
+/// The transcript goes into one fenced block: it is already laid out for a
+/// terminal, and a fence keeps that layout (and any markdown-looking text in
+/// command output) intact.
+fn render_markdown(lines: &[Line<'static>]) -> String {
+    let texts: Vec<String> = lines.iter().map(line_text).collect();
+    let longest_backtick_run = texts
+        .iter()
+        .flat_map(|text| text.split(|c| c != '`').map(str::len))
+        .max()
+        .unwrap_or(0);
+    let fence = "`".repeat(longest_backtick_run.max(2) + 1);
+
+    let mut out = format!("# {TITLE}\n\n{fence}text\n");
+    for text in texts {
+        out.push_str(text.trim_end());
+        out.push('\n');
+    }
+    out.push_str(&fence);
+    out.push('\n');
+    out
+}
+
+fn render_html(lines: &[Line<'static>]) -> String {
+    let mut out = format!(
+        "<!DOCTYPE html>\n\
+         <html lang=\"en\">\n\
+         <head>\n\
+         <meta charset=\"utf-8\">\n\
+         <title>{TITLE}</title>\n\
+         <style>\n\
+         body {{ background: {BACKGROUND}; color: {FOREGROUND}; margin: 0; padding: 1.5em; }}\n\
+         pre {{ font-family: ui-monospace, SFMono-Regular, Menlo, Consolas, monospace; \
+         font-size: 13px; line-height: 1.4; white-space: pre-wrap; overflow-wrap: anywhere; }}\n\
+         </style>\n\
+         </head>\n\
+         <body>\n\
+         <pre>"
+    );
+    for line in lines {
+        for span in &line.spans {
+            let style = line.style.patch(span.style);
+            let text = escape_html(&span.content);
+            match css(style) {
+                Some(css) => out.push_str(&format!("<span style=\"{css}\">{text}</span>")),
+                None => out.push_str(&text),
+            }
+        }
+        out.push('\n');
+    }
+    out.push_str("</pre>\n</body>\n</html>\n");
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+const FOREGROUND: &str = "#d4d4d4";
+const BACKGROUND: &str = "#1e1e1e";
+
+/// Inline CSS for `style`, or `None` when it is the default style.
+fn css(style: Style) -> Option<String> {
+    let (mut fg, mut bg) = (style.fg.and_then(css_color), style.bg.and_then(css_color));
+    if style.add_modifier.contains(Modifier::REVERSED) {
+        (fg, bg) = (
+            Some(bg.unwrap_or_else(|| BACKGROUND.to_string())),
+            Some(fg.unwrap_or_else(|| FOREGROUND.to_string())),
+        );
+    }
+
+    let mut rules = Vec::new();
+    if let Some(fg) = fg {
+        rules.push(format!("color: {fg}"));
+    }
+    if let Some(bg) = bg {
+        rules.push(format!("background: {bg}"));
+    }
+    let modifiers = style.add_modifier;
+    if modifiers.contains(Modifier::BOLD) {
+        rules.push("font-weight: bold".to_string());
+    }
+    if modifiers.contains(Modifier::DIM) {
+        rules.push("opacity: 0.6".to_string());
+    }
+    if modifiers.contains(Modifier::ITALIC) {
+        rules.push("font-style: italic".to_string());
+    }
+    match (
+        modifiers.contains(Modifier::UNDERLINED),
+        modifiers.contains(Modifier::CROSSED_OUT),
+    ) {
+        (true, true) => rules.push("text-decoration: underline line-through".to_string()),
+        (true, false) => rules.push("text-decoration: underline".to_string()),
+        (false, true) => rules.push("text-decoration: line-through".to_string()),
+        (false, false) => {}
+    }
+    (!rules.is_empty()).then(|| rules.join("; "))
+}
+
+/// The 16 ANSI colors, in palette order, as a dark terminal theme shows them.
+const ANSI_PALETTE: [&str; 16] = [
+    "#000000", "#cd3131", "#0dbc79", "#e5e510", "#2472c8", "#bc3fbc", "#11a8cd", "#e5e5e5",
+    "#666666", "#f14c4c", "#23d18b", "#f5f543", "#3b8eea", "#d670d6", "#29b8db", "#ffffff",
+];
+
+fn css_color(color: Color) -> Option<String> {
+    let index = match color {
+        Color::Reset => return None,
+        Color::Black => 0,
+        Color::Red => 1,
+        Color::Green => 2,
+        Color::Yellow => 3,
+        Color::Blue => 4,
+        Color::Magenta => 5,
+        Color::Cyan => 6,
+        Color::Gray => 7,
+        Color::DarkGray => 8,
+        Color::LightRed => 9,
+        Color::LightGreen => 10,
+        Color::LightYellow => 11,
+        Color::LightBlue => 12,
+        Color::LightMagenta => 13,
+        Color::LightCyan => 14,
+        Color::White => 15,
+        Color::Rgb(r, g, b) => return Some(format!("#{r:02x}{g:02x}{b:02x}")),
+        Color::Indexed(i) => return Some(indexed_color(i)),
+    };
+    Some(ANSI_PALETTE[index].to_string())
+}
+
+/// CSS for an entry of the xterm 256-color palette.
+fn indexed_color(index: u8) -> String {
+    match index {
+        0..=15 => ANSI_PALETTE[index as usize].to_string(),
+        16..=231 => {
+            let level = |n: u8| if n == 0 { 0 } else { 55 + 40 * n };
+            let i = index - 16;
+            format!(
+                "#{:02x}{:02x}{:02x}",
+                level(i / 36),
+                level(i / 6 % 6),
+                level(i % 6)
+            )
+        }
+        _ => {
+            let gray = 8 + 10 * (index - 232);
+            format!("#{gray:02x}{gray:02x}{gray:02x}")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use pretty_assertions::assert_eq;
+    use ratatui::style::Stylize;
+
+    fn transcript() -> Vec<Line<'static>> {
+        vec![
+            Line::from("user".cyan().bold()),
+            Line::from("print <b> & ```fences```"),
+            Line::from(vec!["$ ".magenta(), "false".into()]),
+            Line::from(vec!["✗".red().bold(), " (1)".into(), " • 5ms".dim()]),
+        ]
+    }
+
+    #[test]
+    fn parses_format_and_path() {
+        let cwd = Path::new("/work");
+        let now = Local.with_ymd_and_hms(2025, 1, 2, 3, 4, 5).unwrap();
+        let parse = |args| parse_export_args(args, cwd, now);
+
+        assert_eq!(
+            parse(""),
+            Ok((
+                ExportFormat::Markdown,
+                PathBuf::from("/work/codex-session-20250102-030405.md")
+            ))
+        );
+        assert_eq!(
+            parse("html"),
+            Ok((
+                ExportFormat::Html,
+                PathBuf::from("/work/codex-session-20250102-030405.html")
+            ))
+        );
+        assert_eq!(
+            parse("out/session.html"),
+            Ok((ExportFormat::Html, PathBuf::from("/work/out/session.html")))
+        );
+        assert_eq!(
+            parse("md '/tmp/my session.txt'"),
+            Ok((ExportFormat::Markdown, PathBuf::from("/tmp/my session.txt")))
+        );
+        assert_eq!(parse("pdf out.pdf"), Err(USAGE.to_string()));
+    }
+
+    #[test]
+    fn markdown_fences_the_transcript_text() {
+        assert_eq!(
+            render(&transcript(), ExportFormat::Markdown),
+            "# Codex session\n\n\
+             ````text\n\
+             user\n\
+             print <b> & ```fences```\n\
+             $ false\n\
+             ✗ (1) • 5ms\n\
+             ````\n"
+        );
+    }
+
+    #[test]
+    fn html_escapes_text_and_inlines_styles() {
+        let html = render(&transcript(), ExportFormat::Html);
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains(
+            "<pre><span style=\"color: #11a8cd; font-weight: bold\">user</span>\n\
+             print &lt;b&gt; &amp; ```fences```\n"
+        ));
+        assert!(html.contains(
+            "<span style=\"color: #cd3131; font-weight: bold\">✗</span> (1)\
+             <span style=\"opacity: 0.6\"> • 5ms</span>\n</pre>"
+        ));
+    }
+
+    #[test]
+    fn maps_indexed_colors() {
+        assert_eq!(indexed_color(1), "#cd3131");
+        assert_eq!(indexed_color(16), "#000000");
+        assert_eq!(indexed_color(196), "#ff0000");
+        assert_eq!(indexed_color(244), "#808080");
+    }
+}
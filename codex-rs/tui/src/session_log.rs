@@ -168,13 +168,53 @@ pub(crate) fn log_inbound_app_event(event: &AppEvent) {
             });
             LOGGER.write_json_line(value);
         }
-        AppEvent::FileSearchResult { query, matches } => {
+        AppEvent::FileSearchResult {
+            query,
+            matches,
+            content_matches,
+        } => {
             let value = json!({
                 "ts": now_ts(),
                 "dir": "to_tui",
                 "kind": "file_search_result",
                 "query": query,
                 "matches": matches.len(),
+                "content_matches": content_matches.len(),
+            });
+            LOGGER.write_json_line(value);
+        }
+        AppEvent::RecordRecentFiles(paths) => {
+            let value = json!({
+                "ts": now_ts(),
+                "dir": "to_tui",
+                "kind": "record_recent_files",
+                "paths": paths.len(),
+            });
+            LOGGER.write_json_line(value);
+        }
+        AppEvent::ShellCommandOutput(chunk) => {
+            let value = json!({
+                "ts": now_ts(),
+                "dir": "to_tui",
+                "kind": "shell_command_output",
+                "bytes": chunk.len(),
+            });
+            LOGGER.write_json_line(value);
+        }
+        AppEvent::ShellCommandFinished {
+            command,
+            output,
+            duration,
+        } => {
+            let value = json!({
+                "ts": now_ts(),
+                "dir": "to_tui",
+                "kind": "shell_command",
+                "command": command,
+                "exit_code": output.exit_code,
+                "stdout": output.stdout,
+                "stderr": output.stderr,
+                "duration_ms": duration.as_millis() as u64,
             });
             LOGGER.write_json_line(value);
         }
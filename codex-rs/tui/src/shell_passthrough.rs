@@ -0,0 +1,423 @@
+//! `!command` in the composer: runs the command in the user's own shell,
+//! outside the agent loop.
+//!
+//! The command is not sandboxed, needs no approval and is not sent to the
+//! model. Its output streams into an exec cell like the agent's commands do;
+//! once it exits, Ctrl+S places the output in the composer so the user can
+//! share it with the agent in their next message.
+
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_common::elapsed::format_duration;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncReadExt;
+use tokio::process::Command;
+use tokio::sync::oneshot;
+
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+use crate::history_cell::CommandOutput;
+
+/// Most lines of output shared with the agent; earlier lines are dropped
+/// since the end of the output (the failure, the summary) matters most.
+const SHARE_MAX_LINES: usize = 200;
+
+/// Output is forwarded in chunks of up to this many bytes, sent at most
+/// every [`OUTPUT_DELTA_INTERVAL`] unless they fill up first, like the
+/// output of the agent's commands.
+const OUTPUT_DELTA_MAX_BYTES: usize = 16 * 1024;
+const OUTPUT_DELTA_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Most output kept per stream once the command exits; earlier output is
+/// dropped.
+const OUTPUT_MAX_BYTES: usize = 1024 * 1024;
+
+/// How long to keep reading output after the shell exits. Anything it left
+/// running in the background that still holds the pipes open, like
+/// `server &`, is not waited for.
+const POST_EXIT_READ_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// The command in a `!command` submission, or `None` for a regular message.
+pub(crate) fn parse_shell_command(text: &str) -> Option<&str> {
+    let command = text.trim().strip_prefix('!')?.trim();
+    (!command.is_empty()).then_some(command)
+}
+
+/// How the command is stored in its exec cell: the `bash -lc` form the
+/// agent's commands use, which the cell displays as the bare script.
+pub(crate) fn display_command(command: &str) -> Vec<String> {
+    vec!["bash".to_string(), "-lc".to_string(), command.to_string()]
+}
+
+/// Starts `command` in `cwd`. Output is sent as
+/// [`AppEvent::ShellCommandOutput`] as it arrives, followed by one
+/// [`AppEvent::ShellCommandFinished`]. Sending on (or dropping) the returned
+/// sender kills the command.
+pub(crate) fn spawn_shell_command(
+    command: String,
+    cwd: PathBuf,
+    tx: AppEventSender,
+) -> oneshot::Sender<()> {
+    let (cancel_tx, cancel_rx) = oneshot::channel();
+    tokio::spawn(async move {
+        let start = Instant::now();
+        let output = match run(&command, cwd, &tx, cancel_rx).await {
+            Ok(output) => output,
+            Err(e) => {
+                let stderr = format!("failed to run command: {e}");
+                CommandOutput {
+                    exit_code: -1,
+                    stdout: String::new(),
+                    formatted_output: stderr.clone(),
+                    stderr,
+                    sandbox_denials: Vec::new(),
+                }
+            }
+        };
+        tx.send(AppEvent::ShellCommandFinished {
+            command,
+            output,
+            duration: start.elapsed(),
+        });
+    });
+    cancel_tx
+}
+
+#[cfg(unix)]
+fn shell_invocation(command: &str) -> Command {
+    let shell = std::env::var("SHELL")
+        .ok()
+        .filter(|shell| !shell.is_empty())
+        .unwrap_or_else(|| "/bin/sh".to_string());
+    let mut cmd = Command::new(shell);
+    cmd.arg("-c").arg(command);
+    // A group of its own, so cancelling also stops whatever the shell started.
+    cmd.process_group(0);
+    cmd
+}
+
+#[cfg(windows)]
+fn shell_invocation(command: &str) -> Command {
+    let mut cmd = Command::new("cmd");
+    cmd.arg("/C").arg(command);
+    cmd
+}
+
+async fn run(
+    command: &str,
+    cwd: PathBuf,
+    tx: &AppEventSender,
+    cancel: oneshot::Receiver<()>,
+) -> std::io::Result<CommandOutput> {
+    let mut child = shell_invocation(command)
+        .current_dir(cwd)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()?;
+    let stdout_tail = Arc::new(Mutex::new(Vec::new()));
+    let stderr_tail = Arc::new(Mutex::new(Vec::new()));
+    let stdout = child
+        .stdout
+        .take()
+        .map(|r| tokio::spawn(forward(r, tx.clone(), stdout_tail.clone())));
+    let stderr = child
+        .stderr
+        .take()
+        .map(|r| tokio::spawn(forward(r, tx.clone(), stderr_tail.clone())));
+
+    let status = tokio::select! {
+        status = child.wait() => status?,
+        _ = cancel => {
+            kill(&mut child)?;
+            child.wait().await?
+        }
+    };
+    let deadline = tokio::time::Instant::now() + POST_EXIT_READ_TIMEOUT;
+    for mut reader in [stdout, stderr].into_iter().flatten() {
+        if tokio::time::timeout_at(deadline, &mut reader)
+            .await
+            .is_err()
+        {
+            reader.abort();
+        }
+    }
+    let stdout = collect(&stdout_tail);
+    let stderr = collect(&stderr_tail);
+    Ok(CommandOutput {
+        exit_code: exit_code(status),
+        formatted_output: format!("{stdout}{stderr}"),
+        stdout,
+        stderr,
+        sandbox_denials: Vec::new(),
+    })
+}
+
+#[cfg(unix)]
+fn kill(child: &mut tokio::process::Child) -> std::io::Result<()> {
+    if let Some(pid) = child.id() {
+        // SAFETY: `pid` leads the process group created in `shell_invocation`.
+        unsafe {
+            libc::killpg(pid as libc::pid_t, libc::SIGKILL);
+        }
+    }
+    child.start_kill()
+}
+
+#[cfg(windows)]
+fn kill(child: &mut tokio::process::Child) -> std::io::Result<()> {
+    child.start_kill()
+}
+
+/// The exit code as a shell reports it: 128 + the signal for a command that
+/// was killed.
+fn exit_code(status: std::process::ExitStatus) -> i32 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::process::ExitStatusExt;
+        if let Some(signal) = status.signal() {
+            return 128 + signal;
+        }
+    }
+    status.code().unwrap_or(-1)
+}
+
+/// Sends what `reader` produces as coalesced [`AppEvent::ShellCommandOutput`]
+/// events, and keeps the last [`OUTPUT_MAX_BYTES`] of it in `tail`.
+async fn forward<R>(mut reader: R, tx: AppEventSender, tail: Arc<Mutex<Vec<u8>>>)
+where
+    R: AsyncRead + Unpin,
+{
+    let mut buf = [0u8; 8192];
+    let mut pending = Vec::new();
+    let mut flush_at = tokio::time::Instant::now();
+    loop {
+        let read = if pending.is_empty() {
+            Ok(reader.read(&mut buf).await)
+        } else {
+            tokio::time::timeout_at(flush_at, reader.read(&mut buf)).await
+        };
+        match read {
+            Ok(Ok(0)) | Ok(Err(_)) => break,
+            Ok(Ok(n)) => {
+                keep_tail(&mut lock(&tail), &buf[..n]);
+                if pending.is_empty() {
+                    flush_at = tokio::time::Instant::now() + OUTPUT_DELTA_INTERVAL;
+                }
+                pending.extend_from_slice(&buf[..n]);
+                if pending.len() >= OUTPUT_DELTA_MAX_BYTES {
+                    tx.send(AppEvent::ShellCommandOutput(std::mem::take(&mut pending)));
+                }
+            }
+            // Nothing more arrived in time; send what there is.
+            Err(_) => tx.send(AppEvent::ShellCommandOutput(std::mem::take(&mut pending))),
+        }
+    }
+    if !pending.is_empty() {
+        tx.send(AppEvent::ShellCommandOutput(pending));
+    }
+}
+
+fn keep_tail(tail: &mut Vec<u8>, chunk: &[u8]) {
+    tail.extend_from_slice(chunk);
+    if tail.len() > OUTPUT_MAX_BYTES {
+        let excess = tail.len() - OUTPUT_MAX_BYTES;
+        tail.drain(..excess);
+    }
+}
+
+fn collect(tail: &Mutex<Vec<u8>>) -> String {
+    String::from_utf8_lossy(&lock(tail)).into_owned()
+}
+
+fn lock(tail: &Mutex<Vec<u8>>) -> std::sync::MutexGuard<'_, Vec<u8>> {
+    tail.lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+}
+
+/// The message placed in the composer when the user shares the output of
+/// `command`.
+pub(crate) fn share_text(command: &str, output: &CommandOutput, duration: Duration) -> String {
+    let text = output.formatted_output.trim_end();
+    let lines: Vec<&str> = text.lines().collect();
+    let omitted = lines.len().saturating_sub(SHARE_MAX_LINES);
+    let kept = lines[omitted..].join("\n");
+
+    let longest_backtick_run = kept.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest_backtick_run.max(2) + 1);
+
+    let mut message = format!(
+        "I ran `{command}` in my shell. It exited with code {} after {}.",
+        output.exit_code,
+        format_duration(duration)
+    );
+    if text.is_empty() {
+        message.push_str(" It printed nothing.");
+        return message;
+    }
+    if omitted > 0 {
+        message.push_str(&format!(" Its last {SHARE_MAX_LINES} lines of output:"));
+    } else {
+        message.push_str(" Its output:");
+    }
+    message.push_str(&format!("\n\n{fence}\n{kept}\n{fence}"));
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    fn output(exit_code: i32, formatted_output: &str) -> CommandOutput {
+        CommandOutput {
+            exit_code,
+            stdout: formatted_output.to_string(),
+            stderr: String::new(),
+            formatted_output: formatted_output.to_string(),
+            sandbox_denials: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn parses_bang_prefixed_commands() {
+        assert_eq!(parse_shell_command("!cargo test"), Some("cargo test"));
+        assert_eq!(parse_shell_command("  ! ls -la \n"), Some("ls -la"));
+        assert_eq!(parse_shell_command("!"), None);
+        assert_eq!(parse_shell_command("run !this"), None);
+    }
+
+    #[test]
+    fn share_text_fences_the_tail_of_the_output() {
+        assert_eq!(
+            share_text(
+                "cargo test",
+                &output(101, "test a ... ok\ntest b ... FAILED\n"),
+                Duration::from_millis(1500)
+            ),
+            "I ran `cargo test` in my shell. It exited with code 101 after 1.50s. Its output:\n\n\
+             ```\ntest a ... ok\ntest b ... FAILED\n```"
+        );
+        assert_eq!(
+            share_text("true", &output(0, ""), Duration::from_millis(3)),
+            "I ran `true` in my shell. It exited with code 0 after 3ms. It printed nothing."
+        );
+
+        let long: String = (1..=250).map(|i| format!("line {i}\n")).collect();
+        let text = share_text("seq", &output(0, &long), Duration::from_millis(3));
+        assert!(text.contains("Its last 200 lines of output:\n\n```\nline 51\n"));
+        assert!(text.ends_with("line 250\n```"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "current_thread")]
+    async fn streams_output_then_reports_the_exit_code() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let _cancel = spawn_shell_command(
+            "printf out; printf err >&2; exit 3".to_string(),
+            std::env::temp_dir(),
+            AppEventSender::new(tx_raw),
+        );
+
+        let mut streamed = Vec::new();
+        loop {
+            match rx.recv().await.expect("event") {
+                AppEvent::ShellCommandOutput(chunk) => streamed.extend(chunk),
+                AppEvent::ShellCommandFinished {
+                    command, output, ..
+                } => {
+                    assert_eq!(command, "printf out; printf err >&2; exit 3");
+                    assert_eq!(output.exit_code, 3);
+                    assert_eq!(output.stdout, "out");
+                    assert_eq!(output.stderr, "err");
+                    break;
+                }
+                other => panic!("unexpected event: {other:?}"),
+            }
+        }
+        streamed.sort_unstable();
+        assert_eq!(streamed, b"eorrtu".to_vec());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "current_thread")]
+    async fn large_output_is_coalesced_and_only_its_tail_kept() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let _cancel = spawn_shell_command(
+            "head -c 2097152 /dev/zero | tr '\\0' a; printf end".to_string(),
+            std::env::temp_dir(),
+            AppEventSender::new(tx_raw),
+        );
+
+        let mut events = 0;
+        let mut streamed = 0;
+        loop {
+            match rx.recv().await.expect("event") {
+                AppEvent::ShellCommandOutput(chunk) => {
+                    events += 1;
+                    streamed += chunk.len();
+                }
+                AppEvent::ShellCommandFinished { output, .. } => {
+                    assert_eq!(output.exit_code, 0);
+                    assert_eq!(output.stdout.len(), OUTPUT_MAX_BYTES);
+                    assert!(output.stdout.ends_with("aaaend"));
+                    break;
+                }
+                other => panic!("unexpected event: {other:?}"),
+            }
+        }
+        assert_eq!(streamed, 2 * 1024 * 1024 + "end".len());
+        // 128 full chunks, plus the few flushed early by the interval.
+        assert!(events < 160, "{events} output events");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "current_thread")]
+    async fn background_jobs_do_not_keep_the_command_running() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let _cancel = spawn_shell_command(
+            "sleep 30 & echo started".to_string(),
+            std::env::temp_dir(),
+            AppEventSender::new(tx_raw),
+        );
+
+        let finished = tokio::time::timeout(Duration::from_secs(10), async {
+            loop {
+                if let AppEvent::ShellCommandFinished { output, .. } =
+                    rx.recv().await.expect("event")
+                {
+                    return output;
+                }
+            }
+        })
+        .await
+        .expect("command finishes while its background job still runs");
+        assert_eq!(finished.exit_code, 0);
+        assert_eq!(finished.stdout, "started\n");
+    }
+
+    #[cfg(unix)]
+    #[tokio::test(flavor = "current_thread")]
+    async fn cancelling_kills_the_command() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let cancel = spawn_shell_command(
+            "sleep 30".to_string(),
+            std::env::temp_dir(),
+            AppEventSender::new(tx_raw),
+        );
+        let _ = cancel.send(());
+        match rx.recv().await.expect("event") {
+            AppEvent::ShellCommandFinished { output, .. } => {
+                assert_eq!(output.exit_code, 128 + libc::SIGKILL);
+            }
+            other => panic!("unexpected event: {other:?}"),
+        }
+    }
+}
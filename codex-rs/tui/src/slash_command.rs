@@ -14,12 +14,19 @@ pub enum SlashCommand {
     // more frequently used commands should be listed first.
     Model,
     Approvals,
+    Profile,
     New,
     Init,
     Compact,
+    Map,
     Diff,
+    Export,
+    Tasks,
     Mention,
     Status,
+    Statusbar,
+    Split,
+    Usage,
     Mcp,
     Logout,
     Quit,
@@ -34,12 +41,23 @@ impl SlashCommand {
             SlashCommand::New => "start a new chat during a conversation",
             SlashCommand::Init => "create an AGENTS.md file with instructions for Codex",
             SlashCommand::Compact => "summarize conversation to prevent hitting the context limit",
+            SlashCommand::Map => "regenerate the repository map given to the model",
             SlashCommand::Quit => "exit Codex",
             SlashCommand::Diff => "show git diff (including untracked files)",
+            SlashCommand::Export => {
+                "save the conversation as Markdown or HTML (/export [md|html] [path])"
+            }
+            SlashCommand::Tasks => "list background tasks (/tasks run <command>, /tasks kill <id>)",
             SlashCommand::Mention => "mention a file",
             SlashCommand::Status => "show current session configuration and token usage",
+            SlashCommand::Statusbar => "show or hide the status bar under the composer",
+            SlashCommand::Split => "show tool activity in a working pane above the composer",
+            SlashCommand::Usage => {
+                "show tokens and cost per turn and what they were spent on (/usage detail)"
+            }
             SlashCommand::Model => "choose what model and reasoning effort to use",
             SlashCommand::Approvals => "choose what Codex can do without approval",
+            SlashCommand::Profile => "switch to a profile from config.toml (/profile [name])",
             SlashCommand::Mcp => "list configured MCP tools",
             SlashCommand::Logout => "log out of Codex",
             #[cfg(debug_assertions)]
@@ -58,3 +76,105 @@ impl SlashCommand {
 pub fn built_in_slash_commands() -> Vec<(&'static str, SlashCommand)> {
     SlashCommand::iter().map(|c| (c.command(), c)).collect()
 }
+
+/// Splits `/name args` into the command name and its trimmed arguments, or
+/// returns `None` when `text` does not start with a slash.
+pub(crate) fn parse_slash_command(text: &str) -> Option<(&str, &str)> {
+    let rest = text.trim().strip_prefix('/')?;
+    let (name, args) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+    (!name.is_empty()).then(|| (name, args.trim()))
+}
+
+/// Expands the prompt template of a custom slash command with `args`, the
+/// text typed after the command. See
+/// [`codex_core::config_types::CustomSlashCommand::prompt`].
+pub(crate) fn expand_prompt_template(template: &str, args: &str) -> Result<String, String> {
+    let args = args.trim();
+    let mut words: Option<Vec<String>> = None;
+    let mut used_args = false;
+    let mut out = String::with_capacity(template.len() + args.len());
+    let mut rest = template;
+    while let Some(idx) = rest.find('$') {
+        out.push_str(&rest[..idx]);
+        let after = &rest[idx + 1..];
+        if let Some(tail) = after.strip_prefix("ARGUMENTS") {
+            out.push_str(args);
+            used_args = true;
+            rest = tail;
+        } else if let Some(n) = after
+            .chars()
+            .next()
+            .and_then(|c| c.to_digit(10))
+            .filter(|n| *n > 0)
+        {
+            if words.is_none() {
+                words = Some(
+                    shlex::split(args)
+                        .ok_or_else(|| "unbalanced quotes in arguments".to_string())?,
+                );
+            }
+            if let Some(word) = words.as_ref().and_then(|w| w.get(n as usize - 1)) {
+                out.push_str(word);
+            }
+            used_args = true;
+            rest = &after[1..];
+        } else {
+            out.push('$');
+            rest = after;
+        }
+    }
+    out.push_str(rest);
+    if !used_args && !args.is_empty() {
+        out.push_str("\n\n");
+        out.push_str(args);
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parses_command_name_and_arguments() {
+        assert_eq!(
+            parse_slash_command(" /profile  fast "),
+            Some(("profile", "fast"))
+        );
+        assert_eq!(parse_slash_command("/compact"), Some(("compact", "")));
+        assert_eq!(parse_slash_command("explain this repo"), None);
+        assert_eq!(parse_slash_command("/"), None);
+    }
+
+    #[test]
+    fn expands_whole_and_positional_arguments() {
+        assert_eq!(
+            expand_prompt_template(
+                "Write release notes from $1 to $2 for $ARGUMENTS ($3).",
+                "v1.0 'v1.1 rc' "
+            ),
+            Ok("Write release notes from v1.0 to v1.1 rc for v1.0 'v1.1 rc' ().".to_string())
+        );
+        assert_eq!(
+            expand_prompt_template("Fix $1$2, keep $0 and $HOME", "a"),
+            Ok("Fix a, keep $0 and $HOME".to_string())
+        );
+        assert_eq!(
+            expand_prompt_template("Review $1", "'unterminated"),
+            Err("unbalanced quotes in arguments".to_string())
+        );
+    }
+
+    #[test]
+    fn appends_arguments_when_the_template_has_no_placeholder() {
+        assert_eq!(
+            expand_prompt_template("Do a security review.", " focus on auth "),
+            Ok("Do a security review.\n\nfocus on auth".to_string())
+        );
+        assert_eq!(
+            expand_prompt_template("Do a security review.", ""),
+            Ok("Do a security review.".to_string())
+        );
+    }
+}
@@ -24,6 +24,9 @@ pub(crate) struct StatusIndicatorWidget {
     header: String,
     /// Queued user messages to display under the status line.
     queued_messages: Vec<String>,
+    /// Slot among `queued_messages` of a message being edited in the
+    /// composer, shown as a marker in its place.
+    editing_slot: Option<usize>,
 
     start_time: Instant,
     app_event_tx: AppEventSender,
@@ -35,6 +38,7 @@ impl StatusIndicatorWidget {
         Self {
             header: String::from("Working"),
             queued_messages: Vec::new(),
+            editing_slot: None,
             start_time: Instant::now(),
 
             app_event_tx,
@@ -60,12 +64,18 @@ impl StatusIndicatorWidget {
                     total = total.saturating_add(1); // ellipsis line
                 }
             }
-            if !self.queued_messages.is_empty() {
+            if self.editing_slot.is_some() {
+                total = total.saturating_add(1); // editing marker line
+            }
+            if !self.queued_messages.is_empty() || self.editing_slot.is_some() {
                 total = total.saturating_add(1); // keybind hint line
             }
         } else {
             // At least one line per message if width is extremely narrow
             total = total.saturating_add(self.queued_messages.len() as u16);
+            if self.editing_slot.is_some() {
+                total = total.saturating_add(1);
+            }
         }
         total.saturating_add(1) // spacer line
     }
@@ -81,9 +91,11 @@ impl StatusIndicatorWidget {
         }
     }
 
-    /// Replace the queued messages displayed beneath the header.
-    pub(crate) fn set_queued_messages(&mut self, queued: Vec<String>) {
+    /// Replace the queued messages displayed beneath the header, marking
+    /// `editing_slot` as the place of the message being edited.
+    pub(crate) fn set_queued_messages(&mut self, queued: Vec<String>, editing_slot: Option<usize>) {
         self.queued_messages = queued;
+        self.editing_slot = editing_slot;
         // Ensure a redraw so changes are visible.
         self.frame_requester.schedule_frame();
     }
@@ -118,7 +130,11 @@ impl WidgetRef for StatusIndicatorWidget {
         let opts = TwOptions::new(text_width as usize)
             .break_words(false)
             .word_splitter(WordSplitter::NoHyphenation);
-        for q in &self.queued_messages {
+        let editing_marker = || Line::from(" ✎ editing in composer…".cyan().italic());
+        for (slot, q) in self.queued_messages.iter().enumerate() {
+            if self.editing_slot == Some(slot) {
+                lines.push(editing_marker());
+            }
             let wrapped = textwrap::wrap(q, &opts);
             for (i, piece) in wrapped.iter().take(3).enumerate() {
                 let prefix = if i == 0 { " ↳ " } else { "   " };
@@ -129,7 +145,24 @@ impl WidgetRef for StatusIndicatorWidget {
                 lines.push(Line::from("   …".dim().italic()));
             }
         }
-        if !self.queued_messages.is_empty() {
+        if self
+            .editing_slot
+            .is_some_and(|slot| slot >= self.queued_messages.len())
+        {
+            lines.push(editing_marker());
+        }
+        if self.editing_slot.is_some() {
+            lines.push(
+                Line::from(vec![
+                    "   ".into(),
+                    "Alt+↑/↓".cyan(),
+                    " edit another · ".into(),
+                    "Enter".cyan(),
+                    " requeue · clear the composer to remove".into(),
+                ])
+                .dim(),
+            );
+        } else if !self.queued_messages.is_empty() {
             lines.push(Line::from(vec!["   ".into(), "Alt+↑".cyan(), " edit".into()]).dim());
         }
 
@@ -181,7 +214,7 @@ mod tests {
         let (tx_raw, _rx) = unbounded_channel::<AppEvent>();
         let tx = AppEventSender::new(tx_raw);
         let mut w = StatusIndicatorWidget::new(tx, crate::tui::FrameRequester::test_dummy());
-        w.set_queued_messages(vec!["first".to_string(), "second".to_string()]);
+        w.set_queued_messages(vec!["first".to_string(), "second".to_string()], None);
 
         // Render into a fixed-size test terminal and snapshot the backend.
         let mut terminal = Terminal::new(TestBackend::new(80, 8)).expect("terminal");
@@ -190,4 +223,19 @@ mod tests {
             .expect("draw");
         assert_snapshot!(terminal.backend());
     }
+
+    #[test]
+    fn renders_queued_message_being_edited() {
+        let (tx_raw, _rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx_raw);
+        let mut w = StatusIndicatorWidget::new(tx, crate::tui::FrameRequester::test_dummy());
+        w.set_queued_messages(vec!["first".to_string(), "third".to_string()], Some(1));
+        assert_eq!(w.desired_height(80), 6);
+
+        let mut terminal = Terminal::new(TestBackend::new(80, 6)).expect("terminal");
+        terminal
+            .draw(|f| w.render_ref(f.area(), f.buffer_mut()))
+            .expect("draw");
+        assert_snapshot!(terminal.backend());
+    }
 }
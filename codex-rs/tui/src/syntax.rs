@@ -0,0 +1,207 @@
+//! Syntax highlighting for code blocks and patch previews, backed by
+//! `syntect`'s bundled syntaxes and themes.
+//!
+//! Highlighting is best-effort: an unknown language, a theme set to `none`
+//! or a line the highlighter fails on all render as plain text.
+
+use std::path::Path;
+use std::sync::LazyLock;
+
+use ratatui::style::Color;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::text::Span;
+use syntect::easy::HighlightLines;
+use syntect::highlighting::FontStyle;
+use syntect::highlighting::Theme;
+use syntect::highlighting::ThemeSet;
+use syntect::parsing::SyntaxReference;
+use syntect::parsing::SyntaxSet;
+
+/// Theme used when `tui.syntax_theme` is not set or names an unknown theme.
+pub(crate) const DEFAULT_SYNTAX_THEME: &str = "base16-ocean.dark";
+
+/// Setting for `tui.syntax_theme` that turns highlighting off.
+const NO_THEME: &str = "none";
+
+static SYNTAXES: LazyLock<SyntaxSet> = LazyLock::new(SyntaxSet::load_defaults_newlines);
+static THEMES: LazyLock<ThemeSet> = LazyLock::new(ThemeSet::load_defaults);
+
+/// Fence languages that name a syntax under a different token.
+const LANGUAGE_ALIASES: &[(&str, &str)] = &[
+    ("console", "bash"),
+    ("shell", "bash"),
+    ("zsh", "bash"),
+    ("ts", "js"),
+    ("tsx", "js"),
+    ("typescript", "js"),
+    ("jsx", "js"),
+];
+
+/// Highlights consecutive lines of one piece of code. Keeps the parser state
+/// between lines, so lines must be fed in order.
+pub(crate) struct Highlighter {
+    lines: HighlightLines<'static>,
+}
+
+impl Highlighter {
+    /// A highlighter for a fenced code block tagged with `language`.
+    pub(crate) fn for_language(language: &str, theme: &str) -> Option<Self> {
+        let token = language.split([',', ' ', '{']).next().unwrap_or_default();
+        let token = token.to_ascii_lowercase();
+        let token = LANGUAGE_ALIASES
+            .iter()
+            .find(|(alias, _)| *alias == token)
+            .map_or(token.as_str(), |(_, target)| target);
+        Self::new(SYNTAXES.find_syntax_by_token(token)?, theme)
+    }
+
+    /// A highlighter for the contents of the file at `path`, chosen by its
+    /// extension or file name.
+    pub(crate) fn for_path(path: &Path, theme: &str) -> Option<Self> {
+        let syntax = path
+            .extension()
+            .and_then(|ext| SYNTAXES.find_syntax_by_extension(&ext.to_string_lossy()))
+            .or_else(|| {
+                let name = path.file_name()?.to_string_lossy();
+                SYNTAXES.find_syntax_by_extension(&name)
+            })?;
+        Self::new(syntax, theme)
+    }
+
+    fn new(syntax: &'static SyntaxReference, theme: &str) -> Option<Self> {
+        if syntax.name == SYNTAXES.find_syntax_plain_text().name {
+            return None;
+        }
+        Some(Self {
+            lines: HighlightLines::new(syntax, resolve_theme(theme)?),
+        })
+    }
+
+    /// Highlights `line`, which must not contain a newline.
+    pub(crate) fn highlight(&mut self, line: &str) -> Vec<Span<'static>> {
+        // The bundled syntaxes expect each line to end with a newline.
+        let with_newline = format!("{line}\n");
+        match self.lines.highlight_line(&with_newline, &SYNTAXES) {
+            Ok(regions) => regions
+                .into_iter()
+                .filter_map(|(style, text)| {
+                    let text = text.strip_suffix('\n').unwrap_or(text);
+                    (!text.is_empty()).then(|| Span::styled(text.to_string(), to_style(style)))
+                })
+                .collect(),
+            Err(_) => vec![Span::raw(line.to_string())],
+        }
+    }
+}
+
+fn resolve_theme(name: &str) -> Option<&'static Theme> {
+    if name.eq_ignore_ascii_case(NO_THEME) {
+        return None;
+    }
+    THEMES
+        .themes
+        .get(name)
+        .or_else(|| THEMES.themes.get(DEFAULT_SYNTAX_THEME))
+}
+
+/// Converts a syntect style to a ratatui one. Only the foreground and font
+/// style are kept so code blends into the terminal's own background, and the
+/// foreground is mapped onto the terminal's ANSI palette so it follows the
+/// user's color scheme.
+fn to_style(style: syntect::highlighting::Style) -> Style {
+    let mut out = ansi_style(style.foreground);
+    if style.font_style.contains(FontStyle::BOLD) {
+        out = out.add_modifier(Modifier::BOLD);
+    }
+    if style.font_style.contains(FontStyle::ITALIC) {
+        out = out.add_modifier(Modifier::ITALIC);
+    }
+    if style.font_style.contains(FontStyle::UNDERLINE) {
+        out = out.add_modifier(Modifier::UNDERLINED);
+    }
+    out
+}
+
+/// The ANSI color closest in hue to `color`. Grays keep the default
+/// foreground, except mid-tone ones (typically comments), which are dimmed.
+fn ansi_style(color: syntect::highlighting::Color) -> Style {
+    let [r, g, b] = [color.r, color.g, color.b].map(|c| f32::from(c) / 255.0);
+    let max = r.max(g).max(b);
+    let min = r.min(g).min(b);
+    let lightness = (max + min) / 2.0;
+    let chroma = max - min;
+    if chroma < 0.11 {
+        return if (0.3..0.7).contains(&lightness) {
+            Style::default().add_modifier(Modifier::DIM)
+        } else {
+            Style::default()
+        };
+    }
+    let hue = if max == r {
+        60.0 * ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / chroma + 2.0)
+    } else {
+        60.0 * ((r - g) / chroma + 4.0)
+    };
+    let color = match hue {
+        h if !(10.0..345.0).contains(&h) => Color::Red,
+        h if h < 70.0 => Color::Yellow,
+        h if h < 160.0 => Color::Green,
+        h if h < 200.0 => Color::Cyan,
+        h if h < 260.0 => Color::Blue,
+        _ => Color::Magenta,
+    };
+    Style::default().fg(color)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn text(spans: &[Span<'static>]) -> String {
+        spans.iter().map(|s| s.content.as_ref()).collect()
+    }
+
+    #[test]
+    fn highlights_known_languages_and_keeps_text() {
+        let mut highlighter =
+            Highlighter::for_language("rust", DEFAULT_SYNTAX_THEME).expect("rust syntax");
+        let spans = highlighter.highlight("fn main() { let x = 1; }");
+        assert_eq!(text(&spans), "fn main() { let x = 1; }");
+        assert!(spans.len() > 1, "expected several styled spans: {spans:?}");
+        assert!(spans.iter().any(|s| s.style.fg.is_some()));
+
+        assert!(Highlighter::for_language("ts", DEFAULT_SYNTAX_THEME).is_some());
+        assert!(Highlighter::for_language("py title=\"x\"", DEFAULT_SYNTAX_THEME).is_some());
+        assert!(Highlighter::for_path(Path::new("src/lib.rs"), DEFAULT_SYNTAX_THEME).is_some());
+    }
+
+    #[test]
+    fn maps_colors_onto_the_ansi_palette() {
+        let rgb = |r, g, b| syntect::highlighting::Color { r, g, b, a: 0xff };
+        assert_eq!(ansi_style(rgb(0xbf, 0x61, 0x6a)).fg, Some(Color::Red));
+        assert_eq!(ansi_style(rgb(0xd0, 0x87, 0x70)).fg, Some(Color::Yellow));
+        assert_eq!(ansi_style(rgb(0xa3, 0xbe, 0x8c)).fg, Some(Color::Green));
+        assert_eq!(ansi_style(rgb(0x96, 0xb5, 0xb4)).fg, Some(Color::Cyan));
+        assert_eq!(ansi_style(rgb(0x8f, 0xa1, 0xb3)).fg, Some(Color::Blue));
+        assert_eq!(ansi_style(rgb(0xb4, 0x8e, 0xad)).fg, Some(Color::Magenta));
+        assert_eq!(ansi_style(rgb(0xc0, 0xc5, 0xce)), Style::default());
+        assert_eq!(
+            ansi_style(rgb(0x65, 0x73, 0x7e)),
+            Style::default().add_modifier(Modifier::DIM)
+        );
+    }
+
+    #[test]
+    fn falls_back_for_unknown_languages_and_disabled_theme() {
+        assert!(Highlighter::for_language("no-such-language", DEFAULT_SYNTAX_THEME).is_none());
+        assert!(
+            Highlighter::for_path(Path::new("notes.unknownext"), DEFAULT_SYNTAX_THEME).is_none()
+        );
+        assert!(Highlighter::for_language("rust", "none").is_none());
+        assert!(Highlighter::for_language("rust", "no-such-theme").is_some());
+    }
+}
@@ -0,0 +1,121 @@
+//! Inline images in the history, drawn with the kitty, iTerm2 or sixel
+//! graphics protocol when the terminal supports one.
+//!
+//! Support is detected once at startup by querying the terminal. Without a
+//! graphics protocol, image cells only show their caption.
+
+use std::sync::OnceLock;
+
+use image::DynamicImage;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::widgets::Widget;
+use ratatui_image::FilterType;
+use ratatui_image::Image;
+use ratatui_image::Resize;
+use ratatui_image::picker::Picker;
+use ratatui_image::picker::ProtocolType;
+
+/// Tallest an inline image may be, in rows, so a screenshot does not push
+/// the whole conversation out of view.
+pub(crate) const MAX_IMAGE_ROWS: u16 = 20;
+
+static PICKER: OnceLock<Option<Picker>> = OnceLock::new();
+
+/// Queries the terminal for a graphics protocol and its cell size. Must run
+/// after raw mode is enabled and before anything else reads terminal input.
+pub(crate) fn detect() {
+    PICKER.get_or_init(|| match Picker::from_query_stdio() {
+        // Half blocks would approximate the image with colored text; the
+        // caption is more useful than a blurry thumbnail.
+        Ok(picker) if picker.protocol_type() != ProtocolType::Halfblocks => Some(picker),
+        Ok(_) => None,
+        Err(e) => {
+            tracing::debug!("terminal graphics detection failed: {e}");
+            None
+        }
+    });
+}
+
+/// Whether images can be drawn inline.
+pub(crate) fn is_supported() -> bool {
+    PICKER.get().is_some_and(Option::is_some)
+}
+
+/// Encodes `image`, scaled down to fit `max_width` × `max_height` cells, as
+/// a buffer of cells carrying the graphics protocol's escape sequences. The
+/// buffer is as large as the image ended up; cells marked as skipped must
+/// not be written.
+pub(crate) fn render(image: &DynamicImage, max_width: u16, max_height: u16) -> Option<Buffer> {
+    let picker = PICKER.get()?.as_ref()?;
+    render_with(picker, image, max_width, max_height)
+}
+
+fn render_with(
+    picker: &Picker,
+    image: &DynamicImage,
+    max_width: u16,
+    max_height: u16,
+) -> Option<Buffer> {
+    if max_width == 0 || max_height == 0 {
+        return None;
+    }
+    let protocol = picker
+        .new_protocol(
+            image.clone(),
+            Rect::new(0, 0, max_width, max_height),
+            Resize::Fit(Some(FilterType::Triangle)),
+        )
+        .inspect_err(|e| tracing::warn!("failed to encode inline image: {e}"))
+        .ok()?;
+    let area = protocol.area();
+    if area.is_empty() {
+        return None;
+    }
+    let mut buf = Buffer::empty(area);
+    Image::new(&protocol).render(area, &mut buf);
+    Some(buf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+    use image::RgbaImage;
+    use pretty_assertions::assert_eq;
+
+    fn picker(protocol: ProtocolType) -> Picker {
+        let mut picker = Picker::from_fontsize((10, 20));
+        picker.set_protocol_type(protocol);
+        picker
+    }
+
+    fn screenshot() -> DynamicImage {
+        RgbaImage::from_pixel(400, 200, Rgba([200, 30, 30, 255])).into()
+    }
+
+    #[test]
+    fn scales_images_down_to_the_available_cells() {
+        let buf = render_with(&picker(ProtocolType::Sixel), &screenshot(), 20, 5).expect("sixel");
+        assert_eq!(buf.area, Rect::new(0, 0, 20, 5));
+        assert!(buf[(0, 0)].symbol().starts_with("\x1bP"));
+        assert!(buf[(1, 0)].skip);
+
+        // Small images keep their size: 400x200 px is 40x10 cells.
+        let buf = render_with(&picker(ProtocolType::Iterm2), &screenshot(), 80, 20).expect("iterm");
+        assert_eq!(buf.area, Rect::new(0, 0, 40, 10));
+        assert!(buf[(0, 0)].symbol().contains("\x1b]1337;File=inline=1"));
+
+        assert!(render_with(&picker(ProtocolType::Kitty), &screenshot(), 80, 0).is_none());
+    }
+
+    #[test]
+    fn kitty_images_are_written_row_by_row() {
+        let buf = render_with(&picker(ProtocolType::Kitty), &screenshot(), 20, 5).expect("kitty");
+        assert!(buf[(0, 0)].symbol().contains("\x1b_G"));
+        for y in 0..buf.area.height {
+            assert!(buf[(0, y)].symbol().contains('\u{10EEEE}'));
+            assert!(buf[(1, y)].skip);
+        }
+    }
+}
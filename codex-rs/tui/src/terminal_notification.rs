@@ -0,0 +1,137 @@
+//! Bell, OSC 9 and title notifications for events that happen while the
+//! terminal window is unfocused (`[notifications]` `terminal` and `title`).
+//!
+//! Focus is tracked from the terminal's focus events. Terminals that never
+//! report focus are treated as focused, so they get no notifications.
+
+use std::io::Write;
+use std::io::stdout;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicU8;
+use std::sync::atomic::Ordering;
+
+use codex_core::config_types::NotificationEvent;
+use codex_core::config_types::Notifications;
+use codex_core::config_types::TerminalNotification;
+
+const FOCUS_UNKNOWN: u8 = 0;
+const FOCUS_GAINED: u8 = 1;
+const FOCUS_LOST: u8 = 2;
+
+static FOCUS: AtomicU8 = AtomicU8::new(FOCUS_UNKNOWN);
+
+/// Whether the title we found was saved on the terminal's title stack, to be
+/// restored when focus comes back.
+static TITLE_SAVED: AtomicBool = AtomicBool::new(false);
+
+/// Saves the window title on the terminal's stack (XTWINOPS 22).
+const PUSH_TITLE: &str = "\x1b[22;0t";
+/// Restores the title saved by `PUSH_TITLE` (XTWINOPS 23).
+const POP_TITLE: &str = "\x1b[23;0t";
+
+/// Records a focus event from the terminal. Regaining focus restores the
+/// title a notification replaced.
+pub(crate) fn set_focused(focused: bool) {
+    FOCUS.store(
+        if focused { FOCUS_GAINED } else { FOCUS_LOST },
+        Ordering::Relaxed,
+    );
+    if focused {
+        restore_title();
+    }
+}
+
+/// Puts back the title a notification replaced, if any.
+pub(crate) fn restore_title() {
+    if TITLE_SAVED.swap(false, Ordering::Relaxed) {
+        write(POP_TITLE);
+    }
+}
+
+/// Notifies the user of `event` if the terminal is unfocused and the
+/// settings ask for it.
+pub(crate) fn notify(settings: &Notifications, event: NotificationEvent) {
+    if FOCUS.load(Ordering::Relaxed) != FOCUS_LOST || !settings.events.contains(&event) {
+        return;
+    }
+    let save_title = settings.title && !TITLE_SAVED.swap(true, Ordering::Relaxed);
+    let sequence = escape_sequence(settings, save_title, message(event));
+    if !sequence.is_empty() {
+        write(&sequence);
+    }
+}
+
+fn message(event: NotificationEvent) -> &'static str {
+    match event {
+        NotificationEvent::ApprovalRequested => "Codex needs your approval",
+        NotificationEvent::AgentTurnComplete => "Codex finished its turn",
+        NotificationEvent::Error => "Codex stopped with an error",
+    }
+}
+
+/// The bytes that notify of `message`: the title (saving the current one
+/// first when `save_title`), then the bell or OSC 9 notification.
+fn escape_sequence(settings: &Notifications, save_title: bool, message: &str) -> String {
+    let mut sequence = String::new();
+    if settings.title {
+        if save_title {
+            sequence.push_str(PUSH_TITLE);
+        }
+        sequence.push_str(&format!("\x1b]0;{message}\x07"));
+    }
+    match settings.terminal {
+        TerminalNotification::Off => {}
+        TerminalNotification::Bell => sequence.push('\x07'),
+        TerminalNotification::Osc9 => sequence.push_str(&format!("\x1b]9;{message}\x07")),
+    }
+    sequence
+}
+
+fn write(sequence: &str) {
+    let mut out = stdout();
+    if let Err(e) = out
+        .write_all(sequence.as_bytes())
+        .and_then(|()| out.flush())
+    {
+        tracing::debug!("failed to write terminal notification: {e}");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn settings(terminal: TerminalNotification, title: bool) -> Notifications {
+        Notifications {
+            terminal,
+            title,
+            ..Notifications::default()
+        }
+    }
+
+    #[test]
+    fn builds_title_and_alert_sequences() {
+        assert_eq!(
+            escape_sequence(
+                &settings(TerminalNotification::Bell, true),
+                true,
+                "Codex finished its turn"
+            ),
+            "\x1b[22;0t\x1b]0;Codex finished its turn\x07\x07"
+        );
+        // The title saved by the first notification is kept for later ones.
+        assert_eq!(
+            escape_sequence(
+                &settings(TerminalNotification::Osc9, true),
+                false,
+                "Codex needs your approval"
+            ),
+            "\x1b]0;Codex needs your approval\x07\x1b]9;Codex needs your approval\x07"
+        );
+        assert_eq!(
+            escape_sequence(&settings(TerminalNotification::Off, false), true, "x"),
+            ""
+        );
+    }
+}
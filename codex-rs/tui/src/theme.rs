@@ -0,0 +1,383 @@
+//! Color themes.
+//!
+//! Widgets keep drawing with the palette from `styles.md`: cyan for accents,
+//! green for success, red for errors, magenta for Codex and `dim` for
+//! secondary text. The active theme maps those palette slots to the colors
+//! it wants just before they reach the terminal, in both the viewport and
+//! the lines inserted into the scrollback, so no widget has to know which
+//! theme is active.
+
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+use codex_core::config_types::ThemePalette;
+use codex_core::config_types::Tui;
+use ratatui::buffer::Buffer;
+use ratatui::style::Color;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+
+static THEME: OnceLock<Theme> = OnceLock::new();
+
+/// How secondary (`dim`) text is drawn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Secondary {
+    Dim,
+    /// The normal foreground, for terminals where dim text is hard to read.
+    Plain,
+    Color(Color),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Theme {
+    accent: Color,
+    success: Color,
+    error: Color,
+    codex: Color,
+    secondary: Secondary,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Background {
+    Dark,
+    Light,
+}
+
+impl Theme {
+    /// The style guide's palette as is.
+    const DARK: Theme = Theme {
+        accent: Color::Cyan,
+        success: Color::Green,
+        error: Color::Red,
+        codex: Color::Magenta,
+        secondary: Secondary::Dim,
+    };
+
+    /// ANSI cyan is barely readable on most light backgrounds.
+    const LIGHT: Theme = Theme {
+        accent: Color::Blue,
+        ..Theme::DARK
+    };
+
+    const HIGH_CONTRAST: Theme = Theme {
+        accent: Color::LightCyan,
+        success: Color::LightGreen,
+        error: Color::LightRed,
+        codex: Color::LightMagenta,
+        secondary: Secondary::Plain,
+    };
+
+    /// The built-in theme called `name`; `auto`, and any name that is not a
+    /// built-in theme, picks one for the terminal's `background`.
+    fn built_in(name: &str, background: impl FnOnce() -> Background) -> Theme {
+        match name {
+            "dark" => Theme::DARK,
+            "light" => Theme::LIGHT,
+            "high-contrast" => Theme::HIGH_CONTRAST,
+            other => {
+                if other != "auto" {
+                    tracing::warn!("unknown theme `{other}`, using auto");
+                }
+                match background() {
+                    Background::Dark => Theme::DARK,
+                    Background::Light => Theme::LIGHT,
+                }
+            }
+        }
+    }
+
+    /// The theme selected by `tui.theme`. `background` is only consulted
+    /// when the theme depends on it.
+    pub(crate) fn from_config(tui: &Tui, background: impl FnOnce() -> Background) -> Theme {
+        match tui.themes.get(&tui.theme) {
+            Some(palette) => {
+                let base = palette.base.as_deref().unwrap_or("auto");
+                Theme::built_in(base, background).with_palette(palette)
+            }
+            None => Theme::built_in(&tui.theme, background),
+        }
+    }
+
+    fn with_palette(mut self, palette: &ThemePalette) -> Theme {
+        for (slot, value) in [
+            (&mut self.accent, &palette.accent),
+            (&mut self.success, &palette.success),
+            (&mut self.error, &palette.error),
+            (&mut self.codex, &palette.codex),
+        ] {
+            if let Some(color) = value.as_deref().and_then(parse_color) {
+                *slot = color;
+            }
+        }
+        self.secondary = match palette.secondary.as_deref() {
+            None => self.secondary,
+            Some("dim") => Secondary::Dim,
+            Some("default") => Secondary::Plain,
+            Some(value) => parse_color(value).map_or(self.secondary, Secondary::Color),
+        };
+        self
+    }
+
+    /// Redraws `style` in this theme's colors.
+    pub(crate) fn apply(&self, mut style: Style) -> Style {
+        style.fg = style.fg.map(|color| self.map_color(color));
+        style.bg = style.bg.map(|color| self.map_color(color));
+        if style.add_modifier.contains(Modifier::DIM) {
+            match self.secondary {
+                Secondary::Dim => {}
+                Secondary::Plain => style.add_modifier.remove(Modifier::DIM),
+                Secondary::Color(color) => {
+                    style.add_modifier.remove(Modifier::DIM);
+                    if matches!(style.fg, None | Some(Color::Reset)) {
+                        style.fg = Some(color);
+                    }
+                }
+            }
+        }
+        style
+    }
+
+    fn map_color(&self, color: Color) -> Color {
+        match color {
+            Color::Cyan => self.accent,
+            Color::Green => self.success,
+            Color::Red => self.error,
+            Color::Magenta => self.codex,
+            other => other,
+        }
+    }
+}
+
+fn parse_color(value: &str) -> Option<Color> {
+    Color::from_str(value)
+        .inspect_err(|_| tracing::warn!("invalid theme color `{value}`"))
+        .ok()
+}
+
+/// Picks the theme from `tui` and makes it the one every frame is drawn
+/// with. Like [`crate::terminal_image::detect`], must run after raw mode is
+/// enabled and before the event stream starts reading terminal input.
+pub(crate) fn init(tui: &Tui) {
+    THEME.get_or_init(|| Theme::from_config(tui, detect_background));
+}
+
+/// `style` in the active theme's colors.
+pub(crate) fn apply(style: Style) -> Style {
+    match THEME.get() {
+        Some(theme) if *theme != Theme::DARK => theme.apply(style),
+        _ => style,
+    }
+}
+
+/// Redraws every cell of `buf` in the active theme's colors.
+pub(crate) fn apply_to_buffer(buf: &mut Buffer) {
+    let Some(theme) = THEME.get().filter(|theme| **theme != Theme::DARK) else {
+        return;
+    };
+    for cell in &mut buf.content {
+        let style = theme.apply(cell.style());
+        cell.fg = style.fg.unwrap_or(cell.fg);
+        cell.bg = style.bg.unwrap_or(cell.bg);
+        cell.modifier = style.add_modifier;
+    }
+}
+
+fn detect_background() -> Background {
+    std::env::var("COLORFGBG")
+        .ok()
+        .and_then(|value| background_from_colorfgbg(&value))
+        .or_else(query_background)
+        .unwrap_or(Background::Dark)
+}
+
+/// `COLORFGBG` is `fg;bg` (or `fg;default;bg`) in ANSI color indices; only
+/// white and the bright colors other than dark gray are light.
+fn background_from_colorfgbg(value: &str) -> Option<Background> {
+    let bg: u8 = value.rsplit(';').next()?.parse().ok()?;
+    Some(match bg {
+        7 | 9..=15 => Background::Light,
+        _ => Background::Dark,
+    })
+}
+
+/// Asks the terminal for its background color (OSC 11). A device
+/// attributes request follows it, which every terminal answers, so
+/// terminals that ignore OSC 11 do not cost the whole timeout.
+#[cfg(unix)]
+fn query_background() -> Option<Background> {
+    use std::io::Write;
+    use std::os::fd::AsRawFd;
+    use std::time::Duration;
+    use std::time::Instant;
+
+    const TIMEOUT: Duration = Duration::from_millis(200);
+
+    let mut stdout = std::io::stdout();
+    stdout.write_all(b"\x1b]11;?\x1b\\\x1b[c").ok()?;
+    stdout.flush().ok()?;
+
+    let fd = std::io::stdin().as_raw_fd();
+    let deadline = Instant::now() + TIMEOUT;
+    let mut reply = Vec::new();
+    while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+        let mut pollfd = libc::pollfd {
+            fd,
+            events: libc::POLLIN,
+            revents: 0,
+        };
+        let ready = unsafe { libc::poll(&mut pollfd, 1, remaining.as_millis() as i32) };
+        if ready <= 0 {
+            break;
+        }
+        let mut chunk = [0u8; 256];
+        let read = unsafe { libc::read(fd, chunk.as_mut_ptr().cast(), chunk.len()) };
+        if read <= 0 {
+            break;
+        }
+        reply.extend_from_slice(&chunk[..read as usize]);
+        if ends_with_device_attributes(&reply) {
+            break;
+        }
+    }
+    background_from_osc11_reply(&String::from_utf8_lossy(&reply))
+}
+
+#[cfg(not(unix))]
+fn query_background() -> Option<Background> {
+    None
+}
+
+/// Whether `reply` ends with a device attributes report, `ESC [ ? … c`.
+#[cfg(unix)]
+fn ends_with_device_attributes(reply: &[u8]) -> bool {
+    reply.ends_with(b"c")
+        && reply
+            .windows(3)
+            .rposition(|w| w == b"\x1b[?")
+            .is_some_and(|start| {
+                reply[start + 3..reply.len() - 1]
+                    .iter()
+                    .all(|b| b.is_ascii_digit() || *b == b';')
+            })
+}
+
+/// Parses the `rgb:RRRR/GGGG/BBBB` color in an OSC 11 reply.
+fn background_from_osc11_reply(reply: &str) -> Option<Background> {
+    let rgb = &reply[reply.find("]11;rgb:")? + "]11;rgb:".len()..];
+    let mut channels = rgb.splitn(3, '/').map(|part| {
+        let digits: String = part.chars().take_while(char::is_ascii_hexdigit).collect();
+        let max = 16f32.powi(digits.len() as i32) - 1.0;
+        u32::from_str_radix(&digits, 16)
+            .ok()
+            .map(|value| value as f32 / max)
+    });
+    let (r, g, b) = (channels.next()??, channels.next()??, channels.next()??);
+    let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    Some(if luminance > 0.5 {
+        Background::Light
+    } else {
+        Background::Dark
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn tui(theme: &str) -> Tui {
+        Tui {
+            theme: theme.to_string(),
+            ..Tui::default()
+        }
+    }
+
+    #[test]
+    fn picks_built_in_themes_and_follows_the_background() {
+        let light = || Background::Light;
+        assert_eq!(Theme::from_config(&tui("auto"), light), Theme::LIGHT);
+        assert_eq!(
+            Theme::from_config(&tui("auto"), || Background::Dark),
+            Theme::DARK
+        );
+        assert_eq!(Theme::from_config(&tui("dark"), light), Theme::DARK);
+        assert_eq!(
+            Theme::from_config(&tui("high-contrast"), light),
+            Theme::HIGH_CONTRAST
+        );
+        assert_eq!(
+            Theme::from_config(&tui("no-such-theme"), light),
+            Theme::LIGHT
+        );
+    }
+
+    #[test]
+    fn custom_palettes_override_their_base() {
+        let mut config = tui("solarized");
+        config.themes.insert(
+            "solarized".to_string(),
+            ThemePalette {
+                base: Some("high-contrast".to_string()),
+                accent: Some("#268bd2".to_string()),
+                error: Some("not-a-color".to_string()),
+                secondary: Some("dark-gray".to_string()),
+                ..ThemePalette::default()
+            },
+        );
+        let theme = Theme::from_config(&config, || Background::Dark);
+        assert_eq!(
+            theme,
+            Theme {
+                accent: Color::from_str("#268bd2").expect("hex color"),
+                secondary: Secondary::Color(Color::DarkGray),
+                ..Theme::HIGH_CONTRAST
+            }
+        );
+    }
+
+    #[test]
+    fn maps_palette_slots_and_secondary_text() {
+        let theme = Theme::HIGH_CONTRAST;
+        assert_eq!(
+            theme.apply(Style::new().fg(Color::Cyan).bg(Color::Red)),
+            Style::new().fg(Color::LightCyan).bg(Color::LightRed)
+        );
+        assert_eq!(
+            theme.apply(Style::new().fg(Color::Gray).add_modifier(Modifier::DIM)),
+            Style::new().fg(Color::Gray)
+        );
+
+        let theme = Theme {
+            secondary: Secondary::Color(Color::DarkGray),
+            ..Theme::DARK
+        };
+        assert_eq!(
+            theme.apply(Style::new().add_modifier(Modifier::DIM | Modifier::BOLD)),
+            Style::new()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD)
+        );
+        let dim = Style::new().add_modifier(Modifier::DIM);
+        assert_eq!(Theme::DARK.apply(dim), dim);
+    }
+
+    #[test]
+    fn detects_the_background_from_the_terminal() {
+        assert_eq!(background_from_colorfgbg("15;0"), Some(Background::Dark));
+        assert_eq!(
+            background_from_colorfgbg("0;default;15"),
+            Some(Background::Light)
+        );
+        assert_eq!(background_from_colorfgbg("default"), None);
+
+        assert_eq!(
+            background_from_osc11_reply("\x1b]11;rgb:ffff/ffff/dddd\x1b\\\x1b[?62;22c"),
+            Some(Background::Light)
+        );
+        assert_eq!(
+            background_from_osc11_reply("\x1b]11;rgb:1e/1e/1e\x07"),
+            Some(Background::Dark)
+        );
+        assert_eq!(background_from_osc11_reply("\x1b[?1;2c"), None);
+    }
+}
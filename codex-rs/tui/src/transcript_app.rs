@@ -1,14 +1,25 @@
 use std::io::Result;
+use std::path::Path;
 
+use crate::file_citation::CitationLink;
+use crate::file_citation::FileCitation;
+use crate::file_citation::citation_at;
+use crate::file_citation::citation_links;
 use crate::insert_history;
+use crate::pager_search::PagerSearch;
+use crate::pager_search::SearchInput;
+use crate::pager_search::add_modifier_to_ranges;
 use crate::tui;
 use crate::tui::TuiEvent;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
+use crossterm::event::MouseEvent;
+use crossterm::event::MouseEventKind;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::style::Color;
+use ratatui::style::Modifier;
 use ratatui::style::Style;
 use ratatui::style::Styled;
 use ratatui::style::Stylize;
@@ -17,12 +28,28 @@ use ratatui::text::Span;
 use ratatui::widgets::Paragraph;
 use ratatui::widgets::WidgetRef;
 
+/// Lines scrolled per mouse wheel notch.
+pub(crate) const MOUSE_SCROLL_LINES: usize = 3;
+
 pub(crate) struct TranscriptApp {
     pub(crate) transcript_lines: Vec<Line<'static>>,
     pub(crate) scroll_offset: usize,
     pub(crate) is_done: bool,
     title: String,
     highlight_range: Option<(usize, usize)>,
+    search: PagerSearch,
+    /// One-line message shown in the footer, e.g. "pattern not found".
+    status: Option<String>,
+    /// File citation selected with Tab, opened with Enter.
+    selected_citation: Option<SelectedCitation>,
+}
+
+/// A citation selected in the transcript as wrapped at `width`.
+struct SelectedCitation {
+    /// Index of the wrapped line holding the citation.
+    line: usize,
+    width: u16,
+    link: CitationLink,
 }
 
 impl TranscriptApp {
@@ -33,6 +60,9 @@ impl TranscriptApp {
             is_done: false,
             title: "T R A N S C R I P T".to_string(),
             highlight_range: None,
+            search: PagerSearch::default(),
+            status: None,
+            selected_citation: None,
         }
     }
 
@@ -43,20 +73,34 @@ impl TranscriptApp {
             is_done: false,
             title,
             highlight_range: None,
+            search: PagerSearch::default(),
+            status: None,
+            selected_citation: None,
         }
     }
     pub(crate) fn insert_lines(&mut self, lines: Vec<Line<'static>>) {
         self.transcript_lines.extend(lines);
     }
 
+    /// Whether a search pattern is being typed, in which case every key,
+    /// including Esc and Enter, belongs to the search prompt.
+    pub(crate) fn is_searching(&self) -> bool {
+        self.search.is_editing()
+    }
+
     /// Highlight the specified range [start, end) of transcript lines.
     pub(crate) fn set_highlight_range(&mut self, range: Option<(usize, usize)>) {
         self.highlight_range = range;
+        // Enter edits the highlighted message now, so it no longer opens a file.
+        if range.is_some() {
+            self.selected_citation = None;
+        }
     }
 
     pub(crate) fn handle_event(&mut self, tui: &mut tui::Tui, event: TuiEvent) -> Result<()> {
         match event {
             TuiEvent::Key(key_event) => self.handle_key_event(tui, key_event),
+            TuiEvent::Mouse(mouse_event) => self.handle_mouse_event(tui, mouse_event),
             TuiEvent::Draw => {
                 tui.draw(u16::MAX, |frame| {
                     self.render(frame.area(), frame.buffer);
@@ -93,7 +137,6 @@ impl TranscriptApp {
 
     fn apply_highlight_to_lines(&self, lines: &mut [Line<'static>]) {
         if let Some((start, end)) = self.highlight_range {
-            use ratatui::style::Modifier;
             let len = lines.len();
             let start = start.min(len);
             let end = end.min(len);
@@ -122,8 +165,29 @@ impl TranscriptApp {
             .min(wrapped.len().saturating_sub(area.height as usize));
         let start = self.scroll_offset;
         let end = (start + area.height as usize).min(wrapped.len());
-        let page = &wrapped[start..end];
-        Paragraph::new(page.to_vec()).render_ref(area, buf);
+        let selected = self
+            .selected_citation
+            .as_ref()
+            .filter(|selected| selected.width == area.width);
+        let page: Vec<Line<'static>> = wrapped[start..end]
+            .iter()
+            .enumerate()
+            .map(|(i, line)| {
+                let line = self.search.highlight(line, start + i);
+                match selected {
+                    Some(selected) if selected.line == start + i => {
+                        let range = &selected.link.range;
+                        add_modifier_to_ranges(
+                            &line,
+                            &[(range.start, range.end)],
+                            Modifier::REVERSED,
+                        )
+                    }
+                    _ => line,
+                }
+            })
+            .collect();
+        Paragraph::new(page).render_ref(area, buf);
 
         // Fill remaining visible lines (if any) with a leading '~' in the first column.
         let visible = (end - start) as u16;
@@ -187,6 +251,16 @@ impl TranscriptApp {
     /// Render the dimmed key hints (scroll/page/jump and backtrack cue).
     fn render_hints(&self, buf: &mut Buffer, hints_rect: Rect) {
         let key_hint_style = Style::default().fg(Color::Cyan);
+        if let Some(prompt) = self.search.prompt_line() {
+            let mut lines = vec![prompt];
+            lines.extend(
+                self.status
+                    .clone()
+                    .map(|status| Line::from(format!(" {status}")).dim()),
+            );
+            Paragraph::new(lines).render_ref(hints_rect, buf);
+            return;
+        }
         let hints1 = vec![
             " ".into(),
             "↑".set_style(key_hint_style),
@@ -207,8 +281,36 @@ impl TranscriptApp {
             "   ".into(),
             "Esc".set_style(key_hint_style),
             " edit prev".into(),
+            "   ".into(),
+            "/".set_style(key_hint_style),
+            " search".into(),
         ]);
-        self.maybe_append_enter_edit_hint(&mut hints2, key_hint_style);
+        if !self.search.pattern().is_empty() {
+            hints2.extend([
+                "   ".into(),
+                "n".set_style(key_hint_style),
+                "/".into(),
+                "N".set_style(key_hint_style),
+                " next/prev match".into(),
+            ]);
+        }
+        if self.selected_citation.is_some() {
+            hints2.extend([
+                "   ".into(),
+                "⏎".set_style(key_hint_style),
+                " open file".into(),
+            ]);
+        } else {
+            self.maybe_append_enter_edit_hint(&mut hints2, key_hint_style);
+        }
+        hints2.extend([
+            "   ".into(),
+            "Tab".set_style(key_hint_style),
+            " file links".into(),
+        ]);
+        if let Some(status) = &self.status {
+            hints2.extend(["   ".into(), status.clone().into()]);
+        }
         Paragraph::new(vec![Line::from(hints1).dim(), Line::from(hints2).dim()])
             .render_ref(hints_rect, buf);
     }
@@ -227,6 +329,17 @@ impl TranscriptApp {
     }
 
     fn handle_key_event(&mut self, tui: &mut tui::Tui, key_event: KeyEvent) {
+        if self.search.is_editing() {
+            if matches!(key_event.kind, KeyEventKind::Press | KeyEventKind::Repeat) {
+                match self.search.handle_input_key(key_event) {
+                    SearchInput::Changed => self.find_match(tui.terminal.viewport_area, true),
+                    SearchInput::Confirmed | SearchInput::Cancelled => self.status = None,
+                    SearchInput::Ignored => {}
+                }
+                tui.frame_requester().schedule_frame();
+            }
+            return;
+        }
         match key_event {
             // Ctrl+Z is handled at the App level when transcript overlay is active
             KeyEvent {
@@ -292,6 +405,29 @@ impl TranscriptApp {
             } => {
                 self.scroll_offset = usize::MAX;
             }
+            KeyEvent {
+                code: KeyCode::Char('/'),
+                kind: KeyEventKind::Press,
+                ..
+            } => {
+                self.clamp_scroll_offset(tui.terminal.viewport_area);
+                self.search.start(self.scroll_offset);
+                self.status = None;
+            }
+            KeyEvent {
+                code: KeyCode::Char('n'),
+                kind: KeyEventKind::Press | KeyEventKind::Repeat,
+                ..
+            } => {
+                self.find_match(tui.terminal.viewport_area, true);
+            }
+            KeyEvent {
+                code: KeyCode::Char('N'),
+                kind: KeyEventKind::Press | KeyEventKind::Repeat,
+                ..
+            } => {
+                self.find_match(tui.terminal.viewport_area, false);
+            }
             _ => {
                 return;
             }
@@ -299,6 +435,105 @@ impl TranscriptApp {
         tui.frame_requester().schedule_frame();
     }
 
+    fn handle_mouse_event(&mut self, tui: &mut tui::Tui, mouse_event: MouseEvent) {
+        match mouse_event.kind {
+            MouseEventKind::ScrollUp => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(MOUSE_SCROLL_LINES);
+            }
+            MouseEventKind::ScrollDown => {
+                self.scroll_offset = self.scroll_offset.saturating_add(MOUSE_SCROLL_LINES);
+            }
+            _ => return,
+        }
+        tui.frame_requester().schedule_frame();
+    }
+
+    /// Whether `row` falls on the footer below the transcript, where a click
+    /// hands focus back to the composer.
+    pub(crate) fn is_footer_row(&self, viewport: Rect, row: u16) -> bool {
+        row >= self.scroll_area(viewport).bottom() && row < viewport.bottom()
+    }
+
+    /// The file citation shown at the given screen position, if any.
+    pub(crate) fn citation_at(
+        &self,
+        viewport: Rect,
+        column: u16,
+        row: u16,
+        cwd: &Path,
+    ) -> Option<FileCitation> {
+        let area = self.scroll_area(viewport);
+        if row < area.y || row >= area.bottom() || column < area.x {
+            return None;
+        }
+        let wrapped = insert_history::word_wrap_lines(&self.transcript_lines, area.width);
+        let max_scroll = wrapped.len().saturating_sub(area.height as usize);
+        let index = self.scroll_offset.min(max_scroll) + (row - area.y) as usize;
+        citation_at(wrapped.get(index)?, column - area.x, cwd)
+    }
+
+    /// The citation selected with Tab, which Enter opens.
+    pub(crate) fn selected_citation(&self) -> Option<&FileCitation> {
+        self.selected_citation
+            .as_ref()
+            .map(|selected| &selected.link.citation)
+    }
+
+    /// Selects the next (or previous) file citation after the selected one,
+    /// or from the top of the page when none is, wrapping around the
+    /// transcript, and scrolls it into view.
+    pub(crate) fn select_citation(&mut self, viewport: Rect, forward: bool, cwd: &Path) {
+        let area = self.scroll_area(viewport);
+        let wrapped = insert_history::word_wrap_lines(&self.transcript_lines, area.width);
+        self.clamp_scroll_offset(viewport);
+        let current = self
+            .selected_citation
+            .as_ref()
+            .filter(|selected| selected.width == area.width)
+            .map(|selected| (selected.line, selected.link.range.start));
+        let Some((line, link)) = find_citation(&wrapped, current, self.scroll_offset, forward, cwd)
+        else {
+            self.status = Some("no file citations".to_string());
+            return;
+        };
+        let height = area.height as usize;
+        if line < self.scroll_offset {
+            self.scroll_offset = line;
+        } else if line >= self.scroll_offset + height {
+            self.scroll_offset = line + 1 - height.max(1);
+        }
+        self.status = None;
+        self.selected_citation = Some(SelectedCitation {
+            line,
+            width: area.width,
+            link,
+        });
+    }
+
+    /// Moves to the next (or previous) line matching the search and scrolls
+    /// it into view.
+    fn find_match(&mut self, viewport: Rect, forward: bool) {
+        let area = self.scroll_area(viewport);
+        let wrapped = insert_history::word_wrap_lines(&self.transcript_lines, area.width);
+        self.clamp_scroll_offset(viewport);
+        self.status = self.search.find(
+            &wrapped,
+            &mut self.scroll_offset,
+            area.height as usize,
+            forward,
+        );
+    }
+
+    /// Resolves the "scrolled to the end" sentinel to a real offset so
+    /// searches start from the page being shown.
+    fn clamp_scroll_offset(&mut self, viewport: Rect) {
+        let area = self.scroll_area(viewport);
+        let wrapped_len = insert_history::word_wrap_lines(&self.transcript_lines, area.width).len();
+        self.scroll_offset = self
+            .scroll_offset
+            .min(wrapped_len.saturating_sub(area.height as usize));
+    }
+
     fn scroll_area(&self, area: Rect) -> Rect {
         let mut area = area;
         // Reserve 1 line for the header and 4 lines for the bottom status section. This matches the chat composer.
@@ -308,9 +543,57 @@ impl TranscriptApp {
     }
 }
 
+/// The citation after (or before) `current`, a wrapped line and the byte
+/// offset of a citation in it, wrapping around the transcript. Without a
+/// current citation, the search starts at line `top`.
+fn find_citation(
+    wrapped: &[Line<'static>],
+    current: Option<(usize, usize)>,
+    top: usize,
+    forward: bool,
+    cwd: &Path,
+) -> Option<(usize, CitationLink)> {
+    let len = wrapped.len();
+    if len == 0 {
+        return None;
+    }
+    let links_on = |index: usize| {
+        let mut links = citation_links(&wrapped[index], cwd);
+        if !forward {
+            links.reverse();
+        }
+        links
+    };
+    let (start, first_step) = match current {
+        Some((line, offset)) => {
+            let next = links_on(line).into_iter().find(|link| {
+                if forward {
+                    link.range.start > offset
+                } else {
+                    link.range.start < offset
+                }
+            });
+            if let Some(link) = next {
+                return Some((line, link));
+            }
+            (line, 1)
+        }
+        None => (top.min(len - 1), 0),
+    };
+    (first_step..=len).find_map(|step| {
+        let index = if forward {
+            (start + step) % len
+        } else {
+            (start + len * 2 - step) % len
+        };
+        links_on(index).into_iter().next().map(|link| (index, link))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use pretty_assertions::assert_eq;
 
     #[test]
     fn edit_prev_hint_is_visible() {
@@ -334,4 +617,98 @@ mod tests {
             "expected 'edit prev' hint in overlay footer, got: {s:?}"
         );
     }
+
+    #[test]
+    fn citation_at_maps_screen_position_to_scrolled_line() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("a.rs"), "").expect("write");
+        let lines = (0..20)
+            .map(|i| Line::from(format!("line {i} a.rs:{i}")))
+            .collect();
+        let mut app = TranscriptApp::new(lines);
+        app.scroll_offset = 5;
+        let viewport = Rect::new(0, 0, 40, 10);
+
+        // The first content row sits below the one-line header.
+        let citation = app.citation_at(viewport, 9, 1, dir.path());
+        assert_eq!(citation.and_then(|c| c.line), Some(5));
+        assert_eq!(app.citation_at(viewport, 0, 1, dir.path()), None);
+        assert_eq!(app.citation_at(viewport, 9, 0, dir.path()), None);
+        assert!(app.is_footer_row(viewport, 6));
+        assert!(!app.is_footer_row(viewport, 5));
+    }
+
+    #[test]
+    fn tab_selects_citations_in_order_and_wraps_around() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("a.rs"), "").expect("write");
+        let lines = (0..20)
+            .map(|i| match i {
+                2 => Line::from("see a.rs:2 and a.rs:3"),
+                15 => Line::from("then a.rs:15"),
+                _ => Line::from(format!("line {i}")),
+            })
+            .collect();
+        let mut app = TranscriptApp::new(lines);
+        app.scroll_offset = 0;
+        let viewport = Rect::new(0, 0, 40, 10);
+        let selected_line = |app: &TranscriptApp| app.selected_citation().and_then(|c| c.line);
+
+        app.select_citation(viewport, true, dir.path());
+        assert_eq!(selected_line(&app), Some(2));
+        app.select_citation(viewport, true, dir.path());
+        assert_eq!(selected_line(&app), Some(3));
+        app.select_citation(viewport, true, dir.path());
+        assert_eq!(selected_line(&app), Some(15));
+        // Line 15 is below the five-line page, so it is scrolled into view.
+        assert_eq!(app.scroll_offset, 11);
+        app.select_citation(viewport, true, dir.path());
+        assert_eq!(selected_line(&app), Some(2));
+        app.select_citation(viewport, false, dir.path());
+        assert_eq!(selected_line(&app), Some(15));
+
+        app.set_highlight_range(Some((0, 1)));
+        assert_eq!(app.selected_citation(), None);
+
+        let mut empty = TranscriptApp::new(vec![Line::from("no links here")]);
+        empty.select_citation(viewport, true, dir.path());
+        assert_eq!(empty.selected_citation(), None);
+        assert_eq!(empty.status.as_deref(), Some("no file citations"));
+    }
+
+    #[test]
+    fn search_prompt_finds_matches_and_reports_misses() {
+        let lines = (0..30)
+            .map(|i| Line::from(format!("line {i}{}", if i == 20 { " needle" } else { "" })))
+            .collect();
+        let mut app = TranscriptApp::new(lines);
+        let viewport = Rect::new(0, 0, 40, 10);
+        app.scroll_offset = 0;
+
+        app.search.start(0);
+        for c in "NEEDLE".chars() {
+            app.search.handle_input_key(KeyEvent::new(
+                KeyCode::Char(c),
+                crossterm::event::KeyModifiers::NONE,
+            ));
+        }
+        app.find_match(viewport, true);
+        assert_eq!(app.search.current_match, Some(20));
+        assert!(app.scroll_offset <= 20 && 20 < app.scroll_offset + 5);
+
+        let mut buf = Buffer::empty(viewport);
+        app.render(viewport, &mut buf);
+        let footer: String = (0..viewport.width)
+            .map(|x| buf[(x, 7)].symbol().to_string())
+            .collect();
+        assert!(footer.contains("/NEEDLE"), "{footer:?}");
+
+        app.search.handle_input_key(KeyEvent::new(
+            KeyCode::Char('!'),
+            crossterm::event::KeyModifiers::NONE,
+        ));
+        app.find_match(viewport, true);
+        assert_eq!(app.search.current_match, None);
+        assert_eq!(app.status.as_deref(), Some("pattern not found: NEEDLE!"));
+    }
 }
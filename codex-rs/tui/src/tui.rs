@@ -15,18 +15,24 @@ use crossterm::SynchronizedUpdate;
 use crossterm::cursor;
 use crossterm::cursor::MoveTo;
 use crossterm::event::DisableBracketedPaste;
+use crossterm::event::DisableFocusChange;
+use crossterm::event::DisableMouseCapture;
 use crossterm::event::EnableBracketedPaste;
+use crossterm::event::EnableFocusChange;
+use crossterm::event::EnableMouseCapture;
 use crossterm::event::Event;
 use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
 use crossterm::event::KeyModifiers;
 use crossterm::event::KeyboardEnhancementFlags;
+use crossterm::event::MouseEvent;
 use crossterm::event::PopKeyboardEnhancementFlags;
 use crossterm::event::PushKeyboardEnhancementFlags;
 use crossterm::terminal::EnterAlternateScreen;
 use crossterm::terminal::LeaveAlternateScreen;
 use crossterm::terminal::ScrollUp;
+use image::DynamicImage;
 use ratatui::backend::Backend;
 use ratatui::backend::CrosstermBackend;
 use ratatui::crossterm::execute;
@@ -38,14 +44,35 @@ use ratatui::text::Line;
 use crate::clipboard_paste::paste_image_to_temp_png;
 use crate::custom_terminal;
 use crate::custom_terminal::Terminal as CustomTerminal;
+use crate::insert_history::InsertedHistory;
+use crate::terminal_image;
 use tokio::select;
 use tokio_stream::Stream;
 
+/// History waiting to be inserted above the viewport on the next draw.
+enum PendingHistory {
+    Lines(Vec<Line<'static>>),
+    Image(DynamicImage),
+}
+
+/// How long the terminal width has to stay the same before the history above
+/// the viewport is wrapped again.
+const RESIZE_DEBOUNCE: Duration = Duration::from_millis(150);
+
 /// A type alias for the terminal type used in this application
 pub type Terminal = CustomTerminal<CrosstermBackend<Stdout>>;
 
+/// Whether mouse capture is part of the modes set by `set_modes`. Chosen once
+/// by `init` from the `tui.mouse` config.
+static MOUSE_CAPTURE: AtomicBool = AtomicBool::new(false);
+
 pub fn set_modes() -> Result<()> {
     execute!(stdout(), EnableBracketedPaste)?;
+    // Focus events tell notifications whether the user is looking.
+    let _ = execute!(stdout(), EnableFocusChange);
+    if MOUSE_CAPTURE.load(Ordering::Relaxed) {
+        execute!(stdout(), EnableMouseCapture)?;
+    }
 
     enable_raw_mode()?;
     // Enable keyboard enhancement flags so modifiers for keys like Enter are disambiguated.
@@ -70,14 +97,20 @@ pub fn set_modes() -> Result<()> {
 pub fn restore() -> Result<()> {
     // Pop may fail on platforms that didn't support the push; ignore errors.
     let _ = execute!(stdout(), PopKeyboardEnhancementFlags);
+    if MOUSE_CAPTURE.load(Ordering::Relaxed) {
+        execute!(stdout(), DisableMouseCapture)?;
+    }
     execute!(stdout(), DisableBracketedPaste)?;
+    let _ = execute!(stdout(), DisableFocusChange);
+    crate::terminal_notification::restore_title();
     disable_raw_mode()?;
     let _ = execute!(stdout(), crossterm::cursor::Show);
     Ok(())
 }
 
 /// Initialize the terminal (inline viewport; history stays in normal scrollback)
-pub fn init() -> Result<Terminal> {
+pub fn init(mouse_capture: bool) -> Result<Terminal> {
+    MOUSE_CAPTURE.store(mouse_capture, Ordering::Relaxed);
     set_modes()?;
 
     set_panic_hook();
@@ -107,6 +140,7 @@ fn set_panic_hook() {
 #[derive(Debug)]
 pub enum TuiEvent {
     Key(KeyEvent),
+    Mouse(MouseEvent),
     Paste(String),
     Draw,
     AttachImage {
@@ -121,7 +155,11 @@ pub struct Tui {
     frame_schedule_tx: tokio::sync::mpsc::UnboundedSender<Instant>,
     draw_tx: tokio::sync::broadcast::Sender<()>,
     pub(crate) terminal: Terminal,
-    pending_history_lines: Vec<Line<'static>>,
+    pending_history: Vec<PendingHistory>,
+    // Newest history above the viewport, to wrap again when the width changes
+    inserted_history: InsertedHistory,
+    // When the width first differed from the history's, while a resize settles
+    resize_started_at: Option<Instant>,
     alt_saved_viewport: Option<ratatui::layout::Rect>,
     #[cfg(unix)]
     resume_pending: Arc<AtomicU8>, // Stores a ResumeAction
@@ -227,7 +265,9 @@ impl Tui {
             frame_schedule_tx,
             draw_tx,
             terminal,
-            pending_history_lines: vec![],
+            pending_history: vec![],
+            inserted_history: InsertedHistory::default(),
+            resize_started_at: None,
             alt_saved_viewport: None,
             #[cfg(unix)]
             resume_pending: Arc::new(AtomicU8::new(0)),
@@ -305,10 +345,35 @@ impl Tui {
                             Event::Resize(_, _) => {
                                 yield TuiEvent::Draw;
                             }
+                            // Terminals that paste with Cmd+V or Ctrl+Shift+V send an
+                            // empty paste when the clipboard holds only an image.
+                            Event::Paste(pasted) if pasted.is_empty() => {
+                                match paste_image_to_temp_png() {
+                                    Ok((path, info)) => {
+                                        yield TuiEvent::AttachImage {
+                                            path,
+                                            width: info.width,
+                                            height: info.height,
+                                            format_label: info.encoded_format.label(),
+                                        };
+                                    }
+                                    Err(e) => {
+                                        tracing::debug!("empty paste without a clipboard image: {e}");
+                                    }
+                                }
+                            }
                             Event::Paste(pasted) => {
                                 yield TuiEvent::Paste(pasted);
                             }
-                            _ => {}
+                            Event::Mouse(mouse_event) => {
+                                yield TuiEvent::Mouse(mouse_event);
+                            }
+                            Event::FocusGained => {
+                                crate::terminal_notification::set_focused(true);
+                            }
+                            Event::FocusLost => {
+                                crate::terminal_notification::set_focused(false);
+                            }
                         }
                     }
                     result = draw_rx.recv() => {
@@ -412,8 +477,36 @@ impl Tui {
         Ok(())
     }
 
+    /// Hand the terminal to a foreground program such as an editor while `f`
+    /// runs, then take it back and schedule a full redraw.
+    pub fn with_restored_terminal<R>(&mut self, f: impl FnOnce() -> R) -> Result<R> {
+        let alt_screen_active = self.alt_screen_active.load(Ordering::Relaxed);
+        if alt_screen_active {
+            execute!(self.terminal.backend_mut(), LeaveAlternateScreen)?;
+        }
+        restore()?;
+        let result = f();
+        set_modes()?;
+        if alt_screen_active {
+            execute!(self.terminal.backend_mut(), EnterAlternateScreen)?;
+        }
+        self.terminal.clear()?;
+        self.frame_requester().schedule_frame();
+        Ok(result)
+    }
+
     pub fn insert_history_lines(&mut self, lines: Vec<Line<'static>>) {
-        self.pending_history_lines.extend(lines);
+        self.pending_history.push(PendingHistory::Lines(lines));
+        self.frame_requester().schedule_frame();
+    }
+
+    /// Draw `image` inline below the history inserted so far. Does nothing
+    /// when the terminal has no graphics protocol.
+    pub(crate) fn insert_history_image(&mut self, image: DynamicImage) {
+        if !terminal_image::is_supported() {
+            return;
+        }
+        self.pending_history.push(PendingHistory::Image(image));
         self.frame_requester().schedule_frame();
     }
 
@@ -428,6 +521,32 @@ impl Tui {
         #[cfg(unix)]
         let mut prepared_resume =
             self.prepare_resume_action(take_resume_action(&self.resume_pending))?;
+        let alt_screen_active = self.alt_screen_active.load(Ordering::Relaxed);
+        let screen_width = self.terminal.size()?.width;
+        // History in the scrollback was wrapped at the old width. Wait for the
+        // width to settle before wrapping it again, and keep new history back
+        // until then so that all of it is wrapped at the same width.
+        let mut rewrap_history = false;
+        if !alt_screen_active
+            && self
+                .inserted_history
+                .width()
+                .is_some_and(|width| width != screen_width)
+        {
+            let started_at = *self.resize_started_at.get_or_insert_with(Instant::now);
+            let settled_in = RESIZE_DEBOUNCE.saturating_sub(started_at.elapsed());
+            if settled_in.is_zero() {
+                rewrap_history = true;
+            } else {
+                self.frame_requester().schedule_frame_in(settled_in);
+            }
+        } else {
+            self.resize_started_at = None;
+        }
+        let hold_history = self.resize_started_at.is_some() && !rewrap_history;
+        if rewrap_history {
+            self.resize_started_at = None;
+        }
         {
             let terminal = &mut self.terminal;
             let screen_size = terminal.size()?;
@@ -474,12 +593,37 @@ impl Tui {
                 terminal.clear()?;
                 terminal.set_viewport_area(area);
             }
-            if !self.pending_history_lines.is_empty() {
-                crate::insert_history::insert_history_lines(
-                    terminal,
-                    self.pending_history_lines.clone(),
-                );
-                self.pending_history_lines.clear();
+            if rewrap_history {
+                crate::insert_history::rewrap_history(terminal, &mut self.inserted_history);
+                terminal.clear()?;
+            }
+            let pending_history = if hold_history {
+                Vec::new()
+            } else {
+                std::mem::take(&mut self.pending_history)
+            };
+            for pending in pending_history {
+                match pending {
+                    PendingHistory::Lines(lines) => {
+                        if !alt_screen_active {
+                            self.inserted_history.push(lines.clone(), size.width);
+                        }
+                        crate::insert_history::insert_history_lines(terminal, lines);
+                    }
+                    PendingHistory::Image(image) => {
+                        self.inserted_history.clear();
+                        // Inserting history can push the viewport down to the
+                        // bottom of the screen, but no further, so this is how
+                        // many rows the image can take.
+                        let free_rows = size.height.saturating_sub(terminal.viewport_area.height);
+                        let max_rows = free_rows.min(terminal_image::MAX_IMAGE_ROWS);
+                        if let Some(image) =
+                            terminal_image::render(&image, terminal.viewport_area.width, max_rows)
+                        {
+                            crate::insert_history::insert_history_image(terminal, &image);
+                        }
+                    }
+                }
             }
             terminal.draw(|frame| {
                 draw_fn(frame);
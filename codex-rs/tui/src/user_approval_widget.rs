@@ -9,6 +9,7 @@
 use std::path::PathBuf;
 use std::sync::LazyLock;
 
+use codex_core::exec_policy::command_pattern;
 use codex_core::protocol::Op;
 use codex_core::protocol::ReviewDecision;
 use crossterm::event::KeyCode;
@@ -36,22 +37,64 @@ pub(crate) enum ApprovalRequest {
         id: String,
         command: Vec<String>,
         reason: Option<String>,
+        matched_rule: Option<String>,
     },
     ApplyPatch {
         id: String,
         reason: Option<String>,
         grant_root: Option<PathBuf>,
+        /// Each changed file with its rendered diff, ordered by path.
+        files: Vec<(PathBuf, Vec<Line<'static>>)>,
     },
 }
 
 /// Options displayed in the *select* mode.
 ///
 /// The `key` is matched case-insensitively.
+#[derive(Clone)]
 struct SelectOption {
     label: Line<'static>,
     description: &'static str,
     key: KeyCode,
-    decision: ReviewDecision,
+    action: SelectAction,
+}
+
+#[derive(Clone, Copy)]
+enum SelectAction {
+    Decide(ReviewDecision),
+    /// Approve the command and allow its pattern for the session.
+    AllowPattern,
+    /// Open the per-file review of a patch.
+    ReviewFiles,
+}
+
+/// Most files listed at once in the per-file review.
+const MAX_FILE_ROWS: usize = 6;
+
+/// Most diff lines shown at once in the per-file review.
+const MAX_DIFF_ROWS: usize = 12;
+
+/// State of the per-file review of a patch, where each file's changes can be
+/// accepted or rejected on their own.
+struct FileReview {
+    /// Whether the changes to each file, in request order, are accepted.
+    accepted: Vec<bool>,
+    selected: usize,
+    /// First line of the selected file's diff that is shown.
+    scroll: usize,
+}
+
+/// Most commands listed at once in a batch of similar commands.
+const MAX_COMMAND_ROWS: usize = 8;
+
+/// Similar command requests decided together: queued requests whose command
+/// matches the same pattern as the first one.
+struct CommandBatch {
+    /// Id and command of each request, the first one included.
+    commands: Vec<(String, Vec<String>)>,
+    /// Whether each command is approved.
+    accepted: Vec<bool>,
+    selected: usize,
 }
 
 static COMMAND_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
@@ -60,19 +103,19 @@ static COMMAND_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
             label: Line::from(vec!["Y".underlined(), "es".into()]),
             description: "Approve and run the command",
             key: KeyCode::Char('y'),
-            decision: ReviewDecision::Approved,
+            action: SelectAction::Decide(ReviewDecision::Approved),
         },
         SelectOption {
             label: Line::from(vec!["A".underlined(), "lways".into()]),
             description: "Approve the command for the remainder of this session",
             key: KeyCode::Char('a'),
-            decision: ReviewDecision::ApprovedForSession,
+            action: SelectAction::Decide(ReviewDecision::ApprovedForSession),
         },
         SelectOption {
             label: Line::from(vec!["N".underlined(), "o".into()]),
             description: "Do not run the command",
             key: KeyCode::Char('n'),
-            decision: ReviewDecision::Denied,
+            action: SelectAction::Decide(ReviewDecision::Denied),
         },
         SelectOption {
             label: Line::from(vec![
@@ -83,7 +126,7 @@ static COMMAND_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
             ]),
             description: "Do not run the command; provide feedback",
             key: KeyCode::Char('f'),
-            decision: ReviewDecision::Abort,
+            action: SelectAction::Decide(ReviewDecision::Abort),
         },
     ]
 });
@@ -94,13 +137,13 @@ static PATCH_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
             label: Line::from(vec!["Y".underlined(), "es".into()]),
             description: "Approve and apply the changes",
             key: KeyCode::Char('y'),
-            decision: ReviewDecision::Approved,
+            action: SelectAction::Decide(ReviewDecision::Approved),
         },
         SelectOption {
             label: Line::from(vec!["N".underlined(), "o".into()]),
             description: "Do not apply the changes",
             key: KeyCode::Char('n'),
-            decision: ReviewDecision::Denied,
+            action: SelectAction::Decide(ReviewDecision::Denied),
         },
         SelectOption {
             label: Line::from(vec![
@@ -111,11 +154,43 @@ static PATCH_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
             ]),
             description: "Do not apply the changes; provide feedback",
             key: KeyCode::Char('f'),
-            decision: ReviewDecision::Abort,
+            action: SelectAction::Decide(ReviewDecision::Abort),
         },
     ]
 });
 
+/// Command options when the command has a pattern that similar commands
+/// share, which can then be allowed for the session.
+static PATTERN_COMMAND_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
+    let mut options = COMMAND_SELECT_OPTIONS.clone();
+    options.insert(
+        2,
+        SelectOption {
+            label: Line::from(vec!["Allow ".into(), "p".underlined(), "attern".into()]),
+            description: "Approve, and run commands matching the pattern in the sandbox without asking this session",
+            key: KeyCode::Char('p'),
+            action: SelectAction::AllowPattern,
+        },
+    );
+    options
+});
+
+/// Patch options when the patch touches several files, which can then be
+/// reviewed one by one.
+static MULTI_FILE_PATCH_SELECT_OPTIONS: LazyLock<Vec<SelectOption>> = LazyLock::new(|| {
+    let mut options = PATCH_SELECT_OPTIONS.clone();
+    options.insert(
+        1,
+        SelectOption {
+            label: Line::from(vec!["R".underlined(), "eview each file".into()]),
+            description: "Choose which files to change",
+            key: KeyCode::Char('r'),
+            action: SelectAction::ReviewFiles,
+        },
+    );
+    options
+});
+
 /// A modal prompting the user to approve or deny the pending request.
 pub(crate) struct UserApprovalWidget {
     approval_request: ApprovalRequest,
@@ -126,6 +201,15 @@ pub(crate) struct UserApprovalWidget {
     /// Currently selected index in *select* mode.
     selected_option: usize,
 
+    /// Set while the user reviews a patch file by file.
+    review: Option<FileReview>,
+
+    /// For commands, the `[exec_policy]` rule that would allow similar ones.
+    pattern: Option<String>,
+
+    /// Set once similar command requests have been batched with this one.
+    batch: Option<CommandBatch>,
+
     /// Set to `true` once a decision has been sent – the parent view can then
     /// remove this widget from its queue.
     done: bool,
@@ -163,7 +247,10 @@ impl UserApprovalWidget {
     pub(crate) fn new(approval_request: ApprovalRequest, app_event_tx: AppEventSender) -> Self {
         let confirmation_prompt = match &approval_request {
             ApprovalRequest::Exec {
-                command, reason, ..
+                command,
+                reason,
+                matched_rule,
+                ..
             } => {
                 let cmd = strip_bash_lc_and_escape(command);
                 let mut contents: Vec<Line> = to_command_display(
@@ -177,6 +264,14 @@ impl UserApprovalWidget {
                     contents.push(Line::from(reason.clone().italic()));
                     contents.push(Line::from(""));
                 }
+                if let Some(rule) = matched_rule {
+                    contents.push(Line::from(format!("Required by {rule}").dim()));
+                    contents.push(Line::from(""));
+                }
+                if let Some(pattern) = command_pattern(command) {
+                    contents.push(Line::from(format!("Pattern: {pattern}").dim()));
+                    contents.push(Line::from(""));
+                }
                 Paragraph::new(contents).wrap(Wrap { trim: false })
             }
             ApprovalRequest::ApplyPatch {
@@ -201,19 +296,59 @@ impl UserApprovalWidget {
             }
         };
 
+        let pattern = match &approval_request {
+            ApprovalRequest::Exec { command, .. } => command_pattern(command),
+            ApprovalRequest::ApplyPatch { .. } => None,
+        };
         Self {
             select_options: match &approval_request {
+                ApprovalRequest::Exec { .. } if pattern.is_some() => {
+                    &PATTERN_COMMAND_SELECT_OPTIONS
+                }
                 ApprovalRequest::Exec { .. } => &COMMAND_SELECT_OPTIONS,
+                ApprovalRequest::ApplyPatch { files, .. } if files.len() > 1 => {
+                    &MULTI_FILE_PATCH_SELECT_OPTIONS
+                }
                 ApprovalRequest::ApplyPatch { .. } => &PATCH_SELECT_OPTIONS,
             },
             approval_request,
             app_event_tx,
             confirmation_prompt,
             selected_option: 0,
+            review: None,
+            pattern,
+            batch: None,
             done: false,
         }
     }
 
+    /// Adds `request` to the batch of commands decided together if it is a
+    /// command with the same pattern as this one, or hands it back.
+    pub(crate) fn try_batch(&mut self, request: ApprovalRequest) -> Option<ApprovalRequest> {
+        let ApprovalRequest::Exec { id, command, .. } = &request else {
+            return Some(request);
+        };
+        let ApprovalRequest::Exec {
+            id: first_id,
+            command: first_command,
+            ..
+        } = &self.approval_request
+        else {
+            return Some(request);
+        };
+        if self.done || self.pattern.is_none() || command_pattern(command) != self.pattern {
+            return Some(request);
+        }
+        let batch = self.batch.get_or_insert_with(|| CommandBatch {
+            commands: vec![(first_id.clone(), first_command.clone())],
+            accepted: vec![true],
+            selected: 0,
+        });
+        batch.commands.push((id.clone(), command.clone()));
+        batch.accepted.push(true);
+        None
+    }
+
     fn get_confirmation_prompt_height(&self, width: u16) -> u16 {
         // Should cache this for last value of width.
         self.confirmation_prompt.line_count(width) as u16
@@ -225,7 +360,14 @@ impl UserApprovalWidget {
     /// captures input while visible, we don’t need to report whether the event
     /// was consumed—callers can assume it always is.
     pub(crate) fn handle_key_event(&mut self, key: KeyEvent) {
-        if key.kind == KeyEventKind::Press {
+        if key.kind != KeyEventKind::Press {
+            return;
+        }
+        if self.batch.is_some() {
+            self.handle_batch_key(key);
+        } else if self.review.is_some() {
+            self.handle_review_key(key);
+        } else {
             self.handle_select_key(key);
         }
     }
@@ -257,7 +399,7 @@ impl UserApprovalWidget {
             }
             KeyCode::Enter => {
                 let opt = &self.select_options[self.selected_option];
-                self.send_decision(opt.decision);
+                self.select(opt.action);
             }
             KeyCode::Esc => {
                 self.send_decision(ReviewDecision::Abort);
@@ -269,78 +411,221 @@ impl UserApprovalWidget {
                     .iter()
                     .find(|opt| Self::normalize_keycode(opt.key) == normalized)
                 {
-                    self.send_decision(opt.decision);
+                    self.select(opt.action);
+                }
+            }
+        }
+    }
+
+    fn select(&mut self, action: SelectAction) {
+        match action {
+            SelectAction::Decide(decision) => self.send_decision(decision),
+            SelectAction::AllowPattern => {
+                let pattern = self.pattern.clone();
+                self.send_decision_with_feedback(
+                    ReviewDecision::Approved,
+                    String::new(),
+                    None,
+                    pattern,
+                );
+            }
+            SelectAction::ReviewFiles => {
+                self.review = Some(FileReview {
+                    accepted: vec![true; self.patch_files().len()],
+                    selected: 0,
+                    scroll: 0,
+                });
+            }
+        }
+    }
+
+    fn patch_files(&self) -> &[(PathBuf, Vec<Line<'static>>)] {
+        match &self.approval_request {
+            ApprovalRequest::ApplyPatch { files, .. } => files,
+            ApprovalRequest::Exec { .. } => &[],
+        }
+    }
+
+    fn handle_review_key(&mut self, key_event: KeyEvent) {
+        let files = match &self.approval_request {
+            ApprovalRequest::ApplyPatch { files, .. } => files.as_slice(),
+            ApprovalRequest::Exec { .. } => &[],
+        };
+        let Some(review) = self.review.as_mut() else {
+            return;
+        };
+        let file_count = files.len();
+        let diff_len = files.get(review.selected).map_or(0, |(_, diff)| diff.len());
+        match key_event.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                review.selected = review.selected.saturating_sub(1);
+                review.scroll = 0;
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                review.selected = (review.selected + 1).min(file_count.saturating_sub(1));
+                review.scroll = 0;
+            }
+            KeyCode::PageUp => {
+                review.scroll = review.scroll.saturating_sub(MAX_DIFF_ROWS);
+            }
+            KeyCode::PageDown => {
+                review.scroll =
+                    (review.scroll + MAX_DIFF_ROWS).min(diff_len.saturating_sub(MAX_DIFF_ROWS));
+            }
+            KeyCode::Char(' ') => {
+                if let Some(accepted) = review.accepted.get_mut(review.selected) {
+                    *accepted = !*accepted;
+                }
+            }
+            KeyCode::Char('a') => {
+                let accept = !review.accepted.iter().all(|a| *a);
+                review.accepted.fill(accept);
+            }
+            KeyCode::Enter => self.submit_review(),
+            KeyCode::Esc => self.review = None,
+            _ => {}
+        }
+    }
+
+    fn handle_batch_key(&mut self, key_event: KeyEvent) {
+        let Some(batch) = self.batch.as_mut() else {
+            return;
+        };
+        match key_event.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                batch.selected = batch.selected.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                batch.selected = (batch.selected + 1).min(batch.commands.len() - 1);
+            }
+            KeyCode::Char(' ') => {
+                if let Some(accepted) = batch.accepted.get_mut(batch.selected) {
+                    *accepted = !*accepted;
                 }
             }
+            KeyCode::Char('a') => {
+                let accept = !batch.accepted.iter().all(|a| *a);
+                batch.accepted.fill(accept);
+            }
+            KeyCode::Enter => {
+                let decisions = batch
+                    .accepted
+                    .iter()
+                    .map(|accepted| {
+                        if *accepted {
+                            ReviewDecision::Approved
+                        } else {
+                            ReviewDecision::Denied
+                        }
+                    })
+                    .collect();
+                self.send_batch_decisions(decisions, None);
+            }
+            KeyCode::Char('p') => {
+                let decisions = vec![ReviewDecision::Approved; batch.commands.len()];
+                let pattern = self.pattern.clone();
+                self.send_batch_decisions(decisions, pattern);
+            }
+            KeyCode::Esc => self.send_decision(ReviewDecision::Abort),
+            _ => {}
+        }
+    }
+
+    /// Sends one decision per batched command, in batch order. With
+    /// `allow_pattern`, the first approval also allows the pattern.
+    fn send_batch_decisions(
+        &mut self,
+        decisions: Vec<ReviewDecision>,
+        mut allow_pattern: Option<String>,
+    ) {
+        let Some(batch) = self.batch.take() else {
+            return;
+        };
+        let mut lines: Vec<Line<'static>> = vec![Line::from("")];
+        for ((id, command), decision) in batch.commands.into_iter().zip(decisions) {
+            let allow_pattern = if decision == ReviewDecision::Approved {
+                allow_pattern.take()
+            } else {
+                None
+            };
+            lines.extend(command_decision_lines(
+                &command,
+                decision,
+                allow_pattern.as_deref(),
+            ));
+            self.app_event_tx.send(AppEvent::CodexOp(Op::ExecApproval {
+                id,
+                decision,
+                allow_pattern,
+            }));
+        }
+        self.app_event_tx.send(AppEvent::InsertHistoryLines(lines));
+        self.done = true;
+    }
+
+    /// Approves the patch for the accepted files: all of it when every file
+    /// is accepted, and none of it when no file is.
+    fn submit_review(&mut self) {
+        let Some(review) = self.review.take() else {
+            return;
+        };
+        let files = self.patch_files();
+        let accepted: Vec<PathBuf> = files
+            .iter()
+            .zip(&review.accepted)
+            .filter(|(_, accepted)| **accepted)
+            .map(|((path, _), _)| path.clone())
+            .collect();
+        if accepted.is_empty() {
+            self.send_decision(ReviewDecision::Denied);
+        } else if accepted.len() == files.len() {
+            self.send_decision(ReviewDecision::Approved);
+        } else {
+            self.send_decision_with_feedback(
+                ReviewDecision::Approved,
+                String::new(),
+                Some(accepted),
+                None,
+            );
         }
     }
 
     fn send_decision(&mut self, decision: ReviewDecision) {
-        self.send_decision_with_feedback(decision, String::new())
+        if let Some(batch) = &self.batch {
+            let decisions = vec![decision; batch.commands.len()];
+            self.send_batch_decisions(decisions, None);
+            return;
+        }
+        self.send_decision_with_feedback(decision, String::new(), None, None)
     }
 
-    fn send_decision_with_feedback(&mut self, decision: ReviewDecision, feedback: String) {
+    fn send_decision_with_feedback(
+        &mut self,
+        decision: ReviewDecision,
+        feedback: String,
+        accepted_files: Option<Vec<PathBuf>>,
+        allow_pattern: Option<String>,
+    ) {
         let mut lines: Vec<Line<'static>> = vec![Line::from("")];
         match &self.approval_request {
             ApprovalRequest::Exec { command, .. } => {
-                let cmd = strip_bash_lc_and_escape(command);
-                let mut cmd_span: Span = cmd.clone().into();
-                cmd_span.style = cmd_span.style.add_modifier(Modifier::DIM);
-
-                // Result line based on decision.
-                match decision {
-                    ReviewDecision::Approved => {
-                        lines.extend(to_command_display(
-                            vec![
-                                "✔ ".fg(Color::Green),
-                                "You ".into(),
-                                "approved".bold(),
-                                " codex to run ".into(),
-                            ],
-                            cmd,
-                            vec![" this time".bold()],
-                        ));
-                    }
-                    ReviewDecision::ApprovedForSession => {
-                        lines.extend(to_command_display(
-                            vec![
-                                "✔ ".fg(Color::Green),
-                                "You ".into(),
-                                "approved".bold(),
-                                " codex to run ".into(),
-                            ],
-                            cmd,
-                            vec![" every time this session".bold()],
-                        ));
-                    }
-                    ReviewDecision::Denied => {
-                        lines.extend(to_command_display(
-                            vec![
-                                "✗ ".fg(Color::Red),
-                                "You ".into(),
-                                "did not approve".bold(),
-                                " codex to run ".into(),
-                            ],
-                            cmd,
-                            vec![],
-                        ));
-                    }
-                    ReviewDecision::Abort => {
-                        lines.extend(to_command_display(
-                            vec![
-                                "✗ ".fg(Color::Red),
-                                "You ".into(),
-                                "canceled".bold(),
-                                " the request to run ".into(),
-                            ],
-                            cmd,
-                            vec![],
-                        ));
-                    }
-                }
+                lines.extend(command_decision_lines(
+                    command,
+                    decision,
+                    allow_pattern.as_deref(),
+                ));
             }
-            ApprovalRequest::ApplyPatch { .. } => {
+            ApprovalRequest::ApplyPatch { files, .. } => {
                 lines.push(Line::from(format!("patch approval decision: {decision:?}")));
+                if let Some(accepted) = &accepted_files {
+                    for (path, _) in files.iter().filter(|(path, _)| !accepted.contains(path)) {
+                        lines.push(Line::from(vec![
+                            "  ✗ ".red(),
+                            "rejected ".into(),
+                            path.display().to_string().dim(),
+                        ]));
+                    }
+                }
             }
         }
         if !feedback.trim().is_empty() {
@@ -355,10 +640,12 @@ impl UserApprovalWidget {
             ApprovalRequest::Exec { id, .. } => Op::ExecApproval {
                 id: id.clone(),
                 decision,
+                allow_pattern,
             },
             ApprovalRequest::ApplyPatch { id, .. } => Op::PatchApproval {
                 id: id.clone(),
                 decision,
+                accepted_files,
             },
         };
 
@@ -373,21 +660,236 @@ impl UserApprovalWidget {
     }
 
     pub(crate) fn desired_height(&self, width: u16) -> u16 {
+        if let Some(batch) = &self.batch {
+            // A title, the command list and a hint.
+            return (2 + batch.commands.len().min(MAX_COMMAND_ROWS)) as u16;
+        }
+        if self.review.is_some() {
+            return self.get_confirmation_prompt_height(width) + self.review_height();
+        }
         // Reserve space for:
         // - 1 title line ("Allow command?" or "Apply changes?")
         // - 1 buttons line (options rendered horizontally on a single row)
         // - 1 description line (context for the currently selected option)
         self.get_confirmation_prompt_height(width) + 3
     }
+
+    /// Rows of the per-file review: a title, the file list, the tallest diff
+    /// (so the view keeps its height while moving between files) and a hint.
+    fn review_height(&self) -> u16 {
+        let files = self.patch_files();
+        let diff_rows = files
+            .iter()
+            .map(|(_, diff)| diff.len().min(MAX_DIFF_ROWS))
+            .max()
+            .unwrap_or(0);
+        (2 + files.len().min(MAX_FILE_ROWS) + diff_rows) as u16
+    }
+
+    fn render_review(&self, review: &FileReview, area: Rect, buf: &mut Buffer) {
+        let files = self.patch_files();
+        let file_rows = files.len().min(MAX_FILE_ROWS);
+        let [title_area, files_area, diff_area, hint_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(file_rows as u16),
+            Constraint::Min(0),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let accepted_count = review.accepted.iter().filter(|a| **a).count();
+        Line::from(vec![
+            "Review changes ".bold(),
+            format!("({accepted_count} of {} files accepted)", files.len()).dim(),
+        ])
+        .render(title_area, buf);
+
+        // Scroll the list so the selected file stays visible.
+        let first = (review.selected + 1).saturating_sub(file_rows);
+        let file_lines: Vec<Line> = files
+            .iter()
+            .zip(&review.accepted)
+            .enumerate()
+            .skip(first)
+            .take(file_rows)
+            .map(|(idx, ((path, _), accepted))| {
+                let marker = if *accepted {
+                    "✓".green()
+                } else {
+                    "✗".red()
+                };
+                let path = path.display().to_string();
+                if idx == review.selected {
+                    Line::from(vec!["› ".cyan(), marker, " ".into(), path.cyan().bold()])
+                } else {
+                    Line::from(vec!["  ".into(), marker, " ".into(), path.into()])
+                }
+            })
+            .collect();
+        Paragraph::new(file_lines).render(files_area, buf);
+
+        if let Some((_, diff)) = files.get(review.selected) {
+            let diff_lines: Vec<Line> = diff
+                .iter()
+                .skip(review.scroll)
+                .take(diff_area.height as usize)
+                .cloned()
+                .collect();
+            Paragraph::new(diff_lines).render(diff_area, buf);
+        }
+
+        Line::from("↑/↓ file · space toggle · a all · PgUp/PgDn scroll · ⏎ submit · esc back")
+            .style(Style::new().italic().add_modifier(Modifier::DIM))
+            .render(hint_area, buf);
+    }
+
+    fn render_batch(&self, batch: &CommandBatch, area: Rect, buf: &mut Buffer) {
+        let command_rows = batch.commands.len().min(MAX_COMMAND_ROWS);
+        let [title_area, commands_area, hint_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(command_rows as u16),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let accepted_count = batch.accepted.iter().filter(|a| **a).count();
+        let pattern = self.pattern.as_deref().unwrap_or_default();
+        Line::from(vec![
+            "? ".fg(Color::Cyan),
+            format!("Codex wants to run {} ", batch.commands.len()).bold(),
+            pattern.to_string().dim(),
+            " commands ".bold(),
+            format!("({accepted_count} approved)").dim(),
+        ])
+        .render(title_area, buf);
+
+        // Scroll the list so the selected command stays visible.
+        let first = (batch.selected + 1).saturating_sub(command_rows);
+        let command_lines: Vec<Line> = batch
+            .commands
+            .iter()
+            .zip(&batch.accepted)
+            .enumerate()
+            .skip(first)
+            .take(command_rows)
+            .map(|(idx, ((_, command), accepted))| {
+                let marker = if *accepted {
+                    "✓".green()
+                } else {
+                    "✗".red()
+                };
+                let command = strip_bash_lc_and_escape(command).replace('\n', " ");
+                if idx == batch.selected {
+                    Line::from(vec!["› ".cyan(), marker, " ".into(), command.cyan().bold()])
+                } else {
+                    Line::from(vec!["  ".into(), marker, " ".into(), command.into()])
+                }
+            })
+            .collect();
+        Paragraph::new(command_lines).render(commands_area, buf);
+
+        Line::from("↑/↓ command · space toggle · a all · ⏎ submit · p allow pattern · esc cancel")
+            .style(Style::new().italic().add_modifier(Modifier::DIM))
+            .render(hint_area, buf);
+    }
+}
+
+/// The history lines recording `decision` on `command`.
+fn command_decision_lines(
+    command: &[String],
+    decision: ReviewDecision,
+    allow_pattern: Option<&str>,
+) -> Vec<Line<'static>> {
+    let cmd = strip_bash_lc_and_escape(command);
+    match (decision, allow_pattern) {
+        (ReviewDecision::Approved, Some(pattern)) => to_command_display(
+            vec![
+                "✔ ".fg(Color::Green),
+                "You ".into(),
+                "approved".bold(),
+                " codex to run ".into(),
+            ],
+            cmd,
+            vec![
+                " and every ".into(),
+                pattern.to_string().dim(),
+                " command this session".bold(),
+            ],
+        ),
+        (ReviewDecision::Approved, None) => to_command_display(
+            vec![
+                "✔ ".fg(Color::Green),
+                "You ".into(),
+                "approved".bold(),
+                " codex to run ".into(),
+            ],
+            cmd,
+            vec![" this time".bold()],
+        ),
+        (ReviewDecision::ApprovedForSession, _) => to_command_display(
+            vec![
+                "✔ ".fg(Color::Green),
+                "You ".into(),
+                "approved".bold(),
+                " codex to run ".into(),
+            ],
+            cmd,
+            vec![" every time this session".bold()],
+        ),
+        (ReviewDecision::Denied, _) => to_command_display(
+            vec![
+                "✗ ".fg(Color::Red),
+                "You ".into(),
+                "did not approve".bold(),
+                " codex to run ".into(),
+            ],
+            cmd,
+            vec![],
+        ),
+        (ReviewDecision::Abort, _) => to_command_display(
+            vec![
+                "✗ ".fg(Color::Red),
+                "You ".into(),
+                "canceled".bold(),
+                " the request to run ".into(),
+            ],
+            cmd,
+            vec![],
+        ),
+    }
 }
 
 impl WidgetRef for &UserApprovalWidget {
     fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        if let Some(batch) = &self.batch {
+            // The list of commands replaces the prompt for the first one.
+            Block::bordered()
+                .border_type(BorderType::QuadrantOutside)
+                .border_style(Style::default().fg(Color::Cyan))
+                .borders(Borders::LEFT)
+                .render_ref(Rect::new(0, area.y, 1, area.height), buf);
+            self.render_batch(batch, area.inner(Margin::new(1, 0)), buf);
+            return;
+        }
         let prompt_height = self.get_confirmation_prompt_height(area.width);
         let [prompt_chunk, response_chunk] = Layout::default()
             .direction(Direction::Vertical)
             .constraints([Constraint::Length(prompt_height), Constraint::Min(0)])
             .areas(area);
+        self.confirmation_prompt.clone().render(prompt_chunk, buf);
+        Block::bordered()
+            .border_type(BorderType::QuadrantOutside)
+            .border_style(Style::default().fg(Color::Cyan))
+            .borders(Borders::LEFT)
+            .render_ref(
+                Rect::new(0, response_chunk.y, 1, response_chunk.height),
+                buf,
+            );
+
+        if let Some(review) = &self.review {
+            self.render_review(review, response_chunk.inner(Margin::new(1, 0)), buf);
+            return;
+        }
 
         let lines: Vec<Line> = self
             .select_options
@@ -414,8 +916,6 @@ impl WidgetRef for &UserApprovalWidget {
             ApprovalRequest::ApplyPatch { .. } => "Apply changes?",
         };
         Line::from(title).render(title_area, buf);
-
-        self.confirmation_prompt.clone().render(prompt_chunk, buf);
         let areas = Layout::horizontal(
             lines
                 .iter()
@@ -431,15 +931,6 @@ impl WidgetRef for &UserApprovalWidget {
         Line::from(self.select_options[self.selected_option].description)
             .style(Style::new().italic().add_modifier(Modifier::DIM))
             .render(description_area.inner(Margin::new(1, 0)), buf);
-
-        Block::bordered()
-            .border_type(BorderType::QuadrantOutside)
-            .border_style(Style::default().fg(Color::Cyan))
-            .borders(Borders::LEFT)
-            .render_ref(
-                Rect::new(0, response_chunk.y, 1, response_chunk.height),
-                buf,
-            );
     }
 }
 
@@ -449,6 +940,9 @@ mod tests {
     use crossterm::event::KeyCode;
     use crossterm::event::KeyEvent;
     use crossterm::event::KeyModifiers;
+    use pretty_assertions::assert_eq;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
     use tokio::sync::mpsc::unbounded_channel;
 
     #[test]
@@ -459,6 +953,7 @@ mod tests {
             id: "1".to_string(),
             command: vec!["echo".to_string()],
             reason: None,
+            matched_rule: None,
         };
         let mut widget = UserApprovalWidget::new(req, tx);
         widget.handle_key_event(KeyEvent::new(KeyCode::Char('y'), KeyModifiers::NONE));
@@ -484,6 +979,7 @@ mod tests {
             id: "2".to_string(),
             command: vec!["echo".to_string()],
             reason: None,
+            matched_rule: None,
         };
         let mut widget = UserApprovalWidget::new(req, tx);
         widget.handle_key_event(KeyEvent::new(KeyCode::Char('Y'), KeyModifiers::NONE));
@@ -500,4 +996,187 @@ mod tests {
             })
         )));
     }
+
+    fn patch_request() -> ApprovalRequest {
+        let diff = |text: &str| vec![Line::from(format!("+{text}"))];
+        ApprovalRequest::ApplyPatch {
+            id: "3".to_string(),
+            reason: None,
+            grant_root: None,
+            files: vec![
+                (PathBuf::from("/repo/a.rs"), diff("a")),
+                (PathBuf::from("/repo/b.rs"), diff("b")),
+                (PathBuf::from("/repo/c.rs"), diff("c")),
+            ],
+        }
+    }
+
+    fn press(widget: &mut UserApprovalWidget, code: KeyCode) {
+        widget.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    #[test]
+    fn per_file_review_sends_partial_approval() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let mut widget = UserApprovalWidget::new(patch_request(), AppEventSender::new(tx_raw));
+        press(&mut widget, KeyCode::Char('r'));
+        press(&mut widget, KeyCode::Down);
+        press(&mut widget, KeyCode::Char(' '));
+
+        let mut terminal = Terminal::new(TestBackend::new(60, 6)).expect("terminal");
+        terminal
+            .draw(|f| (&widget).render_ref(f.area(), f.buffer_mut()))
+            .expect("draw");
+        insta::assert_snapshot!(terminal.backend());
+
+        press(&mut widget, KeyCode::Enter);
+        assert!(widget.is_complete());
+        let mut op = None;
+        while let Ok(ev) = rx.try_recv() {
+            if let AppEvent::CodexOp(ev) = ev {
+                op = Some(ev);
+            }
+        }
+        assert_eq!(
+            op,
+            Some(Op::PatchApproval {
+                id: "3".to_string(),
+                decision: ReviewDecision::Approved,
+                accepted_files: Some(vec![
+                    PathBuf::from("/repo/a.rs"),
+                    PathBuf::from("/repo/c.rs")
+                ]),
+            })
+        );
+    }
+
+    #[test]
+    fn per_file_review_without_rejections_approves_whole_patch() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let mut widget = UserApprovalWidget::new(patch_request(), AppEventSender::new(tx_raw));
+        press(&mut widget, KeyCode::Char('r'));
+        // Esc leaves the review without deciding.
+        press(&mut widget, KeyCode::Esc);
+        assert!(!widget.is_complete());
+        press(&mut widget, KeyCode::Char('r'));
+        press(&mut widget, KeyCode::Char('a'));
+        press(&mut widget, KeyCode::Char('a'));
+        press(&mut widget, KeyCode::Enter);
+        let ops: Vec<Op> = std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|ev| match ev {
+                AppEvent::CodexOp(op) => Some(op),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(
+            ops,
+            vec![Op::PatchApproval {
+                id: "3".to_string(),
+                decision: ReviewDecision::Approved,
+                accepted_files: None,
+            }]
+        );
+    }
+
+    fn cargo_test_request(id: &str, krate: &str) -> ApprovalRequest {
+        ApprovalRequest::Exec {
+            id: id.to_string(),
+            command: vec![
+                "bash".to_string(),
+                "-lc".to_string(),
+                format!("cargo test -p {krate}"),
+            ],
+            reason: None,
+            matched_rule: None,
+        }
+    }
+
+    fn sent_ops(rx: &mut tokio::sync::mpsc::UnboundedReceiver<AppEvent>) -> Vec<Op> {
+        std::iter::from_fn(|| rx.try_recv().ok())
+            .filter_map(|ev| match ev {
+                AppEvent::CodexOp(op) => Some(op),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn allow_pattern_option_sends_the_commands_pattern() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let mut widget =
+            UserApprovalWidget::new(cargo_test_request("4", "core"), AppEventSender::new(tx_raw));
+        press(&mut widget, KeyCode::Char('p'));
+        assert_eq!(
+            sent_ops(&mut rx),
+            vec![Op::ExecApproval {
+                id: "4".to_string(),
+                decision: ReviewDecision::Approved,
+                allow_pattern: Some("cargo test".to_string()),
+            }]
+        );
+    }
+
+    #[test]
+    fn similar_commands_are_batched_with_per_command_toggles() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let mut widget =
+            UserApprovalWidget::new(cargo_test_request("5", "core"), AppEventSender::new(tx_raw));
+        assert!(widget.try_batch(cargo_test_request("6", "tui")).is_none());
+        assert!(widget.try_batch(cargo_test_request("7", "exec")).is_none());
+        let other = ApprovalRequest::Exec {
+            id: "8".to_string(),
+            command: vec!["cargo".to_string(), "build".to_string()],
+            reason: None,
+            matched_rule: None,
+        };
+        assert!(widget.try_batch(other).is_some());
+
+        press(&mut widget, KeyCode::Down);
+        press(&mut widget, KeyCode::Char(' '));
+        let mut terminal = Terminal::new(TestBackend::new(80, 5)).expect("terminal");
+        terminal
+            .draw(|f| (&widget).render_ref(f.area(), f.buffer_mut()))
+            .expect("draw");
+        insta::assert_snapshot!(terminal.backend());
+
+        press(&mut widget, KeyCode::Enter);
+        assert!(widget.is_complete());
+        let decision = |id: &str, decision| Op::ExecApproval {
+            id: id.to_string(),
+            decision,
+            allow_pattern: None,
+        };
+        assert_eq!(
+            sent_ops(&mut rx),
+            vec![
+                decision("5", ReviewDecision::Approved),
+                decision("6", ReviewDecision::Denied),
+                decision("7", ReviewDecision::Approved),
+            ]
+        );
+    }
+
+    #[test]
+    fn allowing_the_pattern_approves_the_whole_batch() {
+        let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
+        let mut widget =
+            UserApprovalWidget::new(cargo_test_request("5", "core"), AppEventSender::new(tx_raw));
+        assert!(widget.try_batch(cargo_test_request("6", "tui")).is_none());
+        press(&mut widget, KeyCode::Char('p'));
+        assert_eq!(
+            sent_ops(&mut rx),
+            vec![
+                Op::ExecApproval {
+                    id: "5".to_string(),
+                    decision: ReviewDecision::Approved,
+                    allow_pattern: Some("cargo test".to_string()),
+                },
+                Op::ExecApproval {
+                    id: "6".to_string(),
+                    decision: ReviewDecision::Approved,
+                    allow_pattern: None,
+                },
+            ]
+        );
+    }
 }
@@ -0,0 +1,126 @@
+//! Banners shown above the composer while the session runs in a degraded
+//! state, such as without a sandbox. They stay until dismissed with Ctrl+X.
+
+use codex_core::protocol::DegradedModeEvent;
+use codex_core::protocol::DegradedModeKind;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::WidgetRef;
+use ratatui::widgets::Wrap;
+
+#[derive(Default)]
+pub(crate) struct WarningBanners {
+    banners: Vec<DegradedModeEvent>,
+}
+
+impl WarningBanners {
+    /// Show `event`, replacing any banner of the same kind.
+    pub(crate) fn push(&mut self, event: DegradedModeEvent) {
+        self.banners.retain(|b| b.kind != event.kind);
+        self.banners.push(event);
+    }
+
+    pub(crate) fn dismiss_all(&mut self) {
+        self.banners.clear();
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.banners.is_empty()
+    }
+
+    pub(crate) fn desired_height(&self, width: u16) -> u16 {
+        if self.banners.is_empty() {
+            return 0;
+        }
+        Paragraph::new(self.lines())
+            .wrap(Wrap { trim: false })
+            .line_count(width) as u16
+    }
+
+    fn lines(&self) -> Vec<Line<'static>> {
+        let mut lines: Vec<Line<'static>> = self
+            .banners
+            .iter()
+            .map(|banner| {
+                Line::from(vec![
+                    " ⚠ ".red().bold(),
+                    format!("{}: ", title(banner.kind)).red().bold(),
+                    banner.message.clone().into(),
+                ])
+            })
+            .collect();
+        lines.push(Line::from(vec!["   ".into(), "Ctrl+X".cyan(), " dismiss".into()]).dim());
+        lines
+    }
+}
+
+fn title(kind: DegradedModeKind) -> &'static str {
+    match kind {
+        DegradedModeKind::AuthExpiring => "Sign-in expiring",
+        DegradedModeKind::SandboxUnavailable => "No sandbox",
+        DegradedModeKind::ConfigInvalid => "Config problems",
+    }
+}
+
+impl WidgetRef for &WarningBanners {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() || self.banners.is_empty() {
+            return;
+        }
+        Paragraph::new(self.lines())
+            .wrap(Wrap { trim: false })
+            .render_ref(area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn render_to_strings(banners: &WarningBanners, width: u16) -> Vec<String> {
+        let height = banners.desired_height(width);
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        banners.render_ref(area, &mut buf);
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shows_one_banner_per_kind_until_dismissed() {
+        let mut banners = WarningBanners::default();
+        assert_eq!(banners.desired_height(80), 0);
+
+        banners.push(DegradedModeEvent {
+            kind: DegradedModeKind::SandboxUnavailable,
+            message: "old".to_string(),
+        });
+        banners.push(DegradedModeEvent {
+            kind: DegradedModeKind::SandboxUnavailable,
+            message: "commands run unsandboxed".to_string(),
+        });
+
+        assert_eq!(
+            render_to_strings(&banners, 80),
+            vec![
+                " ⚠ No sandbox: commands run unsandboxed",
+                "   Ctrl+X dismiss",
+            ]
+        );
+
+        banners.dismiss_all();
+        assert!(banners.is_empty());
+        assert_eq!(banners.desired_height(80), 0);
+    }
+}
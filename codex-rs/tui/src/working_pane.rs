@@ -0,0 +1,145 @@
+//! The working pane of the split layout (`tui.split_pane`): live tool
+//! activity shown between the transcript and the composer instead of inline.
+//!
+//! It shows the command that is running with everything it has printed so
+//! far, or, between commands, the diff of the files changed this turn. Only
+//! the last rows that fit are drawn, so the latest output stays in view.
+
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Block;
+use ratatui::widgets::Borders;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::WidgetRef;
+use ratatui::widgets::Wrap;
+
+use crate::history_cell::ExecCell;
+
+/// Smallest height the pane can be resized to: the title and two rows.
+pub(crate) const MIN_HEIGHT: u16 = 3;
+
+/// Tallest the pane can be resized to.
+pub(crate) const MAX_HEIGHT: u16 = 40;
+
+pub(crate) struct WorkingPane<'a> {
+    pub(crate) active_exec: Option<&'a ExecCell>,
+    /// Unified diff of the changes made during the current turn.
+    pub(crate) turn_diff: Option<&'a str>,
+}
+
+impl WorkingPane<'_> {
+    fn title_and_lines(&self) -> (&'static str, Vec<Line<'static>>) {
+        if let Some(cell) = self.active_exec {
+            return ("running", cell.working_lines());
+        }
+        match self.turn_diff {
+            Some(diff) if !diff.is_empty() => ("changes this turn", diff_lines(diff)),
+            _ => ("idle", vec!["  No tool activity yet.".dim().into()]),
+        }
+    }
+}
+
+fn diff_lines(diff: &str) -> Vec<Line<'static>> {
+    diff.lines()
+        .map(|line| {
+            let span = Span::from(format!("  {line}"));
+            if line.starts_with("+++") || line.starts_with("---") || line.starts_with("diff ") {
+                span.bold()
+            } else if line.starts_with('+') {
+                span.green()
+            } else if line.starts_with('-') {
+                span.red()
+            } else if line.starts_with("@@") {
+                span.cyan()
+            } else if line.starts_with(' ') {
+                span
+            } else {
+                span.dim()
+            }
+            .into()
+        })
+        .collect()
+}
+
+impl WidgetRef for WorkingPane<'_> {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        if area.is_empty() {
+            return;
+        }
+        let (title, lines) = self.title_and_lines();
+        let block = Block::default()
+            .borders(Borders::TOP)
+            .border_style(Style::default().dim())
+            .title(Line::from(vec![
+                "─ ".dim(),
+                title.bold(),
+                " · Ctrl+↑/↓ resize ".dim(),
+            ]));
+        let inner = block.inner(area);
+        block.render_ref(area, buf);
+
+        let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false });
+        let overflow = (paragraph.line_count(inner.width) as u16).saturating_sub(inner.height);
+        paragraph.scroll((overflow, 0)).render_ref(inner, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn render_to_strings(pane: &WorkingPane<'_>, width: u16, height: u16) -> Vec<String> {
+        let area = Rect::new(0, 0, width, height);
+        let mut buf = Buffer::empty(area);
+        pane.render_ref(area, &mut buf);
+        (0..height)
+            .map(|y| {
+                (0..width)
+                    .map(|x| buf[(x, y)].symbol())
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn shows_the_tail_of_the_turn_diff() {
+        let diff = "diff --git a/a.txt b/a.txt\n--- a/a.txt\n+++ b/a.txt\n@@ -1,2 +1,2 @@\n keep\n-old\n+new\n";
+        let pane = WorkingPane {
+            active_exec: None,
+            turn_diff: Some(diff),
+        };
+        assert_eq!(
+            render_to_strings(&pane, 40, 5),
+            vec![
+                "─ changes this turn · Ctrl+↑/↓ resize ──",
+                "  @@ -1,2 +1,2 @@",
+                "   keep",
+                "  -old",
+                "  +new",
+            ]
+        );
+    }
+
+    #[test]
+    fn idle_without_activity() {
+        let pane = WorkingPane {
+            active_exec: None,
+            turn_diff: None,
+        };
+        assert_eq!(
+            render_to_strings(&pane, 40, 3),
+            vec![
+                "─ idle · Ctrl+↑/↓ resize ───────────────",
+                "  No tool activity yet.",
+                "",
+            ]
+        );
+    }
+}
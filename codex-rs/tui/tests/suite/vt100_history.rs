@@ -1,6 +1,7 @@
 #![cfg(feature = "vt100-tests")]
 #![expect(clippy::expect_used)]
 
+use codex_tui::insert_history::InsertedHistory;
 use ratatui::backend::TestBackend;
 use ratatui::layout::Rect;
 use ratatui::style::Color;
@@ -243,3 +244,110 @@ fn pre_scroll_region_down() {
         "expected insertion SetScrollRegion 1..5, got: {s:?}"
     );
 }
+
+/// Insert each of `cells` as its own history cell, record it, and return the
+/// bytes written.
+fn insert_cells(
+    scenario: &mut TestScenario,
+    history: &mut InsertedHistory,
+    cells: Vec<Vec<Line<'static>>>,
+) -> Vec<u8> {
+    let mut buf = Vec::new();
+    for cell in cells {
+        history.push(cell.clone(), scenario.term.viewport_area.width);
+        buf.extend(scenario.run_insert(cell));
+    }
+    buf
+}
+
+/// Narrow the screen to `width` columns and wrap the history again.
+fn resize_and_rewrap(
+    scenario: &mut TestScenario,
+    history: &mut InsertedHistory,
+    width: u16,
+) -> Vec<u8> {
+    scenario.width = width;
+    scenario.term.backend_mut().resize(width, scenario.height);
+    let mut area = scenario.term.viewport_area;
+    area.width = width;
+    scenario.term.set_viewport_area(area);
+    let mut buf = Vec::new();
+    codex_tui::insert_history::rewrap_history_to_writer(&mut scenario.term, &mut buf, history);
+    buf
+}
+
+fn screen_rows(parser: &vt100::Parser) -> Vec<String> {
+    let (rows, cols) = parser.screen().size();
+    parser
+        .screen()
+        .rows(0, cols)
+        .take(rows as usize)
+        .map(|row| row.trim_end().to_string())
+        .collect()
+}
+
+fn scrollback_len(parser: &mut vt100::Parser) -> usize {
+    parser.screen_mut().set_scrollback(usize::MAX);
+    let len = parser.screen().scrollback();
+    parser.screen_mut().set_scrollback(0);
+    len
+}
+
+#[test]
+fn rewrap_keeps_rows_above_history_and_follows_with_viewport() {
+    let mut scenario = TestScenario::new(20, 6, Rect::new(0, 1, 20, 1));
+    let mut history = InsertedHistory::default();
+    let mut parser = vt100::Parser::new(6, 20, 100);
+    // Output of the shell before Codex started.
+    parser.process(b"$ codex");
+
+    let inserted = insert_cells(
+        &mut scenario,
+        &mut history,
+        vec![vec![Line::from("aaaa bbbb cccc dddd")]],
+    );
+    parser.process(&inserted);
+    assert_eq!(Rect::new(0, 2, 20, 1), scenario.term.viewport_area);
+
+    let rewrapped = resize_and_rewrap(&mut scenario, &mut history, 10);
+    parser.screen_mut().set_size(6, 10);
+    parser.process(&rewrapped);
+
+    assert_eq!(
+        vec!["$ codex", "aaaa bbbb", "cccc dddd", "", "", ""],
+        screen_rows(&parser)
+    );
+    assert_eq!(Rect::new(0, 3, 10, 1), scenario.term.viewport_area);
+    assert_eq!(Some(10), history.width());
+}
+
+#[test]
+fn rewrap_leaves_scrollback_alone() {
+    let mut scenario = TestScenario::new(20, 4, Rect::new(0, 3, 20, 1));
+    let mut history = InsertedHistory::default();
+    let mut parser = vt100::Parser::new(4, 20, 100);
+
+    let cells = (1..=6)
+        .map(|n| vec![Line::from(format!("history line {n}"))])
+        .collect();
+    let inserted = insert_cells(&mut scenario, &mut history, cells);
+    parser.process(&inserted);
+    let scrollback_before = scrollback_len(&mut parser);
+
+    let rewrapped = resize_and_rewrap(&mut scenario, &mut history, 10);
+    parser.screen_mut().set_size(4, 10);
+    parser.process(&rewrapped);
+
+    // Only the newest rows that fit above the viewport are redrawn, in place.
+    assert_eq!(
+        vec!["line 5", "history", "line 6", ""],
+        screen_rows(&parser)
+    );
+    assert_eq!(Rect::new(0, 3, 10, 1), scenario.term.viewport_area);
+    assert_eq!(scrollback_before, scrollback_len(&mut parser));
+    let rewrapped = String::from_utf8_lossy(&rewrapped);
+    assert!(
+        !rewrapped.contains("\u{1b}[3J"),
+        "scrollback must not be purged: {rewrapped:?}"
+    );
+}
@@ -0,0 +1,198 @@
+use std::io;
+use std::path::Path;
+use std::ptr;
+
+use windows_sys::Win32::Foundation::HLOCAL;
+use windows_sys::Win32::Foundation::LocalFree;
+use windows_sys::Win32::Security::ACL;
+use windows_sys::Win32::Security::Authorization::ACCESS_MODE;
+use windows_sys::Win32::Security::Authorization::DENY_ACCESS;
+use windows_sys::Win32::Security::Authorization::EXPLICIT_ACCESS_W;
+use windows_sys::Win32::Security::Authorization::GRANT_ACCESS;
+use windows_sys::Win32::Security::Authorization::GetExplicitEntriesFromAclW;
+use windows_sys::Win32::Security::Authorization::GetNamedSecurityInfoW;
+use windows_sys::Win32::Security::Authorization::NO_MULTIPLE_TRUSTEE;
+use windows_sys::Win32::Security::Authorization::REVOKE_ACCESS;
+use windows_sys::Win32::Security::Authorization::SE_FILE_OBJECT;
+use windows_sys::Win32::Security::Authorization::SetEntriesInAclW;
+use windows_sys::Win32::Security::Authorization::SetNamedSecurityInfoW;
+use windows_sys::Win32::Security::Authorization::TRUSTEE_IS_SID;
+use windows_sys::Win32::Security::Authorization::TRUSTEE_IS_UNKNOWN;
+use windows_sys::Win32::Security::Authorization::TRUSTEE_W;
+use windows_sys::Win32::Security::DACL_SECURITY_INFORMATION;
+use windows_sys::Win32::Security::EqualSid;
+use windows_sys::Win32::Security::NO_INHERITANCE;
+use windows_sys::Win32::Security::PSECURITY_DESCRIPTOR;
+use windows_sys::Win32::Security::SUB_CONTAINERS_AND_OBJECTS_INHERIT;
+use windows_sys::Win32::Storage::FileSystem::DELETE;
+use windows_sys::Win32::Storage::FileSystem::FILE_DELETE_CHILD;
+use windows_sys::Win32::Storage::FileSystem::FILE_GENERIC_WRITE;
+
+use crate::token::CapabilitySid;
+use crate::token::to_wide;
+
+/// Access rights that make up "write access" to a file or directory tree.
+const WRITE_ACCESS: u32 = FILE_GENERIC_WRITE | DELETE | FILE_DELETE_CHILD;
+
+/// Adds an inheritable ACE to `path` granting `sid` write access to it and
+/// everything below it.
+pub(crate) fn grant_write_access(path: &Path, sid: &CapabilitySid) -> io::Result<()> {
+    add_ace(path, sid, GRANT_ACCESS)
+}
+
+/// Adds an inheritable ACE to `path` denying `sid` write access to it and
+/// everything below it. Deny ACEs take precedence over the grant on an
+/// enclosing writable root.
+pub(crate) fn deny_write_access(path: &Path, sid: &CapabilitySid) -> io::Result<()> {
+    add_ace(path, sid, DENY_ACCESS)
+}
+
+/// Removes every explicit ACE for `sid` from `path`, undoing
+/// [`grant_write_access`] and [`deny_write_access`]. Paths that no longer
+/// exist are skipped.
+pub(crate) fn revoke_access(path: &Path, sid: &CapabilitySid) -> io::Result<()> {
+    let dacl = match NamedDacl::read(path) {
+        Ok(dacl) => dacl,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    };
+    if !dacl.has_explicit_entry(sid, None)? {
+        return Ok(());
+    }
+    dacl.set_entry(sid, REVOKE_ACCESS, 0, NO_INHERITANCE)
+}
+
+/// Adds the ACE unless `path` already carries it. Writing a DACL propagates
+/// inherited ACEs through the whole tree, so only the first sandboxed command
+/// under a root pays for it; the ACEs are removed by [`revoke_access`] when
+/// the session ends.
+fn add_ace(path: &Path, sid: &CapabilitySid, mode: ACCESS_MODE) -> io::Result<()> {
+    let dacl = NamedDacl::read(path)?;
+    if dacl.has_explicit_entry(sid, Some(mode))? {
+        return Ok(());
+    }
+    dacl.set_entry(sid, mode, WRITE_ACCESS, SUB_CONTAINERS_AND_OBJECTS_INHERIT)
+}
+
+/// The DACL of a file or directory, together with the security descriptor
+/// that owns it.
+struct NamedDacl {
+    path_w: Vec<u16>,
+    dacl: *mut ACL,
+    security_descriptor: PSECURITY_DESCRIPTOR,
+}
+
+impl NamedDacl {
+    fn read(path: &Path) -> io::Result<Self> {
+        let path_w = to_wide(path.as_os_str());
+        let mut dacl: *mut ACL = ptr::null_mut();
+        let mut security_descriptor: PSECURITY_DESCRIPTOR = ptr::null_mut();
+        let status = unsafe {
+            GetNamedSecurityInfoW(
+                path_w.as_ptr(),
+                SE_FILE_OBJECT,
+                DACL_SECURITY_INFORMATION,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut dacl,
+                ptr::null_mut(),
+                &mut security_descriptor,
+            )
+        };
+        if status != 0 {
+            return Err(io::Error::from_raw_os_error(status as i32));
+        }
+        Ok(Self {
+            path_w,
+            dacl,
+            security_descriptor,
+        })
+    }
+
+    /// Whether the DACL has an explicit (non-inherited) ACE for `sid`,
+    /// optionally restricted to one covering [`WRITE_ACCESS`] with `mode`.
+    fn has_explicit_entry(
+        &self,
+        sid: &CapabilitySid,
+        mode: Option<ACCESS_MODE>,
+    ) -> io::Result<bool> {
+        if self.dacl.is_null() {
+            return Ok(false);
+        }
+        let mut count: u32 = 0;
+        let mut entries: *mut EXPLICIT_ACCESS_W = ptr::null_mut();
+        let status = unsafe { GetExplicitEntriesFromAclW(self.dacl, &mut count, &mut entries) };
+        if status != 0 {
+            return Err(io::Error::from_raw_os_error(status as i32));
+        }
+        let found = (0..count as usize).any(|i| {
+            let entry = unsafe { &*entries.add(i) };
+            entry.Trustee.TrusteeForm == TRUSTEE_IS_SID
+                && unsafe { EqualSid(entry.Trustee.ptstrName as _, sid.as_psid()) } != 0
+                && mode.is_none_or(|mode| {
+                    entry.grfAccessMode == mode
+                        && entry.grfAccessPermissions & WRITE_ACCESS == WRITE_ACCESS
+                })
+        });
+        unsafe {
+            LocalFree(entries as HLOCAL);
+        }
+        Ok(found)
+    }
+
+    /// Merges one entry for `sid` into the DACL and writes it back to the
+    /// file.
+    fn set_entry(
+        &self,
+        sid: &CapabilitySid,
+        mode: ACCESS_MODE,
+        permissions: u32,
+        inheritance: u32,
+    ) -> io::Result<()> {
+        let entry = EXPLICIT_ACCESS_W {
+            grfAccessPermissions: permissions,
+            grfAccessMode: mode,
+            grfInheritance: inheritance,
+            Trustee: TRUSTEE_W {
+                pMultipleTrustee: ptr::null_mut(),
+                MultipleTrusteeOperation: NO_MULTIPLE_TRUSTEE,
+                TrusteeForm: TRUSTEE_IS_SID,
+                TrusteeType: TRUSTEE_IS_UNKNOWN,
+                ptstrName: sid.as_psid() as *mut u16,
+            },
+        };
+        let mut new_dacl: *mut ACL = ptr::null_mut();
+        let status = unsafe { SetEntriesInAclW(1, &entry, self.dacl, &mut new_dacl) };
+        if status != 0 {
+            return Err(io::Error::from_raw_os_error(status as i32));
+        }
+
+        let status = unsafe {
+            SetNamedSecurityInfoW(
+                self.path_w.as_ptr(),
+                SE_FILE_OBJECT,
+                DACL_SECURITY_INFORMATION,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                new_dacl,
+                ptr::null(),
+            )
+        };
+        unsafe {
+            LocalFree(new_dacl as HLOCAL);
+        }
+        if status != 0 {
+            return Err(io::Error::from_raw_os_error(status as i32));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for NamedDacl {
+    fn drop(&mut self) {
+        // `dacl` points into the security descriptor.
+        unsafe {
+            LocalFree(self.security_descriptor as HLOCAL);
+        }
+    }
+}
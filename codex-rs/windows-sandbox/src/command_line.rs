@@ -0,0 +1,72 @@
+/// Joins `args` into a single command line using the quoting rules expected
+/// by `CommandLineToArgvW` and the MSVC runtime, so that the child process
+/// sees exactly the same argv.
+pub fn create_command_line(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| quote_arg(arg))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn quote_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '\n', '\u{b}', '"']) {
+        return arg.to_string();
+    }
+
+    let mut quoted = String::with_capacity(arg.len() + 2);
+    quoted.push('"');
+    let mut backslashes = 0;
+    for c in arg.chars() {
+        match c {
+            '\\' => backslashes += 1,
+            '"' => {
+                // Backslashes preceding a quote must be doubled, and the
+                // quote itself escaped.
+                quoted.push_str(&"\\".repeat(backslashes * 2 + 1));
+                quoted.push('"');
+                backslashes = 0;
+            }
+            c => {
+                quoted.push_str(&"\\".repeat(backslashes));
+                quoted.push(c);
+                backslashes = 0;
+            }
+        }
+    }
+    // Backslashes before the closing quote must be doubled.
+    quoted.push_str(&"\\".repeat(backslashes * 2));
+    quoted.push('"');
+    quoted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn vec_str(args: &[&str]) -> Vec<String> {
+        args.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn plain_args_are_not_quoted() {
+        assert_eq!(
+            create_command_line(&vec_str(&["cmd.exe", "/c", "dir"])),
+            "cmd.exe /c dir"
+        );
+    }
+
+    #[test]
+    fn args_with_spaces_quotes_and_backslashes_are_escaped() {
+        assert_eq!(
+            create_command_line(&vec_str(&[
+                "C:\\Program Files\\Git\\bin\\bash.exe",
+                "-lc",
+                "echo \"hi\"",
+                "C:\\dir with space\\",
+                "",
+            ])),
+            r#""C:\Program Files\Git\bin\bash.exe" -lc "echo \"hi\"" "C:\dir with space\\" """#
+        );
+    }
+}
@@ -0,0 +1,21 @@
+#[cfg(target_os = "windows")]
+mod acl;
+mod command_line;
+#[cfg(target_os = "windows")]
+mod process;
+#[cfg(target_os = "windows")]
+mod token;
+#[cfg(target_os = "windows")]
+mod windows_run_main;
+
+pub use command_line::create_command_line;
+
+#[cfg(target_os = "windows")]
+pub fn run_main() -> ! {
+    windows_run_main::run_main();
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn run_main() -> ! {
+    panic!("codex-windows-sandbox is only supported on Windows");
+}
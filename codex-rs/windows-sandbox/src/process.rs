@@ -0,0 +1,133 @@
+use std::io;
+use std::os::windows::io::FromRawHandle;
+use std::os::windows::io::OwnedHandle;
+use std::ptr;
+
+use windows_sys::Win32::Foundation::TRUE;
+use windows_sys::Win32::Foundation::WAIT_FAILED;
+use windows_sys::Win32::System::Console::GetStdHandle;
+use windows_sys::Win32::System::Console::STD_ERROR_HANDLE;
+use windows_sys::Win32::System::Console::STD_INPUT_HANDLE;
+use windows_sys::Win32::System::Console::STD_OUTPUT_HANDLE;
+use windows_sys::Win32::System::JobObjects::AssignProcessToJobObject;
+use windows_sys::Win32::System::JobObjects::CreateJobObjectW;
+use windows_sys::Win32::System::JobObjects::JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+use windows_sys::Win32::System::JobObjects::JOBOBJECT_EXTENDED_LIMIT_INFORMATION;
+use windows_sys::Win32::System::JobObjects::JobObjectExtendedLimitInformation;
+use windows_sys::Win32::System::JobObjects::SetInformationJobObject;
+use windows_sys::Win32::System::JobObjects::TerminateJobObject;
+use windows_sys::Win32::System::Threading::CREATE_SUSPENDED;
+use windows_sys::Win32::System::Threading::CREATE_UNICODE_ENVIRONMENT;
+use windows_sys::Win32::System::Threading::CreateProcessAsUserW;
+use windows_sys::Win32::System::Threading::CreateProcessW;
+use windows_sys::Win32::System::Threading::GetExitCodeProcess;
+use windows_sys::Win32::System::Threading::INFINITE;
+use windows_sys::Win32::System::Threading::PROCESS_INFORMATION;
+use windows_sys::Win32::System::Threading::ResumeThread;
+use windows_sys::Win32::System::Threading::STARTF_USESTDHANDLES;
+use windows_sys::Win32::System::Threading::STARTUPINFOW;
+use windows_sys::Win32::System::Threading::WaitForSingleObject;
+
+use crate::command_line::create_command_line;
+use crate::token::raw;
+use crate::token::to_wide;
+
+/// Spawns `command` with `token` (or the current process token if `None`),
+/// inheriting this process's stdio, cwd and environment, and waits for it to
+/// exit. The child is placed in a job object that kills the whole process
+/// tree once this process exits, so a timeout that kills the helper also
+/// kills everything the command started.
+pub(crate) fn spawn_and_wait(token: Option<&OwnedHandle>, command: &[String]) -> io::Result<u32> {
+    let job = create_kill_on_close_job()?;
+
+    let mut command_line = to_wide(create_command_line(command));
+    let startup_info = STARTUPINFOW {
+        cb: std::mem::size_of::<STARTUPINFOW>() as u32,
+        dwFlags: STARTF_USESTDHANDLES,
+        hStdInput: unsafe { GetStdHandle(STD_INPUT_HANDLE) },
+        hStdOutput: unsafe { GetStdHandle(STD_OUTPUT_HANDLE) },
+        hStdError: unsafe { GetStdHandle(STD_ERROR_HANDLE) },
+        ..unsafe { std::mem::zeroed() }
+    };
+    let mut process_info: PROCESS_INFORMATION = unsafe { std::mem::zeroed() };
+    let ok = unsafe {
+        match token {
+            Some(token) => CreateProcessAsUserW(
+                raw(token),
+                ptr::null(),
+                command_line.as_mut_ptr(),
+                ptr::null(),
+                ptr::null(),
+                TRUE,
+                CREATE_SUSPENDED | CREATE_UNICODE_ENVIRONMENT,
+                ptr::null(),
+                ptr::null(),
+                &startup_info,
+                &mut process_info,
+            ),
+            None => CreateProcessW(
+                ptr::null(),
+                command_line.as_mut_ptr(),
+                ptr::null(),
+                ptr::null(),
+                TRUE,
+                CREATE_SUSPENDED | CREATE_UNICODE_ENVIRONMENT,
+                ptr::null(),
+                ptr::null(),
+                &startup_info,
+                &mut process_info,
+            ),
+        }
+    };
+    if ok == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let process = unsafe { OwnedHandle::from_raw_handle(process_info.hProcess) };
+    let thread = unsafe { OwnedHandle::from_raw_handle(process_info.hThread) };
+
+    // Assign the process to the job before it runs so that nothing it spawns
+    // can escape the job.
+    if unsafe { AssignProcessToJobObject(raw(&job), raw(&process)) } == 0 {
+        let err = io::Error::last_os_error();
+        unsafe {
+            TerminateJobObject(raw(&job), 1);
+        }
+        return Err(err);
+    }
+    if unsafe { ResumeThread(raw(&thread)) } == u32::MAX {
+        return Err(io::Error::last_os_error());
+    }
+    drop(thread);
+
+    if unsafe { WaitForSingleObject(raw(&process), INFINITE) } == WAIT_FAILED {
+        return Err(io::Error::last_os_error());
+    }
+    let mut exit_code: u32 = 0;
+    if unsafe { GetExitCodeProcess(raw(&process), &mut exit_code) } == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(exit_code)
+}
+
+fn create_kill_on_close_job() -> io::Result<OwnedHandle> {
+    let job = unsafe { CreateJobObjectW(ptr::null(), ptr::null()) };
+    if job.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+    let job = unsafe { OwnedHandle::from_raw_handle(job) };
+
+    let mut limits: JOBOBJECT_EXTENDED_LIMIT_INFORMATION = unsafe { std::mem::zeroed() };
+    limits.BasicLimitInformation.LimitFlags = JOB_OBJECT_LIMIT_KILL_ON_JOB_CLOSE;
+    let ok = unsafe {
+        SetInformationJobObject(
+            raw(&job),
+            JobObjectExtendedLimitInformation,
+            &limits as *const JOBOBJECT_EXTENDED_LIMIT_INFORMATION as *const _,
+            std::mem::size_of::<JOBOBJECT_EXTENDED_LIMIT_INFORMATION>() as u32,
+        )
+    };
+    if ok == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(job)
+}
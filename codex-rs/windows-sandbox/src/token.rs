@@ -0,0 +1,237 @@
+use std::io;
+use std::os::windows::io::FromRawHandle;
+use std::os::windows::io::OwnedHandle;
+use std::ptr;
+
+use windows_sys::Win32::Foundation::GENERIC_ALL;
+use windows_sys::Win32::Foundation::HANDLE;
+use windows_sys::Win32::Foundation::HLOCAL;
+use windows_sys::Win32::Foundation::LocalFree;
+use windows_sys::Win32::Security::ACL;
+use windows_sys::Win32::Security::Authorization::EXPLICIT_ACCESS_W;
+use windows_sys::Win32::Security::Authorization::GRANT_ACCESS;
+use windows_sys::Win32::Security::Authorization::NO_MULTIPLE_TRUSTEE;
+use windows_sys::Win32::Security::Authorization::SetEntriesInAclW;
+use windows_sys::Win32::Security::Authorization::TRUSTEE_IS_SID;
+use windows_sys::Win32::Security::Authorization::TRUSTEE_IS_UNKNOWN;
+use windows_sys::Win32::Security::Authorization::TRUSTEE_W;
+use windows_sys::Win32::Security::CreateRestrictedToken;
+use windows_sys::Win32::Security::DISABLE_MAX_PRIVILEGE;
+use windows_sys::Win32::Security::DeriveCapabilitySidsFromName;
+use windows_sys::Win32::Security::GetTokenInformation;
+use windows_sys::Win32::Security::NO_INHERITANCE;
+use windows_sys::Win32::Security::PSID;
+use windows_sys::Win32::Security::SID_AND_ATTRIBUTES;
+use windows_sys::Win32::Security::SetTokenInformation;
+use windows_sys::Win32::Security::TOKEN_ADJUST_DEFAULT;
+use windows_sys::Win32::Security::TOKEN_ASSIGN_PRIMARY;
+use windows_sys::Win32::Security::TOKEN_DEFAULT_DACL;
+use windows_sys::Win32::Security::TOKEN_DUPLICATE;
+use windows_sys::Win32::Security::TOKEN_QUERY;
+use windows_sys::Win32::Security::TOKEN_USER;
+use windows_sys::Win32::Security::TokenDefaultDacl;
+use windows_sys::Win32::Security::TokenUser;
+use windows_sys::Win32::Security::WRITE_RESTRICTED;
+use windows_sys::Win32::System::Threading::GetCurrentProcess;
+use windows_sys::Win32::System::Threading::OpenProcessToken;
+
+/// Name from which the capability SID gating write access is derived.
+const WORKSPACE_WRITE_CAPABILITY: &str = "codexWorkspaceWrite";
+
+/// A SID allocated by `DeriveCapabilitySidsFromName`, freed on drop.
+pub(crate) struct CapabilitySid {
+    sid: PSID,
+}
+
+impl CapabilitySid {
+    pub(crate) fn as_psid(&self) -> PSID {
+        self.sid
+    }
+}
+
+impl Drop for CapabilitySid {
+    fn drop(&mut self) {
+        unsafe {
+            LocalFree(self.sid as HLOCAL);
+        }
+    }
+}
+
+/// Returns the capability SID that writable roots are granted to. The SID is
+/// derived from a fixed name, so it is stable across invocations.
+pub(crate) fn workspace_write_capability_sid() -> io::Result<CapabilitySid> {
+    let name = to_wide(WORKSPACE_WRITE_CAPABILITY);
+    let mut group_sids: *mut PSID = ptr::null_mut();
+    let mut group_sid_count: u32 = 0;
+    let mut sids: *mut PSID = ptr::null_mut();
+    let mut sid_count: u32 = 0;
+    let ok = unsafe {
+        DeriveCapabilitySidsFromName(
+            name.as_ptr(),
+            &mut group_sids,
+            &mut group_sid_count,
+            &mut sids,
+            &mut sid_count,
+        )
+    };
+    if ok == 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    // Keep the first capability SID and release everything else.
+    let mut capability_sid = None;
+    unsafe {
+        for i in 0..group_sid_count as usize {
+            LocalFree(*group_sids.add(i) as HLOCAL);
+        }
+        LocalFree(group_sids as HLOCAL);
+        for i in 0..sid_count as usize {
+            let sid = *sids.add(i);
+            if capability_sid.is_none() {
+                capability_sid = Some(CapabilitySid { sid });
+            } else {
+                LocalFree(sid as HLOCAL);
+            }
+        }
+        LocalFree(sids as HLOCAL);
+    }
+    capability_sid.ok_or_else(|| io::Error::other("no capability SID was derived"))
+}
+
+/// Creates a primary token from the current process token that has every
+/// privilege (except `SeChangeNotifyPrivilege`) removed and that is
+/// write-restricted to `sid`: reads are checked as usual, but writes only
+/// succeed where `sid` has also been granted access.
+pub(crate) fn create_write_restricted_token(sid: &CapabilitySid) -> io::Result<OwnedHandle> {
+    let mut process_token: HANDLE = ptr::null_mut();
+    let ok = unsafe {
+        OpenProcessToken(
+            GetCurrentProcess(),
+            TOKEN_DUPLICATE | TOKEN_ASSIGN_PRIMARY | TOKEN_QUERY | TOKEN_ADJUST_DEFAULT,
+            &mut process_token,
+        )
+    };
+    if ok == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let process_token = unsafe { OwnedHandle::from_raw_handle(process_token) };
+
+    let restricting_sids = [SID_AND_ATTRIBUTES {
+        Sid: sid.as_psid(),
+        Attributes: 0,
+    }];
+    let mut restricted_token: HANDLE = ptr::null_mut();
+    let ok = unsafe {
+        CreateRestrictedToken(
+            raw(&process_token),
+            DISABLE_MAX_PRIVILEGE | WRITE_RESTRICTED,
+            0,
+            ptr::null(),
+            0,
+            ptr::null(),
+            restricting_sids.len() as u32,
+            restricting_sids.as_ptr(),
+            &mut restricted_token,
+        )
+    };
+    if ok == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    let restricted_token = unsafe { OwnedHandle::from_raw_handle(restricted_token) };
+
+    set_default_dacl(&process_token, &restricted_token, sid)?;
+    Ok(restricted_token)
+}
+
+/// Objects created by the sandboxed process (pipes, events, ...) receive the
+/// token's default DACL. Grant the capability SID access to them as well, or
+/// the write-restricted process could not use the objects it creates.
+fn set_default_dacl(
+    process_token: &OwnedHandle,
+    restricted_token: &OwnedHandle,
+    sid: &CapabilitySid,
+) -> io::Result<()> {
+    let user = query_token_user(process_token)?;
+    let user_sid = unsafe { (*(user.as_ptr() as *const TOKEN_USER)).User.Sid };
+
+    let entries = [user_sid, sid.as_psid()].map(|trustee| EXPLICIT_ACCESS_W {
+        grfAccessPermissions: GENERIC_ALL,
+        grfAccessMode: GRANT_ACCESS,
+        grfInheritance: NO_INHERITANCE,
+        Trustee: TRUSTEE_W {
+            pMultipleTrustee: ptr::null_mut(),
+            MultipleTrusteeOperation: NO_MULTIPLE_TRUSTEE,
+            TrusteeForm: TRUSTEE_IS_SID,
+            TrusteeType: TRUSTEE_IS_UNKNOWN,
+            ptstrName: trustee as *mut u16,
+        },
+    });
+    let mut dacl: *mut ACL = ptr::null_mut();
+    let status = unsafe {
+        SetEntriesInAclW(
+            entries.len() as u32,
+            entries.as_ptr(),
+            ptr::null(),
+            &mut dacl,
+        )
+    };
+    if status != 0 {
+        return Err(io::Error::from_raw_os_error(status as i32));
+    }
+
+    let default_dacl = TOKEN_DEFAULT_DACL { DefaultDacl: dacl };
+    let ok = unsafe {
+        SetTokenInformation(
+            raw(restricted_token),
+            TokenDefaultDacl,
+            &default_dacl as *const TOKEN_DEFAULT_DACL as *const _,
+            std::mem::size_of::<TOKEN_DEFAULT_DACL>() as u32,
+        )
+    };
+    let result = if ok == 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(())
+    };
+    unsafe {
+        LocalFree(dacl as HLOCAL);
+    }
+    result
+}
+
+/// Returns the `TOKEN_USER` structure for `token` in a suitably sized
+/// buffer.
+fn query_token_user(token: &OwnedHandle) -> io::Result<Vec<u64>> {
+    let mut len: u32 = 0;
+    unsafe {
+        GetTokenInformation(raw(token), TokenUser, ptr::null_mut(), 0, &mut len);
+    }
+    if len == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    // Use a u64 buffer so the TOKEN_USER header is suitably aligned.
+    let mut buffer = vec![0u64; (len as usize).div_ceil(8)];
+    let ok = unsafe {
+        GetTokenInformation(
+            raw(token),
+            TokenUser,
+            buffer.as_mut_ptr() as *mut _,
+            len,
+            &mut len,
+        )
+    };
+    if ok == 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(buffer)
+}
+
+pub(crate) fn raw(handle: &OwnedHandle) -> HANDLE {
+    use std::os::windows::io::AsRawHandle;
+    handle.as_raw_handle() as HANDLE
+}
+
+pub(crate) fn to_wide(s: impl AsRef<std::ffi::OsStr>) -> Vec<u16> {
+    use std::os::windows::ffi::OsStrExt;
+    s.as_ref().encode_wide().chain(std::iter::once(0)).collect()
+}
@@ -0,0 +1,98 @@
+use clap::Parser;
+use std::path::PathBuf;
+
+use crate::acl::deny_write_access;
+use crate::acl::grant_write_access;
+use crate::acl::revoke_access;
+use crate::process::spawn_and_wait;
+use crate::token::create_write_restricted_token;
+use crate::token::workspace_write_capability_sid;
+use codex_core::CODEX_WINDOWS_SANDBOX_REVOKE_ARG;
+use codex_core::protocol::SandboxPolicy;
+
+#[derive(Debug, Parser)]
+pub struct WindowsSandboxCommand {
+    /// It is possible that the cwd used in the context of the sandbox policy
+    /// is different from the cwd of the process to spawn.
+    pub sandbox_policy_cwd: PathBuf,
+
+    pub sandbox_policy: SandboxPolicy,
+
+    /// Full command args to run under the restricted token.
+    #[arg(trailing_var_arg = true)]
+    pub command: Vec<String>,
+}
+
+pub fn run_main() -> ! {
+    if std::env::args_os()
+        .nth(2)
+        .is_some_and(|arg| arg == CODEX_WINDOWS_SANDBOX_REVOKE_ARG)
+    {
+        revoke_main(std::env::args_os().skip(3).map(PathBuf::from).collect());
+    }
+
+    // argv[1] is the `--codex-run-as-windows-sandbox` marker, so skip argv[0]
+    // and let clap treat the marker as the program name.
+    let WindowsSandboxCommand {
+        sandbox_policy_cwd,
+        sandbox_policy,
+        command,
+    } = WindowsSandboxCommand::parse_from(std::env::args_os().skip(1));
+
+    if command.is_empty() {
+        panic!("No command specified to execute.");
+    }
+
+    // Full access runs the command without a restricted token, so only
+    // restricted runs have to reject a read blocklist.
+    let token = if sandbox_policy.has_full_disk_write_access() {
+        None
+    } else if !sandbox_policy.has_full_disk_read_access() {
+        // `spawn_command_under_windows_sandbox` rejects this before spawning
+        // the helper; this guards direct invocations.
+        panic!("read_blocklist is not supported by the Windows sandbox");
+    } else {
+        match prepare_restricted_token(&sandbox_policy, &sandbox_policy_cwd) {
+            Ok(token) => Some(token),
+            Err(e) => panic!("error preparing windows sandbox: {e:?}"),
+        }
+    };
+
+    match spawn_and_wait(token.as_ref(), &command) {
+        Ok(exit_code) => std::process::exit(exit_code as i32),
+        Err(e) => panic!("Failed to spawn {}: {e}", command[0].as_str()),
+    }
+}
+
+/// Grants the sandbox capability SID write access to every writable root
+/// (except its read-only subpaths) and returns a token that can only write
+/// where that SID has been granted access.
+fn prepare_restricted_token(
+    sandbox_policy: &SandboxPolicy,
+    cwd: &std::path::Path,
+) -> std::io::Result<std::os::windows::io::OwnedHandle> {
+    let sid = workspace_write_capability_sid()?;
+    for writable_root in sandbox_policy.get_writable_roots_with_cwd(cwd) {
+        grant_write_access(&writable_root.root, &sid)?;
+        for subpath in &writable_root.read_only_subpaths {
+            deny_write_access(subpath, &sid)?;
+        }
+    }
+    create_write_restricted_token(&sid)
+}
+
+/// Removes the ACEs that earlier runs added for the sandbox capability SID.
+fn revoke_main(paths: Vec<PathBuf>) -> ! {
+    let sid = match workspace_write_capability_sid() {
+        Ok(sid) => sid,
+        Err(e) => panic!("error deriving windows sandbox capability SID: {e:?}"),
+    };
+    let mut failed = false;
+    for path in &paths {
+        if let Err(e) = revoke_access(path, &sid) {
+            eprintln!("failed to revoke sandbox access to {}: {e}", path.display());
+            failed = true;
+        }
+    }
+    std::process::exit(i32::from(failed));
+}